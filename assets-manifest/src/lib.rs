@@ -0,0 +1,13 @@
+//! Asset path schema and manifest shared by the client loaders and the asset
+//! converters.
+//!
+//! [`schema`] says how a converted file must be named; [`AssetManifest`]
+//! lists the files an asset root actually has, so a mistyped path is caught
+//! when the manifest is generated or checked instead of as a missing asset
+//! in game.
+
+pub mod manifest;
+pub mod schema;
+
+pub use manifest::{AssetEntry, AssetManifest, ManifestError, MANIFEST_FILE, MANIFEST_VERSION};
+pub use schema::{canonical_path, check_path, AssetCategory, PathIssue};
@@ -0,0 +1,113 @@
+//! `assets-manifest generate` writes the manifest of an asset root;
+//! `assets-manifest check` looks paths up in it.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use assets_manifest::{AssetManifest, MANIFEST_FILE};
+
+const USAGE: &str = "\
+Usage:
+  assets-manifest generate [--assets-root DIR] [--out FILE] [--allow-issues]
+  assets-manifest check [--assets-root DIR] PATH...
+
+generate  Lists data/ and remaster/data/ under the asset root into
+          manifest.json and fails if a file breaks the naming schema,
+          unless --allow-issues is given.
+check     Fails if a path (relative to the asset root) is not in the
+          manifest, naming the closest listed path.";
+
+struct Options {
+    assets_root: PathBuf,
+    out: Option<PathBuf>,
+    allow_issues: bool,
+    paths: Vec<String>,
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let command = args.next();
+    let options = match parse_options(args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{message}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let result = match command.as_deref() {
+        Some("generate") => generate(&options),
+        Some("check") => check(&options),
+        Some("--help" | "-h") => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        assets_root: PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../assets")),
+        out: None,
+        allow_issues: false,
+        paths: Vec::new(),
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--assets-root" => {
+                options.assets_root = args.next().ok_or("missing value for --assets-root")?.into();
+            }
+            "--out" => options.out = Some(args.next().ok_or("missing value for --out")?.into()),
+            "--allow-issues" => options.allow_issues = true,
+            flag if flag.starts_with("--") => return Err(format!("unknown option: {flag}")),
+            _ => options.paths.push(arg),
+        }
+    }
+    Ok(options)
+}
+
+fn generate(options: &Options) -> Result<bool, assets_manifest::ManifestError> {
+    let (manifest, issues) = AssetManifest::scan(&options.assets_root)?;
+    let out = options
+        .out
+        .clone()
+        .unwrap_or_else(|| options.assets_root.join(MANIFEST_FILE));
+    manifest.write(&out)?;
+    println!(
+        "{} assets listed in {}",
+        manifest.assets.len(),
+        out.display()
+    );
+
+    for issue in &issues {
+        eprintln!("{issue}");
+    }
+    if !issues.is_empty() {
+        eprintln!("{} files break the naming schema", issues.len());
+    }
+    Ok(issues.is_empty() || options.allow_issues)
+}
+
+fn check(options: &Options) -> Result<bool, assets_manifest::ManifestError> {
+    let manifest = AssetManifest::read(&options.assets_root.join(MANIFEST_FILE))?;
+    let mut ok = true;
+    for path in &options.paths {
+        if let Err(err) = manifest.check(path) {
+            eprintln!("{err}");
+            ok = false;
+        }
+    }
+    Ok(ok)
+}
@@ -0,0 +1,301 @@
+//! The manifest: every file under the asset root's `data/` trees, generated
+//! by the `assets-manifest` CLI and read by the client at startup.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::schema::{self, AssetCategory, PathIssue, DATA_PREFIX, REMASTER_PREFIX};
+
+/// File name of the manifest, at the asset root.
+pub const MANIFEST_FILE: &str = "manifest.json";
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Largest edit distance between a mistyped file name and the suggestion.
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ManifestError {
+    #[error("{}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+
+    #[error("invalid manifest: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("manifest version {0} is not supported")]
+    UnsupportedVersion(u32),
+
+    #[error("asset {path} is not in the manifest{}", did_you_mean(suggestion))]
+    UnknownAsset {
+        path: String,
+        suggestion: Option<String>,
+    },
+}
+
+fn did_you_mean(suggestion: &Option<String>) -> String {
+    suggestion
+        .as_ref()
+        .map(|path| format!("; did you mean {path}?"))
+        .unwrap_or_default()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetEntry {
+    pub category: AssetCategory,
+    pub bytes: u64,
+}
+
+/// Files of an asset root, keyed by their path relative to it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetManifest {
+    pub version: u32,
+    pub assets: BTreeMap<String, AssetEntry>,
+}
+
+impl Default for AssetManifest {
+    fn default() -> Self {
+        Self {
+            version: MANIFEST_VERSION,
+            assets: BTreeMap::new(),
+        }
+    }
+}
+
+impl AssetManifest {
+    /// Lists `data/` and `remaster/data/` under `root`. Files that break the
+    /// schema are listed too, under their actual path, and reported.
+    pub fn scan(root: &Path) -> Result<(Self, Vec<PathIssue>), ManifestError> {
+        let mut manifest = Self::default();
+        let mut issues = Vec::new();
+        for tree in [
+            DATA_PREFIX.to_string(),
+            format!("{REMASTER_PREFIX}{DATA_PREFIX}"),
+        ] {
+            let directory = root.join(&tree);
+            if directory.is_dir() {
+                manifest.scan_directory(&directory, tree.trim_end_matches('/'), &mut issues)?;
+            }
+        }
+        Ok((manifest, issues))
+    }
+
+    fn scan_directory(
+        &mut self,
+        directory: &Path,
+        relative: &str,
+        issues: &mut Vec<PathIssue>,
+    ) -> Result<(), ManifestError> {
+        let io_error = |source| ManifestError::Io {
+            path: directory.to_path_buf(),
+            source,
+        };
+        for entry in fs::read_dir(directory).map_err(io_error)? {
+            let entry = entry.map_err(io_error)?;
+            let path = format!("{relative}/{}", entry.file_name().to_string_lossy());
+            let metadata = entry.metadata().map_err(io_error)?;
+            if metadata.is_dir() {
+                self.scan_directory(&entry.path(), &path, issues)?;
+                continue;
+            }
+            let category = schema::check_path(&path).unwrap_or_else(|issue| {
+                issues.push(issue);
+                category_of(&path)
+            });
+            self.assets.insert(
+                path,
+                AssetEntry {
+                    category,
+                    bytes: metadata.len(),
+                },
+            );
+        }
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<Self, ManifestError> {
+        let bytes = fs::read(path).map_err(|source| ManifestError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let manifest: Self = serde_json::from_slice(&bytes)?;
+        if manifest.version != MANIFEST_VERSION {
+            return Err(ManifestError::UnsupportedVersion(manifest.version));
+        }
+        Ok(manifest)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), ManifestError> {
+        let json = serde_json::to_vec_pretty(self)?;
+        fs::write(path, json).map_err(|source| ManifestError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    pub fn get(&self, path: &str) -> Option<&AssetEntry> {
+        self.assets.get(path)
+    }
+
+    /// The entry of `path`, or an error naming the listed path it most likely
+    /// meant.
+    pub fn check(&self, path: &str) -> Result<&AssetEntry, ManifestError> {
+        self.get(path).ok_or_else(|| ManifestError::UnknownAsset {
+            path: path.to_string(),
+            suggestion: self.suggest(path).map(str::to_string),
+        })
+    }
+
+    /// Listed path closest to an unlisted one: its canonical form, or else a
+    /// file of the same directory a couple of typos away.
+    pub fn suggest(&self, path: &str) -> Option<&str> {
+        let canonical = schema::canonical_path(path);
+        if let Some((listed, _)) = self.assets.get_key_value(&canonical) {
+            return Some(listed);
+        }
+
+        let (directory, name) = canonical.rsplit_once('/')?;
+        let prefix = format!("{directory}/");
+        self.assets
+            .range(prefix.clone()..)
+            .map(|(listed, _)| listed)
+            .take_while(|listed| listed.starts_with(&prefix))
+            .filter(|listed| !listed[prefix.len()..].contains('/'))
+            .map(|listed| (edit_distance(&listed[prefix.len()..], name), listed))
+            .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, listed)| listed.as_str())
+    }
+}
+
+/// Category of a path that breaks the schema, as if it were canonical.
+fn category_of(path: &str) -> AssetCategory {
+    let relative = path.strip_prefix(REMASTER_PREFIX).unwrap_or(path);
+    relative
+        .strip_prefix(DATA_PREFIX)
+        .and_then(|inner| inner.split_once('/'))
+        .map(|(directory, _)| AssetCategory::of_directory(&schema::snake_case(directory)))
+        .unwrap_or(AssetCategory::Other)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset_root(name: &str, files: &[&str]) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("assets-manifest-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for file in files {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, b"mu").unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn scan_lists_both_trees_and_reports_bad_names() {
+        let root = asset_root(
+            "scan",
+            &[
+                "data/world_1/terrain_height.json",
+                "data/player/armor_class_01.glb",
+                "data/Monster/BullFighter.glb",
+                "remaster/data/player/armor_class_01.glb",
+                "shaders/terrain.wgsl",
+            ],
+        );
+
+        let (manifest, issues) = AssetManifest::scan(&root).unwrap();
+        assert_eq!(
+            manifest.assets.keys().collect::<Vec<_>>(),
+            [
+                "data/Monster/BullFighter.glb",
+                "data/player/armor_class_01.glb",
+                "data/world_1/terrain_height.json",
+                "remaster/data/player/armor_class_01.glb",
+            ]
+        );
+        assert_eq!(
+            manifest.get("data/world_1/terrain_height.json"),
+            Some(&AssetEntry {
+                category: AssetCategory::World,
+                bytes: 2,
+            })
+        );
+        assert_eq!(
+            issues,
+            [PathIssue::NotCanonical {
+                path: "data/Monster/BullFighter.glb".to_string(),
+                canonical: "data/monster/bull_fighter.glb".to_string(),
+            }]
+        );
+        assert_eq!(
+            manifest
+                .get("data/Monster/BullFighter.glb")
+                .unwrap()
+                .category,
+            AssetCategory::Monster
+        );
+
+        let file = root.join(MANIFEST_FILE);
+        manifest.write(&file).unwrap();
+        assert_eq!(AssetManifest::read(&file).unwrap(), manifest);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn unknown_paths_suggest_the_listed_one() {
+        let mut manifest = AssetManifest::default();
+        for path in [
+            "data/player/armor_class_01.glb",
+            "data/player/armor_class_02.glb",
+            "data/object_74/object_114.glb",
+        ] {
+            manifest.assets.insert(
+                path.to_string(),
+                AssetEntry {
+                    category: AssetCategory::Other,
+                    bytes: 1,
+                },
+            );
+        }
+
+        assert!(manifest.check("data/player/armor_class_01.glb").is_ok());
+        assert_eq!(
+            manifest.suggest("data/Object74/Object114.glb"),
+            Some("data/object_74/object_114.glb")
+        );
+        assert_eq!(
+            manifest.suggest("data/player/armour_class_01.glb"),
+            Some("data/player/armor_class_01.glb")
+        );
+        assert_eq!(manifest.suggest("data/player/helm_class_01.glb"), None);
+        assert_eq!(
+            manifest
+                .check("data/player/armr_class_02.glb")
+                .unwrap_err()
+                .to_string(),
+            "asset data/player/armr_class_02.glb is not in the manifest; \
+             did you mean data/player/armor_class_02.glb?"
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! Canonical asset paths.
+//!
+//! Every converted file lives under `data/` (or its remaster mirror under
+//! `remaster/data/`), with each directory and file name in the snake_case
+//! form `scripts/normalize_filenames/normalize.py` produces: `World1` becomes
+//! `world_1`, `Object74/Object114.glb` becomes `object_74/object_114.glb`.
+//! The first directory under `data/` picks the [`AssetCategory`], which in
+//! turn limits the file types it may hold.
+
+use serde::{Deserialize, Serialize};
+
+pub const DATA_PREFIX: &str = "data/";
+pub const REMASTER_PREFIX: &str = "remaster/";
+
+/// Family of assets, from the first directory under `data/`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetCategory {
+    /// `data/world_<n>/`: terrain sidecars, tile textures, scene objects.
+    World,
+    /// `data/player/`: body parts and equipment worn by characters.
+    Player,
+    /// `data/monster/`.
+    Monster,
+    /// `data/effect/` and `data/skill/`: textures and meshes of visual effects.
+    Effect,
+    /// Everything else (objects, items, sounds, interface), named by the same
+    /// rules but of any file type.
+    Other,
+}
+
+impl AssetCategory {
+    /// Category of the asset directory right under `data/`.
+    pub fn of_directory(directory: &str) -> Self {
+        match directory {
+            "player" => Self::Player,
+            "monster" => Self::Monster,
+            "effect" | "skill" => Self::Effect,
+            _ if is_world_directory(directory) => Self::World,
+            _ => Self::Other,
+        }
+    }
+
+    /// File extensions the category holds; `None` for any.
+    pub fn extensions(self) -> Option<&'static [&'static str]> {
+        match self {
+            Self::World => Some(&["json", "png", "jpg"]),
+            Self::Player | Self::Monster => Some(&["glb", "png"]),
+            Self::Effect => Some(&["glb", "png", "json"]),
+            Self::Other => None,
+        }
+    }
+}
+
+fn is_world_directory(directory: &str) -> bool {
+    directory
+        .strip_prefix("world_")
+        .is_some_and(|number| !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Why a path does not follow the schema.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum PathIssue {
+    #[error("{path} is outside data/")]
+    OutsideData { path: String },
+
+    #[error("{path} is not canonical, expected {canonical}")]
+    NotCanonical { path: String, canonical: String },
+
+    #[error("{path} has a file type {category:?} assets do not use")]
+    UnexpectedExtension {
+        path: String,
+        category: AssetCategory,
+    },
+}
+
+/// Checks `path`, relative to the asset root, against the schema and returns
+/// its category.
+pub fn check_path(path: &str) -> Result<AssetCategory, PathIssue> {
+    let relative = path.strip_prefix(REMASTER_PREFIX).unwrap_or(path);
+    let Some(inner) = relative.strip_prefix(DATA_PREFIX) else {
+        return Err(PathIssue::OutsideData {
+            path: path.to_string(),
+        });
+    };
+
+    let canonical = canonical_path(path);
+    if canonical != path {
+        return Err(PathIssue::NotCanonical {
+            path: path.to_string(),
+            canonical,
+        });
+    }
+
+    let category = match inner.split_once('/') {
+        Some((directory, _)) => AssetCategory::of_directory(directory),
+        None => AssetCategory::Other,
+    };
+    if let Some(extensions) = category.extensions() {
+        let extension = inner.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("");
+        if !extensions.contains(&extension) {
+            return Err(PathIssue::UnexpectedExtension {
+                path: path.to_string(),
+                category,
+            });
+        }
+    }
+    Ok(category)
+}
+
+/// Canonical form of a `/`-separated path: directories and file stems in
+/// snake_case, extensions in lowercase.
+pub fn canonical_path(path: &str) -> String {
+    let mut segments: Vec<String> = Vec::new();
+    let mut parts = path.split('/').peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_some() {
+            segments.push(snake_case(part));
+        } else {
+            segments.push(canonical_file_name(part));
+        }
+    }
+    segments.join("/")
+}
+
+/// `Object114.GLB` -> `object_114.glb`; the stem keeps inner dots, as in
+/// `enc_terrain_1.map.json`.
+pub fn canonical_file_name(name: &str) -> String {
+    match name.rfind('.') {
+        Some(dot) if dot > 0 => {
+            format!(
+                "{}{}",
+                snake_case(&name[..dot]),
+                name[dot..].to_ascii_lowercase()
+            )
+        }
+        _ => snake_case(name),
+    }
+}
+
+/// Same conversion as `to_snake_case` in `normalize.py`.
+pub fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name
+        .chars()
+        .filter(|&c| c != '!')
+        .map(|c| match c {
+            '(' | ')' | ' ' | '-' => '_',
+            c => c,
+        })
+        .collect();
+
+    let mut out = String::with_capacity(chars.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1).copied();
+        let split = match prev {
+            // partCharge -> part_charge
+            Some(p) if p.is_ascii_lowercase() && c.is_ascii_uppercase() => true,
+            // IGSStorage -> igs_storage
+            Some(p)
+                if p.is_ascii_uppercase()
+                    && c.is_ascii_uppercase()
+                    && next.is_some_and(|n| n.is_ascii_lowercase()) =>
+            {
+                true
+            }
+            // Object40 -> object_40
+            Some(p) if p.is_ascii_alphabetic() && c.is_ascii_digit() => true,
+            _ => false,
+        };
+        if split {
+            out.push('_');
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+
+    let mut collapsed = String::with_capacity(out.len());
+    for c in out.chars() {
+        if c == '_' && collapsed.ends_with('_') {
+            continue;
+        }
+        collapsed.push(c);
+    }
+    collapsed.trim_matches('_').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_follow_the_normalizer() {
+        assert_eq!(snake_case("World1"), "world_1");
+        assert_eq!(snake_case("Object74"), "object_74");
+        assert_eq!(snake_case("partCharge"), "part_charge");
+        assert_eq!(snake_case("IGSStorage"), "igs_storage");
+        assert_eq!(snake_case("Tile Grass-01 (2)!"), "tile_grass_01_2");
+        assert_eq!(canonical_file_name("iButtonMove.WAV"), "i_button_move.wav");
+        assert_eq!(
+            canonical_file_name("enc_terrain_1.map.json"),
+            "enc_terrain_1.map.json"
+        );
+        assert_eq!(
+            canonical_path("data/Object74/Object114.glb"),
+            "data/object_74/object_114.glb"
+        );
+    }
+
+    #[test]
+    fn paths_are_checked_by_category() {
+        assert_eq!(
+            check_path("data/world_1/terrain_height.json"),
+            Ok(AssetCategory::World)
+        );
+        assert_eq!(
+            check_path("remaster/data/player/armor_class_01.glb"),
+            Ok(AssetCategory::Player)
+        );
+        assert_eq!(
+            check_path("data/skill/piercing.glb"),
+            Ok(AssetCategory::Effect)
+        );
+        assert_eq!(
+            check_path("data/sound/a_water.wav"),
+            Ok(AssetCategory::Other)
+        );
+
+        assert_eq!(
+            check_path("data/World1/terrain_height.json"),
+            Err(PathIssue::NotCanonical {
+                path: "data/World1/terrain_height.json".to_string(),
+                canonical: "data/world_1/terrain_height.json".to_string(),
+            })
+        );
+        assert!(matches!(
+            check_path("data/monster/bull_fighter.wav"),
+            Err(PathIssue::UnexpectedExtension {
+                category: AssetCategory::Monster,
+                ..
+            })
+        ));
+        assert!(matches!(
+            check_path("shaders/terrain.wgsl"),
+            Err(PathIssue::OutsideData { .. })
+        ));
+    }
+}
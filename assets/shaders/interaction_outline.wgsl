@@ -0,0 +1,33 @@
+// Outline shell drawn around the scene object under the cursor.
+// The mesh is pushed out along its normals and only its back faces are
+// drawn, so the shell shows as a solid rim around the silhouette.
+
+#import bevy_pbr::mesh_functions::{get_world_from_local, mesh_position_local_to_world, mesh_normal_local_to_world}
+#import bevy_pbr::view_transformations::position_world_to_clip
+
+struct OutlineVertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+};
+
+struct OutlineParams {
+    color: vec4<f32>,
+    width: f32,
+    _padding: vec3<f32>,
+};
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var<uniform> params: OutlineParams;
+
+@vertex
+fn vertex(vertex: OutlineVertex) -> @builtin(position) vec4<f32> {
+    let model = get_world_from_local(vertex.instance_index);
+    let world_pos = mesh_position_local_to_world(model, vec4<f32>(vertex.position, 1.0));
+    let world_normal = mesh_normal_local_to_world(vertex.normal, vertex.instance_index);
+    return position_world_to_clip(world_pos.xyz + world_normal * params.width);
+}
+
+@fragment
+fn fragment() -> @location(0) vec4<f32> {
+    return params.color;
+}
@@ -0,0 +1,618 @@
+//! Standard performance benchmark, started with `--benchmark`.
+//!
+//! Loads a fixed world offline, flies the camera along a scripted orbit for
+//! the sample window while bursts of skill VFX go off around the map centre,
+//! and writes frame-time percentiles with hardware and graphics settings to a
+//! JSON report. VSync and the FPS cap are turned off for the run, so reports
+//! from different builds on the same machine can be compared directly.
+//!
+//! Options: `--world <id>` (default Lorencia), `--duration-secs <n>`,
+//! `--settle-secs <n>`, `--timeout-secs <n>` and `--report <path>`. The
+//! usual `--gpu` and `--gpu-backend` overrides still apply.
+
+use std::f32::consts::TAU;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use bevy::render::renderer::RenderAdapterInfo;
+use bevy::window::PrimaryWindow;
+use bevy::winit::WinitSettings;
+use common::WorldMap;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::AppState;
+use crate::app::world_smoke::missing_mandatory_assets;
+use crate::app::{gpu, logging};
+use crate::gameplay::runtime::pipeline::GameplayPipelineSet;
+use crate::gameplay::scenes::gameplay::GameplayWorldOverride;
+use crate::infra::assets::asset_path_exists;
+use crate::scene_runtime::components::{CameraTour, RuntimeSceneEntity};
+use crate::scene_runtime::state::RuntimeSceneAssets;
+use crate::scene_runtime::systems::{SceneObjectsSpawned, TerrainSpawned, spawn_death_stab_vfx};
+use crate::scene_runtime::terrain_height::TerrainHeightField;
+use crate::settings::{FpsLimitSetting, GraphicsSettings};
+
+pub const BENCHMARK_FLAG: &str = "--benchmark";
+const WORLD_FLAG: &str = "--world";
+const DURATION_FLAG: &str = "--duration-secs";
+const SETTLE_FLAG: &str = "--settle-secs";
+const TIMEOUT_FLAG: &str = "--timeout-secs";
+const REPORT_FLAG: &str = "--report";
+
+/// Distance of the orbit from the map centre; it swings in and out by
+/// `ORBIT_RADIUS_SWING` twice per lap.
+const ORBIT_RADIUS: f32 = 2_200.0;
+const ORBIT_RADIUS_SWING: f32 = 700.0;
+const ORBIT_HEIGHT: f32 = 900.0;
+const ORBIT_HEIGHT_SWING: f32 = 350.0;
+/// Seconds between VFX bursts and effects per burst.
+const BURST_INTERVAL_SECS: f32 = 4.0;
+const BURST_SIZE: usize = 6;
+/// Distance of the burst ring from the map centre.
+const BURST_RING_RADIUS: f32 = 450.0;
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum BenchmarkArgsError {
+    #[error("{flag} needs a value")]
+    MissingValue { flag: &'static str },
+    #[error("invalid value '{value}' for {flag}")]
+    InvalidValue { flag: &'static str, value: String },
+    #[error("'{0}' is not a known world id")]
+    UnknownWorld(String),
+    #[error("unknown option '{0}'")]
+    UnknownOption(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkConfig {
+    pub world: WorldMap,
+    /// Length of the scripted camera path, which is also the sample window.
+    pub duration_secs: f32,
+    /// Time spent in the loaded world before the path starts, so shader
+    /// compilation and streaming are not measured.
+    pub settle_secs: f32,
+    /// Longest the world may take to spawn terrain and objects.
+    pub timeout_secs: f32,
+    pub report_path: PathBuf,
+}
+
+impl Default for BenchmarkConfig {
+    fn default() -> Self {
+        Self {
+            world: WorldMap::Lorencia,
+            duration_secs: 60.0,
+            settle_secs: 5.0,
+            timeout_secs: 120.0,
+            report_path: PathBuf::from("benchmark_report.json"),
+        }
+    }
+}
+
+impl BenchmarkConfig {
+    pub fn from_args(
+        args: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<Self, BenchmarkArgsError> {
+        let mut config = Self::default();
+        let mut args = args.into_iter().map(Into::into);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                BENCHMARK_FLAG => {}
+                // Read by `GpuSelection::with_args` and `LogOptions::with_args`.
+                gpu::BACKEND_FLAG | gpu::ADAPTER_FLAG | logging::LOG_FILTER_FLAG => {
+                    args.next();
+                }
+                WORLD_FLAG => {
+                    let value = flag_value(WORLD_FLAG, args.next())?;
+                    config.world = value
+                        .trim()
+                        .parse::<u8>()
+                        .ok()
+                        .and_then(WorldMap::from_id)
+                        .ok_or(BenchmarkArgsError::UnknownWorld(value))?;
+                }
+                DURATION_FLAG => {
+                    config.duration_secs = parse_number(DURATION_FLAG, args.next())?;
+                }
+                SETTLE_FLAG => config.settle_secs = parse_number(SETTLE_FLAG, args.next())?,
+                TIMEOUT_FLAG => config.timeout_secs = parse_number(TIMEOUT_FLAG, args.next())?,
+                REPORT_FLAG => {
+                    config.report_path = PathBuf::from(flag_value(REPORT_FLAG, args.next())?);
+                }
+                _ => return Err(BenchmarkArgsError::UnknownOption(arg)),
+            }
+        }
+        if config.duration_secs <= 0.0 {
+            return Err(BenchmarkArgsError::InvalidValue {
+                flag: DURATION_FLAG,
+                value: config.duration_secs.to_string(),
+            });
+        }
+        Ok(config)
+    }
+
+    /// Graphics settings the benchmark runs with: the player's, uncapped.
+    pub fn graphics(&self, graphics: &GraphicsSettings) -> GraphicsSettings {
+        GraphicsSettings {
+            vsync: false,
+            fps_limit: FpsLimitSetting::Unlimited,
+            ..graphics.clone()
+        }
+    }
+}
+
+fn flag_value(flag: &'static str, value: Option<String>) -> Result<String, BenchmarkArgsError> {
+    value.ok_or(BenchmarkArgsError::MissingValue { flag })
+}
+
+fn parse_number(flag: &'static str, value: Option<String>) -> Result<f32, BenchmarkArgsError> {
+    let value = flag_value(flag, value)?;
+    match value.trim().parse::<f32>() {
+        Ok(number) if number.is_finite() && number >= 0.0 => Ok(number),
+        _ => Err(BenchmarkArgsError::InvalidValue { flag, value }),
+    }
+}
+
+/// Frame times of the sample window, in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct FrameTimeStats {
+    pub frames: usize,
+    pub avg_fps: f32,
+    pub avg_ms: f32,
+    pub p50_ms: f32,
+    pub p90_ms: f32,
+    pub p95_ms: f32,
+    pub p99_ms: f32,
+    pub max_ms: f32,
+}
+
+impl FrameTimeStats {
+    pub fn from_frame_times(frame_ms: &[f32]) -> Option<Self> {
+        let mut sorted: Vec<f32> = frame_ms
+            .iter()
+            .copied()
+            .filter(|ms| ms.is_finite() && *ms > 0.0)
+            .collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_by(f32::total_cmp);
+        let total_ms: f32 = sorted.iter().sum();
+        let avg_ms = total_ms / sorted.len() as f32;
+        Some(Self {
+            frames: sorted.len(),
+            avg_fps: 1000.0 / avg_ms,
+            avg_ms,
+            p50_ms: percentile(&sorted, 0.50),
+            p90_ms: percentile(&sorted, 0.90),
+            p95_ms: percentile(&sorted, 0.95),
+            p99_ms: percentile(&sorted, 0.99),
+            max_ms: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// Nearest-rank percentile of sorted, non-empty samples.
+fn percentile(sorted: &[f32], fraction: f32) -> f32 {
+    let rank = (fraction * sorted.len() as f32).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct HardwareInfo {
+    pub os: String,
+    pub arch: String,
+    pub cpu_threads: Option<usize>,
+    pub gpu: Option<String>,
+    pub gpu_backend: Option<String>,
+    pub gpu_driver: Option<String>,
+    /// Physical size of the window the run rendered to.
+    pub resolution: Option<String>,
+}
+
+impl HardwareInfo {
+    fn gather(adapter: Option<&RenderAdapterInfo>, window: Option<&Window>) -> Self {
+        Self {
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            cpu_threads: std::thread::available_parallelism()
+                .ok()
+                .map(|threads| threads.get()),
+            gpu: adapter.map(|info| info.name.clone()),
+            gpu_backend: adapter.map(|info| format!("{:?}", info.backend)),
+            gpu_driver: adapter.map(|info| {
+                format!("{} {}", info.driver, info.driver_info)
+                    .trim()
+                    .to_string()
+            }),
+            resolution: window.map(|window| {
+                format!(
+                    "{}x{}",
+                    window.resolution.physical_width(),
+                    window.resolution.physical_height()
+                )
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BenchmarkOutcome {
+    Completed,
+    MissingAssets,
+    TimedOut,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BenchmarkReport {
+    pub outcome: BenchmarkOutcome,
+    pub client_version: &'static str,
+    pub debug_build: bool,
+    pub world_id: u8,
+    pub world_name: String,
+    pub duration_secs: f32,
+    /// Time from entering the world until terrain and objects spawned.
+    pub load_ms: Option<u64>,
+    pub frame_times: Option<FrameTimeStats>,
+    pub vfx_bursts: u32,
+    pub hardware: HardwareInfo,
+    pub graphics: GraphicsSettings,
+    pub missing_assets: Vec<String>,
+}
+
+impl BenchmarkReport {
+    fn new(
+        config: &BenchmarkConfig,
+        graphics: GraphicsSettings,
+        outcome: BenchmarkOutcome,
+    ) -> Self {
+        Self {
+            outcome,
+            client_version: env!("CARGO_PKG_VERSION"),
+            debug_build: cfg!(debug_assertions),
+            world_id: config.world as u8,
+            world_name: config.world.name().to_string(),
+            duration_secs: config.duration_secs,
+            load_ms: None,
+            frame_times: None,
+            vfx_bursts: 0,
+            hardware: HardwareInfo::default(),
+            graphics,
+            missing_assets: Vec::new(),
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let Some(stats) = &self.frame_times else {
+            return format!("Benchmark of {}: {:?}", self.world_name, self.outcome);
+        };
+        format!(
+            "Benchmark of {} ({} frames): {:.1} fps avg, p50 {:.2} ms, p95 {:.2} ms, p99 {:.2} ms, max {:.2} ms",
+            self.world_name,
+            stats.frames,
+            stats.avg_fps,
+            stats.p50_ms,
+            stats.p95_ms,
+            stats.p99_ms,
+            stats.max_ms
+        )
+    }
+
+    fn write(&self, path: &Path) {
+        let result = serde_json::to_vec_pretty(self)
+            .map_err(std::io::Error::other)
+            .and_then(|json| std::fs::write(path, json));
+        if let Err(error) = result {
+            eprintln!(
+                "Failed to write benchmark report '{}': {error}",
+                path.display()
+            );
+        }
+    }
+}
+
+/// Camera pose at `progress` (0..1) along the orbit around `center`.
+fn orbit_pose(progress: f32, center: Vec3) -> Transform {
+    let angle = TAU * progress;
+    let radius = ORBIT_RADIUS + ORBIT_RADIUS_SWING * (2.0 * angle).sin();
+    let height = ORBIT_HEIGHT + ORBIT_HEIGHT_SWING * angle.sin();
+    let eye = center + Vec3::new(angle.cos() * radius, height, angle.sin() * radius);
+    Transform::from_translation(eye).looking_at(center, Vec3::Y)
+}
+
+/// Where the effects of burst number `burst` go off, each facing the centre.
+/// The ring turns a little every burst so they do not land on the same spots.
+fn burst_positions(burst: u32, center: Vec3) -> Vec<(Vec3, Quat)> {
+    let offset = burst as f32 * 0.5;
+    (0..BURST_SIZE)
+        .map(|index| {
+            let angle = offset + TAU * index as f32 / BURST_SIZE as f32;
+            let position = center + Vec3::new(angle.cos(), 0.0, angle.sin()) * BURST_RING_RADIUS;
+            let facing = Transform::from_translation(position)
+                .looking_at(Vec3::new(center.x, position.y, center.z), Vec3::Y)
+                .rotation;
+            (position, facing)
+        })
+        .collect()
+}
+
+enum Phase {
+    /// Waiting in `AppState::Loading` to enter the world.
+    Idle,
+    Loading {
+        started: f64,
+    },
+    Settling {
+        started: f64,
+        load_ms: u64,
+    },
+    Running {
+        started: f64,
+        load_ms: u64,
+        /// Map centre on the ground, the orbit's focus.
+        center: Vec3,
+        frame_ms: Vec<f32>,
+        bursts: u32,
+    },
+    /// Report written; waiting for the app to exit.
+    Finished,
+}
+
+#[derive(Resource)]
+struct BenchmarkRun {
+    config: BenchmarkConfig,
+    graphics: GraphicsSettings,
+    phase: Phase,
+}
+
+pub struct BenchmarkPlugin {
+    pub config: BenchmarkConfig,
+    /// Graphics settings the app was configured with.
+    pub graphics: GraphicsSettings,
+}
+
+impl Plugin for BenchmarkPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(WinitSettings::continuous())
+            .insert_resource(BenchmarkRun {
+                config: self.config.clone(),
+                graphics: self.graphics.clone(),
+                phase: Phase::Idle,
+            })
+            .add_systems(Update, drive_benchmark.after(GameplayPipelineSet::Camera));
+    }
+}
+
+fn drive_benchmark(
+    time: Res<Time<Real>>,
+    state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut commands: Commands,
+    mut run: ResMut<BenchmarkRun>,
+    scene_assets: Option<Res<RuntimeSceneAssets>>,
+    ground: Option<Res<TerrainHeightField>>,
+    terrain: Query<(), With<TerrainSpawned>>,
+    objects: Query<(), With<SceneObjectsSpawned>>,
+    mut cameras: Query<(&mut Transform, Option<&mut CameraTour>), With<Camera3d>>,
+    adapter: Option<Res<RenderAdapterInfo>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let now = time.elapsed_secs_f64();
+    let run = &mut *run;
+
+    match &mut run.phase {
+        Phase::Idle => {
+            if *state.get() != AppState::Loading {
+                return;
+            }
+            let missing = missing_mandatory_assets(run.config.world, asset_path_exists);
+            if !missing.is_empty() {
+                let mut report = BenchmarkReport::new(
+                    &run.config,
+                    run.graphics.clone(),
+                    BenchmarkOutcome::MissingAssets,
+                );
+                report.missing_assets = missing;
+                finish_benchmark(report, &run.config, &mut exit);
+                run.phase = Phase::Finished;
+                return;
+            }
+            info!(
+                "Benchmarking {} (ID: {})",
+                run.config.world.name(),
+                run.config.world as u8
+            );
+            commands.insert_resource(GameplayWorldOverride(run.config.world));
+            next_state.set(AppState::Gameplay);
+            run.phase = Phase::Loading { started: now };
+        }
+        Phase::Loading { started } => {
+            let loaded = *state.get() == AppState::Gameplay
+                && scene_assets.as_ref().is_some_and(|assets| assets.loaded)
+                && !terrain.is_empty()
+                && !objects.is_empty();
+            if loaded {
+                run.phase = Phase::Settling {
+                    started: now,
+                    load_ms: ((now - *started) * 1000.0) as u64,
+                };
+            } else if now - *started > f64::from(run.config.timeout_secs) {
+                let report = BenchmarkReport::new(
+                    &run.config,
+                    run.graphics.clone(),
+                    BenchmarkOutcome::TimedOut,
+                );
+                finish_benchmark(report, &run.config, &mut exit);
+                run.phase = Phase::Finished;
+            }
+        }
+        Phase::Settling { started, load_ms } => {
+            if now - *started < f64::from(run.config.settle_secs) {
+                return;
+            }
+            let center = ground.as_deref().map(map_center).unwrap_or(Vec3::ZERO);
+            // The scripted path replaces the world's camera tour.
+            for (_, tour) in &mut cameras {
+                if let Some(mut tour) = tour {
+                    tour.active = false;
+                }
+            }
+            run.phase = Phase::Running {
+                started: now,
+                load_ms: *load_ms,
+                center,
+                frame_ms: Vec::new(),
+                bursts: 0,
+            };
+        }
+        Phase::Running {
+            started,
+            load_ms,
+            center,
+            frame_ms,
+            bursts,
+        } => {
+            let elapsed = (now - *started) as f32;
+            if elapsed > 0.0 {
+                frame_ms.push(time.delta_secs() * 1000.0);
+            }
+
+            let progress = (elapsed / run.config.duration_secs).min(1.0);
+            let pose = orbit_pose(progress, *center);
+            for (mut transform, _) in &mut cameras {
+                *transform = pose;
+            }
+
+            while (*bursts as f32) * BURST_INTERVAL_SECS <= elapsed {
+                spawn_vfx_burst(&mut commands, *bursts, *center);
+                *bursts += 1;
+            }
+
+            if progress < 1.0 {
+                return;
+            }
+            let mut report = BenchmarkReport::new(
+                &run.config,
+                run.graphics.clone(),
+                BenchmarkOutcome::Completed,
+            );
+            report.load_ms = Some(*load_ms);
+            report.frame_times = FrameTimeStats::from_frame_times(frame_ms);
+            report.vfx_bursts = *bursts;
+            report.hardware = HardwareInfo::gather(adapter.as_deref(), windows.single().ok());
+            finish_benchmark(report, &run.config, &mut exit);
+            run.phase = Phase::Finished;
+        }
+        Phase::Finished => {}
+    }
+}
+
+/// Centre of the loaded map, on the terrain.
+fn map_center(ground: &TerrainHeightField) -> Vec3 {
+    let (map_max_x, map_max_z) = ground.map_max();
+    ground.on_ground(Vec3::new(map_max_x / 2.0, 0.0, map_max_z / 2.0))
+}
+
+fn spawn_vfx_burst(commands: &mut Commands, burst: u32, center: Vec3) {
+    for (position, rotation) in burst_positions(burst, center) {
+        let caster = commands
+            .spawn((
+                RuntimeSceneEntity,
+                Transform::from_translation(position).with_rotation(rotation),
+            ))
+            .id();
+        let target = position + rotation * Vec3::NEG_Z * BURST_RING_RADIUS;
+        spawn_death_stab_vfx(commands, caster, position, rotation, target);
+    }
+}
+
+fn finish_benchmark(
+    report: BenchmarkReport,
+    config: &BenchmarkConfig,
+    exit: &mut MessageWriter<AppExit>,
+) {
+    report.write(&config.report_path);
+    println!("{}", report.summary());
+    println!("Report written to {}", config.report_path.display());
+    exit.write(if report.outcome == BenchmarkOutcome::Completed {
+        AppExit::Success
+    } else {
+        AppExit::error()
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_options_next_to_gpu_overrides() {
+        let config = BenchmarkConfig::from_args([
+            "--benchmark",
+            "--gpu",
+            "nvidia",
+            "--world",
+            "3",
+            "--duration-secs",
+            "30",
+            "--report",
+            "bench.json",
+        ])
+        .unwrap();
+
+        assert_eq!(config.world, WorldMap::Devias);
+        assert_eq!(config.duration_secs, 30.0);
+        assert_eq!(config.report_path, PathBuf::from("bench.json"));
+        assert_eq!(
+            BenchmarkConfig::from_args(["--benchmark", "--world", "999"]),
+            Err(BenchmarkArgsError::UnknownWorld("999".to_string()))
+        );
+        assert!(matches!(
+            BenchmarkConfig::from_args(["--duration-secs", "0"]),
+            Err(BenchmarkArgsError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            BenchmarkConfig::from_args(["--maps", "1"]),
+            Err(BenchmarkArgsError::UnknownOption(_))
+        ));
+    }
+
+    #[test]
+    fn percentiles_use_the_nearest_rank() {
+        assert_eq!(FrameTimeStats::from_frame_times(&[]), None);
+        let frames: Vec<f32> = (1..=100).rev().map(|ms| ms as f32).collect();
+        let stats = FrameTimeStats::from_frame_times(&frames).unwrap();
+        assert_eq!(stats.frames, 100);
+        assert_eq!(stats.p50_ms, 50.0);
+        assert_eq!(stats.p95_ms, 95.0);
+        assert_eq!(stats.p99_ms, 99.0);
+        assert_eq!(stats.max_ms, 100.0);
+        assert!((stats.avg_ms - 50.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn the_orbit_is_a_closed_loop_looking_at_the_centre() {
+        let center = Vec3::new(12_800.0, 200.0, 12_800.0);
+        let start = orbit_pose(0.0, center);
+        let end = orbit_pose(1.0, center);
+        assert!(start.translation.distance(end.translation) < 1.0);
+
+        let halfway = orbit_pose(0.5, center);
+        let to_center = (center - halfway.translation).normalize();
+        assert!(halfway.forward().dot(to_center) > 0.999);
+        assert!(halfway.translation.y > center.y);
+    }
+
+    #[test]
+    fn bursts_ring_the_centre_and_turn() {
+        let center = Vec3::new(1_000.0, 50.0, 1_000.0);
+        let first = burst_positions(0, center);
+        assert_eq!(first.len(), BURST_SIZE);
+        for (position, _) in &first {
+            assert!((position.distance(center) - BURST_RING_RADIUS).abs() < 0.01);
+        }
+        assert_ne!(first[0].0, burst_positions(1, center)[0].0);
+    }
+}
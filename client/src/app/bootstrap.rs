@@ -1,22 +1,180 @@
-use bevy::prelude::App;
+use std::path::PathBuf;
 
-use crate::composition::client_runtime::configure_client_app;
+use bevy::prelude::{App, AppExit, Startup};
+
+use crate::AppState;
+use crate::app::benchmark::{self, BenchmarkConfig, BenchmarkPlugin};
+use crate::app::gpu::{self, GpuSelection, GpuStartup};
+use crate::app::replay::{self, ReplayPlaybackPlugin};
+use crate::app::world_smoke::{
+    SharedSmokeReport, SmokeTestConfig, WorldSmokeTestPlugin, worlds_to_visit,
+};
+use crate::composition::client_runtime::{configure_client_app, configure_client_app_in_state};
 use crate::domain::settings::GameSettings;
-use crate::infra::persistence::settings_store;
+use crate::infra::persistence::{crash_log, paths, settings_store};
+use crate::infra::replay::{RECORD_FLAG, ReplayRecorderPlugin, read_replay};
 
 pub fn run_client_app() {
+    crash_log::install_crash_log_hook();
+    let migration = paths::migrate_legacy_files();
     let startup_settings = load_startup_settings();
+    let requested_gpu = match GpuSelection::from_settings(&startup_settings.graphics)
+        .with_args(std::env::args().skip(1))
+    {
+        Ok(selection) => selection,
+        Err(error) => {
+            eprintln!("{error}");
+            std::process::exit(2);
+        }
+    };
+
+    let adapters = gpu::available_adapters();
+    if std::env::args().any(|arg| arg == gpu::LIST_FLAG) {
+        gpu::print_adapters(&adapters);
+        return;
+    }
+    if std::env::args().any(|arg| arg == benchmark::BENCHMARK_FLAG) {
+        run_benchmark(
+            startup_settings,
+            GpuStartup::resolve(requested_gpu, adapters),
+            migration,
+        );
+        return;
+    }
+    let (replay_path, record_path) = match (
+        replay::path_arg(replay::REPLAY_FLAG, std::env::args().skip(1)),
+        replay::path_arg(RECORD_FLAG, std::env::args().skip(1)),
+    ) {
+        (Ok(replay_path), Ok(record_path)) => (replay_path, record_path),
+        (Err(error), _) | (_, Err(error)) => {
+            eprintln!("{error}");
+            std::process::exit(2);
+        }
+    };
+    if let Some(path) = replay_path {
+        run_replay(
+            startup_settings,
+            GpuStartup::resolve(requested_gpu, adapters),
+            path,
+            migration,
+        );
+        return;
+    }
+
     let mut app = App::new();
-    configure_client_app(&mut app, &startup_settings);
+    configure_client_app(
+        &mut app,
+        &startup_settings,
+        GpuStartup::resolve(requested_gpu, adapters),
+    );
+    report_migration(&mut app, migration);
+    if let Some(path) = record_path {
+        app.add_plugins(ReplayRecorderPlugin { path });
+    }
     app.run();
 }
 
+/// Runs the world-switch smoke test (see [`crate::app::world_smoke`]) and
+/// exits non-zero when any world fails.
+pub fn run_world_smoke_test() {
+    let config = match SmokeTestConfig::from_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{error}");
+            std::process::exit(2);
+        }
+    };
+
+    let startup_settings = load_startup_settings();
+    let gpu = GpuStartup::resolve(
+        GpuSelection::from_settings(&startup_settings.graphics),
+        gpu::available_adapters(),
+    );
+
+    let mut app = App::new();
+    configure_client_app_in_state(&mut app, &startup_settings, gpu, AppState::Loading);
+
+    let worlds = worlds_to_visit(&config);
+    if worlds.is_empty() {
+        eprintln!("No worlds found under the asset root");
+        std::process::exit(2);
+    }
+
+    let report = SharedSmokeReport::default();
+    report.install_panic_hook(config.report_path.clone());
+    app.add_plugins(WorldSmokeTestPlugin {
+        config,
+        worlds,
+        report,
+    });
+    if app.run() != AppExit::Success {
+        std::process::exit(1);
+    }
+}
+
+/// Runs the standard benchmark (see [`crate::app::benchmark`]) and exits
+/// non-zero when the world could not be measured.
+fn run_benchmark(
+    mut startup_settings: GameSettings,
+    gpu: GpuStartup,
+    migration: paths::LegacyMigration,
+) {
+    let config = match BenchmarkConfig::from_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{error}");
+            std::process::exit(2);
+        }
+    };
+    // Only for this run; the settings file is left alone.
+    startup_settings.graphics = config.graphics(&startup_settings.graphics);
+
+    let mut app = App::new();
+    configure_client_app_in_state(&mut app, &startup_settings, gpu, AppState::Loading);
+    report_migration(&mut app, migration);
+    app.add_plugins(BenchmarkPlugin {
+        config,
+        graphics: startup_settings.graphics,
+    });
+    if app.run() != AppExit::Success {
+        std::process::exit(1);
+    }
+}
+
+/// Plays a recorded session back (see [`crate::app::replay`]).
+fn run_replay(
+    startup_settings: GameSettings,
+    gpu: GpuStartup,
+    path: PathBuf,
+    migration: paths::LegacyMigration,
+) {
+    let entries = match read_replay(&path) {
+        Ok(entries) => entries,
+        Err(error) => {
+            eprintln!("Failed to read replay '{}': {}", path.display(), error);
+            std::process::exit(2);
+        }
+    };
+
+    let mut app = App::new();
+    configure_client_app_in_state(&mut app, &startup_settings, gpu, AppState::Loading);
+    report_migration(&mut app, migration);
+    app.add_plugins(ReplayPlaybackPlugin { entries });
+    app.run();
+}
+
+/// Logs the move of old files once the app's logger is up.
+fn report_migration(app: &mut App, migration: paths::LegacyMigration) {
+    app.insert_resource(migration)
+        .add_systems(Startup, paths::log_legacy_migration);
+}
+
 fn load_startup_settings() -> GameSettings {
     let startup_settings = settings_store::load();
     if let Err(error) = settings_store::ensure_exists(&startup_settings) {
         eprintln!(
             "Failed to ensure startup settings file '{}': {}",
-            crate::settings::SETTINGS_FILE_PATH,
+            crate::settings::settings_file_path().display(),
             error
         );
     }
@@ -0,0 +1,391 @@
+//! Renderer backend and GPU selection.
+//!
+//! The choice comes from the graphics settings and can be overridden for one
+//! run with `--gpu-backend <auto|vulkan|dx12|metal>` and `--gpu <name>`;
+//! `--list-gpus` prints what is available and exits. It is checked against
+//! the adapters wgpu can see before the renderer starts, because wgpu would
+//! otherwise quietly pick another GPU (or panic when the backend is missing).
+//! An unavailable choice falls back to automatic selection and explains why
+//! in a dialog.
+
+use crate::settings::{GpuBackendSetting, GraphicsSettings, SettingsResource};
+use bevy::prelude::*;
+use bevy::render::settings::{Backends, WgpuSettings};
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use thiserror::Error;
+
+pub const BACKEND_FLAG: &str = "--gpu-backend";
+pub const ADAPTER_FLAG: &str = "--gpu";
+pub const LIST_FLAG: &str = "--list-gpus";
+
+/// Backends probed when listing adapters, in the order they are listed.
+const PROBED_BACKENDS: [GpuBackendSetting; 3] = [
+    GpuBackendSetting::Vulkan,
+    GpuBackendSetting::Dx12,
+    GpuBackendSetting::Metal,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum GpuSelectionError {
+    #[error("{flag} needs a value")]
+    MissingValue { flag: &'static str },
+    #[error("unknown GPU backend '{0}' (expected auto, vulkan, dx12 or metal)")]
+    UnknownBackend(String),
+    #[error("no GPU supports {backend} on this machine")]
+    BackendUnavailable { backend: &'static str },
+    #[error("no {backend} GPU matches '{name}'")]
+    AdapterNotFound { backend: &'static str, name: String },
+}
+
+/// An adapter wgpu can render on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GpuAdapter {
+    pub name: String,
+    pub backend: GpuBackendSetting,
+    /// "discrete", "integrated", "virtual", "cpu" or "other".
+    pub kind: &'static str,
+}
+
+impl GpuAdapter {
+    pub fn label(&self) -> String {
+        format!("{} ({}, {})", self.name, self.backend.label(), self.kind)
+    }
+}
+
+/// Lists the adapters of every backend this build can use.
+pub fn available_adapters() -> Vec<GpuAdapter> {
+    let probed = PROBED_BACKENDS
+        .iter()
+        .filter_map(|backend| backend.backends())
+        .fold(Backends::empty(), |all, backends| all | backends);
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: probed,
+        ..wgpu::InstanceDescriptor::from_env_or_default()
+    });
+
+    PROBED_BACKENDS
+        .iter()
+        .flat_map(|backend| {
+            let backends = backend.backends().unwrap_or(Backends::empty());
+            instance
+                .enumerate_adapters(backends)
+                .into_iter()
+                .map(|adapter| {
+                    let info = adapter.get_info();
+                    GpuAdapter {
+                        name: info.name,
+                        backend: *backend,
+                        kind: match info.device_type {
+                            wgpu::DeviceType::DiscreteGpu => "discrete",
+                            wgpu::DeviceType::IntegratedGpu => "integrated",
+                            wgpu::DeviceType::VirtualGpu => "virtual",
+                            wgpu::DeviceType::Cpu => "cpu",
+                            wgpu::DeviceType::Other => "other",
+                        },
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+pub fn print_adapters(adapters: &[GpuAdapter]) {
+    if adapters.is_empty() {
+        println!("No GPU found");
+    }
+    for adapter in adapters {
+        println!("{}", adapter.label());
+    }
+}
+
+/// Backend and adapter the renderer is asked for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GpuSelection {
+    pub backend: GpuBackendSetting,
+    /// Case-insensitive part of the adapter name.
+    pub adapter: Option<String>,
+}
+
+impl GpuSelection {
+    pub fn from_settings(graphics: &GraphicsSettings) -> Self {
+        Self {
+            backend: graphics.gpu_backend,
+            adapter: graphics.gpu_adapter.clone(),
+        }
+    }
+
+    /// Applies `--gpu-backend` and `--gpu` overrides; other arguments are ignored.
+    pub fn with_args(
+        mut self,
+        args: impl IntoIterator<Item = String>,
+    ) -> Result<Self, GpuSelectionError> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                BACKEND_FLAG => {
+                    let value = args
+                        .next()
+                        .ok_or(GpuSelectionError::MissingValue { flag: BACKEND_FLAG })?;
+                    self.backend = GpuBackendSetting::from_arg(&value)
+                        .ok_or(GpuSelectionError::UnknownBackend(value))?;
+                }
+                ADAPTER_FLAG => {
+                    let value = args
+                        .next()
+                        .ok_or(GpuSelectionError::MissingValue { flag: ADAPTER_FLAG })?;
+                    self.adapter = Some(value).filter(|name| !name.trim().is_empty());
+                }
+                _ => {}
+            }
+        }
+        Ok(self)
+    }
+
+    /// Whether `adapter` can be used for this selection.
+    pub fn accepts(&self, adapter: &GpuAdapter) -> bool {
+        let backend_matches = match self.backend {
+            GpuBackendSetting::Auto => adapter
+                .backend
+                .backends()
+                .is_some_and(|backends| self.backends().contains(backends)),
+            backend => adapter.backend == backend,
+        };
+        let name_matches = self.adapter.as_ref().is_none_or(|name| {
+            adapter
+                .name
+                .to_lowercase()
+                .contains(&name.trim().to_lowercase())
+        });
+        backend_matches && name_matches
+    }
+
+    /// Checks the selection against the adapters found on this machine.
+    pub fn check(&self, adapters: &[GpuAdapter]) -> Result<(), GpuSelectionError> {
+        if adapters.iter().any(|adapter| self.accepts(adapter)) {
+            return Ok(());
+        }
+
+        let backend = self.backend.label();
+        match &self.adapter {
+            Some(name) => Err(GpuSelectionError::AdapterNotFound {
+                backend,
+                name: name.clone(),
+            }),
+            // Nothing to fall back to; leave the error to the renderer.
+            None if self.backend == GpuBackendSetting::Auto => Ok(()),
+            None => Err(GpuSelectionError::BackendUnavailable { backend }),
+        }
+    }
+
+    pub fn wgpu_settings(&self) -> WgpuSettings {
+        let defaults = WgpuSettings::default();
+        WgpuSettings {
+            backends: self.backend.backends().or(defaults.backends),
+            adapter_name: self.adapter.clone(),
+            ..defaults
+        }
+    }
+
+    fn backends(&self) -> Backends {
+        self.backend
+            .backends()
+            .or(WgpuSettings::default().backends)
+            .unwrap_or(Backends::all())
+    }
+}
+
+/// Outcome of the startup check, handed to [`GpuSelectionPlugin`].
+#[derive(Debug, Clone)]
+pub struct GpuStartup {
+    /// Selection the renderer is created with.
+    pub selection: GpuSelection,
+    pub adapters: Vec<GpuAdapter>,
+    /// Why the requested selection was replaced by automatic selection.
+    pub failure: Option<GpuSelectionError>,
+}
+
+impl GpuStartup {
+    pub fn resolve(requested: GpuSelection, adapters: Vec<GpuAdapter>) -> Self {
+        match requested.check(&adapters) {
+            Ok(()) => Self {
+                selection: requested,
+                adapters,
+                failure: None,
+            },
+            Err(error) => {
+                eprintln!("GPU selection unavailable, using automatic selection: {error}");
+                Self {
+                    selection: GpuSelection::default(),
+                    adapters,
+                    failure: Some(error),
+                }
+            }
+        }
+    }
+}
+
+/// Adapters found at startup, offered by the graphics settings.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct AvailableGpus(pub Vec<GpuAdapter>);
+
+#[derive(Resource, Debug)]
+struct GpuSelectionFailure(GpuSelectionError);
+
+pub struct GpuSelectionPlugin(pub GpuStartup);
+
+impl Plugin for GpuSelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AvailableGpus(self.0.adapters.clone()))
+            .add_systems(
+                EguiPrimaryContextPass,
+                draw_gpu_selection_dialog.run_if(resource_exists::<GpuSelectionFailure>),
+            );
+        if let Some(error) = &self.0.failure {
+            app.insert_resource(GpuSelectionFailure(error.clone()));
+        }
+    }
+}
+
+fn draw_gpu_selection_dialog(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    failure: Res<GpuSelectionFailure>,
+    gpus: Res<AvailableGpus>,
+    mut settings: ResMut<SettingsResource>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let mut use_automatic = false;
+    let mut dismiss = false;
+    let mut quit = false;
+    egui::Window::new("Placa de video indisponivel")
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .collapsible(false)
+        .resizable(false)
+        .movable(false)
+        .default_width(460.0)
+        .show(ctx, |ui| {
+            ui.label("A placa de video escolhida nao pode ser usada:");
+            ui.strong(failure.0.to_string());
+            ui.label("O jogo iniciou com a selecao automatica.");
+            ui.add_space(6.0);
+
+            ui.label("Placas encontradas:");
+            if gpus.0.is_empty() {
+                ui.weak("Nenhuma");
+            }
+            for adapter in &gpus.0 {
+                ui.monospace(adapter.label());
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                use_automatic = ui.button("Usar selecao automatica").clicked();
+                dismiss = ui.button("Manter escolha").clicked();
+                quit = ui.button("Sair do jogo").clicked();
+            });
+        });
+
+    if use_automatic {
+        settings.current.graphics.gpu_backend = GpuBackendSetting::Auto;
+        settings.current.graphics.gpu_adapter = None;
+        if let Err(error) = settings.save_to_disk() {
+            warn!("Failed to save settings: {error}");
+        }
+    }
+    if use_automatic || dismiss {
+        commands.remove_resource::<GpuSelectionFailure>();
+    }
+    if quit {
+        exit.write(AppExit::Success);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adapter(name: &str, backend: GpuBackendSetting) -> GpuAdapter {
+        GpuAdapter {
+            name: name.to_string(),
+            backend,
+            kind: "discrete",
+        }
+    }
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn command_line_overrides_settings() {
+        let graphics = GraphicsSettings {
+            gpu_adapter: Some("Intel".to_string()),
+            ..GraphicsSettings::default()
+        };
+        let saved = GpuSelection::from_settings(&graphics);
+
+        let selection = saved
+            .clone()
+            .with_args(args(&[
+                "--windowed",
+                "--gpu-backend",
+                "DX12",
+                "--gpu",
+                "RTX",
+            ]))
+            .unwrap();
+        assert_eq!(selection.backend, GpuBackendSetting::Dx12);
+        assert_eq!(selection.adapter.as_deref(), Some("RTX"));
+        assert_eq!(saved.clone().with_args(Vec::new()).unwrap(), saved);
+
+        assert_eq!(
+            saved.clone().with_args(args(&["--gpu-backend", "opengl"])),
+            Err(GpuSelectionError::UnknownBackend("opengl".to_string()))
+        );
+        assert_eq!(
+            saved.with_args(args(&["--gpu"])),
+            Err(GpuSelectionError::MissingValue { flag: "--gpu" })
+        );
+    }
+
+    #[test]
+    fn unavailable_selection_falls_back_to_automatic() {
+        let adapters = vec![
+            adapter("Intel(R) UHD Graphics", GpuBackendSetting::Vulkan),
+            adapter(
+                "NVIDIA GeForce RTX 3060 Laptop GPU",
+                GpuBackendSetting::Vulkan,
+            ),
+        ];
+        let select = |backend, name: Option<&str>| GpuSelection {
+            backend,
+            adapter: name.map(str::to_string),
+        };
+
+        let dedicated = select(GpuBackendSetting::Vulkan, Some("rtx"));
+        let startup = GpuStartup::resolve(dedicated.clone(), adapters.clone());
+        assert_eq!(startup.selection, dedicated);
+        assert_eq!(startup.failure, None);
+
+        let startup = GpuStartup::resolve(select(GpuBackendSetting::Dx12, None), adapters.clone());
+        assert_eq!(startup.selection, GpuSelection::default());
+        assert_eq!(
+            startup.failure,
+            Some(GpuSelectionError::BackendUnavailable {
+                backend: "DirectX 12"
+            })
+        );
+
+        assert_eq!(
+            select(GpuBackendSetting::Vulkan, Some("Radeon")).check(&adapters),
+            Err(GpuSelectionError::AdapterNotFound {
+                backend: "Vulkan",
+                name: "Radeon".to_string()
+            })
+        );
+    }
+}
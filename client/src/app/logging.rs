@@ -0,0 +1,130 @@
+//! Log output: stdout as Bevy sets it up, plus the rotating log file (see
+//! [`crate::infra::persistence::log_file`]).
+//!
+//! The level comes from `diagnostics.log_verbosity` in the settings, and
+//! `--log-filter <directives>` adds per-module filters for one run, e.g.
+//! `--log-filter client::infra::network=trace`. `RUST_LOG` still replaces
+//! both when set.
+
+use std::sync::Mutex;
+
+use bevy::log::tracing_subscriber::{Layer, fmt};
+use bevy::log::{BoxedLayer, DEFAULT_FILTER, LogPlugin};
+use bevy::prelude::*;
+use thiserror::Error;
+
+use crate::infra::persistence::log_file::{KEPT_LOG_FILES, MAX_LOG_FILE_BYTES, RotatingLog};
+use crate::infra::persistence::paths::client_dirs;
+use crate::settings::{DiagnosticsSettings, LogVerbositySetting};
+
+pub const LOG_FILTER_FLAG: &str = "--log-filter";
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LogOptionsError {
+    #[error("{flag} needs a value")]
+    MissingValue { flag: &'static str },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogOptions {
+    pub verbosity: LogVerbositySetting,
+    /// `EnvFilter` directives applied on top of the defaults.
+    pub filter: Option<String>,
+}
+
+impl LogOptions {
+    pub fn from_settings(diagnostics: &DiagnosticsSettings) -> Self {
+        Self {
+            verbosity: diagnostics.log_verbosity,
+            filter: None,
+        }
+    }
+
+    /// Applies `--log-filter`; other arguments are ignored.
+    pub fn with_args(
+        mut self,
+        args: impl IntoIterator<Item = String>,
+    ) -> Result<Self, LogOptionsError> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == LOG_FILTER_FLAG {
+                let value = args.next().ok_or(LogOptionsError::MissingValue {
+                    flag: LOG_FILTER_FLAG,
+                })?;
+                self.filter = Some(value).filter(|filter| !filter.trim().is_empty());
+            }
+        }
+        Ok(self)
+    }
+
+    /// Filter string of the log plugin; later directives win over the
+    /// defaults for the same module.
+    pub fn filter_directives(&self) -> String {
+        match &self.filter {
+            Some(filter) => format!("{DEFAULT_FILTER}{}", filter.trim()),
+            None => DEFAULT_FILTER.to_string(),
+        }
+    }
+
+    pub fn log_plugin(&self) -> LogPlugin {
+        LogPlugin {
+            filter: self.filter_directives(),
+            level: self.verbosity.level(),
+            custom_layer: file_log_layer,
+            ..Default::default()
+        }
+    }
+}
+
+/// Writes the same lines as stdout, without colors, to the log file. Logging
+/// goes on to stdout only when the file cannot be created.
+fn file_log_layer(_app: &mut App) -> Option<BoxedLayer> {
+    let path = client_dirs().log_file();
+    match RotatingLog::open(path.clone(), MAX_LOG_FILE_BYTES, KEPT_LOG_FILES) {
+        Ok(log) => Some(
+            fmt::layer()
+                .with_ansi(false)
+                .with_writer(Mutex::new(log))
+                .boxed(),
+        ),
+        Err(error) => {
+            eprintln!("Failed to open log file '{}': {}", path.display(), error);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn filter_flag_extends_the_default_filter() {
+        let settings = DiagnosticsSettings {
+            log_verbosity: LogVerbositySetting::Debug,
+        };
+        let options = LogOptions::from_settings(&settings)
+            .with_args(args(&["--gpu", "RTX", LOG_FILTER_FLAG, "client=trace"]))
+            .unwrap();
+        assert_eq!(options.verbosity, LogVerbositySetting::Debug);
+        assert_eq!(
+            options.filter_directives(),
+            format!("{DEFAULT_FILTER}client=trace")
+        );
+
+        assert_eq!(
+            LogOptions::default().with_args(args(&[LOG_FILTER_FLAG])),
+            Err(LogOptionsError::MissingValue {
+                flag: LOG_FILTER_FLAG
+            })
+        );
+        assert_eq!(
+            LogOptions::default().filter_directives(),
+            DEFAULT_FILTER.to_string()
+        );
+    }
+}
@@ -1,3 +1,8 @@
+pub mod benchmark;
 pub mod bootstrap;
+pub mod gpu;
+pub mod logging;
 pub mod plugins;
+pub mod replay;
 pub mod state;
+pub mod world_smoke;
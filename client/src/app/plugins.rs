@@ -1,13 +1,20 @@
 use bevy::app::PluginGroupBuilder;
 use bevy::asset::AssetPlugin;
 use bevy::prelude::*;
+use bevy::render::RenderPlugin;
 use bevy::render::pipelined_rendering::PipelinedRenderingPlugin;
+use bevy::render::settings::RenderCreation;
 use bevy::window::WindowResolution;
 use bevy::winit::WinitSettings;
 
+use crate::app::gpu::GpuSelection;
+use crate::app::logging::LogOptions;
 use crate::settings::{self, GameSettings};
 
-pub fn build_bevy_plugins(startup_settings: &GameSettings) -> PluginGroupBuilder {
+pub fn build_bevy_plugins(
+    startup_settings: &GameSettings,
+    gpu_selection: &GpuSelection,
+) -> PluginGroupBuilder {
     DefaultPlugins
         .set(WindowPlugin {
             primary_window: Some(create_window_settings(startup_settings)),
@@ -17,9 +24,27 @@ pub fn build_bevy_plugins(startup_settings: &GameSettings) -> PluginGroupBuilder
             file_path: concat!(env!("CARGO_MANIFEST_DIR"), "/../assets").into(),
             ..Default::default()
         })
+        .set(RenderPlugin {
+            render_creation: RenderCreation::Automatic(gpu_selection.wgpu_settings()),
+            ..Default::default()
+        })
+        .set(log_options(startup_settings).log_plugin())
         .disable::<PipelinedRenderingPlugin>()
 }
 
+/// Log level from the settings, with `--log-filter` on top. A flag without a
+/// value is reported and the run goes on with the settings alone.
+fn log_options(startup_settings: &GameSettings) -> LogOptions {
+    let from_settings = LogOptions::from_settings(&startup_settings.diagnostics);
+    match from_settings.clone().with_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{error}");
+            from_settings
+        }
+    }
+}
+
 pub fn create_winit_settings(startup_settings: &GameSettings) -> WinitSettings {
     let focused_mode = startup_settings.graphics.fps_limit.to_update_mode();
     WinitSettings {
@@ -0,0 +1,253 @@
+//! Replay playback, started with `--replay <path>`.
+//!
+//! Loads the map the recording entered, then feeds the recorded server
+//! messages back in at their original timing, so the session re-simulates
+//! through the usual systems without a server. The camera is free from the
+//! start (WASD to move, right mouse to look, Ctrl+W to let go); the client
+//! messages of the recording only show in the network overlay. Recorded
+//! datagrams go through the same sequencing as live ones, so those a newer
+//! one overtook are dropped again. Sessions are recorded with
+//! `--record-replay <path>`, see [`crate::infra::replay`].
+
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use common::WorldMap;
+use protocol::{PacketPayload, RecordedPacket, ServerMessage, TransportKind, preferred_channel};
+use thiserror::Error;
+
+use crate::AppState;
+use crate::gameplay::runtime::pipeline::GameplayPipelineSet;
+use crate::gameplay::scenes::gameplay::{GameplayWorldOverride, world_of_map};
+use crate::gameplay::systems::camera::{
+    DebugFreeCameraController, control_debug_free_camera, toggle_debug_free_camera,
+};
+use crate::infra::network::{NetworkStats, ServerMessageReceived};
+use crate::scene_runtime::components::CameraTour;
+use crate::scene_runtime::state::RuntimeSceneAssets;
+use crate::scene_runtime::systems::SceneObjectsSpawned;
+
+pub const REPLAY_FLAG: &str = "--replay";
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ReplayArgsError {
+    #[error("{flag} needs a path")]
+    MissingPath { flag: &'static str },
+}
+
+/// Path given after `flag`, if the flag is there.
+pub fn path_arg(
+    flag: &'static str,
+    args: impl IntoIterator<Item = impl Into<String>>,
+) -> Result<Option<PathBuf>, ReplayArgsError> {
+    let mut args = args.into_iter().map(Into::into);
+    if !args.any(|arg| arg == flag) {
+        return Ok(None);
+    }
+    args.next()
+        .filter(|path| !path.trim().is_empty())
+        .map(|path| Some(PathBuf::from(path)))
+        .ok_or(ReplayArgsError::MissingPath { flag })
+}
+
+/// Map of the first `EnterMap` in the recording, Lorencia without one.
+pub fn replay_world(entries: &[RecordedPacket]) -> WorldMap {
+    entries
+        .iter()
+        .find_map(|entry| match &entry.packet.payload {
+            PacketPayload::Server(ServerMessage::EnterMap { map_id, .. }) => world_of_map(*map_id),
+            _ => None,
+        })
+        .unwrap_or(WorldMap::Lorencia)
+}
+
+pub struct ReplayPlaybackPlugin {
+    pub entries: Vec<RecordedPacket>,
+}
+
+impl Plugin for ReplayPlaybackPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ReplayPlayback {
+            world: replay_world(&self.entries),
+            entries: self.entries.clone(),
+            next: 0,
+            phase: Phase::Idle,
+        })
+        .add_systems(Update, drive_replay.before(GameplayPipelineSet::Camera));
+
+        // Debug builds already register the free camera.
+        if !cfg!(debug_assertions) {
+            app.init_resource::<DebugFreeCameraController>()
+                .add_systems(
+                    Update,
+                    (toggle_debug_free_camera, control_debug_free_camera)
+                        .in_set(GameplayPipelineSet::Camera)
+                        .run_if(in_state(AppState::Gameplay)),
+                );
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Idle,
+    Loading,
+    Playing { started: f64 },
+    Finished,
+}
+
+#[derive(Resource)]
+struct ReplayPlayback {
+    entries: Vec<RecordedPacket>,
+    next: usize,
+    world: WorldMap,
+    phase: Phase,
+}
+
+impl ReplayPlayback {
+    /// Entries due `elapsed_ms` into the playback that were not played yet.
+    fn take_due(&mut self, elapsed_ms: u64) -> &[RecordedPacket] {
+        let start = self.next;
+        while self
+            .entries
+            .get(self.next)
+            .is_some_and(|entry| entry.at_ms <= elapsed_ms)
+        {
+            self.next += 1;
+        }
+        &self.entries[start..self.next]
+    }
+}
+
+fn drive_replay(
+    time: Res<Time<Real>>,
+    state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut commands: Commands,
+    mut playback: ResMut<ReplayPlayback>,
+    scene_assets: Option<Res<RuntimeSceneAssets>>,
+    objects: Query<(), With<SceneObjectsSpawned>>,
+    free_camera: Option<ResMut<DebugFreeCameraController>>,
+    mut cameras: Query<(&Transform, Option<&mut CameraTour>), With<Camera3d>>,
+    mut stats: ResMut<NetworkStats>,
+    mut incoming: MessageWriter<ServerMessageReceived>,
+) {
+    let now = time.elapsed_secs_f64();
+    let playback = &mut *playback;
+
+    match playback.phase {
+        Phase::Idle => {
+            if *state.get() != AppState::Loading {
+                return;
+            }
+            info!(
+                "Replaying {} messages in {} (ID: {})",
+                playback.entries.len(),
+                playback.world.name(),
+                playback.world as u8
+            );
+            commands.insert_resource(GameplayWorldOverride(playback.world));
+            next_state.set(AppState::Gameplay);
+            playback.phase = Phase::Loading;
+        }
+        Phase::Loading => {
+            // Messages played before the world exists would be lost.
+            let loaded = *state.get() == AppState::Gameplay
+                && scene_assets.is_some_and(|assets| assets.loaded)
+                && !objects.is_empty();
+            if !loaded {
+                return;
+            }
+            if let (Some(mut free_camera), Ok((transform, tour))) =
+                (free_camera, cameras.single_mut())
+            {
+                if let Some(mut tour) = tour {
+                    free_camera.tour_was_active = tour.active;
+                    tour.active = false;
+                }
+                free_camera.take_over(transform);
+            }
+            playback.phase = Phase::Playing { started: now };
+        }
+        Phase::Playing { started } => {
+            let elapsed_ms = ((now - started) * 1000.0) as u64;
+            for entry in playback.take_due(elapsed_ms) {
+                match &entry.packet.payload {
+                    PacketPayload::Server(message) => {
+                        // Playback time stands in for the arrival clock.
+                        let datagram = preferred_channel(&entry.packet.payload).transport()
+                            == TransportKind::Datagram;
+                        if datagram && !stats.accept_datagram(&entry.packet, elapsed_ms) {
+                            continue;
+                        }
+                        incoming.write(ServerMessageReceived(message.clone()));
+                    }
+                    PacketPayload::Client(message) => stats.record_outgoing(message),
+                }
+            }
+            if playback.next == playback.entries.len() {
+                info!("Replay finished after {:.1}s", now - started);
+                playback.phase = Phase::Finished;
+            }
+        }
+        Phase::Finished => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::{ClientMessage, RouteKey, WirePacket};
+
+    fn entry(at_ms: u64, payload: PacketPayload) -> RecordedPacket {
+        RecordedPacket {
+            at_ms,
+            packet: WirePacket::new(0, RouteKey::LOBBY, 0, None, at_ms, payload),
+        }
+    }
+
+    #[test]
+    fn playback_starts_in_the_recorded_map_and_keeps_timing() {
+        let enter = |map_id| {
+            PacketPayload::Server(ServerMessage::EnterMap {
+                entity_id: 1,
+                map_id,
+                x: 0,
+                y: 0,
+            })
+        };
+        let keep_alive = PacketPayload::Client(ClientMessage::KeepAlive { client_time_ms: 0 });
+        let entries = vec![
+            entry(0, keep_alive.clone()),
+            entry(40, enter(WorldMap::Devias as u16)),
+            entry(40, keep_alive.clone()),
+            entry(900, enter(WorldMap::Noria as u16)),
+        ];
+        assert_eq!(replay_world(&entries), WorldMap::Devias);
+        assert_eq!(replay_world(&entries[..1]), WorldMap::Lorencia);
+
+        let mut playback = ReplayPlayback {
+            entries,
+            next: 0,
+            world: WorldMap::Devias,
+            phase: Phase::Idle,
+        };
+        assert_eq!(playback.take_due(39).len(), 1);
+        assert_eq!(playback.take_due(500).len(), 2);
+        assert!(playback.take_due(500).is_empty());
+        assert_eq!(playback.take_due(1_000).len(), 1);
+    }
+
+    #[test]
+    fn replay_paths_follow_their_flag() {
+        assert_eq!(path_arg(REPLAY_FLAG, ["--gpu", "RTX"]), Ok(None));
+        assert_eq!(
+            path_arg(REPLAY_FLAG, ["--replay", "bug.replay"]),
+            Ok(Some(PathBuf::from("bug.replay")))
+        );
+        assert_eq!(
+            path_arg(REPLAY_FLAG, ["--replay"]),
+            Err(ReplayArgsError::MissingPath { flag: REPLAY_FLAG })
+        );
+    }
+}
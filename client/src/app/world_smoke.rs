@@ -0,0 +1,644 @@
+//! World-switch smoke test.
+//!
+//! Cycles through every world the asset pack contains, entering the gameplay
+//! scene for each one with a hidden window. A map passes when its mandatory
+//! scene files exist and load, terrain and objects spawn before the timeout,
+//! nothing panics, and the average FPS over the sample window reaches the
+//! minimum. The per-map report is rewritten after every map, so a crash still
+//! leaves the results gathered so far (with the current map marked as
+//! panicked).
+//!
+//! Options: `--maps <id,id,..>`, `--min-fps <n>`, `--settle-secs <n>`,
+//! `--sample-secs <n>`, `--timeout-secs <n>`, `--report <path>` and
+//! `--visible` to keep the window shown (hidden windows are throttled by some
+//! compositors, which lowers the measured FPS). Without `--maps`,
+//! `MU_SEASON=<n>` leaves out maps that season does not have.
+
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use bevy::asset::UntypedAssetLoadFailedEvent;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy::winit::WinitSettings;
+use common::{Season, WorldMap};
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::AppState;
+use crate::gameplay::scenes::gameplay::GameplayWorldOverride;
+use crate::infra::assets::{asset_path_exists, current_asset_root_path};
+use crate::scene_runtime::state::RuntimeSceneAssets;
+use crate::scene_runtime::systems::{SceneObjectsSpawned, TerrainSpawned};
+use crate::world::debug_season;
+
+const MAPS_FLAG: &str = "--maps";
+const MIN_FPS_FLAG: &str = "--min-fps";
+const SETTLE_FLAG: &str = "--settle-secs";
+const SAMPLE_FLAG: &str = "--sample-secs";
+const TIMEOUT_FLAG: &str = "--timeout-secs";
+const REPORT_FLAG: &str = "--report";
+const VISIBLE_FLAG: &str = "--visible";
+
+/// Scene files every world needs before the gameplay scene can finish loading.
+const MANDATORY_WORLD_FILES: [&str; 4] = [
+    "terrain_config.json",
+    "terrain_height.json",
+    "scene_objects.json",
+    "camera_tour.json",
+];
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum SmokeTestArgsError {
+    #[error("{flag} needs a value")]
+    MissingValue { flag: &'static str },
+    #[error("invalid value '{value}' for {flag}")]
+    InvalidValue { flag: &'static str, value: String },
+    #[error("'{0}' is not a known world id")]
+    UnknownWorld(String),
+    #[error("unknown option '{0}'")]
+    UnknownOption(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmokeTestConfig {
+    /// Worlds to visit; empty means every world found in the asset pack.
+    pub maps: Vec<WorldMap>,
+    pub min_fps: f32,
+    /// Time spent in a loaded world before sampling, so shader compilation
+    /// and streaming do not count against the FPS.
+    pub settle_secs: f32,
+    pub sample_secs: f32,
+    /// Longest a world may take to spawn terrain and objects.
+    pub timeout_secs: f32,
+    pub report_path: PathBuf,
+    pub visible: bool,
+}
+
+impl Default for SmokeTestConfig {
+    fn default() -> Self {
+        Self {
+            maps: Vec::new(),
+            min_fps: 20.0,
+            settle_secs: 2.0,
+            sample_secs: 3.0,
+            timeout_secs: 60.0,
+            report_path: PathBuf::from("world_smoke_report.json"),
+            visible: false,
+        }
+    }
+}
+
+impl SmokeTestConfig {
+    pub fn from_args(
+        args: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<Self, SmokeTestArgsError> {
+        let mut config = Self::default();
+        let mut args = args.into_iter().map(Into::into);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                MAPS_FLAG => {
+                    let value = flag_value(MAPS_FLAG, args.next())?;
+                    config.maps = parse_maps(&value)?;
+                }
+                MIN_FPS_FLAG => config.min_fps = parse_number(MIN_FPS_FLAG, args.next())?,
+                SETTLE_FLAG => config.settle_secs = parse_number(SETTLE_FLAG, args.next())?,
+                SAMPLE_FLAG => config.sample_secs = parse_number(SAMPLE_FLAG, args.next())?,
+                TIMEOUT_FLAG => config.timeout_secs = parse_number(TIMEOUT_FLAG, args.next())?,
+                REPORT_FLAG => {
+                    config.report_path = PathBuf::from(flag_value(REPORT_FLAG, args.next())?);
+                }
+                VISIBLE_FLAG => config.visible = true,
+                _ => return Err(SmokeTestArgsError::UnknownOption(arg)),
+            }
+        }
+        Ok(config)
+    }
+}
+
+fn flag_value(flag: &'static str, value: Option<String>) -> Result<String, SmokeTestArgsError> {
+    value.ok_or(SmokeTestArgsError::MissingValue { flag })
+}
+
+fn parse_number(flag: &'static str, value: Option<String>) -> Result<f32, SmokeTestArgsError> {
+    let value = flag_value(flag, value)?;
+    match value.trim().parse::<f32>() {
+        Ok(number) if number.is_finite() && number >= 0.0 => Ok(number),
+        _ => Err(SmokeTestArgsError::InvalidValue { flag, value }),
+    }
+}
+
+fn parse_maps(value: &str) -> Result<Vec<WorldMap>, SmokeTestArgsError> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| {
+            id.parse::<u8>()
+                .ok()
+                .and_then(WorldMap::from_id)
+                .ok_or_else(|| SmokeTestArgsError::UnknownWorld(id.to_string()))
+        })
+        .collect()
+}
+
+/// Worlds of `season` with a `data/world_N` folder under the asset root, in
+/// id order.
+pub fn discover_worlds(asset_root: &Path, season: Season) -> Vec<WorldMap> {
+    WorldMap::iter()
+        .filter(|map| map.available_in(season))
+        .filter(|map| asset_root.join(world_data_dir(*map)).is_dir())
+        .collect()
+}
+
+fn world_data_dir(map: WorldMap) -> String {
+    format!("data/world_{}", map as u8)
+}
+
+/// Mandatory scene files of `map` that are not in the asset pack.
+pub fn missing_mandatory_assets(map: WorldMap, exists: impl Fn(&str) -> bool) -> Vec<String> {
+    let dir = world_data_dir(map);
+    let mut missing: Vec<String> = MANDATORY_WORLD_FILES
+        .iter()
+        .map(|file| format!("{dir}/{file}"))
+        .filter(|path| !exists(path))
+        .collect();
+
+    let terrain_map = format!("{dir}/terrain_map.json");
+    let legacy_terrain_map = format!("{dir}/enc_terrain_{}.map.json", map as u8);
+    if !exists(&terrain_map) && !exists(&legacy_terrain_map) {
+        missing.push(format!("{terrain_map} (or {legacy_terrain_map})"));
+    }
+    missing
+}
+
+/// Whether a failed load under `map`'s data folder blocks the scene.
+fn is_mandatory_failure(map: WorldMap, failed_path: &str) -> bool {
+    let dir = world_data_dir(map);
+    MANDATORY_WORLD_FILES
+        .iter()
+        .any(|file| failed_path.ends_with(&format!("{dir}/{file}")))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MapOutcome {
+    Passed,
+    MissingAssets,
+    LoadFailed,
+    TimedOut,
+    LowFps,
+    Panicked,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MapReport {
+    pub world_id: u8,
+    pub name: String,
+    pub outcome: MapOutcome,
+    /// Time from entering the scene until terrain and objects spawned.
+    pub load_ms: Option<u64>,
+    pub avg_fps: Option<f32>,
+    /// FPS of the slowest sampled frame.
+    pub worst_fps: Option<f32>,
+    pub missing_assets: Vec<String>,
+    pub failed_assets: Vec<String>,
+    pub panic: Option<String>,
+}
+
+impl MapReport {
+    fn new(map: WorldMap, outcome: MapOutcome) -> Self {
+        Self {
+            world_id: map as u8,
+            name: map.name().to_string(),
+            outcome,
+            load_ms: None,
+            avg_fps: None,
+            worst_fps: None,
+            missing_assets: Vec::new(),
+            failed_assets: Vec::new(),
+            panic: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SmokeReport {
+    pub min_fps: f32,
+    pub maps: Vec<MapReport>,
+    /// World being visited, reported as panicked if the run dies.
+    #[serde(skip)]
+    current: Option<WorldMap>,
+}
+
+impl SmokeReport {
+    pub fn passed(&self) -> bool {
+        self.maps
+            .iter()
+            .all(|report| report.outcome == MapOutcome::Passed)
+    }
+
+    pub fn summary(&self) -> String {
+        let mut lines = Vec::with_capacity(self.maps.len() + 1);
+        for report in &self.maps {
+            let fps = report
+                .avg_fps
+                .map(|fps| format!("{fps:.1} fps"))
+                .unwrap_or_else(|| "-".to_string());
+            let load = report
+                .load_ms
+                .map(|ms| format!("{ms} ms"))
+                .unwrap_or_else(|| "-".to_string());
+            lines.push(format!(
+                "{:>3} {:<24} {:<14} load {:<10} {}",
+                report.world_id,
+                report.name,
+                format!("{:?}", report.outcome),
+                load,
+                fps
+            ));
+        }
+        let passed = self
+            .maps
+            .iter()
+            .filter(|report| report.outcome == MapOutcome::Passed)
+            .count();
+        lines.push(format!("{passed}/{} worlds passed", self.maps.len()));
+        lines.join("\n")
+    }
+
+    fn write(&self, path: &Path) {
+        let result = serde_json::to_vec_pretty(self)
+            .map_err(std::io::Error::other)
+            .and_then(|json| std::fs::write(path, json));
+        if let Err(error) = result {
+            eprintln!(
+                "Failed to write smoke test report '{}': {error}",
+                path.display()
+            );
+        }
+    }
+}
+
+/// Report shared with the panic hook.
+#[derive(Resource, Clone, Default)]
+pub struct SharedSmokeReport(pub Arc<Mutex<SmokeReport>>);
+
+impl SharedSmokeReport {
+    /// Marks the world being visited as panicked and flushes the report
+    /// before the default hook runs.
+    pub fn install_panic_hook(&self, report_path: PathBuf) {
+        let shared = self.0.clone();
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if let Ok(mut report) = shared.lock() {
+                if let Some(map) = report.current.take() {
+                    let mut entry = MapReport::new(map, MapOutcome::Panicked);
+                    entry.panic = Some(info.to_string());
+                    report.maps.push(entry);
+                }
+                report.write(&report_path);
+            }
+            default_hook(info);
+        }));
+    }
+}
+
+enum Phase {
+    /// Waiting in `AppState::Loading` for the previous world to unload.
+    Idle,
+    Loading {
+        started: f64,
+    },
+    Settling {
+        started: f64,
+        load_ms: u64,
+    },
+    Sampling {
+        started: f64,
+        load_ms: u64,
+        frame_fps: Vec<f32>,
+    },
+}
+
+#[derive(Resource)]
+struct SmokeTestRun {
+    config: SmokeTestConfig,
+    queue: std::collections::VecDeque<WorldMap>,
+    current: Option<WorldMap>,
+    phase: Phase,
+    failed_assets: Vec<String>,
+}
+
+pub struct WorldSmokeTestPlugin {
+    pub config: SmokeTestConfig,
+    pub worlds: Vec<WorldMap>,
+    pub report: SharedSmokeReport,
+}
+
+impl Plugin for WorldSmokeTestPlugin {
+    fn build(&self, app: &mut App) {
+        if let Ok(mut report) = self.report.0.lock() {
+            report.min_fps = self.config.min_fps;
+        }
+        app.insert_resource(WinitSettings::continuous())
+            .insert_resource(self.report.clone())
+            .insert_resource(SmokeTestRun {
+                config: self.config.clone(),
+                queue: self.worlds.iter().copied().collect(),
+                current: None,
+                phase: Phase::Idle,
+                failed_assets: Vec::new(),
+            })
+            .add_systems(Update, (record_asset_failures, drive_smoke_test).chain());
+        if !self.config.visible {
+            app.add_systems(Startup, hide_primary_window);
+        }
+    }
+}
+
+fn hide_primary_window(mut windows: Query<&mut Window, With<PrimaryWindow>>) {
+    for mut window in &mut windows {
+        window.visible = false;
+    }
+}
+
+fn record_asset_failures(
+    mut failures: MessageReader<UntypedAssetLoadFailedEvent>,
+    mut run: ResMut<SmokeTestRun>,
+) {
+    for failure in failures.read() {
+        warn!("Asset failed to load: {} ({})", failure.path, failure.error);
+        if run.current.is_some() {
+            run.failed_assets.push(failure.path.to_string());
+        }
+    }
+}
+
+fn drive_smoke_test(
+    time: Res<Time<Real>>,
+    state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut commands: Commands,
+    mut run: ResMut<SmokeTestRun>,
+    shared: Res<SharedSmokeReport>,
+    scene_assets: Option<Res<RuntimeSceneAssets>>,
+    terrain: Query<(), With<TerrainSpawned>>,
+    objects: Query<(), With<SceneObjectsSpawned>>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let now = time.elapsed_secs_f64();
+    let run = &mut *run;
+
+    let Some(map) = run.current else {
+        if *state.get() != AppState::Loading {
+            return;
+        }
+        let Some(map) = run.queue.pop_front() else {
+            finish_run(&shared, &run.config, &mut exit);
+            return;
+        };
+        start_world(map, run, &shared, &mut commands, &mut next_state, now);
+        return;
+    };
+
+    if *state.get() != AppState::Gameplay {
+        return;
+    }
+
+    if run
+        .failed_assets
+        .iter()
+        .any(|path| is_mandatory_failure(map, path))
+    {
+        let mut report = MapReport::new(map, MapOutcome::LoadFailed);
+        report.failed_assets = run.failed_assets.clone();
+        finish_world(report, run, &shared, &mut next_state);
+        return;
+    }
+
+    let report = match &mut run.phase {
+        Phase::Idle => None,
+        Phase::Loading { started } => {
+            let loaded = scene_assets.is_some_and(|assets| assets.loaded)
+                && !terrain.is_empty()
+                && !objects.is_empty();
+            if loaded {
+                let load_ms = ((now - *started) * 1000.0) as u64;
+                run.phase = Phase::Settling {
+                    started: now,
+                    load_ms,
+                };
+                None
+            } else if now - *started > f64::from(run.config.timeout_secs) {
+                Some(MapReport::new(map, MapOutcome::TimedOut))
+            } else {
+                None
+            }
+        }
+        Phase::Settling { started, load_ms } => {
+            if now - *started >= f64::from(run.config.settle_secs) {
+                run.phase = Phase::Sampling {
+                    started: now,
+                    load_ms: *load_ms,
+                    frame_fps: Vec::new(),
+                };
+            }
+            None
+        }
+        Phase::Sampling {
+            started,
+            load_ms,
+            frame_fps,
+        } => {
+            let delta = time.delta_secs();
+            if delta > 0.0 {
+                frame_fps.push(1.0 / delta);
+            }
+            if now - *started < f64::from(run.config.sample_secs) {
+                None
+            } else {
+                let avg_fps = average_fps(frame_fps);
+                let outcome = if avg_fps.is_some_and(|fps| fps >= run.config.min_fps) {
+                    MapOutcome::Passed
+                } else {
+                    MapOutcome::LowFps
+                };
+                let mut report = MapReport::new(map, outcome);
+                report.load_ms = Some(*load_ms);
+                report.avg_fps = avg_fps;
+                report.worst_fps = frame_fps.iter().copied().reduce(f32::min);
+                Some(report)
+            }
+        }
+    };
+
+    if let Some(mut report) = report {
+        report.failed_assets = run.failed_assets.clone();
+        finish_world(report, run, &shared, &mut next_state);
+    }
+}
+
+/// Frames per second over the whole sample, not the mean of per-frame rates.
+fn average_fps(frame_fps: &[f32]) -> Option<f32> {
+    let total_secs: f32 = frame_fps.iter().map(|fps| 1.0 / fps).sum();
+    (total_secs > 0.0).then(|| frame_fps.len() as f32 / total_secs)
+}
+
+fn start_world(
+    map: WorldMap,
+    run: &mut SmokeTestRun,
+    shared: &SharedSmokeReport,
+    commands: &mut Commands,
+    next_state: &mut NextState<AppState>,
+    now: f64,
+) {
+    let missing = missing_mandatory_assets(map, asset_path_exists);
+    if !missing.is_empty() {
+        let mut report = MapReport::new(map, MapOutcome::MissingAssets);
+        report.missing_assets = missing;
+        record_report(report, shared, &run.config);
+        return;
+    }
+
+    info!("Smoke testing {} (ID: {})", map.name(), map as u8);
+    if let Ok(mut report) = shared.0.lock() {
+        report.current = Some(map);
+    }
+    run.current = Some(map);
+    run.phase = Phase::Loading { started: now };
+    run.failed_assets.clear();
+    commands.insert_resource(GameplayWorldOverride(map));
+    next_state.set(AppState::Gameplay);
+}
+
+fn finish_world(
+    report: MapReport,
+    run: &mut SmokeTestRun,
+    shared: &SharedSmokeReport,
+    next_state: &mut NextState<AppState>,
+) {
+    info!(
+        "Smoke test of {} finished: {:?}",
+        report.name, report.outcome
+    );
+    run.current = None;
+    run.phase = Phase::Idle;
+    record_report(report, shared, &run.config);
+    next_state.set(AppState::Loading);
+}
+
+fn record_report(report: MapReport, shared: &SharedSmokeReport, config: &SmokeTestConfig) {
+    let Ok(mut shared) = shared.0.lock() else {
+        return;
+    };
+    shared.current = None;
+    shared.maps.push(report);
+    shared.write(&config.report_path);
+}
+
+fn finish_run(
+    shared: &SharedSmokeReport,
+    config: &SmokeTestConfig,
+    exit: &mut MessageWriter<AppExit>,
+) {
+    let Ok(report) = shared.0.lock() else {
+        exit.write(AppExit::error());
+        return;
+    };
+    report.write(&config.report_path);
+    println!("{}", report.summary());
+    println!("Report written to {}", config.report_path.display());
+    exit.write(if report.passed() && !report.maps.is_empty() {
+        AppExit::Success
+    } else {
+        AppExit::error()
+    });
+}
+
+/// Worlds to visit: the requested ones, or every world under the asset root
+/// that `MU_SEASON` has.
+pub fn worlds_to_visit(config: &SmokeTestConfig) -> Vec<WorldMap> {
+    if config.maps.is_empty() {
+        discover_worlds(&current_asset_root_path(), debug_season())
+    } else {
+        config.maps.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_options() {
+        let config = SmokeTestConfig::from_args([
+            "--maps",
+            "1, 3",
+            "--min-fps",
+            "30",
+            "--report",
+            "out.json",
+            "--visible",
+        ])
+        .unwrap();
+
+        assert_eq!(config.maps, vec![WorldMap::Lorencia, WorldMap::Devias]);
+        assert_eq!(config.min_fps, 30.0);
+        assert_eq!(config.report_path, PathBuf::from("out.json"));
+        assert!(config.visible);
+        assert_eq!(
+            SmokeTestConfig::from_args(["--min-fps"]),
+            Err(SmokeTestArgsError::MissingValue { flag: "--min-fps" })
+        );
+        assert_eq!(
+            SmokeTestConfig::from_args(["--sample-secs", "-1"]),
+            Err(SmokeTestArgsError::InvalidValue {
+                flag: "--sample-secs",
+                value: "-1".to_string()
+            })
+        );
+        assert!(matches!(
+            SmokeTestConfig::from_args(["--fast"]),
+            Err(SmokeTestArgsError::UnknownOption(_))
+        ));
+    }
+
+    #[test]
+    fn either_terrain_map_satisfies_the_mandatory_check() {
+        let legacy_only = |path: &str| !path.ends_with("terrain_map.json");
+        assert!(missing_mandatory_assets(WorldMap::Lorencia, legacy_only).is_empty());
+
+        let missing = missing_mandatory_assets(WorldMap::Lorencia, |path: &str| {
+            !path.contains("terrain_map")
+                && !path.contains(".map.json")
+                && !path.ends_with("camera_tour.json")
+        });
+        assert_eq!(missing.len(), 2);
+        assert_eq!(missing[0], "data/world_1/camera_tour.json");
+    }
+
+    #[test]
+    fn only_the_current_worlds_mandatory_files_fail_the_map() {
+        assert!(is_mandatory_failure(
+            WorldMap::Lorencia,
+            "data/world_1/scene_objects.json"
+        ));
+        assert!(!is_mandatory_failure(
+            WorldMap::Lorencia,
+            "data/world_1/map_vfx.json"
+        ));
+        assert!(!is_mandatory_failure(
+            WorldMap::Devias,
+            "data/world_1/scene_objects.json"
+        ));
+    }
+
+    #[test]
+    fn average_fps_weights_by_frame_time() {
+        assert_eq!(average_fps(&[]), None);
+        let fps = average_fps(&[60.0, 20.0]).unwrap();
+        assert!((fps - 30.0).abs() < 0.01);
+    }
+}
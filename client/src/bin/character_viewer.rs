@@ -11,7 +11,6 @@ use bevy::animation::{AnimatedBy, AnimationTargetId};
 use bevy::asset::RenderAssetUsages;
 #[cfg(feature = "solari")]
 use bevy::camera::CameraMainTextureUsages;
-use bevy::core_pipeline::tonemapping::Tonemapping;
 use bevy::gizmos::config::{DefaultGizmoConfigGroup, GizmoConfigStore};
 use bevy::gltf::Gltf;
 use bevy::image::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor};
@@ -31,11 +30,19 @@ use bevy::render::render_resource::TextureUsages;
 use bevy::solari::prelude::{RaytracingMesh3d, SolariLighting};
 use bevy_egui::input::EguiWantsInput;
 use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+#[cfg(feature = "skill-capture")]
+#[path = "character_viewer/capture.rs"]
+mod character_viewer_capture;
 #[path = "character_viewer/dw_magic.rs"]
 mod character_viewer_dw_magic;
 #[path = "character_viewer/skills.rs"]
 mod character_viewer_skills;
 use character_viewer_skills::skills_for_class;
+use common::BuffEffect;
+use common::worldscale::{
+    CAMERA_DISTANCE, CAMERA_LOOK_HEIGHT, CAMERA_PITCH_DEG, CAMERA_YAW_DEG, HEIGHT_MULTIPLIER,
+    MAP_WORLD_SIZE, TERRAIN_SIZE, TILE_WORLD_SIZE, ZOOM_MAX, ZOOM_MIN, ZOOM_STEP,
+};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::time::Duration;
 
@@ -45,14 +52,16 @@ use client::composition::character_viewer_runtime::configure_character_viewer_ap
 use client::grid_overlay::{
     GRID_OVERLAY_COLOR, GridOverlayConfig, build_grid_segments, grid_line_count, segment_transform,
 };
-use client::infra::assets::{resolve_asset_path, set_use_remaster_assets};
+use client::infra::assets::{AssetVariant, resolve_asset_group, resolve_asset_path, set_asset_variant};
 use client::infra::persistence::settings_store;
 use client::scene_runtime::systems::{
-    apply_death_stab_vfx_materials, ensure_death_stab_animation_players,
-    initialize_lightning_hurt_effects, spawn_death_stab_vfx_tracked,
-    spawn_lightning_overlay_camera, update_death_stab_energy_particles,
-    update_death_stab_lightning_arcs, update_death_stab_spike_particles,
-    update_death_stab_timeline, update_lightning_hurt_effects, update_skill_vfx_auto_lifetimes,
+    CameraEffectsConfig, CameraShakeOffset, EARTHQUAKE_IMPACT, LocalPlayerHealth,
+    StatusEffectVisuals, apply_death_stab_vfx_materials, approach_zoom,
+    ensure_death_stab_animation_players, initialize_lightning_hurt_effects, queue_camera_impact,
+    spawn_death_stab_vfx_tracked, spawn_lightning_overlay_camera,
+    update_death_stab_energy_particles, update_death_stab_lightning_arcs,
+    update_death_stab_spike_particles, update_death_stab_timeline, update_lightning_hurt_effects,
+    update_skill_vfx_auto_lifetimes,
 };
 
 // ============================================================================
@@ -63,80 +72,7 @@ mod character {
     pub mod types {
         use bevy::prelude::*;
 
-        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-        pub enum CharacterClass {
-            DarkKnight,
-            DarkWizard,
-            FairyElf,
-            MagicGladiator,
-            DarkLord,
-            Summoner,
-            RageFighter,
-        }
-
-        impl CharacterClass {
-            pub const ALL: &'static [CharacterClass] = &[
-                CharacterClass::DarkKnight,
-                CharacterClass::DarkWizard,
-                CharacterClass::FairyElf,
-                CharacterClass::MagicGladiator,
-                CharacterClass::DarkLord,
-                CharacterClass::Summoner,
-                CharacterClass::RageFighter,
-            ];
-
-            pub fn name(&self) -> &'static str {
-                match self {
-                    CharacterClass::DarkKnight => "DarkKnight",
-                    CharacterClass::DarkWizard => "DarkWizard",
-                    CharacterClass::FairyElf => "FairyElf",
-                    CharacterClass::MagicGladiator => "MagicGladiator",
-                    CharacterClass::DarkLord => "DarkLord",
-                    CharacterClass::Summoner => "Summoner",
-                    CharacterClass::RageFighter => "RageFighter",
-                }
-            }
-
-            pub fn class_id(&self) -> u8 {
-                match self {
-                    CharacterClass::DarkWizard => 1,
-                    CharacterClass::DarkKnight => 2,
-                    CharacterClass::FairyElf => 3,
-                    CharacterClass::MagicGladiator => 4,
-                    CharacterClass::DarkLord => 5,
-                    CharacterClass::Summoner => 6,
-                    CharacterClass::RageFighter => 7,
-                }
-            }
-
-            pub fn body_type(&self) -> BodyType {
-                match self {
-                    CharacterClass::DarkKnight
-                    | CharacterClass::DarkWizard
-                    | CharacterClass::MagicGladiator
-                    | CharacterClass::DarkLord => BodyType::Male,
-                    CharacterClass::FairyElf | CharacterClass::Summoner => BodyType::Elf,
-                    CharacterClass::RageFighter => BodyType::Monk,
-                }
-            }
-        }
-
-        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-        pub enum BodyType {
-            Male,
-            Elf,
-            Monk,
-        }
-
-        impl BodyType {
-            pub fn slug(&self) -> &'static str {
-                match self {
-                    BodyType::Male => "male",
-                    BodyType::Elf => "elf",
-                    BodyType::Monk => "monk",
-                }
-            }
-        }
+        pub use common::{BodyType, CharacterClass};
 
         #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
         pub enum BodySlot {
@@ -946,16 +882,13 @@ use character::types::*;
 const DEFAULT_PLAYBACK_SPEED: f32 = 0.16;
 
 /// MU terrain grid cell size (same as terrain scale in scene_loader).
-const GRID_CELL_SIZE: f32 = 100.0;
+const GRID_CELL_SIZE: f32 = TILE_WORLD_SIZE;
 
 /// Number of heightmap cells in each direction.
-const GROUND_CELLS: usize = 256;
+const GROUND_CELLS: usize = TERRAIN_SIZE as usize;
 
 /// Ground plane total size (256 grid cells).
-const GROUND_SIZE: f32 = GROUND_CELLS as f32 * GRID_CELL_SIZE;
-
-/// Height multiplier from terrain config (world_1 default).
-const HEIGHT_MULTIPLIER: f32 = 1.5;
+const GROUND_SIZE: f32 = MAP_WORLD_SIZE;
 /// Ground UV tiling: one texture tile spans 2x2 terrain cells.
 const GROUND_UV_CELLS_PER_TILE: f32 = 2.0;
 
@@ -964,15 +897,11 @@ const GRID_VISIBLE_HALF_CELLS: i32 = 25;
 const GRID_LINE_THICKNESS: f32 = 1.0;
 const GRID_Y_OFFSET: f32 = 1.0;
 
-/// MU Camera parameters (from MuClient5.2 ZzzScene.cpp).
-const MU_CAMERA_PITCH_DEG: f32 = 48.5;
-const MU_CAMERA_YAW_DEG: f32 = -45.0;
-const MU_CAMERA_DISTANCE: f32 = 1000.0;
-const MU_CAMERA_LOOK_HEIGHT: f32 = 80.0;
+/// How long the Ice/Poison preview buttons keep a status on.
+const STATUS_PREVIEW_SECS: f32 = 8.0;
 
-const ZOOM_MIN: f32 = 300.0;
-const ZOOM_MAX: f32 = 2500.0;
-const ZOOM_SPEED: f32 = 100.0;
+/// Health pool behind the "Preview HP" slider that drives the low-health vignette.
+const PREVIEW_MAX_HP: u16 = 100;
 
 /// Camera rotation sensitivity (degrees per pixel of mouse movement).
 const CAMERA_ROTATION_SENSITIVITY: f32 = 0.3;
@@ -1187,19 +1116,27 @@ struct ViewerState {
     status: String,
     selected_set_index: usize,
     available_sets: Vec<EquipmentSet>,
-    use_remaster: bool,
+    asset_variant: AssetVariant,
     #[cfg(feature = "solari")]
     use_raytracing: bool,
     #[cfg(feature = "solari")]
     pending_rt_change: bool,
 }
 
+/// Position of `class` in the class picker.
+fn class_index(class: CharacterClass) -> usize {
+    CharacterClass::ALL
+        .iter()
+        .position(|candidate| *candidate == class)
+        .unwrap_or(0)
+}
+
 impl Default for ViewerState {
     fn default() -> Self {
-        let initial_class = CharacterClass::ALL[0];
+        let initial_class = CharacterClass::DarkKnight;
         let body_type = initial_class.body_type();
         Self {
-            selected_class_index: 0,
+            selected_class_index: class_index(initial_class),
             selected_animation: 1, // StopMale (idle)
             playback_speed: DEFAULT_PLAYBACK_SPEED,
             pending_animation_repeat: None,
@@ -1220,7 +1157,7 @@ impl Default for ViewerState {
             status: "Loading player.glb...".to_string(),
             selected_set_index: 0,
             available_sets: EquipmentSet::available_for(body_type),
-            use_remaster: true,
+            asset_variant: AssetVariant::Remaster,
             #[cfg(feature = "solari")]
             use_raytracing: true,
             #[cfg(feature = "solari")]
@@ -1465,8 +1402,14 @@ struct MuCamera {
     pitch_deg: f32,
     yaw_deg: f32,
     distance: f32,
+    /// Distance the scroll wheel asked for; `distance` eases towards it.
+    target_distance: f32,
 }
 
+/// Present while the viewer panels must stay off screen (skill capture runs).
+#[derive(Resource)]
+struct ViewerUiHidden;
+
 /// Tracks the ground texture handle for deferred sampler configuration.
 #[derive(Resource)]
 struct GroundTextureState {
@@ -1480,7 +1423,7 @@ struct GroundTextureState {
 
 fn main() {
     let startup_settings = settings_store::load();
-    let use_remaster_assets = startup_settings.graphics.use_remaster_assets;
+    let asset_variant = startup_settings.graphics.asset_variant;
 
     // Load heightmap synchronously at startup (small JSON file).
     let asset_root = asset_root_path();
@@ -1499,23 +1442,29 @@ fn main() {
 
     let mut app = App::new();
     let mut viewer_state = ViewerState::default();
-    viewer_state.use_remaster = use_remaster_assets;
+    viewer_state.asset_variant = asset_variant;
 
     app.insert_resource(GlobalAmbientLight {
         color: Color::WHITE,
         brightness: 250.0,
         affects_lightmapped_meshes: true,
     })
+    .insert_resource(CameraEffectsConfig::from(&startup_settings.camera))
+    .insert_resource(LocalPlayerHealth {
+        current: PREVIEW_MAX_HP,
+        max: PREVIEW_MAX_HP,
+    })
     .insert_resource(viewer_state)
     .insert_resource(SkillVfxPreloadCache::default())
     .insert_resource(heightmap)
     .insert_resource(DirectionalLightShadowMap { size: 4096 });
-    configure_character_viewer_app(&mut app, asset_root, use_remaster_assets);
+    configure_character_viewer_app(&mut app, asset_root, asset_variant);
 
     app.add_systems(Startup, (setup_viewer, configure_gizmos, spawn_lightning_overlay_camera))
         .add_systems(
             EguiPrimaryContextPass,
-            (draw_character_viewer_ui, draw_bottom_info_bar),
+            (draw_character_viewer_ui, draw_bottom_info_bar)
+                .run_if(not(resource_exists::<ViewerUiHidden>)),
         )
         .add_systems(
             Update,
@@ -1617,12 +1566,17 @@ fn main() {
     #[cfg(feature = "solari")]
     app.add_systems(Update, toggle_raytracing);
 
+    #[cfg(feature = "skill-capture")]
+    if let Some(output_dir) = character_viewer_capture::output_dir_from_args() {
+        character_viewer_capture::install(&mut app, output_dir);
+    }
+
     app.run();
 }
 
 pub fn run_skill43_demo() {
     let startup_settings = settings_store::load();
-    let use_remaster_assets = startup_settings.graphics.use_remaster_assets;
+    let asset_variant = startup_settings.graphics.asset_variant;
 
     // Reuse the existing viewer bootstrap logic so animation timing and VFX systems
     // stay aligned with the full viewer.
@@ -1644,8 +1598,8 @@ pub fn run_skill43_demo() {
     let mut app = App::new();
 
     let mut viewer_state = ViewerState::default();
-    viewer_state.use_remaster = use_remaster_assets;
-    viewer_state.selected_class_index = 0;
+    viewer_state.asset_variant = asset_variant;
+    viewer_state.selected_class_index = class_index(CharacterClass::DarkKnight);
     viewer_state.available_skills = skills_for_class(CharacterClass::DarkKnight).to_vec();
     viewer_state.selected_skill_index = viewer_state
         .available_skills
@@ -1664,7 +1618,7 @@ pub fn run_skill43_demo() {
     .insert_resource(DirectionalLightShadowMap { size: 4096 })
     .insert_resource(Skill43AutoCastTimer::default());
 
-    configure_character_viewer_app(&mut app, asset_root, use_remaster_assets);
+    configure_character_viewer_app(&mut app, asset_root, asset_variant);
 
     app.add_systems(
         Startup,
@@ -1807,16 +1761,16 @@ fn setup_viewer(
 
     // MU-style follow camera with Gaussian shadow filtering
     let mu_cam = MuCamera {
-        pitch_deg: MU_CAMERA_PITCH_DEG,
-        yaw_deg: MU_CAMERA_YAW_DEG,
-        distance: MU_CAMERA_DISTANCE,
+        pitch_deg: CAMERA_PITCH_DEG,
+        yaw_deg: CAMERA_YAW_DEG,
+        distance: CAMERA_DISTANCE,
+        target_distance: CAMERA_DISTANCE,
     };
     let cam_transform = compute_mu_camera_transform(&mu_cam, Vec3::ZERO);
 
     let mut _camera = commands.spawn((
         Camera3dBundle {
             transform: cam_transform,
-            tonemapping: Tonemapping::ReinhardLuminance,
             projection: Projection::Perspective(PerspectiveProjection {
                 near: 10.0,
                 far: 50_000.0,
@@ -1825,6 +1779,7 @@ fn setup_viewer(
             ..default()
         },
         mu_cam,
+        CameraShakeOffset::default(),
         ShadowFilteringMethod::Gaussian,
     ));
 
@@ -2083,7 +2038,8 @@ fn handle_scroll_zoom(
     }
 
     for mut mu_cam in &mut cameras {
-        mu_cam.distance = (mu_cam.distance - delta * ZOOM_SPEED).clamp(ZOOM_MIN, ZOOM_MAX);
+        mu_cam.target_distance =
+            (mu_cam.target_distance - delta * ZOOM_STEP).clamp(ZOOM_MIN, ZOOM_MAX);
     }
 }
 
@@ -2178,23 +2134,31 @@ fn compute_mu_camera_transform(cam: &MuCamera, char_pos: Vec3) -> Transform {
         horizontal * yaw_rad.cos(),
     );
 
-    let look_at = Vec3::new(char_pos.x, char_pos.y + MU_CAMERA_LOOK_HEIGHT, char_pos.z);
+    let look_at = Vec3::new(char_pos.x, char_pos.y + CAMERA_LOOK_HEIGHT, char_pos.z);
     let eye = look_at + offset;
 
     Transform::from_translation(eye).looking_at(look_at, Vec3::Y)
 }
 
 fn update_mu_camera(
+    time: Res<Time>,
+    effects: Res<CameraEffectsConfig>,
     characters: Query<&Transform, With<CharacterRoot>>,
-    mut cameras: Query<(&mut Transform, &MuCamera), Without<CharacterRoot>>,
+    mut cameras: Query<(&mut Transform, &mut MuCamera), Without<CharacterRoot>>,
 ) {
     let char_pos = characters
         .single()
         .map(|t| t.translation)
         .unwrap_or(Vec3::ZERO);
 
-    for (mut cam_transform, mu_cam) in &mut cameras {
-        *cam_transform = compute_mu_camera_transform(mu_cam, char_pos);
+    for (mut cam_transform, mut mu_cam) in &mut cameras {
+        mu_cam.distance = approach_zoom(
+            mu_cam.distance,
+            mu_cam.target_distance,
+            time.delta_secs(),
+            effects.smooth_zoom,
+        );
+        *cam_transform = compute_mu_camera_transform(&mu_cam, char_pos);
     }
 }
 
@@ -2207,10 +2171,13 @@ fn draw_character_viewer_ui(
     mut viewer: ResMut<ViewerState>,
     keys: Res<ButtonInput<KeyCode>>,
     library: Option<Res<PlayerAnimLib>>,
+    mut camera_effects: ResMut<CameraEffectsConfig>,
+    mut preview_health: ResMut<LocalPlayerHealth>,
+    mut status_previews: Query<&mut StatusEffectVisuals, With<CharacterRoot>>,
 ) {
     if keys.just_pressed(KeyCode::F10) {
-        viewer.use_remaster = !viewer.use_remaster;
-        set_use_remaster_assets(viewer.use_remaster);
+        viewer.asset_variant = viewer.asset_variant.toggled();
+        set_asset_variant(viewer.asset_variant);
         viewer.pending_class_change = true;
     }
 
@@ -2266,10 +2233,15 @@ fn draw_character_viewer_ui(
 
             // Remaster toggle
             ui.separator();
-            let prev_remaster = viewer.use_remaster;
-            ui.checkbox(&mut viewer.use_remaster, "Use Remaster models");
-            if viewer.use_remaster != prev_remaster {
-                set_use_remaster_assets(viewer.use_remaster);
+            let prev_variant = viewer.asset_variant;
+            ui.horizontal(|ui| {
+                ui.label("Models:");
+                for variant in AssetVariant::ALL {
+                    ui.selectable_value(&mut viewer.asset_variant, variant, format!("{variant:?}"));
+                }
+            });
+            if viewer.asset_variant != prev_variant {
+                set_asset_variant(viewer.asset_variant);
                 viewer.pending_class_change = true; // Respawn with new paths
             }
 
@@ -2288,6 +2260,41 @@ fn draw_character_viewer_ui(
                 viewer.pending_class_change = true;
             }
 
+            ui.separator();
+            ui.collapsing("Camera Effects", |ui| {
+                ui.checkbox(&mut camera_effects.smooth_zoom, "Smooth zoom");
+                ui.add(
+                    egui::Slider::new(&mut camera_effects.shake_scale, 0.0..=2.0)
+                        .text("Impact shake"),
+                );
+                ui.checkbox(&mut camera_effects.low_health_vignette, "Low-health vignette");
+                let max_hp = preview_health.max;
+                ui.add(
+                    egui::Slider::new(&mut preview_health.current, 0..=max_hp).text("Preview HP"),
+                );
+            });
+
+            ui.collapsing("Status Effects", |ui| {
+                ui.horizontal(|ui| {
+                    let mut applied = None;
+                    if ui.button("Ice").clicked() {
+                        applied = Some(BuffEffect::Ice);
+                    }
+                    if ui.button("Poison").clicked() {
+                        applied = Some(BuffEffect::Poison);
+                    }
+                    let cleared = ui.button("Clear").clicked();
+                    for mut visuals in &mut status_previews {
+                        if let Some(kind) = applied {
+                            visuals.apply(kind, STATUS_PREVIEW_SECS);
+                        }
+                        if cleared {
+                            visuals.clear();
+                        }
+                    }
+                });
+            });
+
             ui.separator();
 
             // Skill selector
@@ -2401,20 +2408,6 @@ fn draw_bottom_info_bar(
         });
 }
 
-// ============================================================================
-// Remaster path resolution
-// ============================================================================
-
-/// Given a standard asset path like `data/player/foo.glb`, return the remaster
-/// version `remaster/data/player/foo.glb` if it exists on disk, otherwise the original.
-fn resolve_asset_path_for_mode(path: &str, use_remaster: bool) -> String {
-    client::infra::assets::resolve_asset_path_with_mode(path, use_remaster)
-}
-
-fn remaster_asset_exists(path: &str) -> bool {
-    client::infra::assets::remaster_variant_exists(path)
-}
-
 // ============================================================================
 // Class change -> despawn/respawn character
 // ============================================================================
@@ -2449,13 +2442,14 @@ fn handle_class_change(
 
     // Keep body parts + animation skeleton on the same asset variant.
     // Mixing remaster body parts with base `player.glb` causes bone mismatch/distortion.
-    let requested_remaster = viewer.use_remaster;
-    let remaster_ready = requested_remaster
-        && remaster_asset_exists("data/player/player.glb")
-        && slots
-            .iter()
-            .all(|slot| remaster_asset_exists(&equipment_set.glb_path(*slot, body_type, class)));
-    let use_remaster_assets = requested_remaster && remaster_ready;
+    let requested_variant = viewer.asset_variant;
+    let mut group_paths: Vec<String> = slots
+        .iter()
+        .map(|slot| equipment_set.glb_path(*slot, body_type, class))
+        .collect();
+    group_paths.push("data/player/player.glb".to_string());
+    let (loaded_variant, mut glb_paths) = resolve_asset_group(&group_paths, requested_variant);
+    let skeleton_glb = glb_paths.pop().unwrap_or_default();
 
     // Set default idle animation for the class
     viewer.selected_animation = idle_action_for_class(class);
@@ -2483,6 +2477,7 @@ fn handle_class_change(
                 ..default()
             },
             CharacterRoot,
+            StatusEffectVisuals::default(),
             CharacterController {
                 class,
                 state: CharacterState::Idle,
@@ -2494,9 +2489,7 @@ fn handle_class_change(
         ))
         .id();
 
-    for &slot in slots {
-        let base_path = equipment_set.glb_path(slot, body_type, class);
-        let glb_path = resolve_asset_path_for_mode(&base_path, use_remaster_assets);
+    for (&slot, glb_path) in slots.iter().zip(glb_paths) {
         let scene_path = format!("{glb_path}#Scene0");
         let scene_handle: Handle<Scene> = asset_server.load(scene_path);
 
@@ -2514,7 +2507,6 @@ fn handle_class_change(
     }
 
     // Spawn the animated skeleton (player.glb has animations, 0 meshes).
-    let skeleton_glb = resolve_asset_path_for_mode("data/player/player.glb", use_remaster_assets);
     let skeleton_scene: Handle<Scene> = asset_server.load(format!("{}#Scene0", skeleton_glb));
     let skeleton = commands
         .spawn((
@@ -2528,7 +2520,7 @@ fn handle_class_change(
     commands.entity(root).add_child(skeleton);
 
     viewer.character_entity = Some(root);
-    if requested_remaster && !use_remaster_assets {
+    if loaded_variant != requested_variant {
         viewer.status = format!(
             "Spawned {} ({} body, {}) [Base assets: remaster pack incomplete]",
             class.name(),
@@ -2536,7 +2528,7 @@ fn handle_class_change(
             equipment_set.display_name(),
         );
     } else {
-        let remaster_tag = if use_remaster_assets {
+        let remaster_tag = if loaded_variant == AssetVariant::Remaster {
             " [Remaster]"
         } else {
             ""
@@ -3884,6 +3876,7 @@ fn spawn_skill_vfx_for_entry(
             );
         }
         SkillVfxProfile::Earthshake => {
+            queue_camera_impact(commands, EARTHQUAKE_IMPACT);
             spawn_skill_vfx_scene(
                 commands,
                 asset_server,
@@ -6197,7 +6190,7 @@ fn update_skill_impact_bursts(
         if burst.elapsed >= burst.delay && !burst.fired {
             burst.fired = true;
             let position = transform.translation();
-            let mut rng = rand::thread_rng();
+            let mut rng = client::scene_runtime::vfx_rng::vfx_rng();
 
             for _ in 0..burst.burst_count {
                 let cfg = &burst.emitter_config;
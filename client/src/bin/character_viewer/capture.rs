@@ -0,0 +1,367 @@
+//! Skill cast capture for VFX regression review (`--features skill-capture`).
+//!
+//! `character_viewer --capture-skills <dir>` casts every skill of every class
+//! from a fresh spawn with the default camera, a fixed time step and a fixed
+//! VFX seed, and saves each cast as a PNG sequence. When `ffmpeg` is on `PATH`
+//! the sequences are also encoded to `.mp4` and animated `.webp`. A
+//! `manifest.json` lists every clip so two capture runs can be diffed.
+//!
+//! The viewer still needs a window to render into; on headless machines run it
+//! under a virtual display, e.g. `xvfb-run -s "-screen 0 1440x900x24"`.
+
+use super::character::types::CharacterClass;
+use super::character_viewer_skills::skills_for_class;
+use super::{
+    CharacterRoot, MU_CAMERA_DISTANCE, MU_CAMERA_PITCH_DEG, MU_CAMERA_YAW_DEG, MuCamera,
+    PlayerAnimLib, SkillEntry, SkillVfx, SkillVfxPreloadCache, ViewerState, ViewerUiHidden,
+};
+use bevy::ecs::schedule::ExecutorKind;
+use bevy::prelude::*;
+use bevy::render::view::screenshot::{Screenshot, save_to_disk};
+use bevy::time::TimeUpdateStrategy;
+use client::scene_runtime::vfx_rng::seed_vfx_rng;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+const CAPTURE_FLAG: &str = "--capture-skills";
+const CAPTURE_SEED: u64 = 0x4d55_5346_5843;
+const CAPTURE_FPS: u32 = 30;
+const CLIP_FRAMES: u32 = 90;
+/// Frames rendered after the character is ready and before the cast starts.
+const SETTLE_FRAMES: u32 = 15;
+/// Give up waiting for assets (and capture anyway) after this many frames.
+const MAX_WAIT_FRAMES: u32 = 1_800;
+/// Frames kept alive after the last clip so pending screenshots reach disk.
+const FLUSH_FRAMES: u32 = 10;
+
+/// Output directory passed with `--capture-skills <dir>`, if any.
+pub(super) fn output_dir_from_args() -> Option<PathBuf> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == CAPTURE_FLAG {
+            return Some(
+                args.next()
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| PathBuf::from("skill_captures")),
+            );
+        }
+    }
+    None
+}
+
+pub(super) fn install(app: &mut App, output_dir: PathBuf) {
+    seed_vfx_rng(CAPTURE_SEED);
+
+    let jobs: Vec<CaptureJob> = CharacterClass::ALL
+        .iter()
+        .enumerate()
+        .flat_map(|(class_index, class)| {
+            skills_for_class(*class)
+                .iter()
+                .enumerate()
+                .map(move |(skill_index, skill)| CaptureJob {
+                    class_index,
+                    skill_index,
+                    class: *class,
+                    skill: *skill,
+                })
+        })
+        .collect();
+    info!(
+        "Capturing {} skill casts into {}",
+        jobs.len(),
+        output_dir.display()
+    );
+
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        1.0 / f64::from(CAPTURE_FPS),
+    )))
+    .insert_resource(ViewerUiHidden)
+    .insert_resource(SkillCapture {
+        output_dir,
+        jobs,
+        cursor: 0,
+        phase: CapturePhase::Spawning {
+            waited: 0,
+            settled: 0,
+        },
+        clips: Vec::new(),
+    })
+    .add_systems(
+        Update,
+        drive_skill_capture
+            .before(super::handle_class_change)
+            .before(super::trigger_selected_skill),
+    );
+
+    // Effect randomness is drawn in system order, which must not vary between runs.
+    app.edit_schedule(Update, |schedule| {
+        schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+    });
+}
+
+#[derive(Clone, Copy)]
+struct CaptureJob {
+    class_index: usize,
+    skill_index: usize,
+    class: CharacterClass,
+    skill: SkillEntry,
+}
+
+impl CaptureJob {
+    fn clip_dir(&self, output_dir: &Path) -> PathBuf {
+        output_dir.join(slug(self.class.name())).join(format!(
+            "{}_{}",
+            self.skill.skill_id,
+            slug(self.skill.name)
+        ))
+    }
+}
+
+enum CapturePhase {
+    Spawning { waited: u32, settled: u32 },
+    Recording { frame: u32 },
+    Flushing { waited: u32 },
+    Finished,
+}
+
+#[derive(Serialize)]
+struct CaptureManifest<'a> {
+    seed: u64,
+    fps: u32,
+    frames_per_clip: u32,
+    clips: &'a [CapturedClip],
+}
+
+#[derive(Serialize)]
+struct CapturedClip {
+    class: &'static str,
+    skill_id: u16,
+    skill: &'static str,
+    vfx_profile: String,
+    frames_dir: PathBuf,
+    mp4: Option<PathBuf>,
+    webp: Option<PathBuf>,
+}
+
+#[derive(Resource)]
+struct SkillCapture {
+    output_dir: PathBuf,
+    jobs: Vec<CaptureJob>,
+    cursor: usize,
+    phase: CapturePhase,
+    clips: Vec<CapturedClip>,
+}
+
+fn drive_skill_capture(
+    mut commands: Commands,
+    mut capture: ResMut<SkillCapture>,
+    mut viewer: ResMut<ViewerState>,
+    library: Res<PlayerAnimLib>,
+    preload_cache: Res<SkillVfxPreloadCache>,
+    asset_server: Res<AssetServer>,
+    scenes: Query<&SceneRoot>,
+    characters: Query<(), With<CharacterRoot>>,
+    skill_vfx: Query<(), With<SkillVfx>>,
+    mut cameras: Query<&mut MuCamera>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let capture = &mut *capture;
+
+    match capture.phase {
+        CapturePhase::Spawning { waited, settled } => {
+            let Some(job) = capture.jobs.get(capture.cursor).copied() else {
+                capture.phase = CapturePhase::Flushing { waited: 0 };
+                return;
+            };
+
+            if waited == 0 {
+                // Every clip starts from a fresh spawn so earlier casts cannot leak in.
+                viewer.selected_class_index = job.class_index;
+                viewer.selected_skill_index = job.skill_index;
+                viewer.pending_class_change = true;
+                viewer.pending_skill_cast = false;
+                viewer.movement_target = None;
+                for mut camera in &mut cameras {
+                    camera.pitch_deg = MU_CAMERA_PITCH_DEG;
+                    camera.yaw_deg = MU_CAMERA_YAW_DEG;
+                    camera.distance = MU_CAMERA_DISTANCE;
+                    camera.target_distance = MU_CAMERA_DISTANCE;
+                }
+                capture.phase = CapturePhase::Spawning {
+                    waited: 1,
+                    settled: 0,
+                };
+                return;
+            }
+
+            let loaded = scenes
+                .iter()
+                .map(|scene| scene.0.id().untyped())
+                .chain(preload_cache.scene_handles.iter().map(|h| h.id().untyped()))
+                .chain(preload_cache.gltf_handles.iter().map(|h| h.id().untyped()))
+                .all(|id| asset_server.is_loaded_with_dependencies(id));
+            let ready = !viewer.pending_class_change
+                && library.initialized
+                && !characters.is_empty()
+                && skill_vfx.is_empty()
+                && loaded;
+
+            let settled = if ready { settled + 1 } else { 0 };
+            if settled >= SETTLE_FRAMES || waited >= MAX_WAIT_FRAMES {
+                if !ready {
+                    warn!(
+                        "Capturing {} skill {} before its assets finished loading",
+                        job.class.name(),
+                        job.skill.skill_id
+                    );
+                }
+                viewer.selected_skill_index = job.skill_index;
+                viewer.pending_skill_cast = true;
+                record_frame(&mut commands, &capture.output_dir, &job, 0);
+                capture.phase = CapturePhase::Recording { frame: 1 };
+            } else {
+                capture.phase = CapturePhase::Spawning {
+                    waited: waited + 1,
+                    settled,
+                };
+            }
+        }
+        CapturePhase::Recording { frame } => {
+            let job = capture.jobs[capture.cursor];
+            if frame < CLIP_FRAMES {
+                record_frame(&mut commands, &capture.output_dir, &job, frame);
+                capture.phase = CapturePhase::Recording { frame: frame + 1 };
+                return;
+            }
+
+            capture.clips.push(CapturedClip {
+                class: job.class.name(),
+                skill_id: job.skill.skill_id,
+                skill: job.skill.name,
+                vfx_profile: format!("{:?}", job.skill.vfx),
+                frames_dir: job.clip_dir(&capture.output_dir),
+                mp4: None,
+                webp: None,
+            });
+            capture.cursor += 1;
+            capture.phase = CapturePhase::Spawning {
+                waited: 0,
+                settled: 0,
+            };
+        }
+        CapturePhase::Flushing { waited } => {
+            if waited < FLUSH_FRAMES {
+                capture.phase = CapturePhase::Flushing { waited: waited + 1 };
+                return;
+            }
+
+            encode_clips(&mut capture.clips);
+            write_manifest(&capture.output_dir, &capture.clips);
+            capture.phase = CapturePhase::Finished;
+            exit.write(AppExit::Success);
+        }
+        CapturePhase::Finished => {}
+    }
+}
+
+fn record_frame(commands: &mut Commands, output_dir: &Path, job: &CaptureJob, frame: u32) {
+    let path = job
+        .clip_dir(output_dir)
+        .join(format!("frame_{frame:04}.png"));
+    if let Some(parent) = path.parent() {
+        if let Err(err) = std::fs::create_dir_all(parent) {
+            error!("Failed to create {}: {}", parent.display(), err);
+            return;
+        }
+    }
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(save_to_disk(path));
+}
+
+fn encode_clips(clips: &mut [CapturedClip]) {
+    let ffmpeg_available = Command::new("ffmpeg")
+        .arg("-version")
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if !ffmpeg_available {
+        warn!("ffmpeg not found on PATH; keeping PNG sequences only");
+        return;
+    }
+
+    for clip in clips {
+        let mp4 = clip.frames_dir.with_extension("mp4");
+        if run_ffmpeg(
+            &clip.frames_dir,
+            &["-c:v", "libx264", "-pix_fmt", "yuv420p", "-crf", "18"],
+            &mp4,
+        ) {
+            clip.mp4 = Some(mp4);
+        }
+
+        let webp = clip.frames_dir.with_extension("webp");
+        if run_ffmpeg(
+            &clip.frames_dir,
+            &["-c:v", "libwebp", "-quality", "80", "-loop", "0"],
+            &webp,
+        ) {
+            clip.webp = Some(webp);
+        }
+    }
+}
+
+fn run_ffmpeg(frames_dir: &Path, codec_args: &[&str], output: &Path) -> bool {
+    let status = Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-framerate"])
+        .arg(CAPTURE_FPS.to_string())
+        .arg("-i")
+        .arg(frames_dir.join("frame_%04d.png"))
+        .args(codec_args)
+        .arg(output)
+        .status();
+
+    match status {
+        Ok(status) if status.success() => true,
+        Ok(status) => {
+            error!("ffmpeg exited with {} for {}", status, output.display());
+            false
+        }
+        Err(err) => {
+            error!("Failed to run ffmpeg for {}: {}", output.display(), err);
+            false
+        }
+    }
+}
+
+fn write_manifest(output_dir: &Path, clips: &[CapturedClip]) {
+    let manifest = CaptureManifest {
+        seed: CAPTURE_SEED,
+        fps: CAPTURE_FPS,
+        frames_per_clip: CLIP_FRAMES,
+        clips,
+    };
+    let path = output_dir.join("manifest.json");
+    let result = serde_json::to_vec_pretty(&manifest)
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| std::fs::write(&path, bytes).map_err(|err| err.to_string()));
+
+    match result {
+        Ok(()) => info!(
+            "Captured {} clips; manifest at {}",
+            clips.len(),
+            path.display()
+        ),
+        Err(err) => error!("Failed to write {}: {}", path.display(), err),
+    }
+}
+
+fn slug(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("_")
+}
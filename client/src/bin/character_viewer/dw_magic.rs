@@ -1,5 +1,7 @@
 use super::*;
+use client::scene_runtime::vfx_rng::vfx_rng;
 use rand::Rng;
+use rand::rngs::StdRng;
 use std::f32::consts::TAU;
 
 const DW_BASE_FPS: f32 = 25.0;
@@ -157,7 +159,7 @@ pub(super) fn update_dw_magic_effects(
 ) {
     let dt = time.delta_secs();
     let frame_factor = dt * DW_BASE_FPS;
-    let mut rng = rand::thread_rng();
+    let mut rng = vfx_rng();
 
     for (effect_entity, mut effect, mut effect_transform) in &mut effects {
         if !effect.initialized {
@@ -713,7 +715,7 @@ fn update_meteorite_effect(
     asset_server: &AssetServer,
     effect: &mut DwMagicRuntime,
     effect_transform: &mut Transform,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut StdRng,
 ) {
     let travel_t = if effect.travel_duration_secs > 0.0 {
         (effect.elapsed_secs / effect.travel_duration_secs).clamp(0.0, 1.0)
@@ -825,7 +827,7 @@ fn update_lightning_effect(
     commands: &mut Commands,
     asset_server: &AssetServer,
     effect: &mut DwMagicRuntime,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut StdRng,
 ) {
     while effect.emission_accumulator_secs >= 0.045 {
         effect.emission_accumulator_secs -= 0.045;
@@ -894,7 +896,7 @@ fn update_fire_ball_effect(
     asset_server: &AssetServer,
     effect: &mut DwMagicRuntime,
     effect_transform: &mut Transform,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut StdRng,
 ) {
     update_projectile_motion(
         effect,
@@ -996,7 +998,7 @@ fn update_flame_effect(
     commands: &mut Commands,
     asset_server: &AssetServer,
     effect: &mut DwMagicRuntime,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut StdRng,
 ) {
     while effect.emission_accumulator_secs >= 0.04 {
         effect.emission_accumulator_secs -= 0.04;
@@ -1075,7 +1077,7 @@ fn update_twister_effect(
     commands: &mut Commands,
     asset_server: &AssetServer,
     effect: &mut DwMagicRuntime,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut StdRng,
 ) {
     let swirl_angle = effect.auxiliary_accumulator_frames * 0.22;
 
@@ -1155,7 +1157,7 @@ fn update_evil_spirit_effect(
     caster_transforms: &Query<&GlobalTransform>,
     effect: &mut DwMagicRuntime,
     frame_factor: f32,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut StdRng,
 ) {
     let caster_anchor = caster_transforms
         .get(effect.caster_entity)
@@ -1318,7 +1320,7 @@ fn move_humming_spirit(
     target: Vec3,
     turn_rate: f32,
     frame_factor: f32,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut StdRng,
 ) {
     let dx = target.x - position.x;
     let dz = target.z - position.z;
@@ -1369,7 +1371,7 @@ fn update_hell_fire_effect(
     caster_transforms: &Query<&GlobalTransform>,
     effect: &mut DwMagicRuntime,
     effect_transform: &mut Transform,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut StdRng,
 ) {
     if let Ok(caster_transform) = caster_transforms.get(effect.caster_entity) {
         effect_transform.translation = caster_transform.translation();
@@ -1435,7 +1437,7 @@ fn update_aqua_beam_effect(
     commands: &mut Commands,
     asset_server: &AssetServer,
     effect: &mut DwMagicRuntime,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut StdRng,
 ) {
     while effect.emission_accumulator_secs >= 0.05 {
         effect.emission_accumulator_secs -= 0.05;
@@ -1501,7 +1503,7 @@ fn update_inferno_effect(
     commands: &mut Commands,
     asset_server: &AssetServer,
     effect: &mut DwMagicRuntime,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut StdRng,
 ) {
     if !effect.impact_triggered && effect.elapsed_secs >= 0.10 {
         effect.impact_triggered = true;
@@ -1596,7 +1598,7 @@ fn update_energy_ball_effect(
     asset_server: &AssetServer,
     effect: &mut DwMagicRuntime,
     effect_transform: &mut Transform,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut StdRng,
 ) {
     update_projectile_motion(
         effect,
@@ -1698,7 +1700,7 @@ fn update_ice_storm_effect(
     commands: &mut Commands,
     asset_server: &AssetServer,
     effect: &mut DwMagicRuntime,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut StdRng,
 ) {
     while effect.emission_accumulator_secs >= 0.16 {
         effect.emission_accumulator_secs -= 0.16;
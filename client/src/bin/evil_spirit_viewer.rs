@@ -1,7 +1,6 @@
 use bevy::asset::AssetPlugin;
 use bevy::camera::ClearColorConfig;
 use bevy::color::LinearRgba;
-use bevy::core_pipeline::tonemapping::Tonemapping;
 use bevy::input::mouse::MouseWheel;
 use bevy::light::{NotShadowCaster, NotShadowReceiver};
 use bevy::prelude::*;
@@ -9,6 +8,8 @@ use bevy::sprite::Anchor;
 use bevy::window::{PrimaryWindow, WindowResolution};
 use bevy_egui::input::EguiWantsInput;
 use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiPrimaryContextPass};
+use client::scene_runtime::systems::ColorProfilePlugin;
+use common::worldscale::{CAMERA_PITCH_DEG, CAMERA_YAW_DEG};
 use rand::Rng;
 use std::f32::consts::PI;
 use std::path::Path;
@@ -203,8 +204,8 @@ impl Default for EvilSpiritTuning {
             tail_alpha: 0.65,
             shock_alpha: 0.72,
             shock_enabled: true,
-            camera_pitch_deg: 48.5,
-            camera_yaw_deg: -45.0,
+            camera_pitch_deg: CAMERA_PITCH_DEG,
+            camera_yaw_deg: CAMERA_YAW_DEG,
             camera_look_height: 90.0,
             camera_distance_default: 980.0,
             zoom_min: 280.0,
@@ -308,6 +309,7 @@ fn main() {
                 }),
         )
         .add_plugins(EguiPlugin::default())
+        .add_plugins(ColorProfilePlugin)
         .add_systems(Startup, setup_scene)
         .add_systems(
             EguiPrimaryContextPass,
@@ -404,7 +406,6 @@ fn setup_scene(
             ..default()
         },
         Camera3d::default(),
-        Tonemapping::ReinhardLuminance,
     ));
 
     commands.spawn((
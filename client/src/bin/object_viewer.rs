@@ -8,7 +8,6 @@
 use bevy::asset::AssetId;
 #[cfg(feature = "solari")]
 use bevy::camera::CameraMainTextureUsages;
-use bevy::core_pipeline::tonemapping::Tonemapping;
 use bevy::gltf::{Gltf, GltfMaterialExtras};
 use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
 use bevy::light::GlobalAmbientLight;
@@ -27,7 +26,7 @@ use std::time::Duration;
 
 use client::bevy_compat::*;
 use client::composition::object_viewer_runtime::configure_object_animation_viewer_app;
-use client::infra::assets::{resolve_asset_path, set_use_remaster_assets};
+use client::infra::assets::{AssetVariant, resolve_asset_path, set_asset_variant};
 use client::infra::persistence::settings_store;
 use client::legacy_additive::{
     LegacyAdditiveMaterial, legacy_additive_from_standard, legacy_additive_intensity_from_extras,
@@ -56,7 +55,7 @@ struct ViewerState {
     pending_apply_selection: bool,
     pending_toggle_playback: bool,
     animations_initialized: bool,
-    use_remaster: bool,
+    asset_variant: AssetVariant,
     status: String,
     #[cfg(feature = "solari")]
     use_raytracing: bool,
@@ -82,7 +81,7 @@ impl Default for ViewerState {
             pending_apply_selection: false,
             pending_toggle_playback: false,
             animations_initialized: false,
-            use_remaster: true,
+            asset_variant: AssetVariant::Remaster,
             status: "Ready. Enter a .glb path and click Load.".to_string(),
             #[cfg(feature = "solari")]
             use_raytracing: true,
@@ -121,10 +120,10 @@ struct MeshBoundsCache {
 
 fn main() {
     let startup_settings = settings_store::load();
-    let use_remaster_assets = startup_settings.graphics.use_remaster_assets;
+    let asset_variant = startup_settings.graphics.asset_variant;
 
     let mut viewer_state = ViewerState::default();
-    viewer_state.use_remaster = use_remaster_assets;
+    viewer_state.asset_variant = asset_variant;
 
     let mut app = App::new();
     app.insert_resource(GlobalAmbientLight {
@@ -134,7 +133,7 @@ fn main() {
     })
     .insert_resource(MeshBoundsCache::default())
     .insert_resource(viewer_state);
-    configure_object_animation_viewer_app(&mut app, asset_root_path(), use_remaster_assets);
+    configure_object_animation_viewer_app(&mut app, asset_root_path(), asset_variant);
 
     app.add_systems(Startup, setup_viewer_scene)
         .add_systems(EguiPrimaryContextPass, draw_ui_panel)
@@ -183,7 +182,6 @@ fn setup_viewer_scene(
     let camera = commands.spawn((
         Camera3dBundle {
             transform: camera_transform,
-            tonemapping: Tonemapping::ReinhardLuminance,
             ..default()
         },
         orbit_camera,
@@ -236,16 +234,9 @@ fn draw_ui_panel(
     mut clipboard: ResMut<EguiClipboard>,
 ) {
     if keys.just_pressed(KeyCode::F10) {
-        viewer.use_remaster = !viewer.use_remaster;
-        set_use_remaster_assets(viewer.use_remaster);
-        viewer.status = format!(
-            "Remaster assets {} (F10).",
-            if viewer.use_remaster {
-                "enabled"
-            } else {
-                "disabled"
-            }
-        );
+        viewer.asset_variant = viewer.asset_variant.toggled();
+        set_asset_variant(viewer.asset_variant);
+        viewer.status = format!("{:?} assets (F10).", viewer.asset_variant);
     }
 
     let Ok(ctx) = contexts.ctx_mut() else {
@@ -312,10 +303,15 @@ fn draw_ui_panel(
                 }
             });
 
-            let prev_remaster = viewer.use_remaster;
-            ui.checkbox(&mut viewer.use_remaster, "Use Remaster assets");
-            if viewer.use_remaster != prev_remaster {
-                set_use_remaster_assets(viewer.use_remaster);
+            let prev_variant = viewer.asset_variant;
+            ui.horizontal(|ui| {
+                ui.label("Assets:");
+                for variant in AssetVariant::ALL {
+                    ui.selectable_value(&mut viewer.asset_variant, variant, format!("{variant:?}"));
+                }
+            });
+            if viewer.asset_variant != prev_variant {
+                set_asset_variant(viewer.asset_variant);
             }
 
             let speed_slider =
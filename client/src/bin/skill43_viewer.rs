@@ -1,7 +1,6 @@
 use bevy::asset::AssetPlugin;
 use bevy::camera::ClearColorConfig;
 use bevy::color::LinearRgba;
-use bevy::core_pipeline::tonemapping::Tonemapping;
 use bevy::input::mouse::MouseWheel;
 use bevy::light::{NotShadowCaster, NotShadowReceiver};
 use bevy::mesh::PrimitiveTopology;
@@ -10,6 +9,10 @@ use bevy::sprite::Anchor;
 use bevy::window::{PrimaryWindow, WindowResolution};
 use bevy_egui::input::EguiWantsInput;
 use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiPrimaryContextPass};
+use client::scene_runtime::systems::ColorProfilePlugin;
+use common::worldscale::{
+    CAMERA_DISTANCE, CAMERA_LOOK_HEIGHT, CAMERA_PITCH_DEG, CAMERA_YAW_DEG, ZOOM_MIN,
+};
 use rand::Rng;
 use std::collections::VecDeque;
 use std::f32::consts::PI;
@@ -296,11 +299,11 @@ impl Default for Skill43Tuning {
             combo_scale_end: 220.0,
             combo_emissive: 8.0,
             // Camera do viewer (nao equivalente direto no C++).
-            camera_pitch_deg: 48.5,
-            camera_yaw_deg: -45.0,
-            camera_look_height: 80.0,
-            camera_distance_default: 1000.0,
-            zoom_min: 300.0,
+            camera_pitch_deg: CAMERA_PITCH_DEG,
+            camera_yaw_deg: CAMERA_YAW_DEG,
+            camera_look_height: CAMERA_LOOK_HEIGHT,
+            camera_distance_default: CAMERA_DISTANCE,
+            zoom_min: ZOOM_MIN,
             zoom_max: 2400.0,
             zoom_speed: 90.0,
         }
@@ -382,6 +385,7 @@ fn main() {
                 }),
         )
         .add_plugins(EguiPlugin::default())
+        .add_plugins(ColorProfilePlugin)
         .add_systems(Startup, setup_scene)
         .add_systems(
             EguiPrimaryContextPass,
@@ -484,7 +488,6 @@ fn setup_scene(
             ..default()
         },
         Camera3d::default(),
-        Tonemapping::ReinhardLuminance,
     ));
 
     commands.spawn((
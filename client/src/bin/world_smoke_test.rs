@@ -0,0 +1,3 @@
+fn main() {
+    client::app::bootstrap::run_world_smoke_test();
+}
@@ -1,4 +1,9 @@
 use super::types::{BodySlot, BodyType, CharacterClass};
+use common::ItemCode;
+
+/// Armor set indexes (the item index shared by groups 7..=11) modelled on the
+/// elf body, as `{slot}_elf_01..05`.
+const ELF_ARMOR_SETS: std::ops::RangeInclusive<u16> = 10..=14;
 
 /// Equipment set variants available in the character viewer.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -65,6 +70,21 @@ impl EquipmentSet {
         sets
     }
 
+    /// Model set for an armor piece from the item catalog, when one exists
+    /// for the body type. Male files are numbered from the set index plus one.
+    pub fn for_item(code: ItemCode, body_type: BodyType) -> Option<EquipmentSet> {
+        let set = code.definition()?.armor_set()?;
+        let id = match body_type {
+            BodyType::Elf if ELF_ARMOR_SETS.contains(&set) => set - ELF_ARMOR_SETS.start() + 1,
+            BodyType::Male if !ELF_ARMOR_SETS.contains(&set) => set + 1,
+            _ => return None,
+        };
+        let id = u8::try_from(id).ok()?;
+        Self::available_for(body_type)
+            .into_iter()
+            .find(|candidate| *candidate == EquipmentSet::Standard(id))
+    }
+
     /// GLB asset path for a specific slot in this equipment set.
     pub fn glb_path(&self, slot: BodySlot, body_type: BodyType, class: CharacterClass) -> String {
         match self {
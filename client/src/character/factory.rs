@@ -1,7 +1,7 @@
 use super::controller::{CharacterAnimState, CharacterController, CharacterState};
 use super::types::{BodyPartMarker, BodySlot, CharacterClass, CharacterRoot};
 use crate::bevy_compat::*;
-use crate::infra::assets::resolve_asset_path;
+use crate::infra::assets::{current_asset_variant, resolve_asset_group};
 use bevy::prelude::*;
 
 pub struct CharacterFactory;
@@ -36,10 +36,16 @@ impl CharacterFactory {
             ))
             .id();
 
-        for &slot in slots {
-            let glb_path = slot.default_glb_path(body_type);
+        // Parts share one skeleton, so they all come from the same variant.
+        let part_paths: Vec<String> = slots
+            .iter()
+            .map(|slot| slot.default_glb_path(body_type))
+            .collect();
+        let (_, glb_paths) = resolve_asset_group(&part_paths, current_asset_variant());
+
+        for (&slot, glb_path) in slots.iter().zip(glb_paths) {
             let scene_path = format!("{glb_path}#Scene0");
-            let scene_handle: Handle<Scene> = asset_server.load(resolve_asset_path(&scene_path));
+            let scene_handle: Handle<Scene> = asset_server.load(scene_path);
 
             let part = commands
                 .spawn((
@@ -5,7 +5,10 @@ pub mod controller;
 pub mod equipment;
 pub mod factory;
 pub mod movement;
+pub mod pathfinding;
+pub mod status;
 pub mod types;
+pub mod waypoints;
 
 pub use animation::{
     PlayerAnimationLibrary, apply_character_animation_changes, bind_character_animation_players,
@@ -17,4 +20,12 @@ pub use controller::{CharacterAnimState, CharacterController, CharacterState};
 pub use equipment::EquipmentSet;
 pub use factory::CharacterFactory;
 pub use movement::advance_character_movement;
+pub use pathfinding::{MovementRoute, follow_movement_routes, plan_movement};
+pub use status::{
+    apply_entity_status_effects, apply_gens_factions, apply_guild_relations, apply_monster_affixes,
+    apply_nameplate_damage,
+};
 pub use types::{BodyPartMarker, BodySlot, BodyType, CharacterClass, CharacterRoot};
+pub use waypoints::{
+    RemotePathFollower, apply_entity_paths, follow_remote_paths, track_prediction_error,
+};
@@ -1,5 +1,6 @@
 use super::controller::{CharacterAnimState, CharacterController, CharacterState};
 use super::types::CharacterRoot;
+use crate::scene_runtime::terrain_height::TerrainHeightField;
 use bevy::prelude::*;
 
 const WALK_SPEED: f32 = 300.0;
@@ -9,9 +10,11 @@ const TURN_SPEED: f32 = 10.0;
 const RUN_TO_WALK_THRESHOLD: f32 = 300.0;
 const MODEL_YAW_OFFSET: f32 = 0.0;
 
-/// Advance character position toward movement target each frame.
+/// Advance character position toward movement target each frame, keeping
+/// characters on the ground once the world's terrain heights are known.
 pub fn advance_character_movement(
     time: Res<Time>,
+    terrain: Option<Res<TerrainHeightField>>,
     mut characters: Query<
         (
             &mut Transform,
@@ -55,7 +58,9 @@ pub fn advance_character_movement(
         let distance = diff.length();
 
         if distance < ARRIVAL_THRESHOLD {
-            transform.translation = target;
+            transform.translation = terrain
+                .as_deref()
+                .map_or(target, |terrain| terrain.on_ground(target));
             controller.state = CharacterState::Idle;
             let idle_action = config_helpers::idle_action_for_class(controller.class);
             if anim_state.current_action != idle_action {
@@ -87,7 +92,13 @@ pub fn advance_character_movement(
         transform.translation.x += direction.x * step;
         transform.translation.z += direction.z * step;
 
-        // Keep vertical motion stable and converging to target height.
+        if let Some(terrain) = terrain.as_deref() {
+            transform.translation = terrain.on_ground(transform.translation);
+            continue;
+        }
+
+        // Without terrain heights, keep vertical motion stable and converging
+        // to target height.
         let progress = if distance > f32::EPSILON {
             step / distance
         } else {
@@ -103,7 +114,7 @@ fn mu_heading_to_bevy_yaw(direction_x: f32, direction_z: f32) -> f32 {
 }
 
 /// Helper module for looking up class defaults without needing the full config resource.
-pub(super) mod config_helpers {
+pub(crate) mod config_helpers {
     use super::super::types::CharacterClass;
 
     pub fn idle_action_for_class(class: CharacterClass) -> usize {
@@ -0,0 +1,122 @@
+//! Local movement routes that walk around object collision footprints.
+
+use super::controller::{CharacterController, CharacterState};
+use super::waypoints::{tile_to_world, world_to_tile};
+use crate::scene_runtime::collision::WorldCollision;
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+/// Longest route planned for a single move order, in tiles.
+const MAX_ROUTE_STEPS: usize = 64;
+
+/// Pending route corners of a locally controlled character.
+#[derive(Component, Debug, Default)]
+pub struct MovementRoute {
+    waypoints: VecDeque<Vec3>,
+}
+
+impl MovementRoute {
+    pub fn pending_waypoints(&self) -> usize {
+        self.waypoints.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.waypoints.clear();
+    }
+}
+
+/// Replaces `route` with a walkable path from `from` to `to`.
+///
+/// Straight moves keep the exact target; detours pass through tile centres
+/// and only keep the corners. Returns `false` (leaving the route empty) when
+/// the target tile is blocked or out of reach.
+pub fn plan_movement(
+    collision: &WorldCollision,
+    route: &mut MovementRoute,
+    from: Vec3,
+    to: Vec3,
+) -> bool {
+    route.clear();
+
+    let grid = collision.grid();
+    let start = world_to_tile(from);
+    let goal = world_to_tile(to);
+    if grid.is_blocked(goal.0, goal.1) {
+        return false;
+    }
+    if grid.line_is_clear(start, goal) {
+        route.waypoints.push_back(to);
+        return true;
+    }
+
+    let Some(tiles) = grid.find_path(start, goal, MAX_ROUTE_STEPS) else {
+        return false;
+    };
+
+    // Keep only the tiles where the route changes direction.
+    let step = |a: (u16, u16), b: (u16, u16)| {
+        (
+            i32::from(b.0) - i32::from(a.0),
+            i32::from(b.1) - i32::from(a.1),
+        )
+    };
+    let mut previous = start;
+    for pair in tiles.windows(2) {
+        let (corner, next) = (pair[0], pair[1]);
+        if step(previous, corner) != step(corner, next) {
+            route
+                .waypoints
+                .push_back(tile_to_world(corner.0, corner.1, from.y));
+        }
+        previous = corner;
+    }
+    route.waypoints.push_back(to);
+    true
+}
+
+/// Feeds the next route corner to the movement controller once idle.
+pub fn follow_movement_routes(mut routes: Query<(&mut MovementRoute, &mut CharacterController)>) {
+    for (mut route, mut controller) in &mut routes {
+        if !matches!(controller.state, CharacterState::Idle) {
+            continue;
+        }
+
+        if let Some(target) = route.waypoints.pop_front() {
+            controller.state = CharacterState::Running { target };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::collision::CollisionGrid;
+
+    #[test]
+    fn routes_turn_around_blocked_tiles() {
+        let mut grid = CollisionGrid::open();
+        for y in 8..=12 {
+            grid.block(12, y);
+        }
+        let collision = WorldCollision::from(grid);
+        let mut route = MovementRoute::default();
+
+        let from = tile_to_world(10, 10, 0.0);
+        let to = tile_to_world(14, 10, 0.0);
+        assert!(plan_movement(&collision, &mut route, from, to));
+        assert!(route.pending_waypoints() > 1);
+        assert_eq!(route.waypoints.back(), Some(&to));
+        assert!(route.waypoints.iter().all(|waypoint| {
+            let (x, y) = world_to_tile(*waypoint);
+            !collision.grid().is_blocked(x, y)
+        }));
+
+        let open_target = tile_to_world(10, 14, 0.0);
+        assert!(plan_movement(&collision, &mut route, from, open_target));
+        assert_eq!(route.pending_waypoints(), 1);
+
+        let wall = tile_to_world(12, 10, 0.0);
+        assert!(!plan_movement(&collision, &mut route, from, wall));
+        assert_eq!(route.pending_waypoints(), 0);
+    }
+}
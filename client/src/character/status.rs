@@ -0,0 +1,144 @@
+use super::waypoints::RemotePathFollower;
+use crate::infra::network::ServerMessageReceived;
+use crate::presentation::ui::nameplate::Nameplate;
+use crate::scene_runtime::systems::{EliteMarker, StatusEffectVisuals};
+use bevy::prelude::*;
+use protocol::ServerMessage;
+
+/// Shows replicated buffs and debuffs on the remote character they belong to.
+pub fn apply_entity_status_effects(
+    mut commands: Commands,
+    mut incoming: MessageReader<ServerMessageReceived>,
+    mut remotes: Query<(
+        Entity,
+        &RemotePathFollower,
+        Option<&mut StatusEffectVisuals>,
+    )>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        let ServerMessage::EntityStatus { entity_id, effects } = message else {
+            continue;
+        };
+
+        let Some((entity, _, visuals)) = remotes
+            .iter_mut()
+            .find(|(_, follower, _)| follower.entity_id == *entity_id)
+        else {
+            continue;
+        };
+        match visuals {
+            Some(mut visuals) => visuals.replace(effects),
+            None if !effects.is_empty() => {
+                let mut visuals = StatusEffectVisuals::default();
+                visuals.replace(effects);
+                commands.entity(entity).insert(visuals);
+            }
+            None => {}
+        }
+    }
+}
+
+/// Colours remote players by how their guild stands with the local
+/// player's, from the relation carried in their state flags.
+pub fn apply_guild_relations(
+    mut incoming: MessageReader<ServerMessageReceived>,
+    mut remotes: Query<(&RemotePathFollower, &mut Nameplate)>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        let ServerMessage::StateDelta { entities, .. } = message else {
+            continue;
+        };
+        for delta in entities {
+            let Some((_, mut nameplate)) = remotes
+                .iter_mut()
+                .find(|(follower, _)| follower.entity_id == delta.entity_id)
+            else {
+                continue;
+            };
+            let relation = delta.guild_relation();
+            if nameplate.relation != relation {
+                nameplate.relation = relation;
+            }
+        }
+    }
+}
+
+/// Tags remote players with the Gens faction carried in their state flags.
+pub fn apply_gens_factions(
+    mut incoming: MessageReader<ServerMessageReceived>,
+    mut remotes: Query<(&RemotePathFollower, &mut Nameplate)>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        let ServerMessage::StateDelta { entities, .. } = message else {
+            continue;
+        };
+        for delta in entities {
+            let Some((_, mut nameplate)) = remotes
+                .iter_mut()
+                .find(|(follower, _)| follower.entity_id == delta.entity_id)
+            else {
+                continue;
+            };
+            let faction = delta.gens_faction();
+            if nameplate.gens != faction {
+                nameplate.gens = faction;
+            }
+        }
+    }
+}
+
+/// Marks remote monsters that rolled affixes as elites or bosses.
+pub fn apply_monster_affixes(
+    mut commands: Commands,
+    mut incoming: MessageReader<ServerMessageReceived>,
+    mut remotes: Query<(Entity, &RemotePathFollower, Option<&mut Nameplate>)>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        let ServerMessage::MonsterAffixes { entity_id, affixes } = message else {
+            continue;
+        };
+        let Some(marker) = EliteMarker::new(affixes) else {
+            continue;
+        };
+
+        let Some((entity, _, nameplate)) = remotes
+            .iter_mut()
+            .find(|(_, follower, _)| follower.entity_id == *entity_id)
+        else {
+            continue;
+        };
+        if let Some(mut nameplate) = nameplate {
+            nameplate.rank = Some(marker.rank);
+        }
+        commands.entity(entity).insert(marker);
+    }
+}
+
+/// Fills the HP bars of remote nameplates from damage events and marks the
+/// plate of whatever the local player hit last as its target.
+pub fn apply_nameplate_damage(
+    mut incoming: MessageReader<ServerMessageReceived>,
+    mut local_entity_id: Local<Option<u32>>,
+    mut remotes: Query<(&RemotePathFollower, &mut Nameplate)>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        let hit = match message {
+            ServerMessage::EnterMap { entity_id, .. } => {
+                *local_entity_id = Some(*entity_id);
+                continue;
+            }
+            ServerMessage::DamageEvent(hit) => hit,
+            _ => continue,
+        };
+        let dealt = *local_entity_id == Some(hit.attacker_entity_id);
+        for (follower, mut nameplate) in &mut remotes {
+            let hit_here = follower.entity_id == hit.target_entity_id;
+            if hit_here {
+                nameplate.record_hit(hit.damage, hit.remaining_hp);
+            }
+            if dealt && nameplate.target != hit_here {
+                nameplate.target = hit_here;
+            }
+        }
+    }
+}
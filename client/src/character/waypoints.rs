@@ -0,0 +1,220 @@
+use super::animations::PlayerAction;
+use super::controller::{CharacterController, CharacterState};
+use super::factory::CharacterFactory;
+use super::pathfinding::MovementRoute;
+use super::types::CharacterClass;
+use crate::infra::network::{NetworkStats, ServerMessageReceived};
+use crate::presentation::ui::nameplate::Nameplate;
+use crate::scene_runtime::world_coordinates::{mirror_map_xz_with_axis, world_mirror_axis};
+use bevy::prelude::*;
+use common::collision::tile_index;
+use common::worldscale::{MAP_WORLD_SIZE, TILE_WORLD_SIZE};
+use protocol::{ServerMessage, WaypointPath};
+use std::collections::{HashMap, VecDeque};
+
+/// Class remote characters are drawn as; paths do not carry it.
+const REMOTE_CHARACTER_CLASS: CharacterClass = CharacterClass::DarkKnight;
+
+/// Remote character animated along server waypoint paths.
+#[derive(Component, Debug, Default)]
+pub struct RemotePathFollower {
+    pub entity_id: u32,
+    waypoints: VecDeque<(u16, u16)>,
+}
+
+impl RemotePathFollower {
+    pub fn new(entity_id: u32) -> Self {
+        Self {
+            entity_id,
+            waypoints: VecDeque::new(),
+        }
+    }
+
+    /// Replaces pending steps; a path without steps snaps to its start tile.
+    pub fn replace_path(&mut self, path: &WaypointPath) {
+        self.waypoints.clear();
+        if path.directions.is_empty() {
+            self.waypoints.push_back((path.start_x, path.start_y));
+        } else {
+            self.waypoints.extend(path.tiles());
+        }
+    }
+
+    pub fn pending_steps(&self) -> usize {
+        self.waypoints.len()
+    }
+
+    fn next_waypoint(&mut self) -> Option<(u16, u16)> {
+        self.waypoints.pop_front()
+    }
+}
+
+/// Center of a map tile in Bevy world space.
+pub fn tile_to_world(x: u16, y: u16, height: f32) -> Vec3 {
+    let map_x = (f32::from(x) + 0.5) * TILE_WORLD_SIZE;
+    let map_z = (f32::from(y) + 0.5) * TILE_WORLD_SIZE;
+    let (world_x, world_z) = mirror_map_xz_with_axis(
+        map_x,
+        map_z,
+        MAP_WORLD_SIZE,
+        MAP_WORLD_SIZE,
+        world_mirror_axis(),
+    );
+    Vec3::new(world_x, height, world_z)
+}
+
+/// Map tile under a Bevy world-space position.
+pub fn world_to_tile(position: Vec3) -> (u16, u16) {
+    let (map_x, map_z) = mirror_map_xz_with_axis(
+        position.x,
+        position.z,
+        MAP_WORLD_SIZE,
+        MAP_WORLD_SIZE,
+        world_mirror_axis(),
+    );
+    (tile_index(map_x), tile_index(map_z))
+}
+
+/// Moves remote characters along server paths. One seen for the first time
+/// is spawned at its path's start tile, with a nameplate; entering a map
+/// drops the ones of the previous map.
+pub fn apply_entity_paths(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut incoming: MessageReader<ServerMessageReceived>,
+    mut local_entity_id: Local<Option<u32>>,
+    mut followers: Query<(Entity, &mut RemotePathFollower)>,
+) {
+    let mut arrived: HashMap<u32, RemotePathFollower> = HashMap::new();
+    for ServerMessageReceived(message) in incoming.read() {
+        let path = match message {
+            ServerMessage::EnterMap { entity_id, .. } => {
+                *local_entity_id = Some(*entity_id);
+                for (entity, _) in &followers {
+                    commands.entity(entity).despawn();
+                }
+                arrived.clear();
+                continue;
+            }
+            ServerMessage::EntityPath(path) => path,
+            _ => continue,
+        };
+        // The local player moves on its own input.
+        if *local_entity_id == Some(path.entity_id) {
+            continue;
+        }
+
+        if let Some((_, mut follower)) = followers
+            .iter_mut()
+            .find(|(_, follower)| follower.entity_id == path.entity_id)
+        {
+            follower.replace_path(path);
+            continue;
+        }
+        arrived
+            .entry(path.entity_id)
+            .or_insert_with(|| RemotePathFollower::new(path.entity_id))
+            .replace_path(path);
+    }
+
+    for (entity_id, follower) in arrived {
+        let Some(&(x, y)) = follower.waypoints.front() else {
+            continue;
+        };
+        let root = CharacterFactory::spawn(
+            &mut commands,
+            &asset_server,
+            REMOTE_CHARACTER_CLASS,
+            tile_to_world(x, y, 0.0),
+            PlayerAction::StopMale as usize,
+            1.0,
+        );
+        commands.entity(root).insert((
+            follower,
+            Nameplate {
+                name: format!("#{entity_id}"),
+                ..default()
+            },
+        ));
+    }
+}
+
+/// Feeds the next waypoint to the movement controller once the previous one is reached.
+pub fn follow_remote_paths(
+    mut followers: Query<(
+        &mut RemotePathFollower,
+        &mut CharacterController,
+        &Transform,
+    )>,
+) {
+    for (mut follower, mut controller, transform) in &mut followers {
+        if !matches!(controller.state, CharacterState::Idle) {
+            continue;
+        }
+
+        if let Some((x, y)) = follower.next_waypoint() {
+            controller.state = CharacterState::Walking {
+                target: tile_to_world(x, y, transform.translation.y),
+            };
+        }
+    }
+}
+
+/// Compares the local character with the tile the server last reported for it.
+pub fn track_prediction_error(
+    mut incoming: MessageReader<ServerMessageReceived>,
+    mut local_entity_id: Local<Option<u32>>,
+    local_character: Query<&Transform, (With<MovementRoute>, Without<RemotePathFollower>)>,
+    mut stats: ResMut<NetworkStats>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        let (x, y) = match message {
+            ServerMessage::EnterMap { entity_id, .. } => {
+                *local_entity_id = Some(*entity_id);
+                continue;
+            }
+            ServerMessage::StateDelta { entities, .. } => {
+                let Some(delta) = entities
+                    .iter()
+                    .find(|delta| Some(delta.entity_id) == *local_entity_id)
+                else {
+                    continue;
+                };
+                (delta.x, delta.y)
+            }
+            _ => continue,
+        };
+        let Ok(transform) = local_character.single() else {
+            continue;
+        };
+        let predicted = transform.translation;
+        stats.record_prediction(predicted, tile_to_world(x, y, predicted.y));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replace_path_queues_tiles_or_snaps() {
+        let mut follower = RemotePathFollower::new(5);
+        follower.replace_path(&WaypointPath {
+            entity_id: 5,
+            start_x: 10,
+            start_y: 10,
+            directions: vec![2, 2],
+        });
+        assert_eq!(follower.pending_steps(), 2);
+        assert_eq!(follower.next_waypoint(), Some((11, 10)));
+
+        follower.replace_path(&WaypointPath {
+            entity_id: 5,
+            start_x: 40,
+            start_y: 41,
+            directions: Vec::new(),
+        });
+        assert_eq!(follower.next_waypoint(), Some((40, 41)));
+        assert_eq!(follower.next_waypoint(), None);
+    }
+}
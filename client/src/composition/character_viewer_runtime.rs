@@ -4,15 +4,16 @@ use bevy::sprite_render::Material2dPlugin;
 use bevy::window::WindowResolution;
 use bevy_egui::EguiPlugin;
 
-use crate::infra::assets::configure_asset_resolver;
+use crate::infra::assets::{AssetVariant, configure_asset_resolver};
 use crate::lightning_sprite_2d::LightningSprite2dMaterial;
+use crate::scene_runtime::systems::{CameraEffectsPlugin, ColorProfilePlugin, StatusEffectsPlugin};
 
 pub fn configure_character_viewer_app(
     app: &mut App,
     asset_root: String,
-    use_remaster_assets: bool,
+    asset_variant: AssetVariant,
 ) {
-    configure_asset_resolver(asset_root.clone(), use_remaster_assets);
+    configure_asset_resolver(asset_root.clone(), asset_variant);
 
     app.add_plugins(
         DefaultPlugins
@@ -31,6 +32,9 @@ pub fn configure_character_viewer_app(
             }),
     )
     .add_plugins(EguiPlugin::default())
+    .add_plugins(CameraEffectsPlugin)
+    .add_plugins(ColorProfilePlugin)
+    .add_plugins(StatusEffectsPlugin)
     .add_plugins(Material2dPlugin::<LightningSprite2dMaterial>::default());
 
     #[cfg(feature = "solari")]
@@ -3,33 +3,96 @@ use bevy::state::app::AppExtStates;
 use bevy_egui::EguiPlugin;
 
 use crate::AppState;
+use crate::app::gpu::{GpuSelectionPlugin, GpuStartup};
 use crate::app::plugins::{build_bevy_plugins, create_winit_settings};
-use crate::domain::settings::{GameSettings, SettingsPlugin, SettingsResource};
+use crate::domain::settings::{GameSettings, SettingsPlugin, SettingsResource, SettingsSyncPlugin};
+use crate::gameplay::area_targeting::AreaTargetingPlugin;
 use crate::gameplay::controllers::scene_controller::SceneControllerPlugin;
+use crate::gameplay::emotes::EmotesPlugin;
+use crate::gameplay::helper::MuHelperPlugin;
+use crate::gameplay::interaction_hover::InteractionHoverPlugin;
 use crate::gameplay::runtime::registration::register_gameplay_runtime;
 use crate::gameplay::scenes::gameplay::GameplayScene;
 use crate::gameplay::scenes::login::LoginScene;
+use crate::gameplay::town_props::TownPropsPlugin;
+use crate::gameplay::world_doors::WorldDoorsPlugin;
 use crate::infra::assets::{configure_asset_resolver, default_asset_root_path};
+use crate::infra::audio::SoundPlugin;
+use crate::infra::input::InputBufferPlugin;
+use crate::infra::network::NetworkPlugin;
+use crate::presentation::debug::NetworkDebugPlugin;
+use crate::presentation::ui::accessibility::AccessibilityPlugin;
+use crate::presentation::ui::bestiary::BestiaryPresentationPlugin;
+use crate::presentation::ui::chat::ChatPresentationPlugin;
+use crate::presentation::ui::combat_log::CombatLogPresentationPlugin;
+use crate::presentation::ui::disconnect::DisconnectPresentationPlugin;
+use crate::presentation::ui::emote_wheel::EmoteWheelPresentationPlugin;
+use crate::presentation::ui::event_notice::EventNoticePresentationPlugin;
+use crate::presentation::ui::helper::HelperPresentationPlugin;
+use crate::presentation::ui::hints::HintPresentationPlugin;
 use crate::presentation::ui::hud::HudPresentationPlugin;
+use crate::presentation::ui::hud_layout::HudLayoutPresentationPlugin;
+use crate::presentation::ui::interaction_prompt::InteractionPromptPresentationPlugin;
+use crate::presentation::ui::mailbox::MailboxPresentationPlugin;
+use crate::presentation::ui::map_transfer::MapTransferPresentationPlugin;
+use crate::presentation::ui::nameplate::NameplatePresentationPlugin;
+use crate::presentation::ui::ui_sounds::UiSoundPresentationPlugin;
 use crate::scene_runtime::scene_loader::SceneLoaderPlugin;
 use crate::world::WorldPlugin;
 
-pub fn configure_client_app(app: &mut App, startup_settings: &GameSettings) {
+pub fn configure_client_app(app: &mut App, startup_settings: &GameSettings, gpu: GpuStartup) {
+    configure_client_app_in_state(app, startup_settings, gpu, AppState::default());
+}
+
+/// Same as [`configure_client_app`], but starting in `initial_state` instead of the login.
+pub fn configure_client_app_in_state(
+    app: &mut App,
+    startup_settings: &GameSettings,
+    gpu: GpuStartup,
+    initial_state: AppState,
+) {
     configure_asset_resolver(
         default_asset_root_path(),
-        startup_settings.graphics.use_remaster_assets,
+        startup_settings.graphics.asset_variant,
     );
 
     app.insert_resource(SettingsResource::new(startup_settings.clone()))
-        .add_plugins(build_bevy_plugins(startup_settings))
+        .add_plugins(build_bevy_plugins(startup_settings, &gpu.selection))
         .insert_resource(create_winit_settings(startup_settings))
         .add_plugins(bevy::diagnostic::FrameTimeDiagnosticsPlugin::default())
         .add_plugins(EguiPlugin::default())
         .add_plugins(SceneLoaderPlugin)
         .add_plugins(WorldPlugin)
         .add_plugins(SettingsPlugin)
+        .add_plugins(SoundPlugin)
+        .add_plugins(GpuSelectionPlugin(gpu))
+        .add_plugins(NetworkPlugin)
+        .add_plugins(SettingsSyncPlugin)
+        .add_plugins(InputBufferPlugin)
+        .add_plugins(AccessibilityPlugin)
         .add_plugins(HudPresentationPlugin)
-        .init_state::<AppState>()
+        .add_plugins(HudLayoutPresentationPlugin)
+        .add_plugins(MailboxPresentationPlugin)
+        .add_plugins(BestiaryPresentationPlugin)
+        .add_plugins(EventNoticePresentationPlugin)
+        .add_plugins(DisconnectPresentationPlugin)
+        .add_plugins(CombatLogPresentationPlugin)
+        .add_plugins(ChatPresentationPlugin)
+        .add_plugins(EmoteWheelPresentationPlugin)
+        .add_plugins(MuHelperPlugin)
+        .add_plugins(AreaTargetingPlugin)
+        .add_plugins(EmotesPlugin)
+        .add_plugins(TownPropsPlugin)
+        .add_plugins(InteractionHoverPlugin)
+        .add_plugins(WorldDoorsPlugin)
+        .add_plugins(HelperPresentationPlugin)
+        .add_plugins(HintPresentationPlugin)
+        .add_plugins(NameplatePresentationPlugin)
+        .add_plugins(InteractionPromptPresentationPlugin)
+        .add_plugins(MapTransferPresentationPlugin)
+        .add_plugins(UiSoundPresentationPlugin)
+        .add_plugins(NetworkDebugPlugin)
+        .insert_state(initial_state)
         .add_plugins(SceneControllerPlugin::<LoginScene>::default())
         .add_plugins(SceneControllerPlugin::<GameplayScene>::default());
 
@@ -4,15 +4,16 @@ use bevy::prelude::*;
 use bevy::window::WindowResolution;
 use bevy_egui::EguiPlugin;
 
-use crate::infra::assets::configure_asset_resolver;
+use crate::infra::assets::{AssetVariant, configure_asset_resolver};
 use crate::legacy_additive::LegacyAdditiveMaterial;
+use crate::scene_runtime::systems::ColorProfilePlugin;
 
 pub fn configure_object_animation_viewer_app(
     app: &mut App,
     asset_root: String,
-    use_remaster_assets: bool,
+    asset_variant: AssetVariant,
 ) {
-    configure_asset_resolver(asset_root.clone(), use_remaster_assets);
+    configure_asset_resolver(asset_root.clone(), asset_variant);
 
     app.add_plugins(
         DefaultPlugins
@@ -31,7 +32,8 @@ pub fn configure_object_animation_viewer_app(
             }),
     )
     .add_plugins(MaterialPlugin::<LegacyAdditiveMaterial>::default())
-    .add_plugins(EguiPlugin::default());
+    .add_plugins(EguiPlugin::default())
+    .add_plugins(ColorProfilePlugin);
 
     #[cfg(feature = "solari")]
     app.add_plugins(bevy::solari::SolariPlugins);
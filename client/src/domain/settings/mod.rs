@@ -1,5 +1,8 @@
 pub use crate::settings::{
-    AudioSettings, FpsLimitSetting, GameSettings, GraphicsSettings, RenderDistanceSetting,
-    ResolutionSetting, SettingsIoError, SettingsPlugin, SettingsResource, ShadowQualitySetting,
-    WindowModeSetting,
+    AccessibilitySettings, AudioSettings, BackgroundSettings, CameraSettings, ColorProfileSetting,
+    ColorblindModeSetting, DiagnosticsSettings, FpsLimitSetting, GameSettings, GpuBackendSetting,
+    GraphicsSettings, HelperSettings, HudElement, HudElementLayout, HudSettings,
+    LogVerbositySetting, PickupRules, RenderDistanceSetting, ResolutionSetting, SettingsIoError,
+    SettingsPlugin, SettingsResource, SettingsSyncPlugin, ShadowQualitySetting, SoundCategory,
+    SyncSettings, TextureFilteringSetting, UiFontSetting, WindowModeSetting,
 };
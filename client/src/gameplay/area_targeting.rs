@@ -0,0 +1,458 @@
+//! Ground-targeted area skills.
+//!
+//! An area skill hotkey starts aiming: a decal the size of the skill radius
+//! follows the tile under the cursor, a left click sends `UseSkill` at that
+//! tile and Escape cancels. The decal turns red over tiles out of range or
+//! blocked in the collision grid (NoMove), where a click does nothing.
+
+use crate::AppState;
+use crate::bevy_compat::*;
+use crate::infra::input::{BufferedInput, InputGate, ReplayedInput};
+use crate::infra::network::{SendClientMessage, ServerMessageReceived};
+use crate::scene_runtime::collision::WorldCollision;
+use crate::scene_runtime::components::RuntimeSceneEntity;
+use crate::scene_runtime::terrain_height::TerrainHeightField;
+use crate::scene_runtime::world_coordinates::mirror_map_xz_with_axis;
+use bevy::light::{NotShadowCaster, NotShadowReceiver};
+use bevy::prelude::*;
+use bevy::state::prelude::{OnExit, in_state};
+use bevy::window::PrimaryWindow;
+use bevy_egui::input::EguiWantsInput;
+use common::collision::{CollisionGrid, TERRAIN_SIZE};
+use protocol::message::EntityDelta;
+use protocol::{ClientMessage, ServerMessage, UseSkillInput};
+
+/// Height of the decal above the terrain, against z-fighting.
+const DECAL_Y_OFFSET: f32 = 3.0;
+const VALID_DECAL_COLOR: Color = Color::srgba(0.3, 0.9, 0.4, 0.35);
+const INVALID_DECAL_COLOR: Color = Color::srgba(0.95, 0.2, 0.15, 0.35);
+/// How far the cursor ray looks for the ground; past any map corner.
+const CURSOR_RAY_MAX_DISTANCE: f32 = 40_000.0;
+
+type Tile = (u16, u16);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AreaSkill {
+    pub skill_id: u16,
+    pub hotkey: KeyCode,
+    /// Farthest tile, from the player, the skill can be cast on.
+    pub range_tiles: u16,
+    /// Tiles hit around the target tile.
+    pub radius_tiles: u16,
+}
+
+pub const AREA_SKILLS: [AreaSkill; 4] = [
+    // Flame
+    AreaSkill {
+        skill_id: 5,
+        hotkey: KeyCode::KeyQ,
+        range_tiles: 6,
+        radius_tiles: 2,
+    },
+    // Cometfall
+    AreaSkill {
+        skill_id: 13,
+        hotkey: KeyCode::KeyW,
+        range_tiles: 6,
+        radius_tiles: 1,
+    },
+    // Decay
+    AreaSkill {
+        skill_id: 38,
+        hotkey: KeyCode::KeyE,
+        range_tiles: 6,
+        radius_tiles: 2,
+    },
+    // Ice Storm
+    AreaSkill {
+        skill_id: 39,
+        hotkey: KeyCode::KeyR,
+        range_tiles: 6,
+        radius_tiles: 3,
+    },
+];
+
+impl AreaSkill {
+    pub fn for_hotkey(key: KeyCode) -> Option<Self> {
+        AREA_SKILLS.into_iter().find(|skill| skill.hotkey == key)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetValidity {
+    Valid,
+    OutOfRange,
+    /// NoMove tile: nothing can stand or land there.
+    Blocked,
+}
+
+#[derive(Resource, Default)]
+pub struct AreaTargeting {
+    aiming: Option<AreaSkill>,
+    /// Tile under the cursor; `None` while the cursor is off the terrain.
+    pub cursor: Option<Tile>,
+    entity_id: Option<u32>,
+    position: Option<Tile>,
+    client_tick: u32,
+}
+
+impl AreaTargeting {
+    /// Skill being aimed, if any.
+    pub fn aiming(&self) -> Option<AreaSkill> {
+        self.aiming
+    }
+
+    pub fn select(&mut self, skill: AreaSkill) {
+        self.aiming = Some(skill);
+    }
+
+    /// Stops aiming; returns whether a skill was being aimed.
+    pub fn cancel(&mut self) -> bool {
+        self.aiming.take().is_some()
+    }
+
+    fn enter_map(&mut self, entity_id: u32, tile: Tile) {
+        self.entity_id = Some(entity_id);
+        self.position = Some(tile);
+        self.cursor = None;
+        self.aiming = None;
+    }
+
+    fn apply_delta(&mut self, delta: &EntityDelta) {
+        if Some(delta.entity_id) == self.entity_id {
+            self.position = Some((delta.x, delta.y));
+        }
+    }
+
+    pub fn validity(&self, tile: Tile, grid: &CollisionGrid) -> TargetValidity {
+        let in_range = match (self.aiming, self.position) {
+            (Some(skill), Some(position)) => tile_distance(tile, position) <= skill.range_tiles,
+            _ => false,
+        };
+        if !in_range {
+            TargetValidity::OutOfRange
+        } else if grid.is_blocked(tile.0, tile.1) {
+            TargetValidity::Blocked
+        } else {
+            TargetValidity::Valid
+        }
+    }
+
+    /// Casts the aimed skill at `tile` and stops aiming. Invalid tiles keep
+    /// the skill aimed and send nothing.
+    pub fn confirm(&mut self, tile: Tile, grid: &CollisionGrid) -> Option<ClientMessage> {
+        if self.validity(tile, grid) != TargetValidity::Valid {
+            return None;
+        }
+        let skill = self.aiming.take()?;
+        self.client_tick = self.client_tick.wrapping_add(1);
+        Some(ClientMessage::UseSkill(UseSkillInput {
+            client_tick: self.client_tick,
+            skill_id: skill.skill_id,
+            target_entity_id: None,
+            target_x: tile.0,
+            target_y: tile.1,
+        }))
+    }
+}
+
+fn tile_distance(a: Tile, b: Tile) -> u16 {
+    a.0.abs_diff(b.0).max(a.1.abs_diff(b.1))
+}
+
+/// Decal under the cursor while aiming.
+#[derive(Component)]
+struct AreaTargetDecal {
+    valid: Handle<StandardMaterial>,
+    invalid: Handle<StandardMaterial>,
+}
+
+/// Map tile under a world position.
+fn tile_at(terrain: &TerrainHeightField, world: Vec3) -> Tile {
+    let (map_max_x, map_max_z) = terrain.map_max();
+    let (x, z) = mirror_map_xz_with_axis(
+        world.x,
+        world.z,
+        map_max_x,
+        map_max_z,
+        terrain.mirror_axis(),
+    );
+    let max = f32::from(TERRAIN_SIZE - 1);
+    let index = |value: f32| (value / terrain.cell_size()).floor().clamp(0.0, max) as u16;
+    (index(x), index(z))
+}
+
+/// Centre of `tile`, on the ground.
+fn tile_center(terrain: &TerrainHeightField, tile: Tile) -> Vec3 {
+    let (map_max_x, map_max_z) = terrain.map_max();
+    let (x, z) = mirror_map_xz_with_axis(
+        (f32::from(tile.0) + 0.5) * terrain.cell_size(),
+        (f32::from(tile.1) + 0.5) * terrain.cell_size(),
+        map_max_x,
+        map_max_z,
+        terrain.mirror_axis(),
+    );
+    Vec3::new(x, terrain.height_at(x, z) + DECAL_Y_OFFSET, z)
+}
+
+pub struct AreaTargetingPlugin;
+
+impl Plugin for AreaTargetingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AreaTargeting>()
+            .add_systems(OnExit(AppState::Gameplay), reset_area_targeting)
+            .add_systems(
+                Update,
+                (
+                    apply_targeting_messages,
+                    // After the settings modal, which leaves Escape to an aimed skill.
+                    handle_targeting_keys.after(crate::ui::toggle_settings_modal_with_escape),
+                    aim_area_skill,
+                    confirm_area_skill,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Gameplay)),
+            );
+    }
+}
+
+fn reset_area_targeting(
+    mut targeting: ResMut<AreaTargeting>,
+    mut decals: Query<&mut Visibility, With<AreaTargetDecal>>,
+) {
+    *targeting = AreaTargeting::default();
+    for mut visibility in &mut decals {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn apply_targeting_messages(
+    mut targeting: ResMut<AreaTargeting>,
+    mut incoming: MessageReader<ServerMessageReceived>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        match message {
+            ServerMessage::EnterMap {
+                entity_id, x, y, ..
+            } => targeting.enter_map(*entity_id, (*x, *y)),
+            ServerMessage::StateDelta { entities, .. } => {
+                for delta in entities {
+                    targeting.apply_delta(delta);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn handle_targeting_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    egui_wants_input: Option<Res<EguiWantsInput>>,
+    gate: Res<InputGate>,
+    mut replayed: MessageReader<ReplayedInput>,
+    mut targeting: ResMut<AreaTargeting>,
+) {
+    let replayed_keys: Vec<KeyCode> = replayed
+        .read()
+        .filter_map(|ReplayedInput(input)| match input {
+            BufferedInput::Key(key) => Some(*key),
+            _ => None,
+        })
+        .collect();
+    if gate.is_held() || egui_wants_input.is_some_and(|egui| egui.wants_any_keyboard_input()) {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        targeting.cancel();
+        return;
+    }
+    let pressed = keys.get_just_pressed().copied().chain(replayed_keys);
+    if let Some(skill) = pressed.filter_map(AreaSkill::for_hotkey).last() {
+        targeting.select(skill);
+    }
+}
+
+fn aim_area_skill(
+    mut commands: Commands,
+    mut targeting: ResMut<AreaTargeting>,
+    terrain: Option<Res<TerrainHeightField>>,
+    collision: Option<Res<WorldCollision>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut decals: Query<(
+        &AreaTargetDecal,
+        &mut Transform,
+        &mut Visibility,
+        &mut MeshMaterial3d<StandardMaterial>,
+    )>,
+) {
+    targeting.cursor = None;
+    let aimed = targeting.aiming().and_then(|skill| {
+        let terrain = terrain.as_deref()?;
+        let cursor = windows.single().ok()?.cursor_position()?;
+        let (camera, camera_transform) = cameras.single().ok()?;
+        let ray = camera.viewport_to_world(camera_transform, cursor).ok()?;
+        let tile = tile_at(terrain, terrain.raycast(ray, CURSOR_RAY_MAX_DISTANCE)?);
+        let radius = (f32::from(skill.radius_tiles) + 0.5) * terrain.cell_size();
+        Some((tile, tile_center(terrain, tile), radius))
+    });
+
+    let Some((tile, center, radius)) = aimed else {
+        for (_, _, mut visibility, _) in &mut decals {
+            *visibility = Visibility::Hidden;
+        }
+        return;
+    };
+    targeting.cursor = Some(tile);
+    let valid = collision.as_ref().is_some_and(|collision| {
+        targeting.validity(tile, collision.grid()) == TargetValidity::Valid
+    });
+
+    let transform = Transform::from_translation(center)
+        .with_rotation(Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2))
+        .with_scale(Vec3::new(radius, radius, 1.0));
+    if decals.is_empty() {
+        spawn_decal(&mut commands, &mut meshes, &mut materials, transform, valid);
+        return;
+    }
+    for (decal, mut decal_transform, mut visibility, mut material) in &mut decals {
+        *decal_transform = transform;
+        *visibility = Visibility::Visible;
+        material.0 = if valid {
+            decal.valid.clone()
+        } else {
+            decal.invalid.clone()
+        };
+    }
+}
+
+fn spawn_decal(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    transform: Transform,
+    valid: bool,
+) {
+    let mut decal_material = |color: Color| {
+        materials.add(StandardMaterial {
+            base_color: color,
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            cull_mode: None,
+            ..default()
+        })
+    };
+    let decal = AreaTargetDecal {
+        valid: decal_material(VALID_DECAL_COLOR),
+        invalid: decal_material(INVALID_DECAL_COLOR),
+    };
+    let material = if valid {
+        decal.valid.clone()
+    } else {
+        decal.invalid.clone()
+    };
+
+    commands.spawn((
+        RuntimeSceneEntity,
+        decal,
+        NotShadowCaster,
+        NotShadowReceiver,
+        PbrBundle {
+            mesh: Mesh3d(meshes.add(Mesh::from(Circle::new(1.0)))),
+            material: MeshMaterial3d(material),
+            transform,
+            ..default()
+        },
+    ));
+}
+
+fn confirm_area_skill(
+    mouse: Res<ButtonInput<MouseButton>>,
+    egui_wants_input: Option<Res<EguiWantsInput>>,
+    collision: Option<Res<WorldCollision>>,
+    mut targeting: ResMut<AreaTargeting>,
+    mut outgoing: MessageWriter<SendClientMessage>,
+) {
+    if !mouse.just_pressed(MouseButton::Left)
+        || egui_wants_input.is_some_and(|egui| egui.wants_any_pointer_input())
+    {
+        return;
+    }
+    let (Some(tile), Some(collision)) = (targeting.cursor, collision) else {
+        return;
+    };
+    if let Some(input) = targeting.confirm(tile, collision.grid()) {
+        outgoing.write(SendClientMessage(input));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aiming_from(position: Tile, hotkey: KeyCode) -> AreaTargeting {
+        let mut targeting = AreaTargeting::default();
+        targeting.enter_map(7, position);
+        targeting.select(AreaSkill::for_hotkey(hotkey).unwrap());
+        targeting
+    }
+
+    #[test]
+    fn hotkeys_select_area_skills_and_escape_cancels() {
+        assert_eq!(AreaSkill::for_hotkey(KeyCode::Digit1), None);
+        let mut targeting = aiming_from((100, 100), KeyCode::KeyR);
+        assert_eq!(targeting.aiming().map(|skill| skill.skill_id), Some(39));
+
+        assert!(targeting.cancel());
+        assert!(!targeting.cancel());
+        assert_eq!(targeting.aiming(), None);
+    }
+
+    #[test]
+    fn out_of_range_and_blocked_tiles_are_refused() {
+        let mut grid = CollisionGrid::open();
+        grid.block(104, 100);
+        let mut targeting = aiming_from((100, 100), KeyCode::KeyQ);
+
+        assert_eq!(
+            targeting.validity((107, 100), &grid),
+            TargetValidity::OutOfRange
+        );
+        assert_eq!(
+            targeting.validity((104, 100), &grid),
+            TargetValidity::Blocked
+        );
+        assert_eq!(targeting.confirm((104, 100), &grid), None);
+        // A refused click keeps the skill aimed.
+        assert!(targeting.aiming().is_some());
+
+        // The player walked closer.
+        targeting.apply_delta(&EntityDelta {
+            entity_id: 7,
+            x: 102,
+            y: 100,
+            height: 0,
+            hp: 100,
+            state_flags: 0,
+        });
+        assert_eq!(targeting.validity((107, 100), &grid), TargetValidity::Valid);
+    }
+
+    #[test]
+    fn confirm_casts_at_the_tile_and_stops_aiming() {
+        let grid = CollisionGrid::open();
+        let mut targeting = aiming_from((100, 100), KeyCode::KeyW);
+
+        let Some(ClientMessage::UseSkill(input)) = targeting.confirm((103, 98), &grid) else {
+            panic!("expected a cast");
+        };
+        assert_eq!(input.skill_id, 13);
+        assert_eq!(input.target_entity_id, None);
+        assert_eq!((input.target_x, input.target_y), (103, 98));
+        assert_eq!(targeting.aiming(), None);
+        assert_eq!(targeting.confirm((103, 98), &grid), None);
+    }
+}
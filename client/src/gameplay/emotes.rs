@@ -0,0 +1,316 @@
+//! Social emotes: greetings, claps, sitting and the like.
+//!
+//! The local character plays an emote asked for through `EmoteRequest` (emote
+//! wheel, `/emote` in chat, town props) and sends it as `ClientMessage::Emote`;
+//! the server relays it to the other players on the map as `EntityEmote`.
+//! Emotes with a female variant pick it from the character's body type.
+
+use crate::AppState;
+use crate::character::movement::config_helpers::{idle_action_for_class, idle_playback_speed};
+use crate::character::{
+    BodyType, CharacterAnimState, CharacterClass, CharacterController, CharacterState,
+    MovementRoute, PlayerAction, RemotePathFollower,
+};
+use crate::infra::network::{SendClientMessage, ServerMessageReceived};
+use crate::scene_runtime::components::{InteractiveProp, PropInteraction};
+use bevy::prelude::*;
+use bevy::state::prelude::in_state;
+use protocol::{ClientMessage, Emote, ServerMessage};
+
+/// Remote players sitting this close to a seat are placed on it.
+const SEAT_SNAP_DISTANCE: f32 = 150.0;
+const EMOTE_PLAYBACK_SPEED: f32 = 0.25;
+/// How long an emote plays before the character goes back to idle.
+const EMOTE_SECS: f32 = 2.5;
+
+/// Asks the local character to play an emote and show it to other players.
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmoteRequest(pub Emote);
+
+/// Emote a character is playing. Sitting lasts until the character moves;
+/// every other emote goes back to idle on its own.
+#[derive(Component, Debug)]
+pub struct PlayingEmote {
+    pub emote: Emote,
+    remaining: Option<Timer>,
+}
+
+impl PlayingEmote {
+    fn new(emote: Emote) -> Self {
+        let remaining =
+            (emote != Emote::Sit).then(|| Timer::from_seconds(EMOTE_SECS, TimerMode::Once));
+        Self { emote, remaining }
+    }
+}
+
+/// Character animation for `emote`.
+pub fn emote_action(emote: Emote, class: CharacterClass) -> PlayerAction {
+    let female = class.body_type() == BodyType::Elf;
+    let (male_action, female_action) = match emote {
+        Emote::Sit => (PlayerAction::Sit1, PlayerAction::SitFemale1),
+        Emote::Greeting => (PlayerAction::Greeting1, PlayerAction::GreetingFemale1),
+        Emote::Goodbye => (PlayerAction::Goodbye1, PlayerAction::GoodbyeFemale1),
+        Emote::Clap => (PlayerAction::Clap1, PlayerAction::ClapFemale1),
+        Emote::Cheer => (PlayerAction::Cheer1, PlayerAction::CheerFemale1),
+        Emote::Direction => (PlayerAction::Direction1, PlayerAction::DirectionFemale1),
+        Emote::Gesture => (PlayerAction::Gesture1, PlayerAction::GestureFemale1),
+        Emote::Cry => (PlayerAction::Cry1, PlayerAction::CryFemale1),
+        Emote::Awkward => (PlayerAction::Awkward1, PlayerAction::AwkwardFemale1),
+        Emote::See => (PlayerAction::See1, PlayerAction::SeeFemale1),
+        Emote::Win => (PlayerAction::Win1, PlayerAction::WinFemale1),
+        Emote::Smile => (PlayerAction::Smile1, PlayerAction::SmileFemale1),
+        Emote::Sleep => (PlayerAction::Sleep1, PlayerAction::SleepFemale1),
+        Emote::Cold => (PlayerAction::Cold1, PlayerAction::ColdFemale1),
+        Emote::Again => (PlayerAction::Again1, PlayerAction::AgainFemale1),
+        // No female variant in the animation set.
+        Emote::Drink => return PlayerAction::Cheers,
+        Emote::Respect => return PlayerAction::Respect1,
+        Emote::Salute => return PlayerAction::Salute1,
+        Emote::Provocation => return PlayerAction::Provocation,
+        Emote::LookAround => return PlayerAction::LookAround,
+    };
+    if female { female_action } else { male_action }
+}
+
+/// Name typed after `/emote`.
+pub fn emote_name(emote: Emote) -> &'static str {
+    match emote {
+        Emote::Sit => "sit",
+        Emote::Drink => "drink",
+        Emote::Greeting => "greeting",
+        Emote::Goodbye => "goodbye",
+        Emote::Clap => "clap",
+        Emote::Cheer => "cheer",
+        Emote::Direction => "direction",
+        Emote::Gesture => "gesture",
+        Emote::Cry => "cry",
+        Emote::Awkward => "awkward",
+        Emote::See => "see",
+        Emote::Win => "win",
+        Emote::Smile => "smile",
+        Emote::Sleep => "sleep",
+        Emote::Cold => "cold",
+        Emote::Again => "again",
+        Emote::Respect => "respect",
+        Emote::Salute => "salute",
+        Emote::Provocation => "provocation",
+        Emote::LookAround => "look_around",
+    }
+}
+
+pub fn emote_label(emote: Emote) -> &'static str {
+    match emote {
+        Emote::Sit => "Sentar",
+        Emote::Drink => "Beber",
+        Emote::Greeting => "Saudar",
+        Emote::Goodbye => "Tchau",
+        Emote::Clap => "Aplaudir",
+        Emote::Cheer => "Comemorar",
+        Emote::Direction => "Apontar",
+        Emote::Gesture => "Gesticular",
+        Emote::Cry => "Chorar",
+        Emote::Awkward => "Sem graca",
+        Emote::See => "Olhar",
+        Emote::Win => "Vitoria",
+        Emote::Smile => "Sorrir",
+        Emote::Sleep => "Dormir",
+        Emote::Cold => "Frio",
+        Emote::Again => "De novo",
+        Emote::Respect => "Respeito",
+        Emote::Salute => "Continencia",
+        Emote::Provocation => "Provocar",
+        Emote::LookAround => "Olhar em volta",
+    }
+}
+
+/// Emote by its `/emote` name or its label, ignoring case.
+pub fn find_emote(name: &str) -> Option<Emote> {
+    let name = name.trim();
+    Emote::ALL.into_iter().find(|emote| {
+        emote_name(*emote).eq_ignore_ascii_case(name)
+            || emote_label(*emote).eq_ignore_ascii_case(name)
+    })
+}
+
+pub(crate) fn yaw_of(rotation: Quat) -> Quat {
+    let (yaw, _, _) = rotation.to_euler(EulerRot::YXZ);
+    Quat::from_rotation_y(yaw)
+}
+
+pub fn play_emote(
+    commands: &mut Commands,
+    character: Entity,
+    class: CharacterClass,
+    anim_state: &mut CharacterAnimState,
+    emote: Emote,
+) {
+    anim_state.current_action = emote_action(emote, class).index();
+    anim_state.playback_speed = EMOTE_PLAYBACK_SPEED;
+    commands.entity(character).insert(PlayingEmote::new(emote));
+}
+
+pub struct EmotesPlugin;
+
+impl Plugin for EmotesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<EmoteRequest>().add_systems(
+            Update,
+            (play_requested_emotes, apply_entity_emotes, finish_emotes)
+                .chain()
+                .run_if(in_state(AppState::Gameplay)),
+        );
+    }
+}
+
+/// Plays requested emotes on the local character while it stands still.
+fn play_requested_emotes(
+    mut commands: Commands,
+    mut requests: MessageReader<EmoteRequest>,
+    mut players: Query<
+        (Entity, &CharacterController, &mut CharacterAnimState),
+        (With<MovementRoute>, Without<RemotePathFollower>),
+    >,
+    mut outgoing: MessageWriter<SendClientMessage>,
+) {
+    let Some(EmoteRequest(emote)) = requests.read().last().copied() else {
+        return;
+    };
+    let Ok((player, controller, mut anim_state)) = players.single_mut() else {
+        return;
+    };
+    if !matches!(controller.state, CharacterState::Idle) {
+        return;
+    }
+    play_emote(
+        &mut commands,
+        player,
+        controller.class,
+        &mut anim_state,
+        emote,
+    );
+    outgoing.write(SendClientMessage(ClientMessage::Emote { emote }));
+}
+
+/// Plays emotes of other players; sitting ones are placed on the nearest seat.
+fn apply_entity_emotes(
+    mut commands: Commands,
+    mut incoming: MessageReader<ServerMessageReceived>,
+    seats: Query<(&InteractiveProp, &Transform), Without<RemotePathFollower>>,
+    mut remotes: Query<(
+        Entity,
+        &RemotePathFollower,
+        &CharacterController,
+        &mut Transform,
+        &mut CharacterAnimState,
+    )>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        let ServerMessage::EntityEmote { entity_id, emote } = message else {
+            continue;
+        };
+        let Some((entity, _, controller, mut transform, mut anim_state)) = remotes
+            .iter_mut()
+            .find(|(_, follower, ..)| follower.entity_id == *entity_id)
+        else {
+            continue;
+        };
+
+        if *emote == Emote::Sit {
+            let seat = seats
+                .iter()
+                .filter(|(InteractiveProp(interaction), _)| *interaction == PropInteraction::Seat)
+                .map(|(_, seat)| (seat, seat.translation.distance(transform.translation)))
+                .filter(|(_, distance)| *distance <= SEAT_SNAP_DISTANCE)
+                .min_by(|(_, a), (_, b)| a.total_cmp(b));
+            if let Some((seat, _)) = seat {
+                transform.translation = seat.translation;
+                transform.rotation = yaw_of(seat.rotation);
+            }
+        }
+        play_emote(
+            &mut commands,
+            entity,
+            controller.class,
+            &mut anim_state,
+            *emote,
+        );
+    }
+}
+
+fn finish_emotes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut characters: Query<(
+        Entity,
+        &mut PlayingEmote,
+        &CharacterController,
+        &mut CharacterAnimState,
+    )>,
+) {
+    for (entity, mut playing, controller, mut anim_state) in &mut characters {
+        // Movement already switched the animation.
+        if !matches!(controller.state, CharacterState::Idle) {
+            commands.entity(entity).remove::<PlayingEmote>();
+            continue;
+        }
+        let finished = playing
+            .remaining
+            .as_mut()
+            .is_some_and(|timer| timer.tick(time.delta()).is_finished());
+        if finished {
+            anim_state.current_action = idle_action_for_class(controller.class);
+            anim_state.playback_speed = idle_playback_speed(controller.class);
+            commands.entity(entity).remove::<PlayingEmote>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn female_variants_follow_the_body_type() {
+        assert_eq!(
+            emote_action(Emote::Sit, CharacterClass::DarkKnight),
+            PlayerAction::Sit1
+        );
+        assert_eq!(
+            emote_action(Emote::Sit, CharacterClass::FairyElf),
+            PlayerAction::SitFemale1
+        );
+        assert_eq!(
+            emote_action(Emote::Clap, CharacterClass::Summoner),
+            PlayerAction::ClapFemale1
+        );
+        assert_eq!(
+            emote_action(Emote::Clap, CharacterClass::RageFighter),
+            PlayerAction::Clap1
+        );
+        // Emotes without a female variant play the same for everyone.
+        assert_eq!(
+            emote_action(Emote::Salute, CharacterClass::FairyElf),
+            PlayerAction::Salute1
+        );
+        assert_eq!(
+            emote_action(Emote::Drink, CharacterClass::Summoner),
+            PlayerAction::Cheers
+        );
+    }
+
+    #[test]
+    fn emotes_are_found_by_name_or_label() {
+        assert_eq!(find_emote("clap"), Some(Emote::Clap));
+        assert_eq!(find_emote(" Look_Around "), Some(Emote::LookAround));
+        assert_eq!(find_emote("aplaudir"), Some(Emote::Clap));
+        assert_eq!(find_emote("sem graca"), Some(Emote::Awkward));
+        assert_eq!(find_emote("dance"), None);
+    }
+
+    #[test]
+    fn only_sitting_lasts_until_the_character_moves() {
+        assert!(PlayingEmote::new(Emote::Sit).remaining.is_none());
+        assert!(PlayingEmote::new(Emote::Cry).remaining.is_some());
+        assert!(PlayingEmote::new(Emote::Drink).remaining.is_some());
+    }
+}
@@ -0,0 +1,349 @@
+//! MU Helper (auto-hunt).
+//!
+//! Hunts the monsters around the tile where it was switched on, casting the
+//! configured skill rotation. It only sends the inputs a player would (`Move`
+//! and `UseSkill`), and only after the server accepted `SetHelperActive`: the
+//! world may deny the helper, and the session is marked while it runs.
+
+use std::collections::HashMap;
+
+use crate::AppState;
+use crate::domain::settings::{HelperSettings, SettingsResource};
+use crate::infra::network::{SendClientMessage, ServerMessageReceived};
+use bevy::prelude::*;
+use bevy::state::prelude::{OnExit, in_state};
+use protocol::message::EntityDelta;
+use protocol::{ClientMessage, MoveInput, ServerMessage, UseSkillInput};
+
+/// Skill sent when the rotation is empty.
+const PLAIN_ATTACK_SKILL_ID: u16 = 0;
+/// Tiles from which a monster can be hit without walking closer.
+const ATTACK_REACH_TILES: u16 = 2;
+
+type Tile = (u16, u16);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HelperAction {
+    Walk {
+        x: u16,
+        y: u16,
+    },
+    Attack {
+        skill_id: u16,
+        target_entity_id: u32,
+        x: u16,
+        y: u16,
+    },
+}
+
+#[derive(Resource, Default)]
+pub struct MuHelper {
+    /// The player wants the helper running.
+    pub requested: bool,
+    /// World policy from the last server answer; `None` until the first one.
+    pub allowed: Option<bool>,
+    /// The server marked the session and the helper is hunting.
+    pub active: bool,
+    entity_id: Option<u32>,
+    position: Option<Tile>,
+    anchor: Option<Tile>,
+    monsters: HashMap<u32, Tile>,
+    rotation_index: usize,
+    next_action_at: f32,
+    client_tick: u32,
+}
+
+impl MuHelper {
+    /// Records the player's choice and returns the request for the server.
+    pub fn request(&mut self, active: bool) -> ClientMessage {
+        self.requested = active;
+        if !active {
+            self.active = false;
+        }
+        ClientMessage::SetHelperActive { active }
+    }
+
+    fn enter_map(&mut self, entity_id: u32, tile: Tile) {
+        self.entity_id = Some(entity_id);
+        self.position = Some(tile);
+        self.monsters.clear();
+        // The server ends the helper with the old map; it must be asked again.
+        self.active = false;
+    }
+
+    fn apply_status(&mut self, allowed: bool, active: bool) {
+        self.allowed = Some(allowed);
+        self.active = active;
+        self.requested = active;
+        if active {
+            self.anchor = self.position;
+            self.rotation_index = 0;
+        }
+    }
+
+    fn apply_delta(&mut self, delta: &EntityDelta) {
+        let tile = (delta.x, delta.y);
+        if Some(delta.entity_id) == self.entity_id {
+            self.position = Some(tile);
+        } else if delta.state_flags & EntityDelta::FLAG_MONSTER != 0 {
+            if delta.hp == 0 {
+                self.monsters.remove(&delta.entity_id);
+            } else {
+                self.monsters.insert(delta.entity_id, tile);
+            }
+        }
+    }
+
+    /// Attacks the closest monster in range of the anchor, walking up to it
+    /// first; walks back to the anchor once nothing is left to hunt.
+    pub fn next_action(&mut self, settings: &HelperSettings) -> Option<HelperAction> {
+        let position = self.position?;
+        let anchor = self.anchor?;
+        let range = settings.hunt_range();
+
+        let target = self
+            .monsters
+            .iter()
+            .filter(|(_, tile)| tile_distance(**tile, anchor) <= range)
+            .min_by_key(|(entity_id, tile)| (tile_distance(**tile, position), **entity_id));
+
+        match target {
+            Some((&entity_id, &(x, y)))
+                if tile_distance((x, y), position) <= ATTACK_REACH_TILES =>
+            {
+                let rotation = &settings.skill_rotation;
+                let skill_id = if rotation.is_empty() {
+                    PLAIN_ATTACK_SKILL_ID
+                } else {
+                    rotation[self.rotation_index % rotation.len()]
+                };
+                self.rotation_index = self.rotation_index.wrapping_add(1);
+                Some(HelperAction::Attack {
+                    skill_id,
+                    target_entity_id: entity_id,
+                    x,
+                    y,
+                })
+            }
+            Some((_, &(x, y))) => Some(HelperAction::Walk { x, y }),
+            None if position != anchor => Some(HelperAction::Walk {
+                x: anchor.0,
+                y: anchor.1,
+            }),
+            None => None,
+        }
+    }
+
+    fn input_for(&mut self, action: HelperAction) -> ClientMessage {
+        self.client_tick = self.client_tick.wrapping_add(1);
+        match action {
+            HelperAction::Walk { x, y } => ClientMessage::Move(MoveInput {
+                client_tick: self.client_tick,
+                x,
+                y,
+                // The server routes the walk itself.
+                direction: 0,
+                path: [0; 8],
+            }),
+            HelperAction::Attack {
+                skill_id,
+                target_entity_id,
+                x,
+                y,
+            } => ClientMessage::UseSkill(UseSkillInput {
+                client_tick: self.client_tick,
+                skill_id,
+                target_entity_id: Some(target_entity_id),
+                target_x: x,
+                target_y: y,
+            }),
+        }
+    }
+}
+
+fn tile_distance(a: Tile, b: Tile) -> u16 {
+    a.0.abs_diff(b.0).max(a.1.abs_diff(b.1))
+}
+
+pub struct MuHelperPlugin;
+
+impl Plugin for MuHelperPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MuHelper>()
+            .add_systems(OnExit(AppState::Gameplay), reset_helper)
+            .add_systems(
+                Update,
+                (apply_helper_messages, run_helper)
+                    .chain()
+                    .run_if(in_state(AppState::Gameplay)),
+            );
+    }
+}
+
+fn reset_helper(mut helper: ResMut<MuHelper>) {
+    *helper = MuHelper::default();
+}
+
+fn apply_helper_messages(
+    mut helper: ResMut<MuHelper>,
+    mut incoming: MessageReader<ServerMessageReceived>,
+    mut outgoing: MessageWriter<SendClientMessage>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        match message {
+            ServerMessage::EnterMap {
+                entity_id, x, y, ..
+            } => {
+                helper.enter_map(*entity_id, (*x, *y));
+                if helper.requested {
+                    outgoing.write(SendClientMessage(helper.request(true)));
+                }
+            }
+            ServerMessage::HelperStatus { allowed, active } => {
+                helper.apply_status(*allowed, *active);
+            }
+            ServerMessage::StateDelta { entities, .. } => {
+                for delta in entities {
+                    helper.apply_delta(delta);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn run_helper(
+    time: Res<Time>,
+    settings: Res<SettingsResource>,
+    mut helper: ResMut<MuHelper>,
+    mut outgoing: MessageWriter<SendClientMessage>,
+) {
+    let now = time.elapsed_secs();
+    if !helper.active || now < helper.next_action_at {
+        return;
+    }
+
+    let settings = &settings.current.helper;
+    if let Some(action) = helper.next_action(settings) {
+        let input = helper.input_for(action);
+        outgoing.write(SendClientMessage(input));
+        helper.next_action_at = now + settings.attack_interval_secs();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::settings::PickupRules;
+    use protocol::{ItemInstance, ItemOptions};
+
+    fn monster(entity_id: u32, x: u16, y: u16, hp: u16) -> EntityDelta {
+        EntityDelta {
+            entity_id,
+            x,
+            y,
+            height: 0,
+            hp,
+            state_flags: EntityDelta::FLAG_MONSTER,
+        }
+    }
+
+    fn hunting_at(tile: Tile) -> MuHelper {
+        let mut helper = MuHelper::default();
+        helper.enter_map(1, tile);
+        helper.request(true);
+        helper.apply_status(true, true);
+        helper
+    }
+
+    #[test]
+    fn hunts_the_closest_monster_in_range_with_the_rotation() {
+        let settings = HelperSettings {
+            skill_rotation: vec![41, 43],
+            hunt_range_tiles: 5,
+            ..HelperSettings::default()
+        };
+        let mut helper = hunting_at((100, 100));
+        helper.apply_delta(&monster(7, 104, 100, 50));
+        // Out of range of the anchor, even though the player will walk past it.
+        helper.apply_delta(&monster(8, 108, 100, 50));
+        // Players are never targets.
+        helper.apply_delta(&EntityDelta {
+            state_flags: 0,
+            ..monster(9, 101, 100, 50)
+        });
+
+        assert_eq!(
+            helper.next_action(&settings),
+            Some(HelperAction::Walk { x: 104, y: 100 })
+        );
+
+        helper.apply_delta(&EntityDelta {
+            state_flags: 0,
+            ..monster(1, 102, 100, 100)
+        });
+        let skills: Vec<u16> = (0..3)
+            .filter_map(|_| match helper.next_action(&settings) {
+                Some(HelperAction::Attack {
+                    skill_id,
+                    target_entity_id: 7,
+                    ..
+                }) => Some(skill_id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(skills, vec![41, 43, 41]);
+
+        helper.apply_delta(&monster(7, 104, 100, 0));
+        assert_eq!(
+            helper.next_action(&settings),
+            Some(HelperAction::Walk { x: 100, y: 100 })
+        );
+    }
+
+    #[test]
+    fn denied_helper_stays_idle_and_map_changes_ask_again() {
+        let mut helper = MuHelper::default();
+        helper.enter_map(1, (10, 10));
+        helper.request(true);
+        helper.apply_status(false, false);
+        assert!(!helper.active && !helper.requested);
+        assert_eq!(helper.allowed, Some(false));
+
+        let mut helper = hunting_at((10, 10));
+        helper.enter_map(1, (50, 50));
+        assert!(!helper.active && helper.requested);
+    }
+
+    #[test]
+    fn pickup_rules_match_jewels_excellent_and_level() {
+        let item = |group, index, level, excellent| ItemInstance {
+            serial: 0,
+            group,
+            index,
+            level,
+            quantity: 1,
+            options: ItemOptions {
+                excellent,
+                ..ItemOptions::default()
+            },
+            expires_at_ms: None,
+        };
+        let bless = item(14, 13, 0, 0);
+        let excellent_sword = item(0, 3, 0, 0b1);
+        let plus_nine_armor = item(8, 2, 9, 0);
+
+        let rules = PickupRules::default();
+        assert!(rules.wants(&bless) && rules.wants(&excellent_sword));
+        assert!(!rules.wants(&plus_nine_armor));
+
+        let rules = PickupRules {
+            jewels: false,
+            excellent: false,
+            min_level: 9,
+            ..PickupRules::default()
+        };
+        assert!(!rules.wants(&bless) && !rules.wants(&excellent_sword));
+        assert!(rules.wants(&plus_nine_armor));
+    }
+}
@@ -0,0 +1,229 @@
+//! Highlight of the interactive object under the cursor.
+//!
+//! Scene objects with an [`InteractionPrompt`] (town props, NPCs, portals)
+//! get an outline while the cursor hovers them, and the prompt panel draws
+//! their label above them. The outline is a shell over each mesh of the
+//! object, pushed out along the normals and drawn back faces only.
+
+use std::collections::HashSet;
+
+use crate::AppState;
+use crate::gameplay::area_targeting::AreaTargeting;
+use crate::gameplay::town_props::pick_scene_object;
+use crate::infra::input::InputGate;
+use crate::scene_runtime::components::InteractionPrompt;
+use bevy::light::{NotShadowCaster, NotShadowReceiver};
+use bevy::mesh::MeshVertexBufferLayoutRef;
+use bevy::pbr::{MaterialPipeline, MaterialPipelineKey, MaterialPlugin};
+use bevy::prelude::*;
+use bevy::render::render_resource::{
+    AsBindGroup, Face, RenderPipelineDescriptor, ShaderType, SpecializedMeshPipelineError,
+};
+use bevy::shader::ShaderRef;
+use bevy::state::prelude::{OnExit, in_state};
+use bevy::window::PrimaryWindow;
+use bevy_egui::input::EguiWantsInput;
+
+const OUTLINE_COLOR: LinearRgba = LinearRgba::rgb(1.0, 0.82, 0.35);
+/// Thickness of the outline in world units.
+const OUTLINE_WIDTH: f32 = 4.0;
+
+#[derive(Asset, AsBindGroup, TypePath, Debug, Clone)]
+pub struct OutlineMaterial {
+    #[uniform(0)]
+    pub params: OutlineParams,
+}
+
+#[derive(ShaderType, Debug, Clone, Copy)]
+pub struct OutlineParams {
+    pub color: Vec4,
+    pub width: f32,
+    pub _padding: Vec3,
+}
+
+impl Material for OutlineMaterial {
+    fn vertex_shader() -> ShaderRef {
+        "shaders/interaction_outline.wgsl".into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        "shaders/interaction_outline.wgsl".into()
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        // Only the far side of the shell shows, around the object.
+        descriptor.primitive.cull_mode = Some(Face::Front);
+        Ok(())
+    }
+}
+
+/// Interactive object under the cursor, if any.
+#[derive(Resource, Debug, Default, PartialEq, Eq)]
+pub struct HoveredInteractable {
+    entity: Option<Entity>,
+}
+
+impl HoveredInteractable {
+    pub fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+}
+
+/// Outline copy of one mesh of the hovered object.
+#[derive(Component, Debug)]
+struct OutlineShell;
+
+/// Outline shells spawned for the hovered object.
+#[derive(Resource, Debug)]
+struct InteractionOutline {
+    material: Handle<OutlineMaterial>,
+    owner: Option<Entity>,
+    shells: Vec<Entity>,
+    /// Meshes of the owner that already have a shell.
+    outlined: HashSet<Entity>,
+}
+
+impl FromWorld for InteractionOutline {
+    fn from_world(world: &mut World) -> Self {
+        let material = world
+            .resource_mut::<Assets<OutlineMaterial>>()
+            .add(OutlineMaterial {
+                params: OutlineParams {
+                    color: OUTLINE_COLOR.to_vec4(),
+                    width: OUTLINE_WIDTH,
+                    _padding: Vec3::ZERO,
+                },
+            });
+        Self {
+            material,
+            owner: None,
+            shells: Vec::new(),
+            outlined: HashSet::new(),
+        }
+    }
+}
+
+pub struct InteractionHoverPlugin;
+
+impl Plugin for InteractionHoverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MaterialPlugin::<OutlineMaterial>::default())
+            .init_resource::<HoveredInteractable>()
+            .init_resource::<InteractionOutline>()
+            .add_systems(OnExit(AppState::Gameplay), clear_hover)
+            .add_systems(
+                Update,
+                (track_hovered_interactable, outline_hovered_interactable)
+                    .chain()
+                    .run_if(in_state(AppState::Gameplay)),
+            );
+    }
+}
+
+fn clear_hover(mut hovered: ResMut<HoveredInteractable>, mut outline: ResMut<InteractionOutline>) {
+    *hovered = HoveredInteractable::default();
+    // The shells went away with the scene.
+    outline.owner = None;
+    outline.shells.clear();
+    outline.outlined.clear();
+}
+
+fn track_hovered_interactable(
+    mut hovered: ResMut<HoveredInteractable>,
+    egui_wants_input: Option<Res<EguiWantsInput>>,
+    gate: Res<InputGate>,
+    targeting: Res<AreaTargeting>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    interactables: Query<(Entity, &Transform), With<InteractionPrompt>>,
+) {
+    let blocked = gate.is_held()
+        || egui_wants_input.is_some_and(|egui| egui.wants_any_pointer_input())
+        || targeting.aiming().is_some();
+    let entity = windows
+        .single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .filter(|_| !blocked)
+        .zip(cameras.single().ok())
+        .and_then(|(cursor, (camera, camera_transform))| {
+            camera.viewport_to_world(camera_transform, cursor).ok()
+        })
+        .and_then(|ray| pick_scene_object(ray, interactables));
+
+    if hovered.entity != entity {
+        hovered.entity = entity;
+    }
+}
+
+fn outline_hovered_interactable(
+    mut commands: Commands,
+    hovered: Res<HoveredInteractable>,
+    mut outline: ResMut<InteractionOutline>,
+    children: Query<&Children>,
+    meshes: Query<&Mesh3d, Without<OutlineShell>>,
+) {
+    if outline.owner != hovered.entity {
+        for shell in outline.shells.drain(..) {
+            if let Ok(mut shell) = commands.get_entity(shell) {
+                shell.despawn();
+            }
+        }
+        outline.outlined.clear();
+        outline.owner = hovered.entity;
+    }
+    let Some(owner) = outline.owner else {
+        return;
+    };
+
+    // Scenes load after the object spawns, so new meshes are picked up as
+    // they appear.
+    for mesh_entity in std::iter::once(owner).chain(children.iter_descendants(owner)) {
+        let Ok(mesh) = meshes.get(mesh_entity) else {
+            continue;
+        };
+        if !outline.outlined.insert(mesh_entity) {
+            continue;
+        }
+        let shell = commands
+            .spawn((
+                Mesh3d(mesh.0.clone()),
+                MeshMaterial3d(outline.material.clone()),
+                Transform::IDENTITY,
+                OutlineShell,
+                NotShadowCaster,
+                NotShadowReceiver,
+                ChildOf(mesh_entity),
+            ))
+            .id();
+        outline.shells.push(shell);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scene_runtime::scene_loader::{ObjectProperties, PropInteraction};
+
+    #[test]
+    fn prompts_come_from_the_world_data_or_the_interaction() {
+        let mut properties = ObjectProperties::default();
+        assert_eq!(properties.interaction_prompt(), None);
+
+        properties.interaction = Some(PropInteraction::Talk { npc_id: 249 });
+        assert_eq!(
+            properties.interaction_prompt().as_deref(),
+            Some("Conversar")
+        );
+
+        properties.prompt = Some("Entrar na masmorra".to_string());
+        assert_eq!(
+            properties.interaction_prompt().as_deref(),
+            Some("Entrar na masmorra")
+        );
+    }
+}
@@ -1,6 +1,12 @@
 //! Gameplay layer.
 
+pub mod area_targeting;
 pub mod controllers;
+pub mod emotes;
+pub mod helper;
+pub mod interaction_hover;
 pub mod runtime;
 pub mod scenes;
 pub mod systems;
+pub mod town_props;
+pub mod world_doors;
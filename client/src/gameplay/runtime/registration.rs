@@ -1,3 +1,8 @@
+use crate::character::{
+    advance_character_movement, apply_entity_paths, apply_entity_status_effects,
+    apply_gens_factions, apply_guild_relations, apply_monster_affixes, apply_nameplate_damage,
+    follow_movement_routes, follow_remote_paths, track_prediction_error,
+};
 use crate::gameplay::runtime::pipeline::GameplayPipelineSet;
 use crate::gameplay::systems::{camera, lighting, objects, particles, skills, terrain};
 use crate::legacy_additive::LegacyAdditiveMaterial;
@@ -7,11 +12,14 @@ use crate::scene_runtime::components::{
     SceneObjectAnimationInitialized, SceneObjectAnimationSource,
 };
 use crate::scene_runtime::systems::{
-    DynamicLightBudget, GrassMaterial, SceneObjectDistanceCullingConfig,
-    animate_world_56_dark_lord, animate_world_56_flying_monsters,
-    animate_world_56_sky_vortex_objects, animate_world_56_skybox, handle_window_occlusion,
-    initialize_world_56_login_fx, load_scene_runtime_assets, spawn_skybox_when_ready,
-    spawn_world_56_meteors, update_boids, update_world_56_meteors,
+    BackgroundMode, CameraEffectsPlugin, ColorProfilePlugin, DynamicLightBudget, EliteMarkersPlugin,
+    GrassMaterial, SceneObjectDistanceCullingConfig, StatusEffectsPlugin, TextureFilteringPlugin,
+    animate_world_56_dark_lord,
+    animate_world_56_flying_monsters, animate_world_56_sky_vortex_objects, animate_world_56_skybox,
+    apply_background_audio_mute, background_particles_running, initialize_world_56_login_fx,
+    load_scene_runtime_assets, spawn_ambient_sounds_when_ready, spawn_skybox_when_ready,
+    spawn_world_56_meteors, update_ambient_sound_volumes, update_background_mode, update_boids,
+    update_world_56_meteors,
 };
 use bevy::gizmos::config::{DefaultGizmoConfigGroup, GizmoConfigStore};
 use bevy::pbr::MaterialPlugin;
@@ -33,10 +41,16 @@ pub fn register_gameplay_runtime(app: &mut App) {
     app.add_plugins(MaterialPlugin::<GrassMaterial>::default())
         .add_plugins(MaterialPlugin::<LegacyAdditiveMaterial>::default())
         .add_plugins(Material2dPlugin::<LightningSprite2dMaterial>::default())
+        .add_plugins(CameraEffectsPlugin)
+        .add_plugins(StatusEffectsPlugin)
+        .add_plugins(EliteMarkersPlugin)
+        .add_plugins(TextureFilteringPlugin)
+        .add_plugins(ColorProfilePlugin)
         .add_systems(Startup, configure_runtime_gizmos)
         .init_resource::<camera::DebugOverlayState>()
         .init_resource::<camera::DebugSceneStats>()
         .init_resource::<DynamicLightBudget>()
+        .init_resource::<BackgroundMode>()
         .init_resource::<SceneObjectDistanceCullingConfig>()
         .configure_sets(
             Update,
@@ -63,7 +77,9 @@ pub fn register_gameplay_runtime(app: &mut App) {
                 terrain::spawn_terrain_when_ready,
                 terrain::spawn_terrain_grass_when_ready,
                 objects::spawn_scene_objects_when_ready,
+                objects::reload_changed_scene_objects,
                 spawn_skybox_when_ready,
+                spawn_ambient_sounds_when_ready,
                 lighting::spawn_runtime_sun_light,
                 terrain::spawn_boundary_walls_when_ready,
                 camera::setup_camera_tour,
@@ -94,10 +110,29 @@ pub fn register_gameplay_runtime(app: &mut App) {
                 spawn_world_56_meteors,
                 update_world_56_meteors,
                 animate_world_56_dark_lord,
+                update_ambient_sound_volumes,
             )
                 .in_set(GameplayPipelineSet::WorldSimulate)
                 .run_if(runtime_state_is_active),
         )
+        .add_systems(
+            Update,
+            (
+                apply_entity_paths,
+                apply_entity_status_effects,
+                apply_monster_affixes,
+                apply_gens_factions,
+                apply_guild_relations,
+                apply_nameplate_damage,
+                follow_remote_paths,
+                follow_movement_routes,
+                advance_character_movement,
+                track_prediction_error,
+            )
+                .chain()
+                .in_set(GameplayPipelineSet::WorldSimulate)
+                .run_if(runtime_state_is_active),
+        )
         .add_systems(
             Update,
             (
@@ -113,7 +148,7 @@ pub fn register_gameplay_runtime(app: &mut App) {
                 skills::update_lightning_hurt_effects,
                 skills::update_death_stab_lightning_arcs,
                 skills::update_skill_vfx_auto_lifetimes,
-                particles::update_particle_emitters,
+                particles::update_particle_emitters.run_if(background_particles_running),
                 particles::ensure_particle_render_batches,
                 particles::update_particle_render_batches,
                 particles::update_map_vfx_billboard_sprites,
@@ -172,7 +207,10 @@ pub fn register_gameplay_runtime(app: &mut App) {
                 .run_if(|s: Res<camera::DebugOverlayState>| s.visible),
         );
 
-    app.add_systems(Update, handle_window_occlusion);
+    app.add_systems(
+        Update,
+        (update_background_mode, apply_background_audio_mute).chain(),
+    );
 
     if cfg!(debug_assertions) {
         app.init_resource::<camera::DebugFreeCameraController>()
@@ -2,14 +2,18 @@
 
 use crate::gameplay::controllers::scene_controller::{SceneController, SceneId};
 use crate::gameplay::controllers::world_controller;
+use crate::infra::network::ServerMessageReceived;
+use crate::scene_runtime::collision::WorldCollision;
 use crate::scene_runtime::components::{ParticleDefinitions, RuntimeSceneEntity};
 use crate::scene_runtime::state::RuntimeSceneAssets;
 use crate::scene_runtime::systems::ParticleDefinitionsLoader;
-use crate::world::{WorldId, WorldRequest};
+use crate::scene_runtime::terrain_height::TerrainHeightField;
+use crate::world::{CurrentWorld, WorldId, WorldRequest, debug_season};
 use bevy::camera::ClearColorConfig;
 use bevy::prelude::*;
 use bevy::state::prelude::{OnEnter, OnExit};
 use common::WorldMap;
+use protocol::ServerMessage;
 use std::collections::HashMap;
 
 const DEFAULT_GAMEPLAY_WORLD: WorldMap = WorldMap::Lorencia;
@@ -22,6 +26,10 @@ impl SceneController for GameplayScene {
         app.init_asset::<ParticleDefinitions>()
             .init_asset_loader::<ParticleDefinitionsLoader>()
             .add_systems(OnEnter(crate::AppState::Gameplay), setup_gameplay_scene)
+            .add_systems(
+                Update,
+                follow_entered_world.run_if(in_state(crate::AppState::Gameplay)),
+            )
             .add_systems(OnExit(crate::AppState::Gameplay), cleanup_gameplay_scene);
     }
 
@@ -33,12 +41,18 @@ impl SceneController for GameplayScene {
 #[derive(Component)]
 struct GameplaySceneRoot;
 
+/// World entered by the next gameplay scene, taking precedence over
+/// `MU_GAMEPLAY_WORLD`. Used by tooling that drives the scene directly.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct GameplayWorldOverride(pub WorldMap);
+
 fn get_gameplay_world() -> WorldMap {
     match std::env::var("MU_GAMEPLAY_WORLD") {
         Ok(raw_world) => {
             let trimmed = raw_world.trim();
+            let season = debug_season();
             if let Ok(id) = trimmed.parse::<u8>() {
-                if let Some(map) = WorldMap::from_id(id) {
+                if let Some(map) = WorldMap::from_id(id).filter(|map| map.available_in(season)) {
                     info!(
                         "Using gameplay world from MU_GAMEPLAY_WORLD: {} (ID: {})",
                         map.name(),
@@ -49,8 +63,9 @@ fn get_gameplay_world() -> WorldMap {
             }
 
             warn!(
-                "MU_GAMEPLAY_WORLD='{}' is not a valid world ID. Using default {} ({})",
+                "MU_GAMEPLAY_WORLD='{}' is not a valid world ID in {}. Using default {} ({})",
                 raw_world,
+                season,
                 DEFAULT_GAMEPLAY_WORLD.name(),
                 DEFAULT_GAMEPLAY_WORLD as u8
             );
@@ -60,6 +75,15 @@ fn get_gameplay_world() -> WorldMap {
     }
 }
 
+/// World a server map ID stands for. Map 0 predates World folder IDs and
+/// keeps whatever world is loaded.
+pub fn world_of_map(map_id: u16) -> Option<WorldMap> {
+    u8::try_from(map_id)
+        .ok()
+        .and_then(WorldMap::from_id)
+        .filter(|map| *map != WorldMap::Unk0)
+}
+
 fn default_particle_definitions() -> ParticleDefinitions {
     ParticleDefinitions {
         emitters: HashMap::new(),
@@ -71,9 +95,11 @@ fn setup_gameplay_scene(
     mut particle_definitions_assets: ResMut<Assets<ParticleDefinitions>>,
     mut world_requests: MessageWriter<WorldRequest>,
     mut camera_query: Query<&mut Camera, With<Camera3d>>,
+    world_override: Option<Res<GameplayWorldOverride>>,
 ) {
-    let gameplay_world = get_gameplay_world();
-    let world_name = format!("world_{}", gameplay_world as u8);
+    let gameplay_world = world_override
+        .map(|world| world.0)
+        .unwrap_or_else(get_gameplay_world);
 
     info!(
         "Setting up gameplay scene: {} (ID: {})",
@@ -81,20 +107,73 @@ fn setup_gameplay_scene(
         gameplay_world as u8
     );
 
-    world_controller::request_world(&mut world_requests, WorldId::Game(gameplay_world));
+    load_gameplay_world(
+        &mut commands,
+        &mut particle_definitions_assets,
+        &mut world_requests,
+        gameplay_world,
+    );
     for mut camera in &mut camera_query {
         camera.clear_color = ClearColorConfig::Custom(GAMEPLAY_CLEAR_COLOR);
     }
 
+    commands.spawn((GameplaySceneRoot, RuntimeSceneEntity, Transform::default()));
+}
+
+fn load_gameplay_world(
+    commands: &mut Commands,
+    particle_definitions_assets: &mut Assets<ParticleDefinitions>,
+    world_requests: &mut MessageWriter<WorldRequest>,
+    world: WorldMap,
+) {
+    world_controller::request_world(world_requests, WorldId::Game(world));
+
     let particle_defs = particle_definitions_assets.add(default_particle_definitions());
     commands.insert_resource(RuntimeSceneAssets {
-        world_name,
+        world_name: format!("world_{}", world as u8),
         world: None,
         particle_defs,
         loaded: false,
     });
+}
 
-    commands.spawn((GameplaySceneRoot, RuntimeSceneEntity, Transform::default()));
+/// Swaps the loaded world for the one the server put the character on, such
+/// as Noria for a new Fairy Elf.
+fn follow_entered_world(
+    mut commands: Commands,
+    mut incoming: MessageReader<ServerMessageReceived>,
+    current_world: Res<CurrentWorld>,
+    mut particle_definitions_assets: ResMut<Assets<ParticleDefinitions>>,
+    mut world_requests: MessageWriter<WorldRequest>,
+    scene_entities: Query<Entity, (With<RuntimeSceneEntity>, Without<GameplaySceneRoot>)>,
+) {
+    let entered = incoming
+        .read()
+        .filter_map(|ServerMessageReceived(message)| match message {
+            ServerMessage::EnterMap { map_id, .. } => world_of_map(*map_id),
+            _ => None,
+        })
+        .last();
+    let Some(world) = entered.filter(|world| current_world.0 != Some(WorldId::Game(*world))) else {
+        return;
+    };
+
+    info!(
+        "Server entered the character in {} (ID: {}); reloading the world",
+        world.name(),
+        world as u8
+    );
+    for entity in &scene_entities {
+        commands.entity(entity).try_despawn();
+    }
+    commands.remove_resource::<WorldCollision>();
+    commands.remove_resource::<TerrainHeightField>();
+    load_gameplay_world(
+        &mut commands,
+        &mut particle_definitions_assets,
+        &mut world_requests,
+        world,
+    );
 }
 
 fn cleanup_gameplay_scene(
@@ -108,6 +187,8 @@ fn cleanup_gameplay_scene(
     }
 
     commands.remove_resource::<RuntimeSceneAssets>();
+    commands.remove_resource::<WorldCollision>();
+    commands.remove_resource::<TerrainHeightField>();
 }
 
 #[cfg(test)]
@@ -131,7 +212,9 @@ mod tests {
             bevy::asset::AssetPlugin::default(),
         ))
         .init_state::<crate::AppState>()
-        .add_message::<WorldRequest>();
+        .init_resource::<CurrentWorld>()
+        .add_message::<WorldRequest>()
+        .add_message::<ServerMessageReceived>();
 
         GameplayScene::register(&mut app);
 
@@ -151,4 +234,12 @@ mod tests {
         assert_eq!(count_gameplay_roots(app.world_mut()), 0);
         assert!(!app.world().contains_resource::<RuntimeSceneAssets>());
     }
+
+    #[test]
+    fn server_maps_pick_their_world() {
+        assert_eq!(world_of_map(4), Some(WorldMap::Noria));
+        assert_eq!(world_of_map(52), Some(WorldMap::Elbeland));
+        assert_eq!(world_of_map(0), None);
+        assert_eq!(world_of_map(300), None);
+    }
 }
@@ -1,5 +1,6 @@
 pub use crate::scene_runtime::systems::{
     apply_legacy_gltf_material_overrides, apply_map_vfx_profile_to_scene_objects,
     apply_scene_object_distance_culling, ensure_scene_object_animation_players,
-    fix_scene_object_materials, spawn_scene_objects_when_ready, toggle_offscreen_scene_animations,
+    fix_scene_object_materials, reload_changed_scene_objects, spawn_scene_objects_when_ready,
+    toggle_offscreen_scene_animations,
 };
@@ -0,0 +1,343 @@
+//! Interactive town props: chairs, bar counters, doors and NPCs.
+//!
+//! Objects with `properties.interaction` in the world data can be clicked.
+//! The local character walks up to the prop and then sits, has a drink,
+//! swings the door open or talks to the NPC. Sitting and drinking are played
+//! as emotes, so other players on the map see them too; doors only move on
+//! this client, and talking is sent to the server as `TalkToNpc`.
+
+use crate::AppState;
+use crate::character::{
+    CharacterController, CharacterState, MovementRoute, RemotePathFollower, plan_movement,
+};
+use crate::gameplay::area_targeting::AreaTargeting;
+use crate::gameplay::emotes::{EmoteRequest, yaw_of};
+use crate::infra::input::InputGate;
+use crate::infra::network::SendClientMessage;
+use crate::scene_runtime::collision::WorldCollision;
+use crate::scene_runtime::components::{InteractiveProp, PropInteraction};
+use bevy::prelude::*;
+use bevy::state::prelude::in_state;
+use bevy::window::PrimaryWindow;
+use bevy_egui::input::EguiWantsInput;
+use protocol::{ClientMessage, Emote};
+
+/// Click radius around a prop's origin, before the prop's scale.
+const PICK_RADIUS: f32 = 90.0;
+/// How close the character must stand to use a prop.
+const USE_REACH: f32 = 160.0;
+/// Where the character stops in front of counters and doors, which usually
+/// stand on blocked tiles.
+const APPROACH_DISTANCE: f32 = 120.0;
+const DOOR_OPEN_ANGLE: f32 = std::f32::consts::FRAC_PI_2;
+const DOOR_SWING_SECS: f32 = 0.8;
+/// Doors close again on their own after this long.
+const DOOR_OPEN_SECS: f32 = 6.0;
+
+/// Prop the local character is walking to.
+#[derive(Component, Debug)]
+struct PropApproach {
+    prop: Entity,
+}
+
+/// Swing of a door prop; `openness` runs from 0 (closed) to 1 (open).
+#[derive(Component, Debug)]
+struct DoorSwing {
+    closed_rotation: Quat,
+    openness: f32,
+    opening: bool,
+    close_in: Timer,
+}
+
+impl DoorSwing {
+    fn new(closed_rotation: Quat) -> Self {
+        Self {
+            closed_rotation,
+            openness: 0.0,
+            opening: false,
+            close_in: Timer::from_seconds(DOOR_OPEN_SECS, TimerMode::Once),
+        }
+    }
+
+    /// Opens a closed door and closes an open one.
+    fn toggle(&mut self) {
+        self.opening = !self.opening;
+        self.close_in.reset();
+    }
+
+    /// Advances the swing; returns the door's rotation.
+    fn advance(&mut self, dt: std::time::Duration) -> Quat {
+        let step = dt.as_secs_f32() / DOOR_SWING_SECS;
+        if self.opening {
+            self.openness = (self.openness + step).min(1.0);
+            if self.openness >= 1.0 && self.close_in.tick(dt).is_finished() {
+                self.opening = false;
+            }
+        } else {
+            self.openness = (self.openness - step).max(0.0);
+        }
+        self.closed_rotation * Quat::from_rotation_y(DOOR_OPEN_ANGLE * smoothstep(self.openness))
+    }
+
+    fn is_closed(&self) -> bool {
+        !self.opening && self.openness <= 0.0
+    }
+}
+
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Nearest scene object under the cursor `ray`, picked by a sphere around its
+/// origin that grows with its scale.
+pub(crate) fn pick_scene_object<'a>(
+    ray: Ray3d,
+    objects: impl IntoIterator<Item = (Entity, &'a Transform)>,
+) -> Option<Entity> {
+    pick_prop(
+        ray,
+        objects.into_iter().map(|(entity, transform)| {
+            (
+                entity,
+                transform.translation,
+                PICK_RADIUS * transform.scale.max_element(),
+            )
+        }),
+    )
+}
+
+/// Nearest prop hit by `ray`, given each prop's centre and click radius.
+fn pick_prop(ray: Ray3d, props: impl IntoIterator<Item = (Entity, Vec3, f32)>) -> Option<Entity> {
+    let direction = *ray.direction;
+    props
+        .into_iter()
+        .filter_map(|(entity, center, radius)| {
+            let along = (center - ray.origin).dot(direction);
+            let closest = ray.origin + direction * along;
+            (along >= 0.0 && closest.distance_squared(center) <= radius * radius)
+                .then_some((entity, along))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity)
+}
+
+/// Spot the character walks to before using a prop: the seat itself, or a
+/// step in front of counters, doors and NPCs on the character's side.
+fn approach_point(interaction: PropInteraction, prop: Vec3, character: Vec3) -> Vec3 {
+    if interaction == PropInteraction::Seat {
+        return prop;
+    }
+    let away = (character - prop).with_y(0.0).normalize_or(Vec3::X);
+    prop + away * APPROACH_DISTANCE
+}
+
+pub struct TownPropsPlugin;
+
+impl Plugin for TownPropsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (pick_interactive_prop, use_reached_props, swing_doors)
+                .chain()
+                .run_if(in_state(AppState::Gameplay)),
+        );
+    }
+}
+
+fn pick_interactive_prop(
+    mut commands: Commands,
+    mouse: Res<ButtonInput<MouseButton>>,
+    egui_wants_input: Option<Res<EguiWantsInput>>,
+    gate: Res<InputGate>,
+    targeting: Res<AreaTargeting>,
+    collision: Option<Res<WorldCollision>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    props: Query<(Entity, &InteractiveProp, &Transform)>,
+    mut players: Query<
+        (Entity, &Transform, &mut MovementRoute),
+        (Without<RemotePathFollower>, Without<InteractiveProp>),
+    >,
+) {
+    if !mouse.just_pressed(MouseButton::Left)
+        || gate.is_held()
+        || egui_wants_input.is_some_and(|egui| egui.wants_any_pointer_input())
+    {
+        return;
+    }
+    // The click belongs to an aimed area skill, even when it was cast this frame.
+    if targeting.aiming().is_some() || targeting.cursor.is_some() {
+        return;
+    }
+    let Some(ray) = windows
+        .single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .zip(cameras.single().ok())
+        .and_then(|(cursor, (camera, camera_transform))| {
+            camera.viewport_to_world(camera_transform, cursor).ok()
+        })
+    else {
+        return;
+    };
+    let Some(prop) = pick_scene_object(
+        ray,
+        props
+            .iter()
+            .map(|(entity, _, transform)| (entity, transform)),
+    ) else {
+        return;
+    };
+    let Ok((_, InteractiveProp(interaction), prop_transform)) = props.get(prop) else {
+        return;
+    };
+    let Ok((player, transform, mut route)) = players.single_mut() else {
+        return;
+    };
+
+    let target = approach_point(
+        *interaction,
+        prop_transform.translation,
+        transform.translation,
+    );
+    let planned = collision.is_some_and(|collision| {
+        plan_movement(&collision, &mut route, transform.translation, target)
+    });
+    let already_there = transform.translation.distance(target) <= USE_REACH;
+    if planned || already_there {
+        commands.entity(player).insert(PropApproach { prop });
+    }
+}
+
+fn use_reached_props(
+    mut commands: Commands,
+    mut props: Query<(&InteractiveProp, &Transform, Option<&mut DoorSwing>)>,
+    mut players: Query<
+        (
+            Entity,
+            &PropApproach,
+            &MovementRoute,
+            &CharacterController,
+            &mut Transform,
+        ),
+        Without<InteractiveProp>,
+    >,
+    mut emotes: MessageWriter<EmoteRequest>,
+    mut outgoing: MessageWriter<SendClientMessage>,
+) {
+    for (player, approach, route, controller, mut transform) in &mut players {
+        if !matches!(controller.state, CharacterState::Idle) || route.pending_waypoints() > 0 {
+            continue;
+        }
+        commands.entity(player).remove::<PropApproach>();
+
+        let Ok((InteractiveProp(interaction), prop_transform, door)) = props.get_mut(approach.prop)
+        else {
+            continue;
+        };
+        let prop_position = prop_transform.translation;
+        let reach = approach_point(*interaction, prop_position, transform.translation);
+        if transform.translation.distance(reach) > USE_REACH {
+            continue;
+        }
+
+        let emote = match interaction {
+            PropInteraction::Seat => {
+                transform.translation = prop_position;
+                transform.rotation = yaw_of(prop_transform.rotation);
+                Emote::Sit
+            }
+            PropInteraction::Drink => {
+                let y = transform.translation.y;
+                transform.look_at(prop_position.with_y(y), Vec3::Y);
+                Emote::Drink
+            }
+            PropInteraction::Door => {
+                match door {
+                    Some(mut door) => door.toggle(),
+                    None => {
+                        let mut door = DoorSwing::new(prop_transform.rotation);
+                        door.toggle();
+                        commands.entity(approach.prop).insert(door);
+                    }
+                }
+                continue;
+            }
+            PropInteraction::Talk { npc_id } => {
+                let y = transform.translation.y;
+                transform.look_at(prop_position.with_y(y), Vec3::Y);
+                outgoing.write(SendClientMessage(ClientMessage::TalkToNpc {
+                    npc_id: *npc_id,
+                }));
+                continue;
+            }
+        };
+
+        emotes.write(EmoteRequest(emote));
+    }
+}
+
+fn swing_doors(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut doors: Query<(Entity, &mut DoorSwing, &mut Transform)>,
+) {
+    for (entity, mut door, mut transform) in &mut doors {
+        transform.rotation = door.advance(time.delta());
+        if door.is_closed() {
+            commands.entity(entity).remove::<DoorSwing>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn clicks_pick_the_nearest_prop_under_the_cursor() {
+        let ray = Ray3d::new(Vec3::new(0.0, 1_000.0, 0.0), Dir3::NEG_Y);
+        let chair = Entity::from_raw_u32(1).unwrap();
+        let table = Entity::from_raw_u32(2).unwrap();
+        let door = Entity::from_raw_u32(3).unwrap();
+        let props = [
+            (chair, Vec3::new(50.0, 0.0, 0.0), 90.0),
+            (table, Vec3::new(0.0, 100.0, 0.0), 90.0),
+            (door, Vec3::new(400.0, 0.0, 0.0), 90.0),
+        ];
+        assert_eq!(pick_prop(ray, props), Some(table));
+        assert_eq!(pick_prop(ray, [props[2]]), None);
+
+        // Props behind the camera cannot be clicked.
+        let above = (door, Vec3::new(0.0, 2_000.0, 0.0), 90.0);
+        assert_eq!(pick_prop(ray, [above]), None);
+    }
+
+    #[test]
+    fn doors_open_then_close_by_themselves() {
+        let mut door = DoorSwing::new(Quat::IDENTITY);
+        assert!(door.is_closed());
+        door.toggle();
+
+        let open = door.advance(Duration::from_secs_f32(DOOR_SWING_SECS));
+        assert!(open.abs_diff_eq(Quat::from_rotation_y(DOOR_OPEN_ANGLE), 1e-4));
+        assert!(!door.is_closed());
+
+        door.advance(Duration::from_secs_f32(DOOR_OPEN_SECS));
+        let closed = door.advance(Duration::from_secs_f32(DOOR_SWING_SECS));
+        assert!(closed.abs_diff_eq(Quat::IDENTITY, 1e-4));
+        assert!(door.is_closed());
+    }
+
+    #[test]
+    fn counters_are_used_from_the_characters_side() {
+        let bar = Vec3::new(1_000.0, 0.0, 1_000.0);
+        let character = Vec3::new(1_000.0, 0.0, 1_600.0);
+        assert_eq!(
+            approach_point(PropInteraction::Drink, bar, character),
+            Vec3::new(1_000.0, 0.0, 1_000.0 + APPROACH_DISTANCE)
+        );
+        assert_eq!(approach_point(PropInteraction::Seat, bar, character), bar);
+    }
+}
@@ -0,0 +1,192 @@
+//! Doors and gates whose state the server owns.
+//!
+//! Scene objects with `properties.door_id` show a server door (Devias gates,
+//! event doors, Castle Siege gates). `DoorStates` messages lift open gates,
+//! hide destroyed ones and clear their collision so paths go through them.
+//! The server checks movement itself; the client only mirrors it.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::AppState;
+use crate::infra::network::ServerMessageReceived;
+use crate::scene_runtime::collision::WorldCollision;
+use crate::scene_runtime::components::ReplicatedDoor;
+use bevy::prelude::*;
+use bevy::state::prelude::{OnExit, in_state};
+use protocol::{DoorState, ServerMessage};
+
+/// How far an open gate rises above its closed position.
+const GATE_LIFT: f32 = 260.0;
+const GATE_MOVE_SECS: f32 = 1.5;
+
+/// Last state the server sent for each door of the current map.
+#[derive(Resource, Debug, Default)]
+pub struct WorldDoors {
+    states: HashMap<u16, DoorState>,
+}
+
+impl WorldDoors {
+    /// Doors the server has not mentioned are closed.
+    pub fn state(&self, door_id: u16) -> DoorState {
+        self.states
+            .get(&door_id)
+            .copied()
+            .unwrap_or(DoorState::Closed)
+    }
+}
+
+/// Lift of a door object; `openness` runs from 0 (closed) to 1 (open).
+#[derive(Component, Debug)]
+struct GateMotion {
+    closed_translation: Vec3,
+    openness: f32,
+}
+
+impl GateMotion {
+    fn new(closed_translation: Vec3) -> Self {
+        Self {
+            closed_translation,
+            openness: 0.0,
+        }
+    }
+
+    /// Moves towards `state`; returns the gate's translation.
+    fn advance(&mut self, state: DoorState, dt: Duration) -> Vec3 {
+        let step = dt.as_secs_f32() / GATE_MOVE_SECS;
+        self.openness = match state {
+            DoorState::Open => (self.openness + step).min(1.0),
+            DoorState::Closed => (self.openness - step).max(0.0),
+            // Rubble does not rise; a rebuilt gate comes back closed.
+            DoorState::Destroyed => 0.0,
+        };
+        self.closed_translation + Vec3::Y * GATE_LIFT * smoothstep(self.openness)
+    }
+}
+
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+pub struct WorldDoorsPlugin;
+
+impl Plugin for WorldDoorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldDoors>()
+            .add_systems(OnExit(AppState::Gameplay), reset_world_doors)
+            .add_systems(
+                Update,
+                (
+                    apply_door_messages,
+                    update_door_collision,
+                    track_new_doors,
+                    move_doors,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Gameplay)),
+            );
+    }
+}
+
+fn reset_world_doors(mut doors: ResMut<WorldDoors>) {
+    *doors = WorldDoors::default();
+}
+
+fn apply_door_messages(
+    mut doors: ResMut<WorldDoors>,
+    mut incoming: MessageReader<ServerMessageReceived>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        match message {
+            ServerMessage::EnterMap { .. } => doors.states.clear(),
+            ServerMessage::DoorStates { doors: statuses } => {
+                for status in statuses {
+                    doors.states.insert(status.door_id, status.state);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Keeps door tiles in the collision grid in step with the server, also when
+/// the scene (and its grid) loads after the states arrived.
+fn update_door_collision(doors: Res<WorldDoors>, collision: Option<ResMut<WorldCollision>>) {
+    let Some(mut collision) = collision else {
+        return;
+    };
+    if !doors.is_changed() && !collision.is_added() {
+        return;
+    }
+    for (door_id, state) in &doors.states {
+        collision.set_door_blocks(*door_id, state.blocks());
+    }
+}
+
+fn track_new_doors(
+    mut commands: Commands,
+    doors: Query<(Entity, &Transform), Added<ReplicatedDoor>>,
+) {
+    for (entity, transform) in &doors {
+        commands
+            .entity(entity)
+            .insert(GateMotion::new(transform.translation));
+    }
+}
+
+fn move_doors(
+    time: Res<Time>,
+    doors: Res<WorldDoors>,
+    mut objects: Query<(
+        &ReplicatedDoor,
+        &mut GateMotion,
+        &mut Transform,
+        &mut Visibility,
+    )>,
+) {
+    for (door, mut motion, mut transform, mut visibility) in &mut objects {
+        let state = doors.state(door.door_id);
+        let shown = if state == DoorState::Destroyed {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        visibility.set_if_neq(shown);
+        let translation = motion.advance(state, time.delta());
+        if transform.translation != translation {
+            transform.translation = translation;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gates_rise_when_opened_and_settle_when_closed() {
+        let mut gate = GateMotion::new(Vec3::new(10.0, 0.0, 20.0));
+        let half = Duration::from_secs_f32(GATE_MOVE_SECS / 2.0);
+
+        let rising = gate.advance(DoorState::Open, half);
+        assert!(rising.y > 0.0 && rising.y < GATE_LIFT);
+        let open = gate.advance(DoorState::Open, half * 3);
+        assert_eq!(open, Vec3::new(10.0, GATE_LIFT, 20.0));
+
+        gate.advance(DoorState::Closed, half * 3);
+        assert_eq!(gate.openness, 0.0);
+        gate.advance(DoorState::Open, half);
+        assert_eq!(
+            gate.advance(DoorState::Destroyed, half),
+            Vec3::new(10.0, 0.0, 20.0)
+        );
+    }
+
+    #[test]
+    fn unknown_doors_are_closed() {
+        let mut doors = WorldDoors::default();
+        doors.states.insert(3, DoorState::Open);
+        assert_eq!(doors.state(3), DoorState::Open);
+        assert_eq!(doors.state(4), DoorState::Closed);
+    }
+}
@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use common::worldscale::TILE_WORLD_SIZE;
 
 pub const GRID_OVERLAY_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.25);
 
@@ -13,7 +14,7 @@ pub struct GridOverlayConfig {
 impl Default for GridOverlayConfig {
     fn default() -> Self {
         Self {
-            cell_size: 100.0,
+            cell_size: TILE_WORLD_SIZE,
             visible_half_cells: 25,
             y_offset: 0.25,
             color: GRID_OVERLAY_COLOR,
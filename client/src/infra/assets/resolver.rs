@@ -1,12 +1,70 @@
-use std::path::PathBuf;
+use assets_manifest::{AssetManifest, MANIFEST_FILE, ManifestError};
+use bevy::log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{OnceLock, RwLock};
+use std::sync::{Mutex, OnceLock, RwLock};
 
 const DATA_PREFIX: &str = "data/";
 const REMASTER_PREFIX: &str = "remaster/";
 
 static ASSET_ROOT: OnceLock<RwLock<PathBuf>> = OnceLock::new();
 static USE_REMASTER_ASSETS: AtomicBool = AtomicBool::new(true);
+static RESOLVED_ASSETS: OnceLock<RwLock<HashMap<String, ResolvedAsset>>> = OnceLock::new();
+static ASSET_MANIFEST: OnceLock<RwLock<Option<AssetManifest>>> = OnceLock::new();
+static REPORTED_UNLISTED: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+/// Set of files the loaders read: the converted classic data under `data/` or
+/// the remastered copies mirrored under `remaster/data/`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetVariant {
+    Classic,
+    #[default]
+    Remaster,
+}
+
+impl AssetVariant {
+    pub const ALL: [Self; 2] = [Self::Classic, Self::Remaster];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Classic => "Classico",
+            Self::Remaster => "Remaster",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Classic => Self::Remaster,
+            Self::Remaster => Self::Classic,
+        }
+    }
+}
+
+/// Variant an asset was asked for and the one its file came from; they differ
+/// when the remaster copy is missing and the classic file was used instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolvedAsset {
+    pub requested: AssetVariant,
+    pub loaded: AssetVariant,
+}
+
+impl ResolvedAsset {
+    pub fn is_fallback(self) -> bool {
+        self.requested != self.loaded
+    }
+}
+
+/// Counts of the assets resolved so far, by the variant they were loaded from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AssetVariantReport {
+    pub classic: usize,
+    pub remaster: usize,
+    /// Assets asked as remaster that only exist as classic, sorted.
+    pub fallbacks: Vec<String>,
+}
 
 pub fn default_asset_root_path() -> PathBuf {
     PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../assets"))
@@ -19,41 +77,84 @@ pub fn current_asset_root_path() -> PathBuf {
     }
 }
 
-pub fn configure_asset_resolver(asset_root: impl Into<PathBuf>, use_remaster: bool) {
+pub fn configure_asset_resolver(asset_root: impl Into<PathBuf>, variant: AssetVariant) {
     let new_root = asset_root.into();
+    set_asset_manifest(read_asset_manifest(&new_root));
     match asset_root_lock().write() {
         Ok(mut root) => *root = new_root,
         Err(poisoned) => *poisoned.into_inner() = new_root,
     }
-    set_use_remaster_assets(use_remaster);
+    set_asset_variant(variant);
 }
 
-pub fn use_remaster_assets_enabled() -> bool {
-    USE_REMASTER_ASSETS.load(Ordering::Relaxed)
+pub fn current_asset_variant() -> AssetVariant {
+    if USE_REMASTER_ASSETS.load(Ordering::Relaxed) {
+        AssetVariant::Remaster
+    } else {
+        AssetVariant::Classic
+    }
 }
 
-pub fn set_use_remaster_assets(enabled: bool) {
-    USE_REMASTER_ASSETS.store(enabled, Ordering::Relaxed);
+/// Switches the variant for assets loaded from now on; handles already loaded
+/// keep their files until the scene reloads them.
+pub fn set_asset_variant(variant: AssetVariant) {
+    USE_REMASTER_ASSETS.store(variant == AssetVariant::Remaster, Ordering::Relaxed);
 }
 
+/// Resolves a path about to be loaded; one missing from the asset manifest
+/// is reported along with the listed path it most likely meant.
 pub fn resolve_asset_path(path: &str) -> String {
-    resolve_asset_path_with_mode(path, use_remaster_assets_enabled())
+    report_unlisted_asset(path);
+    resolve_asset_path_for(path, current_asset_variant())
+}
+
+/// Resolves `path` for `variant`, falling back to the classic file when the
+/// remaster copy is missing, and records the outcome in the variant report.
+pub fn resolve_asset_path_for(path: &str, variant: AssetVariant) -> String {
+    resolve_and_record(path, variant, variant)
+}
+
+/// Resolves assets that must come from the same variant, such as the body
+/// parts sharing a skeleton: if any of them lacks a remaster copy, all of them
+/// load as classic. Returns the variant used and the paths in input order.
+pub fn resolve_asset_group(paths: &[String], variant: AssetVariant) -> (AssetVariant, Vec<String>) {
+    let loaded = if variant == AssetVariant::Remaster
+        && paths.iter().all(|path| remaster_variant_exists(path))
+    {
+        AssetVariant::Remaster
+    } else {
+        AssetVariant::Classic
+    };
+    let resolved = paths
+        .iter()
+        .inspect(|path| report_unlisted_asset(path))
+        .map(|path| resolve_and_record(path, loaded, variant))
+        .collect();
+    (loaded, resolved)
 }
 
-pub fn resolve_asset_path_with_mode(path: &str, use_remaster: bool) -> String {
+fn resolve_and_record(path: &str, variant: AssetVariant, requested: AssetVariant) -> String {
     let normalized = normalize_asset_path(path);
-    if normalized.is_empty() || !use_remaster {
+    if normalized.is_empty() {
         return normalized;
     }
 
     let (base_path, label_suffix) = split_asset_label(&normalized);
+    if base_path.starts_with(REMASTER_PREFIX) {
+        record_resolution(base_path, AssetVariant::Remaster, AssetVariant::Remaster);
+        return normalized;
+    }
+    // Only `data/` is mirrored by the remaster pack; UI art and the like have
+    // a single variant and stay out of the report.
     let Some(remaster_candidate) = remaster_candidate_for(base_path) else {
         return normalized;
     };
 
-    if path_exists_under_root(&remaster_candidate) {
+    if variant == AssetVariant::Remaster && path_exists_under_root(&remaster_candidate) {
+        record_resolution(base_path, requested, AssetVariant::Remaster);
         format!("{remaster_candidate}{label_suffix}")
     } else {
+        record_resolution(base_path, requested, AssetVariant::Classic);
         normalized
     }
 }
@@ -71,11 +172,100 @@ pub fn remaster_variant_exists(path: &str) -> bool {
     }
 }
 
+/// Variant the last resolution of `path` was loaded from, if it was resolved.
+pub fn resolved_asset(path: &str) -> Option<ResolvedAsset> {
+    let normalized = normalize_asset_path(path);
+    let (base_path, _) = split_asset_label(&normalized);
+    match resolved_assets_lock().read() {
+        Ok(resolved) => resolved.get(base_path).copied(),
+        Err(poisoned) => poisoned.into_inner().get(base_path).copied(),
+    }
+}
+
+pub fn asset_variant_report() -> AssetVariantReport {
+    let mut report = AssetVariantReport::default();
+    let collect = |resolved: &HashMap<String, ResolvedAsset>, report: &mut AssetVariantReport| {
+        for (path, asset) in resolved {
+            match asset.loaded {
+                AssetVariant::Classic => report.classic += 1,
+                AssetVariant::Remaster => report.remaster += 1,
+            }
+            if asset.is_fallback() {
+                report.fallbacks.push(path.clone());
+            }
+        }
+    };
+    match resolved_assets_lock().read() {
+        Ok(resolved) => collect(&resolved, &mut report),
+        Err(poisoned) => collect(&poisoned.into_inner(), &mut report),
+    }
+    report.fallbacks.sort();
+    report
+}
+
+/// Probes for `path` without reporting it when it is not in the manifest,
+/// as callers try several spellings.
 pub fn asset_path_exists(path: &str) -> bool {
-    let resolved = resolve_asset_path(path);
+    let resolved = resolve_asset_path_for(path, current_asset_variant());
     asset_path_exists_exact(&resolved)
 }
 
+/// Replaces the manifest paths are checked against; `None` checks nothing.
+pub fn set_asset_manifest(manifest: Option<AssetManifest>) {
+    match asset_manifest_lock().write() {
+        Ok(mut current) => *current = manifest,
+        Err(poisoned) => *poisoned.into_inner() = manifest,
+    }
+}
+
+/// Checks a `data/` path against the manifest of the asset root. Passes
+/// when the root has no manifest, as asset trees converted before it do not.
+pub fn check_asset_path(path: &str) -> Result<(), ManifestError> {
+    let normalized = normalize_asset_path(path);
+    let (base_path, _) = split_asset_label(&normalized);
+    if !base_path.starts_with(DATA_PREFIX) && !base_path.starts_with(REMASTER_PREFIX) {
+        return Ok(());
+    }
+    let manifest = match asset_manifest_lock().read() {
+        Ok(manifest) => manifest,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    match manifest.as_ref() {
+        Some(manifest) => manifest.check(base_path).map(|_| ()),
+        None => Ok(()),
+    }
+}
+
+fn read_asset_manifest(root: &Path) -> Option<AssetManifest> {
+    let path = root.join(MANIFEST_FILE);
+    if !path.is_file() {
+        return None;
+    }
+    match AssetManifest::read(&path) {
+        Ok(manifest) => Some(manifest),
+        Err(err) => {
+            warn!("Asset manifest ignored: {err}");
+            None
+        }
+    }
+}
+
+fn report_unlisted_asset(path: &str) {
+    let Err(err) = check_asset_path(path) else {
+        return;
+    };
+    let mut reported = match REPORTED_UNLISTED
+        .get_or_init(|| Mutex::new(HashSet::new()))
+        .lock()
+    {
+        Ok(reported) => reported,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if reported.insert(path.to_string()) {
+        warn!("{err}");
+    }
+}
+
 pub fn asset_path_exists_exact(path: &str) -> bool {
     let normalized = normalize_asset_path(path);
     if normalized.is_empty() {
@@ -89,6 +279,10 @@ fn asset_root_lock() -> &'static RwLock<PathBuf> {
     ASSET_ROOT.get_or_init(|| RwLock::new(default_asset_root_path()))
 }
 
+fn asset_manifest_lock() -> &'static RwLock<Option<AssetManifest>> {
+    ASSET_MANIFEST.get_or_init(|| RwLock::new(None))
+}
+
 fn normalize_asset_path(raw_path: &str) -> String {
     raw_path
         .trim()
@@ -115,3 +309,18 @@ fn path_exists_under_root(relative_path: &str) -> bool {
     let root = current_asset_root_path();
     root.join(relative_path).is_file()
 }
+
+fn resolved_assets_lock() -> &'static RwLock<HashMap<String, ResolvedAsset>> {
+    RESOLVED_ASSETS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+fn record_resolution(base_path: &str, requested: AssetVariant, loaded: AssetVariant) {
+    let resolved = ResolvedAsset { requested, loaded };
+    let mut assets = match resolved_assets_lock().write() {
+        Ok(assets) => assets,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if assets.get(base_path) != Some(&resolved) {
+        assets.insert(base_path.to_string(), resolved);
+    }
+}
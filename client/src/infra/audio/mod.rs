@@ -0,0 +1,67 @@
+//! One-shot sound playback.
+//!
+//! Any system asks for a sound with [`PlaySound`]; it starts at the volume of
+//! its category and is despawned when it ends. Sounds come from the asset
+//! tree like everything else, so a missing file is logged once and then
+//! skipped: the converter only copies the original `.wav` files when the
+//! source client has them.
+
+use crate::infra::assets::{asset_path_exists, resolve_asset_path};
+use crate::settings::{AudioCategoryState, SoundCategory};
+use bevy::audio::Volume;
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlaySound {
+    /// Path under the asset root, e.g. `data/Sound/iButtonClick.wav`.
+    pub path: &'static str,
+    pub category: SoundCategory,
+}
+
+/// Loaded sounds by path; `None` for files that do not exist.
+#[derive(Resource, Default)]
+struct SoundCache {
+    handles: HashMap<&'static str, Option<Handle<AudioSource>>>,
+}
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<PlaySound>()
+            .init_resource::<SoundCache>()
+            .add_systems(PostUpdate, play_sounds);
+    }
+}
+
+fn play_sounds(
+    mut requests: MessageReader<PlaySound>,
+    categories: Res<AudioCategoryState>,
+    asset_server: Res<AssetServer>,
+    mut cache: ResMut<SoundCache>,
+    mut commands: Commands,
+) {
+    // The same sound asked twice in a frame plays once.
+    let mut started = HashSet::new();
+    for request in requests.read() {
+        let volume = categories.volume(request.category);
+        if volume <= 0.0 || !started.insert(request.path) {
+            continue;
+        }
+        let handle = cache.handles.entry(request.path).or_insert_with(|| {
+            if asset_path_exists(request.path) {
+                Some(asset_server.load(resolve_asset_path(request.path)))
+            } else {
+                warn!("Sound '{}' not found; it will not play", request.path);
+                None
+            }
+        });
+        if let Some(handle) = handle {
+            commands.spawn((
+                AudioPlayer(handle.clone()),
+                PlaybackSettings::DESPAWN.with_volume(Volume::Linear(volume)),
+            ));
+        }
+    }
+}
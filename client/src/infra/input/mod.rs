@@ -1 +1,264 @@
 //! Input infrastructure surface.
+//!
+//! Inputs pressed while the world is not ready (loading, map transfer) are
+//! queued and, once gameplay resumes, either replayed or dropped on purpose.
+
+use crate::AppState;
+use crate::infra::network::ServerMessageReceived;
+use bevy::prelude::*;
+use bevy::state::prelude::{OnEnter, in_state};
+use protocol::{ServerErrorKind, ServerMessage};
+use std::collections::VecDeque;
+
+/// Buffered inputs older than this are dropped instead of replayed.
+const MAX_REPLAY_AGE_SECS: f64 = 1.5;
+const MAX_BUFFERED_INPUTS: usize = 32;
+
+/// Hotkeys that stay meaningful after a transition (skills and potions).
+const GAMEPLAY_KEYS: [KeyCode; 14] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::Digit0,
+    KeyCode::KeyQ,
+    KeyCode::KeyW,
+    KeyCode::KeyE,
+    KeyCode::KeyR,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BufferedInput {
+    Key(KeyCode),
+    MouseClick {
+        button: MouseButton,
+        cursor: Option<Vec2>,
+    },
+}
+
+impl BufferedInput {
+    /// Clicks target a world that was not on screen yet, so only hotkeys replay.
+    pub fn replays(&self) -> bool {
+        matches!(self, Self::Key(_))
+    }
+}
+
+/// Input replayed after a transition, read alongside `ButtonInput`.
+#[derive(Message, Debug, Clone, Copy, PartialEq)]
+pub struct ReplayedInput(pub BufferedInput);
+
+/// Held by systems that need gameplay input paused, e.g. during map transfer.
+#[derive(Resource, Debug, Default)]
+pub struct InputGate {
+    holds: u32,
+}
+
+impl InputGate {
+    pub fn hold(&mut self) {
+        self.holds += 1;
+    }
+
+    pub fn release(&mut self) {
+        self.holds = self.holds.saturating_sub(1);
+    }
+
+    pub fn is_held(&self) -> bool {
+        self.holds > 0
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct InputBuffer {
+    entries: VecDeque<(BufferedInput, f64)>,
+    pub discarded: u64,
+}
+
+impl InputBuffer {
+    pub fn push(&mut self, input: BufferedInput, now_secs: f64) {
+        if self.entries.len() == MAX_BUFFERED_INPUTS {
+            self.entries.pop_front();
+            self.discarded += 1;
+        }
+        self.entries.push_back((input, now_secs));
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Empties the buffer, returning the inputs that should still be replayed.
+    pub fn drain_replayable(&mut self, now_secs: f64) -> Vec<BufferedInput> {
+        let mut replay = Vec::new();
+        for (input, captured_at) in self.entries.drain(..) {
+            if input.replays() && now_secs - captured_at <= MAX_REPLAY_AGE_SECS {
+                replay.push(input);
+            } else {
+                self.discarded += 1;
+            }
+        }
+        replay
+    }
+
+    pub fn clear(&mut self) {
+        self.discarded += self.entries.len() as u64;
+        self.entries.clear();
+    }
+}
+
+pub struct InputBufferPlugin;
+
+impl Plugin for InputBufferPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputBuffer>()
+            .init_resource::<InputGate>()
+            .add_message::<ReplayedInput>()
+            .add_systems(OnEnter(AppState::Login), clear_input_buffer)
+            .add_systems(
+                Update,
+                (
+                    gate_input_during_map_transfer,
+                    capture_gated_inputs.run_if(input_is_gated),
+                    flush_input_buffer
+                        .run_if(in_state(AppState::Gameplay))
+                        .run_if(|gate: Res<InputGate>| !gate.is_held()),
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn input_is_gated(state: Res<State<AppState>>, gate: Res<InputGate>) -> bool {
+    matches!(state.get(), AppState::Loading) || gate.is_held()
+}
+
+fn gate_input_during_map_transfer(
+    mut incoming: MessageReader<ServerMessageReceived>,
+    mut gate: ResMut<InputGate>,
+    mut transfer_pending: Local<bool>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        match message {
+            ServerMessage::MapTransfer(_) if !*transfer_pending => {
+                *transfer_pending = true;
+                gate.hold();
+            }
+            ServerMessage::EnterMap { .. } if *transfer_pending => {
+                *transfer_pending = false;
+                gate.release();
+            }
+            // A cast rejected for cooldown does not answer the transfer.
+            ServerMessage::Error(error)
+                if *transfer_pending && error.kind != ServerErrorKind::SkillCooldown =>
+            {
+                *transfer_pending = false;
+                gate.release();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn capture_gated_inputs(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    mut buffer: ResMut<InputBuffer>,
+) {
+    let now_secs = time.elapsed_secs_f64();
+
+    for key in keys.get_just_pressed() {
+        if GAMEPLAY_KEYS.contains(key) {
+            buffer.push(BufferedInput::Key(*key), now_secs);
+        }
+    }
+
+    let cursor = windows
+        .single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+    for button in mouse.get_just_pressed() {
+        buffer.push(
+            BufferedInput::MouseClick {
+                button: *button,
+                cursor,
+            },
+            now_secs,
+        );
+    }
+}
+
+fn flush_input_buffer(
+    time: Res<Time>,
+    mut buffer: ResMut<InputBuffer>,
+    mut replayed: MessageWriter<ReplayedInput>,
+) {
+    if buffer.is_empty() {
+        return;
+    }
+
+    for input in buffer.drain_replayable(time.elapsed_secs_f64()) {
+        replayed.write(ReplayedInput(input));
+    }
+}
+
+fn clear_input_buffer(mut buffer: ResMut<InputBuffer>) {
+    buffer.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_fresh_hotkeys_are_replayed() {
+        let mut buffer = InputBuffer::default();
+        buffer.push(BufferedInput::Key(KeyCode::Digit1), 0.0);
+        buffer.push(
+            BufferedInput::MouseClick {
+                button: MouseButton::Left,
+                cursor: Some(Vec2::new(10.0, 20.0)),
+            },
+            1.0,
+        );
+        buffer.push(BufferedInput::Key(KeyCode::KeyQ), 1.0);
+
+        let replay = buffer.drain_replayable(2.0);
+
+        assert_eq!(replay, vec![BufferedInput::Key(KeyCode::KeyQ)]);
+        assert_eq!(buffer.discarded, 2);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn buffer_drops_oldest_when_full() {
+        let mut buffer = InputBuffer::default();
+        for _ in 0..MAX_BUFFERED_INPUTS + 3 {
+            buffer.push(BufferedInput::Key(KeyCode::Digit2), 0.0);
+        }
+
+        assert_eq!(buffer.len(), MAX_BUFFERED_INPUTS);
+        assert_eq!(buffer.discarded, 3);
+    }
+
+    #[test]
+    fn gate_counts_nested_holds() {
+        let mut gate = InputGate::default();
+        gate.hold();
+        gate.hold();
+        gate.release();
+        assert!(gate.is_held());
+        gate.release();
+        gate.release();
+        assert!(!gate.is_held());
+    }
+}
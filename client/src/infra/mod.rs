@@ -1,6 +1,9 @@
 //! Infrastructure layer.
 
 pub mod assets;
+pub mod audio;
 pub mod input;
+pub mod network;
 pub mod persistence;
 pub mod render;
+pub mod replay;
@@ -0,0 +1,415 @@
+//! Network diagnostics shared by the transport and prediction layers.
+
+use bevy::prelude::*;
+use protocol::{
+    ClientMessage, LatencyEstimator, PingSample, SequenceVerdict, SequencedReceiver,
+    SequencedSender, ServerMessage, WirePacket,
+};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Length of the sliding window used to compute per-second message rates.
+const RATE_WINDOW_SECS: f32 = 1.0;
+
+/// Seconds between pings. They also keep the session open, well under the
+/// server's 30 s idle timeout.
+const PING_INTERVAL_SECS: f64 = 2.0;
+
+/// Server message decoded by the transport.
+#[derive(Message, Debug, Clone)]
+pub struct ServerMessageReceived(pub ServerMessage);
+
+/// Client message queued for the transport to send.
+#[derive(Message, Debug, Clone)]
+pub struct SendClientMessage(pub ClientMessage);
+
+pub struct NetworkPlugin;
+
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NetworkStats>()
+            .add_message::<ServerMessageReceived>()
+            .add_message::<SendClientMessage>()
+            .add_systems(
+                Update,
+                (record_network_traffic, advance_network_stats).chain(),
+            )
+            .add_systems(
+                Update,
+                send_ping.run_if(in_state(crate::AppState::Gameplay)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MessageDirection {
+    Incoming,
+    Outgoing,
+}
+
+impl MessageDirection {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Incoming => "RX",
+            Self::Outgoing => "TX",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MessageCounter {
+    pub total: u64,
+    pub per_second: f32,
+    window_count: u64,
+}
+
+/// Rolling network statistics fed by the transport and the prediction layer.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct NetworkStats {
+    counters: BTreeMap<(MessageDirection, &'static str), MessageCounter>,
+    window_elapsed: f32,
+    pub last_server_tick: Option<u32>,
+    pub last_snapshot_at_secs: Option<f64>,
+    pub predicted_position: Option<Vec3>,
+    pub server_position: Option<Vec3>,
+    pub prediction_error: f32,
+    pub max_prediction_error: f32,
+    pub latency: LatencyEstimator,
+    /// Movement datagrams received, with duplicates and stale ones dropped.
+    pub datagrams_in: SequencedReceiver,
+    /// Sequence numbers of the `Move` datagrams sent.
+    pub datagrams_out: SequencedSender,
+}
+
+impl NetworkStats {
+    pub fn record_incoming(&mut self, message: &ServerMessage, now_secs: f64, unix_ms: u64) {
+        self.bump(MessageDirection::Incoming, server_message_kind(message));
+        match message {
+            ServerMessage::StateDelta { server_tick, .. } => {
+                self.last_server_tick = Some(*server_tick);
+                self.last_snapshot_at_secs = Some(now_secs);
+            }
+            ServerMessage::Pong {
+                client_sent_ms,
+                server_received_ms,
+                server_sent_ms,
+            } => self.latency.record(PingSample {
+                client_sent_ms: *client_sent_ms,
+                server_received_ms: *server_received_ms,
+                server_sent_ms: *server_sent_ms,
+                client_received_ms: unix_ms,
+            }),
+            _ => {}
+        }
+    }
+
+    /// Runs a datagram through the per-route sequencing before the transport
+    /// hands it on as [`ServerMessageReceived`]; `false` means drop it, as a
+    /// newer position from its route was already delivered.
+    pub fn accept_datagram(&mut self, packet: &WirePacket, unix_ms: u64) -> bool {
+        self.datagrams_in.receive(packet, unix_ms) == SequenceVerdict::Accepted
+    }
+
+    /// Numbers a datagram the transport is about to send.
+    pub fn stamp_datagram(&mut self, packet: &mut WirePacket) {
+        self.datagrams_out.stamp(packet);
+    }
+
+    pub fn record_outgoing(&mut self, message: &ClientMessage) {
+        self.bump(MessageDirection::Outgoing, client_message_kind(message));
+    }
+
+    /// Stores the latest authoritative position next to the locally predicted one.
+    pub fn record_prediction(&mut self, predicted: Vec3, server: Vec3) {
+        let error = predicted.distance(server);
+        self.predicted_position = Some(predicted);
+        self.server_position = Some(server);
+        self.prediction_error = error;
+        self.max_prediction_error = self.max_prediction_error.max(error);
+    }
+
+    /// Seconds since the last world snapshot arrived, if any arrived yet.
+    pub fn snapshot_age_secs(&self, now_secs: f64) -> Option<f32> {
+        self.last_snapshot_at_secs
+            .map(|received_at| (now_secs - received_at).max(0.0) as f32)
+    }
+
+    /// Advances the rate window; rates are refreshed once per elapsed window.
+    pub fn advance(&mut self, delta_secs: f32) {
+        self.window_elapsed += delta_secs;
+        if self.window_elapsed < RATE_WINDOW_SECS {
+            return;
+        }
+
+        let window = self.window_elapsed;
+        for counter in self.counters.values_mut() {
+            counter.per_second = counter.window_count as f32 / window;
+            counter.window_count = 0;
+        }
+        self.window_elapsed = 0.0;
+    }
+
+    pub fn counters(
+        &self,
+    ) -> impl Iterator<Item = (MessageDirection, &'static str, &MessageCounter)> {
+        self.counters
+            .iter()
+            .map(|((direction, kind), counter)| (*direction, *kind, counter))
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn bump(&mut self, direction: MessageDirection, kind: &'static str) {
+        let counter = self.counters.entry((direction, kind)).or_default();
+        counter.total += 1;
+        counter.window_count += 1;
+    }
+}
+
+pub fn client_message_kind(message: &ClientMessage) -> &'static str {
+    match message {
+        ClientMessage::Hello(_) => "Hello",
+        ClientMessage::KeepAlive { .. } => "KeepAlive",
+        ClientMessage::Ping { .. } => "Ping",
+        ClientMessage::SelectCharacter { .. } => "SelectCharacter",
+        ClientMessage::Move(_) => "Move",
+        ClientMessage::UseSkill(_) => "UseSkill",
+        ClientMessage::Chat(_) => "Chat",
+        ClientMessage::Whisper(_) => "Whisper",
+        ClientMessage::MapTransferAck { .. } => "MapTransferAck",
+        ClientMessage::RequestMailbox => "RequestMailbox",
+        ClientMessage::ClaimMail { .. } => "ClaimMail",
+        ClientMessage::RequestAccountSettings => "RequestAccountSettings",
+        ClientMessage::StoreAccountSettings(_) => "StoreAccountSettings",
+        ClientMessage::SetHelperActive { .. } => "SetHelperActive",
+        ClientMessage::JoinGens { .. } => "JoinGens",
+        ClientMessage::Emote { .. } => "Emote",
+        ClientMessage::TalkToNpc { .. } => "TalkToNpc",
+        ClientMessage::EquipItem { .. } => "EquipItem",
+        ClientMessage::MoveItem { .. } => "MoveItem",
+        ClientMessage::UnequipItem { .. } => "UnequipItem",
+        ClientMessage::DropItem { .. } => "DropItem",
+        ClientMessage::PickupItem { .. } => "PickupItem",
+        ClientMessage::UseItem { .. } => "UseItem",
+        ClientMessage::RequestBestiary => "RequestBestiary",
+        ClientMessage::RequestDropTable { .. } => "RequestDropTable",
+        ClientMessage::RequestTrade { .. } => "RequestTrade",
+        ClientMessage::RespondTrade { .. } => "RespondTrade",
+        ClientMessage::AddTradeItem { .. } => "AddTradeItem",
+        ClientMessage::RemoveTradeItem { .. } => "RemoveTradeItem",
+        ClientMessage::SetTradeZen { .. } => "SetTradeZen",
+        ClientMessage::LockTrade { .. } => "LockTrade",
+        ClientMessage::ConfirmTrade => "ConfirmTrade",
+        ClientMessage::CancelTrade => "CancelTrade",
+        ClientMessage::OpenPersonalStore { .. } => "OpenPersonalStore",
+        ClientMessage::ClosePersonalStore => "ClosePersonalStore",
+        ClientMessage::BrowsePersonalStore { .. } => "BrowsePersonalStore",
+        ClientMessage::BuyFromPersonalStore { .. } => "BuyFromPersonalStore",
+        ClientMessage::RegisterForEvent { .. } => "RegisterForEvent",
+        ClientMessage::CancelEventRegistration { .. } => "CancelEventRegistration",
+        ClientMessage::Logout => "Logout",
+    }
+}
+
+pub fn server_message_kind(message: &ServerMessage) -> &'static str {
+    match message {
+        ServerMessage::HelloAck { .. } => "HelloAck",
+        ServerMessage::CharacterList { .. } => "CharacterList",
+        ServerMessage::EnterMap { .. } => "EnterMap",
+        ServerMessage::StateDelta { .. } => "StateDelta",
+        ServerMessage::WorldSnapshot(_) => "WorldSnapshot",
+        ServerMessage::WorldDelta(_) => "WorldDelta",
+        ServerMessage::Chat(_) => "Chat",
+        ServerMessage::WhisperResult { .. } => "WhisperResult",
+        ServerMessage::SystemNotice(_) => "SystemNotice",
+        ServerMessage::EntityPath(_) => "EntityPath",
+        ServerMessage::EntityStatus { .. } => "EntityStatus",
+        ServerMessage::MapTransfer(_) => "MapTransfer",
+        ServerMessage::Disconnect { .. } => "Disconnect",
+        ServerMessage::MonsterAffixes { .. } => "MonsterAffixes",
+        ServerMessage::EntityEmote { .. } => "EntityEmote",
+        ServerMessage::GuildWarScore(_) => "GuildWarScore",
+        ServerMessage::DamageEvent(_) => "DamageEvent",
+        ServerMessage::DoorStates { .. } => "DoorStates",
+        ServerMessage::Pong { .. } => "Pong",
+        ServerMessage::Mailbox { .. } => "Mailbox",
+        ServerMessage::MailClaimed { .. } => "MailClaimed",
+        ServerMessage::ItemsExpired { .. } => "ItemsExpired",
+        ServerMessage::ItemMoved { .. } => "ItemMoved",
+        ServerMessage::ItemDropped { .. } => "ItemDropped",
+        ServerMessage::ItemPickedUp { .. } => "ItemPickedUp",
+        ServerMessage::ItemUsed { .. } => "ItemUsed",
+        ServerMessage::ItemRejected { .. } => "ItemRejected",
+        ServerMessage::TradeRequested { .. } => "TradeRequested",
+        ServerMessage::TradeOpened { .. } => "TradeOpened",
+        ServerMessage::TradeUpdated { .. } => "TradeUpdated",
+        ServerMessage::TradeRejected { .. } => "TradeRejected",
+        ServerMessage::TradeClosed { .. } => "TradeClosed",
+        ServerMessage::PersonalStoreSign { .. } => "PersonalStoreSign",
+        ServerMessage::PersonalStore(_) => "PersonalStore",
+        ServerMessage::PersonalStorePurchased { .. } => "PersonalStorePurchased",
+        ServerMessage::PersonalStoreSold { .. } => "PersonalStoreSold",
+        ServerMessage::PersonalStoreRejected { .. } => "PersonalStoreRejected",
+        ServerMessage::EventRegistered { .. } => "EventRegistered",
+        ServerMessage::EventRegistrationRejected { .. } => "EventRegistrationRejected",
+        ServerMessage::EventTicketConsumed { .. } => "EventTicketConsumed",
+        ServerMessage::EventCountdown(_) => "EventCountdown",
+        ServerMessage::EventResult(_) => "EventResult",
+        ServerMessage::Maintenance(_) => "Maintenance",
+        ServerMessage::EventNotice(_) => "EventNotice",
+        ServerMessage::DoppelgangerStatus(_) => "DoppelgangerStatus",
+        ServerMessage::AccountSettings { .. } => "AccountSettings",
+        ServerMessage::HelperStatus { .. } => "HelperStatus",
+        ServerMessage::GensStatus(_) => "GensStatus",
+        ServerMessage::QuestStatus(_) => "QuestStatus",
+        ServerMessage::Bestiary { .. } => "Bestiary",
+        ServerMessage::BestiaryUnlocked { .. } => "BestiaryUnlocked",
+        ServerMessage::DropTable(_) => "DropTable",
+        ServerMessage::Error(_) => "Error",
+    }
+}
+
+fn record_network_traffic(
+    time: Res<Time>,
+    mut stats: ResMut<NetworkStats>,
+    mut incoming: MessageReader<ServerMessageReceived>,
+    mut outgoing: MessageReader<SendClientMessage>,
+) {
+    let now_secs = time.elapsed_secs_f64();
+    let unix_ms = unix_time_ms();
+    for ServerMessageReceived(message) in incoming.read() {
+        stats.record_incoming(message, now_secs, unix_ms);
+    }
+    for SendClientMessage(message) in outgoing.read() {
+        stats.record_outgoing(message);
+    }
+}
+
+fn advance_network_stats(time: Res<Time>, mut stats: ResMut<NetworkStats>) {
+    stats.advance(time.delta_secs());
+}
+
+/// Measured in real time so a throttled or minimized window, which updates
+/// only a few times a second, still keeps the session open.
+fn send_ping(
+    time: Res<Time<Real>>,
+    mut last_sent_secs: Local<Option<f64>>,
+    mut outgoing: MessageWriter<SendClientMessage>,
+) {
+    let now_secs = time.elapsed_secs_f64();
+    if last_sent_secs.is_some_and(|sent| now_secs - sent < PING_INTERVAL_SECS) {
+        return;
+    }
+
+    *last_sent_secs = Some(now_secs);
+    outgoing.write(SendClientMessage(ClientMessage::Ping {
+        client_sent_ms: unix_time_ms(),
+    }));
+}
+
+/// Wall clock, which the server's `Pong` timestamps are compared with.
+fn unix_time_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rates_are_computed_per_window() {
+        let mut stats = NetworkStats::default();
+        for _ in 0..4 {
+            stats.record_outgoing(&ClientMessage::KeepAlive { client_time_ms: 0 });
+        }
+
+        stats.advance(0.5);
+        let (_, _, counter) = stats.counters().next().unwrap();
+        assert_eq!(counter.per_second, 0.0);
+
+        stats.advance(0.5);
+        let (direction, kind, counter) = stats.counters().next().unwrap();
+        assert_eq!(direction, MessageDirection::Outgoing);
+        assert_eq!(kind, "KeepAlive");
+        assert_eq!(counter.total, 4);
+        assert!((counter.per_second - 4.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn state_delta_updates_snapshot_age() {
+        let mut stats = NetworkStats::default();
+        assert_eq!(stats.snapshot_age_secs(10.0), None);
+
+        stats.record_incoming(
+            &ServerMessage::StateDelta {
+                server_tick: 42,
+                entities: Vec::new(),
+            },
+            10.0,
+            0,
+        );
+
+        assert_eq!(stats.last_server_tick, Some(42));
+        assert_eq!(stats.snapshot_age_secs(10.25), Some(0.25));
+    }
+
+    #[test]
+    fn pongs_feed_the_latency_estimate() {
+        let mut stats = NetworkStats::default();
+        stats.record_incoming(
+            &ServerMessage::Pong {
+                client_sent_ms: 1_000,
+                server_received_ms: 1_540,
+                server_sent_ms: 1_550,
+            },
+            1.0,
+            1_090,
+        );
+
+        assert_eq!(stats.latency.rtt_ms(), Some(80.0));
+        assert_eq!(stats.latency.clock_offset_ms(), Some(500));
+    }
+
+    #[test]
+    fn stale_position_datagrams_are_dropped() {
+        let delta = |sequence, sent_at_ms| {
+            WirePacket::server(
+                1,
+                protocol::RouteKey::LOBBY,
+                sequence,
+                None,
+                sent_at_ms,
+                ServerMessage::StateDelta {
+                    server_tick: sequence,
+                    entities: Vec::new(),
+                },
+            )
+        };
+        let mut stats = NetworkStats::default();
+
+        assert!(stats.accept_datagram(&delta(2, 100), 140));
+        assert!(!stats.accept_datagram(&delta(1, 50), 150));
+        assert!(!stats.accept_datagram(&delta(2, 100), 160));
+        assert!(stats.accept_datagram(&delta(3, 150), 190));
+
+        let sequencing = stats.datagrams_in.stats();
+        assert_eq!(sequencing.accepted, 2);
+        assert_eq!(sequencing.stale, 1);
+        assert_eq!(sequencing.duplicates, 1);
+    }
+
+    #[test]
+    fn prediction_error_tracks_peak() {
+        let mut stats = NetworkStats::default();
+        stats.record_prediction(Vec3::ZERO, Vec3::new(3.0, 0.0, 4.0));
+        stats.record_prediction(Vec3::ZERO, Vec3::new(1.0, 0.0, 0.0));
+
+        assert_eq!(stats.prediction_error, 1.0);
+        assert_eq!(stats.max_prediction_error, 5.0);
+    }
+}
@@ -0,0 +1,81 @@
+//! Panic reports written to the crash directory before the default hook runs.
+//!
+//! Each crash gets its own `crash-<ms>/` bundle: the panic message with its
+//! backtrace, and a copy of the log the run was writing.
+
+use std::backtrace::Backtrace;
+use std::fs;
+use std::io;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::paths::client_dirs;
+
+const PANIC_FILE_NAME: &str = "panic.log";
+
+pub fn install_crash_log_hook() {
+    let crash_dir = client_dirs().crash_dir.clone();
+    let log_file = client_dirs().log_file();
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let report = format!("{info}\n\n{}", Backtrace::force_capture());
+        match write_crash_bundle(&crash_dir, &report, &log_file) {
+            Ok(path) => eprintln!("Crash report written to {}", path.display()),
+            Err(error) => eprintln!(
+                "Failed to write crash report to '{}': {}",
+                crash_dir.display(),
+                error
+            ),
+        }
+        default_hook(info);
+    }));
+}
+
+/// Writes `report` and a copy of `log_file`, when there is one, to a new
+/// bundle under `crash_dir`. Returns the bundle directory.
+fn write_crash_bundle(crash_dir: &Path, report: &str, log_file: &Path) -> io::Result<PathBuf> {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+    let bundle = crash_dir.join(format!("crash-{stamp}"));
+    fs::create_dir_all(&bundle)?;
+    fs::write(bundle.join(PANIC_FILE_NAME), report)?;
+    if let Some(name) = log_file.file_name().filter(|_| log_file.is_file()) {
+        fs::copy(log_file, bundle.join(name))?;
+    }
+    Ok(bundle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn bundle_holds_the_report_and_the_run_log() {
+        let root = env::temp_dir().join(format!("mu-client-crash-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let log_file = root.join("logs").join("client.log");
+        fs::create_dir_all(log_file.parent().unwrap()).unwrap();
+        fs::write(&log_file, "INFO loading Lorencia").unwrap();
+
+        let bundle = write_crash_bundle(&root.join("crash"), "boom", &log_file).unwrap();
+        assert_eq!(
+            fs::read_to_string(bundle.join(PANIC_FILE_NAME)).unwrap(),
+            "boom"
+        );
+        assert_eq!(
+            fs::read_to_string(bundle.join("client.log")).unwrap(),
+            "INFO loading Lorencia"
+        );
+
+        // Crashes before the first log line still get their report.
+        let missing = root.join("logs").join("missing.log");
+        let bundle = write_crash_bundle(&root.join("crash-2"), "boom", &missing).unwrap();
+        assert!(bundle.join(PANIC_FILE_NAME).is_file());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
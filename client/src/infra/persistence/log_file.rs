@@ -0,0 +1,129 @@
+//! Log files of the client.
+//!
+//! Every run writes `client.log` in the log directory; the logs of earlier
+//! runs stay next to it as `client.1.log`, `client.2.log` and so on, newest
+//! first. A run that logs past the size limit rotates the same way.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Size at which the current log is rotated.
+pub const MAX_LOG_FILE_BYTES: u64 = 8 * 1024 * 1024;
+/// Log files kept, the current one included.
+pub const KEPT_LOG_FILES: usize = 5;
+
+/// Writer behind the file log layer.
+pub struct RotatingLog {
+    path: PathBuf,
+    file: File,
+    written: u64,
+    max_bytes: u64,
+    kept: usize,
+}
+
+impl RotatingLog {
+    /// Moves the logs of earlier runs aside and starts a new one at `path`.
+    pub fn open(path: PathBuf, max_bytes: u64, kept: usize) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        rotate_log_files(&path, kept)?;
+        let file = File::create(&path)?;
+        Ok(Self {
+            path,
+            file,
+            written: 0,
+            max_bytes,
+            kept,
+        })
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        rotate_log_files(&self.path, self.kept)?;
+        self.file = File::create(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingLog {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// `client.log` numbered `n`, e.g. `client.2.log`.
+pub fn numbered_log_file(path: &Path, n: usize) -> PathBuf {
+    path.with_extension(format!("{n}.log"))
+}
+
+/// Shifts `path` and its numbered copies up by one, dropping whatever would
+/// go past `kept` files.
+pub fn rotate_log_files(path: &Path, kept: usize) -> io::Result<()> {
+    let files: Vec<PathBuf> = std::iter::once(path.to_path_buf())
+        .chain((1..kept.max(1)).map(|n| numbered_log_file(path, n)))
+        .collect();
+    if let Some(oldest) = files.last().filter(|oldest| oldest.exists()) {
+        fs::remove_file(oldest)?;
+    }
+    for pair in files.windows(2).rev() {
+        if pair[0].exists() {
+            fs::rename(&pair[0], &pair[1])?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("mu-client-logs-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn runs_and_full_logs_rotate_keeping_the_newest() {
+        let dir = scratch_dir("rotate");
+        let path = dir.join("client.log");
+        for run in ["first", "second", "third"] {
+            let mut log = RotatingLog::open(path.clone(), 1024, 3).unwrap();
+            log.write_all(run.as_bytes()).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "third");
+        assert_eq!(
+            fs::read_to_string(numbered_log_file(&path, 1)).unwrap(),
+            "second"
+        );
+        assert_eq!(
+            fs::read_to_string(numbered_log_file(&path, 2)).unwrap(),
+            "first"
+        );
+        assert!(!numbered_log_file(&path, 3).exists());
+
+        let mut log = RotatingLog::open(path.clone(), 8, 3).unwrap();
+        log.write_all(b"12345").unwrap();
+        log.write_all(b"6789").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "6789");
+        assert_eq!(
+            fs::read_to_string(numbered_log_file(&path, 1)).unwrap(),
+            "12345"
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1 +1,4 @@
+pub mod crash_log;
+pub mod log_file;
+pub mod paths;
 pub mod settings_store;
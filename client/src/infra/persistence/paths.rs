@@ -0,0 +1,173 @@
+//! Where the client keeps the files it writes.
+//!
+//! Settings go to the platform config directory (XDG on Linux, AppData on
+//! Windows, Application Support on macOS), crash reports and client logs to
+//! the platform state directory, so the game also runs from read-only install
+//! locations.
+//! `MU_CLIENT_HOME` puts everything under one directory instead.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use bevy::log::{info, warn};
+use bevy::prelude::{Res, Resource};
+use platform_dirs::AppDirs;
+
+/// Overrides the platform layout with a single directory.
+pub const HOME_ENV_VAR: &str = "MU_CLIENT_HOME";
+
+const APP_NAME: &str = "mu-rust";
+const SETTINGS_FILE_NAME: &str = "settings.yaml";
+const CRASH_DIR_NAME: &str = "crash";
+const LOG_DIR_NAME: &str = "logs";
+const LOG_FILE_NAME: &str = "client.log";
+/// Files older builds wrote to the working directory.
+const LEGACY_FILES: [&str; 1] = [SETTINGS_FILE_NAME];
+
+static CLIENT_DIRS: OnceLock<ClientDirs> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientDirs {
+    pub config_dir: PathBuf,
+    pub crash_dir: PathBuf,
+    pub log_dir: PathBuf,
+}
+
+impl ClientDirs {
+    /// Layout under `home` when set, the platform layout otherwise. Falls back
+    /// to the working directory when the platform has no home directory.
+    pub fn resolve(home: Option<PathBuf>) -> Self {
+        if let Some(home) = home {
+            return Self::under(home);
+        }
+
+        match AppDirs::new(Some(APP_NAME), false) {
+            Some(dirs) => Self {
+                config_dir: dirs.config_dir,
+                crash_dir: dirs.state_dir.join(CRASH_DIR_NAME),
+                log_dir: dirs.state_dir.join(LOG_DIR_NAME),
+            },
+            None => Self::under(PathBuf::from(".")),
+        }
+    }
+
+    fn under(root: PathBuf) -> Self {
+        Self {
+            crash_dir: root.join(CRASH_DIR_NAME),
+            log_dir: root.join(LOG_DIR_NAME),
+            config_dir: root,
+        }
+    }
+
+    pub fn settings_file(&self) -> PathBuf {
+        self.config_dir.join(SETTINGS_FILE_NAME)
+    }
+
+    /// Log of the current run; older runs are numbered next to it.
+    pub fn log_file(&self) -> PathBuf {
+        self.log_dir.join(LOG_FILE_NAME)
+    }
+
+    /// Moves files left in `legacy_root` by older builds, keeping any copy
+    /// already in the new location. Returns the files moved.
+    pub fn migrate_from(&self, legacy_root: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut moved = Vec::new();
+        for name in LEGACY_FILES {
+            let from = legacy_root.join(name);
+            let to = self.config_dir.join(name);
+            if !from.is_file() || to.exists() {
+                continue;
+            }
+
+            fs::create_dir_all(&self.config_dir)?;
+            if fs::rename(&from, &to).is_err() {
+                // Across filesystems a rename fails; copy and drop the original.
+                fs::copy(&from, &to)?;
+                fs::remove_file(&from)?;
+            }
+            moved.push(to);
+        }
+        Ok(moved)
+    }
+}
+
+/// Directories of this run, resolved once from the environment.
+pub fn client_dirs() -> &'static ClientDirs {
+    CLIENT_DIRS.get_or_init(|| ClientDirs::resolve(env::var_os(HOME_ENV_VAR).map(PathBuf::from)))
+}
+
+/// Outcome of [`migrate_legacy_files`]. The move happens before the app
+/// and its logger exist, so [`log_legacy_migration`] reports it at startup.
+#[derive(Resource, Debug)]
+pub struct LegacyMigration(io::Result<Vec<PathBuf>>);
+
+/// Moves files older builds left in the working directory.
+pub fn migrate_legacy_files() -> LegacyMigration {
+    LegacyMigration(client_dirs().migrate_from(Path::new(".")))
+}
+
+pub fn log_legacy_migration(migration: Res<LegacyMigration>) {
+    match &migration.0 {
+        Ok(moved) => {
+            for path in moved {
+                info!("Moved {} to {}", SETTINGS_FILE_NAME, path.display());
+            }
+        }
+        Err(error) => warn!(
+            "Failed to move old files to '{}': {}",
+            client_dirs().config_dir.display(),
+            error
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("mu-client-paths-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn home_override_keeps_everything_in_one_directory() {
+        let dirs = ClientDirs::resolve(Some(PathBuf::from("/opt/mu-home")));
+        assert_eq!(
+            dirs.settings_file(),
+            PathBuf::from("/opt/mu-home/settings.yaml")
+        );
+        assert_eq!(dirs.crash_dir, PathBuf::from("/opt/mu-home/crash"));
+        assert_eq!(
+            dirs.log_file(),
+            PathBuf::from("/opt/mu-home/logs/client.log")
+        );
+    }
+
+    #[test]
+    fn legacy_settings_move_unless_the_new_location_has_some() {
+        let root = scratch_dir("migrate");
+        let legacy = root.join("install");
+        fs::create_dir_all(&legacy).unwrap();
+        fs::write(legacy.join(SETTINGS_FILE_NAME), "old").unwrap();
+
+        let dirs = ClientDirs::resolve(Some(root.join("home")));
+        assert_eq!(
+            dirs.migrate_from(&legacy).unwrap(),
+            vec![dirs.settings_file()]
+        );
+        assert_eq!(fs::read_to_string(dirs.settings_file()).unwrap(), "old");
+        assert!(!legacy.join(SETTINGS_FILE_NAME).exists());
+
+        fs::write(legacy.join(SETTINGS_FILE_NAME), "stale").unwrap();
+        assert!(dirs.migrate_from(&legacy).unwrap().is_empty());
+        assert_eq!(fs::read_to_string(dirs.settings_file()).unwrap(), "old");
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,195 @@
+//! Replay files: the server messages a session received and the client
+//! messages it sent, with their timing.
+//!
+//! Files use the session recording format of [`protocol::recording`], so the
+//! same tools read client replays and server recordings. `at_ms` of each
+//! record holds the milliseconds since recording started; outgoing `Move`
+//! datagrams carry the sequence stamped on them for their route. Start
+//! recording with `--record-replay <path>`; [`crate::app::replay`] plays
+//! files back.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use protocol::{
+    PacketPayload, RecordedPacket, RecordingError, RecordingReader, RecordingWriter, RouteKey,
+    TransportKind, WirePacket, preferred_channel,
+};
+
+use crate::infra::network::{NetworkStats, SendClientMessage, ServerMessageReceived};
+
+pub const RECORD_FLAG: &str = "--record-replay";
+
+/// Seconds between flushes, so a crash loses at most this much.
+const FLUSH_INTERVAL_SECS: f64 = 1.0;
+
+/// Records of a replay file, in recording order. A file cut short by a
+/// crash still reads up to its last complete record.
+pub fn read_replay(path: &Path) -> Result<Vec<RecordedPacket>, RecordingError> {
+    RecordingReader::open(BufReader::new(File::open(path)?))?.collect()
+}
+
+/// Replay file being written for this session. The index is written when
+/// the recorder is dropped with the app.
+#[derive(Resource)]
+pub struct ReplayRecorder {
+    path: PathBuf,
+    writer: Option<RecordingWriter<BufWriter<File>>>,
+    started_at_secs: Option<f64>,
+    flushed_at_secs: f64,
+    sequence: u32,
+}
+
+impl ReplayRecorder {
+    pub fn create(path: PathBuf) -> Result<Self, RecordingError> {
+        let writer = RecordingWriter::new(BufWriter::new(File::create(&path)?))?;
+        Ok(Self {
+            path,
+            writer: Some(writer),
+            started_at_secs: None,
+            flushed_at_secs: 0.0,
+            sequence: 0,
+        })
+    }
+
+    fn record(&mut self, payload: PacketPayload, now_secs: f64, stats: &mut NetworkStats) {
+        let Some(writer) = self.writer.as_mut() else {
+            return;
+        };
+        let started_at = *self.started_at_secs.get_or_insert(now_secs);
+        let at_ms = ((now_secs - started_at) * 1000.0) as u64;
+        let outgoing_datagram = matches!(payload, PacketPayload::Client(_))
+            && preferred_channel(&payload).transport() == TransportKind::Datagram;
+        let mut packet = WirePacket::new(0, RouteKey::LOBBY, self.sequence, None, at_ms, payload);
+        if outgoing_datagram {
+            stats.stamp_datagram(&mut packet);
+        }
+        if let Err(error) = writer.record(at_ms, &packet) {
+            // One bad write would leave the rest of the file unreadable.
+            warn!(
+                "Stopped recording replay {}: {}",
+                self.path.display(),
+                error
+            );
+            self.writer = None;
+            return;
+        }
+        self.sequence = self.sequence.wrapping_add(1);
+    }
+}
+
+impl Drop for ReplayRecorder {
+    fn drop(&mut self) {
+        let Some(writer) = self.writer.take() else {
+            return;
+        };
+        if let Err(error) = writer.finish() {
+            warn!("Failed to finish replay {}: {}", self.path.display(), error);
+        }
+    }
+}
+
+/// Records the session to `path`; nothing is recorded if the file cannot be
+/// created.
+pub struct ReplayRecorderPlugin {
+    pub path: PathBuf,
+}
+
+impl Plugin for ReplayRecorderPlugin {
+    fn build(&self, app: &mut App) {
+        match ReplayRecorder::create(self.path.clone()) {
+            Ok(recorder) => {
+                info!("Recording replay to {}", self.path.display());
+                app.insert_resource(recorder)
+                    .add_systems(Update, record_replay);
+            }
+            Err(error) => warn!(
+                "Replay {} could not be created: {}",
+                self.path.display(),
+                error
+            ),
+        }
+    }
+}
+
+fn record_replay(
+    time: Res<Time<Real>>,
+    mut recorder: ResMut<ReplayRecorder>,
+    mut stats: ResMut<NetworkStats>,
+    mut incoming: MessageReader<ServerMessageReceived>,
+    mut outgoing: MessageReader<SendClientMessage>,
+) {
+    let now_secs = time.elapsed_secs_f64();
+    for ServerMessageReceived(message) in incoming.read() {
+        recorder.record(PacketPayload::Server(message.clone()), now_secs, &mut stats);
+    }
+    for SendClientMessage(message) in outgoing.read() {
+        recorder.record(PacketPayload::Client(message.clone()), now_secs, &mut stats);
+    }
+
+    if now_secs - recorder.flushed_at_secs >= FLUSH_INTERVAL_SECS {
+        recorder.flushed_at_secs = now_secs;
+        let recorder = &mut *recorder;
+        if let Some(Err(error)) = recorder.writer.as_mut().map(RecordingWriter::flush) {
+            warn!(
+                "Failed to flush replay {}: {}",
+                recorder.path.display(),
+                error
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::{ClientMessage, MoveInput, ServerMessage};
+
+    #[test]
+    fn recorded_sessions_read_back_in_order() {
+        let path = std::env::temp_dir().join(format!(
+            "mu-replay-{}-{:?}.murec",
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        let enter = PacketPayload::Server(ServerMessage::EnterMap {
+            entity_id: 7,
+            map_id: 3,
+            x: 120,
+            y: 130,
+        });
+        let keep_alive = PacketPayload::Client(ClientMessage::KeepAlive { client_time_ms: 1 });
+        let step = PacketPayload::Client(ClientMessage::Move(MoveInput {
+            client_tick: 4,
+            x: 121,
+            y: 130,
+            direction: 2,
+            path: [0; 8],
+        }));
+
+        let mut stats = NetworkStats::default();
+        let mut recorder = ReplayRecorder::create(path.clone()).unwrap();
+        recorder.record(enter.clone(), 2.0, &mut stats);
+        recorder.record(keep_alive.clone(), 2.25, &mut stats);
+        recorder.record(step.clone(), 2.5, &mut stats);
+        drop(recorder);
+
+        let records = read_replay(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let recorded: Vec<_> = records
+            .into_iter()
+            .map(|record| (record.at_ms, record.packet.sequence, record.packet.payload))
+            .collect();
+        // The move is numbered on its own, as the datagram it was sent as.
+        assert_eq!(
+            recorded,
+            vec![(0, 0, enter), (250, 1, keep_alive), (500, 0, step)]
+        );
+        assert!(matches!(
+            read_replay(Path::new("missing.murec")),
+            Err(RecordingError::Io(_))
+        ));
+    }
+}
@@ -1 +1,5 @@
 //! Presentation debug surface.
+
+pub mod network_panel;
+
+pub use network_panel::{NetworkDebugPanelState, NetworkDebugPlugin};
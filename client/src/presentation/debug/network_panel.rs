@@ -0,0 +1,156 @@
+use crate::AppState;
+use crate::infra::network::NetworkStats;
+use bevy::math::Isometry3d;
+use bevy::prelude::*;
+use bevy::state::prelude::{OnEnter, in_state};
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+/// Height (in world units) of the capsule drawn at the server position.
+const GHOST_CAPSULE_LENGTH: f32 = 120.0;
+const GHOST_CAPSULE_RADIUS: f32 = 35.0;
+const GHOST_COLOR: Color = Color::srgba(0.2, 0.9, 1.0, 0.8);
+
+#[derive(Resource, Default)]
+pub struct NetworkDebugPanelState {
+    pub open: bool,
+    pub show_server_ghost: bool,
+}
+
+pub struct NetworkDebugPlugin;
+
+impl Plugin for NetworkDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NetworkDebugPanelState>()
+            .add_systems(OnEnter(AppState::Gameplay), reset_network_stats)
+            .add_systems(
+                Update,
+                (
+                    toggle_network_debug_panel,
+                    draw_server_ghost_capsule
+                        .run_if(in_state(AppState::Gameplay))
+                        .run_if(|state: Res<NetworkDebugPanelState>| state.show_server_ghost),
+                ),
+            )
+            .add_systems(
+                EguiPrimaryContextPass,
+                draw_network_debug_panel.run_if(|state: Res<NetworkDebugPanelState>| state.open),
+            );
+    }
+}
+
+fn reset_network_stats(mut stats: ResMut<NetworkStats>) {
+    stats.reset();
+}
+
+fn toggle_network_debug_panel(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut panel_state: ResMut<NetworkDebugPanelState>,
+) {
+    if keys.just_pressed(KeyCode::F6) {
+        panel_state.open = !panel_state.open;
+    }
+}
+
+fn draw_network_debug_panel(
+    mut contexts: EguiContexts,
+    time: Res<Time>,
+    mut stats: ResMut<NetworkStats>,
+    mut panel_state: ResMut<NetworkDebugPanelState>,
+) {
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let now_secs = time.elapsed_secs_f64();
+    let mut open = panel_state.open;
+
+    egui::Window::new("Rede")
+        .open(&mut open)
+        .anchor(egui::Align2::LEFT_TOP, egui::vec2(14.0, 14.0))
+        .resizable(false)
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            egui::Grid::new("network_debug_rates")
+                .striped(true)
+                .num_columns(4)
+                .show(ui, |ui| {
+                    ui.strong("Dir");
+                    ui.strong("Mensagem");
+                    ui.strong("msg/s");
+                    ui.strong("Total");
+                    ui.end_row();
+
+                    for (direction, kind, counter) in stats.counters() {
+                        ui.label(direction.label());
+                        ui.label(kind);
+                        ui.label(format!("{:.1}", counter.per_second));
+                        ui.label(counter.total.to_string());
+                        ui.end_row();
+                    }
+                });
+
+            ui.separator();
+
+            let snapshot_age = stats
+                .snapshot_age_secs(now_secs)
+                .map(|age| format!("{:.0} ms", age * 1000.0))
+                .unwrap_or_else(|| "n/a".to_string());
+            let server_tick = stats
+                .last_server_tick
+                .map(|tick| tick.to_string())
+                .unwrap_or_else(|| "n/a".to_string());
+            let latency = match (stats.latency.rtt_ms(), stats.latency.rtt_variation_ms()) {
+                (Some(rtt), Some(variation)) => format!("{rtt:.0} ms (+/- {variation:.0})"),
+                _ => "n/a".to_string(),
+            };
+            let clock_offset = stats
+                .latency
+                .clock_offset_ms()
+                .map(|offset| format!("{offset:+} ms"))
+                .unwrap_or_else(|| "n/a".to_string());
+            ui.label(format!("Latencia: {latency}"));
+            ui.label(format!("Relogio do servidor: {clock_offset}"));
+            let datagrams = stats.datagrams_in.stats();
+            ui.label(format!("Idade do snapshot: {snapshot_age}"));
+            ui.label(format!(
+                "Datagramas: {} aceitos, {} atrasados, {} duplicados, {} perdidos",
+                datagrams.accepted, datagrams.stale, datagrams.duplicates, datagrams.skipped
+            ));
+            ui.label(format!("Jitter: {:.1} ms", datagrams.jitter_ms));
+            ui.label(format!("Server tick: {server_tick}"));
+            ui.label(format!(
+                "Erro de predicao: {:.1} (max {:.1})",
+                stats.prediction_error, stats.max_prediction_error
+            ));
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.checkbox(
+                    &mut panel_state.show_server_ghost,
+                    "Mostrar posicao do servidor",
+                );
+                if ui.button("Zerar").clicked() {
+                    stats.reset();
+                }
+            });
+        });
+
+    panel_state.open = open;
+}
+
+fn draw_server_ghost_capsule(stats: Res<NetworkStats>, mut gizmos: Gizmos) {
+    let Some(server_position) = stats.server_position else {
+        return;
+    };
+
+    let center = server_position + Vec3::Y * (GHOST_CAPSULE_LENGTH * 0.5 + GHOST_CAPSULE_RADIUS);
+    gizmos.primitive_3d(
+        &Capsule3d::new(GHOST_CAPSULE_RADIUS, GHOST_CAPSULE_LENGTH),
+        Isometry3d::from_translation(center),
+        GHOST_COLOR,
+    );
+
+    if let Some(predicted_position) = stats.predicted_position {
+        gizmos.line(predicted_position, server_position, GHOST_COLOR);
+    }
+}
@@ -0,0 +1,236 @@
+//! Colorblind palettes and font scaling applied to every egui surface.
+
+use crate::infra::assets::current_asset_root_path;
+use crate::settings::{
+    AccessibilitySettings, ColorblindModeSetting, SettingsResource, UiFontSetting,
+};
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use std::sync::Arc;
+
+/// Dyslexia-friendly font, relative to the asset root.
+const DYSLEXIC_FONT_PATH: &str = "ui/fonts/OpenDyslexic-Regular.otf";
+const DYSLEXIC_FONT_NAME: &str = "open_dyslexic";
+
+/// Semantic UI colors; swapped as a whole when a colorblind mode is selected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UiPalette {
+    pub damage_normal: egui::Color32,
+    pub damage_critical: egui::Color32,
+    pub damage_excellent: egui::Color32,
+    pub rarity_excellent: egui::Color32,
+    pub rarity_option: egui::Color32,
+    pub rarity_socket: egui::Color32,
+    pub hp_bar_full: egui::Color32,
+    pub hp_bar_low: egui::Color32,
+    pub guild_ally: egui::Color32,
+    pub guild_hostile: egui::Color32,
+    pub monster_elite: egui::Color32,
+    pub monster_boss: egui::Color32,
+    pub gens_duprian: egui::Color32,
+    pub gens_vanert: egui::Color32,
+    /// Player names by murder status.
+    pub murder_warning: egui::Color32,
+    pub murderer: egui::Color32,
+    pub error: egui::Color32,
+}
+
+impl UiPalette {
+    pub fn for_mode(mode: ColorblindModeSetting) -> Self {
+        match mode {
+            ColorblindModeSetting::Off => Self {
+                damage_normal: egui::Color32::from_rgb(235, 235, 235),
+                damage_critical: egui::Color32::from_rgb(90, 160, 255),
+                damage_excellent: egui::Color32::from_rgb(80, 210, 110),
+                rarity_excellent: egui::Color32::from_rgb(80, 210, 110),
+                rarity_option: egui::Color32::from_rgb(120, 160, 255),
+                rarity_socket: egui::Color32::from_rgb(200, 120, 230),
+                hp_bar_full: egui::Color32::from_rgb(70, 190, 80),
+                hp_bar_low: egui::Color32::from_rgb(210, 50, 45),
+                guild_ally: egui::Color32::from_rgb(90, 200, 255),
+                guild_hostile: egui::Color32::from_rgb(255, 80, 70),
+                monster_elite: egui::Color32::from_rgb(255, 200, 60),
+                monster_boss: egui::Color32::from_rgb(255, 110, 40),
+                gens_duprian: egui::Color32::from_rgb(240, 190, 80),
+                gens_vanert: egui::Color32::from_rgb(170, 130, 255),
+                murder_warning: egui::Color32::from_rgb(255, 160, 60),
+                murderer: egui::Color32::from_rgb(255, 60, 60),
+                error: egui::Color32::from_rgb(230, 90, 90),
+            },
+            // Okabe-Ito hues: blue/orange pairs stay distinct without red-green contrast.
+            ColorblindModeSetting::Deuteranopia => Self {
+                damage_normal: egui::Color32::from_rgb(235, 235, 235),
+                damage_critical: egui::Color32::from_rgb(240, 228, 66),
+                damage_excellent: egui::Color32::from_rgb(86, 180, 233),
+                rarity_excellent: egui::Color32::from_rgb(86, 180, 233),
+                rarity_option: egui::Color32::from_rgb(240, 228, 66),
+                rarity_socket: egui::Color32::from_rgb(204, 121, 167),
+                hp_bar_full: egui::Color32::from_rgb(0, 114, 178),
+                hp_bar_low: egui::Color32::from_rgb(213, 94, 0),
+                guild_ally: egui::Color32::from_rgb(86, 180, 233),
+                guild_hostile: egui::Color32::from_rgb(213, 94, 0),
+                monster_elite: egui::Color32::from_rgb(240, 228, 66),
+                monster_boss: egui::Color32::from_rgb(204, 121, 167),
+                gens_duprian: egui::Color32::from_rgb(240, 228, 66),
+                gens_vanert: egui::Color32::from_rgb(0, 114, 178),
+                murder_warning: egui::Color32::from_rgb(240, 228, 66),
+                murderer: egui::Color32::from_rgb(213, 94, 0),
+                error: egui::Color32::from_rgb(213, 94, 0),
+            },
+            // Reds read as dark for protanopes, so warnings use brighter orange/yellow.
+            ColorblindModeSetting::Protanopia => Self {
+                damage_normal: egui::Color32::from_rgb(235, 235, 235),
+                damage_critical: egui::Color32::from_rgb(240, 228, 66),
+                damage_excellent: egui::Color32::from_rgb(86, 180, 233),
+                rarity_excellent: egui::Color32::from_rgb(86, 180, 233),
+                rarity_option: egui::Color32::from_rgb(240, 228, 66),
+                rarity_socket: egui::Color32::from_rgb(204, 121, 167),
+                hp_bar_full: egui::Color32::from_rgb(0, 114, 178),
+                hp_bar_low: egui::Color32::from_rgb(230, 159, 0),
+                guild_ally: egui::Color32::from_rgb(86, 180, 233),
+                guild_hostile: egui::Color32::from_rgb(230, 159, 0),
+                monster_elite: egui::Color32::from_rgb(240, 228, 66),
+                monster_boss: egui::Color32::from_rgb(204, 121, 167),
+                gens_duprian: egui::Color32::from_rgb(240, 228, 66),
+                gens_vanert: egui::Color32::from_rgb(0, 114, 178),
+                murder_warning: egui::Color32::from_rgb(240, 228, 66),
+                murderer: egui::Color32::from_rgb(230, 159, 0),
+                error: egui::Color32::from_rgb(230, 159, 0),
+            },
+        }
+    }
+
+    /// HP bar fill color, blending from full to low as health drops.
+    pub fn hp_bar(&self, fraction: f32) -> egui::Color32 {
+        let t = fraction.clamp(0.0, 1.0);
+        self.hp_bar_low.lerp_to_gamma(self.hp_bar_full, t)
+    }
+}
+
+impl Default for UiPalette {
+    fn default() -> Self {
+        Self::for_mode(ColorblindModeSetting::Off)
+    }
+}
+
+/// Accessibility state derived from `GameSettings`, read by UI systems.
+#[derive(Resource, Debug, Clone)]
+pub struct UiAccessibility {
+    pub palette: UiPalette,
+    pub chat_font: egui::FontId,
+}
+
+impl Default for UiAccessibility {
+    fn default() -> Self {
+        Self::from_settings(&AccessibilitySettings::default())
+    }
+}
+
+impl UiAccessibility {
+    pub fn from_settings(settings: &AccessibilitySettings) -> Self {
+        Self {
+            palette: UiPalette::for_mode(settings.colorblind_mode),
+            chat_font: egui::FontId::proportional(settings.chat_font_size()),
+        }
+    }
+}
+
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiAccessibility>()
+            .add_systems(EguiPrimaryContextPass, apply_accessibility_settings);
+    }
+}
+
+fn apply_accessibility_settings(
+    mut contexts: EguiContexts,
+    settings: Res<SettingsResource>,
+    mut accessibility: ResMut<UiAccessibility>,
+    mut last_applied: Local<Option<AccessibilitySettings>>,
+) {
+    let current = &settings.current.accessibility;
+    if last_applied.as_ref() == Some(current) {
+        return;
+    }
+
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    *accessibility = UiAccessibility::from_settings(current);
+    ctx.set_zoom_factor(current.ui_scale());
+
+    let font_changed = last_applied
+        .as_ref()
+        .is_none_or(|previous| previous.ui_font != current.ui_font);
+    if font_changed {
+        ctx.set_fonts(font_definitions(current.ui_font));
+    }
+
+    *last_applied = Some(current.clone());
+}
+
+fn font_definitions(font: UiFontSetting) -> egui::FontDefinitions {
+    let mut fonts = egui::FontDefinitions::default();
+    if font != UiFontSetting::Dyslexic {
+        return fonts;
+    }
+
+    let path = current_asset_root_path().join(DYSLEXIC_FONT_PATH);
+    match std::fs::read(&path) {
+        Ok(bytes) => {
+            fonts.font_data.insert(
+                DYSLEXIC_FONT_NAME.to_string(),
+                Arc::new(egui::FontData::from_owned(bytes)),
+            );
+            fonts
+                .families
+                .entry(egui::FontFamily::Proportional)
+                .or_default()
+                .insert(0, DYSLEXIC_FONT_NAME.to_string());
+        }
+        Err(error) => {
+            warn!(
+                "Dyslexia-friendly font '{}' unavailable: {}. Using default font.",
+                path.display(),
+                error
+            );
+        }
+    }
+
+    fonts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colorblind_palettes_avoid_red_green_pairs() {
+        for mode in [
+            ColorblindModeSetting::Deuteranopia,
+            ColorblindModeSetting::Protanopia,
+        ] {
+            let palette = UiPalette::for_mode(mode);
+            assert_ne!(palette, UiPalette::default());
+            // Ally/hostile must differ in the blue channel, which both deficiencies perceive.
+            assert!(palette.guild_ally.b() > palette.guild_hostile.b());
+            assert!(palette.hp_bar_full.b() > palette.hp_bar_low.b());
+            assert!(palette.monster_boss.b() > palette.monster_elite.b());
+        }
+    }
+
+    #[test]
+    fn scale_is_clamped_to_supported_range() {
+        let settings = AccessibilitySettings {
+            ui_scale_percent: 500,
+            chat_font_size: 2,
+            ..AccessibilitySettings::default()
+        };
+
+        assert_eq!(settings.ui_scale(), 2.0);
+        assert_eq!(settings.chat_font_size(), 10.0);
+    }
+}
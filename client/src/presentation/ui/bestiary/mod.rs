@@ -0,0 +1,274 @@
+//! Bestiary window listing the monsters native to the current map. A monster
+//! unlocks on its first kill; the server then lets the window preview its
+//! drop table. Unlocks are kept per character on the server.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::AppState;
+use crate::infra::network::{SendClientMessage, ServerMessageReceived};
+use crate::presentation::ui::accessibility::UiAccessibility;
+use crate::presentation::ui::server_errors::server_error_text;
+use crate::world::{CurrentWorld, WorldId};
+use bevy::prelude::*;
+use bevy::state::prelude::{OnEnter, in_state};
+use bevy_egui::input::EguiWantsInput;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use common::{ItemCode, MonsterKind, WorldMap};
+use protocol::{ClientMessage, DropEntry, DropTable, ServerErrorKind, ServerMessage};
+
+#[derive(Resource, Default)]
+pub struct BestiaryState {
+    pub open: bool,
+    pub unlocked: BTreeSet<u16>,
+    // key: monster_id
+    pub drops: HashMap<u16, DropTable>,
+    pub selected: Option<MonsterKind>,
+    pub last_error: Option<String>,
+}
+
+pub struct BestiaryPresentationPlugin;
+
+impl Plugin for BestiaryPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BestiaryState>()
+            .add_systems(OnEnter(AppState::Gameplay), request_bestiary)
+            .add_systems(
+                Update,
+                (apply_bestiary_messages, handle_bestiary_keys)
+                    .run_if(in_state(AppState::Gameplay)),
+            )
+            .add_systems(
+                EguiPrimaryContextPass,
+                draw_bestiary_window
+                    .run_if(in_state(AppState::Gameplay))
+                    .run_if(|state: Res<BestiaryState>| state.open),
+            );
+    }
+}
+
+/// Monsters native to `map`, weakest first.
+pub fn map_monsters(map: WorldMap) -> Vec<MonsterKind> {
+    let mut monsters: Vec<MonsterKind> = MonsterKind::ALL
+        .into_iter()
+        .filter(|kind| kind.info().native_map == map)
+        .collect();
+    monsters.sort_by_key(|kind| kind.info().level);
+    monsters
+}
+
+/// Where on its map a monster roams: weaker monsters stay close to town,
+/// stronger ones further out.
+pub fn location_hint(kind: MonsterKind) -> &'static str {
+    let info = kind.info();
+    let levels: Vec<u16> = map_monsters(info.native_map)
+        .iter()
+        .map(|kind| kind.info().level)
+        .collect();
+    let (Some(&lowest), Some(&highest)) = (levels.first(), levels.last()) else {
+        return "Local desconhecido";
+    };
+    let spread = (highest - lowest).max(1);
+    match (info.level - lowest) * 3 / spread {
+        0 => "Arredores da cidade",
+        1 => "Interior do mapa",
+        _ => "Regioes mais distantes",
+    }
+}
+
+/// Tooltip line of a drop: item name, level range and chance per kill.
+pub fn drop_line(entry: &DropEntry) -> String {
+    let code = ItemCode::new(entry.group, entry.index);
+    let mut line = match code.definition() {
+        Some(definition) => definition.name.to_string(),
+        None => format!("Item {code}"),
+    };
+    if entry.max_level > 0 {
+        if entry.min_level == entry.max_level {
+            line.push_str(&format!(" +{}", entry.max_level));
+        } else {
+            line.push_str(&format!(" +{}~+{}", entry.min_level, entry.max_level));
+        }
+    }
+    line.push_str(&format!(
+        " ({:.1}%)",
+        f32::from(entry.chance_per_mille) / 10.0
+    ));
+    line
+}
+
+fn request_bestiary(
+    mut state: ResMut<BestiaryState>,
+    mut outgoing: MessageWriter<SendClientMessage>,
+) {
+    *state = BestiaryState::default();
+    outgoing.write(SendClientMessage(ClientMessage::RequestBestiary));
+}
+
+fn apply_bestiary_messages(
+    mut state: ResMut<BestiaryState>,
+    mut incoming: MessageReader<ServerMessageReceived>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        match message {
+            ServerMessage::Bestiary { unlocked } => {
+                state.unlocked = unlocked.iter().copied().collect();
+            }
+            ServerMessage::BestiaryUnlocked { monster_id } => {
+                state.unlocked.insert(*monster_id);
+            }
+            ServerMessage::DropTable(table) => {
+                state.drops.insert(table.monster_id, table.clone());
+                state.last_error = None;
+            }
+            // Casts rejected for cooldown are not about the bestiary.
+            ServerMessage::Error(error)
+                if state.open && error.kind != ServerErrorKind::SkillCooldown =>
+            {
+                state.last_error = Some(server_error_text(error.code()).to_string());
+            }
+            _ => {}
+        }
+    }
+}
+
+fn handle_bestiary_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    egui_wants_input: Option<Res<EguiWantsInput>>,
+    mut state: ResMut<BestiaryState>,
+) {
+    if egui_wants_input.is_some_and(|egui| egui.wants_any_keyboard_input()) {
+        return;
+    }
+    if keys.just_pressed(KeyCode::KeyB) {
+        state.open = !state.open;
+    }
+}
+
+fn draw_bestiary_window(
+    mut contexts: EguiContexts,
+    mut state: ResMut<BestiaryState>,
+    current_world: Res<CurrentWorld>,
+    accessibility: Res<UiAccessibility>,
+    mut outgoing: MessageWriter<SendClientMessage>,
+) {
+    let palette = accessibility.palette;
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+    let map = match current_world.0 {
+        Some(WorldId::Game(map)) => Some(map),
+        _ => None,
+    };
+
+    let mut open = state.open;
+    let mut selected = state.selected;
+
+    egui::Window::new("Bestiario")
+        .open(&mut open)
+        .anchor(egui::Align2::RIGHT_CENTER, egui::vec2(-14.0, 0.0))
+        .resizable(false)
+        .collapsible(false)
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            let Some(map) = map else {
+                ui.label("Entre em um mapa para ver seus monstros.");
+                return;
+            };
+            ui.strong(map.name());
+            ui.separator();
+
+            let monsters = map_monsters(map);
+            if monsters.is_empty() {
+                ui.label("Nenhum monstro catalogado neste mapa.");
+            }
+            for kind in monsters {
+                let unlocked = state.unlocked.contains(&kind.id());
+                let title = format!("{} (Nv. {})", kind.name(), kind.info().level);
+                ui.horizontal(|ui| {
+                    if unlocked {
+                        let response = ui.selectable_label(selected == Some(kind), title);
+                        if response.clicked() {
+                            selected = Some(kind);
+                            if !state.drops.contains_key(&kind.id()) {
+                                outgoing.write(SendClientMessage(
+                                    ClientMessage::RequestDropTable {
+                                        monster_id: kind.id(),
+                                    },
+                                ));
+                            }
+                        }
+                    } else {
+                        ui.weak(title);
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.weak(location_hint(kind));
+                    });
+                });
+            }
+
+            if let Some(kind) = selected.filter(|kind| state.unlocked.contains(&kind.id())) {
+                ui.separator();
+                ui.strong(format!("Drops de {}", kind.name()));
+                match state.drops.get(&kind.id()) {
+                    Some(table) if table.entries.is_empty() => {
+                        ui.label("Apenas os drops comuns do mapa.");
+                    }
+                    Some(table) => {
+                        for entry in &table.entries {
+                            ui.label(drop_line(entry));
+                        }
+                    }
+                    None => {
+                        ui.weak("Carregando...");
+                    }
+                }
+            }
+
+            if let Some(error) = &state.last_error {
+                ui.separator();
+                ui.colored_label(palette.error, error);
+            }
+        });
+
+    state.open = open;
+    state.selected = selected;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lorencia_lists_its_monsters_weakest_first() {
+        let monsters = map_monsters(WorldMap::Lorencia);
+        assert_eq!(monsters.first(), Some(&MonsterKind::Spider));
+        assert!(monsters.contains(&MonsterKind::BullFighter));
+        assert!(!monsters.contains(&MonsterKind::Zaikan));
+        assert_eq!(location_hint(monsters[0]), "Arredores da cidade");
+        assert_eq!(
+            location_hint(*monsters.last().unwrap()),
+            "Regioes mais distantes"
+        );
+    }
+
+    #[test]
+    fn drop_lines_name_catalog_items() {
+        let potion = DropEntry {
+            group: 14,
+            index: 1,
+            min_level: 0,
+            max_level: 0,
+            chance_per_mille: 120,
+        };
+        assert_eq!(drop_line(&potion), "Small Healing Potion (12.0%)");
+
+        let helm = DropEntry {
+            group: 7,
+            index: 4,
+            min_level: 1,
+            max_level: 3,
+            chance_per_mille: 8,
+        };
+        assert_eq!(drop_line(&helm), "Bone Helm +1~+3 (0.8%)");
+    }
+}
@@ -0,0 +1,248 @@
+//! Chat box, opened with Enter.
+//!
+//! Lines typed in the box go to the local channel. `/emote <nome>` (or `/e`)
+//! plays an emote instead; names are the English ones (`clap`,
+//! `look_around`) or the labels shown in the emote wheel (`aplaudir`).
+
+use std::collections::VecDeque;
+
+use crate::AppState;
+use crate::gameplay::emotes::{EmoteRequest, find_emote};
+use crate::infra::network::{SendClientMessage, ServerMessageReceived};
+use crate::presentation::ui::accessibility::UiAccessibility;
+use crate::presentation::ui::hud_layout::{self, HudLayoutEditor};
+use crate::settings::{HudElement, SettingsResource};
+use bevy::prelude::*;
+use bevy::state::prelude::{OnExit, in_state};
+use bevy_egui::input::EguiWantsInput;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use protocol::{ChatChannel, ChatPayload, ClientMessage, Emote, ServerMessage};
+
+/// Lines kept in the box; older ones are dropped.
+const MAX_LINES: usize = 50;
+
+/// What a submitted chat line does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChatCommand {
+    Say(String),
+    Emote(Emote),
+    /// Shown only to the player, e.g. for a mistyped command.
+    Notice(String),
+}
+
+/// Reads a submitted line; blank lines do nothing.
+pub fn parse_chat_line(line: &str) -> Option<ChatCommand> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    let Some(rest) = line
+        .strip_prefix("/emote")
+        .or_else(|| line.strip_prefix("/e"))
+        .filter(|rest| rest.is_empty() || rest.starts_with(' '))
+    else {
+        return Some(ChatCommand::Say(line.to_string()));
+    };
+
+    let name = rest.trim();
+    if name.is_empty() {
+        return Some(ChatCommand::Notice("Uso: /emote <nome>".to_string()));
+    }
+    Some(match find_emote(name) {
+        Some(emote) => ChatCommand::Emote(emote),
+        None => ChatCommand::Notice(format!("Emote desconhecido: {name}")),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ChatLine {
+    text: String,
+    notice: bool,
+}
+
+#[derive(Resource, Default)]
+pub struct ChatBox {
+    pub open: bool,
+    draft: String,
+    focus_pending: bool,
+    lines: VecDeque<ChatLine>,
+}
+
+impl ChatBox {
+    fn push(&mut self, text: String, notice: bool) {
+        if self.lines.len() == MAX_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(ChatLine { text, notice });
+    }
+
+    fn close(&mut self) {
+        self.open = false;
+        self.draft.clear();
+    }
+}
+
+pub struct ChatPresentationPlugin;
+
+impl Plugin for ChatPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChatBox>()
+            .add_systems(OnExit(AppState::Gameplay), reset_chat)
+            .add_systems(
+                Update,
+                (apply_chat_messages, open_chat_with_enter).run_if(in_state(AppState::Gameplay)),
+            )
+            .add_systems(
+                EguiPrimaryContextPass,
+                draw_chat.run_if(in_state(AppState::Gameplay)),
+            );
+    }
+}
+
+fn reset_chat(mut chat: ResMut<ChatBox>) {
+    *chat = ChatBox::default();
+}
+
+fn apply_chat_messages(
+    mut chat: ResMut<ChatBox>,
+    mut incoming: MessageReader<ServerMessageReceived>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        if let ServerMessage::Chat(payload) = message {
+            chat.push(payload.text.clone(), false);
+        }
+    }
+}
+
+fn open_chat_with_enter(
+    keys: Res<ButtonInput<KeyCode>>,
+    egui_wants_input: Option<Res<EguiWantsInput>>,
+    mut chat: ResMut<ChatBox>,
+) {
+    if chat.open
+        || !keys.just_pressed(KeyCode::Enter)
+        || egui_wants_input.is_some_and(|egui| egui.wants_any_keyboard_input())
+    {
+        return;
+    }
+    chat.open = true;
+    chat.focus_pending = true;
+}
+
+fn draw_chat(
+    mut contexts: EguiContexts,
+    mut chat: ResMut<ChatBox>,
+    settings_resource: Res<SettingsResource>,
+    layout_editor: Res<HudLayoutEditor>,
+    accessibility: Res<UiAccessibility>,
+    mut outgoing: MessageWriter<SendClientMessage>,
+    mut emotes: MessageWriter<EmoteRequest>,
+) {
+    if !chat.open && chat.lines.is_empty() {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+    let layout = layout_editor.layout(&settings_resource.current.hud);
+
+    let submitted = hud_layout::show_element(ctx, layout, HudElement::Chat, |ui| {
+        ui.set_width(360.0);
+        egui::ScrollArea::vertical()
+            .max_height(140.0)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for line in &chat.lines {
+                    let color = if line.notice {
+                        accessibility.palette.error
+                    } else {
+                        ui.visuals().text_color()
+                    };
+                    ui.label(
+                        egui::RichText::new(&line.text)
+                            .font(accessibility.chat_font.clone())
+                            .color(color),
+                    );
+                }
+            });
+        if !chat.open {
+            return None;
+        }
+
+        let chat = &mut *chat;
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut chat.draft)
+                .font(accessibility.chat_font.clone())
+                .desired_width(f32::INFINITY),
+        );
+        // The Enter that opened the box must not submit it right away.
+        if std::mem::take(&mut chat.focus_pending) {
+            response.request_focus();
+            return None;
+        }
+        if !response.lost_focus() {
+            return None;
+        }
+        let line = ui
+            .input(|input| input.key_pressed(egui::Key::Enter))
+            .then(|| chat.draft.clone());
+        chat.close();
+        line
+    });
+
+    let Some(line) = submitted else {
+        return;
+    };
+    match parse_chat_line(&line) {
+        Some(ChatCommand::Say(text)) => {
+            outgoing.write(SendClientMessage(ClientMessage::Chat(ChatPayload {
+                channel: ChatChannel::Local,
+                target: None,
+                text,
+            })));
+        }
+        Some(ChatCommand::Emote(emote)) => {
+            emotes.write(EmoteRequest(emote));
+        }
+        Some(ChatCommand::Notice(text)) => chat.push(text, true),
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emote_commands_are_not_sent_as_chat() {
+        assert_eq!(
+            parse_chat_line("/emote clap"),
+            Some(ChatCommand::Emote(Emote::Clap))
+        );
+        assert_eq!(
+            parse_chat_line("/e  Sorrir "),
+            Some(ChatCommand::Emote(Emote::Smile))
+        );
+        assert_eq!(
+            parse_chat_line("/emote dance"),
+            Some(ChatCommand::Notice("Emote desconhecido: dance".to_string()))
+        );
+        assert_eq!(
+            parse_chat_line("/emote"),
+            Some(ChatCommand::Notice("Uso: /emote <nome>".to_string()))
+        );
+    }
+
+    #[test]
+    fn other_lines_go_to_the_local_channel() {
+        assert_eq!(
+            parse_chat_line(" ola a todos "),
+            Some(ChatCommand::Say("ola a todos".to_string()))
+        );
+        assert_eq!(
+            parse_chat_line("/elfo"),
+            Some(ChatCommand::Say("/elfo".to_string()))
+        );
+        assert_eq!(parse_chat_line("   "), None);
+    }
+}
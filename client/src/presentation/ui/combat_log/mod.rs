@@ -0,0 +1,395 @@
+//! Combat log and DPS meter, opened with L.
+//!
+//! Keeps the `DamageEvent`s the player dealt or received with their time and
+//! skill. Hits more than `ENCOUNTER_GAP_MS` apart start a new encounter; the
+//! meter sums the last one.
+
+use std::collections::VecDeque;
+
+use crate::AppState;
+use crate::infra::network::ServerMessageReceived;
+use crate::presentation::ui::accessibility::UiAccessibility;
+use bevy::prelude::*;
+use bevy::state::prelude::{OnExit, in_state};
+use bevy_egui::input::EguiWantsInput;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use protocol::{DamageEvent, ServerMessage};
+
+/// Hits kept in the log; older ones are dropped.
+const MAX_ENTRIES: usize = 200;
+/// Quiet time that closes an encounter.
+const ENCOUNTER_GAP_MS: u64 = 5_000;
+/// Shortest encounter the meter divides by, so one hit is not a spike.
+const MIN_ENCOUNTER_MS: u64 = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitDirection {
+    Dealt,
+    Received,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CombatLogEntry {
+    pub server_time_ms: u64,
+    pub direction: HitDirection,
+    /// Target of a dealt hit, attacker of a received one.
+    pub other_entity_id: u32,
+    pub skill_id: u16,
+    pub damage: u32,
+    pub killed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CombatLogFilter {
+    #[default]
+    All,
+    Dealt,
+    Received,
+}
+
+impl CombatLogFilter {
+    pub fn shows(self, entry: &CombatLogEntry) -> bool {
+        match self {
+            Self::All => true,
+            Self::Dealt => entry.direction == HitDirection::Dealt,
+            Self::Received => entry.direction == HitDirection::Received,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Encounter {
+    pub started_at_ms: u64,
+    pub last_hit_at_ms: u64,
+    pub dealt: u64,
+    pub received: u64,
+}
+
+impl Encounter {
+    pub fn duration_ms(&self) -> u64 {
+        self.last_hit_at_ms.saturating_sub(self.started_at_ms)
+    }
+
+    pub fn dealt_per_second(&self) -> f32 {
+        self.dealt as f32 * 1000.0 / self.duration_ms().max(MIN_ENCOUNTER_MS) as f32
+    }
+
+    pub fn received_per_second(&self) -> f32 {
+        self.received as f32 * 1000.0 / self.duration_ms().max(MIN_ENCOUNTER_MS) as f32
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct CombatLog {
+    pub open: bool,
+    pub filter: CombatLogFilter,
+    /// Only hits of this skill are listed.
+    pub skill_filter: Option<u16>,
+    entity_id: Option<u32>,
+    entries: VecDeque<CombatLogEntry>,
+    encounter: Option<Encounter>,
+}
+
+impl CombatLog {
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &CombatLogEntry> {
+        self.entries.iter()
+    }
+
+    /// Last encounter, still running or not.
+    pub fn encounter(&self) -> Option<Encounter> {
+        self.encounter
+    }
+
+    pub fn shows(&self, entry: &CombatLogEntry) -> bool {
+        self.filter.shows(entry) && self.skill_filter.is_none_or(|id| id == entry.skill_id)
+    }
+
+    /// Skills seen in the log, for the skill filter.
+    pub fn skills(&self) -> Vec<u16> {
+        let mut skills: Vec<u16> = self.entries.iter().map(|entry| entry.skill_id).collect();
+        skills.sort_unstable();
+        skills.dedup();
+        skills
+    }
+
+    fn enter_map(&mut self, entity_id: u32) {
+        self.entity_id = Some(entity_id);
+        self.encounter = None;
+    }
+
+    /// Logs `hit` when the player dealt or took it.
+    pub fn record(&mut self, hit: &DamageEvent) {
+        let Some(entity_id) = self.entity_id else {
+            return;
+        };
+        let (direction, other_entity_id) = if hit.attacker_entity_id == entity_id {
+            (HitDirection::Dealt, hit.target_entity_id)
+        } else if hit.target_entity_id == entity_id {
+            (HitDirection::Received, hit.attacker_entity_id)
+        } else {
+            return;
+        };
+
+        if self.entries.len() == MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(CombatLogEntry {
+            server_time_ms: hit.server_time_ms,
+            direction,
+            other_entity_id,
+            skill_id: hit.skill_id,
+            damage: hit.damage,
+            killed: hit.remaining_hp == 0,
+        });
+
+        let now = hit.server_time_ms;
+        let encounter = match &mut self.encounter {
+            Some(encounter) if now.saturating_sub(encounter.last_hit_at_ms) <= ENCOUNTER_GAP_MS => {
+                encounter
+            }
+            slot => slot.insert(Encounter {
+                started_at_ms: now,
+                ..Encounter::default()
+            }),
+        };
+        encounter.last_hit_at_ms = encounter.last_hit_at_ms.max(now);
+        match direction {
+            HitDirection::Dealt => encounter.dealt += u64::from(hit.damage),
+            HitDirection::Received => encounter.received += u64::from(hit.damage),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.encounter = None;
+    }
+}
+
+pub fn skill_label(skill_id: u16) -> String {
+    match skill_id {
+        0 => "Ataque".to_string(),
+        id => format!("Habilidade {id}"),
+    }
+}
+
+/// Server time as `HH:MM:SS` of the UTC day.
+pub fn clock_label(server_time_ms: u64) -> String {
+    let secs = server_time_ms / 1000;
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+pub struct CombatLogPresentationPlugin;
+
+impl Plugin for CombatLogPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CombatLog>()
+            .add_systems(OnExit(AppState::Gameplay), reset_combat_log)
+            .add_systems(
+                Update,
+                (apply_combat_messages, handle_combat_log_keys)
+                    .run_if(in_state(AppState::Gameplay)),
+            )
+            .add_systems(
+                EguiPrimaryContextPass,
+                draw_combat_log
+                    .run_if(in_state(AppState::Gameplay))
+                    .run_if(|log: Res<CombatLog>| log.open),
+            );
+    }
+}
+
+fn reset_combat_log(mut log: ResMut<CombatLog>) {
+    *log = CombatLog::default();
+}
+
+fn apply_combat_messages(
+    mut log: ResMut<CombatLog>,
+    mut incoming: MessageReader<ServerMessageReceived>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        match message {
+            ServerMessage::EnterMap { entity_id, .. } => log.enter_map(*entity_id),
+            ServerMessage::DamageEvent(hit) => log.record(hit),
+            _ => {}
+        }
+    }
+}
+
+fn handle_combat_log_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    egui_wants_input: Option<Res<EguiWantsInput>>,
+    mut log: ResMut<CombatLog>,
+) {
+    if egui_wants_input.is_some_and(|egui| egui.wants_any_keyboard_input()) {
+        return;
+    }
+    if keys.just_pressed(KeyCode::KeyL) {
+        log.open = !log.open;
+    }
+}
+
+fn draw_combat_log(
+    mut contexts: EguiContexts,
+    mut log: ResMut<CombatLog>,
+    accessibility: Res<UiAccessibility>,
+) {
+    let palette = accessibility.palette;
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let mut open = log.open;
+
+    egui::Window::new("Registro de combate")
+        .open(&mut open)
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(14.0, -14.0))
+        .collapsible(true)
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            match log.encounter() {
+                Some(encounter) => {
+                    ui.label(format!(
+                        "Ultimo combate: {:.1}s, {:.0} DPS causado, {:.0} DPS recebido",
+                        encounter.duration_ms() as f32 / 1000.0,
+                        encounter.dealt_per_second(),
+                        encounter.received_per_second()
+                    ));
+                }
+                None => {
+                    ui.label("Nenhum combate registrado");
+                }
+            }
+
+            ui.horizontal(|ui| {
+                for (filter, label) in [
+                    (CombatLogFilter::All, "Todos"),
+                    (CombatLogFilter::Dealt, "Causado"),
+                    (CombatLogFilter::Received, "Recebido"),
+                ] {
+                    ui.selectable_value(&mut log.filter, filter, label);
+                }
+                let skills = log.skills();
+                let selected = log.skill_filter.map_or("Todas".to_string(), skill_label);
+                egui::ComboBox::from_id_salt("combat_log_skill")
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut log.skill_filter, None, "Todas");
+                        for skill_id in skills {
+                            ui.selectable_value(
+                                &mut log.skill_filter,
+                                Some(skill_id),
+                                skill_label(skill_id),
+                            );
+                        }
+                    });
+                if ui.button("Limpar").clicked() {
+                    log.clear();
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .max_height(240.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for entry in log.entries().filter(|entry| log.shows(entry)) {
+                        let (color, verb, preposition) = match entry.direction {
+                            HitDirection::Dealt => (palette.damage_normal, "causou", "em"),
+                            HitDirection::Received => (palette.error, "recebeu", "de"),
+                        };
+                        let killed = if entry.killed { ", abatido" } else { "" };
+                        ui.colored_label(
+                            color,
+                            format!(
+                                "[{}] Voce {} {} de dano {} #{} ({}{})",
+                                clock_label(entry.server_time_ms),
+                                verb,
+                                entry.damage,
+                                preposition,
+                                entry.other_entity_id,
+                                skill_label(entry.skill_id),
+                                killed
+                            ),
+                        );
+                    }
+                });
+        });
+
+    log.open = open;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(attacker: u32, target: u32, skill_id: u16, damage: u32, at_ms: u64) -> DamageEvent {
+        DamageEvent {
+            attacker_entity_id: attacker,
+            target_entity_id: target,
+            skill_id,
+            damage,
+            remaining_hp: 50,
+            server_time_ms: at_ms,
+        }
+    }
+
+    #[test]
+    fn logs_only_the_players_hits_and_filters_them() {
+        let mut log = CombatLog::default();
+        log.record(&hit(7, 9, 1, 20, 0));
+        assert_eq!(log.entries().count(), 0, "nothing logged before EnterMap");
+
+        log.enter_map(7);
+        log.record(&hit(7, 9, 1, 20, 1_000));
+        log.record(&hit(9, 7, 5, 15, 1_500));
+        log.record(&hit(9, 11, 1, 30, 1_600));
+        let directions: Vec<_> = log.entries().map(|entry| entry.direction).collect();
+        assert_eq!(
+            directions,
+            vec![HitDirection::Dealt, HitDirection::Received]
+        );
+
+        log.filter = CombatLogFilter::Received;
+        let shown: Vec<_> = log.entries().filter(|entry| log.shows(entry)).collect();
+        assert_eq!(shown.len(), 1);
+        assert_eq!(shown[0].other_entity_id, 9);
+
+        log.filter = CombatLogFilter::All;
+        log.skill_filter = Some(1);
+        assert_eq!(log.skills(), vec![1, 5]);
+        assert_eq!(log.entries().filter(|entry| log.shows(entry)).count(), 1);
+    }
+
+    #[test]
+    fn a_quiet_gap_starts_a_new_encounter() {
+        let mut log = CombatLog::default();
+        log.enter_map(7);
+        log.record(&hit(7, 9, 1, 100, 10_000));
+        log.record(&hit(7, 9, 1, 100, 12_000));
+        log.record(&hit(9, 7, 1, 40, 14_000));
+
+        let encounter = log.encounter().unwrap();
+        assert_eq!((encounter.dealt, encounter.received), (200, 40));
+        assert_eq!(encounter.duration_ms(), 4_000);
+        assert_eq!(encounter.dealt_per_second(), 50.0);
+
+        log.record(&hit(7, 9, 1, 30, 14_000 + ENCOUNTER_GAP_MS + 1));
+        let encounter = log.encounter().unwrap();
+        assert_eq!((encounter.dealt, encounter.received), (30, 0));
+        // A single hit is spread over the shortest encounter.
+        assert_eq!(encounter.dealt_per_second(), 30.0);
+    }
+
+    #[test]
+    fn clock_shows_the_time_of_day() {
+        assert_eq!(
+            clock_label(((25 * 60 + 2) * 60 + 3) * 1000 + 999),
+            "01:02:03"
+        );
+    }
+}
@@ -0,0 +1,104 @@
+//! Dialog telling the player why the server ended the session (kick, ban,
+//! login from another connection, maintenance), shown on the login screen
+//! instead of a generic connection error.
+
+use crate::AppState;
+use crate::infra::network::ServerMessageReceived;
+use bevy::prelude::*;
+use bevy::state::prelude::in_state;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use protocol::{DisconnectReason, ServerMessage};
+
+#[derive(Resource, Debug, Default)]
+pub struct DisconnectNotice {
+    pub current: Option<String>,
+}
+
+pub struct DisconnectPresentationPlugin;
+
+impl Plugin for DisconnectPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DisconnectNotice>()
+            .add_systems(
+                Update,
+                apply_disconnect_messages.run_if(in_state(AppState::Gameplay)),
+            )
+            .add_systems(
+                EguiPrimaryContextPass,
+                draw_disconnect_notice
+                    .run_if(|notice: Res<DisconnectNotice>| notice.current.is_some()),
+            );
+    }
+}
+
+/// Text for a `Disconnect` message; with an empty `message` it also fits a
+/// connection closed with `DisconnectReason::close_code`.
+pub fn disconnect_text(reason: DisconnectReason, message: &str) -> String {
+    let text = match reason {
+        DisconnectReason::Kicked => "Voce foi desconectado por um administrador.",
+        DisconnectReason::Banned => "Esta conta foi bloqueada.",
+        DisconnectReason::DuplicateLogin => "Esta conta entrou no jogo em outra conexao.",
+        DisconnectReason::Maintenance => "O servidor entrou em manutencao.",
+    };
+    let message = message.trim();
+    if message.is_empty() {
+        text.to_string()
+    } else {
+        format!("{text}\n{message}")
+    }
+}
+
+fn apply_disconnect_messages(
+    mut notice: ResMut<DisconnectNotice>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut incoming: MessageReader<ServerMessageReceived>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        if let ServerMessage::Disconnect { reason, message } = message {
+            notice.current = Some(disconnect_text(*reason, message));
+            next_state.set(AppState::Login);
+        }
+    }
+}
+
+fn draw_disconnect_notice(mut contexts: EguiContexts, mut notice: ResMut<DisconnectNotice>) {
+    let Some(text) = &notice.current else {
+        return;
+    };
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let mut acknowledged = false;
+    egui::Window::new("Desconectado")
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, -180.0))
+        .collapsible(false)
+        .resizable(false)
+        .movable(false)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            ui.label(text.as_str());
+            ui.add_space(8.0);
+            acknowledged = ui.button("OK").clicked();
+        });
+    if acknowledged {
+        notice.current = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operator_text_follows_the_reason() {
+        assert_eq!(
+            disconnect_text(DisconnectReason::Maintenance, ""),
+            "O servidor entrou em manutencao."
+        );
+        assert_eq!(
+            disconnect_text(DisconnectReason::Banned, " uso de hack "),
+            "Esta conta foi bloqueada.\nuso de hack"
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! Emote wheel, shown while T is held.
+//!
+//! Pointing at an entry and releasing T (or clicking it) plays the emote;
+//! releasing with the pointer in the middle plays nothing.
+
+use crate::AppState;
+use crate::gameplay::emotes::{EmoteRequest, emote_label};
+use bevy::prelude::*;
+use bevy::state::prelude::{OnExit, in_state};
+use bevy_egui::input::EguiWantsInput;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use protocol::Emote;
+
+/// Entries clockwise from the top.
+const WHEEL_EMOTES: [Emote; 12] = [
+    Emote::Greeting,
+    Emote::Goodbye,
+    Emote::Clap,
+    Emote::Cheer,
+    Emote::Cry,
+    Emote::Smile,
+    Emote::Win,
+    Emote::Salute,
+    Emote::Respect,
+    Emote::Provocation,
+    Emote::Sleep,
+    Emote::Sit,
+];
+const WHEEL_RADIUS: f32 = 150.0;
+/// Pointer distance from the centre below which no entry is picked.
+const DEAD_ZONE: f32 = 40.0;
+const ENTRY_RADIUS: f32 = 32.0;
+
+/// Entry under `offset` from the wheel centre, counted clockwise from the
+/// top in a y-down screen space.
+fn wheel_entry(offset: egui::Vec2, count: usize) -> Option<usize> {
+    if offset.length() < DEAD_ZONE || count == 0 {
+        return None;
+    }
+    let sector = std::f32::consts::TAU / count as f32;
+    let angle = offset.x.atan2(-offset.y).rem_euclid(std::f32::consts::TAU);
+    Some(((angle + sector / 2.0) / sector) as usize % count)
+}
+
+fn entry_center(center: egui::Pos2, index: usize, count: usize) -> egui::Pos2 {
+    let angle = std::f32::consts::TAU * index as f32 / count as f32;
+    center + egui::vec2(angle.sin(), -angle.cos()) * (WHEEL_RADIUS - ENTRY_RADIUS - 12.0)
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct EmoteWheel {
+    pub open: bool,
+    hovered: Option<usize>,
+}
+
+pub struct EmoteWheelPresentationPlugin;
+
+impl Plugin for EmoteWheelPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EmoteWheel>()
+            .add_systems(OnExit(AppState::Gameplay), reset_emote_wheel)
+            .add_systems(
+                Update,
+                handle_emote_wheel_keys.run_if(in_state(AppState::Gameplay)),
+            )
+            .add_systems(
+                EguiPrimaryContextPass,
+                draw_emote_wheel
+                    .run_if(in_state(AppState::Gameplay))
+                    .run_if(|wheel: Res<EmoteWheel>| wheel.open),
+            );
+    }
+}
+
+fn reset_emote_wheel(mut wheel: ResMut<EmoteWheel>) {
+    *wheel = EmoteWheel::default();
+}
+
+fn handle_emote_wheel_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    egui_wants_input: Option<Res<EguiWantsInput>>,
+    mut wheel: ResMut<EmoteWheel>,
+    mut emotes: MessageWriter<EmoteRequest>,
+) {
+    if wheel.open {
+        if keys.just_released(KeyCode::KeyT) {
+            if let Some(index) = wheel.hovered {
+                emotes.write(EmoteRequest(WHEEL_EMOTES[index]));
+            }
+            *wheel = EmoteWheel::default();
+        }
+        return;
+    }
+    if keys.just_pressed(KeyCode::KeyT)
+        && !egui_wants_input.is_some_and(|egui| egui.wants_any_keyboard_input())
+    {
+        wheel.open = true;
+    }
+}
+
+fn draw_emote_wheel(
+    mut contexts: EguiContexts,
+    mut wheel: ResMut<EmoteWheel>,
+    mut emotes: MessageWriter<EmoteRequest>,
+) {
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+    let center = ctx.content_rect().center();
+    let size = egui::Vec2::splat(WHEEL_RADIUS * 2.0);
+
+    let clicked = egui::Area::new(egui::Id::new("emote_wheel"))
+        .order(egui::Order::Foreground)
+        .fixed_pos(center - size / 2.0)
+        .show(ctx, |ui| {
+            let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+            // The pointer may leave the wheel while flicking towards an entry.
+            wheel.hovered = ui
+                .ctx()
+                .pointer_latest_pos()
+                .and_then(|pointer| wheel_entry(pointer - center, WHEEL_EMOTES.len()));
+
+            let visuals = ui.visuals();
+            let painter = ui.painter();
+            painter.circle_filled(
+                rect.center(),
+                WHEEL_RADIUS,
+                egui::Color32::from_rgba_unmultiplied(0, 0, 0, 160),
+            );
+            for (index, emote) in WHEEL_EMOTES.into_iter().enumerate() {
+                let position = entry_center(center, index, WHEEL_EMOTES.len());
+                if wheel.hovered == Some(index) {
+                    painter.circle_filled(position, ENTRY_RADIUS, visuals.selection.bg_fill);
+                }
+                painter.text(
+                    position,
+                    egui::Align2::CENTER_CENTER,
+                    emote_label(emote),
+                    egui::FontId::proportional(13.0),
+                    visuals.strong_text_color(),
+                );
+            }
+            let title = wheel
+                .hovered
+                .map_or("Emotes", |index| emote_label(WHEEL_EMOTES[index]));
+            painter.text(
+                center,
+                egui::Align2::CENTER_CENTER,
+                title,
+                egui::FontId::proportional(15.0),
+                visuals.strong_text_color(),
+            );
+            response.clicked()
+        })
+        .inner;
+
+    if let Some(index) = wheel.hovered.filter(|_| clicked) {
+        emotes.write(EmoteRequest(WHEEL_EMOTES[index]));
+        *wheel = EmoteWheel::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pointer_angle_picks_the_entry() {
+        let count = WHEEL_EMOTES.len();
+        assert_eq!(wheel_entry(egui::vec2(0.0, -100.0), count), Some(0));
+        assert_eq!(wheel_entry(egui::vec2(100.0, 0.0), count), Some(3));
+        assert_eq!(wheel_entry(egui::vec2(0.0, 100.0), count), Some(6));
+        assert_eq!(wheel_entry(egui::vec2(-100.0, 0.0), count), Some(9));
+        // Just left of the top still belongs to the first entry.
+        assert_eq!(wheel_entry(egui::vec2(-10.0, -100.0), count), Some(0));
+        assert_eq!(wheel_entry(egui::vec2(5.0, 5.0), count), None);
+    }
+
+    #[test]
+    fn entries_sit_where_the_pointer_picks_them() {
+        let center = egui::pos2(400.0, 300.0);
+        for index in 0..WHEEL_EMOTES.len() {
+            let position = entry_center(center, index, WHEEL_EMOTES.len());
+            assert_eq!(
+                wheel_entry(position - center, WHEEL_EMOTES.len()),
+                Some(index)
+            );
+        }
+    }
+}
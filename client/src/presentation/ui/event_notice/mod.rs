@@ -0,0 +1,226 @@
+//! Banner announcing Crywolf and Kanturu phase changes, Doppelganger waves,
+//! time-limited items running out and maintenance or restart countdowns.
+
+use crate::AppState;
+use crate::infra::network::ServerMessageReceived;
+use crate::presentation::ui::accessibility::UiAccessibility;
+use crate::presentation::ui::widgets::item_tooltip::item_title;
+use bevy::prelude::*;
+use bevy::state::prelude::{OnExit, in_state};
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use protocol::{
+    DoppelgangerStatus, EventNotice, EventPhase, ItemInstance, MaintenanceNotice, SequenceEvent,
+    ServerMessage,
+};
+
+/// How long a notice stays on screen.
+const NOTICE_SECS: f32 = 8.0;
+const NOTICE_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 215, 120);
+
+#[derive(Resource, Default)]
+pub struct EventNoticeState {
+    pub current: Option<String>,
+    /// The notice reports a lost event.
+    pub failed: bool,
+    shown_at: f32,
+}
+
+pub struct EventNoticePresentationPlugin;
+
+impl Plugin for EventNoticePresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EventNoticeState>()
+            .add_systems(OnExit(AppState::Gameplay), clear_event_notice)
+            .add_systems(
+                Update,
+                apply_event_notices.run_if(in_state(AppState::Gameplay)),
+            )
+            .add_systems(
+                EguiPrimaryContextPass,
+                draw_event_notice
+                    .run_if(in_state(AppState::Gameplay))
+                    .run_if(|state: Res<EventNoticeState>| state.current.is_some()),
+            );
+    }
+}
+
+pub fn notice_text(notice: &EventNotice) -> String {
+    let name = match notice.event {
+        SequenceEvent::Crywolf => "Crywolf",
+        SequenceEvent::Kanturu => "Kanturu",
+    };
+    let text = match (notice.phase, notice.outcome) {
+        (EventPhase::Closed, Some(true)) => "vitoria! O evento terminou",
+        (EventPhase::Closed, Some(false)) => "derrota. O evento terminou",
+        (EventPhase::Closed, None) => "o evento terminou",
+        (EventPhase::Notice, _) => "o evento comeca em breve",
+        (EventPhase::AltarContracts, _) => "elfos, assinem os contratos nos altares",
+        (EventPhase::StatueDefense, _) => "defendam a estatua do lobo!",
+        (EventPhase::MayaHands, _) => "derrotem as maos de Maya!",
+        (EventPhase::Nightmare, _) => "Nightmare apareceu!",
+        (EventPhase::TowerOpen, _) => "a Torre do Refinamento esta aberta",
+    };
+    format!("{name}: {text}")
+}
+
+pub fn doppelganger_text(status: &DoppelgangerStatus) -> String {
+    match status.outcome {
+        Some(true) => "Doppelganger: todas as ondas vencidas!".to_string(),
+        Some(false) => format!(
+            "Doppelganger: o grupo caiu na onda {}/{}",
+            status.wave, status.waves
+        ),
+        None => format!(
+            "Doppelganger: onda {}/{} ({} aliados espelhados)",
+            status.wave,
+            status.waves,
+            status.allies.len()
+        ),
+    }
+}
+
+pub fn expired_items_text(items: &[ItemInstance]) -> String {
+    match items {
+        [item] => format!("{} expirou e foi removido", item_title(item)),
+        _ => format!("{} itens expiraram e foram removidos", items.len()),
+    }
+}
+
+/// Restarts come as a maintenance of the whole world.
+pub fn maintenance_text(notice: &MaintenanceNotice) -> String {
+    let scope = match notice.map_id {
+        Some(_) => "este mapa",
+        None => "o servidor",
+    };
+    let minutes = notice.seconds_remaining.div_ceil(60);
+    let countdown = if minutes > 1 {
+        format!("{scope} fecha em {minutes} minutos")
+    } else {
+        format!("{scope} fecha em menos de 1 minuto")
+    };
+    if notice.reason.is_empty() {
+        format!("Manutencao: {countdown}")
+    } else {
+        format!("Manutencao: {countdown} ({})", notice.reason)
+    }
+}
+
+fn clear_event_notice(mut state: ResMut<EventNoticeState>) {
+    *state = EventNoticeState::default();
+}
+
+fn apply_event_notices(
+    time: Res<Time>,
+    mut state: ResMut<EventNoticeState>,
+    mut incoming: MessageReader<ServerMessageReceived>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        let (text, outcome) = match message {
+            ServerMessage::EventNotice(notice) => (notice_text(notice), notice.outcome),
+            ServerMessage::DoppelgangerStatus(status) => {
+                (doppelganger_text(status), status.outcome)
+            }
+            ServerMessage::ItemsExpired { items } if !items.is_empty() => {
+                (expired_items_text(items), None)
+            }
+            ServerMessage::Maintenance(notice) => (maintenance_text(notice), None),
+            _ => continue,
+        };
+        state.current = Some(text);
+        state.failed = outcome == Some(false);
+        state.shown_at = time.elapsed_secs();
+    }
+
+    if time.elapsed_secs() - state.shown_at > NOTICE_SECS {
+        state.current = None;
+    }
+}
+
+fn draw_event_notice(
+    mut contexts: EguiContexts,
+    state: Res<EventNoticeState>,
+    accessibility: Res<UiAccessibility>,
+) {
+    let Some(text) = &state.current else {
+        return;
+    };
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let color = if state.failed {
+        accessibility.palette.error
+    } else {
+        NOTICE_COLOR
+    };
+    egui::Area::new(egui::Id::new("event_notice"))
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+        .interactable(false)
+        .show(ctx, |ui| {
+            ui.label(egui::RichText::new(text).color(color).size(20.0).strong());
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closing_notice_tells_the_outcome() {
+        let notice = |phase, outcome| EventNotice {
+            world_id: 1,
+            event: SequenceEvent::Kanturu,
+            cycle: 3,
+            phase,
+            ends_at_ms: 0,
+            outcome,
+        };
+
+        assert_eq!(
+            notice_text(&notice(EventPhase::Nightmare, None)),
+            "Kanturu: Nightmare apareceu!"
+        );
+        assert!(notice_text(&notice(EventPhase::Closed, Some(true))).contains("vitoria"));
+        assert!(notice_text(&notice(EventPhase::Closed, Some(false))).contains("derrota"));
+    }
+
+    #[test]
+    fn doppelganger_notice_counts_waves() {
+        let mut status = DoppelgangerStatus {
+            run_id: 1,
+            zone_map_id: 66,
+            wave: 2,
+            waves: 5,
+            wave_ends_at_ms: 0,
+            allies: Vec::new(),
+            outcome: None,
+        };
+        assert_eq!(
+            doppelganger_text(&status),
+            "Doppelganger: onda 2/5 (0 aliados espelhados)"
+        );
+        status.outcome = Some(false);
+        assert!(doppelganger_text(&status).contains("caiu na onda 2/5"));
+    }
+
+    #[test]
+    fn maintenance_notice_counts_minutes_down() {
+        let mut notice = MaintenanceNotice {
+            world_id: 1,
+            map_id: None,
+            closes_at_ms: 0,
+            seconds_remaining: 600,
+            reason: "atualizacao".to_string(),
+        };
+        assert_eq!(
+            maintenance_text(&notice),
+            "Manutencao: o servidor fecha em 10 minutos (atualizacao)"
+        );
+        notice.seconds_remaining = 45;
+        notice.reason.clear();
+        assert_eq!(
+            maintenance_text(&notice),
+            "Manutencao: o servidor fecha em menos de 1 minuto"
+        );
+    }
+}
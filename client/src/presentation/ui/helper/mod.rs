@@ -0,0 +1,105 @@
+//! MU Helper switch and status, opened with Z; Home turns the helper on or off.
+
+use crate::AppState;
+use crate::gameplay::helper::MuHelper;
+use crate::infra::network::SendClientMessage;
+use crate::presentation::ui::accessibility::UiAccessibility;
+use bevy::prelude::*;
+use bevy::state::prelude::in_state;
+use bevy_egui::input::EguiWantsInput;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+#[derive(Resource, Default)]
+pub struct HelperPanelState {
+    pub open: bool,
+}
+
+pub struct HelperPresentationPlugin;
+
+impl Plugin for HelperPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HelperPanelState>()
+            .add_systems(
+                Update,
+                handle_helper_keys.run_if(in_state(AppState::Gameplay)),
+            )
+            .add_systems(
+                EguiPrimaryContextPass,
+                draw_helper_panel
+                    .run_if(in_state(AppState::Gameplay))
+                    .run_if(|state: Res<HelperPanelState>| state.open),
+            );
+    }
+}
+
+fn handle_helper_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    egui_wants_input: Option<Res<EguiWantsInput>>,
+    mut panel: ResMut<HelperPanelState>,
+    mut helper: ResMut<MuHelper>,
+    mut outgoing: MessageWriter<SendClientMessage>,
+) {
+    if egui_wants_input.is_some_and(|egui| egui.wants_any_keyboard_input()) {
+        return;
+    }
+    if keys.just_pressed(KeyCode::KeyZ) {
+        panel.open = !panel.open;
+    }
+    if keys.just_pressed(KeyCode::Home) {
+        let on = !helper.requested;
+        outgoing.write(SendClientMessage(helper.request(on)));
+    }
+}
+
+fn draw_helper_panel(
+    mut contexts: EguiContexts,
+    mut panel: ResMut<HelperPanelState>,
+    mut helper: ResMut<MuHelper>,
+    accessibility: Res<UiAccessibility>,
+    mut outgoing: MessageWriter<SendClientMessage>,
+) {
+    let palette = accessibility.palette;
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let mut open = panel.open;
+
+    egui::Window::new("MU Helper")
+        .open(&mut open)
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-14.0, 14.0))
+        .resizable(false)
+        .collapsible(false)
+        .default_width(220.0)
+        .show(ctx, |ui| {
+            match (helper.allowed, helper.active, helper.requested) {
+                (Some(false), _, _) => {
+                    ui.colored_label(palette.error, "Nao permitido neste mundo");
+                }
+                (_, true, _) => {
+                    ui.label("Cacando");
+                }
+                (_, false, true) => {
+                    ui.label("Aguardando o servidor...");
+                }
+                _ => {
+                    ui.label("Desligado");
+                }
+            }
+
+            let label = if helper.requested {
+                "Desligar"
+            } else {
+                "Ligar"
+            };
+            if ui.button(label).clicked() {
+                let on = !helper.requested;
+                outgoing.write(SendClientMessage(helper.request(on)));
+            }
+            ui.label(
+                egui::RichText::new("Habilidades, alcance e coleta: Configuracoes > Helper").weak(),
+            );
+        });
+
+    panel.open = open;
+}
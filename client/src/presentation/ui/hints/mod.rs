@@ -0,0 +1,185 @@
+//! First-time hints: a dismissible tip the first time the player meets a part
+//! of the game. Dismissed hints are stored in the settings and the Conta tab
+//! shows them all again.
+//!
+//! Any system can raise one with [`ShowHint`]; this module raises the death
+//! hint on the first hit that kills the local character and the inventory
+//! hint on the first mail reward claimed. Nothing raises
+//! [`Hint::LevelUpPoint`] yet, as the server does not report level-ups.
+
+use crate::AppState;
+use crate::infra::network::ServerMessageReceived;
+use crate::settings::{Hint, SettingsResource};
+use bevy::prelude::*;
+use bevy::state::prelude::{OnExit, in_state};
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use protocol::ServerMessage;
+
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShowHint(pub Hint);
+
+#[derive(Resource, Default)]
+pub struct HintState {
+    /// Hints waiting, shown one at a time in order.
+    pub queue: Vec<Hint>,
+    local_entity_id: Option<u32>,
+}
+
+impl HintState {
+    pub fn current(&self) -> Option<Hint> {
+        self.queue.first().copied()
+    }
+
+    fn push(&mut self, hint: Hint) {
+        if !self.queue.contains(&hint) {
+            self.queue.push(hint);
+        }
+    }
+}
+
+pub struct HintPresentationPlugin;
+
+impl Plugin for HintPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HintState>()
+            .add_message::<ShowHint>()
+            .add_systems(OnExit(AppState::Gameplay), clear_hints)
+            .add_systems(
+                Update,
+                (raise_hints_from_server, queue_hints)
+                    .chain()
+                    .run_if(in_state(AppState::Gameplay)),
+            )
+            .add_systems(
+                EguiPrimaryContextPass,
+                draw_hint
+                    .run_if(in_state(AppState::Gameplay))
+                    .run_if(|state: Res<HintState>| !state.queue.is_empty()),
+            );
+    }
+}
+
+fn clear_hints(mut state: ResMut<HintState>) {
+    *state = HintState::default();
+}
+
+/// Hint a server message introduces, if any.
+fn hint_for(message: &ServerMessage, local_entity_id: Option<u32>) -> Option<Hint> {
+    match message {
+        ServerMessage::DamageEvent(hit)
+            if hit.remaining_hp == 0 && Some(hit.target_entity_id) == local_entity_id =>
+        {
+            Some(Hint::FirstDeath)
+        }
+        ServerMessage::MailClaimed { .. } => Some(Hint::Inventory),
+        _ => None,
+    }
+}
+
+fn raise_hints_from_server(
+    mut state: ResMut<HintState>,
+    mut incoming: MessageReader<ServerMessageReceived>,
+    mut hints: MessageWriter<ShowHint>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        if let ServerMessage::EnterMap { entity_id, .. } = message {
+            state.local_entity_id = Some(*entity_id);
+        }
+        if let Some(hint) = hint_for(message, state.local_entity_id) {
+            hints.write(ShowHint(hint));
+        }
+    }
+}
+
+fn queue_hints(
+    settings: Res<SettingsResource>,
+    mut state: ResMut<HintState>,
+    mut requests: MessageReader<ShowHint>,
+) {
+    for ShowHint(hint) in requests.read() {
+        if settings.current.hints.should_show(*hint) {
+            state.push(*hint);
+        }
+    }
+}
+
+fn draw_hint(
+    mut contexts: EguiContexts,
+    mut state: ResMut<HintState>,
+    mut settings: ResMut<SettingsResource>,
+) {
+    let Some(hint) = state.current() else {
+        return;
+    };
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let mut dismissed = false;
+    let mut disable_all = false;
+    egui::Window::new(format!("Dica: {}", hint.title()))
+        .id(egui::Id::new("first_time_hint"))
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -120.0))
+        .resizable(false)
+        .collapsible(false)
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            ui.label(hint.text());
+            ui.horizontal(|ui| {
+                dismissed = ui.button("Entendi").clicked();
+                disable_all = ui.button("Nao mostrar dicas").clicked();
+            });
+        });
+
+    if !dismissed && !disable_all {
+        return;
+    }
+    let hints = &mut settings.current.hints;
+    hints.seen.insert(hint);
+    if disable_all {
+        hints.enabled = false;
+        state.queue.clear();
+    } else {
+        state.queue.remove(0);
+    }
+    if let Err(error) = settings.save_to_disk() {
+        warn!(
+            "Failed to save settings file '{}': {}",
+            settings.path().display(),
+            error
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::DamageEvent;
+
+    #[test]
+    fn only_the_local_characters_death_raises_a_hint() {
+        let killed = |target_entity_id, remaining_hp| {
+            ServerMessage::DamageEvent(DamageEvent {
+                attacker_entity_id: 9,
+                target_entity_id,
+                skill_id: 0,
+                damage: 50,
+                remaining_hp,
+                server_time_ms: 0,
+            })
+        };
+        assert_eq!(hint_for(&killed(1, 0), Some(1)), Some(Hint::FirstDeath));
+        assert_eq!(hint_for(&killed(1, 10), Some(1)), None);
+        assert_eq!(hint_for(&killed(2, 0), Some(1)), None);
+        assert_eq!(hint_for(&killed(1, 0), None), None);
+        assert_eq!(
+            hint_for(&ServerMessage::MailClaimed { mail_id: 3 }, Some(1)),
+            Some(Hint::Inventory)
+        );
+
+        let mut state = HintState::default();
+        state.push(Hint::Inventory);
+        state.push(Hint::Inventory);
+        assert_eq!(state.queue, vec![Hint::Inventory]);
+    }
+}
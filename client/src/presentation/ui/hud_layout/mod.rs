@@ -0,0 +1,383 @@
+//! Placement of HUD elements and the edit mode that moves them.
+//!
+//! Every element has a default slot anchored to a screen edge. Once the layout
+//! is unlocked from the settings, elements can be dragged and scaled; saving
+//! stores the result in `settings.yaml` under the current screen resolution.
+//! Widgets draw through [`show_element`] so they follow the layout.
+
+use crate::AppState;
+use crate::settings::{
+    HudElement, HudElementLayout, HudSettings, ResolutionSetting, SettingsResource,
+};
+use bevy::prelude::*;
+use bevy::state::prelude::in_state;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+const EDIT_FRAME_FILL: egui::Color32 = egui::Color32::from_rgba_premultiplied(30, 60, 90, 90);
+const EDIT_FRAME_STROKE: egui::Color32 = egui::Color32::from_rgb(120, 190, 255);
+
+/// Default spot of an element and the space it takes at 100% scale.
+struct HudSlot {
+    anchor: egui::Align2,
+    offset: egui::Vec2,
+    size: egui::Vec2,
+}
+
+fn default_slot(element: HudElement) -> HudSlot {
+    match element {
+        HudElement::Chat => HudSlot {
+            anchor: egui::Align2::LEFT_BOTTOM,
+            offset: egui::vec2(12.0, -80.0),
+            size: egui::vec2(360.0, 180.0),
+        },
+        HudElement::Minimap => HudSlot {
+            anchor: egui::Align2::RIGHT_TOP,
+            offset: egui::vec2(-12.0, 12.0),
+            size: egui::vec2(180.0, 180.0),
+        },
+        HudElement::PartyFrames => HudSlot {
+            anchor: egui::Align2::LEFT_TOP,
+            offset: egui::vec2(12.0, 120.0),
+            size: egui::vec2(200.0, 240.0),
+        },
+        HudElement::Hotbar => HudSlot {
+            anchor: egui::Align2::CENTER_BOTTOM,
+            offset: egui::vec2(0.0, -8.0),
+            size: egui::vec2(66.0, 62.0),
+        },
+        HudElement::BuffBar => HudSlot {
+            anchor: egui::Align2::RIGHT_TOP,
+            offset: egui::vec2(-12.0, 204.0),
+            size: egui::vec2(240.0, 36.0),
+        },
+    }
+}
+
+/// Resolution the layout is stored under: the egui screen in physical pixels.
+pub fn screen_resolution(ctx: &egui::Context) -> ResolutionSetting {
+    let size = ctx.content_rect().size() * ctx.pixels_per_point();
+    ResolutionSetting {
+        width: size.x.round() as u32,
+        height: size.y.round() as u32,
+    }
+}
+
+/// Where `element` sits with no user offset, before it is kept on screen.
+fn slot_rect(element: HudElement, placement: HudElementLayout, screen: egui::Rect) -> egui::Rect {
+    let slot = default_slot(element);
+    slot.anchor
+        .align_size_within_rect(slot.size * placement.scale(), screen)
+        .translate(slot.offset)
+}
+
+/// Moves `rect` the least needed to fit inside `screen`.
+fn keep_on_screen(rect: egui::Rect, screen: egui::Rect) -> egui::Rect {
+    let dx = (screen.min.x - rect.min.x).max(0.0) - (rect.max.x - screen.max.x).max(0.0);
+    let dy = (screen.min.y - rect.min.y).max(0.0) - (rect.max.y - screen.max.y).max(0.0);
+    rect.translate(egui::vec2(dx, dy))
+}
+
+/// Screen rect of `element` at `placement`, kept inside `screen`.
+pub fn element_rect(
+    element: HudElement,
+    placement: HudElementLayout,
+    screen: egui::Rect,
+) -> egui::Rect {
+    let offset = egui::vec2(placement.offset_x as f32, placement.offset_y as f32);
+    keep_on_screen(
+        slot_rect(element, placement, screen).translate(offset),
+        screen,
+    )
+}
+
+/// `placement` after dragging `element` by `delta`. The stored offset stops
+/// at the screen edge, so dragging back responds at once.
+pub fn drag_element(
+    element: HudElement,
+    placement: HudElementLayout,
+    screen: egui::Rect,
+    delta: egui::Vec2,
+) -> HudElementLayout {
+    let moved = keep_on_screen(
+        element_rect(element, placement, screen).translate(delta),
+        screen,
+    );
+    let offset = moved.min - slot_rect(element, placement, screen).min;
+    HudElementLayout {
+        offset_x: offset.x.round() as i32,
+        offset_y: offset.y.round() as i32,
+        ..placement
+    }
+}
+
+/// Draws `add_contents` where the layout puts `element`, scaled about the
+/// element's top-left corner.
+pub fn show_element<R>(
+    ctx: &egui::Context,
+    layout: &HudSettings,
+    element: HudElement,
+    add_contents: impl FnOnce(&mut egui::Ui) -> R,
+) -> R {
+    let screen = ctx.content_rect();
+    let placement = layout.element(screen_resolution(ctx), element);
+    let rect = element_rect(element, placement, screen);
+    let scale = placement.scale();
+
+    let id = egui::Id::new(("hud_element", element));
+    ctx.set_transform_layer(
+        egui::LayerId::new(egui::Order::Middle, id),
+        egui::emath::TSTransform::new(rect.min.to_vec2() * (1.0 - scale), scale),
+    );
+    egui::Area::new(id)
+        .order(egui::Order::Middle)
+        .fixed_pos(rect.min)
+        .constrain(false)
+        .show(ctx, add_contents)
+        .inner
+}
+
+/// Layout edit mode. Holds the layout being edited until it is saved or
+/// dropped.
+#[derive(Resource, Default)]
+pub struct HudLayoutEditor {
+    draft: Option<HudSettings>,
+}
+
+impl HudLayoutEditor {
+    pub fn is_editing(&self) -> bool {
+        self.draft.is_some()
+    }
+
+    pub fn unlock(&mut self, saved: &HudSettings) {
+        self.draft = Some(saved.clone());
+    }
+
+    /// Layout widgets follow this frame: the draft while editing.
+    pub fn layout<'a>(&'a self, saved: &'a HudSettings) -> &'a HudSettings {
+        self.draft.as_ref().unwrap_or(saved)
+    }
+}
+
+pub struct HudLayoutPresentationPlugin;
+
+impl Plugin for HudLayoutPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HudLayoutEditor>()
+            .add_systems(OnExit(AppState::Gameplay), lock_layout)
+            .add_systems(
+                EguiPrimaryContextPass,
+                draw_layout_editor
+                    .run_if(in_state(AppState::Gameplay))
+                    .run_if(|editor: Res<HudLayoutEditor>| editor.is_editing()),
+            );
+    }
+}
+
+fn lock_layout(mut editor: ResMut<HudLayoutEditor>) {
+    editor.draft = None;
+}
+
+fn draw_layout_editor(
+    mut contexts: EguiContexts,
+    mut editor: ResMut<HudLayoutEditor>,
+    mut settings: ResMut<SettingsResource>,
+) {
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+    let Some(draft) = editor.draft.as_mut() else {
+        return;
+    };
+
+    let screen = ctx.content_rect();
+    let resolution = screen_resolution(ctx);
+
+    for element in HudElement::ALL {
+        let placement = draft.element(resolution, element);
+        let rect = element_rect(element, placement, screen);
+        let response = egui::Area::new(egui::Id::new(("hud_edit_frame", element)))
+            .order(egui::Order::Foreground)
+            .fixed_pos(rect.min)
+            .constrain(false)
+            .show(ctx, |ui| {
+                let (frame, response) = ui.allocate_exact_size(rect.size(), egui::Sense::drag());
+                let painter = ui.painter();
+                painter.rect_filled(frame, 6.0, EDIT_FRAME_FILL);
+                painter.rect_stroke(
+                    frame,
+                    6.0,
+                    egui::Stroke::new(1.5, EDIT_FRAME_STROKE),
+                    egui::StrokeKind::Inside,
+                );
+                painter.text(
+                    frame.center(),
+                    egui::Align2::CENTER_CENTER,
+                    element.label(),
+                    egui::FontId::proportional(13.0),
+                    egui::Color32::WHITE,
+                );
+                response
+            })
+            .inner;
+
+        if response.dragged() {
+            let moved = drag_element(element, placement, screen, response.drag_delta());
+            draft.set_element(resolution, element, moved);
+        }
+    }
+
+    let mut save = false;
+    let mut cancel = false;
+    egui::Window::new("Layout do HUD")
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 24.0))
+        .order(egui::Order::Foreground)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "Arraste os elementos para move-los. Layout de {}.",
+                resolution.label()
+            ));
+            ui.separator();
+
+            egui::Grid::new("hud_layout_elements")
+                .num_columns(3)
+                .show(ui, |ui| {
+                    for element in HudElement::ALL {
+                        let mut placement = draft.element(resolution, element);
+                        ui.label(element.label());
+                        let scaled = ui
+                            .add(
+                                egui::Slider::new(
+                                    &mut placement.scale_percent,
+                                    HudElementLayout::SCALE_PERCENT_RANGE,
+                                )
+                                .suffix("%"),
+                            )
+                            .changed();
+                        if scaled {
+                            draft.set_element(resolution, element, placement);
+                        }
+                        if ui.button("Restaurar").clicked() {
+                            draft.set_element(resolution, element, HudElementLayout::default());
+                        }
+                        ui.end_row();
+                    }
+                });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Restaurar tudo").clicked() {
+                    draft.reset(resolution);
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    save = ui.button("Salvar").clicked();
+                    cancel = ui.button("Cancelar").clicked();
+                });
+            });
+        });
+
+    if save {
+        settings.current.hud = draft.clone();
+        if let Err(error) = settings.save_to_disk() {
+            warn!(
+                "Failed to save settings file '{}': {}",
+                settings.path().display(),
+                error
+            );
+        }
+    }
+    if save || cancel {
+        editor.draft = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen() -> egui::Rect {
+        egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(1280.0, 720.0))
+    }
+
+    #[test]
+    fn default_slots_fit_the_smallest_preset() {
+        let screen = screen();
+        for element in HudElement::ALL {
+            let rect = element_rect(element, HudElementLayout::default(), screen);
+            assert!(screen.contains_rect(rect), "{element:?} at {rect:?}");
+            assert_eq!(
+                rect,
+                slot_rect(element, HudElementLayout::default(), screen)
+            );
+        }
+    }
+
+    #[test]
+    fn dragging_stops_at_the_screen_edge() {
+        let screen = screen();
+        let start = HudElementLayout::default();
+        let far_left = drag_element(HudElement::Hotbar, start, screen, egui::vec2(-5_000.0, 0.0));
+        let rect = element_rect(HudElement::Hotbar, far_left, screen);
+        assert_eq!(rect.min.x, 0.0);
+
+        // The offset stopped at the edge, so a small drag back moves it.
+        let back = drag_element(HudElement::Hotbar, far_left, screen, egui::vec2(10.0, 0.0));
+        assert_eq!(element_rect(HudElement::Hotbar, back, screen).min.x, 10.0);
+    }
+
+    #[test]
+    fn layouts_are_kept_per_resolution() {
+        let small = ResolutionSetting {
+            width: 1280,
+            height: 720,
+        };
+        let large = ResolutionSetting {
+            width: 1920,
+            height: 1080,
+        };
+        let moved = HudElementLayout {
+            offset_x: 40,
+            offset_y: -20,
+            scale_percent: 150,
+        };
+
+        let mut layout = HudSettings::default();
+        layout.set_element(small, HudElement::Minimap, moved);
+        assert_eq!(layout.element(small, HudElement::Minimap), moved);
+        assert_eq!(
+            layout.element(large, HudElement::Minimap),
+            HudElementLayout::default()
+        );
+
+        // Elements back at their default are not stored.
+        layout.set_element(small, HudElement::Minimap, HudElementLayout::default());
+        assert!(layout.layouts.is_empty());
+
+        layout.set_element(large, HudElement::Chat, moved);
+        layout.reset(large);
+        assert_eq!(layout, HudSettings::default());
+    }
+
+    #[test]
+    fn layout_round_trips_through_yaml() {
+        let mut layout = HudSettings::default();
+        layout.set_element(
+            ResolutionSetting {
+                width: 2560,
+                height: 1440,
+            },
+            HudElement::PartyFrames,
+            HudElementLayout {
+                offset_x: 8,
+                offset_y: 300,
+                scale_percent: 80,
+            },
+        );
+
+        let yaml = serde_yaml::to_string(&layout).unwrap();
+        assert!(yaml.contains("2560x1440"));
+        assert!(yaml.contains("party_frames"));
+        assert_eq!(serde_yaml::from_str::<HudSettings>(&yaml).unwrap(), layout);
+    }
+}
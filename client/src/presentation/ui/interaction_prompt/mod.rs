@@ -0,0 +1,63 @@
+//! Label above the interactive object under the cursor ("Conversar",
+//! "Entrar na masmorra").
+
+use crate::AppState;
+use crate::gameplay::interaction_hover::HoveredInteractable;
+use crate::scene_runtime::components::InteractionPrompt;
+use bevy::prelude::*;
+use bevy::state::prelude::in_state;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+/// World-space offset of the label above the object's origin.
+const PROMPT_HEIGHT: f32 = 180.0;
+const PROMPT_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 215, 120);
+const PROMPT_PADDING: egui::Vec2 = egui::vec2(6.0, 3.0);
+
+pub struct InteractionPromptPresentationPlugin;
+
+impl Plugin for InteractionPromptPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            EguiPrimaryContextPass,
+            draw_interaction_prompt
+                .run_if(in_state(AppState::Gameplay))
+                .run_if(|hovered: Res<HoveredInteractable>| hovered.entity().is_some()),
+        );
+    }
+}
+
+fn draw_interaction_prompt(
+    mut contexts: EguiContexts,
+    hovered: Res<HoveredInteractable>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    prompts: Query<(&InteractionPrompt, &GlobalTransform)>,
+) {
+    let Some((InteractionPrompt(label), transform)) =
+        hovered.entity().and_then(|entity| prompts.get(entity).ok())
+    else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = cameras.single() else {
+        return;
+    };
+    let anchor = transform.translation() + Vec3::Y * PROMPT_HEIGHT;
+    let Ok(screen) = camera.world_to_viewport(camera_transform, anchor) else {
+        return;
+    };
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let painter = ctx.layer_painter(egui::LayerId::background());
+    let text = painter.layout_no_wrap(
+        label.clone(),
+        egui::FontId::proportional(14.0),
+        PROMPT_COLOR,
+    );
+    let frame = egui::Rect::from_center_size(
+        egui::pos2(screen.x, screen.y),
+        text.size() + PROMPT_PADDING * 2.0,
+    );
+    painter.rect_filled(frame, 3.0, egui::Color32::from_black_alpha(170));
+    painter.galley(frame.min + PROMPT_PADDING, text, PROMPT_COLOR);
+}
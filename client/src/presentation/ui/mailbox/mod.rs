@@ -0,0 +1,138 @@
+//! Event reward mailbox shown after entering the world.
+
+use crate::AppState;
+use crate::infra::network::{SendClientMessage, ServerMessageReceived};
+use crate::presentation::ui::accessibility::UiAccessibility;
+use crate::presentation::ui::server_errors::server_error_text;
+use crate::presentation::ui::widgets::item_tooltip::{item_title, item_tooltip};
+use bevy::prelude::*;
+use bevy::state::prelude::{OnEnter, in_state};
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use protocol::{ClientMessage, MailEntry, ServerErrorKind, ServerMessage};
+
+#[derive(Resource, Default)]
+pub struct MailboxState {
+    pub entries: Vec<MailEntry>,
+    pub open: bool,
+    pub last_error: Option<String>,
+}
+
+pub struct MailboxPresentationPlugin;
+
+impl Plugin for MailboxPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MailboxState>()
+            .add_systems(OnEnter(AppState::Gameplay), request_mailbox)
+            .add_systems(
+                Update,
+                apply_mailbox_messages.run_if(in_state(AppState::Gameplay)),
+            )
+            .add_systems(
+                EguiPrimaryContextPass,
+                draw_mailbox_window
+                    .run_if(in_state(AppState::Gameplay))
+                    .run_if(|state: Res<MailboxState>| state.open),
+            );
+    }
+}
+
+fn request_mailbox(
+    mut state: ResMut<MailboxState>,
+    mut outgoing: MessageWriter<SendClientMessage>,
+) {
+    *state = MailboxState::default();
+    outgoing.write(SendClientMessage(ClientMessage::RequestMailbox));
+}
+
+fn apply_mailbox_messages(
+    mut state: ResMut<MailboxState>,
+    mut incoming: MessageReader<ServerMessageReceived>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        match message {
+            ServerMessage::Mailbox { entries } => {
+                state.entries = entries.clone();
+                state.open = !state.entries.is_empty();
+                state.last_error = None;
+            }
+            ServerMessage::MailClaimed { mail_id } => {
+                state.entries.retain(|entry| entry.mail_id != *mail_id);
+                state.last_error = None;
+                if state.entries.is_empty() {
+                    state.open = false;
+                }
+            }
+            ServerMessage::ItemsExpired { items } => {
+                for entry in &mut state.entries {
+                    entry
+                        .items
+                        .retain(|item| !items.iter().any(|gone| gone.serial == item.serial));
+                }
+                state
+                    .entries
+                    .retain(|entry| entry.zen > 0 || !entry.items.is_empty());
+                if state.entries.is_empty() {
+                    state.open = false;
+                }
+            }
+            // Casts rejected for cooldown are not about the mail.
+            ServerMessage::Error(error)
+                if state.open && error.kind != ServerErrorKind::SkillCooldown =>
+            {
+                state.last_error = Some(server_error_text(error.code()).to_string());
+            }
+            _ => {}
+        }
+    }
+}
+
+fn draw_mailbox_window(
+    mut contexts: EguiContexts,
+    mut state: ResMut<MailboxState>,
+    accessibility: Res<UiAccessibility>,
+    mut outgoing: MessageWriter<SendClientMessage>,
+) {
+    let palette = accessibility.palette;
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let mut open = state.open;
+
+    egui::Window::new("Correio")
+        .open(&mut open)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .resizable(false)
+        .collapsible(false)
+        .default_width(340.0)
+        .show(ctx, |ui| {
+            for entry in &state.entries {
+                ui.group(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.strong(&entry.source);
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.button("Resgatar").clicked() {
+                                outgoing.write(SendClientMessage(ClientMessage::ClaimMail {
+                                    mail_id: entry.mail_id,
+                                }));
+                            }
+                        });
+                    });
+                    if entry.zen > 0 {
+                        ui.label(format!("{} Zen", entry.zen));
+                    }
+                    for item in &entry.items {
+                        ui.label(item_title(item))
+                            .on_hover_ui(|ui| item_tooltip(ui, item, &palette));
+                    }
+                });
+            }
+
+            if let Some(error) = &state.last_error {
+                ui.separator();
+                ui.colored_label(palette.error, error);
+            }
+        });
+
+    state.open = open;
+}
@@ -0,0 +1,184 @@
+//! Swirl drawn over the world while a map transfer loads, from the server's
+//! `MapTransfer` (a portal walked into, a warp) until the character enters
+//! the next map or the transfer is refused.
+
+use crate::AppState;
+use crate::infra::network::ServerMessageReceived;
+use bevy::prelude::*;
+use bevy::state::prelude::{OnExit, in_state};
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use protocol::{ServerErrorKind, ServerMessage};
+
+const SWIRL_ARMS: usize = 3;
+const SWIRL_RADIUS: f32 = 110.0;
+/// Points along each arm.
+const ARM_SEGMENTS: usize = 24;
+/// How far each arm winds around the centre, in turns.
+const ARM_TURNS: f32 = 0.8;
+const TURNS_PER_SEC: f32 = 0.5;
+const FADE_IN_SECS: f32 = 0.4;
+const SWIRL_COLOR: egui::Color32 = egui::Color32::from_rgb(150, 200, 255);
+
+#[derive(Resource, Debug, Default)]
+pub struct MapTransferSwirl {
+    /// `Time::elapsed_secs` when the transfer started.
+    started_at: Option<f32>,
+}
+
+impl MapTransferSwirl {
+    pub fn is_active(&self) -> bool {
+        self.started_at.is_some()
+    }
+
+    fn apply(&mut self, message: &ServerMessage, now_secs: f32) {
+        match message {
+            ServerMessage::MapTransfer(_) if self.started_at.is_none() => {
+                self.started_at = Some(now_secs);
+            }
+            ServerMessage::EnterMap { .. } => self.started_at = None,
+            // A cast rejected for cooldown does not answer the transfer.
+            ServerMessage::Error(error) if error.kind != ServerErrorKind::SkillCooldown => {
+                self.started_at = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+pub struct MapTransferPresentationPlugin;
+
+impl Plugin for MapTransferPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MapTransferSwirl>()
+            .add_systems(OnExit(AppState::Gameplay), reset_swirl)
+            .add_systems(
+                Update,
+                follow_map_transfers.run_if(in_state(AppState::Gameplay)),
+            )
+            .add_systems(
+                EguiPrimaryContextPass,
+                draw_swirl
+                    .run_if(in_state(AppState::Gameplay))
+                    .run_if(|swirl: Res<MapTransferSwirl>| swirl.is_active()),
+            );
+    }
+}
+
+fn reset_swirl(mut swirl: ResMut<MapTransferSwirl>) {
+    *swirl = MapTransferSwirl::default();
+}
+
+fn follow_map_transfers(
+    time: Res<Time>,
+    mut swirl: ResMut<MapTransferSwirl>,
+    mut incoming: MessageReader<ServerMessageReceived>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        swirl.apply(message, time.elapsed_secs());
+    }
+}
+
+/// Points of one arm, from the centre outwards, turned by `rotation` radians.
+fn arm_points(center: egui::Pos2, arm: usize, rotation: f32) -> Vec<egui::Pos2> {
+    let offset = std::f32::consts::TAU * arm as f32 / SWIRL_ARMS as f32;
+    (0..=ARM_SEGMENTS)
+        .map(|step| {
+            let along = step as f32 / ARM_SEGMENTS as f32;
+            let angle = offset + rotation + along * ARM_TURNS * std::f32::consts::TAU;
+            center + egui::vec2(angle.cos(), angle.sin()) * SWIRL_RADIUS * along
+        })
+        .collect()
+}
+
+fn draw_swirl(mut contexts: EguiContexts, time: Res<Time>, swirl: Res<MapTransferSwirl>) {
+    let Some(started_at) = swirl.started_at else {
+        return;
+    };
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+    let elapsed = time.elapsed_secs() - started_at;
+    let fade = (elapsed / FADE_IN_SECS).clamp(0.0, 1.0);
+    let rotation = -elapsed * TURNS_PER_SEC * std::f32::consts::TAU;
+    let screen = ctx.content_rect();
+    let center = screen.center();
+
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("map_transfer_swirl"),
+    ));
+    painter.rect_filled(
+        screen,
+        0.0,
+        egui::Color32::from_black_alpha((fade * 200.0) as u8),
+    );
+    let color = SWIRL_COLOR.gamma_multiply(fade);
+    for arm in 0..SWIRL_ARMS {
+        let points = arm_points(center, arm, rotation);
+        // Arms thin out towards their tips.
+        for (index, segment) in points.windows(2).enumerate() {
+            let width = 6.0 * (1.0 - index as f32 / ARM_SEGMENTS as f32) + 1.0;
+            painter.line_segment([segment[0], segment[1]], egui::Stroke::new(width, color));
+        }
+    }
+    painter.circle_filled(center, 10.0, color);
+    painter.text(
+        center + egui::vec2(0.0, SWIRL_RADIUS + 28.0),
+        egui::Align2::CENTER_CENTER,
+        "Teleportando...",
+        egui::FontId::proportional(16.0),
+        color,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::ServerError;
+
+    fn error(kind: ServerErrorKind) -> ServerMessage {
+        ServerMessage::Error(ServerError::new(kind, ""))
+    }
+
+    #[test]
+    fn swirl_lasts_until_the_next_map_or_a_refusal() {
+        let transfer = ServerMessage::MapTransfer(protocol::MapTransferDirective {
+            transfer_id: 1,
+            route: protocol::RouteKey::LOBBY,
+            host: "127.0.0.1".to_string(),
+            port: 55901,
+            route_token: String::new(),
+            expires_at_ms: 0,
+        });
+        let mut swirl = MapTransferSwirl::default();
+        swirl.apply(&transfer, 1.0);
+        swirl.apply(&transfer, 2.0);
+        assert_eq!(swirl.started_at, Some(1.0));
+        swirl.apply(&error(ServerErrorKind::SkillCooldown), 2.0);
+        assert!(swirl.is_active());
+        swirl.apply(&error(ServerErrorKind::MapClosed), 2.5);
+        assert!(!swirl.is_active());
+
+        swirl.apply(&transfer, 3.0);
+        swirl.apply(
+            &ServerMessage::EnterMap {
+                entity_id: 7,
+                map_id: 2,
+                x: 108,
+                y: 247,
+            },
+            4.0,
+        );
+        assert!(!swirl.is_active());
+    }
+
+    #[test]
+    fn arms_wind_out_from_the_centre() {
+        let center = egui::pos2(400.0, 300.0);
+        let points = arm_points(center, 0, 0.0);
+        assert_eq!(points.len(), ARM_SEGMENTS + 1);
+        assert_eq!(points[0], center);
+        let tip = *points.last().unwrap();
+        assert!(((tip - center).length() - SWIRL_RADIUS).abs() < 1e-3);
+    }
+}
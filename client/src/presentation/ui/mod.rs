@@ -1,3 +1,19 @@
+pub mod accessibility;
+pub mod bestiary;
+pub mod chat;
+pub mod combat_log;
+pub mod disconnect;
+pub mod emote_wheel;
+pub mod event_notice;
+pub mod helper;
+pub mod hints;
 pub mod hud;
+pub mod hud_layout;
+pub mod interaction_prompt;
 pub mod login;
+pub mod mailbox;
+pub mod map_transfer;
+pub mod nameplate;
+pub mod server_errors;
+pub mod ui_sounds;
 pub mod widgets;
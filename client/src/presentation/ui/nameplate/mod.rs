@@ -0,0 +1,407 @@
+//! Floating character names with guild relation indicators, Gens factions,
+//! monster ranks, HP bars and status icons.
+//!
+//! Crowded maps would draw hundreds of plates, so they are culled by distance
+//! from the camera: the target and party members always get the full plate,
+//! the rest drop the HP bar and icons past half the nameplate distance of the
+//! render distance setting and disappear past it. The performance overlay
+//! shows how many were drawn.
+
+use crate::AppState;
+use crate::domain::settings::{RenderDistanceSetting, SettingsResource};
+use crate::presentation::ui::accessibility::{UiAccessibility, UiPalette};
+use crate::scene_runtime::systems::StatusEffectVisuals;
+use bevy::prelude::*;
+use bevy::state::prelude::in_state;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use common::{BuffEffect, BuffKind, MurderStatus};
+use protocol::{GensFaction, GuildRelation, MonsterRank};
+
+/// World-space offset of the nameplate above the entity origin.
+const NAMEPLATE_HEIGHT: f32 = 220.0;
+const NAME_COLOR: egui::Color32 = egui::Color32::from_rgb(235, 235, 235);
+/// Share of the nameplate distance within which HP bars and icons are drawn.
+const DETAIL_DISTANCE_SHARE: f32 = 0.5;
+const HP_BAR_SIZE: egui::Vec2 = egui::vec2(60.0, 5.0);
+const STATUS_ICON_SIZE: f32 = 10.0;
+const ICE_ICON_COLOR: egui::Color32 = egui::Color32::from_rgb(120, 180, 255);
+const POISON_ICON_COLOR: egui::Color32 = egui::Color32::from_rgb(110, 220, 80);
+const BUFF_ICON_COLOR: egui::Color32 = egui::Color32::from_rgb(240, 200, 90);
+const DEBUFF_ICON_COLOR: egui::Color32 = egui::Color32::from_rgb(230, 95, 80);
+
+#[derive(Component, Debug, Clone, Default)]
+pub struct Nameplate {
+    pub name: String,
+    pub guild: Option<String>,
+    /// Relation between this entity's guild and the local player's guild.
+    pub relation: Option<GuildRelation>,
+    /// Elite or boss rank of a monster.
+    pub rank: Option<MonsterRank>,
+    /// Gens faction of a player, shown only on worlds that run Gens.
+    pub gens: Option<GensFaction>,
+    /// Murder status of a player; murderers are fair game on most maps.
+    pub murder: MurderStatus,
+    /// Last HP seen in damage events; no bar until the entity is hit.
+    pub hp: Option<NameplateHp>,
+    /// The local player's current target.
+    pub target: bool,
+    /// Member of the local player's party.
+    pub party: bool,
+}
+
+impl Nameplate {
+    /// Target and party plates are drawn in full at any distance.
+    pub fn pinned(&self) -> bool {
+        self.target || self.party
+    }
+
+    /// Updates the HP bar from a hit. The server only sends the HP left, so
+    /// the highest HP seen before a hit stands in for the maximum.
+    pub fn record_hit(&mut self, damage: u32, remaining_hp: u32) {
+        let seen = remaining_hp.saturating_add(damage);
+        let max = self.hp.map_or(seen, |hp| hp.max.max(seen));
+        self.hp = Some(NameplateHp {
+            current: remaining_hp,
+            max,
+        });
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NameplateHp {
+    pub current: u32,
+    pub max: u32,
+}
+
+impl NameplateHp {
+    pub fn fraction(self) -> f32 {
+        if self.max == 0 {
+            0.0
+        } else {
+            self.current as f32 / self.max as f32
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameplateDetail {
+    /// Name, guild, HP bar and status icons.
+    Full,
+    NameOnly,
+    Culled,
+}
+
+/// Camera distances (world units) at which plates lose detail.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct NameplateLod {
+    pub detail_distance: f32,
+    pub max_distance: f32,
+}
+
+impl Default for NameplateLod {
+    fn default() -> Self {
+        Self::for_render_distance(RenderDistanceSetting::default())
+    }
+}
+
+impl NameplateLod {
+    pub fn for_render_distance(render_distance: RenderDistanceSetting) -> Self {
+        let max_distance = render_distance.nameplate_distance();
+        Self {
+            detail_distance: max_distance * DETAIL_DISTANCE_SHARE,
+            max_distance,
+        }
+    }
+
+    pub fn detail(&self, distance: f32, pinned: bool) -> NameplateDetail {
+        if pinned || distance <= self.detail_distance {
+            NameplateDetail::Full
+        } else if distance <= self.max_distance {
+            NameplateDetail::NameOnly
+        } else {
+            NameplateDetail::Culled
+        }
+    }
+}
+
+/// Plates of the last frame, for the performance overlay.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NameplateStats {
+    pub full: usize,
+    pub name_only: usize,
+    /// Too far away or off screen.
+    pub culled: usize,
+}
+
+impl NameplateStats {
+    pub fn drawn(&self) -> usize {
+        self.full + self.name_only
+    }
+
+    pub fn total(&self) -> usize {
+        self.drawn() + self.culled
+    }
+}
+
+pub struct NameplatePresentationPlugin;
+
+impl Plugin for NameplatePresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NameplateLod>()
+            .init_resource::<NameplateStats>()
+            .add_systems(Update, sync_nameplate_lod)
+            .add_systems(
+                EguiPrimaryContextPass,
+                draw_nameplates.run_if(in_state(AppState::Gameplay)),
+            );
+    }
+}
+
+pub fn relation_color(palette: &UiPalette, relation: Option<GuildRelation>) -> egui::Color32 {
+    match relation {
+        Some(GuildRelation::Alliance) => palette.guild_ally,
+        Some(GuildRelation::Hostility) => palette.guild_hostile,
+        None => NAME_COLOR,
+    }
+}
+
+pub fn name_color(
+    palette: &UiPalette,
+    rank: Option<MonsterRank>,
+    murder: MurderStatus,
+) -> egui::Color32 {
+    match (rank, murder) {
+        (Some(MonsterRank::Elite), _) => palette.monster_elite,
+        (Some(MonsterRank::Boss), _) => palette.monster_boss,
+        (None, MurderStatus::Warning) => palette.murder_warning,
+        (None, MurderStatus::Murderer) => palette.murderer,
+        (None, MurderStatus::Commoner) => NAME_COLOR,
+    }
+}
+
+pub fn gens_color(palette: &UiPalette, faction: GensFaction) -> egui::Color32 {
+    match faction {
+        GensFaction::Duprian => palette.gens_duprian,
+        GensFaction::Vanert => palette.gens_vanert,
+    }
+}
+
+fn gens_marker(faction: GensFaction) -> &'static str {
+    match faction {
+        GensFaction::Duprian => "[Duprian]",
+        GensFaction::Vanert => "[Vanert]",
+    }
+}
+
+fn relation_marker(relation: Option<GuildRelation>) -> &'static str {
+    match relation {
+        Some(GuildRelation::Alliance) => " [Alianca]",
+        Some(GuildRelation::Hostility) => " [Hostil]",
+        None => "",
+    }
+}
+
+fn status_icon(effect: BuffEffect) -> (&'static str, egui::Color32) {
+    let letter = match effect {
+        BuffEffect::GreaterDamage => "A",
+        BuffEffect::GreaterDefense => "D",
+        BuffEffect::ElfBuffer => "N",
+        BuffEffect::SoulBarrier => "M",
+        BuffEffect::CriticalDamage => "C",
+        BuffEffect::InfinityArrow => "I",
+        BuffEffect::GreaterFortitude => "F",
+        BuffEffect::Poison => "V",
+        BuffEffect::Ice => "G",
+        BuffEffect::IceArrow => "P",
+        BuffEffect::DefenseReduction => "Q",
+        BuffEffect::Stun => "T",
+        BuffEffect::DamageReflect => "R",
+        BuffEffect::Sleep => "S",
+        BuffEffect::Blind => "X",
+        BuffEffect::Berserker => "B",
+    };
+    let color = match effect {
+        BuffEffect::Ice | BuffEffect::IceArrow => ICE_ICON_COLOR,
+        BuffEffect::Poison => POISON_ICON_COLOR,
+        _ => match effect.info().kind {
+            BuffKind::Buff => BUFF_ICON_COLOR,
+            BuffKind::Debuff => DEBUFF_ICON_COLOR,
+        },
+    };
+    (letter, color)
+}
+
+fn sync_nameplate_lod(settings: Res<SettingsResource>, mut lod: ResMut<NameplateLod>) {
+    if settings.is_changed() {
+        let wanted = NameplateLod::for_render_distance(settings.current.graphics.render_distance);
+        if *lod != wanted {
+            *lod = wanted;
+        }
+    }
+}
+
+fn draw_nameplates(
+    mut contexts: EguiContexts,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    nameplates: Query<(&Nameplate, &GlobalTransform, Option<&StatusEffectVisuals>)>,
+    accessibility: Res<UiAccessibility>,
+    lod: Res<NameplateLod>,
+    mut stats: ResMut<NameplateStats>,
+) {
+    let Ok((camera, camera_transform)) = cameras.single() else {
+        return;
+    };
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let painter = ctx.layer_painter(egui::LayerId::background());
+    let font = egui::FontId::proportional(14.0);
+    let guild_font = egui::FontId::proportional(12.0);
+    let icon_font = egui::FontId::proportional(9.0);
+    let camera_position = camera_transform.translation();
+    let mut frame = NameplateStats::default();
+
+    for (nameplate, transform, status) in &nameplates {
+        let distance = transform.translation().distance(camera_position);
+        let detail = lod.detail(distance, nameplate.pinned());
+        if detail == NameplateDetail::Culled {
+            frame.culled += 1;
+            continue;
+        }
+        let anchor = transform.translation() + Vec3::Y * NAMEPLATE_HEIGHT;
+        let Ok(screen) = camera.world_to_viewport(camera_transform, anchor) else {
+            frame.culled += 1;
+            continue;
+        };
+        let position = egui::pos2(screen.x, screen.y);
+
+        if let Some(faction) = nameplate.gens {
+            painter.text(
+                position - egui::vec2(0.0, 16.0),
+                egui::Align2::CENTER_BOTTOM,
+                gens_marker(faction),
+                guild_font.clone(),
+                gens_color(&accessibility.palette, faction),
+            );
+        }
+
+        painter.text(
+            position,
+            egui::Align2::CENTER_BOTTOM,
+            &nameplate.name,
+            font.clone(),
+            name_color(&accessibility.palette, nameplate.rank, nameplate.murder),
+        );
+
+        if let Some(guild) = &nameplate.guild {
+            painter.text(
+                position + egui::vec2(0.0, 2.0),
+                egui::Align2::CENTER_TOP,
+                format!("<{guild}>{}", relation_marker(nameplate.relation)),
+                guild_font.clone(),
+                relation_color(&accessibility.palette, nameplate.relation),
+            );
+        }
+
+        if detail == NameplateDetail::NameOnly {
+            frame.name_only += 1;
+            continue;
+        }
+        frame.full += 1;
+
+        let mut row_top = position.y + if nameplate.guild.is_some() { 18.0 } else { 4.0 };
+        if let Some(hp) = nameplate.hp {
+            let bar = egui::Rect::from_center_size(
+                egui::pos2(position.x, row_top + HP_BAR_SIZE.y / 2.0),
+                HP_BAR_SIZE,
+            );
+            let mut filled = bar;
+            filled.set_width(bar.width() * hp.fraction());
+            painter.rect_filled(bar, 1.0, egui::Color32::from_black_alpha(160));
+            painter.rect_filled(filled, 1.0, accessibility.palette.hp_bar(hp.fraction()));
+            row_top = bar.bottom() + 2.0;
+        }
+
+        let effects: Vec<BuffEffect> = status
+            .map(|status| status.effects().collect())
+            .unwrap_or_default();
+        let row_width = effects.len() as f32 * (STATUS_ICON_SIZE + 2.0) - 2.0;
+        for (index, effect) in effects.into_iter().enumerate() {
+            let (letter, color) = status_icon(effect);
+            let left = position.x - row_width / 2.0 + index as f32 * (STATUS_ICON_SIZE + 2.0);
+            let icon = egui::Rect::from_min_size(
+                egui::pos2(left, row_top),
+                egui::vec2(STATUS_ICON_SIZE, STATUS_ICON_SIZE),
+            );
+            painter.rect_filled(icon, 2.0, color);
+            painter.text(
+                icon.center(),
+                egui::Align2::CENTER_CENTER,
+                letter,
+                icon_font.clone(),
+                egui::Color32::BLACK,
+            );
+        }
+    }
+
+    if *stats != frame {
+        *stats = frame;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn far_plates_lose_detail_unless_pinned() {
+        let lod = NameplateLod::for_render_distance(RenderDistanceSetting::Medium);
+        assert_eq!(
+            lod.detail(lod.detail_distance, false),
+            NameplateDetail::Full
+        );
+        assert_eq!(
+            lod.detail(lod.detail_distance + 1.0, false),
+            NameplateDetail::NameOnly
+        );
+        assert_eq!(
+            lod.detail(lod.max_distance + 1.0, false),
+            NameplateDetail::Culled
+        );
+        assert_eq!(
+            lod.detail(lod.max_distance * 4.0, true),
+            NameplateDetail::Full
+        );
+        assert!(
+            NameplateLod::for_render_distance(RenderDistanceSetting::Low).max_distance
+                < NameplateLod::for_render_distance(RenderDistanceSetting::Ultra).max_distance
+        );
+    }
+
+    #[test]
+    fn status_icons_follow_the_effect_kind() {
+        assert_eq!(status_icon(BuffEffect::Ice), ("G", ICE_ICON_COLOR));
+        assert_eq!(status_icon(BuffEffect::IceArrow).1, ICE_ICON_COLOR);
+        assert_eq!(status_icon(BuffEffect::Poison), ("V", POISON_ICON_COLOR));
+        assert_eq!(status_icon(BuffEffect::SoulBarrier).1, BUFF_ICON_COLOR);
+        assert_eq!(status_icon(BuffEffect::Stun).1, DEBUFF_ICON_COLOR);
+    }
+
+    #[test]
+    fn hp_bar_keeps_the_highest_hp_seen() {
+        let mut plate = Nameplate::default();
+        plate.record_hit(20, 80);
+        assert_eq!(
+            plate.hp,
+            Some(NameplateHp {
+                current: 80,
+                max: 100
+            })
+        );
+        plate.record_hit(30, 50);
+        assert_eq!(plate.hp.map(NameplateHp::fraction), Some(0.5));
+        plate.record_hit(10, 0);
+        assert_eq!(plate.hp.map(NameplateHp::fraction), Some(0.0));
+    }
+}
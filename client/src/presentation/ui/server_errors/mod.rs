@@ -0,0 +1,81 @@
+//! User-facing text for `ServerMessage::Error`, keyed by the stable numeric
+//! code so older clients still show something sensible for codes added later.
+
+use protocol::{ServerErrorCategory, ServerErrorKind};
+
+const MESSAGES: &[(u16, &str)] = &[
+    (100, "Sua sessao expirou. Entre novamente."),
+    (101, "Conta ou senha incorretas."),
+    (102, "Este personagem nao pertence a sua conta."),
+    (103, "Personagem nao encontrado."),
+    (104, "Este personagem ja esta em jogo em outra sessao."),
+    (105, "Sua conta nao tem permissao para isso."),
+    (106, "Versao do cliente incompativel. Atualize o jogo."),
+    (200, "Acao invalida."),
+    (201, "Pedido invalido."),
+    (202, "Entre em um mapa antes de fazer isso."),
+    (203, "Nao encontrado."),
+    (204, "Voce esta silenciado no chat."),
+    (205, "Voce nao cumpre os requisitos deste portal."),
+    (300, "Servidor indisponivel no momento."),
+    (301, "Nenhuma instancia disponivel para este mapa."),
+    (302, "Este mapa esta fechado."),
+    (303, "Inventario cheio."),
+    (304, "Muitas tentativas. Aguarde um momento."),
+    (400, "O servidor esta em manutencao."),
+    (500, "Habilidade em recarga."),
+    (501, "Voce nao possui este item."),
+    (502, "Pedido rejeitado pelo servidor."),
+    (900, "Erro interno do servidor."),
+];
+
+/// Text for an error code; unknown codes fall back to their category.
+pub fn server_error_text(code: u16) -> &'static str {
+    if let Some((_, text)) = MESSAGES.iter().find(|(known, _)| *known == code) {
+        return text;
+    }
+    let category = match ServerErrorKind::from_code(code) {
+        Some(kind) => kind.category(),
+        None => category_from_range(code),
+    };
+    match category {
+        ServerErrorCategory::Auth => "Falha de autenticacao.",
+        ServerErrorCategory::Validation => "Pedido invalido.",
+        ServerErrorCategory::Capacity => "Servidor indisponivel no momento.",
+        ServerErrorCategory::Maintenance => "O servidor esta em manutencao.",
+        ServerErrorCategory::AntiCheat => "Pedido rejeitado pelo servidor.",
+        ServerErrorCategory::Internal => "Erro interno do servidor.",
+    }
+}
+
+fn category_from_range(code: u16) -> ServerErrorCategory {
+    match code / 100 {
+        1 => ServerErrorCategory::Auth,
+        2 => ServerErrorCategory::Validation,
+        3 => ServerErrorCategory::Capacity,
+        4 => ServerErrorCategory::Maintenance,
+        5 => ServerErrorCategory::AntiCheat,
+        _ => ServerErrorCategory::Internal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_kind_has_its_own_text() {
+        for kind in ServerErrorKind::ALL {
+            assert!(
+                MESSAGES.iter().any(|(code, _)| *code == kind.code()),
+                "{kind:?} has no text"
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_codes_fall_back_to_their_range() {
+        assert_eq!(server_error_text(199), "Falha de autenticacao.");
+        assert_eq!(server_error_text(7), "Erro interno do servidor.");
+    }
+}
@@ -0,0 +1,174 @@
+//! Interface sounds: buttons hovered and pressed, windows opening and
+//! closing, items received, level-ups and error beeps, all in the Interface
+//! category of the Som tab.
+//!
+//! Hover, press and window sounds come from the egui interaction state, so
+//! every window gets them without code of its own. Items received play on
+//! mail rewards and ground pickups, rejected item moves beep, and nothing
+//! raises [`UiSound::LevelUp`] until the server reports level-ups. Any system
+//! can play a cue with [`PlayUiSound`].
+
+use crate::infra::audio::PlaySound;
+use crate::infra::network::ServerMessageReceived;
+use crate::settings::SoundCategory;
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use protocol::{ServerErrorKind, ServerMessage};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UiSound {
+    ButtonHover,
+    ButtonPress,
+    WindowOpen,
+    WindowClose,
+    ItemPickup,
+    LevelUp,
+    Error,
+}
+
+impl UiSound {
+    /// File of the original client; it has one sound for windows opening
+    /// and closing.
+    pub fn path(self) -> &'static str {
+        match self {
+            Self::ButtonHover => "data/Sound/iButtonMove.wav",
+            Self::ButtonPress => "data/Sound/iButtonClick.wav",
+            Self::WindowOpen | Self::WindowClose => "data/Sound/iCreateWindow.wav",
+            Self::ItemPickup => "data/Sound/pGetItem.wav",
+            Self::LevelUp => "data/Sound/pLevelUp.wav",
+            Self::Error => "data/Sound/iButtonError.wav",
+        }
+    }
+}
+
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayUiSound(pub UiSound);
+
+/// What the interface looked like last pass, to tell what changed.
+#[derive(Default)]
+struct EguiSoundState {
+    hovered_button: Option<egui::Id>,
+    /// `None` until the first pass, so the windows already open on start
+    /// stay quiet.
+    windows: Option<HashSet<egui::LayerId>>,
+}
+
+pub struct UiSoundPresentationPlugin;
+
+impl Plugin for UiSoundPresentationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<PlayUiSound>()
+            .add_systems(Update, (server_message_sounds, play_ui_sounds).chain())
+            .add_systems(EguiPrimaryContextPass, egui_interaction_sounds);
+    }
+}
+
+fn play_ui_sounds(mut cues: MessageReader<PlayUiSound>, mut sounds: MessageWriter<PlaySound>) {
+    for PlayUiSound(cue) in cues.read() {
+        sounds.write(PlaySound {
+            path: cue.path(),
+            category: SoundCategory::Interface,
+        });
+    }
+}
+
+fn server_message_sounds(
+    mut incoming: MessageReader<ServerMessageReceived>,
+    mut cues: MessageWriter<PlayUiSound>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        if let Some(cue) = sound_for(message) {
+            cues.write(PlayUiSound(cue));
+        }
+    }
+}
+
+fn sound_for(message: &ServerMessage) -> Option<UiSound> {
+    match message {
+        ServerMessage::MailClaimed { .. } | ServerMessage::ItemPickedUp { .. } => {
+            Some(UiSound::ItemPickup)
+        }
+        ServerMessage::ItemRejected { .. } => Some(UiSound::Error),
+        // Cooldown rejections come with every early cast; beeping at each
+        // would drown the game out.
+        ServerMessage::Error(error) if error.kind != ServerErrorKind::SkillCooldown => {
+            Some(UiSound::Error)
+        }
+        _ => None,
+    }
+}
+
+fn egui_interaction_sounds(
+    mut contexts: EguiContexts,
+    mut state: Local<EguiSoundState>,
+    mut cues: MessageWriter<PlayUiSound>,
+) {
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let (clicked, hovered) =
+        ctx.interaction_snapshot(|snapshot| (snapshot.clicked, snapshot.hovered.clone()));
+    if clicked.is_some() {
+        cues.write(PlayUiSound(UiSound::ButtonPress));
+    }
+
+    // Window bodies and title bars sense drags; buttons, checkboxes and
+    // combo boxes only sense clicks.
+    let hovered_button = hovered.into_iter().find(|id| {
+        ctx.read_response(*id)
+            .is_some_and(|response| response.sense.senses_click() && !response.sense.senses_drag())
+    });
+    if hovered_button.is_some() && hovered_button != state.hovered_button && clicked.is_none() {
+        cues.write(PlayUiSound(UiSound::ButtonHover));
+    }
+    state.hovered_button = hovered_button;
+
+    let windows: HashSet<egui::LayerId> = ctx.memory(|memory| {
+        memory
+            .areas()
+            .visible_layer_ids()
+            .into_iter()
+            .filter(|layer| layer.order == egui::Order::Middle)
+            .collect()
+    });
+    if let Some(previous) = &state.windows {
+        // Screens swapping several windows at once, such as entering the
+        // world, are not a window the player opened.
+        if windows.difference(previous).count() == 1 {
+            cues.write(PlayUiSound(UiSound::WindowOpen));
+        } else if previous.difference(&windows).count() == 1 {
+            cues.write(PlayUiSound(UiSound::WindowClose));
+        }
+    }
+    state.windows = Some(windows);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::ServerError;
+
+    #[test]
+    fn server_messages_map_to_interface_cues() {
+        assert_eq!(
+            sound_for(&ServerMessage::MailClaimed { mail_id: 1 }),
+            Some(UiSound::ItemPickup)
+        );
+        let error = |kind| ServerMessage::Error(ServerError::new(kind, ""));
+        assert_eq!(
+            sound_for(&error(ServerErrorKind::InventoryFull)),
+            Some(UiSound::Error)
+        );
+        assert_eq!(sound_for(&error(ServerErrorKind::SkillCooldown)), None);
+        assert_eq!(
+            sound_for(&ServerMessage::Pong {
+                client_sent_ms: 0,
+                server_received_ms: 0,
+                server_sent_ms: 0,
+            }),
+            None
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use crate::presentation::ui::accessibility::UiPalette;
+use bevy_egui::egui;
+use common::ItemCode;
+use protocol::{ItemInstance, ItemOptions};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Excellent options for weapons, pendants and staffs (groups 0..=5 and 13).
+const WEAPON_EXCELLENT_OPTIONS: [&str; 6] = [
+    "Mana ao matar +Mana/8",
+    "Vida ao matar +Vida/8",
+    "Velocidade de ataque +7",
+    "Dano +2%",
+    "Dano +Nivel/20",
+    "Chance de dano excelente +10%",
+];
+
+/// Excellent options for shields, armor and rings (groups 6..=11).
+const ARMOR_EXCELLENT_OPTIONS: [&str; 6] = [
+    "Zen ao matar +40%",
+    "Taxa de defesa +10%",
+    "Reflete dano +5%",
+    "Reduz dano -4%",
+    "Mana maxima +4%",
+    "Vida maxima +4%",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemOptionLineKind {
+    Excellent,
+    Option,
+    Socket,
+}
+
+impl ItemOptionLineKind {
+    fn color(self, palette: &UiPalette) -> egui::Color32 {
+        match self {
+            Self::Excellent => palette.rarity_excellent,
+            Self::Option => palette.rarity_option,
+            Self::Socket => palette.rarity_socket,
+        }
+    }
+}
+
+/// Human readable option lines, in the order they are shown in the tooltip.
+pub fn item_option_lines(group: u8, options: &ItemOptions) -> Vec<(ItemOptionLineKind, String)> {
+    let mut lines = Vec::new();
+
+    if options.luck {
+        lines.push((
+            ItemOptionLineKind::Option,
+            "Sorte (chance de sucesso +25%, critico +5%)".to_string(),
+        ));
+    }
+    if options.additional > 0 {
+        let label = if (6..=11).contains(&group) {
+            "Defesa adicional"
+        } else {
+            "Dano adicional"
+        };
+        lines.push((
+            ItemOptionLineKind::Option,
+            format!("{label} +{}", u32::from(options.additional) * 4),
+        ));
+    }
+
+    let excellent_names = if (6..=11).contains(&group) {
+        &ARMOR_EXCELLENT_OPTIONS
+    } else {
+        &WEAPON_EXCELLENT_OPTIONS
+    };
+    for (bit, name) in excellent_names.iter().enumerate() {
+        if options.excellent & (1 << bit) != 0 {
+            lines.push((ItemOptionLineKind::Excellent, (*name).to_string()));
+        }
+    }
+
+    for (slot, payload) in options.sockets.iter().enumerate() {
+        let bytes = payload
+            .iter()
+            .map(|byte| format!("{byte:02X}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        lines.push((
+            ItemOptionLineKind::Socket,
+            format!("Socket {}: {bytes}", slot + 1),
+        ));
+    }
+
+    lines
+}
+
+/// Time left on a time-limited item, counted down against the local clock.
+pub fn expiry_line(expires_at_ms: u64, now_ms: u64) -> String {
+    let Some(remaining_ms) = expires_at_ms.checked_sub(now_ms).filter(|ms| *ms > 0) else {
+        return "Expirado".to_string();
+    };
+    let secs = remaining_ms.div_ceil(1000);
+    let (days, hours, minutes) = (secs / 86_400, secs / 3_600 % 24, secs / 60 % 60);
+    let left = if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes:02}m")
+    } else {
+        format!("{minutes}m {:02}s", secs % 60)
+    };
+    format!("Expira em {left}")
+}
+
+/// Renders the item name line followed by its option slots and, for
+/// time-limited items, the time left.
+pub fn item_tooltip(ui: &mut egui::Ui, item: &ItemInstance, palette: &UiPalette) {
+    let title = if item.options.excellent != 0 {
+        egui::RichText::new(item_title(item)).color(palette.rarity_excellent)
+    } else {
+        egui::RichText::new(item_title(item))
+    };
+    ui.strong(title);
+
+    for (kind, line) in item_option_lines(item.group, &item.options) {
+        ui.colored_label(kind.color(palette), line);
+    }
+
+    if let Some(expires_at_ms) = item.expires_at_ms {
+        ui.colored_label(palette.error, expiry_line(expires_at_ms, now_ms()));
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+pub fn item_title(item: &ItemInstance) -> String {
+    let code = ItemCode::new(item.group, item.index);
+    let mut title = match code.definition() {
+        Some(definition) => definition.name.to_string(),
+        None => format!("Item {code}"),
+    };
+    if item.level > 0 {
+        title.push_str(&format!(" +{}", item.level));
+    }
+    if item.quantity > 1 {
+        title.push_str(&format!(" x{}", item.quantity));
+    }
+    title
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn option_lines_follow_item_kind() {
+        let options = ItemOptions {
+            excellent: 0b0000_1001,
+            luck: true,
+            additional: 2,
+            sockets: vec![vec![0x1A, 0x02]],
+        };
+
+        let weapon = item_option_lines(0, &options);
+        assert_eq!(weapon[0].0, ItemOptionLineKind::Option);
+        assert_eq!(weapon[1].1, "Dano adicional +8");
+        assert_eq!(weapon[2].1, "Mana ao matar +Mana/8");
+        assert_eq!(weapon[3].1, "Dano +2%");
+        assert_eq!(weapon[4].1, "Socket 1: 1A 02");
+
+        let armor = item_option_lines(8, &options);
+        assert_eq!(armor[1].1, "Defesa adicional +8");
+        assert_eq!(armor[2].1, "Zen ao matar +40%");
+    }
+
+    #[test]
+    fn expiry_line_counts_down() {
+        assert_eq!(expiry_line(10_000, 10_000), "Expirado");
+        assert_eq!(expiry_line(10_000, 20_000), "Expirado");
+        assert_eq!(expiry_line(95_500, 0), "Expira em 1m 36s");
+        assert_eq!(
+            expiry_line(2 * 3_600_000 + 5 * 60_000, 0),
+            "Expira em 2h 05m"
+        );
+        assert_eq!(
+            expiry_line(3 * 86_400_000 + 3_600_000, 0),
+            "Expira em 3d 1h"
+        );
+    }
+}
@@ -1 +1,3 @@
 //! Shared UI widget surface.
+
+pub mod item_tooltip;
@@ -0,0 +1,172 @@
+//! Object collision footprints of the loaded world.
+
+use crate::scene_runtime::scene_loader::{SceneObjectDef, SceneRotationEncoding};
+use bevy::prelude::*;
+use common::collision::{CollisionFootprint, CollisionGrid};
+
+/// Tiles blocked by scene objects, in unmirrored map space.
+#[derive(Resource, Debug, Default)]
+pub struct WorldCollision {
+    grid: CollisionGrid,
+    /// Footprints of objects that always block.
+    footprints: Vec<CollisionFootprint>,
+    /// Footprints of server doors, which block only while closed.
+    doors: Vec<DoorFootprint>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct DoorFootprint {
+    door_id: u16,
+    footprint: CollisionFootprint,
+    blocks: bool,
+}
+
+impl WorldCollision {
+    /// Doors start closed until the server says otherwise.
+    pub fn from_scene_objects(
+        objects: &[SceneObjectDef],
+        rotation_encoding: SceneRotationEncoding,
+    ) -> Self {
+        let mut footprints = Vec::new();
+        let mut doors = Vec::new();
+        for object in objects {
+            let Some(footprint) = object_footprint(object, rotation_encoding) else {
+                continue;
+            };
+            match object.properties.door_id {
+                Some(door_id) => doors.push(DoorFootprint {
+                    door_id,
+                    footprint,
+                    blocks: true,
+                }),
+                None => footprints.push(footprint),
+            }
+        }
+        let mut collision = Self {
+            grid: CollisionGrid::default(),
+            footprints,
+            doors,
+        };
+        collision.rebuild();
+        collision
+    }
+
+    pub fn grid(&self) -> &CollisionGrid {
+        &self.grid
+    }
+
+    /// Makes a door block or clear its tiles. Returns whether anything
+    /// changed.
+    pub fn set_door_blocks(&mut self, door_id: u16, blocks: bool) -> bool {
+        let mut changed = false;
+        for door in self.doors.iter_mut().filter(|door| door.door_id == door_id) {
+            changed |= door.blocks != blocks;
+            door.blocks = blocks;
+        }
+        if changed {
+            self.rebuild();
+        }
+        changed
+    }
+
+    fn rebuild(&mut self) {
+        let closed_doors = self
+            .doors
+            .iter()
+            .filter(|door| door.blocks)
+            .map(|door| &door.footprint);
+        self.grid = CollisionGrid::from_footprints(self.footprints.iter().chain(closed_doors));
+    }
+}
+
+impl From<CollisionGrid> for WorldCollision {
+    fn from(grid: CollisionGrid) -> Self {
+        Self {
+            grid,
+            footprints: Vec::new(),
+            doors: Vec::new(),
+        }
+    }
+}
+
+fn object_footprint(
+    object: &SceneObjectDef,
+    rotation_encoding: SceneRotationEncoding,
+) -> Option<CollisionFootprint> {
+    let shape = object.properties.collision?;
+    // Scene positions are Y-up: MU x/y live in position x/z.
+    let yaw_degrees = match rotation_encoding {
+        SceneRotationEncoding::MuAnglesDegrees => object.rotation[2],
+        SceneRotationEncoding::LegacySwizzledDegrees => object.rotation[1],
+    };
+    Some(CollisionFootprint {
+        position: [object.position[0], object.position[2]],
+        yaw_degrees,
+        scale: object.scale[0],
+        shape,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene_runtime::scene_loader::ObjectProperties;
+    use common::collision::CollisionShape;
+
+    #[test]
+    fn only_objects_with_shapes_block_tiles() {
+        let fountain = SceneObjectDef {
+            id: "obj_00001".to_string(),
+            object_type: 105,
+            model: "data/object_1/waterspout_01.glb".to_string(),
+            position: [13_050.0, 170.0, 13_050.0],
+            rotation: [0.0, 0.0, 0.0],
+            scale: [1.0, 1.0, 1.0],
+            properties: ObjectProperties {
+                collision: Some(CollisionShape::Circle { radius: 220.0 }),
+                ..default()
+            },
+        };
+        let mut grass = fountain.clone();
+        grass.object_type = 20;
+        grass.position = [5_050.0, 170.0, 5_050.0];
+        grass.properties.collision = None;
+
+        let collision = WorldCollision::from_scene_objects(
+            &[fountain, grass],
+            SceneRotationEncoding::MuAnglesDegrees,
+        );
+
+        assert!(collision.grid().is_blocked(130, 130));
+        assert!(!collision.grid().is_blocked(50, 50));
+        assert_eq!(collision.grid().blocked_tiles(), 13);
+    }
+
+    #[test]
+    fn door_footprints_block_only_while_closed() {
+        let gate = SceneObjectDef {
+            id: "obj_00002".to_string(),
+            object_type: 87,
+            model: "data/object_3/gate_01.glb".to_string(),
+            position: [19_750.0, 170.0, 10_550.0],
+            rotation: [0.0, 0.0, 0.0],
+            scale: [1.0, 1.0, 1.0],
+            properties: ObjectProperties {
+                collision: Some(CollisionShape::Circle { radius: 120.0 }),
+                door_id: Some(1),
+                ..default()
+            },
+        };
+        let mut collision =
+            WorldCollision::from_scene_objects(&[gate], SceneRotationEncoding::MuAnglesDegrees);
+        assert!(collision.grid().is_blocked(197, 105));
+
+        assert!(collision.set_door_blocks(1, false));
+        assert!(!collision.grid().is_blocked(197, 105));
+        assert!(!collision.set_door_blocks(1, false));
+        assert!(!collision.set_door_blocks(2, true));
+
+        assert!(collision.set_door_blocks(1, true));
+        assert!(collision.grid().is_blocked(197, 105));
+    }
+}
@@ -1,7 +1,7 @@
 pub use crate::scene_runtime::scene_loader::{
     CameraTourData, HeightmapData, MapVfxBlendMode, MapVfxProfile, ObjectProperties,
-    SceneObjectDef, SceneObjectsData, TerrainConfig, TerrainMapData, TerrainMapSample,
-    TerrainTextureSlotsData,
+    PropInteraction, SceneObjectDef, SceneObjectsData, TerrainConfig, TerrainMapData,
+    TerrainMapSample, TerrainTextureSlotsData,
 };
 use bevy::gltf::Gltf;
 use bevy::prelude::*;
@@ -36,6 +36,25 @@ pub struct SceneObject;
 #[derive(Component, Clone, Copy, Debug, Eq, PartialEq)]
 pub struct SceneObjectKind(pub u32);
 
+/// Id of the placement entry an entity was spawned from, so a reloaded
+/// placement file can find the entities of the objects it changed.
+#[derive(Component, Clone, Debug, Eq, PartialEq)]
+pub struct SceneObjectId(pub String);
+
+/// Scene object the player can click to sit, drink or open it.
+#[derive(Component, Clone, Copy, Debug, Eq, PartialEq)]
+pub struct InteractiveProp(pub PropInteraction);
+
+/// Scene object outlined, with this label above it, while the cursor hovers it.
+#[derive(Component, Clone, Debug, Eq, PartialEq)]
+pub struct InteractionPrompt(pub String);
+
+/// Scene object showing a door or gate whose state the server owns.
+#[derive(Component, Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ReplicatedDoor {
+    pub door_id: u16,
+}
+
 /// Animation metadata for scene objects spawned from GLB scenes.
 #[derive(Component, Clone)]
 pub struct SceneObjectAnimationSource {
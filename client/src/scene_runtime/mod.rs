@@ -1,6 +1,10 @@
+pub mod collision;
 pub mod components;
+pub mod object_diff;
 pub mod scene_loader;
 pub mod state;
 pub mod systems;
+pub mod terrain_height;
 pub mod transforms;
+pub mod vfx_rng;
 pub mod world_coordinates;
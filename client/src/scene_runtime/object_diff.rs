@@ -0,0 +1,108 @@
+//! Changes between two versions of a world's object placement file.
+
+use crate::scene_runtime::scene_loader::SceneObjectDef;
+use std::collections::HashMap;
+
+/// What a reloaded placement file changed, matched by object id.
+#[derive(Debug, Default)]
+pub struct SceneObjectDiff {
+    /// Objects new in the file.
+    pub added: Vec<SceneObjectDef>,
+    /// Ids of objects no longer in the file.
+    pub removed: Vec<String>,
+    /// Objects whose position, rotation or scale changed and nothing else.
+    pub moved: Vec<SceneObjectDef>,
+    /// Objects with a new type, model or properties, which are spawned again.
+    pub replaced: Vec<SceneObjectDef>,
+}
+
+impl SceneObjectDiff {
+    pub fn between(previous: &[SceneObjectDef], next: &[SceneObjectDef]) -> Self {
+        let previous_by_id: HashMap<&str, &SceneObjectDef> = previous
+            .iter()
+            .map(|object| (object.id.as_str(), object))
+            .collect();
+        let next_by_id: HashMap<&str, &SceneObjectDef> = next
+            .iter()
+            .map(|object| (object.id.as_str(), object))
+            .collect();
+
+        let mut diff = Self {
+            removed: previous
+                .iter()
+                .filter(|object| !next_by_id.contains_key(object.id.as_str()))
+                .map(|object| object.id.clone())
+                .collect(),
+            ..Self::default()
+        };
+        for object in next {
+            match previous_by_id.get(object.id.as_str()) {
+                None => diff.added.push(object.clone()),
+                Some(old) if *old == object => {}
+                Some(old)
+                    if old.object_type == object.object_type
+                        && old.model == object.model
+                        && old.properties == object.properties =>
+                {
+                    diff.moved.push(object.clone())
+                }
+                Some(_) => diff.replaced.push(object.clone()),
+            }
+        }
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.moved.is_empty()
+            && self.replaced.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene_runtime::scene_loader::ObjectProperties;
+
+    fn object(id: &str, position: [f32; 3]) -> SceneObjectDef {
+        SceneObjectDef {
+            id: id.to_string(),
+            object_type: 1,
+            model: "data/object_1/tree_01.glb".to_string(),
+            position,
+            rotation: [0.0, 0.0, 0.0],
+            scale: [1.0, 1.0, 1.0],
+            properties: ObjectProperties::default(),
+        }
+    }
+
+    #[test]
+    fn only_changed_objects_are_reported() {
+        let previous = vec![
+            object("obj_00001", [100.0, 0.0, 100.0]),
+            object("obj_00002", [200.0, 0.0, 200.0]),
+            object("obj_00003", [300.0, 0.0, 300.0]),
+            object("obj_00004", [400.0, 0.0, 400.0]),
+        ];
+        let mut next = previous.clone();
+        next[1].position = [250.0, 0.0, 200.0];
+        next[2].properties.light_color = Some([1.0, 0.5, 0.0]);
+        next.remove(3);
+        next.push(object("obj_00005", [500.0, 0.0, 500.0]));
+
+        let diff = SceneObjectDiff::between(&previous, &next);
+
+        let ids = |objects: &[SceneObjectDef]| {
+            objects
+                .iter()
+                .map(|object| object.id.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&diff.moved), ["obj_00002"]);
+        assert_eq!(ids(&diff.replaced), ["obj_00003"]);
+        assert_eq!(diff.removed, ["obj_00004"]);
+        assert_eq!(ids(&diff.added), ["obj_00005"]);
+        assert!(SceneObjectDiff::between(&next, &next).is_empty());
+    }
+}
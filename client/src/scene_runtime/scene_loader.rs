@@ -2,6 +2,8 @@ use crate::infra::assets::{asset_path_exists, resolve_asset_path};
 use bevy::asset::io::Reader;
 use bevy::asset::{AssetLoader, LoadContext};
 use bevy::prelude::*;
+use common::collision::CollisionShape;
+use common::worldscale::{HEIGHT_MULTIPLIER, TILE_WORLD_SIZE};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use thiserror::Error;
@@ -13,6 +15,7 @@ const TERRAIN_TEXTURE_SLOTS_FILE: &str = "terrain_texture_slots.json";
 const SCENE_OBJECTS_FILE: &str = "scene_objects.json";
 const CAMERA_TOUR_FILE: &str = "camera_tour.json";
 const MAP_VFX_FILE: &str = "map_vfx.json";
+const AMBIENT_SOUNDS_FILE: &str = "ambient_sounds.json";
 const SCENE_OBJECTS_FILE_OVERRIDE_ENV: &str = "MU_SCENE_OBJECTS_FILE";
 const DISABLE_MAP_VFX_ENV: &str = "MU_DISABLE_MAP_VFX";
 
@@ -31,11 +34,11 @@ pub struct TerrainConfig {
 }
 
 fn default_height_multiplier() -> f32 {
-    1.5
+    HEIGHT_MULTIPLIER
 }
 
 fn default_legacy_terrain_scale() -> f32 {
-    100.0
+    TILE_WORLD_SIZE
 }
 
 fn default_terrain_ambient_light() -> f32 {
@@ -173,7 +176,7 @@ pub enum SceneRotationEncoding {
     MuAnglesDegrees,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct SceneObjectDef {
     pub id: String,
     #[serde(rename = "type")]
@@ -186,7 +189,7 @@ pub struct SceneObjectDef {
     pub properties: ObjectProperties,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
 pub struct ObjectProperties {
     pub model_renderable: Option<bool>,
     pub model_validation_reason: Option<String>,
@@ -201,6 +204,53 @@ pub struct ObjectProperties {
     pub cast_shadow: Option<bool>,
     pub particle_count: Option<u32>,
     pub particle_scale_multiplier: Option<f32>,
+    /// Ground footprint that blocks movement (fountains, buildings, walls).
+    pub collision: Option<CollisionShape>,
+    /// What a click on the object does (chairs, bar counters, doors).
+    pub interaction: Option<PropInteraction>,
+    /// Server door whose state the object shows (gates, event doors). Its
+    /// collision then only blocks while the door is closed.
+    pub door_id: Option<u16>,
+    /// Label shown while the cursor hovers the object ("Entrar na masmorra").
+    /// Objects with an `interaction` fall back to its own label; any other
+    /// object with a prompt (portals, warp gates) is highlighted too.
+    pub prompt: Option<String>,
+}
+
+impl ObjectProperties {
+    /// Label of the hover prompt, `None` for objects that are not highlighted.
+    pub fn interaction_prompt(&self) -> Option<String> {
+        self.prompt.clone().or_else(|| {
+            self.interaction
+                .map(|interaction| interaction.label().to_string())
+        })
+    }
+}
+
+/// Town prop or NPC a player can use by clicking it.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PropInteraction {
+    /// Chair or bench: the character sits on it, facing the seat's way.
+    Seat,
+    /// Bar counter or barrel: the character has a drink.
+    Drink,
+    /// Door that swings open and closes again by itself.
+    Door,
+    /// NPC: the character walks up and talks to it.
+    Talk { npc_id: u16 },
+}
+
+impl PropInteraction {
+    /// Default hover prompt.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Seat => "Sentar",
+            Self::Drink => "Beber",
+            Self::Door => "Abrir",
+            Self::Talk { .. } => "Conversar",
+        }
+    }
 }
 
 #[derive(Asset, TypePath, Serialize, Deserialize, Clone)]
@@ -229,6 +279,36 @@ pub struct MapVfxProfile {
     pub object_sprites: Vec<MapVfxObjectSprite>,
 }
 
+/// Looping sounds placed in a world: waterfalls, wind, tavern chatter,
+/// torches. Optional; worlds without the file are silent.
+#[derive(Asset, TypePath, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AmbientSoundsData {
+    #[serde(default)]
+    pub emitters: Vec<AmbientEmitterDef>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AmbientEmitterDef {
+    /// Sound file under the asset root, e.g. `data/Sound/aWater.wav`.
+    pub sound: String,
+    /// Same map coordinates as scene objects.
+    pub position: [f32; 3],
+    /// Distance at which the sound fades out completely.
+    #[serde(default = "default_ambient_radius")]
+    pub radius: f32,
+    /// Loudness next to the emitter, before the ambient volume setting.
+    #[serde(default = "default_ambient_volume")]
+    pub volume: f32,
+}
+
+fn default_ambient_radius() -> f32 {
+    1_500.0
+}
+
+fn default_ambient_volume() -> f32 {
+    1.0
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct MapVfxObjectOverride {
     pub object_type: u32,
@@ -546,6 +626,39 @@ impl AssetLoader for MapVfxLoader {
     }
 }
 
+#[derive(Default, TypePath)]
+pub struct AmbientSoundsLoader;
+
+#[derive(Debug, Error)]
+pub enum AmbientSoundsLoaderError {
+    #[error("Could not load ambient sounds: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse JSON: {0}")]
+    JsonError(#[from] serde_json::Error),
+}
+
+impl AssetLoader for AmbientSoundsLoader {
+    type Asset = AmbientSoundsData;
+    type Settings = ();
+    type Error = AmbientSoundsLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let sounds = serde_json::from_slice::<AmbientSoundsData>(&bytes)?;
+        Ok(sounds)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[AMBIENT_SOUNDS_FILE]
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct LoadedSceneWorld {
     pub world_name: String,
@@ -557,6 +670,8 @@ pub struct LoadedSceneWorld {
     pub scene_objects: Handle<SceneObjectsData>,
     pub camera_tour: Handle<CameraTourData>,
     pub map_vfx: Option<Handle<MapVfxProfile>>,
+    /// Not waited for: the world is ready without its sounds.
+    pub ambient_sounds: Option<Handle<AmbientSoundsData>>,
 }
 
 enum SceneLoadState {
@@ -671,6 +786,8 @@ impl SceneLoader {
             ))),
             map_vfx: map_vfx_asset_path(world_name)
                 .map(|path| asset_server.load(resolve_asset_path(&path))),
+            ambient_sounds: optional_world_asset_path_if_exists(world_name, AMBIENT_SOUNDS_FILE)
+                .map(|path| asset_server.load(resolve_asset_path(&path))),
         };
 
         self.worlds.insert(
@@ -764,12 +881,14 @@ impl Plugin for SceneLoaderPlugin {
             .init_asset::<SceneObjectsData>()
             .init_asset::<CameraTourData>()
             .init_asset::<MapVfxProfile>()
+            .init_asset::<AmbientSoundsData>()
             .init_asset_loader::<TerrainConfigLoader>()
             .init_asset_loader::<HeightmapLoader>()
             .init_asset_loader::<TerrainMapLoader>()
             .init_asset_loader::<TerrainTextureSlotsLoader>()
             .init_asset_loader::<SceneObjectsLoader>()
             .init_asset_loader::<CameraTourLoader>()
-            .init_asset_loader::<MapVfxLoader>();
+            .init_asset_loader::<MapVfxLoader>()
+            .init_asset_loader::<AmbientSoundsLoader>();
     }
 }
@@ -0,0 +1,144 @@
+//! Looping ambient sounds placed in the world from `ambient_sounds.json`.
+//!
+//! Each emitter plays from the moment the world spawns, silent until the
+//! camera comes within its radius, then louder the closer it gets. Emitters
+//! past the scene object culling distance are paused with the objects around
+//! them. Volume follows the Ambient category of the Som tab.
+
+use crate::infra::assets::{asset_path_exists, resolve_asset_path};
+use crate::scene_runtime::components::*;
+use crate::scene_runtime::scene_loader::AmbientSoundsData;
+use crate::scene_runtime::state::RuntimeSceneAssets;
+use crate::scene_runtime::world_coordinates::{mirror_map_position_with_axis, world_mirror_axis};
+use crate::settings::{AudioCategoryState, SoundCategory};
+use bevy::audio::{AudioSink, AudioSinkPlayback, Volume};
+use bevy::prelude::*;
+
+use super::SceneObjectDistanceCullingConfig;
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct AmbientSoundEmitter {
+    pub radius: f32,
+    pub volume: f32,
+}
+
+/// Marker: ambient emitters of the current world have been spawned.
+#[derive(Component)]
+pub struct AmbientSoundsSpawned;
+
+pub fn spawn_ambient_sounds_when_ready(
+    mut commands: Commands,
+    assets: Res<RuntimeSceneAssets>,
+    terrain_configs: Res<Assets<TerrainConfig>>,
+    ambient_sounds: Res<Assets<AmbientSoundsData>>,
+    asset_server: Res<AssetServer>,
+    spawned_query: Query<(), With<AmbientSoundsSpawned>>,
+) {
+    if !spawned_query.is_empty() || !assets.loaded {
+        return;
+    }
+    let Some(world) = assets.world.as_ref() else {
+        return;
+    };
+    let Some(handle) = world.ambient_sounds.as_ref() else {
+        return;
+    };
+    let Some(data) = ambient_sounds.get(handle) else {
+        return;
+    };
+    let Some(terrain_config) = terrain_configs.get(&world.terrain_config) else {
+        return;
+    };
+
+    let mirror_axis = world_mirror_axis();
+    let map_max_x =
+        (terrain_config.size.width.saturating_sub(1) as f32) * terrain_config.size.scale;
+    let map_max_z =
+        (terrain_config.size.depth.saturating_sub(1) as f32) * terrain_config.size.scale;
+
+    let mut spawned = 0usize;
+    for emitter in &data.emitters {
+        if !asset_path_exists(&emitter.sound) {
+            warn!("Ambient sound {} not found; emitter skipped", emitter.sound);
+            continue;
+        }
+        let position = mirror_map_position_with_axis(
+            Vec3::from(emitter.position),
+            map_max_x,
+            map_max_z,
+            mirror_axis,
+        );
+        commands.spawn((
+            RuntimeSceneEntity,
+            AmbientSoundEmitter {
+                radius: emitter.radius,
+                volume: emitter.volume,
+            },
+            Transform::from_translation(position),
+            AudioPlayer::<AudioSource>(asset_server.load(resolve_asset_path(&emitter.sound))),
+            PlaybackSettings::LOOP.with_volume(Volume::Linear(0.0)),
+        ));
+        spawned += 1;
+    }
+
+    info!("Spawned {} ambient sound emitters", spawned);
+    commands.spawn((AmbientSoundsSpawned, RuntimeSceneEntity));
+}
+
+pub fn update_ambient_sound_volumes(
+    categories: Res<AudioCategoryState>,
+    culling: Res<SceneObjectDistanceCullingConfig>,
+    camera_query: Query<&Transform, With<Camera3d>>,
+    mut emitters: Query<(&AmbientSoundEmitter, &Transform, &mut AudioSink)>,
+) {
+    let Ok(camera_transform) = camera_query.single() else {
+        return;
+    };
+    let category_volume = categories.volume(SoundCategory::Ambient);
+    let cull_distance = culling.enabled.then_some(culling.max_distance);
+
+    for (emitter, transform, mut sink) in &mut emitters {
+        let distance = transform.translation.distance(camera_transform.translation);
+        let volume = ambient_emitter_volume(emitter, distance, cull_distance) * category_volume;
+        if volume <= 0.0 {
+            if !sink.is_paused() {
+                sink.pause();
+            }
+            continue;
+        }
+        sink.set_volume(Volume::Linear(volume));
+        if sink.is_paused() {
+            sink.play();
+        }
+    }
+}
+
+/// Linear falloff from `emitter.volume` at the emitter to silence at its
+/// radius; silent past `cull_distance`.
+fn ambient_emitter_volume(
+    emitter: &AmbientSoundEmitter,
+    distance: f32,
+    cull_distance: Option<f32>,
+) -> f32 {
+    if cull_distance.is_some_and(|cull| distance > cull) || emitter.radius <= 0.0 {
+        return 0.0;
+    }
+    emitter.volume * (1.0 - distance / emitter.radius).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emitters_fade_with_distance_and_culling() {
+        let emitter = AmbientSoundEmitter {
+            radius: 1_000.0,
+            volume: 0.8,
+        };
+        assert_eq!(ambient_emitter_volume(&emitter, 0.0, None), 0.8);
+        assert!((ambient_emitter_volume(&emitter, 500.0, None) - 0.4).abs() < 1e-6);
+        assert_eq!(ambient_emitter_volume(&emitter, 1_200.0, None), 0.0);
+        assert_eq!(ambient_emitter_volume(&emitter, 500.0, Some(400.0)), 0.0);
+    }
+}
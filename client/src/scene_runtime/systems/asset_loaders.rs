@@ -1,6 +1,8 @@
 use crate::scene_runtime::components::*;
 use crate::scene_runtime::scene_loader::SceneLoader;
 use crate::scene_runtime::state::RuntimeSceneAssets;
+use crate::scene_runtime::terrain_height::TerrainHeightField;
+use crate::scene_runtime::world_coordinates::world_mirror_axis;
 use bevy::asset::io::Reader;
 use bevy::asset::{AssetLoader, LoadContext};
 use bevy::prelude::*;
@@ -12,8 +14,10 @@ use thiserror::Error;
 
 /// System to check if all runtime scene assets are loaded.
 /// It requests a world by name through the shared scene loader and only marks
-/// the scene as ready when world data + scene-specific assets are available.
+/// the scene as ready when world data + scene-specific assets are available,
+/// inserting the world's `TerrainHeightField` at that point.
 pub fn load_scene_runtime_assets(
+    mut commands: Commands,
     mut assets: ResMut<RuntimeSceneAssets>,
     mut scene_loader: ResMut<SceneLoader>,
     asset_server: Res<AssetServer>,
@@ -50,6 +54,27 @@ pub fn load_scene_runtime_assets(
         "All runtime scene assets loaded successfully for {}",
         world.world_name
     );
+    let height_field = terrain_configs
+        .get(&world.terrain_config)
+        .zip(heightmaps.get(&world.heightmap))
+        .and_then(|(config, heightmap)| {
+            TerrainHeightField::from_heightmap(
+                &world.world_name,
+                heightmap,
+                config,
+                world_mirror_axis(),
+            )
+        });
+    match height_field {
+        Some(height_field) => commands.insert_resource(height_field),
+        None => {
+            warn!(
+                "Terrain of {} is too small to sample heights",
+                world.world_name
+            );
+            commands.remove_resource::<TerrainHeightField>();
+        }
+    }
     assets.world = Some(world);
     assets.loaded = true;
 }
@@ -1,11 +1,15 @@
 use crate::bevy_compat::*;
 use crate::scene_runtime::components::*;
 use crate::scene_runtime::state::RuntimeSceneAssets;
+use crate::scene_runtime::terrain_height::TerrainHeightField;
 use crate::scene_runtime::world_coordinates::{mirror_map_position_with_axis, world_mirror_axis};
 use bevy::camera::Projection;
 use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
 use bevy::prelude::*;
 
+/// Height the debug free camera keeps above the terrain.
+const FREE_CAMERA_GROUND_CLEARANCE: f32 = 50.0;
+
 /// Marker for camera tour setup
 #[derive(Component)]
 pub struct CameraTourSetup;
@@ -39,6 +43,16 @@ impl Default for DebugFreeCameraController {
     }
 }
 
+impl DebugFreeCameraController {
+    /// Switches to the free camera, looking where `transform` looks.
+    pub fn take_over(&mut self, transform: &Transform) {
+        let forward = transform.forward();
+        self.enabled = true;
+        self.yaw = forward.x.atan2(forward.z);
+        self.pitch = forward.y.clamp(-0.999, 0.999).asin();
+    }
+}
+
 fn login_camera_fov_for_world(world_name: &str) -> Option<f32> {
     match world_name {
         // Legacy login scene: C++ WD_55LOGINSCENE (assets from World56).
@@ -106,11 +120,10 @@ pub fn toggle_debug_free_camera(
         return;
     };
 
-    controller.enabled = !controller.enabled;
     if controller.enabled {
-        let forward = transform.forward();
-        controller.yaw = forward.x.atan2(forward.z);
-        controller.pitch = forward.y.clamp(-0.999, 0.999).asin();
+        controller.enabled = false;
+    } else {
+        controller.take_over(transform);
     }
 
     if let Some(mut tour) = maybe_tour {
@@ -139,6 +152,7 @@ pub fn control_debug_free_camera(
     mut mouse_motion: MessageReader<MouseMotion>,
     mut mouse_wheel: MessageReader<MouseWheel>,
     mut controller: ResMut<DebugFreeCameraController>,
+    terrain: Option<Res<TerrainHeightField>>,
     mut camera_query: Query<&mut Transform, With<Camera3d>>,
 ) {
     if !controller.enabled {
@@ -204,6 +218,12 @@ pub fn control_debug_free_camera(
         let forward = *transform.forward();
         transform.translation += forward * zoom_units * controller.zoom_sensitivity;
     }
+
+    if let Some(terrain) = terrain {
+        let floor = terrain.height_at(transform.translation.x, transform.translation.z)
+            + FREE_CAMERA_GROUND_CLEARANCE;
+        transform.translation.y = transform.translation.y.max(floor);
+    }
 }
 
 /// System to setup camera tour once assets are loaded
@@ -0,0 +1,313 @@
+//! Camera feel: smoothed zoom, impact shake and the low-health heartbeat vignette.
+//!
+//! Each effect is driven by [`CameraEffectsConfig`], which mirrors the camera
+//! section of the player settings.
+
+use crate::settings::CameraSettings;
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+/// Exponential approach rate of the zoom towards its target (1/s).
+const ZOOM_SMOOTHING_RATE: f32 = 12.0;
+/// Below this distance (world units) the zoom snaps onto its target.
+const ZOOM_SNAP_DISTANCE: f32 = 0.5;
+
+/// Trauma added by a Death Stab hit landing.
+pub const DEATH_STAB_IMPACT: f32 = 0.45;
+/// Trauma added by an Earthquake (Earthshake) cast.
+pub const EARTHQUAKE_IMPACT: f32 = 0.8;
+
+/// Trauma lost per second.
+const SHAKE_DECAY_PER_SEC: f32 = 1.6;
+/// Camera offset at full trauma and 100% intensity, in world units.
+const SHAKE_MAX_OFFSET: f32 = 18.0;
+const SHAKE_MAX_ROLL_DEG: f32 = 1.2;
+const SHAKE_FREQUENCY: f32 = 23.0;
+
+/// Health fraction under which the vignette starts beating.
+const LOW_HEALTH_THRESHOLD: f32 = 0.3;
+const HEARTBEAT_MIN_BPM: f32 = 70.0;
+const HEARTBEAT_MAX_BPM: f32 = 140.0;
+/// Vignette band width as a fraction of the shorter screen side.
+const VIGNETTE_BAND: f32 = 0.18;
+const VIGNETTE_MAX_ALPHA: f32 = 170.0;
+
+/// Runtime copy of the camera settings.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct CameraEffectsConfig {
+    pub smooth_zoom: bool,
+    /// Shake multiplier; 0 disables shake.
+    pub shake_scale: f32,
+    pub low_health_vignette: bool,
+}
+
+impl Default for CameraEffectsConfig {
+    fn default() -> Self {
+        Self::from(&CameraSettings::default())
+    }
+}
+
+impl From<&CameraSettings> for CameraEffectsConfig {
+    fn from(settings: &CameraSettings) -> Self {
+        Self {
+            smooth_zoom: settings.smooth_zoom,
+            shake_scale: settings.shake_scale(),
+            low_health_vignette: settings.low_health_vignette,
+        }
+    }
+}
+
+/// Accumulated impact trauma; the visible shake is proportional to its square.
+#[derive(Resource, Default, Debug)]
+pub struct CameraShake {
+    trauma: f32,
+    elapsed: f32,
+}
+
+impl CameraShake {
+    pub fn add_impact(&mut self, strength: f32) {
+        self.trauma = (self.trauma + strength).clamp(0.0, 1.0);
+    }
+
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    fn tick(&mut self, dt: f32) {
+        self.elapsed += dt;
+        self.trauma = (self.trauma - SHAKE_DECAY_PER_SEC * dt).max(0.0);
+    }
+
+    /// Translation and roll (radians) for the current frame.
+    ///
+    /// Uses summed sines rather than random noise so seeded VFX captures stay
+    /// reproducible.
+    fn sample(&self, scale: f32) -> (Vec3, f32) {
+        let amount = self.trauma * self.trauma * scale;
+        if amount <= 0.0 {
+            return (Vec3::ZERO, 0.0);
+        }
+
+        let t = self.elapsed * SHAKE_FREQUENCY;
+        let wobble = |phase: f32| (t + phase).sin() * 0.6 + (t * 2.3 + phase * 1.7).sin() * 0.4;
+        let offset = Vec3::new(wobble(0.0), wobble(2.1), wobble(4.2)) * SHAKE_MAX_OFFSET;
+        (
+            offset * amount,
+            wobble(5.9) * SHAKE_MAX_ROLL_DEG.to_radians() * amount,
+        )
+    }
+}
+
+/// Marks a camera as shaken by [`CameraShake`] and remembers the offset applied
+/// this frame, so it can be removed before camera controllers run again.
+#[derive(Component, Default, Debug)]
+pub struct CameraShakeOffset {
+    translation: Vec3,
+    roll: f32,
+}
+
+/// Health of the locally controlled character, when known.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LocalPlayerHealth {
+    pub current: u16,
+    pub max: u16,
+}
+
+impl LocalPlayerHealth {
+    pub fn fraction(&self) -> f32 {
+        if self.max == 0 {
+            return 1.0;
+        }
+        (f32::from(self.current) / f32::from(self.max)).clamp(0.0, 1.0)
+    }
+}
+
+pub struct CameraEffectsPlugin;
+
+impl Plugin for CameraEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraEffectsConfig>()
+            .init_resource::<CameraShake>()
+            .add_systems(First, remove_camera_shake)
+            .add_systems(
+                PostUpdate,
+                apply_camera_shake.before(bevy::transform::TransformSystems::Propagate),
+            )
+            .add_systems(EguiPrimaryContextPass, draw_low_health_vignette);
+    }
+}
+
+/// Moves `current` towards `target`, or straight onto it when smoothing is off.
+pub fn approach_zoom(current: f32, target: f32, dt: f32, smooth: bool) -> f32 {
+    if !smooth {
+        return target;
+    }
+    let next = current + (target - current) * (1.0 - (-ZOOM_SMOOTHING_RATE * dt).exp());
+    if (target - next).abs() < ZOOM_SNAP_DISTANCE {
+        target
+    } else {
+        next
+    }
+}
+
+/// Queues an impact shake from any system with access to `Commands`.
+pub fn queue_camera_impact(commands: &mut Commands, strength: f32) {
+    commands.queue(move |world: &mut World| {
+        if let Some(mut shake) = world.get_resource_mut::<CameraShake>() {
+            shake.add_impact(strength);
+        }
+    });
+}
+
+fn remove_camera_shake(mut cameras: Query<(&mut Transform, &mut CameraShakeOffset)>) {
+    for (mut transform, mut offset) in &mut cameras {
+        transform.rotate_local_z(-offset.roll);
+        transform.translation -= offset.translation;
+        *offset = CameraShakeOffset::default();
+    }
+}
+
+fn apply_camera_shake(
+    time: Res<Time>,
+    config: Res<CameraEffectsConfig>,
+    mut shake: ResMut<CameraShake>,
+    mut cameras: Query<(&mut Transform, &mut CameraShakeOffset)>,
+) {
+    shake.tick(time.delta_secs());
+    let (translation, roll) = shake.sample(config.shake_scale);
+    if translation == Vec3::ZERO && roll == 0.0 {
+        return;
+    }
+
+    for (mut transform, mut offset) in &mut cameras {
+        transform.translation += translation;
+        transform.rotate_local_z(roll);
+        offset.translation = translation;
+        offset.roll = roll;
+    }
+}
+
+/// Vignette opacity (0..=1) for a health fraction at `time` seconds.
+///
+/// Zero above the low-health threshold; below it the heartbeat quickens and
+/// the vignette deepens as health drops.
+pub fn heartbeat_vignette_alpha(health_fraction: f32, time: f32) -> f32 {
+    if health_fraction >= LOW_HEALTH_THRESHOLD {
+        return 0.0;
+    }
+
+    let severity = 1.0 - health_fraction.max(0.0) / LOW_HEALTH_THRESHOLD;
+    let bpm = HEARTBEAT_MIN_BPM + (HEARTBEAT_MAX_BPM - HEARTBEAT_MIN_BPM) * severity;
+    let phase = (time * bpm / 60.0).fract();
+    // "Lub-dub": a strong beat followed by a softer one.
+    let thump = |x: f32| if x < 0.0 { 0.0 } else { (-x * 14.0).exp() };
+    let pulse = thump(phase).max(0.6 * thump(phase - 0.28));
+
+    (0.35 + 0.65 * severity) * (0.45 + 0.55 * pulse)
+}
+
+fn draw_low_health_vignette(
+    mut contexts: EguiContexts,
+    time: Res<Time>,
+    config: Res<CameraEffectsConfig>,
+    health: Option<Res<LocalPlayerHealth>>,
+) {
+    if !config.low_health_vignette {
+        return;
+    }
+    let Some(health) = health else {
+        return;
+    };
+    let alpha = heartbeat_vignette_alpha(health.fraction(), time.elapsed_secs());
+    if alpha <= 0.0 {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let outer = ctx.content_rect();
+    let band = outer.width().min(outer.height()) * VIGNETTE_BAND;
+    let inner = outer.shrink(band);
+    let edge = egui::Color32::from_rgba_unmultiplied(150, 0, 0, (alpha * VIGNETTE_MAX_ALPHA) as u8);
+
+    // Four trapezoids fading from the screen edge to transparent inside.
+    let mut mesh = egui::Mesh::default();
+    let outer_corners = [
+        outer.left_top(),
+        outer.right_top(),
+        outer.right_bottom(),
+        outer.left_bottom(),
+    ];
+    let inner_corners = [
+        inner.left_top(),
+        inner.right_top(),
+        inner.right_bottom(),
+        inner.left_bottom(),
+    ];
+    for corner in outer_corners {
+        mesh.colored_vertex(corner, edge);
+    }
+    for corner in inner_corners {
+        mesh.colored_vertex(corner, egui::Color32::TRANSPARENT);
+    }
+    for side in 0..4u32 {
+        let next = (side + 1) % 4;
+        mesh.add_triangle(side, next, 4 + next);
+        mesh.add_triangle(side, 4 + next, 4 + side);
+    }
+
+    ctx.layer_painter(egui::LayerId::background()).add(mesh);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zoom_eases_towards_target_unless_disabled() {
+        assert_eq!(approach_zoom(1000.0, 1500.0, 1.0 / 60.0, false), 1500.0);
+
+        let mut distance = 1000.0;
+        let first = approach_zoom(distance, 1500.0, 1.0 / 60.0, true);
+        assert!(first > 1000.0 && first < 1500.0);
+        for _ in 0..120 {
+            distance = approach_zoom(distance, 1500.0, 1.0 / 60.0, true);
+        }
+        assert_eq!(distance, 1500.0);
+    }
+
+    #[test]
+    fn shake_fades_out_and_respects_intensity() {
+        let mut shake = CameraShake::default();
+        shake.add_impact(EARTHQUAKE_IMPACT);
+        shake.add_impact(EARTHQUAKE_IMPACT);
+        assert_eq!(shake.trauma(), 1.0);
+
+        shake.tick(0.05);
+        assert_eq!(shake.sample(0.0), (Vec3::ZERO, 0.0));
+        let (half, _) = shake.sample(0.5);
+        let (full, _) = shake.sample(1.0);
+        assert!((full.length() - 2.0 * half.length()).abs() < 1e-3);
+
+        shake.tick(1.0);
+        assert_eq!(shake.trauma(), 0.0);
+        assert_eq!(shake.sample(1.0), (Vec3::ZERO, 0.0));
+    }
+
+    #[test]
+    fn vignette_only_beats_at_low_health() {
+        assert_eq!(heartbeat_vignette_alpha(0.5, 0.0), 0.0);
+        assert_eq!(heartbeat_vignette_alpha(LOW_HEALTH_THRESHOLD, 0.0), 0.0);
+
+        let faint = heartbeat_vignette_alpha(0.25, 0.0);
+        let deep = heartbeat_vignette_alpha(0.05, 0.0);
+        assert!(faint > 0.0 && deep > faint);
+        // Between beats the vignette relaxes but never vanishes.
+        let between = heartbeat_vignette_alpha(0.05, 0.3);
+        assert!(between > 0.0 && between < deep);
+
+        let unknown_max = LocalPlayerHealth { current: 0, max: 0 };
+        assert_eq!(unknown_max.fraction(), 1.0);
+    }
+}
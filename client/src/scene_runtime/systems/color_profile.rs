@@ -0,0 +1,109 @@
+//! Color response of every 3D camera, from the Graficos tab.
+//!
+//! The world camera and the viewers get the tonemapping and exposure of the
+//! setting as soon as they spawn, and again whenever the setting changes.
+//! In the game the ambient light follows the setting too; the viewers have
+//! no settings, so they use the default profile and keep their own ambient.
+
+use crate::settings::{ColorProfileSetting, SettingsResource};
+use bevy::camera::Exposure;
+use bevy::core_pipeline::tonemapping::Tonemapping;
+use bevy::light::GlobalAmbientLight;
+use bevy::prelude::*;
+
+/// Camera and ambient values of a [`ColorProfileSetting`].
+#[derive(Clone, Copy)]
+pub struct ColorProfile {
+    pub tonemapping: Tonemapping,
+    pub exposure: Exposure,
+    /// `GlobalAmbientLight::brightness`, in cd/m².
+    pub ambient_brightness: f32,
+}
+
+/// The original client lit textures with clamped vertex colors and wrote
+/// them straight to the framebuffer: no tonemapping curve, a face square to
+/// the sun shows its texture color and unlit faces keep a flat ambient.
+/// EV100 10.4 maps the 5000 lux runtime sun to about 1.0 on a white surface.
+const CLASSIC_EXPOSURE_EV100: f32 = 10.4;
+const CLASSIC_AMBIENT_BRIGHTNESS: f32 = 350.0;
+const STANDARD_AMBIENT_BRIGHTNESS: f32 = 0.55;
+
+pub fn color_profile(setting: ColorProfileSetting) -> ColorProfile {
+    match setting {
+        ColorProfileSetting::Standard => ColorProfile {
+            tonemapping: Tonemapping::ReinhardLuminance,
+            exposure: Exposure::default(),
+            ambient_brightness: STANDARD_AMBIENT_BRIGHTNESS,
+        },
+        ColorProfileSetting::Classic => ColorProfile {
+            tonemapping: Tonemapping::None,
+            exposure: Exposure {
+                ev100: CLASSIC_EXPOSURE_EV100,
+            },
+            ambient_brightness: CLASSIC_AMBIENT_BRIGHTNESS,
+        },
+    }
+}
+
+#[derive(Resource, Default)]
+struct AppliedColorProfile(Option<ColorProfileSetting>);
+
+pub struct ColorProfilePlugin;
+
+impl Plugin for ColorProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AppliedColorProfile>()
+            .add_systems(Update, apply_color_profile);
+    }
+}
+
+fn apply_color_profile(
+    mut commands: Commands,
+    settings: Option<Res<SettingsResource>>,
+    mut applied: ResMut<AppliedColorProfile>,
+    mut ambient_light: ResMut<GlobalAmbientLight>,
+    cameras: Query<Entity, With<Camera3d>>,
+    new_cameras: Query<Entity, Added<Camera3d>>,
+) {
+    let setting = settings
+        .as_ref()
+        .map(|settings| settings.current.graphics.color_profile)
+        .unwrap_or_default();
+    let profile = color_profile(setting);
+
+    let changed = applied.0 != Some(setting);
+    if changed {
+        applied.0 = Some(setting);
+        if settings.is_some() {
+            ambient_light.brightness = profile.ambient_brightness;
+        }
+    }
+
+    let targets = if changed {
+        cameras.iter().collect::<Vec<_>>()
+    } else {
+        new_cameras.iter().collect()
+    };
+    for entity in targets {
+        commands
+            .entity(entity)
+            .insert((profile.tonemapping, profile.exposure));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classic_colors_skip_the_tonemapping_curve() {
+        let standard = color_profile(ColorProfileSetting::Standard);
+        assert_eq!(standard.tonemapping, Tonemapping::ReinhardLuminance);
+        assert_eq!(standard.exposure.ev100, Exposure::EV100_BLENDER);
+
+        let classic = color_profile(ColorProfileSetting::Classic);
+        assert_eq!(classic.tonemapping, Tonemapping::None);
+        assert!(classic.exposure.ev100 > standard.exposure.ev100);
+        assert!(classic.ambient_brightness > standard.ambient_brightness);
+    }
+}
@@ -1,6 +1,8 @@
 use crate::infra::assets::{asset_path_exists, resolve_asset_path};
 use crate::lightning_sprite_2d::{LightningSprite2dMaterial, LightningSprite2dParams};
 use crate::scene_runtime::components::*;
+use crate::scene_runtime::systems::camera_effects::{DEATH_STAB_IMPACT, queue_camera_impact};
+use crate::scene_runtime::vfx_rng::vfx_rng;
 use bevy::gltf::Gltf;
 use bevy::image::{ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor};
 use bevy::light::{NotShadowCaster, NotShadowReceiver};
@@ -495,7 +497,7 @@ pub fn update_death_stab_timeline(
     let spear_glb = spear_asset();
     let energy_ttl = frames_to_seconds(DEATH_STAB_ENERGY_PARTICLE_LIFE_FRAMES);
     let spike_ttl = frames_to_seconds(DEATH_STAB_SPIKE_LIFE_FRAMES);
-    let mut rng = rand::thread_rng();
+    let mut rng = vfx_rng();
 
     for (entity, mut timeline) in &mut timelines {
         timeline.elapsed_seconds += dt;
@@ -618,6 +620,7 @@ pub fn update_death_stab_timeline(
             // Phase 3: Victim lightning at lifeInt 10
             if current_life == DEATH_STAB_IMPACT_LIFE_INT && !timeline.impact_applied {
                 timeline.impact_applied = true;
+                queue_camera_impact(&mut commands, DEATH_STAB_IMPACT);
                 let center = target_pos + Vec3::Y * 80.0;
                 commands.spawn((
                     RuntimeSceneEntity,
@@ -978,7 +981,7 @@ pub fn update_lightning_hurt_effects(
 
     let dt = time.delta_secs();
     let factor = fps_animation_factor(dt);
-    let mut rng = rand::thread_rng();
+    let mut rng = vfx_rng();
 
     for (entity, mut effect, mut transform) in &mut effects {
         // Early-exit: if target entity has despawned, remove effect immediately (like C# RemoveSelf)
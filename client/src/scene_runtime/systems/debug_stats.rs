@@ -2,7 +2,8 @@ use super::frame_limiter::DebugFrameLimiter;
 use super::objects::SceneObjectDistanceCullingConfig;
 use super::shadow_quality::DebugShadowQuality;
 use crate::bevy_compat::*;
-use crate::infra::assets::set_use_remaster_assets;
+use crate::infra::assets::{asset_variant_report, current_asset_variant, set_asset_variant};
+use crate::presentation::ui::nameplate::NameplateStats;
 use crate::scene_runtime::components::*;
 use crate::settings::SettingsResource;
 use bevy::asset::AssetId;
@@ -19,6 +20,7 @@ use std::collections::{HashMap, HashSet};
 pub struct DebugHudExtras<'w> {
     frame_limiter: Option<Res<'w, DebugFrameLimiter>>,
     shadow_quality: Option<Res<'w, DebugShadowQuality>>,
+    nameplates: Option<Res<'w, NameplateStats>>,
 }
 
 /// UI marker for performance stats text (fps/frame/object counters).
@@ -102,20 +104,21 @@ pub fn toggle_remaster_assets_shortcut(
         return;
     }
 
-    settings.current.graphics.use_remaster_assets = !settings.current.graphics.use_remaster_assets;
-    set_use_remaster_assets(settings.current.graphics.use_remaster_assets);
+    let variant = settings.current.graphics.asset_variant.toggled();
+    settings.current.graphics.asset_variant = variant;
+    set_asset_variant(variant);
     if let Err(error) = settings.save_to_disk() {
         warn!("Failed to persist remaster asset toggle: {error}");
     }
 
+    let report = asset_variant_report();
     info!(
-        "Remaster assets {}",
-        if settings.current.graphics.use_remaster_assets {
-            "enabled"
-        } else {
-            "disabled"
-        }
+        "Asset variant set to {variant:?} ({} remaster, {} classic loaded so far)",
+        report.remaster, report.classic
     );
+    for path in &report.fallbacks {
+        info!("No remaster copy of '{path}', using the classic file");
+    }
 }
 
 pub fn apply_debug_overlay_visibility(
@@ -325,10 +328,22 @@ pub fn update_debug_scene_stats(
     } else {
         "n/a".to_string()
     };
+    let nameplate_text = debug_extras
+        .nameplates
+        .as_ref()
+        .map(|stats| {
+            format!(
+                "{}/{} ({} com barra)",
+                stats.drawn(),
+                stats.total(),
+                stats.full
+            )
+        })
+        .unwrap_or_else(|| "n/a".to_string());
     for mut text in &mut perf_text_query {
         text.0 = if cfg!(debug_assertions) {
             format!(
-                "FPS: {fps_text}\nFrame: {frame_text} ms\nTempo: {elapsed_text} s\nCull dist: {distance_culling_text}\nObjetos render: {}/{}\nMeshes render: {}/{}\nPoligonos render: {}/{}",
+                "FPS: {fps_text}\nFrame: {frame_text} ms\nTempo: {elapsed_text} s\nCull dist: {distance_culling_text}\nObjetos render: {}/{}\nMeshes render: {}/{}\nPoligonos render: {}/{}\nNomes: {nameplate_text}",
                 debug_stats.visible_object_count,
                 debug_stats.object_count,
                 debug_stats.visible_mesh_count,
@@ -337,7 +352,9 @@ pub fn update_debug_scene_stats(
                 debug_stats.polygon_count,
             )
         } else {
-            format!("FPS: {fps_text}\nFrame: {frame_text} ms\nTempo: {elapsed_text} s")
+            format!(
+                "FPS: {fps_text}\nFrame: {frame_text} ms\nTempo: {elapsed_text} s\nNomes: {nameplate_text}"
+            )
         };
     }
 
@@ -351,10 +368,18 @@ pub fn update_debug_scene_stats(
         .as_ref()
         .map(|sq| format!("{}", sq.mode))
         .unwrap_or_else(|| "n/a".to_string());
+    let asset_report = asset_variant_report();
+    let asset_text = format!(
+        "{} ({} remaster, {} classico, {} sem remaster)",
+        current_asset_variant().label(),
+        asset_report.remaster,
+        asset_report.classic,
+        asset_report.fallbacks.len()
+    );
 
     for mut text in &mut gpu_text_query {
         text.0 = format!(
-            "GPU: {gpu_name}\nVideo API: {graphics_api}\nVersao: {graphics_version}\n[F4] FPS Limit: {frame_limit_text}\n[F5] Sombra: {shadow_text}",
+            "GPU: {gpu_name}\nVideo API: {graphics_api}\nVersao: {graphics_version}\n[F4] FPS Limit: {frame_limit_text}\n[F5] Sombra: {shadow_text}\n[F10] Assets: {asset_text}",
         );
     }
 }
@@ -0,0 +1,125 @@
+//! Size and colour that set elite and boss monsters apart.
+//!
+//! An [`EliteMarker`] scales its root entity once and gives every mesh below
+//! it a tinted copy of its `StandardMaterial`. The copy becomes the mesh's
+//! material for good, so status layers tint on top of it.
+
+use super::StatusTintedMaterial;
+use bevy::prelude::*;
+use protocol::{MonsterAffix, MonsterRank};
+
+const ELITE_SCALE: f32 = 1.25;
+const BOSS_SCALE: f32 = 1.5;
+const ELITE_TINT: LinearRgba = LinearRgba::rgb(1.0, 0.9, 0.55);
+const ELITE_GLOW: LinearRgba = LinearRgba::rgb(0.25, 0.18, 0.0);
+const BOSS_TINT: LinearRgba = LinearRgba::rgb(1.0, 0.6, 0.5);
+const BOSS_GLOW: LinearRgba = LinearRgba::rgb(0.45, 0.06, 0.02);
+
+/// Affixes a monster rolled at spawn, replicated in `MonsterAffixes`.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct EliteMarker {
+    pub rank: MonsterRank,
+    pub affixes: Vec<MonsterAffix>,
+}
+
+impl EliteMarker {
+    /// Marker for `affixes`; `None` for a plain monster.
+    pub fn new(affixes: &[MonsterAffix]) -> Option<Self> {
+        Some(Self {
+            rank: MonsterRank::of(affixes)?,
+            affixes: affixes.to_vec(),
+        })
+    }
+}
+
+/// Mesh already drawn with its elite material.
+#[derive(Component, Debug)]
+pub struct EliteTintedMaterial;
+
+pub struct EliteMarkersPlugin;
+
+impl Plugin for EliteMarkersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (scale_elites, tint_elite_materials));
+    }
+}
+
+/// Scale multiplier of a rank.
+pub fn rank_scale(rank: MonsterRank) -> f32 {
+    match rank {
+        MonsterRank::Elite => ELITE_SCALE,
+        MonsterRank::Boss => BOSS_SCALE,
+    }
+}
+
+/// Base-colour multiplier and added emissive of a rank.
+pub fn rank_tint(rank: MonsterRank) -> (LinearRgba, LinearRgba) {
+    match rank {
+        MonsterRank::Elite => (ELITE_TINT, ELITE_GLOW),
+        MonsterRank::Boss => (BOSS_TINT, BOSS_GLOW),
+    }
+}
+
+fn scale_elites(mut elites: Query<(&EliteMarker, &mut Transform), Added<EliteMarker>>) {
+    for (marker, mut transform) in &mut elites {
+        transform.scale *= rank_scale(marker.rank);
+    }
+}
+
+fn tint_elite_materials(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    elites: Query<(Entity, &EliteMarker)>,
+    children: Query<&Children>,
+    // Meshes under a status layer are tinted once the status expires.
+    untinted: Query<
+        &MeshMaterial3d<StandardMaterial>,
+        (Without<EliteTintedMaterial>, Without<StatusTintedMaterial>),
+    >,
+) {
+    for (owner, marker) in &elites {
+        let (tint, glow) = rank_tint(marker.rank);
+        for mesh in children.iter_descendants(owner) {
+            let Ok(material) = untinted.get(mesh) else {
+                continue;
+            };
+            let Some(mut copy) = materials.get(&material.0).cloned() else {
+                continue;
+            };
+            let base = copy.base_color.to_linear();
+            copy.base_color = Color::from(LinearRgba::new(
+                base.red * tint.red,
+                base.green * tint.green,
+                base.blue * tint.blue,
+                base.alpha,
+            ));
+            copy.emissive += glow;
+            commands
+                .entity(mesh)
+                .insert((MeshMaterial3d(materials.add(copy)), EliteTintedMaterial));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bosses_stand_out_more_than_elites() {
+        assert_eq!(EliteMarker::new(&[]), None);
+        let elite = EliteMarker::new(&[MonsterAffix::Swift]).unwrap();
+        let boss = EliteMarker::new(&[MonsterAffix::Armored, MonsterAffix::Enraged]).unwrap();
+        assert_eq!(
+            (elite.rank, boss.rank),
+            (MonsterRank::Elite, MonsterRank::Boss)
+        );
+
+        assert!(rank_scale(boss.rank) > rank_scale(elite.rank));
+        let (_, elite_glow) = rank_tint(elite.rank);
+        let (boss_tint, boss_glow) = rank_tint(boss.rank);
+        // Elites glow gold, bosses red.
+        assert!(elite_glow.green > elite_glow.blue);
+        assert!(boss_glow.red > boss_glow.green && boss_tint.red > boss_tint.blue);
+    }
+}
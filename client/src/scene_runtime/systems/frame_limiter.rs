@@ -1,5 +1,7 @@
+use crate::settings::{AudioCategoryState, SettingsResource};
+use bevy::audio::{AudioSink, AudioSinkPlayback};
 use bevy::prelude::*;
-use bevy::window::{PresentMode, WindowOccluded};
+use bevy::window::{PresentMode, PrimaryWindow, WindowOccluded};
 use bevy::winit::{UpdateMode, WinitSettings};
 use std::time::Duration;
 
@@ -53,19 +55,16 @@ pub fn cycle_debug_frame_limit(
         match limiter.mode {
             FrameLimitMode::Default => {
                 window.present_mode = PresentMode::AutoVsync;
-                let mode = UpdateMode::reactive(Duration::from_secs_f64(1.0 / 60.0));
-                winit_settings.focused_mode = mode;
-                winit_settings.unfocused_mode = mode;
+                winit_settings.focused_mode =
+                    UpdateMode::reactive(Duration::from_secs_f64(1.0 / 60.0));
             }
             FrameLimitMode::MonitorLimit => {
                 window.present_mode = PresentMode::AutoVsync;
                 winit_settings.focused_mode = UpdateMode::Continuous;
-                winit_settings.unfocused_mode = UpdateMode::Continuous;
             }
             FrameLimitMode::Disabled => {
                 window.present_mode = PresentMode::AutoNoVsync;
                 winit_settings.focused_mode = UpdateMode::Continuous;
-                winit_settings.unfocused_mode = UpdateMode::Continuous;
             }
         }
     }
@@ -73,18 +72,79 @@ pub fn cycle_debug_frame_limit(
     info!("Frame limit: {}", limiter.mode);
 }
 
-pub fn handle_window_occlusion(
-    mut events: MessageReader<WindowOccluded>,
+/// Frame interval of a minimized or fully hidden window, whatever the settings.
+const OCCLUDED_FRAME_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Whether the window is in the background and what is throttled while it is.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BackgroundMode {
+    pub active: bool,
+    pub particles_paused: bool,
+    pub audio_muted: bool,
+}
+
+/// Run condition for effects that stop while the window is in the background.
+pub fn background_particles_running(background: Res<BackgroundMode>) -> bool {
+    !background.particles_paused
+}
+
+/// Drops to a low-power update mode while the window is out of focus or
+/// minimized. Network heartbeats keep going since the app still updates.
+pub fn update_background_mode(
+    mut occlusion_events: MessageReader<WindowOccluded>,
+    mut occluded: Local<bool>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    settings: Res<SettingsResource>,
     mut winit_settings: ResMut<WinitSettings>,
+    mut background: ResMut<BackgroundMode>,
+    mut audio_categories: ResMut<AudioCategoryState>,
+) {
+    for event in occlusion_events.read() {
+        *occluded = event.occluded;
+    }
+
+    let focused = windows.single().map_or(true, |window| window.focused);
+    let config = &settings.current.background;
+    let active = *occluded || (!focused && config.throttle);
+
+    let unfocused_mode = if *occluded {
+        UpdateMode::reactive_low_power(OCCLUDED_FRAME_INTERVAL)
+    } else if config.throttle {
+        UpdateMode::reactive_low_power(config.frame_interval())
+    } else {
+        winit_settings.focused_mode
+    };
+    if winit_settings.unfocused_mode != unfocused_mode {
+        winit_settings.unfocused_mode = unfocused_mode;
+    }
+
+    let next = BackgroundMode {
+        active,
+        particles_paused: active && config.pause_particles,
+        audio_muted: active && config.mute_audio,
+    };
+    if *background != next {
+        if next.active != background.active {
+            info!(
+                "Background mode: {}",
+                if next.active { "on" } else { "off" }
+            );
+        }
+        *background = next;
+        audio_categories.background_muted = next.audio_muted;
+    }
+}
+
+/// Mutes every playing sound while the background mode asks for it.
+pub fn apply_background_audio_mute(
+    background: Res<BackgroundMode>,
+    mut sinks: Query<&mut AudioSink>,
 ) {
-    for event in events.read() {
-        if event.occluded {
-            // Minimized/hidden — drop to 5fps
-            winit_settings.unfocused_mode =
-                UpdateMode::reactive_low_power(Duration::from_millis(200));
-        } else {
-            // Visible again — match focused_mode
-            winit_settings.unfocused_mode = winit_settings.focused_mode;
+    for mut sink in &mut sinks {
+        if background.audio_muted && !sink.is_muted() {
+            sink.mute();
+        } else if !background.audio_muted && sink.is_muted() {
+            sink.unmute();
         }
     }
 }
@@ -3,6 +3,7 @@ use crate::bevy_compat::*;
 use crate::infra::assets::{asset_path_exists, resolve_asset_path};
 use crate::scene_runtime::components::*;
 use crate::scene_runtime::state::RuntimeSceneAssets;
+use crate::scene_runtime::terrain_height::terrain_vertical_scale;
 use crate::scene_runtime::world_coordinates::{mirror_map_xz_with_axis, world_mirror_axis};
 use crate::settings::SettingsResource;
 use bevy::image::{ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor};
@@ -29,15 +30,6 @@ const CLIENT_ASSETS_ROOT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../assets
 const GRASS_CHUNK_SIZE: usize = 8;
 const GRASS_CULLING_CAMERA_MOVE_THRESHOLD_SQ: f32 = 100.0;
 
-fn effective_height_multiplier(world_name: &str, config: &TerrainConfig) -> f32 {
-    if world_name == "world_56" {
-        // C++ WD_55LOGINSCENE loads World56 terrain and applies x3.0 height.
-        3.0
-    } else {
-        config.height_multiplier
-    }
-}
-
 /// Marker component to track if terrain grass has been spawned.
 #[derive(Component)]
 pub struct TerrainGrassSpawned;
@@ -191,8 +183,7 @@ pub fn spawn_terrain_grass_when_ready(
 
     // Build grass quads grouped by chunk
     let scale = config.size.scale;
-    let vertical_scale = effective_height_multiplier(&world.world_name, config)
-        * (scale / config.legacy_terrain_scale.max(1.0));
+    let vertical_scale = terrain_vertical_scale(&world.world_name, config);
 
     let map_width = terrain_map.width().min(heightmap.width as usize);
     let map_height = terrain_map.height().min(heightmap.height as usize);
@@ -1,6 +1,8 @@
+use super::color_profile::color_profile;
 use crate::bevy_compat::*;
 use crate::scene_runtime::components::*;
 use crate::scene_runtime::state::RuntimeSceneAssets;
+use crate::settings::SettingsResource;
 use bevy::light::{
     CascadeShadowConfigBuilder, DirectionalLightShadowMap, GlobalAmbientLight,
     ShadowFilteringMethod,
@@ -62,6 +64,7 @@ pub fn spawn_runtime_sun_light(
     mut commands: Commands,
     assets: Res<RuntimeSceneAssets>,
     terrain_configs: Res<Assets<TerrainConfig>>,
+    settings: Res<SettingsResource>,
     mut ambient_light: ResMut<GlobalAmbientLight>,
     query: Query<Entity, With<RuntimeSunLight>>,
     camera_query: Query<Entity, With<Camera3d>>,
@@ -125,7 +128,8 @@ pub fn spawn_runtime_sun_light(
 
     // Lift shadowed areas so terrain/object shadows remain visible but not crushed to black.
     ambient_light.color = Color::srgb(0.96, 0.97, 1.0);
-    ambient_light.brightness = 0.55;
+    ambient_light.brightness =
+        color_profile(settings.current.graphics.color_profile).ambient_brightness;
     ambient_light.affects_lightmapped_meshes = true;
 
     // Set initial shadow quality to Low
@@ -1,10 +1,14 @@
+mod ambient_sounds;
 mod animations;
 mod asset_loaders;
 mod boids;
 mod boundary_walls;
 mod camera;
+mod camera_effects;
+mod color_profile;
 mod death_stab;
 mod debug_stats;
+mod elite_markers;
 mod frame_limiter;
 mod grass;
 mod grid_overlay;
@@ -15,17 +19,23 @@ mod particles;
 mod shadow_quality;
 mod skill_effects;
 mod skybox;
+mod status_effects;
 mod terrain;
+mod texture_filtering;
 mod vfx;
 mod weapon_trail;
 
+pub use ambient_sounds::*;
 pub use animations::*;
 pub use asset_loaders::*;
 pub use boids::*;
 pub use boundary_walls::*;
 pub use camera::*;
+pub use camera_effects::*;
+pub use color_profile::*;
 pub use death_stab::*;
 pub use debug_stats::*;
+pub use elite_markers::*;
 pub use frame_limiter::*;
 pub use grass::*;
 pub use grid_overlay::*;
@@ -36,6 +46,8 @@ pub use particles::*;
 pub use shadow_quality::*;
 pub use skill_effects::*;
 pub use skybox::*;
+pub use status_effects::*;
 pub use terrain::*;
+pub use texture_filtering::*;
 pub use vfx::*;
 pub use weapon_trail::*;
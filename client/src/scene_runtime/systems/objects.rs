@@ -4,7 +4,9 @@ use crate::infra::assets::resolve_asset_path;
 use crate::legacy_additive::{
     LegacyAdditiveMaterial, legacy_additive_from_standard, legacy_additive_intensity_from_extras,
 };
+use crate::scene_runtime::collision::WorldCollision;
 use crate::scene_runtime::components::*;
+use crate::scene_runtime::object_diff::SceneObjectDiff;
 use crate::scene_runtime::scene_loader::{SceneObjectsMetadata, SceneRotationEncoding};
 use crate::scene_runtime::state::RuntimeSceneAssets;
 use crate::scene_runtime::transforms::scene_object_rotation_to_quat;
@@ -30,6 +32,8 @@ const SCENE_OBJECT_YAW_OFFSET_ENV: &str = "MU_SCENE_OBJECT_YAW_OFFSET_DEGREES";
 const DEFAULT_SCENE_OBJECT_CULL_DISTANCE: f32 = 25000.0;
 const SCENE_OBJECT_CULL_DISTANCE_ENV: &str = "MU_SCENE_OBJECT_CULL_DISTANCE";
 const SCENE_OBJECTS_UNLIT_ENV: &str = "MU_SCENE_OBJECTS_UNLIT";
+/// Eagle spawn point, which also spawns a boid circling it.
+const BOID_SPAWN_OBJECT_TYPE: u32 = 62;
 
 fn scene_objects_unlit() -> bool {
     static UNLIT: OnceLock<bool> = OnceLock::new();
@@ -150,6 +154,49 @@ fn scene_objects_unlit() -> bool {
 #[derive(Component)]
 pub struct SceneObjectsSpawned;
 
+/// Objects currently spawned and how they were placed, kept on the
+/// `SceneObjectsSpawned` marker so a reloaded placement file can be diffed
+/// against them.
+#[derive(Component)]
+pub struct AppliedSceneObjects {
+    objects: Vec<SceneObjectDef>,
+    placement: ScenePlacement,
+}
+
+/// World-space placement settings shared by every object of a scene.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ScenePlacement {
+    rotation_encoding: SceneRotationEncoding,
+    rotation_yaw_offset_degrees: f32,
+    map_max_x: f32,
+    map_max_z: f32,
+    mirror_axis: WorldMirrorAxis,
+}
+
+impl ScenePlacement {
+    fn transform(&self, object_def: &SceneObjectDef) -> Transform {
+        let translation = mirror_map_position_with_axis(
+            Vec3::from(object_def.position),
+            self.map_max_x,
+            self.map_max_z,
+            self.mirror_axis,
+        );
+        let rotation = scene_object_rotation_to_quat(
+            apply_scene_object_yaw_offset(
+                object_def.rotation,
+                self.rotation_encoding,
+                self.rotation_yaw_offset_degrees,
+            ),
+            self.rotation_encoding,
+        );
+        Transform {
+            translation,
+            rotation,
+            scale: Vec3::from(object_def.scale),
+        }
+    }
+}
+
 #[derive(Default)]
 pub(crate) struct ModelValidationCache {
     by_model: HashMap<String, bool>,
@@ -231,12 +278,186 @@ pub fn spawn_scene_objects_when_ready(
         return;
     };
 
-    let mirror_axis = world_mirror_axis();
-    let map_max_x =
-        (terrain_config.size.width.saturating_sub(1) as f32) * terrain_config.size.scale;
-    let map_max_z =
-        (terrain_config.size.depth.saturating_sub(1) as f32) * terrain_config.size.scale;
+    let (object_defs, placement) = scene_object_layout(scene_data, terrain_config);
+
+    info!("Spawning {} scene objects", object_defs.len());
+    let spawn_started_at = Instant::now();
 
+    // Spawn each object
+    for object in &object_defs {
+        spawn_scene_object(
+            &mut commands,
+            &asset_server,
+            &mut meshes,
+            &mut materials,
+            &mut model_validation_cache,
+            &mut proxy_assets,
+            object,
+            particle_definitions,
+            &placement,
+        );
+    }
+
+    let collision = WorldCollision::from_scene_objects(&object_defs, placement.rotation_encoding);
+    info!(
+        "Scene object collision blocks {} tiles",
+        collision.grid().blocked_tiles()
+    );
+    commands.insert_resource(collision);
+
+    // Mark as spawned
+    commands.spawn((
+        SceneObjectsSpawned,
+        AppliedSceneObjects {
+            objects: object_defs,
+            placement,
+        },
+        RuntimeSceneEntity,
+    ));
+
+    info!(
+        "Scene objects spawned successfully in {} ms",
+        spawn_started_at.elapsed().as_millis()
+    );
+}
+
+/// Applies edits to the loaded world's object placement file (asset hot
+/// reload or the map editor) by spawning, despawning or moving only the
+/// objects that changed, instead of reloading the whole world.
+pub fn reload_changed_scene_objects(
+    mut commands: Commands,
+    mut asset_events: MessageReader<AssetEvent<SceneObjectsData>>,
+    assets: Res<RuntimeSceneAssets>,
+    terrain_configs: Res<Assets<TerrainConfig>>,
+    scene_objects_data: Res<Assets<SceneObjectsData>>,
+    particle_defs: Res<Assets<ParticleDefinitions>>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut model_validation_cache: Local<ModelValidationCache>,
+    mut proxy_assets: Local<ProxyAssetCache>,
+    mut applied_query: Query<&mut AppliedSceneObjects>,
+    mut placed_objects: Query<(Entity, &SceneObjectId, &mut Transform, Has<SceneObject>)>,
+) {
+    let Some(world) = assets.world.as_ref() else {
+        asset_events.clear();
+        return;
+    };
+    let scene_objects_id = world.scene_objects.id();
+    let modified = asset_events
+        .read()
+        .filter(|event| matches!(event, AssetEvent::Modified { id } if *id == scene_objects_id))
+        .count();
+    if modified == 0 {
+        return;
+    }
+
+    // Not spawned yet: the first spawn already picks up the new file.
+    let Ok(mut applied) = applied_query.single_mut() else {
+        return;
+    };
+    let Some(scene_data) = scene_objects_data.get(&world.scene_objects) else {
+        return;
+    };
+    let Some(terrain_config) = terrain_configs.get(&world.terrain_config) else {
+        return;
+    };
+    let Some(particle_definitions) = particle_defs.get(&assets.particle_defs) else {
+        return;
+    };
+
+    let (object_defs, placement) = scene_object_layout(scene_data, terrain_config);
+    let mut diff = SceneObjectDiff::between(&applied.objects, &object_defs);
+    if placement != applied.placement {
+        // A new rotation encoding or yaw offset moves every object.
+        let changed: HashSet<&str> = diff
+            .added
+            .iter()
+            .chain(&diff.moved)
+            .chain(&diff.replaced)
+            .map(|object| object.id.as_str())
+            .collect();
+        let unchanged: Vec<SceneObjectDef> = object_defs
+            .iter()
+            .filter(|object| !changed.contains(object.id.as_str()))
+            .cloned()
+            .collect();
+        diff.moved.extend(unchanged);
+    }
+    if diff.is_empty() {
+        return;
+    }
+
+    // A moved eagle spawn point respawns, as its boid is not a child.
+    let (respawned_boids, moved): (Vec<_>, Vec<_>) = diff
+        .moved
+        .iter()
+        .partition(|object| object.object_type == BOID_SPAWN_OBJECT_TYPE);
+    let despawned: HashSet<&str> = diff
+        .removed
+        .iter()
+        .map(String::as_str)
+        .chain(diff.replaced.iter().map(|object| object.id.as_str()))
+        .chain(respawned_boids.iter().map(|object| object.id.as_str()))
+        .collect();
+    let moved: HashMap<&str, &SceneObjectDef> = moved
+        .into_iter()
+        .map(|object| (object.id.as_str(), object))
+        .collect();
+
+    for (entity, object_id, mut transform, is_scene_object) in &mut placed_objects {
+        if despawned.contains(object_id.0.as_str()) {
+            commands.entity(entity).despawn();
+        } else if let Some(object_def) = moved.get(object_id.0.as_str()).filter(|_| is_scene_object)
+        {
+            *transform = placement.transform(object_def);
+        }
+    }
+
+    for object in diff
+        .added
+        .iter()
+        .chain(&diff.replaced)
+        .chain(respawned_boids.iter().copied())
+    {
+        spawn_scene_object(
+            &mut commands,
+            &asset_server,
+            &mut meshes,
+            &mut materials,
+            &mut model_validation_cache,
+            &mut proxy_assets,
+            object,
+            particle_definitions,
+            &placement,
+        );
+    }
+
+    // Removing the old grid first makes the new one count as added, so door
+    // states already received from the server are applied to it again.
+    commands.remove_resource::<WorldCollision>();
+    commands.insert_resource(WorldCollision::from_scene_objects(
+        &object_defs,
+        placement.rotation_encoding,
+    ));
+
+    info!(
+        "Reloaded scene objects: {} added, {} removed, {} moved, {} respawned",
+        diff.added.len(),
+        diff.removed.len(),
+        moved.len(),
+        diff.replaced.len() + respawned_boids.len()
+    );
+    applied.objects = object_defs;
+    applied.placement = placement;
+}
+
+/// Objects of a placement file, or placeholder login objects when it is
+/// empty, with the placement shared by all of them.
+fn scene_object_layout(
+    scene_data: &SceneObjectsData,
+    terrain_config: &TerrainConfig,
+) -> (Vec<SceneObjectDef>, ScenePlacement) {
     let (object_defs, rotation_encoding, rotation_yaw_offset_degrees) = if scene_data
         .objects
         .is_empty()
@@ -267,35 +488,14 @@ pub fn spawn_scene_objects_when_ready(
         );
     }
 
-    info!("Spawning {} scene objects", object_defs.len());
-    let spawn_started_at = Instant::now();
-
-    // Spawn each object
-    for object in &object_defs {
-        spawn_scene_object(
-            &mut commands,
-            &asset_server,
-            &mut meshes,
-            &mut materials,
-            &mut model_validation_cache,
-            &mut proxy_assets,
-            object,
-            particle_definitions,
-            rotation_encoding,
-            rotation_yaw_offset_degrees,
-            map_max_x,
-            map_max_z,
-            mirror_axis,
-        );
-    }
-
-    // Mark as spawned
-    commands.spawn((SceneObjectsSpawned, RuntimeSceneEntity));
-
-    info!(
-        "Scene objects spawned successfully in {} ms",
-        spawn_started_at.elapsed().as_millis()
-    );
+    let placement = ScenePlacement {
+        rotation_encoding,
+        rotation_yaw_offset_degrees,
+        map_max_x: (terrain_config.size.width.saturating_sub(1) as f32) * terrain_config.size.scale,
+        map_max_z: (terrain_config.size.depth.saturating_sub(1) as f32) * terrain_config.size.scale,
+        mirror_axis: world_mirror_axis(),
+    };
+    (object_defs, placement)
 }
 
 /// Additional distance-based culling on top of Bevy frustum culling.
@@ -361,38 +561,18 @@ fn spawn_scene_object(
     proxy_assets: &mut ProxyAssetCache,
     object_def: &SceneObjectDef,
     particle_defs: &ParticleDefinitions,
-    rotation_encoding: SceneRotationEncoding,
-    rotation_yaw_offset_degrees: f32,
-    map_max_x: f32,
-    map_max_z: f32,
-    mirror_axis: WorldMirrorAxis,
+    placement: &ScenePlacement,
 ) {
-    let position = mirror_map_position_with_axis(
-        Vec3::from(object_def.position),
-        map_max_x,
-        map_max_z,
-        mirror_axis,
-    );
-    let rotation = scene_object_rotation_to_quat(
-        apply_scene_object_yaw_offset(
-            object_def.rotation,
-            rotation_encoding,
-            rotation_yaw_offset_degrees,
-        ),
-        rotation_encoding,
-    );
-    let scale = Vec3::from(object_def.scale);
+    let transform = placement.transform(object_def);
+    let position = transform.translation;
 
     let mut entity_cmd = commands.spawn((
         RuntimeSceneEntity,
         SceneObject,
         SceneObjectKind(object_def.object_type),
+        SceneObjectId(object_def.id.clone()),
         SpatialBundle {
-            transform: Transform {
-                translation: position,
-                rotation,
-                scale,
-            },
+            transform,
             ..default()
         },
     ));
@@ -442,7 +622,9 @@ fn spawn_scene_object(
                 playback_speed: animation_speed,
             }
         });
-        if let Some(source) = animation_source.clone() {
+        // Doors swing when opened instead of looping their clip.
+        let loops_clip = object_def.properties.interaction != Some(PropInteraction::Door);
+        if let Some(source) = animation_source.clone().filter(|_| loops_clip) {
             entity_cmd.insert(source);
         }
         let scene: Handle<Scene> = asset_server.load(resolve_asset_path(&scene_path));
@@ -462,6 +644,16 @@ fn spawn_scene_object(
         );
     }
 
+    if let Some(interaction) = object_def.properties.interaction {
+        entity_cmd.insert(InteractiveProp(interaction));
+    }
+    if let Some(prompt) = object_def.properties.interaction_prompt() {
+        entity_cmd.insert(InteractionPrompt(prompt));
+    }
+    if let Some(door_id) = object_def.properties.door_id {
+        entity_cmd.insert(ReplicatedDoor { door_id });
+    }
+
     // Add particle emitter if specified
     if let Some(emitter_type) = &object_def.properties.particle_emitter {
         if let Some(emitter_def) = particle_defs.emitters.get(emitter_type) {
@@ -479,9 +671,9 @@ fn spawn_scene_object(
         add_dynamic_light(&mut entity_cmd, &object_def.properties, light_color);
     }
 
-    // Add boid spawner if object type is 62 (eagle spawn point)
-    if object_def.object_type == 62 {
-        spawn_boid(commands, position, &object_def.properties);
+    // Add boid spawner at eagle spawn points
+    if object_def.object_type == BOID_SPAWN_OBJECT_TYPE {
+        spawn_boid(commands, position, object_def);
     }
 }
 
@@ -661,11 +853,12 @@ fn add_dynamic_light(
 }
 
 /// Spawn a boid (eagle) at the object location
-fn spawn_boid(commands: &mut Commands, spawn_point: Vec3, properties: &ObjectProperties) {
-    let flight_radius = properties.flight_radius.unwrap_or(30.0);
+fn spawn_boid(commands: &mut Commands, spawn_point: Vec3, object_def: &SceneObjectDef) {
+    let flight_radius = object_def.properties.flight_radius.unwrap_or(30.0);
 
     commands.spawn((
         RuntimeSceneEntity,
+        SceneObjectId(object_def.id.clone()),
         SpatialBundle {
             transform: Transform::from_translation(spawn_point),
             ..default()
@@ -1,6 +1,7 @@
 use crate::bevy_compat::*;
 use crate::infra::assets::resolve_asset_path;
 use crate::scene_runtime::components::*;
+use crate::scene_runtime::vfx_rng::vfx_rng;
 use bevy::image::{ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor};
 use bevy::light::{NotShadowCaster, NotShadowReceiver};
 use bevy::mesh::PrimitiveTopology;
@@ -284,7 +285,7 @@ pub fn update_particle_render_batches(
 }
 
 fn spawn_particle(emitter: &mut ParticleEmitter, position: Vec3) {
-    let mut rng = rand::thread_rng();
+    let mut rng = vfx_rng();
 
     let lifetime = rng.gen_range(emitter.config.lifetime_range.0..=emitter.config.lifetime_range.1);
     let velocity_offset = Vec3::new(
@@ -1,4 +1,5 @@
 use crate::scene_runtime::components::*;
+use crate::scene_runtime::vfx_rng::vfx_rng;
 use bevy::prelude::*;
 use bevy::time::Timer;
 use rand::Rng;
@@ -21,7 +22,7 @@ pub fn update_skill_impact_bursts(
             // Insert a particle emitter with pre-spawned particles for the burst
             let position = transform.translation();
             let mut particles = Vec::with_capacity(burst.burst_count as usize);
-            let mut rng = rand::thread_rng();
+            let mut rng = vfx_rng();
 
             for _ in 0..burst.burst_count {
                 let cfg = &burst.emitter_config;
@@ -0,0 +1,271 @@
+//! Buff and debuff looks, layered over character and monster materials.
+//!
+//! Ice, Ice Arrow and Poison tint the body; the other effects only show as
+//! nameplate icons. A [`StatusEffectVisuals`] on a root entity tints every
+//! mesh below it with its newest tinting effect: each
+//! mesh gets its own copy of its `StandardMaterial`, animated from the
+//! untouched original every frame, and gets the original back once the status
+//! expires.
+
+use bevy::prelude::*;
+use common::BuffEffect;
+use protocol::StatusEffect;
+
+/// Ice shimmer speed (rad/s); slow so it reads as frost, not as a flash.
+const ICE_SHIMMER_RATE: f32 = 1.6;
+const ICE_TINT: LinearRgba = LinearRgba::rgb(0.55, 0.75, 1.0);
+const ICE_GLOW: LinearRgba = LinearRgba::rgb(0.12, 0.3, 0.7);
+/// Poison pulse speed (rad/s).
+const POISON_PULSE_RATE: f32 = 4.5;
+const POISON_TINT: LinearRgba = LinearRgba::rgb(0.5, 1.0, 0.4);
+const POISON_GLOW: LinearRgba = LinearRgba::rgb(0.1, 0.6, 0.05);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ActiveStatus {
+    effect: BuffEffect,
+    remaining_secs: f32,
+}
+
+/// Status effects shown on an entity and everything below it.
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+pub struct StatusEffectVisuals {
+    /// Oldest first; the newest one is drawn.
+    active: Vec<ActiveStatus>,
+}
+
+impl StatusEffectVisuals {
+    /// Replaces the effects with a replicated list.
+    pub fn replace(&mut self, effects: &[StatusEffect]) {
+        self.active = effects
+            .iter()
+            .filter(|status| status.remaining_ms > 0)
+            .filter_map(|status| {
+                Some(ActiveStatus {
+                    effect: BuffEffect::from_id(status.effect_id)?,
+                    remaining_secs: status.remaining_ms as f32 / 1000.0,
+                })
+            })
+            .collect();
+    }
+
+    /// Adds or refreshes one effect, making it the newest.
+    pub fn apply(&mut self, effect: BuffEffect, duration_secs: f32) {
+        self.active.retain(|status| status.effect != effect);
+        self.active.push(ActiveStatus {
+            effect,
+            remaining_secs: duration_secs,
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.active.clear();
+    }
+
+    /// Newest effect that tints the body.
+    pub fn shown(&self) -> Option<BuffEffect> {
+        self.active
+            .iter()
+            .rev()
+            .map(|status| status.effect)
+            .find(|effect| status_tint(*effect, 0.0).is_some())
+    }
+
+    /// Every active effect, oldest first.
+    pub fn effects(&self) -> impl Iterator<Item = BuffEffect> + '_ {
+        self.active.iter().map(|status| status.effect)
+    }
+
+    fn tick(&mut self, dt: f32) {
+        for status in &mut self.active {
+            status.remaining_secs -= dt;
+        }
+        self.active.retain(|status| status.remaining_secs > 0.0);
+    }
+}
+
+/// Mesh drawn with a tinted copy of `original` while its owner has a status.
+#[derive(Component, Debug)]
+pub struct StatusTintedMaterial {
+    owner: Entity,
+    original: Handle<StandardMaterial>,
+}
+
+pub struct StatusEffectsPlugin;
+
+impl Plugin for StatusEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (tick_status_effects, layer_status_materials).chain(),
+        );
+    }
+}
+
+/// Base-colour multiplier and added emissive of an effect at `time` seconds;
+/// `None` for effects that leave the body untouched.
+pub fn status_tint(effect: BuffEffect, time: f32) -> Option<(LinearRgba, LinearRgba)> {
+    match effect {
+        BuffEffect::Ice | BuffEffect::IceArrow => {
+            let shimmer = 0.5 + 0.5 * (time * ICE_SHIMMER_RATE).sin();
+            Some((ICE_TINT, ICE_GLOW * (0.4 + 0.6 * shimmer)))
+        }
+        BuffEffect::Poison => {
+            let pulse = (0.5 + 0.5 * (time * POISON_PULSE_RATE).sin()).powi(2);
+            let tint = LinearRgba::WHITE.mix(&POISON_TINT, 0.6 + 0.4 * pulse);
+            Some((tint, POISON_GLOW * pulse))
+        }
+        _ => None,
+    }
+}
+
+fn tick_status_effects(time: Res<Time>, mut owners: Query<&mut StatusEffectVisuals>) {
+    let dt = time.delta_secs();
+    for mut visuals in &mut owners {
+        if !visuals.active.is_empty() {
+            visuals.tick(dt);
+        }
+    }
+}
+
+fn layer_status_materials(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    owners: Query<(Entity, &StatusEffectVisuals)>,
+    children: Query<&Children>,
+    untinted: Query<&MeshMaterial3d<StandardMaterial>, Without<StatusTintedMaterial>>,
+    tinted: Query<(
+        Entity,
+        &StatusTintedMaterial,
+        &MeshMaterial3d<StandardMaterial>,
+    )>,
+) {
+    for (owner, visuals) in &owners {
+        if visuals.shown().is_none() {
+            continue;
+        }
+        for mesh in children.iter_descendants(owner) {
+            let Ok(material) = untinted.get(mesh) else {
+                continue;
+            };
+            let Some(copy) = materials.get(&material.0).cloned() else {
+                continue;
+            };
+            commands.entity(mesh).insert((
+                MeshMaterial3d(materials.add(copy)),
+                StatusTintedMaterial {
+                    owner,
+                    original: material.0.clone(),
+                },
+            ));
+        }
+    }
+
+    let now = time.elapsed_secs();
+    for (mesh, layer, material) in &tinted {
+        let shown = owners
+            .get(layer.owner)
+            .ok()
+            .and_then(|(_, visuals)| visuals.shown());
+        let Some(effect) = shown else {
+            // Expired or cleared: hand the mesh its own material back.
+            commands
+                .entity(mesh)
+                .insert(MeshMaterial3d(layer.original.clone()))
+                .remove::<StatusTintedMaterial>();
+            continue;
+        };
+
+        let Some(original) = materials.get(&layer.original).cloned() else {
+            continue;
+        };
+        let Some(layered) = materials.get_mut(&material.0) else {
+            continue;
+        };
+        let Some((tint, glow)) = status_tint(effect, now) else {
+            continue;
+        };
+        layered.base_color = Color::from(modulate(original.base_color.to_linear(), tint));
+        layered.emissive = original.emissive + glow;
+    }
+}
+
+fn modulate(color: LinearRgba, tint: LinearRgba) -> LinearRgba {
+    LinearRgba::new(
+        color.red * tint.red,
+        color.green * tint.green,
+        color.blue * tint.blue,
+        color.alpha,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(effect: BuffEffect, remaining_ms: u32) -> StatusEffect {
+        StatusEffect {
+            effect_id: effect.id(),
+            remaining_ms,
+        }
+    }
+
+    #[test]
+    fn newest_status_is_shown_until_it_expires() {
+        let mut visuals = StatusEffectVisuals::default();
+        assert_eq!(visuals.shown(), None);
+
+        visuals.replace(&[
+            status(BuffEffect::Ice, 3_000),
+            status(BuffEffect::Poison, 1_000),
+            // Unknown ids are dropped.
+            StatusEffect {
+                effect_id: 999,
+                remaining_ms: 1_000,
+            },
+        ]);
+        assert_eq!(visuals.shown(), Some(BuffEffect::Poison));
+        assert_eq!(visuals.effects().count(), 2);
+
+        visuals.tick(1.5);
+        assert_eq!(visuals.shown(), Some(BuffEffect::Ice));
+        visuals.apply(BuffEffect::Poison, 0.5);
+        visuals.apply(BuffEffect::Ice, 0.2);
+        assert_eq!(visuals.shown(), Some(BuffEffect::Ice));
+
+        visuals.tick(0.3);
+        assert_eq!(visuals.shown(), Some(BuffEffect::Poison));
+        visuals.tick(1.0);
+        assert_eq!(visuals.shown(), None);
+    }
+
+    #[test]
+    fn buffs_without_a_tint_are_listed_but_not_shown() {
+        let mut visuals = StatusEffectVisuals::default();
+        visuals.apply(BuffEffect::Poison, 5.0);
+        visuals.apply(BuffEffect::SoulBarrier, 5.0);
+        assert_eq!(visuals.shown(), Some(BuffEffect::Poison));
+        assert_eq!(
+            visuals.effects().collect::<Vec<_>>(),
+            vec![BuffEffect::Poison, BuffEffect::SoulBarrier]
+        );
+    }
+
+    #[test]
+    fn tints_stay_in_their_colour_family() {
+        for step in 0..40 {
+            let time = step as f32 * 0.1;
+
+            let (ice, frost) = status_tint(BuffEffect::Ice, time).unwrap();
+            assert!(ice.blue > ice.red && frost.blue > frost.green);
+
+            let (poison, glow) = status_tint(BuffEffect::Poison, time).unwrap();
+            assert!(poison.green >= poison.red && poison.green >= poison.blue);
+            assert!(glow.green >= glow.red && glow.green >= glow.blue);
+        }
+
+        // Poison visibly pulses; ice only shimmers around a steady tint.
+        let glow = |time| status_tint(BuffEffect::Poison, time).unwrap().1.green;
+        assert!(glow(0.35) > 0.5 && glow(1.05) < 0.05);
+    }
+}
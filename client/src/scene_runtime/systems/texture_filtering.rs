@@ -0,0 +1,148 @@
+//! Texture filtering of world and character materials, from the Graficos tab.
+//!
+//! Every texture a `StandardMaterial` samples (terrain, scene objects and
+//! character models) gets the filters of the setting as soon as it loads,
+//! keeping its address modes, and again whenever the setting changes.
+//! Particles, skills and the interface keep the samplers they load with.
+
+use crate::settings::{SettingsResource, TextureFilteringSetting};
+use bevy::image::{ImageFilterMode, ImageSampler, ImageSamplerDescriptor};
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+/// Textures sampled by a `StandardMaterial` so far.
+#[derive(Resource, Default)]
+struct FilteredTextures {
+    images: HashSet<AssetId<Image>>,
+    applied: Option<TextureFilteringSetting>,
+}
+
+pub struct TextureFilteringPlugin;
+
+impl Plugin for TextureFilteringPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FilteredTextures>()
+            .add_systems(Update, apply_texture_filtering);
+    }
+}
+
+/// `descriptor` with the filters of `filtering`; address modes and LOD
+/// clamps are kept.
+pub fn filtered_sampler(
+    descriptor: &ImageSamplerDescriptor,
+    filtering: TextureFilteringSetting,
+) -> ImageSamplerDescriptor {
+    ImageSamplerDescriptor {
+        mag_filter: ImageFilterMode::Linear,
+        min_filter: ImageFilterMode::Linear,
+        mipmap_filter: if filtering.blends_mipmaps() {
+            ImageFilterMode::Linear
+        } else {
+            ImageFilterMode::Nearest
+        },
+        anisotropy_clamp: filtering.anisotropy(),
+        ..descriptor.clone()
+    }
+}
+
+fn material_textures(material: &StandardMaterial) -> impl Iterator<Item = AssetId<Image>> + '_ {
+    [
+        &material.base_color_texture,
+        &material.emissive_texture,
+        &material.normal_map_texture,
+    ]
+    .into_iter()
+    .flatten()
+    .map(Handle::id)
+}
+
+fn apply_texture_filtering(
+    settings: Res<SettingsResource>,
+    mut textures: ResMut<FilteredTextures>,
+    mut material_events: MessageReader<AssetEvent<StandardMaterial>>,
+    mut image_events: MessageReader<AssetEvent<Image>>,
+    materials: Res<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let filtering = settings.current.graphics.texture_filtering;
+    let mut pending = Vec::new();
+
+    for event in material_events.read() {
+        let (AssetEvent::Added { id } | AssetEvent::Modified { id }) = event else {
+            continue;
+        };
+        let Some(material) = materials.get(*id) else {
+            continue;
+        };
+        for texture in material_textures(material) {
+            if textures.images.insert(texture) {
+                pending.push(texture);
+            }
+        }
+    }
+
+    // Images filtered here come back as `Modified`, which is left alone.
+    for event in image_events.read() {
+        match event {
+            AssetEvent::Added { id } | AssetEvent::LoadedWithDependencies { id }
+                if textures.images.contains(id) =>
+            {
+                pending.push(*id);
+            }
+            AssetEvent::Removed { id } => {
+                textures.images.remove(id);
+            }
+            _ => {}
+        }
+    }
+
+    if textures.applied != Some(filtering) {
+        textures.applied = Some(filtering);
+        pending = textures.images.iter().copied().collect();
+    }
+
+    for id in pending {
+        let Some(image) = images.get(id) else {
+            continue;
+        };
+        // Images without a sampler of their own use the `ImagePlugin`
+        // default, which is linear.
+        let current = match &image.sampler {
+            ImageSampler::Descriptor(descriptor) => descriptor.clone(),
+            ImageSampler::Default => ImageSamplerDescriptor::linear(),
+        };
+        let filtered = filtered_sampler(&current, filtering);
+        if image.sampler == ImageSampler::Descriptor(filtered.clone()) {
+            continue;
+        }
+        if let Some(image) = images.get_mut(id) {
+            image.sampler = ImageSampler::Descriptor(filtered);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::image::ImageAddressMode;
+
+    #[test]
+    fn filtering_keeps_address_modes_and_sets_anisotropy() {
+        let terrain = ImageSamplerDescriptor {
+            address_mode_u: ImageAddressMode::Repeat,
+            address_mode_v: ImageAddressMode::Repeat,
+            ..ImageSamplerDescriptor::nearest()
+        };
+
+        let bilinear = filtered_sampler(&terrain, TextureFilteringSetting::Bilinear);
+        assert_eq!(bilinear.min_filter, ImageFilterMode::Linear);
+        assert_eq!(bilinear.mipmap_filter, ImageFilterMode::Nearest);
+        assert_eq!(bilinear.anisotropy_clamp, 1);
+
+        let anisotropic = filtered_sampler(&terrain, TextureFilteringSetting::Anisotropic16x);
+        assert_eq!(anisotropic.address_mode_u, ImageAddressMode::Repeat);
+        assert_eq!(anisotropic.address_mode_v, ImageAddressMode::Repeat);
+        assert_eq!(anisotropic.mipmap_filter, ImageFilterMode::Linear);
+        assert_eq!(anisotropic.anisotropy_clamp, 16);
+    }
+}
@@ -0,0 +1,238 @@
+//! Ground height of the loaded world, shared by every system that needs to
+//! stand something on the terrain.
+
+use crate::scene_runtime::scene_loader::{HeightmapData, TerrainConfig};
+use crate::scene_runtime::world_coordinates::{WorldMirrorAxis, mirror_map_xz_with_axis};
+use bevy::prelude::*;
+
+/// Bisection passes refining a ray hit once the march crosses the ground.
+const RAYCAST_REFINE_PASSES: usize = 8;
+
+/// Height multiplier of `config`; the login scene raises World56 like the
+/// C++ client does.
+fn effective_height_multiplier(world_name: &str, config: &TerrainConfig) -> f32 {
+    if world_name == "world_56" {
+        // C++ WD_55LOGINSCENE loads World56 terrain and applies x3.0 height.
+        3.0
+    } else {
+        config.height_multiplier
+    }
+}
+
+/// Factor turning heightmap samples into world units.
+pub fn terrain_vertical_scale(world_name: &str, config: &TerrainConfig) -> f32 {
+    effective_height_multiplier(world_name, config)
+        * (config.size.scale / config.legacy_terrain_scale.max(1.0))
+}
+
+/// Heightmap of the loaded world in world units, inserted once its terrain
+/// assets finish loading.
+#[derive(Resource, Debug, Clone)]
+pub struct TerrainHeightField {
+    world_name: String,
+    width: usize,
+    depth: usize,
+    /// Heights in world units, row by row along map z.
+    heights: Vec<f32>,
+    cell_size: f32,
+    map_max_x: f32,
+    map_max_z: f32,
+    mirror_axis: WorldMirrorAxis,
+}
+
+impl TerrainHeightField {
+    /// `None` for heightmaps too small to interpolate.
+    pub fn from_heightmap(
+        world_name: &str,
+        heightmap: &HeightmapData,
+        config: &TerrainConfig,
+        mirror_axis: WorldMirrorAxis,
+    ) -> Option<Self> {
+        Self::new(
+            world_name,
+            heightmap,
+            config.size.scale,
+            terrain_vertical_scale(world_name, config),
+            mirror_axis,
+        )
+    }
+
+    fn new(
+        world_name: &str,
+        heightmap: &HeightmapData,
+        cell_size: f32,
+        vertical_scale: f32,
+        mirror_axis: WorldMirrorAxis,
+    ) -> Option<Self> {
+        let width = heightmap.width as usize;
+        let depth = heightmap.height as usize;
+        if width < 2 || depth < 2 || cell_size <= 0.0 {
+            return None;
+        }
+
+        let mut heights = Vec::with_capacity(width * depth);
+        for z in 0..depth {
+            for x in 0..width {
+                heights.push(heightmap.get_height(x, z) * vertical_scale);
+            }
+        }
+        Some(Self {
+            world_name: world_name.to_string(),
+            width,
+            depth,
+            heights,
+            cell_size,
+            map_max_x: (width - 1) as f32 * cell_size,
+            map_max_z: (depth - 1) as f32 * cell_size,
+            mirror_axis,
+        })
+    }
+
+    pub fn world_name(&self) -> &str {
+        &self.world_name
+    }
+
+    /// World size of a heightmap cell, which is also a map tile.
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Largest map-space coordinates, for mirroring positions.
+    pub fn map_max(&self) -> (f32, f32) {
+        (self.map_max_x, self.map_max_z)
+    }
+
+    pub fn mirror_axis(&self) -> WorldMirrorAxis {
+        self.mirror_axis
+    }
+
+    /// Ground height under a world position, interpolated between the four
+    /// nearest samples. Positions off the map take the height of its edge.
+    pub fn height_at(&self, world_x: f32, world_z: f32) -> f32 {
+        let (map_x, map_z) = mirror_map_xz_with_axis(
+            world_x,
+            world_z,
+            self.map_max_x,
+            self.map_max_z,
+            self.mirror_axis,
+        );
+        let grid_x = (map_x / self.cell_size).clamp(0.0, (self.width - 1) as f32);
+        let grid_z = (map_z / self.cell_size).clamp(0.0, (self.depth - 1) as f32);
+
+        let x0 = grid_x.floor() as usize;
+        let z0 = grid_z.floor() as usize;
+        let x1 = (x0 + 1).min(self.width - 1);
+        let z1 = (z0 + 1).min(self.depth - 1);
+        let tx = grid_x - x0 as f32;
+        let tz = grid_z - z0 as f32;
+
+        let h0 = self.sample(x0, z0).lerp(self.sample(x1, z0), tx);
+        let h1 = self.sample(x0, z1).lerp(self.sample(x1, z1), tx);
+        h0.lerp(h1, tz)
+    }
+
+    /// `position` moved onto the ground.
+    pub fn on_ground(&self, position: Vec3) -> Vec3 {
+        Vec3::new(
+            position.x,
+            self.height_at(position.x, position.z),
+            position.z,
+        )
+    }
+
+    /// Upward surface normal under a world position, from the slope across
+    /// one cell on each side.
+    pub fn normal_at(&self, world_x: f32, world_z: f32) -> Vec3 {
+        let step = self.cell_size;
+        let slope_x =
+            self.height_at(world_x - step, world_z) - self.height_at(world_x + step, world_z);
+        let slope_z =
+            self.height_at(world_x, world_z - step) - self.height_at(world_x, world_z + step);
+        Vec3::new(slope_x, 2.0 * step, slope_z).normalize_or(Vec3::Y)
+    }
+
+    /// First point where `ray` meets the ground within `max_distance`.
+    /// Rays starting under the ground hit nothing.
+    pub fn raycast(&self, ray: Ray3d, max_distance: f32) -> Option<Vec3> {
+        let clearance = |distance: f32| {
+            let point = ray.get_point(distance);
+            point.y - self.height_at(point.x, point.z)
+        };
+        if clearance(0.0) < 0.0 {
+            return None;
+        }
+
+        let step = self.cell_size * 0.5;
+        let mut near = 0.0;
+        while near < max_distance {
+            let far = (near + step).min(max_distance);
+            if clearance(far) <= 0.0 {
+                let (mut above, mut below) = (near, far);
+                for _ in 0..RAYCAST_REFINE_PASSES {
+                    let middle = (above + below) * 0.5;
+                    if clearance(middle) > 0.0 {
+                        above = middle;
+                    } else {
+                        below = middle;
+                    }
+                }
+                return Some(self.on_ground(ray.get_point(below)));
+            }
+            near = far;
+        }
+        None
+    }
+
+    fn sample(&self, x: usize, z: usize) -> f32 {
+        self.heights[z * self.width + x]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 3x3 map rising 10 units per cell along map x.
+    fn ramp(mirror_axis: WorldMirrorAxis) -> TerrainHeightField {
+        let heightmap = HeightmapData {
+            width: 3,
+            height: 3,
+            heights: vec![vec![0.0, 5.0, 10.0]; 3],
+        };
+        TerrainHeightField::new("world_1", &heightmap, 100.0, 2.0, mirror_axis).unwrap()
+    }
+
+    #[test]
+    fn heights_interpolate_between_samples_and_clamp_off_the_map() {
+        let field = ramp(WorldMirrorAxis::None);
+        assert_eq!(field.height_at(0.0, 0.0), 0.0);
+        assert_eq!(field.height_at(50.0, 120.0), 5.0);
+        assert_eq!(field.height_at(150.0, 80.0), 15.0);
+        assert_eq!(field.height_at(-500.0, 0.0), 0.0);
+        assert_eq!(field.height_at(900.0, 900.0), 20.0);
+
+        let mirrored = ramp(WorldMirrorAxis::X);
+        assert_eq!(mirrored.height_at(50.0, 120.0), 15.0);
+    }
+
+    #[test]
+    fn normals_lean_away_from_the_slope() {
+        let field = ramp(WorldMirrorAxis::None);
+        let normal = field.normal_at(100.0, 100.0);
+        assert!(normal.x < 0.0 && normal.y > 0.0);
+        assert!(normal.z.abs() < 1e-6);
+        assert!((normal.length() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn rays_hit_the_ground_they_cross() {
+        let field = ramp(WorldMirrorAxis::None);
+        let down = Ray3d::new(Vec3::new(100.0, 500.0, 100.0), Dir3::NEG_Y);
+        let hit = field.raycast(down, 1_000.0).unwrap();
+        assert!((hit - Vec3::new(100.0, 10.0, 100.0)).length() < 0.1);
+
+        assert_eq!(field.raycast(down, 100.0), None);
+        let from_below = Ray3d::new(Vec3::new(100.0, -50.0, 100.0), Dir3::Y);
+        assert_eq!(field.raycast(from_below, 1_000.0), None);
+    }
+}
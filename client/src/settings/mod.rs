@@ -1,19 +1,32 @@
-use crate::infra::assets::set_use_remaster_assets;
-use crate::scene_runtime::systems::{RuntimeSunLight, SceneObjectDistanceCullingConfig};
+use crate::infra::assets::{AssetVariant, set_asset_variant};
+use crate::infra::persistence::paths::client_dirs;
+use crate::scene_runtime::systems::{
+    CameraEffectsConfig, RuntimeSunLight, SceneObjectDistanceCullingConfig,
+};
 use bevy::light::{
     CascadeShadowConfig, CascadeShadowConfigBuilder, DirectionalLightShadowMap,
     ShadowFilteringMethod,
 };
 use bevy::prelude::*;
+use bevy::render::settings::Backends;
 use bevy::window::{MonitorSelection, PresentMode, PrimaryWindow, WindowMode, WindowResolution};
 use bevy::winit::{UpdateMode, WinitSettings};
+use protocol::ItemInstance;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
-pub const SETTINGS_FILE_PATH: &str = "./settings.yaml";
+mod sync;
+
+pub use sync::SettingsSyncPlugin;
+
+/// `settings.yaml` in the client config directory.
+pub fn settings_file_path() -> PathBuf {
+    client_dirs().settings_file()
+}
 
 const RESOLUTION_PRESETS: [ResolutionSetting; 4] = [
     ResolutionSetting {
@@ -109,6 +122,90 @@ impl ShadowQualitySetting {
     }
 }
 
+/// Filtering of world and character textures. Anisotropic filtering keeps
+/// tiled ground sharp at the steep angle the camera looks at it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextureFilteringSetting {
+    Bilinear,
+    Trilinear,
+    Anisotropic2x,
+    Anisotropic4x,
+    Anisotropic8x,
+    Anisotropic16x,
+}
+
+impl Default for TextureFilteringSetting {
+    fn default() -> Self {
+        Self::Anisotropic8x
+    }
+}
+
+impl TextureFilteringSetting {
+    pub const ALL: [Self; 6] = [
+        Self::Bilinear,
+        Self::Trilinear,
+        Self::Anisotropic2x,
+        Self::Anisotropic4x,
+        Self::Anisotropic8x,
+        Self::Anisotropic16x,
+    ];
+
+    /// Samples taken along the direction the texture is stretched; 1 turns
+    /// anisotropic filtering off.
+    pub fn anisotropy(self) -> u16 {
+        match self {
+            Self::Bilinear | Self::Trilinear => 1,
+            Self::Anisotropic2x => 2,
+            Self::Anisotropic4x => 4,
+            Self::Anisotropic8x => 8,
+            Self::Anisotropic16x => 16,
+        }
+    }
+
+    /// Whether mip levels are blended rather than picked.
+    pub fn blends_mipmaps(self) -> bool {
+        self != Self::Bilinear
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Bilinear => "Bilinear",
+            Self::Trilinear => "Trilinear",
+            Self::Anisotropic2x => "Anisotropic 2x",
+            Self::Anisotropic4x => "Anisotropic 4x",
+            Self::Anisotropic8x => "Anisotropic 8x",
+            Self::Anisotropic16x => "Anisotropic 16x",
+        }
+    }
+}
+
+/// Color response of the 3D cameras. `Classic` approximates the palette of
+/// the original fixed-function client; `Standard` compresses highlights.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorProfileSetting {
+    Standard,
+    Classic,
+}
+
+impl Default for ColorProfileSetting {
+    fn default() -> Self {
+        Self::Standard
+    }
+}
+
+impl ColorProfileSetting {
+    pub const ALL: [Self; 2] = [Self::Standard, Self::Classic];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Standard => "Padrao",
+            Self::Classic => "Cores classicas",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum FpsLimitSetting {
@@ -186,6 +283,17 @@ impl RenderDistanceSetting {
         }
     }
 
+    /// Distance past which nameplates of entities other than the target and
+    /// the party are culled; a tile is 100 units.
+    pub fn nameplate_distance(self) -> f32 {
+        match self {
+            Self::Low => 1500.0,
+            Self::Medium => 2500.0,
+            Self::High => 4000.0,
+            Self::Ultra => 6000.0,
+        }
+    }
+
     pub fn label(self) -> &'static str {
         match self {
             Self::Low => "Low",
@@ -196,6 +304,178 @@ impl RenderDistanceSetting {
     }
 }
 
+/// Graphics API the renderer is created with. Needs a restart to apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GpuBackendSetting {
+    Auto,
+    Vulkan,
+    Dx12,
+    Metal,
+}
+
+impl Default for GpuBackendSetting {
+    fn default() -> Self {
+        Self::Auto
+    }
+}
+
+impl GpuBackendSetting {
+    pub const ALL: [Self; 4] = [Self::Auto, Self::Vulkan, Self::Dx12, Self::Metal];
+
+    pub fn next(self) -> Self {
+        match self {
+            Self::Auto => Self::Vulkan,
+            Self::Vulkan => Self::Dx12,
+            Self::Dx12 => Self::Metal,
+            Self::Metal => Self::Auto,
+        }
+    }
+
+    /// Parses the value of the `--gpu-backend` flag.
+    pub fn from_arg(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "vulkan" => Some(Self::Vulkan),
+            "dx12" | "d3d12" => Some(Self::Dx12),
+            "metal" => Some(Self::Metal),
+            _ => None,
+        }
+    }
+
+    /// `None` leaves the choice to wgpu (and the `WGPU_BACKEND` variable).
+    pub fn backends(self) -> Option<Backends> {
+        match self {
+            Self::Auto => None,
+            Self::Vulkan => Some(Backends::VULKAN),
+            Self::Dx12 => Some(Backends::DX12),
+            Self::Metal => Some(Backends::METAL),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Auto => "Auto",
+            Self::Vulkan => "Vulkan",
+            Self::Dx12 => "DirectX 12",
+            Self::Metal => "Metal",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorblindModeSetting {
+    Off,
+    Deuteranopia,
+    Protanopia,
+}
+
+impl Default for ColorblindModeSetting {
+    fn default() -> Self {
+        Self::Off
+    }
+}
+
+impl ColorblindModeSetting {
+    pub const ALL: [Self; 3] = [Self::Off, Self::Deuteranopia, Self::Protanopia];
+
+    pub fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Deuteranopia,
+            Self::Deuteranopia => Self::Protanopia,
+            Self::Protanopia => Self::Off,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::Deuteranopia => "Deuteranopia",
+            Self::Protanopia => "Protanopia",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UiFontSetting {
+    Default,
+    Dyslexic,
+}
+
+impl Default for UiFontSetting {
+    fn default() -> Self {
+        Self::Default
+    }
+}
+
+impl UiFontSetting {
+    pub const ALL: [Self; 2] = [Self::Default, Self::Dyslexic];
+
+    pub fn next(self) -> Self {
+        match self {
+            Self::Default => Self::Dyslexic,
+            Self::Dyslexic => Self::Default,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Default => "Default",
+            Self::Dyslexic => "Dyslexia-friendly",
+        }
+    }
+}
+
+/// Least severe log level written to stdout and the log file. Needs a
+/// restart to apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogVerbositySetting {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Default for LogVerbositySetting {
+    fn default() -> Self {
+        Self::Info
+    }
+}
+
+impl LogVerbositySetting {
+    pub const ALL: [Self; 5] = [
+        Self::Error,
+        Self::Warn,
+        Self::Info,
+        Self::Debug,
+        Self::Trace,
+    ];
+
+    pub fn level(self) -> bevy::log::Level {
+        match self {
+            Self::Error => bevy::log::Level::ERROR,
+            Self::Warn => bevy::log::Level::WARN,
+            Self::Info => bevy::log::Level::INFO,
+            Self::Debug => bevy::log::Level::DEBUG,
+            Self::Trace => bevy::log::Level::TRACE,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Error => "Error",
+            Self::Warn => "Warning",
+            Self::Info => "Info",
+            Self::Debug => "Debug",
+            Self::Trace => "Trace",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ResolutionSetting {
@@ -236,11 +516,18 @@ pub struct GraphicsSettings {
     pub window_mode: WindowModeSetting,
     pub resolution: ResolutionSetting,
     pub shadow_quality: ShadowQualitySetting,
+    pub texture_filtering: TextureFilteringSetting,
+    pub color_profile: ColorProfileSetting,
     pub vsync: bool,
     pub fps_limit: FpsLimitSetting,
     pub render_distance: RenderDistanceSetting,
     pub show_grass: bool,
-    pub use_remaster_assets: bool,
+    /// Asset set the loaders prefer; assets missing from it use the classic files.
+    pub asset_variant: AssetVariant,
+    pub gpu_backend: GpuBackendSetting,
+    /// Part of the adapter name to render on (case-insensitive); `None` lets
+    /// wgpu pick, which usually prefers the integrated GPU on laptops.
+    pub gpu_adapter: Option<String>,
 }
 
 impl Default for GraphicsSettings {
@@ -249,11 +536,15 @@ impl Default for GraphicsSettings {
             window_mode: WindowModeSetting::Windowed,
             resolution: ResolutionSetting::default(),
             shadow_quality: ShadowQualitySetting::Low,
+            texture_filtering: TextureFilteringSetting::default(),
+            color_profile: ColorProfileSetting::default(),
             vsync: true,
             fps_limit: FpsLimitSetting::Default60,
             render_distance: RenderDistanceSetting::Medium,
             show_grass: true,
-            use_remaster_assets: true,
+            asset_variant: AssetVariant::Remaster,
+            gpu_backend: GpuBackendSetting::Auto,
+            gpu_adapter: None,
         }
     }
 }
@@ -263,6 +554,10 @@ impl Default for GraphicsSettings {
 pub struct AudioSettings {
     pub ambient_enabled: bool,
     pub effects_enabled: bool,
+    pub interface_enabled: bool,
+    pub ambient_volume_percent: u8,
+    pub effects_volume_percent: u8,
+    pub interface_volume_percent: u8,
 }
 
 impl Default for AudioSettings {
@@ -270,15 +565,417 @@ impl Default for AudioSettings {
         Self {
             ambient_enabled: true,
             effects_enabled: true,
+            interface_enabled: true,
+            ambient_volume_percent: 100,
+            effects_volume_percent: 100,
+            interface_volume_percent: 80,
+        }
+    }
+}
+
+impl AudioSettings {
+    pub const VOLUME_PERCENT_RANGE: std::ops::RangeInclusive<u8> = 0..=100;
+
+    /// Linear volume of `category`, 0 when the category is turned off.
+    pub fn volume(&self, category: SoundCategory) -> f32 {
+        let (enabled, percent) = match category {
+            SoundCategory::Ambient => (self.ambient_enabled, self.ambient_volume_percent),
+            SoundCategory::Effects => (self.effects_enabled, self.effects_volume_percent),
+            SoundCategory::Interface => (self.interface_enabled, self.interface_volume_percent),
+        };
+        if !enabled {
+            return 0.0;
         }
+        let max = *Self::VOLUME_PERCENT_RANGE.end();
+        f32::from(percent.min(max)) / 100.0
     }
 }
 
+/// Group of sounds sharing a switch and a volume in the Som tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoundCategory {
+    Ambient,
+    Effects,
+    Interface,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    pub colorblind_mode: ColorblindModeSetting,
+    pub ui_font: UiFontSetting,
+    pub ui_scale_percent: u16,
+    pub chat_font_size: u8,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            colorblind_mode: ColorblindModeSetting::Off,
+            ui_font: UiFontSetting::Default,
+            ui_scale_percent: 100,
+            chat_font_size: 14,
+        }
+    }
+}
+
+impl AccessibilitySettings {
+    pub const UI_SCALE_PERCENT_RANGE: std::ops::RangeInclusive<u16> = 75..=200;
+    pub const CHAT_FONT_SIZE_RANGE: std::ops::RangeInclusive<u8> = 10..=28;
+
+    /// UI zoom factor, clamped so a hand-edited settings file cannot break the layout.
+    pub fn ui_scale(&self) -> f32 {
+        let range = Self::UI_SCALE_PERCENT_RANGE;
+        f32::from(self.ui_scale_percent.clamp(*range.start(), *range.end())) / 100.0
+    }
+
+    pub fn chat_font_size(&self) -> f32 {
+        let range = Self::CHAT_FONT_SIZE_RANGE;
+        f32::from(self.chat_font_size.clamp(*range.start(), *range.end()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraSettings {
+    pub smooth_zoom: bool,
+    pub shake_intensity_percent: u8,
+    pub low_health_vignette: bool,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            smooth_zoom: true,
+            shake_intensity_percent: 100,
+            low_health_vignette: true,
+        }
+    }
+}
+
+impl CameraSettings {
+    pub const SHAKE_INTENSITY_PERCENT_RANGE: std::ops::RangeInclusive<u8> = 0..=200;
+
+    /// Impact shake multiplier; 0 turns shake off.
+    pub fn shake_scale(&self) -> f32 {
+        let max = *Self::SHAKE_INTENSITY_PERCENT_RANGE.end();
+        f32::from(self.shake_intensity_percent.min(max)) / 100.0
+    }
+}
+
+/// Low-power mode while the window is out of focus or minimized.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackgroundSettings {
+    /// Caps the frame rate while the window is out of focus; a minimized
+    /// window is always throttled.
+    pub throttle: bool,
+    pub fps: u8,
+    pub pause_particles: bool,
+    pub mute_audio: bool,
+}
+
+impl Default for BackgroundSettings {
+    fn default() -> Self {
+        Self {
+            throttle: true,
+            fps: 10,
+            pause_particles: true,
+            mute_audio: false,
+        }
+    }
+}
+
+impl BackgroundSettings {
+    pub const FPS_RANGE: std::ops::RangeInclusive<u8> = 1..=30;
+
+    pub fn frame_interval(&self) -> Duration {
+        let range = Self::FPS_RANGE;
+        Duration::from_secs_f64(1.0 / f64::from(self.fps.clamp(*range.start(), *range.end())))
+    }
+}
+
+/// Jewels as `(group, index)`: Chaos, Bless, Soul, Life, Creation, Guardian
+/// and Harmony.
+const JEWELS: [(u8, u16); 7] = [
+    (12, 15),
+    (14, 13),
+    (14, 14),
+    (14, 16),
+    (14, 22),
+    (14, 31),
+    (14, 42),
+];
+
+/// Drops the MU Helper walks over to pick up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PickupRules {
+    pub zen: bool,
+    pub jewels: bool,
+    pub excellent: bool,
+    /// Picks any item of at least this level; 0 turns the rule off.
+    pub min_level: u8,
+}
+
+impl Default for PickupRules {
+    fn default() -> Self {
+        Self {
+            zen: true,
+            jewels: true,
+            excellent: true,
+            min_level: 0,
+        }
+    }
+}
+
+impl PickupRules {
+    pub const MIN_LEVEL_RANGE: std::ops::RangeInclusive<u8> = 0..=15;
+
+    pub fn wants(&self, item: &ItemInstance) -> bool {
+        (self.jewels && JEWELS.contains(&(item.group, item.index)))
+            || (self.excellent && item.options.excellent != 0)
+            || (self.min_level > 0 && item.level >= self.min_level)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HelperSettings {
+    /// Skills cast in turn, one per attack; empty uses the plain attack.
+    pub skill_rotation: Vec<u16>,
+    pub attack_interval_ms: u32,
+    /// How far from where it was switched on the helper hunts.
+    pub hunt_range_tiles: u8,
+    pub pickup: PickupRules,
+}
+
+impl Default for HelperSettings {
+    fn default() -> Self {
+        Self {
+            skill_rotation: Vec::new(),
+            attack_interval_ms: 800,
+            hunt_range_tiles: 6,
+            pickup: PickupRules::default(),
+        }
+    }
+}
+
+impl HelperSettings {
+    pub const ATTACK_INTERVAL_MS_RANGE: std::ops::RangeInclusive<u32> = 200..=3_000;
+    pub const HUNT_RANGE_TILES_RANGE: std::ops::RangeInclusive<u8> = 1..=15;
+    pub const MAX_ROTATION_SKILLS: usize = 8;
+
+    pub fn attack_interval_secs(&self) -> f32 {
+        let range = Self::ATTACK_INTERVAL_MS_RANGE;
+        self.attack_interval_ms.clamp(*range.start(), *range.end()) as f32 / 1000.0
+    }
+
+    pub fn hunt_range(&self) -> u16 {
+        let range = Self::HUNT_RANGE_TILES_RANGE;
+        u16::from(self.hunt_range_tiles.clamp(*range.start(), *range.end()))
+    }
+}
+
+/// HUD element that can be moved and scaled in the layout edit mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HudElement {
+    Chat,
+    Minimap,
+    PartyFrames,
+    Hotbar,
+    BuffBar,
+}
+
+impl HudElement {
+    pub const ALL: [Self; 5] = [
+        Self::Chat,
+        Self::Minimap,
+        Self::PartyFrames,
+        Self::Hotbar,
+        Self::BuffBar,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Chat => "Chat",
+            Self::Minimap => "Minimapa",
+            Self::PartyFrames => "Grupo",
+            Self::Hotbar => "Barra de atalhos",
+            Self::BuffBar => "Buffs",
+        }
+    }
+}
+
+/// Placement of one HUD element, relative to where it sits by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HudElementLayout {
+    /// Offset from the default position, in UI points.
+    pub offset_x: i32,
+    pub offset_y: i32,
+    pub scale_percent: u16,
+}
+
+impl Default for HudElementLayout {
+    fn default() -> Self {
+        Self {
+            offset_x: 0,
+            offset_y: 0,
+            scale_percent: 100,
+        }
+    }
+}
+
+impl HudElementLayout {
+    pub const SCALE_PERCENT_RANGE: std::ops::RangeInclusive<u16> = 50..=200;
+
+    pub fn scale(&self) -> f32 {
+        let range = Self::SCALE_PERCENT_RANGE;
+        f32::from(self.scale_percent.clamp(*range.start(), *range.end())) / 100.0
+    }
+}
+
+/// HUD layouts saved from the edit mode, one per screen resolution since a
+/// spot that works at 1920x1080 can fall off a 1280x720 screen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct HudSettings {
+    /// Keyed by `ResolutionSetting::label`; elements left at their default
+    /// placement are not stored.
+    pub layouts: BTreeMap<String, BTreeMap<HudElement, HudElementLayout>>,
+}
+
+impl HudSettings {
+    pub fn element(&self, resolution: ResolutionSetting, element: HudElement) -> HudElementLayout {
+        self.layouts
+            .get(&resolution.label())
+            .and_then(|layout| layout.get(&element))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn set_element(
+        &mut self,
+        resolution: ResolutionSetting,
+        element: HudElement,
+        placement: HudElementLayout,
+    ) {
+        let key = resolution.label();
+        if placement == HudElementLayout::default() {
+            if let Some(layout) = self.layouts.get_mut(&key) {
+                layout.remove(&element);
+                if layout.is_empty() {
+                    self.layouts.remove(&key);
+                }
+            }
+        } else {
+            self.layouts
+                .entry(key)
+                .or_default()
+                .insert(element, placement);
+        }
+    }
+
+    /// Puts every element of `resolution` back where it starts.
+    pub fn reset(&mut self, resolution: ResolutionSetting) {
+        self.layouts.remove(&resolution.label());
+    }
+}
+
+/// First-time hint about a part of the game; see
+/// `presentation::ui::hints`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Hint {
+    Inventory,
+    LevelUpPoint,
+    FirstDeath,
+}
+
+impl Hint {
+    pub const ALL: [Self; 3] = [Self::Inventory, Self::LevelUpPoint, Self::FirstDeath];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Inventory => "Inventario",
+            Self::LevelUpPoint => "Pontos de status",
+            Self::FirstDeath => "Voce morreu",
+        }
+    }
+
+    pub fn text(self) -> &'static str {
+        match self {
+            Self::Inventory => {
+                "Recompensas e itens coletados vao para o inventario. \
+                 Itens com tempo limitado somem quando o tempo acaba."
+            }
+            Self::LevelUpPoint => {
+                "Cada nivel da pontos para distribuir entre Forca, Agilidade, \
+                 Vitalidade e Energia."
+            }
+            Self::FirstDeath => {
+                "Fique longe de monstros acima do seu nivel e abra o registro \
+                 de combate (L) para ver quem causou mais dano."
+            }
+        }
+    }
+}
+
+/// Seen hints travel with the settings, so account sync carries them too.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HintSettings {
+    pub enabled: bool,
+    pub seen: BTreeSet<Hint>,
+}
+
+impl Default for HintSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            seen: BTreeSet::new(),
+        }
+    }
+}
+
+impl HintSettings {
+    pub fn should_show(&self, hint: Hint) -> bool {
+        self.enabled && !self.seen.contains(&hint)
+    }
+}
+
+/// Logging of this machine; `--log-filter` adds per-module filters for one run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct DiagnosticsSettings {
+    pub log_verbosity: LogVerbositySetting,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct SyncSettings {
+    /// Upload saves to the account and download them on login.
+    pub enabled: bool,
+    /// Time of the last local save (ms since the Unix epoch); the newer of the
+    /// local and account copies wins.
+    pub updated_at_ms: u64,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Resource)]
 #[serde(default)]
 pub struct GameSettings {
     pub graphics: GraphicsSettings,
     pub audio: AudioSettings,
+    pub accessibility: AccessibilitySettings,
+    pub camera: CameraSettings,
+    pub background: BackgroundSettings,
+    pub helper: HelperSettings,
+    pub hud: HudSettings,
+    pub hints: HintSettings,
+    pub diagnostics: DiagnosticsSettings,
+    pub sync: SyncSettings,
 }
 
 impl Default for GameSettings {
@@ -286,6 +983,14 @@ impl Default for GameSettings {
         Self {
             graphics: GraphicsSettings::default(),
             audio: AudioSettings::default(),
+            accessibility: AccessibilitySettings::default(),
+            camera: CameraSettings::default(),
+            background: BackgroundSettings::default(),
+            helper: HelperSettings::default(),
+            hud: HudSettings::default(),
+            hints: HintSettings::default(),
+            diagnostics: DiagnosticsSettings::default(),
+            sync: SyncSettings::default(),
         }
     }
 }
@@ -312,11 +1017,30 @@ impl SettingsResource {
     pub fn new(current: GameSettings) -> Self {
         Self {
             current,
-            path: PathBuf::from(SETTINGS_FILE_PATH),
+            path: settings_file_path(),
         }
     }
 
-    pub fn save_to_disk(&self) -> Result<(), SettingsIoError> {
+    /// Stamps the save time and writes the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn save_to_disk(&mut self) -> Result<(), SettingsIoError> {
+        self.current.sync.updated_at_ms = now_ms();
+        write_settings_to_path(&self.current, &self.path)
+    }
+
+    /// Replaces the current settings with a copy downloaded from the account.
+    ///
+    /// Graphics and diagnostics stay as configured on this machine; the
+    /// account copy's save time is kept so it is not uploaded straight back.
+    pub fn adopt_synced(&mut self, synced: GameSettings) -> Result<(), SettingsIoError> {
+        self.current = GameSettings {
+            graphics: self.current.graphics.clone(),
+            diagnostics: self.current.diagnostics.clone(),
+            ..synced
+        };
         write_settings_to_path(&self.current, &self.path)
     }
 }
@@ -325,6 +1049,10 @@ impl SettingsResource {
 pub struct AudioCategoryState {
     pub ambient_enabled: bool,
     pub effects_enabled: bool,
+    /// Category volumes from the settings, applied to sounds as they start.
+    pub audio: AudioSettings,
+    /// Set while the window is in the background with audio muting on.
+    pub background_muted: bool,
 }
 
 impl Default for AudioCategoryState {
@@ -332,6 +1060,19 @@ impl Default for AudioCategoryState {
         Self {
             ambient_enabled: true,
             effects_enabled: true,
+            audio: AudioSettings::default(),
+            background_muted: false,
+        }
+    }
+}
+
+impl AudioCategoryState {
+    /// Volume a new sound of `category` starts at; 0 while muted.
+    pub fn volume(&self, category: SoundCategory) -> f32 {
+        if self.background_muted {
+            0.0
+        } else {
+            self.audio.volume(category)
         }
     }
 }
@@ -341,23 +1082,25 @@ pub struct SettingsPlugin;
 impl Plugin for SettingsPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<AudioCategoryState>()
+            .init_resource::<CameraEffectsConfig>()
             .add_systems(Update, apply_runtime_settings);
     }
 }
 
 pub fn load_settings_or_default() -> GameSettings {
-    let path = Path::new(SETTINGS_FILE_PATH);
+    let path = settings_file_path();
 
     if !path.exists() {
         return GameSettings::default();
     }
 
-    match load_settings_from_path(path) {
+    match load_settings_from_path(&path) {
         Ok(settings) => settings,
         Err(error) => {
             eprintln!(
                 "Failed to load settings from '{}': {}. Falling back to defaults.",
-                SETTINGS_FILE_PATH, error
+                path.display(),
+                error
             );
             GameSettings::default()
         }
@@ -365,12 +1108,12 @@ pub fn load_settings_or_default() -> GameSettings {
 }
 
 pub fn ensure_settings_file_exists(settings: &GameSettings) -> Result<(), SettingsIoError> {
-    let path = Path::new(SETTINGS_FILE_PATH);
+    let path = settings_file_path();
     if path.exists() {
         return Ok(());
     }
 
-    write_settings_to_path(settings, path)
+    write_settings_to_path(settings, &path)
 }
 
 pub fn present_mode_for(graphics: &GraphicsSettings) -> PresentMode {
@@ -383,6 +1126,13 @@ pub fn present_mode_for(graphics: &GraphicsSettings) -> PresentMode {
     }
 }
 
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
 fn load_settings_from_path(path: &Path) -> Result<GameSettings, SettingsIoError> {
     let raw = fs::read_to_string(path).map_err(SettingsIoError::Read)?;
     serde_yaml::from_str::<GameSettings>(&raw).map_err(SettingsIoError::Deserialize)
@@ -390,6 +1140,9 @@ fn load_settings_from_path(path: &Path) -> Result<GameSettings, SettingsIoError>
 
 fn write_settings_to_path(settings: &GameSettings, path: &Path) -> Result<(), SettingsIoError> {
     let encoded = serde_yaml::to_string(settings).map_err(SettingsIoError::Serialize)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(SettingsIoError::Write)?;
+    }
     fs::write(path, encoded).map_err(SettingsIoError::Write)
 }
 
@@ -403,6 +1156,7 @@ fn apply_runtime_settings(
     camera_query: Query<Entity, With<Camera3d>>,
     added_sun_query: Query<(), Added<RuntimeSunLight>>,
     mut audio_categories: ResMut<AudioCategoryState>,
+    mut camera_effects: ResMut<CameraEffectsConfig>,
     mut commands: Commands,
     mut last_applied: Local<Option<GameSettings>>,
 ) {
@@ -427,9 +1181,8 @@ fn apply_runtime_settings(
         window.present_mode = present_mode_for(&settings.current.graphics);
     }
 
-    let update_mode = settings.current.graphics.fps_limit.to_update_mode();
-    winit_settings.focused_mode = update_mode;
-    winit_settings.unfocused_mode = update_mode;
+    // The unfocused mode belongs to the background throttle.
+    winit_settings.focused_mode = settings.current.graphics.fps_limit.to_update_mode();
 
     if let Some(mut culling_config) = culling {
         let max_distance = settings.current.graphics.render_distance.max_distance();
@@ -446,10 +1199,13 @@ fn apply_runtime_settings(
         &mut commands,
     );
 
-    set_use_remaster_assets(settings.current.graphics.use_remaster_assets);
+    set_asset_variant(settings.current.graphics.asset_variant);
 
     audio_categories.ambient_enabled = settings.current.audio.ambient_enabled;
     audio_categories.effects_enabled = settings.current.audio.effects_enabled;
+    audio_categories.audio = settings.current.audio.clone();
+
+    *camera_effects = CameraEffectsConfig::from(&settings.current.camera);
 
     *last_applied = Some(settings.current.clone());
 }
@@ -0,0 +1,185 @@
+//! Optional sync of the settings file through the player's account.
+//!
+//! On entering the world the client asks for the account copy and keeps
+//! whichever of the two was saved last; later saves are uploaded right away.
+
+use super::{GameSettings, SettingsResource};
+use crate::AppState;
+use crate::infra::network::{SendClientMessage, ServerMessageReceived};
+use bevy::prelude::*;
+use bevy::state::prelude::{OnEnter, in_state};
+use protocol::{AccountSettings, ClientMessage, ServerMessage};
+
+#[derive(Resource, Default)]
+struct SettingsSyncState {
+    requested: bool,
+    /// Save time both sides agree on, once the server answered.
+    synced_at_ms: Option<u64>,
+}
+
+pub struct SettingsSyncPlugin;
+
+impl Plugin for SettingsSyncPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SettingsSyncState>()
+            .add_systems(OnEnter(AppState::Gameplay), reset_settings_sync)
+            .add_systems(
+                Update,
+                (apply_account_settings, sync_saved_settings)
+                    .chain()
+                    .run_if(in_state(AppState::Gameplay)),
+            );
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum SyncResolution {
+    Upload,
+    Adopt(GameSettings),
+    InSync,
+}
+
+/// Picks the copy saved last; a local file that was never saved does not
+/// overwrite the account.
+fn resolve_synced_settings(
+    local: &GameSettings,
+    account: Option<&AccountSettings>,
+) -> Result<SyncResolution, serde_yaml::Error> {
+    let local_at_ms = local.sync.updated_at_ms;
+    match account {
+        Some(account) if account.updated_at_ms > local_at_ms => {
+            serde_yaml::from_str(&account.yaml).map(SyncResolution::Adopt)
+        }
+        Some(account) if account.updated_at_ms == local_at_ms => Ok(SyncResolution::InSync),
+        None if local_at_ms == 0 => Ok(SyncResolution::InSync),
+        _ => Ok(SyncResolution::Upload),
+    }
+}
+
+fn reset_settings_sync(mut state: ResMut<SettingsSyncState>) {
+    *state = SettingsSyncState::default();
+}
+
+fn apply_account_settings(
+    mut settings: ResMut<SettingsResource>,
+    mut state: ResMut<SettingsSyncState>,
+    mut incoming: MessageReader<ServerMessageReceived>,
+    mut outgoing: MessageWriter<SendClientMessage>,
+) {
+    for ServerMessageReceived(message) in incoming.read() {
+        let ServerMessage::AccountSettings { settings: account } = message else {
+            continue;
+        };
+
+        match resolve_synced_settings(&settings.current, account.as_ref()) {
+            Ok(SyncResolution::Adopt(synced)) => {
+                info!(
+                    "Using account settings saved at {}",
+                    synced.sync.updated_at_ms
+                );
+                if let Err(error) = settings.adopt_synced(synced) {
+                    warn!("Failed to save synced settings: {error}");
+                }
+            }
+            Ok(SyncResolution::Upload) => upload_settings(&settings.current, &mut outgoing),
+            Ok(SyncResolution::InSync) => {}
+            Err(error) => warn!("Ignoring unreadable account settings: {error}"),
+        }
+        state.synced_at_ms = Some(settings.current.sync.updated_at_ms);
+    }
+}
+
+fn sync_saved_settings(
+    settings: Res<SettingsResource>,
+    mut state: ResMut<SettingsSyncState>,
+    mut outgoing: MessageWriter<SendClientMessage>,
+) {
+    let current = &settings.current;
+    if !current.sync.enabled {
+        return;
+    }
+
+    match state.synced_at_ms {
+        None if !state.requested => {
+            outgoing.write(SendClientMessage(ClientMessage::RequestAccountSettings));
+            state.requested = true;
+        }
+        Some(synced_at_ms) if current.sync.updated_at_ms > synced_at_ms => {
+            upload_settings(current, &mut outgoing);
+            state.synced_at_ms = Some(current.sync.updated_at_ms);
+        }
+        _ => {}
+    }
+}
+
+fn upload_settings(settings: &GameSettings, outgoing: &mut MessageWriter<SendClientMessage>) {
+    match serde_yaml::to_string(settings) {
+        Ok(yaml) if yaml.len() <= AccountSettings::MAX_YAML_BYTES => {
+            outgoing.write(SendClientMessage(ClientMessage::StoreAccountSettings(
+                AccountSettings {
+                    updated_at_ms: settings.sync.updated_at_ms,
+                    yaml,
+                },
+            )));
+        }
+        Ok(yaml) => warn!("Settings file is too large to sync ({} bytes)", yaml.len()),
+        Err(error) => warn!("Failed to encode settings for sync: {error}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved_at(updated_at_ms: u64) -> GameSettings {
+        let mut settings = GameSettings::default();
+        settings.sync.enabled = true;
+        settings.sync.updated_at_ms = updated_at_ms;
+        settings
+    }
+
+    fn account_copy(settings: &GameSettings) -> AccountSettings {
+        AccountSettings {
+            updated_at_ms: settings.sync.updated_at_ms,
+            yaml: serde_yaml::to_string(settings).unwrap(),
+        }
+    }
+
+    #[test]
+    fn newest_save_wins() {
+        let local = saved_at(2_000);
+
+        let mut newer = saved_at(3_000);
+        newer.camera.smooth_zoom = false;
+        assert_eq!(
+            resolve_synced_settings(&local, Some(&account_copy(&newer))).unwrap(),
+            SyncResolution::Adopt(newer)
+        );
+        assert_eq!(
+            resolve_synced_settings(&local, Some(&account_copy(&saved_at(1_000)))).unwrap(),
+            SyncResolution::Upload
+        );
+        assert_eq!(
+            resolve_synced_settings(&local, Some(&account_copy(&local))).unwrap(),
+            SyncResolution::InSync
+        );
+    }
+
+    #[test]
+    fn empty_account_only_receives_saved_settings() {
+        assert_eq!(
+            resolve_synced_settings(&saved_at(2_000), None).unwrap(),
+            SyncResolution::Upload
+        );
+        assert_eq!(
+            resolve_synced_settings(&saved_at(0), None).unwrap(),
+            SyncResolution::InSync
+        );
+
+        let corrupt = AccountSettings {
+            updated_at_ms: 9_000,
+            yaml: "camera: [".to_string(),
+        };
+        assert!(resolve_synced_settings(&saved_at(2_000), Some(&corrupt)).is_err());
+    }
+}
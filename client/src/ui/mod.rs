@@ -1,11 +1,19 @@
 use crate::AppState;
+use crate::app::gpu::{AvailableGpus, GpuAdapter, GpuSelection};
+use crate::gameplay::area_targeting::AreaTargeting;
+use crate::infra::assets::AssetVariant;
+use crate::presentation::ui::hud_layout::{self, HudLayoutEditor};
 use crate::settings::{
-    self, FpsLimitSetting, GameSettings, RenderDistanceSetting, ResolutionSetting,
-    SettingsResource, ShadowQualitySetting, WindowModeSetting,
+    AccessibilitySettings, AudioSettings, BackgroundSettings, CameraSettings, ColorProfileSetting,
+    ColorblindModeSetting, FpsLimitSetting, GameSettings, GpuBackendSetting, HelperSettings, Hint,
+    HudElement, HudSettings, LogVerbositySetting, PickupRules, RenderDistanceSetting,
+    ResolutionSetting, SettingsResource, ShadowQualitySetting, TextureFilteringSetting,
+    UiFontSetting, WindowModeSetting,
 };
 use bevy::camera::ClearColorConfig;
 use bevy::prelude::*;
 use bevy::state::prelude::OnEnter;
+use bevy_egui::input::EguiWantsInput;
 use bevy_egui::{EguiContexts, EguiPrimaryContextPass, EguiTextureHandle, egui};
 
 const LOGIN_BACKGROUND: Color = Color::srgb(0.42, 0.42, 0.42);
@@ -44,10 +52,14 @@ enum SettingsTab {
     #[default]
     Graphics,
     Audio,
+    Accessibility,
+    Camera,
+    Helper,
+    Account,
 }
 
 #[derive(Resource)]
-struct HudUiState {
+pub(crate) struct HudUiState {
     settings_open: bool,
     settings_tab: SettingsTab,
     draft: GameSettings,
@@ -103,14 +115,24 @@ fn sync_world_camera_clear_color(
     }
 }
 
-fn toggle_settings_modal_with_escape(
+pub(crate) fn toggle_settings_modal_with_escape(
     keys: Res<ButtonInput<KeyCode>>,
+    egui_wants_input: Option<Res<EguiWantsInput>>,
+    targeting: Option<Res<AreaTargeting>>,
     settings_resource: Res<SettingsResource>,
     mut hud_state: ResMut<HudUiState>,
 ) {
     if !keys.just_pressed(KeyCode::Escape) {
         return;
     }
+    // Escape leaves a focused text field, e.g. the chat box.
+    if egui_wants_input.is_some_and(|egui| egui.wants_any_keyboard_input()) {
+        return;
+    }
+    // Escape cancels an aimed area skill first.
+    if targeting.is_some_and(|targeting| targeting.aiming().is_some()) {
+        return;
+    }
 
     if !hud_state.settings_open {
         hud_state.draft = settings_resource.current.clone();
@@ -125,6 +147,8 @@ fn draw_hud_egui(
     mut hud_state: ResMut<HudUiState>,
     mut hud_assets: ResMut<HudAssets>,
     mut settings_resource: ResMut<SettingsResource>,
+    mut layout_editor: ResMut<HudLayoutEditor>,
+    gpus: Res<AvailableGpus>,
     app_state: Res<State<AppState>>,
     mut next_state: ResMut<NextState<AppState>>,
     mut theme_initialized: Local<bool>,
@@ -148,12 +172,21 @@ fn draw_hud_egui(
         draw_login_form(&mut hud_state, ctx, &mut next_state);
     }
 
-    draw_bottom_bar(&mut hud_state, &settings_resource, ctx, settings_icon_id);
+    let layout = layout_editor.layout(&settings_resource.current.hud);
+    draw_bottom_bar(
+        &mut hud_state,
+        &settings_resource,
+        layout,
+        ctx,
+        settings_icon_id,
+    );
 
     if hud_state.settings_open {
         draw_settings_modal(
             &mut hud_state,
             &mut settings_resource,
+            &mut layout_editor,
+            &gpus.0,
             app_state.get(),
             &mut next_state,
             ctx,
@@ -218,49 +251,45 @@ fn draw_login_form(
 fn draw_bottom_bar(
     hud_state: &mut HudUiState,
     settings_resource: &SettingsResource,
+    layout: &HudSettings,
     ctx: &egui::Context,
     settings_icon_id: Option<egui::TextureId>,
 ) {
-    egui::TopBottomPanel::bottom("hud_bottom_bar")
-        .resizable(false)
-        .frame(egui::Frame::NONE)
-        .show(ctx, |ui| {
-            ui.add_space(6.0);
-            ui.horizontal_centered(|ui| {
-                egui::Frame::new()
-                    .fill(egui::Color32::from_rgba_unmultiplied(0, 0, 0, 128))
-                    .corner_radius(egui::CornerRadius::same(12))
-                    .inner_margin(egui::Margin::symmetric(12, 10))
-                    .show(ui, |ui| {
-                        ui.horizontal(|ui| {
-                            let open_settings = if let Some(texture_id) = settings_icon_id {
-                                let image = egui::Image::new((texture_id, egui::vec2(18.0, 18.0)));
-                                ui.add(
-                                    egui::Button::image(image)
-                                        .min_size(egui::vec2(42.0, 42.0))
-                                        .frame(true),
-                                )
-                                .clicked()
-                            } else {
-                                ui.add_sized(egui::vec2(42.0, 42.0), egui::Button::new("Menu"))
-                                    .clicked()
-                            };
-
-                            if open_settings {
-                                hud_state.settings_open = true;
-                                hud_state.settings_tab = SettingsTab::Graphics;
-                                hud_state.draft = settings_resource.current.clone();
-                            }
-                        });
-                    });
+    hud_layout::show_element(ctx, layout, HudElement::Hotbar, |ui| {
+        egui::Frame::new()
+            .fill(egui::Color32::from_rgba_unmultiplied(0, 0, 0, 128))
+            .corner_radius(egui::CornerRadius::same(12))
+            .inner_margin(egui::Margin::symmetric(12, 10))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let open_settings = if let Some(texture_id) = settings_icon_id {
+                        let image = egui::Image::new((texture_id, egui::vec2(18.0, 18.0)));
+                        ui.add(
+                            egui::Button::image(image)
+                                .min_size(egui::vec2(42.0, 42.0))
+                                .frame(true),
+                        )
+                        .clicked()
+                    } else {
+                        ui.add_sized(egui::vec2(42.0, 42.0), egui::Button::new("Menu"))
+                            .clicked()
+                    };
+
+                    if open_settings {
+                        hud_state.settings_open = true;
+                        hud_state.settings_tab = SettingsTab::Graphics;
+                        hud_state.draft = settings_resource.current.clone();
+                    }
+                });
             });
-            ui.add_space(8.0);
-        });
+    });
 }
 
 fn draw_settings_modal(
     hud_state: &mut HudUiState,
     settings_resource: &mut SettingsResource,
+    layout_editor: &mut HudLayoutEditor,
+    gpus: &[GpuAdapter],
     app_state: &AppState,
     next_state: &mut ResMut<NextState<AppState>>,
     ctx: &egui::Context,
@@ -270,6 +299,7 @@ fn draw_settings_modal(
     let mut should_apply = false;
     let mut should_close = false;
     let mut should_logout = false;
+    let mut should_edit_layout = false;
 
     egui::Window::new("Settings")
         .open(&mut window_open)
@@ -286,17 +316,41 @@ fn draw_settings_modal(
                     "Grafico",
                 );
                 ui.selectable_value(&mut hud_state.settings_tab, SettingsTab::Audio, "Som");
+                ui.selectable_value(
+                    &mut hud_state.settings_tab,
+                    SettingsTab::Accessibility,
+                    "Acessibilidade",
+                );
+                ui.selectable_value(&mut hud_state.settings_tab, SettingsTab::Camera, "Camera");
+                ui.selectable_value(&mut hud_state.settings_tab, SettingsTab::Helper, "Helper");
+                ui.selectable_value(&mut hud_state.settings_tab, SettingsTab::Account, "Conta");
             });
 
             ui.separator();
 
             match hud_state.settings_tab {
                 SettingsTab::Graphics => {
-                    draw_graphics_settings_tab(ui, &mut hud_state.draft);
+                    draw_graphics_settings_tab(ui, &mut hud_state.draft, gpus);
                 }
                 SettingsTab::Audio => {
                     draw_audio_settings_tab(ui, &mut hud_state.draft);
                 }
+                SettingsTab::Accessibility => {
+                    draw_accessibility_settings_tab(ui, &mut hud_state.draft);
+                    if matches!(app_state, AppState::Gameplay) {
+                        ui.separator();
+                        should_edit_layout = ui.button("Editar layout do HUD").clicked();
+                    }
+                }
+                SettingsTab::Camera => {
+                    draw_camera_settings_tab(ui, &mut hud_state.draft);
+                }
+                SettingsTab::Helper => {
+                    draw_helper_settings_tab(ui, &mut hud_state.draft);
+                }
+                SettingsTab::Account => {
+                    draw_account_settings_tab(ui, &mut hud_state.draft);
+                }
             }
 
             ui.separator();
@@ -317,7 +371,7 @@ fn draw_settings_modal(
         if let Err(error) = settings_resource.save_to_disk() {
             warn!(
                 "Failed to save settings file '{}': {}",
-                settings::SETTINGS_FILE_PATH,
+                settings_resource.path().display(),
                 error
             );
         }
@@ -328,6 +382,11 @@ fn draw_settings_modal(
         hud_state.draft = settings_resource.current.clone();
     }
 
+    if should_edit_layout {
+        window_open = false;
+        layout_editor.unlock(&settings_resource.current.hud);
+    }
+
     if should_logout {
         window_open = false;
         hud_state.draft = settings_resource.current.clone();
@@ -343,7 +402,7 @@ fn draw_settings_modal(
     }
 }
 
-fn draw_graphics_settings_tab(ui: &mut egui::Ui, draft: &mut GameSettings) {
+fn draw_graphics_settings_tab(ui: &mut egui::Ui, draft: &mut GameSettings, gpus: &[GpuAdapter]) {
     egui::ComboBox::from_label("Tipo de janela")
         .selected_text(draft.graphics.window_mode.label())
         .show_ui(ui, |ui| {
@@ -368,6 +427,26 @@ fn draw_graphics_settings_tab(ui: &mut egui::Ui, draft: &mut GameSettings) {
             }
         });
 
+    egui::ComboBox::from_label("Filtragem de texturas")
+        .selected_text(draft.graphics.texture_filtering.label())
+        .show_ui(ui, |ui| {
+            for option in TextureFilteringSetting::ALL {
+                ui.selectable_value(
+                    &mut draft.graphics.texture_filtering,
+                    option,
+                    option.label(),
+                );
+            }
+        });
+
+    egui::ComboBox::from_label("Cores")
+        .selected_text(draft.graphics.color_profile.label())
+        .show_ui(ui, |ui| {
+            for option in ColorProfileSetting::ALL {
+                ui.selectable_value(&mut draft.graphics.color_profile, option, option.label());
+            }
+        });
+
     egui::ComboBox::from_label("Limite de FPS")
         .selected_text(draft.graphics.fps_limit.label())
         .show_ui(ui, |ui| {
@@ -386,13 +465,238 @@ fn draw_graphics_settings_tab(ui: &mut egui::Ui, draft: &mut GameSettings) {
 
     ui.checkbox(&mut draft.graphics.vsync, "VSync");
     ui.checkbox(&mut draft.graphics.show_grass, "Mostrar grama");
+    egui::ComboBox::from_label("Assets (F10)")
+        .selected_text(draft.graphics.asset_variant.label())
+        .show_ui(ui, |ui| {
+            for option in AssetVariant::ALL {
+                ui.selectable_value(&mut draft.graphics.asset_variant, option, option.label());
+            }
+        });
+
+    ui.separator();
+    let background = &mut draft.background;
     ui.checkbox(
-        &mut draft.graphics.use_remaster_assets,
-        "Usar assets remaster (F10)",
+        &mut background.throttle,
+        "Economia de energia em segundo plano",
+    );
+    ui.add_enabled(
+        background.throttle,
+        egui::Slider::new(&mut background.fps, BackgroundSettings::FPS_RANGE)
+            .suffix(" FPS")
+            .text("Limite em segundo plano"),
     );
+    ui.checkbox(
+        &mut background.pause_particles,
+        "Pausar particulas em segundo plano",
+    );
+    ui.checkbox(&mut background.mute_audio, "Silenciar som em segundo plano");
+
+    ui.separator();
+    egui::ComboBox::from_label("API grafica")
+        .selected_text(draft.graphics.gpu_backend.label())
+        .show_ui(ui, |ui| {
+            for option in GpuBackendSetting::ALL {
+                ui.selectable_value(&mut draft.graphics.gpu_backend, option, option.label());
+            }
+        });
+
+    let selection = GpuSelection {
+        backend: draft.graphics.gpu_backend,
+        adapter: None,
+    };
+    egui::ComboBox::from_label("Placa de video")
+        .selected_text(
+            draft
+                .graphics
+                .gpu_adapter
+                .as_deref()
+                .unwrap_or("Automatica"),
+        )
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut draft.graphics.gpu_adapter, None, "Automatica");
+            for adapter in gpus.iter().filter(|adapter| selection.accepts(adapter)) {
+                ui.selectable_value(
+                    &mut draft.graphics.gpu_adapter,
+                    Some(adapter.name.clone()),
+                    adapter.label(),
+                );
+            }
+        });
+    egui::ComboBox::from_label("Nivel de log")
+        .selected_text(draft.diagnostics.log_verbosity.label())
+        .show_ui(ui, |ui| {
+            for option in LogVerbositySetting::ALL {
+                ui.selectable_value(&mut draft.diagnostics.log_verbosity, option, option.label());
+            }
+        });
+    ui.weak("API grafica, placa de video e nivel de log valem a partir do proximo inicio do jogo.");
 }
 
 fn draw_audio_settings_tab(ui: &mut egui::Ui, draft: &mut GameSettings) {
-    ui.checkbox(&mut draft.audio.ambient_enabled, "Som ambiente");
-    ui.checkbox(&mut draft.audio.effects_enabled, "Outros sons");
+    let audio = &mut draft.audio;
+    let range = AudioSettings::VOLUME_PERCENT_RANGE;
+
+    for (enabled, volume, label) in [
+        (
+            &mut audio.ambient_enabled,
+            &mut audio.ambient_volume_percent,
+            "Som ambiente",
+        ),
+        (
+            &mut audio.effects_enabled,
+            &mut audio.effects_volume_percent,
+            "Outros sons",
+        ),
+        (
+            &mut audio.interface_enabled,
+            &mut audio.interface_volume_percent,
+            "Sons da interface",
+        ),
+    ] {
+        ui.horizontal(|ui| {
+            ui.checkbox(enabled, label);
+            ui.add_enabled(
+                *enabled,
+                egui::Slider::new(volume, range.clone()).suffix("%"),
+            );
+        });
+    }
+}
+
+fn draw_accessibility_settings_tab(ui: &mut egui::Ui, draft: &mut GameSettings) {
+    let accessibility = &mut draft.accessibility;
+
+    egui::ComboBox::from_label("Modo daltonico")
+        .selected_text(accessibility.colorblind_mode.label())
+        .show_ui(ui, |ui| {
+            for option in ColorblindModeSetting::ALL {
+                ui.selectable_value(&mut accessibility.colorblind_mode, option, option.label());
+            }
+        });
+
+    egui::ComboBox::from_label("Fonte")
+        .selected_text(accessibility.ui_font.label())
+        .show_ui(ui, |ui| {
+            for option in UiFontSetting::ALL {
+                ui.selectable_value(&mut accessibility.ui_font, option, option.label());
+            }
+        });
+
+    ui.add(
+        egui::Slider::new(
+            &mut accessibility.ui_scale_percent,
+            AccessibilitySettings::UI_SCALE_PERCENT_RANGE,
+        )
+        .suffix("%")
+        .text("Escala da interface"),
+    );
+    ui.add(
+        egui::Slider::new(
+            &mut accessibility.chat_font_size,
+            AccessibilitySettings::CHAT_FONT_SIZE_RANGE,
+        )
+        .text("Fonte do chat"),
+    );
+}
+
+fn draw_camera_settings_tab(ui: &mut egui::Ui, draft: &mut GameSettings) {
+    let camera = &mut draft.camera;
+
+    ui.checkbox(&mut camera.smooth_zoom, "Zoom suave");
+    ui.add(
+        egui::Slider::new(
+            &mut camera.shake_intensity_percent,
+            CameraSettings::SHAKE_INTENSITY_PERCENT_RANGE,
+        )
+        .suffix("%")
+        .text("Tremor de impacto"),
+    );
+    ui.checkbox(
+        &mut camera.low_health_vignette,
+        "Vinheta de vida baixa (batimento)",
+    );
+}
+
+fn draw_helper_settings_tab(ui: &mut egui::Ui, draft: &mut GameSettings) {
+    let helper = &mut draft.helper;
+
+    ui.label("Rotacao de habilidades (uma por ataque)");
+    let mut removed = None;
+    for (slot, skill_id) in helper.skill_rotation.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.label(format!("{}.", slot + 1));
+            ui.add(egui::DragValue::new(skill_id).prefix("Skill "));
+            if ui.small_button("Remover").clicked() {
+                removed = Some(slot);
+            }
+        });
+    }
+    if let Some(slot) = removed {
+        helper.skill_rotation.remove(slot);
+    }
+    if helper.skill_rotation.len() < HelperSettings::MAX_ROTATION_SKILLS
+        && ui.button("Adicionar habilidade").clicked()
+    {
+        helper.skill_rotation.push(0);
+    }
+    if helper.skill_rotation.is_empty() {
+        ui.label(egui::RichText::new("Sem habilidades: usa o ataque normal.").weak());
+    }
+
+    ui.separator();
+    ui.add(
+        egui::Slider::new(
+            &mut helper.attack_interval_ms,
+            HelperSettings::ATTACK_INTERVAL_MS_RANGE,
+        )
+        .suffix(" ms")
+        .text("Intervalo entre ataques"),
+    );
+    ui.add(
+        egui::Slider::new(
+            &mut helper.hunt_range_tiles,
+            HelperSettings::HUNT_RANGE_TILES_RANGE,
+        )
+        .text("Alcance de caca (tiles)"),
+    );
+
+    ui.separator();
+    ui.label("Coletar");
+    let pickup = &mut helper.pickup;
+    ui.checkbox(&mut pickup.zen, "Zen");
+    ui.checkbox(&mut pickup.jewels, "Joias");
+    ui.checkbox(&mut pickup.excellent, "Itens excelentes");
+    ui.add(
+        egui::Slider::new(&mut pickup.min_level, PickupRules::MIN_LEVEL_RANGE)
+            .prefix("+")
+            .text("Itens a partir do nivel (0 = desligado)"),
+    );
+}
+
+fn draw_account_settings_tab(ui: &mut egui::Ui, draft: &mut GameSettings) {
+    ui.checkbox(
+        &mut draft.sync.enabled,
+        "Sincronizar configuracoes com a conta",
+    );
+    ui.label(
+        egui::RichText::new(
+            "Ao entrar no jogo, a copia salva por ultimo (aqui ou na conta) e usada. \
+             As opcoes graficas continuam locais a este computador.",
+        )
+        .weak(),
+    );
+
+    ui.separator();
+    ui.checkbox(&mut draft.hints.enabled, "Mostrar dicas para iniciantes");
+    let seen = draft.hints.seen.len();
+    ui.horizontal(|ui| {
+        ui.label(format!("Dicas vistas: {}/{}", seen, Hint::ALL.len()));
+        if ui
+            .add_enabled(seen > 0, egui::Button::new("Mostrar de novo"))
+            .clicked()
+        {
+            draft.hints.seen.clear();
+            draft.hints.enabled = true;
+        }
+    });
 }
@@ -1,8 +1,9 @@
 use bevy::camera::{ClearColorConfig, PerspectiveProjection, Projection};
-use bevy::core_pipeline::tonemapping::Tonemapping;
 use bevy::light::GlobalAmbientLight;
 use bevy::prelude::*;
-use common::WorldMap;
+use common::{Season, WorldMap};
+
+use crate::scene_runtime::systems::CameraShakeOffset;
 
 /// Represents the current world/map being displayed in the client
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -18,6 +19,18 @@ pub enum WorldId {
 #[derive(Message)]
 pub struct WorldRequest(pub WorldId);
 
+/// Season whose maps the debug world selectors offer (`MU_SEASON=6`); every
+/// map when unset.
+pub fn debug_season() -> Season {
+    match std::env::var("MU_SEASON") {
+        Ok(raw) => raw.parse().unwrap_or_else(|error| {
+            warn!("Ignoring MU_SEASON: {}", error);
+            Season::LATEST
+        }),
+        Err(_) => Season::LATEST,
+    }
+}
+
 #[derive(Message)]
 pub struct WorldReady;
 
@@ -86,13 +99,13 @@ fn setup_world_camera(mut commands: Commands) {
     // 3D Camera for world rendering
     commands.spawn((
         WorldCamera,
+        CameraShakeOffset::default(),
         Camera3d::default(),
         Camera {
             order: 0, // Render 3D world first
             clear_color: ClearColorConfig::Custom(Color::srgb(0.1, 0.1, 0.15)),
             ..Default::default()
         },
-        Tonemapping::None,
         Projection::Perspective(PerspectiveProjection {
             near: 10.0,
             far: 50_000.0,
@@ -0,0 +1,278 @@
+//! What other players need to draw a character: its class and the visible
+//! equipment, with the glow of each piece.
+//!
+//! The server sends an [`Appearance`] with every character that comes into
+//! view and the client builds the model from it, so the encoding is kept
+//! small: a class byte, a bitmask of the worn slots and four bytes per worn
+//! piece. Rings and pendants are not drawn and are left out.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{CharacterClass, EquipSlot, ItemCode};
+
+/// Slots drawn on the character model, in encoding order.
+pub const VISIBLE_SLOTS: [EquipSlot; 9] = [
+    EquipSlot::Weapon,
+    EquipSlot::OffHand,
+    EquipSlot::Helm,
+    EquipSlot::Armor,
+    EquipSlot::Pants,
+    EquipSlot::Gloves,
+    EquipSlot::Boots,
+    EquipSlot::Wings,
+    EquipSlot::Pet,
+];
+
+/// Longest encoded appearance, every visible slot worn.
+pub const MAX_ENCODED_LEN: usize = HEADER_LEN + VISIBLE_SLOTS.len() * ITEM_LEN;
+
+const HEADER_LEN: usize = 3;
+const ITEM_LEN: usize = 4;
+const LEVEL_MASK: u8 = 0x0F;
+const EXCELLENT_FLAG: u8 = 1 << 4;
+const ANCIENT_FLAG: u8 = 1 << 5;
+
+/// A worn piece as others see it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VisibleItem {
+    pub code: ItemCode,
+    /// Enhancement level, +0..+15; higher levels glow.
+    pub level: u8,
+    pub excellent: bool,
+    pub ancient: bool,
+}
+
+impl VisibleItem {
+    pub const fn new(code: ItemCode) -> Self {
+        Self {
+            code,
+            level: 0,
+            excellent: false,
+            ancient: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "Vec<u8>", into = "Vec<u8>")]
+pub struct Appearance {
+    pub class: CharacterClass,
+    equipment: [Option<VisibleItem>; VISIBLE_SLOTS.len()],
+}
+
+impl Appearance {
+    /// A character wearing nothing.
+    pub fn new(class: CharacterClass) -> Self {
+        Self {
+            class,
+            equipment: [None; VISIBLE_SLOTS.len()],
+        }
+    }
+
+    pub fn item(&self, slot: EquipSlot) -> Option<VisibleItem> {
+        slot_index(slot).and_then(|index| self.equipment[index])
+    }
+
+    /// Wears `item` in `slot`, or empties it with `None`. Slots that are not
+    /// drawn are ignored.
+    pub fn set_item(&mut self, slot: EquipSlot, item: Option<VisibleItem>) {
+        if let Some(index) = slot_index(slot) {
+            self.equipment[index] = item;
+        }
+    }
+
+    pub fn with_item(mut self, slot: EquipSlot, item: VisibleItem) -> Self {
+        self.set_item(slot, Some(item));
+        self
+    }
+
+    pub fn wings(&self) -> Option<VisibleItem> {
+        self.item(EquipSlot::Wings)
+    }
+
+    pub fn pet(&self) -> Option<VisibleItem> {
+        self.item(EquipSlot::Pet)
+    }
+
+    /// Worn pieces with their slot, in encoding order.
+    pub fn items(&self) -> impl Iterator<Item = (EquipSlot, VisibleItem)> + '_ {
+        VISIBLE_SLOTS
+            .into_iter()
+            .zip(self.equipment)
+            .filter_map(|(slot, item)| item.map(|item| (slot, item)))
+    }
+
+    /// Class id, worn-slot bitmask (little endian), then per worn slot the
+    /// item group, index (little endian) and a byte with the level in the
+    /// low nibble and the excellent and ancient flags above it.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut worn = 0u16;
+        for (index, item) in self.equipment.iter().enumerate() {
+            if item.is_some() {
+                worn |= 1 << index;
+            }
+        }
+
+        let mut bytes = Vec::with_capacity(MAX_ENCODED_LEN);
+        bytes.push(self.class.class_id());
+        bytes.extend_from_slice(&worn.to_le_bytes());
+        for (_, item) in self.items() {
+            let mut flags = item.level.min(LEVEL_MASK);
+            if item.excellent {
+                flags |= EXCELLENT_FLAG;
+            }
+            if item.ancient {
+                flags |= ANCIENT_FLAG;
+            }
+            bytes.push(item.code.group);
+            bytes.extend_from_slice(&item.code.index.to_le_bytes());
+            bytes.push(flags);
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, AppearanceError> {
+        let (header, mut rest) = bytes
+            .split_first_chunk::<HEADER_LEN>()
+            .ok_or(AppearanceError::Truncated)?;
+        let class = CharacterClass::from_class_id(header[0])
+            .ok_or(AppearanceError::UnknownClass(header[0]))?;
+        let worn = u16::from_le_bytes([header[1], header[2]]);
+        if worn >> VISIBLE_SLOTS.len() != 0 {
+            return Err(AppearanceError::UnknownSlots(worn));
+        }
+
+        let mut appearance = Self::new(class);
+        for (index, item) in appearance.equipment.iter_mut().enumerate() {
+            if worn & (1 << index) == 0 {
+                continue;
+            }
+            let (chunk, tail) = rest
+                .split_first_chunk::<ITEM_LEN>()
+                .ok_or(AppearanceError::Truncated)?;
+            let [group, index_lo, index_hi, flags] = *chunk;
+            *item = Some(VisibleItem {
+                code: ItemCode::new(group, u16::from_le_bytes([index_lo, index_hi])),
+                level: flags & LEVEL_MASK,
+                excellent: flags & EXCELLENT_FLAG != 0,
+                ancient: flags & ANCIENT_FLAG != 0,
+            });
+            rest = tail;
+        }
+        if !rest.is_empty() {
+            return Err(AppearanceError::TrailingBytes(rest.len()));
+        }
+        Ok(appearance)
+    }
+}
+
+fn slot_index(slot: EquipSlot) -> Option<usize> {
+    VISIBLE_SLOTS.iter().position(|visible| *visible == slot)
+}
+
+impl From<Appearance> for Vec<u8> {
+    fn from(appearance: Appearance) -> Self {
+        appearance.encode()
+    }
+}
+
+impl TryFrom<Vec<u8>> for Appearance {
+    type Error = AppearanceError;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::decode(&bytes)
+    }
+}
+
+/// Bytes that are not an encoded [`Appearance`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppearanceError {
+    Truncated,
+    UnknownClass(u8),
+    /// Bits set past the visible slots.
+    UnknownSlots(u16),
+    TrailingBytes(usize),
+}
+
+impl std::fmt::Display for AppearanceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "appearance ends early"),
+            Self::UnknownClass(id) => write!(f, "unknown class id {id}"),
+            Self::UnknownSlots(worn) => write!(f, "unknown slots in mask {worn:#06x}"),
+            Self::TrailingBytes(count) => write!(f, "{count} bytes after the appearance"),
+        }
+    }
+}
+
+impl std::error::Error for AppearanceError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appearances_round_trip_through_their_encoding() {
+        let knight = Appearance::new(CharacterClass::DarkKnight)
+            .with_item(
+                EquipSlot::Weapon,
+                VisibleItem {
+                    level: 13,
+                    excellent: true,
+                    ..VisibleItem::new(ItemCode::new(0, 19))
+                },
+            )
+            .with_item(
+                EquipSlot::Armor,
+                VisibleItem {
+                    ancient: true,
+                    ..VisibleItem::new(ItemCode::new(8, 1))
+                },
+            )
+            .with_item(EquipSlot::Wings, VisibleItem::new(ItemCode::new(12, 5)));
+        let bytes = knight.encode();
+        assert_eq!(bytes.len(), HEADER_LEN + 3 * ITEM_LEN);
+        assert_eq!(&bytes[..HEADER_LEN], &[2, 0b1000_1001, 0]);
+        assert_eq!(
+            &bytes[HEADER_LEN..HEADER_LEN + ITEM_LEN],
+            &[0, 19, 0, 13 | EXCELLENT_FLAG]
+        );
+        assert_eq!(Appearance::decode(&bytes), Ok(knight.clone()));
+        assert_eq!(
+            knight.wings().map(|wings| wings.code),
+            Some(ItemCode::new(12, 5))
+        );
+        assert_eq!(knight.pet(), None);
+
+        let json = serde_json::to_string(&knight).unwrap();
+        assert_eq!(serde_json::from_str::<Appearance>(&json).unwrap(), knight);
+
+        let mut naked = Appearance::new(CharacterClass::FairyElf);
+        naked.set_item(
+            EquipSlot::Ring,
+            Some(VisibleItem::new(ItemCode::new(13, 8))),
+        );
+        assert_eq!(naked.encode(), vec![3, 0, 0]);
+    }
+
+    #[test]
+    fn malformed_appearances_are_rejected() {
+        assert_eq!(Appearance::decode(&[2, 0]), Err(AppearanceError::Truncated));
+        assert_eq!(
+            Appearance::decode(&[9, 0, 0]),
+            Err(AppearanceError::UnknownClass(9))
+        );
+        assert_eq!(
+            Appearance::decode(&[2, 0, 0b10]),
+            Err(AppearanceError::UnknownSlots(0x200))
+        );
+        assert_eq!(
+            Appearance::decode(&[2, 1, 0, 0, 1]),
+            Err(AppearanceError::Truncated)
+        );
+        assert_eq!(
+            Appearance::decode(&[2, 0, 0, 7]),
+            Err(AppearanceError::TrailingBytes(1))
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Buffs and debuffs an entity can be under, shared by the server's effect
+//! timers, the protocol's `EntityStatus` list and the client's icons and tints.
+//!
+//! Ids follow the ViewSkillState numbers of the Season 6 client, so an effect
+//! keeps its id on the wire and in the converted icon sheets.
+
+/// Whether an effect helps or hurts the entity under it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuffKind {
+    Buff,
+    Debuff,
+}
+
+/// What happens when an effect is applied to an entity already under it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuffStacking {
+    /// The timer restarts, keeping the stronger of the two values.
+    Refresh,
+    /// Ignored until the running one ends, so it cannot be chained.
+    Unique,
+}
+
+/// Static metadata of an effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuffInfo {
+    pub kind: BuffKind,
+    /// Duration before the caster's stats scale it, in ms.
+    pub base_duration_ms: u32,
+    pub stacking: BuffStacking,
+}
+
+/// Buff or debuff, by ViewSkillState id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u16)]
+pub enum BuffEffect {
+    /// Elf's Greater Damage: raises attack.
+    GreaterDamage = 1,
+    /// Elf's Greater Defense.
+    GreaterDefense = 2,
+    /// Blessing of the starting-town NPC for low-level characters.
+    ElfBuffer = 3,
+    /// Wizard's Soul Barrier: part of the damage taken is paid with mana.
+    SoulBarrier = 4,
+    /// Dark Lord's Critical Damage.
+    CriticalDamage = 5,
+    /// Elf's Infinity Arrow: arrows are not consumed.
+    InfinityArrow = 6,
+    /// Knight's Greater Fortitude (Swell Life): raises max HP.
+    GreaterFortitude = 8,
+    /// Losing HP over time.
+    Poison = 55,
+    /// Frozen: movement slowed.
+    Ice = 56,
+    /// Elf's Ice Arrow: rooted in place.
+    IceArrow = 57,
+    /// Fire Slash and similar: defense lowered.
+    DefenseReduction = 58,
+    /// Cannot move, attack or cast.
+    Stun = 61,
+    /// Part of the damage taken is dealt back.
+    DamageReflect = 71,
+    /// Asleep until hit or the effect ends.
+    Sleep = 72,
+    /// Attack success rate lowered.
+    Blind = 73,
+    /// Summoner's Berserker: more damage dealt and taken.
+    Berserker = 81,
+}
+
+impl BuffEffect {
+    pub const ALL: [Self; 16] = [
+        Self::GreaterDamage,
+        Self::GreaterDefense,
+        Self::ElfBuffer,
+        Self::SoulBarrier,
+        Self::CriticalDamage,
+        Self::InfinityArrow,
+        Self::GreaterFortitude,
+        Self::Poison,
+        Self::Ice,
+        Self::IceArrow,
+        Self::DefenseReduction,
+        Self::Stun,
+        Self::DamageReflect,
+        Self::Sleep,
+        Self::Blind,
+        Self::Berserker,
+    ];
+
+    /// ViewSkillState id.
+    pub fn id(self) -> u16 {
+        self as u16
+    }
+
+    pub fn from_id(id: u16) -> Option<Self> {
+        Self::ALL.into_iter().find(|effect| effect.id() == id)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::GreaterDamage => "Greater Damage",
+            Self::GreaterDefense => "Greater Defense",
+            Self::ElfBuffer => "Elf Buffer",
+            Self::SoulBarrier => "Soul Barrier",
+            Self::CriticalDamage => "Critical Damage",
+            Self::InfinityArrow => "Infinity Arrow",
+            Self::GreaterFortitude => "Greater Fortitude",
+            Self::Poison => "Poison",
+            Self::Ice => "Ice",
+            Self::IceArrow => "Ice Arrow",
+            Self::DefenseReduction => "Defense Reduction",
+            Self::Stun => "Stun",
+            Self::DamageReflect => "Damage Reflect",
+            Self::Sleep => "Sleep",
+            Self::Blind => "Blind",
+            Self::Berserker => "Berserker",
+        }
+    }
+
+    pub fn info(self) -> BuffInfo {
+        let (kind, base_duration_ms, stacking) = match self {
+            Self::GreaterDamage
+            | Self::GreaterDefense
+            | Self::SoulBarrier
+            | Self::CriticalDamage
+            | Self::GreaterFortitude => (BuffKind::Buff, 60_000, BuffStacking::Refresh),
+            Self::ElfBuffer => (BuffKind::Buff, 1_800_000, BuffStacking::Refresh),
+            Self::InfinityArrow => (BuffKind::Buff, 600_000, BuffStacking::Refresh),
+            Self::DamageReflect | Self::Berserker => {
+                (BuffKind::Buff, 30_000, BuffStacking::Refresh)
+            }
+            Self::Poison => (BuffKind::Debuff, 10_000, BuffStacking::Refresh),
+            Self::Ice => (BuffKind::Debuff, 10_000, BuffStacking::Refresh),
+            Self::IceArrow => (BuffKind::Debuff, 3_000, BuffStacking::Unique),
+            Self::DefenseReduction => (BuffKind::Debuff, 10_000, BuffStacking::Refresh),
+            Self::Stun => (BuffKind::Debuff, 2_000, BuffStacking::Unique),
+            Self::Sleep => (BuffKind::Debuff, 5_000, BuffStacking::Unique),
+            Self::Blind => (BuffKind::Debuff, 10_000, BuffStacking::Refresh),
+        };
+        BuffInfo {
+            kind,
+            base_duration_ms,
+            stacking,
+        }
+    }
+}
+
+impl std::fmt::Display for BuffEffect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_round_trip_and_match_view_skill_state() {
+        for effect in BuffEffect::ALL {
+            assert_eq!(BuffEffect::from_id(effect.id()), Some(effect));
+        }
+        assert_eq!(BuffEffect::SoulBarrier.id(), 4);
+        assert_eq!(BuffEffect::GreaterFortitude.id(), 8);
+        assert_eq!(BuffEffect::Poison.id(), 55);
+        assert_eq!(BuffEffect::Ice.id(), 56);
+        assert_eq!(BuffEffect::from_id(0), None);
+        assert_eq!(BuffEffect::from_id(7), None);
+    }
+
+    #[test]
+    fn crowd_control_cannot_be_chained() {
+        for effect in [BuffEffect::Stun, BuffEffect::Sleep, BuffEffect::IceArrow] {
+            let info = effect.info();
+            assert_eq!(info.kind, BuffKind::Debuff);
+            assert_eq!(info.stacking, BuffStacking::Unique);
+        }
+        assert_eq!(BuffEffect::SoulBarrier.info().kind, BuffKind::Buff);
+        assert_eq!(BuffEffect::Poison.info().stacking, BuffStacking::Refresh);
+    }
+}
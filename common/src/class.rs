@@ -0,0 +1,329 @@
+//! Character classes: ids, body types, class evolutions, and the stats each
+//! class starts with and gains per level.
+//!
+//! Shared by the server, which stores the class name on character documents,
+//! and the client, which picks models and animations by class and body type.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CharacterClass {
+    DarkWizard,
+    DarkKnight,
+    FairyElf,
+    MagicGladiator,
+    DarkLord,
+    Summoner,
+    RageFighter,
+}
+
+/// Skeleton and model set a class is drawn with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BodyType {
+    Male,
+    Elf,
+    Monk,
+}
+
+impl BodyType {
+    /// Suffix of the player model files, e.g. `armor_elf_01.glb`.
+    pub fn slug(self) -> &'static str {
+        match self {
+            BodyType::Male => "male",
+            BodyType::Elf => "elf",
+            BodyType::Monk => "monk",
+        }
+    }
+}
+
+/// Step of a class evolution, unlocked by the class change quests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ClassRank {
+    Base,
+    Second,
+    Third,
+    Fourth,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClassEvolution {
+    pub rank: ClassRank,
+    pub name: &'static str,
+    /// Character level the class change quest asks for.
+    pub required_level: u16,
+    /// Master levels asked for on top; only the fourth class needs them.
+    pub required_master_level: u16,
+}
+
+/// Stats of a freshly created character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StartingStats {
+    pub strength: u32,
+    pub agility: u32,
+    pub vitality: u32,
+    pub energy: u32,
+    /// Leadership; only Dark Lords have it.
+    pub command: u32,
+    pub life: u32,
+    pub mana: u32,
+}
+
+/// What a class gains per level and per stat point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelGains {
+    pub points_per_level: u32,
+    /// Life per level, in tenths.
+    pub life_per_level_tenths: u32,
+    /// Mana per level, in tenths.
+    pub mana_per_level_tenths: u32,
+    pub life_per_vitality: u32,
+    pub mana_per_energy: u32,
+}
+
+const fn evolution(
+    rank: ClassRank,
+    name: &'static str,
+    required_level: u16,
+    required_master_level: u16,
+) -> ClassEvolution {
+    ClassEvolution {
+        rank,
+        name,
+        required_level,
+        required_master_level,
+    }
+}
+
+const DARK_WIZARD: &[ClassEvolution] = &[
+    evolution(ClassRank::Base, "Dark Wizard", 1, 0),
+    evolution(ClassRank::Second, "Soul Master", 150, 0),
+    evolution(ClassRank::Third, "Grand Master", 400, 0),
+    evolution(ClassRank::Fourth, "Soul Wizard", 400, 400),
+];
+
+const DARK_KNIGHT: &[ClassEvolution] = &[
+    evolution(ClassRank::Base, "Dark Knight", 1, 0),
+    evolution(ClassRank::Second, "Blade Knight", 150, 0),
+    evolution(ClassRank::Third, "Blade Master", 400, 0),
+    evolution(ClassRank::Fourth, "Dragon Knight", 400, 400),
+];
+
+const FAIRY_ELF: &[ClassEvolution] = &[
+    evolution(ClassRank::Base, "Fairy Elf", 1, 0),
+    evolution(ClassRank::Second, "Muse Elf", 150, 0),
+    evolution(ClassRank::Third, "High Elf", 400, 0),
+    evolution(ClassRank::Fourth, "Noble Elf", 400, 400),
+];
+
+// Magic Gladiators, Dark Lords and Rage Fighters start at what other classes
+// reach with their second class, so they have none.
+const MAGIC_GLADIATOR: &[ClassEvolution] = &[
+    evolution(ClassRank::Base, "Magic Gladiator", 1, 0),
+    evolution(ClassRank::Third, "Duel Master", 400, 0),
+    evolution(ClassRank::Fourth, "Magic Knight", 400, 400),
+];
+
+const DARK_LORD: &[ClassEvolution] = &[
+    evolution(ClassRank::Base, "Dark Lord", 1, 0),
+    evolution(ClassRank::Third, "Lord Emperor", 400, 0),
+    evolution(ClassRank::Fourth, "Empire Lord", 400, 400),
+];
+
+const SUMMONER: &[ClassEvolution] = &[
+    evolution(ClassRank::Base, "Summoner", 1, 0),
+    evolution(ClassRank::Second, "Bloody Summoner", 150, 0),
+    evolution(ClassRank::Third, "Dimension Master", 400, 0),
+    evolution(ClassRank::Fourth, "Dimension Summoner", 400, 400),
+];
+
+const RAGE_FIGHTER: &[ClassEvolution] = &[
+    evolution(ClassRank::Base, "Rage Fighter", 1, 0),
+    evolution(ClassRank::Third, "Fist Master", 400, 0),
+    evolution(ClassRank::Fourth, "Fist Blazer", 400, 400),
+];
+
+impl CharacterClass {
+    /// Every class, by class id.
+    pub const ALL: [Self; 7] = [
+        Self::DarkWizard,
+        Self::DarkKnight,
+        Self::FairyElf,
+        Self::MagicGladiator,
+        Self::DarkLord,
+        Self::Summoner,
+        Self::RageFighter,
+    ];
+
+    /// Name stored on character documents.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::DarkWizard => "DarkWizard",
+            Self::DarkKnight => "DarkKnight",
+            Self::FairyElf => "FairyElf",
+            Self::MagicGladiator => "MagicGladiator",
+            Self::DarkLord => "DarkLord",
+            Self::Summoner => "Summoner",
+            Self::RageFighter => "RageFighter",
+        }
+    }
+
+    /// Parses a stored name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|class| class.name().eq_ignore_ascii_case(name))
+    }
+
+    /// 1-based class ID matching the C++ CLASS_TYPE enum (+1).
+    /// Used for `_class_{id:02}` equipment file naming.
+    pub fn class_id(self) -> u8 {
+        match self {
+            Self::DarkWizard => 1,
+            Self::DarkKnight => 2,
+            Self::FairyElf => 3,
+            Self::MagicGladiator => 4,
+            Self::DarkLord => 5,
+            Self::Summoner => 6,
+            Self::RageFighter => 7,
+        }
+    }
+
+    pub fn from_class_id(id: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|class| class.class_id() == id)
+    }
+
+    pub fn body_type(self) -> BodyType {
+        match self {
+            Self::DarkKnight | Self::DarkWizard | Self::MagicGladiator | Self::DarkLord => {
+                BodyType::Male
+            }
+            Self::FairyElf | Self::Summoner => BodyType::Elf,
+            Self::RageFighter => BodyType::Monk,
+        }
+    }
+
+    /// Level another character of the account must have reached before the
+    /// class can be created; 0 when it is always available.
+    pub fn creation_level(self) -> u16 {
+        match self {
+            Self::MagicGladiator => 220,
+            Self::DarkLord => 250,
+            Self::RageFighter => 150,
+            _ => 0,
+        }
+    }
+
+    /// Class evolutions, base class first.
+    pub fn evolutions(self) -> &'static [ClassEvolution] {
+        match self {
+            Self::DarkWizard => DARK_WIZARD,
+            Self::DarkKnight => DARK_KNIGHT,
+            Self::FairyElf => FAIRY_ELF,
+            Self::MagicGladiator => MAGIC_GLADIATOR,
+            Self::DarkLord => DARK_LORD,
+            Self::Summoner => SUMMONER,
+            Self::RageFighter => RAGE_FIGHTER,
+        }
+    }
+
+    /// The evolution at `rank`; `None` for ranks the class skips.
+    pub fn evolution(self, rank: ClassRank) -> Option<&'static ClassEvolution> {
+        self.evolutions()
+            .iter()
+            .find(|evolution| evolution.rank == rank)
+    }
+
+    pub fn starting_stats(self) -> StartingStats {
+        let (strength, agility, vitality, energy) = match self {
+            Self::DarkWizard => (18, 18, 15, 30),
+            Self::DarkKnight => (28, 20, 25, 10),
+            Self::FairyElf => (22, 25, 20, 15),
+            Self::MagicGladiator => (26, 26, 26, 16),
+            Self::DarkLord => (26, 20, 20, 15),
+            Self::Summoner => (21, 21, 18, 23),
+            Self::RageFighter => (32, 27, 25, 20),
+        };
+        let (life, mana) = match self {
+            Self::DarkWizard => (60, 60),
+            Self::DarkKnight => (110, 20),
+            Self::FairyElf => (80, 30),
+            Self::MagicGladiator => (110, 60),
+            Self::DarkLord => (90, 40),
+            Self::Summoner => (70, 40),
+            Self::RageFighter => (100, 40),
+        };
+        StartingStats {
+            strength,
+            agility,
+            vitality,
+            energy,
+            command: if self == Self::DarkLord { 25 } else { 0 },
+            life,
+            mana,
+        }
+    }
+
+    pub fn level_gains(self) -> LevelGains {
+        let (life_per_level_tenths, mana_per_level_tenths) = match self {
+            Self::DarkWizard => (10, 20),
+            Self::DarkKnight => (20, 5),
+            Self::FairyElf => (10, 15),
+            Self::MagicGladiator => (10, 10),
+            Self::DarkLord => (15, 10),
+            Self::Summoner => (10, 20),
+            Self::RageFighter => (13, 10),
+        };
+        let (life_per_vitality, mana_per_energy) = match self {
+            Self::DarkKnight => (3, 1),
+            Self::DarkWizard | Self::MagicGladiator | Self::Summoner => (2, 2),
+            Self::FairyElf | Self::DarkLord | Self::RageFighter => (2, 1),
+        };
+        LevelGains {
+            points_per_level: match self {
+                Self::MagicGladiator | Self::DarkLord | Self::RageFighter => 7,
+                _ => 5,
+            },
+            life_per_level_tenths,
+            mana_per_level_tenths,
+            life_per_vitality,
+            mana_per_energy,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn class_names_and_ids_round_trip() {
+        for class in CharacterClass::ALL {
+            assert_eq!(CharacterClass::from_name(class.name()), Some(class));
+            assert_eq!(CharacterClass::from_class_id(class.class_id()), Some(class));
+        }
+        assert_eq!(
+            CharacterClass::from_name("darkknight"),
+            Some(CharacterClass::DarkKnight)
+        );
+        assert_eq!(CharacterClass::from_name("Paladin"), None);
+        assert_eq!(CharacterClass::from_class_id(0), None);
+    }
+
+    #[test]
+    fn evolutions_climb_from_the_base_class() {
+        for class in CharacterClass::ALL {
+            let evolutions = class.evolutions();
+            assert_eq!(evolutions[0].rank, ClassRank::Base);
+            assert!(evolutions.windows(2).all(|pair| pair[0].rank < pair[1].rank
+                && pair[0].required_level <= pair[1].required_level));
+            assert!(class.evolution(ClassRank::Fourth).is_some());
+        }
+        assert_eq!(
+            CharacterClass::DarkKnight
+                .evolution(ClassRank::Second)
+                .map(|evolution| evolution.name),
+            Some("Blade Knight")
+        );
+        assert!(CharacterClass::DarkLord
+            .evolution(ClassRank::Second)
+            .is_none());
+    }
+}
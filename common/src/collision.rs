@@ -0,0 +1,440 @@
+//! Collision footprints of large world objects (fountains, buildings, walls).
+//!
+//! Terrain attributes only flag whole tiles; objects placed on top of walkable
+//! terrain carry their own footprint, emitted per object by the asset converter.
+//! Footprints are rasterized onto the 256x256 tile grid shared by server
+//! walkability checks and client pathfinding. A grid may also carry the
+//! ground height of each tile, from the same heightmap the client renders:
+//! steps steeper than its slope limit are refused, so stairs and ramps stay
+//! walkable while cliffs do not.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::Arc;
+
+pub use crate::worldscale::{HEIGHT_MULTIPLIER, TERRAIN_SIZE, TILE_WORLD_SIZE};
+
+/// Steepest ground a step may climb or descend, as height change per unit
+/// walked: 45 degrees.
+pub const DEFAULT_MAX_SLOPE: f32 = 1.0;
+
+/// Upper bound on tiles expanded by a single path search.
+const MAX_SEARCH_NODES: usize = 4_096;
+
+/// Eight-neighbour steps.
+const STEP_OFFSETS: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+];
+
+/// Ground-plane shape in model units, centred on the object origin.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CollisionShape {
+    Circle {
+        radius: f32,
+    },
+    /// Rectangle along the model axes, rotated with the object's yaw.
+    Box {
+        half_extents: [f32; 2],
+    },
+}
+
+/// A collision shape placed in the world.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CollisionFootprint {
+    /// Object origin on the ground plane (MU x/y, world units).
+    pub position: [f32; 2],
+    #[serde(default)]
+    pub yaw_degrees: f32,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    pub shape: CollisionShape,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+impl CollisionFootprint {
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        let dx = x - self.position[0];
+        let dy = y - self.position[1];
+        match self.shape {
+            CollisionShape::Circle { radius } => {
+                let radius = radius * self.scale;
+                dx * dx + dy * dy <= radius * radius
+            }
+            CollisionShape::Box { half_extents } => {
+                let (sin, cos) = self.yaw_degrees.to_radians().sin_cos();
+                let local_x = dx * cos + dy * sin;
+                let local_y = dy * cos - dx * sin;
+                local_x.abs() <= half_extents[0] * self.scale
+                    && local_y.abs() <= half_extents[1] * self.scale
+            }
+        }
+    }
+
+    /// Radius of a circle enclosing the footprint, in world units.
+    fn bounding_radius(&self) -> f32 {
+        match self.shape {
+            CollisionShape::Circle { radius } => radius * self.scale,
+            CollisionShape::Box { half_extents } => {
+                half_extents[0].hypot(half_extents[1]) * self.scale
+            }
+        }
+    }
+}
+
+/// `collision.json` sidecar written per world by the asset converter.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CollisionMapData {
+    #[serde(default)]
+    pub world: Option<u8>,
+    #[serde(default)]
+    pub footprints: Vec<CollisionFootprint>,
+}
+
+/// `terrain_height.json` written per world by the asset converter: raw
+/// heightmap samples, one row per map y.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TerrainHeightData {
+    pub width: u32,
+    pub height: u32,
+    pub heights: Vec<Vec<f32>>,
+}
+
+impl TerrainHeightData {
+    /// Sample under tile `(x, y)`; 0 past the rows the file has.
+    pub fn sample(&self, x: u16, y: u16) -> f32 {
+        self.heights
+            .get(usize::from(y))
+            .and_then(|row| row.get(usize::from(x)))
+            .copied()
+            .unwrap_or(0.0)
+    }
+}
+
+/// Tiles blocked by object footprints, and the ground height of each tile
+/// when known.
+#[derive(Debug, Clone)]
+pub struct CollisionGrid {
+    blocked: Vec<bool>,
+    /// In world units; shared, since doors copy the grid as they open and
+    /// close.
+    heights: Option<Arc<[f32]>>,
+    max_slope: f32,
+}
+
+impl Default for CollisionGrid {
+    fn default() -> Self {
+        Self::open()
+    }
+}
+
+impl CollisionGrid {
+    /// Grid with every tile walkable.
+    pub fn open() -> Self {
+        let side = usize::from(TERRAIN_SIZE);
+        Self {
+            blocked: vec![false; side * side],
+            heights: None,
+            max_slope: DEFAULT_MAX_SLOPE,
+        }
+    }
+
+    /// Gives every tile its ground height, `terrain` samples scaled by
+    /// [`HEIGHT_MULTIPLIER`], and refuses steps steeper than `max_slope`.
+    pub fn with_heights(mut self, terrain: &TerrainHeightData, max_slope: f32) -> Self {
+        let heights = (0..TERRAIN_SIZE)
+            .flat_map(|y| (0..TERRAIN_SIZE).map(move |x| (x, y)))
+            .map(|(x, y)| terrain.sample(x, y) * HEIGHT_MULTIPLIER)
+            .collect();
+        self.heights = Some(heights);
+        self.max_slope = max_slope;
+        self
+    }
+
+    pub fn has_heights(&self) -> bool {
+        self.heights.is_some()
+    }
+
+    /// Ground height of a tile in world units; 0 without heights or off the
+    /// map.
+    pub fn ground_height(&self, x: u16, y: u16) -> f32 {
+        match (&self.heights, index_of(x, y)) {
+            (Some(heights), Some(index)) => heights[index],
+            _ => 0.0,
+        }
+    }
+
+    /// Blocks every tile whose centre lies inside a footprint.
+    pub fn from_footprints<'a>(
+        footprints: impl IntoIterator<Item = &'a CollisionFootprint>,
+    ) -> Self {
+        let mut grid = Self::open();
+        for footprint in footprints {
+            let reach = footprint.bounding_radius();
+            let min_x = tile_index(footprint.position[0] - reach);
+            let max_x = tile_index(footprint.position[0] + reach);
+            let min_y = tile_index(footprint.position[1] - reach);
+            let max_y = tile_index(footprint.position[1] + reach);
+
+            for y in min_y..=max_y {
+                for x in min_x..=max_x {
+                    let (center_x, center_y) = tile_center(x, y);
+                    if footprint.contains(center_x, center_y) {
+                        grid.block(x, y);
+                    }
+                }
+            }
+        }
+        grid
+    }
+
+    pub fn block(&mut self, x: u16, y: u16) {
+        if let Some(index) = index_of(x, y) {
+            self.blocked[index] = true;
+        }
+    }
+
+    /// Tiles outside the map are blocked.
+    pub fn is_blocked(&self, x: u16, y: u16) -> bool {
+        index_of(x, y).is_none_or(|index| self.blocked[index])
+    }
+
+    pub fn blocked_tiles(&self) -> usize {
+        self.blocked.iter().filter(|blocked| **blocked).count()
+    }
+
+    /// True when the straight 8-direction walk between two tiles stays walkable.
+    pub fn line_is_clear(&self, from: (u16, u16), to: (u16, u16)) -> bool {
+        let (mut x, mut y) = (i32::from(from.0), i32::from(from.1));
+        let (target_x, target_y) = (i32::from(to.0), i32::from(to.1));
+        while (x, y) != (target_x, target_y) {
+            let step = ((target_x - x).signum(), (target_y - y).signum());
+            if !self.can_step((x, y), step) {
+                return false;
+            }
+            x += step.0;
+            y += step.1;
+        }
+        true
+    }
+
+    /// Shortest walkable route (A*), excluding the start tile.
+    ///
+    /// Diagonal steps may not cut the corner of a blocked tile. Returns `None`
+    /// when the goal is blocked or no route of at most `max_steps` exists.
+    pub fn find_path(
+        &self,
+        from: (u16, u16),
+        to: (u16, u16),
+        max_steps: usize,
+    ) -> Option<Vec<(u16, u16)>> {
+        if self.is_blocked(to.0, to.1) {
+            return None;
+        }
+        if from == to {
+            return Some(Vec::new());
+        }
+
+        let start = (i32::from(from.0), i32::from(from.1));
+        let goal = (i32::from(to.0), i32::from(to.1));
+        let heuristic = |tile: (i32, i32)| (tile.0 - goal.0).abs().max((tile.1 - goal.1).abs());
+        if heuristic(start) as usize > max_steps {
+            return None;
+        }
+
+        let mut open = BinaryHeap::new();
+        let mut steps: HashMap<(i32, i32), usize> = HashMap::from([(start, 0)]);
+        let mut came_from: HashMap<(i32, i32), (i32, i32)> = HashMap::new();
+        open.push(Reverse((heuristic(start) as usize, 0usize, start)));
+
+        let mut expanded = 0;
+        while let Some(Reverse((_, cost, tile))) = open.pop() {
+            if tile == goal {
+                let mut path = vec![to_tile(tile)];
+                let mut current = tile;
+                while let Some(previous) = came_from.get(&current) {
+                    if *previous == start {
+                        break;
+                    }
+                    path.push(to_tile(*previous));
+                    current = *previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if cost > steps.get(&tile).copied().unwrap_or(usize::MAX) {
+                continue;
+            }
+
+            expanded += 1;
+            if expanded > MAX_SEARCH_NODES || cost == max_steps {
+                continue;
+            }
+
+            for step in STEP_OFFSETS {
+                if !self.can_step(tile, step) {
+                    continue;
+                }
+                let next = (tile.0 + step.0, tile.1 + step.1);
+                let next_cost = cost + 1;
+                if next_cost < steps.get(&next).copied().unwrap_or(usize::MAX) {
+                    steps.insert(next, next_cost);
+                    came_from.insert(next, tile);
+                    open.push(Reverse((
+                        next_cost + heuristic(next) as usize,
+                        next_cost,
+                        next,
+                    )));
+                }
+            }
+        }
+
+        None
+    }
+
+    fn is_blocked_at(&self, tile: (i32, i32)) -> bool {
+        match (u16::try_from(tile.0), u16::try_from(tile.1)) {
+            (Ok(x), Ok(y)) => self.is_blocked(x, y),
+            _ => true,
+        }
+    }
+
+    fn can_step(&self, from: (i32, i32), step: (i32, i32)) -> bool {
+        let next = (from.0 + step.0, from.1 + step.1);
+        if self.is_blocked_at(next) || self.too_steep(from, next) {
+            return false;
+        }
+        // Diagonals need both orthogonal neighbours open.
+        step.0 == 0
+            || step.1 == 0
+            || (!self.is_blocked_at((from.0 + step.0, from.1))
+                && !self.is_blocked_at((from.0, from.1 + step.1)))
+    }
+
+    fn too_steep(&self, from: (i32, i32), to: (i32, i32)) -> bool {
+        if self.heights.is_none() {
+            return false;
+        }
+        let (from, to) = (to_tile(from), to_tile(to));
+        let rise = (self.ground_height(to.0, to.1) - self.ground_height(from.0, from.1)).abs();
+        let run = if from.0 != to.0 && from.1 != to.1 {
+            TILE_WORLD_SIZE * std::f32::consts::SQRT_2
+        } else {
+            TILE_WORLD_SIZE
+        };
+        rise > run * self.max_slope
+    }
+}
+
+/// Tile containing a world coordinate, clamped to the map.
+pub fn tile_index(world: f32) -> u16 {
+    let max = f32::from(TERRAIN_SIZE - 1);
+    (world / TILE_WORLD_SIZE).floor().clamp(0.0, max) as u16
+}
+
+/// World coordinates of a tile's centre.
+pub fn tile_center(x: u16, y: u16) -> (f32, f32) {
+    (
+        (f32::from(x) + 0.5) * TILE_WORLD_SIZE,
+        (f32::from(y) + 0.5) * TILE_WORLD_SIZE,
+    )
+}
+
+fn index_of(x: u16, y: u16) -> Option<usize> {
+    (x < TERRAIN_SIZE && y < TERRAIN_SIZE)
+        .then(|| usize::from(y) * usize::from(TERRAIN_SIZE) + usize::from(x))
+}
+
+fn to_tile(tile: (i32, i32)) -> (u16, u16) {
+    (tile.0 as u16, tile.1 as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fountain() -> CollisionFootprint {
+        CollisionFootprint {
+            position: [13_050.0, 13_050.0],
+            yaw_degrees: 0.0,
+            scale: 1.0,
+            shape: CollisionShape::Circle { radius: 220.0 },
+        }
+    }
+
+    #[test]
+    fn footprints_block_covered_tiles() {
+        let grid = CollisionGrid::from_footprints(&[fountain()]);
+        assert!(grid.is_blocked(130, 130));
+        assert!(grid.is_blocked(132, 130));
+        assert!(!grid.is_blocked(133, 130));
+        assert!(!grid.is_blocked(128, 128));
+        assert!(grid.is_blocked(TERRAIN_SIZE, 0));
+
+        let wall = CollisionFootprint {
+            position: [1_000.0, 1_000.0],
+            yaw_degrees: 90.0,
+            scale: 2.0,
+            shape: CollisionShape::Box {
+                half_extents: [200.0, 50.0],
+            },
+        };
+        let grid = CollisionGrid::from_footprints(&[wall]);
+        // Rotated a quarter turn the wall runs along y.
+        assert!(grid.is_blocked(9, 6));
+        assert!(grid.is_blocked(9, 13));
+        assert!(!grid.is_blocked(6, 9));
+    }
+
+    #[test]
+    fn paths_route_around_footprints() {
+        let grid = CollisionGrid::from_footprints(&[fountain()]);
+        assert!(!grid.line_is_clear((126, 130), (136, 130)));
+
+        let path = grid.find_path((126, 130), (136, 130), 30).unwrap();
+        assert_eq!(path.last(), Some(&(136, 130)));
+        assert!(path.iter().all(|(x, y)| !grid.is_blocked(*x, *y)));
+        // Diagonal steps sidestep the basin without lengthening the walk.
+        assert_eq!(path.len(), 10);
+        assert!(path.iter().any(|(_, y)| *y != 130));
+
+        assert_eq!(grid.find_path((126, 130), (130, 130), 30), None);
+        assert_eq!(grid.find_path((126, 130), (136, 130), 9), None);
+        assert_eq!(grid.find_path((5, 5), (5, 5), 0), Some(Vec::new()));
+    }
+
+    #[test]
+    fn steep_steps_are_refused() {
+        // A gentle ramp rising along x, then a cliff at x = 12.
+        let heights = (0..20)
+            .map(|_| {
+                (0..20u16)
+                    .map(|x| if x < 12 { f32::from(x) * 40.0 } else { 1_000.0 })
+                    .collect()
+            })
+            .collect();
+        let terrain = TerrainHeightData {
+            width: 20,
+            height: 20,
+            heights,
+        };
+        let grid = CollisionGrid::open().with_heights(&terrain, DEFAULT_MAX_SLOPE);
+        assert_eq!(grid.ground_height(2, 5), 120.0);
+        assert_eq!(grid.ground_height(100, 100), 0.0);
+
+        assert!(grid.line_is_clear((0, 5), (11, 5)));
+        assert!(!grid.line_is_clear((11, 5), (12, 5)));
+        assert_eq!(grid.find_path((5, 5), (14, 5), 40), None);
+    }
+}
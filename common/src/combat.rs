@@ -0,0 +1,210 @@
+//! Combat formulas: character stats by class, level and gear, and the damage
+//! one side deals the other.
+//!
+//! Characters are modelled with a reference build that puts half of the
+//! level-up points into the class's damage stat and a quarter each into
+//! vitality and agility. Monsters scale with their level the way the classic
+//! MU monster tables do.
+
+use crate::class::CharacterClass;
+
+/// Highest character level.
+pub const MAX_LEVEL: u16 = 400;
+/// Highest gear tier, a full set of +15 items.
+pub const MAX_GEAR_TIER: u8 = 15;
+
+/// Fastest attack interval, however high agility goes.
+const MIN_ATTACK_INTERVAL_MS: u32 = 250;
+const BASE_ATTACK_INTERVAL_MS: u32 = 1_000;
+/// Agility that halves the attack interval.
+const AGILITY_PER_HALVED_INTERVAL: u32 = 500;
+/// Share of the attack that always lands, in tenths, however high the defense.
+const MIN_DAMAGE_TENTHS: u32 = 1;
+
+/// Wizards and summoners hit with energy, elves with agility, the rest with
+/// strength.
+fn damage_stat(class: CharacterClass, stats: &Attributes) -> u32 {
+    match class {
+        CharacterClass::DarkWizard | CharacterClass::Summoner => stats.energy,
+        CharacterClass::FairyElf => stats.agility,
+        _ => stats.strength,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Attributes {
+    pub strength: u32,
+    pub agility: u32,
+    pub vitality: u32,
+    pub energy: u32,
+}
+
+impl Attributes {
+    /// Reference build of `class` at `level`.
+    pub fn reference(class: CharacterClass, level: u16) -> Self {
+        let start = class.starting_stats();
+        let points =
+            class.level_gains().points_per_level * u32::from(level.clamp(1, MAX_LEVEL) - 1);
+        let mut stats = Self {
+            strength: start.strength,
+            agility: start.agility + points / 4,
+            vitality: start.vitality + points / 4,
+            energy: start.energy,
+        };
+        let damage_points = points - 2 * (points / 4);
+        match class {
+            CharacterClass::DarkWizard | CharacterClass::Summoner => stats.energy += damage_points,
+            CharacterClass::FairyElf => stats.agility += damage_points,
+            _ => stats.strength += damage_points,
+        }
+        stats
+    }
+}
+
+/// Weapon damage and armor defense of a full set at a tier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Equipment {
+    pub weapon_damage: u32,
+    pub armor_defense: u32,
+}
+
+impl Equipment {
+    /// Set of `tier` items; tier 0 is bare-handed. Item levels add a growing
+    /// bonus on top of the base item, as upgrades past +9 do.
+    pub fn of_tier(tier: u8) -> Self {
+        let tier = u32::from(tier.min(MAX_GEAR_TIER));
+        Self {
+            weapon_damage: 12 * tier + tier * tier,
+            armor_defense: 8 * tier + tier * tier / 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CombatStats {
+    pub max_hp: u32,
+    pub min_damage: u32,
+    pub max_damage: u32,
+    pub defense: u32,
+    pub attack_interval_ms: u32,
+}
+
+impl CombatStats {
+    /// Character of the reference build.
+    pub fn character(class: CharacterClass, level: u16, equipment: Equipment) -> Self {
+        let start = class.starting_stats();
+        let gains = class.level_gains();
+        let level = u32::from(level.clamp(1, MAX_LEVEL));
+        let attributes = Attributes::reference(class, level as u16);
+        let damage_stat = damage_stat(class, &attributes);
+
+        Self {
+            max_hp: start.life
+                + gains.life_per_level_tenths * (level - 1) / 10
+                + gains.life_per_vitality * (attributes.vitality - start.vitality),
+            min_damage: damage_stat / 8 + equipment.weapon_damage,
+            max_damage: damage_stat / 4 + equipment.weapon_damage,
+            defense: attributes.agility / 4 + equipment.armor_defense,
+            attack_interval_ms: attack_interval_ms(attributes.agility),
+        }
+    }
+
+    /// Monster of `level`.
+    pub fn monster(level: u16) -> Self {
+        let level = u32::from(level.max(1));
+        Self {
+            max_hp: level * level + 10 * level,
+            min_damage: 2 * level - level / 2,
+            max_damage: 2 * level + level / 2,
+            defense: level / 2,
+            attack_interval_ms: BASE_ATTACK_INTERVAL_MS,
+        }
+    }
+
+    pub fn average_damage(&self) -> u32 {
+        (self.min_damage + self.max_damage) / 2
+    }
+
+    /// Hits of average damage that bring `target` down.
+    pub fn hits_to_kill(&self, target: &CombatStats) -> u32 {
+        let per_hit = hit_damage(self.average_damage(), target.defense);
+        target.max_hp.div_ceil(per_hit)
+    }
+
+    /// Time to bring `target` down; the first hit lands at once.
+    pub fn time_to_kill_ms(&self, target: &CombatStats) -> u64 {
+        u64::from(self.hits_to_kill(target) - 1) * u64::from(self.attack_interval_ms)
+    }
+
+    /// Raw damage of `attacker` absorbed before going down: HP scaled by how
+    /// much of each hit the defense stops.
+    pub fn effective_hp(&self, attacker: &CombatStats) -> u64 {
+        let raw = attacker.average_damage().max(1);
+        let taken = hit_damage(raw, self.defense);
+        u64::from(self.max_hp) * u64::from(raw) / u64::from(taken)
+    }
+}
+
+/// Damage a hit of `attack` deals through `defense`. A tenth of the attack
+/// always lands, and at least one point.
+pub fn hit_damage(attack: u32, defense: u32) -> u32 {
+    attack
+        .saturating_sub(defense)
+        .max(attack * MIN_DAMAGE_TENTHS / 10)
+        .max(1)
+}
+
+/// Attack interval at `agility`: halved every `AGILITY_PER_HALVED_INTERVAL`
+/// points, down to the floor.
+pub fn attack_interval_ms(agility: u32) -> u32 {
+    (BASE_ATTACK_INTERVAL_MS * AGILITY_PER_HALVED_INTERVAL
+        / (AGILITY_PER_HALVED_INTERVAL + agility))
+        .max(MIN_ATTACK_INTERVAL_MS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reference_build_spends_every_point() {
+        let first = Attributes::reference(CharacterClass::DarkKnight, 1);
+        let at_100 = Attributes::reference(CharacterClass::DarkKnight, 100);
+        let total = |a: Attributes| a.strength + a.agility + a.vitality + a.energy;
+        assert_eq!(total(at_100) - total(first), 5 * 99);
+        assert!(at_100.strength - first.strength > at_100.vitality - first.vitality);
+
+        let wizard = Attributes::reference(CharacterClass::DarkWizard, 100);
+        assert!(wizard.energy > wizard.strength);
+    }
+
+    #[test]
+    fn defense_never_stops_a_hit_entirely() {
+        assert_eq!(hit_damage(100, 30), 70);
+        assert_eq!(hit_damage(100, 500), 10);
+        assert_eq!(hit_damage(3, 500), 1);
+    }
+
+    #[test]
+    fn gear_and_levels_shorten_kills() {
+        let monster = CombatStats::monster(50);
+        let bare = CombatStats::character(CharacterClass::DarkKnight, 50, Equipment::default());
+        let geared = CombatStats::character(CharacterClass::DarkKnight, 50, Equipment::of_tier(7));
+        assert!(geared.time_to_kill_ms(&monster) < bare.time_to_kill_ms(&monster));
+        assert!(geared.effective_hp(&monster) > bare.effective_hp(&monster));
+
+        // One hit that kills takes no time.
+        let weak = CombatStats::monster(1);
+        let strong =
+            CombatStats::character(CharacterClass::DarkKnight, 400, Equipment::of_tier(15));
+        assert_eq!(strong.hits_to_kill(&weak), 1);
+        assert_eq!(strong.time_to_kill_ms(&weak), 0);
+    }
+
+    #[test]
+    fn agility_speeds_attacks_up_to_the_floor() {
+        assert_eq!(attack_interval_ms(0), BASE_ATTACK_INTERVAL_MS);
+        assert_eq!(attack_interval_ms(AGILITY_PER_HALVED_INTERVAL), 500);
+        assert_eq!(attack_interval_ms(100_000), MIN_ATTACK_INTERVAL_MS);
+    }
+}
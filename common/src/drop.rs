@@ -0,0 +1,376 @@
+//! Drop tables shared by the server's monster kills and the tooling that lints
+//! custom server configs.
+//!
+//! A table is a list of named groups. When a monster of a group dies, one roll
+//! decides whether it drops an item, zen or nothing; the item is then picked
+//! by weight among the group's entries and rolls its level and options.
+//! Chances are in thousandths, like the bestiary's `chance_per_mille`.
+
+use crate::item::{item_definition, ItemCode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Highest enhancement level an item drops with.
+pub const MAX_DROP_LEVEL: u8 = 15;
+
+/// Chances are out of this many.
+pub const PER_MILLE: u16 = 1000;
+
+/// Named set of drop groups, as loaded from a config file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DropTable {
+    #[serde(default)]
+    pub groups: Vec<DropGroup>,
+}
+
+/// What one kind of kill can drop.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DropGroup {
+    pub name: String,
+    /// Chance that a kill drops one of `entries`.
+    #[serde(default)]
+    pub item_per_mille: u16,
+    /// Chance that a kill without an item drops zen.
+    #[serde(default)]
+    pub zen_per_mille: u16,
+    #[serde(default)]
+    pub zen: ZenRange,
+    /// Chances of a dropped item rolling each option; items that take no
+    /// options never roll them.
+    #[serde(default)]
+    pub excellent_per_mille: u16,
+    #[serde(default)]
+    pub luck_per_mille: u16,
+    #[serde(default)]
+    pub skill_per_mille: u16,
+    #[serde(default)]
+    pub entries: Vec<DropEntry>,
+}
+
+/// Item of a group, picked in proportion to its weight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DropEntry {
+    pub group: u8,
+    pub index: u16,
+    pub weight: u32,
+    #[serde(default)]
+    pub min_level: u8,
+    #[serde(default)]
+    pub max_level: u8,
+}
+
+/// Inclusive amount of zen a kill drops.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ZenRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+/// Outcome of one kill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RolledDrop {
+    Zen(u32),
+    Item {
+        code: ItemCode,
+        level: u8,
+        excellent: bool,
+        luck: bool,
+        skill: bool,
+    },
+}
+
+impl DropEntry {
+    pub fn code(&self) -> ItemCode {
+        ItemCode::new(self.group, self.index)
+    }
+}
+
+impl DropGroup {
+    pub fn total_weight(&self) -> u64 {
+        self.entries
+            .iter()
+            .map(|entry| u64::from(entry.weight))
+            .sum()
+    }
+
+    /// Entry that `roll`, in `0..total_weight()`, lands on.
+    pub fn pick(&self, roll: u64) -> Option<&DropEntry> {
+        let mut left = roll;
+        self.entries.iter().find(|entry| {
+            let weight = u64::from(entry.weight);
+            if left < weight {
+                true
+            } else {
+                left -= weight;
+                false
+            }
+        })
+    }
+
+    /// Rolls one kill. `rng(n)` must return a value in `0..n`.
+    pub fn roll(&self, rng: &mut impl FnMut(u64) -> u64) -> Option<RolledDrop> {
+        if chance(rng, self.item_per_mille) {
+            let total = self.total_weight();
+            if total > 0 {
+                return self
+                    .pick(rng(total))
+                    .map(|entry| self.roll_item(entry, rng));
+            }
+        }
+        if chance(rng, self.zen_per_mille) {
+            let ZenRange { min, max } = self.zen;
+            let span = u64::from(max.saturating_sub(min)) + 1;
+            return Some(RolledDrop::Zen(min + rng(span) as u32));
+        }
+        None
+    }
+
+    fn roll_item(&self, entry: &DropEntry, rng: &mut impl FnMut(u64) -> u64) -> RolledDrop {
+        let code = entry.code();
+        let max_level = entry.max_level.max(entry.min_level);
+        let level = entry.min_level + rng(u64::from(max_level - entry.min_level) + 1) as u8;
+        let takes_options = item_definition(code).is_some_and(|item| item.takes_options());
+        let mut option = |per_mille: u16| takes_options && chance(rng, per_mille);
+        RolledDrop::Item {
+            code,
+            level,
+            excellent: option(self.excellent_per_mille),
+            luck: option(self.luck_per_mille),
+            skill: option(self.skill_per_mille),
+        }
+    }
+}
+
+fn chance(rng: &mut impl FnMut(u64) -> u64, per_mille: u16) -> bool {
+    rng(u64::from(PER_MILLE)) < u64::from(per_mille)
+}
+
+impl DropTable {
+    pub fn group(&self, name: &str) -> Option<&DropGroup> {
+        self.groups.iter().find(|group| group.name == name)
+    }
+
+    /// Parses a table from TOML, as the server's config files are written.
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(source: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(source)
+    }
+
+    /// Mistakes that would make a group drop nothing or something unintended;
+    /// empty for a sound table.
+    pub fn lint(&self) -> Vec<DropIssue> {
+        let mut issues = Vec::new();
+        let mut names = HashSet::new();
+        for group in &self.groups {
+            let issue = |kind| DropIssue {
+                group: group.name.clone(),
+                kind,
+            };
+            if !names.insert(group.name.as_str()) {
+                issues.push(issue(DropIssueKind::DuplicateGroup));
+            }
+            for (field, per_mille) in [
+                ("item_per_mille", group.item_per_mille),
+                ("zen_per_mille", group.zen_per_mille),
+                ("excellent_per_mille", group.excellent_per_mille),
+                ("luck_per_mille", group.luck_per_mille),
+                ("skill_per_mille", group.skill_per_mille),
+            ] {
+                if per_mille > PER_MILLE {
+                    issues.push(issue(DropIssueKind::ChanceOver { field, per_mille }));
+                }
+            }
+            if group.item_per_mille > 0 && group.total_weight() == 0 {
+                issues.push(issue(DropIssueKind::NoItems));
+            }
+            if group.zen.min > group.zen.max {
+                issues.push(issue(DropIssueKind::ZenRange(group.zen)));
+            }
+            for entry in &group.entries {
+                let code = entry.code();
+                if item_definition(code).is_none() {
+                    issues.push(issue(DropIssueKind::UnknownItem(code)));
+                }
+                if entry.weight == 0 {
+                    issues.push(issue(DropIssueKind::ZeroWeight(code)));
+                }
+                if entry.min_level.max(entry.max_level) > MAX_DROP_LEVEL {
+                    issues.push(issue(DropIssueKind::LevelRange(code)));
+                }
+            }
+        }
+        issues
+    }
+}
+
+/// Problem found by [`DropTable::lint`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DropIssue {
+    pub group: String,
+    pub kind: DropIssueKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DropIssueKind {
+    DuplicateGroup,
+    ChanceOver {
+        field: &'static str,
+        per_mille: u16,
+    },
+    /// Items can drop but no entry has weight.
+    NoItems,
+    ZenRange(ZenRange),
+    UnknownItem(ItemCode),
+    ZeroWeight(ItemCode),
+    LevelRange(ItemCode),
+}
+
+impl fmt::Display for DropIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "group {:?}: ", self.group)?;
+        match self.kind {
+            DropIssueKind::DuplicateGroup => f.write_str("defined more than once"),
+            DropIssueKind::ChanceOver { field, per_mille } => {
+                write!(f, "{field} is {per_mille}, above {PER_MILLE}")
+            }
+            DropIssueKind::NoItems => f.write_str("item_per_mille is set but no entry has weight"),
+            DropIssueKind::ZenRange(zen) => write!(f, "zen min {} above max {}", zen.min, zen.max),
+            DropIssueKind::UnknownItem(code) => write!(f, "item {code} is not in the catalog"),
+            DropIssueKind::ZeroWeight(code) => write!(f, "item {code} has weight 0"),
+            DropIssueKind::LevelRange(code) => {
+                write!(f, "item {code} levels must lie within 0..={MAX_DROP_LEVEL}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group() -> DropGroup {
+        DropGroup {
+            name: "lorencia".to_string(),
+            item_per_mille: 500,
+            zen_per_mille: 1000,
+            zen: ZenRange { min: 10, max: 20 },
+            excellent_per_mille: 0,
+            luck_per_mille: 1000,
+            skill_per_mille: 0,
+            entries: vec![
+                DropEntry {
+                    group: 14,
+                    index: 1,
+                    weight: 3,
+                    min_level: 0,
+                    max_level: 0,
+                },
+                DropEntry {
+                    group: 0,
+                    index: 0,
+                    weight: 1,
+                    min_level: 1,
+                    max_level: 3,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn picks_entries_by_weight_and_rolls_zen_otherwise() {
+        let group = group();
+        assert_eq!(
+            group.pick(2).map(DropEntry::code),
+            Some(ItemCode::new(14, 1))
+        );
+        assert_eq!(
+            group.pick(3).map(DropEntry::code),
+            Some(ItemCode::new(0, 0))
+        );
+        assert_eq!(group.pick(4), None);
+
+        // Item chance, pick, level, then excellent, luck and skill.
+        let mut rolls = [0, 3, 2, 0, 0, 0].into_iter();
+        let dropped = group.roll(&mut |_| rolls.next().unwrap());
+        assert_eq!(
+            dropped,
+            Some(RolledDrop::Item {
+                code: ItemCode::new(0, 0),
+                level: 3,
+                excellent: false,
+                luck: true,
+                skill: false,
+            })
+        );
+
+        let mut rolls = [999, 0, 5].into_iter();
+        assert_eq!(
+            group.roll(&mut |_| rolls.next().unwrap()),
+            Some(RolledDrop::Zen(15))
+        );
+    }
+
+    #[test]
+    fn lint_reports_config_mistakes() {
+        assert!(DropTable {
+            groups: vec![group()]
+        }
+        .lint()
+        .is_empty());
+
+        let mut broken = group();
+        broken.zen = ZenRange { min: 30, max: 20 };
+        broken.luck_per_mille = 1200;
+        broken.entries[0].weight = 0;
+        broken.entries[1].index = 999;
+        broken.entries[1].max_level = 16;
+        let table = DropTable {
+            groups: vec![broken, group()],
+        };
+        let kinds: Vec<_> = table.lint().into_iter().map(|issue| issue.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DropIssueKind::ChanceOver {
+                    field: "luck_per_mille",
+                    per_mille: 1200
+                },
+                DropIssueKind::ZenRange(ZenRange { min: 30, max: 20 }),
+                DropIssueKind::ZeroWeight(ItemCode::new(14, 1)),
+                DropIssueKind::UnknownItem(ItemCode::new(0, 999)),
+                DropIssueKind::LevelRange(ItemCode::new(0, 999)),
+                DropIssueKind::DuplicateGroup,
+            ]
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn loads_toml() {
+        let table = DropTable::from_toml_str(
+            r#"
+            [[groups]]
+            name = "lorencia"
+            item_per_mille = 500
+            zen_per_mille = 1000
+            zen = { min = 10, max = 20 }
+            luck_per_mille = 1000
+
+            [[groups.entries]]
+            group = 14
+            index = 1
+            weight = 3
+
+            [[groups.entries]]
+            group = 0
+            index = 0
+            weight = 1
+            min_level = 1
+            max_level = 3
+            "#,
+        )
+        .expect("valid drop table");
+        assert_eq!(table.group("lorencia"), Some(&group()));
+    }
+}
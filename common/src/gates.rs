@@ -0,0 +1,484 @@
+//! Warp gates and spawn points shared by the server's map transfers and the
+//! client's gate prompts.
+//!
+//! Ids, areas and levels follow the Season 6 `Gate.txt`. A gate is the area a
+//! character walks into; it lands on `target_coords` of `target_map`. Spawn
+//! points are the areas characters appear in when they warp with the move
+//! list or respawn in town, and share the gate id space.
+
+use crate::WorldMap;
+
+/// Inclusive rectangle of tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TileRect {
+    pub x1: u16,
+    pub y1: u16,
+    pub x2: u16,
+    pub y2: u16,
+}
+
+impl TileRect {
+    pub const fn new(x1: u16, y1: u16, x2: u16, y2: u16) -> Self {
+        Self { x1, y1, x2, y2 }
+    }
+
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        (self.x1..=self.x2).contains(&x) && (self.y1..=self.y2).contains(&y)
+    }
+
+    pub fn center(&self) -> (u16, u16) {
+        ((self.x1 + self.x2) / 2, (self.y1 + self.y2) / 2)
+    }
+}
+
+/// Walk-in gate to another map, or to another level of the same map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gate {
+    pub id: u16,
+    pub from_map: WorldMap,
+    pub target_map: WorldMap,
+    pub source_rect: TileRect,
+    pub target_coords: (u16, u16),
+    /// Lowest character level let through; 0 for an open gate.
+    pub min_level: u16,
+}
+
+impl Gate {
+    pub fn admits_level(&self, level: u16) -> bool {
+        level >= self.min_level
+    }
+}
+
+/// Area characters appear in when warping to, or respawning on, a map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpawnPoint {
+    pub id: u16,
+    pub map: WorldMap,
+    pub area: TileRect,
+}
+
+const fn gate(
+    id: u16,
+    from_map: WorldMap,
+    source_rect: TileRect,
+    target_map: WorldMap,
+    target_coords: (u16, u16),
+    min_level: u16,
+) -> Gate {
+    Gate {
+        id,
+        from_map,
+        target_map,
+        source_rect,
+        target_coords,
+        min_level,
+    }
+}
+
+const fn spawn(id: u16, map: WorldMap, area: TileRect) -> SpawnPoint {
+    SpawnPoint { id, map, area }
+}
+
+/// Walk-in gates, sorted by id.
+pub const GATES: &[Gate] = &[
+    // Lorencia and the three Dungeon levels.
+    gate(
+        1,
+        WorldMap::Lorencia,
+        TileRect::new(121, 232, 123, 233),
+        WorldMap::Dungeon,
+        (108, 247),
+        20,
+    ),
+    gate(
+        3,
+        WorldMap::Dungeon,
+        TileRect::new(108, 248, 109, 248),
+        WorldMap::Lorencia,
+        (122, 231),
+        0,
+    ),
+    gate(
+        5,
+        WorldMap::Dungeon,
+        TileRect::new(239, 149, 239, 150),
+        WorldMap::Dungeon,
+        (232, 126),
+        0,
+    ),
+    gate(
+        7,
+        WorldMap::Dungeon,
+        TileRect::new(232, 127, 233, 127),
+        WorldMap::Dungeon,
+        (240, 145),
+        0,
+    ),
+    gate(
+        9,
+        WorldMap::Dungeon,
+        TileRect::new(2, 17, 2, 18),
+        WorldMap::Dungeon,
+        (3, 84),
+        0,
+    ),
+    gate(
+        11,
+        WorldMap::Dungeon,
+        TileRect::new(2, 84, 2, 85),
+        WorldMap::Dungeon,
+        (5, 16),
+        0,
+    ),
+    // Dungeon 3 and Devias.
+    gate(
+        13,
+        WorldMap::Dungeon,
+        TileRect::new(29, 125, 30, 126),
+        WorldMap::Devias,
+        (22, 27),
+        30,
+    ),
+    gate(
+        15,
+        WorldMap::Devias,
+        TileRect::new(22, 26, 23, 26),
+        WorldMap::Dungeon,
+        (30, 124),
+        0,
+    ),
+    // Devias and Lost Tower 1.
+    gate(
+        18,
+        WorldMap::Devias,
+        TileRect::new(3, 8, 4, 9),
+        WorldMap::LostTower,
+        (211, 82),
+        50,
+    ),
+    gate(
+        19,
+        WorldMap::LostTower,
+        TileRect::new(211, 80, 212, 80),
+        WorldMap::Devias,
+        (5, 10),
+        0,
+    ),
+    // Lost Tower levels, 1 to 7.
+    gate(
+        29,
+        WorldMap::LostTower,
+        TileRect::new(241, 245, 242, 245),
+        WorldMap::LostTower,
+        (165, 87),
+        0,
+    ),
+    gate(
+        31,
+        WorldMap::LostTower,
+        TileRect::new(165, 85, 166, 85),
+        WorldMap::LostTower,
+        (241, 243),
+        0,
+    ),
+    gate(
+        33,
+        WorldMap::LostTower,
+        TileRect::new(86, 163, 87, 163),
+        WorldMap::LostTower,
+        (86, 86),
+        0,
+    ),
+    gate(
+        35,
+        WorldMap::LostTower,
+        TileRect::new(86, 88, 87, 88),
+        WorldMap::LostTower,
+        (86, 165),
+        0,
+    ),
+    gate(
+        37,
+        WorldMap::LostTower,
+        TileRect::new(6, 86, 7, 86),
+        WorldMap::LostTower,
+        (133, 166),
+        0,
+    ),
+    gate(
+        39,
+        WorldMap::LostTower,
+        TileRect::new(132, 164, 133, 164),
+        WorldMap::LostTower,
+        (6, 88),
+        0,
+    ),
+    gate(
+        40,
+        WorldMap::LostTower,
+        TileRect::new(134, 163, 135, 163),
+        WorldMap::LostTower,
+        (131, 92),
+        0,
+    ),
+    gate(
+        41,
+        WorldMap::LostTower,
+        TileRect::new(131, 89, 132, 89),
+        WorldMap::LostTower,
+        (134, 161),
+        0,
+    ),
+    // Atlans levels, 1 to 3.
+    gate(
+        45,
+        WorldMap::Atlans,
+        TileRect::new(226, 53, 227, 56),
+        WorldMap::Atlans,
+        (9, 83),
+        0,
+    ),
+    gate(
+        46,
+        WorldMap::Atlans,
+        TileRect::new(7, 82, 8, 84),
+        WorldMap::Atlans,
+        (224, 54),
+        0,
+    ),
+    gate(
+        47,
+        WorldMap::Atlans,
+        TileRect::new(22, 228, 25, 229),
+        WorldMap::Atlans,
+        (167, 14),
+        0,
+    ),
+    gate(
+        48,
+        WorldMap::Atlans,
+        TileRect::new(166, 12, 168, 13),
+        WorldMap::Atlans,
+        (23, 226),
+        0,
+    ),
+    // Tarkan levels, 1 and 2.
+    gate(
+        58,
+        WorldMap::Tarkan,
+        TileRect::new(248, 54, 248, 57),
+        WorldMap::Tarkan,
+        (77, 230),
+        0,
+    ),
+    gate(
+        59,
+        WorldMap::Tarkan,
+        TileRect::new(76, 232, 79, 232),
+        WorldMap::Tarkan,
+        (246, 55),
+        0,
+    ),
+    // Aida levels, 1 and 2.
+    gate(
+        120,
+        WorldMap::Aida,
+        TileRect::new(85, 235, 88, 237),
+        WorldMap::Aida,
+        (118, 135),
+        0,
+    ),
+    gate(
+        121,
+        WorldMap::Aida,
+        TileRect::new(116, 136, 119, 138),
+        WorldMap::Aida,
+        (86, 233),
+        0,
+    ),
+    // Kanturu levels, 1 to 3, and the way into Kanturu Remain.
+    gate(
+        133,
+        WorldMap::Kanturu,
+        TileRect::new(178, 16, 181, 19),
+        WorldMap::Kanturu,
+        (195, 125),
+        0,
+    ),
+    gate(
+        134,
+        WorldMap::Kanturu,
+        TileRect::new(193, 120, 196, 123),
+        WorldMap::Kanturu,
+        (180, 21),
+        0,
+    ),
+    gate(
+        135,
+        WorldMap::Kanturu,
+        TileRect::new(85, 179, 88, 182),
+        WorldMap::KanturuRemain,
+        (69, 110),
+        200,
+    ),
+    gate(
+        136,
+        WorldMap::KanturuRemain,
+        TileRect::new(68, 112, 70, 114),
+        WorldMap::Kanturu,
+        (86, 177),
+        0,
+    ),
+    // Elbeland levels.
+    gate(
+        268,
+        WorldMap::Elbeland,
+        TileRect::new(10, 196, 12, 199),
+        WorldMap::Elbeland,
+        (54, 161),
+        0,
+    ),
+    gate(
+        269,
+        WorldMap::Elbeland,
+        TileRect::new(52, 155, 55, 158),
+        WorldMap::Elbeland,
+        (14, 197),
+        0,
+    ),
+    // Raklion and Selupan's lair.
+    gate(
+        288,
+        WorldMap::Raklion,
+        TileRect::new(169, 22, 172, 25),
+        WorldMap::RaklionBoss,
+        (134, 67),
+        280,
+    ),
+    gate(
+        289,
+        WorldMap::RaklionBoss,
+        TileRect::new(132, 64, 135, 65),
+        WorldMap::Raklion,
+        (171, 27),
+        0,
+    ),
+];
+
+/// Move list destinations and town respawn areas, sorted by id.
+pub const SPAWN_POINTS: &[SpawnPoint] = &[
+    spawn(17, WorldMap::Lorencia, TileRect::new(133, 118, 151, 135)),
+    spawn(22, WorldMap::Devias, TileRect::new(197, 35, 218, 50)),
+    spawn(27, WorldMap::Noria, TileRect::new(174, 101, 187, 125)),
+    spawn(42, WorldMap::LostTower, TileRect::new(208, 81, 214, 83)),
+    spawn(49, WorldMap::Atlans, TileRect::new(14, 11, 27, 23)),
+    spawn(50, WorldMap::Arena, TileRect::new(50, 90, 55, 95)),
+    spawn(57, WorldMap::Tarkan, TileRect::new(187, 54, 203, 69)),
+    spawn(62, WorldMap::Icarus, TileRect::new(14, 12, 18, 15)),
+    spawn(
+        106,
+        WorldMap::ValleyOfLoren,
+        TileRect::new(66, 193, 72, 199),
+    ),
+    spawn(118, WorldMap::Crywolf, TileRect::new(117, 48, 124, 56)),
+    spawn(119, WorldMap::Aida, TileRect::new(82, 8, 87, 14)),
+    spawn(138, WorldMap::Kanturu, TileRect::new(15, 70, 22, 78)),
+    spawn(256, WorldMap::BalgassBarracks, TileRect::new(80, 5, 84, 9)),
+    spawn(258, WorldMap::BalgassRefuge, TileRect::new(85, 98, 90, 103)),
+    spawn(267, WorldMap::Elbeland, TileRect::new(51, 224, 55, 228)),
+    spawn(
+        273,
+        WorldMap::SwampOfPeace,
+        TileRect::new(135, 105, 145, 115),
+    ),
+    spawn(287, WorldMap::Raklion, TileRect::new(220, 217, 228, 224)),
+    spawn(294, WorldMap::Vulcanus, TileRect::new(115, 107, 124, 116)),
+    spawn(
+        333,
+        WorldMap::LorenMarket,
+        TileRect::new(118, 118, 132, 132),
+    ),
+];
+
+/// Gates characters can walk into on `map`.
+pub fn gates_in(map: WorldMap) -> impl Iterator<Item = &'static Gate> {
+    GATES.iter().filter(move |gate| gate.from_map == map)
+}
+
+pub fn gate_by_id(id: u16) -> Option<&'static Gate> {
+    GATES
+        .binary_search_by_key(&id, |gate| gate.id)
+        .ok()
+        .map(|index| &GATES[index])
+}
+
+/// Gate whose area covers tile `(x, y)` of `map`.
+pub fn gate_at(map: WorldMap, x: u16, y: u16) -> Option<&'static Gate> {
+    gates_in(map).find(|gate| gate.source_rect.contains(x, y))
+}
+
+pub fn spawn_point_by_id(id: u16) -> Option<&'static SpawnPoint> {
+    SPAWN_POINTS
+        .binary_search_by_key(&id, |spawn| spawn.id)
+        .ok()
+        .map(|index| &SPAWN_POINTS[index])
+}
+
+/// Where characters warping to `map` appear; `None` for maps only reached
+/// through gates or event entries.
+pub fn spawn_point(map: WorldMap) -> Option<&'static SpawnPoint> {
+    SPAWN_POINTS.iter().find(|spawn| spawn.map == map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collision::TERRAIN_SIZE;
+
+    #[test]
+    fn tables_are_sorted_by_unique_id() {
+        assert!(GATES.windows(2).all(|pair| pair[0].id < pair[1].id));
+        assert!(SPAWN_POINTS.windows(2).all(|pair| pair[0].id < pair[1].id));
+        assert!(GATES
+            .iter()
+            .all(|gate| spawn_point_by_id(gate.id).is_none()));
+    }
+
+    #[test]
+    fn gates_stay_on_the_terrain() {
+        for gate in GATES {
+            let rect = gate.source_rect;
+            assert!(rect.x1 <= rect.x2 && rect.y1 <= rect.y2, "gate {}", gate.id);
+            assert!(
+                rect.x2 < TERRAIN_SIZE && rect.y2 < TERRAIN_SIZE,
+                "gate {}",
+                gate.id
+            );
+            let (x, y) = gate.target_coords;
+            assert!(x < TERRAIN_SIZE && y < TERRAIN_SIZE, "gate {}", gate.id);
+        }
+    }
+
+    #[test]
+    fn landing_outside_a_gate_does_not_bounce_back() {
+        for gate in GATES {
+            let (x, y) = gate.target_coords;
+            assert_eq!(gate_at(gate.target_map, x, y), None, "gate {}", gate.id);
+        }
+    }
+
+    #[test]
+    fn lookups_find_lorencia_dungeon_gate() {
+        let gate = gate_by_id(1).unwrap();
+        assert_eq!(gate.from_map, WorldMap::Lorencia);
+        assert_eq!(gate.target_map, WorldMap::Dungeon);
+        assert!(!gate.admits_level(19));
+        assert_eq!(gate_at(WorldMap::Lorencia, 122, 232), Some(gate));
+        assert!(gates_in(WorldMap::Lorencia).any(|candidate| candidate.id == 1));
+        assert_eq!(gate_by_id(2), None);
+
+        let town = spawn_point(WorldMap::Lorencia).unwrap();
+        assert_eq!(town.id, 17);
+        assert!(town.area.contains(140, 125));
+    }
+}
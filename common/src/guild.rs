@@ -0,0 +1,118 @@
+//! Guild identifiers, ranks, relations and size limits.
+//!
+//! The server's guild subsystems and the protocol messages share these types,
+//! so a guild is always a [`GuildId`] and never a bare number or name.
+
+use serde::{Deserialize, Serialize};
+
+/// Most members a guild can hold, whatever its master's level.
+pub const MAX_GUILD_MEMBERS: usize = 80;
+/// Levels of the guild master per member slot.
+pub const LEVELS_PER_MEMBER_SLOT: u16 = 10;
+/// Command of a Dark Lord master per extra member slot.
+pub const COMMAND_PER_MEMBER_SLOT: u32 = 10;
+pub const MAX_ASSISTANT_MASTERS: usize = 1;
+pub const MAX_BATTLE_MASTERS: usize = 3;
+/// Guilds in one alliance, its master guild included.
+pub const MAX_ALLIANCE_GUILDS: usize = 7;
+
+/// Guild, as the server stores and sends it.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct GuildId(pub u32);
+
+impl From<u32> for GuildId {
+    fn from(id: u32) -> Self {
+        Self(id)
+    }
+}
+
+impl From<GuildId> for u32 {
+    fn from(id: GuildId) -> Self {
+        id.0
+    }
+}
+
+impl std::fmt::Display for GuildId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Position of a member inside the guild, highest first.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum GuildRank {
+    Master,
+    AssistantMaster,
+    BattleMaster,
+    #[default]
+    Member,
+}
+
+impl GuildRank {
+    /// Accepting and expelling members.
+    pub fn can_manage_members(self) -> bool {
+        matches!(self, Self::Master | Self::AssistantMaster)
+    }
+
+    /// Declaring alliances, hostilities and wars.
+    pub fn can_declare_relations(self) -> bool {
+        matches!(self, Self::Master | Self::AssistantMaster)
+    }
+
+    /// Members the guild may have of this rank; `None` when only the total
+    /// limit applies.
+    pub fn limit(self) -> Option<usize> {
+        match self {
+            Self::Master => Some(1),
+            Self::AssistantMaster => Some(MAX_ASSISTANT_MASTERS),
+            Self::BattleMaster => Some(MAX_BATTLE_MASTERS),
+            Self::Member => None,
+        }
+    }
+}
+
+/// Relation declared between two guilds.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum GuildRelation {
+    Alliance,
+    Hostility,
+}
+
+/// Members a guild whose master has `level` and `command` can hold; command
+/// only counts for Dark Lords, so pass 0 for other classes.
+pub fn member_limit(level: u16, command: u32) -> usize {
+    let by_level = usize::from(level / LEVELS_PER_MEMBER_SLOT);
+    let by_command = (command / COMMAND_PER_MEMBER_SLOT) as usize;
+    (by_level + by_command).min(MAX_GUILD_MEMBERS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn member_limit_grows_with_the_master() {
+        assert_eq!(member_limit(9, 0), 0);
+        assert_eq!(member_limit(250, 0), 25);
+        assert_eq!(member_limit(250, 300), 55);
+        assert_eq!(member_limit(400, 2_000), MAX_GUILD_MEMBERS);
+
+        assert!(GuildRank::Master < GuildRank::Member);
+        assert!(GuildRank::AssistantMaster.can_manage_members());
+        assert!(!GuildRank::BattleMaster.can_declare_relations());
+        assert_eq!(GuildRank::BattleMaster.limit(), Some(MAX_BATTLE_MASTERS));
+        assert_eq!(
+            serde_json::to_string(&GuildId(7)).unwrap(),
+            "7",
+            "ids stay plain numbers on the wire"
+        );
+    }
+}
@@ -0,0 +1,535 @@
+//! Item catalog shared by the client's equipment rendering and tooltips and
+//! the server's inventory validation.
+//!
+//! Items are identified the Season 6 way, by `(group, index)`, with the
+//! enhancement level (+0..+15) carried on the item instance rather than the
+//! definition. Requirements and durability follow the Season 6 `Item.txt`.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Locale;
+
+/// Identifier of an item definition, as in `Item.txt` sections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ItemCode {
+    pub group: u8,
+    pub index: u16,
+}
+
+impl ItemCode {
+    pub const fn new(group: u8, index: u16) -> Self {
+        Self { group, index }
+    }
+
+    pub fn definition(self) -> Option<&'static ItemDefinition> {
+        item_definition(self)
+    }
+}
+
+impl fmt::Display for ItemCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.group, self.index)
+    }
+}
+
+/// Broad item family; decides where an item is worn and which options it
+/// can roll.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemKind {
+    Weapon,
+    Shield,
+    /// Arrows and bolts, worn in the off hand.
+    Ammo,
+    /// Helm, armor, pants, gloves or boots; the group tells which.
+    Armor,
+    Wings,
+    Pet,
+    Ring,
+    Pendant,
+    Jewel,
+    Potion,
+    Scroll,
+}
+
+/// Equipment slot an item is worn in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EquipSlot {
+    Weapon,
+    OffHand,
+    Helm,
+    Armor,
+    Pants,
+    Gloves,
+    Boots,
+    Wings,
+    Pet,
+    Pendant,
+    Ring,
+}
+
+/// Static data of one item definition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ItemDefinition {
+    pub code: ItemCode,
+    pub name: &'static str,
+    pub kind: ItemKind,
+    /// Inventory footprint in cells.
+    pub width: u8,
+    pub height: u8,
+    pub required_level: u16,
+    pub required_strength: u16,
+    pub required_agility: u16,
+    /// Durability at +0; 0 for items that do not wear out.
+    pub durability: u8,
+    /// Largest quantity a single slot holds; 1 for items that do not stack.
+    pub max_stack: u16,
+}
+
+impl ItemDefinition {
+    /// [`Self::name`](ItemDefinition::name) in `locale`.
+    pub fn localized_name(&self, locale: Locale) -> Cow<'static, str> {
+        locale.translate(self.name)
+    }
+
+    pub fn equip_slot(&self) -> Option<EquipSlot> {
+        match self.kind {
+            ItemKind::Weapon => Some(EquipSlot::Weapon),
+            ItemKind::Shield | ItemKind::Ammo => Some(EquipSlot::OffHand),
+            ItemKind::Armor => match self.code.group {
+                7 => Some(EquipSlot::Helm),
+                8 => Some(EquipSlot::Armor),
+                9 => Some(EquipSlot::Pants),
+                10 => Some(EquipSlot::Gloves),
+                11 => Some(EquipSlot::Boots),
+                _ => None,
+            },
+            ItemKind::Wings => Some(EquipSlot::Wings),
+            ItemKind::Pet => Some(EquipSlot::Pet),
+            ItemKind::Ring => Some(EquipSlot::Ring),
+            ItemKind::Pendant => Some(EquipSlot::Pendant),
+            ItemKind::Jewel | ItemKind::Potion | ItemKind::Scroll => None,
+        }
+    }
+
+    /// Whether the item can roll luck, excellent, additional or socket
+    /// options; jewels, potions and scrolls cannot.
+    pub fn takes_options(&self) -> bool {
+        !matches!(
+            self.kind,
+            ItemKind::Ammo | ItemKind::Jewel | ItemKind::Potion | ItemKind::Scroll
+        )
+    }
+
+    /// Armor set the piece belongs to; pieces of one set share the index
+    /// across groups 7..=11.
+    pub fn armor_set(&self) -> Option<u16> {
+        (self.kind == ItemKind::Armor).then_some(self.code.index)
+    }
+}
+
+pub fn item_definition(code: ItemCode) -> Option<&'static ItemDefinition> {
+    ITEMS
+        .binary_search_by_key(&code, |item| item.code)
+        .ok()
+        .map(|slot| &ITEMS[slot])
+}
+
+pub fn items() -> &'static [ItemDefinition] {
+    ITEMS
+}
+
+pub fn items_in_group(group: u8) -> impl Iterator<Item = &'static ItemDefinition> {
+    ITEMS.iter().filter(move |item| item.code.group == group)
+}
+
+/// Stack limit of potions; jewels and scrolls do not stack.
+const POTION_STACK: u16 = 255;
+/// Arrows and bolts per quiver.
+const AMMO_DURABILITY: u8 = 255;
+const WINGS_DURABILITY: u8 = 200;
+const PET_DURABILITY: u8 = 255;
+const JEWELRY_DURABILITY: u8 = 50;
+
+const fn base(group: u8, index: u16, name: &'static str, kind: ItemKind) -> ItemDefinition {
+    ItemDefinition {
+        code: ItemCode::new(group, index),
+        name,
+        kind,
+        width: 1,
+        height: 1,
+        required_level: 0,
+        required_strength: 0,
+        required_agility: 0,
+        durability: 0,
+        max_stack: 1,
+    }
+}
+
+const fn weapon(
+    group: u8,
+    index: u16,
+    name: &'static str,
+    (width, height): (u8, u8),
+    (required_strength, required_agility): (u16, u16),
+    durability: u8,
+) -> ItemDefinition {
+    ItemDefinition {
+        width,
+        height,
+        required_strength,
+        required_agility,
+        durability,
+        ..base(group, index, name, ItemKind::Weapon)
+    }
+}
+
+const fn shield(
+    group: u8,
+    index: u16,
+    name: &'static str,
+    size: (u8, u8),
+    requirements: (u16, u16),
+    durability: u8,
+) -> ItemDefinition {
+    ItemDefinition {
+        kind: ItemKind::Shield,
+        ..weapon(group, index, name, size, requirements, durability)
+    }
+}
+
+/// Helms, pants, gloves and boots take 2x2 cells, armors 2x3.
+const fn armor(
+    group: u8,
+    index: u16,
+    name: &'static str,
+    requirements: (u16, u16),
+    durability: u8,
+) -> ItemDefinition {
+    let size = if group == 8 { (2, 3) } else { (2, 2) };
+    ItemDefinition {
+        kind: ItemKind::Armor,
+        ..weapon(group, index, name, size, requirements, durability)
+    }
+}
+
+const fn ammo(group: u8, index: u16, name: &'static str) -> ItemDefinition {
+    ItemDefinition {
+        durability: AMMO_DURABILITY,
+        ..base(group, index, name, ItemKind::Ammo)
+    }
+}
+
+const fn wings(
+    group: u8,
+    index: u16,
+    name: &'static str,
+    (width, height): (u8, u8),
+    required_level: u16,
+) -> ItemDefinition {
+    ItemDefinition {
+        width,
+        height,
+        required_level,
+        durability: WINGS_DURABILITY,
+        ..base(group, index, name, ItemKind::Wings)
+    }
+}
+
+const fn accessory(
+    group: u8,
+    index: u16,
+    name: &'static str,
+    kind: ItemKind,
+    required_level: u16,
+) -> ItemDefinition {
+    let durability = match kind {
+        ItemKind::Pet => PET_DURABILITY,
+        _ => JEWELRY_DURABILITY,
+    };
+    ItemDefinition {
+        required_level,
+        durability,
+        ..base(group, index, name, kind)
+    }
+}
+
+const fn potion(group: u8, index: u16, name: &'static str) -> ItemDefinition {
+    ItemDefinition {
+        max_stack: POTION_STACK,
+        ..base(group, index, name, ItemKind::Potion)
+    }
+}
+
+const fn jewel(group: u8, index: u16, name: &'static str) -> ItemDefinition {
+    base(group, index, name, ItemKind::Jewel)
+}
+
+const fn scroll(group: u8, index: u16, name: &'static str) -> ItemDefinition {
+    base(group, index, name, ItemKind::Scroll)
+}
+
+/// Catalog sorted by code so lookups can binary search.
+static ITEMS: &[ItemDefinition] = &[
+    // 0: swords
+    weapon(0, 0, "Kris", (1, 2), (10, 8), 20),
+    weapon(0, 1, "Short Sword", (1, 3), (20, 0), 22),
+    weapon(0, 2, "Rapier", (1, 3), (50, 40), 23),
+    weapon(0, 3, "Katana", (1, 3), (80, 40), 27),
+    weapon(0, 4, "Sword of Assassin", (1, 3), (60, 40), 24),
+    weapon(0, 5, "Blade", (1, 3), (80, 50), 39),
+    weapon(0, 6, "Gladius", (1, 3), (110, 0), 40),
+    weapon(0, 7, "Falchion", (1, 3), (120, 0), 41),
+    weapon(0, 8, "Serpent Sword", (1, 3), (130, 0), 42),
+    weapon(0, 9, "Sword of Salamander", (2, 3), (103, 0), 45),
+    weapon(0, 10, "Light Saber", (2, 4), (80, 60), 47),
+    weapon(0, 11, "Legendary Sword", (2, 3), (120, 0), 44),
+    weapon(0, 12, "Heliacal Sword", (2, 3), (140, 0), 51),
+    weapon(0, 13, "Double Blade", (1, 3), (70, 70), 53),
+    weapon(0, 14, "Lightning Sword", (1, 4), (90, 50), 54),
+    weapon(0, 15, "Giant Sword", (2, 3), (140, 0), 55),
+    // 1: axes
+    weapon(1, 0, "Small Axe", (1, 3), (20, 0), 18),
+    weapon(1, 1, "Hand Axe", (1, 3), (70, 0), 20),
+    weapon(1, 2, "Double Axe", (1, 3), (90, 0), 24),
+    weapon(1, 3, "Tomahawk", (1, 3), (100, 0), 26),
+    weapon(1, 4, "Elven Axe", (1, 3), (50, 70), 28),
+    weapon(1, 5, "Battle Axe", (2, 3), (120, 0), 33),
+    weapon(1, 6, "Nikkea Axe", (2, 3), (130, 0), 37),
+    weapon(1, 7, "Larkan Axe", (2, 3), (140, 0), 40),
+    weapon(1, 8, "Crescent Axe", (2, 3), (100, 40), 42),
+    // 2: maces
+    weapon(2, 0, "Mace", (1, 3), (100, 0), 30),
+    weapon(2, 1, "Morning Star", (1, 3), (100, 0), 32),
+    weapon(2, 2, "Flail", (1, 3), (80, 50), 33),
+    weapon(2, 3, "Great Hammer", (2, 3), (150, 0), 50),
+    weapon(2, 4, "Crystal Morning Star", (2, 3), (130, 0), 58),
+    weapon(2, 5, "Crystal Sword", (2, 4), (130, 70), 64),
+    weapon(2, 6, "Chaos Dragon Axe", (2, 4), (140, 50), 75),
+    // 3: spears
+    weapon(3, 0, "Light Spear", (2, 4), (60, 70), 30),
+    weapon(3, 1, "Spear", (2, 4), (70, 50), 30),
+    weapon(3, 2, "Dragon Lance", (2, 4), (70, 50), 33),
+    weapon(3, 3, "Giant Trident", (2, 4), (90, 30), 36),
+    weapon(3, 4, "Serpent Spear", (2, 4), (90, 30), 40),
+    weapon(3, 5, "Double Poleaxe", (2, 4), (70, 50), 44),
+    weapon(3, 6, "Halberd", (2, 4), (70, 50), 47),
+    weapon(3, 7, "Berdysh", (2, 4), (80, 50), 50),
+    weapon(3, 8, "Great Scythe", (2, 4), (90, 50), 54),
+    weapon(3, 9, "Bill of Balrog", (2, 4), (80, 50), 58),
+    // 4: bows and crossbows
+    weapon(4, 0, "Short Bow", (2, 3), (20, 80), 20),
+    weapon(4, 1, "Bow", (2, 3), (30, 90), 24),
+    weapon(4, 2, "Elven Bow", (2, 3), (30, 90), 28),
+    weapon(4, 3, "Battle Bow", (2, 3), (30, 90), 33),
+    weapon(4, 4, "Tiger Bow", (2, 4), (30, 100), 37),
+    weapon(4, 5, "Silver Bow", (2, 4), (30, 100), 40),
+    weapon(4, 6, "Chaos Nature Bow", (2, 4), (40, 150), 57),
+    ammo(4, 7, "Bolt"),
+    weapon(4, 8, "Crossbow", (2, 2), (20, 90), 22),
+    weapon(4, 9, "Golden Crossbow", (2, 2), (30, 90), 26),
+    weapon(4, 10, "Arquebus", (2, 2), (30, 90), 30),
+    weapon(4, 11, "Light Crossbow", (2, 3), (30, 90), 35),
+    weapon(4, 12, "Serpent Crossbow", (2, 3), (30, 100), 38),
+    weapon(4, 13, "Bluewing Crossbow", (2, 3), (40, 110), 42),
+    weapon(4, 14, "Aquagold Crossbow", (2, 3), (50, 130), 48),
+    ammo(4, 15, "Arrows"),
+    // 5: staffs
+    weapon(5, 0, "Skull Staff", (1, 3), (40, 0), 20),
+    weapon(5, 1, "Angelic Staff", (2, 3), (50, 0), 25),
+    weapon(5, 2, "Serpent Staff", (2, 3), (50, 0), 30),
+    weapon(5, 3, "Thunder Staff", (2, 4), (40, 10), 35),
+    weapon(5, 4, "Gorgon Staff", (2, 4), (50, 0), 40),
+    weapon(5, 5, "Legendary Staff", (1, 4), (50, 0), 45),
+    weapon(5, 6, "Staff of Resurrection", (1, 4), (60, 10), 48),
+    weapon(5, 7, "Chaos Lightning Staff", (2, 4), (60, 10), 55),
+    // 6: shields
+    shield(6, 0, "Small Shield", (2, 2), (70, 0), 22),
+    shield(6, 1, "Horn Shield", (2, 2), (100, 0), 28),
+    shield(6, 2, "Kite Shield", (2, 2), (110, 0), 32),
+    shield(6, 3, "Elven Shield", (2, 2), (30, 100), 32),
+    shield(6, 4, "Buckler", (2, 2), (80, 0), 24),
+    shield(6, 5, "Dragon Slayer Shield", (2, 2), (100, 40), 36),
+    shield(6, 6, "Skull Shield", (2, 2), (110, 0), 38),
+    shield(6, 7, "Spiked Shield", (2, 2), (130, 0), 40),
+    shield(6, 8, "Tower Shield", (2, 2), (130, 0), 44),
+    shield(6, 9, "Plate Shield", (2, 2), (120, 0), 45),
+    shield(6, 10, "Big Round Shield", (2, 2), (120, 0), 46),
+    shield(6, 11, "Serpent Shield", (2, 2), (130, 0), 48),
+    shield(6, 12, "Bronze Shield", (2, 2), (140, 0), 49),
+    shield(6, 13, "Dragon Shield", (2, 2), (120, 40), 50),
+    shield(6, 14, "Legendary Shield", (2, 3), (90, 25), 48),
+    // 7: helms (Storm Crow has no helm)
+    armor(7, 0, "Bronze Helm", (80, 20), 34),
+    armor(7, 1, "Dragon Helm", (120, 30), 68),
+    armor(7, 2, "Pad Helm", (20, 0), 28),
+    armor(7, 3, "Legendary Helm", (30, 0), 42),
+    armor(7, 4, "Bone Helm", (30, 0), 30),
+    armor(7, 5, "Leather Helm", (80, 0), 28),
+    armor(7, 6, "Scale Helm", (110, 0), 44),
+    armor(7, 7, "Sphinx Helm", (30, 0), 40),
+    armor(7, 8, "Brass Helm", (100, 30), 39),
+    armor(7, 9, "Plate Helm", (130, 0), 48),
+    armor(7, 10, "Vine Helm", (30, 60), 22),
+    armor(7, 11, "Silk Helm", (30, 70), 26),
+    armor(7, 12, "Wind Helm", (30, 80), 32),
+    armor(7, 13, "Spirit Helm", (40, 150), 38),
+    armor(7, 14, "Guardian Helm", (40, 140), 44),
+    // 8: armors
+    armor(8, 0, "Bronze Armor", (80, 20), 40),
+    armor(8, 1, "Dragon Armor", (120, 30), 74),
+    armor(8, 2, "Pad Armor", (20, 0), 34),
+    armor(8, 3, "Legendary Armor", (30, 0), 48),
+    armor(8, 4, "Bone Armor", (30, 0), 36),
+    armor(8, 5, "Leather Armor", (80, 0), 34),
+    armor(8, 6, "Scale Armor", (110, 0), 50),
+    armor(8, 7, "Sphinx Armor", (30, 0), 46),
+    armor(8, 8, "Brass Armor", (100, 30), 45),
+    armor(8, 9, "Plate Armor", (130, 0), 54),
+    armor(8, 10, "Vine Armor", (30, 60), 28),
+    armor(8, 11, "Silk Armor", (30, 70), 32),
+    armor(8, 12, "Wind Armor", (30, 80), 38),
+    armor(8, 13, "Spirit Armor", (40, 150), 44),
+    armor(8, 14, "Guardian Armor", (40, 140), 50),
+    armor(8, 15, "Storm Crow Armor", (150, 70), 56),
+    // 9: pants
+    armor(9, 0, "Bronze Pants", (80, 20), 38),
+    armor(9, 1, "Dragon Pants", (120, 30), 72),
+    armor(9, 2, "Pad Pants", (20, 0), 32),
+    armor(9, 3, "Legendary Pants", (30, 0), 46),
+    armor(9, 4, "Bone Pants", (30, 0), 34),
+    armor(9, 5, "Leather Pants", (80, 0), 32),
+    armor(9, 6, "Scale Pants", (110, 0), 48),
+    armor(9, 7, "Sphinx Pants", (30, 0), 44),
+    armor(9, 8, "Brass Pants", (100, 30), 43),
+    armor(9, 9, "Plate Pants", (130, 0), 52),
+    armor(9, 10, "Vine Pants", (30, 60), 26),
+    armor(9, 11, "Silk Pants", (30, 70), 30),
+    armor(9, 12, "Wind Pants", (30, 80), 36),
+    armor(9, 13, "Spirit Pants", (40, 150), 42),
+    armor(9, 14, "Guardian Pants", (40, 140), 48),
+    armor(9, 15, "Storm Crow Pants", (150, 70), 54),
+    // 10: gloves
+    armor(10, 0, "Bronze Gloves", (80, 20), 30),
+    armor(10, 1, "Dragon Gloves", (120, 30), 64),
+    armor(10, 2, "Pad Gloves", (20, 0), 24),
+    armor(10, 3, "Legendary Gloves", (30, 0), 38),
+    armor(10, 4, "Bone Gloves", (30, 0), 26),
+    armor(10, 5, "Leather Gloves", (80, 0), 24),
+    armor(10, 6, "Scale Gloves", (110, 0), 40),
+    armor(10, 7, "Sphinx Gloves", (30, 0), 36),
+    armor(10, 8, "Brass Gloves", (100, 30), 35),
+    armor(10, 9, "Plate Gloves", (130, 0), 44),
+    armor(10, 10, "Vine Gloves", (30, 60), 18),
+    armor(10, 11, "Silk Gloves", (30, 70), 22),
+    armor(10, 12, "Wind Gloves", (30, 80), 28),
+    armor(10, 13, "Spirit Gloves", (40, 150), 34),
+    armor(10, 14, "Guardian Gloves", (40, 140), 40),
+    armor(10, 15, "Storm Crow Gloves", (150, 70), 46),
+    // 11: boots
+    armor(11, 0, "Bronze Boots", (80, 20), 32),
+    armor(11, 1, "Dragon Boots", (120, 30), 66),
+    armor(11, 2, "Pad Boots", (20, 0), 26),
+    armor(11, 3, "Legendary Boots", (30, 0), 40),
+    armor(11, 4, "Bone Boots", (30, 0), 28),
+    armor(11, 5, "Leather Boots", (80, 0), 26),
+    armor(11, 6, "Scale Boots", (110, 0), 42),
+    armor(11, 7, "Sphinx Boots", (30, 0), 38),
+    armor(11, 8, "Brass Boots", (100, 30), 37),
+    armor(11, 9, "Plate Boots", (130, 0), 46),
+    armor(11, 10, "Vine Boots", (30, 60), 20),
+    armor(11, 11, "Silk Boots", (30, 70), 24),
+    armor(11, 12, "Wind Boots", (30, 80), 30),
+    armor(11, 13, "Spirit Boots", (40, 150), 36),
+    armor(11, 14, "Guardian Boots", (40, 140), 42),
+    armor(11, 15, "Storm Crow Boots", (150, 70), 48),
+    // 12: wings and the chaos jewel
+    wings(12, 0, "Wings of Elf", (3, 2), 100),
+    wings(12, 1, "Wings of Heaven", (5, 3), 100),
+    wings(12, 2, "Wings of Satan", (5, 2), 100),
+    wings(12, 3, "Wings of Spirits", (5, 3), 150),
+    wings(12, 4, "Wings of Soul", (5, 3), 150),
+    wings(12, 5, "Wings of Dragon", (5, 3), 150),
+    wings(12, 6, "Wings of Darkness", (4, 2), 150),
+    jewel(12, 15, "Jewel of Chaos"),
+    // 13: pets, rings and pendants
+    accessory(13, 0, "Guardian Angel", ItemKind::Pet, 23),
+    accessory(13, 1, "Imp", ItemKind::Pet, 28),
+    accessory(13, 2, "Horn of Uniria", ItemKind::Pet, 25),
+    accessory(13, 3, "Horn of Dinorant", ItemKind::Pet, 110),
+    accessory(13, 8, "Ring of Ice", ItemKind::Ring, 20),
+    accessory(13, 9, "Ring of Poison", ItemKind::Ring, 17),
+    accessory(13, 12, "Pendant of Lightning", ItemKind::Pendant, 21),
+    accessory(13, 13, "Pendant of Fire", ItemKind::Pendant, 13),
+    // 14: potions, jewels and event tickets
+    potion(14, 0, "Apple"),
+    potion(14, 1, "Small Healing Potion"),
+    potion(14, 2, "Healing Potion"),
+    potion(14, 3, "Large Healing Potion"),
+    potion(14, 4, "Small Mana Potion"),
+    potion(14, 5, "Mana Potion"),
+    potion(14, 6, "Large Mana Potion"),
+    potion(14, 8, "Antidote"),
+    potion(14, 9, "Ale"),
+    scroll(14, 10, "Town Portal Scroll"),
+    jewel(14, 13, "Jewel of Bless"),
+    jewel(14, 14, "Jewel of Soul"),
+    jewel(14, 16, "Jewel of Life"),
+    jewel(14, 22, "Jewel of Creation"),
+    jewel(14, 31, "Jewel of Guardian"),
+    scroll(14, 111, "Mirror of Dimensions"),
+    // 15: skill scrolls
+    scroll(15, 0, "Scroll of Poison"),
+    scroll(15, 1, "Scroll of Meteorite"),
+    scroll(15, 2, "Scroll of Lightning"),
+    scroll(15, 3, "Scroll of Fire Ball"),
+    scroll(15, 4, "Scroll of Flame"),
+    scroll(15, 5, "Scroll of Teleport"),
+    scroll(15, 6, "Scroll of Ice"),
+    scroll(15, 7, "Scroll of Twister"),
+    scroll(15, 8, "Scroll of Evil Spirit"),
+    scroll(15, 9, "Scroll of Hellfire"),
+    scroll(15, 10, "Scroll of Power Wave"),
+    scroll(15, 11, "Scroll of Aqua Beam"),
+    scroll(15, 12, "Scroll of Cometfall"),
+    scroll(15, 13, "Scroll of Inferno"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalog_is_sorted_and_unique() {
+        for pair in ITEMS.windows(2) {
+            assert!(pair[0].code < pair[1].code, "{}", pair[1].code);
+        }
+    }
+
+    #[test]
+    fn lookups_find_catalog_entries() {
+        let blade = ItemCode::new(0, 5).definition().unwrap();
+        assert_eq!(blade.name, "Blade");
+        assert_eq!(blade.equip_slot(), Some(EquipSlot::Weapon));
+
+        let pants = item_definition(ItemCode::new(9, 10)).unwrap();
+        assert_eq!(pants.name, "Vine Pants");
+        assert_eq!(pants.equip_slot(), Some(EquipSlot::Pants));
+        assert_eq!(pants.armor_set(), Some(10));
+
+        assert_eq!(item_definition(ItemCode::new(7, 15)), None);
+        assert_eq!(items_in_group(11).count(), 16);
+    }
+
+    #[test]
+    fn consumables_stack_and_take_no_options() {
+        for item in items_in_group(14).chain(items_in_group(15)) {
+            assert!(!item.takes_options(), "{}", item.name);
+            assert_eq!(item.equip_slot(), None, "{}", item.name);
+            assert!(item.max_stack >= 1, "{}", item.name);
+        }
+        assert!(ItemCode::new(12, 1).definition().unwrap().takes_options());
+    }
+}
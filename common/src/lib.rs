@@ -8,6 +8,38 @@
 //! Enum discriminant values match the World folder numbers (World1, World2, etc.)
 //! from the game data files.
 
+pub mod appearance;
+pub mod buff;
+pub mod class;
+pub mod collision;
+pub mod combat;
+pub mod drop;
+pub mod gates;
+pub mod guild;
+pub mod item;
+pub mod locale;
+pub mod monster;
+pub mod party;
+pub mod pvp;
+pub mod season;
+pub mod stats;
+pub mod world_map_info;
+pub mod world_map_serde;
+pub mod worldscale;
+
+pub use appearance::{Appearance, VisibleItem};
+pub use buff::{BuffEffect, BuffInfo, BuffKind, BuffStacking};
+pub use class::{BodyType, CharacterClass, ClassEvolution, ClassRank, LevelGains, StartingStats};
+pub use guild::{GuildId, GuildRank, GuildRelation};
+pub use item::{EquipSlot, ItemCode, ItemDefinition, ItemKind};
+pub use locale::Locale;
+pub use monster::{MonsterInfo, MonsterKind};
+pub use party::PartyId;
+pub use pvp::{MurderStatus, PvpMode, PvpRules, PvpStanding};
+pub use season::Season;
+pub use stats::{BaseStats, DerivedStats};
+pub use world_map_info::{EntryRequirement, Traversal, WorldMapInfo};
+
 /// Represents all available worlds/maps in MU Online
 ///
 /// The ID values correspond to the World folder numbers used in the game data
@@ -382,6 +414,24 @@ pub enum WorldMap {
     DoppelgangerIceZoneNew = 143,
 }
 
+/// Kind of place a map is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MapCategory {
+    /// Safe hub with shops and warehouses.
+    Town,
+    /// Open hunting ground.
+    Field,
+    /// Timed event instance entered with a ticket or at a scheduled hour
+    /// (Blood Castle, Chaos Castle, Devil Square, Illusion Temple, ...).
+    EventDungeon,
+    /// Map built for fights between players or guilds.
+    Battleground,
+    /// Backdrop of the login and character selection screens.
+    LoginScene,
+    /// `Unk0`, the id without a World folder.
+    Unknown,
+}
+
 impl WorldMap {
     /// Returns a human-readable name for the world
     pub fn name(&self) -> &'static str {
@@ -504,80 +554,205 @@ impl WorldMap {
         }
     }
 
+    /// [`Self::name`] in `locale`; see [`locale`] for how names are
+    /// translated.
+    pub fn localized_name(&self, locale: Locale) -> std::borrow::Cow<'static, str> {
+        locale.translate(self.name())
+    }
+
     /// Returns the World folder name for this map (e.g. "World1" for Lorencia)
     pub fn world_folder(&self) -> String {
         format!("World{}", *self as u8)
     }
 
-    /// Returns true if this is a login/character selection scene
-    pub fn is_login_scene(&self) -> bool {
-        matches!(
-            self,
-            WorldMap::LoginScene
-                | WorldMap::CharacterScene
-                | WorldMap::NewLoginScene1
-                | WorldMap::NewLoginScene2
-                | WorldMap::NewCharacterScene2
-        )
+    /// Every map with a World folder, in id order. `Unk0` is left out.
+    pub const ALL: [WorldMap; 114] = [
+        WorldMap::Lorencia,
+        WorldMap::Dungeon,
+        WorldMap::Devias,
+        WorldMap::Noria,
+        WorldMap::LostTower,
+        WorldMap::Exile,
+        WorldMap::Arena,
+        WorldMap::Atlans,
+        WorldMap::Tarkan,
+        WorldMap::DevilSquare,
+        WorldMap::Icarus,
+        WorldMap::BloodCastle1,
+        WorldMap::BloodCastle2,
+        WorldMap::BloodCastle3,
+        WorldMap::BloodCastle4,
+        WorldMap::BloodCastle5,
+        WorldMap::BloodCastle6,
+        WorldMap::BloodCastle7,
+        WorldMap::ChaosCastle1,
+        WorldMap::ChaosCastle2,
+        WorldMap::ChaosCastle3,
+        WorldMap::ChaosCastle4,
+        WorldMap::ChaosCastle5,
+        WorldMap::ChaosCastle6,
+        WorldMap::Kalima1,
+        WorldMap::Kalima2,
+        WorldMap::Kalima3,
+        WorldMap::Kalima4,
+        WorldMap::Kalima5,
+        WorldMap::Kalima6,
+        WorldMap::ValleyOfLoren,
+        WorldMap::LandOfTrials,
+        WorldMap::DevilSquare2,
+        WorldMap::Aida,
+        WorldMap::Crywolf,
+        WorldMap::Kalima7,
+        WorldMap::Kanturu,
+        WorldMap::KanturuRemain,
+        WorldMap::RefineTower,
+        WorldMap::SilentMap,
+        WorldMap::BalgassBarracks,
+        WorldMap::BalgassRefuge,
+        WorldMap::IllusionTemple1,
+        WorldMap::IllusionTemple2,
+        WorldMap::IllusionTemple3,
+        WorldMap::IllusionTemple4,
+        WorldMap::IllusionTemple5,
+        WorldMap::Elbeland2,
+        WorldMap::Elbeland,
+        WorldMap::BloodCastle8,
+        WorldMap::ChaosCastle7,
+        WorldMap::CharacterScene,
+        WorldMap::LoginScene,
+        WorldMap::SwampOfPeace,
+        WorldMap::Raklion,
+        WorldMap::RaklionBoss,
+        WorldMap::SantaVillage,
+        WorldMap::Vulcanus,
+        WorldMap::DuelArena,
+        WorldMap::DoppelgangerIceZone,
+        WorldMap::DoppelgangerBlazeZone,
+        WorldMap::DoppelgangerUnderwater,
+        WorldMap::DoppelgangerCrystalCave,
+        WorldMap::ImperialGuardian4,
+        WorldMap::ImperialGuardian3,
+        WorldMap::ImperialGuardian2,
+        WorldMap::ImperialGuardian1,
+        WorldMap::NewLoginScene1,
+        WorldMap::EventSquare,
+        WorldMap::NewLoginScene2,
+        WorldMap::NewCharacterScene2,
+        WorldMap::LorenMarket,
+        WorldMap::Karutan1,
+        WorldMap::Karutan2,
+        WorldMap::DoppelgangerRenewal,
+        WorldMap::NewArena,
+        WorldMap::Acheron,
+        WorldMap::Acheron2,
+        WorldMap::UrukMountain3,
+        WorldMap::UrukMountain2,
+        WorldMap::Debenter,
+        WorldMap::DebenterArcaBattle,
+        WorldMap::IllusionTempleLeague,
+        WorldMap::IllusionTempleLeague2,
+        WorldMap::UrukMountain,
+        WorldMap::TormentedSquare,
+        WorldMap::Nars,
+        WorldMap::Ferea,
+        WorldMap::NixiesLake,
+        WorldMap::LorenMarketS6,
+        WorldMap::DeepDungeon1,
+        WorldMap::DeepDungeon2,
+        WorldMap::DeepDungeon3,
+        WorldMap::DeepDungeon4,
+        WorldMap::DeepDungeon5,
+        WorldMap::PlaceOfQualification,
+        WorldMap::SwampOfDarkness,
+        WorldMap::KuberaMine1,
+        WorldMap::KuberaMine2,
+        WorldMap::AbyssOfAtlans,
+        WorldMap::AbyssOfAtlans2,
+        WorldMap::AbyssOfAtlans3,
+        WorldMap::ScorchedTunnels,
+        WorldMap::RedSmokeIcarus,
+        WorldMap::TempleOfArnil,
+        WorldMap::AshenAida,
+        WorldMap::OldKethotum,
+        WorldMap::BlazeKethotum,
+        WorldMap::KanturuUndergrounds,
+        WorldMap::IgnisVolcano,
+        WorldMap::BossBattleZone,
+        WorldMap::BloodyTarkan,
+        WorldMap::TormentaIsland,
+        WorldMap::DoppelgangerIceZoneNew,
+    ];
+
+    /// Iterates [`Self::ALL`].
+    pub fn iter() -> impl Iterator<Item = WorldMap> {
+        Self::ALL.into_iter()
     }
 
-    /// Returns true if this is a PvP area
-    pub fn is_pvp_area(&self) -> bool {
-        matches!(
-            self,
+    /// What kind of place the map is.
+    pub fn category(&self) -> MapCategory {
+        match self {
+            WorldMap::Lorencia
+            | WorldMap::Devias
+            | WorldMap::Noria
+            | WorldMap::Elbeland
+            | WorldMap::Elbeland2
+            | WorldMap::LorenMarket
+            | WorldMap::LorenMarketS6
+            | WorldMap::SantaVillage => MapCategory::Town,
+            WorldMap::LoginScene
+            | WorldMap::CharacterScene
+            | WorldMap::NewLoginScene1
+            | WorldMap::NewLoginScene2
+            | WorldMap::NewCharacterScene2 => MapCategory::LoginScene,
             WorldMap::Arena
-                | WorldMap::Vulcanus
-                | WorldMap::DuelArena
-                | WorldMap::DevilSquare
-                | WorldMap::NewArena
-        )
-    }
-
-    /// Returns true if this is a special event dungeon
-    pub fn is_event_dungeon(&self) -> bool {
-        matches!(
-            self,
+            | WorldMap::ValleyOfLoren
+            | WorldMap::Vulcanus
+            | WorldMap::DuelArena
+            | WorldMap::NewArena
+            | WorldMap::DebenterArcaBattle => MapCategory::Battleground,
             WorldMap::BloodCastle1
-                | WorldMap::BloodCastle2
-                | WorldMap::BloodCastle3
-                | WorldMap::BloodCastle4
-                | WorldMap::BloodCastle5
-                | WorldMap::BloodCastle6
-                | WorldMap::BloodCastle7
-                | WorldMap::BloodCastle8
-                | WorldMap::ChaosCastle1
-                | WorldMap::ChaosCastle2
-                | WorldMap::ChaosCastle3
-                | WorldMap::ChaosCastle4
-                | WorldMap::ChaosCastle5
-                | WorldMap::ChaosCastle6
-                | WorldMap::ChaosCastle7
-                | WorldMap::DevilSquare
-                | WorldMap::DevilSquare2
-                | WorldMap::Crywolf
-                | WorldMap::Kanturu
-                | WorldMap::KanturuRemain
-                | WorldMap::RefineTower
-                | WorldMap::IllusionTemple1
-                | WorldMap::IllusionTemple2
-                | WorldMap::IllusionTemple3
-                | WorldMap::IllusionTemple4
-                | WorldMap::IllusionTemple5
-                | WorldMap::IllusionTempleLeague
-                | WorldMap::IllusionTempleLeague2
-                | WorldMap::DoppelgangerIceZone
-                | WorldMap::DoppelgangerBlazeZone
-                | WorldMap::DoppelgangerUnderwater
-                | WorldMap::DoppelgangerCrystalCave
-                | WorldMap::DoppelgangerRenewal
-                | WorldMap::DoppelgangerIceZoneNew
-                | WorldMap::ImperialGuardian1
-                | WorldMap::ImperialGuardian2
-                | WorldMap::ImperialGuardian3
-                | WorldMap::ImperialGuardian4
-                | WorldMap::TormentedSquare
-                | WorldMap::BossBattleZone
-        )
+            | WorldMap::BloodCastle2
+            | WorldMap::BloodCastle3
+            | WorldMap::BloodCastle4
+            | WorldMap::BloodCastle5
+            | WorldMap::BloodCastle6
+            | WorldMap::BloodCastle7
+            | WorldMap::BloodCastle8
+            | WorldMap::ChaosCastle1
+            | WorldMap::ChaosCastle2
+            | WorldMap::ChaosCastle3
+            | WorldMap::ChaosCastle4
+            | WorldMap::ChaosCastle5
+            | WorldMap::ChaosCastle6
+            | WorldMap::ChaosCastle7
+            | WorldMap::DevilSquare
+            | WorldMap::DevilSquare2
+            | WorldMap::Crywolf
+            | WorldMap::Kanturu
+            | WorldMap::KanturuRemain
+            | WorldMap::RefineTower
+            | WorldMap::IllusionTemple1
+            | WorldMap::IllusionTemple2
+            | WorldMap::IllusionTemple3
+            | WorldMap::IllusionTemple4
+            | WorldMap::IllusionTemple5
+            | WorldMap::IllusionTempleLeague
+            | WorldMap::IllusionTempleLeague2
+            | WorldMap::DoppelgangerIceZone
+            | WorldMap::DoppelgangerBlazeZone
+            | WorldMap::DoppelgangerUnderwater
+            | WorldMap::DoppelgangerCrystalCave
+            | WorldMap::DoppelgangerRenewal
+            | WorldMap::DoppelgangerIceZoneNew
+            | WorldMap::ImperialGuardian1
+            | WorldMap::ImperialGuardian2
+            | WorldMap::ImperialGuardian3
+            | WorldMap::ImperialGuardian4
+            | WorldMap::TormentedSquare
+            | WorldMap::BossBattleZone => MapCategory::EventDungeon,
+            WorldMap::Unk0 => MapCategory::Unknown,
+            _ => MapCategory::Field,
+        }
     }
 
     /// Tries to create a WorldMap from a World folder ID (e.g. 1 for World1/Lorencia)
@@ -701,6 +876,28 @@ impl WorldMap {
             _ => None,
         }
     }
+
+    /// Looks a world up by name, ignoring case, spaces and punctuation
+    /// (`"Lost Tower"`, `"lost_tower"` and `"losttower"` all match).
+    pub fn from_name(name: &str) -> Option<Self> {
+        let wanted = normalize_name(name);
+        if wanted.is_empty() {
+            return None;
+        }
+        Self::iter().find(|map| normalize_name(map.name()) == wanted)
+    }
+
+    /// True when `name` refers to this world, with the same leniency as [`Self::from_name`].
+    pub fn matches_name(&self, name: &str) -> bool {
+        normalize_name(self.name()) == normalize_name(name)
+    }
+}
+
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
 }
 
 impl std::fmt::Display for WorldMap {
@@ -709,6 +906,28 @@ impl std::fmt::Display for WorldMap {
     }
 }
 
+/// Parses what [`WorldMap::name`] (and `Display`) print, as leniently as
+/// [`WorldMap::from_name`].
+impl std::str::FromStr for WorldMap {
+    type Err = UnknownWorldMap;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::from_name(name).ok_or_else(|| UnknownWorldMap(name.to_string()))
+    }
+}
+
+/// Name that matches no [`WorldMap`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownWorldMap(pub String);
+
+impl std::fmt::Display for UnknownWorldMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown world map {:?}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownWorldMap {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -775,29 +994,32 @@ mod tests {
     }
 
     #[test]
-    fn test_is_login_scene() {
-        assert!(WorldMap::LoginScene.is_login_scene());
-        assert!(WorldMap::CharacterScene.is_login_scene());
-        assert!(WorldMap::NewCharacterScene2.is_login_scene());
-        assert!(!WorldMap::EventSquare.is_login_scene());
-        assert!(!WorldMap::Lorencia.is_login_scene());
-    }
-
-    #[test]
-    fn test_is_pvp_area() {
-        assert!(WorldMap::Arena.is_pvp_area());
-        assert!(WorldMap::Vulcanus.is_pvp_area());
-        assert!(!WorldMap::Lorencia.is_pvp_area());
+    fn test_all_matches_from_id() {
+        let from_ids: Vec<WorldMap> = (1..=u8::MAX).filter_map(WorldMap::from_id).collect();
+        assert_eq!(WorldMap::iter().collect::<Vec<_>>(), from_ids);
+        assert!(!WorldMap::ALL.contains(&WorldMap::Unk0));
     }
 
     #[test]
-    fn test_is_event_dungeon() {
-        assert!(WorldMap::BloodCastle8.is_event_dungeon());
-        assert!(WorldMap::ChaosCastle7.is_event_dungeon());
-        assert!(WorldMap::IllusionTemple1.is_event_dungeon());
-        assert!(WorldMap::DoppelgangerRenewal.is_event_dungeon());
-        assert!(WorldMap::TormentedSquare.is_event_dungeon());
-        assert!(!WorldMap::Lorencia.is_event_dungeon());
+    fn test_category() {
+        assert_eq!(WorldMap::Lorencia.category(), MapCategory::Town);
+        assert_eq!(WorldMap::LostTower.category(), MapCategory::Field);
+        assert_eq!(WorldMap::LoginScene.category(), MapCategory::LoginScene);
+        assert_eq!(
+            WorldMap::NewCharacterScene2.category(),
+            MapCategory::LoginScene
+        );
+        assert_eq!(WorldMap::EventSquare.category(), MapCategory::Field);
+        assert_eq!(WorldMap::Arena.category(), MapCategory::Battleground);
+        assert_eq!(WorldMap::Vulcanus.category(), MapCategory::Battleground);
+        assert_eq!(WorldMap::BloodCastle8.category(), MapCategory::EventDungeon);
+        assert_eq!(WorldMap::ChaosCastle7.category(), MapCategory::EventDungeon);
+        assert_eq!(WorldMap::DevilSquare.category(), MapCategory::EventDungeon);
+        assert_eq!(
+            WorldMap::DoppelgangerRenewal.category(),
+            MapCategory::EventDungeon
+        );
+        assert_eq!(WorldMap::Unk0.category(), MapCategory::Unknown);
     }
 
     #[test]
@@ -805,4 +1027,31 @@ mod tests {
         assert_eq!(format!("{}", WorldMap::ValleyOfLoren), "Valley of Loren");
         assert_eq!(format!("{}", WorldMap::Raklion), "Raklion");
     }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(WorldMap::from_name("Lorencia"), Some(WorldMap::Lorencia));
+        assert_eq!(WorldMap::from_name("lost_tower"), Some(WorldMap::LostTower));
+        assert_eq!(
+            WorldMap::from_name(" LOST TOWER "),
+            Some(WorldMap::LostTower)
+        );
+        assert_eq!(WorldMap::from_name("Atlantis"), None);
+        assert_eq!(WorldMap::from_name(""), None);
+        assert!(WorldMap::Devias.matches_name("devias"));
+    }
+
+    #[test]
+    fn test_from_str_reads_every_name() {
+        for map in WorldMap::iter() {
+            // Loren Market has two folders; the name reads back as the first.
+            let parsed = map.to_string().parse::<WorldMap>().unwrap();
+            assert_eq!(parsed.name(), map.name());
+        }
+        assert_eq!("Loren Market".parse(), Ok(WorldMap::LorenMarket));
+        assert_eq!(
+            "Atlantis".parse::<WorldMap>(),
+            Err(UnknownWorldMap("Atlantis".to_string()))
+        );
+    }
 }
@@ -0,0 +1,616 @@
+//! Localized names of maps, items and skills.
+//!
+//! The English names in [`crate::WorldMap::name`], the item catalog and
+//! [`SKILL_NAMES`] are the keys: each locale maps an English name to its own,
+//! and a name missing from a table stays in English. Proper names (Lorencia,
+//! Kris, Kalima) are left out on purpose. Numbered maps (`Blood Castle 3`)
+//! translate their family name and keep the number, and skill scrolls
+//! (`Scroll of Flame`) reuse the skill's translation.
+//!
+//! Translations are written without accents, like the rest of the client's
+//! UI text, so the bundled fonts render them.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// Language names are shown in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "en")]
+    En,
+    #[serde(rename = "pt-BR")]
+    PtBr,
+    #[serde(rename = "es")]
+    Es,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 3] = [Locale::En, Locale::PtBr, Locale::Es];
+
+    /// BCP 47 tag, e.g. `pt-BR`.
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::PtBr => "pt-BR",
+            Locale::Es => "es",
+        }
+    }
+
+    /// Name of the language in itself, for language pickers.
+    pub fn label(self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::PtBr => "Portugues (Brasil)",
+            Locale::Es => "Espanol",
+        }
+    }
+
+    /// Name of `english` in this locale; see the module docs for the rules.
+    pub fn translate(self, english: &'static str) -> Cow<'static, str> {
+        let Some(table) = self.table() else {
+            return Cow::Borrowed(english);
+        };
+        if let Some(name) = lookup(table.names, english) {
+            return Cow::Borrowed(name);
+        }
+        if let Some((family, number)) = english.rsplit_once(' ') {
+            if number.bytes().all(|byte| byte.is_ascii_digit()) {
+                if let Some(name) = lookup(table.names, family) {
+                    return Cow::Owned(format!("{name} {number}"));
+                }
+            }
+        }
+        if let Some(skill) = english.strip_prefix("Scroll of ") {
+            let skill = lookup(table.names, skill).unwrap_or(skill);
+            return Cow::Owned(format!("{} {skill}", table.scroll_of));
+        }
+        Cow::Borrowed(english)
+    }
+
+    fn table(self) -> Option<&'static Translations> {
+        match self {
+            Locale::En => None,
+            Locale::PtBr => Some(&PT_BR),
+            Locale::Es => Some(&ES),
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Error returned when parsing an unsupported locale tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownLocale(pub String);
+
+impl fmt::Display for UnknownLocale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown locale: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownLocale {}
+
+impl FromStr for Locale {
+    type Err = UnknownLocale;
+
+    /// Accepts the tag in any case, with `-` or `_`, and a bare language
+    /// (`pt`, `es-MX`) for the one locale of that language.
+    fn from_str(tag: &str) -> Result<Self, Self::Err> {
+        let normalized = tag.trim().replace('_', "-").to_ascii_lowercase();
+        let language = normalized.split('-').next().unwrap_or_default();
+        match language {
+            "en" => Ok(Locale::En),
+            "pt" => Ok(Locale::PtBr),
+            "es" => Ok(Locale::Es),
+            _ => Err(UnknownLocale(tag.to_string())),
+        }
+    }
+}
+
+/// English name of a skill by its Season 6 skill id.
+pub fn skill_name(skill_id: u16) -> Option<&'static str> {
+    SKILL_NAMES
+        .binary_search_by_key(&skill_id, |(id, _)| *id)
+        .ok()
+        .map(|slot| SKILL_NAMES[slot].1)
+}
+
+/// [`skill_name`] in `locale`.
+pub fn localized_skill_name(skill_id: u16, locale: Locale) -> Option<Cow<'static, str>> {
+    skill_name(skill_id).map(|name| locale.translate(name))
+}
+
+fn lookup(names: &'static [(&'static str, &'static str)], english: &str) -> Option<&'static str> {
+    names
+        .iter()
+        .find(|(key, _)| *key == english)
+        .map(|(_, name)| *name)
+}
+
+struct Translations {
+    /// Prefix of skill scrolls, in place of `Scroll of`.
+    scroll_of: &'static str,
+    /// English name to localized name.
+    names: &'static [(&'static str, &'static str)],
+}
+
+/// Skill names by id, sorted by id so lookups can binary search.
+pub static SKILL_NAMES: &[(u16, &str)] = &[
+    (1, "Poison"),
+    (2, "Meteorite"),
+    (3, "Lightning"),
+    (4, "Fire Ball"),
+    (5, "Flame"),
+    (6, "Teleport"),
+    (7, "Ice"),
+    (8, "Twister"),
+    (9, "Evil Spirit"),
+    (10, "Hell Fire"),
+    (11, "Power Wave"),
+    (12, "Aqua Beam"),
+    (13, "Cometfall"),
+    (14, "Inferno"),
+    (15, "Teleport Ally"),
+    (16, "Soul Barrier"),
+    (17, "Energy Ball"),
+    (18, "Defense"),
+    (19, "Falling Slash"),
+    (20, "Lunge"),
+    (21, "Uppercut"),
+    (22, "Cyclone"),
+    (23, "Slash"),
+    (24, "Triple Shot"),
+    (25, "Power Shot"),
+    (26, "Heal"),
+    (27, "Greater Defense"),
+    (28, "Greater Damage"),
+    (30, "Summon Goblin"),
+    (31, "Summon Stone Golem"),
+    (32, "Summon Assassin"),
+    (33, "Summon Elite Yeti"),
+    (34, "Summon Dark Knight"),
+    (35, "Summon Bali"),
+    (36, "Summon Soldier"),
+    (38, "Decay"),
+    (39, "Ice Storm"),
+    (40, "Nova"),
+    (41, "Twisting Slash"),
+    (42, "Rageful Blow"),
+    (43, "Death Stab"),
+    (46, "Starfall"),
+    (47, "Impale"),
+    (48, "Greater Fortitude"),
+    (49, "Fire Breath"),
+    (51, "Ice Arrow"),
+    (52, "Penetration"),
+    (55, "Fire Slash"),
+    (56, "Power Slash"),
+    (57, "Spiral Slash"),
+    (59, "Combo"),
+    (60, "Force"),
+    (61, "Fire Burst"),
+    (62, "Earthshake"),
+    (63, "Summon"),
+    (64, "Increase Critical"),
+    (65, "Electric Spike"),
+    (66, "Force Wave"),
+    (67, "Stun"),
+    (68, "Cancel Stun"),
+    (69, "Swell Mana"),
+    (70, "Invisibility"),
+    (71, "Cancel Invisibility"),
+    (72, "Abolish Magic"),
+    (73, "Mana Rays"),
+    (74, "Fire Blast"),
+    (76, "Plasma Storm"),
+    (77, "Infinity Arrow"),
+    (78, "Fire Scream"),
+    (79, "Explosion"),
+    (213, "Shield Burn"),
+    (214, "Drain Life"),
+    (215, "Chain Lightning"),
+    (217, "Damage Reflection"),
+    (218, "Berserker"),
+    (219, "Sleep"),
+    (221, "Weakness"),
+    (222, "Innovation"),
+    (223, "Explosion"),
+    (224, "Requiem"),
+    (225, "Pollution"),
+    (230, "Lightning Shock"),
+    (232, "Blow of Destruction"),
+    (233, "Swell of Magic Power"),
+    (234, "Recovery"),
+    (235, "Multi-Shot"),
+    (236, "Flame Strike"),
+    (237, "Gigantic Storm"),
+    (260, "Killing Blow"),
+    (261, "Beast Uppercut"),
+    (262, "Chain Drive"),
+    (263, "Dark Side"),
+    (264, "Dragon Roar"),
+    (265, "Dragon Slasher"),
+    (266, "Ignore Defense"),
+    (267, "Increase Health"),
+    (268, "Increase Block"),
+    (269, "Charge"),
+    (270, "Phoenix Shot"),
+    (495, "Earth Prison"),
+    (565, "Blood Howling"),
+];
+
+static PT_BR: Translations = Translations {
+    scroll_of: "Pergaminho de",
+    names: &[
+        // Maps
+        ("Unknown", "Desconhecido"),
+        ("Dungeon", "Masmorra"),
+        ("Lost Tower", "Torre Perdida"),
+        ("Exile", "Exilio"),
+        ("Devil Square", "Praca do Diabo"),
+        ("Blood Castle", "Castelo Sangrento"),
+        ("Chaos Castle", "Castelo do Caos"),
+        ("Valley of Loren", "Vale de Loren"),
+        ("Land of Trials", "Terra das Provacoes"),
+        ("Refine Tower", "Torre de Refino"),
+        ("Silent Map", "Mapa Silencioso"),
+        ("Balgass Barracks", "Quartel de Balgass"),
+        ("Balgass Refuge", "Refugio de Balgass"),
+        ("Illusion Temple", "Templo da Ilusao"),
+        ("Character Selection", "Selecao de Personagem"),
+        ("Login Scene", "Tela de Login"),
+        ("Swamp of Peace", "Pantano da Paz"),
+        ("Santa Village", "Vila do Papai Noel"),
+        ("Duel Arena", "Arena de Duelo"),
+        ("Imperial Guardian", "Guardiao Imperial"),
+        ("Event Square", "Praca de Eventos"),
+        ("Loren Market", "Mercado de Loren"),
+        ("New Arena", "Nova Arena"),
+        ("Uruk Mountain", "Montanha Uruk"),
+        ("Tormented Square", "Praca Atormentada"),
+        ("Nixies Lake", "Lago das Nixies"),
+        ("Deep Dungeon", "Masmorra Profunda"),
+        ("Place of Qualification", "Local de Qualificacao"),
+        ("Swamp of Darkness", "Pantano da Escuridao"),
+        ("Kubera Mine", "Mina de Kubera"),
+        ("Abyss of Atlans", "Abismo de Atlans"),
+        ("Scorched Tunnels", "Tuneis Chamuscados"),
+        ("Temple of Arnil", "Templo de Arnil"),
+        ("Kanturu Undergrounds", "Subterraneos de Kanturu"),
+        ("Ignis Volcano", "Vulcao Ignis"),
+        ("Boss Battle Zone", "Zona de Chefes"),
+        ("Tormenta Island", "Ilha Tormenta"),
+        // Items
+        ("Short Sword", "Espada Curta"),
+        ("Small Axe", "Machado Pequeno"),
+        ("Hand Axe", "Machadinha"),
+        ("Double Axe", "Machado Duplo"),
+        ("Battle Axe", "Machado de Batalha"),
+        ("Mace", "Maca"),
+        ("Great Hammer", "Grande Martelo"),
+        ("Spear", "Lanca"),
+        ("Short Bow", "Arco Curto"),
+        ("Bow", "Arco"),
+        ("Elven Bow", "Arco Elfico"),
+        ("Battle Bow", "Arco de Batalha"),
+        ("Bolt", "Virotes"),
+        ("Crossbow", "Besta"),
+        ("Arrows", "Flechas"),
+        ("Small Shield", "Escudo Pequeno"),
+        ("Wings of Elf", "Asas de Elfo"),
+        ("Wings of Heaven", "Asas do Ceu"),
+        ("Wings of Satan", "Asas de Satan"),
+        ("Wings of Spirits", "Asas dos Espiritos"),
+        ("Wings of Soul", "Asas da Alma"),
+        ("Wings of Dragon", "Asas do Dragao"),
+        ("Wings of Darkness", "Asas da Escuridao"),
+        ("Guardian Angel", "Anjo Guardiao"),
+        ("Ring of Ice", "Anel de Gelo"),
+        ("Ring of Poison", "Anel de Veneno"),
+        ("Pendant of Lightning", "Pingente do Raio"),
+        ("Pendant of Fire", "Pingente de Fogo"),
+        ("Apple", "Maca"),
+        ("Small Healing Potion", "Pocao de Cura Pequena"),
+        ("Healing Potion", "Pocao de Cura"),
+        ("Large Healing Potion", "Pocao de Cura Grande"),
+        ("Small Mana Potion", "Pocao de Mana Pequena"),
+        ("Mana Potion", "Pocao de Mana"),
+        ("Large Mana Potion", "Pocao de Mana Grande"),
+        ("Antidote", "Antidoto"),
+        ("Ale", "Cerveja"),
+        ("Town Portal Scroll", "Pergaminho de Retorno"),
+        ("Jewel of Chaos", "Joia do Caos"),
+        ("Jewel of Bless", "Joia da Bencao"),
+        ("Jewel of Soul", "Joia da Alma"),
+        ("Jewel of Life", "Joia da Vida"),
+        ("Jewel of Creation", "Joia da Criacao"),
+        ("Jewel of Guardian", "Joia do Guardiao"),
+        ("Mirror of Dimensions", "Espelho das Dimensoes"),
+        // Skills
+        ("Poison", "Veneno"),
+        ("Meteorite", "Meteoro"),
+        ("Lightning", "Relampago"),
+        ("Fire Ball", "Bola de Fogo"),
+        ("Flame", "Chama"),
+        ("Teleport", "Teleporte"),
+        ("Ice", "Gelo"),
+        ("Twister", "Tornado"),
+        ("Evil Spirit", "Espirito Maligno"),
+        ("Hell Fire", "Fogo do Inferno"),
+        ("Power Wave", "Onda de Poder"),
+        ("Aqua Beam", "Raio Aquatico"),
+        ("Cometfall", "Chuva de Cometas"),
+        ("Teleport Ally", "Teleportar Aliado"),
+        ("Soul Barrier", "Barreira da Alma"),
+        ("Energy Ball", "Bola de Energia"),
+        ("Defense", "Defesa"),
+        ("Falling Slash", "Golpe Descendente"),
+        ("Lunge", "Investida"),
+        ("Uppercut", "Gancho"),
+        ("Cyclone", "Ciclone"),
+        ("Slash", "Corte"),
+        ("Triple Shot", "Tiro Triplo"),
+        ("Power Shot", "Tiro Poderoso"),
+        ("Heal", "Cura"),
+        ("Greater Defense", "Defesa Maior"),
+        ("Greater Damage", "Dano Maior"),
+        ("Ice Storm", "Tempestade de Gelo"),
+        ("Twisting Slash", "Corte Giratorio"),
+        ("Rageful Blow", "Golpe Furioso"),
+        ("Death Stab", "Estocada Mortal"),
+        ("Starfall", "Chuva de Estrelas"),
+        ("Impale", "Empalar"),
+        ("Greater Fortitude", "Fortitude Maior"),
+        ("Fire Breath", "Sopro de Fogo"),
+        ("Ice Arrow", "Flecha de Gelo"),
+        ("Penetration", "Penetracao"),
+        ("Fire Slash", "Corte de Fogo"),
+        ("Power Slash", "Corte Poderoso"),
+        ("Force", "Forca"),
+        ("Fire Burst", "Explosao de Fogo"),
+        ("Earthshake", "Terremoto"),
+        ("Summon", "Invocar"),
+        ("Increase Critical", "Aumentar Critico"),
+        ("Electric Spike", "Espinho Eletrico"),
+        ("Stun", "Atordoar"),
+        ("Invisibility", "Invisibilidade"),
+        ("Explosion", "Explosao"),
+        ("Drain Life", "Drenar Vida"),
+        ("Chain Lightning", "Relampago em Cadeia"),
+        ("Sleep", "Sono"),
+        ("Weakness", "Fraqueza"),
+        ("Recovery", "Recuperacao"),
+        ("Multi-Shot", "Tiro Multiplo"),
+        ("Gigantic Storm", "Tempestade Gigante"),
+        ("Killing Blow", "Golpe Mortal"),
+        ("Dragon Roar", "Rugido do Dragao"),
+        ("Increase Health", "Aumentar Vida"),
+        ("Increase Block", "Aumentar Bloqueio"),
+    ],
+};
+
+static ES: Translations = Translations {
+    scroll_of: "Pergamino de",
+    names: &[
+        // Maps
+        ("Unknown", "Desconocido"),
+        ("Dungeon", "Mazmorra"),
+        ("Lost Tower", "Torre Perdida"),
+        ("Exile", "Exilio"),
+        ("Devil Square", "Plaza del Diablo"),
+        ("Blood Castle", "Castillo de Sangre"),
+        ("Chaos Castle", "Castillo del Caos"),
+        ("Valley of Loren", "Valle de Loren"),
+        ("Land of Trials", "Tierra de las Pruebas"),
+        ("Refine Tower", "Torre de Refinamiento"),
+        ("Silent Map", "Mapa Silencioso"),
+        ("Balgass Barracks", "Cuartel de Balgass"),
+        ("Balgass Refuge", "Refugio de Balgass"),
+        ("Illusion Temple", "Templo de la Ilusion"),
+        ("Character Selection", "Seleccion de Personaje"),
+        ("Login Scene", "Pantalla de Inicio"),
+        ("Swamp of Peace", "Pantano de la Paz"),
+        ("Santa Village", "Aldea de Santa"),
+        ("Duel Arena", "Arena de Duelo"),
+        ("Imperial Guardian", "Guardian Imperial"),
+        ("Event Square", "Plaza de Eventos"),
+        ("Loren Market", "Mercado de Loren"),
+        ("New Arena", "Nueva Arena"),
+        ("Uruk Mountain", "Montana Uruk"),
+        ("Tormented Square", "Plaza Atormentada"),
+        ("Nixies Lake", "Lago de las Nixies"),
+        ("Deep Dungeon", "Mazmorra Profunda"),
+        ("Place of Qualification", "Lugar de Clasificacion"),
+        ("Swamp of Darkness", "Pantano de la Oscuridad"),
+        ("Kubera Mine", "Mina de Kubera"),
+        ("Abyss of Atlans", "Abismo de Atlans"),
+        ("Scorched Tunnels", "Tuneles Abrasados"),
+        ("Temple of Arnil", "Templo de Arnil"),
+        ("Kanturu Undergrounds", "Subterraneos de Kanturu"),
+        ("Ignis Volcano", "Volcan Ignis"),
+        ("Boss Battle Zone", "Zona de Jefes"),
+        ("Tormenta Island", "Isla Tormenta"),
+        // Items
+        ("Short Sword", "Espada Corta"),
+        ("Small Axe", "Hacha Pequena"),
+        ("Hand Axe", "Hacha de Mano"),
+        ("Double Axe", "Hacha Doble"),
+        ("Battle Axe", "Hacha de Batalla"),
+        ("Mace", "Maza"),
+        ("Great Hammer", "Gran Martillo"),
+        ("Spear", "Lanza"),
+        ("Short Bow", "Arco Corto"),
+        ("Bow", "Arco"),
+        ("Elven Bow", "Arco Elfico"),
+        ("Battle Bow", "Arco de Batalla"),
+        ("Bolt", "Virotes"),
+        ("Crossbow", "Ballesta"),
+        ("Arrows", "Flechas"),
+        ("Small Shield", "Escudo Pequeno"),
+        ("Wings of Elf", "Alas de Elfo"),
+        ("Wings of Heaven", "Alas del Cielo"),
+        ("Wings of Satan", "Alas de Satan"),
+        ("Wings of Spirits", "Alas de los Espiritus"),
+        ("Wings of Soul", "Alas del Alma"),
+        ("Wings of Dragon", "Alas del Dragon"),
+        ("Wings of Darkness", "Alas de la Oscuridad"),
+        ("Guardian Angel", "Angel Guardian"),
+        ("Ring of Ice", "Anillo de Hielo"),
+        ("Ring of Poison", "Anillo de Veneno"),
+        ("Pendant of Lightning", "Colgante del Rayo"),
+        ("Pendant of Fire", "Colgante de Fuego"),
+        ("Apple", "Manzana"),
+        ("Small Healing Potion", "Pocion de Curacion Pequena"),
+        ("Healing Potion", "Pocion de Curacion"),
+        ("Large Healing Potion", "Pocion de Curacion Grande"),
+        ("Small Mana Potion", "Pocion de Mana Pequena"),
+        ("Mana Potion", "Pocion de Mana"),
+        ("Large Mana Potion", "Pocion de Mana Grande"),
+        ("Antidote", "Antidoto"),
+        ("Ale", "Cerveza"),
+        ("Town Portal Scroll", "Pergamino de Regreso"),
+        ("Jewel of Chaos", "Joya del Caos"),
+        ("Jewel of Bless", "Joya de la Bendicion"),
+        ("Jewel of Soul", "Joya del Alma"),
+        ("Jewel of Life", "Joya de la Vida"),
+        ("Jewel of Creation", "Joya de la Creacion"),
+        ("Jewel of Guardian", "Joya del Guardian"),
+        ("Mirror of Dimensions", "Espejo de las Dimensiones"),
+        // Skills
+        ("Poison", "Veneno"),
+        ("Meteorite", "Meteorito"),
+        ("Lightning", "Relampago"),
+        ("Fire Ball", "Bola de Fuego"),
+        ("Flame", "Llama"),
+        ("Teleport", "Teletransporte"),
+        ("Ice", "Hielo"),
+        ("Twister", "Tornado"),
+        ("Evil Spirit", "Espiritu Maligno"),
+        ("Hell Fire", "Fuego Infernal"),
+        ("Power Wave", "Onda de Poder"),
+        ("Aqua Beam", "Rayo Acuatico"),
+        ("Cometfall", "Lluvia de Cometas"),
+        ("Inferno", "Infierno"),
+        ("Teleport Ally", "Teletransportar Aliado"),
+        ("Soul Barrier", "Barrera del Alma"),
+        ("Energy Ball", "Bola de Energia"),
+        ("Defense", "Defensa"),
+        ("Falling Slash", "Tajo Descendente"),
+        ("Lunge", "Estocada"),
+        ("Uppercut", "Gancho"),
+        ("Cyclone", "Ciclon"),
+        ("Slash", "Tajo"),
+        ("Triple Shot", "Disparo Triple"),
+        ("Power Shot", "Disparo Poderoso"),
+        ("Heal", "Curacion"),
+        ("Greater Defense", "Defensa Mayor"),
+        ("Greater Damage", "Dano Mayor"),
+        ("Ice Storm", "Tormenta de Hielo"),
+        ("Twisting Slash", "Tajo Giratorio"),
+        ("Rageful Blow", "Golpe Furioso"),
+        ("Death Stab", "Punalada Mortal"),
+        ("Starfall", "Lluvia de Estrellas"),
+        ("Impale", "Empalar"),
+        ("Greater Fortitude", "Fortaleza Mayor"),
+        ("Fire Breath", "Aliento de Fuego"),
+        ("Ice Arrow", "Flecha de Hielo"),
+        ("Penetration", "Penetracion"),
+        ("Fire Slash", "Tajo de Fuego"),
+        ("Power Slash", "Tajo Poderoso"),
+        ("Force", "Fuerza"),
+        ("Fire Burst", "Estallido de Fuego"),
+        ("Earthshake", "Terremoto"),
+        ("Summon", "Invocar"),
+        ("Increase Critical", "Aumentar Critico"),
+        ("Electric Spike", "Pua Electrica"),
+        ("Stun", "Aturdir"),
+        ("Invisibility", "Invisibilidad"),
+        ("Explosion", "Explosion"),
+        ("Drain Life", "Drenar Vida"),
+        ("Chain Lightning", "Relampago en Cadena"),
+        ("Sleep", "Sueno"),
+        ("Weakness", "Debilidad"),
+        ("Recovery", "Recuperacion"),
+        ("Multi-Shot", "Disparo Multiple"),
+        ("Gigantic Storm", "Tormenta Gigante"),
+        ("Killing Blow", "Golpe Mortal"),
+        ("Dragon Roar", "Rugido del Dragon"),
+        ("Increase Health", "Aumentar Vida"),
+        ("Increase Block", "Aumentar Bloqueo"),
+    ],
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::items;
+    use crate::{ItemCode, WorldMap};
+
+    #[test]
+    fn names_translate_with_english_fallback() {
+        assert_eq!(
+            WorldMap::BloodCastle3.localized_name(Locale::PtBr),
+            "Castelo Sangrento 3"
+        );
+        assert_eq!(
+            WorldMap::LostTower.localized_name(Locale::Es),
+            "Torre Perdida"
+        );
+        assert_eq!(WorldMap::Lorencia.localized_name(Locale::PtBr), "Lorencia");
+        assert_eq!(WorldMap::Kalima2.localized_name(Locale::Es), "Kalima 2");
+        assert_eq!(WorldMap::Dungeon.localized_name(Locale::En), "Dungeon");
+
+        let scroll = ItemCode::new(15, 4).definition().unwrap();
+        assert_eq!(scroll.localized_name(Locale::PtBr), "Pergaminho de Chama");
+        assert_eq!(scroll.localized_name(Locale::En), "Scroll of Flame");
+
+        assert_eq!(
+            localized_skill_name(43, Locale::Es).as_deref(),
+            Some("Punalada Mortal")
+        );
+        assert_eq!(localized_skill_name(29, Locale::Es), None);
+    }
+
+    #[test]
+    fn tables_only_hold_known_names() {
+        assert!(SKILL_NAMES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        let known = |english: &str| {
+            std::iter::once(WorldMap::Unk0)
+                .chain(WorldMap::iter())
+                .any(|map| {
+                    let name = map.name();
+                    name == english
+                        || name
+                            .rsplit_once(' ')
+                            .is_some_and(|(family, _)| family == english)
+                })
+                || items().iter().any(|item| item.name == english)
+                || SKILL_NAMES.iter().any(|(_, name)| *name == english)
+        };
+        for table in [&PT_BR, &ES] {
+            for (english, _) in table.names {
+                assert!(known(english), "no map, item or skill named {english}");
+            }
+        }
+    }
+
+    #[test]
+    fn tags_parse_loosely() {
+        assert_eq!("pt-BR".parse::<Locale>(), Ok(Locale::PtBr));
+        assert_eq!("pt_br".parse::<Locale>(), Ok(Locale::PtBr));
+        assert_eq!("es-MX".parse::<Locale>(), Ok(Locale::Es));
+        assert_eq!("EN".parse::<Locale>(), Ok(Locale::En));
+        assert!("fr".parse::<Locale>().is_err());
+        for locale in Locale::ALL {
+            assert_eq!(locale.code().parse::<Locale>(), Ok(locale));
+        }
+    }
+}
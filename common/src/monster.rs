@@ -0,0 +1,362 @@
+//! Monster catalog shared by the server's spawns and AI and the client's
+//! nameplates.
+//!
+//! Stats follow the Season 6 `Monster.txt`: level, HP, damage range, defense,
+//! walking speed and the map the monster is native to.
+
+use crate::combat::CombatStats;
+use crate::WorldMap;
+
+/// Attack interval of catalog monsters; `Monster.txt` attack speeds are all
+/// close to it.
+const MONSTER_ATTACK_INTERVAL_MS: u32 = 1_000;
+
+/// Monsters of the Season 6 `Monster.txt`.
+///
+/// The ID values are the monster indexes the server spawns and quests refer to.
+/// Event and invasion monsters (golden invaders, Red Dragon) are left out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u16)]
+pub enum MonsterKind {
+    BullFighter = 0,
+    Hound = 1,
+    BudgeDragon = 2,
+    Spider = 3,
+    EliteBullFighter = 4,
+    HellHound = 5,
+    Lich = 6,
+    Giant = 7,
+    PoisonBullFighter = 8,
+    ThunderLich = 9,
+    DarkKnight = 10,
+    Ghost = 11,
+    Larva = 12,
+    HellSpider = 13,
+    SkeletonWarrior = 14,
+    SkeletonArcher = 15,
+    SkeletonCaptain = 16,
+    Cyclops = 17,
+    Gorgon = 18,
+    Yeti = 19,
+    EliteYeti = 20,
+    Assassin = 21,
+    IceMonster = 22,
+    Hommerd = 23,
+    Worm = 24,
+    IceQueen = 25,
+    Goblin = 26,
+    ChainScorpion = 27,
+    BeetleMonster = 28,
+    Hunter = 29,
+    ForestMonster = 30,
+    Agon = 31,
+    StoneGolem = 32,
+    EliteGoblin = 33,
+    CursedWizard = 34,
+    DeathGorgon = 35,
+    Shadow = 36,
+    Devil = 37,
+    Balrog = 38,
+    PoisonShadow = 39,
+    DeathKnight = 40,
+    DeathCow = 41,
+    Bahamut = 45,
+    Vepar = 46,
+    Valkyrie = 47,
+    LizardKing = 48,
+    Hydra = 49,
+    GreatBahamut = 51,
+    SilverValkyrie = 52,
+    DeathKing = 55,
+    DeathBone = 56,
+    IronWheel = 57,
+    Tantalos = 58,
+    Zaikan = 59,
+    BloodyWolf = 60,
+    BeamKnight = 61,
+    Mutant = 62,
+    DeathBeamKnight = 63,
+}
+
+impl MonsterKind {
+    pub const ALL: [Self; 58] = [
+        Self::BullFighter,
+        Self::Hound,
+        Self::BudgeDragon,
+        Self::Spider,
+        Self::EliteBullFighter,
+        Self::HellHound,
+        Self::Lich,
+        Self::Giant,
+        Self::PoisonBullFighter,
+        Self::ThunderLich,
+        Self::DarkKnight,
+        Self::Ghost,
+        Self::Larva,
+        Self::HellSpider,
+        Self::SkeletonWarrior,
+        Self::SkeletonArcher,
+        Self::SkeletonCaptain,
+        Self::Cyclops,
+        Self::Gorgon,
+        Self::Yeti,
+        Self::EliteYeti,
+        Self::Assassin,
+        Self::IceMonster,
+        Self::Hommerd,
+        Self::Worm,
+        Self::IceQueen,
+        Self::Goblin,
+        Self::ChainScorpion,
+        Self::BeetleMonster,
+        Self::Hunter,
+        Self::ForestMonster,
+        Self::Agon,
+        Self::StoneGolem,
+        Self::EliteGoblin,
+        Self::CursedWizard,
+        Self::DeathGorgon,
+        Self::Shadow,
+        Self::Devil,
+        Self::Balrog,
+        Self::PoisonShadow,
+        Self::DeathKnight,
+        Self::DeathCow,
+        Self::Bahamut,
+        Self::Vepar,
+        Self::Valkyrie,
+        Self::LizardKing,
+        Self::Hydra,
+        Self::GreatBahamut,
+        Self::SilverValkyrie,
+        Self::DeathKing,
+        Self::DeathBone,
+        Self::IronWheel,
+        Self::Tantalos,
+        Self::Zaikan,
+        Self::BloodyWolf,
+        Self::BeamKnight,
+        Self::Mutant,
+        Self::DeathBeamKnight,
+    ];
+
+    pub fn id(self) -> u16 {
+        self as u16
+    }
+
+    pub fn from_id(id: u16) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.id() == id)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::BullFighter => "Bull Fighter",
+            Self::Hound => "Hound",
+            Self::BudgeDragon => "Budge Dragon",
+            Self::Spider => "Spider",
+            Self::EliteBullFighter => "Elite Bull Fighter",
+            Self::HellHound => "Hell Hound",
+            Self::Lich => "Lich",
+            Self::Giant => "Giant",
+            Self::PoisonBullFighter => "Poison Bull Fighter",
+            Self::ThunderLich => "Thunder Lich",
+            Self::DarkKnight => "Dark Knight",
+            Self::Ghost => "Ghost",
+            Self::Larva => "Larva",
+            Self::HellSpider => "Hell Spider",
+            Self::SkeletonWarrior => "Skeleton Warrior",
+            Self::SkeletonArcher => "Skeleton Archer",
+            Self::SkeletonCaptain => "Skeleton Captain",
+            Self::Cyclops => "Cyclops",
+            Self::Gorgon => "Gorgon",
+            Self::Yeti => "Yeti",
+            Self::EliteYeti => "Elite Yeti",
+            Self::Assassin => "Assassin",
+            Self::IceMonster => "Ice Monster",
+            Self::Hommerd => "Hommerd",
+            Self::Worm => "Worm",
+            Self::IceQueen => "Ice Queen",
+            Self::Goblin => "Goblin",
+            Self::ChainScorpion => "Chain Scorpion",
+            Self::BeetleMonster => "Beetle Monster",
+            Self::Hunter => "Hunter",
+            Self::ForestMonster => "Forest Monster",
+            Self::Agon => "Agon",
+            Self::StoneGolem => "Stone Golem",
+            Self::EliteGoblin => "Elite Goblin",
+            Self::CursedWizard => "Cursed Wizard",
+            Self::DeathGorgon => "Death Gorgon",
+            Self::Shadow => "Shadow",
+            Self::Devil => "Devil",
+            Self::Balrog => "Balrog",
+            Self::PoisonShadow => "Poison Shadow",
+            Self::DeathKnight => "Death Knight",
+            Self::DeathCow => "Death Cow",
+            Self::Bahamut => "Bahamut",
+            Self::Vepar => "Vepar",
+            Self::Valkyrie => "Valkyrie",
+            Self::LizardKing => "Lizard King",
+            Self::Hydra => "Hydra",
+            Self::GreatBahamut => "Great Bahamut",
+            Self::SilverValkyrie => "Silver Valkyrie",
+            Self::DeathKing => "Death King",
+            Self::DeathBone => "Death Bone",
+            Self::IronWheel => "Iron Wheel",
+            Self::Tantalos => "Tantalos",
+            Self::Zaikan => "Zaikan",
+            Self::BloodyWolf => "Bloody Wolf",
+            Self::BeamKnight => "Beam Knight",
+            Self::Mutant => "Mutant",
+            Self::DeathBeamKnight => "Death Beam Knight",
+        }
+    }
+
+    /// Level, combat stats and home map of the monster.
+    pub fn info(self) -> MonsterInfo {
+        match self {
+            Self::BullFighter => info(6, 100, (16, 20), 6, 400, WorldMap::Lorencia),
+            Self::Hound => info(9, 140, (22, 27), 9, 400, WorldMap::Lorencia),
+            Self::BudgeDragon => info(4, 60, (10, 13), 3, 400, WorldMap::Lorencia),
+            Self::Spider => info(2, 30, (4, 7), 1, 600, WorldMap::Lorencia),
+            Self::EliteBullFighter => info(12, 190, (31, 36), 12, 400, WorldMap::Lorencia),
+            Self::HellHound => info(38, 1400, (130, 150), 70, 400, WorldMap::Dungeon),
+            Self::Lich => info(14, 255, (41, 46), 14, 400, WorldMap::Lorencia),
+            Self::Giant => info(17, 400, (57, 62), 18, 400, WorldMap::Lorencia),
+            Self::PoisonBullFighter => info(46, 2500, (150, 165), 100, 400, WorldMap::Dungeon),
+            Self::ThunderLich => info(44, 2000, (140, 155), 80, 400, WorldMap::Dungeon),
+            Self::DarkKnight => info(48, 3000, (165, 180), 110, 400, WorldMap::Dungeon),
+            Self::Ghost => info(20, 530, (65, 70), 24, 400, WorldMap::Dungeon),
+            Self::Larva => info(25, 750, (75, 80), 30, 400, WorldMap::Dungeon),
+            Self::HellSpider => info(40, 1600, (135, 150), 75, 400, WorldMap::Dungeon),
+            Self::SkeletonWarrior => info(19, 525, (68, 74), 22, 400, WorldMap::Dungeon),
+            Self::SkeletonArcher => info(24, 800, (85, 90), 28, 400, WorldMap::Dungeon),
+            Self::SkeletonCaptain => info(26, 1000, (90, 95), 35, 400, WorldMap::Dungeon),
+            Self::Cyclops => info(28, 1150, (100, 105), 37, 400, WorldMap::Dungeon),
+            Self::Gorgon => info(55, 6000, (200, 220), 140, 400, WorldMap::Dungeon),
+            Self::Yeti => info(30, 900, (105, 110), 37, 400, WorldMap::Devias),
+            Self::EliteYeti => info(36, 1200, (120, 130), 50, 400, WorldMap::Devias),
+            Self::Assassin => info(26, 800, (95, 100), 33, 400, WorldMap::Devias),
+            Self::IceMonster => info(22, 650, (80, 85), 27, 400, WorldMap::Devias),
+            Self::Hommerd => info(24, 700, (85, 90), 29, 400, WorldMap::Devias),
+            Self::Worm => info(20, 600, (75, 80), 25, 400, WorldMap::Devias),
+            Self::IceQueen => info(52, 4000, (155, 175), 90, 400, WorldMap::Devias),
+            Self::Goblin => info(3, 45, (7, 10), 2, 400, WorldMap::Noria),
+            Self::ChainScorpion => info(5, 80, (13, 17), 4, 400, WorldMap::Noria),
+            Self::BeetleMonster => info(10, 165, (26, 31), 10, 400, WorldMap::Noria),
+            Self::Hunter => info(13, 220, (36, 41), 13, 400, WorldMap::Noria),
+            Self::ForestMonster => info(15, 295, (46, 51), 15, 400, WorldMap::Noria),
+            Self::Agon => info(16, 340, (51, 57), 16, 400, WorldMap::Noria),
+            Self::StoneGolem => info(18, 465, (62, 67), 20, 800, WorldMap::Noria),
+            Self::EliteGoblin => info(8, 120, (19, 23), 8, 400, WorldMap::Noria),
+            Self::CursedWizard => info(54, 4000, (180, 195), 110, 400, WorldMap::LostTower),
+            Self::DeathGorgon => info(64, 6000, (230, 260), 160, 400, WorldMap::LostTower),
+            Self::Shadow => info(47, 2800, (155, 160), 95, 400, WorldMap::LostTower),
+            Self::Devil => info(60, 5000, (220, 240), 150, 400, WorldMap::LostTower),
+            Self::Balrog => info(66, 9000, (250, 270), 180, 400, WorldMap::LostTower),
+            Self::PoisonShadow => info(50, 3500, (165, 175), 105, 400, WorldMap::LostTower),
+            Self::DeathKnight => info(62, 5500, (225, 250), 155, 400, WorldMap::LostTower),
+            Self::DeathCow => info(57, 4500, (210, 230), 130, 400, WorldMap::LostTower),
+            Self::Bahamut => info(43, 2000, (140, 150), 80, 400, WorldMap::Atlans),
+            Self::Vepar => info(45, 2500, (145, 155), 85, 400, WorldMap::Atlans),
+            Self::Valkyrie => info(46, 2600, (150, 160), 90, 400, WorldMap::Atlans),
+            Self::LizardKing => info(66, 10000, (260, 290), 180, 400, WorldMap::Atlans),
+            Self::Hydra => info(74, 22000, (300, 340), 220, 800, WorldMap::Atlans),
+            Self::GreatBahamut => info(50, 3500, (170, 180), 100, 400, WorldMap::Atlans),
+            Self::SilverValkyrie => info(55, 4500, (190, 205), 120, 400, WorldMap::Atlans),
+            Self::DeathKing => info(72, 12000, (290, 320), 200, 400, WorldMap::LostTower),
+            Self::DeathBone => info(68, 10000, (270, 300), 190, 400, WorldMap::LostTower),
+            Self::IronWheel => info(80, 20000, (330, 360), 240, 400, WorldMap::Tarkan),
+            Self::Tantalos => info(83, 22000, (350, 380), 250, 400, WorldMap::Tarkan),
+            Self::Zaikan => info(90, 30000, (400, 450), 290, 400, WorldMap::Tarkan),
+            Self::BloodyWolf => info(77, 16000, (320, 350), 230, 400, WorldMap::Tarkan),
+            Self::BeamKnight => info(84, 24000, (360, 390), 260, 400, WorldMap::Tarkan),
+            Self::Mutant => info(72, 13000, (300, 330), 210, 400, WorldMap::Tarkan),
+            Self::DeathBeamKnight => info(93, 35000, (420, 470), 300, 400, WorldMap::Tarkan),
+        }
+    }
+}
+
+/// Static stats of a monster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonsterInfo {
+    pub level: u16,
+    pub max_hp: u32,
+    pub min_damage: u32,
+    pub max_damage: u32,
+    pub defense: u32,
+    /// Milliseconds to walk one tile.
+    pub move_speed_ms: u16,
+    /// Map the monster spawns on outside of events.
+    pub native_map: WorldMap,
+}
+
+impl MonsterInfo {
+    pub fn combat_stats(&self) -> CombatStats {
+        CombatStats {
+            max_hp: self.max_hp,
+            min_damage: self.min_damage,
+            max_damage: self.max_damage,
+            defense: self.defense,
+            attack_interval_ms: MONSTER_ATTACK_INTERVAL_MS,
+        }
+    }
+}
+
+const fn info(
+    level: u16,
+    max_hp: u32,
+    (min_damage, max_damage): (u32, u32),
+    defense: u32,
+    move_speed_ms: u16,
+    native_map: WorldMap,
+) -> MonsterInfo {
+    MonsterInfo {
+        level,
+        max_hp,
+        min_damage,
+        max_damage,
+        defense,
+        move_speed_ms,
+        native_map,
+    }
+}
+
+impl std::fmt::Display for MonsterKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_round_trip() {
+        for kind in MonsterKind::ALL {
+            assert_eq!(MonsterKind::from_id(kind.id()), Some(kind));
+        }
+        assert_eq!(MonsterKind::from_id(3), Some(MonsterKind::Spider));
+        assert_eq!(MonsterKind::from_id(42), None);
+    }
+
+    #[test]
+    fn stats_are_consistent() {
+        for kind in MonsterKind::ALL {
+            let info = kind.info();
+            assert!(info.level > 0, "{kind}");
+            assert!(info.min_damage <= info.max_damage, "{kind}");
+            assert!(info.max_hp > 0, "{kind}");
+        }
+    }
+
+    #[test]
+    fn lorencia_monsters_are_weaker_than_tarkan_ones() {
+        let spider = MonsterKind::Spider.info();
+        assert_eq!(spider.native_map, WorldMap::Lorencia);
+        let zaikan = MonsterKind::Zaikan.info();
+        assert_eq!(zaikan.native_map, WorldMap::Tarkan);
+        assert_eq!(zaikan.combat_stats().hits_to_kill(&spider.combat_stats()), 1);
+        assert!(spider.combat_stats().hits_to_kill(&zaikan.combat_stats()) > 100);
+    }
+}
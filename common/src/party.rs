@@ -0,0 +1,31 @@
+//! Party identifiers and size limits.
+
+use serde::{Deserialize, Serialize};
+
+/// Most characters in one party.
+pub const MAX_PARTY_MEMBERS: usize = 5;
+
+/// Party, as the server tracks and sends it.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct PartyId(pub u32);
+
+impl From<u32> for PartyId {
+    fn from(id: u32) -> Self {
+        Self(id)
+    }
+}
+
+impl From<PartyId> for u32 {
+    fn from(id: PartyId) -> Self {
+        id.0
+    }
+}
+
+impl std::fmt::Display for PartyId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
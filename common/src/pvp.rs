@@ -0,0 +1,222 @@
+//! Who may attack whom on each map, and the murder status killers earn.
+//!
+//! The server's combat gate and the client's name colors both read these
+//! rules, so a name the client shows as an outlaw is one the server lets
+//! anyone hit. Guild relations, wars, Gens and duels are resolved by the
+//! caller into a [`PvpStanding`]; the rules only decide what it allows.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{MapCategory, WorldMap};
+
+/// Player kills after which a character counts as a murderer.
+pub const MURDERER_KILLS: u32 = 2;
+
+/// Which fights a map allows between players.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PvpMode {
+    /// No player may hit another, not even in a guild war.
+    Safe,
+    /// Only characters dueling each other.
+    DuelOnly,
+    /// Duels, hostile or warring guilds and Gens rivals; murderers may be
+    /// hunted by anyone.
+    #[default]
+    GuildWarOnly,
+    /// Anyone but guildmates and allies.
+    FreePk,
+}
+
+/// PvP rules of one map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PvpRules {
+    pub mode: PvpMode,
+    /// Whether killing a character who was not fair game raises the killer's
+    /// murder status. Off where killing is the point, like Vulcanus.
+    pub murder_penalty: bool,
+}
+
+impl Default for PvpRules {
+    fn default() -> Self {
+        Self::new(PvpMode::default())
+    }
+}
+
+/// How an attacker stands towards its target.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct PvpStanding {
+    /// Same guild or allied guilds.
+    pub allied: bool,
+    /// Hostile guilds, guilds at war, or Gens rivals inside a battle zone.
+    pub hostile: bool,
+    /// Dueling each other.
+    pub dueling: bool,
+}
+
+/// Reputation from player kills, shown as the name color.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MurderStatus {
+    #[default]
+    Commoner,
+    /// One murder; a warning before becoming an outlaw.
+    Warning,
+    /// Anyone may attack a murderer on maps that allow guild wars.
+    Murderer,
+}
+
+impl MurderStatus {
+    pub fn from_kills(kills: u32) -> Self {
+        match kills {
+            0 => Self::Commoner,
+            kills if kills < MURDERER_KILLS => Self::Warning,
+            _ => Self::Murderer,
+        }
+    }
+}
+
+impl PvpRules {
+    /// Rules of `mode`, with the murder penalty on where unprovoked kills
+    /// are possible.
+    pub const fn new(mode: PvpMode) -> Self {
+        Self {
+            mode,
+            murder_penalty: matches!(mode, PvpMode::FreePk),
+        }
+    }
+
+    pub const fn without_murder_penalty(self) -> Self {
+        Self {
+            murder_penalty: false,
+            ..self
+        }
+    }
+
+    /// Whether an attacker with `standing` may hit a target of `victim`
+    /// status.
+    pub fn allows(&self, standing: PvpStanding, victim: MurderStatus) -> bool {
+        match self.mode {
+            PvpMode::Safe => false,
+            PvpMode::DuelOnly => standing.dueling,
+            PvpMode::GuildWarOnly => {
+                standing.dueling
+                    || standing.hostile
+                    || (!standing.allied && victim == MurderStatus::Murderer)
+            }
+            PvpMode::FreePk => standing.dueling || standing.hostile || !standing.allied,
+        }
+    }
+
+    /// Whether a kill the rules allowed counts as a murder: the victim was
+    /// neither an opponent nor a murderer, on a map with the penalty.
+    pub fn is_murder(&self, standing: PvpStanding, victim: MurderStatus) -> bool {
+        self.murder_penalty
+            && !standing.dueling
+            && !standing.hostile
+            && victim != MurderStatus::Murderer
+    }
+}
+
+impl WorldMap {
+    /// PvP rules of the map; maps not listed follow [`PvpRules::default`].
+    pub fn pvp_rules(&self) -> PvpRules {
+        match self {
+            WorldMap::Vulcanus => PvpRules::new(PvpMode::FreePk).without_murder_penalty(),
+            WorldMap::ChaosCastle1
+            | WorldMap::ChaosCastle2
+            | WorldMap::ChaosCastle3
+            | WorldMap::ChaosCastle4
+            | WorldMap::ChaosCastle5
+            | WorldMap::ChaosCastle6
+            | WorldMap::ChaosCastle7 => PvpRules::new(PvpMode::FreePk).without_murder_penalty(),
+            WorldMap::Arena | WorldMap::DuelArena | WorldMap::NewArena => {
+                PvpRules::new(PvpMode::DuelOnly)
+            }
+            // Illusion Temple teams fight as hostile sides.
+            WorldMap::IllusionTemple1
+            | WorldMap::IllusionTemple2
+            | WorldMap::IllusionTemple3
+            | WorldMap::IllusionTemple4
+            | WorldMap::IllusionTemple5 => PvpRules::default(),
+            WorldMap::LorenMarket | WorldMap::LorenMarketS6 | WorldMap::SantaVillage => {
+                PvpRules::new(PvpMode::Safe)
+            }
+            _ if matches!(
+                self.category(),
+                MapCategory::EventDungeon | MapCategory::LoginScene | MapCategory::Unknown
+            ) =>
+            {
+                PvpRules::new(PvpMode::Safe)
+            }
+            _ => PvpRules::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRANGER: PvpStanding = PvpStanding {
+        allied: false,
+        hostile: false,
+        dueling: false,
+    };
+
+    #[test]
+    fn maps_decide_who_may_fight() {
+        let hostile = PvpStanding {
+            hostile: true,
+            ..STRANGER
+        };
+        let ally = PvpStanding {
+            allied: true,
+            ..STRANGER
+        };
+        let duel = PvpStanding {
+            dueling: true,
+            ..STRANGER
+        };
+
+        let vulcanus = WorldMap::Vulcanus.pvp_rules();
+        assert!(vulcanus.allows(STRANGER, MurderStatus::Commoner));
+        assert!(!vulcanus.allows(ally, MurderStatus::Commoner));
+        assert!(!vulcanus.is_murder(STRANGER, MurderStatus::Commoner));
+
+        let arena = WorldMap::Arena.pvp_rules();
+        assert!(arena.allows(duel, MurderStatus::Commoner));
+        assert!(!arena.allows(hostile, MurderStatus::Commoner));
+
+        let lorencia = WorldMap::Lorencia.pvp_rules();
+        assert!(lorencia.allows(hostile, MurderStatus::Commoner));
+        assert!(!lorencia.allows(STRANGER, MurderStatus::Warning));
+        assert!(lorencia.allows(STRANGER, MurderStatus::Murderer));
+        assert!(!lorencia.allows(ally, MurderStatus::Murderer));
+
+        for safe in [WorldMap::LorenMarket, WorldMap::BloodCastle1] {
+            assert!(!safe.pvp_rules().allows(hostile, MurderStatus::Murderer));
+        }
+        assert_eq!(WorldMap::ValleyOfLoren.pvp_rules(), PvpRules::default());
+    }
+
+    #[test]
+    fn unprovoked_kills_raise_murder_status() {
+        let free = PvpRules::new(PvpMode::FreePk);
+        assert!(free.is_murder(STRANGER, MurderStatus::Commoner));
+        assert!(!free.is_murder(STRANGER, MurderStatus::Murderer));
+        assert!(!free.is_murder(
+            PvpStanding {
+                hostile: true,
+                ..STRANGER
+            },
+            MurderStatus::Commoner
+        ));
+
+        assert_eq!(MurderStatus::from_kills(0), MurderStatus::Commoner);
+        assert_eq!(MurderStatus::from_kills(1), MurderStatus::Warning);
+        assert_eq!(
+            MurderStatus::from_kills(MURDERER_KILLS),
+            MurderStatus::Murderer
+        );
+    }
+}
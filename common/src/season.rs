@@ -0,0 +1,274 @@
+//! Game versions and the maps each one ships.
+//!
+//! Seasons follow the global client releases; a map counts from the season
+//! that introduced it until the one that replaced it, if any. The server
+//! refuses runtime configs that host maps its season does not have, and the
+//! client's debug tooling hides them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::WorldMap;
+
+/// Game version a server or client targets; `season = 6` in config files.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(try_from = "u8", into = "u8")]
+#[repr(u8)]
+pub enum Season {
+    /// Releases before Season 1 (0.97 to 1.0).
+    Classic = 0,
+    Season1 = 1,
+    Season2 = 2,
+    Season3 = 3,
+    Season4 = 4,
+    Season5 = 5,
+    #[default]
+    Season6 = 6,
+    Season7 = 7,
+    Season8 = 8,
+    Season9 = 9,
+    Season10 = 10,
+    Season11 = 11,
+    Season12 = 12,
+    Season13 = 13,
+    Season14 = 14,
+    Season15 = 15,
+    Season16 = 16,
+    Season17 = 17,
+    Season18 = 18,
+    Season19 = 19,
+}
+
+impl Season {
+    pub const ALL: [Self; 20] = [
+        Self::Classic,
+        Self::Season1,
+        Self::Season2,
+        Self::Season3,
+        Self::Season4,
+        Self::Season5,
+        Self::Season6,
+        Self::Season7,
+        Self::Season8,
+        Self::Season9,
+        Self::Season10,
+        Self::Season11,
+        Self::Season12,
+        Self::Season13,
+        Self::Season14,
+        Self::Season15,
+        Self::Season16,
+        Self::Season17,
+        Self::Season18,
+        Self::Season19,
+    ];
+
+    /// The newest season this table knows, which has every map still in use.
+    pub const LATEST: Self = Self::Season19;
+
+    /// Season number; 0 for [`Season::Classic`].
+    pub fn number(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_number(number: u8) -> Option<Self> {
+        Self::ALL.get(usize::from(number)).copied()
+    }
+}
+
+impl From<Season> for u8 {
+    fn from(season: Season) -> Self {
+        season.number()
+    }
+}
+
+impl TryFrom<u8> for Season {
+    type Error = UnknownSeason;
+
+    fn try_from(number: u8) -> Result<Self, Self::Error> {
+        Self::from_number(number).ok_or_else(|| UnknownSeason(number.to_string()))
+    }
+}
+
+impl std::fmt::Display for Season {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Classic => write!(f, "Classic"),
+            season => write!(f, "Season {}", season.number()),
+        }
+    }
+}
+
+/// Parses `6`, `s6`, `season6`, `Season 6` and `classic`.
+impl std::str::FromStr for Season {
+    type Err = UnknownSeason;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let normalized: String = value
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if normalized == "classic" {
+            return Ok(Self::Classic);
+        }
+        let digits = normalized
+            .strip_prefix("season")
+            .or_else(|| normalized.strip_prefix('s'))
+            .unwrap_or(&normalized);
+        digits
+            .parse::<u8>()
+            .ok()
+            .and_then(Self::from_number)
+            .ok_or_else(|| UnknownSeason(value.to_string()))
+    }
+}
+
+/// Value that names no [`Season`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownSeason(pub String);
+
+impl std::fmt::Display for UnknownSeason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown season {:?}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownSeason {}
+
+impl WorldMap {
+    /// Season that introduced the map.
+    pub fn introduced_in(&self) -> Season {
+        match self {
+            WorldMap::Aida | WorldMap::Crywolf | WorldMap::Kalima7 => Season::Season1,
+            WorldMap::Kanturu | WorldMap::KanturuRemain | WorldMap::RefineTower => Season::Season2,
+            WorldMap::BalgassBarracks
+            | WorldMap::BalgassRefuge
+            | WorldMap::IllusionTemple1
+            | WorldMap::IllusionTemple2
+            | WorldMap::IllusionTemple3
+            | WorldMap::IllusionTemple4
+            | WorldMap::IllusionTemple5
+            | WorldMap::BloodCastle8
+            | WorldMap::ChaosCastle7 => Season::Season3,
+            WorldMap::Elbeland
+            | WorldMap::Elbeland2
+            | WorldMap::SwampOfPeace
+            | WorldMap::Raklion
+            | WorldMap::RaklionBoss
+            | WorldMap::SantaVillage => Season::Season4,
+            WorldMap::Vulcanus
+            | WorldMap::DuelArena
+            | WorldMap::DoppelgangerIceZone
+            | WorldMap::DoppelgangerBlazeZone
+            | WorldMap::DoppelgangerUnderwater
+            | WorldMap::DoppelgangerCrystalCave
+            | WorldMap::ImperialGuardian1
+            | WorldMap::ImperialGuardian2
+            | WorldMap::ImperialGuardian3
+            | WorldMap::ImperialGuardian4 => Season::Season5,
+            WorldMap::NewLoginScene1
+            | WorldMap::EventSquare
+            | WorldMap::LorenMarket
+            | WorldMap::LorenMarketS6
+            | WorldMap::Karutan1
+            | WorldMap::Karutan2 => Season::Season6,
+            WorldMap::Acheron
+            | WorldMap::Acheron2
+            | WorldMap::Debenter
+            | WorldMap::DebenterArcaBattle
+            | WorldMap::NewLoginScene2
+            | WorldMap::NewCharacterScene2 => Season::Season8,
+            WorldMap::UrukMountain
+            | WorldMap::UrukMountain2
+            | WorldMap::UrukMountain3
+            | WorldMap::DoppelgangerRenewal
+            | WorldMap::NewArena => Season::Season9,
+            WorldMap::Nars
+            | WorldMap::IllusionTempleLeague
+            | WorldMap::IllusionTempleLeague2
+            | WorldMap::TormentedSquare => Season::Season10,
+            WorldMap::Ferea => Season::Season11,
+            WorldMap::NixiesLake
+            | WorldMap::DeepDungeon1
+            | WorldMap::DeepDungeon2
+            | WorldMap::DeepDungeon3
+            | WorldMap::DeepDungeon4
+            | WorldMap::DeepDungeon5 => Season::Season12,
+            WorldMap::SwampOfDarkness | WorldMap::PlaceOfQualification => Season::Season13,
+            WorldMap::KuberaMine1 | WorldMap::KuberaMine2 => Season::Season14,
+            WorldMap::AbyssOfAtlans | WorldMap::AbyssOfAtlans2 | WorldMap::AbyssOfAtlans3 => {
+                Season::Season15
+            }
+            WorldMap::ScorchedTunnels | WorldMap::RedSmokeIcarus => Season::Season16,
+            WorldMap::TempleOfArnil
+            | WorldMap::AshenAida
+            | WorldMap::OldKethotum
+            | WorldMap::BossBattleZone => Season::Season17,
+            WorldMap::BlazeKethotum | WorldMap::KanturuUndergrounds | WorldMap::IgnisVolcano => {
+                Season::Season18
+            }
+            WorldMap::BloodyTarkan
+            | WorldMap::TormentaIsland
+            | WorldMap::DoppelgangerIceZoneNew => Season::Season19,
+            _ => Season::Classic,
+        }
+    }
+
+    /// First season without the map, when a later one replaced it.
+    pub fn removed_in(&self) -> Option<Season> {
+        match self {
+            // Folded into Doppelganger Renewal.
+            WorldMap::DoppelgangerIceZone
+            | WorldMap::DoppelgangerBlazeZone
+            | WorldMap::DoppelgangerUnderwater
+            | WorldMap::DoppelgangerCrystalCave => Some(Season::Season9),
+            _ => None,
+        }
+    }
+
+    /// Whether a `season` server or client has the map.
+    pub fn available_in(&self, season: Season) -> bool {
+        self.introduced_in() <= season && self.removed_in().is_none_or(|removed| season < removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn season_six_has_no_later_maps() {
+        let s6 = Season::Season6;
+        for map in [
+            WorldMap::Lorencia,
+            WorldMap::Karutan2,
+            WorldMap::DoppelgangerIceZone,
+        ] {
+            assert!(map.available_in(s6), "{map}");
+        }
+        for map in [
+            WorldMap::Acheron,
+            WorldMap::KuberaMine1,
+            WorldMap::AbyssOfAtlans,
+        ] {
+            assert!(!map.available_in(s6), "{map}");
+        }
+        assert!(!WorldMap::Elbeland.available_in(Season::Season3));
+        assert!(!WorldMap::DoppelgangerIceZone.available_in(Season::LATEST));
+        assert!(WorldMap::DoppelgangerRenewal.available_in(Season::LATEST));
+    }
+
+    #[test]
+    fn seasons_parse_from_numbers_and_names() {
+        for season in Season::ALL {
+            assert_eq!(season.to_string().parse(), Ok(season));
+            assert_eq!(Season::try_from(season.number()), Ok(season));
+        }
+        assert_eq!("s6".parse(), Ok(Season::Season6));
+        assert_eq!("6".parse(), Ok(Season::Season6));
+        assert!("Season 20".parse::<Season>().is_err());
+        assert!(Season::try_from(20).is_err());
+    }
+}
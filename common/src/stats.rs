@@ -0,0 +1,273 @@
+//! Character stats and what follows from them, with the Season 6 formulas.
+//!
+//! The client's character sheet and the server's combat both read derived
+//! values from here, so the two never disagree about a character. Unlike
+//! [`crate::combat`], which models a reference build for balancing, these take
+//! the points a character actually spent.
+
+use crate::class::{CharacterClass, StartingStats};
+
+/// Points spent in each stat.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct BaseStats {
+    pub strength: u32,
+    pub agility: u32,
+    pub vitality: u32,
+    pub energy: u32,
+    /// Leadership; only Dark Lords have it.
+    pub command: u32,
+}
+
+impl From<StartingStats> for BaseStats {
+    fn from(start: StartingStats) -> Self {
+        Self {
+            strength: start.strength,
+            agility: start.agility,
+            vitality: start.vitality,
+            energy: start.energy,
+            command: start.command,
+        }
+    }
+}
+
+impl BaseStats {
+    /// Stats of a freshly created character of `class`.
+    pub fn starting(class: CharacterClass) -> Self {
+        class.starting_stats().into()
+    }
+
+    pub fn total(&self) -> u32 {
+        self.strength + self.agility + self.vitality + self.energy + self.command
+    }
+}
+
+/// Values a character sheet shows, before gear, buffs and master skills.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DerivedStats {
+    pub min_damage: u32,
+    pub max_damage: u32,
+    /// Wizardry of the classes that cast spells with energy; 0 for the others.
+    pub min_magic_damage: u32,
+    pub max_magic_damage: u32,
+    /// Attack success rate against monsters.
+    pub attack_rate: u32,
+    /// Defense success rate against monsters.
+    pub defense_rate: u32,
+    pub defense: u32,
+    pub attack_speed: u32,
+    pub magic_speed: u32,
+    pub max_hp: u32,
+    pub max_mp: u32,
+    pub max_sd: u32,
+    pub max_ag: u32,
+}
+
+impl DerivedStats {
+    /// Derived values of a `class` character of `level` with `stats`.
+    pub fn of(class: CharacterClass, level: u16, stats: &BaseStats) -> Self {
+        let (min_damage, max_damage) = damage(class, stats);
+        let (min_magic_damage, max_magic_damage) = magic_damage(class, stats);
+        let (attack_speed, magic_speed) = speeds(class, stats);
+        Self {
+            min_damage,
+            max_damage,
+            min_magic_damage,
+            max_magic_damage,
+            attack_rate: attack_rate(class, level, stats),
+            defense_rate: defense_rate(class, stats),
+            defense: defense(class, stats),
+            attack_speed,
+            magic_speed,
+            max_hp: max_hp(class, level, stats),
+            max_mp: max_mp(class, level, stats),
+            max_sd: max_sd(level, stats),
+            max_ag: max_ag(class, stats),
+        }
+    }
+}
+
+/// Physical damage range. Elves are taken to hold a bow or crossbow, as they
+/// do past the first levels.
+pub fn damage(class: CharacterClass, stats: &BaseStats) -> (u32, u32) {
+    let BaseStats {
+        strength: str,
+        agility: agi,
+        vitality: vit,
+        energy: ene,
+        ..
+    } = *stats;
+    match class {
+        CharacterClass::DarkWizard => (str / 8, str / 4),
+        CharacterClass::DarkKnight => (str / 6, str / 4),
+        CharacterClass::FairyElf => (str / 14 + agi / 7, str / 8 + agi / 4),
+        CharacterClass::MagicGladiator => (str / 6 + ene / 12, str / 4 + ene / 8),
+        CharacterClass::DarkLord => (str / 7 + ene / 14, str / 5 + ene / 10),
+        CharacterClass::Summoner => ((str + agi) / 7, (str + agi) / 4),
+        CharacterClass::RageFighter => (str / 7 + vit / 15, str / 5 + vit / 12),
+    }
+}
+
+/// Wizardry range of Dark Wizards, Magic Gladiators and Summoners.
+pub fn magic_damage(class: CharacterClass, stats: &BaseStats) -> (u32, u32) {
+    match class {
+        CharacterClass::DarkWizard | CharacterClass::MagicGladiator | CharacterClass::Summoner => {
+            (stats.energy / 9, stats.energy / 4)
+        }
+        _ => (0, 0),
+    }
+}
+
+pub fn attack_rate(class: CharacterClass, level: u16, stats: &BaseStats) -> u32 {
+    let level = u32::from(level);
+    let BaseStats {
+        strength: str,
+        agility: agi,
+        command: cmd,
+        ..
+    } = *stats;
+    match class {
+        CharacterClass::DarkLord => level * 5 + agi * 5 / 2 + str / 6 + cmd / 10,
+        CharacterClass::RageFighter => level * 3 + agi * 5 / 4 + str / 6,
+        _ => level * 5 + agi * 3 / 2 + str / 4,
+    }
+}
+
+pub fn defense_rate(class: CharacterClass, stats: &BaseStats) -> u32 {
+    stats.agility
+        / match class {
+            CharacterClass::FairyElf | CharacterClass::Summoner => 4,
+            CharacterClass::DarkLord => 7,
+            CharacterClass::RageFighter => 10,
+            _ => 3,
+        }
+}
+
+pub fn defense(class: CharacterClass, stats: &BaseStats) -> u32 {
+    stats.agility
+        / match class {
+            CharacterClass::DarkKnight | CharacterClass::Summoner => 3,
+            CharacterClass::DarkWizard | CharacterClass::MagicGladiator => 4,
+            CharacterClass::DarkLord => 7,
+            CharacterClass::RageFighter => 8,
+            CharacterClass::FairyElf => 10,
+        }
+}
+
+/// Attack and magic speed.
+pub fn speeds(class: CharacterClass, stats: &BaseStats) -> (u32, u32) {
+    let (attack, magic) = match class {
+        CharacterClass::DarkWizard => (20, 10),
+        CharacterClass::DarkKnight | CharacterClass::MagicGladiator => (15, 20),
+        CharacterClass::FairyElf => (50, 50),
+        CharacterClass::DarkLord => (10, 10),
+        CharacterClass::Summoner => (20, 20),
+        CharacterClass::RageFighter => (9, 9),
+    };
+    (stats.agility / attack, stats.agility / magic)
+}
+
+/// Life from the class's start, its gain per level and per vitality point
+/// added since creation.
+pub fn max_hp(class: CharacterClass, level: u16, stats: &BaseStats) -> u32 {
+    let start = class.starting_stats();
+    let gains = class.level_gains();
+    start.life
+        + gains.life_per_level_tenths * u32::from(level.max(1) - 1) / 10
+        + gains.life_per_vitality * stats.vitality.saturating_sub(start.vitality)
+}
+
+/// Mana, like [`max_hp`] with energy.
+pub fn max_mp(class: CharacterClass, level: u16, stats: &BaseStats) -> u32 {
+    let start = class.starting_stats();
+    let gains = class.level_gains();
+    start.mana
+        + gains.mana_per_level_tenths * u32::from(level.max(1) - 1) / 10
+        + gains.mana_per_energy * stats.energy.saturating_sub(start.energy)
+}
+
+/// Shield: 1.2 per stat point plus a level term.
+pub fn max_sd(level: u16, stats: &BaseStats) -> u32 {
+    let level = u32::from(level);
+    stats.total() * 12 / 10 + level * level / 30
+}
+
+/// Ability gauge, spent by skills.
+pub fn max_ag(class: CharacterClass, stats: &BaseStats) -> u32 {
+    // Hundredths of AG per point of strength, agility, vitality, energy and
+    // command.
+    let (str, agi, vit, ene, cmd) = match class {
+        CharacterClass::DarkWizard => (20, 40, 30, 20, 0),
+        CharacterClass::DarkKnight | CharacterClass::RageFighter => (15, 20, 30, 100, 0),
+        CharacterClass::FairyElf => (30, 20, 30, 20, 0),
+        CharacterClass::MagicGladiator | CharacterClass::Summoner => (20, 25, 30, 15, 0),
+        CharacterClass::DarkLord => (30, 20, 10, 15, 30),
+    };
+    (stats.strength * str
+        + stats.agility * agi
+        + stats.vitality * vit
+        + stats.energy * ene
+        + stats.command * cmd)
+        / 100
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fresh_dark_knight_matches_the_season_6_sheet() {
+        let stats = BaseStats::starting(CharacterClass::DarkKnight);
+        assert_eq!(stats.total(), 83);
+        assert_eq!(
+            DerivedStats::of(CharacterClass::DarkKnight, 1, &stats),
+            DerivedStats {
+                min_damage: 4,
+                max_damage: 7,
+                min_magic_damage: 0,
+                max_magic_damage: 0,
+                attack_rate: 42,
+                defense_rate: 6,
+                defense: 6,
+                attack_speed: 1,
+                magic_speed: 1,
+                max_hp: 110,
+                max_mp: 20,
+                max_sd: 99,
+                max_ag: 25,
+            }
+        );
+    }
+
+    #[test]
+    fn points_and_levels_raise_the_pools() {
+        let class = CharacterClass::DarkWizard;
+        let start = BaseStats::starting(class);
+        let trained = BaseStats {
+            vitality: start.vitality + 10,
+            energy: start.energy + 100,
+            ..start
+        };
+        let fresh = DerivedStats::of(class, 1, &start);
+        let later = DerivedStats::of(class, 51, &trained);
+        assert_eq!(later.max_hp - fresh.max_hp, 50 + 2 * 10);
+        assert_eq!(later.max_mp - fresh.max_mp, 100 + 2 * 100);
+        assert_eq!((later.min_magic_damage, later.max_magic_damage), (14, 32));
+        assert_eq!(later.max_sd - fresh.max_sd, 132 + 51 * 51 / 30);
+
+        // Dark Lords alone get attack rate and AG from command.
+        let lord = BaseStats::starting(CharacterClass::DarkLord);
+        let commanding = BaseStats {
+            command: lord.command + 100,
+            ..lord
+        };
+        assert_eq!(
+            attack_rate(CharacterClass::DarkLord, 1, &commanding)
+                - attack_rate(CharacterClass::DarkLord, 1, &lord),
+            10
+        );
+        assert_eq!(
+            max_ag(CharacterClass::DarkLord, &commanding) - max_ag(CharacterClass::DarkLord, &lord),
+            30
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Per-map metadata shared by the server's entry checks and the client's gate
+//! prompts.
+//!
+//! Level requirements follow the Season 6 move list. Maps outside it (the later
+//! seasons' additions) have no level gate until their values are known.
+
+use crate::collision::TERRAIN_SIZE;
+use crate::{MapCategory, WorldMap};
+
+/// Item or quest a character needs to enter a map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntryRequirement {
+    /// Blood Castle.
+    InvisibilityCloak,
+    /// Devil Square.
+    DevilsInvitation,
+    /// Chaos Castle.
+    ArmorOfGuardsman,
+    /// Kalima, through the gate the map opens.
+    LostMap,
+    /// Illusion Temple.
+    ScrollOfBlood,
+    /// Doppelganger.
+    MirrorOfDimensions,
+    /// Imperial Guardian.
+    SuspiciousScrapOfPaper,
+    /// Kanturu Remain, where Nightmare is fought.
+    MoonstonePendant,
+    /// Balgass' barracks and refuge, opened by the third class quest.
+    ThirdClassQuest,
+}
+
+/// How characters get around on a map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Traversal {
+    Walk,
+    /// Underwater; characters swim instead of walking.
+    Swim,
+    /// No ground to walk on; wings or a flying mount are needed to enter.
+    Fly,
+}
+
+/// Static metadata of a map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorldMapInfo {
+    /// Terrain width in tiles.
+    pub width: u16,
+    /// Terrain height in tiles.
+    pub height: u16,
+    /// Town whose spawn area is a safe zone.
+    pub town: bool,
+    /// Lowest character level let in; 0 when the map is not level gated.
+    pub min_level: u16,
+    pub entry: Option<EntryRequirement>,
+    pub traversal: Traversal,
+}
+
+impl WorldMapInfo {
+    /// Whether a character of `level` meets the map's level gate. Items,
+    /// quests and traversal are left to the caller.
+    pub fn admits_level(&self, level: u16) -> bool {
+        level >= self.min_level
+    }
+}
+
+impl WorldMap {
+    /// Size, safe zone and entry requirements of the map.
+    pub fn info(&self) -> WorldMapInfo {
+        WorldMapInfo {
+            width: TERRAIN_SIZE,
+            height: TERRAIN_SIZE,
+            town: self.category() == MapCategory::Town,
+            min_level: self.min_level(),
+            entry: self.entry_requirement(),
+            traversal: self.traversal(),
+        }
+    }
+
+    fn min_level(&self) -> u16 {
+        match self {
+            WorldMap::Noria
+            | WorldMap::ValleyOfLoren
+            | WorldMap::LandOfTrials
+            | WorldMap::Crywolf => 10,
+            WorldMap::DevilSquare
+            | WorldMap::DevilSquare2
+            | WorldMap::BloodCastle1
+            | WorldMap::ChaosCastle1
+            | WorldMap::DoppelgangerIceZone
+            | WorldMap::DoppelgangerBlazeZone
+            | WorldMap::DoppelgangerUnderwater
+            | WorldMap::DoppelgangerCrystalCave
+            | WorldMap::ImperialGuardian1
+            | WorldMap::ImperialGuardian2
+            | WorldMap::ImperialGuardian3
+            | WorldMap::ImperialGuardian4 => 15,
+            WorldMap::Devias => 20,
+            WorldMap::Dungeon | WorldMap::Vulcanus => 30,
+            WorldMap::Kalima1 => 40,
+            WorldMap::LostTower | WorldMap::Arena | WorldMap::ChaosCastle2 => 50,
+            WorldMap::Atlans => 70,
+            WorldMap::BloodCastle2 => 81,
+            WorldMap::ChaosCastle3 => 120,
+            WorldMap::BloodCastle3 | WorldMap::Kalima2 => 131,
+            WorldMap::Tarkan => 140,
+            WorldMap::Aida => 150,
+            WorldMap::Kanturu => 160,
+            WorldMap::Icarus | WorldMap::Karutan1 | WorldMap::Karutan2 => 170,
+            WorldMap::ChaosCastle4 => 180,
+            WorldMap::BloodCastle4 | WorldMap::Kalima3 => 181,
+            WorldMap::KanturuRemain | WorldMap::RefineTower => 200,
+            WorldMap::IllusionTemple1 => 220,
+            WorldMap::BloodCastle5 | WorldMap::Kalima4 => 231,
+            WorldMap::ChaosCastle5 => 240,
+            WorldMap::IllusionTemple2 => 271,
+            WorldMap::Raklion | WorldMap::RaklionBoss => 280,
+            WorldMap::BloodCastle6 | WorldMap::Kalima5 => 281,
+            WorldMap::ChaosCastle6 => 300,
+            WorldMap::IllusionTemple3 => 321,
+            WorldMap::BloodCastle7 | WorldMap::Kalima6 => 331,
+            WorldMap::Kalima7 | WorldMap::BalgassBarracks | WorldMap::BalgassRefuge => 350,
+            WorldMap::IllusionTemple4 => 351,
+            WorldMap::IllusionTemple5 => 381,
+            WorldMap::BloodCastle8 | WorldMap::ChaosCastle7 | WorldMap::SwampOfPeace => 400,
+            _ => 0,
+        }
+    }
+
+    fn entry_requirement(&self) -> Option<EntryRequirement> {
+        let requirement = match self {
+            WorldMap::BloodCastle1
+            | WorldMap::BloodCastle2
+            | WorldMap::BloodCastle3
+            | WorldMap::BloodCastle4
+            | WorldMap::BloodCastle5
+            | WorldMap::BloodCastle6
+            | WorldMap::BloodCastle7
+            | WorldMap::BloodCastle8 => EntryRequirement::InvisibilityCloak,
+            WorldMap::DevilSquare | WorldMap::DevilSquare2 => EntryRequirement::DevilsInvitation,
+            WorldMap::ChaosCastle1
+            | WorldMap::ChaosCastle2
+            | WorldMap::ChaosCastle3
+            | WorldMap::ChaosCastle4
+            | WorldMap::ChaosCastle5
+            | WorldMap::ChaosCastle6
+            | WorldMap::ChaosCastle7 => EntryRequirement::ArmorOfGuardsman,
+            WorldMap::Kalima1
+            | WorldMap::Kalima2
+            | WorldMap::Kalima3
+            | WorldMap::Kalima4
+            | WorldMap::Kalima5
+            | WorldMap::Kalima6
+            | WorldMap::Kalima7 => EntryRequirement::LostMap,
+            WorldMap::IllusionTemple1
+            | WorldMap::IllusionTemple2
+            | WorldMap::IllusionTemple3
+            | WorldMap::IllusionTemple4
+            | WorldMap::IllusionTemple5 => EntryRequirement::ScrollOfBlood,
+            WorldMap::DoppelgangerIceZone
+            | WorldMap::DoppelgangerBlazeZone
+            | WorldMap::DoppelgangerUnderwater
+            | WorldMap::DoppelgangerCrystalCave => EntryRequirement::MirrorOfDimensions,
+            WorldMap::ImperialGuardian1
+            | WorldMap::ImperialGuardian2
+            | WorldMap::ImperialGuardian3
+            | WorldMap::ImperialGuardian4 => EntryRequirement::SuspiciousScrapOfPaper,
+            WorldMap::KanturuRemain => EntryRequirement::MoonstonePendant,
+            WorldMap::BalgassBarracks | WorldMap::BalgassRefuge => {
+                EntryRequirement::ThirdClassQuest
+            }
+            _ => return None,
+        };
+        Some(requirement)
+    }
+
+    fn traversal(&self) -> Traversal {
+        match self {
+            WorldMap::Atlans
+            | WorldMap::AbyssOfAtlans
+            | WorldMap::AbyssOfAtlans2
+            | WorldMap::AbyssOfAtlans3
+            | WorldMap::DoppelgangerUnderwater => Traversal::Swim,
+            WorldMap::Icarus | WorldMap::RedSmokeIcarus => Traversal::Fly,
+            _ => Traversal::Walk,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn towns_are_open_to_everyone() {
+        let lorencia = WorldMap::Lorencia.info();
+        assert!(lorencia.town);
+        assert_eq!((lorencia.width, lorencia.height), (256, 256));
+        assert!(lorencia.admits_level(1));
+        assert_eq!(lorencia.entry, None);
+        assert_eq!(lorencia.traversal, Traversal::Walk);
+        assert!(!WorldMap::Dungeon.info().town);
+    }
+
+    #[test]
+    fn gated_maps_carry_their_requirements() {
+        let atlans = WorldMap::Atlans.info();
+        assert_eq!(atlans.traversal, Traversal::Swim);
+        assert!(!atlans.admits_level(69));
+        assert!(atlans.admits_level(70));
+        assert_eq!(WorldMap::Icarus.info().traversal, Traversal::Fly);
+        assert_eq!(
+            WorldMap::BloodCastle3.info().entry,
+            Some(EntryRequirement::InvisibilityCloak)
+        );
+        assert_eq!(
+            WorldMap::BalgassRefuge.info().entry,
+            Some(EntryRequirement::ThirdClassQuest)
+        );
+    }
+
+    #[test]
+    fn event_tiers_get_harder() {
+        let tiers = [
+            WorldMap::BloodCastle1,
+            WorldMap::BloodCastle2,
+            WorldMap::BloodCastle3,
+            WorldMap::BloodCastle4,
+            WorldMap::BloodCastle5,
+            WorldMap::BloodCastle6,
+            WorldMap::BloodCastle7,
+            WorldMap::BloodCastle8,
+        ];
+        let levels: Vec<u16> = tiers.iter().map(|map| map.info().min_level).collect();
+        assert!(levels.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
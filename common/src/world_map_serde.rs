@@ -0,0 +1,136 @@
+//! `serde` support for [`WorldMap`].
+//!
+//! A map serializes as its World folder ID (`Lorencia` is `1`), the same `u8`
+//! the server and client already exchange. Fields that should read as the
+//! canonical name in human-readable formats instead use [`by_name`]:
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Spawn {
+//!     #[serde(with = "common::world_map_serde::by_name")]
+//!     map: WorldMap,
+//! }
+//! ```
+//!
+//! In human-readable formats both forms deserialize from either the ID or
+//! any name [`WorldMap::from_name`] accepts. Binary formats such as postcard
+//! are not self-describing, so there a map is always the ID.
+
+use std::fmt;
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::WorldMap;
+
+impl Serialize for WorldMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+impl<'de> Deserialize<'de> for WorldMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(WorldMapVisitor)
+        } else {
+            deserializer.deserialize_u8(WorldMapVisitor)
+        }
+    }
+}
+
+/// Serializes a [`WorldMap`] as its canonical name (`"Lost Tower"`). A name
+/// two maps share (Loren Market) is written as the ID, so it reads back as
+/// the same map; binary formats always get the ID.
+pub mod by_name {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(map: &WorldMap, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() && WorldMap::from_name(map.name()) == Some(*map) {
+            serializer.serialize_str(map.name())
+        } else {
+            map.serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<WorldMap, D::Error> {
+        WorldMap::deserialize(deserializer)
+    }
+}
+
+struct WorldMapVisitor;
+
+impl Visitor<'_> for WorldMapVisitor {
+    type Value = WorldMap;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a World folder ID or a world name")
+    }
+
+    fn visit_u64<E: de::Error>(self, id: u64) -> Result<WorldMap, E> {
+        u8::try_from(id)
+            .ok()
+            .and_then(WorldMap::from_id)
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Unsigned(id), &self))
+    }
+
+    fn visit_i64<E: de::Error>(self, id: i64) -> Result<WorldMap, E> {
+        u64::try_from(id)
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(id), &self))
+            .and_then(|id| self.visit_u64(id))
+    }
+
+    fn visit_str<E: de::Error>(self, name: &str) -> Result<WorldMap, E> {
+        WorldMap::from_name(name).ok_or_else(|| E::invalid_value(de::Unexpected::Str(name), &self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Spawn {
+        map: WorldMap,
+        #[serde(with = "by_name")]
+        town: WorldMap,
+    }
+
+    #[test]
+    fn maps_serialize_as_id_or_name_and_read_back_from_both() {
+        let spawn = Spawn {
+            map: WorldMap::LostTower,
+            town: WorldMap::Lorencia,
+        };
+        let json = serde_json::to_string(&spawn).unwrap();
+        assert_eq!(json, r#"{"map":5,"town":"Lorencia"}"#);
+        assert_eq!(serde_json::from_str::<Spawn>(&json).unwrap(), spawn);
+
+        let swapped: Spawn = serde_json::from_str(r#"{"map":"lost_tower","town":1}"#).unwrap();
+        assert_eq!(swapped, spawn);
+        let market = Spawn {
+            map: WorldMap::LorenMarketS6,
+            town: WorldMap::LorenMarketS6,
+        };
+        let json = serde_json::to_string(&market).unwrap();
+        assert_eq!(json, r#"{"map":115,"town":115}"#);
+        assert_eq!(serde_json::from_str::<Spawn>(&json).unwrap(), market);
+
+        assert!(serde_json::from_str::<WorldMap>("200").is_err());
+        assert!(serde_json::from_str::<WorldMap>("-1").is_err());
+        assert!(serde_json::from_str::<WorldMap>(r#""Atlantis""#).is_err());
+    }
+
+    #[test]
+    fn maps_read_back_from_postcard_as_ids() {
+        let spawn = Spawn {
+            map: WorldMap::LostTower,
+            town: WorldMap::Lorencia,
+        };
+        let bytes = postcard::to_stdvec(&spawn).unwrap();
+        assert_eq!(bytes, [5, 1]);
+        assert_eq!(postcard::from_bytes::<Spawn>(&bytes).unwrap(), spawn);
+
+        assert!(postcard::from_bytes::<WorldMap>(&[200]).is_err());
+    }
+}
@@ -0,0 +1,42 @@
+//! Scale of the game world, shared by the server, the client and its viewers.
+//!
+//! Distances are in world units (one tile is 100 units, matching the legacy
+//! client), angles in degrees. The camera values reproduce the follow camera
+//! of MuClient5.2 `ZzzScene.cpp`.
+
+/// Tiles per map side.
+pub const TERRAIN_SIZE: u16 = 256;
+/// World units per tile side.
+pub const TILE_WORLD_SIZE: f32 = 100.0;
+/// World units per map side.
+pub const MAP_WORLD_SIZE: f32 = TERRAIN_SIZE as f32 * TILE_WORLD_SIZE;
+
+/// Factor turning heightmap samples into world units on regular maps.
+pub const HEIGHT_MULTIPLIER: f32 = 1.5;
+
+/// Downward tilt of the follow camera, in degrees below the horizon.
+pub const CAMERA_PITCH_DEG: f32 = 48.5;
+/// Heading of the follow camera around the character, in degrees.
+pub const CAMERA_YAW_DEG: f32 = -45.0;
+/// Default distance from the camera to the point it looks at, in world units.
+pub const CAMERA_DISTANCE: f32 = 1000.0;
+/// Height above the character's feet the camera looks at, in world units.
+pub const CAMERA_LOOK_HEIGHT: f32 = 80.0;
+
+/// Closest the camera zooms in, in world units.
+pub const ZOOM_MIN: f32 = 300.0;
+/// Farthest the camera zooms out, in world units.
+pub const ZOOM_MAX: f32 = 2500.0;
+/// Distance covered by one mouse wheel notch, in world units.
+pub const ZOOM_STEP: f32 = 100.0;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_camera_distance_lies_within_zoom_limits() {
+        assert!((ZOOM_MIN..=ZOOM_MAX).contains(&CAMERA_DISTANCE));
+        assert_eq!(MAP_WORLD_SIZE, 25_600.0);
+    }
+}
@@ -2,6 +2,7 @@ use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_ma
 use protocol::channel::QuicChannel;
 use protocol::codec::WireCodec;
 use protocol::message::{ClientHello, ClientMessage, RouteKey, WirePacket};
+use protocol::negotiation::ProtocolCapabilities;
 
 fn sample_move_packet() -> WirePacket {
     WirePacket::client(
@@ -37,6 +38,7 @@ fn sample_control_packet() -> WirePacket {
             auth_token: "bench-token".to_string(),
             client_build: "0.1.0-bench".to_string(),
             locale: "en-US".to_string(),
+            capabilities: ProtocolCapabilities::SUPPORTED,
         }),
     )
 }
@@ -1,4 +1,13 @@
 //! QUIC transport channel definitions.
+//!
+//! Datagrams of [`QuicChannel::GameplayInput`] may arrive late, twice or
+//! not at all. [`SequencedSender`] numbers them per route and
+//! [`SequencedReceiver`] keeps only those newer than the last one taken
+//! from that route, so a stale position never overwrites a fresh one.
+
+use std::collections::HashMap;
+
+use crate::message::{RouteKey, WirePacket};
 
 /// Logical transport primitive used by a channel.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -89,9 +98,134 @@ impl core::fmt::Display for InvalidChannel {
 
 impl std::error::Error for InvalidChannel {}
 
+/// Weight of each new transit sample in the jitter estimate (RFC 3550).
+const JITTER_GAIN: f64 = 1.0 / 16.0;
+
+/// Whether sequence `a` comes after `b`, allowing for wrap-around: anything
+/// up to half the sequence space ahead counts as newer.
+#[must_use]
+pub const fn sequence_is_newer(a: u32, b: u32) -> bool {
+    a != b && a.wrapping_sub(b) < 1 << 31
+}
+
+/// Stamps outgoing datagrams with a per-route sequence number.
+#[derive(Debug, Clone, Default)]
+pub struct SequencedSender {
+    next: HashMap<RouteKey, u32>,
+}
+
+impl SequencedSender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sequence number for the next datagram on `route`.
+    pub fn next_sequence(&mut self, route: RouteKey) -> u32 {
+        let next = self.next.entry(route).or_insert(0);
+        let sequence = *next;
+        *next = next.wrapping_add(1);
+        sequence
+    }
+
+    /// Overwrites the sequence of `packet` with the next one of its route.
+    pub fn stamp(&mut self, packet: &mut WirePacket) {
+        packet.sequence = self.next_sequence(packet.route);
+    }
+}
+
+/// What a [`SequencedReceiver`] made of a datagram.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SequenceVerdict {
+    /// Newer than anything seen on its route; deliver it.
+    Accepted,
+    /// Same sequence as the last accepted one.
+    Duplicate,
+    /// Older than the last accepted one.
+    Stale,
+}
+
+/// Counters and jitter of a sequenced channel.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SequenceStats {
+    pub accepted: u64,
+    pub duplicates: u64,
+    pub stale: u64,
+    /// Sequences skipped over by accepted datagrams, i.e. lost or still to
+    /// come and then stale.
+    pub skipped: u64,
+    /// Smoothed variation of transit time, in ms.
+    pub jitter_ms: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RouteSequence {
+    last: u32,
+    /// Arrival minus send time of the last accepted datagram. Both clocks
+    /// only need to tick at the same rate.
+    transit_ms: i64,
+}
+
+/// Drops duplicated and out-of-date datagrams, per route.
+#[derive(Debug, Clone, Default)]
+pub struct SequencedReceiver {
+    routes: HashMap<RouteKey, RouteSequence>,
+    stats: SequenceStats,
+}
+
+impl SequencedReceiver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Judges `packet`, received at `received_at_ms` of the local clock.
+    pub fn receive(&mut self, packet: &WirePacket, received_at_ms: u64) -> SequenceVerdict {
+        let transit_ms = received_at_ms as i64 - packet.sent_at_ms as i64;
+        let Some(route) = self.routes.get_mut(&packet.route) else {
+            self.routes.insert(
+                packet.route,
+                RouteSequence {
+                    last: packet.sequence,
+                    transit_ms,
+                },
+            );
+            self.stats.accepted += 1;
+            return SequenceVerdict::Accepted;
+        };
+
+        if packet.sequence == route.last {
+            self.stats.duplicates += 1;
+            return SequenceVerdict::Duplicate;
+        }
+        if !sequence_is_newer(packet.sequence, route.last) {
+            self.stats.stale += 1;
+            return SequenceVerdict::Stale;
+        }
+
+        self.stats.skipped += u64::from(packet.sequence.wrapping_sub(route.last) - 1);
+        let variation = (transit_ms - route.transit_ms).unsigned_abs() as f64;
+        self.stats.jitter_ms += (variation - self.stats.jitter_ms) * JITTER_GAIN;
+        route.last = packet.sequence;
+        route.transit_ms = transit_ms;
+        self.stats.accepted += 1;
+        SequenceVerdict::Accepted
+    }
+
+    #[must_use]
+    pub fn stats(&self) -> SequenceStats {
+        self.stats
+    }
+
+    /// Forgets `route`, e.g. after leaving its map; the next datagram from
+    /// it starts a new sequence.
+    pub fn forget(&mut self, route: &RouteKey) {
+        self.routes.remove(route);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::message::{ClientMessage, MoveInput};
 
     #[test]
     fn gameplay_input_is_datagram_unreliable() {
@@ -110,4 +244,66 @@ mod tests {
         assert!(QuicChannel::Economy.is_critical());
         assert!(!QuicChannel::GameplayInput.is_critical());
     }
+
+    fn datagram(route: RouteKey, sequence: u32, sent_at_ms: u64) -> WirePacket {
+        WirePacket::client(
+            7,
+            route,
+            sequence,
+            None,
+            sent_at_ms,
+            ClientMessage::Move(MoveInput {
+                client_tick: sequence,
+                x: 120,
+                y: 130,
+                direction: 2,
+                path: [0; 8],
+            }),
+        )
+    }
+
+    fn route(map_id: u16) -> RouteKey {
+        RouteKey {
+            map_id,
+            ..RouteKey::LOBBY
+        }
+    }
+
+    #[test]
+    fn sequences_wrap_around() {
+        assert!(sequence_is_newer(1, 0));
+        assert!(sequence_is_newer(0, u32::MAX));
+        assert!(!sequence_is_newer(u32::MAX, 0));
+        assert!(!sequence_is_newer(5, 5));
+    }
+
+    #[test]
+    fn receiver_drops_duplicates_and_stale_datagrams_per_route() {
+        let lorencia = route(0);
+        let devias = route(2);
+        let mut sender = SequencedSender::new();
+        let mut receiver = SequencedReceiver::new();
+
+        let first = datagram(lorencia, sender.next_sequence(lorencia), 0);
+        let second = datagram(lorencia, sender.next_sequence(lorencia), 50);
+        let third = datagram(lorencia, sender.next_sequence(lorencia), 100);
+        assert_eq!(sender.next_sequence(devias), 0);
+
+        assert_eq!(receiver.receive(&first, 20), SequenceVerdict::Accepted);
+        assert_eq!(receiver.receive(&third, 130), SequenceVerdict::Accepted);
+        assert_eq!(receiver.receive(&second, 140), SequenceVerdict::Stale);
+        assert_eq!(receiver.receive(&third, 150), SequenceVerdict::Duplicate);
+        assert_eq!(
+            receiver.receive(&datagram(devias, 0, 100), 120),
+            SequenceVerdict::Accepted
+        );
+
+        let stats = receiver.stats();
+        assert_eq!(stats.accepted, 3);
+        assert_eq!(stats.stale, 1);
+        assert_eq!(stats.duplicates, 1);
+        assert_eq!(stats.skipped, 1);
+        // Transit went from 20 ms to 30 ms.
+        assert_eq!(stats.jitter_ms, 10.0 / 16.0);
+    }
 }
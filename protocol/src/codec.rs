@@ -2,24 +2,46 @@
 
 use crate::channel::{InvalidChannel, QuicChannel, TransportKind};
 use crate::message::{
-    ClientMessage, PROTOCOL_VERSION, PacketPayload, ProtocolVersion, ServerMessage, WirePacket,
+    ClientMessage, FrameCompression, PROTOCOL_VERSION, PacketPayload, ProtocolVersion,
+    ServerMessage, WirePacket,
 };
+use crate::negotiation::MIN_PROTOCOL_VERSION;
+use std::collections::BTreeMap;
 
 const STREAM_MAGIC: [u8; 2] = *b"MU";
 const STREAM_LENGTH_LEN: usize = 4;
 const STREAM_CHANNEL_LEN: usize = 1;
 const STREAM_MAGIC_LEN: usize = 2;
 const DATAGRAM_CHANNEL_LEN: usize = 1;
+/// Set on the channel byte of stream frames whose payload is LZ4 compressed.
+const COMPRESSED_FLAG: u8 = 0x80;
+/// Set on the channel byte of stream frames that carry one piece of a
+/// payload too large for a single frame.
+const FRAGMENT_FLAG: u8 = 0x40;
+
+/// Stream payloads below this size are sent uncompressed; LZ4 rarely
+/// shrinks them.
+pub const COMPRESSION_THRESHOLD: usize = 256;
 
 /// Number of bytes in the stream frame header.
 pub const STREAM_FRAME_HEADER_LEN: usize =
     STREAM_MAGIC_LEN + STREAM_CHANNEL_LEN + STREAM_LENGTH_LEN;
 
+/// Number of bytes in the fragment header that opens the payload of a
+/// fragment frame: message id (LE u32), index and total (LE u16 each).
+pub const FRAGMENT_HEADER_LEN: usize = 8;
+
 /// Limits used by the wire codec to protect against malformed payloads.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct CodecLimits {
     pub max_datagram_size: usize,
+    /// Largest stream payload on the wire, compressed or not. Larger ones
+    /// are split into fragment frames.
     pub max_stream_payload_size: usize,
+    /// Largest payload a run of fragment frames may add up to.
+    pub max_message_size: usize,
+    /// Largest payload a compressed stream frame may expand to.
+    pub max_decompressed_size: usize,
 }
 
 impl Default for CodecLimits {
@@ -28,6 +50,8 @@ impl Default for CodecLimits {
             // Safe baseline for internet paths without MTU discovery.
             max_datagram_size: 1200,
             max_stream_payload_size: 64 * 1024,
+            max_message_size: 1024 * 1024,
+            max_decompressed_size: 1024 * 1024,
         }
     }
 }
@@ -49,7 +73,7 @@ pub struct DecodedStreamFrame {
 /// Errors produced while encoding/decoding wire packets.
 #[derive(thiserror::Error, Debug)]
 pub enum CodecError {
-    #[error("packet version mismatch: expected {expected:?}, got {actual:?}")]
+    #[error("packet version mismatch: expected {expected:?} or a later minor, got {actual:?}")]
     VersionMismatch {
         expected: ProtocolVersion,
         actual: ProtocolVersion,
@@ -76,9 +100,29 @@ pub enum CodecError {
     #[error("stream payload exceeds limit: limit={limit} actual={actual}")]
     StreamPayloadTooLarge { limit: usize, actual: usize },
 
+    #[error("message {message_id} ended after {received} of {total} fragments")]
+    IncompleteMessage {
+        message_id: u32,
+        received: u16,
+        total: u16,
+    },
+
+    #[error("invalid fragment {index}/{total} of message {message_id}")]
+    InvalidFragment {
+        message_id: u32,
+        index: u16,
+        total: u16,
+    },
+
     #[error("invalid stream magic: expected [4D,55], got {actual:02X?}")]
     InvalidStreamMagic { actual: [u8; 2] },
 
+    #[error("decompressed payload exceeds limit: limit={limit} actual={actual}")]
+    DecompressedTooLarge { limit: usize, actual: usize },
+
+    #[error("decompression error: {0}")]
+    Decompression(#[from] lz4_flex::block::DecompressError),
+
     #[error("serialization error: {0}")]
     Serialization(#[from] postcard::Error),
 
@@ -87,17 +131,24 @@ pub enum CodecError {
 }
 
 /// Wire codec that serializes protocol packets with `postcard` and QUIC-aware framing.
+///
+/// Compressed stream frames are always decoded; they are only produced once
+/// [`WireCodec::with_compression`] picked the scheme negotiated with the peer.
 #[derive(Clone, Debug)]
 pub struct WireCodec {
     expected_version: ProtocolVersion,
+    min_version: ProtocolVersion,
     limits: CodecLimits,
+    compression: Option<FrameCompression>,
 }
 
 impl Default for WireCodec {
     fn default() -> Self {
         Self {
             expected_version: PROTOCOL_VERSION,
+            min_version: MIN_PROTOCOL_VERSION,
             limits: CodecLimits::default(),
+            compression: None,
         }
     }
 }
@@ -107,15 +158,45 @@ impl WireCodec {
     pub const fn new(expected_version: ProtocolVersion, limits: CodecLimits) -> Self {
         Self {
             expected_version,
+            min_version: expected_version,
             limits,
+            compression: None,
         }
     }
 
+    /// Same codec, compressing large stream payloads with `compression`.
+    #[must_use]
+    pub const fn with_compression(mut self, compression: Option<FrameCompression>) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    #[must_use]
+    pub const fn compression(&self) -> Option<FrameCompression> {
+        self.compression
+    }
+
+    /// Same codec, also reading packets of older minor versions down to
+    /// `min_version`.
+    #[must_use]
+    pub const fn with_min_version(mut self, min_version: ProtocolVersion) -> Self {
+        self.min_version = min_version;
+        self
+    }
+
     #[must_use]
     pub const fn expected_version(&self) -> ProtocolVersion {
         self.expected_version
     }
 
+    /// Whether packets stamped with `version` are read: the expected major,
+    /// from `min_version` on. Later minors only add what the peer will not
+    /// send before the hello agreed on it.
+    #[must_use]
+    pub fn accepts(&self, version: ProtocolVersion) -> bool {
+        version.major == self.expected_version.major && version >= self.min_version
+    }
+
     #[must_use]
     pub const fn limits(&self) -> CodecLimits {
         self.limits
@@ -176,9 +257,15 @@ impl WireCodec {
     ///
     /// Frame format:
     /// - bytes 0..2: magic `MU`
-    /// - byte 2: channel id
+    /// - byte 2: channel id, with the high bit set when compressed
     /// - bytes 3..7: payload length (LE u32)
-    /// - remaining bytes: postcard payload
+    /// - remaining bytes: postcard payload, or the LZ4 block of it preceded
+    ///   by its uncompressed length (LE u32)
+    ///
+    /// A payload above `max_stream_payload_size` is split into back-to-back
+    /// fragment frames, flagged with bit `0x40` of the channel byte. Each
+    /// opens with a fragment header: the packet sequence as message id
+    /// (LE u32), then the fragment index and total (LE u16 each).
     pub fn encode_stream_frame(
         &self,
         channel: QuicChannel,
@@ -190,29 +277,74 @@ impl WireCodec {
         self.validate_version(packet)?;
         self.validate_channel(channel, packet)?;
 
-        let payload = postcard::to_stdvec(packet)?;
-        if payload.len() > self.limits.max_stream_payload_size {
-            return Err(CodecError::StreamPayloadTooLarge {
-                limit: self.limits.max_stream_payload_size,
-                actual: payload.len(),
-            });
+        let mut payload = postcard::to_stdvec(packet)?;
+        let mut channel_byte = channel as u8;
+        if self.compression == Some(FrameCompression::Lz4) && payload.len() >= COMPRESSION_THRESHOLD
+        {
+            if payload.len() > self.limits.max_decompressed_size {
+                return Err(CodecError::DecompressedTooLarge {
+                    limit: self.limits.max_decompressed_size,
+                    actual: payload.len(),
+                });
+            }
+            let compressed = lz4_flex::compress_prepend_size(&payload);
+            if compressed.len() < payload.len() {
+                payload = compressed;
+                channel_byte |= COMPRESSED_FLAG;
+            }
+        }
+        if payload.len() <= self.limits.max_stream_payload_size {
+            let mut frame = Vec::with_capacity(STREAM_FRAME_HEADER_LEN + payload.len());
+            push_stream_frame(&mut frame, channel_byte, &[], &payload);
+            return Ok(frame);
         }
 
-        let mut frame = Vec::with_capacity(STREAM_FRAME_HEADER_LEN + payload.len());
-        frame.extend_from_slice(&STREAM_MAGIC);
-        frame.push(channel as u8);
-        frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
-        frame.extend_from_slice(&payload);
+        let chunk_len = self
+            .limits
+            .max_stream_payload_size
+            .saturating_sub(FRAGMENT_HEADER_LEN);
+        let total = (chunk_len > 0 && payload.len() <= self.limits.max_message_size)
+            .then(|| u16::try_from(payload.len().div_ceil(chunk_len)).ok())
+            .flatten()
+            .ok_or(CodecError::StreamPayloadTooLarge {
+                limit: self.limits.max_message_size,
+                actual: payload.len(),
+            })?;
+
+        let mut frame = Vec::with_capacity(
+            payload.len() + usize::from(total) * (STREAM_FRAME_HEADER_LEN + FRAGMENT_HEADER_LEN),
+        );
+        for (index, chunk) in (0..total).zip(payload.chunks(chunk_len)) {
+            let mut header = [0; FRAGMENT_HEADER_LEN];
+            header[..4].copy_from_slice(&packet.sequence.to_le_bytes());
+            header[4..6].copy_from_slice(&index.to_le_bytes());
+            header[6..].copy_from_slice(&total.to_le_bytes());
+            push_stream_frame(&mut frame, channel_byte | FRAGMENT_FLAG, &header, chunk);
+        }
         Ok(frame)
     }
 
     /// Attempts to decode a single stream frame from the beginning of `buffer`.
     ///
-    /// Returns `Ok(None)` when there are not enough bytes yet.
+    /// Returns `Ok(None)` when there are not enough bytes yet. Fragments are
+    /// reassembled whatever order they arrive in, as long as nothing else
+    /// comes between them; a run that stops short of its total waits for
+    /// more bytes, and one cut by another frame is an
+    /// [`CodecError::IncompleteMessage`]. Each call starts over; readers fed
+    /// a stream piece by piece keep a [`StreamDecoder`] instead.
     pub fn try_decode_stream_frame(
         &self,
         buffer: &[u8],
     ) -> Result<Option<(DecodedStreamFrame, usize)>, CodecError> {
+        StreamDecoder::new(self.clone()).decode(buffer)
+    }
+
+    /// Splits the header off the stream frame at the start of `buffer`,
+    /// without decoding its payload.
+    fn split_stream_frame<'a>(
+        &self,
+        buffer: &'a [u8],
+    ) -> Result<Option<(RawStreamFrame<'a>, usize)>, CodecError> {
         if buffer.len() < STREAM_FRAME_HEADER_LEN {
             return Ok(None);
         }
@@ -224,7 +356,9 @@ impl WireCodec {
             });
         }
 
-        let channel = QuicChannel::try_from(buffer[2])?;
+        let compressed = buffer[2] & COMPRESSED_FLAG != 0;
+        let fragmented = buffer[2] & FRAGMENT_FLAG != 0;
+        let channel = QuicChannel::try_from(buffer[2] & !(COMPRESSED_FLAG | FRAGMENT_FLAG))?;
         if channel.transport() == TransportKind::Datagram {
             return Err(CodecError::NotStreamChannel { channel });
         }
@@ -242,15 +376,90 @@ impl WireCodec {
             return Ok(None);
         }
 
-        let packet: WirePacket = postcard::from_bytes(&buffer[STREAM_FRAME_HEADER_LEN..total_len])?;
+        let mut payload = &buffer[STREAM_FRAME_HEADER_LEN..total_len];
+        let fragment = if fragmented {
+            let (header, rest) = payload
+                .split_first_chunk::<FRAGMENT_HEADER_LEN>()
+                .ok_or(postcard::Error::DeserializeUnexpectedEnd)?;
+            payload = rest;
+            let fragment = FragmentHeader {
+                message_id: u32::from_le_bytes([header[0], header[1], header[2], header[3]]),
+                index: u16::from_le_bytes([header[4], header[5]]),
+                total: u16::from_le_bytes([header[6], header[7]]),
+            };
+            if fragment.index >= fragment.total {
+                return Err(CodecError::InvalidFragment {
+                    message_id: fragment.message_id,
+                    index: fragment.index,
+                    total: fragment.total,
+                });
+            }
+            Some(fragment)
+        } else {
+            None
+        };
+
+        Ok(Some((
+            RawStreamFrame {
+                channel,
+                compressed,
+                fragment,
+                payload,
+            },
+            total_len,
+        )))
+    }
+
+    /// Rejects a run of fragments that cannot fit `max_message_size`, given
+    /// one fragment of it: every fragment but the last is as long as the
+    /// first, and the last carries at least one byte.
+    fn check_run_size(&self, fragment: FragmentHeader, len: usize) -> Result<(), CodecError> {
+        let others = usize::from(fragment.total - 1);
+        let smallest = if fragment.index == fragment.total - 1 {
+            others + len
+        } else {
+            others.saturating_mul(len) + 1
+        };
+        if smallest > self.limits.max_message_size {
+            return Err(CodecError::StreamPayloadTooLarge {
+                limit: self.limits.max_message_size,
+                actual: smallest,
+            });
+        }
+        Ok(())
+    }
+
+    fn decode_stream_payload(
+        &self,
+        channel: QuicChannel,
+        compressed: bool,
+        payload: &[u8],
+    ) -> Result<DecodedStreamFrame, CodecError> {
+        let packet: WirePacket = if compressed {
+            postcard::from_bytes(&self.decompress(payload)?)?
+        } else {
+            postcard::from_bytes(payload)?
+        };
         self.validate_version(&packet)?;
         self.validate_channel(channel, &packet)?;
 
-        Ok(Some((DecodedStreamFrame { channel, packet }, total_len)))
+        Ok(DecodedStreamFrame { channel, packet })
+    }
+
+    /// Checks the announced size against the limit before allocating it.
+    fn decompress(&self, payload: &[u8]) -> Result<Vec<u8>, CodecError> {
+        let (size, _) = lz4_flex::block::uncompressed_size(payload)?;
+        if size > self.limits.max_decompressed_size {
+            return Err(CodecError::DecompressedTooLarge {
+                limit: self.limits.max_decompressed_size,
+                actual: size,
+            });
+        }
+        Ok(lz4_flex::decompress_size_prepended(payload)?)
     }
 
     fn validate_version(&self, packet: &WirePacket) -> Result<(), CodecError> {
-        if packet.version != self.expected_version {
+        if !self.accepts(packet.version) {
             return Err(CodecError::VersionMismatch {
                 expected: self.expected_version,
                 actual: packet.version,
@@ -272,6 +481,182 @@ impl WireCodec {
     }
 }
 
+/// Stream frame decoder that keeps the fragments of a message between calls,
+/// so each frame of a run is read once however the bytes trickle in.
+///
+/// Until a call returns a frame, every call must be given the same buffer,
+/// possibly with more bytes appended; the returned length counts from its
+/// start.
+#[derive(Clone, Debug)]
+pub struct StreamDecoder {
+    codec: WireCodec,
+    pending: Option<PendingMessage>,
+}
+
+impl StreamDecoder {
+    #[must_use]
+    pub const fn new(codec: WireCodec) -> Self {
+        Self {
+            codec,
+            pending: None,
+        }
+    }
+
+    pub const fn codec(&self) -> &WireCodec {
+        &self.codec
+    }
+
+    /// Decodes the next frame of `buffer`, like
+    /// [`WireCodec::try_decode_stream_frame`]. An error drops the fragments
+    /// read so far.
+    pub fn decode(
+        &mut self,
+        buffer: &[u8],
+    ) -> Result<Option<(DecodedStreamFrame, usize)>, CodecError> {
+        let decoded = self.advance(buffer);
+        if decoded.is_err() {
+            self.pending = None;
+        }
+        decoded
+    }
+
+    fn advance(
+        &mut self,
+        buffer: &[u8],
+    ) -> Result<Option<(DecodedStreamFrame, usize)>, CodecError> {
+        let mut consumed = self.pending.as_ref().map_or(0, |pending| pending.read);
+        loop {
+            let rest = buffer.get(consumed..).unwrap_or_default();
+            let Some((frame, used)) = self.codec.split_stream_frame(rest)? else {
+                return Ok(None);
+            };
+            let Some(fragment) = frame.fragment else {
+                if let Some(pending) = &self.pending {
+                    return Err(pending.incomplete());
+                }
+                let decoded = self.codec.decode_stream_payload(
+                    frame.channel,
+                    frame.compressed,
+                    frame.payload,
+                )?;
+                return Ok(Some((decoded, consumed + used)));
+            };
+
+            let pending = match &mut self.pending {
+                Some(pending) if pending.message_id == fragment.message_id => pending,
+                Some(pending) => return Err(pending.incomplete()),
+                None => {
+                    self.codec.check_run_size(fragment, frame.payload.len())?;
+                    self.pending.insert(PendingMessage {
+                        message_id: fragment.message_id,
+                        total: fragment.total,
+                        channel: frame.channel,
+                        compressed: frame.compressed,
+                        piece_len: None,
+                        pieces: BTreeMap::new(),
+                        size: 0,
+                        read: 0,
+                    })
+                }
+            };
+            let last = fragment.index + 1 == fragment.total;
+            if fragment.total != pending.total
+                || frame.channel != pending.channel
+                || frame.compressed != pending.compressed
+                || pending.pieces.contains_key(&fragment.index)
+                || (!last
+                    && pending
+                        .piece_len
+                        .is_some_and(|len| len != frame.payload.len()))
+            {
+                return Err(CodecError::InvalidFragment {
+                    message_id: fragment.message_id,
+                    index: fragment.index,
+                    total: fragment.total,
+                });
+            }
+            if !last && pending.piece_len.is_none() {
+                self.codec.check_run_size(fragment, frame.payload.len())?;
+                pending.piece_len = Some(frame.payload.len());
+            }
+
+            pending.size += frame.payload.len();
+            if pending.size > self.codec.limits.max_message_size {
+                return Err(CodecError::StreamPayloadTooLarge {
+                    limit: self.codec.limits.max_message_size,
+                    actual: pending.size,
+                });
+            }
+            pending
+                .pieces
+                .insert(fragment.index, frame.payload.to_vec());
+            consumed += used;
+            pending.read = consumed;
+
+            if let Some(complete) = self
+                .pending
+                .take_if(|pending| pending.pieces.len() == usize::from(pending.total))
+            {
+                let payload: Vec<u8> = complete.pieces.into_values().flatten().collect();
+                let decoded = self.codec.decode_stream_payload(
+                    complete.channel,
+                    complete.compressed,
+                    &payload,
+                )?;
+                return Ok(Some((decoded, consumed)));
+            }
+        }
+    }
+}
+
+/// Fragments of a message read so far, by index.
+#[derive(Clone, Debug)]
+struct PendingMessage {
+    message_id: u32,
+    total: u16,
+    channel: QuicChannel,
+    compressed: bool,
+    /// Length every fragment but the last shares, once one of them arrived.
+    piece_len: Option<usize>,
+    pieces: BTreeMap<u16, Vec<u8>>,
+    size: usize,
+    /// Bytes of the caller's buffer the fragments were read from.
+    read: usize,
+}
+
+impl PendingMessage {
+    fn incomplete(&self) -> CodecError {
+        CodecError::IncompleteMessage {
+            message_id: self.message_id,
+            received: self.pieces.len() as u16,
+            total: self.total,
+        }
+    }
+}
+
+/// Stream frame whose header was read but whose payload was not decoded.
+struct RawStreamFrame<'a> {
+    channel: QuicChannel,
+    compressed: bool,
+    fragment: Option<FragmentHeader>,
+    payload: &'a [u8],
+}
+
+#[derive(Clone, Copy)]
+struct FragmentHeader {
+    message_id: u32,
+    index: u16,
+    total: u16,
+}
+
+fn push_stream_frame(frame: &mut Vec<u8>, channel_byte: u8, header: &[u8], payload: &[u8]) {
+    frame.extend_from_slice(&STREAM_MAGIC);
+    frame.push(channel_byte);
+    frame.extend_from_slice(&((header.len() + payload.len()) as u32).to_le_bytes());
+    frame.extend_from_slice(header);
+    frame.extend_from_slice(payload);
+}
+
 /// Returns the default channel for a payload variant.
 #[must_use]
 pub fn preferred_channel(payload: &PacketPayload) -> QuicChannel {
@@ -279,22 +664,100 @@ pub fn preferred_channel(payload: &PacketPayload) -> QuicChannel {
         PacketPayload::Client(msg) => match msg {
             ClientMessage::Move(_) => QuicChannel::GameplayInput,
             ClientMessage::UseSkill(_) => QuicChannel::GameplayEvent,
-            ClientMessage::Chat(_) => QuicChannel::Chat,
+            ClientMessage::Chat(_) | ClientMessage::Whisper(_) => QuicChannel::Chat,
+            ClientMessage::Emote { .. }
+            | ClientMessage::TalkToNpc { .. }
+            | ClientMessage::EquipItem { .. } => QuicChannel::GameplayEvent,
+            ClientMessage::RequestMailbox
+            | ClientMessage::ClaimMail { .. }
+            | ClientMessage::MoveItem { .. }
+            | ClientMessage::UnequipItem { .. }
+            | ClientMessage::DropItem { .. }
+            | ClientMessage::PickupItem { .. }
+            | ClientMessage::UseItem { .. }
+            | ClientMessage::RequestTrade { .. }
+            | ClientMessage::RespondTrade { .. }
+            | ClientMessage::AddTradeItem { .. }
+            | ClientMessage::RemoveTradeItem { .. }
+            | ClientMessage::SetTradeZen { .. }
+            | ClientMessage::LockTrade { .. }
+            | ClientMessage::ConfirmTrade
+            | ClientMessage::CancelTrade
+            | ClientMessage::OpenPersonalStore { .. }
+            | ClientMessage::ClosePersonalStore
+            | ClientMessage::BrowsePersonalStore { .. }
+            | ClientMessage::BuyFromPersonalStore { .. } => QuicChannel::Economy,
             ClientMessage::Hello(_)
             | ClientMessage::KeepAlive { .. }
+            | ClientMessage::Ping { .. }
             | ClientMessage::SelectCharacter { .. }
             | ClientMessage::MapTransferAck { .. }
+            | ClientMessage::RequestAccountSettings
+            | ClientMessage::StoreAccountSettings(_)
+            | ClientMessage::SetHelperActive { .. }
+            | ClientMessage::JoinGens { .. }
+            | ClientMessage::RequestBestiary
+            | ClientMessage::RequestDropTable { .. }
+            | ClientMessage::RegisterForEvent { .. }
+            | ClientMessage::CancelEventRegistration { .. }
             | ClientMessage::Logout => QuicChannel::Control,
         },
         PacketPayload::Server(msg) => match msg {
             ServerMessage::StateDelta { .. } => QuicChannel::GameplayInput,
-            ServerMessage::Chat(_) => QuicChannel::Chat,
-            ServerMessage::EnterMap { .. } => QuicChannel::GameplayEvent,
+            // Deltas build on each other, so unlike `StateDelta` none may be lost.
+            ServerMessage::WorldSnapshot(_) | ServerMessage::WorldDelta(_) => {
+                QuicChannel::GameplayEvent
+            }
+            ServerMessage::Chat(_)
+            | ServerMessage::WhisperResult { .. }
+            | ServerMessage::SystemNotice(_) => QuicChannel::Chat,
+            ServerMessage::EnterMap { .. }
+            | ServerMessage::EntityPath(_)
+            | ServerMessage::EntityStatus { .. }
+            | ServerMessage::MonsterAffixes { .. }
+            | ServerMessage::EntityEmote { .. }
+            | ServerMessage::GuildWarScore(_)
+            | ServerMessage::DamageEvent(_)
+            | ServerMessage::DoorStates { .. } => QuicChannel::GameplayEvent,
+            ServerMessage::Mailbox { .. }
+            | ServerMessage::MailClaimed { .. }
+            | ServerMessage::ItemsExpired { .. }
+            | ServerMessage::ItemMoved { .. }
+            | ServerMessage::ItemDropped { .. }
+            | ServerMessage::ItemPickedUp { .. }
+            | ServerMessage::ItemUsed { .. }
+            | ServerMessage::ItemRejected { .. }
+            | ServerMessage::TradeRequested { .. }
+            | ServerMessage::TradeOpened { .. }
+            | ServerMessage::TradeUpdated { .. }
+            | ServerMessage::TradeRejected { .. }
+            | ServerMessage::TradeClosed { .. }
+            | ServerMessage::PersonalStoreSign { .. }
+            | ServerMessage::PersonalStore(_)
+            | ServerMessage::PersonalStorePurchased { .. }
+            | ServerMessage::PersonalStoreSold { .. }
+            | ServerMessage::PersonalStoreRejected { .. }
+            | ServerMessage::EventTicketConsumed { .. } => QuicChannel::Economy,
             ServerMessage::HelloAck { .. }
             | ServerMessage::CharacterList { .. }
             | ServerMessage::MapTransfer(_)
+            | ServerMessage::Disconnect { .. }
             | ServerMessage::Pong { .. }
-            | ServerMessage::Error { .. } => QuicChannel::Control,
+            | ServerMessage::Maintenance(_)
+            | ServerMessage::EventNotice(_)
+            | ServerMessage::DoppelgangerStatus(_)
+            | ServerMessage::AccountSettings { .. }
+            | ServerMessage::HelperStatus { .. }
+            | ServerMessage::GensStatus(_)
+            | ServerMessage::QuestStatus(_)
+            | ServerMessage::Bestiary { .. }
+            | ServerMessage::BestiaryUnlocked { .. }
+            | ServerMessage::DropTable(_)
+            | ServerMessage::EventRegistered { .. }
+            | ServerMessage::EventRegistrationRejected { .. }
+            | ServerMessage::EventCountdown(_)
+            | ServerMessage::EventResult(_)
+            | ServerMessage::Error(_) => QuicChannel::Control,
         },
     }
 }
@@ -302,7 +765,8 @@ pub fn preferred_channel(payload: &PacketPayload) -> QuicChannel {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::message::{ClientMessage, MoveInput, RouteKey, WirePacket};
+    use crate::message::{ClientMessage, MoveInput, RouteKey, ServerMessage, WirePacket};
+    use crate::negotiation::ProtocolCapabilities;
 
     fn sample_packet() -> WirePacket {
         WirePacket::client(
@@ -358,4 +822,261 @@ mod tests {
         let partial = &frame[..frame.len() - 1];
         assert!(codec.try_decode_stream_frame(partial).unwrap().is_none());
     }
+
+    fn motd_packet(motd: String) -> WirePacket {
+        WirePacket::server(
+            10,
+            RouteKey::LOBBY,
+            1,
+            None,
+            50,
+            ServerMessage::HelloAck {
+                session_id: 10,
+                heartbeat_interval_ms: 5_000,
+                motd,
+                characters: Vec::new(),
+                protocol_version: PROTOCOL_VERSION,
+                capabilities: ProtocolCapabilities::LZ4_COMPRESSION,
+            },
+        )
+    }
+
+    #[test]
+    fn large_stream_payloads_are_compressed_once_negotiated() {
+        let packet = motd_packet("Bem-vindo a Lorencia! ".repeat(200));
+        let plain = WireCodec::default()
+            .encode_stream_frame(QuicChannel::Control, &packet)
+            .unwrap();
+        let codec = WireCodec::default().with_compression(Some(FrameCompression::Lz4));
+        let compressed = codec
+            .encode_stream_frame(QuicChannel::Control, &packet)
+            .unwrap();
+
+        assert_eq!(plain[2], QuicChannel::Control as u8);
+        assert_eq!(compressed[2], QuicChannel::Control as u8 | COMPRESSED_FLAG);
+        assert!(compressed.len() < plain.len() / 4);
+        // Any codec reads compressed frames.
+        let (decoded, used) = WireCodec::default()
+            .try_decode_stream_frame(&compressed)
+            .unwrap()
+            .unwrap();
+        assert_eq!(used, compressed.len());
+        assert_eq!(decoded.channel, QuicChannel::Control);
+        assert_eq!(decoded.packet, packet);
+
+        let small = motd_packet("oi".to_string());
+        let frame = codec
+            .encode_stream_frame(QuicChannel::Control, &small)
+            .unwrap();
+        assert_eq!(frame[2], QuicChannel::Control as u8, "too small to pay off");
+    }
+
+    #[test]
+    fn decompressed_size_is_capped() {
+        let packet = motd_packet("x".repeat(8 * 1024));
+        let frame = WireCodec::default()
+            .with_compression(Some(FrameCompression::Lz4))
+            .encode_stream_frame(QuicChannel::Control, &packet)
+            .unwrap();
+        let strict = WireCodec::new(
+            PROTOCOL_VERSION,
+            CodecLimits {
+                max_decompressed_size: 4 * 1024,
+                ..CodecLimits::default()
+            },
+        );
+
+        assert!(matches!(
+            strict.try_decode_stream_frame(&frame),
+            Err(CodecError::DecompressedTooLarge { limit: 4096, .. })
+        ));
+        assert!(matches!(
+            strict
+                .with_compression(Some(FrameCompression::Lz4))
+                .encode_stream_frame(QuicChannel::Control, &packet),
+            Err(CodecError::DecompressedTooLarge { .. })
+        ));
+    }
+
+    fn fragmenting_codec() -> WireCodec {
+        WireCodec::new(
+            PROTOCOL_VERSION,
+            CodecLimits {
+                max_stream_payload_size: 64,
+                ..CodecLimits::default()
+            },
+        )
+    }
+
+    /// Splits back-to-back stream frames at their length fields.
+    fn split_frames(mut bytes: &[u8]) -> Vec<&[u8]> {
+        let mut frames = Vec::new();
+        while !bytes.is_empty() {
+            let len = u32::from_le_bytes([bytes[3], bytes[4], bytes[5], bytes[6]]) as usize;
+            let (frame, rest) = bytes.split_at(STREAM_FRAME_HEADER_LEN + len);
+            frames.push(frame);
+            bytes = rest;
+        }
+        frames
+    }
+
+    #[test]
+    fn oversized_payloads_travel_as_fragments() {
+        let codec = fragmenting_codec();
+        let packet = motd_packet("Lorencia, Devias e Noria. ".repeat(20));
+        let bytes = codec
+            .encode_stream_frame(QuicChannel::Control, &packet)
+            .unwrap();
+
+        let frames = split_frames(&bytes);
+        assert!(frames.len() > 2);
+        for (index, frame) in frames.iter().enumerate() {
+            assert_eq!(frame[2], QuicChannel::Control as u8 | FRAGMENT_FLAG);
+            assert!(frame.len() <= STREAM_FRAME_HEADER_LEN + 64);
+            let header = &frame[STREAM_FRAME_HEADER_LEN..];
+            assert_eq!(header[..4], packet.sequence.to_le_bytes());
+            assert_eq!(header[4..6], (index as u16).to_le_bytes());
+            assert_eq!(header[6..8], (frames.len() as u16).to_le_bytes());
+        }
+
+        let (decoded, used) = codec.try_decode_stream_frame(&bytes).unwrap().unwrap();
+        assert_eq!(used, bytes.len());
+        assert_eq!(decoded.packet, packet);
+
+        let strict = WireCodec::new(
+            PROTOCOL_VERSION,
+            CodecLimits {
+                max_stream_payload_size: 64,
+                max_message_size: 256,
+                ..CodecLimits::default()
+            },
+        );
+        assert!(matches!(
+            strict.encode_stream_frame(QuicChannel::Control, &packet),
+            Err(CodecError::StreamPayloadTooLarge { limit: 256, .. })
+        ));
+        assert!(matches!(
+            strict.try_decode_stream_frame(&bytes),
+            Err(CodecError::StreamPayloadTooLarge { limit: 256, .. })
+        ));
+    }
+
+    #[test]
+    fn fragments_are_reassembled_in_any_order() {
+        let codec = fragmenting_codec().with_compression(Some(FrameCompression::Lz4));
+        let packet = motd_packet((0..400).map(|n| format!("{n:03}")).collect());
+        let bytes = codec
+            .encode_stream_frame(QuicChannel::Control, &packet)
+            .unwrap();
+        assert_ne!(bytes[2] & COMPRESSED_FLAG, 0);
+
+        let mut frames = split_frames(&bytes);
+        frames.reverse();
+        frames.swap(0, 1);
+        let shuffled = frames.concat();
+        let trailing = codec
+            .encode_stream_frame(QuicChannel::Control, &motd_packet("oi".to_string()))
+            .unwrap();
+        let buffer = [shuffled.as_slice(), trailing.as_slice()].concat();
+
+        let (decoded, used) = codec.try_decode_stream_frame(&buffer).unwrap().unwrap();
+        assert_eq!(used, shuffled.len());
+        assert_eq!(decoded.packet, packet);
+        let (next, _) = codec
+            .try_decode_stream_frame(&buffer[used..])
+            .unwrap()
+            .unwrap();
+        assert_eq!(next.packet, motd_packet("oi".to_string()));
+
+        // The same fragment twice never completes a message.
+        let repeated = [frames[0], frames[0]].concat();
+        assert!(matches!(
+            codec.try_decode_stream_frame(&repeated),
+            Err(CodecError::InvalidFragment { .. })
+        ));
+    }
+
+    #[test]
+    fn stream_decoder_reads_each_fragment_once() {
+        let codec = fragmenting_codec();
+        let packet = motd_packet("Noria, Elbeland e Aida. ".repeat(20));
+        let bytes = codec
+            .encode_stream_frame(QuicChannel::Control, &packet)
+            .unwrap();
+        let frames = split_frames(&bytes);
+        let mut decoder = StreamDecoder::new(codec);
+
+        let mut buffer = Vec::new();
+        for frame in &frames[..frames.len() - 1] {
+            buffer.extend_from_slice(frame);
+            assert!(decoder.decode(&buffer).unwrap().is_none());
+        }
+        // Bytes already read are not parsed again.
+        buffer.fill(0);
+        buffer.extend_from_slice(frames[frames.len() - 1]);
+
+        let (decoded, used) = decoder.decode(&buffer).unwrap().unwrap();
+        assert_eq!(used, bytes.len());
+        assert_eq!(decoded.packet, packet);
+        assert!(decoder.decode(&[]).unwrap().is_none());
+    }
+
+    #[test]
+    fn runs_too_large_for_the_message_limit_are_rejected_up_front() {
+        let codec = WireCodec::default();
+        let mut header = [0; FRAGMENT_HEADER_LEN];
+        header[..4].copy_from_slice(&7u32.to_le_bytes());
+        header[4..6].copy_from_slice(&0u16.to_le_bytes());
+        header[6..].copy_from_slice(&u16::MAX.to_le_bytes());
+        let mut frame = Vec::new();
+        push_stream_frame(
+            &mut frame,
+            QuicChannel::Control as u8 | FRAGMENT_FLAG,
+            &header,
+            &[0; 64],
+        );
+
+        assert!(matches!(
+            codec.try_decode_stream_frame(&frame),
+            Err(CodecError::StreamPayloadTooLarge { limit, .. })
+                if limit == CodecLimits::default().max_message_size
+        ));
+    }
+
+    #[test]
+    fn losing_the_final_fragment_leaves_the_message_incomplete() {
+        let codec = fragmenting_codec();
+        let packet = motd_packet("Bem-vindo a Lorencia! ".repeat(20));
+        let bytes = codec
+            .encode_stream_frame(QuicChannel::Control, &packet)
+            .unwrap();
+        let frames = split_frames(&bytes);
+        let total = frames.len() as u16;
+        let without_last = frames[..frames.len() - 1].concat();
+
+        // Until the stream ends, the last fragment may still be on its way.
+        assert!(
+            codec
+                .try_decode_stream_frame(&without_last)
+                .unwrap()
+                .is_none()
+        );
+
+        let next = codec
+            .encode_stream_frame(QuicChannel::Control, &motd_packet("oi".to_string()))
+            .unwrap();
+        let buffer = [without_last.as_slice(), next.as_slice()].concat();
+        match codec.try_decode_stream_frame(&buffer) {
+            Err(CodecError::IncompleteMessage {
+                message_id,
+                received,
+                total: expected,
+            }) => {
+                assert_eq!(message_id, packet.sequence);
+                assert_eq!(received, total - 1);
+                assert_eq!(expected, total);
+            }
+            other => panic!("expected an incomplete message, got {other:?}"),
+        }
+    }
 }
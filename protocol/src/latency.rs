@@ -0,0 +1,161 @@
+//! Round-trip time and clock offset from `Ping`/`Pong` exchanges.
+//!
+//! An exchange carries four timestamps: the client sends at `t0`, the server
+//! receives at `t1` and answers at `t2`, the client receives at `t3`. The
+//! round trip is `(t3 - t0) - (t2 - t1)` and, taking both legs as equally
+//! long, the server clock runs `((t1 - t0) + (t2 - t3)) / 2` ahead of the
+//! client's. [`LatencyEstimator`] smooths the round trip the way TCP does
+//! (RFC 6298) and takes the offset from the fastest recent exchange, whose
+//! legs are the least likely to be lopsided.
+
+use std::collections::VecDeque;
+
+/// Recent exchanges the clock offset is picked from.
+const OFFSET_WINDOW: usize = 8;
+
+/// Timestamps of one exchange, in ms of each side's clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PingSample {
+    pub client_sent_ms: u64,
+    pub server_received_ms: u64,
+    pub server_sent_ms: u64,
+    pub client_received_ms: u64,
+}
+
+impl PingSample {
+    /// Time on the wire, without the time the server held the ping.
+    pub fn rtt_ms(&self) -> u64 {
+        let total = self.client_received_ms.saturating_sub(self.client_sent_ms);
+        let held = self.server_sent_ms.saturating_sub(self.server_received_ms);
+        total.saturating_sub(held)
+    }
+
+    /// How far the server clock is ahead of the client's; negative when it
+    /// is behind.
+    pub fn clock_offset_ms(&self) -> i64 {
+        let outbound = self.server_received_ms as i64 - self.client_sent_ms as i64;
+        let inbound = self.server_sent_ms as i64 - self.client_received_ms as i64;
+        (outbound + inbound) / 2
+    }
+}
+
+/// Latency of a connection, from the exchanges seen so far.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyEstimator {
+    smoothed_rtt_ms: Option<f64>,
+    rtt_variation_ms: f64,
+    recent: VecDeque<PingSample>,
+}
+
+impl LatencyEstimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, sample: PingSample) {
+        let rtt = sample.rtt_ms() as f64;
+        match self.smoothed_rtt_ms {
+            None => {
+                self.smoothed_rtt_ms = Some(rtt);
+                self.rtt_variation_ms = rtt / 2.0;
+            }
+            Some(smoothed) => {
+                self.rtt_variation_ms =
+                    0.75 * self.rtt_variation_ms + 0.25 * (smoothed - rtt).abs();
+                self.smoothed_rtt_ms = Some(0.875 * smoothed + 0.125 * rtt);
+            }
+        }
+
+        if self.recent.len() == OFFSET_WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(sample);
+    }
+
+    /// Smoothed round trip; `None` before the first exchange.
+    pub fn rtt_ms(&self) -> Option<f64> {
+        self.smoothed_rtt_ms
+    }
+
+    /// How much the round trip varies, i.e. the jitter.
+    pub fn rtt_variation_ms(&self) -> Option<f64> {
+        self.smoothed_rtt_ms.map(|_| self.rtt_variation_ms)
+    }
+
+    /// Offset of the fastest recent exchange; see
+    /// [`PingSample::clock_offset_ms`].
+    pub fn clock_offset_ms(&self) -> Option<i64> {
+        self.recent
+            .iter()
+            .min_by_key(|sample| sample.rtt_ms())
+            .map(PingSample::clock_offset_ms)
+    }
+
+    /// Server clock when the local one reads `client_now_ms`.
+    pub fn server_time_ms(&self, client_now_ms: u64) -> Option<u64> {
+        self.clock_offset_ms()
+            .map(|offset| client_now_ms.saturating_add_signed(offset))
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(client_sent_ms: u64, one_way_ms: u64, server_ahead_ms: u64) -> PingSample {
+        let server_received_ms = client_sent_ms + one_way_ms + server_ahead_ms;
+        PingSample {
+            client_sent_ms,
+            server_received_ms,
+            server_sent_ms: server_received_ms + 5,
+            client_received_ms: client_sent_ms + 2 * one_way_ms + 5,
+        }
+    }
+
+    #[test]
+    fn samples_give_round_trip_and_offset() {
+        let ping = sample(1_000, 40, 250);
+        assert_eq!(ping.rtt_ms(), 80);
+        assert_eq!(ping.clock_offset_ms(), 250);
+
+        let behind = PingSample {
+            client_sent_ms: 1_000,
+            server_received_ms: 620,
+            server_sent_ms: 620,
+            client_received_ms: 1_040,
+        };
+        assert_eq!(behind.clock_offset_ms(), -400);
+    }
+
+    #[test]
+    fn estimator_smooths_rtt_and_trusts_the_fastest_offset() {
+        let mut latency = LatencyEstimator::new();
+        assert_eq!(latency.rtt_ms(), None);
+        assert_eq!(latency.server_time_ms(0), None);
+
+        latency.record(sample(0, 50, 100));
+        assert_eq!(latency.rtt_ms(), Some(100.0));
+        assert_eq!(latency.rtt_variation_ms(), Some(50.0));
+
+        // A slow exchange barely moves the smoothed RTT, and its lopsided
+        // legs do not replace the offset.
+        latency.record(PingSample {
+            client_sent_ms: 1_000,
+            server_received_ms: 1_120,
+            server_sent_ms: 1_120,
+            client_received_ms: 1_300,
+        });
+        assert_eq!(latency.rtt_ms(), Some(125.0));
+        assert_eq!(latency.clock_offset_ms(), Some(100));
+        assert_eq!(latency.server_time_ms(5_000), Some(5_100));
+
+        for at in 0..OFFSET_WINDOW as u64 {
+            latency.record(sample(2_000 + at * 1_000, 60, 30));
+        }
+        assert_eq!(latency.clock_offset_ms(), Some(30), "old exchanges age out");
+    }
+}
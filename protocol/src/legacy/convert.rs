@@ -0,0 +1,507 @@
+//! Mapping between classic Season 6 packets and the v2 message model.
+//!
+//! Only packets with a v2 counterpart convert; the others come back as
+//! `None` so a bridge can log and drop them. Names are 10-byte NUL-padded
+//! fields and entity ids are big-endian, as in the original layouts.
+
+use super::LegacyError;
+use super::packet::{LegacyPacket, read_text, write_text};
+use crate::{
+    ChatChannel, ChatPayload, ClientMessage, MoveInput, NoticeStyle, ServerMessage, SystemNotice,
+    TradeFailure, TradeOutcome, WhisperRequest,
+};
+
+const CHAT: u8 = 0x00;
+const WHISPER: u8 = 0x02;
+const NOTICE: u8 = 0x0D;
+const PING: u8 = 0x0E;
+const PICKUP_ITEM: u8 = 0x22;
+const TRADE_REQUEST: u8 = 0x36;
+const TRADE_RESPONSE: u8 = 0x37;
+const TRADE_ZEN: u8 = 0x3A;
+const TRADE_PARTNER_ZEN: u8 = 0x3B;
+const TRADE_OK: u8 = 0x3C;
+const TRADE_CLOSE: u8 = 0x3D;
+const PERSONAL_STORE: u8 = 0x3F;
+const WALK: u8 = 0xD4;
+const CONNECTION: u8 = 0xF1;
+
+const STORE_CLOSE: u8 = 0x03;
+const STORE_BROWSE: u8 = 0x05;
+const CONNECTION_LOGOUT: u8 = 0x02;
+
+const NAME_LEN: usize = 10;
+/// Classic chat box line limit.
+const MESSAGE_LEN: usize = 60;
+/// Offset of the text in a notice body, after the type and colour fields.
+const NOTICE_TEXT_OFFSET: usize = 10;
+
+/// Tile offsets of the eight walk directions, indexed by direction.
+const WALK_STEPS: [(i16, i16); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+];
+
+/// Chat prefixes the classic client puts in front of party, guild and Gens
+/// lines; anything else is said out loud.
+const CHAT_PREFIXES: [(char, ChatChannel); 3] = [
+    ('~', ChatChannel::Party),
+    ('@', ChatChannel::Guild),
+    ('$', ChatChannel::Gens),
+];
+
+/// Message a classic client packet stands for.
+pub fn client_message(packet: &LegacyPacket) -> Result<Option<ClientMessage>, LegacyError> {
+    let body = packet.body.as_slice();
+    let message = match packet.code {
+        CHAT => {
+            let text = read_text(field(body, NAME_LEN, MESSAGE_LEN)?);
+            let (channel, text) = CHAT_PREFIXES
+                .iter()
+                .find_map(|&(prefix, channel)| {
+                    text.strip_prefix(prefix)
+                        .map(|rest| (channel, rest.to_string()))
+                })
+                .unwrap_or((ChatChannel::Local, text));
+            ClientMessage::Chat(ChatPayload {
+                channel,
+                target: None,
+                text,
+            })
+        }
+        WHISPER => ClientMessage::Whisper(WhisperRequest {
+            target: read_text(field(body, 0, NAME_LEN)?),
+            text: read_text(field(body, NAME_LEN, MESSAGE_LEN)?),
+        }),
+        PING => ClientMessage::KeepAlive {
+            client_time_ms: u64::from(u32_le(body, 1)?),
+        },
+        WALK => ClientMessage::Move(walk_input(body)?),
+        PICKUP_ITEM => ClientMessage::PickupItem {
+            entity_id: u32::from(u16_be(body, 0)?),
+        },
+        TRADE_REQUEST => ClientMessage::RequestTrade {
+            target_entity_id: u32::from(u16_be(body, 0)?),
+        },
+        TRADE_RESPONSE => ClientMessage::RespondTrade {
+            accept: byte(body, 0)? != 0,
+        },
+        TRADE_ZEN => ClientMessage::SetTradeZen {
+            zen: u64::from(u32_le(body, 1)?),
+        },
+        TRADE_OK => ClientMessage::LockTrade {
+            locked: byte(body, 0)? != 0,
+        },
+        TRADE_CLOSE => ClientMessage::CancelTrade,
+        PERSONAL_STORE => match packet.sub_code() {
+            Some(STORE_CLOSE) => ClientMessage::ClosePersonalStore,
+            Some(STORE_BROWSE) => ClientMessage::BrowsePersonalStore {
+                owner_entity_id: u32::from(u16_be(body, 1)?),
+            },
+            _ => return Ok(None),
+        },
+        CONNECTION if packet.sub_code() == Some(CONNECTION_LOGOUT) => ClientMessage::Logout,
+        _ => return Ok(None),
+    };
+    Ok(Some(message))
+}
+
+/// Packet a classic client sends for `message`, `None` when it has no
+/// classic counterpart. `sender` fills the name field of chat lines.
+pub fn client_packet(
+    message: &ClientMessage,
+    sender: &str,
+) -> Result<Option<LegacyPacket>, LegacyError> {
+    let mut body = Vec::new();
+    let code = match message {
+        ClientMessage::Chat(chat) => {
+            let prefix = CHAT_PREFIXES
+                .iter()
+                .find(|(_, channel)| *channel == chat.channel)
+                .map(|&(prefix, _)| prefix.to_string())
+                .unwrap_or_default();
+            write_text(&mut body, sender, NAME_LEN);
+            write_line(&mut body, &format!("{prefix}{}", chat.text));
+            CHAT
+        }
+        ClientMessage::Whisper(whisper) => {
+            write_text(&mut body, &whisper.target, NAME_LEN);
+            write_line(&mut body, &whisper.text);
+            WHISPER
+        }
+        ClientMessage::KeepAlive { client_time_ms } => {
+            body.push(0);
+            body.extend_from_slice(&(*client_time_ms as u32).to_le_bytes());
+            body.extend_from_slice(&[0; 4]);
+            PING
+        }
+        ClientMessage::Move(input) => {
+            body.extend_from_slice(&walk_bytes(input));
+            WALK
+        }
+        ClientMessage::PickupItem { entity_id } => {
+            body.extend_from_slice(&classic_entity_id(*entity_id)?.to_be_bytes());
+            PICKUP_ITEM
+        }
+        ClientMessage::RequestTrade { target_entity_id } => {
+            body.extend_from_slice(&classic_entity_id(*target_entity_id)?.to_be_bytes());
+            TRADE_REQUEST
+        }
+        ClientMessage::RespondTrade { accept } => {
+            body.push(u8::from(*accept));
+            TRADE_RESPONSE
+        }
+        ClientMessage::SetTradeZen { zen } => {
+            body.push(0);
+            body.extend_from_slice(&u32::try_from(*zen).unwrap_or(u32::MAX).to_le_bytes());
+            TRADE_ZEN
+        }
+        ClientMessage::LockTrade { locked } => {
+            body.push(u8::from(*locked));
+            TRADE_OK
+        }
+        ClientMessage::CancelTrade => TRADE_CLOSE,
+        ClientMessage::ClosePersonalStore => {
+            body.push(STORE_CLOSE);
+            PERSONAL_STORE
+        }
+        ClientMessage::BrowsePersonalStore { owner_entity_id } => {
+            body.push(STORE_BROWSE);
+            body.extend_from_slice(&classic_entity_id(*owner_entity_id)?.to_be_bytes());
+            write_text(&mut body, "", NAME_LEN);
+            PERSONAL_STORE
+        }
+        ClientMessage::Logout => {
+            body.extend_from_slice(&[CONNECTION_LOGOUT, 0]);
+            CONNECTION
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(LegacyPacket::new(code, body)))
+}
+
+/// Packets that tell a classic client about `message`; empty when it has
+/// no classic counterpart. A trade update takes two: the partner's zen and
+/// OK button.
+pub fn server_packets(message: &ServerMessage) -> Vec<LegacyPacket> {
+    let mut body = Vec::new();
+    let packet = match message {
+        ServerMessage::Chat(chat) => {
+            let name = chat.target.as_deref().unwrap_or_default();
+            write_text(&mut body, name, NAME_LEN);
+            write_line(&mut body, &chat.text);
+            let code = if chat.channel == ChatChannel::Whisper {
+                WHISPER
+            } else {
+                CHAT
+            };
+            LegacyPacket::new(code, body)
+        }
+        ServerMessage::SystemNotice(notice) => {
+            body.push(match notice.style {
+                NoticeStyle::Golden => 0,
+                NoticeStyle::Chat => 1,
+                NoticeStyle::Guild => 2,
+            });
+            body.resize(NOTICE_TEXT_OFFSET, 0);
+            body.extend_from_slice(notice.text.as_bytes());
+            body.push(0);
+            LegacyPacket::new(NOTICE, body)
+        }
+        ServerMessage::TradeRequested { name, .. } => {
+            write_text(&mut body, name, NAME_LEN);
+            LegacyPacket::new(TRADE_REQUEST, body).encrypted()
+        }
+        ServerMessage::TradeOpened {
+            partner_name,
+            partner_level,
+            ..
+        } => trade_response(true, partner_name, *partner_level),
+        ServerMessage::TradeClosed {
+            outcome: TradeOutcome::Declined,
+        } => trade_response(false, "", 0),
+        ServerMessage::TradeClosed { outcome } => LegacyPacket::new(
+            TRADE_CLOSE,
+            vec![match outcome {
+                TradeOutcome::Cancelled | TradeOutcome::Declined => 0,
+                TradeOutcome::Completed => 1,
+                TradeOutcome::Failed(TradeFailure::InventoryFull) => 2,
+                TradeOutcome::Failed(_) => 3,
+            }],
+        ),
+        ServerMessage::TradeUpdated { partner, .. } => {
+            let mut zen = vec![0];
+            zen.extend_from_slice(&u32::try_from(partner.zen).unwrap_or(u32::MAX).to_le_bytes());
+            // Partner items need the classic item encoding, which the
+            // bridge does not have yet.
+            return vec![
+                LegacyPacket::new(TRADE_PARTNER_ZEN, zen),
+                LegacyPacket::new(TRADE_OK, vec![u8::from(partner.locked)]),
+            ];
+        }
+        _ => return Vec::new(),
+    };
+    vec![packet]
+}
+
+/// Message a classic server packet stands for, where one packet is enough
+/// to rebuild it. Entity ids a classic packet leaves out come back as 0.
+pub fn server_message(packet: &LegacyPacket) -> Result<Option<ServerMessage>, LegacyError> {
+    let body = packet.body.as_slice();
+    let message = match packet.code {
+        CHAT | WHISPER => {
+            let name = read_text(field(body, 0, NAME_LEN)?);
+            ServerMessage::Chat(ChatPayload {
+                channel: if packet.code == WHISPER {
+                    ChatChannel::Whisper
+                } else {
+                    ChatChannel::Local
+                },
+                target: (!name.is_empty()).then_some(name),
+                text: read_text(field(body, NAME_LEN, MESSAGE_LEN)?),
+            })
+        }
+        NOTICE => ServerMessage::SystemNotice(SystemNotice {
+            style: match byte(body, 0)? {
+                0 => NoticeStyle::Golden,
+                2 => NoticeStyle::Guild,
+                _ => NoticeStyle::Chat,
+            },
+            text: read_text(
+                body.get(NOTICE_TEXT_OFFSET..)
+                    .ok_or(LegacyError::Truncated)?,
+            ),
+        }),
+        TRADE_REQUEST => ServerMessage::TradeRequested {
+            from_entity_id: 0,
+            name: read_text(field(body, 0, NAME_LEN)?),
+            level: 0,
+        },
+        TRADE_RESPONSE if byte(body, 0)? == 1 => ServerMessage::TradeOpened {
+            partner_entity_id: 0,
+            partner_name: read_text(field(body, 1, NAME_LEN)?),
+            partner_level: u16::from_le_bytes([byte(body, 11)?, byte(body, 12)?]),
+        },
+        TRADE_RESPONSE => ServerMessage::TradeClosed {
+            outcome: TradeOutcome::Declined,
+        },
+        TRADE_CLOSE => ServerMessage::TradeClosed {
+            outcome: match byte(body, 0)? {
+                0 => TradeOutcome::Cancelled,
+                1 => TradeOutcome::Completed,
+                2 => TradeOutcome::Failed(TradeFailure::InventoryFull),
+                _ => TradeOutcome::Failed(TradeFailure::NotAllowed),
+            },
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(message))
+}
+
+/// Trade request answer: partner name, level and guild number on accept.
+fn trade_response(accepted: bool, name: &str, level: u16) -> LegacyPacket {
+    let mut body = vec![u8::from(accepted)];
+    write_text(&mut body, name, NAME_LEN);
+    body.extend_from_slice(&level.to_le_bytes());
+    body.extend_from_slice(&[0; 4]);
+    LegacyPacket::new(TRADE_RESPONSE, body)
+}
+
+/// Walk request: start tile, then the facing direction and step count in
+/// one byte and the step directions two to a byte. The v2 input carries the
+/// tile the walk ends on.
+fn walk_input(body: &[u8]) -> Result<MoveInput, LegacyError> {
+    let (mut x, mut y) = (u16::from(byte(body, 0)?), u16::from(byte(body, 1)?));
+    let mut path = [0u8; 8];
+    let steps = body.get(2..).unwrap_or_default();
+    let copied = steps.len().min(path.len());
+    path[..copied].copy_from_slice(&steps[..copied]);
+
+    let count = usize::from(path[0] & 0x0F).min((path.len() - 1) * 2);
+    for step in 0..count {
+        let packed = path[1 + step / 2];
+        let direction = if step % 2 == 0 {
+            packed >> 4
+        } else {
+            packed & 0x0F
+        };
+        let (dx, dy) = WALK_STEPS[usize::from(direction & 0x07)];
+        x = x.wrapping_add_signed(dx);
+        y = y.wrapping_add_signed(dy);
+    }
+    Ok(MoveInput {
+        client_tick: 0,
+        x,
+        y,
+        direction: path[0] >> 4,
+        path,
+    })
+}
+
+/// Inverse of [`walk_input`]: walks the steps back from the end tile.
+fn walk_bytes(input: &MoveInput) -> Vec<u8> {
+    let mut path = input.path;
+    path[0] = (input.direction << 4) | (path[0] & 0x0F);
+    let count = usize::from(path[0] & 0x0F).min((path.len() - 1) * 2);
+    let (mut x, mut y) = (input.x, input.y);
+    for step in 0..count {
+        let packed = path[1 + step / 2];
+        let direction = if step % 2 == 0 {
+            packed >> 4
+        } else {
+            packed & 0x0F
+        };
+        let (dx, dy) = WALK_STEPS[usize::from(direction & 0x07)];
+        x = x.wrapping_add_signed(-dx);
+        y = y.wrapping_add_signed(-dy);
+    }
+    let mut bytes = vec![x as u8, y as u8];
+    bytes.extend_from_slice(&path);
+    bytes
+}
+
+/// Chat text, cut to the classic line limit and NUL-terminated.
+fn write_line(bytes: &mut Vec<u8>, text: &str) {
+    let mut end = text.len().min(MESSAGE_LEN - 1);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    bytes.extend_from_slice(&text.as_bytes()[..end]);
+    bytes.push(0);
+}
+
+/// Up to `len` bytes from `offset`; text fields may be sent short.
+fn field(body: &[u8], offset: usize, len: usize) -> Result<&[u8], LegacyError> {
+    let rest = body.get(offset..).ok_or(LegacyError::Truncated)?;
+    Ok(&rest[..rest.len().min(len)])
+}
+
+fn byte(body: &[u8], offset: usize) -> Result<u8, LegacyError> {
+    body.get(offset).copied().ok_or(LegacyError::Truncated)
+}
+
+fn u16_be(body: &[u8], offset: usize) -> Result<u16, LegacyError> {
+    Ok(u16::from_be_bytes([
+        byte(body, offset)?,
+        byte(body, offset + 1)?,
+    ]))
+}
+
+/// Classic packets carry entity ids in two bytes.
+fn classic_entity_id(entity_id: u32) -> Result<u16, LegacyError> {
+    u16::try_from(entity_id).map_err(|_| LegacyError::EntityIdOutOfRange(entity_id))
+}
+
+fn u32_le(body: &[u8], offset: usize) -> Result<u32, LegacyError> {
+    let bytes = body.get(offset..offset + 4).ok_or(LegacyError::Truncated)?;
+    Ok(u32::from_le_bytes(bytes.try_into().expect("4-byte slice")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(message: ClientMessage) {
+        let packet = client_packet(&message, "Elfa")
+            .unwrap()
+            .expect("classic counterpart");
+        assert_eq!(client_message(&packet).unwrap(), Some(message));
+    }
+
+    #[test]
+    fn client_messages_survive_the_classic_layout() {
+        round_trip(ClientMessage::Chat(ChatPayload {
+            channel: ChatChannel::Guild,
+            target: None,
+            text: "reuniao no lorencia".to_string(),
+        }));
+        round_trip(ClientMessage::Whisper(WhisperRequest {
+            target: "Guerreiro".to_string(),
+            text: "oi".to_string(),
+        }));
+        round_trip(ClientMessage::KeepAlive {
+            client_time_ms: 123_456,
+        });
+        round_trip(ClientMessage::PickupItem { entity_id: 0x1234 });
+        round_trip(ClientMessage::RequestTrade {
+            target_entity_id: 77,
+        });
+        round_trip(ClientMessage::SetTradeZen { zen: 1_500_000 });
+        round_trip(ClientMessage::LockTrade { locked: true });
+        round_trip(ClientMessage::CancelTrade);
+        round_trip(ClientMessage::BrowsePersonalStore {
+            owner_entity_id: 12,
+        });
+        round_trip(ClientMessage::Logout);
+        assert_eq!(
+            client_packet(&ClientMessage::ConfirmTrade, "Elfa"),
+            Ok(None)
+        );
+        assert_eq!(
+            client_packet(
+                &ClientMessage::PickupItem {
+                    entity_id: 0x1_0000
+                },
+                "Elfa"
+            ),
+            Err(LegacyError::EntityIdOutOfRange(0x1_0000))
+        );
+    }
+
+    #[test]
+    fn walks_end_on_the_last_step() {
+        // From (130, 120) facing 3: two steps east, one south-east.
+        let packet = LegacyPacket::new(WALK, vec![130, 120, 0x33, 0x33, 0x40]);
+        let Some(ClientMessage::Move(input)) = client_message(&packet).unwrap() else {
+            panic!("walk converts to a move");
+        };
+        assert_eq!((input.x, input.y, input.direction), (133, 121, 3));
+        assert_eq!(walk_bytes(&input)[..5], packet.body[..]);
+    }
+
+    #[test]
+    fn server_messages_reach_classic_clients() {
+        let messages = [
+            ServerMessage::Chat(ChatPayload {
+                channel: ChatChannel::Whisper,
+                target: Some("Elfa".to_string()),
+                text: "oi".to_string(),
+            }),
+            ServerMessage::SystemNotice(SystemNotice {
+                style: NoticeStyle::Golden,
+                text: "Blood Castle abre em 5 minutos".to_string(),
+            }),
+            ServerMessage::TradeOpened {
+                partner_entity_id: 0,
+                partner_name: "Guerreiro".to_string(),
+                partner_level: 350,
+            },
+            ServerMessage::TradeClosed {
+                outcome: TradeOutcome::Declined,
+            },
+            ServerMessage::TradeClosed {
+                outcome: TradeOutcome::Completed,
+            },
+        ];
+        for message in messages {
+            let packets = server_packets(&message);
+            assert_eq!(packets.len(), 1);
+            assert_eq!(server_message(&packets[0]).unwrap(), Some(message));
+        }
+        assert!(server_packets(&ServerMessage::Bestiary { unlocked: vec![] }).is_empty());
+        assert!(
+            server_packets(&ServerMessage::TradeRequested {
+                from_entity_id: 4,
+                name: "Elfa".to_string(),
+                level: 10,
+            })[0]
+                .encrypted
+        );
+    }
+}
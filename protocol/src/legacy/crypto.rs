@@ -0,0 +1,283 @@
+//! Ciphers of classic MU packets: SimpleModulus for C3/C4 and the XOR32
+//! table the client runs over every packet it sends.
+
+use super::LegacyError;
+
+/// Plain bytes per SimpleModulus block.
+const PLAIN_BLOCK_LEN: usize = 8;
+/// Encrypted bytes per SimpleModulus block.
+const CIPHER_BLOCK_LEN: usize = 11;
+/// Bits each of the four block values takes in the cipher text.
+const VALUE_BITS: usize = 18;
+const CHECKSUM_SEED: u8 = 0xF8;
+const SIZE_MASK: u8 = 0x3D;
+/// Mask of the `Enc*.dat`/`Dec*.dat` key files.
+const KEY_FILE_XOR: [u32; 4] = [0x3F08_A79B, 0xE25C_C287, 0x93D2_7AB9, 0x20DE_A7BF];
+const KEY_FILE_HEADER: u16 = 0x1112;
+
+/// One direction of the SimpleModulus cipher. The side that encrypts holds
+/// the encryption key and the other side the matching decryption key; the
+/// modulus and XOR values are the same on both.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SimpleModulusKeys {
+    pub modulus: [u32; 4],
+    pub key: [u32; 4],
+    pub xor: [u32; 4],
+}
+
+impl SimpleModulusKeys {
+    /// Season 6 client encryption (`Enc2.dat`): packets the client sends.
+    pub const CLIENT_ENCRYPT: Self = Self {
+        modulus: [128_079, 164_742, 70_235, 106_898],
+        key: [23_489, 11_911, 19_816, 13_647],
+        xor: [48_413, 46_165, 15_171, 37_433],
+    };
+    /// Season 6 server decryption (`Dec1.dat`): packets the client sent.
+    pub const SERVER_DECRYPT: Self = Self {
+        modulus: [128_079, 164_742, 70_235, 106_898],
+        key: [31_544, 2_047, 57_011, 10_183],
+        xor: [48_413, 46_165, 15_171, 37_433],
+    };
+    /// Season 6 server encryption (`Enc1.dat`): packets the server sends.
+    pub const SERVER_ENCRYPT: Self = Self {
+        modulus: [73_326, 109_989, 98_843, 171_058],
+        key: [13_169, 19_036, 35_482, 29_587],
+        xor: [62_004, 64_409, 35_374, 64_599],
+    };
+    /// Season 6 client decryption (`Dec2.dat`): packets the server sent.
+    pub const CLIENT_DECRYPT: Self = Self {
+        modulus: [73_326, 109_989, 98_843, 171_058],
+        key: [18_035, 30_340, 24_701, 11_141],
+        xor: [62_004, 64_409, 35_374, 64_599],
+    };
+
+    /// Reads an `Enc*.dat` or `Dec*.dat` file: a 6-byte header, then the
+    /// modulus, the key and the XOR values, each masked with a fixed table.
+    pub fn from_key_file(bytes: &[u8]) -> Result<Self, LegacyError> {
+        const BODY_LEN: usize = 3 * 16;
+        if bytes.len() != 6 + BODY_LEN
+            || u16::from_le_bytes([bytes[0], bytes[1]]) != KEY_FILE_HEADER
+            || u32::from_le_bytes([bytes[2], bytes[3], bytes[4], bytes[5]]) as usize != bytes.len()
+        {
+            return Err(LegacyError::InvalidKeyFile);
+        }
+
+        let section = |index: usize| {
+            let start = 6 + index * 16;
+            std::array::from_fn(|i| {
+                let at = start + i * 4;
+                u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+                    ^ KEY_FILE_XOR[i]
+            })
+        };
+        Ok(Self {
+            modulus: section(0),
+            key: section(1),
+            xor: section(2),
+        })
+    }
+
+    /// Encrypts `plain` in 8-byte blocks of 11 bytes each.
+    #[must_use]
+    pub fn encrypt(&self, plain: &[u8]) -> Vec<u8> {
+        let mut cipher =
+            Vec::with_capacity(plain.len().div_ceil(PLAIN_BLOCK_LEN) * CIPHER_BLOCK_LEN);
+        for chunk in plain.chunks(PLAIN_BLOCK_LEN) {
+            let mut block = [0u8; PLAIN_BLOCK_LEN];
+            block[..chunk.len()].copy_from_slice(chunk);
+            cipher.extend_from_slice(&self.encrypt_block(&block, chunk.len() as u8));
+        }
+        cipher
+    }
+
+    /// Decrypts what [`encrypt`](Self::encrypt) produced, checking the
+    /// checksum of every block.
+    pub fn decrypt(&self, cipher: &[u8]) -> Result<Vec<u8>, LegacyError> {
+        if cipher.is_empty() || !cipher.len().is_multiple_of(CIPHER_BLOCK_LEN) {
+            return Err(LegacyError::InvalidCipherLength(cipher.len()));
+        }
+        let mut plain = Vec::with_capacity(cipher.len() / CIPHER_BLOCK_LEN * PLAIN_BLOCK_LEN);
+        for block in cipher.chunks_exact(CIPHER_BLOCK_LEN) {
+            let (bytes, len) = self.decrypt_block(block.try_into().expect("11-byte chunk"))?;
+            plain.extend_from_slice(&bytes[..len]);
+        }
+        Ok(plain)
+    }
+
+    fn encrypt_block(&self, plain: &[u8; PLAIN_BLOCK_LEN], len: u8) -> [u8; CIPHER_BLOCK_LEN] {
+        let mut values = [0u32; 4];
+        let mut previous = 0u32;
+        for i in 0..4 {
+            let word = u32::from(u16::from_le_bytes([plain[i * 2], plain[i * 2 + 1]]));
+            // 32-bit wrapping, as in the original client.
+            values[i] = (self.xor[i] ^ word ^ previous).wrapping_mul(self.key[i]) % self.modulus[i];
+            previous = values[i] & 0xFFFF;
+        }
+        for i in 0..3 {
+            values[i] ^= self.xor[i] ^ (values[i + 1] & 0xFFFF);
+        }
+
+        let mut cipher = [0u8; CIPHER_BLOCK_LEN];
+        let mut bit = 0;
+        for value in values {
+            bit = write_bits(&mut cipher, bit, value, VALUE_BITS);
+        }
+        let checksum = plain.iter().fold(CHECKSUM_SEED, |sum, byte| sum ^ byte);
+        // Size byte first, then the checksum.
+        let trailer = (u32::from(checksum) << 8) | u32::from(checksum ^ len ^ SIZE_MASK);
+        write_bits(&mut cipher, bit, trailer, 16);
+        cipher
+    }
+
+    fn decrypt_block(
+        &self,
+        cipher: &[u8; CIPHER_BLOCK_LEN],
+    ) -> Result<([u8; PLAIN_BLOCK_LEN], usize), LegacyError> {
+        let mut values = [0u32; 4];
+        for (i, value) in values.iter_mut().enumerate() {
+            *value = read_bits(cipher, i * VALUE_BITS, VALUE_BITS);
+        }
+        for i in (0..3).rev() {
+            values[i] ^= self.xor[i] ^ (values[i + 1] & 0xFFFF);
+        }
+
+        let mut plain = [0u8; PLAIN_BLOCK_LEN];
+        let mut previous = 0u32;
+        for i in 0..4 {
+            let word =
+                (values[i].wrapping_mul(self.key[i]) % self.modulus[i]) ^ self.xor[i] ^ previous;
+            previous = values[i] & 0xFFFF;
+            plain[i * 2..i * 2 + 2].copy_from_slice(&(word as u16).to_le_bytes());
+        }
+
+        let trailer = read_bits(cipher, 4 * VALUE_BITS, 16);
+        let (sized, checksum) = (trailer as u8, (trailer >> 8) as u8);
+        if plain.iter().fold(CHECKSUM_SEED, |sum, byte| sum ^ byte) != checksum {
+            return Err(LegacyError::ChecksumMismatch);
+        }
+        let len = usize::from(sized ^ checksum ^ SIZE_MASK);
+        if len == 0 || len > PLAIN_BLOCK_LEN {
+            return Err(LegacyError::ChecksumMismatch);
+        }
+        Ok((plain, len))
+    }
+}
+
+/// Writes the low `len` bits of `value`, least significant byte first and
+/// each byte from its high bit, as the original cipher lays them out.
+fn write_bits(buffer: &mut [u8], start: usize, value: u32, len: usize) -> usize {
+    for offset in 0..len {
+        let source = lsb_first_bit(offset);
+        if (value >> source) & 1 != 0 {
+            let bit = start + offset;
+            buffer[bit / 8] |= 0x80 >> (bit % 8);
+        }
+    }
+    start + len
+}
+
+fn read_bits(buffer: &[u8], start: usize, len: usize) -> u32 {
+    (0..len).fold(0, |value, offset| {
+        let bit = start + offset;
+        let set = buffer[bit / 8] & (0x80 >> (bit % 8)) != 0;
+        value | (u32::from(set) << lsb_first_bit(offset))
+    })
+}
+
+/// Value bit stored at `offset` of a little-endian field read from the high
+/// bit of each byte: 7..0, then 15..8, then 17 and 16, the two bits past a
+/// `u16` that the 18-bit block values keep at the end of their third byte.
+const fn lsb_first_bit(offset: usize) -> usize {
+    let byte = offset / 8;
+    let within = offset % 8;
+    if byte == 2 {
+        17 - within
+    } else {
+        byte * 8 + 7 - within
+    }
+}
+
+/// Key the client XORs into every packet it sends, after the header and
+/// packet code, before any SimpleModulus encryption.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Xor32Key(pub [u8; 32]);
+
+impl Xor32Key {
+    pub const SEASON6: Self = Self([
+        0xAB, 0x11, 0xCD, 0xFE, 0x18, 0x23, 0xC5, 0xA3, 0xCA, 0x33, 0xC1, 0xCC, 0x66, 0x67, 0x21,
+        0xF3, 0x32, 0x12, 0x15, 0x35, 0x29, 0xFF, 0xFE, 0x1D, 0x44, 0xEF, 0xCD, 0x41, 0x26, 0x3C,
+        0x4E, 0x4D,
+    ]);
+
+    /// Scrambles a plain C1/C2 packet in place, as the client does.
+    pub fn encrypt(&self, packet: &mut [u8], header_len: usize) {
+        for i in header_len + 1..packet.len() {
+            packet[i] ^= packet[i - 1] ^ self.0[i % 32];
+        }
+    }
+
+    /// Undoes [`encrypt`](Self::encrypt), from the last byte backwards.
+    pub fn decrypt(&self, packet: &mut [u8], header_len: usize) {
+        for i in (header_len + 1..packet.len()).rev() {
+            packet[i] ^= packet[i - 1] ^ self.0[i % 32];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simple_modulus_round_trips_with_season6_keys() {
+        let plain: Vec<u8> = (0..21u8).map(|i| i.wrapping_mul(37)).collect();
+        for (encrypt, decrypt) in [
+            (
+                SimpleModulusKeys::CLIENT_ENCRYPT,
+                SimpleModulusKeys::SERVER_DECRYPT,
+            ),
+            (
+                SimpleModulusKeys::SERVER_ENCRYPT,
+                SimpleModulusKeys::CLIENT_DECRYPT,
+            ),
+        ] {
+            let cipher = encrypt.encrypt(&plain);
+            assert_eq!(cipher.len(), 3 * CIPHER_BLOCK_LEN);
+            assert_ne!(&cipher[..8], &plain[..8]);
+            assert_eq!(decrypt.decrypt(&cipher).unwrap(), plain);
+
+            let mut tampered = cipher.clone();
+            tampered[4] ^= 0x10;
+            assert!(decrypt.decrypt(&tampered).is_err());
+        }
+        assert!(matches!(
+            SimpleModulusKeys::SERVER_DECRYPT.decrypt(&[0; 10]),
+            Err(LegacyError::InvalidCipherLength(10))
+        ));
+    }
+
+    #[test]
+    fn key_files_unmask_their_sections() {
+        let keys = SimpleModulusKeys::SERVER_DECRYPT;
+        let mut file = KEY_FILE_HEADER.to_le_bytes().to_vec();
+        file.extend_from_slice(&54u32.to_le_bytes());
+        for section in [keys.modulus, keys.key, keys.xor] {
+            for (value, mask) in section.iter().zip(KEY_FILE_XOR) {
+                file.extend_from_slice(&(value ^ mask).to_le_bytes());
+            }
+        }
+        assert_eq!(SimpleModulusKeys::from_key_file(&file).unwrap(), keys);
+        assert!(SimpleModulusKeys::from_key_file(&file[..40]).is_err());
+    }
+
+    #[test]
+    fn xor32_leaves_the_header_and_code_alone() {
+        let plain = [0xC1, 0x08, 0x00, 0x41, 0x42, 0x43, 0x44, 0x45];
+        let mut packet = plain;
+        Xor32Key::SEASON6.encrypt(&mut packet, 2);
+        assert_eq!(packet[..3], plain[..3]);
+        assert_ne!(packet[3..], plain[3..]);
+        Xor32Key::SEASON6.decrypt(&mut packet, 2);
+        assert_eq!(packet, plain);
+    }
+}
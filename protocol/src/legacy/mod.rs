@@ -0,0 +1,262 @@
+//! Classic MU packets (C1/C2 plain, C3/C4 SimpleModulus encrypted), so
+//! original Season 6 clients can reach the server while they are migrated.
+//!
+//! [`LegacyCodec`] turns the bytes of one TCP connection into
+//! [`LegacyPacket`]s and back; [`convert`] maps those onto
+//! [`ClientMessage`](crate::ClientMessage) and
+//! [`ServerMessage`](crate::ServerMessage) where the v2 model has a
+//! counterpart.
+
+pub mod convert;
+mod crypto;
+mod packet;
+
+pub use crypto::{SimpleModulusKeys, Xor32Key};
+pub use packet::{LegacyPacket, PacketHeader, frame_len};
+
+/// Errors produced while reading or building classic packets.
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum LegacyError {
+    #[error("invalid packet header byte {0:#04x}")]
+    InvalidHeader(u8),
+
+    #[error("invalid packet size {0}")]
+    InvalidSize(usize),
+
+    #[error("packet ends before its fields")]
+    Truncated,
+
+    #[error("encrypted payload of {0} bytes is not a whole number of blocks")]
+    InvalidCipherLength(usize),
+
+    #[error("encrypted block checksum mismatch")]
+    ChecksumMismatch,
+
+    #[error("packet serial {actual}, expected {expected}")]
+    SerialMismatch { expected: u8, actual: u8 },
+
+    #[error("invalid SimpleModulus key file")]
+    InvalidKeyFile,
+
+    #[error("entity id {0} does not fit a classic packet")]
+    EntityIdOutOfRange(u32),
+}
+
+/// Which end of the connection a [`LegacyCodec`] runs on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LegacySide {
+    Client,
+    Server,
+}
+
+/// Per-connection state of the classic transport: the keys of each
+/// direction and the serial counters of encrypted packets.
+#[derive(Clone, Debug)]
+pub struct LegacyCodec {
+    side: LegacySide,
+    encrypt: SimpleModulusKeys,
+    decrypt: SimpleModulusKeys,
+    xor32: Xor32Key,
+    send_serial: u8,
+    /// Serial the next encrypted packet must carry; set by the first one.
+    receive_serial: Option<u8>,
+}
+
+impl LegacyCodec {
+    /// Codec of a game server talking to Season 6 clients.
+    pub fn server() -> Self {
+        Self::new(
+            LegacySide::Server,
+            SimpleModulusKeys::SERVER_ENCRYPT,
+            SimpleModulusKeys::SERVER_DECRYPT,
+            Xor32Key::SEASON6,
+        )
+    }
+
+    /// Codec of a Season 6 client, for tools and tests that stand in for one.
+    pub fn client() -> Self {
+        Self::new(
+            LegacySide::Client,
+            SimpleModulusKeys::CLIENT_ENCRYPT,
+            SimpleModulusKeys::CLIENT_DECRYPT,
+            Xor32Key::SEASON6,
+        )
+    }
+
+    /// Codec with the keys of a client build that ships its own.
+    pub fn new(
+        side: LegacySide,
+        encrypt: SimpleModulusKeys,
+        decrypt: SimpleModulusKeys,
+        xor32: Xor32Key,
+    ) -> Self {
+        Self {
+            side,
+            encrypt,
+            decrypt,
+            xor32,
+            send_serial: 0,
+            receive_serial: None,
+        }
+    }
+
+    /// Builds the bytes of one packet, encrypting C3/C4 packets and, on the
+    /// client, applying the XOR32 table.
+    pub fn encode(&mut self, packet: &LegacyPacket) -> Result<Vec<u8>, LegacyError> {
+        let header = plain_header(packet);
+        let mut bytes = packet.plain_bytes_with(header)?;
+        if self.side == LegacySide::Client {
+            self.xor32.encrypt(&mut bytes, header.size());
+        }
+        if !packet.encrypted {
+            return Ok(bytes);
+        }
+
+        let mut plain = Vec::with_capacity(bytes.len() - header.size() + 1);
+        plain.push(self.send_serial);
+        plain.extend_from_slice(&bytes[header.size()..]);
+        self.send_serial = self.send_serial.wrapping_add(1);
+        let cipher = self.encrypt.encrypt(&plain);
+
+        let header = header.with_encryption(true);
+        let len = header.size() + cipher.len();
+        let mut frame = Vec::with_capacity(len);
+        frame.push(header.byte());
+        match header.size() {
+            2 => frame.push(len as u8),
+            _ => frame.extend_from_slice(
+                &u16::try_from(len)
+                    .map_err(|_| LegacyError::InvalidSize(len))?
+                    .to_be_bytes(),
+            ),
+        }
+        frame.extend_from_slice(&cipher);
+        Ok(frame)
+    }
+
+    /// Reads one whole packet, as split off by [`frame_len`].
+    pub fn decode(&mut self, frame: &[u8]) -> Result<LegacyPacket, LegacyError> {
+        let len = frame_len(frame)?.ok_or(LegacyError::Truncated)?;
+        if len != frame.len() {
+            return Err(LegacyError::InvalidSize(len));
+        }
+        let header =
+            PacketHeader::from_byte(frame[0]).ok_or(LegacyError::InvalidHeader(frame[0]))?;
+
+        let mut bytes = if header.is_encrypted() {
+            let plain = self.decrypt.decrypt(&frame[header.size()..])?;
+            let (&serial, inner) = plain.split_first().ok_or(LegacyError::Truncated)?;
+            let expected = self.receive_serial.unwrap_or(serial);
+            if serial != expected {
+                return Err(LegacyError::SerialMismatch {
+                    expected,
+                    actual: serial,
+                });
+            }
+            self.receive_serial = Some(serial.wrapping_add(1));
+
+            // Rebuilt with a plain header of the same size, which the XOR32
+            // table of the sender was run over.
+            let plain_header = header.with_encryption(false);
+            let len = plain_header.size() + inner.len();
+            let mut bytes = Vec::with_capacity(len);
+            bytes.push(plain_header.byte());
+            match plain_header.size() {
+                2 => bytes.push(len as u8),
+                _ => bytes.extend_from_slice(&(len as u16).to_be_bytes()),
+            }
+            bytes.extend_from_slice(inner);
+            bytes
+        } else {
+            frame.to_vec()
+        };
+
+        if self.side == LegacySide::Server {
+            self.xor32.decrypt(&mut bytes, header.size());
+        }
+        let code = *bytes.get(header.size()).ok_or(LegacyError::Truncated)?;
+        Ok(LegacyPacket {
+            encrypted: header.is_encrypted(),
+            code,
+            body: bytes[header.size() + 1..].to_vec(),
+        })
+    }
+}
+
+/// Plain header of a packet: for C3/C4, the one whose size field still fits
+/// the packet once encrypted.
+fn plain_header(packet: &LegacyPacket) -> PacketHeader {
+    // Serial, code and body, in 8-byte blocks of 11.
+    let encrypted_len = (packet.body.len() + 2).div_ceil(8) * 11;
+    let content_len = if packet.encrypted {
+        encrypted_len
+    } else {
+        packet.body.len() + 1
+    };
+    if PacketHeader::C1.size() + content_len <= usize::from(u8::MAX) {
+        PacketHeader::C1
+    } else {
+        PacketHeader::C2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_packets_reach_the_server_intact() {
+        let mut client = LegacyCodec::client();
+        let mut server = LegacyCodec::server();
+
+        let login = LegacyPacket::new(0xF1, (0..60).collect()).encrypted();
+        let chat = LegacyPacket::new(0x00, b"Elfa\0\0\0\0\0\0oi".to_vec());
+        let large = LegacyPacket::new(0xF3, vec![9; 400]).encrypted();
+        for packet in [&login, &chat, &login, &large] {
+            let frame = client.encode(packet).unwrap();
+            assert_eq!(
+                PacketHeader::from_byte(frame[0]).unwrap().is_encrypted(),
+                packet.encrypted
+            );
+            if !packet.encrypted {
+                assert_ne!(frame[3..], packet.body[..]);
+            }
+            assert_eq!(frame_len(&frame).unwrap(), Some(frame.len()));
+            assert_eq!(&server.decode(&frame).unwrap(), packet);
+        }
+        assert_eq!(client.encode(&large).unwrap()[0], 0xC4);
+    }
+
+    #[test]
+    fn replayed_encrypted_packets_are_rejected() {
+        let mut client = LegacyCodec::client();
+        let mut server = LegacyCodec::server();
+        let packet = LegacyPacket::new(0x0E, vec![0; 11]).encrypted();
+
+        let first = client.encode(&packet).unwrap();
+        let second = client.encode(&packet).unwrap();
+        assert_ne!(first, second, "serial advances");
+        server.decode(&first).unwrap();
+        assert_eq!(
+            server.decode(&first),
+            Err(LegacyError::SerialMismatch {
+                expected: 1,
+                actual: 0
+            })
+        );
+        server.decode(&second).unwrap();
+    }
+
+    #[test]
+    fn server_packets_reach_the_client_intact() {
+        let mut server = LegacyCodec::server();
+        let mut client = LegacyCodec::client();
+        for packet in [
+            LegacyPacket::new(0x0D, b"\0\0\0\0\0\0\0\0\0\0Welcome".to_vec()),
+            LegacyPacket::new(0x36, b"Elfa\0\0\0\0\0\0".to_vec()).encrypted(),
+        ] {
+            let frame = server.encode(&packet).unwrap();
+            assert_eq!(client.decode(&frame).unwrap(), packet);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! Framing of classic MU packets.
+
+use super::LegacyError;
+
+/// First byte of a classic packet: its size field and whether the body is
+/// SimpleModulus encrypted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PacketHeader {
+    /// Plain, 1-byte size.
+    C1,
+    /// Plain, 2-byte big-endian size.
+    C2,
+    /// Encrypted, 1-byte size.
+    C3,
+    /// Encrypted, 2-byte big-endian size.
+    C4,
+}
+
+impl PacketHeader {
+    pub const fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0xC1 => Some(Self::C1),
+            0xC2 => Some(Self::C2),
+            0xC3 => Some(Self::C3),
+            0xC4 => Some(Self::C4),
+            _ => None,
+        }
+    }
+
+    pub const fn byte(self) -> u8 {
+        match self {
+            Self::C1 => 0xC1,
+            Self::C2 => 0xC2,
+            Self::C3 => 0xC3,
+            Self::C4 => 0xC4,
+        }
+    }
+
+    /// Header byte plus size field.
+    pub const fn size(self) -> usize {
+        match self {
+            Self::C1 | Self::C3 => 2,
+            Self::C2 | Self::C4 => 3,
+        }
+    }
+
+    pub const fn is_encrypted(self) -> bool {
+        matches!(self, Self::C3 | Self::C4)
+    }
+
+    /// Header of the same size field with or without encryption.
+    pub const fn with_encryption(self, encrypted: bool) -> Self {
+        match (self.size(), encrypted) {
+            (2, false) => Self::C1,
+            (2, true) => Self::C3,
+            (_, false) => Self::C2,
+            (_, true) => Self::C4,
+        }
+    }
+}
+
+/// Size of the first packet in `buffer`, or `None` until its header has
+/// arrived. Lets a TCP reader split the byte stream into packets.
+pub fn frame_len(buffer: &[u8]) -> Result<Option<usize>, LegacyError> {
+    let Some(&first) = buffer.first() else {
+        return Ok(None);
+    };
+    let header = PacketHeader::from_byte(first).ok_or(LegacyError::InvalidHeader(first))?;
+    if buffer.len() < header.size() {
+        return Ok(None);
+    }
+    let len = match header.size() {
+        2 => usize::from(buffer[1]),
+        _ => usize::from(u16::from_be_bytes([buffer[1], buffer[2]])),
+    };
+    if len <= header.size() {
+        return Err(LegacyError::InvalidSize(len));
+    }
+    Ok(Some(len))
+}
+
+/// Classic packet with its framing and encryption stripped. `encrypted`
+/// says whether it travels as C3/C4.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LegacyPacket {
+    pub encrypted: bool,
+    pub code: u8,
+    /// Everything after the packet code, sub-code included.
+    pub body: Vec<u8>,
+}
+
+impl LegacyPacket {
+    pub fn new(code: u8, body: Vec<u8>) -> Self {
+        Self {
+            encrypted: false,
+            code,
+            body,
+        }
+    }
+
+    /// Same packet sent as C3/C4.
+    #[must_use]
+    pub fn encrypted(mut self) -> Self {
+        self.encrypted = true;
+        self
+    }
+
+    /// First byte of the body, used by packets grouped under one code.
+    pub fn sub_code(&self) -> Option<u8> {
+        self.body.first().copied()
+    }
+
+    /// C1 or C2 bytes of the packet, the smallest header that fits.
+    pub fn to_plain_bytes(&self) -> Result<Vec<u8>, LegacyError> {
+        let header = if self.body.len() + 1 + PacketHeader::C1.size() <= usize::from(u8::MAX) {
+            PacketHeader::C1
+        } else {
+            PacketHeader::C2
+        };
+        self.plain_bytes_with(header)
+    }
+
+    /// Plain bytes of the packet under the size field of `header`.
+    pub(crate) fn plain_bytes_with(&self, header: PacketHeader) -> Result<Vec<u8>, LegacyError> {
+        let header = header.with_encryption(false);
+        let len = header.size() + 1 + self.body.len();
+        let mut bytes = Vec::with_capacity(len);
+        bytes.push(header.byte());
+        match header {
+            PacketHeader::C1 => {
+                bytes.push(u8::try_from(len).map_err(|_| LegacyError::InvalidSize(len))?)
+            }
+            _ => bytes.extend_from_slice(
+                &u16::try_from(len)
+                    .map_err(|_| LegacyError::InvalidSize(len))?
+                    .to_be_bytes(),
+            ),
+        }
+        bytes.push(self.code);
+        bytes.extend_from_slice(&self.body);
+        Ok(bytes)
+    }
+
+    /// Reads a C1/C2 packet whose size field matches `bytes`.
+    pub fn from_plain_bytes(bytes: &[u8]) -> Result<Self, LegacyError> {
+        let header = bytes
+            .first()
+            .map(|&first| PacketHeader::from_byte(first).ok_or(LegacyError::InvalidHeader(first)))
+            .transpose()?
+            .ok_or(LegacyError::Truncated)?;
+        if header.is_encrypted() {
+            return Err(LegacyError::InvalidHeader(header.byte()));
+        }
+        let len = frame_len(bytes)?.ok_or(LegacyError::Truncated)?;
+        if len != bytes.len() {
+            return Err(LegacyError::InvalidSize(len));
+        }
+        Ok(Self {
+            encrypted: false,
+            code: bytes[header.size()],
+            body: bytes[header.size() + 1..].to_vec(),
+        })
+    }
+}
+
+/// Fixed-size, NUL-padded text field (character names, messages).
+pub(crate) fn write_text(bytes: &mut Vec<u8>, text: &str, len: usize) {
+    let mut field = vec![0u8; len];
+    let text = text.as_bytes();
+    let copied = text.len().min(len);
+    field[..copied].copy_from_slice(&text[..copied]);
+    bytes.extend_from_slice(&field);
+}
+
+/// Text of a NUL-padded field; classic clients send Latin-1, which is read
+/// lossily.
+pub(crate) fn read_text(field: &[u8]) -> String {
+    let end = field
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_packets_pick_the_smallest_header() {
+        let short = LegacyPacket::new(0x0E, vec![0; 9]);
+        let bytes = short.to_plain_bytes().unwrap();
+        assert_eq!(bytes[..3], [0xC1, 12, 0x0E]);
+        assert_eq!(frame_len(&bytes).unwrap(), Some(12));
+        assert_eq!(LegacyPacket::from_plain_bytes(&bytes).unwrap(), short);
+
+        let long = LegacyPacket::new(0xF3, vec![7; 300]);
+        let bytes = long.to_plain_bytes().unwrap();
+        assert_eq!(bytes[..4], [0xC2, 0x01, 0x30, 0xF3]);
+        assert_eq!(frame_len(&bytes).unwrap(), Some(304));
+        assert_eq!(LegacyPacket::from_plain_bytes(&bytes).unwrap(), long);
+
+        assert_eq!(frame_len(&[0xC2, 0x01]).unwrap(), None);
+        assert!(matches!(
+            frame_len(&[0x17, 3, 0]),
+            Err(LegacyError::InvalidHeader(0x17))
+        ));
+        assert!(LegacyPacket::from_plain_bytes(&bytes[..100]).is_err());
+    }
+}
@@ -5,17 +5,41 @@
 
 pub mod channel;
 pub mod codec;
+pub mod latency;
+pub mod legacy;
 pub mod message;
+pub mod negotiation;
+pub mod recording;
 
-pub use channel::{DeliveryGuarantee, QuicChannel, TransportKind};
+pub use channel::{
+    DeliveryGuarantee, QuicChannel, SequenceStats, SequenceVerdict, SequencedReceiver,
+    SequencedSender, TransportKind, sequence_is_newer,
+};
 pub use codec::{
-    CodecError, CodecLimits, DecodedDatagramFrame, DecodedStreamFrame, STREAM_FRAME_HEADER_LEN,
-    WireCodec, preferred_channel,
+    CodecError, CodecLimits, DecodedDatagramFrame, DecodedStreamFrame, FRAGMENT_HEADER_LEN,
+    STREAM_FRAME_HEADER_LEN, StreamDecoder, WireCodec, preferred_channel,
 };
+pub use latency::{LatencyEstimator, PingSample};
 pub use message::{
-    ChatChannel, ChatPayload, ClientHello, ClientMessage, MapTransferDirective, MoveInput,
-    PROTOCOL_VERSION, PacketPayload, ProtocolVersion, RouteKey, ServerErrorKind, ServerMessage,
-    UseSkillInput, WireEnvelope, WirePacket,
+    AccountSettings, AnimationState, ArenaEvent, ArenaStage, ChatChannel, ChatPayload, ChatTarget,
+    ClientHello, ClientMessage, DamageEvent, DisconnectReason, DoorState, DoorStatus,
+    DoppelgangerStatus, DropEntry, DropTable, Emote, EntityKind, EntitySpawn, EntityState,
+    EquipSlot, EventCountdown, EventFailure, EventNotice, EventPhase, EventResult, EventReward,
+    FrameCompression, GensFaction, GensStatus, GuildRelation, GuildWarScore, ItemAction,
+    ItemFailure, ItemInstance, ItemLocation, ItemOptions, MAX_ZEN, MOVE_DIRECTION_OFFSETS,
+    MailEntry, MaintenanceNotice, MapTransferDirective, MirrorAlly, MonsterAffix, MonsterRank,
+    MoveInput, NoticeStyle, PROTOCOL_VERSION, PacketPayload, PersonalStore, ProtocolVersion,
+    QuestObjective, QuestStatus, RouteKey, SequenceEvent, SequenceGap, ServerError,
+    ServerErrorCategory, ServerErrorKind, ServerMessage, StatusEffect, StoreFailure, StoreItem,
+    StoreListing, SystemNotice, TradeFailure, TradeItem, TradeOffer, TradeOutcome,
+    UnknownServerErrorCode, UseSkillInput, WaypointPath, WhisperRequest, WhisperResult,
+    WireEnvelope, WirePacket, WorldDelta, WorldSnapshot,
+};
+pub use negotiation::{
+    MIN_PROTOCOL_VERSION, Negotiated, ProtocolCapabilities, UnsupportedVersion, negotiate,
+};
+pub use recording::{
+    IndexEntry, Playback, RecordedPacket, RecordingError, RecordingReader, RecordingWriter,
 };
 
 /// Returns the protocol crate version string.
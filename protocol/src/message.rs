@@ -1,12 +1,18 @@
 //! Versioned protocol messages for MU's QUIC transport.
 
+pub use common::{Appearance, EquipSlot, GuildId, GuildRelation};
 use serde::{Deserialize, Serialize};
 
-/// Current protocol version expected by client and server.
-pub const PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion::new(2, 0);
+use crate::negotiation::ProtocolCapabilities;
 
-/// Semantic protocol version used in every wire envelope.
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+/// Protocol version this build speaks; peers down to
+/// [`MIN_PROTOCOL_VERSION`](crate::negotiation::MIN_PROTOCOL_VERSION) are
+/// accepted.
+pub const PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion::new(3, 0);
+
+/// Semantic protocol version used in every wire envelope. Orders by major,
+/// then minor.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ProtocolVersion {
     pub major: u8,
     pub minor: u8,
@@ -21,6 +27,7 @@ impl ProtocolVersion {
 
 /// Route metadata for the world/entry/map shard handling this packet.
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct RouteKey {
     pub world_id: u16,
     pub entry_id: u16,
@@ -79,6 +86,8 @@ pub enum ChatChannel {
     Whisper,
     Party,
     Guild,
+    /// Members of the sender's Gens faction.
+    Gens,
     Global,
 }
 
@@ -90,6 +99,68 @@ pub struct ChatPayload {
     pub text: String,
 }
 
+impl ChatPayload {
+    /// Where the server delivers the line; `None` for a whisper without a
+    /// character name.
+    pub fn chat_target(&self) -> Option<ChatTarget> {
+        Some(match self.channel {
+            ChatChannel::Local => ChatTarget::Local,
+            ChatChannel::Whisper => ChatTarget::Character(self.target.clone()?),
+            ChatChannel::Party => ChatTarget::Party,
+            ChatChannel::Guild => ChatTarget::Guild,
+            ChatChannel::Gens => ChatTarget::Gens,
+            ChatChannel::Global => ChatTarget::Global,
+        })
+    }
+}
+
+/// Recipients of a chat line. Party, guild and Gens are the sender's own.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ChatTarget {
+    /// Characters in view on the sender's map.
+    Local,
+    /// One character, by name.
+    Character(String),
+    Party,
+    Guild,
+    Gens,
+    Global,
+}
+
+/// Private line to one character, wherever it is playing.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WhisperRequest {
+    pub target: String,
+    pub text: String,
+}
+
+/// Answer to a [`WhisperRequest`]; the line itself reaches the target as a
+/// whisper [`ServerMessage::Chat`] naming the sender.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum WhisperResult {
+    Delivered,
+    /// No character of that name is playing.
+    NotOnline,
+}
+
+/// How the client shows a [`SystemNotice`].
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum NoticeStyle {
+    /// Golden line in the middle of the screen, for server-wide news.
+    Golden,
+    /// Blue line in the chat box.
+    Chat,
+    /// Line in the chat box in the guild color, for the guild notice.
+    Guild,
+}
+
+/// Message from the server itself rather than a player.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SystemNotice {
+    pub style: NoticeStyle,
+    pub text: String,
+}
+
 /// First message sent by the client after transport session setup.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ClientHello {
@@ -97,6 +168,17 @@ pub struct ClientHello {
     pub auth_token: String,
     pub client_build: String,
     pub locale: String,
+    /// Optional features the client implements; the version it speaks is
+    /// the one its envelope carries.
+    pub capabilities: ProtocolCapabilities,
+}
+
+/// Compression of stream frame payloads, used when both sides have
+/// [`ProtocolCapabilities::LZ4_COMPRESSION`].
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum FrameCompression {
+    /// LZ4 block with the uncompressed size in front.
+    Lz4,
 }
 
 /// Player movement input.
@@ -126,16 +208,134 @@ pub enum ClientMessage {
     KeepAlive {
         client_time_ms: u64,
     },
+    /// Asks for a `Pong` to measure the round trip and the clock offset.
+    Ping {
+        /// Client clock, in ms since the Unix epoch.
+        client_sent_ms: u64,
+    },
     SelectCharacter {
         character_id: u64,
     },
     Move(MoveInput),
     UseSkill(UseSkillInput),
     Chat(ChatPayload),
+    Whisper(WhisperRequest),
     MapTransferAck {
         transfer_id: u64,
         route_token: String,
     },
+    RequestMailbox,
+    ClaimMail {
+        mail_id: u64,
+    },
+    RequestAccountSettings,
+    StoreAccountSettings(AccountSettings),
+    /// Turns the MU Helper (auto-hunt) on or off for the session.
+    SetHelperActive {
+        active: bool,
+    },
+    /// Registers the session's character with a Gens faction.
+    JoinGens {
+        faction: GensFaction,
+    },
+    /// Emote of the session's character, shown to the other players on the map.
+    Emote {
+        emote: Emote,
+    },
+    /// The session's character spoke to an NPC.
+    TalkToNpc {
+        npc_id: u16,
+    },
+    /// The session's character put on one of its items.
+    EquipItem {
+        serial: u64,
+    },
+    /// Moves an item of the session's character between inventory cells and
+    /// equipment slots; `from` is where the client last saw it.
+    MoveItem {
+        serial: u64,
+        from: ItemLocation,
+        to: ItemLocation,
+    },
+    /// Takes off the item worn in `slot` and puts it at inventory `cell`.
+    UnequipItem {
+        slot: EquipSlot,
+        cell: u8,
+    },
+    /// Drops an item on the ground at the given tile.
+    DropItem {
+        serial: u64,
+        x: u16,
+        y: u16,
+    },
+    /// Picks up the ground item with this entity id.
+    PickupItem {
+        entity_id: u32,
+    },
+    /// Drinks, reads or applies a consumable.
+    UseItem {
+        serial: u64,
+    },
+    /// Monster kinds the session's character unlocked in its bestiary.
+    RequestBestiary,
+    /// Drop preview of a monster kind already unlocked in the bestiary.
+    RequestDropTable {
+        monster_id: u16,
+    },
+    /// Asks the character with this entity id to trade.
+    RequestTrade {
+        target_entity_id: u32,
+    },
+    /// Answer to a `TradeRequested`.
+    RespondTrade {
+        accept: bool,
+    },
+    /// Puts an inventory item at `cell` of the trade window.
+    AddTradeItem {
+        serial: u64,
+        cell: u8,
+    },
+    RemoveTradeItem {
+        serial: u64,
+    },
+    /// Zen offered, replacing the previous amount.
+    SetTradeZen {
+        zen: u64,
+    },
+    /// Presses or releases OK on the session's offer.
+    LockTrade {
+        locked: bool,
+    },
+    /// Final confirmation, once both offers are locked.
+    ConfirmTrade,
+    CancelTrade,
+    /// Opens a personal store where the character stands.
+    OpenPersonalStore {
+        title: String,
+        listings: Vec<StoreListing>,
+    },
+    ClosePersonalStore,
+    BrowsePersonalStore {
+        owner_entity_id: u32,
+    },
+    /// Buys one item; `price_zen` is the price the buyer saw, so a store
+    /// that changed it turns the purchase down.
+    BuyFromPersonalStore {
+        owner_entity_id: u32,
+        serial: u64,
+        price_zen: u64,
+    },
+    /// Signs up for the next run of `event` in `bracket` with the ticket
+    /// item `ticket_serial`, which is consumed when the entry closes.
+    RegisterForEvent {
+        event: ArenaEvent,
+        bracket: u8,
+        ticket_serial: u64,
+    },
+    /// Withdraws a registration before the entry closes; the ticket is kept.
+    CancelEventRegistration {
+        event: ArenaEvent,
+    },
     Logout,
 }
 
@@ -154,12 +354,532 @@ pub struct EntityDelta {
     pub entity_id: u32,
     pub x: u16,
     pub y: u16,
+    /// Ground height under the tile in world units, from the map's
+    /// heightmap; 0 on maps the server has no heights for.
+    pub height: i16,
     pub hp: u16,
     pub state_flags: u16,
 }
 
+impl EntityDelta {
+    /// Entity belongs to a guild allied with the observer's guild.
+    pub const FLAG_GUILD_ALLY: u16 = 1 << 0;
+    /// Entity belongs to a guild hostile to the observer's guild.
+    pub const FLAG_GUILD_HOSTILE: u16 = 1 << 1;
+    /// Entity is a monster, not a player.
+    pub const FLAG_MONSTER: u16 = 1 << 2;
+    /// Entity is registered with the Duprian Gens.
+    pub const FLAG_GENS_DUPRIAN: u16 = 1 << 3;
+    /// Entity is registered with the Vanert Gens.
+    pub const FLAG_GENS_VANERT: u16 = 1 << 4;
+
+    pub fn guild_relation(&self) -> Option<GuildRelation> {
+        if self.state_flags & Self::FLAG_GUILD_HOSTILE != 0 {
+            Some(GuildRelation::Hostility)
+        } else if self.state_flags & Self::FLAG_GUILD_ALLY != 0 {
+            Some(GuildRelation::Alliance)
+        } else {
+            None
+        }
+    }
+
+    pub const fn guild_relation_flag(relation: GuildRelation) -> u16 {
+        match relation {
+            GuildRelation::Alliance => Self::FLAG_GUILD_ALLY,
+            GuildRelation::Hostility => Self::FLAG_GUILD_HOSTILE,
+        }
+    }
+
+    pub fn gens_faction(&self) -> Option<GensFaction> {
+        if self.state_flags & Self::FLAG_GENS_DUPRIAN != 0 {
+            Some(GensFaction::Duprian)
+        } else if self.state_flags & Self::FLAG_GENS_VANERT != 0 {
+            Some(GensFaction::Vanert)
+        } else {
+            None
+        }
+    }
+}
+
+/// What an entity is doing, for the client to pick its animation.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+pub enum AnimationState {
+    #[default]
+    Idle,
+    Walk,
+    Attack,
+    /// Casting the skill with this id.
+    Skill(u16),
+    Hit,
+    Sit,
+    Dead,
+}
+
+/// Kind of an entity, with the data id the client loads its model from.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum EntityKind {
+    Player,
+    Monster { monster_id: u16 },
+    Npc { npc_id: u16 },
+}
+
+/// State of one entity in the area of interest, resent whole when any of it
+/// changes.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EntityState {
+    pub entity_id: u32,
+    pub x: u16,
+    pub y: u16,
+    /// Same as [`EntityDelta::height`].
+    pub height: i16,
+    pub direction: u8,
+    pub hp: u16,
+    pub max_hp: u16,
+    pub animation: AnimationState,
+    /// Same flags as [`EntityDelta::state_flags`].
+    pub state_flags: u16,
+}
+
+/// Entity coming into the area of interest.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EntitySpawn {
+    pub kind: EntityKind,
+    pub name: String,
+    /// Class and visible equipment of players.
+    pub appearance: Option<Appearance>,
+    pub state: EntityState,
+}
+
+/// Every entity in the character's area of interest, sent on entering a map
+/// and whenever the client lost track of the deltas.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WorldSnapshot {
+    pub sequence: u32,
+    pub server_tick: u32,
+    /// Sorted by entity id.
+    pub entities: Vec<EntitySpawn>,
+}
+
+/// Changes to the area of interest since the snapshot or delta numbered
+/// `base_sequence`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WorldDelta {
+    pub sequence: u32,
+    pub base_sequence: u32,
+    pub server_tick: u32,
+    pub spawned: Vec<EntitySpawn>,
+    pub updated: Vec<EntityState>,
+    /// Entities that left the area of interest or the map.
+    pub despawned: Vec<u32>,
+}
+
+impl WorldSnapshot {
+    /// Brings the snapshot up to `delta`. A delta built on another sequence
+    /// leaves it untouched; the client then waits for a fresh snapshot.
+    pub fn apply(&mut self, delta: &WorldDelta) -> Result<(), SequenceGap> {
+        if delta.base_sequence != self.sequence {
+            return Err(SequenceGap {
+                expected: self.sequence,
+                found: delta.base_sequence,
+            });
+        }
+
+        self.entities
+            .retain(|entity| !delta.despawned.contains(&entity.state.entity_id));
+        for spawn in &delta.spawned {
+            match self.position(spawn.state.entity_id) {
+                Ok(index) => self.entities[index] = spawn.clone(),
+                Err(index) => self.entities.insert(index, spawn.clone()),
+            }
+        }
+        // Updates for entities the snapshot does not hold are dropped.
+        for state in &delta.updated {
+            if let Ok(index) = self.position(state.entity_id) {
+                self.entities[index].state = state.clone();
+            }
+        }
+        self.sequence = delta.sequence;
+        self.server_tick = delta.server_tick;
+        Ok(())
+    }
+
+    pub fn entity(&self, entity_id: u32) -> Option<&EntitySpawn> {
+        self.position(entity_id)
+            .ok()
+            .map(|index| &self.entities[index])
+    }
+
+    fn position(&self, entity_id: u32) -> Result<usize, usize> {
+        self.entities
+            .binary_search_by_key(&entity_id, |entity| entity.state.entity_id)
+    }
+}
+
+/// Delta that does not follow the snapshot it was applied to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SequenceGap {
+    pub expected: u32,
+    pub found: u32,
+}
+
+impl std::fmt::Display for SequenceGap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "world delta based on sequence {}, expected {}",
+            self.found, self.expected
+        )
+    }
+}
+
+impl std::error::Error for SequenceGap {}
+
+/// Gens faction a character fights for in Gens battle zones.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum GensFaction {
+    Duprian,
+    Vanert,
+}
+
+impl GensFaction {
+    pub const fn state_flag(self) -> u16 {
+        match self {
+            Self::Duprian => EntityDelta::FLAG_GENS_DUPRIAN,
+            Self::Vanert => EntityDelta::FLAG_GENS_VANERT,
+        }
+    }
+
+    pub const fn rival(self) -> Self {
+        match self {
+            Self::Duprian => Self::Vanert,
+            Self::Vanert => Self::Duprian,
+        }
+    }
+}
+
+/// Social animation a character plays, shown to the players around it.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Emote {
+    /// Sitting on the ground or a seat; lasts until the character moves.
+    Sit,
+    /// Drinking at a bar counter.
+    Drink,
+    Greeting,
+    Goodbye,
+    Clap,
+    Cheer,
+    Direction,
+    Gesture,
+    Cry,
+    Awkward,
+    See,
+    Win,
+    Smile,
+    Sleep,
+    Cold,
+    Again,
+    Respect,
+    Salute,
+    Provocation,
+    LookAround,
+}
+
+impl Emote {
+    pub const ALL: [Self; 20] = [
+        Self::Sit,
+        Self::Drink,
+        Self::Greeting,
+        Self::Goodbye,
+        Self::Clap,
+        Self::Cheer,
+        Self::Direction,
+        Self::Gesture,
+        Self::Cry,
+        Self::Awkward,
+        Self::See,
+        Self::Win,
+        Self::Smile,
+        Self::Sleep,
+        Self::Cold,
+        Self::Again,
+        Self::Respect,
+        Self::Salute,
+        Self::Provocation,
+        Self::LookAround,
+    ];
+}
+
+/// State of a door or gate the server owns.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum DoorState {
+    Open,
+    Closed,
+    Destroyed,
+}
+
+impl DoorState {
+    /// Only closed doors stop movement; destroyed gates leave a gap.
+    pub const fn blocks(self) -> bool {
+        matches!(self, Self::Closed)
+    }
+}
+
+/// A door of the current map.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DoorStatus {
+    pub door_id: u16,
+    pub state: DoorState,
+    /// Hit points left on destructible gates.
+    pub hp: Option<u32>,
+}
+
+impl DoorStatus {
+    /// Doors are targeted as entities from this id up.
+    pub const ENTITY_ID_BASE: u32 = 0x3000_0000;
+
+    pub const fn entity_id(door_id: u16) -> u32 {
+        Self::ENTITY_ID_BASE + door_id as u32
+    }
+
+    /// Door behind a target entity id, if it is one.
+    pub fn door_id(entity_id: u32) -> Option<u16> {
+        entity_id
+            .checked_sub(Self::ENTITY_ID_BASE)
+            .and_then(|offset| u16::try_from(offset).ok())
+    }
+}
+
+/// What a quest step asks the character to do.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum QuestObjective {
+    TalkToNpc { npc_id: u16 },
+    KillMonsters { monster_id: u16, count: u16 },
+    EquipItem,
+}
+
+/// Progress of a quest of the session's character, sent when the quest starts
+/// and whenever it advances.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QuestStatus {
+    pub quest_id: u16,
+    pub name: String,
+    /// Index of the current step.
+    pub step: u8,
+    pub steps: u8,
+    /// `None` once every step is done.
+    pub objective: Option<QuestObjective>,
+    /// Kills counted towards a `KillMonsters` objective.
+    pub progress: u16,
+}
+
+/// Item a monster can drop, as previewed in the bestiary.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DropEntry {
+    pub group: u8,
+    pub index: u16,
+    pub min_level: u8,
+    pub max_level: u8,
+    /// Chance per kill, in thousandths.
+    pub chance_per_mille: u16,
+}
+
+/// Drop table of one monster kind, sent in reply to `RequestDropTable`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DropTable {
+    pub monster_id: u16,
+    pub entries: Vec<DropEntry>,
+}
+
+/// Gens membership of the session's character, sent in reply to `JoinGens`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GensStatus {
+    /// `false` when the current world runs without Gens.
+    pub enabled: bool,
+    pub faction: Option<GensFaction>,
+    pub contribution: u32,
+}
+
+/// Scoreboard of a guild war; both guilds receive the same copy.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GuildWarScore {
+    pub war_id: u32,
+    pub guild_id: GuildId,
+    pub target_guild_id: GuildId,
+    /// Kills scored by `guild_id` on members of `target_guild_id`.
+    pub guild_kills: u32,
+    pub target_kills: u32,
+    pub ends_at_ms: u64,
+    /// Set on the last update, once the time window closed.
+    pub finished: bool,
+}
+
+/// Hit landed by a skill, sent to the map so the client can log it.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DamageEvent {
+    pub attacker_entity_id: u32,
+    pub target_entity_id: u32,
+    pub skill_id: u16,
+    pub damage: u32,
+    /// Target HP left after the hit; zero when it was killed.
+    pub remaining_hp: u32,
+    pub server_time_ms: u64,
+}
+
+/// Buff or debuff active on an entity.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StatusEffect {
+    /// ViewSkillState id of the effect (`common::BuffEffect`).
+    pub effect_id: u16,
+    /// Time left when the message was sent.
+    pub remaining_ms: u32,
+}
+
+/// Modifier rolled on an elite monster at spawn.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum MonsterAffix {
+    /// Hits harder.
+    Enraged,
+    /// Takes less damage.
+    Armored,
+    /// Moves faster.
+    Swift,
+    /// Heals from the damage it deals.
+    Vampiric,
+}
+
+impl MonsterAffix {
+    pub const ALL: [Self; 4] = [Self::Enraged, Self::Armored, Self::Swift, Self::Vampiric];
+}
+
+/// Tier of a monster that rolled affixes.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum MonsterRank {
+    Elite,
+    Boss,
+}
+
+impl MonsterRank {
+    /// One affix makes an elite, more make a boss; none is a regular monster.
+    pub fn of(affixes: &[MonsterAffix]) -> Option<Self> {
+        match affixes.len() {
+            0 => None,
+            1 => Some(Self::Elite),
+            _ => Some(Self::Boss),
+        }
+    }
+}
+
+/// Tile offsets for each movement direction code, clockwise starting at north (+y).
+pub const MOVE_DIRECTION_OFFSETS: [(i8, i8); 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+];
+
+/// Compact movement path: a start tile followed by one direction code per step.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WaypointPath {
+    pub entity_id: u32,
+    pub start_x: u16,
+    pub start_y: u16,
+    pub directions: Vec<u8>,
+}
+
+impl WaypointPath {
+    /// Builds the 8-way path between two tiles, moving diagonally first.
+    /// Returns `None` when the tiles are further apart than `max_steps`.
+    pub fn between(
+        entity_id: u32,
+        from: (u16, u16),
+        to: (u16, u16),
+        max_steps: usize,
+    ) -> Option<Self> {
+        let mut directions = Vec::new();
+        let (mut x, mut y) = (i32::from(from.0), i32::from(from.1));
+        let (target_x, target_y) = (i32::from(to.0), i32::from(to.1));
+
+        while (x, y) != (target_x, target_y) {
+            if directions.len() == max_steps {
+                return None;
+            }
+
+            let step = ((target_x - x).signum(), (target_y - y).signum());
+            let direction = MOVE_DIRECTION_OFFSETS
+                .iter()
+                .position(|&(dx, dy)| (i32::from(dx), i32::from(dy)) == step)?;
+            directions.push(direction as u8);
+            x += step.0;
+            y += step.1;
+        }
+
+        Some(Self {
+            entity_id,
+            start_x: from.0,
+            start_y: from.1,
+            directions,
+        })
+    }
+
+    /// Encodes a precomputed route (e.g. from a pathfinder) that excludes `from`.
+    /// Returns `None` when two consecutive tiles are not neighbours.
+    pub fn along(entity_id: u32, from: (u16, u16), route: &[(u16, u16)]) -> Option<Self> {
+        let mut directions = Vec::with_capacity(route.len());
+        let mut previous = from;
+        for &tile in route {
+            let step = (
+                i32::from(tile.0) - i32::from(previous.0),
+                i32::from(tile.1) - i32::from(previous.1),
+            );
+            let direction = MOVE_DIRECTION_OFFSETS
+                .iter()
+                .position(|&(dx, dy)| (i32::from(dx), i32::from(dy)) == step)?;
+            directions.push(direction as u8);
+            previous = tile;
+        }
+
+        Some(Self {
+            entity_id,
+            start_x: from.0,
+            start_y: from.1,
+            directions,
+        })
+    }
+
+    /// Tiles visited after each step, excluding the start tile.
+    pub fn tiles(&self) -> impl Iterator<Item = (u16, u16)> + '_ {
+        self.directions
+            .iter()
+            .scan((self.start_x, self.start_y), |tile, &direction| {
+                let (dx, dy) = MOVE_DIRECTION_OFFSETS[usize::from(direction & 7)];
+                tile.0 = tile.0.saturating_add_signed(i16::from(dx));
+                tile.1 = tile.1.saturating_add_signed(i16::from(dy));
+                Some(*tile)
+            })
+    }
+
+    pub fn end_tile(&self) -> (u16, u16) {
+        self.tiles().last().unwrap_or((self.start_x, self.start_y))
+    }
+}
+
 /// Routing directive used when the player must connect to another map instance.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct MapTransferDirective {
     pub transfer_id: u64,
     pub route: RouteKey,
@@ -169,17 +889,818 @@ pub struct MapTransferDirective {
     pub expires_at_ms: u64,
 }
 
-/// Error classes returned by the server.
+/// Item instance as exchanged between client and server.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ItemInstance {
+    /// Globally unique serial the server assigns when the item is created;
+    /// 0 until then.
+    #[serde(default)]
+    pub serial: u64,
+    pub group: u8,
+    pub index: u16,
+    pub level: u8,
+    pub quantity: u16,
+    pub options: ItemOptions,
+    /// Unix time in ms at which a time-limited item (seal, rental, event
+    /// buff) disappears; `None` for permanent items.
+    #[serde(default)]
+    pub expires_at_ms: Option<u64>,
+}
+
+/// Option slots rolled on an item.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ItemOptions {
+    /// Bitmask of excellent options, one bit per option.
+    pub excellent: u8,
+    pub luck: bool,
+    /// Additional damage/defense level; each level grants +4.
+    pub additional: u8,
+    /// Socket and harmony payloads, opaque until a season defines their layout.
+    pub sockets: Vec<Vec<u8>>,
+}
+
+/// Where an item sits on its character.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ItemLocation {
+    /// Top-left cell of the item in the main inventory, counted row by row.
+    Inventory {
+        cell: u8,
+    },
+    Equipment(EquipSlot),
+}
+
+impl ItemLocation {
+    pub const INVENTORY_COLUMNS: u8 = 8;
+    pub const INVENTORY_ROWS: u8 = 8;
+
+    /// Inventory cell at `column` and `row`, or `None` outside the grid.
+    #[must_use]
+    pub const fn inventory(column: u8, row: u8) -> Option<Self> {
+        if column < Self::INVENTORY_COLUMNS && row < Self::INVENTORY_ROWS {
+            Some(Self::Inventory {
+                cell: row * Self::INVENTORY_COLUMNS + column,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Whether an item `width` by `height` cells placed here stays inside
+    /// the inventory grid. Equipment slots take an item of any size.
+    #[must_use]
+    pub const fn fits(self, width: u8, height: u8) -> bool {
+        match self {
+            Self::Inventory { cell } => {
+                let column = cell % Self::INVENTORY_COLUMNS;
+                let row = cell / Self::INVENTORY_COLUMNS;
+                width > 0
+                    && height > 0
+                    && row < Self::INVENTORY_ROWS
+                    && column + width <= Self::INVENTORY_COLUMNS
+                    && row + height <= Self::INVENTORY_ROWS
+            }
+            Self::Equipment(_) => true,
+        }
+    }
+}
+
+/// Item request the server turned down, named in `ItemRejected`.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ItemAction {
+    Move,
+    Equip,
+    Unequip,
+    Drop,
+    Pickup,
+    Use,
+}
+
+/// Why an item request was turned down.
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ItemFailure {
+    /// The character does not hold the item, or it is not where the client
+    /// said.
+    NotHeld,
+    /// Cell outside the inventory, or too close to its edge for the item.
+    InvalidLocation,
+    /// Another item is in the way.
+    Occupied,
+    /// The item is not worn in that slot.
+    CannotEquip,
+    /// Level or stats too low for the item.
+    RequirementsNotMet,
+    /// Not a consumable.
+    NotUsable,
+    InventoryFull,
+    /// Ground item too far from the character.
+    OutOfReach,
+    /// Ground item already picked up or gone.
+    Gone,
+    /// Not allowed where the character is.
+    NotAllowed,
+}
+
+/// Most zen a character can hold, offer in a trade or ask for an item.
+pub const MAX_ZEN: u64 = 2_000_000_000;
+
+/// Item placed in a trade window.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TradeItem {
+    /// Top-left cell of the item in the trade grid, counted row by row.
+    pub cell: u8,
+    pub item: ItemInstance,
+}
+
+/// One side of a trade, resent whole whenever it changes.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TradeOffer {
+    pub items: Vec<TradeItem>,
+    pub zen: u64,
+    /// Set once the side pressed OK; any change to either offer clears both.
+    pub locked: bool,
+}
+
+impl TradeOffer {
+    pub const COLUMNS: u8 = 8;
+    pub const ROWS: u8 = 4;
+
+    /// Whether an item `width` by `height` cells placed at `cell` stays inside
+    /// the trade grid.
+    #[must_use]
+    pub const fn fits(cell: u8, width: u8, height: u8) -> bool {
+        let column = cell % Self::COLUMNS;
+        let row = cell / Self::COLUMNS;
+        width > 0
+            && height > 0
+            && row < Self::ROWS
+            && column + width <= Self::COLUMNS
+            && row + height <= Self::ROWS
+    }
+
+    /// Checks what the protocol alone can tell: zen within bounds, cells
+    /// inside the grid and each item and cell used once. Overlaps between
+    /// items need their sizes and are left to the server.
+    pub fn validate(&self) -> Result<(), TradeFailure> {
+        if self.zen > MAX_ZEN {
+            return Err(TradeFailure::ZenLimit);
+        }
+        for (index, placed) in self.items.iter().enumerate() {
+            if !Self::fits(placed.cell, 1, 1) {
+                return Err(TradeFailure::InvalidCell);
+            }
+            let earlier = &self.items[..index];
+            if earlier.iter().any(|other| other.cell == placed.cell) {
+                return Err(TradeFailure::Occupied);
+            }
+            if earlier
+                .iter()
+                .any(|other| other.item.serial == placed.item.serial)
+            {
+                return Err(TradeFailure::NotHeld);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Why a trade request or change was turned down.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum TradeFailure {
+    /// The other character is already trading, in a store or busy.
+    Busy,
+    /// The other character is too far away or on another map.
+    TooFar,
+    /// No trade is open, or the request names another partner.
+    NoTrade,
+    /// The character does not hold the item or it is already offered.
+    NotHeld,
+    /// Bound, expired or otherwise untradable item.
+    NotTradable,
+    /// Cell outside the trade grid, or too close to its edge for the item.
+    InvalidCell,
+    /// Another offered item is in the way.
+    Occupied,
+    NotEnoughZen,
+    /// Offer, or the zen it would leave either side with, above [`MAX_ZEN`].
+    ZenLimit,
+    /// The offer is locked; cancel the lock before changing it.
+    Locked,
+    /// Confirmed before both sides locked their offers.
+    NotLocked,
+    /// The items received do not fit in the inventory.
+    InventoryFull,
+    /// Not allowed where the character is.
+    NotAllowed,
+    /// The offer would take the account past its daily transfer cap.
+    TransferLimit,
+}
+
+/// How a trade window closed.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum TradeOutcome {
+    /// Both sides confirmed and the offers were swapped.
+    Completed,
+    /// One side cancelled, walked away or disconnected.
+    Cancelled,
+    /// The other character turned the request down.
+    Declined,
+    /// Both confirmed but the swap could not be done; nothing changed hands.
+    Failed(TradeFailure),
+}
+
+/// Item a personal store sells, as its owner lists it.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StoreListing {
+    pub serial: u64,
+    pub price_zen: u64,
+}
+
+/// Item on sale in a personal store, as a visitor sees it.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StoreItem {
+    pub item: ItemInstance,
+    pub price_zen: u64,
+}
+
+/// Personal store opened by a character, sent in reply to
+/// `BrowsePersonalStore`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PersonalStore {
+    pub owner_entity_id: u32,
+    pub owner_name: String,
+    pub title: String,
+    pub items: Vec<StoreItem>,
+}
+
+impl PersonalStore {
+    /// Longest title, in characters, shown above the owner's head.
+    pub const MAX_TITLE_CHARS: usize = 36;
+    /// One listing per cell of the store grid.
+    pub const MAX_LISTINGS: usize = 32;
+
+    /// Checks a store before it opens: a title that is neither blank nor too
+    /// long, at most [`MAX_LISTINGS`](Self::MAX_LISTINGS) items, each listed
+    /// once at a price from 1 to [`MAX_ZEN`].
+    pub fn validate_opening(title: &str, listings: &[StoreListing]) -> Result<(), StoreFailure> {
+        let title = title.trim();
+        if title.is_empty() || title.chars().count() > Self::MAX_TITLE_CHARS {
+            return Err(StoreFailure::InvalidTitle);
+        }
+        if listings.is_empty() || listings.len() > Self::MAX_LISTINGS {
+            return Err(StoreFailure::InvalidListing);
+        }
+        for (index, listing) in listings.iter().enumerate() {
+            if listing.price_zen == 0 || listing.price_zen > MAX_ZEN {
+                return Err(StoreFailure::InvalidPrice);
+            }
+            if listings[..index]
+                .iter()
+                .any(|other| other.serial == listing.serial)
+            {
+                return Err(StoreFailure::InvalidListing);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Why a personal store request was turned down.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum StoreFailure {
+    /// Blank title or one above [`PersonalStore::MAX_TITLE_CHARS`].
+    InvalidTitle,
+    /// No items, too many, or one listed twice.
+    InvalidListing,
+    /// Price of zero or above [`MAX_ZEN`].
+    InvalidPrice,
+    /// The owner does not hold a listed item, or it cannot be traded.
+    NotHeld,
+    /// The store closed or its owner left.
+    Closed,
+    /// The item was sold to someone else.
+    SoldOut,
+    /// The price differs from the one the buyer saw.
+    PriceChanged,
+    NotEnoughZen,
+    /// The sale would leave the owner above [`MAX_ZEN`].
+    ZenLimit,
+    InventoryFull,
+    /// Not allowed where the character is.
+    NotAllowed,
+}
+
+/// Reward waiting in the character mailbox until it is claimed.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MailEntry {
+    pub mail_id: u64,
+    pub source: String,
+    pub zen: u64,
+    pub items: Vec<ItemInstance>,
+    pub received_at_ms: u64,
+}
+
+/// Client settings file synced between the machines an account plays on.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AccountSettings {
+    /// Wall-clock time of the last local save; the newer copy wins a conflict.
+    pub updated_at_ms: u64,
+    /// `settings.yaml` contents.
+    pub yaml: String,
+}
+
+impl AccountSettings {
+    /// Largest settings file the server keeps, well under the stream frame limit.
+    pub const MAX_YAML_BYTES: usize = 32 * 1024;
+}
+
+/// Countdown broadcast to players on a world or map entering maintenance.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MaintenanceNotice {
+    pub world_id: u16,
+    /// `None` when the whole world is closing.
+    pub map_id: Option<u16>,
+    pub closes_at_ms: u64,
+    pub seconds_remaining: u32,
+    pub reason: String,
+}
+
+/// Scheduled event that runs through a fixed sequence of phases each cycle.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SequenceEvent {
+    /// Defense of the Crywolf statue.
+    Crywolf,
+    /// Maya and Nightmare in Kanturu Remain.
+    Kanturu,
+}
+
+/// Phase of a sequence event; each event uses its own subset.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum EventPhase {
+    /// Between two cycles.
+    Closed,
+    /// Announcing the coming cycle.
+    Notice,
+    /// Crywolf: elves sign contracts at the altars.
+    AltarContracts,
+    /// Crywolf: monster waves attack the statue.
+    StatueDefense,
+    /// Kanturu: Maya's hands must be killed.
+    MayaHands,
+    /// Kanturu: Nightmare must be killed.
+    Nightmare,
+    /// Kanturu: the Tower of Refinement is open after a win.
+    TowerOpen,
+}
+
+/// Phase change of a sequence event, broadcast on the maps it runs on.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EventNotice {
+    pub world_id: u16,
+    pub event: SequenceEvent,
+    pub cycle: u32,
+    pub phase: EventPhase,
+    pub ends_at_ms: u64,
+    /// Set when a cycle closes: whether the players won it.
+    pub outcome: Option<bool>,
+}
+
+/// Copy of a party member fighting beside the party in a Doppelganger run.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MirrorAlly {
+    pub entity_id: u32,
+    /// Party member the ally mirrors.
+    pub character_id: u64,
+    pub level: u16,
+}
+
+/// Progress of a Doppelganger run, sent to its party when a wave starts and
+/// when the run ends.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DoppelgangerStatus {
+    pub run_id: u32,
+    pub zone_map_id: u16,
+    pub wave: u8,
+    pub waves: u8,
+    pub wave_ends_at_ms: u64,
+    pub allies: Vec<MirrorAlly>,
+    /// Set when the run ends: whether the party survived every wave.
+    pub outcome: Option<bool>,
+}
+
+/// Instanced event entered with a ticket, run in level brackets that each
+/// get their own copy of the map.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ArenaEvent {
+    /// Entered with an Invisibility Cloak of the bracket's level.
+    BloodCastle,
+    /// Entered with a Devil's Invitation of the bracket's level.
+    DevilSquare,
+}
+
+impl ArenaEvent {
+    /// Highest level before master levels.
+    pub const MAX_LEVEL: u16 = 400;
+
+    /// Lowest level of each bracket but the last, which is for master-level
+    /// characters. Magic Gladiators and Dark Lords (`lowered`) move up
+    /// earlier.
+    const fn bracket_floors(self, lowered: bool) -> &'static [u16] {
+        match (self, lowered) {
+            (Self::BloodCastle, false) => &[15, 81, 131, 181, 231, 281, 331],
+            (Self::BloodCastle, true) => &[10, 61, 111, 161, 211, 261, 311],
+            (Self::DevilSquare, false) => &[15, 131, 181, 231, 281, 331],
+            (Self::DevilSquare, true) => &[10, 111, 161, 211, 261, 311],
+        }
+    }
+
+    /// Number of brackets, counted from 1; the last is the master bracket.
+    #[must_use]
+    pub const fn brackets(self) -> u8 {
+        self.bracket_floors(false).len() as u8 + 1
+    }
+
+    /// Bracket a character enters, or `None` below the first one.
+    #[must_use]
+    pub fn bracket_for(self, level: u16, master: bool, lowered: bool) -> Option<u8> {
+        if master {
+            return Some(self.brackets());
+        }
+        let floors = self.bracket_floors(lowered);
+        let level = level.min(Self::MAX_LEVEL);
+        let bracket = floors.iter().take_while(|&&floor| floor <= level).count();
+        (bracket > 0).then_some(bracket as u8)
+    }
+}
+
+/// Why an event registration was turned down.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum EventFailure {
+    /// The entry of the next run is not open.
+    NotOpen,
+    /// The bracket is not the character's.
+    WrongBracket,
+    /// The character does not hold the ticket.
+    NoTicket,
+    /// The ticket is for another event or bracket.
+    WrongTicket,
+    /// The bracket's copy is full.
+    Full,
+    /// Already registered for a run that has not ended.
+    AlreadyRegistered,
+    /// Player killers may not enter.
+    Outlaw,
+    /// Not allowed where the character is.
+    NotAllowed,
+}
+
+/// Stage of an arena event run a countdown leads to the end of.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ArenaStage {
+    /// Registrations are taken until the entry closes.
+    Entry,
+    /// Registered characters are inside, waiting for the fight to start.
+    Preparation,
+    /// The fight is on until the time runs out.
+    Battle,
+}
+
+/// Countdown of an arena event run, sent to the registered characters when a
+/// stage starts and again as it nears its end.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EventCountdown {
+    pub event: ArenaEvent,
+    pub bracket: u8,
+    pub stage: ArenaStage,
+    pub ends_at_ms: u64,
+    pub seconds_remaining: u32,
+}
+
+/// What a character earned in an arena event run.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EventReward {
+    pub zen: u64,
+    /// Items already placed in the inventory or, when it is full, mailed.
+    pub items: Vec<ItemInstance>,
+}
+
+/// Summary of an arena event run, sent to each character that took part
+/// when it ends.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EventResult {
+    pub event: ArenaEvent,
+    pub bracket: u8,
+    /// Whether the run's goal was met: the castle gate and the weapon for
+    /// Blood Castle, surviving the waves for Devil Square.
+    pub success: bool,
+    /// Place of the character by score, from 1.
+    pub rank: u16,
+    pub participants: u16,
+    pub score: u32,
+    pub experience: u64,
+    pub reward: EventReward,
+}
+
+/// Why the server ended a session.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum DisconnectReason {
+    /// Removed by an operator.
+    Kicked,
+    Banned,
+    /// The account logged in from another connection.
+    DuplicateLogin,
+    Maintenance,
+}
+
+impl DisconnectReason {
+    /// QUIC application close code the gateway closes the connection with, so
+    /// a client that misses the `Disconnect` message still learns the reason.
+    #[must_use]
+    pub const fn close_code(self) -> u32 {
+        match self {
+            Self::Kicked => 0x10,
+            Self::Banned => 0x11,
+            Self::DuplicateLogin => 0x12,
+            Self::Maintenance => 0x13,
+        }
+    }
+
+    #[must_use]
+    pub const fn from_close_code(code: u64) -> Option<Self> {
+        match code {
+            0x10 => Some(Self::Kicked),
+            0x11 => Some(Self::Banned),
+            0x12 => Some(Self::DuplicateLogin),
+            0x13 => Some(Self::Maintenance),
+            _ => None,
+        }
+    }
+}
+
+/// Broad class of a [`ServerErrorKind`]; the hundreds digit of its code.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerErrorCategory {
+    /// Login, sessions and character ownership (1xx).
+    Auth,
+    /// Requests the game rules turn down (2xx).
+    Validation,
+    /// Routes, instances, inventories or request budgets that are full or
+    /// unavailable (3xx).
+    Capacity,
+    /// Worlds or maps closed by an operator (4xx).
+    Maintenance,
+    /// Requests an unmodified client does not send (5xx).
+    AntiCheat,
+    /// Faults on the server side (9xx).
+    Internal,
+}
+
+/// Errors the game and connect servers return.
+///
+/// Travels as its [`code`](Self::code), which never changes once assigned, so
+/// adding or reordering kinds leaves the wire format and logged codes alone.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(into = "u16", try_from = "u16")]
 pub enum ServerErrorKind {
+    /// Session missing, expired or not authenticated.
     InvalidSession,
+    /// Wrong account name or password.
+    InvalidCredentials,
+    /// Token or session issued to another account or connection.
+    AccountMismatch,
+    /// Character does not exist or belongs to another account.
     CharacterNotFound,
+    /// Character is already playing from another session.
+    CharacterInUse,
+    /// The account's role does not grant the action.
+    PermissionDenied,
+    /// Hello from a protocol version the server no longer or not yet speaks.
+    UnsupportedVersion,
+    /// Request the game rules reject in the character's current state.
+    InvalidAction,
+    /// Request that is malformed or out of bounds.
+    InvalidRequest,
+    /// Request that needs a character on a map.
+    NotInMap,
+    /// Mail, record or resource that does not exist.
+    NotFound,
+    /// Chat sent while a moderator has the character muted.
+    ChatMuted,
+    /// Gate entered without the level or items it asks for.
+    GateRequirement,
+    /// No map server serves the requested route.
     RouteUnavailable,
+    /// The map server is up but has no instance to place the character in.
+    InstanceUnavailable,
+    /// Event map whose gate has not opened yet.
+    MapClosed,
+    /// Not enough free inventory slots.
+    InventoryFull,
+    /// Too many requests in a short time.
     RateLimited,
-    InvalidAction,
+    /// World or map closed for maintenance.
+    Maintenance,
+    /// Skill cast before its own cooldown or the global cooldown elapsed.
+    SkillCooldown,
+    /// Request about an item the character does not hold.
+    ItemNotHeld,
+    /// Signed payload that does not match the request it came with.
+    TamperedRequest,
     Internal,
 }
 
+impl ServerErrorKind {
+    pub const ALL: [Self; 23] = [
+        Self::InvalidSession,
+        Self::InvalidCredentials,
+        Self::AccountMismatch,
+        Self::CharacterNotFound,
+        Self::CharacterInUse,
+        Self::PermissionDenied,
+        Self::UnsupportedVersion,
+        Self::InvalidAction,
+        Self::InvalidRequest,
+        Self::NotInMap,
+        Self::NotFound,
+        Self::ChatMuted,
+        Self::GateRequirement,
+        Self::RouteUnavailable,
+        Self::InstanceUnavailable,
+        Self::MapClosed,
+        Self::InventoryFull,
+        Self::RateLimited,
+        Self::Maintenance,
+        Self::SkillCooldown,
+        Self::ItemNotHeld,
+        Self::TamperedRequest,
+        Self::Internal,
+    ];
+
+    /// Stable numeric code; the hundreds digit is the category.
+    #[must_use]
+    pub const fn code(self) -> u16 {
+        match self {
+            Self::InvalidSession => 100,
+            Self::InvalidCredentials => 101,
+            Self::AccountMismatch => 102,
+            Self::CharacterNotFound => 103,
+            Self::CharacterInUse => 104,
+            Self::PermissionDenied => 105,
+            Self::UnsupportedVersion => 106,
+            Self::InvalidAction => 200,
+            Self::InvalidRequest => 201,
+            Self::NotInMap => 202,
+            Self::NotFound => 203,
+            Self::ChatMuted => 204,
+            Self::GateRequirement => 205,
+            Self::RouteUnavailable => 300,
+            Self::InstanceUnavailable => 301,
+            Self::MapClosed => 302,
+            Self::InventoryFull => 303,
+            Self::RateLimited => 304,
+            Self::Maintenance => 400,
+            Self::SkillCooldown => 500,
+            Self::ItemNotHeld => 501,
+            Self::TamperedRequest => 502,
+            Self::Internal => 900,
+        }
+    }
+
+    #[must_use]
+    pub fn from_code(code: u16) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.code() == code)
+    }
+
+    #[must_use]
+    pub const fn category(self) -> ServerErrorCategory {
+        match self {
+            Self::InvalidSession
+            | Self::InvalidCredentials
+            | Self::AccountMismatch
+            | Self::CharacterNotFound
+            | Self::CharacterInUse
+            | Self::PermissionDenied
+            | Self::UnsupportedVersion => ServerErrorCategory::Auth,
+            Self::InvalidAction
+            | Self::InvalidRequest
+            | Self::NotInMap
+            | Self::NotFound
+            | Self::ChatMuted
+            | Self::GateRequirement => ServerErrorCategory::Validation,
+            Self::RouteUnavailable
+            | Self::InstanceUnavailable
+            | Self::MapClosed
+            | Self::InventoryFull
+            | Self::RateLimited => ServerErrorCategory::Capacity,
+            Self::Maintenance => ServerErrorCategory::Maintenance,
+            Self::SkillCooldown | Self::ItemNotHeld | Self::TamperedRequest => {
+                ServerErrorCategory::AntiCheat
+            }
+            Self::Internal => ServerErrorCategory::Internal,
+        }
+    }
+
+    /// Whether the same request can succeed later without the player doing
+    /// anything else: a full map or a closed gate can, a ban or a bad
+    /// password cannot.
+    #[must_use]
+    pub const fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            Self::CharacterInUse
+                | Self::RouteUnavailable
+                | Self::InstanceUnavailable
+                | Self::MapClosed
+                | Self::RateLimited
+                | Self::Maintenance
+                | Self::SkillCooldown
+                | Self::Internal
+        )
+    }
+}
+
+impl From<ServerErrorKind> for u16 {
+    fn from(kind: ServerErrorKind) -> Self {
+        kind.code()
+    }
+}
+
+impl TryFrom<u16> for ServerErrorKind {
+    type Error = UnknownServerErrorCode;
+
+    fn try_from(code: u16) -> Result<Self, Self::Error> {
+        Self::from_code(code).ok_or(UnknownServerErrorCode(code))
+    }
+}
+
+/// Code that matches no [`ServerErrorKind`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnknownServerErrorCode(pub u16);
+
+impl std::fmt::Display for UnknownServerErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown server error code {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownServerErrorCode {}
+
+/// Error payload of the game and connect servers.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ServerError {
+    /// Travels as its stable code.
+    pub kind: ServerErrorKind,
+    pub message: String,
+    /// Whether sending the same request again later can succeed.
+    pub retryable: bool,
+    /// How long to wait before retrying, when the server knows.
+    pub retry_after_ms: Option<u32>,
+}
+
+impl ServerError {
+    /// Error whose retryability is the kind's.
+    pub fn new(kind: ServerErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            retryable: kind.is_retryable(),
+            retry_after_ms: None,
+        }
+    }
+
+    /// Marks the error retryable after `delay_ms`.
+    #[must_use]
+    pub fn retry_after(mut self, delay_ms: u64) -> Self {
+        self.retryable = true;
+        self.retry_after_ms = Some(u32::try_from(delay_ms).unwrap_or(u32::MAX));
+        self
+    }
+
+    #[must_use]
+    pub const fn code(&self) -> u16 {
+        self.kind.code()
+    }
+}
+
+impl std::fmt::Display for ServerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "error {}: {}", self.code(), self.message)
+    }
+}
+
+impl std::error::Error for ServerError {}
+
 /// Messages produced by the game server.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ServerMessage {
@@ -188,6 +1709,11 @@ pub enum ServerMessage {
         heartbeat_interval_ms: u32,
         motd: String,
         characters: Vec<CharacterSummary>,
+        /// Version the session speaks: the older of the client's and the
+        /// server's.
+        protocol_version: ProtocolVersion,
+        /// Capabilities both sides have; nothing else is used this session.
+        capabilities: ProtocolCapabilities,
     },
     CharacterList {
         entries: Vec<CharacterSummary>,
@@ -202,15 +1728,173 @@ pub enum ServerMessage {
         server_tick: u32,
         entities: Vec<EntityDelta>,
     },
+    WorldSnapshot(WorldSnapshot),
+    WorldDelta(WorldDelta),
     Chat(ChatPayload),
+    /// Reply to `Whisper`.
+    WhisperResult {
+        target: String,
+        result: WhisperResult,
+    },
+    SystemNotice(SystemNotice),
+    EntityPath(WaypointPath),
+    /// Every status effect currently on the entity; an empty list clears them.
+    EntityStatus {
+        entity_id: u32,
+        effects: Vec<StatusEffect>,
+    },
     MapTransfer(MapTransferDirective),
+    /// Affixes of an elite or boss monster, sent when it spawns.
+    MonsterAffixes {
+        entity_id: u32,
+        affixes: Vec<MonsterAffix>,
+    },
+    /// Another player on the map played an emote.
+    EntityEmote {
+        entity_id: u32,
+        emote: Emote,
+    },
+    GuildWarScore(GuildWarScore),
+    DamageEvent(DamageEvent),
+    /// Every door of the map on entry, then each door whose state changed.
+    DoorStates {
+        doors: Vec<DoorStatus>,
+    },
+    /// Answer to `Ping` and `KeepAlive`; feeds a [`crate::LatencyEstimator`].
     Pong {
-        server_time_ms: u64,
+        /// Client time the ping carried, echoed back.
+        client_sent_ms: u64,
+        server_received_ms: u64,
+        server_sent_ms: u64,
+    },
+    Mailbox {
+        entries: Vec<MailEntry>,
+    },
+    MailClaimed {
+        mail_id: u64,
+    },
+    /// Time-limited items the server removed because their time ran out,
+    /// wherever they were held.
+    ItemsExpired {
+        items: Vec<ItemInstance>,
+    },
+    /// An item of the character is now at `to`, after a move, equip or
+    /// unequip.
+    ItemMoved {
+        serial: u64,
+        to: ItemLocation,
+    },
+    /// The item left the inventory and lies on the ground as `entity_id`.
+    ItemDropped {
+        serial: u64,
+        entity_id: u32,
+    },
+    ItemPickedUp {
+        item: ItemInstance,
+        cell: u8,
+    },
+    /// Quantity left after a use; 0 when the item is gone.
+    ItemUsed {
+        serial: u64,
+        remaining: u16,
+    },
+    ItemRejected {
+        action: ItemAction,
+        reason: ItemFailure,
+    },
+    Maintenance(MaintenanceNotice),
+    EventNotice(EventNotice),
+    DoppelgangerStatus(DoppelgangerStatus),
+    /// Copy kept by the server, `None` when the account never uploaded one.
+    AccountSettings {
+        settings: Option<AccountSettings>,
+    },
+    /// Reply to `SetHelperActive`; `allowed` is the policy of the current world.
+    HelperStatus {
+        allowed: bool,
+        active: bool,
     },
-    Error {
-        kind: ServerErrorKind,
+    GensStatus(GensStatus),
+    QuestStatus(QuestStatus),
+    /// Reply to `RequestBestiary`, sorted by monster id.
+    Bestiary {
+        unlocked: Vec<u16>,
+    },
+    /// The character killed a monster kind for the first time.
+    BestiaryUnlocked {
+        monster_id: u16,
+    },
+    DropTable(DropTable),
+    /// Another character asks the session's character to trade.
+    TradeRequested {
+        from_entity_id: u32,
+        name: String,
+        level: u16,
+    },
+    /// Both sides accepted; the trade window opens empty.
+    TradeOpened {
+        partner_entity_id: u32,
+        partner_name: String,
+        partner_level: u16,
+    },
+    /// Both offers, whenever either changes.
+    TradeUpdated {
+        own: TradeOffer,
+        partner: TradeOffer,
+    },
+    TradeRejected {
+        reason: TradeFailure,
+    },
+    TradeClosed {
+        outcome: TradeOutcome,
+    },
+    /// A character opened (`Some` title) or closed a personal store, shown
+    /// above its head on the map.
+    PersonalStoreSign {
+        entity_id: u32,
+        title: Option<String>,
+    },
+    PersonalStore(PersonalStore),
+    /// The buyer's copy: the item is now at inventory `cell`.
+    PersonalStorePurchased {
+        owner_entity_id: u32,
+        item: ItemInstance,
+        cell: u8,
+        price_zen: u64,
+    },
+    /// The owner's copy of a sale.
+    PersonalStoreSold {
+        serial: u64,
+        price_zen: u64,
+        buyer_name: String,
+    },
+    PersonalStoreRejected {
+        reason: StoreFailure,
+    },
+    /// Reply to `RegisterForEvent`; the run starts at `starts_at_ms`.
+    EventRegistered {
+        event: ArenaEvent,
+        bracket: u8,
+        starts_at_ms: u64,
+    },
+    EventRegistrationRejected {
+        event: ArenaEvent,
+        reason: EventFailure,
+    },
+    /// The entry closed and the registration's ticket was used up.
+    EventTicketConsumed {
+        event: ArenaEvent,
+        bracket: u8,
+        serial: u64,
+    },
+    EventCountdown(EventCountdown),
+    EventResult(EventResult),
+    /// Last message before the server closes the connection.
+    Disconnect {
+        reason: DisconnectReason,
         message: String,
     },
+    Error(ServerError),
 }
 
 /// Directional packet payload.
@@ -279,4 +1963,322 @@ mod tests {
         );
         assert_eq!(packet.version, PROTOCOL_VERSION);
     }
+
+    #[test]
+    fn hostility_flag_takes_precedence() {
+        let mut delta = EntityDelta {
+            entity_id: 1,
+            x: 0,
+            y: 0,
+            height: 0,
+            hp: 100,
+            state_flags: 0,
+        };
+        assert_eq!(delta.guild_relation(), None);
+
+        delta.state_flags = EntityDelta::guild_relation_flag(GuildRelation::Alliance);
+        assert_eq!(delta.guild_relation(), Some(GuildRelation::Alliance));
+
+        delta.state_flags |= EntityDelta::guild_relation_flag(GuildRelation::Hostility);
+        assert_eq!(delta.guild_relation(), Some(GuildRelation::Hostility));
+    }
+
+    #[test]
+    fn chat_channels_resolve_to_their_targets() {
+        let chat = |channel, target: Option<&str>| ChatPayload {
+            channel,
+            target: target.map(str::to_string),
+            text: "oi".into(),
+        };
+        assert_eq!(
+            chat(ChatChannel::Whisper, Some("Elfa")).chat_target(),
+            Some(ChatTarget::Character("Elfa".into()))
+        );
+        assert_eq!(chat(ChatChannel::Whisper, None).chat_target(), None);
+        assert_eq!(
+            chat(ChatChannel::Gens, None).chat_target(),
+            Some(ChatTarget::Gens)
+        );
+        assert_eq!(
+            chat(ChatChannel::Local, Some("ignored")).chat_target(),
+            Some(ChatTarget::Local)
+        );
+    }
+
+    fn spawn(entity_id: u32) -> EntitySpawn {
+        EntitySpawn {
+            kind: EntityKind::Monster { monster_id: 3 },
+            name: "Spider".into(),
+            appearance: None,
+            state: EntityState {
+                entity_id,
+                x: 130,
+                y: 120,
+                height: 180,
+                direction: 0,
+                hp: 30,
+                max_hp: 30,
+                animation: AnimationState::Idle,
+                state_flags: EntityDelta::FLAG_MONSTER,
+            },
+        }
+    }
+
+    #[test]
+    fn world_deltas_apply_in_sequence() {
+        let mut world = WorldSnapshot {
+            sequence: 7,
+            server_tick: 100,
+            entities: vec![spawn(1), spawn(5)],
+        };
+        let mut hit = spawn(5).state;
+        hit.hp = 12;
+        hit.animation = AnimationState::Hit;
+        let delta = WorldDelta {
+            sequence: 8,
+            base_sequence: 7,
+            server_tick: 101,
+            spawned: vec![spawn(3)],
+            updated: vec![hit.clone(), spawn(9).state],
+            despawned: vec![1],
+        };
+
+        world.apply(&delta).unwrap();
+        let ids: Vec<u32> = world.entities.iter().map(|e| e.state.entity_id).collect();
+        assert_eq!(ids, vec![3, 5]);
+        assert_eq!(world.entity(5).map(|e| &e.state), Some(&hit));
+        assert_eq!((world.sequence, world.server_tick), (8, 101));
+
+        let stale = world.clone();
+        assert_eq!(
+            world.apply(&delta),
+            Err(SequenceGap {
+                expected: 8,
+                found: 7
+            })
+        );
+        assert_eq!(world, stale);
+    }
+
+    #[test]
+    fn waypoint_path_walks_diagonal_then_straight() {
+        let path = WaypointPath::between(3, (10, 10), (13, 11), 15).expect("path");
+
+        assert_eq!(path.directions, vec![1, 2, 2]);
+        assert_eq!(
+            path.tiles().collect::<Vec<_>>(),
+            vec![(11, 11), (12, 11), (13, 11)]
+        );
+        assert_eq!(path.end_tile(), (13, 11));
+        assert!(WaypointPath::between(3, (0, 0), (20, 0), 15).is_none());
+    }
+
+    #[test]
+    fn waypoint_path_encodes_precomputed_route() {
+        let route = [(11, 10), (12, 11), (12, 12)];
+        let path = WaypointPath::along(4, (10, 10), &route).expect("path");
+
+        assert_eq!(path.tiles().collect::<Vec<_>>(), route);
+        assert!(WaypointPath::along(4, (10, 10), &[(12, 10)]).is_none());
+    }
+
+    #[test]
+    fn door_entity_ids_map_back_to_doors() {
+        let entity_id = DoorStatus::entity_id(7);
+        assert_eq!(DoorStatus::door_id(entity_id), Some(7));
+        assert_eq!(DoorStatus::door_id(42), None);
+        assert_eq!(
+            DoorStatus::door_id(DoorStatus::ENTITY_ID_BASE + 70_000),
+            None
+        );
+    }
+
+    #[test]
+    fn disconnect_reasons_survive_the_close_code() {
+        for reason in [
+            DisconnectReason::Kicked,
+            DisconnectReason::Banned,
+            DisconnectReason::DuplicateLogin,
+            DisconnectReason::Maintenance,
+        ] {
+            let code = u64::from(reason.close_code());
+            assert_eq!(DisconnectReason::from_close_code(code), Some(reason));
+        }
+        // 0 is the plain shutdown close.
+        assert_eq!(DisconnectReason::from_close_code(0), None);
+    }
+
+    #[test]
+    fn server_error_codes_are_unique_and_match_categories() {
+        let mut codes: Vec<u16> = ServerErrorKind::ALL
+            .iter()
+            .map(|kind| kind.code())
+            .collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), ServerErrorKind::ALL.len());
+
+        for kind in ServerErrorKind::ALL {
+            assert_eq!(ServerErrorKind::from_code(kind.code()), Some(kind));
+            let expected = match kind.category() {
+                ServerErrorCategory::Auth => 1,
+                ServerErrorCategory::Validation => 2,
+                ServerErrorCategory::Capacity => 3,
+                ServerErrorCategory::Maintenance => 4,
+                ServerErrorCategory::AntiCheat => 5,
+                ServerErrorCategory::Internal => 9,
+            };
+            assert_eq!(kind.code() / 100, expected, "{kind:?}");
+        }
+        assert_eq!(ServerErrorKind::from_code(0), None);
+    }
+
+    #[test]
+    fn server_error_kind_travels_as_its_code() {
+        let encoded = postcard::to_stdvec(&ServerErrorKind::Maintenance).unwrap();
+        assert_eq!(encoded, postcard::to_stdvec(&400u16).unwrap());
+        let decoded: ServerErrorKind = postcard::from_bytes(&encoded).unwrap();
+        assert_eq!(decoded, ServerErrorKind::Maintenance);
+        assert!(
+            postcard::from_bytes::<ServerErrorKind>(&postcard::to_stdvec(&7u16).unwrap()).is_err()
+        );
+    }
+
+    #[test]
+    fn server_errors_say_whether_to_retry() {
+        let banned = ServerError::new(ServerErrorKind::PermissionDenied, "banned");
+        assert!(!banned.retryable);
+        assert_eq!(banned.retry_after_ms, None);
+
+        let full = ServerError::new(ServerErrorKind::InstanceUnavailable, "map full");
+        assert!(full.retryable);
+
+        let cooldown =
+            ServerError::new(ServerErrorKind::SkillCooldown, "cooldown").retry_after(1_500);
+        assert_eq!(cooldown.retry_after_ms, Some(1_500));
+        assert_eq!(
+            ServerError::new(ServerErrorKind::InvalidAction, "busy")
+                .retry_after(u64::MAX)
+                .retry_after_ms,
+            Some(u32::MAX)
+        );
+
+        let message = ServerMessage::Error(cooldown);
+        let encoded = postcard::to_stdvec(&message).unwrap();
+        assert_eq!(
+            postcard::from_bytes::<ServerMessage>(&encoded).unwrap(),
+            message
+        );
+    }
+
+    #[test]
+    fn inventory_locations_keep_items_inside_the_grid() {
+        assert_eq!(
+            ItemLocation::inventory(3, 2),
+            Some(ItemLocation::Inventory { cell: 19 })
+        );
+        assert_eq!(ItemLocation::inventory(8, 0), None);
+
+        let corner = ItemLocation::inventory(6, 5).unwrap();
+        assert!(corner.fits(2, 3));
+        assert!(!corner.fits(3, 1), "past the right edge");
+        assert!(!corner.fits(1, 4), "past the bottom");
+        assert!(!ItemLocation::Inventory { cell: 64 }.fits(1, 1));
+        assert!(ItemLocation::Equipment(EquipSlot::Wings).fits(4, 3));
+
+        let moved = ServerMessage::ItemMoved {
+            serial: 7,
+            to: ItemLocation::Equipment(EquipSlot::Armor),
+        };
+        let encoded = postcard::to_stdvec(&moved).unwrap();
+        assert_eq!(
+            postcard::from_bytes::<ServerMessage>(&encoded).unwrap(),
+            moved
+        );
+    }
+
+    #[test]
+    fn trade_offers_stay_inside_the_grid() {
+        let placed = |cell, serial| TradeItem {
+            cell,
+            item: ItemInstance {
+                serial,
+                group: 14,
+                index: 13,
+                level: 0,
+                quantity: 1,
+                options: ItemOptions::default(),
+                expires_at_ms: None,
+            },
+        };
+        assert!(TradeOffer::fits(6, 2, 4));
+        assert!(!TradeOffer::fits(7, 2, 1), "past the right edge");
+        assert!(!TradeOffer::fits(8, 1, 4), "past the bottom");
+
+        let mut offer = TradeOffer {
+            items: vec![placed(0, 1), placed(9, 2)],
+            zen: MAX_ZEN,
+            locked: false,
+        };
+        assert_eq!(offer.validate(), Ok(()));
+        offer.zen += 1;
+        assert_eq!(offer.validate(), Err(TradeFailure::ZenLimit));
+        offer.zen = 0;
+        offer.items.push(placed(9, 3));
+        assert_eq!(offer.validate(), Err(TradeFailure::Occupied));
+        offer.items[2] = placed(10, 1);
+        assert_eq!(offer.validate(), Err(TradeFailure::NotHeld));
+        offer.items[2] = placed(32, 3);
+        assert_eq!(offer.validate(), Err(TradeFailure::InvalidCell));
+    }
+
+    #[test]
+    fn store_openings_are_validated() {
+        let listing = |serial, price_zen| StoreListing { serial, price_zen };
+        let listings = [listing(1, 500_000), listing(2, MAX_ZEN)];
+        assert_eq!(
+            PersonalStore::validate_opening("Jewels cheap", &listings),
+            Ok(())
+        );
+        assert_eq!(
+            PersonalStore::validate_opening("   ", &listings),
+            Err(StoreFailure::InvalidTitle)
+        );
+        assert_eq!(
+            PersonalStore::validate_opening(&"x".repeat(37), &listings),
+            Err(StoreFailure::InvalidTitle)
+        );
+        assert_eq!(
+            PersonalStore::validate_opening("Jewels", &[]),
+            Err(StoreFailure::InvalidListing)
+        );
+        assert_eq!(
+            PersonalStore::validate_opening("Jewels", &[listing(1, 10), listing(1, 20)]),
+            Err(StoreFailure::InvalidListing)
+        );
+        assert_eq!(
+            PersonalStore::validate_opening("Jewels", &[listing(1, 0)]),
+            Err(StoreFailure::InvalidPrice)
+        );
+    }
+
+    #[test]
+    fn arena_brackets_follow_level_and_class() {
+        let castle = ArenaEvent::BloodCastle;
+        assert_eq!(castle.brackets(), 8);
+        assert_eq!(castle.bracket_for(14, false, false), None);
+        assert_eq!(castle.bracket_for(15, false, false), Some(1));
+        assert_eq!(castle.bracket_for(80, false, false), Some(1));
+        assert_eq!(castle.bracket_for(81, false, false), Some(2));
+        assert_eq!(castle.bracket_for(70, false, true), Some(2));
+        assert_eq!(castle.bracket_for(400, false, false), Some(7));
+        assert_eq!(castle.bracket_for(400, true, false), Some(8));
+
+        let square = ArenaEvent::DevilSquare;
+        assert_eq!(square.brackets(), 7);
+        assert_eq!(square.bracket_for(130, false, false), Some(1));
+        assert_eq!(square.bracket_for(130, false, true), Some(2));
+        assert_eq!(square.bracket_for(9, false, true), None);
+        assert_eq!(square.bracket_for(u16::MAX, false, false), Some(6));
+    }
 }
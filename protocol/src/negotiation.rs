@@ -0,0 +1,171 @@
+//! Protocol version and optional features agreed in the hello.
+//!
+//! The client stamps its hello with its [`PROTOCOL_VERSION`] and lists the
+//! [`ProtocolCapabilities`] it implements. A server accepts any version of
+//! its own major at or above [`MIN_PROTOCOL_VERSION`], answers with the older
+//! of the two versions and with the capabilities both sides have, and
+//! neither side uses anything outside them for the rest of the session.
+//!
+//! Minor versions stay wire compatible: they only append messages and enum
+//! variants, which a peer never sends unless the agreed version has them.
+//! Anything else takes a new major version.
+
+use serde::{Deserialize, Serialize};
+
+use crate::message::{FrameCompression, PROTOCOL_VERSION, ProtocolVersion};
+
+/// Oldest version this build still talks to.
+pub const MIN_PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion::new(3, 0);
+
+/// Optional features of a session, as bit flags. Bits this build does not
+/// know are kept when decoded and dropped by [`negotiate`].
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct ProtocolCapabilities(u32);
+
+impl ProtocolCapabilities {
+    pub const NONE: Self = Self(0);
+    /// Stream frames over the compression threshold are LZ4 compressed.
+    pub const LZ4_COMPRESSION: Self = Self(1 << 0);
+    /// Area of interest sent as `WorldSnapshot` plus `WorldDelta` rather than
+    /// whole entity lists.
+    pub const DELTA_SNAPSHOTS: Self = Self(1 << 1);
+    /// Everything this build implements.
+    pub const SUPPORTED: Self = Self(Self::LZ4_COMPRESSION.0 | Self::DELTA_SNAPSHOTS.0);
+
+    #[must_use]
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    #[must_use]
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    #[must_use]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    #[must_use]
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    #[must_use]
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    #[must_use]
+    pub const fn without(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// Compression of stream frames these capabilities call for.
+    #[must_use]
+    pub const fn compression(self) -> Option<FrameCompression> {
+        if self.contains(Self::LZ4_COMPRESSION) {
+            Some(FrameCompression::Lz4)
+        } else {
+            None
+        }
+    }
+}
+
+/// What a session runs on, as confirmed in `HelloAck`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Negotiated {
+    pub version: ProtocolVersion,
+    pub capabilities: ProtocolCapabilities,
+}
+
+/// Hello from a version this build does not talk to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "protocol {}.{} is not supported (accepted {}.{} to {}.x)",
+    offered.major,
+    offered.minor,
+    MIN_PROTOCOL_VERSION.major,
+    MIN_PROTOCOL_VERSION.minor,
+    PROTOCOL_VERSION.major
+)]
+pub struct UnsupportedVersion {
+    pub offered: ProtocolVersion,
+}
+
+/// Whether packets stamped with `version` can be read by this build.
+#[must_use]
+pub fn is_compatible(version: ProtocolVersion) -> bool {
+    version.major == PROTOCOL_VERSION.major && version >= MIN_PROTOCOL_VERSION
+}
+
+/// Settles a session from the peer's offer and the capabilities `local`
+/// side is willing to use.
+pub fn negotiate(
+    offered_version: ProtocolVersion,
+    offered: ProtocolCapabilities,
+    local: ProtocolCapabilities,
+) -> Result<Negotiated, UnsupportedVersion> {
+    if !is_compatible(offered_version) {
+        return Err(UnsupportedVersion {
+            offered: offered_version,
+        });
+    }
+    Ok(Negotiated {
+        version: offered_version.min(PROTOCOL_VERSION),
+        capabilities: offered
+            .intersection(local)
+            .intersection(ProtocolCapabilities::SUPPORTED),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sessions_settle_on_the_older_version_and_shared_capabilities() {
+        let newer = ProtocolVersion::new(PROTOCOL_VERSION.major, PROTOCOL_VERSION.minor + 1);
+        let future_bit = ProtocolCapabilities::from_bits(1 << 31);
+        let session = negotiate(
+            newer,
+            ProtocolCapabilities::SUPPORTED.union(future_bit),
+            ProtocolCapabilities::SUPPORTED.without(ProtocolCapabilities::LZ4_COMPRESSION),
+        )
+        .unwrap();
+        assert_eq!(session.version, PROTOCOL_VERSION);
+        assert_eq!(session.capabilities, ProtocolCapabilities::DELTA_SNAPSHOTS);
+        assert_eq!(session.capabilities.compression(), None);
+
+        let session = negotiate(
+            MIN_PROTOCOL_VERSION,
+            ProtocolCapabilities::LZ4_COMPRESSION,
+            ProtocolCapabilities::SUPPORTED,
+        )
+        .unwrap();
+        assert_eq!(session.version, MIN_PROTOCOL_VERSION);
+        assert_eq!(
+            session.capabilities.compression(),
+            Some(FrameCompression::Lz4)
+        );
+    }
+
+    #[test]
+    fn other_majors_are_refused() {
+        let next_major = ProtocolVersion::new(PROTOCOL_VERSION.major + 1, 0);
+        assert!(!is_compatible(next_major));
+        assert_eq!(
+            negotiate(
+                next_major,
+                ProtocolCapabilities::NONE,
+                ProtocolCapabilities::SUPPORTED
+            ),
+            Err(UnsupportedVersion {
+                offered: next_major
+            })
+        );
+        assert!(!is_compatible(ProtocolVersion::new(2, 0)));
+    }
+}
@@ -0,0 +1,575 @@
+//! Recordings of a session: the packets it exchanged, with their timing, to
+//! reproduce desyncs and to drive integration tests.
+//!
+//! A file starts with `MUWR` and a format version byte, followed by one
+//! record per packet: a little-endian `u32` length, then the postcard
+//! encoded [`RecordedPacket`] (so timestamps and ids are varints). Once the
+//! recording is finished come the index and a 12 byte footer: the offset of
+//! the index (LE `u64`) and `MUWI`. The index lists the recording length and
+//! one [`IndexEntry`] per [`INDEX_INTERVAL_MS`] of recording, which is enough
+//! to seek without reading the records before.
+//!
+//! A recording cut short (the process crashed before
+//! [`RecordingWriter::finish`]) has no footer; [`RecordingReader`] then
+//! rebuilds the index from the records and drops a last partial one.
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::message::WirePacket;
+
+const RECORDING_MAGIC: [u8; 4] = *b"MUWR";
+const INDEX_MAGIC: [u8; 4] = *b"MUWI";
+const RECORDING_FORMAT_VERSION: u8 = 1;
+const HEADER_LEN: u64 = RECORDING_MAGIC.len() as u64 + 1;
+const RECORD_LENGTH_LEN: u64 = 4;
+const FOOTER_LEN: u64 = 8 + INDEX_MAGIC.len() as u64;
+
+/// Recording time between two index entries.
+pub const INDEX_INTERVAL_MS: u64 = 1_000;
+
+/// Largest record accepted when reading; guards against a corrupt length.
+pub const MAX_RECORD_LEN: u32 = 16 * 1024 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RecordingError {
+    #[error("not a recording")]
+    NotARecording,
+    #[error("recording format {0} is not supported")]
+    UnsupportedFormat(u8),
+    #[error("recording ends in the middle of a record")]
+    Truncated,
+    #[error("record at {at_ms} ms comes after one at {previous_ms} ms")]
+    OutOfOrder { previous_ms: u64, at_ms: u64 },
+    #[error("record exceeds limit: limit={limit} actual={actual}")]
+    RecordTooLarge { limit: u32, actual: usize },
+    #[error("serialization error: {0}")]
+    Serialization(#[from] postcard::Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// A packet, `at_ms` after the recording started.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RecordedPacket {
+    pub at_ms: u64,
+    pub packet: WirePacket,
+}
+
+/// First record at or after a point of the recording.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub at_ms: u64,
+    /// Position of the record in the file.
+    pub offset: u64,
+    /// Number of records before it.
+    pub record: u32,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+struct RecordingIndex {
+    records: u32,
+    duration_ms: u64,
+    entries: Vec<IndexEntry>,
+}
+
+impl RecordingIndex {
+    /// Notes a record, starting an entry when it opens a new interval.
+    fn push(&mut self, at_ms: u64, offset: u64) {
+        let due = self
+            .entries
+            .last()
+            .is_none_or(|last| at_ms >= last.at_ms + INDEX_INTERVAL_MS);
+        if due {
+            self.entries.push(IndexEntry {
+                at_ms,
+                offset,
+                record: self.records,
+            });
+        }
+        self.records += 1;
+        self.duration_ms = at_ms;
+    }
+
+    /// Last entry at or before `at_ms`.
+    fn entry_before(&self, at_ms: u64) -> Option<IndexEntry> {
+        let after = self.entries.partition_point(|entry| entry.at_ms <= at_ms);
+        after.checked_sub(1).map(|at| self.entries[at])
+    }
+}
+
+/// Writes a recording; records must come in time order.
+pub struct RecordingWriter<W: Write> {
+    inner: W,
+    offset: u64,
+    index: RecordingIndex,
+}
+
+impl<W: Write> RecordingWriter<W> {
+    pub fn new(mut inner: W) -> Result<Self, RecordingError> {
+        inner.write_all(&RECORDING_MAGIC)?;
+        inner.write_all(&[RECORDING_FORMAT_VERSION])?;
+        Ok(Self {
+            inner,
+            offset: HEADER_LEN,
+            index: RecordingIndex::default(),
+        })
+    }
+
+    /// Appends `packet`, seen `at_ms` after the recording started.
+    pub fn record(&mut self, at_ms: u64, packet: &WirePacket) -> Result<(), RecordingError> {
+        if self.index.records > 0 && at_ms < self.index.duration_ms {
+            return Err(RecordingError::OutOfOrder {
+                previous_ms: self.index.duration_ms,
+                at_ms,
+            });
+        }
+
+        let bytes = postcard::to_stdvec(&RecordedPacketRef { at_ms, packet })?;
+        let length = u32::try_from(bytes.len())
+            .ok()
+            .filter(|length| *length <= MAX_RECORD_LEN)
+            .ok_or(RecordingError::RecordTooLarge {
+                limit: MAX_RECORD_LEN,
+                actual: bytes.len(),
+            })?;
+        self.inner.write_all(&length.to_le_bytes())?;
+        self.inner.write_all(&bytes)?;
+
+        self.index.push(at_ms, self.offset);
+        self.offset += RECORD_LENGTH_LEN + bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Number of records written so far.
+    pub fn records(&self) -> u32 {
+        self.index.records
+    }
+
+    pub fn flush(&mut self) -> Result<(), RecordingError> {
+        Ok(self.inner.flush()?)
+    }
+
+    /// Writes the index and footer and hands the writer back.
+    pub fn finish(mut self) -> Result<W, RecordingError> {
+        let index = postcard::to_stdvec(&self.index)?;
+        self.inner.write_all(&index)?;
+        self.inner.write_all(&self.offset.to_le_bytes())?;
+        self.inner.write_all(&INDEX_MAGIC)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+/// Borrowing twin of [`RecordedPacket`], so recording does not clone packets.
+#[derive(Serialize)]
+struct RecordedPacketRef<'a> {
+    at_ms: u64,
+    packet: &'a WirePacket,
+}
+
+/// Reads a recording, in order or from any point.
+pub struct RecordingReader<R: Read + Seek> {
+    inner: R,
+    index: RecordingIndex,
+    records_end: u64,
+    position: u64,
+    finished: bool,
+}
+
+impl<R: Read + Seek> RecordingReader<R> {
+    pub fn open(mut inner: R) -> Result<Self, RecordingError> {
+        let mut header = [0; HEADER_LEN as usize];
+        inner
+            .read_exact(&mut header)
+            .map_err(|error| match error.kind() {
+                io::ErrorKind::UnexpectedEof => RecordingError::NotARecording,
+                _ => error.into(),
+            })?;
+        if header[..RECORDING_MAGIC.len()] != RECORDING_MAGIC {
+            return Err(RecordingError::NotARecording);
+        }
+        let format = header[RECORDING_MAGIC.len()];
+        if format != RECORDING_FORMAT_VERSION {
+            return Err(RecordingError::UnsupportedFormat(format));
+        }
+
+        let len = inner.seek(SeekFrom::End(0))?;
+        let (index, records_end, finished) = match read_index(&mut inner, len)? {
+            Some((index, records_end)) => (index, records_end, true),
+            None => {
+                let (index, records_end) = scan_records(&mut inner, len)?;
+                (index, records_end, false)
+            }
+        };
+        inner.seek(SeekFrom::Start(HEADER_LEN))?;
+        Ok(Self {
+            inner,
+            index,
+            records_end,
+            position: HEADER_LEN,
+            finished,
+        })
+    }
+
+    /// Number of records in the recording.
+    pub fn records(&self) -> u32 {
+        self.index.records
+    }
+
+    /// Time of the last record.
+    pub fn duration_ms(&self) -> u64 {
+        self.index.duration_ms
+    }
+
+    pub fn index(&self) -> &[IndexEntry] {
+        &self.index.entries
+    }
+
+    /// False when the recording was cut short and its index rebuilt.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Moves to the first record at or after `at_ms`.
+    pub fn seek(&mut self, at_ms: u64) -> Result<(), RecordingError> {
+        let start = self
+            .index
+            .entry_before(at_ms)
+            .map_or(HEADER_LEN, |entry| entry.offset);
+        self.inner.seek(SeekFrom::Start(start))?;
+        self.position = start;
+        loop {
+            let record_start = self.position;
+            match self.next_record()? {
+                Some(record) if record.at_ms < at_ms => {}
+                _ => {
+                    self.inner.seek(SeekFrom::Start(record_start))?;
+                    self.position = record_start;
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Next record; `None` at the end of the recording.
+    pub fn next_record(&mut self) -> Result<Option<RecordedPacket>, RecordingError> {
+        if self.position >= self.records_end {
+            return Ok(None);
+        }
+        let bytes = read_record(&mut self.inner)?;
+        self.position += RECORD_LENGTH_LEN + bytes.len() as u64;
+        Ok(Some(postcard::from_bytes(&bytes)?))
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read + Seek> Iterator for RecordingReader<R> {
+    type Item = Result<RecordedPacket, RecordingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
+
+/// Index and end of the records of a finished recording; `None` without a
+/// valid footer.
+fn read_index<R: Read + Seek>(
+    inner: &mut R,
+    len: u64,
+) -> Result<Option<(RecordingIndex, u64)>, RecordingError> {
+    if len < HEADER_LEN + FOOTER_LEN {
+        return Ok(None);
+    }
+    inner.seek(SeekFrom::Start(len - FOOTER_LEN))?;
+    let mut footer = [0; FOOTER_LEN as usize];
+    inner.read_exact(&mut footer)?;
+    let (offset, magic) = footer.split_at(8);
+    if magic != INDEX_MAGIC {
+        return Ok(None);
+    }
+    let offset = u64::from_le_bytes(offset.try_into().expect("footer offset is 8 bytes"));
+    if offset < HEADER_LEN || offset > len - FOOTER_LEN {
+        return Ok(None);
+    }
+
+    inner.seek(SeekFrom::Start(offset))?;
+    let mut bytes = vec![0; (len - FOOTER_LEN - offset) as usize];
+    inner.read_exact(&mut bytes)?;
+    Ok(postcard::from_bytes(&bytes)
+        .ok()
+        .map(|index| (index, offset)))
+}
+
+/// Index of a recording without footer, from its complete records.
+fn scan_records<R: Read + Seek>(
+    inner: &mut R,
+    len: u64,
+) -> Result<(RecordingIndex, u64), RecordingError> {
+    let mut index = RecordingIndex::default();
+    let mut offset = HEADER_LEN;
+    inner.seek(SeekFrom::Start(offset))?;
+    while offset < len {
+        let bytes = match read_record(inner) {
+            Ok(bytes) => bytes,
+            Err(RecordingError::Truncated) => break,
+            Err(error) => return Err(error),
+        };
+        let Ok(record) = postcard::from_bytes::<RecordedPacket>(&bytes) else {
+            break;
+        };
+        index.push(record.at_ms, offset);
+        offset += RECORD_LENGTH_LEN + bytes.len() as u64;
+    }
+    Ok((index, offset))
+}
+
+fn read_record<R: Read>(inner: &mut R) -> Result<Vec<u8>, RecordingError> {
+    let truncated = |error: io::Error| match error.kind() {
+        io::ErrorKind::UnexpectedEof => RecordingError::Truncated,
+        _ => error.into(),
+    };
+    let mut length = [0; RECORD_LENGTH_LEN as usize];
+    inner.read_exact(&mut length).map_err(truncated)?;
+    let length = u32::from_le_bytes(length);
+    if length > MAX_RECORD_LEN {
+        return Err(RecordingError::RecordTooLarge {
+            limit: MAX_RECORD_LEN,
+            actual: length as usize,
+        });
+    }
+    let mut bytes = vec![0; length as usize];
+    inner.read_exact(&mut bytes).map_err(truncated)?;
+    Ok(bytes)
+}
+
+/// Hands out the records of a recording at the pace they were recorded.
+///
+/// The clock starts with the first record taken, so a playback that was
+/// [`RecordingReader::seek`]ed starts right away. `speed` scales the pace:
+/// 2.0 plays twice as fast.
+pub struct Playback<R: Read + Seek> {
+    reader: RecordingReader<R>,
+    speed: f64,
+    pending: Option<RecordedPacket>,
+    started: Option<(Instant, u64)>,
+}
+
+impl<R: Read + Seek> Playback<R> {
+    pub fn new(reader: RecordingReader<R>) -> Self {
+        Self {
+            reader,
+            speed: 1.0,
+            pending: None,
+            started: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.speed = speed.max(f64::EPSILON);
+        self
+    }
+
+    /// Time since the playback started at which `record` is due.
+    pub fn due_after(&self, record: &RecordedPacket) -> Duration {
+        self.due_at(record.at_ms)
+    }
+
+    fn due_at(&self, at_ms: u64) -> Duration {
+        let first_ms = self.started.map_or(at_ms, |(_, first_ms)| first_ms);
+        let elapsed_ms = at_ms.saturating_sub(first_ms) as f64 / self.speed;
+        Duration::from_secs_f64(elapsed_ms / 1000.0)
+    }
+
+    /// Next record if it is due at `now`, without waiting.
+    pub fn poll(&mut self, now: Instant) -> Result<Option<RecordedPacket>, RecordingError> {
+        let Some(at_ms) = self.peek()?.map(|record| record.at_ms) else {
+            return Ok(None);
+        };
+        let (started, _) = *self.started.get_or_insert((now, at_ms));
+        let due = self.due_at(at_ms);
+        if now.saturating_duration_since(started) < due {
+            return Ok(None);
+        }
+        Ok(self.pending.take())
+    }
+
+    /// Next record, sleeping until it is due; `None` at the end.
+    pub fn wait_next(&mut self) -> Result<Option<RecordedPacket>, RecordingError> {
+        loop {
+            let now = Instant::now();
+            if let Some(record) = self.poll(now)? {
+                return Ok(Some(record));
+            }
+            let Some(record) = self.pending.as_ref() else {
+                return Ok(None);
+            };
+            let (started, _) = self.started.expect("poll starts the clock");
+            let due = started + self.due_after(record);
+            thread::sleep(due.saturating_duration_since(now));
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<&RecordedPacket>, RecordingError> {
+        if self.pending.is_none() {
+            self.pending = self.reader.next_record()?;
+        }
+        Ok(self.pending.as_ref())
+    }
+
+    pub fn into_reader(self) -> RecordingReader<R> {
+        self.reader
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::{ClientMessage, RouteKey, ServerMessage};
+    use std::io::Cursor;
+
+    fn ping(at_ms: u64, sequence: u32) -> WirePacket {
+        WirePacket::client(
+            10,
+            RouteKey::LOBBY,
+            sequence,
+            None,
+            at_ms,
+            ClientMessage::Ping {
+                client_sent_ms: at_ms,
+            },
+        )
+    }
+
+    fn pong(at_ms: u64, sequence: u32) -> WirePacket {
+        WirePacket::server(
+            10,
+            RouteKey::LOBBY,
+            sequence,
+            Some(sequence),
+            at_ms,
+            ServerMessage::Pong {
+                client_sent_ms: at_ms,
+                server_received_ms: at_ms + 20,
+                server_sent_ms: at_ms + 21,
+            },
+        )
+    }
+
+    /// A ping every 250 ms with its pong 40 ms later, for `seconds`.
+    fn session(seconds: u64) -> Vec<RecordedPacket> {
+        (0..seconds * 4)
+            .flat_map(|step| {
+                let at_ms = step * 250;
+                let sequence = step as u32;
+                [
+                    RecordedPacket {
+                        at_ms,
+                        packet: ping(at_ms, sequence),
+                    },
+                    RecordedPacket {
+                        at_ms: at_ms + 40,
+                        packet: pong(at_ms, sequence),
+                    },
+                ]
+            })
+            .collect()
+    }
+
+    fn write(records: &[RecordedPacket]) -> RecordingWriter<Vec<u8>> {
+        let mut writer = RecordingWriter::new(Vec::new()).unwrap();
+        for record in records {
+            writer.record(record.at_ms, &record.packet).unwrap();
+        }
+        writer
+    }
+
+    #[test]
+    fn recordings_round_trip_with_an_index() {
+        let records = session(5);
+        let bytes = write(&records).finish().unwrap();
+
+        let reader = RecordingReader::open(Cursor::new(bytes)).unwrap();
+        assert!(reader.is_finished());
+        assert_eq!(reader.records(), 40);
+        assert_eq!(reader.duration_ms(), 4_790);
+        let entries: Vec<_> = reader.index().iter().map(|entry| entry.at_ms).collect();
+        assert_eq!(entries, [0, 1_000, 2_000, 3_000, 4_000]);
+
+        let read: Vec<_> = reader.map(Result::unwrap).collect();
+        assert_eq!(read, records);
+    }
+
+    #[test]
+    fn seek_starts_at_the_first_record_not_before() {
+        let bytes = write(&session(5)).finish().unwrap();
+        let mut reader = RecordingReader::open(Cursor::new(bytes)).unwrap();
+
+        reader.seek(2_260).unwrap();
+        assert_eq!(reader.next_record().unwrap().unwrap().at_ms, 2_290);
+        reader.seek(0).unwrap();
+        assert_eq!(reader.next_record().unwrap().unwrap().at_ms, 0);
+        reader.seek(10_000).unwrap();
+        assert!(reader.next_record().unwrap().is_none());
+    }
+
+    #[test]
+    fn unfinished_recordings_keep_their_complete_records() {
+        let records = session(2);
+        let mut bytes = write(&records).inner;
+        bytes.truncate(bytes.len() - 3);
+
+        let mut reader = RecordingReader::open(Cursor::new(bytes)).unwrap();
+        assert!(!reader.is_finished());
+        assert_eq!(reader.records(), 15);
+        assert_eq!(reader.index().len(), 2);
+        reader.seek(1_000).unwrap();
+        let read: Vec<_> = reader.map(Result::unwrap).collect();
+        assert_eq!(read, records[8..15]);
+    }
+
+    #[test]
+    fn records_must_come_in_time_order() {
+        let mut writer = RecordingWriter::new(Vec::new()).unwrap();
+        writer.record(500, &ping(500, 1)).unwrap();
+        assert!(matches!(
+            writer.record(499, &ping(499, 2)),
+            Err(RecordingError::OutOfOrder {
+                previous_ms: 500,
+                at_ms: 499
+            })
+        ));
+        assert!(matches!(
+            RecordingReader::open(Cursor::new(b"MURP\x01".to_vec())),
+            Err(RecordingError::NotARecording)
+        ));
+    }
+
+    #[test]
+    fn playback_follows_the_recorded_timing() {
+        let bytes = write(&session(1)).finish().unwrap();
+        let mut reader = RecordingReader::open(Cursor::new(bytes)).unwrap();
+        reader.seek(250).unwrap();
+        let mut playback = Playback::new(reader).with_speed(2.0);
+
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        assert_eq!(playback.poll(start).unwrap().unwrap().at_ms, 250);
+        assert!(playback.poll(at(19)).unwrap().is_none());
+        assert_eq!(playback.poll(at(20)).unwrap().unwrap().at_ms, 290);
+        assert!(playback.poll(at(124)).unwrap().is_none());
+        assert_eq!(playback.poll(at(125)).unwrap().unwrap().at_ms, 500);
+
+        let rest: Vec<_> = std::iter::from_fn(|| playback.poll(at(10_000)).unwrap())
+            .map(|record| record.at_ms)
+            .collect();
+        assert_eq!(rest, [540, 750, 790]);
+    }
+}
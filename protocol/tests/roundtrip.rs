@@ -1,9 +1,10 @@
 use protocol::channel::QuicChannel;
 use protocol::codec::{CodecError, CodecLimits, WireCodec};
 use protocol::message::{
-    ChatChannel, ChatPayload, ClientHello, ClientMessage, PacketPayload, ProtocolVersion, RouteKey,
-    ServerMessage, WirePacket,
+    ChatChannel, ChatPayload, ClientHello, ClientMessage, PROTOCOL_VERSION, PacketPayload,
+    ProtocolVersion, RouteKey, ServerMessage, WirePacket,
 };
+use protocol::negotiation::{MIN_PROTOCOL_VERSION, ProtocolCapabilities};
 
 fn sample_route() -> RouteKey {
     RouteKey {
@@ -43,6 +44,7 @@ fn sample_control_packet() -> WirePacket {
             auth_token: "token-abc".into(),
             client_build: "0.1.0".into(),
             locale: "pt-BR".into(),
+            capabilities: ProtocolCapabilities::NONE,
         }),
     )
 }
@@ -141,9 +143,10 @@ fn rejects_channel_payload_mismatch() {
 
 #[test]
 fn rejects_version_mismatch_during_decode() {
-    let compat_codec = WireCodec::new(ProtocolVersion::new(3, 0), CodecLimits::default());
+    let next_major = ProtocolVersion::new(PROTOCOL_VERSION.major + 1, 0);
+    let compat_codec = WireCodec::new(next_major, CodecLimits::default());
     let mut packet = sample_control_packet();
-    packet.version = ProtocolVersion::new(3, 0);
+    packet.version = next_major;
 
     let frame = compat_codec
         .encode_stream_frame(QuicChannel::Control, &packet)
@@ -156,13 +159,43 @@ fn rejects_version_mismatch_during_decode() {
     assert!(matches!(err, CodecError::VersionMismatch { .. }));
 }
 
+#[test]
+fn accepts_later_minor_versions_of_its_major() {
+    let next_minor = ProtocolVersion::new(PROTOCOL_VERSION.major, PROTOCOL_VERSION.minor + 1);
+    let newer_codec = WireCodec::new(next_minor, CodecLimits::default());
+    let mut packet = sample_control_packet();
+    packet.version = next_minor;
+
+    let frame = newer_codec
+        .encode_stream_frame(QuicChannel::Control, &packet)
+        .unwrap();
+    let (decoded, _) = WireCodec::default()
+        .try_decode_stream_frame(&frame)
+        .unwrap()
+        .unwrap();
+    assert_eq!(decoded.packet.version, next_minor);
+
+    // The newer build reads this one only once told to go that far back.
+    let ours = WireCodec::default()
+        .encode_stream_frame(QuicChannel::Control, &sample_control_packet())
+        .unwrap();
+    assert!(newer_codec.try_decode_stream_frame(&ours).is_err());
+    assert!(
+        newer_codec
+            .with_min_version(MIN_PROTOCOL_VERSION)
+            .try_decode_stream_frame(&ours)
+            .is_ok()
+    );
+}
+
 #[test]
 fn rejects_oversized_datagram() {
     let tiny_codec = WireCodec::new(
-        ProtocolVersion::new(2, 0),
+        PROTOCOL_VERSION,
         CodecLimits {
             max_datagram_size: 16,
             max_stream_payload_size: 1024,
+            ..CodecLimits::default()
         },
     );
 
@@ -189,3 +222,345 @@ fn packet_payload_variant_is_preserved() {
         PacketPayload::Server(ServerMessage::Chat(_))
     ));
 }
+
+#[test]
+fn mailbox_messages_use_economy_channel() {
+    let codec = WireCodec::default();
+    let packet = WirePacket::server(
+        300,
+        sample_route(),
+        5,
+        Some(4),
+        2_000,
+        ServerMessage::Mailbox {
+            entries: vec![protocol::MailEntry {
+                mail_id: 9,
+                source: "Blood Castle 3".into(),
+                zen: 250_000,
+                items: vec![protocol::ItemInstance {
+                    serial: 0x0190_0000_0000_0001,
+                    group: 14,
+                    index: 13,
+                    level: 0,
+                    quantity: 1,
+                    options: protocol::ItemOptions {
+                        excellent: 0b0010_0001,
+                        luck: true,
+                        additional: 3,
+                        sockets: vec![vec![0x12, 0x03]],
+                    },
+                    expires_at_ms: Some(1_700_000_600_000),
+                }],
+                received_at_ms: 1_900,
+            }],
+        },
+    );
+
+    let frame = codec
+        .encode_stream_frame(QuicChannel::Economy, &packet)
+        .unwrap();
+    let (decoded, _) = codec.try_decode_stream_frame(&frame).unwrap().unwrap();
+
+    assert_eq!(decoded.channel, QuicChannel::Economy);
+    assert_eq!(decoded.packet, packet);
+}
+
+/// Encodes `payload` on its preferred channel and reads it back from the stream.
+fn assert_stream_roundtrip(payload: PacketPayload) {
+    let codec = WireCodec::default();
+    let channel = protocol::preferred_channel(&payload);
+    let packet = match payload {
+        PacketPayload::Client(message) => {
+            WirePacket::client(400, sample_route(), 6, Some(5), 3_000, message)
+        }
+        PacketPayload::Server(message) => {
+            WirePacket::server(400, sample_route(), 6, Some(5), 3_000, message)
+        }
+    };
+
+    let frame = codec.encode_stream_frame(channel, &packet).unwrap();
+    let (decoded, consumed) = codec.try_decode_stream_frame(&frame).unwrap().unwrap();
+
+    assert_eq!(consumed, frame.len());
+    assert_eq!(decoded.channel, channel);
+    assert_eq!(decoded.packet, packet);
+}
+
+#[test]
+fn arena_event_messages_roundtrip() {
+    let client = [
+        ClientMessage::RegisterForEvent {
+            event: protocol::ArenaEvent::BloodCastle,
+            bracket: 3,
+            ticket_serial: 0x0190_0000_0000_0002,
+        },
+        ClientMessage::CancelEventRegistration {
+            event: protocol::ArenaEvent::DevilSquare,
+        },
+    ];
+    let server = [
+        ServerMessage::EventRegistered {
+            event: protocol::ArenaEvent::BloodCastle,
+            bracket: 3,
+            starts_at_ms: 1_700_000_000_000,
+        },
+        ServerMessage::EventRegistrationRejected {
+            event: protocol::ArenaEvent::DevilSquare,
+            reason: protocol::EventFailure::WrongTicket,
+        },
+        ServerMessage::EventTicketConsumed {
+            event: protocol::ArenaEvent::BloodCastle,
+            bracket: 3,
+            serial: 0x0190_0000_0000_0002,
+        },
+        ServerMessage::EventCountdown(protocol::EventCountdown {
+            event: protocol::ArenaEvent::BloodCastle,
+            bracket: 3,
+            stage: protocol::ArenaStage::Preparation,
+            ends_at_ms: 1_700_000_060_000,
+            seconds_remaining: 60,
+        }),
+        ServerMessage::EventResult(protocol::EventResult {
+            event: protocol::ArenaEvent::DevilSquare,
+            bracket: 2,
+            success: true,
+            rank: 1,
+            participants: 8,
+            score: 4_250,
+            experience: 1_200_000,
+            reward: protocol::EventReward {
+                zen: 500_000,
+                items: vec![protocol::ItemInstance {
+                    serial: 0x0190_0000_0000_0003,
+                    group: 14,
+                    index: 16,
+                    level: 0,
+                    quantity: 1,
+                    options: protocol::ItemOptions::default(),
+                    expires_at_ms: None,
+                }],
+            },
+        }),
+    ];
+
+    for message in client {
+        assert_stream_roundtrip(PacketPayload::Client(message));
+    }
+    for message in server {
+        assert_stream_roundtrip(PacketPayload::Server(message));
+    }
+}
+
+fn sample_item(serial: u64) -> protocol::ItemInstance {
+    protocol::ItemInstance {
+        serial,
+        group: 0,
+        index: 5,
+        level: 9,
+        quantity: 1,
+        options: protocol::ItemOptions {
+            excellent: 0b0000_0100,
+            luck: false,
+            additional: 2,
+            sockets: Vec::new(),
+        },
+        expires_at_ms: None,
+    }
+}
+
+#[test]
+fn trade_and_personal_store_messages_roundtrip() {
+    let listing = protocol::StoreListing {
+        serial: 0x0190_0000_0000_0011,
+        price_zen: 1_500_000,
+    };
+    let client = [
+        ClientMessage::RequestTrade {
+            target_entity_id: 812,
+        },
+        ClientMessage::RespondTrade { accept: true },
+        ClientMessage::AddTradeItem {
+            serial: 0x0190_0000_0000_0010,
+            cell: 9,
+        },
+        ClientMessage::RemoveTradeItem {
+            serial: 0x0190_0000_0000_0010,
+        },
+        ClientMessage::SetTradeZen { zen: 750_000 },
+        ClientMessage::LockTrade { locked: true },
+        ClientMessage::ConfirmTrade,
+        ClientMessage::CancelTrade,
+        ClientMessage::OpenPersonalStore {
+            title: "Jewels cheap".into(),
+            listings: vec![listing],
+        },
+        ClientMessage::ClosePersonalStore,
+        ClientMessage::BrowsePersonalStore {
+            owner_entity_id: 812,
+        },
+        ClientMessage::BuyFromPersonalStore {
+            owner_entity_id: 812,
+            serial: listing.serial,
+            price_zen: listing.price_zen,
+        },
+    ];
+    let server = [
+        ServerMessage::TradeRequested {
+            from_entity_id: 812,
+            name: "Merchant".into(),
+            level: 220,
+        },
+        ServerMessage::TradeOpened {
+            partner_entity_id: 812,
+            partner_name: "Merchant".into(),
+            partner_level: 220,
+        },
+        ServerMessage::TradeUpdated {
+            own: protocol::TradeOffer {
+                items: vec![protocol::TradeItem {
+                    cell: 9,
+                    item: sample_item(0x0190_0000_0000_0010),
+                }],
+                zen: 750_000,
+                locked: true,
+            },
+            partner: protocol::TradeOffer::default(),
+        },
+        ServerMessage::TradeRejected {
+            reason: protocol::TradeFailure::TransferLimit,
+        },
+        ServerMessage::TradeClosed {
+            outcome: protocol::TradeOutcome::Failed(protocol::TradeFailure::InventoryFull),
+        },
+        ServerMessage::PersonalStoreSign {
+            entity_id: 812,
+            title: Some("Jewels cheap".into()),
+        },
+        ServerMessage::PersonalStore(protocol::PersonalStore {
+            owner_entity_id: 812,
+            owner_name: "Merchant".into(),
+            title: "Jewels cheap".into(),
+            items: vec![protocol::StoreItem {
+                item: sample_item(listing.serial),
+                price_zen: listing.price_zen,
+            }],
+        }),
+        ServerMessage::PersonalStorePurchased {
+            owner_entity_id: 812,
+            item: sample_item(listing.serial),
+            cell: 24,
+            price_zen: listing.price_zen,
+        },
+        ServerMessage::PersonalStoreSold {
+            serial: listing.serial,
+            price_zen: listing.price_zen,
+            buyer_name: "Visitor".into(),
+        },
+        ServerMessage::PersonalStoreRejected {
+            reason: protocol::StoreFailure::PriceChanged,
+        },
+    ];
+
+    for message in client {
+        assert_stream_roundtrip(PacketPayload::Client(message));
+    }
+    for message in server {
+        assert_stream_roundtrip(PacketPayload::Server(message));
+    }
+}
+
+fn sample_entity_state(entity_id: u32, x: u16, y: u16) -> protocol::EntityState {
+    protocol::EntityState {
+        entity_id,
+        x,
+        y,
+        height: -40,
+        direction: 5,
+        hp: 380,
+        max_hp: 400,
+        animation: protocol::AnimationState::Skill(41),
+        state_flags: 0b0000_0010,
+    }
+}
+
+#[test]
+fn world_snapshot_and_sequenced_deltas_roundtrip() {
+    let mut snapshot = protocol::WorldSnapshot {
+        sequence: 70,
+        server_tick: 9_000,
+        entities: vec![
+            protocol::EntitySpawn {
+                kind: protocol::EntityKind::Player,
+                name: "Archer".into(),
+                appearance: Some(protocol::message::Appearance::new(
+                    common::CharacterClass::FairyElf,
+                )),
+                state: sample_entity_state(10, 130, 120),
+            },
+            protocol::EntitySpawn {
+                kind: protocol::EntityKind::Monster { monster_id: 26 },
+                name: "Goblin".into(),
+                appearance: None,
+                state: sample_entity_state(4_001, 140, 118),
+            },
+        ],
+    };
+    let delta = protocol::WorldDelta {
+        sequence: 71,
+        base_sequence: 70,
+        server_tick: 9_004,
+        spawned: vec![protocol::EntitySpawn {
+            kind: protocol::EntityKind::Npc { npc_id: 251 },
+            name: "Hanzo".into(),
+            appearance: None,
+            state: sample_entity_state(7, 128, 121),
+        }],
+        updated: vec![sample_entity_state(10, 131, 120)],
+        despawned: vec![4_001],
+    };
+
+    assert_stream_roundtrip(PacketPayload::Server(ServerMessage::WorldSnapshot(
+        snapshot.clone(),
+    )));
+    assert_stream_roundtrip(PacketPayload::Server(ServerMessage::WorldDelta(
+        delta.clone(),
+    )));
+
+    let codec = WireCodec::default();
+    let packet = WirePacket::server(
+        400,
+        sample_route(),
+        72,
+        Some(12),
+        3_100,
+        ServerMessage::StateDelta {
+            server_tick: 9_005,
+            entities: vec![protocol::message::EntityDelta {
+                entity_id: 10,
+                x: 132,
+                y: 120,
+                height: -40,
+                hp: 380,
+                state_flags: 0b0000_0010,
+            }],
+        },
+    );
+    let channel = protocol::preferred_channel(&packet.payload);
+    assert_eq!(channel, QuicChannel::GameplayInput);
+    let bytes = codec.encode_datagram_frame(channel, &packet).unwrap();
+    let decoded = codec.decode_datagram_frame(&bytes).unwrap();
+    assert_eq!(decoded.packet, packet);
+
+    snapshot.apply(&delta).unwrap();
+    assert_eq!(snapshot.sequence, 71);
+    assert!(snapshot.entity(4_001).is_none());
+    assert_eq!(snapshot.entity(10).unwrap().state.x, 131);
+    assert_eq!(snapshot.entity(7).unwrap().name, "Hanzo");
+    assert_eq!(
+        snapshot.apply(&delta),
+        Err(protocol::SequenceGap {
+            expected: 71,
+            found: 70,
+        })
+    );
+}
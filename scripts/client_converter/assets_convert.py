@@ -202,6 +202,24 @@ WORLD1_NAMED_MODEL_GROUPS: Tuple[Tuple[int, int, str, int], ...] = (
     (150, 1, "Candle", 1),
     (151, 3, "Beer", 1),
 )
+# Ground footprints (model units, before object scale) of objects that block
+# movement. Keyed by (world number, object type); emitted per object as
+# `properties.collision` and gathered into the world's collision.json.
+WORLD_OBJECT_COLLISION_SHAPES: Dict[Tuple[int, int], Dict[str, object]] = {
+    **{(1, obj_type): {"kind": "circle", "radius": 90.0} for obj_type in range(40, 44)},
+    **{(1, obj_type): {"kind": "box", "half_extents": [200.0, 110.0]} for obj_type in range(98, 102)},
+    (1, 105): {"kind": "circle", "radius": 250.0},
+    **{(1, obj_type): {"kind": "circle", "radius": 120.0} for obj_type in range(106, 110)},
+    **{(1, obj_type): {"kind": "box", "half_extents": [350.0, 300.0]} for obj_type in range(115, 120)},
+    (1, 120): {"kind": "box", "half_extents": [250.0, 200.0]},
+}
+# Town props a player can click, keyed by (world number, object type); emitted
+# per object as `properties.interaction`.
+WORLD_OBJECT_INTERACTIONS: Dict[Tuple[int, int], Dict[str, object]] = {
+    (1, 68): {"kind": "door"},
+    **{(1, obj_type): {"kind": "seat"} for obj_type in (145, 146)},
+    **{(1, obj_type): {"kind": "drink"} for obj_type in range(151, 154)},
+}
 DEFAULT_TERRAIN_TEXTURE_SLOT_FILES: Dict[int, str] = {
     0: "TileGrass01.png",
     1: "TileGrass02.png",
@@ -1851,6 +1869,7 @@ def emit_scene_objects_json(
             )
 
         objects: List[Dict[str, object]] = []
+        footprints: List[Dict[str, object]] = []
         world_uses_named_models = world_number == 1
         model_path_cache: Dict[Tuple[int, int], str] = {}
         model_quality_cache: Dict[str, Tuple[bool, str]] = {}
@@ -1889,6 +1908,22 @@ def emit_scene_objects_json(
                 properties["model_renderable"] = False
                 properties["model_validation_reason"] = model_reason
 
+            collision = WORLD_OBJECT_COLLISION_SHAPES.get((world_number, int(obj_type)))
+            if collision is not None:
+                properties["collision"] = dict(collision)
+                footprints.append(
+                    {
+                        "position": [float(px), float(py)],
+                        "yaw_degrees": float(az),
+                        "scale": float(scale),
+                        "shape": dict(collision),
+                    }
+                )
+
+            interaction = WORLD_OBJECT_INTERACTIONS.get((world_number, int(obj_type)))
+            if interaction is not None:
+                properties["interaction"] = dict(interaction)
+
             objects.append(
                 {
                     "id": f"obj_{index:05d}",
@@ -1946,6 +1981,16 @@ def emit_scene_objects_json(
     stats.scene_objects_json_emitted += 1
     logging.debug("Emitted scene objects json %s -> %s", source, target)
 
+    collision_target = target.with_name("collision.json")
+    try:
+        collision_target.write_text(
+            json.dumps({"world": world_number, "footprints": footprints}, indent=2),
+            encoding="utf-8",
+        )
+    except Exception as exc:  # noqa: BLE001
+        stats.failures.append(f"{source} -> {collision_target}: {exc}")
+        logging.error("Failed to write collision json for %s: %s", source, exc)
+
 
 def parse_world_number(world_dir: Path) -> Optional[int]:
     match = WORLD_DIR_PATTERN.match(world_dir.name.lower())
@@ -3128,11 +3173,12 @@ def copy_other_assets(
         ".tga", ".bmp", ".jpg", ".psd",
         ".smd", ".fbx",
         ".db", ".lnk", ".rar", ".csr", ".dat",
-        ".ein", ".mp3", ".wav", ".ogg", ".mpr", ".txt",
+        ".ein", ".mp3", ".ogg", ".mpr", ".txt",
     }
 
     # Only copy files with extensions that the Rust client actually needs.
-    ALLOW_COPY_EXTENSIONS = {".png", ".json", ".glb", ".bin", ".wgsl"}
+    # `.wav` are the original sound effects, played as they are.
+    ALLOW_COPY_EXTENSIONS = {".png", ".json", ".glb", ".bin", ".wgsl", ".wav"}
 
     if world_filter:
         for world_number in sorted(world_filter):
@@ -16,6 +16,7 @@
 #   --no-embed-textures Keep external PNG references in GLBs
 #   --keep-object-png-textures Keep object PNG files even when embedding into GLB
 #   --dry-run           Show what would be done without executing
+#   --allow-manifest-issues Write the asset manifest even if names break the schema
 #   --force             Force reconversion of all files
 #   --verbose           Enable verbose logging
 #   --help              Show this help message
@@ -73,6 +74,7 @@ CONVERT_MODELS=true
 DRY_RUN=""
 FORCE=""
 VERBOSE=""
+MANIFEST_ARGS=()
 WORLD_FILTER_ARGS=()
 MODEL_TEXTURE_ARGS=()
 
@@ -95,6 +97,7 @@ function print_help() {
     echo "  --no-embed-textures Keep external PNG references in GLBs"
     echo "  --keep-object-png-textures Keep object PNG files even when embedding into GLB"
     echo "  --dry-run           Show what would be done without executing"
+    echo "  --allow-manifest-issues Write the asset manifest even if names break the schema"
     echo "  --force             Force reconversion of all files"
     echo "  --verbose           Enable verbose logging"
     echo "  --help              Show this help message"
@@ -157,6 +160,10 @@ while [[ $# -gt 0 ]]; do
             FORCE="--force"
             shift
             ;;
+        --allow-manifest-issues)
+            MANIFEST_ARGS+=("--allow-issues")
+            shift
+            ;;
         --verbose)
             VERBOSE="--verbose"
             shift
@@ -242,6 +249,21 @@ else
     log_info "Skipping model conversion (--textures-only specified)"
 fi
 
+# Step 3: List the output in assets/manifest.json, which the client checks its
+# asset paths against; fails on names that break the canonical schema.
+if [ -z "$DRY_RUN" ]; then
+    log_info "Generating asset manifest..."
+
+    ASSET_ROOT="${DATA_OUTPUT_ROOT%/data}"
+    if cargo run --quiet --manifest-path "$RUST_ROOT/Cargo.toml" -p assets-manifest -- \
+        generate --assets-root "$ASSET_ROOT" "${MANIFEST_ARGS[@]}"; then
+        log_success "Asset manifest written to $ASSET_ROOT/manifest.json"
+    else
+        log_error "Converted files break the asset naming schema (see above)"
+        exit 1
+    fi
+fi
+
 END_TIME=$(date +%s)
 DURATION=$((END_TIME - START_TIME))
 
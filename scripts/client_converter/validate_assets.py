@@ -5,6 +5,8 @@ validate_assets.py
 
 Structural validation script for all converted assets in rust/assets/.
 Validates PNGs, GLBs, and JSON sidecar files and generates a report.
+When the asset root has a manifest.json (see the assets-manifest crate), the
+models scene_objects.json files point at must be listed in it.
 
 Usage:
     python3 validate_assets.py \\
@@ -24,7 +26,7 @@ import sys
 import time
 from dataclasses import dataclass, field
 from pathlib import Path
-from typing import Dict, List, Optional, Tuple
+from typing import Dict, List, Optional, Set, Tuple
 
 try:
     from PIL import Image
@@ -155,7 +157,9 @@ def validate_gltf(path: Path) -> Tuple[bool, str]:
     return True, "ok"
 
 
-def validate_json_sidecar(path: Path) -> Tuple[bool, str]:
+def validate_json_sidecar(
+    path: Path, manifest: Optional[Set[str]] = None
+) -> Tuple[bool, str]:
     """Validate a JSON sidecar file: valid JSON, expected top-level keys present."""
     try:
         payload = json.loads(path.read_text(encoding="utf-8"))
@@ -173,9 +177,23 @@ def validate_json_sidecar(path: Path) -> Tuple[bool, str]:
             if key not in payload:
                 return False, f"missing expected key: {key}"
 
+    if manifest is not None and fname == "scene_objects.json" and isinstance(payload, dict):
+        for obj in payload.get("objects", []):
+            model = obj.get("model") if isinstance(obj, dict) else None
+            if isinstance(model, str) and model not in manifest:
+                return False, f"model not in manifest: {model}"
+
     return True, "ok"
 
 
+def load_manifest(path: Path) -> Optional[Set[str]]:
+    """Paths listed in the manifest written by `assets-manifest generate`."""
+    if not path.is_file():
+        return None
+    payload = json.loads(path.read_text(encoding="utf-8"))
+    return set(payload.get("assets", {}))
+
+
 # ---------------------------------------------------------------------------
 # Batch validation
 # ---------------------------------------------------------------------------
@@ -195,6 +213,9 @@ def validate_all(
     verbose: bool,
 ) -> ValidationStats:
     stats = ValidationStats()
+    manifest = load_manifest(assets_root / "manifest.json")
+    if manifest is None:
+        logging.info("No manifest.json, model paths are not checked")
 
     for dirpath, _dirnames, filenames in os.walk(assets_root):
         for fname in filenames:
@@ -216,7 +237,7 @@ def validate_all(
                 ok, reason = validate_gltf(fpath)
             elif suffix == ".json":
                 ftype = "json"
-                ok, reason = validate_json_sidecar(fpath)
+                ok, reason = validate_json_sidecar(fpath, manifest)
             else:
                 continue  # skip non-asset files
 
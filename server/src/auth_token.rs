@@ -9,6 +9,8 @@ use protocol::RouteKey;
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
 
+use crate::roles::AccountRole;
+
 type HmacSha256 = Hmac<Sha256>;
 
 const MIN_SECRET_LEN: usize = 32;
@@ -41,6 +43,8 @@ pub struct AuthCharacterSummary {
     pub name: String,
     pub class_id: u8,
     pub level: u16,
+    #[serde(default)]
+    pub guild_id: Option<u32>,
 }
 
 impl AuthCharacterSummary {
@@ -57,6 +61,9 @@ impl AuthCharacterSummary {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct AuthSessionClaims {
     pub account_id: u64,
+    /// Tokens issued before roles existed carry none and act as players.
+    #[serde(default)]
+    pub role: AccountRole,
     pub session_id: String,
     pub issued_at_ms: u64,
     pub expires_at_ms: u64,
@@ -66,6 +73,7 @@ pub struct AuthSessionClaims {
 impl AuthSessionClaims {
     pub fn new(
         account_id: u64,
+        role: AccountRole,
         session_id: String,
         issued_at_ms: u64,
         expires_at_ms: u64,
@@ -73,6 +81,7 @@ impl AuthSessionClaims {
     ) -> Self {
         Self {
             account_id,
+            role,
             session_id,
             issued_at_ms,
             expires_at_ms,
@@ -122,6 +131,7 @@ impl AuthTokenService {
     pub fn issue_session_token(
         &self,
         account_id: u64,
+        role: AccountRole,
         session_id: String,
         characters: Vec<AuthCharacterSummary>,
         issued_at_ms: u64,
@@ -129,6 +139,7 @@ impl AuthTokenService {
         let expires_at_ms = issued_at_ms.saturating_add(self.ttl.as_millis() as u64);
         let claims = AuthSessionClaims::new(
             account_id,
+            role,
             session_id,
             issued_at_ms,
             expires_at_ms,
@@ -265,6 +276,7 @@ mod tests {
         let token = service
             .issue_session_token(
                 77,
+                AccountRole::GameMaster,
                 "session-1".to_string(),
                 vec![AuthCharacterSummary {
                     character_id: 10,
@@ -272,6 +284,7 @@ mod tests {
                     name: "Knight".to_string(),
                     class_id: 1,
                     level: 150,
+                    guild_id: None,
                 }],
                 1_000,
             )
@@ -279,6 +292,7 @@ mod tests {
 
         let claims = service.verify(&token, 1_500).expect("verify token");
         assert_eq!(claims.account_id, 77);
+        assert_eq!(claims.role, AccountRole::GameMaster);
         assert_eq!(claims.session_id, "session-1");
         assert_eq!(claims.characters.len(), 1);
     }
@@ -287,7 +301,7 @@ mod tests {
     fn rejects_tampered_token() {
         let service = test_service();
         let token = service
-            .issue_session_token(1, "s".to_string(), Vec::new(), 10)
+            .issue_session_token(1, AccountRole::Player, "s".to_string(), Vec::new(), 10)
             .expect("issue token");
         let (payload, signature) = token.split_once('.').expect("token split");
         let mut chars: Vec<char> = payload.chars().collect();
@@ -305,7 +319,7 @@ mod tests {
     fn rejects_expired_token() {
         let service = test_service();
         let token = service
-            .issue_session_token(1, "s".to_string(), Vec::new(), 1_000)
+            .issue_session_token(1, AccountRole::Player, "s".to_string(), Vec::new(), 1_000)
             .expect("issue token");
 
         assert!(matches!(
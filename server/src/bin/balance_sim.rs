@@ -0,0 +1,227 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Context};
+use common::combat::{CombatStats, Equipment, MAX_GEAR_TIER, MAX_LEVEL};
+use common::CharacterClass;
+
+const CSV_HEADER: &str = "class,level,gear_tier,monster_level,max_hp,min_damage,max_damage,\
+defense,attack_interval_ms,hits_to_kill,time_to_kill_ms,effective_hp,hits_survived";
+
+#[derive(Debug, Clone, PartialEq)]
+struct BalanceConfig {
+    classes: Vec<CharacterClass>,
+    levels: Vec<u16>,
+    gear_tiers: Vec<u8>,
+    /// Fixed monster level; each row fights a monster of its own level when unset.
+    monster_level: Option<u16>,
+    output: Option<PathBuf>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let cfg = parse_args(std::env::args().skip(1))?;
+
+    match &cfg.output {
+        Some(path) => {
+            let file =
+                File::create(path).with_context(|| format!("falha ao criar {}", path.display()))?;
+            write_csv(&cfg, &mut BufWriter::new(file))?;
+            eprintln!(
+                "[balance-sim] {} linhas escritas em {}",
+                row_count(&cfg),
+                path.display()
+            );
+        }
+        None => write_csv(&cfg, &mut io::stdout().lock())?,
+    }
+    Ok(())
+}
+
+fn row_count(cfg: &BalanceConfig) -> usize {
+    cfg.classes.len() * cfg.levels.len() * cfg.gear_tiers.len()
+}
+
+fn write_csv(cfg: &BalanceConfig, out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "{CSV_HEADER}")?;
+    for &class in &cfg.classes {
+        for &level in &cfg.levels {
+            for &tier in &cfg.gear_tiers {
+                writeln!(out, "{}", csv_row(class, level, tier, cfg.monster_level))?;
+            }
+        }
+    }
+    out.flush()
+}
+
+fn csv_row(class: CharacterClass, level: u16, tier: u8, monster_level: Option<u16>) -> String {
+    let monster_level = monster_level.unwrap_or(level);
+    let monster = CombatStats::monster(monster_level);
+    let character = CombatStats::character(class, level, Equipment::of_tier(tier));
+    let effective_hp = character.effective_hp(&monster);
+    let hits_survived = monster.hits_to_kill(&character);
+
+    format!(
+        "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+        class.name(),
+        level,
+        tier,
+        monster_level,
+        character.max_hp,
+        character.min_damage,
+        character.max_damage,
+        character.defense,
+        character.attack_interval_ms,
+        character.hits_to_kill(&monster),
+        character.time_to_kill_ms(&monster),
+        effective_hp,
+        hits_survived
+    )
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> anyhow::Result<BalanceConfig> {
+    let mut cfg = BalanceConfig {
+        classes: CharacterClass::ALL.to_vec(),
+        levels: vec![1, 50, 100, 150, 200, 250, 300, 350, 400],
+        gear_tiers: vec![0, 3, 6, 9, 12, 15],
+        monster_level: None,
+        output: None,
+    };
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--classes" => {
+                cfg.classes = parse_list(&next_arg_value(&mut args, &arg)?, |name| {
+                    CharacterClass::from_name(name)
+                        .ok_or_else(|| anyhow!("classe desconhecida: {}", name))
+                })?
+            }
+            "--levels" => {
+                cfg.levels = parse_list(&next_arg_value(&mut args, &arg)?, |value| {
+                    parse_bounded(value, 1, MAX_LEVEL, "--levels")
+                })?
+            }
+            "--gear" => {
+                cfg.gear_tiers = parse_list(&next_arg_value(&mut args, &arg)?, |value| {
+                    parse_bounded(value, 0, MAX_GEAR_TIER, "--gear")
+                })?
+            }
+            "--monster-level" => {
+                let value = next_arg_value(&mut args, &arg)?;
+                cfg.monster_level = Some(parse_bounded(&value, 1, MAX_LEVEL, &arg)?);
+            }
+            "--output" => cfg.output = Some(PathBuf::from(next_arg_value(&mut args, &arg)?)),
+            "--help" | "-h" => {
+                print_help();
+                std::process::exit(0);
+            }
+            other => {
+                bail!(
+                    "argumento desconhecido: {}\nUse --help para ver as opcoes.",
+                    other
+                );
+            }
+        }
+    }
+
+    Ok(cfg)
+}
+
+fn parse_list<T>(value: &str, parse: impl Fn(&str) -> anyhow::Result<T>) -> anyhow::Result<Vec<T>> {
+    let items = value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(parse)
+        .collect::<anyhow::Result<Vec<T>>>()?;
+    if items.is_empty() {
+        bail!("lista vazia: {}", value);
+    }
+    Ok(items)
+}
+
+fn parse_bounded<T>(value: &str, min: T, max: T, flag: &str) -> anyhow::Result<T>
+where
+    T: std::str::FromStr + PartialOrd + std::fmt::Display,
+{
+    let parsed = value
+        .parse::<T>()
+        .map_err(|_| anyhow!("{} invalido: {}", flag, value))?;
+    if parsed < min || parsed > max {
+        bail!("{} fora do intervalo {}..={}: {}", flag, min, max, value);
+    }
+    Ok(parsed)
+}
+
+fn next_arg_value(args: &mut impl Iterator<Item = String>, flag: &str) -> anyhow::Result<String> {
+    args.next()
+        .ok_or_else(|| anyhow!("valor ausente para {}", flag))
+}
+
+fn print_help() {
+    println!(
+        "balance_sim - roda as formulas de combate sem servidor e gera CSV\n\n\
+Uso:\n\
+  cargo run --manifest-path server/Cargo.toml --bin balance_sim -- [opcoes]\n\n\
+Cada linha cruza classe, nivel e tier de equipamento contra um monstro e traz\n\
+o tempo para matar (time_to_kill_ms) e o HP efetivo (effective_hp).\n\n\
+Opcoes:\n\
+  --classes <lista>            Classes, ex: DarkKnight,DarkWizard (default: todas)\n\
+  --levels <lista>             Niveis 1-400 (default: 1,50,100,...,400)\n\
+  --gear <lista>               Tiers de equipamento 0-15 (default: 0,3,6,9,12,15)\n\
+  --monster-level <n>          Nivel fixo do monstro (default: o nivel da linha)\n\
+  --output <csv>               Arquivo de saida (default: stdout)\n\
+  --help                       Mostra esta ajuda\n"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn matrix_options_are_parsed_and_validated() {
+        let cfg = parse_args(args(&[
+            "--classes",
+            "DarkKnight, fairyelf",
+            "--levels",
+            "10,400",
+            "--gear",
+            "0",
+            "--monster-level",
+            "20",
+        ]))
+        .unwrap();
+        assert_eq!(
+            cfg.classes,
+            vec![CharacterClass::DarkKnight, CharacterClass::FairyElf]
+        );
+        assert_eq!((cfg.levels, cfg.gear_tiers), (vec![10, 400], vec![0]));
+        assert_eq!(cfg.monster_level, Some(20));
+
+        assert!(parse_args(args(&["--levels", "401"])).is_err());
+        assert!(parse_args(args(&["--gear", "16"])).is_err());
+        assert!(parse_args(args(&["--classes", "Paladin"])).is_err());
+        assert!(parse_args(args(&["--classes", ","])).is_err());
+    }
+
+    #[test]
+    fn csv_has_one_row_per_matrix_cell() {
+        let cfg = parse_args(args(&["--levels", "1,100", "--gear", "0,15"])).unwrap();
+        let mut out = Vec::new();
+        write_csv(&cfg, &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(lines.len(), 1 + row_count(&cfg));
+        let columns = CSV_HEADER.split(',').count();
+        assert!(lines.iter().all(|line| line.split(',').count() == columns));
+        assert!(lines[1].starts_with("DarkWizard,1,0,1,"));
+    }
+}
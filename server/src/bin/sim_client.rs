@@ -7,8 +7,8 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{anyhow, bail, Context};
 use protocol::{
-    ClientHello, ClientMessage, PacketPayload, QuicChannel, RouteKey, ServerMessage, WireCodec,
-    WirePacket,
+    ClientHello, ClientMessage, PacketPayload, ProtocolCapabilities, QuicChannel, RouteKey,
+    ServerMessage, WireCodec, WirePacket,
 };
 use quinn::Endpoint;
 use reqwest::StatusCode;
@@ -236,6 +236,7 @@ async fn run_quic_protocol_flow(cfg: &SimConfig) -> anyhow::Result<()> {
             auth_token: cfg.auth_token.clone(),
             client_build: cfg.client_build.clone(),
             locale: cfg.locale.clone(),
+            capabilities: ProtocolCapabilities::SUPPORTED,
         }),
     );
 
@@ -248,6 +249,7 @@ async fn run_quic_protocol_flow(cfg: &SimConfig) -> anyhow::Result<()> {
                 heartbeat_interval_ms,
                 motd,
                 characters,
+                ..
             }) => Some((
                 *session_id,
                 *heartbeat_interval_ms,
@@ -417,7 +419,7 @@ async fn send_control_request(
         .map_err(|e| anyhow!("falha ao finalizar envio do stream: {}", e))?;
 
     let bytes = recv
-        .read_to_end(codec.limits().max_stream_payload_size.saturating_mul(8))
+        .read_to_end(codec.limits().max_message_size.saturating_mul(2))
         .await
         .context("falha ao ler resposta do stream")?;
 
@@ -0,0 +1,186 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use anyhow::{bail, Context};
+
+use crate::db::bundle::{export_account, export_character, import_bundle, PortableBundle};
+use crate::db::MongoDbContext;
+
+/// What the `server` binary was asked to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// No subcommand: run the Connect Server.
+    Serve,
+    Help,
+    ExportAccount {
+        username: String,
+        output: Option<PathBuf>,
+    },
+    ExportCharacter {
+        name: String,
+        output: Option<PathBuf>,
+    },
+    Import {
+        input: PathBuf,
+        dry_run: bool,
+    },
+}
+
+pub const USAGE: &str = "\
+Usage:
+  server                                              run the Connect Server
+  server help                                         show this message
+  server export-account <username> [--output <file>]  write an account bundle (stdout by default)
+  server export-character <name> [--output <file>]    write a character bundle
+  server import <file> [--dry-run]                    import a bundle, only logging writes with --dry-run";
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> anyhow::Result<Command> {
+    let mut args = args.into_iter();
+    let Some(subcommand) = args.next() else {
+        return Ok(Command::Serve);
+    };
+    if matches!(subcommand.as_str(), "help" | "--help" | "-h") {
+        return Ok(Command::Help);
+    }
+
+    let mut positional = None;
+    let mut output = None;
+    let mut dry_run = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => match args.next() {
+                Some(path) => output = Some(PathBuf::from(path)),
+                None => bail!("missing value for --output"),
+            },
+            "--dry-run" => dry_run = true,
+            other if other.starts_with("--") => bail!("unknown option: {}", other),
+            other if positional.is_none() => positional = Some(other.to_string()),
+            other => bail!("unexpected argument: {}", other),
+        }
+    }
+
+    if dry_run && subcommand != "import" {
+        bail!("--dry-run only applies to import");
+    }
+    if output.is_some() && subcommand == "import" {
+        bail!("--output does not apply to import");
+    }
+
+    Ok(match subcommand.as_str() {
+        "export-account" => Command::ExportAccount {
+            username: positional.context("export-account needs a username")?,
+            output,
+        },
+        "export-character" => Command::ExportCharacter {
+            name: positional.context("export-character needs a character name")?,
+            output,
+        },
+        "import" => Command::Import {
+            input: PathBuf::from(positional.context("import needs a bundle file")?),
+            dry_run,
+        },
+        other => bail!("unknown command: {}", other),
+    })
+}
+
+/// Runs an export or import subcommand against `db`.
+pub async fn run(command: Command, db: &MongoDbContext) -> anyhow::Result<()> {
+    match command {
+        Command::Serve | Command::Help => Ok(()),
+        Command::ExportAccount { username, output } => {
+            let bundle = export_account(db, &username).await?;
+            write_bundle(&bundle, output)
+        }
+        Command::ExportCharacter { name, output } => {
+            let bundle = export_character(db, &name).await?;
+            write_bundle(&bundle, output)
+        }
+        Command::Import { input, .. } => {
+            let raw = fs::read_to_string(&input)
+                .with_context(|| format!("failed to read {}", input.display()))?;
+            let bundle: PortableBundle = serde_json::from_str(&raw)
+                .with_context(|| format!("{} is not a valid bundle", input.display()))?;
+            let summary = import_bundle(db, &bundle).await?;
+            log::info!(
+                "{} {} accounts, {} characters and {} records from {}",
+                if db.is_dry_run() {
+                    "Would import"
+                } else {
+                    "Imported"
+                },
+                summary.accounts,
+                summary.characters,
+                summary.records,
+                input.display()
+            );
+            Ok(())
+        }
+    }
+}
+
+fn write_bundle(bundle: &PortableBundle, output: Option<PathBuf>) -> anyhow::Result<()> {
+    let json = serde_json::to_string_pretty(bundle)?;
+    match output {
+        Some(path) => {
+            fs::write(&path, json)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            log::info!(
+                "Exported {} characters to {}",
+                bundle.characters.len(),
+                path.display()
+            );
+        }
+        None => writeln!(io::stdout().lock(), "{}", json)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> anyhow::Result<Command> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn no_arguments_run_the_server() {
+        assert_eq!(parse(&[]).unwrap(), Command::Serve);
+        assert_eq!(parse(&["--help"]).unwrap(), Command::Help);
+    }
+
+    #[test]
+    fn parses_export_and_import_commands() {
+        assert_eq!(
+            parse(&["export-account", "alice", "--output", "alice.json"]).unwrap(),
+            Command::ExportAccount {
+                username: "alice".to_string(),
+                output: Some(PathBuf::from("alice.json")),
+            }
+        );
+        assert_eq!(
+            parse(&["export-character", "Alicia"]).unwrap(),
+            Command::ExportCharacter {
+                name: "Alicia".to_string(),
+                output: None,
+            }
+        );
+        assert_eq!(
+            parse(&["import", "--dry-run", "alice.json"]).unwrap(),
+            Command::Import {
+                input: PathBuf::from("alice.json"),
+                dry_run: true,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_misplaced_options() {
+        assert!(parse(&["export-account"]).is_err());
+        assert!(parse(&["export-account", "alice", "--dry-run"]).is_err());
+        assert!(parse(&["import", "a.json", "--output", "b.json"]).is_err());
+        assert!(parse(&["import", "a.json", "b.json"]).is_err());
+        assert!(parse(&["migrate"]).is_err());
+    }
+}
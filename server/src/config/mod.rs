@@ -1,12 +1,25 @@
 use serde::Deserialize;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::error::{ConnectServerError, Result};
+use crate::geoip::GeoPoint;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct ServerConfig {
     pub servers: Vec<GameServer>,
+    #[serde(default)]
+    pub geoip: GeoIpConfig,
+}
+
+/// Proximity hints on `/servers`, off unless enabled with a database.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GeoIpConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// CSV table described in [`crate::geoip`].
+    #[serde(default)]
+    pub database: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -14,6 +27,12 @@ pub struct GameServer {
     pub id: String,
     pub name: String,
     pub description: String,
+    /// Region tag shown to clients, e.g. `sa-east`.
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Where the server is hosted, for latency hints.
+    #[serde(default)]
+    pub location: Option<GeoPoint>,
     pub worlds: Vec<WorldServer>,
 }
 
@@ -24,6 +43,12 @@ pub struct WorldServer {
     pub ip: String,
     pub port: u16,
     pub max_players: u32,
+    /// Runtime world this listing is served by, used to report maintenance.
+    #[serde(default)]
+    pub runtime_world_id: Option<u16>,
+    /// Runtime map, when the listing stands for a single map of that world.
+    #[serde(default)]
+    pub runtime_map_id: Option<u16>,
 }
 
 impl ServerConfig {
@@ -123,4 +148,36 @@ max_players = 100
         assert!(config.get_world("world-1-lorencia").is_some());
         assert!(config.get_world("non-existent").is_none());
     }
+
+    #[test]
+    fn test_parse_geoip_settings() {
+        let toml_content = r#"
+[geoip]
+enabled = true
+database = "server/config/geoip.csv"
+
+[[servers]]
+id = "server-1"
+name = "Alpha Server"
+description = "Main game server"
+region = "sa-east"
+location = { latitude = -23.55, longitude = -46.63 }
+worlds = []
+        "#;
+
+        let config: ServerConfig = toml::from_str(toml_content).unwrap();
+        assert!(config.geoip.enabled);
+        assert_eq!(
+            config.geoip.database,
+            Some(PathBuf::from("server/config/geoip.csv"))
+        );
+        assert_eq!(config.servers[0].region.as_deref(), Some("sa-east"));
+        assert_eq!(
+            config.servers[0].location,
+            Some(GeoPoint {
+                latitude: -23.55,
+                longitude: -46.63
+            })
+        );
+    }
 }
@@ -0,0 +1,650 @@
+//! Scheduled database backups and restore verification.
+//!
+//! Backups are driver-level exports: every collection of the live database is
+//! written as canonical extended JSON into a single file, so `mongodump` does
+//! not have to be installed next to the server. Files go to a local directory
+//! or an S3 bucket and only the newest [`BackupConfig::keep`] are kept. A
+//! second job restores the newest backup into a scratch database and checks
+//! that every collection comes back whole.
+
+mod s3;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use futures_util::stream::TryStreamExt;
+use mongodb::bson::{doc, Bson, Document};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use super::MongoDbContext;
+use crate::auth_token::now_ms;
+use crate::error::{ConnectServerError, Result};
+
+use s3::S3Target;
+
+/// Bumped whenever a dump written by an older server can no longer be restored.
+pub const DUMP_FORMAT_VERSION: u32 = 1;
+
+const DEFAULT_INTERVAL_SECS: u64 = 6 * 60 * 60;
+const DEFAULT_VERIFY_INTERVAL_SECS: u64 = 24 * 60 * 60;
+const DEFAULT_KEEP: usize = 7;
+
+/// Where backup files are stored.
+pub enum BackupTarget {
+    Local(PathBuf),
+    S3(Box<S3Target>),
+}
+
+impl BackupTarget {
+    /// `s3://bucket/prefix`, or a local directory otherwise.
+    pub fn parse(value: &str, vars: &impl Fn(&str) -> Option<String>) -> Result<Self> {
+        if value.starts_with("s3://") {
+            return S3Target::from_url(value, vars)
+                .map(|target| BackupTarget::S3(Box::new(target)));
+        }
+        let path = value.strip_prefix("file://").unwrap_or(value);
+        if path.is_empty() {
+            return Err(ConnectServerError::Config(
+                "BACKUP_TARGET is empty".to_string(),
+            ));
+        }
+        Ok(BackupTarget::Local(PathBuf::from(path)))
+    }
+
+    /// Location without credentials, for logs and the admin API.
+    pub fn describe(&self) -> String {
+        match self {
+            BackupTarget::Local(path) => path.display().to_string(),
+            BackupTarget::S3(target) => target.describe(),
+        }
+    }
+
+    async fn put(&self, name: &str, body: Vec<u8>) -> Result<()> {
+        match self {
+            BackupTarget::Local(dir) => {
+                tokio::fs::create_dir_all(dir)
+                    .await
+                    .map_err(storage_error)?;
+                // Written under a temporary name so a crash never leaves a
+                // truncated backup that looks complete.
+                let partial = dir.join(format!("{}.partial", name));
+                tokio::fs::write(&partial, body)
+                    .await
+                    .map_err(storage_error)?;
+                tokio::fs::rename(&partial, dir.join(name))
+                    .await
+                    .map_err(storage_error)
+            }
+            BackupTarget::S3(target) => target.put(name, body).await,
+        }
+    }
+
+    async fn get(&self, name: &str) -> Result<Vec<u8>> {
+        match self {
+            BackupTarget::Local(dir) => {
+                tokio::fs::read(dir.join(name)).await.map_err(storage_error)
+            }
+            BackupTarget::S3(target) => target.get(name).await,
+        }
+    }
+
+    async fn delete(&self, name: &str) -> Result<()> {
+        match self {
+            BackupTarget::Local(dir) => tokio::fs::remove_file(dir.join(name))
+                .await
+                .map_err(storage_error),
+            BackupTarget::S3(target) => target.delete(name).await,
+        }
+    }
+
+    async fn list(&self) -> Result<Vec<String>> {
+        match self {
+            BackupTarget::Local(dir) => {
+                let mut entries = match tokio::fs::read_dir(dir).await {
+                    Ok(entries) => entries,
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+                    Err(err) => return Err(storage_error(err)),
+                };
+                let mut names = Vec::new();
+                while let Some(entry) = entries.next_entry().await.map_err(storage_error)? {
+                    if let Some(name) = entry.file_name().to_str() {
+                        names.push(name.to_string());
+                    }
+                }
+                Ok(names)
+            }
+            BackupTarget::S3(target) => target.list().await,
+        }
+    }
+}
+
+fn storage_error(err: std::io::Error) -> ConnectServerError {
+    ConnectServerError::Storage(err.to_string())
+}
+
+pub struct BackupConfig {
+    pub target: BackupTarget,
+    pub interval: Duration,
+    /// Backups kept at the target; older ones are deleted after each run.
+    pub keep: usize,
+    /// Zero turns restore verification off.
+    pub verify_interval: Duration,
+    /// Database the verification restores into; dropped before and after.
+    pub scratch_database: Option<String>,
+}
+
+impl BackupConfig {
+    /// Reads the `BACKUP_*` variables; `None` when `BACKUP_TARGET` is unset.
+    pub fn from_vars(vars: impl Fn(&str) -> Option<String>) -> Result<Option<Self>> {
+        let Some(target) = vars("BACKUP_TARGET") else {
+            return Ok(None);
+        };
+        let seconds = |name: &str, default: u64| -> Result<Duration> {
+            match vars(name) {
+                Some(value) => value.parse().map(Duration::from_secs).map_err(|_| {
+                    ConnectServerError::Config(format!("{} must be a number of seconds", name))
+                }),
+                None => Ok(Duration::from_secs(default)),
+            }
+        };
+
+        let interval = seconds("BACKUP_INTERVAL_SECS", DEFAULT_INTERVAL_SECS)?;
+        if interval.is_zero() {
+            return Err(ConnectServerError::Config(
+                "BACKUP_INTERVAL_SECS must be positive".to_string(),
+            ));
+        }
+        let keep = match vars("BACKUP_KEEP") {
+            Some(value) => value.parse().ok().filter(|keep| *keep > 0).ok_or_else(|| {
+                ConnectServerError::Config("BACKUP_KEEP must be a positive number".to_string())
+            })?,
+            None => DEFAULT_KEEP,
+        };
+
+        Ok(Some(Self {
+            target: BackupTarget::parse(&target, &vars)?,
+            interval,
+            keep,
+            verify_interval: seconds("BACKUP_VERIFY_INTERVAL_SECS", DEFAULT_VERIFY_INTERVAL_SECS)?,
+            scratch_database: vars("BACKUP_SCRATCH_DATABASE"),
+        }))
+    }
+
+    fn scratch_database_for(&self, live: &str) -> String {
+        self.scratch_database
+            .clone()
+            .unwrap_or_else(|| format!("{}_restore_check", live))
+    }
+}
+
+/// Every collection of a database at one point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseDump {
+    pub format_version: u32,
+    pub database: String,
+    pub created_at: DateTime<Utc>,
+    /// Documents of each collection, as canonical extended JSON so types such
+    /// as ObjectId and dates survive the round trip.
+    pub collections: BTreeMap<String, Vec<Value>>,
+}
+
+impl DatabaseDump {
+    pub fn documents(&self) -> u64 {
+        self.collections
+            .values()
+            .map(|documents| documents.len() as u64)
+            .sum()
+    }
+}
+
+/// Reads every collection of `db`.
+pub async fn dump_database(db: &MongoDbContext) -> Result<DatabaseDump> {
+    let database = db.database();
+    let created_at = Utc::now();
+    let mut collections = BTreeMap::new();
+    for name in database.list_collection_names().await? {
+        if name.starts_with("system.") {
+            continue;
+        }
+        let mut cursor = database.collection::<Document>(&name).find(doc! {}).await?;
+        let mut documents = Vec::new();
+        while let Some(document) = cursor.try_next().await? {
+            documents.push(Bson::Document(document).into_canonical_extjson());
+        }
+        collections.insert(name, documents);
+    }
+
+    Ok(DatabaseDump {
+        format_version: DUMP_FORMAT_VERSION,
+        database: db.database_name().to_string(),
+        created_at,
+        collections,
+    })
+}
+
+/// Writes every collection of `dump` into `db`, which should be empty.
+pub async fn restore_dump(db: &MongoDbContext, dump: &DatabaseDump) -> Result<()> {
+    if dump.format_version != DUMP_FORMAT_VERSION {
+        return Err(ConnectServerError::InvalidRequest(format!(
+            "unsupported dump format {} (expected {})",
+            dump.format_version, DUMP_FORMAT_VERSION
+        )));
+    }
+
+    let database = db.database();
+    for (name, documents) in &dump.collections {
+        if documents.is_empty() {
+            database.create_collection(name).await?;
+            continue;
+        }
+        let documents = documents
+            .iter()
+            .cloned()
+            .map(decode_document)
+            .collect::<Result<Vec<_>>>()?;
+        database
+            .collection::<Document>(name)
+            .insert_many(documents)
+            .await?;
+    }
+    Ok(())
+}
+
+fn decode_document(value: Value) -> Result<Document> {
+    match Bson::try_from(value) {
+        Ok(Bson::Document(document)) => Ok(document),
+        Ok(other) => Err(ConnectServerError::Storage(format!(
+            "expected a document, found {:?}",
+            other.element_type()
+        ))),
+        Err(err) => Err(ConnectServerError::Storage(err.to_string())),
+    }
+}
+
+/// `<database>-<UTC timestamp>.json`, so names sort oldest first.
+fn backup_name(database: &str, at: DateTime<Utc>) -> String {
+    format!("{}-{}.json", database, at.format("%Y%m%dT%H%M%SZ"))
+}
+
+fn is_backup_of(name: &str, database: &str) -> bool {
+    name.strip_prefix(database)
+        .and_then(|rest| rest.strip_prefix('-'))
+        .and_then(|rest| rest.strip_suffix(".json"))
+        .is_some_and(|stamp| NaiveDateTime::parse_from_str(stamp, "%Y%m%dT%H%M%SZ").is_ok())
+}
+
+/// Backups past the newest `keep`, given names sorted oldest first.
+fn expired_backups(stored: &[String], keep: usize) -> &[String] {
+    &stored[..stored.len().saturating_sub(keep)]
+}
+
+/// Result of one backup run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct BackupRun {
+    pub name: String,
+    pub started_at_ms: u64,
+    pub finished_at_ms: u64,
+    pub collections: usize,
+    pub documents: u64,
+    pub bytes: u64,
+    /// Older backups deleted by the retention policy.
+    pub pruned: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// Collection whose restored document count differs from the backup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct CollectionMismatch {
+    pub collection: String,
+    pub expected: u64,
+    pub restored: u64,
+}
+
+/// Result of restoring the newest backup into the scratch database.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct VerificationRun {
+    /// `None` when there was no backup to verify.
+    pub backup: Option<String>,
+    pub scratch_database: String,
+    pub started_at_ms: u64,
+    pub finished_at_ms: u64,
+    pub collections: usize,
+    pub documents: u64,
+    pub mismatches: Vec<CollectionMismatch>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BackupStatus {
+    pub target: String,
+    pub interval_secs: u64,
+    pub keep: usize,
+    pub verify_interval_secs: u64,
+    /// A backup or verification is in progress.
+    pub running: bool,
+    pub last_backup: Option<BackupRun>,
+    pub last_verification: Option<VerificationRun>,
+    /// Backups at the target as of the last run, oldest first.
+    pub stored: Vec<String>,
+}
+
+/// Runs backups and verifications of the live database and keeps their
+/// latest results for the admin API.
+#[derive(Clone)]
+pub struct BackupScheduler {
+    config: Arc<BackupConfig>,
+    db: MongoDbContext,
+    // Held for the whole of a backup or verification, so they never overlap.
+    job: Arc<tokio::sync::Mutex<()>>,
+    last_backup: Arc<StdMutex<Option<BackupRun>>>,
+    last_verification: Arc<StdMutex<Option<VerificationRun>>>,
+    stored: Arc<StdMutex<Vec<String>>>,
+}
+
+impl BackupScheduler {
+    pub fn new(config: BackupConfig, db: MongoDbContext) -> Self {
+        Self {
+            config: Arc::new(config),
+            db,
+            job: Arc::new(tokio::sync::Mutex::new(())),
+            last_backup: Arc::new(StdMutex::new(None)),
+            last_verification: Arc::new(StdMutex::new(None)),
+            stored: Arc::new(StdMutex::new(Vec::new())),
+        }
+    }
+
+    pub fn config(&self) -> &BackupConfig {
+        &self.config
+    }
+
+    pub fn status(&self) -> BackupStatus {
+        BackupStatus {
+            target: self.config.target.describe(),
+            interval_secs: self.config.interval.as_secs(),
+            keep: self.config.keep,
+            verify_interval_secs: self.config.verify_interval.as_secs(),
+            running: self.job.try_lock().is_err(),
+            last_backup: lock(&self.last_backup).clone(),
+            last_verification: lock(&self.last_verification).clone(),
+            stored: lock(&self.stored).clone(),
+        }
+    }
+
+    /// Backs the live database up and prunes old backups. Fails only when
+    /// another job is running; a failed backup is reported in the run.
+    pub async fn run_backup(&self) -> Result<BackupRun> {
+        let _job = self.job.try_lock().map_err(|_| busy())?;
+        let started_at = Utc::now();
+        let mut run = BackupRun {
+            name: backup_name(self.db.database_name(), started_at),
+            started_at_ms: started_at.timestamp_millis() as u64,
+            finished_at_ms: 0,
+            collections: 0,
+            documents: 0,
+            bytes: 0,
+            pruned: Vec::new(),
+            error: None,
+        };
+        if let Err(err) = self.backup(&mut run).await {
+            log::error!("Backup {} failed: {}", run.name, err);
+            run.error = Some(err.to_string());
+        }
+        run.finished_at_ms = now_ms();
+        *lock(&self.last_backup) = Some(run.clone());
+        Ok(run)
+    }
+
+    async fn backup(&self, run: &mut BackupRun) -> Result<()> {
+        let dump = dump_database(&self.db).await?;
+        let body = serde_json::to_vec(&dump)?;
+        run.collections = dump.collections.len();
+        run.documents = dump.documents();
+        run.bytes = body.len() as u64;
+        self.config.target.put(&run.name, body).await?;
+        log::info!(
+            "Backed up {} documents of {} collections to {} at {}",
+            run.documents,
+            run.collections,
+            run.name,
+            self.config.target.describe()
+        );
+
+        let stored = self.refresh_stored().await?;
+        for name in expired_backups(&stored, self.config.keep) {
+            self.config.target.delete(name).await?;
+            run.pruned.push(name.clone());
+        }
+        if !run.pruned.is_empty() {
+            log::info!("Pruned {} old backups", run.pruned.len());
+            lock(&self.stored).retain(|name| !run.pruned.contains(name));
+        }
+        Ok(())
+    }
+
+    /// Restores the newest backup into the scratch database and compares
+    /// every collection. Fails only when another job is running.
+    pub async fn run_verification(&self) -> Result<VerificationRun> {
+        let _job = self.job.try_lock().map_err(|_| busy())?;
+        let mut run = VerificationRun {
+            backup: None,
+            scratch_database: self.config.scratch_database_for(self.db.database_name()),
+            started_at_ms: now_ms(),
+            finished_at_ms: 0,
+            collections: 0,
+            documents: 0,
+            mismatches: Vec::new(),
+            error: None,
+        };
+        if let Err(err) = self.verify(&mut run).await {
+            run.error = Some(err.to_string());
+        }
+        run.finished_at_ms = now_ms();
+        match (&run.error, run.mismatches.is_empty()) {
+            (Some(err), _) => log::error!("Restore verification failed: {}", err),
+            (None, false) => log::error!(
+                "Restore verification of {:?}: {} collections differ",
+                run.backup,
+                run.mismatches.len()
+            ),
+            (None, true) => log::info!(
+                "Restore verification of {:?} passed ({} documents)",
+                run.backup,
+                run.documents
+            ),
+        }
+        *lock(&self.last_verification) = Some(run.clone());
+        Ok(run)
+    }
+
+    async fn verify(&self, run: &mut VerificationRun) -> Result<()> {
+        if self.db.is_dry_run() {
+            return Err(ConnectServerError::Config(
+                "restore verification writes a scratch database and is off in dry-run mode"
+                    .to_string(),
+            ));
+        }
+        if run.scratch_database == self.db.database_name() {
+            return Err(ConnectServerError::Config(
+                "the scratch database cannot be the live database".to_string(),
+            ));
+        }
+
+        let stored = self.refresh_stored().await?;
+        let name = stored.last().cloned().ok_or_else(|| {
+            ConnectServerError::NotFound(format!("backup at {}", self.config.target.describe()))
+        })?;
+        run.backup = Some(name.clone());
+        let dump: DatabaseDump = serde_json::from_slice(&self.config.target.get(&name).await?)?;
+        run.collections = dump.collections.len();
+        run.documents = dump.documents();
+
+        let scratch = self.db.sibling(&run.scratch_database);
+        scratch.database().drop().await?;
+        let compared = match restore_dump(&scratch, &dump).await {
+            Ok(()) => compare_counts(&scratch, &dump).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = scratch.database().drop().await {
+            log::warn!(
+                "Failed to drop scratch database {}: {}",
+                run.scratch_database,
+                err
+            );
+        }
+        run.mismatches = compared?;
+        Ok(())
+    }
+
+    /// Lists the backups of the live database at the target, oldest first.
+    async fn refresh_stored(&self) -> Result<Vec<String>> {
+        let database = self.db.database_name();
+        let mut stored: Vec<String> = self
+            .config
+            .target
+            .list()
+            .await?
+            .into_iter()
+            .filter(|name| is_backup_of(name, database))
+            .collect();
+        stored.sort();
+        *lock(&self.stored) = stored.clone();
+        Ok(stored)
+    }
+}
+
+async fn compare_counts(
+    scratch: &MongoDbContext,
+    dump: &DatabaseDump,
+) -> Result<Vec<CollectionMismatch>> {
+    let mut mismatches = Vec::new();
+    for (name, documents) in &dump.collections {
+        let restored = scratch
+            .database()
+            .collection::<Document>(name)
+            .count_documents(doc! {})
+            .await?;
+        let expected = documents.len() as u64;
+        if restored != expected {
+            mismatches.push(CollectionMismatch {
+                collection: name.clone(),
+                expected,
+                restored,
+            });
+        }
+    }
+    Ok(mismatches)
+}
+
+fn busy() -> ConnectServerError {
+    ConnectServerError::InvalidRequest("a backup job is already running".to_string())
+}
+
+fn lock<T>(mutex: &StdMutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use mongodb::bson::{oid::ObjectId, DateTime as BsonDateTime};
+
+    fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let pairs: Vec<(String, String)> = pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        move |key| {
+            pairs
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.clone())
+        }
+    }
+
+    #[test]
+    fn config_reads_targets_and_defaults() {
+        assert!(BackupConfig::from_vars(vars(&[])).unwrap().is_none());
+
+        let config = BackupConfig::from_vars(vars(&[("BACKUP_TARGET", "/var/backups/mu")]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.target.describe(), "/var/backups/mu");
+        assert_eq!(config.keep, DEFAULT_KEEP);
+        assert_eq!(config.interval.as_secs(), DEFAULT_INTERVAL_SECS);
+        assert_eq!(
+            config.scratch_database_for("mu_online"),
+            "mu_online_restore_check"
+        );
+
+        let config = BackupConfig::from_vars(vars(&[
+            ("BACKUP_TARGET", "s3://mu-backups/nightly"),
+            ("BACKUP_KEEP", "3"),
+            ("AWS_ACCESS_KEY_ID", "key"),
+            ("AWS_SECRET_ACCESS_KEY", "secret"),
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(config.target.describe(), "s3://mu-backups/nightly");
+        assert_eq!(config.keep, 3);
+
+        for broken in [
+            vec![("BACKUP_TARGET", "s3://mu-backups")],
+            vec![("BACKUP_TARGET", "/tmp"), ("BACKUP_KEEP", "0")],
+            vec![("BACKUP_TARGET", "/tmp"), ("BACKUP_INTERVAL_SECS", "0")],
+        ] {
+            assert!(BackupConfig::from_vars(vars(&broken)).is_err());
+        }
+    }
+
+    #[test]
+    fn retention_keeps_the_newest_backups_of_the_database() {
+        let at = |hour| Utc.with_ymd_and_hms(2026, 3, 1, hour, 0, 0).unwrap();
+        let name = backup_name("mu_online", at(6));
+        assert_eq!(name, "mu_online-20260301T060000Z.json");
+        assert!(is_backup_of(&name, "mu_online"));
+        assert!(!is_backup_of(&name, "mu"));
+        assert!(!is_backup_of("mu_online-latest.json", "mu_online"));
+        assert!(!is_backup_of(&format!("{}.partial", name), "mu_online"));
+
+        let stored: Vec<String> = (0..5)
+            .map(|hour| backup_name("mu_online", at(hour)))
+            .collect();
+        assert_eq!(expired_backups(&stored, 3), &stored[..2]);
+        assert!(expired_backups(&stored, 7).is_empty());
+    }
+
+    #[test]
+    fn dumps_keep_bson_types_through_json() {
+        let id = ObjectId::new();
+        let original = doc! {
+            "_id": id,
+            "name": "Alicia",
+            "level": 250_i64,
+            "created_at": BsonDateTime::from_millis(1_700_000_000_000),
+        };
+        let dump = DatabaseDump {
+            format_version: DUMP_FORMAT_VERSION,
+            database: "mu_online".to_string(),
+            created_at: Utc::now(),
+            collections: BTreeMap::from([(
+                "characters".to_string(),
+                vec![Bson::Document(original.clone()).into_canonical_extjson()],
+            )]),
+        };
+
+        let json = serde_json::to_vec(&dump).unwrap();
+        let restored: DatabaseDump = serde_json::from_slice(&json).unwrap();
+        assert_eq!(restored.documents(), 1);
+        let document = decode_document(restored.collections["characters"][0].clone()).unwrap();
+        assert_eq!(document, original);
+        assert!(decode_document(serde_json::json!([1, 2])).is_err());
+    }
+}
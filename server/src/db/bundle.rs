@@ -0,0 +1,351 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use super::models::{
+    Account, AccountSettingsRecord, BestiaryRecord, Character, GensMemberRecord, QuestLogRecord,
+};
+use super::MongoDbContext;
+use crate::auth_token::object_id_to_u64;
+use crate::error::{ConnectServerError, Result};
+
+/// Bumped whenever a bundle written by an older server can no longer be imported.
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Portable JSON copy of an account or a single character, with the records
+/// keyed by their protocol ids.
+///
+/// Accounts and characters keep their `_id`, so protocol ids and every record
+/// keyed by them stay valid in the target database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortableBundle {
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    #[serde(default)]
+    pub accounts: Vec<Account>,
+    #[serde(default)]
+    pub characters: Vec<Character>,
+    #[serde(default)]
+    pub account_settings: Vec<AccountSettingsRecord>,
+    #[serde(default)]
+    pub gens_members: Vec<GensMemberRecord>,
+    #[serde(default)]
+    pub quest_logs: Vec<QuestLogRecord>,
+    #[serde(default)]
+    pub bestiaries: Vec<BestiaryRecord>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub accounts: usize,
+    pub characters: usize,
+    pub records: usize,
+}
+
+impl PortableBundle {
+    fn new() -> Self {
+        Self {
+            format_version: BUNDLE_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            accounts: Vec::new(),
+            characters: Vec::new(),
+            account_settings: Vec::new(),
+            gens_members: Vec::new(),
+            quest_logs: Vec::new(),
+            bestiaries: Vec::new(),
+        }
+    }
+
+    /// Ids of the accounts owning the bundled characters that the bundle does
+    /// not carry itself; they must already exist in the target database.
+    pub fn external_account_ids(&self) -> Vec<ObjectId> {
+        let bundled: HashSet<ObjectId> = self.accounts.iter().filter_map(|a| a.id).collect();
+        let mut external: Vec<ObjectId> = self
+            .characters
+            .iter()
+            .map(|c| c.account_id)
+            .filter(|id| !bundled.contains(id))
+            .collect();
+        external.sort();
+        external.dedup();
+        external
+    }
+
+    /// Checks that the bundle is self-consistent before anything is written.
+    pub fn validate(&self) -> Result<()> {
+        if self.format_version != BUNDLE_FORMAT_VERSION {
+            return Err(ConnectServerError::InvalidRequest(format!(
+                "unsupported bundle format {} (expected {})",
+                self.format_version, BUNDLE_FORMAT_VERSION
+            )));
+        }
+
+        let mut account_ids = HashSet::new();
+        let mut usernames = HashSet::new();
+        for account in &self.accounts {
+            let id = account.id.ok_or_else(|| {
+                ConnectServerError::InvalidRequest(format!(
+                    "account '{}' has no id",
+                    account.username
+                ))
+            })?;
+            if !account_ids.insert(object_id_to_u64(&id)) || !usernames.insert(&account.username) {
+                return Err(ConnectServerError::InvalidRequest(format!(
+                    "account '{}' appears twice",
+                    account.username
+                )));
+            }
+        }
+
+        let mut character_ids = HashSet::new();
+        let mut names = HashSet::new();
+        for character in &self.characters {
+            let id = character.id.ok_or_else(|| {
+                ConnectServerError::InvalidRequest(format!(
+                    "character '{}' has no id",
+                    character.name
+                ))
+            })?;
+            if !character_ids.insert(object_id_to_u64(&id)) || !names.insert(&character.name) {
+                return Err(ConnectServerError::InvalidRequest(format!(
+                    "character '{}' appears twice",
+                    character.name
+                )));
+            }
+        }
+
+        if let Some(record) = self
+            .account_settings
+            .iter()
+            .find(|record| !account_ids.contains(&record.account_id))
+        {
+            return Err(ConnectServerError::InvalidRequest(format!(
+                "settings of account {} have no bundled account",
+                record.account_id
+            )));
+        }
+
+        let orphan = self
+            .gens_members
+            .iter()
+            .map(|record| record.character_id)
+            .chain(self.quest_logs.iter().map(|record| record.character_id))
+            .chain(self.bestiaries.iter().map(|record| record.character_id))
+            .find(|character_id| !character_ids.contains(character_id));
+        if let Some(character_id) = orphan {
+            return Err(ConnectServerError::InvalidRequest(format!(
+                "records of character {} have no bundled character",
+                character_id
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Everything stored for an account: its characters and their records.
+pub async fn export_account(db: &MongoDbContext, username: &str) -> Result<PortableBundle> {
+    let account = db
+        .accounts()
+        .find_by_username(username)
+        .await?
+        .ok_or_else(|| ConnectServerError::NotFound(format!("account '{}'", username)))?;
+    let account_id = account
+        .id
+        .ok_or_else(|| ConnectServerError::Internal("account without id".to_string()))?;
+
+    let mut bundle = PortableBundle::new();
+    if let Some(mut settings) = db
+        .account_settings()
+        .find_by_account_id(object_id_to_u64(&account_id))
+        .await?
+    {
+        settings.id = None;
+        bundle.account_settings.push(settings);
+    }
+    for character in db.characters().find_by_account_id(&account_id).await? {
+        push_character(db, &mut bundle, character).await?;
+    }
+    bundle.accounts.push(account);
+    Ok(bundle)
+}
+
+/// One character and its records, without the owning account.
+pub async fn export_character(db: &MongoDbContext, name: &str) -> Result<PortableBundle> {
+    let character = db
+        .characters()
+        .find_by_name(name)
+        .await?
+        .ok_or_else(|| ConnectServerError::NotFound(format!("character '{}'", name)))?;
+
+    let mut bundle = PortableBundle::new();
+    push_character(db, &mut bundle, character).await?;
+    Ok(bundle)
+}
+
+async fn push_character(
+    db: &MongoDbContext,
+    bundle: &mut PortableBundle,
+    character: Character,
+) -> Result<()> {
+    let id = character
+        .id
+        .ok_or_else(|| ConnectServerError::Internal("character without id".to_string()))?;
+    let character_id = object_id_to_u64(&id);
+
+    if let Some(mut record) = db.gens_members().find_by_character_id(character_id).await? {
+        record.id = None;
+        bundle.gens_members.push(record);
+    }
+    if let Some(mut record) = db.quest_logs().find_by_character_id(character_id).await? {
+        record.id = None;
+        bundle.quest_logs.push(record);
+    }
+    if let Some(mut record) = db.bestiaries().find_by_character_id(character_id).await? {
+        record.id = None;
+        bundle.bestiaries.push(record);
+    }
+    bundle.characters.push(character);
+    Ok(())
+}
+
+/// Writes a bundle into `db`. Nothing is written when a username, character
+/// name or id is already taken, or a character's account is missing.
+pub async fn import_bundle(db: &MongoDbContext, bundle: &PortableBundle) -> Result<ImportSummary> {
+    bundle.validate()?;
+
+    let accounts = db.accounts();
+    let characters = db.characters();
+    for account in &bundle.accounts {
+        let taken = match account.id {
+            Some(id) => accounts.find_by_id(&id).await?.is_some(),
+            None => false,
+        } || accounts
+            .find_by_username(&account.username)
+            .await?
+            .is_some();
+        if taken {
+            return Err(ConnectServerError::InvalidRequest(format!(
+                "account '{}' already exists",
+                account.username
+            )));
+        }
+    }
+    for character in &bundle.characters {
+        if characters.find_by_name(&character.name).await?.is_some() {
+            return Err(ConnectServerError::InvalidRequest(format!(
+                "character '{}' already exists",
+                character.name
+            )));
+        }
+    }
+    for account_id in bundle.external_account_ids() {
+        if accounts.find_by_id(&account_id).await?.is_none() {
+            return Err(ConnectServerError::NotFound(format!(
+                "account {} owning the bundled characters",
+                account_id.to_hex()
+            )));
+        }
+    }
+
+    let mut summary = ImportSummary::default();
+    for account in &bundle.accounts {
+        accounts.insert(account).await?;
+        summary.accounts += 1;
+    }
+    for character in &bundle.characters {
+        characters.insert(character).await?;
+        summary.characters += 1;
+    }
+    let settings = db.account_settings();
+    for record in &bundle.account_settings {
+        settings.save(record).await?;
+        summary.records += 1;
+    }
+    let gens = db.gens_members();
+    for record in &bundle.gens_members {
+        gens.save(record).await?;
+        summary.records += 1;
+    }
+    let quest_logs = db.quest_logs();
+    for record in &bundle.quest_logs {
+        quest_logs.save(record).await?;
+        summary.records += 1;
+    }
+    let bestiaries = db.bestiaries();
+    for record in &bundle.bestiaries {
+        bestiaries.save(record).await?;
+        summary.records += 1;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::GensFaction;
+
+    fn sample_bundle() -> PortableBundle {
+        let mut account = Account::new(
+            "alice".to_string(),
+            "secret",
+            &crate::password::Passwords::default(),
+        )
+        .unwrap();
+        let account_id = ObjectId::new();
+        account.id = Some(account_id);
+        let mut character = Character::new(
+            account_id,
+            "Alicia".to_string(),
+            common::CharacterClass::FairyElf,
+        );
+        let character_id = ObjectId::new();
+        character.id = Some(character_id);
+
+        let mut bundle = PortableBundle::new();
+        bundle.gens_members.push(GensMemberRecord {
+            id: None,
+            character_id: object_id_to_u64(&character_id),
+            faction: GensFaction::Vanert,
+            contribution: 120,
+            joined_at_ms: 5,
+        });
+        bundle.accounts.push(account);
+        bundle.characters.push(character);
+        bundle
+    }
+
+    #[test]
+    fn bundle_round_trips_through_json() {
+        let bundle = sample_bundle();
+        let json = serde_json::to_string_pretty(&bundle).unwrap();
+        let restored: PortableBundle = serde_json::from_str(&json).unwrap();
+
+        assert!(restored.validate().is_ok());
+        assert_eq!(restored.accounts[0].id, bundle.accounts[0].id);
+        assert_eq!(restored.characters[0].id, bundle.characters[0].id);
+        assert_eq!(restored.gens_members[0].contribution, 120);
+        assert!(restored.external_account_ids().is_empty());
+    }
+
+    #[test]
+    fn validation_rejects_orphans_and_foreign_formats() {
+        let mut bundle = sample_bundle();
+        bundle.characters.clear();
+        assert!(matches!(
+            bundle.validate(),
+            Err(ConnectServerError::InvalidRequest(_))
+        ));
+
+        let mut bundle = sample_bundle();
+        bundle.format_version = BUNDLE_FORMAT_VERSION + 1;
+        assert!(bundle.validate().is_err());
+
+        let mut bundle = sample_bundle();
+        let owner = bundle.accounts.remove(0).id.unwrap();
+        assert!(bundle.validate().is_ok());
+        assert_eq!(bundle.external_account_ids(), vec![owner]);
+    }
+}
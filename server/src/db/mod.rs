@@ -1,3 +1,5 @@
+pub mod backup;
+pub mod bundle;
 pub mod models;
 pub mod repository;
 
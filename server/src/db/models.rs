@@ -1,8 +1,21 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
 use chrono::{DateTime, Utc};
+#[cfg(test)]
+use common::CharacterClass;
 use mongodb::bson::oid::ObjectId;
+use protocol::{
+    AccountSettings, EventPhase, GensFaction, GuildRelation, ItemInstance, SequenceEvent,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::error::Result;
+use crate::password::{Passwords, Verification};
+use crate::roles::AccountRole;
+use crate::runtime::cash_shop::CashEntryKind;
+use crate::runtime::item_ledger::{ItemHolder, TransferReason};
+use crate::runtime::mailbox::RewardSource;
+use crate::runtime::quests::QuestProgress;
+use crate::runtime::transfer_limits::TransferChannel;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Account {
@@ -14,12 +27,23 @@ pub struct Account {
     pub email: Option<String>,
     pub created_at: DateTime<Utc>,
     pub last_login: DateTime<Utc>,
+    /// Address of the last login, to tell when one comes from somewhere new.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_login_ip: Option<String>,
+    /// Accounts stored before roles existed load as players.
+    #[serde(default)]
+    pub role: AccountRole,
+    /// Set once the owner entered the code e-mailed at registration.
+    #[serde(default)]
+    pub email_verified: bool,
+    /// Code e-mailed to the owner and not entered yet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_code: Option<AccountCode>,
 }
 
 impl Account {
-    #[cfg(test)]
-    pub fn new(username: String, password: &str) -> Result<Self> {
-        let password_hash = bcrypt::hash(password, bcrypt::DEFAULT_COST)?;
+    pub fn new(username: String, password: &str, passwords: &Passwords) -> Result<Self> {
+        let password_hash = passwords.hash(password)?;
 
         Ok(Self {
             id: None,
@@ -28,11 +52,73 @@ impl Account {
             email: None,
             created_at: Utc::now(),
             last_login: Utc::now(),
+            last_login_ip: None,
+            role: AccountRole::Player,
+            email_verified: false,
+            pending_code: None,
         })
     }
 
-    pub fn verify_password(&self, password: &str) -> Result<bool> {
-        Ok(bcrypt::verify(password, &self.password_hash)?)
+    pub fn verify_password(&self, passwords: &Passwords, password: &str) -> Result<Verification> {
+        passwords.verify(password, &self.password_hash)
+    }
+}
+
+/// What an [`AccountCode`] unlocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CodePurpose {
+    VerifyEmail,
+    PasswordReset,
+}
+
+/// One-time code e-mailed to an account owner. Only its hash is stored, and
+/// a few wrong guesses burn it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountCode {
+    pub purpose: CodePurpose,
+    pub code_hash: String,
+    pub expires_at_ms: u64,
+    pub attempts_left: u8,
+}
+
+impl AccountCode {
+    pub const ATTEMPTS: u8 = 5;
+
+    /// A fresh six digit code for `purpose` and the record to store for it.
+    pub fn issue(
+        purpose: CodePurpose,
+        valid_ms: u64,
+        passwords: &Passwords,
+        now_ms: u64,
+    ) -> Result<(String, Self)> {
+        let code = format!("{:06}", OsRng.next_u32() % 1_000_000);
+        let record = Self {
+            purpose,
+            code_hash: passwords.hash(&code)?,
+            expires_at_ms: now_ms.saturating_add(valid_ms),
+            attempts_left: Self::ATTEMPTS,
+        };
+        Ok((code, record))
+    }
+
+    /// Whether `code` is this code, still valid for `purpose`. A wrong guess
+    /// uses up one attempt.
+    pub fn accept(
+        &mut self,
+        purpose: CodePurpose,
+        code: &str,
+        passwords: &Passwords,
+        now_ms: u64,
+    ) -> Result<bool> {
+        if self.purpose != purpose || self.attempts_left == 0 || now_ms >= self.expires_at_ms {
+            return Ok(false);
+        }
+        let accepted = passwords.verify(code, &self.code_hash)?.is_accepted();
+        if !accepted {
+            self.attempts_left -= 1;
+        }
+        Ok(accepted)
     }
 }
 
@@ -44,30 +130,258 @@ pub struct Character {
     pub name: String,
     pub level: u16,
     pub class: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guild_id: Option<u32>,
     pub created_at: DateTime<Utc>,
 }
 
 impl Character {
     #[cfg(test)]
-    pub fn new(account_id: ObjectId, name: String, class: String) -> Self {
+    pub fn new(account_id: ObjectId, name: String, class: CharacterClass) -> Self {
         Self {
             id: None,
             account_id,
             name,
             level: 1,
-            class,
+            class: class.name().to_string(),
+            guild_id: None,
             created_at: Utc::now(),
         }
     }
 }
 
+/// Alliance or hostility between two guilds, stored with `guild_id < target_guild_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuildRelationRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub guild_id: u32,
+    pub target_guild_id: u32,
+    pub relation: GuildRelation,
+    pub declared_at: DateTime<Utc>,
+}
+
+impl GuildRelationRecord {
+    pub fn new(guild_id: u32, target_guild_id: u32, relation: GuildRelation) -> Self {
+        Self {
+            id: None,
+            guild_id: guild_id.min(target_guild_id),
+            target_guild_id: guild_id.max(target_guild_id),
+            relation,
+            declared_at: Utc::now(),
+        }
+    }
+}
+
+/// Final score of a guild war.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuildWarRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub guild_id: u32,
+    pub target_guild_id: u32,
+    pub guild_kills: u32,
+    pub target_kills: u32,
+    /// `None` on a draw.
+    pub winner_guild_id: Option<u32>,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+}
+
+/// Phase of a sequence event on one world, replaced on every change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SequenceEventRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub world_id: u16,
+    pub event: SequenceEvent,
+    pub cycle: u32,
+    pub phase: EventPhase,
+    pub phase_started_at_ms: u64,
+    pub phase_ends_at_ms: u64,
+    pub last_outcome: Option<bool>,
+}
+
+/// Client settings uploaded by an account, keyed by its protocol account id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountSettingsRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub account_id: u64,
+    pub updated_at_ms: u64,
+    pub yaml: String,
+}
+
+impl AccountSettingsRecord {
+    pub fn new(account_id: u64, settings: AccountSettings) -> Self {
+        Self {
+            id: None,
+            account_id,
+            updated_at_ms: settings.updated_at_ms,
+            yaml: settings.yaml,
+        }
+    }
+
+    pub fn settings(&self) -> AccountSettings {
+        AccountSettings {
+            updated_at_ms: self.updated_at_ms,
+            yaml: self.yaml.clone(),
+        }
+    }
+}
+
+/// One move of a serialized item, appended in the order they happened.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemTransferRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub serial: u64,
+    /// `None` when the item was created.
+    pub from: Option<ItemHolder>,
+    pub to: ItemHolder,
+    pub reason: TransferReason,
+    pub at: DateTime<Utc>,
+    /// Operation the transfer was part of, when it moved with other items.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<u64>,
+}
+
+/// Journal entry of an operation moving several items at once, written before
+/// its transfers and committed once they are all in `item_transfers`. An entry
+/// still uncommitted at startup is replayed, so a crash never leaves half of
+/// an operation written.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemOperationRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub operation_id: u64,
+    pub transfers: Vec<ItemTransferRecord>,
+    pub committed: bool,
+}
+
+/// Gens membership of a character, replaced on every contribution change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GensMemberRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub character_id: u64,
+    pub faction: GensFaction,
+    pub contribution: u32,
+    pub joined_at_ms: u64,
+}
+
+/// Starting kit and tutorial progress of a character, replaced on every change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuestLogRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub character_id: u64,
+    pub kit_granted_at_ms: Option<u64>,
+    pub tutorial: Option<QuestProgress>,
+}
+
+/// Monster kinds a character unlocked in its bestiary, replaced on every unlock.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BestiaryRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub character_id: u64,
+    pub unlocked: Vec<u16>,
+}
+
+/// Zen a character holds, replaced on every change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZenBalanceRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub character_id: u64,
+    pub zen: u64,
+}
+
+/// Reward waiting in a character's mailbox, replaced while it changes and
+/// deleted once claimed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RewardMailRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub mail_id: u64,
+    pub character_id: u64,
+    pub source: RewardSource,
+    pub zen: u64,
+    pub items: Vec<ItemInstance>,
+    pub received_at_ms: u64,
+}
+
+/// Premium-currency balance of an account, replaced after every receipt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CashBalanceRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub account_id: u64,
+    pub balance: u64,
+}
+
+/// Top-up or purchase of premium currency, one per account and idempotency key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CashReceiptRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub account_id: u64,
+    pub idempotency_key: String,
+    pub kind: CashEntryKind,
+    pub amount: u64,
+    pub balance_after: u64,
+    pub at_ms: u64,
+}
+
+/// Zen or items an account moved to another character, kept while inside
+/// the transfer limit window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountTransferRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub account_id: u64,
+    pub channel: TransferChannel,
+    pub zen: u64,
+    pub items: u32,
+    pub at_ms: u64,
+}
+
+/// Where an e-mail of the send queue is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailStatus {
+    Pending,
+    Sent,
+    /// Refused for good or out of attempts; kept for inspection.
+    Failed,
+}
+
+/// Rendered account e-mail, queued until the SMTP server takes it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub to: String,
+    pub template: String,
+    pub subject: String,
+    pub body: String,
+    pub status: EmailStatus,
+    pub attempts: u32,
+    pub queued_at_ms: u64,
+    pub next_attempt_at_ms: u64,
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_account_new() {
-        let account = Account::new("testuser".to_string(), "password123").unwrap();
+        let account =
+            Account::new("testuser".to_string(), "password123", &Passwords::default()).unwrap();
         assert_eq!(account.username, "testuser");
         assert_ne!(account.password_hash, "password123");
         assert!(account.id.is_none());
@@ -75,25 +389,92 @@ mod tests {
 
     #[test]
     fn test_verify_password_correct() {
-        let account = Account::new("testuser".to_string(), "password123").unwrap();
-        assert!(account.verify_password("password123").unwrap());
+        let passwords = Passwords::new(Some(b"pepper".to_vec()));
+        let account = Account::new("testuser".to_string(), "password123", &passwords).unwrap();
+        assert_eq!(
+            account.verify_password(&passwords, "password123").unwrap(),
+            Verification::Accepted
+        );
     }
 
     #[test]
     fn test_verify_password_incorrect() {
-        let account = Account::new("testuser".to_string(), "password123").unwrap();
-        assert!(!account.verify_password("wrongpassword").unwrap());
+        let account =
+            Account::new("testuser".to_string(), "password123", &Passwords::default()).unwrap();
+        assert!(!account
+            .verify_password(&Passwords::default(), "wrongpassword")
+            .unwrap()
+            .is_accepted());
+    }
+
+    #[test]
+    fn account_codes_expire_and_burn_after_wrong_guesses() {
+        let passwords = Passwords::default();
+        let (code, mut pending) =
+            AccountCode::issue(CodePurpose::PasswordReset, 1_000, &passwords, 5_000).unwrap();
+        assert_eq!(code.len(), 6);
+        assert_ne!(pending.code_hash, code);
+
+        let accept = |pending: &mut AccountCode, purpose, code: &str, now_ms| {
+            pending.accept(purpose, code, &passwords, now_ms).unwrap()
+        };
+        assert!(!accept(
+            &mut pending,
+            CodePurpose::VerifyEmail,
+            &code,
+            5_100
+        ));
+        assert!(!accept(
+            &mut pending,
+            CodePurpose::PasswordReset,
+            &code,
+            6_000
+        ));
+        assert!(accept(
+            &mut pending,
+            CodePurpose::PasswordReset,
+            &code,
+            5_100
+        ));
+
+        let wrong = if code == "000000" { "000001" } else { "000000" };
+        for _ in 0..AccountCode::ATTEMPTS {
+            assert!(!accept(
+                &mut pending,
+                CodePurpose::PasswordReset,
+                wrong,
+                5_100
+            ));
+        }
+        assert!(!accept(
+            &mut pending,
+            CodePurpose::PasswordReset,
+            &code,
+            5_100
+        ));
     }
 
     #[test]
     fn test_character_new() {
         let account_id = ObjectId::new();
-        let character =
-            Character::new(account_id, "TestChar".to_string(), "DarkKnight".to_string());
+        let character = Character::new(
+            account_id,
+            "TestChar".to_string(),
+            CharacterClass::DarkKnight,
+        );
 
         assert_eq!(character.name, "TestChar");
         assert_eq!(character.class, "DarkKnight");
         assert_eq!(character.level, 1);
         assert_eq!(character.account_id, account_id);
     }
+
+    #[test]
+    fn test_guild_relation_record_orders_pair() {
+        let record = GuildRelationRecord::new(9, 3, GuildRelation::Hostility);
+
+        assert_eq!(record.guild_id, 3);
+        assert_eq!(record.target_guild_id, 9);
+        assert_eq!(record.relation, GuildRelation::Hostility);
+    }
 }
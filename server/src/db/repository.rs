@@ -2,31 +2,162 @@ use mongodb::{
     bson::{doc, oid::ObjectId, DateTime as BsonDateTime},
     Client, Collection, Database,
 };
+use serde::Deserialize;
 
-use super::models::{Account, Character};
+use super::models::{
+    Account, AccountCode, AccountSettingsRecord, AccountTransferRecord, BestiaryRecord,
+    CashBalanceRecord, CashReceiptRecord, Character, EmailRecord, GensMemberRecord,
+    GuildRelationRecord, GuildWarRecord, ItemOperationRecord, ItemTransferRecord, QuestLogRecord,
+    RewardMailRecord, SequenceEventRecord, ZenBalanceRecord,
+};
 use crate::error::Result;
+use crate::roles::AccountRole;
+use crate::runtime::item_ledger::ItemHolder;
 
 #[derive(Clone)]
 pub struct MongoDbContext {
+    client: Client,
     db: Database,
+    dry_run: bool,
 }
 
 impl MongoDbContext {
     pub fn new(client: Client, database_name: &str) -> Self {
         Self {
             db: client.database(database_name),
+            client,
+            dry_run: false,
         }
     }
 
+    /// Another database on the same server, keeping the dry-run setting.
+    pub fn sibling(&self, database_name: &str) -> Self {
+        Self::new(self.client.clone(), database_name).with_dry_run(self.dry_run)
+    }
+
+    pub fn database_name(&self) -> &str {
+        self.db.name()
+    }
+
+    /// Untyped access for whole-database jobs such as backups.
+    pub fn database(&self) -> &Database {
+        &self.db
+    }
+
+    /// Logs every write instead of applying it. Reads still hit the database.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
     pub fn accounts(&self) -> AccountRepository {
         AccountRepository {
             collection: self.db.collection("accounts"),
+            dry_run: self.dry_run,
         }
     }
 
     pub fn characters(&self) -> CharacterRepository {
         CharacterRepository {
             collection: self.db.collection("characters"),
+            dry_run: self.dry_run,
+        }
+    }
+
+    pub fn guild_relations(&self) -> GuildRelationRepository {
+        GuildRelationRepository {
+            collection: self.db.collection("guild_relations"),
+            dry_run: self.dry_run,
+        }
+    }
+
+    pub fn guild_wars(&self) -> GuildWarRepository {
+        GuildWarRepository {
+            collection: self.db.collection("guild_wars"),
+            dry_run: self.dry_run,
+        }
+    }
+
+    pub fn account_settings(&self) -> AccountSettingsRepository {
+        AccountSettingsRepository {
+            collection: self.db.collection("account_settings"),
+            dry_run: self.dry_run,
+        }
+    }
+
+    pub fn sequence_events(&self) -> SequenceEventRepository {
+        SequenceEventRepository {
+            collection: self.db.collection("sequence_events"),
+            dry_run: self.dry_run,
+        }
+    }
+
+    pub fn item_transfers(&self) -> ItemTransferRepository {
+        ItemTransferRepository {
+            collection: self.db.collection("item_transfers"),
+            operations: self.db.collection("item_operations"),
+            dry_run: self.dry_run,
+        }
+    }
+
+    pub fn gens_members(&self) -> GensMemberRepository {
+        GensMemberRepository {
+            collection: self.db.collection("gens_members"),
+            dry_run: self.dry_run,
+        }
+    }
+
+    pub fn quest_logs(&self) -> QuestLogRepository {
+        QuestLogRepository {
+            collection: self.db.collection("quest_logs"),
+            dry_run: self.dry_run,
+        }
+    }
+
+    pub fn bestiaries(&self) -> BestiaryRepository {
+        BestiaryRepository {
+            collection: self.db.collection("bestiaries"),
+            dry_run: self.dry_run,
+        }
+    }
+
+    pub fn cash_shop(&self) -> CashShopRepository {
+        CashShopRepository {
+            balances: self.db.collection("cash_balances"),
+            receipts: self.db.collection("cash_receipts"),
+            dry_run: self.dry_run,
+        }
+    }
+
+    pub fn email_queue(&self) -> EmailQueueRepository {
+        EmailQueueRepository {
+            collection: self.db.collection("email_queue"),
+            dry_run: self.dry_run,
+        }
+    }
+
+    pub fn zen_balances(&self) -> ZenBalanceRepository {
+        ZenBalanceRepository {
+            collection: self.db.collection("zen_balances"),
+            dry_run: self.dry_run,
+        }
+    }
+
+    pub fn reward_mail(&self) -> RewardMailRepository {
+        RewardMailRepository {
+            collection: self.db.collection("reward_mail"),
+            dry_run: self.dry_run,
+        }
+    }
+
+    pub fn account_transfers(&self) -> AccountTransferRepository {
+        AccountTransferRepository {
+            collection: self.db.collection("account_transfers"),
+            dry_run: self.dry_run,
         }
     }
 
@@ -34,6 +165,11 @@ impl MongoDbContext {
         use mongodb::options::IndexOptions;
         use mongodb::IndexModel;
 
+        if self.dry_run {
+            log::info!("[dry-run] skipping index creation");
+            return Ok(());
+        }
+
         // Create unique index on username
         let username_index = IndexModel::builder()
             .keys(doc! { "username": 1 })
@@ -64,6 +200,174 @@ impl MongoDbContext {
             .create_index(character_name_index)
             .await?;
 
+        // One relation per guild pair
+        let guild_pair_index = IndexModel::builder()
+            .keys(doc! { "guild_id": 1, "target_guild_id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+
+        self.db
+            .collection::<GuildRelationRecord>("guild_relations")
+            .create_index(guild_pair_index)
+            .await?;
+
+        // War history of a guild, on either side
+        for field in ["guild_id", "target_guild_id"] {
+            let guild_war_index = IndexModel::builder().keys(doc! { field: 1 }).build();
+            self.db
+                .collection::<GuildWarRecord>("guild_wars")
+                .create_index(guild_war_index)
+                .await?;
+        }
+
+        // One settings document per account
+        let settings_account_index = IndexModel::builder()
+            .keys(doc! { "account_id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+
+        self.db
+            .collection::<AccountSettingsRecord>("account_settings")
+            .create_index(settings_account_index)
+            .await?;
+
+        // One phase document per world and event
+        let event_world_index = IndexModel::builder()
+            .keys(doc! { "world_id": 1, "event": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+
+        self.db
+            .collection::<SequenceEventRecord>("sequence_events")
+            .create_index(event_world_index)
+            .await?;
+
+        // Ownership trail of an item, oldest first
+        let item_serial_index = IndexModel::builder()
+            .keys(doc! { "serial": 1, "at": 1 })
+            .build();
+
+        self.db
+            .collection::<ItemTransferRecord>("item_transfers")
+            .create_index(item_serial_index)
+            .await?;
+
+        // Transfers of one multi-item operation, replaced when it is replayed
+        let item_operation_transfers_index = IndexModel::builder()
+            .keys(doc! { "operation_id": 1 })
+            .options(IndexOptions::builder().sparse(true).build())
+            .build();
+
+        self.db
+            .collection::<ItemTransferRecord>("item_transfers")
+            .create_index(item_operation_transfers_index)
+            .await?;
+
+        // One journal entry per operation; recovery looks up uncommitted ones
+        let item_operation_index = IndexModel::builder()
+            .keys(doc! { "operation_id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+
+        self.db
+            .collection::<ItemOperationRecord>("item_operations")
+            .create_index(item_operation_index)
+            .await?;
+
+        // One membership per character
+        let gens_character_index = IndexModel::builder()
+            .keys(doc! { "character_id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+
+        self.db
+            .collection::<GensMemberRecord>("gens_members")
+            .create_index(gens_character_index)
+            .await?;
+
+        // One quest log per character
+        let quest_log_character_index = IndexModel::builder()
+            .keys(doc! { "character_id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+
+        self.db
+            .collection::<QuestLogRecord>("quest_logs")
+            .create_index(quest_log_character_index)
+            .await?;
+
+        // One bestiary per character
+        let bestiary_character_index = IndexModel::builder()
+            .keys(doc! { "character_id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+
+        self.db
+            .collection::<BestiaryRecord>("bestiaries")
+            .create_index(bestiary_character_index)
+            .await?;
+
+        // One zen balance per character
+        let zen_balance_index = IndexModel::builder()
+            .keys(doc! { "character_id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+
+        self.db
+            .collection::<ZenBalanceRecord>("zen_balances")
+            .create_index(zen_balance_index)
+            .await?;
+
+        // One document per mail
+        let reward_mail_index = IndexModel::builder()
+            .keys(doc! { "mail_id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+
+        self.db
+            .collection::<RewardMailRecord>("reward_mail")
+            .create_index(reward_mail_index)
+            .await?;
+
+        // One premium balance per account, one receipt per idempotency key
+        let cash_balance_index = IndexModel::builder()
+            .keys(doc! { "account_id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+
+        self.db
+            .collection::<CashBalanceRecord>("cash_balances")
+            .create_index(cash_balance_index)
+            .await?;
+
+        let cash_receipt_index = IndexModel::builder()
+            .keys(doc! { "account_id": 1, "idempotency_key": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+
+        self.db
+            .collection::<CashReceiptRecord>("cash_receipts")
+            .create_index(cash_receipt_index)
+            .await?;
+
+        // Pending e-mails, next due first
+        let email_due_index = IndexModel::builder()
+            .keys(doc! { "status": 1, "next_attempt_at_ms": 1 })
+            .build();
+
+        self.db
+            .collection::<EmailRecord>("email_queue")
+            .create_index(email_due_index)
+            .await?;
+
+        // Transfer limit windows, loaded and pruned by time
+        let account_transfer_index = IndexModel::builder().keys(doc! { "at_ms": 1 }).build();
+
+        self.db
+            .collection::<AccountTransferRecord>("account_transfers")
+            .create_index(account_transfer_index)
+            .await?;
+
         log::info!("Database indexes created successfully");
         Ok(())
     }
@@ -72,6 +376,7 @@ impl MongoDbContext {
 #[derive(Clone)]
 pub struct AccountRepository {
     collection: Collection<Account>,
+    dry_run: bool,
 }
 
 impl AccountRepository {
@@ -83,18 +388,155 @@ impl AccountRepository {
         Ok(account)
     }
 
-    pub async fn update_last_login(&self, id: &ObjectId) -> Result<()> {
+    pub async fn find_by_id(&self, id: &ObjectId) -> Result<Option<Account>> {
+        let account = self.collection.find_one(doc! { "_id": id }).await?;
+        Ok(account)
+    }
+
+    /// Account whose protocol id, the first 8 bytes of its `_id`, is
+    /// `protocol_id`.
+    pub async fn find_by_protocol_id(&self, protocol_id: u64) -> Result<Option<Account>> {
+        let mut first = [0x00; 12];
+        let mut last = [0xff; 12];
+        first[..8].copy_from_slice(&protocol_id.to_be_bytes());
+        last[..8].copy_from_slice(&protocol_id.to_be_bytes());
+        let account = self
+            .collection
+            .find_one(doc! {
+                "_id": {
+                    "$gte": ObjectId::from_bytes(first),
+                    "$lte": ObjectId::from_bytes(last),
+                },
+            })
+            .await?;
+        Ok(account)
+    }
+
+    /// Inserts the account keeping its `_id`, so protocol ids survive an import.
+    pub async fn insert(&self, account: &Account) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("accounts", "insert", &account.username);
+            return Ok(());
+        }
+        self.collection.insert_one(account).await?;
+        Ok(())
+    }
+
+    pub async fn update_last_login(&self, id: &ObjectId, address: Option<&str>) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("accounts", "update last_login", id);
+            return Ok(());
+        }
         let now = BsonDateTime::now();
+        let update = match address {
+            Some(address) => doc! { "$set": { "last_login": now, "last_login_ip": address } },
+            None => doc! { "$set": { "last_login": now } },
+        };
+        self.collection
+            .update_one(doc! { "_id": id }, update)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn update_password_hash(&self, id: &ObjectId, password_hash: &str) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("accounts", "update password_hash", id);
+            return Ok(());
+        }
+        self.collection
+            .update_one(
+                doc! { "_id": id },
+                doc! { "$set": { "password_hash": password_hash } },
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Stores the code e-mailed to the owner, replacing any earlier one.
+    pub async fn set_pending_code(&self, id: &ObjectId, code: &AccountCode) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("accounts", "set pending_code", id);
+            return Ok(());
+        }
+        let code = mongodb::bson::to_bson(code).map_err(mongodb::error::Error::from)?;
         self.collection
-            .update_one(doc! { "_id": id }, doc! { "$set": { "last_login": now } })
+            .update_one(
+                doc! { "_id": id },
+                doc! { "$set": { "pending_code": code } },
+            )
             .await?;
         Ok(())
     }
+
+    /// Stores the attempts left on the pending code after a wrong guess.
+    pub async fn set_code_attempts(&self, id: &ObjectId, attempts_left: u8) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("accounts", "set code attempts", id);
+            return Ok(());
+        }
+        self.collection
+            .update_one(
+                doc! { "_id": id },
+                doc! { "$set": { "pending_code.attempts_left": i32::from(attempts_left) } },
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Marks the e-mail address confirmed and drops the code that did it.
+    pub async fn mark_email_verified(&self, id: &ObjectId) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("accounts", "mark email_verified", id);
+            return Ok(());
+        }
+        self.collection
+            .update_one(
+                doc! { "_id": id },
+                doc! { "$set": { "email_verified": true }, "$unset": { "pending_code": "" } },
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Replaces the password after a reset and drops the code that allowed it.
+    pub async fn reset_password(&self, id: &ObjectId, password_hash: &str) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("accounts", "reset password", id);
+            return Ok(());
+        }
+        self.collection
+            .update_one(
+                doc! { "_id": id },
+                doc! {
+                    "$set": { "password_hash": password_hash },
+                    "$unset": { "pending_code": "" },
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Returns `false` when no account has that username.
+    pub async fn set_role(&self, username: &str, role: AccountRole) -> Result<bool> {
+        if self.dry_run {
+            log_dry_run("accounts", "set role", &username);
+            return Ok(self.find_by_username(username).await?.is_some());
+        }
+        let result = self
+            .collection
+            .update_one(
+                doc! { "username": username },
+                doc! { "$set": { "role": role.name() } },
+            )
+            .await?;
+        Ok(result.matched_count > 0)
+    }
 }
 
 #[derive(Clone)]
 pub struct CharacterRepository {
     collection: Collection<Character>,
+    dry_run: bool,
 }
 
 impl CharacterRepository {
@@ -112,4 +554,654 @@ impl CharacterRepository {
 
         Ok(characters)
     }
+
+    pub async fn find_by_name(&self, name: &str) -> Result<Option<Character>> {
+        let character = self.collection.find_one(doc! { "name": name }).await?;
+        Ok(character)
+    }
+
+    /// Inserts the character keeping its `_id`, so protocol ids survive an import.
+    pub async fn insert(&self, character: &Character) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("characters", "insert", character);
+            return Ok(());
+        }
+        self.collection.insert_one(character).await?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct GuildRelationRepository {
+    collection: Collection<GuildRelationRecord>,
+    dry_run: bool,
+}
+
+impl GuildRelationRepository {
+    pub async fn find_all(&self) -> Result<Vec<GuildRelationRecord>> {
+        let mut cursor = self.collection.find(doc! {}).await?;
+
+        let mut relations = Vec::new();
+        use futures_util::stream::TryStreamExt;
+        while let Some(relation) = cursor.try_next().await? {
+            relations.push(relation);
+        }
+
+        Ok(relations)
+    }
+
+    /// Inserts or replaces the relation for the record's guild pair.
+    pub async fn declare(&self, record: &GuildRelationRecord) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("guild_relations", "replace", record);
+            return Ok(());
+        }
+        self.collection
+            .replace_one(
+                doc! {
+                    "guild_id": record.guild_id,
+                    "target_guild_id": record.target_guild_id,
+                },
+                record,
+            )
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn revoke(&self, guild_id: u32, target_guild_id: u32) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("guild_relations", "delete", &(guild_id, target_guild_id));
+            return Ok(());
+        }
+        self.collection
+            .delete_one(doc! {
+                "guild_id": guild_id.min(target_guild_id),
+                "target_guild_id": guild_id.max(target_guild_id),
+            })
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct GuildWarRepository {
+    collection: Collection<GuildWarRecord>,
+    dry_run: bool,
+}
+
+impl GuildWarRepository {
+    pub async fn insert(&self, record: &GuildWarRecord) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("guild_wars", "insert", record);
+            return Ok(());
+        }
+        self.collection.insert_one(record).await?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct AccountSettingsRepository {
+    collection: Collection<AccountSettingsRecord>,
+    dry_run: bool,
+}
+
+impl AccountSettingsRepository {
+    pub async fn find_all(&self) -> Result<Vec<AccountSettingsRecord>> {
+        let mut cursor = self.collection.find(doc! {}).await?;
+
+        let mut records = Vec::new();
+        use futures_util::stream::TryStreamExt;
+        while let Some(record) = cursor.try_next().await? {
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+
+    pub async fn find_by_account_id(
+        &self,
+        account_id: u64,
+    ) -> Result<Option<AccountSettingsRecord>> {
+        let record = self
+            .collection
+            .find_one(doc! { "account_id": account_id as i64 })
+            .await?;
+        Ok(record)
+    }
+
+    /// Inserts or replaces the settings of the record's account.
+    pub async fn save(&self, record: &AccountSettingsRecord) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("account_settings", "replace", record);
+            return Ok(());
+        }
+        self.collection
+            .replace_one(doc! { "account_id": record.account_id as i64 }, record)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct GensMemberRepository {
+    collection: Collection<GensMemberRecord>,
+    dry_run: bool,
+}
+
+impl GensMemberRepository {
+    pub async fn find_all(&self) -> Result<Vec<GensMemberRecord>> {
+        let mut cursor = self.collection.find(doc! {}).await?;
+
+        let mut records = Vec::new();
+        use futures_util::stream::TryStreamExt;
+        while let Some(record) = cursor.try_next().await? {
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+
+    pub async fn find_by_character_id(
+        &self,
+        character_id: u64,
+    ) -> Result<Option<GensMemberRecord>> {
+        let record = self
+            .collection
+            .find_one(doc! { "character_id": character_id as i64 })
+            .await?;
+        Ok(record)
+    }
+
+    /// Inserts or replaces the membership of the record's character.
+    pub async fn save(&self, record: &GensMemberRecord) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("gens_members", "replace", record);
+            return Ok(());
+        }
+        self.collection
+            .replace_one(doc! { "character_id": record.character_id as i64 }, record)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct QuestLogRepository {
+    collection: Collection<QuestLogRecord>,
+    dry_run: bool,
+}
+
+impl QuestLogRepository {
+    pub async fn find_all(&self) -> Result<Vec<QuestLogRecord>> {
+        let mut cursor = self.collection.find(doc! {}).await?;
+
+        let mut records = Vec::new();
+        use futures_util::stream::TryStreamExt;
+        while let Some(record) = cursor.try_next().await? {
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+
+    pub async fn find_by_character_id(&self, character_id: u64) -> Result<Option<QuestLogRecord>> {
+        let record = self
+            .collection
+            .find_one(doc! { "character_id": character_id as i64 })
+            .await?;
+        Ok(record)
+    }
+
+    /// Inserts or replaces the quest log of the record's character.
+    pub async fn save(&self, record: &QuestLogRecord) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("quest_logs", "replace", record);
+            return Ok(());
+        }
+        self.collection
+            .replace_one(doc! { "character_id": record.character_id as i64 }, record)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct BestiaryRepository {
+    collection: Collection<BestiaryRecord>,
+    dry_run: bool,
+}
+
+impl BestiaryRepository {
+    pub async fn find_all(&self) -> Result<Vec<BestiaryRecord>> {
+        let mut cursor = self.collection.find(doc! {}).await?;
+
+        let mut records = Vec::new();
+        use futures_util::stream::TryStreamExt;
+        while let Some(record) = cursor.try_next().await? {
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+
+    pub async fn find_by_character_id(&self, character_id: u64) -> Result<Option<BestiaryRecord>> {
+        let record = self
+            .collection
+            .find_one(doc! { "character_id": character_id as i64 })
+            .await?;
+        Ok(record)
+    }
+
+    /// Inserts or replaces the bestiary of the record's character.
+    pub async fn save(&self, record: &BestiaryRecord) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("bestiaries", "replace", record);
+            return Ok(());
+        }
+        self.collection
+            .replace_one(doc! { "character_id": record.character_id as i64 }, record)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct ZenBalanceRepository {
+    collection: Collection<ZenBalanceRecord>,
+    dry_run: bool,
+}
+
+impl ZenBalanceRepository {
+    pub async fn find_all(&self) -> Result<Vec<ZenBalanceRecord>> {
+        let mut cursor = self.collection.find(doc! {}).await?;
+
+        let mut records = Vec::new();
+        use futures_util::stream::TryStreamExt;
+        while let Some(record) = cursor.try_next().await? {
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+
+    /// Inserts or replaces the balance of the record's character.
+    pub async fn save(&self, record: &ZenBalanceRecord) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("zen_balances", "replace", record);
+            return Ok(());
+        }
+        self.collection
+            .replace_one(doc! { "character_id": record.character_id as i64 }, record)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct RewardMailRepository {
+    collection: Collection<RewardMailRecord>,
+    dry_run: bool,
+}
+
+impl RewardMailRepository {
+    pub async fn find_all(&self) -> Result<Vec<RewardMailRecord>> {
+        let mut cursor = self.collection.find(doc! {}).await?;
+
+        let mut records = Vec::new();
+        use futures_util::stream::TryStreamExt;
+        while let Some(record) = cursor.try_next().await? {
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+
+    /// Inserts or replaces the record's mail.
+    pub async fn save(&self, record: &RewardMailRecord) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("reward_mail", "replace", record);
+            return Ok(());
+        }
+        self.collection
+            .replace_one(doc! { "mail_id": record.mail_id as i64 }, record)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    /// Deletes a claimed or emptied mail.
+    pub async fn delete(&self, mail_id: u64) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("reward_mail", "delete", &mail_id);
+            return Ok(());
+        }
+        self.collection
+            .delete_one(doc! { "mail_id": mail_id as i64 })
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct CashShopRepository {
+    balances: Collection<CashBalanceRecord>,
+    receipts: Collection<CashReceiptRecord>,
+    dry_run: bool,
+}
+
+impl CashShopRepository {
+    pub async fn find_balances(&self) -> Result<Vec<CashBalanceRecord>> {
+        let mut cursor = self.balances.find(doc! {}).await?;
+
+        let mut records = Vec::new();
+        use futures_util::stream::TryStreamExt;
+        while let Some(record) = cursor.try_next().await? {
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+
+    pub async fn find_receipts(&self) -> Result<Vec<CashReceiptRecord>> {
+        let mut cursor = self.receipts.find(doc! {}).await?;
+
+        let mut records = Vec::new();
+        use futures_util::stream::TryStreamExt;
+        while let Some(record) = cursor.try_next().await? {
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+
+    /// Inserts or replaces the balance of the record's account.
+    pub async fn save_balance(&self, record: &CashBalanceRecord) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("cash_balances", "replace", record);
+            return Ok(());
+        }
+        self.balances
+            .replace_one(doc! { "account_id": record.account_id as i64 }, record)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    /// Inserts the receipt, or replaces it once its delivery is known.
+    pub async fn save_receipt(&self, record: &CashReceiptRecord) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("cash_receipts", "replace", record);
+            return Ok(());
+        }
+        self.receipts
+            .replace_one(
+                doc! {
+                    "account_id": record.account_id as i64,
+                    "idempotency_key": &record.idempotency_key,
+                },
+                record,
+            )
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct SequenceEventRepository {
+    collection: Collection<SequenceEventRecord>,
+    dry_run: bool,
+}
+
+impl SequenceEventRepository {
+    pub async fn find_all(&self) -> Result<Vec<SequenceEventRecord>> {
+        let mut cursor = self.collection.find(doc! {}).await?;
+
+        let mut records = Vec::new();
+        use futures_util::stream::TryStreamExt;
+        while let Some(record) = cursor.try_next().await? {
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+
+    /// Inserts or replaces the phase of the record's world and event.
+    pub async fn save(&self, record: &SequenceEventRecord) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("sequence_events", "replace", record);
+            return Ok(());
+        }
+        let event = mongodb::bson::to_bson(&record.event).map_err(mongodb::error::Error::from)?;
+        self.collection
+            .replace_one(
+                doc! { "world_id": i32::from(record.world_id), "event": event },
+                record,
+            )
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct ItemTransferRepository {
+    collection: Collection<ItemTransferRecord>,
+    operations: Collection<ItemOperationRecord>,
+    dry_run: bool,
+}
+
+impl ItemTransferRepository {
+    pub async fn insert(&self, record: &ItemTransferRecord) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("item_transfers", "insert", record);
+            return Ok(());
+        }
+        self.collection.insert_one(record).await?;
+        Ok(())
+    }
+
+    /// Writes the transfers of a multi-item operation behind a journal entry:
+    /// the entry goes in first, then the transfers replace any earlier partial
+    /// write of the same operation, then the entry is committed. Safe to call
+    /// again for an operation whose write was cut short.
+    pub async fn insert_operation(&self, record: &ItemOperationRecord) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("item_operations", "insert", record);
+            return Ok(());
+        }
+        let operation_id = record.operation_id as i64;
+        self.operations
+            .replace_one(doc! { "operation_id": operation_id }, record)
+            .upsert(true)
+            .await?;
+        self.collection
+            .delete_many(doc! { "operation_id": operation_id })
+            .await?;
+        if !record.transfers.is_empty() {
+            self.collection.insert_many(&record.transfers).await?;
+        }
+        self.operations
+            .update_one(
+                doc! { "operation_id": operation_id },
+                doc! { "$set": { "committed": true } },
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Replays every operation whose write was cut short, so none is left
+    /// half written. Run at startup, before new transfers are saved.
+    /// Returns how many were replayed.
+    pub async fn recover_operations(&self) -> Result<usize> {
+        let mut cursor = self.operations.find(doc! { "committed": false }).await?;
+
+        let mut pending = Vec::new();
+        use futures_util::stream::TryStreamExt;
+        while let Some(record) = cursor.try_next().await? {
+            pending.push(record);
+        }
+
+        for record in &pending {
+            self.insert_operation(record).await?;
+        }
+        Ok(pending.len())
+    }
+
+    /// Holder every item with written transfers ended up with.
+    pub async fn find_holders(&self) -> Result<Vec<(u64, ItemHolder)>> {
+        #[derive(Deserialize)]
+        struct LastHolder {
+            #[serde(rename = "_id")]
+            serial: u64,
+            to: ItemHolder,
+        }
+
+        let mut cursor = self
+            .collection
+            .aggregate([
+                doc! { "$sort": { "at": 1, "_id": 1 } },
+                doc! { "$group": { "_id": "$serial", "to": { "$last": "$to" } } },
+            ])
+            .with_type::<LastHolder>()
+            .await?;
+
+        let mut holders = Vec::new();
+        use futures_util::stream::TryStreamExt;
+        while let Some(last) = cursor.try_next().await? {
+            holders.push((last.serial, last.to));
+        }
+
+        Ok(holders)
+    }
+
+    /// Written transfers of one item, oldest first.
+    pub async fn find_by_serial(&self, serial: u64) -> Result<Vec<ItemTransferRecord>> {
+        let mut cursor = self
+            .collection
+            .find(doc! { "serial": serial as i64 })
+            .sort(doc! { "at": 1, "_id": 1 })
+            .await?;
+
+        let mut records = Vec::new();
+        use futures_util::stream::TryStreamExt;
+        while let Some(record) = cursor.try_next().await? {
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+}
+
+#[derive(Clone)]
+pub struct EmailQueueRepository {
+    collection: Collection<EmailRecord>,
+    dry_run: bool,
+}
+
+impl EmailQueueRepository {
+    pub async fn enqueue(&self, record: &EmailRecord) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("email_queue", "insert", &record.template);
+            return Ok(());
+        }
+        self.collection.insert_one(record).await?;
+        Ok(())
+    }
+
+    /// Pending e-mails whose next attempt is due at `now_ms`, oldest first.
+    pub async fn find_due(&self, now_ms: u64, limit: i64) -> Result<Vec<EmailRecord>> {
+        let mut cursor = self
+            .collection
+            .find(doc! {
+                "status": "pending",
+                "next_attempt_at_ms": { "$lte": now_ms as i64 },
+            })
+            .sort(doc! { "next_attempt_at_ms": 1 })
+            .limit(limit)
+            .await?;
+
+        let mut records = Vec::new();
+        use futures_util::stream::TryStreamExt;
+        while let Some(record) = cursor.try_next().await? {
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+
+    /// Replaces the stored e-mail with the outcome of a send attempt.
+    pub async fn save(&self, record: &EmailRecord) -> Result<()> {
+        let Some(id) = record.id else {
+            return self.enqueue(record).await;
+        };
+        if self.dry_run {
+            log_dry_run("email_queue", "replace", &id);
+            return Ok(());
+        }
+        self.collection
+            .replace_one(doc! { "_id": id }, record)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct AccountTransferRepository {
+    collection: Collection<AccountTransferRecord>,
+    dry_run: bool,
+}
+
+impl AccountTransferRepository {
+    pub async fn insert_many(&self, records: &[AccountTransferRecord]) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        if self.dry_run {
+            log_dry_run("account_transfers", "insert", &records.len());
+            return Ok(());
+        }
+        self.collection.insert_many(records).await?;
+        Ok(())
+    }
+
+    /// Transfers made at or after `since_ms`.
+    pub async fn find_since(&self, since_ms: u64) -> Result<Vec<AccountTransferRecord>> {
+        let mut cursor = self
+            .collection
+            .find(doc! { "at_ms": { "$gte": since_ms as i64 } })
+            .await?;
+
+        let mut records = Vec::new();
+        use futures_util::stream::TryStreamExt;
+        while let Some(record) = cursor.try_next().await? {
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+
+    /// Deletes the transfers made before `before_ms`.
+    pub async fn delete_before(&self, before_ms: u64) -> Result<()> {
+        if self.dry_run {
+            log_dry_run("account_transfers", "delete", &before_ms);
+            return Ok(());
+        }
+        self.collection
+            .delete_many(doc! { "at_ms": { "$lt": before_ms as i64 } })
+            .await?;
+        Ok(())
+    }
+}
+
+/// Logs a write that a dry-run context skipped.
+fn log_dry_run(collection: &str, operation: &str, detail: &impl std::fmt::Debug) {
+    log::info!("[dry-run] {} {}: {:?}", collection, operation, detail);
 }
@@ -0,0 +1,549 @@
+//! Account e-mails sent over SMTP.
+//!
+//! Handlers queue an [`AccountEmail`] with [`EmailNotifier::notify`]. It is
+//! rendered from its template and written to the `email_queue` collection at
+//! once, so nothing queued is lost to a restart. One [`EmailWorker`] sends
+//! what is due, retrying failures with a doubling delay until
+//! `SMTP_MAX_ATTEMPTS` is reached. Without `SMTP_HOST` nothing is queued.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use tokio::sync::Notify;
+
+use crate::auth_token::now_ms;
+use crate::db::models::{EmailRecord, EmailStatus};
+use crate::db::repository::EmailQueueRepository;
+use crate::error::{ConnectServerError, Result};
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 8;
+/// E-mails sent per pass of the worker.
+const BATCH_SIZE: i64 = 50;
+/// How often the worker looks for retries that came due.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+const BASE_RETRY_DELAY: Duration = Duration::from_secs(30);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(3600);
+
+/// How the connection to the SMTP server is secured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with `STARTTLS`, usually on port 587.
+    StartTls,
+    /// TLS from the first byte, usually on port 465.
+    Tls,
+    /// No encryption; only for a relay on the same host or network.
+    None,
+}
+
+impl SmtpSecurity {
+    fn parse(value: &str) -> Result<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "starttls" => Ok(Self::StartTls),
+            "tls" => Ok(Self::Tls),
+            "none" => Ok(Self::None),
+            _ => Err(ConnectServerError::Config(format!(
+                "SMTP_SECURITY must be starttls, tls or none, not '{}'",
+                value
+            ))),
+        }
+    }
+
+    fn default_port(self) -> u16 {
+        match self {
+            Self::StartTls => 587,
+            Self::Tls => 465,
+            Self::None => 25,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EmailConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub credentials: Option<(String, String)>,
+    pub from: Mailbox,
+    pub max_attempts: u32,
+    /// Directory with `<template>.txt` files replacing the built-in ones.
+    pub templates: Option<PathBuf>,
+}
+
+impl EmailConfig {
+    /// Reads the `SMTP_*` variables; `None` when `SMTP_HOST` is unset.
+    pub fn from_vars(vars: impl Fn(&str) -> Option<String>) -> Result<Option<Self>> {
+        let Some(host) = vars("SMTP_HOST") else {
+            return Ok(None);
+        };
+        let security = match vars("SMTP_SECURITY") {
+            Some(value) => SmtpSecurity::parse(&value)?,
+            None => SmtpSecurity::StartTls,
+        };
+        let port = match vars("SMTP_PORT") {
+            Some(value) => value.parse().map_err(|_| {
+                ConnectServerError::Config("SMTP_PORT must be a port number".to_string())
+            })?,
+            None => security.default_port(),
+        };
+        let credentials = match (vars("SMTP_USERNAME"), vars("SMTP_PASSWORD")) {
+            (Some(username), Some(password)) => Some((username, password)),
+            (None, None) => None,
+            _ => {
+                return Err(ConnectServerError::Config(
+                    "Set both SMTP_USERNAME and SMTP_PASSWORD, or neither".to_string(),
+                ))
+            }
+        };
+        let from = vars("SMTP_FROM")
+            .ok_or_else(|| {
+                ConnectServerError::Config("SMTP_FROM is required with SMTP_HOST".to_string())
+            })?
+            .parse()
+            .map_err(|err| ConnectServerError::Config(format!("Invalid SMTP_FROM: {}", err)))?;
+        let max_attempts = match vars("SMTP_MAX_ATTEMPTS") {
+            Some(value) => value.parse().ok().filter(|max| *max > 0).ok_or_else(|| {
+                ConnectServerError::Config(
+                    "SMTP_MAX_ATTEMPTS must be a positive number".to_string(),
+                )
+            })?,
+            None => DEFAULT_MAX_ATTEMPTS,
+        };
+
+        Ok(Some(Self {
+            host,
+            port,
+            security,
+            credentials,
+            from,
+            max_attempts,
+            templates: vars("EMAIL_TEMPLATE_DIR").map(PathBuf::from),
+        }))
+    }
+}
+
+/// Account event an e-mail goes out for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountEmail {
+    /// Code confirming the address given at registration.
+    RegistrationVerification {
+        code: String,
+    },
+    /// Code that lets the owner choose a new password.
+    PasswordReset {
+        code: String,
+        valid_minutes: u32,
+    },
+    /// Login from an address other than the one of the previous login.
+    SuspiciousLogin {
+        address: String,
+        at: DateTime<Utc>,
+    },
+    Banned {
+        reason: String,
+    },
+}
+
+impl AccountEmail {
+    /// Name of the template, also the file name it is overridden with.
+    pub fn template(&self) -> &'static str {
+        match self {
+            Self::RegistrationVerification { .. } => "registration_verification",
+            Self::PasswordReset { .. } => "password_reset",
+            Self::SuspiciousLogin { .. } => "suspicious_login",
+            Self::Banned { .. } => "banned",
+        }
+    }
+
+    /// Values filled into `{{name}}` placeholders, besides `username`.
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Self::RegistrationVerification { code } => vec![("code", code.clone())],
+            Self::PasswordReset {
+                code,
+                valid_minutes,
+            } => vec![
+                ("code", code.clone()),
+                ("valid_minutes", valid_minutes.to_string()),
+            ],
+            Self::SuspiciousLogin { address, at } => vec![
+                ("address", address.clone()),
+                ("at", at.format("%Y-%m-%d %H:%M UTC").to_string()),
+            ],
+            Self::Banned { reason } => vec![("reason", reason.clone())],
+        }
+    }
+
+    fn default_template(&self) -> EmailTemplate {
+        let (subject, body) = match self {
+            Self::RegistrationVerification { .. } => (
+                "Confirm your MU account",
+                "Hello {{username}},\n\n\
+                 Your verification code is {{code}}.\n\n\
+                 If you did not create this account, ignore this e-mail.\n",
+            ),
+            Self::PasswordReset { .. } => (
+                "MU password reset",
+                "Hello {{username}},\n\n\
+                 Your password reset code is {{code}}. It is valid for {{valid_minutes}} minutes.\n\n\
+                 If you did not ask for a reset, your password is unchanged.\n",
+            ),
+            Self::SuspiciousLogin { .. } => (
+                "New login to your MU account",
+                "Hello {{username}},\n\n\
+                 Your account logged in from {{address}} at {{at}}, an address it had not used before.\n\n\
+                 If this was not you, change your password.\n",
+            ),
+            Self::Banned { .. } => (
+                "Your MU account was banned",
+                "Hello {{username}},\n\n\
+                 Your account was banned: {{reason}}\n",
+            ),
+        };
+        EmailTemplate {
+            subject: subject.to_string(),
+            body: body.to_string(),
+        }
+    }
+}
+
+/// Subject and body with `{{name}}` placeholders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailTemplate {
+    pub subject: String,
+    pub body: String,
+}
+
+impl EmailTemplate {
+    /// A `Subject:` line, a blank line and the body.
+    pub fn parse(content: &str) -> Option<Self> {
+        let (first, body) = content.split_once('\n')?;
+        let subject = first.strip_prefix("Subject:")?.trim();
+        Some(Self {
+            subject: subject.to_string(),
+            body: body.strip_prefix('\n').unwrap_or(body).to_string(),
+        })
+    }
+
+    pub fn render(&self, fields: &[(&str, String)]) -> (String, String) {
+        let fill = |text: &str| {
+            fields.iter().fold(text.to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{{{}}}}}", name), value)
+            })
+        };
+        (fill(&self.subject), fill(&self.body))
+    }
+}
+
+/// Templates read from `EMAIL_TEMPLATE_DIR`, falling back to the built-in
+/// ones for files that are not there.
+#[derive(Debug, Clone, Default)]
+struct EmailTemplates {
+    dir: Option<PathBuf>,
+}
+
+impl EmailTemplates {
+    fn template(&self, email: &AccountEmail) -> EmailTemplate {
+        let Some(dir) = &self.dir else {
+            return email.default_template();
+        };
+        let path = dir.join(format!("{}.txt", email.template()));
+        match fs::read_to_string(&path) {
+            Ok(content) => EmailTemplate::parse(&content).unwrap_or_else(|| {
+                log::warn!(
+                    "E-mail template {} has no Subject: line, using the built-in one",
+                    path.display()
+                );
+                email.default_template()
+            }),
+            Err(_) => email.default_template(),
+        }
+    }
+}
+
+struct NotifierInner {
+    queue: EmailQueueRepository,
+    templates: EmailTemplates,
+    wake: Arc<Notify>,
+}
+
+/// Queues account e-mails; does nothing when SMTP is not configured.
+#[derive(Clone, Default)]
+pub struct EmailNotifier {
+    inner: Option<Arc<NotifierInner>>,
+}
+
+impl EmailNotifier {
+    /// The notifier and the worker that sends what it queues.
+    pub fn start(config: &EmailConfig, queue: EmailQueueRepository) -> Result<(Self, EmailWorker)> {
+        if let Some(dir) = &config.templates {
+            if !dir.is_dir() {
+                return Err(ConnectServerError::Config(format!(
+                    "EMAIL_TEMPLATE_DIR {} is not a directory",
+                    dir.display()
+                )));
+            }
+        }
+        let wake = Arc::new(Notify::new());
+        let worker = EmailWorker {
+            transport: smtp_transport(config)?,
+            from: config.from.clone(),
+            queue: queue.clone(),
+            max_attempts: config.max_attempts,
+            wake: wake.clone(),
+        };
+        let notifier = Self {
+            inner: Some(Arc::new(NotifierInner {
+                queue,
+                templates: EmailTemplates {
+                    dir: config.templates.clone(),
+                },
+                wake,
+            })),
+        };
+        Ok((notifier, worker))
+    }
+
+    /// Queues `email` for the account `username` at `to`. An e-mail is never
+    /// worth failing a request over, so a queue that cannot be written is
+    /// only logged.
+    pub async fn notify(&self, to: &str, username: &str, email: &AccountEmail) {
+        let Some(inner) = &self.inner else {
+            return;
+        };
+        let mut fields = email.fields();
+        fields.push(("username", username.to_string()));
+        let (subject, body) = inner.templates.template(email).render(&fields);
+        let now = now_ms();
+        let record = EmailRecord {
+            id: None,
+            to: to.to_string(),
+            template: email.template().to_string(),
+            subject,
+            body,
+            status: EmailStatus::Pending,
+            attempts: 0,
+            queued_at_ms: now,
+            next_attempt_at_ms: now,
+            last_error: None,
+        };
+        match inner.queue.enqueue(&record).await {
+            Ok(()) => inner.wake.notify_one(),
+            Err(err) => log::error!(
+                "Failed to queue {} e-mail for {}: {}",
+                record.template,
+                username,
+                err
+            ),
+        }
+    }
+}
+
+fn smtp_transport(config: &EmailConfig) -> Result<AsyncSmtpTransport<Tokio1Executor>> {
+    let builder = match config.security {
+        SmtpSecurity::StartTls => {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+        }
+        SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host),
+        SmtpSecurity::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+            &config.host,
+        )),
+    }
+    .map_err(|err| ConnectServerError::Config(format!("Invalid SMTP_HOST: {}", err)))?;
+    let builder = builder.port(config.port);
+    let builder = match &config.credentials {
+        Some((username, password)) => {
+            builder.credentials(Credentials::new(username.clone(), password.clone()))
+        }
+        None => builder,
+    };
+    Ok(builder.build())
+}
+
+/// Sends queued e-mails until the server stops.
+pub struct EmailWorker {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    queue: EmailQueueRepository,
+    max_attempts: u32,
+    wake: Arc<Notify>,
+}
+
+impl EmailWorker {
+    /// Sends what is due, then waits for a new e-mail or the next poll. The
+    /// first pass picks up whatever a previous run left in the queue.
+    pub async fn run(self) {
+        loop {
+            if let Err(err) = self.send_due().await {
+                log::warn!("E-mail queue could not be read: {}", err);
+            }
+            tokio::select! {
+                _ = self.wake.notified() => {}
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+        }
+    }
+
+    async fn send_due(&self) -> Result<()> {
+        loop {
+            let due = self.queue.find_due(now_ms(), BATCH_SIZE).await?;
+            let batch = due.len();
+            for mut record in due {
+                self.deliver(&mut record).await;
+                self.queue.save(&record).await?;
+            }
+            if batch < BATCH_SIZE as usize {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn deliver(&self, record: &mut EmailRecord) {
+        record.attempts += 1;
+        let message = match self.message(record) {
+            Ok(message) => message,
+            Err(err) => {
+                log::warn!(
+                    "Dropping {} e-mail to {}: {}",
+                    record.template,
+                    record.to,
+                    err
+                );
+                record.status = EmailStatus::Failed;
+                record.last_error = Some(err);
+                return;
+            }
+        };
+        let err = match self.transport.send(message).await {
+            Ok(_) => {
+                record.status = EmailStatus::Sent;
+                record.last_error = None;
+                return;
+            }
+            Err(err) => err,
+        };
+
+        record.last_error = Some(err.to_string());
+        if err.is_permanent() || record.attempts >= self.max_attempts {
+            log::error!(
+                "Gave up on {} e-mail to {} after {} attempts: {}",
+                record.template,
+                record.to,
+                record.attempts,
+                err
+            );
+            record.status = EmailStatus::Failed;
+        } else {
+            log::warn!(
+                "Sending {} e-mail to {} failed, retrying: {}",
+                record.template,
+                record.to,
+                err
+            );
+            record.next_attempt_at_ms =
+                now_ms() + retry_delay(record.attempts - 1).as_millis() as u64;
+        }
+    }
+
+    fn message(&self, record: &EmailRecord) -> std::result::Result<Message, String> {
+        let to: Mailbox = record
+            .to
+            .parse()
+            .map_err(|err| format!("invalid address: {}", err))?;
+        Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(record.subject.clone())
+            .header(ContentType::TEXT_PLAIN)
+            .body(record.body.clone())
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// Wait before retry `attempt` (0-based), doubling up to an hour.
+fn retry_delay(attempt: u32) -> Duration {
+    BASE_RETRY_DELAY
+        .saturating_mul(1 << attempt.min(16))
+        .min(MAX_RETRY_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let map: HashMap<String, String> = pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        move |name| map.get(name).cloned()
+    }
+
+    #[test]
+    fn smtp_is_optional_and_validated() {
+        assert!(EmailConfig::from_vars(vars(&[])).unwrap().is_none());
+
+        let config = EmailConfig::from_vars(vars(&[
+            ("SMTP_HOST", "smtp.example.com"),
+            ("SMTP_SECURITY", "tls"),
+            ("SMTP_FROM", "MU <noreply@example.com>"),
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(config.port, 465);
+        assert_eq!(config.security, SmtpSecurity::Tls);
+        assert_eq!(config.from.email.to_string(), "noreply@example.com");
+        assert_eq!(config.max_attempts, DEFAULT_MAX_ATTEMPTS);
+        assert!(config.credentials.is_none());
+
+        assert!(EmailConfig::from_vars(vars(&[("SMTP_HOST", "smtp.example.com")])).is_err());
+        assert!(EmailConfig::from_vars(vars(&[
+            ("SMTP_HOST", "smtp.example.com"),
+            ("SMTP_FROM", "noreply@example.com"),
+            ("SMTP_USERNAME", "mu"),
+        ]))
+        .is_err());
+    }
+
+    #[test]
+    fn templates_fill_placeholders() {
+        let email = AccountEmail::PasswordReset {
+            code: "482913".to_string(),
+            valid_minutes: 15,
+        };
+        let mut fields = email.fields();
+        fields.push(("username", "elfa".to_string()));
+        let (subject, body) = email.default_template().render(&fields);
+        assert_eq!(subject, "MU password reset");
+        assert!(body.starts_with("Hello elfa,"));
+        assert!(body.contains("482913. It is valid for 15 minutes"));
+        assert!(!body.contains("{{"));
+
+        let custom =
+            EmailTemplate::parse("Subject: Banido: {{username}}\n\nMotivo: {{reason}}\n").unwrap();
+        let banned = AccountEmail::Banned {
+            reason: "dupe".to_string(),
+        };
+        let mut fields = banned.fields();
+        fields.push(("username", "elfa".to_string()));
+        assert_eq!(
+            custom.render(&fields),
+            ("Banido: elfa".to_string(), "Motivo: dupe\n".to_string())
+        );
+        assert!(EmailTemplate::parse("Motivo: {{reason}}\n").is_none());
+    }
+
+    #[test]
+    fn retries_back_off_up_to_an_hour() {
+        assert_eq!(retry_delay(0), Duration::from_secs(30));
+        assert_eq!(retry_delay(2), Duration::from_secs(120));
+        assert_eq!(retry_delay(20), MAX_RETRY_DELAY);
+    }
+}
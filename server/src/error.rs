@@ -1,5 +1,9 @@
+use std::time::Duration;
+
 use actix_web::{error::ResponseError, http::StatusCode, HttpResponse};
+use protocol::{ServerError, ServerErrorKind};
 use serde::Serialize;
+use utoipa::ToSchema;
 
 #[derive(Debug, thiserror::Error)]
 pub enum ConnectServerError {
@@ -7,7 +11,7 @@ pub enum ConnectServerError {
     Database(#[from] mongodb::error::Error),
 
     #[error("Password hashing error: {0}")]
-    PasswordHash(#[from] bcrypt::BcryptError),
+    PasswordHash(String),
 
     #[error("Invalid credentials")]
     InvalidCredentials,
@@ -15,20 +19,77 @@ pub enum ConnectServerError {
     #[error("Session not found or expired")]
     InvalidSession,
 
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Too many requests")]
+    RateLimited { retry_after: Duration },
+
     #[error("Configuration error: {0}")]
     Config(String),
 
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
+    #[error("Backup storage error: {0}")]
+    Storage(String),
+
     #[error("Internal server error")]
     Internal(String),
 }
 
-#[derive(Serialize)]
-struct ErrorResponse {
+impl ConnectServerError {
+    /// Kind shared with game server errors; its code goes out in the body.
+    pub fn kind(&self) -> ServerErrorKind {
+        match self {
+            ConnectServerError::InvalidCredentials => ServerErrorKind::InvalidCredentials,
+            ConnectServerError::InvalidSession | ConnectServerError::Unauthorized(_) => {
+                ServerErrorKind::InvalidSession
+            }
+            ConnectServerError::Forbidden(_) => ServerErrorKind::PermissionDenied,
+            ConnectServerError::InvalidRequest(_) | ConnectServerError::Serialization(_) => {
+                ServerErrorKind::InvalidRequest
+            }
+            ConnectServerError::NotFound(_) => ServerErrorKind::NotFound,
+            ConnectServerError::RateLimited { .. } => ServerErrorKind::RateLimited,
+            ConnectServerError::Database(_)
+            | ConnectServerError::PasswordHash(_)
+            | ConnectServerError::Config(_)
+            | ConnectServerError::Storage(_)
+            | ConnectServerError::Internal(_) => ServerErrorKind::Internal,
+        }
+    }
+
+    /// Payload shared with game server errors.
+    pub fn server_error(&self) -> ServerError {
+        let error = ServerError::new(self.kind(), self.to_string());
+        match self {
+            ConnectServerError::RateLimited { retry_after } => {
+                error.retry_after(retry_after.as_millis() as u64)
+            }
+            _ => error,
+        }
+    }
+}
+
+/// Body of every error response.
+#[derive(Serialize, ToSchema)]
+pub struct ErrorResponse {
     success: bool,
+    code: u16,
     error: String,
+    retryable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_after_ms: Option<u32>,
 }
 
 impl ResponseError for ConnectServerError {
@@ -36,22 +97,34 @@ impl ResponseError for ConnectServerError {
         match self {
             ConnectServerError::InvalidCredentials => StatusCode::UNAUTHORIZED,
             ConnectServerError::InvalidSession => StatusCode::UNAUTHORIZED,
+            ConnectServerError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ConnectServerError::Forbidden(_) => StatusCode::FORBIDDEN,
             ConnectServerError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
             ConnectServerError::PasswordHash(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ConnectServerError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            ConnectServerError::NotFound(_) => StatusCode::NOT_FOUND,
+            ConnectServerError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
             ConnectServerError::Config(_) => StatusCode::INTERNAL_SERVER_ERROR,
             ConnectServerError::Serialization(_) => StatusCode::BAD_REQUEST,
+            ConnectServerError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
             ConnectServerError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
         let status = self.status_code();
-        let error_response = ErrorResponse {
+        let error = self.server_error();
+        let mut response = HttpResponse::build(status);
+        if let Some(retry_after_ms) = error.retry_after_ms {
+            response.insert_header(("Retry-After", retry_after_ms.div_ceil(1000).to_string()));
+        }
+        response.json(ErrorResponse {
             success: false,
-            error: self.to_string(),
-        };
-
-        HttpResponse::build(status).json(error_response)
+            code: error.code(),
+            error: error.message,
+            retryable: error.retryable,
+            retry_after_ms: error.retry_after_ms,
+        })
     }
 }
 
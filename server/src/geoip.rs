@@ -0,0 +1,204 @@
+//! Client location from a GeoIP table, for the proximity hints of `/servers`.
+//!
+//! The table is a CSV of `network,region,latitude,longitude` rows, with
+//! networks in CIDR form (`177.0.0.0/8`, `2804::/16`); lines starting with `#`
+//! are comments. Exports of the free GeoLite2 or IP2Location LITE databases
+//! reduce to it with a join on their locations file. Latency is only an
+//! estimate from the great-circle distance; the client should still measure
+//! before it connects.
+
+use std::fs;
+use std::net::{IpAddr, Ipv6Addr};
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::config::GeoIpConfig;
+use crate::error::{ConnectServerError, Result};
+
+/// Round trip every connection pays, whatever the distance.
+const BASE_LATENCY_MS: f64 = 5.0;
+/// Distance per millisecond of round trip: light in fiber covers about
+/// 100 km there and back per ms, and routes are rarely straight.
+const KM_PER_RTT_MS: f64 = 70.0;
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// Point on the globe, in degrees.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct GeoPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl GeoPoint {
+    /// Great-circle distance in kilometers.
+    pub fn distance_km(&self, other: &GeoPoint) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.longitude - self.longitude).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+
+    /// Estimated round trip to `other`, in milliseconds.
+    pub fn latency_hint_ms(&self, other: &GeoPoint) -> u32 {
+        (BASE_LATENCY_MS + self.distance_km(other) / KM_PER_RTT_MS).round() as u32
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoLocation {
+    pub region: String,
+    pub point: GeoPoint,
+}
+
+/// Networks sorted by first address; IPv4 is stored IPv6-mapped so both
+/// families share one table.
+#[derive(Debug, Clone, Default)]
+pub struct GeoIpDatabase {
+    ranges: Vec<(u128, u128, GeoLocation)>,
+}
+
+impl GeoIpDatabase {
+    /// The database to use, or `None` when the feature is off.
+    pub fn from_config(config: &GeoIpConfig) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let path = config.database.as_ref().ok_or_else(|| {
+            ConnectServerError::Config("GeoIP is enabled but no database is set".to_string())
+        })?;
+        Self::load_from_file(path).map(Some)
+    }
+
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            ConnectServerError::Config(format!("Failed to read GeoIP database: {}", e))
+        })?;
+        Self::parse(&content)
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut ranges = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let row = parse_row(line).ok_or_else(|| {
+                ConnectServerError::Config(format!(
+                    "Invalid GeoIP row on line {}: {}",
+                    index + 1,
+                    line
+                ))
+            })?;
+            ranges.push(row);
+        }
+        ranges.sort_by_key(|(first, _, _)| *first);
+        Ok(Self { ranges })
+    }
+
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Location of the narrowest network holding `ip`.
+    pub fn lookup(&self, ip: IpAddr) -> Option<&GeoLocation> {
+        let address = mapped(ip);
+        let candidates = self
+            .ranges
+            .partition_point(|(first, _, _)| *first <= address);
+        self.ranges[..candidates]
+            .iter()
+            .filter(|(_, last, _)| address <= *last)
+            .min_by_key(|(first, last, _)| last - first)
+            .map(|(_, _, location)| location)
+    }
+}
+
+fn mapped(ip: IpAddr) -> u128 {
+    let v6: Ipv6Addr = match ip {
+        IpAddr::V4(v4) => v4.to_ipv6_mapped(),
+        IpAddr::V6(v6) => v6,
+    };
+    u128::from(v6)
+}
+
+fn parse_row(line: &str) -> Option<(u128, u128, GeoLocation)> {
+    let mut fields = line.split(',').map(str::trim);
+    let (first, last) = parse_network(fields.next()?)?;
+    let region = fields.next().filter(|region| !region.is_empty())?;
+    let point = GeoPoint {
+        latitude: fields.next()?.parse().ok()?,
+        longitude: fields.next()?.parse().ok()?,
+    };
+    if fields.next().is_some() {
+        return None;
+    }
+    let location = GeoLocation {
+        region: region.to_string(),
+        point,
+    };
+    Some((first, last, location))
+}
+
+/// First and last address of a CIDR network.
+fn parse_network(network: &str) -> Option<(u128, u128)> {
+    let (address, prefix) = network.split_once('/')?;
+    let address: IpAddr = address.parse().ok()?;
+    let prefix: u32 = prefix.parse().ok()?;
+    let prefix = match address {
+        IpAddr::V4(_) if prefix <= 32 => prefix + 96,
+        IpAddr::V6(_) if prefix <= 128 => prefix,
+        _ => return None,
+    };
+    let host_mask = u128::MAX.checked_shr(prefix).unwrap_or(0);
+    let first = mapped(address) & !host_mask;
+    Some((first, first | host_mask))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &str = "\
+# network,region,latitude,longitude
+177.0.0.0/8,sa-east,-23.55,-46.63
+177.10.0.0/16,sa-south,-30.03,-51.23
+2804::/16,sa-east,-23.55,-46.63
+";
+
+    #[test]
+    fn lookups_pick_the_narrowest_network() {
+        let db = GeoIpDatabase::parse(TABLE).unwrap();
+        assert_eq!(db.len(), 3);
+        let region = |ip: &str| db.lookup(ip.parse().unwrap()).map(|at| at.region.as_str());
+        assert_eq!(region("177.1.2.3"), Some("sa-east"));
+        assert_eq!(region("177.10.200.1"), Some("sa-south"));
+        assert_eq!(region("2804:14c::1"), Some("sa-east"));
+        assert_eq!(region("8.8.8.8"), None);
+
+        assert!(GeoIpDatabase::parse("177.0.0.0/33,sa-east,0,0").is_err());
+        assert!(GeoIpDatabase::parse("177.0.0.0/8,sa-east,north,0").is_err());
+    }
+
+    #[test]
+    fn latency_grows_with_distance() {
+        let sao_paulo = GeoPoint {
+            latitude: -23.55,
+            longitude: -46.63,
+        };
+        let frankfurt = GeoPoint {
+            latitude: 50.11,
+            longitude: 8.68,
+        };
+        let distance = sao_paulo.distance_km(&frankfurt);
+        assert!((9_700.0..9_900.0).contains(&distance), "{distance}");
+        assert_eq!(sao_paulo.latency_hint_ms(&sao_paulo), 5);
+        assert_eq!(sao_paulo.latency_hint_ms(&frankfurt), 145);
+    }
+}
@@ -0,0 +1,1119 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::{delete, get, post, put, web, HttpResponse};
+use protocol::{
+    DisconnectReason, DoorState, GuildRelation, ItemInstance, MapTransferDirective, RouteKey,
+    SequenceEvent,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+use crate::{
+    auth_token::now_ms,
+    db::{
+        backup::{BackupRun, BackupScheduler, BackupStatus, VerificationRun},
+        models::GuildRelationRecord,
+        MongoDbContext,
+    },
+    email::{AccountEmail, EmailNotifier},
+    error::{ConnectServerError, ErrorResponse, Result},
+    openapi::ADMIN_ROUTES,
+    roles::AccountRole,
+    runtime::doors::{DoorError, MapDoor},
+    runtime::doppelganger::{DoppelgangerError, DoppelgangerRun, PartyMember},
+    runtime::events::{EventError, EventState},
+    runtime::guild_wars::GuildWar,
+    runtime::helper::HelperActivity,
+    runtime::item_ledger::{DupeReport, ItemHolder, ItemTransfer},
+    runtime::mailbox::{RewardBundle, RewardDelivery, RewardSource},
+    runtime::maintenance::{
+        MaintenanceScope, MaintenanceWindow, DEFAULT_COUNTDOWN_SECS, DEFAULT_FALLBACK_MAP_ID,
+    },
+    runtime::restart::PlannedRestart,
+    runtime::session_links::SessionControlError,
+    runtime::stress::{StressError, StressReport},
+    runtime::transfer_limits::TransferLimitReport,
+    runtime::MuCoreRuntime,
+};
+
+use super::runtime::runtime_ref;
+
+fn default_countdown_secs() -> u32 {
+    DEFAULT_COUNTDOWN_SECS
+}
+
+fn default_fallback_map_id() -> u16 {
+    DEFAULT_FALLBACK_MAP_ID
+}
+
+/// Routes of this module, mounted under `/admin`.
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        list_maintenance,
+        begin_maintenance,
+        end_maintenance,
+        planned_restart,
+        schedule_restart,
+        cancel_restart,
+        grant_reward,
+        declare_guild_relation,
+        revoke_guild_relation,
+        list_guild_wars,
+        start_guild_war,
+        list_helper_sessions,
+        list_sequence_events,
+        resolve_sequence_event,
+        list_doppelganger_runs,
+        start_doppelganger_run,
+        list_stress_runs,
+        start_stress_run,
+        list_doors,
+        set_door,
+        kick_session,
+        transfer_session,
+        item_dupe_report,
+        list_item_transfers,
+        transfer_limit_report,
+        backup_status,
+        start_backup,
+        verify_backup,
+        set_account_role,
+    ),
+    modifiers(&ADMIN_ROUTES)
+)]
+pub(crate) struct AdminApi;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BeginMaintenanceRequest {
+    pub world_id: u16,
+    /// Closes a single map when set, the whole world otherwise.
+    #[serde(default)]
+    pub map_id: Option<u16>,
+    #[serde(default = "default_countdown_secs")]
+    #[schema(default = default_countdown_secs)]
+    pub countdown_secs: u32,
+    #[serde(default = "default_fallback_map_id")]
+    #[schema(default = default_fallback_map_id)]
+    pub fallback_map_id: u16,
+    #[serde(default)]
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct EndMaintenanceRequest {
+    pub world_id: u16,
+    #[serde(default)]
+    pub map_id: Option<u16>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MaintenanceResponse {
+    pub window: MaintenanceWindow,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MaintenanceListResponse {
+    pub windows: Vec<MaintenanceWindow>,
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Open maintenance windows",
+    responses(
+        (status = 200, description = "Maintenance windows", body = MaintenanceListResponse),
+    ),
+)]
+#[get("/maintenance")]
+pub async fn list_maintenance(
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let windows = runtime.maintenance().windows();
+    Ok(HttpResponse::Ok().json(MaintenanceListResponse { windows }))
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Start a maintenance countdown for a world or map",
+    request_body = BeginMaintenanceRequest,
+    responses(
+        (status = 200, description = "Window opened", body = MaintenanceResponse),
+        (status = 400, description = "Scope unknown or already under maintenance", body = ErrorResponse),
+    ),
+)]
+#[post("/maintenance")]
+pub async fn begin_maintenance(
+    req: web::Json<BeginMaintenanceRequest>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let req = req.into_inner();
+    let scope = MaintenanceScope {
+        world_id: req.world_id,
+        map_id: req.map_id,
+    };
+
+    let window = runtime
+        .begin_maintenance(
+            scope,
+            req.reason,
+            req.countdown_secs,
+            req.fallback_map_id,
+            now_ms(),
+        )
+        .await
+        .map_err(|err| ConnectServerError::InvalidRequest(err.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(MaintenanceResponse { window }))
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "End maintenance and reopen the scope",
+    request_body = EndMaintenanceRequest,
+    responses(
+        (status = 200, description = "Window that was closed", body = MaintenanceResponse),
+        (status = 404, description = "No maintenance for the scope", body = ErrorResponse),
+    ),
+)]
+#[delete("/maintenance")]
+pub async fn end_maintenance(
+    req: web::Json<EndMaintenanceRequest>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let scope = MaintenanceScope {
+        world_id: req.world_id,
+        map_id: req.map_id,
+    };
+
+    let window = runtime
+        .end_maintenance(scope)
+        .ok_or_else(|| ConnectServerError::NotFound(format!("no maintenance for {}", scope)))?;
+
+    Ok(HttpResponse::Ok().json(MaintenanceResponse { window }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ScheduleRestartRequest {
+    pub restart_at_ms: u64,
+    #[serde(default)]
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RestartResponse {
+    pub restart: Option<PlannedRestart>,
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Restart planned for the server",
+    responses(
+        (status = 200, description = "Planned restart, if any", body = RestartResponse),
+    ),
+)]
+#[get("/restart")]
+pub async fn planned_restart(
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let restart = runtime.restart().planned();
+    Ok(HttpResponse::Ok().json(RestartResponse { restart }))
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Plan a restart, warning players as it comes closer",
+    request_body = ScheduleRestartRequest,
+    responses(
+        (status = 200, description = "Restart planned", body = RestartResponse),
+        (status = 400, description = "Time already passed or a restart is already planned", body = ErrorResponse),
+    ),
+)]
+#[post("/restart")]
+pub async fn schedule_restart(
+    req: web::Json<ScheduleRestartRequest>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let req = req.into_inner();
+
+    let restart = runtime
+        .schedule_restart(req.restart_at_ms, req.reason, now_ms())
+        .map_err(|err| ConnectServerError::InvalidRequest(err.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(RestartResponse {
+        restart: Some(restart),
+    }))
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Call off the planned restart",
+    responses(
+        (status = 200, description = "Restart that was called off", body = RestartResponse),
+        (status = 404, description = "No restart planned, or it already started", body = ErrorResponse),
+    ),
+)]
+#[delete("/restart")]
+pub async fn cancel_restart(
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let restart = runtime
+        .cancel_restart()
+        .ok_or_else(|| ConnectServerError::NotFound("no restart to call off".to_string()))?;
+
+    Ok(HttpResponse::Ok().json(RestartResponse {
+        restart: Some(restart),
+    }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct GrantRewardRequest {
+    pub character_id: u64,
+    pub source: RewardSource,
+    #[serde(default)]
+    pub zen: u64,
+    #[serde(default)]
+    pub items: Vec<ItemInstance>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GrantRewardResponse {
+    pub delivery: RewardDelivery,
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Grant an event reward, mailed when it does not fit",
+    request_body = GrantRewardRequest,
+    responses(
+        (status = 200, description = "How the reward was delivered", body = GrantRewardResponse),
+        (status = 400, description = "Invalid items or undeliverable reward", body = ErrorResponse),
+    ),
+)]
+#[post("/rewards")]
+pub async fn grant_reward(
+    req: web::Json<GrantRewardRequest>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let req = req.into_inner();
+    let route = runtime
+        .route_of_character(req.character_id)
+        .unwrap_or(RouteKey::LOBBY);
+
+    let delivery = runtime
+        .grant_event_reward(
+            req.character_id,
+            route,
+            req.source,
+            RewardBundle {
+                zen: req.zen,
+                items: req.items,
+            },
+            now_ms(),
+        )
+        .await
+        .map_err(|err| ConnectServerError::InvalidRequest(err.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(GrantRewardResponse { delivery }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DeclareGuildRelationRequest {
+    pub guild_id: u32,
+    pub target_guild_id: u32,
+    pub relation: GuildRelation,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RevokeGuildRelationRequest {
+    pub guild_id: u32,
+    pub target_guild_id: u32,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GuildRelationResponse {
+    pub guild_id: u32,
+    pub target_guild_id: u32,
+    pub relation: Option<GuildRelation>,
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Declare an alliance or hostility between two guilds",
+    request_body = DeclareGuildRelationRequest,
+    responses(
+        (status = 200, description = "Relation stored", body = GuildRelationResponse),
+        (status = 400, description = "Invalid guild pair", body = ErrorResponse),
+    ),
+)]
+#[post("/guild-relations")]
+pub async fn declare_guild_relation(
+    req: web::Json<DeclareGuildRelationRequest>,
+    db: web::Data<MongoDbContext>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+
+    // Validate against the in-memory table first so MongoDB never stores a bad pair.
+    runtime
+        .guild_relations()
+        .declare(req.guild_id, req.target_guild_id, req.relation)
+        .map_err(|err| ConnectServerError::InvalidRequest(err.to_string()))?;
+    db.guild_relations()
+        .declare(&GuildRelationRecord::new(
+            req.guild_id,
+            req.target_guild_id,
+            req.relation,
+        ))
+        .await?;
+
+    Ok(HttpResponse::Ok().json(GuildRelationResponse {
+        guild_id: req.guild_id,
+        target_guild_id: req.target_guild_id,
+        relation: Some(req.relation),
+    }))
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Revoke the relation between two guilds",
+    request_body = RevokeGuildRelationRequest,
+    responses(
+        (status = 200, description = "Relation removed; `relation` is null", body = GuildRelationResponse),
+    ),
+)]
+#[delete("/guild-relations")]
+pub async fn revoke_guild_relation(
+    req: web::Json<RevokeGuildRelationRequest>,
+    db: web::Data<MongoDbContext>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+
+    db.guild_relations()
+        .revoke(req.guild_id, req.target_guild_id)
+        .await?;
+    runtime
+        .guild_relations()
+        .revoke(req.guild_id, req.target_guild_id);
+
+    Ok(HttpResponse::Ok().json(GuildRelationResponse {
+        guild_id: req.guild_id,
+        target_guild_id: req.target_guild_id,
+        relation: None,
+    }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct StartGuildWarRequest {
+    pub guild_id: u32,
+    pub target_guild_id: u32,
+    // Mirrors `MIN_WAR_DURATION` and `MAX_WAR_DURATION`; openapi.rs tests check it.
+    #[schema(minimum = 60, maximum = 7200)]
+    pub duration_secs: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GuildWarResponse {
+    pub war: GuildWar,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GuildWarListResponse {
+    pub wars: Vec<GuildWar>,
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Guild wars in progress with their kill counts",
+    responses(
+        (status = 200, description = "Active wars", body = GuildWarListResponse),
+    ),
+)]
+#[get("/guild-wars")]
+pub async fn list_guild_wars(
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let wars = runtime.guild_wars().active();
+    Ok(HttpResponse::Ok().json(GuildWarListResponse { wars }))
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Start a timed guild war scored by kills between the two guilds",
+    request_body = StartGuildWarRequest,
+    responses(
+        (status = 200, description = "War started", body = GuildWarResponse),
+        (status = 400, description = "Same or allied guilds, a guild already at war, or duration out of range", body = ErrorResponse),
+    ),
+)]
+#[post("/guild-wars")]
+pub async fn start_guild_war(
+    req: web::Json<StartGuildWarRequest>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let war = runtime
+        .start_guild_war(
+            req.guild_id,
+            req.target_guild_id,
+            Duration::from_secs(req.duration_secs),
+            now_ms(),
+        )
+        .map_err(|err| ConnectServerError::InvalidRequest(err.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(GuildWarResponse { war }))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HelperSessionListResponse {
+    pub sessions: Vec<HelperActivity>,
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Sessions running the MU Helper (auto-hunt)",
+    responses(
+        (status = 200, description = "Marked sessions with where and since when", body = HelperSessionListResponse),
+    ),
+)]
+#[get("/helper-sessions")]
+pub async fn list_helper_sessions(
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let sessions = runtime.helpers().list();
+    Ok(HttpResponse::Ok().json(HelperSessionListResponse { sessions }))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SequenceEventListResponse {
+    pub events: Vec<EventState>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ResolveSequenceEventRequest {
+    pub world_id: u16,
+    pub event: SequenceEvent,
+    /// Whether the players met the objective of the current phase.
+    pub won: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SequenceEventResponse {
+    pub event: EventState,
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Crywolf and Kanturu phase and gate of every world",
+    responses(
+        (status = 200, description = "Current cycle of each event", body = SequenceEventListResponse),
+    ),
+)]
+#[get("/events")]
+pub async fn list_sequence_events(
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let events = runtime.sequence_events().states();
+    Ok(HttpResponse::Ok().json(SequenceEventListResponse { events }))
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Settle the objective of an event's current phase",
+    request_body = ResolveSequenceEventRequest,
+    responses(
+        (status = 200, description = "Phase after the win moved on or the loss closed the cycle", body = SequenceEventResponse),
+        (status = 400, description = "The current phase has no objective", body = ErrorResponse),
+        (status = 404, description = "Unknown world", body = ErrorResponse),
+    ),
+)]
+#[post("/events")]
+pub async fn resolve_sequence_event(
+    req: web::Json<ResolveSequenceEventRequest>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let event = runtime
+        .resolve_event_phase(req.world_id, req.event, req.won, now_ms())
+        .map_err(|err| match err {
+            EventError::UnknownWorld(_) => ConnectServerError::NotFound(err.to_string()),
+            EventError::NoObjective { .. } => ConnectServerError::InvalidRequest(err.to_string()),
+        })?;
+
+    Ok(HttpResponse::Ok().json(SequenceEventResponse { event }))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DoppelgangerListResponse {
+    pub runs: Vec<DoppelgangerRun>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct StartDoppelgangerRequest {
+    pub world_id: u16,
+    // Mirrors `MAX_PARTY_SIZE`; openapi.rs tests check it.
+    #[schema(min_items = 1, max_items = 5)]
+    pub party: Vec<PartyMember>,
+    /// Mirror of Dimensions presented by the party leader.
+    pub ticket: ItemInstance,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DoppelgangerResponse {
+    pub run: DoppelgangerRun,
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Doppelganger runs in progress with their wave and mirrored allies",
+    responses(
+        (status = 200, description = "Open runs", body = DoppelgangerListResponse),
+    ),
+)]
+#[get("/doppelganger")]
+pub async fn list_doppelganger_runs(
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let runs = runtime.doppelganger().runs();
+    Ok(HttpResponse::Ok().json(DoppelgangerListResponse { runs }))
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Open a Doppelganger zone for a party and send its members in",
+    request_body = StartDoppelgangerRequest,
+    responses(
+        (status = 200, description = "Run opened on its own zone instance, first wave started", body = DoppelgangerResponse),
+        (status = 400, description = "Invalid party, no Mirror of Dimensions, a member offline or already inside, or a restart before the run would end", body = ErrorResponse),
+        (status = 404, description = "The world has no Doppelganger zone", body = ErrorResponse),
+    ),
+)]
+#[post("/doppelganger")]
+pub async fn start_doppelganger_run(
+    req: web::Json<StartDoppelgangerRequest>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let req = req.into_inner();
+    let run = runtime
+        .start_doppelganger(req.world_id, req.party, &req.ticket, now_ms())
+        .await
+        .map_err(|err| match err {
+            DoppelgangerError::NoZone(_) => ConnectServerError::NotFound(err.to_string()),
+            _ => ConnectServerError::InvalidRequest(err.to_string()),
+        })?;
+
+    Ok(HttpResponse::Ok().json(DoppelgangerResponse { run }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct StartStressRequest {
+    pub world_id: u16,
+    pub map_id: u16,
+    /// First instance of the map when unset.
+    #[serde(default)]
+    pub instance_id: Option<u16>,
+    // Mirrors `MAX_STRESS_MONSTERS`; openapi.rs tests check it.
+    #[schema(minimum = 1, maximum = 20000)]
+    pub monsters: u32,
+    // Mirrors `MAX_STRESS_DURATION`; openapi.rs tests check it.
+    #[schema(minimum = 1, maximum = 600)]
+    pub duration_secs: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StressResponse {
+    pub report: StressReport,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StressListResponse {
+    pub reports: Vec<StressReport>,
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Latest synthetic monster load run of each map",
+    responses(
+        (status = 200, description = "Stress reports; `during` is null while a run is active", body = StressListResponse),
+    ),
+)]
+#[get("/stress")]
+pub async fn list_stress_runs(
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let reports = runtime.stress_reports().await;
+    Ok(HttpResponse::Ok().json(StressListResponse { reports }))
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Debug: spawn synthetic monsters with AI on a map for a while",
+    request_body = StartStressRequest,
+    responses(
+        (status = 200, description = "Run started, with the tick times from before it", body = StressResponse),
+        (status = 400, description = "Invalid count or duration, or a run is already active", body = ErrorResponse),
+        (status = 404, description = "Map not running", body = ErrorResponse),
+    ),
+)]
+#[post("/stress")]
+pub async fn start_stress_run(
+    req: web::Json<StartStressRequest>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let report = runtime
+        .start_stress(
+            req.world_id,
+            req.map_id,
+            req.instance_id,
+            req.monsters,
+            Duration::from_secs(req.duration_secs),
+        )
+        .await
+        .map_err(|err| match err {
+            StressError::UnknownMap { .. } => ConnectServerError::NotFound(err.to_string()),
+            _ => ConnectServerError::InvalidRequest(err.to_string()),
+        })?;
+
+    Ok(HttpResponse::Ok().json(StressResponse { report }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetDoorRequest {
+    pub world_id: u16,
+    pub map_id: u16,
+    /// Every running instance of the map when unset.
+    #[serde(default)]
+    pub instance_id: Option<u16>,
+    pub door_id: u16,
+    pub state: DoorState,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DoorListResponse {
+    pub doors: Vec<MapDoor>,
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Doors and gates of every running map",
+    responses(
+        (status = 200, description = "State and hit points of each door, by map instance", body = DoorListResponse),
+    ),
+)]
+#[get("/doors")]
+pub async fn list_doors(runtime: web::Data<Option<Arc<MuCoreRuntime>>>) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let doors = runtime.doors().await;
+    Ok(HttpResponse::Ok().json(DoorListResponse { doors }))
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Open, close, destroy or rebuild a door on a map",
+    request_body = SetDoorRequest,
+    responses(
+        (status = 200, description = "The door on each instance it was set on", body = DoorListResponse),
+        (status = 400, description = "Map server stopped", body = ErrorResponse),
+        (status = 404, description = "Map not running or without the door", body = ErrorResponse),
+    ),
+)]
+#[post("/doors")]
+pub async fn set_door(
+    req: web::Json<SetDoorRequest>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let doors = runtime
+        .set_door(
+            req.world_id,
+            req.map_id,
+            req.instance_id,
+            req.door_id,
+            req.state,
+        )
+        .await
+        .map_err(|err| match err {
+            DoorError::MapStopped => ConnectServerError::InvalidRequest(err.to_string()),
+            _ => ConnectServerError::NotFound(err.to_string()),
+        })?;
+
+    Ok(HttpResponse::Ok().json(DoorListResponse { doors }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct KickSessionRequest {
+    pub session_id: u64,
+    pub reason: DisconnectReason,
+    /// Shown to the player with the reason.
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct KickSessionResponse {
+    pub session_id: u64,
+    pub reason: DisconnectReason,
+    /// Whether a live connection was told and closed; the session ends either way.
+    pub connected: bool,
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "End a session and close its connection with a typed reason",
+    request_body = KickSessionRequest,
+    responses(
+        (status = 200, description = "Session ended; `connected` tells whether the client was told", body = KickSessionResponse),
+        (status = 404, description = "Session not authenticated", body = ErrorResponse),
+    ),
+)]
+#[post("/sessions/kick")]
+pub async fn kick_session(
+    req: web::Json<KickSessionRequest>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+    db: web::Data<MongoDbContext>,
+    emails: web::Data<EmailNotifier>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let account_id = runtime.session_account(req.session_id);
+    let connected = runtime
+        .kick_session(
+            req.session_id,
+            req.reason,
+            req.message.as_deref().unwrap_or_default(),
+            now_ms(),
+        )
+        .await
+        .map_err(|err| ConnectServerError::NotFound(err.to_string()))?;
+
+    if req.reason == DisconnectReason::Banned {
+        if let Some(account_id) = account_id {
+            notify_ban(&db, &emails, account_id, req.message.as_deref()).await;
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(KickSessionResponse {
+        session_id: req.session_id,
+        reason: req.reason,
+        connected,
+    }))
+}
+
+/// Tells a banned account by e-mail; the ban stands whether or not it can.
+async fn notify_ban(
+    db: &MongoDbContext,
+    emails: &EmailNotifier,
+    account_id: u64,
+    message: Option<&str>,
+) {
+    let account = match db.accounts().find_by_protocol_id(account_id).await {
+        Ok(account) => account,
+        Err(err) => {
+            log::warn!(
+                "Ban e-mail skipped, account {} lookup failed: {}",
+                account_id,
+                err
+            );
+            return;
+        }
+    };
+    let Some(account) = account else {
+        return;
+    };
+    if let Some(address) = &account.email {
+        let reason = message
+            .filter(|message| !message.is_empty())
+            .unwrap_or("no reason given");
+        emails
+            .notify(
+                address,
+                &account.username,
+                &AccountEmail::Banned {
+                    reason: reason.to_string(),
+                },
+            )
+            .await;
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TransferSessionRequest {
+    pub session_id: u64,
+    pub world_id: u16,
+    /// Least busy shard of the world when unset.
+    #[serde(default)]
+    pub entry_id: Option<u16>,
+    /// Map the character is on when unset.
+    #[serde(default)]
+    pub map_id: Option<u16>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TransferSessionResponse {
+    pub session_id: u64,
+    pub directive: MapTransferDirective,
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Hand a session's character to another shard of a world",
+    request_body = TransferSessionRequest,
+    responses(
+        (status = 200, description = "Directive the client reconnects with", body = TransferSessionResponse),
+        (status = 404, description = "Session without a character, or no shard can take the map", body = ErrorResponse),
+    ),
+)]
+#[post("/sessions/transfer")]
+pub async fn transfer_session(
+    req: web::Json<TransferSessionRequest>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let directive = runtime
+        .transfer_session(
+            req.session_id,
+            req.world_id,
+            req.entry_id,
+            req.map_id,
+            now_ms(),
+        )
+        .await
+        .map_err(|err| match err {
+            SessionControlError::Token(_) => ConnectServerError::Internal(err.to_string()),
+            _ => ConnectServerError::NotFound(err.to_string()),
+        })?;
+
+    Ok(HttpResponse::Ok().json(TransferSessionResponse {
+        session_id: req.session_id,
+        directive,
+    }))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DupeReportResponse {
+    /// `None` until the first scan has run.
+    pub report: Option<DupeReport>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ItemTransfersQuery {
+    /// Item serial.
+    pub serial: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ItemTransfersResponse {
+    pub serial: u64,
+    /// Current holder according to the ledger.
+    pub owner: Option<ItemHolder>,
+    pub transfers: Vec<ItemTransfer>,
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Latest scan for duplicated or stray item serials",
+    responses(
+        (status = 200, description = "Anomalies found; `report` is null before the first scan", body = DupeReportResponse),
+    ),
+)]
+#[get("/items/dupes")]
+pub async fn item_dupe_report(
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let report = runtime.item_ledger().last_report();
+    Ok(HttpResponse::Ok().json(DupeReportResponse { report }))
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Ownership trail of one item serial",
+    params(ItemTransfersQuery),
+    responses(
+        (status = 200, description = "Current holder and every transfer, oldest first", body = ItemTransfersResponse),
+        (status = 400, description = "Missing or invalid serial", body = ErrorResponse),
+        (status = 404, description = "Serial never minted", body = ErrorResponse),
+    ),
+)]
+#[get("/items/transfers")]
+pub async fn list_item_transfers(
+    query: web::Query<ItemTransfersQuery>,
+    db: web::Data<MongoDbContext>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let ledger = runtime.item_ledger();
+    let owner = ledger.owner(query.serial);
+
+    let mut transfers: Vec<ItemTransfer> = db
+        .item_transfers()
+        .find_by_serial(query.serial)
+        .await?
+        .iter()
+        .map(ItemTransfer::from)
+        .collect();
+    transfers.extend(ledger.unsaved_transfers(query.serial));
+    if owner.is_none() && transfers.is_empty() {
+        return Err(ConnectServerError::NotFound(format!(
+            "item serial {} not found",
+            query.serial
+        )));
+    }
+
+    Ok(HttpResponse::Ok().json(ItemTransfersResponse {
+        serial: query.serial,
+        owner,
+        transfers,
+    }))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TransferLimitQuery {
+    /// Only this account, whatever its usage.
+    pub account_id: Option<u64>,
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Accounts near their daily zen and item transfer caps",
+    params(TransferLimitQuery),
+    responses(
+        (status = 200, description = "Usage of the last 24 hours, most used first", body = TransferLimitReport),
+        (status = 400, description = "Invalid account id", body = ErrorResponse),
+    ),
+)]
+#[get("/economy/transfer-limits")]
+pub async fn transfer_limit_report(
+    query: web::Query<TransferLimitQuery>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    let limits = runtime.transfer_limits();
+    let now_ms = now_ms();
+    let mut report = limits.near_limits(now_ms);
+    if let Some(account_id) = query.account_id {
+        report.accounts = vec![limits.usage(account_id, now_ms)];
+    }
+    Ok(HttpResponse::Ok().json(report))
+}
+
+fn backups_ref(backups: &Option<BackupScheduler>) -> Result<&BackupScheduler> {
+    backups
+        .as_ref()
+        .ok_or_else(|| ConnectServerError::InvalidRequest("Backups are not configured".to_string()))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BackupStatusResponse {
+    /// `None` when `BACKUP_TARGET` is not set.
+    pub backups: Option<BackupStatus>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BackupRunResponse {
+    pub run: BackupRun,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VerificationResponse {
+    pub verification: VerificationRun,
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Backup schedule, stored backups and the latest runs",
+    responses(
+        (status = 200, description = "Status; `backups` is null when backups are not configured", body = BackupStatusResponse),
+    ),
+)]
+#[get("/backups")]
+pub async fn backup_status(backups: web::Data<Option<BackupScheduler>>) -> Result<HttpResponse> {
+    let backups = backups.get_ref().as_ref().map(BackupScheduler::status);
+    Ok(HttpResponse::Ok().json(BackupStatusResponse { backups }))
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Back the database up now and prune old backups",
+    responses(
+        (status = 200, description = "Finished run; `error` is set when it failed", body = BackupRunResponse),
+        (status = 400, description = "Backups not configured or another backup job running", body = ErrorResponse),
+    ),
+)]
+#[post("/backups")]
+pub async fn start_backup(backups: web::Data<Option<BackupScheduler>>) -> Result<HttpResponse> {
+    let run = backups_ref(backups.get_ref())?.run_backup().await?;
+    Ok(HttpResponse::Ok().json(BackupRunResponse { run }))
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Restore the newest backup into the scratch database and compare it",
+    responses(
+        (status = 200, description = "Finished check; `mismatches` lists collections that came back short", body = VerificationResponse),
+        (status = 400, description = "Backups not configured or another backup job running", body = ErrorResponse),
+    ),
+)]
+#[post("/backups/verify")]
+pub async fn verify_backup(backups: web::Data<Option<BackupScheduler>>) -> Result<HttpResponse> {
+    let verification = backups_ref(backups.get_ref())?.run_verification().await?;
+    Ok(HttpResponse::Ok().json(VerificationResponse { verification }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetAccountRoleRequest {
+    pub role: AccountRole,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccountRoleResponse {
+    pub username: String,
+    pub role: AccountRole,
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Set the role of an account; admin only",
+    params(("username" = String, Path, description = "Account name.")),
+    request_body = SetAccountRoleRequest,
+    responses(
+        (status = 200, description = "Role stored; tokens issued from the next login carry it", body = AccountRoleResponse),
+        (status = 404, description = "No account with that name", body = ErrorResponse),
+    ),
+)]
+#[put("/accounts/{username}/role")]
+pub async fn set_account_role(
+    username: web::Path<String>,
+    req: web::Json<SetAccountRoleRequest>,
+    db: web::Data<MongoDbContext>,
+) -> Result<HttpResponse> {
+    let username = username.into_inner();
+    if !db.accounts().set_role(&username, req.role).await? {
+        return Err(ConnectServerError::NotFound(format!(
+            "account {} not found",
+            username
+        )));
+    }
+    log::info!("Account {} is now {}", username, req.role.name());
+
+    Ok(HttpResponse::Ok().json(AccountRoleResponse {
+        username,
+        role: req.role,
+    }))
+}
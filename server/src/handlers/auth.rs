@@ -1,22 +1,34 @@
-use actix_web::{cookie::Cookie, post, web, HttpResponse};
+use actix_web::{cookie::Cookie, post, web, HttpRequest, HttpResponse};
+use mongodb::bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
 
 use crate::{
     auth_token::{
         class_name_to_id, now_ms, object_id_to_u64, AuthCharacterSummary, AuthTokenService,
     },
-    db::MongoDbContext,
-    error::{ConnectServerError, Result},
+    db::{
+        models::{Account, AccountCode, CodePurpose},
+        MongoDbContext,
+    },
+    email::{AccountEmail, EmailNotifier},
+    error::{ConnectServerError, ErrorResponse, Result},
+    password::{Passwords, Verification},
     session::SessionManager,
 };
 
-#[derive(Debug, Deserialize)]
+/// How long the code e-mailed at registration stays valid.
+const VERIFICATION_CODE_VALID_MS: u64 = 24 * 60 * 60 * 1000;
+/// How long a password reset code stays valid.
+const RESET_CODE_VALID_MINUTES: u32 = 15;
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct LoginRequest {
     pub username: String,
     pub password: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct LoginResponse {
     pub success: bool,
     pub account_id: String,
@@ -24,12 +36,25 @@ pub struct LoginResponse {
     pub message: String,
 }
 
+#[utoipa::path(
+    tag = "auth",
+    summary = "Log in and receive the session cookie and auth token",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Logged in; sets the `session_id` cookie", body = LoginResponse),
+        (status = 401, description = "Invalid credentials", body = ErrorResponse),
+        (status = 429, description = "Too many login attempts from this address", body = ErrorResponse),
+    ),
+)]
 #[post("/login")]
 pub async fn login(
+    http: HttpRequest,
     req: web::Json<LoginRequest>,
     db: web::Data<MongoDbContext>,
     session_manager: web::Data<SessionManager>,
     auth_tokens: web::Data<AuthTokenService>,
+    passwords: web::Data<Passwords>,
+    emails: web::Data<EmailNotifier>,
 ) -> Result<HttpResponse> {
     log::info!("Login attempt for user: {}", req.username);
 
@@ -41,18 +66,49 @@ pub async fn login(
         .ok_or(ConnectServerError::InvalidCredentials)?;
 
     // Verify password
-    if !account.verify_password(&req.password)? {
+    let verification = account.verify_password(&passwords, &req.password)?;
+    if !verification.is_accepted() {
         log::warn!("Failed login attempt for user: {}", req.username);
         return Err(ConnectServerError::InvalidCredentials);
     }
 
     let account_id = account.id.expect("Account should have ID");
 
+    // Imported accounts move to Argon2 the first time they log in.
+    if verification == Verification::Outdated {
+        let password_hash = passwords.hash(&req.password)?;
+        db.accounts()
+            .update_password_hash(&account_id, &password_hash)
+            .await?;
+        log::info!("Upgraded password hash of user: {}", req.username);
+    }
+
     // Create session (will kick old session if exists)
     let session = session_manager.create_session(account_id)?;
 
-    // Update last login time
-    db.accounts().update_last_login(&account_id).await?;
+    // Update last login time and address
+    let address = http.peer_addr().map(|peer| peer.ip().to_string());
+    db.accounts()
+        .update_last_login(&account_id, address.as_deref())
+        .await?;
+
+    // A login from another address than the previous one is worth a heads-up
+    if let (Some(email), Some(previous), Some(address)) =
+        (&account.email, &account.last_login_ip, &address)
+    {
+        if previous != address {
+            emails
+                .notify(
+                    email,
+                    &account.username,
+                    &AccountEmail::SuspiciousLogin {
+                        address: address.clone(),
+                        at: chrono::Utc::now(),
+                    },
+                )
+                .await;
+        }
+    }
 
     let characters = db.characters().find_by_account_id(&account_id).await?;
     let token_characters: Vec<AuthCharacterSummary> = characters
@@ -66,6 +122,7 @@ pub async fn login(
                     name: character.name,
                     class_id: class_name_to_id(&character.class),
                     level: character.level,
+                    guild_id: character.guild_id,
                 }
             })
         })
@@ -74,6 +131,7 @@ pub async fn login(
     let auth_token = auth_tokens
         .issue_session_token(
             object_id_to_u64(&account_id),
+            account.role,
             session.session_id.clone(),
             token_characters,
             now_ms(),
@@ -106,12 +164,21 @@ pub async fn login(
     Ok(HttpResponse::Ok().cookie(cookie).json(response))
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct LogoutResponse {
     pub success: bool,
     pub message: String,
 }
 
+#[utoipa::path(
+    tag = "auth",
+    summary = "End the current session",
+    responses(
+        (status = 200, description = "Logged out; clears the `session_id` cookie", body = LogoutResponse),
+        (status = 401, description = "Missing or expired session", body = ErrorResponse),
+    ),
+    security(("session_cookie" = [])),
+)]
 #[post("/logout")]
 pub async fn logout(
     session_manager: web::Data<SessionManager>,
@@ -134,3 +201,283 @@ pub async fn logout(
 
     Ok(HttpResponse::Ok().cookie(cookie).json(response))
 }
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RegisterRequest {
+    pub username: String,
+    pub password: String,
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RegisterResponse {
+    pub success: bool,
+    pub account_id: String,
+    pub message: String,
+}
+
+/// Creates an account and e-mails the code that confirms its address.
+#[utoipa::path(
+    tag = "auth",
+    summary = "Create an account and e-mail the code that verifies its address",
+    request_body = RegisterRequest,
+    responses(
+        (status = 201, description = "Account created; the code is on its way", body = RegisterResponse),
+        (status = 400, description = "Invalid or taken username, password or e-mail address", body = ErrorResponse),
+        (status = 429, description = "Too many requests from this address", body = ErrorResponse),
+    ),
+)]
+#[post("/register")]
+pub async fn register(
+    req: web::Json<RegisterRequest>,
+    db: web::Data<MongoDbContext>,
+    passwords: web::Data<Passwords>,
+    emails: web::Data<EmailNotifier>,
+) -> Result<HttpResponse> {
+    validate_registration(&req)?;
+    if db
+        .accounts()
+        .find_by_username(&req.username)
+        .await?
+        .is_some()
+    {
+        return Err(ConnectServerError::InvalidRequest(
+            "Username is already taken".to_string(),
+        ));
+    }
+
+    let (code, pending) = AccountCode::issue(
+        CodePurpose::VerifyEmail,
+        VERIFICATION_CODE_VALID_MS,
+        &passwords,
+        now_ms(),
+    )?;
+    let account_id = ObjectId::new();
+    let mut account = Account::new(req.username.clone(), &req.password, &passwords)?;
+    account.id = Some(account_id);
+    account.email = Some(req.email.clone());
+    account.pending_code = Some(pending);
+    db.accounts().insert(&account).await?;
+    emails
+        .notify(
+            &req.email,
+            &req.username,
+            &AccountEmail::RegistrationVerification { code },
+        )
+        .await;
+    log::info!("Registered user: {}", req.username);
+
+    Ok(HttpResponse::Created().json(RegisterResponse {
+        success: true,
+        account_id: account_id.to_hex(),
+        message: "Account created; check your e-mail for the verification code".to_string(),
+    }))
+}
+
+/// Usernames and passwords within what the classic client can type.
+fn validate_registration(req: &RegisterRequest) -> Result<()> {
+    let invalid = |message: &str| Err(ConnectServerError::InvalidRequest(message.to_string()));
+    if !(4..=10).contains(&req.username.len())
+        || !req
+            .username
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric())
+    {
+        return invalid("Username must be 4 to 10 letters or digits");
+    }
+    if !(4..=20).contains(&req.password.len()) {
+        return invalid("Password must be 4 to 20 characters");
+    }
+    let address = req.email.split_once('@');
+    if address.is_none_or(|(user, domain)| user.is_empty() || !domain.contains('.'))
+        || req.email.chars().any(char::is_whitespace)
+    {
+        return invalid("Invalid e-mail address");
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct VerifyEmailRequest {
+    pub username: String,
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PasswordResetRequest {
+    pub username: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PasswordResetConfirmation {
+    pub username: String,
+    pub code: String,
+    pub new_password: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccountCodeResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+#[utoipa::path(
+    tag = "auth",
+    summary = "Confirm the e-mail address with the registration code",
+    request_body = VerifyEmailRequest,
+    responses(
+        (status = 200, description = "Address verified", body = AccountCodeResponse),
+        (status = 400, description = "Invalid or expired code", body = ErrorResponse),
+        (status = 429, description = "Too many requests from this address", body = ErrorResponse),
+    ),
+)]
+#[post("/verify-email")]
+pub async fn verify_email(
+    req: web::Json<VerifyEmailRequest>,
+    db: web::Data<MongoDbContext>,
+    passwords: web::Data<Passwords>,
+) -> Result<HttpResponse> {
+    let account_id = accept_code(
+        &db,
+        &passwords,
+        &req.username,
+        CodePurpose::VerifyEmail,
+        &req.code,
+    )
+    .await?;
+    db.accounts().mark_email_verified(&account_id).await?;
+    log::info!("Verified the e-mail of user: {}", req.username);
+
+    Ok(HttpResponse::Ok().json(AccountCodeResponse {
+        success: true,
+        message: "E-mail address verified".to_string(),
+    }))
+}
+
+/// E-mails a reset code when the account has an address. The answer is the
+/// same either way, so it does not tell which usernames exist.
+#[utoipa::path(
+    tag = "auth",
+    summary = "E-mail a password reset code",
+    request_body = PasswordResetRequest,
+    responses(
+        (status = 202, description = "Sent when the account has an e-mail address; the answer is the same either way", body = AccountCodeResponse),
+        (status = 429, description = "Too many requests from this address", body = ErrorResponse),
+    ),
+)]
+#[post("/password-reset")]
+pub async fn request_password_reset(
+    req: web::Json<PasswordResetRequest>,
+    db: web::Data<MongoDbContext>,
+    passwords: web::Data<Passwords>,
+    emails: web::Data<EmailNotifier>,
+) -> Result<HttpResponse> {
+    let account = db.accounts().find_by_username(&req.username).await?;
+    if let Some((account_id, email)) =
+        account.and_then(|account| Some((account.id?, account.email?)))
+    {
+        let (code, pending) = AccountCode::issue(
+            CodePurpose::PasswordReset,
+            u64::from(RESET_CODE_VALID_MINUTES) * 60 * 1000,
+            &passwords,
+            now_ms(),
+        )?;
+        db.accounts()
+            .set_pending_code(&account_id, &pending)
+            .await?;
+        emails
+            .notify(
+                &email,
+                &req.username,
+                &AccountEmail::PasswordReset {
+                    code,
+                    valid_minutes: RESET_CODE_VALID_MINUTES,
+                },
+            )
+            .await;
+        log::info!("Sent a password reset code to user: {}", req.username);
+    }
+
+    Ok(HttpResponse::Accepted().json(AccountCodeResponse {
+        success: true,
+        message: "If the account has an e-mail address, a reset code was sent to it".to_string(),
+    }))
+}
+
+#[utoipa::path(
+    tag = "auth",
+    summary = "Choose a new password with a reset code",
+    request_body = PasswordResetConfirmation,
+    responses(
+        (status = 200, description = "Password changed", body = AccountCodeResponse),
+        (status = 400, description = "Invalid or expired code, or invalid password", body = ErrorResponse),
+        (status = 429, description = "Too many requests from this address", body = ErrorResponse),
+    ),
+)]
+#[post("/password-reset/confirm")]
+pub async fn confirm_password_reset(
+    req: web::Json<PasswordResetConfirmation>,
+    db: web::Data<MongoDbContext>,
+    passwords: web::Data<Passwords>,
+) -> Result<HttpResponse> {
+    if !(4..=20).contains(&req.new_password.len()) {
+        return Err(ConnectServerError::InvalidRequest(
+            "Password must be 4 to 20 characters".to_string(),
+        ));
+    }
+    let account_id = accept_code(
+        &db,
+        &passwords,
+        &req.username,
+        CodePurpose::PasswordReset,
+        &req.code,
+    )
+    .await?;
+    let password_hash = passwords.hash(&req.new_password)?;
+    db.accounts()
+        .reset_password(&account_id, &password_hash)
+        .await?;
+    log::info!("Reset the password of user: {}", req.username);
+
+    Ok(HttpResponse::Ok().json(AccountCodeResponse {
+        success: true,
+        message: "Password changed".to_string(),
+    }))
+}
+
+/// Account id of `username` when `code` is its pending code for `purpose`.
+/// A wrong guess is stored, so the attempts run out across requests.
+async fn accept_code(
+    db: &MongoDbContext,
+    passwords: &Passwords,
+    username: &str,
+    purpose: CodePurpose,
+    code: &str,
+) -> Result<ObjectId> {
+    let invalid = || ConnectServerError::InvalidRequest("Invalid or expired code".to_string());
+    let account = db.accounts().find_by_username(username).await?;
+    let Some((account_id, mut pending)) =
+        account.and_then(|account| Some((account.id?, account.pending_code?)))
+    else {
+        return Err(invalid());
+    };
+    if pending.accept(purpose, code, passwords, now_ms())? {
+        return Ok(account_id);
+    }
+    db.accounts()
+        .set_code_attempts(&account_id, pending.attempts_left)
+        .await?;
+    Err(invalid())
+}
+
+/// Routes of this module, for the OpenAPI document.
+#[derive(OpenApi)]
+#[openapi(paths(
+    login,
+    register,
+    verify_email,
+    request_password_reset,
+    confirm_password_reset,
+    logout
+))]
+pub(crate) struct AuthApi;
@@ -0,0 +1,239 @@
+use std::sync::Arc;
+
+use actix_web::{get, post, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
+
+use crate::{
+    auth_token::{now_ms, object_id_to_u64},
+    db::MongoDbContext,
+    error::{ConnectServerError, ErrorResponse, Result},
+    openapi::{RouteDefaults, ADMIN_ROUTES, SESSION_COOKIE},
+    runtime::cash_shop::{CashReceipt, CashShop, CashShopError, CashShopProduct},
+    runtime::MuCoreRuntime,
+    session::SessionManager,
+};
+
+use super::runtime::runtime_ref;
+
+fn cash_shop_ref(runtime: &MuCoreRuntime) -> Result<&CashShop> {
+    runtime
+        .cash_shop()
+        .ok_or_else(|| ConnectServerError::NotFound(CashShopError::Disabled.to_string()))
+}
+
+fn cash_error(err: CashShopError) -> ConnectServerError {
+    match err {
+        CashShopError::Disabled | CashShopError::UnknownProduct(_) => {
+            ConnectServerError::NotFound(err.to_string())
+        }
+        _ => ConnectServerError::InvalidRequest(err.to_string()),
+    }
+}
+
+async fn account_id_of(db: &MongoDbContext, username: &str) -> Result<u64> {
+    db.accounts()
+        .find_by_username(username)
+        .await?
+        .and_then(|account| account.id)
+        .map(|id| object_id_to_u64(&id))
+        .ok_or_else(|| ConnectServerError::NotFound(format!("account {} not found", username)))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CashShopResponse {
+    pub currency_name: String,
+    pub balance: u64,
+    pub products: Vec<CashShopProduct>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CashPurchaseRequest {
+    /// Protocol id of the character receiving the product.
+    pub character_id: u64,
+    pub product_id: u32,
+    /// Client-chosen key; retrying with it never debits twice.
+    pub idempotency_key: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CashTopUpRequest {
+    pub amount: u64,
+    /// Order id of the operator's payment flow; retrying with it never
+    /// credits twice.
+    pub idempotency_key: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CashReceiptResponse {
+    pub receipt: CashReceipt,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CashAccountResponse {
+    pub username: String,
+    pub balance: u64,
+    /// Newest first.
+    pub receipts: Vec<CashReceipt>,
+}
+
+#[utoipa::path(
+    tag = "cash-shop",
+    summary = "Cash-shop catalog and the account's balance",
+    responses(
+        (status = 200, description = "Catalog and balance", body = CashShopResponse),
+    ),
+)]
+#[get("/cash-shop")]
+pub async fn cash_shop_catalog(
+    session_manager: web::Data<SessionManager>,
+    session_id: web::ReqData<String>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let session = session_manager.validate_session(&session_id.into_inner())?;
+    let shop = cash_shop_ref(runtime_ref(runtime.get_ref())?)?;
+
+    Ok(HttpResponse::Ok().json(CashShopResponse {
+        currency_name: shop.currency_name().to_string(),
+        balance: shop.balance(object_id_to_u64(&session.account_id)),
+        products: shop.products().to_vec(),
+    }))
+}
+
+#[utoipa::path(
+    tag = "cash-shop",
+    summary = "Buy a product for one of the account's characters",
+    request_body = CashPurchaseRequest,
+    responses(
+        (status = 200, description = "Receipt; the product is in the character's inventory and wallet. A retried key returns the first receipt", body = CashReceiptResponse),
+        (status = 400, description = "Balance too low, no room for the product, or idempotency key invalid or used for another purchase", body = ErrorResponse),
+    ),
+)]
+#[post("/cash-shop/purchases")]
+pub async fn purchase_cash_product(
+    req: web::Json<CashPurchaseRequest>,
+    db: web::Data<MongoDbContext>,
+    session_manager: web::Data<SessionManager>,
+    session_id: web::ReqData<String>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let session = session_manager.validate_session(&session_id.into_inner())?;
+    let runtime = runtime_ref(runtime.get_ref())?;
+    cash_shop_ref(runtime)?;
+    let req = req.into_inner();
+
+    let owns_character = db
+        .characters()
+        .find_by_account_id(&session.account_id)
+        .await?
+        .iter()
+        .filter_map(|character| character.id.as_ref())
+        .any(|id| object_id_to_u64(id) == req.character_id);
+    if !owns_character {
+        return Err(ConnectServerError::NotFound(format!(
+            "character {} not found",
+            req.character_id
+        )));
+    }
+
+    let receipt = runtime
+        .purchase_cash_product(
+            object_id_to_u64(&session.account_id),
+            req.character_id,
+            req.product_id,
+            &req.idempotency_key,
+            now_ms(),
+        )
+        .await
+        .map_err(cash_error)?;
+
+    Ok(HttpResponse::Ok().json(CashReceiptResponse { receipt }))
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Premium balance and receipts of an account; admin only",
+    params(("username" = String, Path, description = "Account name.")),
+    responses(
+        (status = 200, description = "Balance and receipts", body = CashAccountResponse),
+        (status = 404, description = "No account with that name, or the cash shop is disabled", body = ErrorResponse),
+    ),
+)]
+#[get("/accounts/{username}/cash")]
+pub async fn cash_account(
+    username: web::Path<String>,
+    db: web::Data<MongoDbContext>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let shop = cash_shop_ref(runtime_ref(runtime.get_ref())?)?;
+    let username = username.into_inner();
+    let account_id = account_id_of(&db, &username).await?;
+
+    Ok(HttpResponse::Ok().json(CashAccountResponse {
+        username,
+        balance: shop.balance(account_id),
+        receipts: shop.receipts_of(account_id),
+    }))
+}
+
+#[utoipa::path(
+    tag = "admin",
+    summary = "Credit premium currency from a top-up flow; admin only",
+    params(("username" = String, Path, description = "Account name.")),
+    request_body = CashTopUpRequest,
+    responses(
+        (status = 200, description = "Receipt; a retried key returns the first receipt", body = CashReceiptResponse),
+        (status = 400, description = "Amount is zero, or idempotency key invalid or used for another top-up", body = ErrorResponse),
+        (status = 404, description = "No account with that name, or the cash shop is disabled", body = ErrorResponse),
+    ),
+)]
+#[post("/accounts/{username}/cash")]
+pub async fn top_up_cash(
+    username: web::Path<String>,
+    req: web::Json<CashTopUpRequest>,
+    db: web::Data<MongoDbContext>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let shop = cash_shop_ref(runtime_ref(runtime.get_ref())?)?;
+    let username = username.into_inner();
+    let account_id = account_id_of(&db, &username).await?;
+
+    let receipt = shop
+        .top_up(account_id, req.amount, &req.idempotency_key, now_ms())
+        .map_err(cash_error)?;
+    log::info!(
+        "Credited {} to account {} (key {:?}), balance {}",
+        req.amount,
+        username,
+        req.idempotency_key,
+        receipt.balance_after
+    );
+
+    Ok(HttpResponse::Ok().json(CashReceiptResponse { receipt }))
+}
+
+/// Session rejections shared by the player routes.
+const CASH_SHOP_ROUTES: RouteDefaults = RouteDefaults {
+    security: &[SESSION_COOKIE],
+    errors: &[
+        (401, "Missing or expired session"),
+        (404, "Cash shop disabled in the runtime config"),
+        (500, "Runtime core disabled"),
+    ],
+};
+
+/// Player routes of this module, for the OpenAPI document.
+#[derive(OpenApi)]
+#[openapi(
+    paths(cash_shop_catalog, purchase_cash_product),
+    modifiers(&CASH_SHOP_ROUTES)
+)]
+pub(crate) struct CashShopApi;
+
+/// Operator routes of this module, mounted under `/admin`.
+#[derive(OpenApi)]
+#[openapi(
+    paths(cash_account, top_up_cash),
+    modifiers(&ADMIN_ROUTES)
+)]
+pub(crate) struct CashAdminApi;
@@ -1,16 +1,20 @@
 use actix_web::{get, web, HttpResponse};
 use serde::Serialize;
+use utoipa::{OpenApi, ToSchema};
 
 use crate::{
-    auth_token::object_id_to_u64, db::MongoDbContext, error::Result, session::SessionManager,
+    auth_token::object_id_to_u64,
+    db::MongoDbContext,
+    error::{ErrorResponse, Result},
+    session::SessionManager,
 };
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CharacterListResponse {
     pub characters: Vec<CharacterInfo>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CharacterInfo {
     pub id: String,
     pub protocol_character_id: u64,
@@ -19,6 +23,15 @@ pub struct CharacterInfo {
     pub class: String,
 }
 
+#[utoipa::path(
+    tag = "characters",
+    summary = "Characters of the logged-in account",
+    responses(
+        (status = 200, description = "Account characters", body = CharacterListResponse),
+        (status = 401, description = "Missing or expired session", body = ErrorResponse),
+    ),
+    security(("session_cookie" = [])),
+)]
 #[get("/characters")]
 pub async fn list_characters(
     db: web::Data<MongoDbContext>,
@@ -64,3 +77,8 @@ pub async fn list_characters(
 
     Ok(HttpResponse::Ok().json(response))
 }
+
+/// Routes of this module, for the OpenAPI document.
+#[derive(OpenApi)]
+#[openapi(paths(list_characters))]
+pub(crate) struct CharactersApi;
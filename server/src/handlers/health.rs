@@ -1,20 +1,29 @@
 use actix_web::{get, post, web, HttpResponse};
 use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
 
 use crate::{error::Result, monitor::HealthMonitor, session::SessionManager};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct HeartbeatRequest {
     pub world_id: String,
     pub current_players: u32,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct HeartbeatResponse {
     pub success: bool,
     pub next_heartbeat_in: u64,
 }
 
+#[utoipa::path(
+    tag = "health",
+    summary = "Game server heartbeat with its current population",
+    request_body = HeartbeatRequest,
+    responses(
+        (status = 200, description = "Heartbeat recorded", body = HeartbeatResponse),
+    ),
+)]
 #[post("/heartbeat")]
 pub async fn heartbeat(
     req: web::Json<HeartbeatRequest>,
@@ -36,13 +45,20 @@ pub async fn heartbeat(
     Ok(HttpResponse::Ok().json(response))
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct HealthCheckResponse {
     pub status: String,
     pub active_sessions: usize,
     pub online_worlds: usize,
 }
 
+#[utoipa::path(
+    tag = "health",
+    summary = "Liveness probe",
+    responses(
+        (status = 200, description = "Server is up", body = HealthCheckResponse),
+    ),
+)]
 #[get("/health")]
 pub async fn health_check(
     health_monitor: web::Data<HealthMonitor>,
@@ -61,3 +77,8 @@ pub async fn health_check(
 
     Ok(HttpResponse::Ok().json(response))
 }
+
+/// Routes of this module, for the OpenAPI document.
+#[derive(OpenApi)]
+#[openapi(paths(health_check, heartbeat))]
+pub(crate) struct HealthApi;
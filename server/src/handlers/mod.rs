@@ -1,11 +1,43 @@
+pub mod admin;
 pub mod auth;
+pub mod cash_shop;
 pub mod characters;
 pub mod health;
 pub mod runtime;
 pub mod servers;
 
-pub use auth::{login, logout};
+use utoipa::OpenApi;
+
+pub use admin::{
+    backup_status, begin_maintenance, cancel_restart, declare_guild_relation, end_maintenance,
+    grant_reward, item_dupe_report, kick_session, list_doors, list_doppelganger_runs,
+    list_guild_wars, list_helper_sessions, list_item_transfers, list_maintenance,
+    list_sequence_events, list_stress_runs, planned_restart, resolve_sequence_event,
+    revoke_guild_relation, schedule_restart, set_account_role, set_door, start_backup,
+    start_doppelganger_run, start_guild_war, start_stress_run, transfer_limit_report,
+    transfer_session, verify_backup,
+};
+pub use auth::{
+    confirm_password_reset, login, logout, register, request_password_reset, verify_email,
+};
+pub use cash_shop::{cash_account, cash_shop_catalog, purchase_cash_product, top_up_cash};
 pub use characters::list_characters;
 pub use health::{health_check, heartbeat};
-pub use runtime::{runtime_maps, runtime_persistence, runtime_stats, runtime_worlds};
+pub use runtime::{gens_ranking, runtime_maps, runtime_persistence, runtime_stats, runtime_worlds};
 pub use servers::{list_servers, list_worlds};
+
+/// Adds every route of this module to the OpenAPI document, the operator
+/// routes under the `/admin` scope `main.rs` mounts them on.
+pub(crate) fn api_docs(api: &mut utoipa::openapi::OpenApi) {
+    api.merge(auth::AuthApi::openapi());
+    api.merge(characters::CharactersApi::openapi());
+    api.merge(cash_shop::CashShopApi::openapi());
+    api.merge(servers::ServersApi::openapi());
+    api.merge(health::HealthApi::openapi());
+    api.merge(runtime::RuntimeApi::openapi());
+    api.merge(
+        utoipa::openapi::OpenApi::default()
+            .nest("/admin", admin::AdminApi::openapi())
+            .nest("/admin", cash_shop::CashAdminApi::openapi()),
+    );
+}
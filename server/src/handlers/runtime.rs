@@ -1,57 +1,279 @@
 use std::sync::Arc;
 
 use actix_web::{get, web, HttpResponse};
-use serde::Serialize;
+use common::WorldMap;
+use protocol::GensFaction;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::{IntoParams, OpenApi, ToSchema};
 
 use crate::{
-    error::{ConnectServerError, Result},
+    error::{ConnectServerError, ErrorResponse, Result},
+    openapi::{property_names, schema_value},
+    runtime::core::RuntimeStats,
+    runtime::directory::WorldSnapshot,
+    runtime::gens::GensRankEntry,
+    runtime::map_server::MapServerStats,
+    runtime::persistence::PersistenceMetrics,
     runtime::MuCoreRuntime,
 };
 
-fn runtime_ref(runtime: &Option<Arc<MuCoreRuntime>>) -> Result<&Arc<MuCoreRuntime>> {
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+pub const MAX_PAGE_SIZE: usize = 200;
+pub const DEFAULT_GENS_RANKING: usize = 100;
+
+pub(crate) fn runtime_ref(runtime: &Option<Arc<MuCoreRuntime>>) -> Result<&Arc<MuCoreRuntime>> {
     runtime
         .as_ref()
         .ok_or_else(|| ConnectServerError::Internal("Runtime core is disabled".to_string()))
 }
 
-#[derive(Debug, Serialize)]
+/// Query parameters shared by the runtime listings.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RuntimeListQuery {
+    /// 1-based page number.
+    #[param(minimum = 1, default = 1)]
+    pub page: Option<usize>,
+    /// Items per page.
+    // The maximum mirrors `MAX_PAGE_SIZE`; openapi.rs tests check it.
+    #[param(minimum = 1, maximum = 200, default = json!(DEFAULT_PAGE_SIZE))]
+    pub per_page: Option<usize>,
+    /// Only items of this runtime world.
+    pub world_id: Option<u16>,
+    /// Only items of this map, by name (`Lorencia`, `lost_tower`, ...).
+    pub map: Option<String>,
+    /// Comma-separated fields of the item schema to return. All when omitted.
+    pub fields: Option<String>,
+}
+
+impl RuntimeListQuery {
+    fn map_filter(&self) -> Result<Option<WorldMap>> {
+        self.map
+            .as_deref()
+            .map(|name| {
+                WorldMap::from_name(name).ok_or_else(|| {
+                    ConnectServerError::InvalidRequest(format!("unknown map '{name}'"))
+                })
+            })
+            .transpose()
+    }
+
+    /// Cuts one page out of `items`.
+    fn paginate<T>(&self, items: Vec<T>) -> Result<(Vec<T>, Pagination)> {
+        let page = self.page.unwrap_or(1);
+        let per_page = self.per_page.unwrap_or(DEFAULT_PAGE_SIZE);
+        if page == 0 {
+            return Err(ConnectServerError::InvalidRequest(
+                "page starts at 1".to_string(),
+            ));
+        }
+        if !(1..=MAX_PAGE_SIZE).contains(&per_page) {
+            return Err(ConnectServerError::InvalidRequest(format!(
+                "per_page must be between 1 and {MAX_PAGE_SIZE}"
+            )));
+        }
+
+        let total = items.len();
+        let page_items = items
+            .into_iter()
+            .skip((page - 1).saturating_mul(per_page))
+            .take(per_page)
+            .collect();
+        let pagination = Pagination {
+            page,
+            per_page,
+            total,
+            total_pages: total.div_ceil(per_page),
+        };
+        Ok((page_items, pagination))
+    }
+
+    /// Serializes `items`, keeping only the requested fields of each.
+    fn select_fields<T: Serialize + ToSchema>(&self, items: Vec<T>) -> Result<Vec<Value>> {
+        let values = items
+            .into_iter()
+            .map(serde_json::to_value)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let Some(fields) = self.fields.as_deref() else {
+            return Ok(values);
+        };
+
+        let schema = schema_value::<T>();
+        let known = property_names(&schema);
+        let selected: Vec<&str> = fields
+            .split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+            .collect();
+        if let Some(unknown) = selected.iter().find(|field| !known.contains(field)) {
+            return Err(ConnectServerError::InvalidRequest(format!(
+                "unknown field '{unknown}', expected one of: {}",
+                known.join(", ")
+            )));
+        }
+
+        Ok(values
+            .into_iter()
+            .map(|mut value| {
+                if let Value::Object(object) = &mut value {
+                    object.retain(|key, _| selected.contains(&key.as_str()));
+                }
+                value
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub struct Pagination {
+    pub page: usize,
+    pub per_page: usize,
+    pub total: usize,
+    pub total_pages: usize,
+}
+
+/// Worlds stay nested under `worlds.worlds`, as before paging was added.
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RuntimeWorldsResponse {
-    pub worlds: crate::runtime::directory::WorldDirectorySnapshot,
+    #[schema(inline)]
+    pub worlds: RuntimeWorldsPage,
+    pub pagination: Pagination,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RuntimeWorldsPage {
+    #[schema(value_type = Vec<WorldSnapshot>)]
+    pub worlds: Vec<Value>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RuntimeMapsResponse {
-    pub maps: Vec<crate::runtime::map_server::MapServerStats>,
+    #[schema(value_type = Vec<MapServerStats>)]
+    pub maps: Vec<Value>,
+    pub pagination: Pagination,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RuntimePersistenceResponse {
-    pub metrics: crate::runtime::persistence::PersistenceMetrics,
+    pub metrics: PersistenceMetrics,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RuntimeStatsResponse {
-    pub stats: crate::runtime::core::RuntimeStats,
+    pub stats: RuntimeStats,
 }
 
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GensRankingQuery {
+    /// Only members of this faction. Both when omitted.
+    pub faction: Option<GensFaction>,
+    /// Members to return.
+    // The maximum mirrors `MAX_PAGE_SIZE`; openapi.rs tests check it.
+    #[param(minimum = 1, maximum = 200, default = json!(DEFAULT_GENS_RANKING))]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GensRankingResponse {
+    pub ranking: Vec<GensRankEntry>,
+}
+
+/// Applies the world and map filters, dropping entries and worlds left without maps.
+fn filter_worlds(
+    worlds: Vec<WorldSnapshot>,
+    world_id: Option<u16>,
+    map: Option<WorldMap>,
+) -> Vec<WorldSnapshot> {
+    worlds
+        .into_iter()
+        .filter(|world| world_id.is_none_or(|id| world.world_id == id))
+        .filter_map(|mut world| {
+            let Some(map) = map else {
+                return Some(world);
+            };
+            for entry in &mut world.entries {
+                entry
+                    .maps
+                    .retain(|snapshot| map.matches_name(&snapshot.map_name));
+            }
+            world.entries.retain(|entry| !entry.maps.is_empty());
+            (!world.entries.is_empty()).then_some(world)
+        })
+        .collect()
+}
+
+#[utoipa::path(
+    tag = "runtime",
+    summary = "Runtime topology (world/entry/map), one item per world",
+    params(RuntimeListQuery),
+    responses(
+        (status = 200, description = "Page of worlds", body = RuntimeWorldsResponse),
+        (status = 400, description = "Unknown map or field, or paging out of range", body = ErrorResponse),
+        (status = 500, description = "Runtime core disabled", body = ErrorResponse),
+    ),
+)]
 #[get("/runtime/worlds")]
 pub async fn runtime_worlds(
+    query: web::Query<RuntimeListQuery>,
     runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
 ) -> Result<HttpResponse> {
     let runtime = runtime_ref(runtime.get_ref())?;
+    let worlds = filter_worlds(
+        runtime.directory_snapshot().worlds,
+        query.world_id,
+        query.map_filter()?,
+    );
+    let (worlds, pagination) = query.paginate(worlds)?;
     let response = RuntimeWorldsResponse {
-        worlds: runtime.directory_snapshot(),
+        worlds: RuntimeWorldsPage {
+            worlds: query.select_fields(worlds)?,
+        },
+        pagination,
     };
     Ok(HttpResponse::Ok().json(response))
 }
 
+#[utoipa::path(
+    tag = "runtime",
+    summary = "Map loop metrics, one item per map instance",
+    params(RuntimeListQuery),
+    responses(
+        (status = 200, description = "Page of map instances", body = RuntimeMapsResponse),
+        (status = 400, description = "Unknown map or field, or paging out of range", body = ErrorResponse),
+        (status = 500, description = "Runtime core disabled", body = ErrorResponse),
+    ),
+)]
 #[get("/runtime/maps")]
-pub async fn runtime_maps(runtime: web::Data<Option<Arc<MuCoreRuntime>>>) -> Result<HttpResponse> {
+pub async fn runtime_maps(
+    query: web::Query<RuntimeListQuery>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
     let runtime = runtime_ref(runtime.get_ref())?;
-    let maps = runtime.map_stats().await;
-    Ok(HttpResponse::Ok().json(RuntimeMapsResponse { maps }))
+    let map = query.map_filter()?;
+    let maps: Vec<MapServerStats> = runtime
+        .map_stats()
+        .await
+        .into_iter()
+        .filter(|stats| query.world_id.is_none_or(|id| stats.route.world_id == id))
+        .filter(|stats| map.is_none_or(|map| map.matches_name(&stats.map_name)))
+        .collect();
+    let (maps, pagination) = query.paginate(maps)?;
+    Ok(HttpResponse::Ok().json(RuntimeMapsResponse {
+        maps: query.select_fields(maps)?,
+        pagination,
+    }))
 }
 
+#[utoipa::path(
+    tag = "runtime",
+    summary = "Buffered persistence metrics",
+    responses(
+        (status = 200, description = "Persistence metrics", body = RuntimePersistenceResponse),
+        (status = 500, description = "Runtime core disabled", body = ErrorResponse),
+    ),
+)]
 #[get("/runtime/persistence")]
 pub async fn runtime_persistence(
     runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
@@ -61,9 +283,60 @@ pub async fn runtime_persistence(
     Ok(HttpResponse::Ok().json(RuntimePersistenceResponse { metrics }))
 }
 
+#[utoipa::path(
+    tag = "runtime",
+    summary = "Runtime high-level stats",
+    responses(
+        (status = 200, description = "Runtime stats", body = RuntimeStatsResponse),
+        (status = 500, description = "Runtime core disabled", body = ErrorResponse),
+    ),
+)]
 #[get("/runtime/stats")]
 pub async fn runtime_stats(runtime: web::Data<Option<Arc<MuCoreRuntime>>>) -> Result<HttpResponse> {
     let runtime = runtime_ref(runtime.get_ref())?;
     let stats = runtime.runtime_stats().await;
     Ok(HttpResponse::Ok().json(RuntimeStatsResponse { stats }))
 }
+
+#[utoipa::path(
+    tag = "runtime",
+    summary = "Gens members by contribution, highest first",
+    params(GensRankingQuery),
+    responses(
+        (status = 200, description = "Ranking", body = GensRankingResponse),
+        (status = 400, description = "Unknown faction or limit out of range", body = ErrorResponse),
+        (status = 404, description = "Gens disabled on every world", body = ErrorResponse),
+        (status = 500, description = "Runtime core disabled", body = ErrorResponse),
+    ),
+)]
+#[get("/runtime/gens/ranking")]
+pub async fn gens_ranking(
+    query: web::Query<GensRankingQuery>,
+    runtime: web::Data<Option<Arc<MuCoreRuntime>>>,
+) -> Result<HttpResponse> {
+    let runtime = runtime_ref(runtime.get_ref())?;
+    if !runtime.gens_active() {
+        return Err(ConnectServerError::NotFound(
+            "Gens are disabled on every world".to_string(),
+        ));
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_GENS_RANKING);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(ConnectServerError::InvalidRequest(format!(
+            "limit must be between 1 and {MAX_PAGE_SIZE}"
+        )));
+    }
+    let ranking = runtime.gens().ranking(query.faction, limit);
+    Ok(HttpResponse::Ok().json(GensRankingResponse { ranking }))
+}
+
+/// Routes of this module, for the OpenAPI document.
+#[derive(OpenApi)]
+#[openapi(paths(
+    runtime_worlds,
+    runtime_maps,
+    runtime_persistence,
+    runtime_stats,
+    gens_ranking
+))]
+pub(crate) struct RuntimeApi;
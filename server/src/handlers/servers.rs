@@ -1,41 +1,118 @@
-use actix_web::{get, web, HttpResponse};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use actix_web::{get, web, HttpRequest, HttpResponse};
 use serde::Serialize;
+use utoipa::{
+    openapi::schema::{Object, ObjectBuilder, Type},
+    OpenApi, ToSchema,
+};
+
+use crate::{
+    config::{ServerConfig, WorldServer},
+    error::Result,
+    geoip::{GeoIpDatabase, GeoLocation},
+    monitor::HealthMonitor,
+    runtime::MuCoreRuntime,
+};
 
-use crate::{config::ServerConfig, error::Result, monitor::HealthMonitor};
+/// Whether the runtime world/map behind a listing is closed for maintenance.
+fn in_maintenance(world: &WorldServer, runtime: Option<&Arc<MuCoreRuntime>>) -> bool {
+    let (Some(runtime), Some(world_id)) = (runtime, world.runtime_world_id) else {
+        return false;
+    };
+
+    let maintenance = runtime.maintenance();
+    match world.runtime_map_id {
+        Some(map_id) => maintenance.window_for(world_id, map_id).is_some(),
+        None => maintenance.is_world_closed(world_id),
+    }
+}
+
+/// Client address, honoring proxy headers since it only steers hints.
+fn client_ip(req: &HttpRequest) -> Option<IpAddr> {
+    let info = req.connection_info();
+    let address = info.realip_remote_addr()?;
+    address
+        .parse::<SocketAddr>()
+        .map(|socket| socket.ip())
+        .or_else(|_| address.parse::<IpAddr>())
+        .ok()
+}
 
-#[derive(Debug, Serialize)]
+fn server_status() -> Object {
+    ObjectBuilder::new()
+        .schema_type(Type::String)
+        .enum_values(Some(["online", "maintenance", "offline"]))
+        .build()
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ServerListResponse {
+    /// Region the client was located in, when GeoIP is enabled.
+    pub client_region: Option<String>,
     pub servers: Vec<ServerInfo>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ServerInfo {
     pub id: String,
     pub name: String,
     pub description: String,
+    #[schema(schema_with = server_status)]
     pub status: String,
     pub world_count: usize,
+    pub maintenance_worlds: Vec<String>,
+    pub region: Option<String>,
+    /// Estimated round trip from the client; sort by it for proximity.
+    pub latency_hint_ms: Option<u32>,
 }
 
+#[utoipa::path(
+    tag = "servers",
+    summary = "Server groups, how many of their worlds are up and how far they are",
+    responses(
+        (status = 200, description = "Server groups", body = ServerListResponse),
+    ),
+)]
 #[get("/servers")]
 pub async fn list_servers(
+    req: HttpRequest,
     config: web::Data<ServerConfig>,
     health_monitor: web::Data<HealthMonitor>,
+    runtime: Option<web::Data<Option<Arc<MuCoreRuntime>>>>,
+    geoip: Option<web::Data<Option<Arc<GeoIpDatabase>>>>,
 ) -> Result<HttpResponse> {
+    let runtime = runtime.as_ref().and_then(|data| data.get_ref().as_ref());
+    let client: Option<&GeoLocation> = geoip
+        .as_ref()
+        .and_then(|data| data.get_ref().as_ref())
+        .zip(client_ip(&req))
+        .and_then(|(db, ip)| db.lookup(ip));
     let servers: Vec<ServerInfo> = config
         .servers
         .iter()
         .map(|server| {
-            // Count online worlds for this server
+            let maintenance_worlds: Vec<String> = server
+                .worlds
+                .iter()
+                .filter(|w| in_maintenance(w, runtime))
+                .map(|w| w.id.clone())
+                .collect();
+
+            // Count online worlds for this server, leaving out closed ones
             let online_worlds = server
                 .worlds
                 .iter()
                 .filter(|w| health_monitor.is_world_online(&w.id))
+                .filter(|w| !maintenance_worlds.contains(&w.id))
                 .count();
 
             // Server is online if it has at least one online world
             let status = if online_worlds > 0 {
                 "online"
+            } else if !maintenance_worlds.is_empty() {
+                "maintenance"
             } else {
                 "offline"
             };
@@ -46,37 +123,55 @@ pub async fn list_servers(
                 description: server.description.clone(),
                 status: status.to_string(),
                 world_count: online_worlds,
+                maintenance_worlds,
+                region: server.region.clone(),
+                latency_hint_ms: client
+                    .zip(server.location)
+                    .map(|(client, location)| client.point.latency_hint_ms(&location)),
             }
         })
         .collect();
 
-    let response = ServerListResponse { servers };
+    let response = ServerListResponse {
+        client_region: client.map(|location| location.region.clone()),
+        servers,
+    };
 
     Ok(HttpResponse::Ok().json(response))
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct WorldListResponse {
     pub worlds: Vec<WorldInfo>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct WorldInfo {
     pub id: String,
     pub name: String,
     pub server_id: String,
     pub ip: String,
     pub port: u16,
+    #[schema(schema_with = server_status)]
     pub status: String,
     pub current_players: u32,
     pub max_players: u32,
 }
 
+#[utoipa::path(
+    tag = "servers",
+    summary = "Online worlds with their address and population",
+    responses(
+        (status = 200, description = "Online worlds", body = WorldListResponse),
+    ),
+)]
 #[get("/worlds")]
 pub async fn list_worlds(
     config: web::Data<ServerConfig>,
     health_monitor: web::Data<HealthMonitor>,
+    runtime: Option<web::Data<Option<Arc<MuCoreRuntime>>>>,
 ) -> Result<HttpResponse> {
+    let runtime = runtime.as_ref().and_then(|data| data.get_ref().as_ref());
     let mut worlds = Vec::new();
 
     for server in &config.servers {
@@ -85,15 +180,20 @@ pub async fn list_worlds(
                 .get_world_status(&world.id)
                 .unwrap_or((false, 0));
 
-            // Only include online worlds
+            // Only include online worlds; closed ones stay listed but not joinable
             if is_online {
+                let status = if in_maintenance(world, runtime) {
+                    "maintenance"
+                } else {
+                    "online"
+                };
                 worlds.push(WorldInfo {
                     id: world.id.clone(),
                     name: world.name.clone(),
                     server_id: server.id.clone(),
                     ip: world.ip.clone(),
                     port: world.port,
-                    status: "online".to_string(),
+                    status: status.to_string(),
                     current_players,
                     max_players: world.max_players,
                 });
@@ -105,3 +205,8 @@ pub async fn list_worlds(
 
     Ok(HttpResponse::Ok().json(response))
 }
+
+/// Routes of this module, for the OpenAPI document.
+#[derive(OpenApi)]
+#[openapi(paths(list_servers, list_worlds))]
+pub(crate) struct ServersApi;
@@ -1,12 +1,18 @@
 // Library exports for testing and reuse
 
 pub mod auth_token;
+pub mod cli;
 pub mod config;
 pub mod db;
+pub mod email;
 pub mod error;
+pub mod geoip;
 pub mod handlers;
 pub mod middleware;
 pub mod monitor;
+pub mod openapi;
+pub mod password;
 pub mod protocol_runtime;
+pub mod roles;
 pub mod runtime;
 pub mod session;
@@ -1,11 +1,17 @@
 mod auth_token;
+mod cli;
 mod config;
 mod db;
+mod email;
 mod error;
+mod geoip;
 mod handlers;
 mod middleware;
 mod monitor;
+mod openapi;
+mod password;
 mod protocol_runtime;
+mod roles;
 mod runtime;
 mod session;
 
@@ -18,9 +24,17 @@ use tokio::time;
 
 use auth_token::AuthTokenService;
 use config::ServerConfig;
+use db::backup::{BackupConfig, BackupScheduler};
 use db::MongoDbContext;
-use middleware::{auth_middleware, rate_limit_middleware, RateLimiter};
+use email::{EmailConfig, EmailNotifier};
+use geoip::GeoIpDatabase;
+use middleware::{
+    admin_middleware, auth_middleware, rate_limit_middleware, AdminToken, RateLimiter,
+};
 use monitor::HealthMonitor;
+use password::Passwords;
+use runtime::transfer_limits::TRANSFER_WINDOW_MS;
+use runtime::webhooks::WebhookEvent;
 use runtime::{start_quic_gateway, MuCoreRuntime, QuicGatewayHandle, QuicTlsPaths, RuntimeConfig};
 use session::SessionManager;
 
@@ -35,6 +49,32 @@ async fn main() -> std::io::Result<()> {
     // Initialize logger
     env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
 
+    let command = cli::parse_args(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}\n\n{}", e, cli::USAGE);
+        std::process::exit(2);
+    });
+    let dry_run = std::env::var("PERSISTENCE_DRY_RUN")
+        .ok()
+        .map(|v| matches!(v.to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false);
+
+    match command {
+        cli::Command::Serve => {}
+        cli::Command::Help => {
+            println!("{}", cli::USAGE);
+            return Ok(());
+        }
+        command => {
+            let import_dry_run = matches!(command, cli::Command::Import { dry_run: true, .. });
+            let db_context = connect_database(dry_run || import_dry_run).await;
+            if let Err(e) = cli::run(command, &db_context).await {
+                eprintln!("{:#}", e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+    }
+
     log::info!("Starting Connect Server...");
     log::info!("Protocol version: {}", protocol::protocol_version());
 
@@ -52,24 +92,72 @@ async fn main() -> std::io::Result<()> {
     });
     log::info!("Loaded configuration with {} servers", config.servers.len());
 
-    // Connect to MongoDB
-    let mongodb_uri =
-        std::env::var("MONGODB_URI").unwrap_or_else(|_| "mongodb://localhost:27017".to_string());
-    let database_name = std::env::var("DATABASE_NAME").unwrap_or_else(|_| "mu_online".to_string());
+    let geoip: Option<Arc<GeoIpDatabase>> = match GeoIpDatabase::from_config(&config.geoip) {
+        Ok(Some(db)) if db.is_empty() => {
+            log::warn!("GeoIP server hints disabled: the database has no networks");
+            None
+        }
+        Ok(Some(db)) => {
+            log::info!("GeoIP server hints enabled with {} networks", db.len());
+            Some(Arc::new(db))
+        }
+        Ok(None) => None,
+        Err(err) => {
+            log::error!("GeoIP server hints disabled: {}", err);
+            None
+        }
+    };
 
-    log::info!("Connecting to MongoDB at {}...", mongodb_uri);
-    let client = Client::with_uri_str(&mongodb_uri)
-        .await
-        .expect("Failed to connect to MongoDB");
+    let db_context = connect_database(dry_run).await;
+    if db_context.is_dry_run() {
+        log::warn!("PERSISTENCE_DRY_RUN enabled. Database writes are logged and discarded.");
+    }
 
-    let db_context = MongoDbContext::new(client, &database_name);
+    let backups = match BackupConfig::from_vars(|name| std::env::var(name).ok()) {
+        Ok(Some(config)) => {
+            log::info!(
+                "Backing up every {}s to {}, keeping the newest {}",
+                config.interval.as_secs(),
+                config.target.describe(),
+                config.keep
+            );
+            Some(BackupScheduler::new(config, db_context.clone()))
+        }
+        Ok(None) => {
+            log::warn!("BACKUP_TARGET not configured. Scheduled backups are disabled.");
+            None
+        }
+        Err(err) => {
+            log::error!("Scheduled backups disabled: {}", err);
+            None
+        }
+    };
 
-    // Initialize database indexes
-    log::info!("Initializing database indexes...");
-    db_context
-        .init_indexes()
-        .await
-        .expect("Failed to initialize database indexes");
+    let email_notifier = match EmailConfig::from_vars(|name| std::env::var(name).ok()) {
+        Ok(Some(config)) => match EmailNotifier::start(&config, db_context.email_queue()) {
+            Ok((notifier, worker)) => {
+                log::info!(
+                    "Account e-mails go out through {}:{}",
+                    config.host,
+                    config.port
+                );
+                tokio::spawn(worker.run());
+                notifier
+            }
+            Err(err) => {
+                log::error!("Account e-mails disabled: {}", err);
+                EmailNotifier::default()
+            }
+        },
+        Ok(None) => {
+            log::warn!("SMTP_HOST not configured. Account e-mails are disabled.");
+            EmailNotifier::default()
+        }
+        Err(err) => {
+            log::error!("Account e-mails disabled: {}", err);
+            EmailNotifier::default()
+        }
+    };
 
     // Create shared state
     let session_expiry_hours = std::env::var("SESSION_EXPIRY_HOURS")
@@ -83,6 +171,19 @@ async fn main() -> std::io::Result<()> {
 
     log::info!("Session expiry set to {} hours", session_expiry_hours);
 
+    let admin_token = AdminToken::new(std::env::var("ADMIN_API_TOKEN").ok());
+    if !admin_token.is_configured() {
+        log::warn!("ADMIN_API_TOKEN not configured. Admin routes are disabled.");
+    }
+
+    let passwords = Passwords::from_vars(|name| std::env::var(name).ok()).unwrap_or_else(|err| {
+        eprintln!("Failed to load password pepper: {}", err);
+        std::process::exit(1);
+    });
+    if !passwords.is_peppered() {
+        log::warn!("PASSWORD_PEPPER not configured. Password hashes are not peppered.");
+    }
+
     let auth_token_ttl_seconds: u64 = std::env::var("AUTH_TOKEN_TTL_SECONDS")
         .ok()
         .and_then(|v| v.parse().ok())
@@ -149,6 +250,126 @@ async fn main() -> std::io::Result<()> {
         None
     };
 
+    if let Some(runtime) = runtime_core.as_ref() {
+        match db_context.item_transfers().recover_operations().await {
+            Ok(0) => {}
+            Ok(replayed) => log::warn!("Completed {} interrupted item operations", replayed),
+            Err(err) => log::error!("Failed to recover item operations: {}", err),
+        }
+
+        match db_context.item_transfers().find_holders().await {
+            Ok(holders) => {
+                log::info!("Restored holders of {} items", holders.len());
+                for (serial, holder) in holders {
+                    runtime.item_ledger().restore(serial, holder);
+                }
+            }
+            Err(err) => log::error!("Failed to restore item holders: {}", err),
+        }
+
+        match db_context.reward_mail().find_all().await {
+            Ok(records) => {
+                log::info!("Loaded {} reward mails", records.len());
+                runtime.mailbox().load(records);
+            }
+            Err(err) => log::error!("Failed to load reward mail: {}", err),
+        }
+
+        match db_context.zen_balances().find_all().await {
+            Ok(records) => {
+                log::info!("Loaded zen of {} characters", records.len());
+                runtime.wallets().load(records);
+            }
+            Err(err) => log::error!("Failed to load zen balances: {}", err),
+        }
+
+        match db_context.guild_relations().find_all().await {
+            Ok(records) => {
+                log::info!("Loaded {} guild relations", records.len());
+                runtime.guild_relations().load(records);
+            }
+            Err(err) => log::error!("Failed to load guild relations: {}", err),
+        }
+
+        match db_context.account_settings().find_all().await {
+            Ok(records) => {
+                log::info!("Loaded synced settings of {} accounts", records.len());
+                runtime.account_settings().load(records);
+            }
+            Err(err) => log::error!("Failed to load account settings: {}", err),
+        }
+
+        match db_context.sequence_events().find_all().await {
+            Ok(records) => {
+                log::info!("Resuming {} sequence event phases", records.len());
+                runtime.sequence_events().load(records);
+                runtime.sync_event_doors();
+            }
+            Err(err) => log::error!("Failed to load sequence event phases: {}", err),
+        }
+
+        match db_context.gens_members().find_all().await {
+            Ok(records) => {
+                log::info!("Loaded {} Gens members", records.len());
+                runtime.gens().load(records);
+            }
+            Err(err) => log::error!("Failed to load Gens members: {}", err),
+        }
+
+        match db_context.quest_logs().find_all().await {
+            Ok(records) => {
+                log::info!("Loaded quest logs of {} characters", records.len());
+                runtime.quest_logs().load(records);
+            }
+            Err(err) => log::error!("Failed to load quest logs: {}", err),
+        }
+
+        match db_context.bestiaries().find_all().await {
+            Ok(records) => {
+                log::info!("Loaded bestiaries of {} characters", records.len());
+                runtime.bestiary().load(records);
+            }
+            Err(err) => log::error!("Failed to load bestiaries: {}", err),
+        }
+
+        if let Some(shop) = runtime.cash_shop() {
+            let cash_shop = db_context.cash_shop();
+            match (
+                cash_shop.find_balances().await,
+                cash_shop.find_receipts().await,
+            ) {
+                (Ok(balances), Ok(receipts)) => {
+                    log::info!(
+                        "Loaded cash balances of {} accounts and {} receipts",
+                        balances.len(),
+                        receipts.len()
+                    );
+                    shop.load(balances, receipts);
+                }
+                (Err(err), _) | (_, Err(err)) => {
+                    log::error!("Failed to load the cash shop ledger: {}", err)
+                }
+            }
+        }
+
+        let now_ms = auth_token::now_ms();
+        match db_context
+            .account_transfers()
+            .find_since(now_ms.saturating_sub(TRANSFER_WINDOW_MS))
+            .await
+        {
+            Ok(records) => {
+                log::info!("Loaded {} account transfers of the last day", records.len());
+                runtime.transfer_limits().load(records, now_ms);
+            }
+            Err(err) => log::error!("Failed to load account transfers: {}", err),
+        }
+
+        runtime.webhooks().notify(&WebhookEvent::ServerStarted {
+            worlds: runtime.config().worlds.len(),
+        });
+    }
+
     let enable_quic_gateway = std::env::var("ENABLE_QUIC_GATEWAY")
         .ok()
         .map(|v| matches!(v.to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on"))
@@ -234,6 +455,161 @@ async fn main() -> std::io::Result<()> {
         }
     });
 
+    if let Some(runtime) = runtime_core.clone() {
+        tokio::spawn(async move {
+            let mut interval = time::interval(Duration::from_secs(1));
+            loop {
+                interval.tick().await;
+                let migrated = runtime.enforce_maintenance(auth_token::now_ms()).await;
+                if migrated > 0 {
+                    log::info!(
+                        "Maintenance: migrated {} sessions to fallback towns",
+                        migrated
+                    );
+                }
+                runtime.enforce_restart(auth_token::now_ms());
+                runtime.end_expired_guild_wars(auth_token::now_ms());
+                runtime.run_sequence_events(auth_token::now_ms());
+                runtime.run_doppelganger(auth_token::now_ms()).await;
+                let expired = runtime.expire_items(auth_token::now_ms());
+                if expired > 0 {
+                    log::info!("Items: {} time-limited items expired", expired);
+                }
+            }
+        });
+    }
+
+    let account_settings_repository = db_context.account_settings();
+    let guild_war_repository = db_context.guild_wars();
+    let sequence_event_repository = db_context.sequence_events();
+    let item_transfer_repository = db_context.item_transfers();
+    let gens_member_repository = db_context.gens_members();
+    let quest_log_repository = db_context.quest_logs();
+    let bestiary_repository = db_context.bestiaries();
+    let reward_mail_repository = db_context.reward_mail();
+    let zen_balance_repository = db_context.zen_balances();
+    let cash_shop_repository = db_context.cash_shop();
+    let account_transfer_repository = db_context.account_transfers();
+    if let Some(runtime) = runtime_core.clone() {
+        let settings_repository = account_settings_repository.clone();
+        let war_repository = guild_war_repository.clone();
+        let event_repository = sequence_event_repository.clone();
+        let transfer_repository = item_transfer_repository.clone();
+        let gens_repository = gens_member_repository.clone();
+        let quest_repository = quest_log_repository.clone();
+        let bestiary_repository = bestiary_repository.clone();
+        let mail_repository = reward_mail_repository.clone();
+        let zen_repository = zen_balance_repository.clone();
+        let cash_shop_repository = cash_shop_repository.clone();
+        let account_transfer_repository = account_transfer_repository.clone();
+        tokio::spawn(async move {
+            let mut interval = time::interval(Duration::from_secs(30));
+            loop {
+                interval.tick().await;
+                let written = runtime
+                    .account_settings()
+                    .persist(&settings_repository)
+                    .await;
+                if written > 0 {
+                    log::debug!("Saved synced settings of {} accounts", written);
+                }
+                let written = runtime.guild_wars().persist(&war_repository).await;
+                if written > 0 {
+                    log::info!("Saved results of {} guild wars", written);
+                }
+                let written = runtime.sequence_events().persist(&event_repository).await;
+                if written > 0 {
+                    log::debug!("Saved phases of {} sequence events", written);
+                }
+                let written = runtime.item_ledger().persist(&transfer_repository).await;
+                if written > 0 {
+                    log::debug!("Saved {} item transfers", written);
+                }
+                runtime.announce_ranking_leaders();
+                let written = runtime.gens().persist(&gens_repository).await;
+                if written > 0 {
+                    log::debug!("Saved {} Gens members", written);
+                }
+                let written = runtime.quest_logs().persist(&quest_repository).await;
+                if written > 0 {
+                    log::debug!("Saved quest logs of {} characters", written);
+                }
+                let written = runtime.bestiary().persist(&bestiary_repository).await;
+                if written > 0 {
+                    log::debug!("Saved bestiaries of {} characters", written);
+                }
+                let written = runtime.mailbox().persist(&mail_repository).await;
+                if written > 0 {
+                    log::debug!("Saved {} reward mails", written);
+                }
+                let written = runtime.wallets().persist(&zen_repository).await;
+                if written > 0 {
+                    log::debug!("Saved zen of {} characters", written);
+                }
+                if let Some(shop) = runtime.cash_shop() {
+                    let written = shop.persist(&cash_shop_repository).await;
+                    if written > 0 {
+                        log::debug!("Saved {} cash ledger entries", written);
+                    }
+                }
+                let limits = runtime.transfer_limits();
+                let written = limits
+                    .persist(&account_transfer_repository, auth_token::now_ms())
+                    .await;
+                if written > 0 {
+                    log::debug!("Saved {} account transfers", written);
+                }
+                limits.prune_idle(auth_token::now_ms());
+            }
+        });
+    }
+
+    if let Some(runtime) = runtime_core.clone() {
+        tokio::spawn(async move {
+            let mut interval = time::interval(Duration::from_secs(300)); // Every 5 minutes
+            loop {
+                interval.tick().await;
+                let report = runtime.scan_item_dupes(auth_token::now_ms());
+                if !report.anomalies.is_empty() {
+                    log::warn!(
+                        "Item dupe scan: {} anomalies in {} items",
+                        report.anomalies.len(),
+                        report.items_scanned
+                    );
+                }
+            }
+        });
+    }
+
+    if let Some(backups) = backups.clone() {
+        let period = backups.config().interval;
+        tokio::spawn(async move {
+            // The first tick fires at once, so every boot starts with a backup.
+            let mut interval = time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(err) = backups.run_backup().await {
+                    log::warn!("Scheduled backup skipped: {}", err);
+                }
+            }
+        });
+    }
+
+    if let Some(backups) = backups.clone() {
+        let period = backups.config().verify_interval;
+        if !period.is_zero() {
+            tokio::spawn(async move {
+                let mut interval = time::interval_at(time::Instant::now() + period, period);
+                loop {
+                    interval.tick().await;
+                    if let Err(err) = backups.run_verification().await {
+                        log::warn!("Scheduled restore verification skipped: {}", err);
+                    }
+                }
+            });
+        }
+    }
+
     let rate_limiter_clone = rate_limiter.clone();
     tokio::spawn(async move {
         let mut interval = time::interval(Duration::from_secs(300)); // Every 5 minutes
@@ -257,7 +633,7 @@ async fn main() -> std::io::Result<()> {
     let auth_token_for_app = auth_token_service.clone();
 
     // Start HTTP server
-    let http_result = HttpServer::new(move || {
+    let http_server = HttpServer::new(move || {
         App::new()
             // Shared state
             .app_data(web::Data::new(db_context.clone()))
@@ -267,9 +643,51 @@ async fn main() -> std::io::Result<()> {
             .app_data(web::Data::new(rate_limiter.clone()))
             .app_data(web::Data::new(runtime_core_for_app.clone()))
             .app_data(web::Data::new(auth_token_for_app.clone()))
+            .app_data(web::Data::new(admin_token.clone()))
+            .app_data(web::Data::new(passwords.clone()))
+            .app_data(web::Data::new(backups.clone()))
+            .app_data(web::Data::new(geoip.clone()))
+            .app_data(web::Data::new(email_notifier.clone()))
             // Middleware
             .wrap(actix_middleware::Logger::default())
             .wrap(actix_middleware::Compress::default())
+            // Operator routes (admin token or a role allowing them), ahead of
+            // the catch-all scopes so only `/admin` paths go through the check
+            .service(
+                web::scope("/admin")
+                    .wrap(actix_middleware::from_fn(admin_middleware))
+                    .service(handlers::list_maintenance)
+                    .service(handlers::begin_maintenance)
+                    .service(handlers::end_maintenance)
+                    .service(handlers::planned_restart)
+                    .service(handlers::schedule_restart)
+                    .service(handlers::cancel_restart)
+                    .service(handlers::grant_reward)
+                    .service(handlers::declare_guild_relation)
+                    .service(handlers::revoke_guild_relation)
+                    .service(handlers::list_stress_runs)
+                    .service(handlers::start_stress_run)
+                    .service(handlers::list_doors)
+                    .service(handlers::set_door)
+                    .service(handlers::kick_session)
+                    .service(handlers::transfer_session)
+                    .service(handlers::list_guild_wars)
+                    .service(handlers::start_guild_war)
+                    .service(handlers::list_helper_sessions)
+                    .service(handlers::list_sequence_events)
+                    .service(handlers::resolve_sequence_event)
+                    .service(handlers::list_doppelganger_runs)
+                    .service(handlers::start_doppelganger_run)
+                    .service(handlers::item_dupe_report)
+                    .service(handlers::list_item_transfers)
+                    .service(handlers::transfer_limit_report)
+                    .service(handlers::backup_status)
+                    .service(handlers::start_backup)
+                    .service(handlers::verify_backup)
+                    .service(handlers::set_account_role)
+                    .service(handlers::cash_account)
+                    .service(handlers::top_up_cash),
+            )
             // Public routes (no authentication required)
             .service(
                 web::scope("")
@@ -281,10 +699,21 @@ async fn main() -> std::io::Result<()> {
                     .service(handlers::runtime_maps)
                     .service(handlers::runtime_persistence)
                     .service(handlers::runtime_stats)
+                    .service(handlers::gens_ranking)
+                    .service(openapi::openapi_json)
+                    .configure(|cfg| {
+                        if cfg!(debug_assertions) {
+                            cfg.service(openapi::swagger_ui);
+                        }
+                    })
                     .service(
                         web::scope("")
                             .wrap(actix_middleware::from_fn(rate_limit_middleware))
-                            .service(handlers::login),
+                            .service(handlers::login)
+                            .service(handlers::register)
+                            .service(handlers::verify_email)
+                            .service(handlers::request_password_reset)
+                            .service(handlers::confirm_password_reset),
                     ),
             )
             // Protected routes (authentication required)
@@ -292,18 +721,58 @@ async fn main() -> std::io::Result<()> {
                 web::scope("")
                     .wrap(actix_middleware::from_fn(auth_middleware))
                     .service(handlers::logout)
-                    .service(handlers::list_characters),
+                    .service(handlers::list_characters)
+                    .service(handlers::cash_shop_catalog)
+                    .service(handlers::purchase_cash_product),
             )
     })
     .bind((server_host, server_port))?
-    .run()
-    .await;
+    .run();
+
+    // A planned restart stops the HTTP server like a signal would, so the
+    // shutdown below runs the same way.
+    if let Some(runtime) = runtime_core.clone() {
+        let server_handle = http_server.handle();
+        tokio::spawn(async move {
+            runtime.restart().triggered().await;
+            log::warn!("Planned restart: shutting down");
+            server_handle.stop(true).await;
+        });
+    }
+
+    let http_result = http_server.await;
 
     if let Some(handle) = &quic_gateway_handle {
         handle.close();
     }
 
     if let Some(runtime) = &runtime_core {
+        runtime
+            .account_settings()
+            .persist(&account_settings_repository)
+            .await;
+        runtime.end_expired_guild_wars(auth_token::now_ms());
+        runtime.guild_wars().persist(&guild_war_repository).await;
+        runtime
+            .sequence_events()
+            .persist(&sequence_event_repository)
+            .await;
+        runtime
+            .item_ledger()
+            .persist(&item_transfer_repository)
+            .await;
+        runtime.gens().persist(&gens_member_repository).await;
+        runtime.quest_logs().persist(&quest_log_repository).await;
+        runtime.bestiary().persist(&bestiary_repository).await;
+        runtime.mailbox().persist(&reward_mail_repository).await;
+        runtime.wallets().persist(&zen_balance_repository).await;
+        if let Some(shop) = runtime.cash_shop() {
+            shop.persist(&cash_shop_repository).await;
+        }
+        runtime
+            .transfer_limits()
+            .persist(&account_transfer_repository, auth_token::now_ms())
+            .await;
         if let Err(err) = runtime.shutdown().await {
             log::error!("MU core runtime shutdown failed: {}", err);
         }
@@ -311,3 +780,25 @@ async fn main() -> std::io::Result<()> {
 
     http_result
 }
+
+async fn connect_database(dry_run: bool) -> MongoDbContext {
+    let mongodb_uri =
+        std::env::var("MONGODB_URI").unwrap_or_else(|_| "mongodb://localhost:27017".to_string());
+    let database_name = std::env::var("DATABASE_NAME").unwrap_or_else(|_| "mu_online".to_string());
+
+    log::info!("Connecting to MongoDB at {}...", mongodb_uri);
+    let client = Client::with_uri_str(&mongodb_uri)
+        .await
+        .expect("Failed to connect to MongoDB");
+
+    let db_context = MongoDbContext::new(client, &database_name).with_dry_run(dry_run);
+
+    // Initialize database indexes
+    log::info!("Initializing database indexes...");
+    db_context
+        .init_indexes()
+        .await
+        .expect("Failed to initialize database indexes");
+
+    db_context
+}
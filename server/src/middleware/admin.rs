@@ -0,0 +1,139 @@
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    http::{header::AUTHORIZATION, Method},
+    middleware::Next,
+    web,
+};
+
+use crate::auth_token::{now_ms, AuthTokenService};
+use crate::error::ConnectServerError;
+use crate::roles::{AccountRole, Permission};
+use crate::session::SessionManager;
+
+pub const ADMIN_TOKEN_HEADER: &str = "X-Admin-Token";
+
+/// Shared secret granting the admin role on operator routes, disabled when
+/// unset. Accounts reach the same routes with their auth token and role.
+#[derive(Clone, Default)]
+pub struct AdminToken(Option<String>);
+
+impl AdminToken {
+    pub fn new(token: Option<String>) -> Self {
+        Self(token.filter(|token| !token.is_empty()))
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.0.is_some()
+    }
+
+    fn matches(&self, presented: &str) -> bool {
+        let Some(expected) = &self.0 else {
+            return false;
+        };
+
+        // Compare every byte so the check does not leak the matching prefix length.
+        expected.len() == presented.len()
+            && expected
+                .bytes()
+                .zip(presented.bytes())
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+}
+
+pub async fn admin_middleware(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let role = if let Some(presented) = req.headers().get(ADMIN_TOKEN_HEADER) {
+        let admin_token = req
+            .app_data::<web::Data<AdminToken>>()
+            .filter(|token| token.is_configured())
+            .ok_or_else(|| ConnectServerError::Forbidden("Admin token is disabled".to_string()))?;
+        if !presented
+            .to_str()
+            .is_ok_and(|presented| admin_token.matches(presented))
+        {
+            log::warn!("Rejected admin request to {}", req.path());
+            return Err(ConnectServerError::Forbidden("Invalid admin token".to_string()).into());
+        }
+        AccountRole::Admin
+    } else {
+        bearer_role(&req)?
+    };
+
+    let permission = required_permission(req.method(), req.path());
+    if !role.allows(permission) {
+        log::warn!(
+            "Rejected {} {} for role {}",
+            req.method(),
+            req.path(),
+            role.name()
+        );
+        return Err(ConnectServerError::Forbidden(
+            "Role does not allow this operation".to_string(),
+        )
+        .into());
+    }
+
+    next.call(req).await
+}
+
+/// Role of the account behind an `Authorization: Bearer <auth token>` header,
+/// as long as the HTTP session the token was issued with is still open.
+fn bearer_role(req: &ServiceRequest) -> Result<AccountRole, actix_web::Error> {
+    let token = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(|| {
+            ConnectServerError::Unauthorized(
+                "Admin token or bearer auth token required".to_string(),
+            )
+        })?;
+    let auth_tokens = req
+        .app_data::<web::Data<AuthTokenService>>()
+        .ok_or_else(|| ConnectServerError::Internal("Auth tokens not available".to_string()))?;
+    let claims = auth_tokens
+        .verify(token, now_ms())
+        .map_err(|_| ConnectServerError::Unauthorized("Invalid auth token".to_string()))?;
+    if let Some(sessions) = req.app_data::<web::Data<SessionManager>>() {
+        sessions
+            .validate_session(&claims.session_id)
+            .map_err(|_| ConnectServerError::InvalidSession)?;
+    }
+    Ok(claims.role)
+}
+
+fn required_permission(method: &Method, path: &str) -> Permission {
+    if path.starts_with("/admin/accounts/") {
+        Permission::ManageRoles
+    } else if method == Method::GET {
+        Permission::ViewOperations
+    } else {
+        Permission::ManageServer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_need_less_than_writes() {
+        assert_eq!(
+            required_permission(&Method::GET, "/admin/doors"),
+            Permission::ViewOperations
+        );
+        assert_eq!(
+            required_permission(&Method::POST, "/admin/doors"),
+            Permission::ManageServer
+        );
+        assert_eq!(
+            required_permission(&Method::PUT, "/admin/accounts/alice/role"),
+            Permission::ManageRoles
+        );
+    }
+}
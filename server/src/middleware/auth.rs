@@ -1,11 +1,11 @@
 use actix_web::{
     body::MessageBody,
     dev::{ServiceRequest, ServiceResponse},
-    error::ErrorUnauthorized,
     middleware::Next,
     HttpMessage,
 };
 
+use crate::error::ConnectServerError;
 use crate::session::SessionManager;
 
 pub async fn auth_middleware(
@@ -16,17 +16,17 @@ pub async fn auth_middleware(
     let session_id = req
         .cookie("session_id")
         .map(|c| c.value().to_string())
-        .ok_or_else(|| ErrorUnauthorized("Authentication required"))?;
+        .ok_or_else(|| ConnectServerError::Unauthorized("Authentication required".to_string()))?;
 
     // Get SessionManager from app data
     let session_manager = req
         .app_data::<actix_web::web::Data<SessionManager>>()
-        .ok_or_else(|| ErrorUnauthorized("Session manager not available"))?;
+        .ok_or_else(|| ConnectServerError::Internal("Session manager not available".to_string()))?;
 
     // Validate session
     session_manager
         .validate_session(&session_id)
-        .map_err(|_| ErrorUnauthorized("Invalid or expired session"))?;
+        .map_err(|_| ConnectServerError::InvalidSession)?;
 
     // Store session_id in request extensions for handlers to use
     req.extensions_mut().insert(session_id.clone());
@@ -1,5 +1,7 @@
+pub mod admin;
 pub mod auth;
 pub mod rate_limit;
 
+pub use admin::{admin_middleware, AdminToken};
 pub use auth::auth_middleware;
 pub use rate_limit::{rate_limit_middleware, RateLimiter};
@@ -1,7 +1,6 @@
 use actix_web::{
     body::MessageBody,
     dev::{ServiceRequest, ServiceResponse},
-    error::ErrorTooManyRequests,
     middleware::Next,
 };
 use dashmap::DashMap;
@@ -9,6 +8,8 @@ use std::net::IpAddr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use crate::error::ConnectServerError;
+
 const MAX_REQUESTS: usize = 10;
 const WINDOW_DURATION: Duration = Duration::from_secs(60);
 
@@ -43,6 +44,17 @@ impl RateLimiter {
         true
     }
 
+    /// Time until the oldest request of `ip` leaves the window, letting the
+    /// next one through.
+    pub fn retry_after(&self, ip: IpAddr) -> Duration {
+        let now = Instant::now();
+        self.requests
+            .get(&ip)
+            .and_then(|entry| entry.iter().min().copied())
+            .map(|oldest| (oldest + WINDOW_DURATION).saturating_duration_since(now))
+            .unwrap_or(Duration::ZERO)
+    }
+
     pub fn cleanup_old_entries(&self) {
         let cutoff = Instant::now() - WINDOW_DURATION;
 
@@ -66,19 +78,22 @@ pub async fn rate_limit_middleware(
     // Get client IP
     let peer_addr = req
         .peer_addr()
-        .ok_or_else(|| ErrorTooManyRequests("Unable to determine client IP"))?;
+        .ok_or_else(|| ConnectServerError::Internal("Unable to determine client IP".to_string()))?;
 
     let ip = peer_addr.ip();
 
     // Get RateLimiter from app data
     let rate_limiter = req
         .app_data::<actix_web::web::Data<RateLimiter>>()
-        .ok_or_else(|| ErrorTooManyRequests("Rate limiter not available"))?;
+        .ok_or_else(|| ConnectServerError::Internal("Rate limiter not available".to_string()))?;
 
     // Check rate limit
     if !rate_limiter.check_rate_limit(ip) {
         log::warn!("Rate limit exceeded for IP: {}", ip);
-        return Err(ErrorTooManyRequests("Too many requests"));
+        return Err(ConnectServerError::RateLimited {
+            retry_after: rate_limiter.retry_after(ip),
+        }
+        .into());
     }
 
     next.call(req).await
@@ -0,0 +1,782 @@
+//! OpenAPI 3.1 description of the HTTP API.
+//!
+//! `/api-docs/openapi.json` is always served; debug builds also serve Swagger
+//! UI at `/api-docs`. Handlers are annotated with `#[utoipa::path]`, each
+//! handler module gathers its routes in an `OpenApi` derive, and
+//! request/response types derive their schemas with [`ToSchema`]. Tests check
+//! schemas against the JSON the types actually serialize to, and the routes
+//! against the ones `main.rs` mounts, so the document cannot drift from the
+//! handlers.
+
+use actix_web::{get, HttpResponse};
+use serde_json::Value;
+use utoipa::openapi::path::{Operation, PathItem};
+use utoipa::openapi::security::{
+    ApiKey, ApiKeyValue, Http, HttpAuthScheme, SecurityRequirement, SecurityScheme,
+};
+use utoipa::openapi::{ContentBuilder, Info, Ref, ResponseBuilder};
+use utoipa::{Modify, OpenApi, ToSchema};
+
+use crate::error::ErrorResponse;
+use crate::middleware::admin::ADMIN_TOKEN_HEADER;
+
+/// Security scheme of routes behind the session cookie set by `/login`.
+pub const SESSION_COOKIE: &str = "session_cookie";
+/// Security scheme of the operator routes.
+pub const ADMIN_TOKEN: &str = "admin_token";
+/// Auth token from `/login` sent as a bearer token; operator routes check the
+/// role embedded in it.
+pub const BEARER_TOKEN: &str = "bearer_token";
+
+/// Operator routes: the admin token or an account's bearer token, with their
+/// shared rejections.
+pub const ADMIN_ROUTES: RouteDefaults = RouteDefaults {
+    security: &[ADMIN_TOKEN, BEARER_TOKEN],
+    errors: &[
+        (401, "No admin token or bearer token, or an expired one"),
+        (
+            403,
+            "Admin token disabled or wrong, or the account's role does not allow the route",
+        ),
+        (500, "Runtime core disabled"),
+    ],
+};
+
+/// Schema of `T` as JSON.
+pub fn schema_value<T: ToSchema>() -> Value {
+    serde_json::to_value(T::schema()).unwrap_or_default()
+}
+
+/// Property names of an object schema, sorted.
+pub fn property_names(schema: &Value) -> Vec<&str> {
+    schema["properties"]
+        .as_object()
+        .map(|properties| properties.keys().map(String::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// Security and error responses shared by every route of an `OpenApi`
+/// derive; a route documenting the same status keeps its own description.
+pub struct RouteDefaults {
+    /// Schemes the routes accept, any one of them.
+    pub security: &'static [&'static str],
+    /// Errors answered with the shared [`ErrorResponse`] body, by the
+    /// handlers or by their middlewares.
+    pub errors: &'static [(u16, &'static str)],
+}
+
+impl Modify for RouteDefaults {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        for operation in openapi.paths.paths.values_mut().flat_map(operations) {
+            operation.security = Some(
+                self.security
+                    .iter()
+                    .map(|&scheme| SecurityRequirement::new(scheme, Vec::<String>::new()))
+                    .collect(),
+            );
+            for &(status, description) in self.errors {
+                operation
+                    .responses
+                    .responses
+                    .entry(status.to_string())
+                    .or_insert_with(|| error_response(description).into());
+            }
+        }
+    }
+}
+
+fn operations(item: &mut PathItem) -> impl Iterator<Item = &mut Operation> {
+    [
+        &mut item.get,
+        &mut item.post,
+        &mut item.put,
+        &mut item.delete,
+    ]
+    .into_iter()
+    .flatten()
+}
+
+fn error_response(description: &str) -> utoipa::openapi::Response {
+    ResponseBuilder::new()
+        .description(description)
+        .content(
+            "application/json",
+            ContentBuilder::new()
+                .schema(Some(Ref::from_schema_name(ErrorResponse::name())))
+                .build(),
+        )
+        .build()
+}
+
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            SESSION_COOKIE,
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new("session_id"))),
+        );
+        components.add_security_scheme(
+            ADMIN_TOKEN,
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(ADMIN_TOKEN_HEADER))),
+        );
+        components.add_security_scheme(
+            BEARER_TOKEN,
+            SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+        );
+    }
+}
+
+#[derive(OpenApi)]
+#[openapi(components(schemas(ErrorResponse)), modifiers(&SecuritySchemes))]
+struct ApiDoc;
+
+/// The full document.
+pub fn document() -> Value {
+    let mut api = ApiDoc::openapi();
+    api.info = Info::new("MU Connect Server", env!("CARGO_PKG_VERSION"));
+    crate::handlers::api_docs(&mut api);
+    serde_json::to_value(api).unwrap_or_default()
+}
+
+#[get("/api-docs/openapi.json")]
+pub async fn openapi_json() -> HttpResponse {
+    HttpResponse::Ok().json(document())
+}
+
+/// Swagger UI page over `/api-docs/openapi.json`; registered in debug builds only.
+#[get("/api-docs")]
+pub async fn swagger_ui() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(SWAGGER_UI_HTML)
+}
+
+const SWAGGER_UI_HTML: &str = r##"<!doctype html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>MU Connect Server API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>
+    window.ui = SwaggerUIBundle({ url: "/api-docs/openapi.json", dom_id: "#swagger-ui" });
+  </script>
+</body>
+</html>
+"##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::db::backup::{BackupRun, BackupStatus, CollectionMismatch, VerificationRun};
+    use crate::handlers::admin::{
+        AccountRoleResponse, BackupRunResponse, BackupStatusResponse, DoppelgangerListResponse,
+        DupeReportResponse, GrantRewardResponse, GuildRelationResponse, GuildWarListResponse,
+        GuildWarResponse, HelperSessionListResponse, ItemTransfersResponse,
+        MaintenanceListResponse, ResolveSequenceEventRequest, RestartResponse,
+        SequenceEventListResponse, StartDoppelgangerRequest, StressListResponse,
+        VerificationResponse,
+    };
+    use crate::handlers::runtime::{Pagination, RuntimeMapsResponse, RuntimeWorldsResponse};
+    use crate::roles::AccountRole;
+    use crate::runtime::config::TransferLimitsConfig;
+    use crate::runtime::directory::{EntrySnapshot, MapSnapshot, WorldSnapshot};
+    use crate::runtime::doppelganger::{DoppelgangerRuns, PartyMember};
+    use crate::runtime::elites::{EliteSpawn, MonsterStats};
+    use crate::runtime::events::SequenceEvents;
+    use crate::runtime::guild_wars::GuildWars;
+    use crate::runtime::helper::HelperSessions;
+    use crate::runtime::item_ledger::{ItemHolder, ItemLedger, TransferReason};
+    use crate::runtime::mailbox::RewardDelivery;
+    use crate::runtime::maintenance::{MaintenanceRegistry, MaintenanceScope};
+    use crate::runtime::map_objects::ObjectCounts;
+    use crate::runtime::map_server::MapServerStats;
+    use crate::runtime::restart::RestartScheduler;
+    use crate::runtime::stress::{StressReport, TickLoad, TickPercentiles};
+    use crate::runtime::transfer_limits::{TransferChannel, TransferLimitReport, TransferLimits};
+    use protocol::{
+        GuildRelation, ItemInstance, ItemOptions, MonsterAffix, MonsterRank, RouteKey,
+        SequenceEvent,
+    };
+
+    /// `$ref` pointing at the component schema of `T`.
+    fn schema_ref<T: ToSchema>() -> Value {
+        json!({ "$ref": format!("#/components/schemas/{}", T::name()) })
+    }
+
+    /// Fails when `value` has a key its schema does not document, or misses a
+    /// required one.
+    fn assert_matches_schema(value: &Value, schema: &Value, document: &Value) {
+        if let Err(err) = check_schema(value, schema, document) {
+            panic!("{value} does not match {schema}: {err}");
+        }
+    }
+
+    fn check_schema(value: &Value, schema: &Value, document: &Value) -> Result<(), String> {
+        if let Some(reference) = schema["$ref"].as_str() {
+            let name = reference.trim_start_matches("#/components/schemas/");
+            let resolved = &document["components"]["schemas"][name];
+            if !resolved.is_object() {
+                return Err(format!("unresolved schema {reference}"));
+            }
+            return check_schema(value, resolved, document);
+        }
+        let nullable = match &schema["type"] {
+            Value::String(kind) => kind == "null",
+            Value::Array(kinds) => kinds.iter().any(|kind| kind == "null"),
+            _ => false,
+        };
+        if value.is_null() && nullable {
+            return Ok(());
+        }
+        if schema["type"] == "null" && !value.is_null() {
+            return Err(format!("{value} is not null"));
+        }
+        if let Some(all) = schema["allOf"].as_array() {
+            return all
+                .iter()
+                .try_for_each(|schema| check_schema(value, schema, document));
+        }
+        if let Some(variants) = schema["oneOf"].as_array() {
+            return variants
+                .iter()
+                .find_map(|schema| check_schema(value, schema, document).ok())
+                .ok_or_else(|| "no `oneOf` variant matches".to_string());
+        }
+        if let Some(allowed) = schema["enum"].as_array() {
+            if !allowed.contains(value) {
+                return Err(format!("{value} is not one of {allowed:?}"));
+            }
+        }
+
+        match value {
+            Value::Object(fields) => {
+                let properties = schema["properties"]
+                    .as_object()
+                    .ok_or_else(|| "object without properties".to_string())?;
+                for (key, field) in fields {
+                    let property = properties
+                        .get(key)
+                        .ok_or_else(|| format!("undocumented field `{key}`"))?;
+                    check_schema(field, property, document)?;
+                }
+                for required in schema["required"].as_array().into_iter().flatten() {
+                    let required = required.as_str().unwrap();
+                    if !fields.contains_key(required) {
+                        return Err(format!("missing field `{required}`"));
+                    }
+                }
+                Ok(())
+            }
+            Value::Array(items) => items
+                .iter()
+                .try_for_each(|item| check_schema(item, &schema["items"], document)),
+            _ => Ok(()),
+        }
+    }
+
+    #[test]
+    fn runtime_responses_match_their_schemas() {
+        let document = document();
+        let world = WorldSnapshot {
+            world_id: 1,
+            world_name: "Midgard".to_string(),
+            entries: vec![EntrySnapshot {
+                entry_id: 1,
+                entry_name: "Midgard-1".to_string(),
+                host: "127.0.0.1".to_string(),
+                port: 55901,
+                current_players: 3,
+                max_players: 500,
+                maps: vec![MapSnapshot {
+                    map_id: 0,
+                    map_name: "Lorencia".to_string(),
+                    instance_id: 0,
+                    current_players: 3,
+                    soft_player_cap: 200,
+                }],
+            }],
+        };
+        let stats = MapServerStats {
+            route: RouteKey {
+                world_id: 1,
+                entry_id: 1,
+                map_id: 0,
+                instance_id: 0,
+            },
+            map_name: "Lorencia".to_string(),
+            current_players: 3,
+            soft_player_cap: 200,
+            monster_count: 16,
+            player_ticks: 10,
+            monster_ticks: 5,
+            monster_degradation_level: 0,
+            player_tick_p95_us: 120,
+            objects: ObjectCounts::default(),
+            evicted_objects: ObjectCounts::default(),
+        };
+        let pagination = Pagination {
+            page: 1,
+            per_page: 50,
+            total: 1,
+            total_pages: 1,
+        };
+
+        let worlds = json!({
+            "worlds": { "worlds": [world] },
+            "pagination": pagination,
+        });
+        assert_matches_schema(&worlds, &schema_ref::<RuntimeWorldsResponse>(), &document);
+        let maps = json!({ "maps": [stats], "pagination": pagination });
+        assert_matches_schema(&maps, &schema_ref::<RuntimeMapsResponse>(), &document);
+    }
+
+    #[test]
+    fn document_resolves_every_reference() {
+        let document = document();
+        let text = document.to_string();
+        for name in text
+            .split("#/components/schemas/")
+            .skip(1)
+            .map(|rest| rest.split('"').next().unwrap())
+        {
+            assert!(
+                document["components"]["schemas"][name].is_object(),
+                "missing component {name}"
+            );
+        }
+    }
+
+    #[test]
+    fn admin_payloads_match_their_schemas() {
+        let document = document();
+        let windows = json!({
+            "windows": [MaintenanceRegistry::new()
+                .schedule(
+                    MaintenanceScope { world_id: 1, map_id: Some(0) },
+                    "patch".to_string(),
+                    60,
+                    0,
+                    1_000,
+                )
+                .unwrap()],
+        });
+        assert_matches_schema(
+            &windows,
+            &schema_ref::<MaintenanceListResponse>(),
+            &document,
+        );
+        for restart in [
+            None,
+            RestartScheduler::new()
+                .schedule(60_000, "patch".to_string(), 0)
+                .ok(),
+        ] {
+            let restart = json!({ "restart": restart });
+            assert_matches_schema(&restart, &schema_ref::<RestartResponse>(), &document);
+        }
+        for delivery in [
+            RewardDelivery::Direct,
+            RewardDelivery::Mailed { mail_id: 7 },
+        ] {
+            let delivery = json!({ "delivery": delivery });
+            assert_matches_schema(&delivery, &schema_ref::<GrantRewardResponse>(), &document);
+        }
+        let revoked = json!({ "guild_id": 1, "target_guild_id": 2, "relation": null });
+        assert_matches_schema(&revoked, &schema_ref::<GuildRelationResponse>(), &document);
+        let declared =
+            json!({ "guild_id": 1, "target_guild_id": 2, "relation": GuildRelation::Alliance });
+        assert_matches_schema(&declared, &schema_ref::<GuildRelationResponse>(), &document);
+
+        let active = StressReport {
+            route: RouteKey::LOBBY,
+            monsters: 500,
+            duration_secs: 60,
+            started_at_ms: 1_000,
+            finished_at_ms: None,
+            before: TickLoad::default(),
+            during: None,
+            elites: vec![EliteSpawn {
+                entity_id: 0x4000_0002,
+                rank: MonsterRank::Boss,
+                affixes: vec![MonsterAffix::Swift, MonsterAffix::Vampiric],
+                stats: MonsterStats::BASE
+                    .with_affixes(&[MonsterAffix::Swift, MonsterAffix::Vampiric]),
+            }],
+        };
+        let finished = StressReport {
+            finished_at_ms: Some(61_000),
+            during: Some(TickLoad {
+                player_tick: TickPercentiles::from_samples(&[120, 80, 300]),
+                monster_tick: TickPercentiles::from_samples(&[900, 1_400]),
+                peak_degradation_level: 1,
+            }),
+            ..active.clone()
+        };
+        let reports = json!({ "reports": [active, finished] });
+        assert_matches_schema(&reports, &schema_ref::<StressListResponse>(), &document);
+
+        let wars = GuildWars::new();
+        let war = wars
+            .start(1, 2, std::time::Duration::from_secs(600), 1_000)
+            .unwrap();
+        assert_matches_schema(
+            &json!({ "war": war }),
+            &schema_ref::<GuildWarResponse>(),
+            &document,
+        );
+        let listed = json!({ "wars": wars.active() });
+        assert_matches_schema(&listed, &schema_ref::<GuildWarListResponse>(), &document);
+
+        let events = SequenceEvents::new();
+        events.open_world(1, 0);
+        let listed = json!({ "events": events.states() });
+        assert_matches_schema(
+            &listed,
+            &schema_ref::<SequenceEventListResponse>(),
+            &document,
+        );
+        let resolve = json!({ "world_id": 1, "event": SequenceEvent::Kanturu, "won": true });
+        assert_matches_schema(
+            &resolve,
+            &schema_ref::<ResolveSequenceEventRequest>(),
+            &document,
+        );
+
+        let runs = DoppelgangerRuns::new();
+        let party = vec![PartyMember {
+            character_id: 7,
+            level: 250,
+        }];
+        runs.open(runs.next_run_id(), RouteKey::LOBBY, party.clone(), 1_000)
+            .unwrap();
+        let listed = json!({ "runs": runs.runs() });
+        assert_matches_schema(
+            &listed,
+            &schema_ref::<DoppelgangerListResponse>(),
+            &document,
+        );
+        let start = json!({
+            "world_id": 1,
+            "party": party,
+            "ticket": { "group": 14, "index": 111, "level": 0, "quantity": 1, "options": ItemOptions::default() },
+        });
+        assert_matches_schema(&start, &schema_ref::<StartDoppelgangerRequest>(), &document);
+
+        let ledger = ItemLedger::new(1_000);
+        let owner = ItemHolder::Character { character_id: 7 };
+        let mut item = ItemInstance {
+            serial: 0,
+            group: 14,
+            index: 13,
+            level: 0,
+            quantity: 1,
+            options: ItemOptions::default(),
+            expires_at_ms: Some(60_000),
+        };
+        let serial = ledger.mint(&mut item, owner, 1_000);
+        let dropped = ItemHolder::Ground {
+            route: RouteKey::LOBBY,
+        };
+        ledger
+            .transfer(serial, owner, dropped, TransferReason::Drop, 2_000)
+            .unwrap();
+        let trail = json!({
+            "serial": serial,
+            "owner": ledger.owner(serial),
+            "transfers": ledger.unsaved_transfers(serial),
+        });
+        assert_matches_schema(&trail, &schema_ref::<ItemTransfersResponse>(), &document);
+        let report = ledger.scan([(owner, &item), (dropped, &item)], 3_000);
+        assert_matches_schema(
+            &json!({ "report": report }),
+            &schema_ref::<DupeReportResponse>(),
+            &document,
+        );
+
+        let limits = TransferLimits::new(&TransferLimitsConfig {
+            zen_per_day: Some(1_000_000),
+            items_per_day: None,
+            report_threshold_percent: 80,
+        });
+        limits
+            .record(9, TransferChannel::Trade, 900_000, 2, 1_000)
+            .unwrap();
+        assert_matches_schema(
+            &json!(limits.near_limits(2_000)),
+            &schema_ref::<TransferLimitReport>(),
+            &document,
+        );
+
+        let helpers = HelperSessions::new();
+        helpers.start(7, 70, RouteKey::LOBBY, 1_000);
+        let sessions = json!({ "sessions": helpers.list() });
+        assert_matches_schema(
+            &sessions,
+            &schema_ref::<HelperSessionListResponse>(),
+            &document,
+        );
+
+        let run = BackupRun {
+            name: "mu_online-20260301T060000Z.json".to_string(),
+            started_at_ms: 1_000,
+            finished_at_ms: 4_000,
+            collections: 12,
+            documents: 3_400,
+            bytes: 1_048_576,
+            pruned: vec!["mu_online-20260222T060000Z.json".to_string()],
+            error: None,
+        };
+        let verification = VerificationRun {
+            backup: Some(run.name.clone()),
+            scratch_database: "mu_online_restore_check".to_string(),
+            started_at_ms: 5_000,
+            finished_at_ms: 9_000,
+            collections: 12,
+            documents: 3_400,
+            mismatches: vec![CollectionMismatch {
+                collection: "characters".to_string(),
+                expected: 40,
+                restored: 39,
+            }],
+            error: None,
+        };
+        let status = BackupStatus {
+            target: "s3://mu-backups/nightly".to_string(),
+            interval_secs: 21_600,
+            keep: 7,
+            verify_interval_secs: 86_400,
+            running: false,
+            last_backup: Some(run.clone()),
+            last_verification: Some(verification.clone()),
+            stored: vec![run.name.clone()],
+        };
+        assert_matches_schema(
+            &json!({ "backups": status }),
+            &schema_ref::<BackupStatusResponse>(),
+            &document,
+        );
+        assert_matches_schema(
+            &json!({ "backups": null }),
+            &schema_ref::<BackupStatusResponse>(),
+            &document,
+        );
+        assert_matches_schema(
+            &json!({ "run": run }),
+            &schema_ref::<BackupRunResponse>(),
+            &document,
+        );
+        assert_matches_schema(
+            &json!({ "verification": verification }),
+            &schema_ref::<VerificationResponse>(),
+            &document,
+        );
+        let promoted = AccountRoleResponse {
+            username: "alice".to_string(),
+            role: AccountRole::GameMaster,
+        };
+        assert_matches_schema(
+            &serde_json::to_value(promoted).unwrap(),
+            &schema_ref::<AccountRoleResponse>(),
+            &document,
+        );
+    }
+
+    #[test]
+    fn request_limits_follow_the_runtime_constants() {
+        use crate::handlers::runtime::{DEFAULT_GENS_RANKING, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+        use crate::runtime::doppelganger::MAX_PARTY_SIZE;
+        use crate::runtime::guild_wars::{MAX_WAR_DURATION, MIN_WAR_DURATION};
+        use crate::runtime::maintenance::{DEFAULT_COUNTDOWN_SECS, DEFAULT_FALLBACK_MAP_ID};
+        use crate::runtime::stress::{MAX_STRESS_DURATION, MAX_STRESS_MONSTERS};
+
+        let document = document();
+        let schemas = &document["components"]["schemas"];
+        let maintenance = &schemas["BeginMaintenanceRequest"]["properties"];
+        assert_eq!(
+            maintenance["countdown_secs"]["default"],
+            DEFAULT_COUNTDOWN_SECS
+        );
+        assert_eq!(
+            maintenance["fallback_map_id"]["default"],
+            DEFAULT_FALLBACK_MAP_ID
+        );
+
+        let war = &schemas["StartGuildWarRequest"]["properties"]["duration_secs"];
+        assert_eq!(war["minimum"], MIN_WAR_DURATION.as_secs() as f64);
+        assert_eq!(war["maximum"], MAX_WAR_DURATION.as_secs() as f64);
+
+        let party = &schemas["StartDoppelgangerRequest"]["properties"]["party"];
+        assert_eq!(party["minItems"], 1);
+        assert_eq!(party["maxItems"], MAX_PARTY_SIZE);
+
+        for (path, name, default) in [
+            ("/runtime/worlds", "per_page", DEFAULT_PAGE_SIZE),
+            ("/runtime/maps", "per_page", DEFAULT_PAGE_SIZE),
+            ("/runtime/gens/ranking", "limit", DEFAULT_GENS_RANKING),
+        ] {
+            let parameter = document["paths"][path]["get"]["parameters"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|parameter| parameter["name"] == name)
+                .unwrap();
+            assert_eq!(parameter["schema"]["maximum"], MAX_PAGE_SIZE as f64);
+            assert_eq!(parameter["schema"]["default"], default);
+        }
+
+        let stress = &schemas["StartStressRequest"]["properties"];
+        assert_eq!(stress["monsters"]["minimum"], 1.0);
+        assert_eq!(stress["monsters"]["maximum"], MAX_STRESS_MONSTERS as f64);
+        assert_eq!(stress["duration_secs"]["minimum"], 1.0);
+        assert_eq!(
+            stress["duration_secs"]["maximum"],
+            MAX_STRESS_DURATION.as_secs() as f64
+        );
+    }
+
+    /// Method and path of the actix attribute on handler `name`, if `source`
+    /// defines it.
+    fn route_of<'a>(source: &'a str, name: &str) -> Option<(&'a str, &'a str)> {
+        let handler = source.find(&format!("\npub async fn {name}("))?;
+        let attribute = &source[source[..handler].rfind("\n#[")? + 3..handler];
+        let (method, rest) = attribute.split_once("(\"")?;
+        Some((method, rest.split('"').next()?))
+    }
+
+    #[test]
+    fn document_covers_every_route_mounted_in_main() {
+        let handlers = [
+            include_str!("handlers/admin.rs"),
+            include_str!("handlers/auth.rs"),
+            include_str!("handlers/cash_shop.rs"),
+            include_str!("handlers/characters.rs"),
+            include_str!("handlers/health.rs"),
+            include_str!("handlers/runtime.rs"),
+            include_str!("handlers/servers.rs"),
+        ];
+        let document = document();
+
+        let mut scope = "";
+        let mut mounted = 0;
+        for line in include_str!("main.rs").lines() {
+            if let Some((_, rest)) = line.split_once("web::scope(\"") {
+                scope = rest.split('"').next().unwrap();
+            }
+            let Some(name) = line.trim().strip_prefix(".service(handlers::") else {
+                continue;
+            };
+            let name = name.trim_end_matches([')', ',']);
+            let (method, path) = handlers
+                .iter()
+                .find_map(|source| route_of(source, name))
+                .unwrap_or_else(|| panic!("no route attribute on handler {name}"));
+            let path = format!("{scope}{path}");
+            assert!(
+                document["paths"][&path][method].is_object(),
+                "{method} {path} ({name}) is not in the document"
+            );
+            mounted += 1;
+        }
+
+        let documented: usize = document["paths"]
+            .as_object()
+            .unwrap()
+            .values()
+            .map(|item| item.as_object().unwrap().len())
+            .sum();
+        assert!(mounted > 0);
+        assert_eq!(documented, mounted, "the document lists unmounted routes");
+    }
+
+    #[test]
+    fn document_lists_every_route_with_its_security() {
+        let document = document();
+        let routes = [
+            ("post", "/login", None),
+            ("post", "/register", None),
+            ("post", "/verify-email", None),
+            ("post", "/password-reset", None),
+            ("post", "/password-reset/confirm", None),
+            ("post", "/logout", Some(SESSION_COOKIE)),
+            ("get", "/characters", Some(SESSION_COOKIE)),
+            ("get", "/cash-shop", Some(SESSION_COOKIE)),
+            ("post", "/cash-shop/purchases", Some(SESSION_COOKIE)),
+            ("get", "/servers", None),
+            ("get", "/worlds", None),
+            ("get", "/health", None),
+            ("post", "/heartbeat", None),
+            ("get", "/runtime/worlds", None),
+            ("get", "/runtime/maps", None),
+            ("get", "/runtime/persistence", None),
+            ("get", "/runtime/stats", None),
+            ("get", "/runtime/gens/ranking", None),
+            ("get", "/admin/maintenance", Some(ADMIN_TOKEN)),
+            ("post", "/admin/maintenance", Some(ADMIN_TOKEN)),
+            ("delete", "/admin/maintenance", Some(ADMIN_TOKEN)),
+            ("get", "/admin/restart", Some(ADMIN_TOKEN)),
+            ("post", "/admin/restart", Some(ADMIN_TOKEN)),
+            ("delete", "/admin/restart", Some(ADMIN_TOKEN)),
+            ("post", "/admin/rewards", Some(ADMIN_TOKEN)),
+            ("post", "/admin/guild-relations", Some(ADMIN_TOKEN)),
+            ("delete", "/admin/guild-relations", Some(ADMIN_TOKEN)),
+            ("get", "/admin/guild-wars", Some(ADMIN_TOKEN)),
+            ("post", "/admin/guild-wars", Some(ADMIN_TOKEN)),
+            ("get", "/admin/helper-sessions", Some(ADMIN_TOKEN)),
+            ("get", "/admin/events", Some(ADMIN_TOKEN)),
+            ("post", "/admin/events", Some(ADMIN_TOKEN)),
+            ("get", "/admin/doppelganger", Some(ADMIN_TOKEN)),
+            ("post", "/admin/doppelganger", Some(ADMIN_TOKEN)),
+            ("get", "/admin/stress", Some(ADMIN_TOKEN)),
+            ("post", "/admin/stress", Some(ADMIN_TOKEN)),
+            ("get", "/admin/doors", Some(ADMIN_TOKEN)),
+            ("post", "/admin/doors", Some(ADMIN_TOKEN)),
+            ("post", "/admin/sessions/kick", Some(ADMIN_TOKEN)),
+            ("post", "/admin/sessions/transfer", Some(ADMIN_TOKEN)),
+            ("get", "/admin/items/dupes", Some(ADMIN_TOKEN)),
+            ("get", "/admin/items/transfers", Some(ADMIN_TOKEN)),
+            ("get", "/admin/economy/transfer-limits", Some(ADMIN_TOKEN)),
+            ("get", "/admin/backups", Some(ADMIN_TOKEN)),
+            ("post", "/admin/backups", Some(ADMIN_TOKEN)),
+            ("post", "/admin/backups/verify", Some(ADMIN_TOKEN)),
+            ("put", "/admin/accounts/{username}/role", Some(ADMIN_TOKEN)),
+            ("get", "/admin/accounts/{username}/cash", Some(ADMIN_TOKEN)),
+            ("post", "/admin/accounts/{username}/cash", Some(ADMIN_TOKEN)),
+        ];
+
+        let paths = document["paths"].as_object().unwrap();
+        let documented: usize = paths
+            .values()
+            .map(|item| item.as_object().unwrap().len())
+            .sum();
+        assert_eq!(documented, routes.len());
+        for (method, path, security) in routes {
+            let operation = &document["paths"][path][method];
+            assert!(operation.is_object(), "{method} {path} is not documented");
+            let responses = operation["responses"].as_object().unwrap();
+            assert!(
+                responses.keys().any(|status| status.starts_with('2')),
+                "{method} {path} documents no success response"
+            );
+            match security {
+                Some(scheme) => assert!(
+                    operation["security"][0][scheme].is_array(),
+                    "{method} {path} should require {scheme}"
+                ),
+                None => assert!(operation["security"].is_null()),
+            }
+            if security == Some(ADMIN_TOKEN) {
+                assert!(
+                    operation["security"][1][BEARER_TOKEN].is_array(),
+                    "{method} {path} should accept an account's bearer token"
+                );
+            }
+        }
+    }
+}
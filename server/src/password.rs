@@ -0,0 +1,245 @@
+//! Password hashing, with transparent upgrades of imported hashes.
+//!
+//! New hashes are Argon2id PHC strings (`$argon2id$v=19$...`). Accounts
+//! imported from older MU databases keep their hash as it was: unsalted MD5,
+//! SHA-1 or SHA-256 hex digests, or bcrypt. Those still log in, and
+//! [`Verification::Outdated`] tells the login to store an Argon2 hash in their
+//! place, so a database converts itself as players come back.
+//!
+//! An optional pepper, set with `PASSWORD_PEPPER` or read from the file named
+//! by `PASSWORD_PEPPER_FILE`, is fed to Argon2 as its secret. It never reaches
+//! the database: a leaked dump alone cannot be cracked, but changing or
+//! losing the pepper locks every upgraded account out.
+
+use std::fs;
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher as _, PasswordVerifier as _, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use crate::error::{ConnectServerError, Result};
+
+/// Outcome of checking a password against a stored hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verification {
+    Rejected,
+    Accepted,
+    /// Right password behind a legacy hash; the caller should store a fresh
+    /// [`Passwords::hash`].
+    Outdated,
+}
+
+impl Verification {
+    pub fn is_accepted(self) -> bool {
+        self != Self::Rejected
+    }
+}
+
+/// Format of a stored hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashScheme {
+    Argon2,
+    Bcrypt,
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+impl HashScheme {
+    /// Scheme of `stored`, or `None` when it is none of the known ones.
+    pub fn detect(stored: &str) -> Option<Self> {
+        if stored.starts_with("$argon2") {
+            return Some(Self::Argon2);
+        }
+        if ["$2a$", "$2b$", "$2x$", "$2y$"]
+            .iter()
+            .any(|prefix| stored.starts_with(prefix))
+        {
+            return Some(Self::Bcrypt);
+        }
+        if !stored.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return None;
+        }
+        match stored.len() {
+            32 => Some(Self::Md5),
+            40 => Some(Self::Sha1),
+            64 => Some(Self::Sha256),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Passwords {
+    pepper: Option<Vec<u8>>,
+}
+
+impl std::fmt::Debug for Passwords {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Passwords")
+            .field("peppered", &self.pepper.is_some())
+            .finish()
+    }
+}
+
+impl Passwords {
+    /// An empty pepper counts as none.
+    pub fn new(pepper: Option<Vec<u8>>) -> Self {
+        Self {
+            pepper: pepper.filter(|pepper| !pepper.is_empty()),
+        }
+    }
+
+    /// Reads `PASSWORD_PEPPER`, or else the file at `PASSWORD_PEPPER_FILE`
+    /// with surrounding whitespace trimmed, through `var`.
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        if let Some(pepper) = var("PASSWORD_PEPPER") {
+            return Ok(Self::new(Some(pepper.into_bytes())));
+        }
+        let Some(path) = var("PASSWORD_PEPPER_FILE") else {
+            return Ok(Self::new(None));
+        };
+        let pepper = fs::read_to_string(&path).map_err(|e| {
+            ConnectServerError::Config(format!("Failed to read password pepper {}: {}", path, e))
+        })?;
+        Ok(Self::new(Some(pepper.trim().as_bytes().to_vec())))
+    }
+
+    pub fn is_peppered(&self) -> bool {
+        self.pepper.is_some()
+    }
+
+    /// Argon2id hash of `password` with a fresh salt.
+    pub fn hash(&self, password: &str) -> Result<String> {
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = self
+            .argon2()?
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|e| ConnectServerError::PasswordHash(e.to_string()))?;
+        Ok(hash.to_string())
+    }
+
+    pub fn verify(&self, password: &str, stored: &str) -> Result<Verification> {
+        let accepted = match HashScheme::detect(stored) {
+            Some(HashScheme::Argon2) => {
+                let hash = PasswordHash::new(stored)
+                    .map_err(|e| ConnectServerError::PasswordHash(e.to_string()))?;
+                return Ok(
+                    match self.argon2()?.verify_password(password.as_bytes(), &hash) {
+                        Ok(()) => Verification::Accepted,
+                        Err(argon2::password_hash::Error::Password) => Verification::Rejected,
+                        Err(e) => return Err(ConnectServerError::PasswordHash(e.to_string())),
+                    },
+                );
+            }
+            Some(HashScheme::Bcrypt) => bcrypt::verify(password, stored)
+                .map_err(|e| ConnectServerError::PasswordHash(e.to_string()))?,
+            Some(HashScheme::Md5) => digest_matches::<Md5>(password, stored),
+            Some(HashScheme::Sha1) => digest_matches::<Sha1>(password, stored),
+            Some(HashScheme::Sha256) => digest_matches::<Sha256>(password, stored),
+            None => {
+                log::warn!("Stored password hash has an unknown format");
+                false
+            }
+        };
+        Ok(if accepted {
+            Verification::Outdated
+        } else {
+            Verification::Rejected
+        })
+    }
+
+    fn argon2(&self) -> Result<Argon2<'_>> {
+        match &self.pepper {
+            Some(pepper) => Argon2::new_with_secret(
+                pepper,
+                Algorithm::Argon2id,
+                Version::V0x13,
+                Params::default(),
+            )
+            .map_err(|e| ConnectServerError::PasswordHash(e.to_string())),
+            None => Ok(Argon2::default()),
+        }
+    }
+}
+
+/// Compares the hex digest in `stored`, whatever its case, in constant time.
+fn digest_matches<D: Digest>(password: &str, stored: &str) -> bool {
+    let digest = D::digest(password.as_bytes());
+    let expected: Vec<u8> = digest
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0F])
+        .map(|nibble| b"0123456789abcdef"[usize::from(nibble)])
+        .collect();
+    let stored = stored.to_ascii_lowercase();
+    expected.len() == stored.len()
+        && expected
+            .iter()
+            .zip(stored.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_hashes_verify_as_outdated() {
+        let passwords = Passwords::default();
+        let md5 = "482c811da5d5b4bc6d497ffa98491e38"; // "password123"
+        let sha1 = "CBFDAC6008F9CAB4083784CBD1874F76618D2A97";
+        let sha256 = "ef92b778bafe771e89245b89ecbc08a44a4e166c06659911881f383d4473e94f";
+        for stored in [md5, sha1, sha256] {
+            assert_eq!(
+                passwords.verify("password123", stored).unwrap(),
+                Verification::Outdated,
+                "{stored}"
+            );
+            assert_eq!(
+                passwords.verify("password124", stored).unwrap(),
+                Verification::Rejected
+            );
+        }
+
+        let bcrypt = bcrypt::hash("password123", 4).unwrap();
+        assert_eq!(HashScheme::detect(&bcrypt), Some(HashScheme::Bcrypt));
+        assert_eq!(
+            passwords.verify("password123", &bcrypt).unwrap(),
+            Verification::Outdated
+        );
+        assert_eq!(
+            passwords.verify("password123", "plain").unwrap(),
+            Verification::Rejected
+        );
+    }
+
+    #[test]
+    fn argon2_hashes_depend_on_the_pepper() {
+        let peppered = Passwords::new(Some(b"server secret".to_vec()));
+        let hash = peppered.hash("hunter2").unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert_eq!(
+            peppered.verify("hunter2", &hash).unwrap(),
+            Verification::Accepted
+        );
+        assert_eq!(
+            peppered.verify("hunter3", &hash).unwrap(),
+            Verification::Rejected
+        );
+        assert_eq!(
+            Passwords::default().verify("hunter2", &hash).unwrap(),
+            Verification::Rejected
+        );
+
+        let from_env = Passwords::from_vars(|name| {
+            (name == "PASSWORD_PEPPER").then(|| "server secret".to_string())
+        })
+        .unwrap();
+        assert!(from_env.verify("hunter2", &hash).unwrap().is_accepted());
+        assert!(!Passwords::new(Some(Vec::new())).is_peppered());
+    }
+}
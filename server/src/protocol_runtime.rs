@@ -49,7 +49,6 @@ impl ProtocolRuntime {
         }
     }
 
-    #[cfg(test)]
     #[must_use]
     pub fn codec(&self) -> &WireCodec {
         &self.codec
@@ -95,14 +94,25 @@ impl ProtocolRuntime {
                 let ack = Some(packet.sequence);
 
                 let response = match client {
+                    // Baseline sessions stay uncompressed whatever the
+                    // client offers.
                     protocol::ClientMessage::Hello(_) => Some(ServerMessage::HelloAck {
                         session_id: packet.session_id,
                         heartbeat_interval_ms: 5_000,
                         motd: self.motd.clone(),
                         characters: Vec::new(),
+                        protocol_version: packet.version.min(protocol::PROTOCOL_VERSION),
+                        capabilities: protocol::ProtocolCapabilities::NONE,
                     }),
-                    protocol::ClientMessage::KeepAlive { .. } => {
-                        Some(ServerMessage::Pong { server_time_ms })
+                    protocol::ClientMessage::KeepAlive {
+                        client_time_ms: client_sent_ms,
+                    }
+                    | protocol::ClientMessage::Ping { client_sent_ms } => {
+                        Some(ServerMessage::Pong {
+                            client_sent_ms: *client_sent_ms,
+                            server_received_ms: server_time_ms,
+                            server_sent_ms: server_time_ms,
+                        })
                     }
                     protocol::ClientMessage::Chat(chat) if chat.channel == ChatChannel::Local => {
                         Some(ServerMessage::Chat(chat.clone()))
@@ -129,7 +139,7 @@ impl ProtocolRuntime {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use protocol::{ClientHello, ClientMessage, QuicChannel, RouteKey};
+    use protocol::{ClientHello, ClientMessage, ProtocolCapabilities, QuicChannel, RouteKey};
 
     fn sample_route() -> RouteKey {
         RouteKey {
@@ -185,6 +195,7 @@ mod tests {
                 auth_token: "token".into(),
                 client_build: "0.1.0".into(),
                 locale: "pt-BR".into(),
+                capabilities: ProtocolCapabilities::NONE,
             }),
         );
 
@@ -240,8 +251,29 @@ mod tests {
         assert!(matches!(
             response.payload,
             PacketPayload::Server(ServerMessage::Pong {
-                server_time_ms: 2_500
+                client_sent_ms: 2_000,
+                server_received_ms: 2_500,
+                server_sent_ms: 2_500,
             })
         ));
+
+        let ping = WirePacket::client(
+            44,
+            RouteKey::LOBBY,
+            11,
+            None,
+            2_600,
+            ClientMessage::Ping {
+                client_sent_ms: 2_600,
+            },
+        );
+        let response = runtime.baseline_response(&ping, 2_700).unwrap();
+        assert!(matches!(
+            response.map(|packet| packet.payload),
+            Some(PacketPayload::Server(ServerMessage::Pong {
+                client_sent_ms: 2_600,
+                ..
+            }))
+        ));
     }
 }
@@ -0,0 +1,101 @@
+//! Account roles and the permissions each one grants.
+//!
+//! Roles are ranked: every role holds the permissions of the roles below it,
+//! so a check only needs the lowest role allowed to perform an action.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Role stored on an account and embedded in its auth tokens.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountRole {
+    #[default]
+    Player,
+    Helper,
+    GameMaster,
+    Admin,
+}
+
+impl AccountRole {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Player => "player",
+            Self::Helper => "helper",
+            Self::GameMaster => "game_master",
+            Self::Admin => "admin",
+        }
+    }
+
+    pub fn allows(self, permission: Permission) -> bool {
+        self >= permission.minimum_role()
+    }
+}
+
+/// Action gated behind a role.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Mute and unmute players in chat.
+    ModerateChat,
+    /// Chat commands acting on other players, such as kicks.
+    GmCommands,
+    /// Read-only admin routes.
+    ViewOperations,
+    /// Admin routes that change server state.
+    ManageServer,
+    /// Changing the role of an account.
+    ManageRoles,
+}
+
+impl Permission {
+    pub fn minimum_role(self) -> AccountRole {
+        match self {
+            Self::ModerateChat => AccountRole::Helper,
+            Self::GmCommands | Self::ViewOperations => AccountRole::GameMaster,
+            Self::ManageServer | Self::ManageRoles => AccountRole::Admin,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn higher_roles_inherit_lower_permissions() {
+        assert!(!AccountRole::Player.allows(Permission::ModerateChat));
+        assert!(AccountRole::Helper.allows(Permission::ModerateChat));
+        assert!(!AccountRole::Helper.allows(Permission::GmCommands));
+        assert!(AccountRole::GameMaster.allows(Permission::ModerateChat));
+        assert!(AccountRole::GameMaster.allows(Permission::ViewOperations));
+        assert!(!AccountRole::GameMaster.allows(Permission::ManageServer));
+        assert!(AccountRole::Admin.allows(Permission::ManageRoles));
+    }
+
+    #[test]
+    fn roles_serialize_by_name() {
+        for role in [
+            AccountRole::Player,
+            AccountRole::Helper,
+            AccountRole::GameMaster,
+            AccountRole::Admin,
+        ] {
+            let json = serde_json::to_string(&role).unwrap();
+            assert_eq!(json, format!("\"{}\"", role.name()));
+            assert_eq!(serde_json::from_str::<AccountRole>(&json).unwrap(), role);
+        }
+    }
+}
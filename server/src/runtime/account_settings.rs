@@ -0,0 +1,136 @@
+use std::sync::Arc;
+
+use dashmap::mapref::entry::Entry;
+use dashmap::{DashMap, DashSet};
+use protocol::{AccountSettings, ServerErrorKind};
+
+use crate::db::{models::AccountSettingsRecord, repository::AccountSettingsRepository};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AccountSettingsError {
+    #[error("settings file is {0} bytes, limit is {max}", max = AccountSettings::MAX_YAML_BYTES)]
+    TooLarge(usize),
+}
+
+impl AccountSettingsError {
+    pub fn kind(&self) -> ServerErrorKind {
+        match self {
+            Self::TooLarge(_) => ServerErrorKind::InvalidRequest,
+        }
+    }
+}
+
+/// Client settings synced per account.
+///
+/// Loaded from MongoDB at boot; uploads are kept in memory and written back in
+/// batches by [`AccountSettingsStore::persist`].
+#[derive(Clone, Default)]
+pub struct AccountSettingsStore {
+    // key: account_id
+    settings: Arc<DashMap<u64, AccountSettings>>,
+    dirty: Arc<DashSet<u64>>,
+}
+
+impl AccountSettingsStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(&self, records: impl IntoIterator<Item = AccountSettingsRecord>) {
+        for record in records {
+            self.settings.insert(record.account_id, record.settings());
+        }
+    }
+
+    pub fn get(&self, account_id: u64) -> Option<AccountSettings> {
+        self.settings
+            .get(&account_id)
+            .map(|entry| entry.value().clone())
+    }
+
+    /// Keeps the upload unless the stored copy is at least as recent, and
+    /// returns whichever copy is kept.
+    pub fn store(
+        &self,
+        account_id: u64,
+        settings: AccountSettings,
+    ) -> Result<AccountSettings, AccountSettingsError> {
+        if settings.yaml.len() > AccountSettings::MAX_YAML_BYTES {
+            return Err(AccountSettingsError::TooLarge(settings.yaml.len()));
+        }
+
+        match self.settings.entry(account_id) {
+            Entry::Occupied(entry) if entry.get().updated_at_ms >= settings.updated_at_ms => {
+                return Ok(entry.get().clone());
+            }
+            Entry::Occupied(mut entry) => {
+                entry.insert(settings.clone());
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(settings.clone());
+            }
+        }
+        self.dirty.insert(account_id);
+        Ok(settings)
+    }
+
+    /// Writes every changed account to MongoDB. Failed writes stay pending for
+    /// the next call. Returns how many accounts were written.
+    pub async fn persist(&self, repository: &AccountSettingsRepository) -> usize {
+        let pending: Vec<u64> = self.dirty.iter().map(|entry| *entry).collect();
+        let mut written = 0;
+        for account_id in pending {
+            self.dirty.remove(&account_id);
+            let Some(settings) = self.get(account_id) else {
+                continue;
+            };
+
+            let record = AccountSettingsRecord::new(account_id, settings);
+            match repository.save(&record).await {
+                Ok(()) => written += 1,
+                Err(err) => {
+                    log::error!("Failed to save settings of account {}: {}", account_id, err);
+                    self.dirty.insert(account_id);
+                }
+            }
+        }
+        written
+    }
+
+    #[cfg(test)]
+    pub fn pending_writes(&self) -> usize {
+        self.dirty.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(updated_at_ms: u64, yaml: &str) -> AccountSettings {
+        AccountSettings {
+            updated_at_ms,
+            yaml: yaml.to_string(),
+        }
+    }
+
+    #[test]
+    fn newer_upload_wins() {
+        let store = AccountSettingsStore::new();
+        assert_eq!(store.get(7), None);
+
+        assert_eq!(store.store(7, settings(100, "a")), Ok(settings(100, "a")));
+        assert_eq!(store.store(7, settings(90, "old")), Ok(settings(100, "a")));
+        assert_eq!(store.store(7, settings(100, "tie")), Ok(settings(100, "a")));
+        assert_eq!(store.store(7, settings(120, "b")), Ok(settings(120, "b")));
+        assert_eq!(store.get(7), Some(settings(120, "b")));
+        assert_eq!(store.pending_writes(), 1);
+
+        let oversized = "x".repeat(AccountSettings::MAX_YAML_BYTES + 1);
+        assert_eq!(
+            store.store(8, settings(1, &oversized)),
+            Err(AccountSettingsError::TooLarge(oversized.len()))
+        );
+        assert_eq!(store.get(8), None);
+    }
+}
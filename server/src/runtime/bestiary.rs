@@ -0,0 +1,160 @@
+//! Per-character bestiary and the drop tables it previews.
+//!
+//! A monster kind unlocks the first time the character kills one. Only
+//! unlocked kinds have their drop table shown, so the preview never spoils
+//! monsters the player has not met.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use dashmap::{DashMap, DashSet};
+use protocol::DropTable;
+
+use super::config::MonsterConfig;
+use crate::db::{models::BestiaryRecord, repository::BestiaryRepository};
+
+/// Bestiaries of every character, shared by all sessions.
+///
+/// Loaded from MongoDB at boot; changes are written back in batches by
+/// [`Bestiary::persist`].
+#[derive(Clone)]
+pub struct Bestiary {
+    // key: monster_id
+    drops: Arc<HashMap<u16, DropTable>>,
+    // key: character_id
+    unlocked: Arc<DashMap<u64, BTreeSet<u16>>>,
+    dirty: Arc<DashSet<u64>>,
+}
+
+impl Bestiary {
+    pub fn new(monsters: &[MonsterConfig]) -> Self {
+        let drops = monsters
+            .iter()
+            .map(|monster| {
+                let table = DropTable {
+                    monster_id: monster.id,
+                    entries: monster.drops.iter().map(|drop| drop.to_entry()).collect(),
+                };
+                (monster.id, table)
+            })
+            .collect();
+        Self {
+            drops: Arc::new(drops),
+            unlocked: Arc::new(DashMap::new()),
+            dirty: Arc::new(DashSet::new()),
+        }
+    }
+
+    pub fn load(&self, records: impl IntoIterator<Item = BestiaryRecord>) {
+        for record in records {
+            self.unlocked
+                .insert(record.character_id, record.unlocked.into_iter().collect());
+        }
+    }
+
+    /// Monster kinds the character unlocked, sorted by id.
+    pub fn unlocked(&self, character_id: u64) -> Vec<u16> {
+        self.unlocked
+            .get(&character_id)
+            .map(|entry| entry.value().iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn is_unlocked(&self, character_id: u64, monster_id: u16) -> bool {
+        self.unlocked
+            .get(&character_id)
+            .is_some_and(|entry| entry.value().contains(&monster_id))
+    }
+
+    /// Records a kill; returns `true` when it was the first of that kind.
+    pub fn unlock(&self, character_id: u64, monster_id: u16) -> bool {
+        let added = self
+            .unlocked
+            .entry(character_id)
+            .or_default()
+            .insert(monster_id);
+        if added {
+            self.dirty.insert(character_id);
+        }
+        added
+    }
+
+    /// Drop table of a monster kind; empty when the kind drops nothing but
+    /// the regular loot.
+    pub fn drop_table(&self, monster_id: u16) -> DropTable {
+        self.drops.get(&monster_id).cloned().unwrap_or(DropTable {
+            monster_id,
+            entries: Vec::new(),
+        })
+    }
+
+    /// Writes every changed bestiary to MongoDB. Failed writes stay pending
+    /// for the next call. Returns how many bestiaries were written.
+    pub async fn persist(&self, repository: &BestiaryRepository) -> usize {
+        let pending: Vec<u64> = self.dirty.iter().map(|entry| *entry).collect();
+        let mut written = 0;
+        for character_id in pending {
+            self.dirty.remove(&character_id);
+            let record = BestiaryRecord {
+                id: None,
+                character_id,
+                unlocked: self.unlocked(character_id),
+            };
+            match repository.save(&record).await {
+                Ok(()) => written += 1,
+                Err(err) => {
+                    log::error!(
+                        "Failed to save bestiary of character {}: {}",
+                        character_id,
+                        err
+                    );
+                    self.dirty.insert(character_id);
+                }
+            }
+        }
+        written
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::config::DropConfig;
+
+    fn spider() -> MonsterConfig {
+        MonsterConfig {
+            id: 3,
+            name: "Spider".to_string(),
+            link_radius: 0,
+            leash_radius: 15,
+            drops: vec![DropConfig {
+                group: 14,
+                index: 1,
+                min_level: 0,
+                max_level: 0,
+                chance_per_mille: 120,
+            }],
+        }
+    }
+
+    #[test]
+    fn first_kill_of_a_kind_unlocks_it() {
+        let bestiary = Bestiary::new(&[spider()]);
+
+        assert!(bestiary.unlock(7, 3));
+        assert!(!bestiary.unlock(7, 3));
+        assert!(bestiary.unlock(7, 1));
+        assert_eq!(bestiary.unlocked(7), vec![1, 3]);
+        assert!(!bestiary.is_unlocked(8, 3));
+    }
+
+    #[test]
+    fn drop_tables_come_from_the_monster_config() {
+        let bestiary = Bestiary::new(&[spider()]);
+
+        let table = bestiary.drop_table(3);
+        assert_eq!(table.entries.len(), 1);
+        assert_eq!(table.entries[0].chance_per_mille, 120);
+        assert!(bestiary.drop_table(14).entries.is_empty());
+    }
+}
@@ -0,0 +1,472 @@
+use std::sync::{Arc, Mutex as StdMutex};
+
+use dashmap::{DashMap, DashSet};
+use protocol::ItemInstance;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::config::CashShopConfig;
+use super::wallets::WalletError;
+use crate::db::{
+    models::{CashBalanceRecord, CashReceiptRecord},
+    repository::CashShopRepository,
+};
+
+/// Longest idempotency key accepted, in bytes.
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 64;
+
+/// Product of the cash-shop catalog.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct CashShopProduct {
+    pub product_id: u32,
+    pub name: String,
+    pub price: u64,
+    pub zen: u64,
+    pub items: Vec<ItemInstance>,
+}
+
+/// What moved a premium balance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CashEntryKind {
+    /// Credit from the operator's top-up flow.
+    TopUp,
+    Purchase {
+        product_id: u32,
+        character_id: u64,
+    },
+}
+
+/// Outcome of one top-up or purchase, kept per idempotency key so a retried
+/// request gets the same answer instead of moving the balance again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct CashReceipt {
+    pub account_id: u64,
+    pub idempotency_key: String,
+    #[serde(flatten)]
+    pub kind: CashEntryKind,
+    /// Credited by a top-up, debited by a purchase.
+    pub amount: u64,
+    pub balance_after: u64,
+    pub at_ms: u64,
+}
+
+impl CashReceipt {
+    fn from_record(record: CashReceiptRecord) -> Self {
+        Self {
+            account_id: record.account_id,
+            idempotency_key: record.idempotency_key,
+            kind: record.kind,
+            amount: record.amount,
+            balance_after: record.balance_after,
+            at_ms: record.at_ms,
+        }
+    }
+
+    fn to_record(&self) -> CashReceiptRecord {
+        CashReceiptRecord {
+            id: None,
+            account_id: self.account_id,
+            idempotency_key: self.idempotency_key.clone(),
+            kind: self.kind,
+            amount: self.amount,
+            balance_after: self.balance_after,
+            at_ms: self.at_ms,
+        }
+    }
+}
+
+/// Purchase accepted by [`CashShop::purchase`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CashPurchase {
+    pub receipt: CashReceipt,
+    /// Set when the key was already used for this purchase; nothing was
+    /// debited and nothing must be delivered again.
+    pub replayed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CashShopError {
+    #[error("cash shop is disabled")]
+    Disabled,
+
+    #[error("product {0} not found")]
+    UnknownProduct(u32),
+
+    #[error("price is {price}, balance is only {balance}")]
+    InsufficientFunds { price: u64, balance: u64 },
+
+    #[error("amount must be positive")]
+    InvalidAmount,
+
+    #[error("idempotency key must be 1 to {MAX_IDEMPOTENCY_KEY_LEN} bytes")]
+    InvalidKey,
+
+    #[error("idempotency key {0:?} was used for another request")]
+    KeyReused(String),
+
+    #[error("inventory needs {required} free slots, only {available} available")]
+    InventoryFull { required: usize, available: u16 },
+
+    #[error(transparent)]
+    Wallet(#[from] WalletError),
+}
+
+/// Premium-currency balances per account and the catalog they are spent on.
+///
+/// Balances only change together with a receipt; both are loaded from
+/// MongoDB at boot and written back by [`CashShop::persist`], receipts first.
+#[derive(Clone)]
+pub struct CashShop {
+    currency_name: String,
+    products: Arc<Vec<CashShopProduct>>,
+    // key: account_id
+    balances: Arc<DashMap<u64, u64>>,
+    // key: (account_id, idempotency_key)
+    receipts: Arc<DashMap<(u64, String), CashReceipt>>,
+    dirty_balances: Arc<DashSet<u64>>,
+    unsaved_receipts: Arc<DashSet<(u64, String)>>,
+    // Held while a balance and its receipt change together.
+    moving: Arc<StdMutex<()>>,
+}
+
+impl CashShop {
+    pub fn new(config: &CashShopConfig) -> Self {
+        let products = config
+            .products
+            .iter()
+            .map(|product| CashShopProduct {
+                product_id: product.product_id,
+                name: product.name.clone(),
+                price: product.price,
+                zen: product.zen,
+                items: product.items.iter().map(|item| item.to_item()).collect(),
+            })
+            .collect();
+        Self {
+            currency_name: config.currency_name.clone(),
+            products: Arc::new(products),
+            balances: Arc::new(DashMap::new()),
+            receipts: Arc::new(DashMap::new()),
+            dirty_balances: Arc::new(DashSet::new()),
+            unsaved_receipts: Arc::new(DashSet::new()),
+            moving: Arc::new(StdMutex::new(())),
+        }
+    }
+
+    pub fn load(
+        &self,
+        balances: impl IntoIterator<Item = CashBalanceRecord>,
+        receipts: impl IntoIterator<Item = CashReceiptRecord>,
+    ) {
+        for record in balances {
+            self.balances.insert(record.account_id, record.balance);
+        }
+        for record in receipts {
+            let receipt = CashReceipt::from_record(record);
+            self.receipts.insert(
+                (receipt.account_id, receipt.idempotency_key.clone()),
+                receipt,
+            );
+        }
+    }
+
+    pub fn currency_name(&self) -> &str {
+        &self.currency_name
+    }
+
+    pub fn products(&self) -> &[CashShopProduct] {
+        &self.products
+    }
+
+    pub fn product(&self, product_id: u32) -> Option<&CashShopProduct> {
+        self.products
+            .iter()
+            .find(|product| product.product_id == product_id)
+    }
+
+    pub fn balance(&self, account_id: u64) -> u64 {
+        self.balances
+            .get(&account_id)
+            .map(|entry| *entry.value())
+            .unwrap_or(0)
+    }
+
+    /// Receipts of an account, newest first.
+    pub fn receipts_of(&self, account_id: u64) -> Vec<CashReceipt> {
+        let mut receipts: Vec<CashReceipt> = self
+            .receipts
+            .iter()
+            .filter(|entry| entry.key().0 == account_id)
+            .map(|entry| entry.value().clone())
+            .collect();
+        receipts.sort_by_key(|receipt| std::cmp::Reverse(receipt.at_ms));
+        receipts
+    }
+
+    /// Credits `amount` to the account. Retrying with the same key returns
+    /// the first receipt without crediting again.
+    pub fn top_up(
+        &self,
+        account_id: u64,
+        amount: u64,
+        idempotency_key: &str,
+        now_ms: u64,
+    ) -> Result<CashReceipt, CashShopError> {
+        check_key(idempotency_key)?;
+        if amount == 0 {
+            return Err(CashShopError::InvalidAmount);
+        }
+
+        let _moving = self.lock_moves();
+        if let Some(existing) = self.replay(account_id, idempotency_key) {
+            if existing.kind != CashEntryKind::TopUp || existing.amount != amount {
+                return Err(CashShopError::KeyReused(idempotency_key.to_string()));
+            }
+            return Ok(existing);
+        }
+
+        let balance = self.balance(account_id).saturating_add(amount);
+        Ok(self.commit(CashReceipt {
+            account_id,
+            idempotency_key: idempotency_key.to_string(),
+            kind: CashEntryKind::TopUp,
+            amount,
+            balance_after: balance,
+            at_ms: now_ms,
+        }))
+    }
+
+    /// Debits the price of a product for `character_id` once `deliver` has
+    /// handed it over; both happen under the same lock, and a refused
+    /// delivery debits nothing. A replay returns the first receipt without
+    /// delivering again.
+    pub fn purchase(
+        &self,
+        account_id: u64,
+        character_id: u64,
+        product_id: u32,
+        idempotency_key: &str,
+        now_ms: u64,
+        deliver: impl FnOnce(&CashShopProduct) -> Result<(), CashShopError>,
+    ) -> Result<CashPurchase, CashShopError> {
+        check_key(idempotency_key)?;
+        let product = self
+            .product(product_id)
+            .ok_or(CashShopError::UnknownProduct(product_id))?;
+        let kind = CashEntryKind::Purchase {
+            product_id,
+            character_id,
+        };
+
+        let _moving = self.lock_moves();
+        if let Some(existing) = self.replay(account_id, idempotency_key) {
+            if existing.kind != kind {
+                return Err(CashShopError::KeyReused(idempotency_key.to_string()));
+            }
+            return Ok(CashPurchase {
+                receipt: existing,
+                replayed: true,
+            });
+        }
+
+        let balance = self.balance(account_id);
+        if balance < product.price {
+            return Err(CashShopError::InsufficientFunds {
+                price: product.price,
+                balance,
+            });
+        }
+        deliver(product)?;
+        let receipt = self.commit(CashReceipt {
+            account_id,
+            idempotency_key: idempotency_key.to_string(),
+            kind,
+            amount: product.price,
+            balance_after: balance - product.price,
+            at_ms: now_ms,
+        });
+        Ok(CashPurchase {
+            receipt,
+            replayed: false,
+        })
+    }
+
+    /// Writes new receipts, then the balances they moved, to MongoDB. Failed
+    /// writes stay pending for the next call. Returns how many were written.
+    pub async fn persist(&self, repository: &CashShopRepository) -> usize {
+        let mut written = 0;
+        let receipts: Vec<(u64, String)> = self
+            .unsaved_receipts
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect();
+        for key in receipts {
+            self.unsaved_receipts.remove(&key);
+            let Some(record) = self.receipts.get(&key).map(|entry| entry.to_record()) else {
+                continue;
+            };
+            match repository.save_receipt(&record).await {
+                Ok(()) => written += 1,
+                Err(err) => {
+                    log::error!(
+                        "Failed to save cash receipt {:?} of account {}: {}",
+                        key.1,
+                        key.0,
+                        err
+                    );
+                    self.unsaved_receipts.insert(key);
+                }
+            }
+        }
+        // A balance is only written once every receipt behind it is saved.
+        if !self.unsaved_receipts.is_empty() {
+            return written;
+        }
+
+        let accounts: Vec<u64> = self.dirty_balances.iter().map(|entry| *entry).collect();
+        for account_id in accounts {
+            self.dirty_balances.remove(&account_id);
+            let record = CashBalanceRecord {
+                id: None,
+                account_id,
+                balance: self.balance(account_id),
+            };
+            match repository.save_balance(&record).await {
+                Ok(()) => written += 1,
+                Err(err) => {
+                    log::error!(
+                        "Failed to save cash balance of account {}: {}",
+                        account_id,
+                        err
+                    );
+                    self.dirty_balances.insert(account_id);
+                }
+            }
+        }
+        written
+    }
+
+    fn replay(&self, account_id: u64, idempotency_key: &str) -> Option<CashReceipt> {
+        self.receipts
+            .get(&(account_id, idempotency_key.to_string()))
+            .map(|entry| entry.value().clone())
+    }
+
+    fn commit(&self, receipt: CashReceipt) -> CashReceipt {
+        let key = (receipt.account_id, receipt.idempotency_key.clone());
+        self.balances
+            .insert(receipt.account_id, receipt.balance_after);
+        self.receipts.insert(key.clone(), receipt.clone());
+        self.dirty_balances.insert(receipt.account_id);
+        self.unsaved_receipts.insert(key);
+        receipt
+    }
+
+    fn lock_moves(&self) -> std::sync::MutexGuard<'_, ()> {
+        match self.moving.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn pending_writes(&self) -> usize {
+        self.unsaved_receipts.len() + self.dirty_balances.len()
+    }
+}
+
+fn check_key(idempotency_key: &str) -> Result<(), CashShopError> {
+    if idempotency_key.is_empty() || idempotency_key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+        return Err(CashShopError::InvalidKey);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::config::{CashShopProductConfig, ItemGrantConfig};
+
+    fn shop() -> CashShop {
+        CashShop::new(&CashShopConfig {
+            currency_name: "WCoin".to_string(),
+            products: vec![CashShopProductConfig {
+                product_id: 1,
+                name: "Jewel of Bless x10".to_string(),
+                price: 300,
+                zen: 0,
+                items: vec![ItemGrantConfig {
+                    group: 14,
+                    index: 13,
+                    level: 0,
+                    quantity: 10,
+                }],
+            }],
+        })
+    }
+
+    #[test]
+    fn top_ups_and_purchases_apply_once_per_key() {
+        let shop = shop();
+        let credited = shop.top_up(7, 500, "order-1", 10).unwrap();
+        assert_eq!(credited.balance_after, 500);
+        assert_eq!(shop.top_up(7, 500, "order-1", 20), Ok(credited));
+        assert_eq!(
+            shop.top_up(7, 900, "order-1", 20),
+            Err(CashShopError::KeyReused("order-1".to_string()))
+        );
+        assert_eq!(shop.balance(7), 500);
+
+        let refused = shop.purchase(7, 70, 1, "buy-1", 25, |_| {
+            Err(CashShopError::InventoryFull {
+                required: 1,
+                available: 0,
+            })
+        });
+        assert!(matches!(refused, Err(CashShopError::InventoryFull { .. })));
+        assert_eq!(shop.balance(7), 500);
+
+        let mut delivered = 0;
+        let bought = shop
+            .purchase(7, 70, 1, "buy-1", 30, |_| {
+                delivered += 1;
+                Ok(())
+            })
+            .unwrap();
+        assert!(!bought.replayed);
+        assert_eq!(bought.receipt.balance_after, 200);
+        let retried = shop
+            .purchase(7, 70, 1, "buy-1", 40, |_| {
+                delivered += 1;
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(delivered, 1);
+        assert!(retried.replayed);
+        assert_eq!(retried.receipt, bought.receipt);
+        assert_eq!(shop.balance(7), 200);
+
+        assert_eq!(
+            shop.purchase(7, 70, 1, "buy-2", 50, |_| Ok(())),
+            Err(CashShopError::InsufficientFunds {
+                price: 300,
+                balance: 200
+            })
+        );
+        assert_eq!(
+            shop.purchase(7, 70, 9, "buy-3", 50, |_| Ok(())),
+            Err(CashShopError::UnknownProduct(9))
+        );
+        assert_eq!(
+            shop.top_up(7, 0, "order-2", 50),
+            Err(CashShopError::InvalidAmount)
+        );
+        assert_eq!(shop.top_up(7, 1, "", 50), Err(CashShopError::InvalidKey));
+
+        let receipts = shop.receipts_of(7);
+        assert_eq!(receipts.len(), 2);
+        assert_eq!(receipts[0].idempotency_key, "buy-1");
+        assert_eq!(shop.pending_writes(), 3);
+    }
+}
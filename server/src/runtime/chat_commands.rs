@@ -0,0 +1,173 @@
+//! Moderation commands typed into chat, such as `/mute Name 30`.
+//!
+//! Only text starting with a known command word is taken as a command; any
+//! other text, slash or not, is plain chat. Mutes are keyed by character name
+//! and live in memory, so a restart lifts them.
+
+use std::sync::Arc;
+
+use dashmap::DashMap;
+
+use crate::roles::Permission;
+
+/// Longest mute a single command can hand out: one week.
+pub const MAX_MUTE_MINUTES: u32 = 7 * 24 * 60;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChatCommand {
+    Mute { name: String, minutes: u32 },
+    Unmute { name: String },
+    Kick { name: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum ChatCommandError {
+    #[error("usage: /mute <character> <minutes>")]
+    MuteUsage,
+    #[error("usage: /unmute <character>")]
+    UnmuteUsage,
+    #[error("usage: /kick <character>")]
+    KickUsage,
+    #[error("mutes last at most {MAX_MUTE_MINUTES} minutes")]
+    MuteTooLong,
+}
+
+impl ChatCommand {
+    /// `None` when `text` is not a command.
+    pub fn parse(text: &str) -> Option<Result<Self, ChatCommandError>> {
+        let mut words = text.strip_prefix('/')?.split_whitespace();
+        let verb = words.next()?.to_ascii_lowercase();
+        let name = words.next().map(str::to_string);
+        let rest: Vec<&str> = words.collect();
+
+        Some(match verb.as_str() {
+            "mute" => match (name, rest.as_slice()) {
+                (Some(name), [minutes]) => match minutes.parse::<u32>() {
+                    Ok(0) | Err(_) => Err(ChatCommandError::MuteUsage),
+                    Ok(minutes) if minutes > MAX_MUTE_MINUTES => Err(ChatCommandError::MuteTooLong),
+                    Ok(minutes) => Ok(Self::Mute { name, minutes }),
+                },
+                _ => Err(ChatCommandError::MuteUsage),
+            },
+            "unmute" => match (name, rest.is_empty()) {
+                (Some(name), true) => Ok(Self::Unmute { name }),
+                _ => Err(ChatCommandError::UnmuteUsage),
+            },
+            "kick" => match (name, rest.is_empty()) {
+                (Some(name), true) => Ok(Self::Kick { name }),
+                _ => Err(ChatCommandError::KickUsage),
+            },
+            _ => return None,
+        })
+    }
+
+    pub fn permission(&self) -> Permission {
+        match self {
+            Self::Mute { .. } | Self::Unmute { .. } => Permission::ModerateChat,
+            Self::Kick { .. } => Permission::GmCommands,
+        }
+    }
+}
+
+/// Characters currently barred from chat, shared by all sessions.
+#[derive(Clone, Default)]
+pub struct ChatMutes {
+    // key: lowercase character name, value: mute end (ms)
+    until_ms: Arc<DashMap<String, u64>>,
+}
+
+impl ChatMutes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mute(&self, name: &str, until_ms: u64) {
+        self.until_ms.insert(name.to_ascii_lowercase(), until_ms);
+    }
+
+    /// Returns whether `name` was muted.
+    pub fn unmute(&self, name: &str) -> bool {
+        self.until_ms.remove(&name.to_ascii_lowercase()).is_some()
+    }
+
+    /// Ms left on the mute of `name`, dropping it once it has run out.
+    pub fn remaining_ms(&self, name: &str, now_ms: u64) -> Option<u64> {
+        let key = name.to_ascii_lowercase();
+        let until_ms = *self.until_ms.get(&key)?;
+        if until_ms <= now_ms {
+            self.until_ms.remove(&key);
+            return None;
+        }
+        Some(until_ms - now_ms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_known_commands_and_leaves_other_text_as_chat() {
+        assert_eq!(
+            ChatCommand::parse("/mute Spammer 30"),
+            Some(Ok(ChatCommand::Mute {
+                name: "Spammer".to_string(),
+                minutes: 30,
+            }))
+        );
+        assert_eq!(
+            ChatCommand::parse("/KICK Spammer"),
+            Some(Ok(ChatCommand::Kick {
+                name: "Spammer".to_string(),
+            }))
+        );
+        assert_eq!(ChatCommand::parse("hello /mute"), None);
+        assert_eq!(ChatCommand::parse("/dance"), None);
+        assert_eq!(ChatCommand::parse("/"), None);
+    }
+
+    #[test]
+    fn rejects_malformed_commands() {
+        assert_eq!(
+            ChatCommand::parse("/mute Spammer"),
+            Some(Err(ChatCommandError::MuteUsage))
+        );
+        assert_eq!(
+            ChatCommand::parse("/mute Spammer 0"),
+            Some(Err(ChatCommandError::MuteUsage))
+        );
+        assert_eq!(
+            ChatCommand::parse("/mute Spammer 99999"),
+            Some(Err(ChatCommandError::MuteTooLong))
+        );
+        assert_eq!(
+            ChatCommand::parse("/kick"),
+            Some(Err(ChatCommandError::KickUsage))
+        );
+        assert_eq!(
+            ChatCommand::parse("/unmute a b"),
+            Some(Err(ChatCommandError::UnmuteUsage))
+        );
+    }
+
+    #[test]
+    fn kicks_need_more_than_mutes() {
+        let mute = ChatCommand::parse("/mute a 5").unwrap().unwrap();
+        let kick = ChatCommand::parse("/kick a").unwrap().unwrap();
+        assert_eq!(mute.permission(), Permission::ModerateChat);
+        assert_eq!(kick.permission(), Permission::GmCommands);
+    }
+
+    #[test]
+    fn mutes_ignore_case_and_expire() {
+        let mutes = ChatMutes::new();
+        mutes.mute("Spammer", 1_000);
+        assert_eq!(mutes.remaining_ms("spammer", 400), Some(600));
+        assert_eq!(mutes.remaining_ms("SPAMMER", 1_000), None);
+        assert_eq!(mutes.remaining_ms("Spammer", 500), None);
+
+        mutes.mute("Spammer", 1_000);
+        assert!(mutes.unmute("spammer"));
+        assert!(!mutes.unmute("spammer"));
+    }
+}
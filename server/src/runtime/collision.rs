@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use common::collision::{CollisionGrid, CollisionMapData, TerrainHeightData};
+
+use super::config::{MapConfig, RuntimeConfig};
+
+/// Sidecars and slope limit a grid was built from.
+type GridKey = (Option<PathBuf>, Option<PathBuf>, u32);
+
+/// Object collision grids of the configured maps, with their ground heights,
+/// loaded once at boot.
+///
+/// Maps without a `collision` sidecar (or whose sidecar fails to load) get an
+/// open grid, so movement there is only limited by path length; without
+/// `terrain_height` the ground is flat at height 0.
+#[derive(Default)]
+pub struct CollisionCatalog {
+    // key: (world_id, entry_id, map_id)
+    by_map: HashMap<(u16, u16, u16), Arc<CollisionGrid>>,
+    open: Arc<CollisionGrid>,
+}
+
+impl CollisionCatalog {
+    pub fn from_runtime_config(config: &RuntimeConfig) -> Self {
+        let mut catalog = Self::default();
+        let mut loaded: HashMap<GridKey, Arc<CollisionGrid>> = HashMap::new();
+
+        for world in &config.worlds {
+            for entry in &world.entry_points {
+                for map in &entry.maps {
+                    if map.collision.is_none() && map.terrain_height.is_none() {
+                        continue;
+                    }
+                    let key = (
+                        map.collision.clone(),
+                        map.terrain_height.clone(),
+                        map.max_slope.to_bits(),
+                    );
+
+                    let grid = match loaded.get(&key) {
+                        Some(grid) => grid.clone(),
+                        None => {
+                            let grid = Arc::new(load_map_grid(map));
+                            loaded.insert(key, grid.clone());
+                            grid
+                        }
+                    };
+                    catalog.by_map.insert((world.id, entry.id, map.id), grid);
+                }
+            }
+        }
+
+        catalog
+    }
+
+    pub fn grid_for(&self, world_id: u16, entry_id: u16, map_id: u16) -> Arc<CollisionGrid> {
+        self.by_map
+            .get(&(world_id, entry_id, map_id))
+            .unwrap_or(&self.open)
+            .clone()
+    }
+}
+
+/// Footprints and heights of a map; either part that fails to load is left
+/// out.
+fn load_map_grid(map: &MapConfig) -> CollisionGrid {
+    let mut grid = CollisionGrid::open();
+    if let Some(path) = &map.collision {
+        match load_collision_grid(path) {
+            Ok(loaded) => {
+                log::info!(
+                    "Loaded {} blocked tiles for {} from {}",
+                    loaded.blocked_tiles(),
+                    map.name,
+                    path.display()
+                );
+                grid = loaded;
+            }
+            Err(err) => log::warn!(
+                "Ignoring collision data for {} ({}): {}",
+                map.name,
+                path.display(),
+                err
+            ),
+        }
+    }
+    if let Some(path) = &map.terrain_height {
+        match load_terrain_height(path) {
+            Ok(terrain) => {
+                log::info!(
+                    "Loaded terrain heights for {} from {}",
+                    map.name,
+                    path.display()
+                );
+                grid = grid.with_heights(&terrain, map.max_slope);
+            }
+            Err(err) => log::warn!(
+                "Ignoring terrain heights for {} ({}): {}",
+                map.name,
+                path.display(),
+                err
+            ),
+        }
+    }
+    grid
+}
+
+pub fn load_collision_grid(path: &Path) -> anyhow::Result<CollisionGrid> {
+    let content = fs::read_to_string(path)?;
+    let data = serde_json::from_str::<CollisionMapData>(&content)?;
+    Ok(CollisionGrid::from_footprints(&data.footprints))
+}
+
+pub fn load_terrain_height(path: &Path) -> anyhow::Result<TerrainHeightData> {
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_share_grids_and_fall_back_to_open() {
+        let path = std::env::temp_dir().join(format!("collision-{}.json", std::process::id()));
+        fs::write(
+            &path,
+            r#"{"world":1,"footprints":[{"position":[13050.0,13050.0],"shape":{"kind":"circle","radius":220.0}}]}"#,
+        )
+        .unwrap();
+
+        let mut config = RuntimeConfig::default();
+        for map in &mut config.worlds[0].entry_points[0].maps {
+            if map.id == 0 {
+                map.collision = Some(path.clone());
+            }
+        }
+        let catalog = CollisionCatalog::from_runtime_config(&config);
+        fs::remove_file(&path).unwrap();
+
+        assert!(catalog.grid_for(1, 1, 0).is_blocked(130, 130));
+        assert!(!catalog.grid_for(1, 1, 1).is_blocked(130, 130));
+        assert!(!catalog.grid_for(9, 9, 9).is_blocked(130, 130));
+    }
+
+    #[test]
+    fn terrain_heights_join_the_map_grid() {
+        let path = std::env::temp_dir().join(format!("terrain-{}.json", std::process::id()));
+        fs::write(
+            &path,
+            r#"{"width":2,"height":2,"heights":[[0.0,10.0],[0.0,400.0]]}"#,
+        )
+        .unwrap();
+
+        let mut config = RuntimeConfig::default();
+        for map in &mut config.worlds[0].entry_points[0].maps {
+            if map.id == 1 {
+                map.terrain_height = Some(path.clone());
+            }
+        }
+        let catalog = CollisionCatalog::from_runtime_config(&config);
+        fs::remove_file(&path).unwrap();
+
+        let noria = catalog.grid_for(1, 1, 1);
+        assert_eq!(noria.ground_height(1, 0), 15.0);
+        assert!(noria.line_is_clear((0, 0), (1, 0)));
+        assert!(
+            !noria.line_is_clear((0, 1), (1, 1)),
+            "600 units up one tile"
+        );
+        assert_eq!(catalog.grid_for(1, 1, 0).ground_height(1, 1), 0.0);
+    }
+}
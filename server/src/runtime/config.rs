@@ -1,13 +1,42 @@
+use common::collision::DEFAULT_MAX_SLOPE;
+use common::{PvpMode, PvpRules, Season, WorldMap};
+use protocol::{DoorState, DropEntry, ItemInstance, ItemOptions, QuestObjective, SequenceEvent};
 use serde::Deserialize;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
+use super::webhooks::{WebhookEventKind, WebhookFormat};
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct RuntimeConfig {
+    /// Game version served; maps from later seasons are refused at load.
+    #[serde(default)]
+    pub season: Season,
     pub gateway: GatewayConfig,
     pub ticks: TickConfig,
     pub persistence: PersistenceConfig,
+    #[serde(default)]
+    pub combat: CombatConfig,
+    #[serde(default)]
+    pub starting_kit: StartingKitConfig,
+    #[serde(default)]
+    pub starting_zones: StartingZonesConfig,
+    /// Quest chain started by every new character; none when absent.
+    #[serde(default)]
+    pub tutorial: Option<QuestConfig>,
+    #[serde(default)]
+    pub monsters: Vec<MonsterConfig>,
+    /// Premium currency and its shop; disabled when absent.
+    #[serde(default)]
+    pub cash_shop: Option<CashShopConfig>,
+    /// Discord or Slack channels told about server events.
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+    #[serde(default)]
+    pub cleanup: CleanupConfig,
+    #[serde(default)]
+    pub transfer_limits: TransferLimitsConfig,
     pub worlds: Vec<WorldConfig>,
 }
 
@@ -30,13 +59,445 @@ pub struct PersistenceConfig {
     pub max_batch_size: usize,
 }
 
+/// Cast rates and reach enforced by the server, whatever the client UI allows.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CombatConfig {
+    /// Lockout after any cast before the next one is accepted.
+    #[serde(default = "default_global_cooldown_ms")]
+    pub global_cooldown_ms: u64,
+    /// Tiles a skill reaches when it has no `range` of its own.
+    #[serde(default = "default_skill_range")]
+    pub default_range: u16,
+    /// Per-skill cooldowns; skills not listed only wait for the global one.
+    #[serde(default)]
+    pub skills: Vec<SkillCooldownConfig>,
+}
+
+impl CombatConfig {
+    /// Tiles between attacker and target within which `skill_id` hits.
+    pub fn range_of(&self, skill_id: u16) -> u16 {
+        self.skills
+            .iter()
+            .find(|skill| skill.skill_id == skill_id)
+            .and_then(|skill| skill.range)
+            .unwrap_or(self.default_range)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SkillCooldownConfig {
+    pub skill_id: u16,
+    pub cooldown_ms: u64,
+    /// Reach in tiles; `default_range` when unset.
+    #[serde(default)]
+    pub range: Option<u16>,
+}
+
+fn default_global_cooldown_ms() -> u64 {
+    200
+}
+
+fn default_skill_range() -> u16 {
+    3
+}
+
+impl Default for CombatConfig {
+    fn default() -> Self {
+        Self {
+            global_cooldown_ms: default_global_cooldown_ms(),
+            default_range: default_skill_range(),
+            skills: Vec::new(),
+        }
+    }
+}
+
+/// Lifetime and per-map caps of the objects maps leave behind (see
+/// [`super::map_objects`]).
+#[derive(Debug, Clone, Deserialize)]
+pub struct CleanupConfig {
+    /// Time between two sweeps of a map.
+    #[serde(default = "default_cleanup_interval_ms")]
+    pub interval_ms: u64,
+    #[serde(default = "default_ground_item_ttl_ms")]
+    pub ground_item_ttl_ms: u64,
+    #[serde(default = "default_effect_ttl_ms")]
+    pub effect_ttl_ms: u64,
+    #[serde(default = "default_corpse_ttl_ms")]
+    pub corpse_ttl_ms: u64,
+    /// Caps of maps that set none of their own.
+    #[serde(default)]
+    pub caps: ObjectCaps,
+}
+
+impl CleanupConfig {
+    /// The settings `map` runs with, its own caps included.
+    pub fn for_map(&self, map: &MapConfig) -> Self {
+        Self {
+            caps: map.caps.unwrap_or(self.caps),
+            ..self.clone()
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(self.interval_ms)
+    }
+}
+
+impl Default for CleanupConfig {
+    fn default() -> Self {
+        Self {
+            interval_ms: default_cleanup_interval_ms(),
+            ground_item_ttl_ms: default_ground_item_ttl_ms(),
+            effect_ttl_ms: default_effect_ttl_ms(),
+            corpse_ttl_ms: default_corpse_ttl_ms(),
+            caps: ObjectCaps::default(),
+        }
+    }
+}
+
+fn default_cleanup_interval_ms() -> u64 {
+    10_000
+}
+
+fn default_ground_item_ttl_ms() -> u64 {
+    120_000
+}
+
+fn default_effect_ttl_ms() -> u64 {
+    30_000
+}
+
+fn default_corpse_ttl_ms() -> u64 {
+    10_000
+}
+
+/// Daily caps on what an account moves to other characters through the
+/// warehouse, trades and mail (see [`super::transfer_limits`]).
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransferLimitsConfig {
+    /// Zen per rolling 24 hours; uncapped when absent.
+    #[serde(default)]
+    pub zen_per_day: Option<u64>,
+    /// Items per rolling 24 hours; uncapped when absent.
+    #[serde(default)]
+    pub items_per_day: Option<u32>,
+    /// Share of a cap, in percent, from which the admin report lists an account.
+    #[serde(default = "default_transfer_report_threshold")]
+    pub report_threshold_percent: u8,
+}
+
+impl Default for TransferLimitsConfig {
+    fn default() -> Self {
+        Self {
+            zen_per_day: None,
+            items_per_day: None,
+            report_threshold_percent: default_transfer_report_threshold(),
+        }
+    }
+}
+
+fn default_transfer_report_threshold() -> u8 {
+    80
+}
+
+/// Most objects of each kind one map instance keeps; the oldest go first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ObjectCaps {
+    pub ground_items: usize,
+    pub effects: usize,
+    pub corpses: usize,
+}
+
+impl Default for ObjectCaps {
+    fn default() -> Self {
+        Self {
+            ground_items: 500,
+            effects: 200,
+            corpses: 300,
+        }
+    }
+}
+
+/// Tiles around an attacked monster within which its spawn group assists.
+pub const DEFAULT_LINK_RADIUS: u16 = 4;
+/// Tiles from its spawn a monster chases before it gives up.
+pub const DEFAULT_LEASH_RADIUS: u16 = 15;
+
+/// AI behavior of a monster definition.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MonsterConfig {
+    pub id: u16,
+    pub name: String,
+    /// Members of the spawn group this close to an attacked monster join the
+    /// fight; 0 makes the monster fight alone.
+    #[serde(default = "default_link_radius")]
+    pub link_radius: u16,
+    /// Past this distance from its spawn the monster drops its target, walks
+    /// back and heals to full.
+    #[serde(default = "default_leash_radius")]
+    pub leash_radius: u16,
+    /// Loot table, also previewed in the client bestiary.
+    #[serde(default)]
+    pub drops: Vec<DropConfig>,
+}
+
+/// One item of a monster's loot table.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct DropConfig {
+    pub group: u8,
+    pub index: u16,
+    #[serde(default)]
+    pub min_level: u8,
+    #[serde(default)]
+    pub max_level: u8,
+    pub chance_per_mille: u16,
+}
+
+impl DropConfig {
+    pub fn to_entry(self) -> DropEntry {
+        DropEntry {
+            group: self.group,
+            index: self.index,
+            min_level: self.min_level,
+            max_level: self.max_level.max(self.min_level),
+            chance_per_mille: self.chance_per_mille,
+        }
+    }
+}
+
+fn default_link_radius() -> u16 {
+    DEFAULT_LINK_RADIUS
+}
+
+fn default_leash_radius() -> u16 {
+    DEFAULT_LEASH_RADIUS
+}
+
+/// What a character gets the first time it enters the game.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StartingKitConfig {
+    /// Kit of classes without an entry in `classes`.
+    #[serde(default, flatten)]
+    pub default: ClassKitConfig,
+    #[serde(default)]
+    pub classes: Vec<ClassKitConfig>,
+}
+
+impl StartingKitConfig {
+    pub fn for_class(&self, class_id: u8) -> &ClassKitConfig {
+        self.classes
+            .iter()
+            .find(|kit| kit.class_id == Some(class_id))
+            .unwrap_or(&self.default)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ClassKitConfig {
+    /// Class the kit belongs to, as in `class_name_to_id`; unset on the default kit.
+    #[serde(default)]
+    pub class_id: Option<u8>,
+    #[serde(default)]
+    pub zen: u64,
+    /// Map the character spawns on, in place of its `[starting_zones]` town.
+    #[serde(default)]
+    pub map_id: Option<u16>,
+    #[serde(default)]
+    pub items: Vec<ItemGrantConfig>,
+}
+
+/// Town a new character enters the game in, by class. Classic servers start
+/// Fairy Elves in Noria, Summoners in Elbeland and everyone else in Lorencia.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StartingZonesConfig {
+    /// Zone of classes without an entry in `classes`.
+    #[serde(default = "default_starting_zone")]
+    pub default: WorldMap,
+    /// Replaces the classic Noria and Elbeland entries when set.
+    #[serde(default = "default_class_zones")]
+    pub classes: Vec<ClassZoneConfig>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ClassZoneConfig {
+    /// Class as in `class_name_to_id`.
+    pub class_id: u8,
+    /// World name or ID, e.g. `map = "Elbeland"`.
+    pub map: WorldMap,
+}
+
+fn default_starting_zone() -> WorldMap {
+    WorldMap::Lorencia
+}
+
+fn default_class_zones() -> Vec<ClassZoneConfig> {
+    vec![
+        ClassZoneConfig {
+            class_id: 2,
+            map: WorldMap::Noria,
+        },
+        ClassZoneConfig {
+            class_id: 5,
+            map: WorldMap::Elbeland,
+        },
+    ]
+}
+
+impl Default for StartingZonesConfig {
+    fn default() -> Self {
+        Self {
+            default: default_starting_zone(),
+            classes: default_class_zones(),
+        }
+    }
+}
+
+impl StartingZonesConfig {
+    pub fn for_class(&self, class_id: u8) -> WorldMap {
+        self.classes
+            .iter()
+            .find(|zone| zone.class_id == class_id)
+            .map_or(self.default, |zone| zone.map)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ItemGrantConfig {
+    pub group: u8,
+    pub index: u16,
+    #[serde(default)]
+    pub level: u8,
+    #[serde(default = "default_item_quantity")]
+    pub quantity: u16,
+}
+
+fn default_item_quantity() -> u16 {
+    1
+}
+
+impl ItemGrantConfig {
+    pub fn to_item(&self) -> ItemInstance {
+        ItemInstance {
+            serial: 0,
+            group: self.group,
+            index: self.index,
+            level: self.level,
+            quantity: self.quantity,
+            options: ItemOptions::default(),
+            expires_at_ms: None,
+        }
+    }
+}
+
+/// Catalog of the premium-currency shop. Balances are credited by the
+/// operator's own top-up flow through the admin API.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CashShopConfig {
+    /// Name shown next to balances and prices.
+    #[serde(default = "default_currency_name")]
+    pub currency_name: String,
+    #[serde(default)]
+    pub products: Vec<CashShopProductConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CashShopProductConfig {
+    pub product_id: u32,
+    pub name: String,
+    /// Cost in premium currency.
+    pub price: u64,
+    #[serde(default)]
+    pub zen: u64,
+    #[serde(default)]
+    pub items: Vec<ItemGrantConfig>,
+}
+
+fn default_currency_name() -> String {
+    "Cash".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookConfig {
+    /// Incoming-webhook URL; it carries the channel's secret.
+    pub url: String,
+    #[serde(default)]
+    pub format: WebhookFormat,
+    /// Events posted; every event when empty.
+    #[serde(default)]
+    pub events: Vec<WebhookEventKind>,
+    /// Discord allows 30 posts a minute per webhook.
+    #[serde(default = "default_webhook_posts_per_minute")]
+    pub max_per_minute: u32,
+    #[serde(default = "default_webhook_retries")]
+    pub max_retries: u32,
+}
+
+fn default_webhook_posts_per_minute() -> u32 {
+    30
+}
+
+fn default_webhook_retries() -> u32 {
+    3
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuestConfig {
+    pub quest_id: u16,
+    pub name: String,
+    pub steps: Vec<QuestStepConfig>,
+    /// Granted once the last step is done.
+    #[serde(default)]
+    pub reward_zen: u64,
+    #[serde(default)]
+    pub reward_items: Vec<ItemGrantConfig>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum QuestStepConfig {
+    TalkToNpc { npc_id: u16 },
+    KillMonsters { monster_id: u16, count: u16 },
+    EquipItem,
+}
+
+impl QuestStepConfig {
+    pub fn objective(self) -> QuestObjective {
+        match self {
+            Self::TalkToNpc { npc_id } => QuestObjective::TalkToNpc { npc_id },
+            Self::KillMonsters { monster_id, count } => {
+                QuestObjective::KillMonsters { monster_id, count }
+            }
+            Self::EquipItem => QuestObjective::EquipItem,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct WorldConfig {
     pub id: u16,
     pub name: String,
+    /// Lets characters in this world run the MU Helper (auto-hunt).
+    #[serde(default)]
+    pub mu_helper: bool,
+    /// Enables Gens factions (Acheron-era content); classic-season worlds
+    /// leave it off.
+    #[serde(default)]
+    pub gens: bool,
     pub entry_points: Vec<EntryPointConfig>,
 }
 
+impl WorldConfig {
+    /// ID of the first map of this world named after `map`.
+    pub fn map_id_of(&self, map: WorldMap) -> Option<u16> {
+        self.entry_points
+            .iter()
+            .flat_map(|entry| entry.maps.iter())
+            .find(|config| WorldMap::from_name(&config.name) == Some(map))
+            .map(|config| config.id)
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct EntryPointConfig {
     pub id: u16,
@@ -53,15 +514,123 @@ pub struct MapConfig {
     pub name: String,
     pub base_instances: u16,
     pub soft_player_cap: u32,
+    /// Replaces the PvP mode the map has in `common::pvp`, e.g.
+    /// `pvp = "free_pk"`. Maps whose name is not a known world follow the
+    /// default, guild wars only.
+    #[serde(default)]
+    pub pvp: Option<PvpMode>,
+    /// Gens battle zone: rival factions may fight and kills earn contribution.
+    /// Ignored unless the world enables Gens.
+    #[serde(default)]
+    pub gens: bool,
+    /// Object collision sidecar (`collision.json`) written by the asset converter.
+    #[serde(default)]
+    pub collision: Option<PathBuf>,
+    /// Heightmap (`terrain_height.json`) of the map's world, the one clients
+    /// render: monsters walk on it and steps steeper than `max_slope` are
+    /// refused.
+    #[serde(default)]
+    pub terrain_height: Option<PathBuf>,
+    /// Height change per unit walked a step may take; only used with
+    /// `terrain_height`.
+    #[serde(default = "default_max_slope")]
+    pub max_slope: f32,
+    #[serde(default)]
+    pub doors: Vec<DoorConfig>,
+    /// Replaces `cleanup.caps` on this map, e.g. for a busy town.
+    #[serde(default)]
+    pub caps: Option<ObjectCaps>,
+}
+
+impl MapConfig {
+    pub fn pvp_rules(&self) -> PvpRules {
+        let rules =
+            WorldMap::from_name(&self.name).map_or_else(PvpRules::default, |map| map.pvp_rules());
+        match self.pvp {
+            Some(mode) if mode != rules.mode => PvpRules::new(mode),
+            _ => rules,
+        }
+    }
+}
+
+/// Door or gate whose state the server owns: Devias gates, event doors,
+/// Castle Siege gates.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DoorConfig {
+    pub id: u16,
+    pub name: String,
+    /// First tile of the rectangle the door blocks while closed.
+    pub x: u16,
+    pub y: u16,
+    #[serde(default = "default_door_extent")]
+    pub width: u16,
+    #[serde(default = "default_door_extent")]
+    pub height: u16,
+    /// State at boot and after a reset.
+    #[serde(default = "default_door_state")]
+    pub state: DoorState,
+    /// Hit points of a destructible gate; other doors cannot be damaged.
+    #[serde(default)]
+    pub hp: Option<u32>,
+    /// Event whose gate the door follows: open while the event lets players in.
+    #[serde(default)]
+    pub event: Option<SequenceEvent>,
+}
+
+fn default_max_slope() -> f32 {
+    DEFAULT_MAX_SLOPE
+}
+
+fn default_door_extent() -> u16 {
+    1
+}
+
+fn default_door_state() -> DoorState {
+    DoorState::Closed
+}
+
+impl DoorConfig {
+    pub fn tiles(&self) -> impl Iterator<Item = (u16, u16)> + '_ {
+        (self.y..self.y.saturating_add(self.height))
+            .flat_map(move |y| (self.x..self.x.saturating_add(self.width)).map(move |x| (x, y)))
+    }
 }
 
 impl RuntimeConfig {
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
         let content = fs::read_to_string(path)?;
         let parsed = toml::from_str::<Self>(&content)?;
+        parsed.check_season()?;
         Ok(parsed)
     }
 
+    /// Fails on maps the configured season does not have. Maps whose name
+    /// is not a known world are left alone.
+    pub fn check_season(&self) -> anyhow::Result<()> {
+        let unavailable: Vec<String> = self
+            .worlds
+            .iter()
+            .flat_map(|world| world.entry_points.iter())
+            .flat_map(|entry| entry.maps.iter())
+            .filter_map(|map| WorldMap::from_name(&map.name))
+            .filter(|map| !map.available_in(self.season))
+            .map(|map| match map.removed_in() {
+                Some(removed) if removed <= self.season => {
+                    format!("{} (removed in {})", map.name(), removed)
+                }
+                _ => format!("{} (from {})", map.name(), map.introduced_in()),
+            })
+            .collect();
+        if unavailable.is_empty() {
+            return Ok(());
+        }
+        anyhow::bail!(
+            "maps not available in {}: {}",
+            self.season,
+            unavailable.join(", ")
+        )
+    }
+
     pub fn player_tick(&self) -> Duration {
         Duration::from_millis(self.ticks.player_tick_ms)
     }
@@ -82,6 +651,7 @@ impl RuntimeConfig {
 impl Default for RuntimeConfig {
     fn default() -> Self {
         Self {
+            season: Season::default(),
             gateway: GatewayConfig {
                 host: "0.0.0.0".to_string(),
                 port: 6000,
@@ -95,9 +665,20 @@ impl Default for RuntimeConfig {
                 max_flush_lag_ms: 15_000,
                 max_batch_size: 300,
             },
+            combat: CombatConfig::default(),
+            starting_kit: StartingKitConfig::default(),
+            starting_zones: StartingZonesConfig::default(),
+            tutorial: None,
+            monsters: Vec::new(),
+            cash_shop: None,
+            webhooks: Vec::new(),
+            cleanup: CleanupConfig::default(),
+            transfer_limits: TransferLimitsConfig::default(),
             worlds: vec![WorldConfig {
                 id: 1,
                 name: "Midgard".to_string(),
+                mu_helper: true,
+                gens: false,
                 entry_points: vec![EntryPointConfig {
                     id: 1,
                     name: "Midgard-1".to_string(),
@@ -110,12 +691,26 @@ impl Default for RuntimeConfig {
                             name: "Lorencia".to_string(),
                             base_instances: 1,
                             soft_player_cap: 300,
+                            pvp: None,
+                            gens: false,
+                            collision: None,
+                            terrain_height: None,
+                            max_slope: DEFAULT_MAX_SLOPE,
+                            doors: Vec::new(),
+                            caps: None,
                         },
                         MapConfig {
                             id: 1,
                             name: "Noria".to_string(),
                             base_instances: 1,
                             soft_player_cap: 300,
+                            pvp: None,
+                            gens: false,
+                            collision: None,
+                            terrain_height: None,
+                            max_slope: DEFAULT_MAX_SLOPE,
+                            doors: Vec::new(),
+                            caps: None,
                         },
                     ],
                 }],
@@ -132,6 +727,7 @@ mod tests {
     fn default_config_is_valid() {
         let config = RuntimeConfig::default();
         assert_eq!(config.worlds.len(), 1);
+        assert!(config.combat.global_cooldown_ms > 0);
         assert!(!config.worlds[0].entry_points.is_empty());
         assert!(config.player_tick().as_millis() > 0);
     }
@@ -152,9 +748,85 @@ flush_tick_ms = 2000
 max_flush_lag_ms = 15000
 max_batch_size = 200
 
+[combat]
+global_cooldown_ms = 250
+
+[[combat.skills]]
+skill_id = 12
+cooldown_ms = 2000
+range = 6
+
+[starting_kit]
+zen = 5000
+
+[[starting_kit.items]]
+group = 14
+index = 0
+quantity = 10
+
+[[starting_kit.classes]]
+class_id = 2
+zen = 5000
+map_id = 3
+
+[starting_zones]
+default = "Devias"
+
+[tutorial]
+quest_id = 1
+name = "First Steps"
+reward_zen = 10000
+
+[[tutorial.steps]]
+kind = "talk_to_npc"
+npc_id = 249
+
+[[tutorial.steps]]
+kind = "kill_monsters"
+monster_id = 3
+count = 5
+
+[[tutorial.steps]]
+kind = "equip_item"
+
+[[monsters]]
+id = 3
+name = "Spider"
+link_radius = 6
+
+[[monsters]]
+id = 14
+name = "Skeleton"
+
+[cash_shop]
+currency_name = "WCoin"
+
+[[cash_shop.products]]
+product_id = 1
+name = "Jewel of Bless x10"
+price = 300
+items = [{ group = 14, index = 13, quantity = 10 }]
+
+[[webhooks]]
+url = "https://discord.com/api/webhooks/1/token"
+events = ["boss_killed", "castle_siege_ended", "top_ranking"]
+
+[[webhooks]]
+url = "https://hooks.slack.com/services/T/B/X"
+format = "slack"
+max_per_minute = 60
+
+[cleanup]
+ground_item_ttl_ms = 60000
+
+[cleanup.caps]
+corpses = 100
+
 [[worlds]]
 id = 1
 name = "Midgard"
+mu_helper = true
+gens = true
 
 [[worlds.entry_points]]
 id = 1
@@ -163,6 +835,48 @@ host = "127.0.0.1"
 port = 55901
 max_players = 1000
 
+[[worlds.entry_points.maps]]
+id = 0
+name = "Lorencia"
+base_instances = 1
+soft_player_cap = 300
+
+[[worlds.entry_points.maps]]
+id = 92
+name = "Acheron"
+base_instances = 1
+soft_player_cap = 200
+gens = true
+pvp = "free_pk"
+caps = { ground_items = 50, effects = 20, corpses = 30 }
+
+[[worlds.entry_points.maps.doors]]
+id = 1
+name = "Castle Gate"
+x = 120
+y = 40
+width = 3
+hp = 5000
+
+[[worlds.entry_points.maps.doors]]
+id = 2
+name = "Tower Door"
+x = 80
+y = 90
+state = "open"
+event = "kanturu"
+
+[[worlds]]
+id = 2
+name = "Asgard"
+
+[[worlds.entry_points]]
+id = 1
+name = "Asgard-1"
+host = "127.0.0.1"
+port = 55911
+max_players = 1000
+
 [[worlds.entry_points.maps]]
 id = 0
 name = "Lorencia"
@@ -173,5 +887,107 @@ soft_player_cap = 300
         let config: RuntimeConfig = toml::from_str(toml).expect("valid runtime config");
         assert_eq!(config.gateway.port, 6000);
         assert_eq!(config.worlds[0].entry_points[0].maps[0].name, "Lorencia");
+        assert!(config.worlds[0].mu_helper);
+        assert!(!config.worlds[1].mu_helper);
+        assert!(config.worlds[0].gens && !config.worlds[1].gens);
+        let maps = &config.worlds[0].entry_points[0].maps;
+        assert!(!maps[0].gens && maps[1].gens);
+        assert_eq!(maps[0].pvp_rules(), WorldMap::Lorencia.pvp_rules());
+        assert_eq!(maps[1].pvp_rules(), PvpRules::new(PvpMode::FreePk));
+        let gate = &maps[1].doors[0];
+        assert_eq!(
+            (gate.state, gate.hp, gate.event),
+            (DoorState::Closed, Some(5000), None)
+        );
+        assert_eq!(
+            gate.tiles().collect::<Vec<_>>(),
+            [(120, 40), (121, 40), (122, 40)]
+        );
+        let tower = &maps[1].doors[1];
+        assert_eq!(tower.state, DoorState::Open);
+        assert_eq!(tower.event, Some(SequenceEvent::Kanturu));
+        assert!(maps[0].doors.is_empty());
+        assert_eq!(config.combat.global_cooldown_ms, 250);
+        assert_eq!(config.combat.skills[0].skill_id, 12);
+        assert_eq!(config.combat.skills[0].cooldown_ms, 2000);
+        assert_eq!(config.combat.range_of(12), 6);
+        assert_eq!(config.combat.range_of(1), 3);
+
+        let knight = config.starting_kit.for_class(1);
+        assert_eq!((knight.zen, knight.map_id), (5000, None));
+        assert_eq!(knight.items[0].to_item().quantity, 10);
+        let elf = config.starting_kit.for_class(2);
+        assert_eq!((elf.map_id, elf.items.len()), (Some(3), 0));
+        let zones = &config.starting_zones;
+        assert_eq!(zones.for_class(1), WorldMap::Devias);
+        assert_eq!(zones.for_class(5), WorldMap::Elbeland);
+        let classic = StartingZonesConfig::default();
+        assert_eq!(
+            [0, 2, 5].map(|class_id| classic.for_class(class_id)),
+            [WorldMap::Lorencia, WorldMap::Noria, WorldMap::Elbeland]
+        );
+        assert_eq!(
+            RuntimeConfig::default().worlds[0].map_id_of(WorldMap::Noria),
+            Some(1)
+        );
+        let tutorial = config.tutorial.as_ref().expect("tutorial configured");
+        assert_eq!(
+            tutorial.steps[1].objective(),
+            QuestObjective::KillMonsters {
+                monster_id: 3,
+                count: 5
+            }
+        );
+        assert!(RuntimeConfig::default().tutorial.is_none());
+
+        let cash_shop = config.cash_shop.as_ref().expect("cash shop configured");
+        assert_eq!(cash_shop.currency_name, "WCoin");
+        let bless = &cash_shop.products[0];
+        assert_eq!((bless.price, bless.zen), (300, 0));
+        assert_eq!(bless.items[0].to_item().quantity, 10);
+        assert!(RuntimeConfig::default().cash_shop.is_none());
+
+        let (discord, slack) = (&config.webhooks[0], &config.webhooks[1]);
+        assert_eq!(discord.format, WebhookFormat::Discord);
+        assert_eq!(
+            discord.events,
+            vec![
+                WebhookEventKind::BossKilled,
+                WebhookEventKind::CastleSiegeEnded,
+                WebhookEventKind::TopRanking
+            ]
+        );
+        assert_eq!((discord.max_per_minute, discord.max_retries), (30, 3));
+        assert_eq!(slack.format, WebhookFormat::Slack);
+        assert!(slack.events.is_empty());
+        assert_eq!(slack.max_per_minute, 60);
+
+        let spider = &config.monsters[0];
+        assert_eq!(
+            (spider.link_radius, spider.leash_radius),
+            (6, DEFAULT_LEASH_RADIUS)
+        );
+        assert_eq!(config.monsters[1].link_radius, DEFAULT_LINK_RADIUS);
+
+        let cleanup = &config.cleanup;
+        assert_eq!(
+            (cleanup.interval_ms, cleanup.ground_item_ttl_ms),
+            (10_000, 60_000)
+        );
+        assert_eq!(
+            cleanup.for_map(&maps[0]).caps,
+            ObjectCaps {
+                corpses: 100,
+                ..ObjectCaps::default()
+            }
+        );
+        assert_eq!(cleanup.for_map(&maps[1]).caps.ground_items, 50);
+
+        assert_eq!(config.season, Season::Season6);
+        let error = config.check_season().unwrap_err().to_string();
+        assert!(error.contains("Acheron (from Season 8)"), "{error}");
+        let mut config = config;
+        config.season = Season::Season8;
+        assert!(config.check_season().is_ok());
     }
 }
@@ -0,0 +1,191 @@
+//! Skill cooldowns enforced on the server.
+//!
+//! Every accepted cast starts the global cooldown and, for skills listed in
+//! `[combat]`, the skill's own cooldown. Casts arriving before either has run
+//! out are rejected, so a macro cannot outpace the intended cast rate no matter
+//! what the client lets through. Timers follow the character, not the map, so
+//! a map transfer does not reset them.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use dashmap::DashMap;
+
+use super::config::CombatConfig;
+
+/// Slack for casts that arrive early because packets bunched up in transit.
+/// Each early cast pushes the next window back by what it borrowed, so the
+/// slack never adds up to a faster rate.
+pub const LATENCY_GRACE_MS: u64 = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum CooldownError {
+    #[error("global cooldown, ready in {ready_in_ms} ms")]
+    Global { ready_in_ms: u64 },
+    #[error("skill {skill_id} on cooldown, ready in {ready_in_ms} ms")]
+    Skill { skill_id: u16, ready_in_ms: u64 },
+}
+
+impl CooldownError {
+    pub fn ready_in_ms(&self) -> u64 {
+        match self {
+            Self::Global { ready_in_ms } | Self::Skill { ready_in_ms, .. } => *ready_in_ms,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct CastTimers {
+    global_ready_at_ms: u64,
+    // key: skill_id
+    skill_ready_at_ms: HashMap<u16, u64>,
+}
+
+/// Cast timers of every character, shared by all sessions.
+#[derive(Clone)]
+pub struct SkillCooldowns {
+    global_cooldown_ms: u64,
+    // key: skill_id
+    cooldown_ms: Arc<HashMap<u16, u64>>,
+    // key: character_id
+    timers: Arc<DashMap<u64, CastTimers>>,
+}
+
+impl SkillCooldowns {
+    pub fn new(config: &CombatConfig) -> Self {
+        Self {
+            global_cooldown_ms: config.global_cooldown_ms,
+            cooldown_ms: Arc::new(
+                config
+                    .skills
+                    .iter()
+                    .map(|skill| (skill.skill_id, skill.cooldown_ms))
+                    .collect(),
+            ),
+            timers: Arc::new(DashMap::new()),
+        }
+    }
+
+    pub fn cooldown_of(&self, skill_id: u16) -> u64 {
+        self.cooldown_ms.get(&skill_id).copied().unwrap_or(0)
+    }
+
+    /// Accepts the cast and starts its timers, or tells how long is left.
+    /// Rejected casts leave the timers untouched.
+    pub fn try_cast(
+        &self,
+        character_id: u64,
+        skill_id: u16,
+        now_ms: u64,
+    ) -> Result<(), CooldownError> {
+        let mut timers = self.timers.entry(character_id).or_default();
+        let arrived_ms = now_ms + LATENCY_GRACE_MS;
+
+        if arrived_ms < timers.global_ready_at_ms {
+            return Err(CooldownError::Global {
+                ready_in_ms: timers.global_ready_at_ms - now_ms,
+            });
+        }
+        let skill_ready_at_ms = timers
+            .skill_ready_at_ms
+            .get(&skill_id)
+            .copied()
+            .unwrap_or(0);
+        if arrived_ms < skill_ready_at_ms {
+            return Err(CooldownError::Skill {
+                skill_id,
+                ready_in_ms: skill_ready_at_ms - now_ms,
+            });
+        }
+
+        timers.global_ready_at_ms = now_ms.max(timers.global_ready_at_ms) + self.global_cooldown_ms;
+        let cooldown_ms = self.cooldown_of(skill_id);
+        if cooldown_ms > 0 {
+            timers
+                .skill_ready_at_ms
+                .insert(skill_id, now_ms.max(skill_ready_at_ms) + cooldown_ms);
+        }
+        Ok(())
+    }
+
+    /// Drops the timers of a character that logged out once they have all run
+    /// out; running ones stay so relogging cannot skip them.
+    pub fn forget(&self, character_id: u64, now_ms: u64) {
+        self.timers.remove_if(&character_id, |_, timers| {
+            timers.global_ready_at_ms <= now_ms
+                && timers
+                    .skill_ready_at_ms
+                    .values()
+                    .all(|ready_at_ms| *ready_at_ms <= now_ms)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::config::SkillCooldownConfig;
+
+    fn cooldowns() -> SkillCooldowns {
+        SkillCooldowns::new(&CombatConfig {
+            global_cooldown_ms: 200,
+            default_range: 3,
+            skills: vec![SkillCooldownConfig {
+                skill_id: 13,
+                cooldown_ms: 1_500,
+                range: None,
+            }],
+        })
+    }
+
+    #[test]
+    fn global_cooldown_locks_every_skill() {
+        let cooldowns = cooldowns();
+        assert_eq!(cooldowns.try_cast(1, 0, 1_000), Ok(()));
+        assert_eq!(
+            cooldowns.try_cast(1, 5, 1_100),
+            Err(CooldownError::Global { ready_in_ms: 100 })
+        );
+        // Other characters keep their own timers.
+        assert_eq!(cooldowns.try_cast(2, 5, 1_100), Ok(()));
+        assert_eq!(cooldowns.try_cast(1, 5, 1_200), Ok(()));
+    }
+
+    #[test]
+    fn skill_cooldown_outlasts_the_global_one() {
+        let cooldowns = cooldowns();
+        assert_eq!(cooldowns.try_cast(1, 13, 0), Ok(()));
+        assert_eq!(cooldowns.try_cast(1, 0, 300), Ok(()));
+        assert_eq!(
+            cooldowns.try_cast(1, 13, 600),
+            Err(CooldownError::Skill {
+                skill_id: 13,
+                ready_in_ms: 900
+            })
+        );
+        assert_eq!(cooldowns.try_cast(1, 13, 1_500), Ok(()));
+
+        // Relogging keeps running timers.
+        cooldowns.forget(1, 1_600);
+        assert!(cooldowns.try_cast(1, 13, 1_600).is_err());
+        cooldowns.forget(1, 3_000);
+        assert_eq!(cooldowns.try_cast(1, 13, 3_000), Ok(()));
+    }
+
+    #[test]
+    fn latency_grace_does_not_add_up() {
+        let cooldowns = cooldowns();
+        let early = 200 - LATENCY_GRACE_MS;
+        let mut now_ms = 0;
+        let mut accepted = 0;
+        for _ in 0..20 {
+            if cooldowns.try_cast(1, 0, now_ms).is_ok() {
+                accepted += 1;
+            }
+            now_ms += early;
+        }
+        // A macro firing inside the grace gets no more casts than the
+        // cooldown allows over the same span.
+        assert!(accepted <= now_ms / 200 + 1, "{accepted} casts accepted");
+    }
+}
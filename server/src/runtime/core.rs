@@ -1,48 +1,101 @@
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
+use common::{ItemKind, PvpRules};
 use dashmap::DashMap;
 use protocol::message::{CharacterSummary, EntityDelta};
 use protocol::{
-    ClientHello, ClientMessage, MapTransferDirective, PacketPayload, RouteKey, ServerErrorKind,
-    ServerMessage, WireCodec, WirePacket,
+    negotiate, ChatChannel, ChatPayload, ClientHello, ClientMessage, DisconnectReason, DoorState,
+    EventFailure, GensFaction, GensStatus, GuildRelation, ItemAction, ItemFailure, ItemInstance,
+    ItemLocation, MaintenanceNotice, MapTransferDirective, MonsterRank, Negotiated, PacketPayload,
+    ProtocolCapabilities, QuestStatus, RouteKey, SequenceEvent, SequenceVerdict, SequencedReceiver,
+    ServerError, ServerErrorKind, ServerMessage, TradeFailure, TradeOutcome, WhisperResult,
+    WireCodec, WirePacket,
 };
 use serde::Serialize;
-use tokio::sync::Mutex as AsyncMutex;
-
-use super::config::RuntimeConfig;
-use super::directory::{MapRoute, WorldDirectory, WorldDirectorySnapshot};
-use super::map_server::{start_map_server, MapServerConfig, MapServerHandle};
-use super::message_hub::MessageHub;
+use tokio::sync::{mpsc, Mutex as AsyncMutex};
+use utoipa::ToSchema;
+
+use super::account_settings::AccountSettingsStore;
+use super::bestiary::Bestiary;
+use super::cash_shop::{CashReceipt, CashShop, CashShopError, CashShopProduct};
+use super::chat_commands::{ChatCommand, ChatCommandError, ChatMutes};
+use super::collision::CollisionCatalog;
+use super::config::{CleanupConfig, DoorConfig, MapConfig, RuntimeConfig, WorldConfig};
+use super::cooldowns::SkillCooldowns;
+use super::directory::{EntryPointRoute, MapRoute, WorldDirectory, WorldDirectorySnapshot};
+use super::doors::{DoorError, MapDoor};
+use super::doppelganger::{
+    zone_rotation, DoppelgangerError, DoppelgangerRun, DoppelgangerRuns, PartyMember, RETURN_MAP,
+    RUN_DURATION, WAVE_MONSTER_LIFETIME,
+};
+use super::events::{notice_maps, EventError, EventState, SequenceEvents};
+use super::gens::GensRegistry;
+use super::guild_wars::{publish_score, GuildWar, GuildWarError, GuildWars};
+use super::guilds::GuildRelations;
+use super::helper::HelperSessions;
+use super::item_ledger::{DupeReport, ItemHolder, ItemLedger, ItemMove, TransferReason};
+use super::items::{validate_items, ItemOptionError};
+use super::lag_compensation::SessionLatency;
+use super::mailbox::{RewardBundle, RewardDelivery, RewardMailbox, RewardRecipient, RewardSource};
+use super::maintenance::{
+    MaintenanceError, MaintenanceRegistry, MaintenanceScope, MaintenanceWindow,
+};
+use super::map_server::{start_map_server, MapServerConfig, MapServerHandle, MonsterKill};
+use super::message_hub::{HubMessage, HubPayload, MessageHub, MessageScope};
 use super::persistence::{
     start_persistence_worker, CriticalEvent, CriticalEventKind, InMemoryPersistenceSink,
     PersistenceHandle,
 };
+use super::portals::{check_gate, gate_at, WornItems};
+use super::quests::{QuestEvent, QuestLogs};
+use super::restart::{PlannedRestart, RestartError, RestartScheduler};
+use super::session_links::{SessionCommand, SessionControlError, SessionLinks, SessionPush};
+use super::stress::{validate_stress, StressError, StressReport};
+use super::trades::{ConfirmedTrade, Trades};
+use super::transfer_limits::{TransferChannel, TransferLimits};
+use super::wallets::{WalletError, ZenWallets};
+use super::webhooks::{WebhookDispatcher, WebhookEvent};
 use crate::auth_token::{
-    object_id_to_u64, AuthCharacterSummary, AuthSessionClaims, AuthTokenService,
+    object_id_to_u64, AuthCharacterSummary, AuthSessionClaims, AuthTokenError, AuthTokenService,
     MapTransferTokenClaims,
 };
 use crate::protocol_runtime::{IngressPacket, ProtocolRuntime, ProtocolRuntimeError};
+use crate::roles::AccountRole;
 use crate::session::SessionManager;
 
+/// Main inventory size (8x8); every item a character holds and does not wear
+/// takes one slot.
+const INVENTORY_SLOTS: u16 = 64;
+/// Tile characters appear on after a transfer that names none.
+const DEFAULT_LANDING: (u16, u16) = (125, 125);
+/// How long shutdown waits for webhooks to post what they have queued.
+const WEBHOOK_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
 #[derive(Debug, Clone)]
 struct PendingTransfer {
     session_id: u64,
     transfer_id: u64,
     character_id: u64,
     route: RouteKey,
+    /// Tile to appear on; `None` for [`DEFAULT_LANDING`].
+    landing: Option<(u16, u16)>,
 }
 
 #[derive(Debug, Clone)]
 struct AuthenticatedSession {
     account_id: u64,
+    role: AccountRole,
     expires_at_ms: u64,
     characters: HashMap<u64, AuthCharacterSummary>,
+    /// Version and capabilities agreed in the hello.
+    negotiated: Negotiated,
 }
 
 impl AuthenticatedSession {
-    fn from_claims(claims: AuthSessionClaims) -> Self {
+    fn from_claims(claims: AuthSessionClaims, negotiated: Negotiated) -> Self {
         let characters = claims
             .characters
             .into_iter()
@@ -51,8 +104,10 @@ impl AuthenticatedSession {
 
         Self {
             account_id: claims.account_id,
+            role: claims.role,
             expires_at_ms: claims.expires_at_ms,
             characters,
+            negotiated,
         }
     }
 
@@ -76,11 +131,13 @@ impl AuthenticatedSession {
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct RuntimeStats {
     pub online_maps: usize,
     pub active_transfers: usize,
     pub active_sessions_in_maps: usize,
+    pub pending_reward_mail: usize,
+    pub helper_sessions: usize,
 }
 
 #[derive(Clone)]
@@ -98,7 +155,34 @@ pub struct MuCoreRuntime {
     transfer_seq: Arc<AtomicU64>,
     pending_transfers: Arc<DashMap<u64, PendingTransfer>>,
     session_routes: Arc<DashMap<u64, (u64, RouteKey)>>,
+    session_push: SessionPush,
+    latency: SessionLatency,
+    mailbox: RewardMailbox,
+    items: ItemLedger,
+    account_settings: AccountSettingsStore,
+    cash_shop: Option<CashShop>,
+    transfer_limits: TransferLimits,
+    wallets: ZenWallets,
+    trades: Trades,
+    worn_items: WornItems,
+    guilds: GuildRelations,
+    guild_wars: GuildWars,
+    gens: GensRegistry,
+    cooldowns: SkillCooldowns,
+    quests: QuestLogs,
+    bestiary: Bestiary,
+    helpers: HelperSessions,
+    chat_mutes: ChatMutes,
+    maintenance: MaintenanceRegistry,
+    restart: RestartScheduler,
+    events: SequenceEvents,
+    doppelganger: DoppelgangerRuns,
+    webhooks: WebhookDispatcher,
+    // key: faction; value: character leading its ranking when last checked
+    ranking_leaders: Arc<DashMap<GensFaction, u64>>,
+    collision: Arc<CollisionCatalog>,
     scale_lock: Arc<AsyncMutex<()>>,
+    monster_kills: mpsc::UnboundedSender<MonsterKill>,
 }
 
 impl MuCoreRuntime {
@@ -118,6 +202,36 @@ impl MuCoreRuntime {
             sink,
         );
 
+        let active_characters = Arc::new(DashMap::new());
+        let session_routes = Arc::new(DashMap::new());
+        let session_push = SessionPush::new(
+            SessionLinks::new(),
+            session_routes.clone(),
+            active_characters.clone(),
+        );
+        let guilds = GuildRelations::new();
+        let guild_wars = GuildWars::new();
+        let gens = GensRegistry::new();
+        let cooldowns = SkillCooldowns::new(&config.combat);
+        let quests = QuestLogs::new(config.starting_kit.clone(), config.tutorial.clone());
+        let bestiary = Bestiary::new(&config.monsters);
+        let cash_shop = config.cash_shop.as_ref().map(CashShop::new);
+        let transfer_limits = TransferLimits::new(&config.transfer_limits);
+        for product in cash_shop.iter().flat_map(|shop| shop.products()) {
+            validate_items(&product.items).map_err(|err| {
+                anyhow::anyhow!("cash shop product {}: {}", product.product_id, err)
+            })?;
+        }
+        let events = SequenceEvents::new();
+        let webhooks = WebhookDispatcher::start(&config.webhooks);
+        let boot_time_ms = crate::auth_token::now_ms();
+        for world in &config.worlds {
+            events.open_world(world.id, boot_time_ms);
+        }
+        let items = ItemLedger::new(boot_time_ms);
+        let wallets = ZenWallets::new();
+        let (monster_kills, kills) = mpsc::unbounded_channel();
+        let collision = Arc::new(CollisionCatalog::from_runtime_config(&config));
         let map_servers = Arc::new(DashMap::new());
         for world in &config.worlds {
             for entry in &world.entry_points {
@@ -135,12 +249,25 @@ impl MuCoreRuntime {
                                 route,
                                 map_name: map.name.clone(),
                                 soft_player_cap: map.soft_player_cap,
+                                pvp: map.pvp_rules(),
+                                gens_zone: world.gens && map.gens,
                                 player_tick: config.player_tick(),
                                 monster_tick: config.monster_tick(),
+                                collision: collision.grid_for(world.id, entry.id, map.id),
+                                doors: map.doors.clone(),
+                                monsters: config.monsters.clone(),
+                                combat: config.combat.clone(),
+                                cleanup: config.cleanup.for_map(map),
                             },
                             directory.clone(),
                             persistence.clone(),
                             message_hub.clone(),
+                            guilds.clone(),
+                            guild_wars.clone(),
+                            gens.clone(),
+                            session_push.clone(),
+                            items.clone(),
+                            monster_kills.clone(),
                         );
 
                         map_servers.insert(route, handle);
@@ -151,7 +278,7 @@ impl MuCoreRuntime {
 
         let protocol_runtime = ProtocolRuntime::new(WireCodec::default(), "Welcome to MU Online");
 
-        Ok(Self {
+        let runtime = Self {
             config,
             directory,
             message_hub,
@@ -161,12 +288,41 @@ impl MuCoreRuntime {
             auth_tokens,
             session_manager,
             authenticated_sessions: Arc::new(DashMap::new()),
-            active_characters: Arc::new(DashMap::new()),
+            active_characters,
             transfer_seq: Arc::new(AtomicU64::new(1)),
             pending_transfers: Arc::new(DashMap::new()),
-            session_routes: Arc::new(DashMap::new()),
+            session_routes,
+            session_push,
+            latency: SessionLatency::new(),
+            mailbox: RewardMailbox::new(items.clone(), wallets.clone()),
+            items,
+            account_settings: AccountSettingsStore::new(),
+            cash_shop,
+            transfer_limits,
+            wallets,
+            trades: Trades::new(),
+            worn_items: WornItems::new(),
+            guilds,
+            guild_wars,
+            gens,
+            cooldowns,
+            quests,
+            bestiary,
+            helpers: HelperSessions::new(),
+            chat_mutes: ChatMutes::new(),
+            maintenance: MaintenanceRegistry::new(),
+            restart: RestartScheduler::new(),
+            events,
+            doppelganger: DoppelgangerRuns::new(),
+            webhooks,
+            ranking_leaders: Arc::new(DashMap::new()),
+            collision,
             scale_lock: Arc::new(AsyncMutex::new(())),
-        })
+            monster_kills,
+        };
+        runtime.sync_event_doors();
+        runtime.record_monster_kills(kills);
+        Ok(runtime)
     }
 
     pub fn directory_snapshot(&self) -> WorldDirectorySnapshot {
@@ -178,15 +334,204 @@ impl MuCoreRuntime {
             online_maps: self.map_servers.len(),
             active_transfers: self.pending_transfers.len(),
             active_sessions_in_maps: self.session_routes.len(),
+            pending_reward_mail: self.mailbox.pending_count(),
+            helper_sessions: self.helpers.count(),
+        }
+    }
+
+    /// Grants an event reward, routing it through the mailbox when the character
+    /// is offline or lacks inventory room.
+    pub async fn grant_event_reward(
+        &self,
+        character_id: u64,
+        route: RouteKey,
+        source: RewardSource,
+        reward: RewardBundle,
+        server_time_ms: u64,
+    ) -> Result<RewardDelivery, ItemOptionError> {
+        validate_items(&reward.items)?;
+
+        let recipient = RewardRecipient {
+            online: self.active_characters.contains_key(&character_id),
+            free_inventory_slots: self.free_inventory_slots_for(character_id),
+        };
+        let payload = format!("reward:{}:zen={}", source.label(), reward.zen);
+        let delivery = self
+            .mailbox
+            .grant(character_id, source, reward, recipient, server_time_ms);
+
+        let (kind, event_id) = match delivery {
+            RewardDelivery::Direct => (CriticalEventKind::EconomyMutation, 0),
+            RewardDelivery::Mailed { mail_id } => (CriticalEventKind::RewardMailed, mail_id),
+        };
+        let _ = self
+            .persistence
+            .record_critical(CriticalEvent {
+                event_id: ((character_id as u128) << 64) | event_id as u128,
+                character_id,
+                route,
+                kind,
+                payload,
+                occurred_at_ms: server_time_ms,
+            })
+            .await;
+
+        Ok(delivery)
+    }
+
+    /// Counts a monster kill towards the character's tutorial and bestiary
+    /// and tells its session what moved. Boss kills are also posted to the
+    /// webhooks. Map servers report their kills through the channel drained
+    /// by [`MuCoreRuntime::record_monster_kills`].
+    pub async fn record_monster_kill(
+        &self,
+        character_id: u64,
+        monster_id: u16,
+        rank: Option<MonsterRank>,
+        server_time_ms: u64,
+    ) {
+        if rank == Some(MonsterRank::Boss) {
+            let route = self
+                .route_of_character(character_id)
+                .unwrap_or(RouteKey::LOBBY);
+            let monster = self
+                .config
+                .monsters
+                .iter()
+                .find(|monster| monster.id == monster_id)
+                .map_or_else(|| format!("#{monster_id}"), |monster| monster.name.clone());
+            self.webhooks.notify(&WebhookEvent::BossKilled {
+                world_id: route.world_id,
+                map_id: route.map_id,
+                monster,
+                character_id,
+            });
+        }
+
+        if self.bestiary.unlock(character_id, monster_id) {
+            self.session_push.push_to_character(
+                character_id,
+                ServerMessage::BestiaryUnlocked { monster_id },
+                server_time_ms,
+            );
         }
+        if let Some(status) = self
+            .advance_quest(
+                character_id,
+                QuestEvent::Killed { monster_id },
+                server_time_ms,
+            )
+            .await
+        {
+            self.session_push.push_to_character(
+                character_id,
+                ServerMessage::QuestStatus(status),
+                server_time_ms,
+            );
+        }
+    }
+
+    /// Records the kills map servers send until every map server stopped.
+    fn record_monster_kills(&self, mut kills: mpsc::UnboundedReceiver<MonsterKill>) {
+        let runtime = self.clone();
+        tokio::spawn(async move {
+            while let Some(kill) = kills.recv().await {
+                runtime
+                    .record_monster_kill(
+                        kill.character_id,
+                        kill.monster_id,
+                        kill.rank,
+                        kill.server_time_ms,
+                    )
+                    .await;
+            }
+        });
+    }
+
+    /// Applies `event` to the character's quests and grants the reward of a
+    /// quest it finished. Returns the new status when a quest moved.
+    async fn advance_quest(
+        &self,
+        character_id: u64,
+        event: QuestEvent,
+        server_time_ms: u64,
+    ) -> Option<QuestStatus> {
+        let advance = self.quests.record(character_id, event, server_time_ms)?;
+        if let Some(reward) = advance.reward {
+            let route = self
+                .route_of_character(character_id)
+                .unwrap_or(RouteKey::LOBBY);
+            let source = RewardSource::Quest {
+                quest_id: advance.status.quest_id,
+            };
+            if let Err(err) = self
+                .grant_event_reward(character_id, route, source, reward, server_time_ms)
+                .await
+            {
+                log::warn!(
+                    "Failed to grant the reward of quest {} to character {}: {}",
+                    advance.status.quest_id,
+                    character_id,
+                    err
+                );
+            }
+        }
+        Some(advance.status)
+    }
+
+    /// Map route the character is currently playing on, if online.
+    pub fn route_of_character(&self, character_id: u64) -> Option<RouteKey> {
+        let session_id = *self.active_characters.get(&character_id)?.value();
+        self.session_routes
+            .get(&session_id)
+            .map(|entry| entry.value().1)
+    }
+
+    fn free_inventory_slots_for(&self, character_id: u64) -> u16 {
+        let carried = self
+            .items
+            .held_by(ItemHolder::Character { character_id })
+            .into_iter()
+            .filter(|serial| self.worn_items.slot_of(character_id, *serial).is_none())
+            .count();
+        INVENTORY_SLOTS.saturating_sub(u16::try_from(carried).unwrap_or(u16::MAX))
     }
 
+    /// Codec for frames sent to `session_id`, compressing them when the
+    /// session agreed to it.
+    pub fn stream_codec(&self, session_id: u64) -> WireCodec {
+        let compression = self
+            .authenticated_sessions
+            .get(&session_id)
+            .and_then(|session| session.negotiated.capabilities.compression());
+        self.protocol_runtime
+            .codec()
+            .clone()
+            .with_compression(compression)
+    }
+
+    /// Handles a datagram of a connection whose earlier datagrams went
+    /// through `sequencer`; duplicates and datagrams older than one already
+    /// handled for their route are dropped unanswered.
     pub async fn handle_datagram_frame(
         &self,
         datagram: &[u8],
+        sequencer: &mut SequencedReceiver,
         server_time_ms: u64,
     ) -> Result<Option<WirePacket>, ProtocolRuntimeError> {
         let ingress = self.protocol_runtime.decode_v2_datagram(datagram)?;
+        if let IngressPacket::V2Datagram(frame) = &ingress {
+            let verdict = sequencer.receive(&frame.packet, server_time_ms);
+            if verdict != SequenceVerdict::Accepted {
+                log::debug!(
+                    "dropping {:?} datagram {} of session {}",
+                    verdict,
+                    frame.packet.sequence,
+                    frame.packet.session_id
+                );
+                return Ok(None);
+            }
+        }
         self.dispatch_ingress_packet(ingress, server_time_ms).await
     }
 
@@ -286,7 +631,7 @@ impl MuCoreRuntime {
                         return Ok(Some(self.error_for_request(
                             &packet,
                             server_time_ms,
-                            ServerErrorKind::InvalidAction,
+                            ServerErrorKind::CharacterInUse,
                             "Character is already active in another session",
                         )));
                     }
@@ -318,7 +663,7 @@ impl MuCoreRuntime {
                         return Ok(Some(self.error_for_request(
                             &packet,
                             server_time_ms,
-                            ServerErrorKind::InvalidAction,
+                            ServerErrorKind::NotInMap,
                             "Character must enter a map before moving",
                         )))
                     }
@@ -331,6 +676,15 @@ impl MuCoreRuntime {
 
                 if let Some(map) = map {
                     let _ = map.move_player(character_id, input.clone()).await;
+                    // Only a move the map accepted can step on a gate.
+                    if let Some((x, y)) = map.position(character_id).await {
+                        if let Some(response) = self
+                            .enter_gate(&packet, &auth_session, character_id, x, y, server_time_ms)
+                            .await
+                        {
+                            return Ok(Some(response));
+                        }
+                    }
                 } else {
                     return Ok(Some(self.error_for_request(
                         &packet,
@@ -349,8 +703,9 @@ impl MuCoreRuntime {
                             entity_id: character_id as u32,
                             x: input.x,
                             y: input.y,
+                            height: self.ground_height(&packet.route, input.x, input.y),
                             hp: 100,
-                            state_flags: 0,
+                            state_flags: self.gens_state_flags(packet.route.world_id, character_id),
                         }],
                     },
                 )));
@@ -363,7 +718,26 @@ impl MuCoreRuntime {
 
                 if let Some(map) = map {
                     let character_id = self.character_for_session(packet.session_id).unwrap_or(0);
-                    let _ = map.use_skill(character_id, input.clone()).await;
+                    if let Err(err) =
+                        self.cooldowns
+                            .try_cast(character_id, input.skill_id, server_time_ms)
+                    {
+                        return Ok(Some(
+                            self.response_for_request(
+                                &packet,
+                                server_time_ms,
+                                ServerMessage::Error(
+                                    ServerError::new(
+                                        ServerErrorKind::SkillCooldown,
+                                        err.to_string(),
+                                    )
+                                    .retry_after(err.ready_in_ms()),
+                                ),
+                            ),
+                        ));
+                    }
+                    let rtt_ms = self.latency.rtt_ms(packet.session_id);
+                    let _ = map.use_skill(character_id, input.clone(), rtt_ms).await;
 
                     // Critical operations should be persisted immediately.
                     if input.skill_id >= 200 {
@@ -383,6 +757,25 @@ impl MuCoreRuntime {
                 }
             }
             ClientMessage::Chat(chat) => {
+                if let Some(command) = ChatCommand::parse(&chat.text) {
+                    return Ok(Some(
+                        self.handle_chat_command(&packet, &auth_session, command, server_time_ms)
+                            .await,
+                    ));
+                }
+
+                let muted_ms = self
+                    .active_character_name(packet.session_id)
+                    .and_then(|name| self.chat_mutes.remaining_ms(&name, server_time_ms));
+                if let Some(muted_ms) = muted_ms {
+                    return Ok(Some(self.error_for_request(
+                        &packet,
+                        server_time_ms,
+                        ServerErrorKind::ChatMuted,
+                        &format!("Muted for {} more minutes", muted_ms.div_ceil(60_000)),
+                    )));
+                }
+
                 let map = self
                     .map_servers
                     .get(&packet.route)
@@ -395,716 +788,4763 @@ impl MuCoreRuntime {
                         .await;
                 }
             }
-            ClientMessage::Logout => {
-                self.detach_session_from_map(packet.session_id).await;
-                self.clear_pending_transfers(packet.session_id);
-                self.authenticated_sessions.remove(&packet.session_id);
-            }
-            ClientMessage::Hello(_) | ClientMessage::KeepAlive { .. } => {}
-        }
-
-        Ok(baseline)
-    }
-
-    pub async fn shutdown(&self) -> anyhow::Result<()> {
-        let handles: Vec<_> = self
-            .map_servers
-            .iter()
-            .map(|entry| entry.value().clone())
-            .collect();
-
-        for map in handles {
-            map.shutdown().await?;
-        }
-
-        self.persistence.shutdown().await?;
-        Ok(())
-    }
-
-    pub async fn map_stats(&self) -> Vec<super::map_server::MapServerStats> {
-        let handles: Vec<_> = self
-            .map_servers
-            .iter()
-            .map(|entry| entry.value().clone())
-            .collect();
-
-        let mut out = Vec::new();
-        for map in handles {
-            out.push(map.stats().await);
-        }
-
-        out.sort_by_key(|s| {
-            (
-                s.route.world_id,
-                s.route.entry_id,
-                s.route.map_id,
-                s.route.instance_id,
-            )
-        });
-
-        out
-    }
-
-    pub async fn persistence_metrics(&self) -> super::persistence::PersistenceMetrics {
-        self.persistence.metrics().await
-    }
-
-    pub fn config(&self) -> &RuntimeConfig {
-        &self.config
-    }
+            ClientMessage::Whisper(whisper) => {
+                let Some(sender) = self.active_character_name(packet.session_id) else {
+                    return Ok(Some(self.error_for_request(
+                        &packet,
+                        server_time_ms,
+                        ServerErrorKind::NotInMap,
+                        "Character must enter a map before whispering",
+                    )));
+                };
+                if let Some(muted_ms) = self.chat_mutes.remaining_ms(&sender, server_time_ms) {
+                    return Ok(Some(self.error_for_request(
+                        &packet,
+                        server_time_ms,
+                        ServerErrorKind::ChatMuted,
+                        &format!("Muted for {} more minutes", muted_ms.div_ceil(60_000)),
+                    )));
+                }
 
-    fn handle_hello(
-        &self,
-        packet: &WirePacket,
-        hello: &ClientHello,
-        server_time_ms: u64,
-    ) -> WirePacket {
-        let claims = match self.auth_tokens.verify(&hello.auth_token, server_time_ms) {
-            Ok(claims) => claims,
-            Err(err) => {
-                log::warn!("Failed QUIC hello authentication: {}", err);
-                return self.error_for_request(
-                    packet,
+                let result = match self.session_playing(&whisper.target) {
+                    Some(target_session_id) => {
+                        self.message_hub.publish(
+                            MessageScope::Session(target_session_id),
+                            HubMessage {
+                                from_session_id: packet.session_id,
+                                route: packet.route,
+                                payload: HubPayload::Chat(ChatPayload {
+                                    channel: ChatChannel::Whisper,
+                                    target: Some(sender),
+                                    text: whisper.text.clone(),
+                                }),
+                            },
+                        );
+                        WhisperResult::Delivered
+                    }
+                    None => WhisperResult::NotOnline,
+                };
+                return Ok(Some(self.response_for_request(
+                    &packet,
                     server_time_ms,
-                    ServerErrorKind::InvalidSession,
-                    "Invalid auth token",
-                );
+                    ServerMessage::WhisperResult {
+                        target: whisper.target.clone(),
+                        result,
+                    },
+                )));
             }
-        };
-
-        if claims.account_id != hello.account_id {
-            log::warn!(
-                "Failed QUIC hello authentication: account mismatch (packet={} claims={})",
-                hello.account_id,
-                claims.account_id
-            );
-            return self.error_for_request(
-                packet,
-                server_time_ms,
-                ServerErrorKind::InvalidSession,
-                "Account mismatch in auth token",
-            );
-        }
+            ClientMessage::Emote { emote } => {
+                let map = self
+                    .map_servers
+                    .get(&packet.route)
+                    .map(|entry| entry.value().clone());
 
-        if let Some(session_manager) = &self.session_manager {
-            let http_session = match session_manager.validate_session(&claims.session_id) {
-                Ok(session) => session,
-                Err(err) => {
-                    log::warn!(
-                        "Failed QUIC hello authentication: HTTP session '{}' is invalid ({})",
-                        claims.session_id,
-                        err
-                    );
-                    return self.error_for_request(
-                        packet,
-                        server_time_ms,
-                        ServerErrorKind::InvalidSession,
-                        "HTTP session is invalid or expired",
-                    );
+                if let (Some(map), Some(character_id)) =
+                    (map, self.character_for_session(packet.session_id))
+                {
+                    let _ = map.emote(packet.session_id, character_id, *emote).await;
                 }
-            };
+            }
+            ClientMessage::RequestMailbox => {
+                let Some(character_id) = self.character_for_session(packet.session_id) else {
+                    return Ok(Some(self.error_for_request(
+                        &packet,
+                        server_time_ms,
+                        ServerErrorKind::NotInMap,
+                        "Character must enter a map before reading mail",
+                    )));
+                };
 
-            let session_account_id = object_id_to_u64(&http_session.account_id);
-            if session_account_id != claims.account_id {
-                log::warn!(
-                    "Failed QUIC hello authentication: token/session account mismatch (token={} session={})",
-                    claims.account_id,
-                    session_account_id
-                );
-                return self.error_for_request(
-                    packet,
+                let entries = self
+                    .mailbox
+                    .pending_for(character_id)
+                    .iter()
+                    .map(|mail| mail.to_protocol())
+                    .collect();
+                return Ok(Some(self.response_for_request(
+                    &packet,
                     server_time_ms,
-                    ServerErrorKind::InvalidSession,
-                    "Account mismatch in session",
-                );
+                    ServerMessage::Mailbox { entries },
+                )));
             }
-        }
-
-        let auth_session = AuthenticatedSession::from_claims(claims);
-        let characters = auth_session.character_list();
-        self.authenticated_sessions
-            .insert(packet.session_id, auth_session.clone());
-
-        log::info!(
-            "QUIC session authenticated: session_id={} account_id={} characters={}",
-            packet.session_id,
-            auth_session.account_id,
-            characters.len()
-        );
-
-        self.response_for_request(
-            packet,
-            server_time_ms,
-            ServerMessage::HelloAck {
-                session_id: packet.session_id,
-                heartbeat_interval_ms: 5_000,
-                motd: "Welcome to MU Online".to_string(),
-                characters,
-            },
-        )
-    }
+            ClientMessage::ClaimMail { mail_id } => {
+                let Some(character_id) = self.character_for_session(packet.session_id) else {
+                    return Ok(Some(self.error_for_request(
+                        &packet,
+                        server_time_ms,
+                        ServerErrorKind::NotInMap,
+                        "Character must enter a map before claiming mail",
+                    )));
+                };
 
-    async fn authenticated_session(
-        &self,
-        session_id: u64,
+                let free_slots = self.free_inventory_slots_for(character_id);
+                let claimed =
+                    self.mailbox
+                        .claim(character_id, *mail_id, free_slots, server_time_ms);
+                let response = match claimed {
+                    Ok(mail) => {
+                        let _ = self
+                            .persistence
+                            .record_critical(CriticalEvent {
+                                event_id: ((character_id as u128) << 64) | mail.mail_id as u128,
+                                character_id,
+                                route: packet.route,
+                                kind: CriticalEventKind::RewardClaimed,
+                                payload: format!("mail:{}:zen={}", mail.mail_id, mail.reward.zen),
+                                occurred_at_ms: server_time_ms,
+                            })
+                            .await;
+                        ServerMessage::MailClaimed { mail_id: *mail_id }
+                    }
+                    Err(err) => ServerMessage::Error(ServerError::new(err.kind(), err.to_string())),
+                };
+                return Ok(Some(self.response_for_request(
+                    &packet,
+                    server_time_ms,
+                    response,
+                )));
+            }
+            ClientMessage::RequestAccountSettings => {
+                let settings = self.account_settings.get(auth_session.account_id);
+                return Ok(Some(self.response_for_request(
+                    &packet,
+                    server_time_ms,
+                    ServerMessage::AccountSettings { settings },
+                )));
+            }
+            ClientMessage::StoreAccountSettings(settings) => {
+                let response = match self
+                    .account_settings
+                    .store(auth_session.account_id, settings.clone())
+                {
+                    Ok(kept) => ServerMessage::AccountSettings {
+                        settings: Some(kept),
+                    },
+                    Err(err) => ServerMessage::Error(ServerError::new(err.kind(), err.to_string())),
+                };
+                return Ok(Some(self.response_for_request(
+                    &packet,
+                    server_time_ms,
+                    response,
+                )));
+            }
+            ClientMessage::SetHelperActive { active } => {
+                let Some((character_id, route)) = self
+                    .session_routes
+                    .get(&packet.session_id)
+                    .map(|entry| *entry.value())
+                else {
+                    return Ok(Some(self.error_for_request(
+                        &packet,
+                        server_time_ms,
+                        ServerErrorKind::NotInMap,
+                        "Character must enter a map before using the helper",
+                    )));
+                };
+
+                let allowed = self.helper_allowed(route.world_id);
+                if *active && allowed {
+                    self.helpers
+                        .start(packet.session_id, character_id, route, server_time_ms);
+                } else {
+                    self.helpers.stop(packet.session_id);
+                }
+                return Ok(Some(self.response_for_request(
+                    &packet,
+                    server_time_ms,
+                    ServerMessage::HelperStatus {
+                        allowed,
+                        active: self.helpers.is_active(packet.session_id),
+                    },
+                )));
+            }
+            ClientMessage::JoinGens { faction } => {
+                let Some((character_id, route)) = self
+                    .session_routes
+                    .get(&packet.session_id)
+                    .map(|entry| *entry.value())
+                else {
+                    return Ok(Some(self.error_for_request(
+                        &packet,
+                        server_time_ms,
+                        ServerErrorKind::NotInMap,
+                        "Character must enter a map before joining the Gens",
+                    )));
+                };
+
+                let status = if !self.gens_enabled(route.world_id) {
+                    GensStatus {
+                        enabled: false,
+                        faction: None,
+                        contribution: 0,
+                    }
+                } else {
+                    match self.gens.join(character_id, *faction, server_time_ms) {
+                        Ok(_) => self.gens.status(character_id),
+                        Err(err) => {
+                            return Ok(Some(self.error_for_request(
+                                &packet,
+                                server_time_ms,
+                                err.kind(),
+                                &err.to_string(),
+                            )))
+                        }
+                    }
+                };
+                return Ok(Some(self.response_for_request(
+                    &packet,
+                    server_time_ms,
+                    ServerMessage::GensStatus(status),
+                )));
+            }
+            ClientMessage::TalkToNpc { npc_id } => {
+                let Some(character_id) = self.character_for_session(packet.session_id) else {
+                    return Ok(baseline);
+                };
+                let event = QuestEvent::TalkedTo { npc_id: *npc_id };
+                if let Some(status) = self
+                    .advance_quest(character_id, event, server_time_ms)
+                    .await
+                {
+                    return Ok(Some(self.response_for_request(
+                        &packet,
+                        server_time_ms,
+                        ServerMessage::QuestStatus(status),
+                    )));
+                }
+            }
+            ClientMessage::EquipItem { serial } => {
+                let Some(character_id) = self.character_for_session(packet.session_id) else {
+                    return Ok(baseline);
+                };
+                if self.items.owner(*serial) != Some(ItemHolder::Character { character_id }) {
+                    return Ok(Some(self.error_for_request(
+                        &packet,
+                        server_time_ms,
+                        ServerErrorKind::ItemNotHeld,
+                        "Item is not held by the character",
+                    )));
+                }
+                if let Some(code) = self.items.code(*serial) {
+                    self.worn_items.wear(character_id, *serial, code);
+                }
+                if let Some(status) = self
+                    .advance_quest(character_id, QuestEvent::Equipped, server_time_ms)
+                    .await
+                {
+                    return Ok(Some(self.response_for_request(
+                        &packet,
+                        server_time_ms,
+                        ServerMessage::QuestStatus(status),
+                    )));
+                }
+            }
+            ClientMessage::MoveItem { serial, from, to } => {
+                let Some(character_id) = self.character_for_session(packet.session_id) else {
+                    return Ok(baseline);
+                };
+                let reply = match self.move_item(character_id, *serial, *from, *to) {
+                    Ok(()) => ServerMessage::ItemMoved {
+                        serial: *serial,
+                        to: *to,
+                    },
+                    Err(reason) => ServerMessage::ItemRejected {
+                        action: ItemAction::Move,
+                        reason,
+                    },
+                };
+                return Ok(Some(self.response_for_request(
+                    &packet,
+                    server_time_ms,
+                    reply,
+                )));
+            }
+            ClientMessage::UnequipItem { slot, cell } => {
+                let Some(character_id) = self.character_for_session(packet.session_id) else {
+                    return Ok(baseline);
+                };
+                let to = ItemLocation::Inventory { cell: *cell };
+                let moved = self
+                    .worn_items
+                    .in_slot(character_id, *slot)
+                    .ok_or(ItemFailure::NotHeld)
+                    .and_then(|serial| {
+                        self.move_item(character_id, serial, ItemLocation::Equipment(*slot), to)
+                            .map(|()| serial)
+                    });
+                let reply = match moved {
+                    Ok(serial) => ServerMessage::ItemMoved { serial, to },
+                    Err(reason) => ServerMessage::ItemRejected {
+                        action: ItemAction::Unequip,
+                        reason,
+                    },
+                };
+                return Ok(Some(self.response_for_request(
+                    &packet,
+                    server_time_ms,
+                    reply,
+                )));
+            }
+            ClientMessage::DropItem { serial, x, y } => {
+                let Some(character_id) = self.character_for_session(packet.session_id) else {
+                    return Ok(baseline);
+                };
+                let reply = match self.drop_item(character_id, *serial, *x, *y).await {
+                    Ok(entity_id) => ServerMessage::ItemDropped {
+                        serial: *serial,
+                        entity_id,
+                    },
+                    Err(reason) => ServerMessage::ItemRejected {
+                        action: ItemAction::Drop,
+                        reason,
+                    },
+                };
+                return Ok(Some(self.response_for_request(
+                    &packet,
+                    server_time_ms,
+                    reply,
+                )));
+            }
+            ClientMessage::UseItem { serial } => {
+                let Some(character_id) = self.character_for_session(packet.session_id) else {
+                    return Ok(baseline);
+                };
+                let usable = self
+                    .items
+                    .code(*serial)
+                    .and_then(|code| code.definition())
+                    .is_some_and(|item| matches!(item.kind, ItemKind::Potion | ItemKind::Scroll));
+                let reason =
+                    if self.items.owner(*serial) != Some(ItemHolder::Character { character_id }) {
+                        Some(ItemFailure::NotHeld)
+                    } else if !usable {
+                        Some(ItemFailure::NotUsable)
+                    } else {
+                        None
+                    };
+                if let Some(reason) = reason {
+                    return Ok(Some(self.response_for_request(
+                        &packet,
+                        server_time_ms,
+                        ServerMessage::ItemRejected {
+                            action: ItemAction::Use,
+                            reason,
+                        },
+                    )));
+                }
+                // Consumables have no effects yet.
+                return Ok(Some(self.error_for_request(
+                    &packet,
+                    server_time_ms,
+                    ServerErrorKind::InvalidAction,
+                    "Item action is not supported yet",
+                )));
+            }
+            ClientMessage::PickupItem { entity_id } => {
+                let Some(character_id) = self.character_for_session(packet.session_id) else {
+                    return Ok(baseline);
+                };
+                let reply = match self.pick_up_item(character_id, *entity_id).await {
+                    // The client keeps the inventory layout and places the
+                    // item itself.
+                    Ok(item) => ServerMessage::ItemPickedUp { item, cell: 0 },
+                    Err(reason) => ServerMessage::ItemRejected {
+                        action: ItemAction::Pickup,
+                        reason,
+                    },
+                };
+                return Ok(Some(self.response_for_request(
+                    &packet,
+                    server_time_ms,
+                    reply,
+                )));
+            }
+            ClientMessage::RequestBestiary => {
+                let Some(character_id) = self.character_for_session(packet.session_id) else {
+                    return Ok(Some(self.error_for_request(
+                        &packet,
+                        server_time_ms,
+                        ServerErrorKind::NotInMap,
+                        "Character must enter a map before reading the bestiary",
+                    )));
+                };
+                return Ok(Some(self.response_for_request(
+                    &packet,
+                    server_time_ms,
+                    ServerMessage::Bestiary {
+                        unlocked: self.bestiary.unlocked(character_id),
+                    },
+                )));
+            }
+            ClientMessage::RequestDropTable { monster_id } => {
+                let Some(character_id) = self.character_for_session(packet.session_id) else {
+                    return Ok(Some(self.error_for_request(
+                        &packet,
+                        server_time_ms,
+                        ServerErrorKind::NotInMap,
+                        "Character must enter a map before reading the bestiary",
+                    )));
+                };
+                if !self.bestiary.is_unlocked(character_id, *monster_id) {
+                    return Ok(Some(self.error_for_request(
+                        &packet,
+                        server_time_ms,
+                        ServerErrorKind::InvalidAction,
+                        "Monster is not unlocked in the bestiary",
+                    )));
+                }
+                return Ok(Some(self.response_for_request(
+                    &packet,
+                    server_time_ms,
+                    ServerMessage::DropTable(self.bestiary.drop_table(*monster_id)),
+                )));
+            }
+            ClientMessage::RequestTrade { .. }
+            | ClientMessage::RespondTrade { .. }
+            | ClientMessage::AddTradeItem { .. }
+            | ClientMessage::RemoveTradeItem { .. }
+            | ClientMessage::SetTradeZen { .. }
+            | ClientMessage::LockTrade { .. }
+            | ClientMessage::ConfirmTrade
+            | ClientMessage::CancelTrade => {
+                let Some(character_id) = self.character_for_session(packet.session_id) else {
+                    return Ok(baseline);
+                };
+                let reply = self.handle_trade(
+                    packet.session_id,
+                    character_id,
+                    client_message,
+                    server_time_ms,
+                );
+                return Ok(match reply {
+                    Ok(Some(message)) => {
+                        Some(self.response_for_request(&packet, server_time_ms, message))
+                    }
+                    Ok(None) => baseline,
+                    Err(reason) => Some(self.response_for_request(
+                        &packet,
+                        server_time_ms,
+                        ServerMessage::TradeRejected { reason },
+                    )),
+                });
+            }
+            ClientMessage::OpenPersonalStore { .. }
+            | ClientMessage::ClosePersonalStore
+            | ClientMessage::BrowsePersonalStore { .. }
+            | ClientMessage::BuyFromPersonalStore { .. } => {
+                if self.character_for_session(packet.session_id).is_none() {
+                    return Ok(baseline);
+                }
+                // The wire contract is in place; personal stores are not.
+                return Ok(Some(self.error_for_request(
+                    &packet,
+                    server_time_ms,
+                    ServerErrorKind::InvalidAction,
+                    "Personal stores are not supported yet",
+                )));
+            }
+            ClientMessage::RegisterForEvent { event, .. }
+            | ClientMessage::CancelEventRegistration { event } => {
+                if self.character_for_session(packet.session_id).is_none() {
+                    return Ok(baseline);
+                }
+                // The wire contract is in place; the arena scheduler is not.
+                return Ok(Some(self.response_for_request(
+                    &packet,
+                    server_time_ms,
+                    ServerMessage::EventRegistrationRejected {
+                        event: *event,
+                        reason: EventFailure::NotOpen,
+                    },
+                )));
+            }
+            ClientMessage::Logout => self.end_session(packet.session_id, server_time_ms).await,
+            ClientMessage::Hello(_)
+            | ClientMessage::KeepAlive { .. }
+            | ClientMessage::Ping { .. } => {}
+        }
+
+        Ok(baseline)
+    }
+
+    pub async fn shutdown(&self) -> anyhow::Result<()> {
+        self.webhooks.notify(&WebhookEvent::ServerStopping);
+        let handles: Vec<_> = self
+            .map_servers
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect();
+
+        for map in handles {
+            map.shutdown().await?;
+        }
+
+        self.persistence.shutdown().await?;
+        self.webhooks.shutdown(WEBHOOK_DRAIN_TIMEOUT).await;
+        Ok(())
+    }
+
+    pub async fn map_stats(&self) -> Vec<super::map_server::MapServerStats> {
+        let handles: Vec<_> = self
+            .map_servers
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect();
+
+        let mut out = Vec::new();
+        for map in handles {
+            out.push(map.stats().await);
+        }
+
+        out.sort_by_key(|s| {
+            (
+                s.route.world_id,
+                s.route.entry_id,
+                s.route.map_id,
+                s.route.instance_id,
+            )
+        });
+
+        out
+    }
+
+    pub async fn persistence_metrics(&self) -> super::persistence::PersistenceMetrics {
+        self.persistence.metrics().await
+    }
+
+    pub fn config(&self) -> &RuntimeConfig {
+        &self.config
+    }
+
+    pub fn guild_relations(&self) -> &GuildRelations {
+        &self.guilds
+    }
+
+    pub fn guild_wars(&self) -> &GuildWars {
+        &self.guild_wars
+    }
+
+    pub fn gens(&self) -> &GensRegistry {
+        &self.gens
+    }
+
+    pub fn webhooks(&self) -> &WebhookDispatcher {
+        &self.webhooks
+    }
+
+    /// Posts to the webhooks when another character took the lead of a Gens
+    /// faction since the last call. The first call only notes the leaders.
+    /// Returns how many leads changed hands.
+    pub fn announce_ranking_leaders(&self) -> usize {
+        let mut changed = 0;
+        for faction in [GensFaction::Duprian, GensFaction::Vanert] {
+            let Some(leader) = self.gens.ranking(Some(faction), 1).into_iter().next() else {
+                continue;
+            };
+            if leader.contribution == 0 {
+                continue;
+            }
+            let previous = self.ranking_leaders.insert(faction, leader.character_id);
+            if previous.is_some_and(|previous| previous != leader.character_id) {
+                self.webhooks.notify(&WebhookEvent::TopRanking {
+                    faction,
+                    character_id: leader.character_id,
+                    contribution: leader.contribution,
+                });
+                changed += 1;
+            }
+        }
+        changed
+    }
+
+    /// `true` when at least one world runs with Gens.
+    pub fn gens_active(&self) -> bool {
+        self.config.worlds.iter().any(|world| world.gens)
+    }
+
+    pub fn quest_logs(&self) -> &QuestLogs {
+        &self.quests
+    }
+
+    pub fn bestiary(&self) -> &Bestiary {
+        &self.bestiary
+    }
+
+    /// Starts a war between two guilds that are not allied.
+    pub fn start_guild_war(
+        &self,
+        guild_id: u32,
+        target_guild_id: u32,
+        duration: std::time::Duration,
+        server_time_ms: u64,
+    ) -> Result<GuildWar, GuildWarError> {
+        if guild_id != target_guild_id
+            && self.guilds.relation(guild_id, target_guild_id) == Some(GuildRelation::Alliance)
+        {
+            return Err(GuildWarError::Allied(guild_id, target_guild_id));
+        }
+
+        let war = self
+            .guild_wars
+            .start(guild_id, target_guild_id, duration, server_time_ms)?;
+        publish_score(
+            &self.guilds,
+            &self.session_push,
+            &war.score(false),
+            server_time_ms,
+        );
+        log::info!(
+            "Guild war {} started between guilds {} and {} until {}",
+            war.war_id,
+            guild_id,
+            target_guild_id,
+            war.ends_at_ms
+        );
+        Ok(war)
+    }
+
+    /// Closes wars whose window ended and sends both guilds the final score.
+    /// Returns how many wars ended.
+    pub fn end_expired_guild_wars(&self, server_time_ms: u64) -> usize {
+        let finished = self.guild_wars.finish_expired(server_time_ms);
+        for war in &finished {
+            publish_score(
+                &self.guilds,
+                &self.session_push,
+                &war.score(true),
+                server_time_ms,
+            );
+            log::info!(
+                "Guild war {} ended {}-{}",
+                war.war_id,
+                war.guild_kills,
+                war.target_kills
+            );
+        }
+        finished.len()
+    }
+
+    pub fn helpers(&self) -> &HelperSessions {
+        &self.helpers
+    }
+
+    pub fn item_ledger(&self) -> &ItemLedger {
+        &self.items
+    }
+
+    pub fn mailbox(&self) -> &RewardMailbox {
+        &self.mailbox
+    }
+
+    pub fn wallets(&self) -> &ZenWallets {
+        &self.wallets
+    }
+
+    /// Looks for duplicated or stray item serials in every place the runtime
+    /// keeps items. Only mail holds items server-side for now; inventories
+    /// join the scan once the server tracks them.
+    pub fn scan_item_dupes(&self, server_time_ms: u64) -> DupeReport {
+        let holdings = self.mailbox.holdings();
+        self.items.scan(
+            holdings.iter().map(|(holder, item)| (*holder, item)),
+            server_time_ms,
+        )
+    }
+
+    /// Removes time-limited items whose time ran out and tells each online
+    /// recipient which of their items are gone. Returns how many expired.
+    pub fn expire_items(&self, server_time_ms: u64) -> usize {
+        let expired = self.items.expire_due(server_time_ms);
+        let mut by_character: HashMap<u64, Vec<ItemInstance>> = HashMap::new();
+        for (holder, item) in &expired {
+            let character_id = match *holder {
+                ItemHolder::Character { character_id } | ItemHolder::Store { character_id } => {
+                    Some(character_id)
+                }
+                ItemHolder::Mail { mail_id } => self.mailbox.remove_item(mail_id, item.serial),
+                ItemHolder::Ground { .. } | ItemHolder::Destroyed => None,
+            };
+            if let Some(character_id) = character_id {
+                by_character
+                    .entry(character_id)
+                    .or_default()
+                    .push(item.clone());
+            }
+        }
+
+        for (character_id, items) in by_character {
+            let Some(session_id) = self
+                .active_characters
+                .get(&character_id)
+                .map(|entry| *entry.value())
+            else {
+                continue;
+            };
+            let route = self
+                .route_of_character(character_id)
+                .unwrap_or(RouteKey::LOBBY);
+            self.message_hub.publish(
+                MessageScope::Session(session_id),
+                HubMessage {
+                    from_session_id: 0,
+                    route,
+                    payload: HubPayload::ItemsExpired(items),
+                },
+            );
+        }
+        expired.len()
+    }
+
+    pub fn account_settings(&self) -> &AccountSettingsStore {
+        &self.account_settings
+    }
+
+    /// Premium-currency ledger and catalog; `None` when the runtime config has
+    /// no `[cash_shop]`.
+    pub fn cash_shop(&self) -> Option<&CashShop> {
+        self.cash_shop.as_ref()
+    }
+
+    /// Daily caps on the zen and items each account moves to other
+    /// characters.
+    pub fn transfer_limits(&self) -> &TransferLimits {
+        &self.transfer_limits
+    }
+
+    /// Debits a cash-shop product and delivers it to the character in the
+    /// same step. A retried request with the same idempotency key returns the
+    /// first receipt and delivers nothing.
+    pub async fn purchase_cash_product(
+        &self,
+        account_id: u64,
+        character_id: u64,
+        product_id: u32,
+        idempotency_key: &str,
+        server_time_ms: u64,
+    ) -> Result<CashReceipt, CashShopError> {
+        let shop = self.cash_shop.as_ref().ok_or(CashShopError::Disabled)?;
+        let purchase = shop.purchase(
+            account_id,
+            character_id,
+            product_id,
+            idempotency_key,
+            server_time_ms,
+            |product| self.deliver_cash_product(character_id, product, server_time_ms),
+        )?;
+        if purchase.replayed {
+            return Ok(purchase.receipt);
+        }
+
+        let _ = self
+            .persistence
+            .record_critical(CriticalEvent {
+                event_id: ((character_id as u128) << 64) | purchase.receipt.at_ms as u128,
+                character_id,
+                route: self
+                    .route_of_character(character_id)
+                    .unwrap_or(RouteKey::LOBBY),
+                kind: CriticalEventKind::EconomyMutation,
+                payload: format!(
+                    "cash_shop:{}:account={}:price={}",
+                    product_id, account_id, purchase.receipt.amount
+                ),
+                occurred_at_ms: server_time_ms,
+            })
+            .await;
+
+        Ok(purchase.receipt)
+    }
+
+    /// Hands a bought product straight to the character: its zen goes to the
+    /// wallet and its items are minted into the inventory. Refused whole when
+    /// either has no room, so the purchase debits nothing.
+    fn deliver_cash_product(
+        &self,
+        character_id: u64,
+        product: &CashShopProduct,
+        server_time_ms: u64,
+    ) -> Result<(), CashShopError> {
+        let available = self.free_inventory_slots_for(character_id);
+        if (available as usize) < product.items.len() {
+            return Err(CashShopError::InventoryFull {
+                required: product.items.len(),
+                available,
+            });
+        }
+        self.wallets.credit(character_id, product.zen)?;
+        for mut item in product.items.clone() {
+            self.items.mint(
+                &mut item,
+                ItemHolder::Character { character_id },
+                server_time_ms,
+            );
+        }
+        Ok(())
+    }
+
+    /// Runs one trade message of `character_id` and tells its partner what
+    /// changed. Returns the reply to the session, or `None` when the request
+    /// only needs the acknowledgement.
+    fn handle_trade(
+        &self,
+        session_id: u64,
+        character_id: u64,
+        message: &ClientMessage,
+        server_time_ms: u64,
+    ) -> Result<Option<ServerMessage>, TradeFailure> {
+        match message {
+            ClientMessage::RequestTrade { target_entity_id } => {
+                // Player entities carry their character id.
+                let target = u64::from(*target_entity_id);
+                let route = self.route_of_character(character_id);
+                if route.is_none() || self.route_of_character(target) != route {
+                    return Err(TradeFailure::TooFar);
+                }
+                self.trades.request(character_id, target)?;
+                let (name, level) = self
+                    .character_summary(session_id, character_id)
+                    .map(|summary| (summary.name, summary.level))
+                    .unwrap_or_default();
+                self.session_push.push_to_character(
+                    target,
+                    ServerMessage::TradeRequested {
+                        from_entity_id: character_id as u32,
+                        name,
+                        level,
+                    },
+                    server_time_ms,
+                );
+                Ok(None)
+            }
+            ClientMessage::RespondTrade { accept } => {
+                let asking = self.trades.respond(character_id, *accept)?;
+                if !*accept {
+                    self.session_push.push_to_character(
+                        asking,
+                        ServerMessage::TradeClosed {
+                            outcome: TradeOutcome::Declined,
+                        },
+                        server_time_ms,
+                    );
+                    return Ok(None);
+                }
+                let opened = |partner: u64| {
+                    let session_id = self.active_characters.get(&partner).map(|entry| *entry);
+                    let (partner_name, partner_level) = session_id
+                        .and_then(|session_id| self.character_summary(session_id, partner))
+                        .map(|summary| (summary.name, summary.level))
+                        .unwrap_or_default();
+                    ServerMessage::TradeOpened {
+                        partner_entity_id: partner as u32,
+                        partner_name,
+                        partner_level,
+                    }
+                };
+                self.session_push
+                    .push_to_character(asking, opened(character_id), server_time_ms);
+                Ok(Some(opened(asking)))
+            }
+            ClientMessage::AddTradeItem { serial, cell } => {
+                if self.items.owner(*serial) != Some(ItemHolder::Character { character_id }) {
+                    return Err(TradeFailure::NotHeld);
+                }
+                if self.worn_items.slot_of(character_id, *serial).is_some() {
+                    return Err(TradeFailure::NotTradable);
+                }
+                let item = self.items.item(*serial).ok_or(TradeFailure::NotHeld)?;
+                if item.expires_at_ms.is_some() {
+                    return Err(TradeFailure::NotTradable);
+                }
+                self.trades.add_item(character_id, *cell, item)?;
+                Ok(self.send_trade_offers(character_id, server_time_ms))
+            }
+            ClientMessage::RemoveTradeItem { serial } => {
+                self.trades.remove_item(character_id, *serial)?;
+                Ok(self.send_trade_offers(character_id, server_time_ms))
+            }
+            ClientMessage::SetTradeZen { zen } => {
+                if self.wallets.balance(character_id) < *zen {
+                    return Err(TradeFailure::NotEnoughZen);
+                }
+                self.trades.set_zen(character_id, *zen)?;
+                Ok(self.send_trade_offers(character_id, server_time_ms))
+            }
+            ClientMessage::LockTrade { locked } => {
+                self.trades.lock(character_id, *locked)?;
+                Ok(self.send_trade_offers(character_id, server_time_ms))
+            }
+            ClientMessage::ConfirmTrade => {
+                let Some(trade) = self.trades.confirm(character_id)? else {
+                    return Ok(None);
+                };
+                let outcome = match self.swap_trade(&trade, server_time_ms) {
+                    Ok(()) => TradeOutcome::Completed,
+                    Err(reason) => TradeOutcome::Failed(reason),
+                };
+                self.session_push.push_to_character(
+                    trade.second,
+                    ServerMessage::TradeClosed { outcome },
+                    server_time_ms,
+                );
+                Ok(Some(ServerMessage::TradeClosed { outcome }))
+            }
+            ClientMessage::CancelTrade => {
+                let partner = self
+                    .trades
+                    .cancel(character_id)
+                    .ok_or(TradeFailure::NoTrade)?;
+                let closed = ServerMessage::TradeClosed {
+                    outcome: TradeOutcome::Cancelled,
+                };
+                self.session_push
+                    .push_to_character(partner, closed.clone(), server_time_ms);
+                Ok(Some(closed))
+            }
+            _ => Err(TradeFailure::NoTrade),
+        }
+    }
+
+    /// Pushes both offers to the partner of `character_id` and returns the
+    /// update for `character_id` itself.
+    fn send_trade_offers(&self, character_id: u64, server_time_ms: u64) -> Option<ServerMessage> {
+        let (partner, own, other) = self.trades.offers(character_id)?;
+        self.session_push.push_to_character(
+            partner,
+            ServerMessage::TradeUpdated {
+                own: other.clone(),
+                partner: own.clone(),
+            },
+            server_time_ms,
+        );
+        Some(ServerMessage::TradeUpdated {
+            own,
+            partner: other,
+        })
+    }
+
+    /// Swaps the offers of a confirmed trade: the zen between the wallets
+    /// and every item through one ledger operation. A refusal of either puts
+    /// back what already moved, so nothing changes hands. Between accounts,
+    /// each side's offer counts towards its account's transfer caps.
+    fn swap_trade(&self, trade: &ConfirmedTrade, server_time_ms: u64) -> Result<(), TradeFailure> {
+        let sides = [
+            (
+                trade.first,
+                &trade.first_offer,
+                trade.second,
+                &trade.second_offer,
+            ),
+            (
+                trade.second,
+                &trade.second_offer,
+                trade.first,
+                &trade.first_offer,
+            ),
+        ];
+        for (character_id, giving, _, taking) in sides {
+            // Offered items leave the inventory before the received ones land.
+            let room =
+                usize::from(self.free_inventory_slots_for(character_id)) + giving.items.len();
+            if room < taking.items.len() {
+                return Err(TradeFailure::InventoryFull);
+            }
+            if giving.items.iter().any(|placed| {
+                self.worn_items
+                    .slot_of(character_id, placed.item.serial)
+                    .is_some()
+            }) {
+                return Err(TradeFailure::NotTradable);
+            }
+        }
+
+        // Only what leaves an account counts towards its daily caps.
+        let crossing: Vec<(u64, u64, u32)> = match (
+            self.account_of_character(trade.first),
+            self.account_of_character(trade.second),
+        ) {
+            (Some(first), Some(second)) if first != second => {
+                [(first, &trade.first_offer), (second, &trade.second_offer)]
+                    .into_iter()
+                    .map(|(account_id, offer)| {
+                        let items = u32::try_from(offer.items.len()).unwrap_or(u32::MAX);
+                        (account_id, offer.zen, items)
+                    })
+                    .filter(|(_, zen, items)| *zen > 0 || *items > 0)
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+        for (account_id, zen, items) in &crossing {
+            self.transfer_limits
+                .check(*account_id, *zen, *items, server_time_ms)
+                .map_err(|_| TradeFailure::TransferLimit)?;
+        }
+
+        self.wallets
+            .exchange(
+                trade.first,
+                trade.first_offer.zen,
+                trade.second,
+                trade.second_offer.zen,
+            )
+            .map_err(|err| match err {
+                WalletError::Full { .. } => TradeFailure::ZenLimit,
+                WalletError::Short { .. } => TradeFailure::NotEnoughZen,
+            })?;
+
+        let moves: Vec<ItemMove> = sides
+            .iter()
+            .flat_map(|(from, giving, to, _)| {
+                giving.items.iter().map(|placed| ItemMove {
+                    serial: placed.item.serial,
+                    from: ItemHolder::Character {
+                        character_id: *from,
+                    },
+                    to: ItemHolder::Character { character_id: *to },
+                })
+            })
+            .collect();
+        if let Err(err) = self
+            .items
+            .apply(&moves, TransferReason::Trade, server_time_ms)
+        {
+            log::warn!(
+                "Trade between characters {} and {} refused: {}",
+                trade.first,
+                trade.second,
+                err
+            );
+            if let Err(err) = self.wallets.exchange(
+                trade.first,
+                trade.second_offer.zen,
+                trade.second,
+                trade.first_offer.zen,
+            ) {
+                log::error!(
+                    "Failed to return the zen of the trade between characters {} and {}: {}",
+                    trade.first,
+                    trade.second,
+                    err
+                );
+            }
+            return Err(TradeFailure::NotHeld);
+        }
+        for (account_id, zen, items) in crossing {
+            if let Err(err) = self.transfer_limits.record(
+                account_id,
+                TransferChannel::Trade,
+                zen,
+                items,
+                server_time_ms,
+            ) {
+                log::warn!("Trade of account {} went past its cap: {}", account_id, err);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn sequence_events(&self) -> &SequenceEvents {
+        &self.events
+    }
+
+    /// Moves Crywolf and Kanturu along their timers and tells the players on
+    /// their maps. Returns how many events changed phase.
+    pub fn run_sequence_events(&self, server_time_ms: u64) -> usize {
+        let changed = self.events.tick(server_time_ms, |cycle| {
+            !self
+                .restart
+                .blocks_start(server_time_ms, cycle.as_millis() as u64)
+        });
+        for state in &changed {
+            self.announce_event_phase(state);
+        }
+        changed.len()
+    }
+
+    /// Opens or closes every event door to match its event's gate, e.g. after
+    /// the phases were loaded from MongoDB.
+    pub fn sync_event_doors(&self) {
+        for entry in self.map_servers.iter() {
+            self.follow_world_events(entry.key().world_id, entry.value());
+        }
+    }
+
+    fn follow_world_events(&self, world_id: u16, map: &MapServerHandle) {
+        for state in self.events.states() {
+            if state.world_id == world_id {
+                map.follow_event(state.event, state.gate_open);
+            }
+        }
+    }
+
+    /// Settles the objective of an event's current phase.
+    pub fn resolve_event_phase(
+        &self,
+        world_id: u16,
+        event: SequenceEvent,
+        won: bool,
+        server_time_ms: u64,
+    ) -> Result<EventState, EventError> {
+        let state = self.events.resolve(world_id, event, won, server_time_ms)?;
+        self.announce_event_phase(&state);
+        Ok(state)
+    }
+
+    fn announce_event_phase(&self, state: &EventState) {
+        log::info!(
+            "{:?} cycle {} on world {} entered {:?} (gate {})",
+            state.event,
+            state.cycle,
+            state.world_id,
+            state.phase,
+            if state.gate_open { "open" } else { "closed" }
+        );
+
+        for entry in self.map_servers.iter() {
+            if entry.key().world_id == state.world_id {
+                entry.value().follow_event(state.event, state.gate_open);
+            }
+        }
+
+        let notice = state.notice();
+        let routes: Vec<RouteKey> = self
+            .map_servers
+            .iter()
+            .map(|entry| *entry.key())
+            .filter(|route| {
+                route.world_id == state.world_id
+                    && notice_maps(state.event).any(|map_id| map_id == route.map_id)
+            })
+            .collect();
+        for route in routes {
+            self.message_hub.publish(
+                MessageScope::LocalMap(route),
+                HubMessage {
+                    from_session_id: 0,
+                    route,
+                    payload: HubPayload::Event(notice.clone()),
+                },
+            );
+        }
+    }
+
+    pub fn doppelganger(&self) -> &DoppelgangerRuns {
+        &self.doppelganger
+    }
+
+    /// Opens a private Doppelganger zone for an online party, starts the first
+    /// wave and sends every member there.
+    pub async fn start_doppelganger(
+        &self,
+        world_id: u16,
+        party: Vec<PartyMember>,
+        ticket: &ItemInstance,
+        server_time_ms: u64,
+    ) -> Result<DoppelgangerRun, DoppelgangerError> {
+        self.doppelganger.check_entry(&party, ticket)?;
+        if self
+            .restart
+            .blocks_start(server_time_ms, RUN_DURATION.as_millis() as u64)
+        {
+            return Err(DoppelgangerError::RestartPending);
+        }
+        let mut sessions = Vec::with_capacity(party.len());
+        for member in &party {
+            let session_id = self
+                .active_characters
+                .get(&member.character_id)
+                .map(|entry| *entry.value())
+                .ok_or(DoppelgangerError::Offline(member.character_id))?;
+            sessions.push((session_id, member.character_id));
+        }
+
+        let run_id = self.doppelganger.next_run_id();
+        let (entry, map) = self
+            .open_doppelganger_zone(world_id, run_id)
+            .await
+            .ok_or(DoppelgangerError::NoZone(world_id))?;
+        let run = match self
+            .doppelganger
+            .open(run_id, map.route, party, server_time_ms)
+        {
+            Ok(run) => run,
+            Err(err) => {
+                self.retire_map_instance(map.route).await;
+                return Err(err);
+            }
+        };
+        self.start_doppelganger_wave(&run).await;
+
+        for (session_id, character_id) in sessions {
+            match self.issue_transfer(
+                session_id,
+                character_id,
+                entry.clone(),
+                map.clone(),
+                server_time_ms,
+            ) {
+                Ok(directive) => {
+                    self.push(
+                        session_id,
+                        ServerMessage::MapTransfer(directive),
+                        server_time_ms,
+                    );
+                }
+                Err(err) => log::warn!(
+                    "Failed to send character {} to Doppelganger run {}: {}",
+                    character_id,
+                    run.run_id,
+                    err
+                ),
+            }
+        }
+
+        log::info!(
+            "Doppelganger run {} opened on map {} instance {} for {} members",
+            run.run_id,
+            run.route.map_id,
+            run.route.instance_id,
+            run.party.len()
+        );
+        self.announce_doppelganger(&run);
+        Ok(run)
+    }
+
+    /// Starts the next wave of every run whose wave timer ran out and ends the
+    /// finished and abandoned runs. Returns how many runs changed.
+    pub async fn run_doppelganger(&self, server_time_ms: u64) -> usize {
+        let changed = self.doppelganger.tick(server_time_ms, |run| {
+            run.party
+                .iter()
+                .any(|member| self.route_of_character(member.character_id) == Some(run.route))
+        });
+
+        for run in &changed {
+            self.announce_doppelganger(run);
+            if run.outcome.is_some() {
+                self.finish_doppelganger(run, server_time_ms).await;
+            } else {
+                self.start_doppelganger_wave(run).await;
+            }
+        }
+        changed.len()
+    }
+
+    /// Spawns an instance of the first Doppelganger zone the world has,
+    /// starting from the zone after the previous run's.
+    async fn open_doppelganger_zone(
+        &self,
+        world_id: u16,
+        run_id: u32,
+    ) -> Option<(EntryPointRoute, MapRoute)> {
+        let world = self
+            .config
+            .worlds
+            .iter()
+            .find(|world| world.id == world_id)?;
+        let _guard = self.scale_lock.lock().await;
+
+        for map_id in zone_rotation(run_id) {
+            let Some(entry) = world
+                .entry_points
+                .iter()
+                .find(|entry| entry.maps.iter().any(|map| map.id == map_id))
+            else {
+                continue;
+            };
+            let Some(route) = self
+                .spawn_additional_map_instance(world_id, entry.id, map_id)
+                .await
+            else {
+                continue;
+            };
+            let (map_name, soft_player_cap) =
+                self.directory.map_template(world_id, entry.id, map_id)?;
+
+            return Some((
+                EntryPointRoute {
+                    world_id,
+                    entry_id: entry.id,
+                    host: entry.host.clone(),
+                    port: entry.port,
+                    max_players: entry.max_players,
+                },
+                MapRoute {
+                    route,
+                    map_name,
+                    soft_player_cap,
+                },
+            ));
+        }
+
+        None
+    }
+
+    async fn start_doppelganger_wave(&self, run: &DoppelgangerRun) {
+        let map = self
+            .map_servers
+            .get(&run.route)
+            .map(|entry| entry.value().clone());
+        let Some(map) = map else {
+            return;
+        };
+
+        if let Err(err) = map
+            .start_stress(run.wave_monsters(), WAVE_MONSTER_LIFETIME)
+            .await
+        {
+            log::warn!(
+                "Doppelganger run {} could not spawn wave {}: {}",
+                run.run_id,
+                run.wave,
+                err
+            );
+        }
+    }
+
+    /// Rewards the members still inside, sends them back to town and stops
+    /// the instance.
+    async fn finish_doppelganger(&self, run: &DoppelgangerRun, server_time_ms: u64) {
+        let reward = run.reward();
+        let source = RewardSource::Doppelganger {
+            waves: run.waves_cleared(),
+        };
+
+        for member in &run.party {
+            if self.route_of_character(member.character_id) != Some(run.route) {
+                continue;
+            }
+            let Some(session_id) = self
+                .active_characters
+                .get(&member.character_id)
+                .map(|entry| *entry.value())
+            else {
+                continue;
+            };
+
+            if let Err(err) = self
+                .grant_event_reward(
+                    member.character_id,
+                    run.route,
+                    source,
+                    reward.clone(),
+                    server_time_ms,
+                )
+                .await
+            {
+                log::warn!(
+                    "Doppelganger reward for character {} rejected: {}",
+                    member.character_id,
+                    err
+                );
+            }
+
+            self.detach_session_from_map(session_id).await;
+            self.clear_pending_transfers(session_id);
+            match self
+                .transfer_to_town(
+                    session_id,
+                    member.character_id,
+                    run.route.world_id,
+                    RETURN_MAP as u16,
+                    server_time_ms,
+                )
+                .await
+            {
+                Some(directive) => {
+                    self.push(
+                        session_id,
+                        ServerMessage::MapTransfer(directive),
+                        server_time_ms,
+                    );
+                }
+                None => log::warn!(
+                    "No town for character {} leaving Doppelganger run {}",
+                    member.character_id,
+                    run.run_id
+                ),
+            }
+        }
+
+        self.retire_map_instance(run.route).await;
+        log::info!(
+            "Doppelganger run {} {} after {} waves",
+            run.run_id,
+            if run.outcome == Some(true) {
+                "cleared"
+            } else {
+                "lost"
+            },
+            run.waves_cleared()
+        );
+    }
+
+    /// Sends the run's progress to every online member.
+    fn announce_doppelganger(&self, run: &DoppelgangerRun) {
+        let status = run.status();
+        for member in &run.party {
+            let Some(session_id) = self
+                .active_characters
+                .get(&member.character_id)
+                .map(|entry| *entry.value())
+            else {
+                continue;
+            };
+            self.message_hub.publish(
+                MessageScope::Session(session_id),
+                HubMessage {
+                    from_session_id: 0,
+                    route: run.route,
+                    payload: HubPayload::Doppelganger(status.clone()),
+                },
+            );
+        }
+    }
+
+    pub fn maintenance(&self) -> &MaintenanceRegistry {
+        &self.maintenance
+    }
+
+    /// Closes a world or map: new entries are refused right away, present players
+    /// get a countdown and are moved to the fallback town once it ends.
+    pub async fn begin_maintenance(
+        &self,
+        scope: MaintenanceScope,
+        reason: String,
+        countdown_secs: u32,
+        fallback_map_id: u16,
+        server_time_ms: u64,
+    ) -> Result<MaintenanceWindow, MaintenanceError> {
+        let world = self
+            .config
+            .worlds
+            .iter()
+            .find(|world| world.id == scope.world_id)
+            .ok_or(MaintenanceError::UnknownWorld(scope.world_id))?;
+
+        let world_has_map = |world: &WorldConfig, map_id: u16| {
+            world
+                .entry_points
+                .iter()
+                .any(|entry| entry.maps.iter().any(|map| map.id == map_id))
+        };
+        if let Some(map_id) = scope.map_id {
+            if !world_has_map(world, map_id) {
+                return Err(MaintenanceError::UnknownMap {
+                    world_id: scope.world_id,
+                    map_id,
+                });
+            }
+        }
+
+        let fallback_open = self.config.worlds.iter().any(|world| {
+            !scope.covers(world.id, fallback_map_id)
+                && self
+                    .maintenance
+                    .window_for(world.id, fallback_map_id)
+                    .is_none()
+                && world_has_map(world, fallback_map_id)
+        });
+        if !fallback_open {
+            return Err(MaintenanceError::NoFallbackTown(fallback_map_id));
+        }
+
+        let window = self.maintenance.schedule(
+            scope,
+            reason,
+            countdown_secs,
+            fallback_map_id,
+            server_time_ms,
+        )?;
+        log::info!(
+            "Maintenance scheduled for {} (closes in {}s)",
+            scope,
+            countdown_secs
+        );
+
+        self.enforce_maintenance(server_time_ms).await;
+        Ok(window)
+    }
+
+    /// Starts a synthetic monster load on one instance of a map, the first
+    /// instance unless `instance_id` is given.
+    pub async fn start_stress(
+        &self,
+        world_id: u16,
+        map_id: u16,
+        instance_id: Option<u16>,
+        monsters: u32,
+        duration: std::time::Duration,
+    ) -> Result<StressReport, StressError> {
+        validate_stress(monsters, duration)?;
+
+        let map = self
+            .map_servers
+            .iter()
+            .filter(|entry| {
+                let route = entry.key();
+                route.world_id == world_id
+                    && route.map_id == map_id
+                    && instance_id.is_none_or(|instance_id| route.instance_id == instance_id)
+            })
+            .min_by_key(|entry| (entry.key().entry_id, entry.key().instance_id))
+            .map(|entry| entry.value().clone())
+            .ok_or(StressError::UnknownMap { world_id, map_id })?;
+
+        map.start_stress(monsters, duration).await
+    }
+
+    /// Puts a door in `state` on every running instance of the map, or on
+    /// `instance_id` only.
+    pub async fn set_door(
+        &self,
+        world_id: u16,
+        map_id: u16,
+        instance_id: Option<u16>,
+        door_id: u16,
+        state: DoorState,
+    ) -> Result<Vec<MapDoor>, DoorError> {
+        let maps: Vec<(RouteKey, MapServerHandle)> = self
+            .map_servers
+            .iter()
+            .filter(|entry| {
+                let route = entry.key();
+                route.world_id == world_id
+                    && route.map_id == map_id
+                    && instance_id.is_none_or(|instance_id| route.instance_id == instance_id)
+            })
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect();
+        if maps.is_empty() {
+            return Err(DoorError::UnknownMap { world_id, map_id });
+        }
+
+        let mut doors = Vec::with_capacity(maps.len());
+        for (route, map) in maps {
+            let door = map
+                .set_door(door_id, state)
+                .await?
+                .ok_or(DoorError::UnknownDoor { map_id, door_id })?;
+            doors.push(MapDoor { route, door });
+        }
+        doors.sort_by_key(|door| (door.route.entry_id, door.route.instance_id));
+        Ok(doors)
+    }
+
+    /// Doors of every running map.
+    pub async fn doors(&self) -> Vec<MapDoor> {
+        let maps: Vec<(RouteKey, MapServerHandle)> = self
+            .map_servers
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect();
+
+        let mut doors = Vec::new();
+        for (route, map) in maps {
+            doors.extend(
+                map.doors()
+                    .await
+                    .into_iter()
+                    .map(|door| MapDoor { route, door }),
+            );
+        }
+        doors.sort_by_key(|door| {
+            (
+                door.route.world_id,
+                door.route.entry_id,
+                door.route.map_id,
+                door.route.instance_id,
+                door.door.door_id,
+            )
+        });
+        doors
+    }
+
+    /// Latest stress run of every map that had one.
+    pub async fn stress_reports(&self) -> Vec<StressReport> {
+        let handles: Vec<_> = self
+            .map_servers
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect();
+
+        let mut reports = Vec::new();
+        for map in handles {
+            reports.extend(map.stress_report().await);
+        }
+        reports.sort_by_key(|report| {
+            (
+                report.route.world_id,
+                report.route.entry_id,
+                report.route.map_id,
+                report.route.instance_id,
+            )
+        });
+        reports
+    }
+
+    pub fn restart(&self) -> &RestartScheduler {
+        &self.restart
+    }
+
+    /// Plans a restart of the whole server and warns every connected session.
+    pub fn schedule_restart(
+        &self,
+        restart_at_ms: u64,
+        reason: String,
+        server_time_ms: u64,
+    ) -> Result<PlannedRestart, RestartError> {
+        let restart = self
+            .restart
+            .schedule(restart_at_ms, reason, server_time_ms)?;
+        log::info!(
+            "Restart planned for {} (in {}s)",
+            restart.restart_at_ms,
+            restart.seconds_remaining(server_time_ms)
+        );
+        self.enforce_restart(server_time_ms);
+        Ok(restart)
+    }
+
+    pub fn cancel_restart(&self) -> Option<PlannedRestart> {
+        let restart = self.restart.cancel()?;
+        log::info!("Restart planned for {} called off", restart.restart_at_ms);
+        Some(restart)
+    }
+
+    /// Warns every map as the restart countdown crosses its marks. Returns
+    /// whether the restart is due; the shutdown itself is left to whoever
+    /// waits on [`RestartScheduler::triggered`].
+    pub fn enforce_restart(&self, server_time_ms: u64) -> bool {
+        if let Some(restart) = self.restart.due_notice(server_time_ms) {
+            // Every live session hears it, in a map or still in the lobby or
+            // character select.
+            let sessions: Vec<u64> = self
+                .authenticated_sessions
+                .iter()
+                .map(|entry| *entry.key())
+                .collect();
+            for session_id in sessions {
+                let route = self
+                    .session_routes
+                    .get(&session_id)
+                    .map_or(RouteKey::LOBBY, |entry| entry.value().1);
+                // A restart closes every world; clients show it like a
+                // world maintenance.
+                let notice = MaintenanceNotice {
+                    world_id: route.world_id,
+                    map_id: None,
+                    closes_at_ms: restart.restart_at_ms,
+                    seconds_remaining: restart.seconds_remaining(server_time_ms),
+                    reason: restart.reason.clone(),
+                };
+                self.push(
+                    session_id,
+                    ServerMessage::Maintenance(notice),
+                    server_time_ms,
+                );
+            }
+        }
+
+        let Some(restart) = self.restart.take_due(server_time_ms) else {
+            return false;
+        };
+        log::warn!("Planned restart is due: {}", restart.reason);
+        true
+    }
+
+    pub fn end_maintenance(&self, scope: MaintenanceScope) -> Option<MaintenanceWindow> {
+        let window = self.maintenance.end(scope)?;
+        log::info!("Maintenance ended for {}", scope);
+        Some(window)
+    }
+
+    /// Announces countdowns and migrates players out of scopes whose countdown
+    /// ended. Returns how many sessions were moved.
+    pub async fn enforce_maintenance(&self, server_time_ms: u64) -> usize {
+        for window in self.maintenance.due_notices(server_time_ms) {
+            let notice = window.notice(server_time_ms);
+            let sessions: Vec<u64> = self
+                .session_routes
+                .iter()
+                .filter(|entry| window.scope.covers_route(entry.value().1))
+                .map(|entry| *entry.key())
+                .collect();
+            for session_id in sessions {
+                self.push(
+                    session_id,
+                    ServerMessage::Maintenance(notice.clone()),
+                    server_time_ms,
+                );
+            }
+        }
+
+        let mut migrated = 0;
+        for window in self.maintenance.take_closing(server_time_ms) {
+            let sessions: Vec<(u64, u64)> = self
+                .session_routes
+                .iter()
+                .filter(|entry| window.scope.covers_route(entry.value().1))
+                .map(|entry| (*entry.key(), entry.value().0))
+                .collect();
+
+            for (session_id, character_id) in sessions {
+                self.detach_session_from_map(session_id).await;
+                self.clear_pending_transfers(session_id);
+
+                match self
+                    .transfer_to_town(
+                        session_id,
+                        character_id,
+                        window.scope.world_id,
+                        window.fallback_map_id,
+                        server_time_ms,
+                    )
+                    .await
+                {
+                    Some(directive) => {
+                        self.push(
+                            session_id,
+                            ServerMessage::MapTransfer(directive),
+                            server_time_ms,
+                        );
+                        migrated += 1;
+                    }
+                    None => {
+                        log::warn!(
+                            "No fallback town for session {} leaving {}",
+                            session_id,
+                            window.scope
+                        );
+                        let _ = self
+                            .kick_session(
+                                session_id,
+                                DisconnectReason::Maintenance,
+                                &window.reason,
+                                server_time_ms,
+                            )
+                            .await;
+                    }
+                }
+            }
+
+            log::info!("{} closed for maintenance", window.scope);
+        }
+
+        migrated
+    }
+
+    pub fn session_links(&self) -> &SessionLinks {
+        self.session_push.links()
+    }
+
+    /// Connection RTT of every session, measured by the gateway.
+    pub fn latency(&self) -> &SessionLatency {
+        &self.latency
+    }
+
+    /// Protocol id of the account `session_id` belongs to.
+    pub fn session_account(&self, session_id: u64) -> Option<u64> {
+        self.authenticated_sessions
+            .get(&session_id)
+            .map(|session| session.account_id)
+    }
+
+    /// Ends a session for `reason` and closes its connection after telling
+    /// the client why. Returns whether a live connection was told.
+    pub async fn kick_session(
+        &self,
+        session_id: u64,
+        reason: DisconnectReason,
+        message: &str,
+        server_time_ms: u64,
+    ) -> Result<bool, SessionControlError> {
+        if !self.authenticated_sessions.contains_key(&session_id) {
+            return Err(SessionControlError::UnknownSession(session_id));
+        }
+        let packet = self.push_packet(
+            session_id,
+            ServerMessage::Disconnect {
+                reason,
+                message: message.to_string(),
+            },
+            server_time_ms,
+        );
+        self.end_session(session_id, server_time_ms).await;
+        log::info!("Session {} kicked ({:?}): {}", session_id, reason, message);
+        Ok(self
+            .session_links()
+            .send(session_id, SessionCommand::Close { packet, reason }))
+    }
+
+    /// Hands a session's character to another shard (entry point) of
+    /// `world_id`, or the least busy one when `entry_id` is unset, on
+    /// `map_id` or the map it is on now. The client gets the transfer
+    /// directive through its connection and reconnects there.
+    pub async fn transfer_session(
+        &self,
+        session_id: u64,
+        world_id: u16,
+        entry_id: Option<u16>,
+        map_id: Option<u16>,
+        server_time_ms: u64,
+    ) -> Result<MapTransferDirective, SessionControlError> {
+        let directive = self
+            .hand_over(session_id, world_id, entry_id, map_id, None, server_time_ms)
+            .await?;
+        self.push(
+            session_id,
+            ServerMessage::MapTransfer(directive.clone()),
+            server_time_ms,
+        );
+        log::info!(
+            "Session {} handed to {}:{} ({:?})",
+            session_id,
+            directive.host,
+            directive.port,
+            directive.route
+        );
+        Ok(directive)
+    }
+
+    /// Detaches a session from its map and issues the transfer to the next
+    /// one, landing on `landing` when set. Sending the directive is left to
+    /// the caller.
+    async fn hand_over(
+        &self,
+        session_id: u64,
+        world_id: u16,
+        entry_id: Option<u16>,
+        map_id: Option<u16>,
+        landing: Option<(u16, u16)>,
+        server_time_ms: u64,
+    ) -> Result<MapTransferDirective, SessionControlError> {
+        let (character_id, current) = self
+            .session_routes
+            .get(&session_id)
+            .map(|entry| *entry.value())
+            .ok_or(SessionControlError::NoCharacter(session_id))?;
+        let map_id = map_id.unwrap_or(current.map_id);
+        let entry = match entry_id {
+            Some(entry_id) => self.directory.entry(world_id, entry_id),
+            None => self.directory.select_best_entry(world_id),
+        }
+        .ok_or(SessionControlError::NoShard { world_id, map_id })?;
+        let map = self
+            .resolve_or_scale_map_route(world_id, entry.entry_id, map_id)
+            .await
+            .ok_or(SessionControlError::NoShard { world_id, map_id })?;
+
+        self.detach_session_from_map(session_id).await;
+        self.clear_pending_transfers(session_id);
+        let directive =
+            self.issue_transfer(session_id, character_id, entry, map, server_time_ms)?;
+        if let Some(mut transfer) = self.pending_transfers.get_mut(&directive.transfer_id) {
+            transfer.landing = landing;
+        }
+        Ok(directive)
+    }
+
+    /// Sends the character through the gate covering `(x, y)`, if any, once
+    /// it meets the gate's requirements.
+    async fn enter_gate(
+        &self,
+        packet: &WirePacket,
+        auth_session: &AuthenticatedSession,
+        character_id: u64,
+        x: u16,
+        y: u16,
+        server_time_ms: u64,
+    ) -> Option<WirePacket> {
+        let route = self.session_routes.get(&packet.session_id)?.value().1;
+        let gate = gate_at(route.map_id, x, y)?;
+
+        let holder = ItemHolder::Character { character_id };
+        let level = auth_session
+            .characters
+            .get(&character_id)
+            .map_or(0, |character| character.level);
+        let worn: Vec<_> = self
+            .worn_items
+            .worn(character_id)
+            .into_iter()
+            .filter(|(serial, _)| self.items.owner(*serial) == Some(holder))
+            .map(|(_, code)| code)
+            .collect();
+        if let Err(err) = check_gate(gate, level, &worn, |code| self.items.holds(holder, code)) {
+            return Some(self.error_for_request(
+                packet,
+                server_time_ms,
+                ServerErrorKind::GateRequirement,
+                &err.to_string(),
+            ));
+        }
+
+        let transfer = self
+            .hand_over(
+                packet.session_id,
+                route.world_id,
+                Some(route.entry_id),
+                Some(gate.target_map as u16),
+                Some(gate.target_coords),
+                server_time_ms,
+            )
+            .await;
+        Some(match transfer {
+            Ok(directive) => {
+                log::info!(
+                    "Character {} took gate {} to {:?}",
+                    character_id,
+                    gate.id,
+                    directive.route
+                );
+                self.response_for_request(
+                    packet,
+                    server_time_ms,
+                    ServerMessage::MapTransfer(directive),
+                )
+            }
+            Err(err) => self.error_for_request(
+                packet,
+                server_time_ms,
+                ServerErrorKind::RouteUnavailable,
+                &err.to_string(),
+            ),
+        })
+    }
+
+    async fn end_session(&self, session_id: u64, server_time_ms: u64) {
+        if let Some(character_id) = self.character_for_session(session_id) {
+            self.cooldowns.forget(character_id, server_time_ms);
+            self.worn_items.forget(character_id);
+            if let Some(partner) = self.trades.cancel(character_id) {
+                self.session_push.push_to_character(
+                    partner,
+                    ServerMessage::TradeClosed {
+                        outcome: TradeOutcome::Cancelled,
+                    },
+                    server_time_ms,
+                );
+            }
+        }
+        self.detach_session_from_map(session_id).await;
+        self.clear_pending_transfers(session_id);
+        self.latency.forget(session_id);
+        self.authenticated_sessions.remove(&session_id);
+    }
+
+    /// Packet pushed to a session outside any request.
+    fn push_packet(
+        &self,
+        session_id: u64,
+        message: ServerMessage,
+        server_time_ms: u64,
+    ) -> WirePacket {
+        self.session_push
+            .packet(session_id, message, server_time_ms)
+    }
+
+    /// Sends `message` to the session over its live connection. Returns
+    /// false when the session has none.
+    fn push(&self, session_id: u64, message: ServerMessage, server_time_ms: u64) -> bool {
+        self.session_push.push(session_id, message, server_time_ms)
+    }
+
+    /// Sends a session to `map_id`, in `world_id` when it is open there and in
+    /// any other world otherwise.
+    async fn transfer_to_town(
+        &self,
+        session_id: u64,
+        character_id: u64,
+        preferred_world_id: u16,
+        map_id: u16,
+        server_time_ms: u64,
+    ) -> Option<MapTransferDirective> {
+        let worlds = std::iter::once(preferred_world_id).chain(
+            self.config
+                .worlds
+                .iter()
+                .map(|world| world.id)
+                .filter(|world_id| *world_id != preferred_world_id),
+        );
+
+        for world_id in worlds {
+            if self.maintenance.window_for(world_id, map_id).is_some() {
+                continue;
+            }
+            let Some(entry) = self.directory.select_best_entry(world_id) else {
+                continue;
+            };
+            let Some(map) = self
+                .resolve_or_scale_map_route(world_id, entry.entry_id, map_id)
+                .await
+            else {
+                continue;
+            };
+
+            return self
+                .issue_transfer(session_id, character_id, entry, map, server_time_ms)
+                .ok();
+        }
+
+        None
+    }
+
+    fn helper_allowed(&self, world_id: u16) -> bool {
+        self.config
+            .worlds
+            .iter()
+            .any(|world| world.id == world_id && world.mu_helper)
+    }
+
+    fn gens_enabled(&self, world_id: u16) -> bool {
+        self.config
+            .worlds
+            .iter()
+            .any(|world| world.id == world_id && world.gens)
+    }
+
+    fn gens_state_flags(&self, world_id: u16, character_id: u64) -> u16 {
+        if !self.gens_enabled(world_id) {
+            return 0;
+        }
+        self.gens
+            .faction_of(character_id)
+            .map_or(0, GensFaction::state_flag)
+    }
+
+    /// Ground height of a tile of the route's map, as sent in entity deltas.
+    fn ground_height(&self, route: &RouteKey, x: u16, y: u16) -> i16 {
+        let grid = self
+            .collision
+            .grid_for(route.world_id, route.entry_id, route.map_id);
+        grid.ground_height(x, y).round() as i16
+    }
+
+    fn map_gens_zone(&self, world_id: u16, entry_id: u16, map_id: u16) -> bool {
+        self.gens_enabled(world_id)
+            && self
+                .config
+                .worlds
+                .iter()
+                .filter(|world| world.id == world_id)
+                .flat_map(|world| &world.entry_points)
+                .filter(|entry| entry.id == entry_id)
+                .flat_map(|entry| &entry.maps)
+                .any(|map| map.id == map_id && map.gens)
+    }
+
+    fn map_doors(&self, world_id: u16, entry_id: u16, map_id: u16) -> Vec<DoorConfig> {
+        self.config
+            .worlds
+            .iter()
+            .filter(|world| world.id == world_id)
+            .flat_map(|world| &world.entry_points)
+            .filter(|entry| entry.id == entry_id)
+            .flat_map(|entry| &entry.maps)
+            .filter(|map| map.id == map_id)
+            .flat_map(|map| map.doors.iter().cloned())
+            .collect()
+    }
+
+    fn map_pvp_rules(&self, world_id: u16, entry_id: u16, map_id: u16) -> PvpRules {
+        self.config
+            .worlds
+            .iter()
+            .filter(|world| world.id == world_id)
+            .flat_map(|world| &world.entry_points)
+            .filter(|entry| entry.id == entry_id)
+            .flat_map(|entry| &entry.maps)
+            .find(|map| map.id == map_id)
+            .map_or_else(PvpRules::default, MapConfig::pvp_rules)
+    }
+
+    fn map_cleanup(&self, world_id: u16, entry_id: u16, map_id: u16) -> CleanupConfig {
+        self.config
+            .worlds
+            .iter()
+            .filter(|world| world.id == world_id)
+            .flat_map(|world| &world.entry_points)
+            .filter(|entry| entry.id == entry_id)
+            .flat_map(|entry| &entry.maps)
+            .find(|map| map.id == map_id)
+            .map_or_else(
+                || self.config.cleanup.clone(),
+                |map| self.config.cleanup.for_map(map),
+            )
+    }
+
+    fn handle_hello(
+        &self,
+        packet: &WirePacket,
+        hello: &ClientHello,
+        server_time_ms: u64,
+    ) -> WirePacket {
+        let negotiated = match negotiate(
+            packet.version,
+            hello.capabilities,
+            ProtocolCapabilities::SUPPORTED,
+        ) {
+            Ok(negotiated) => negotiated,
+            Err(err) => {
+                log::warn!(
+                    "Refused QUIC hello from client build '{}': {}",
+                    hello.client_build,
+                    err
+                );
+                return self.error_for_request(
+                    packet,
+                    server_time_ms,
+                    ServerErrorKind::UnsupportedVersion,
+                    &err.to_string(),
+                );
+            }
+        };
+
+        let claims = match self.auth_tokens.verify(&hello.auth_token, server_time_ms) {
+            Ok(claims) => claims,
+            Err(err) => {
+                log::warn!("Failed QUIC hello authentication: {}", err);
+                return self.error_for_request(
+                    packet,
+                    server_time_ms,
+                    ServerErrorKind::InvalidSession,
+                    "Invalid auth token",
+                );
+            }
+        };
+
+        if claims.account_id != hello.account_id {
+            log::warn!(
+                "Failed QUIC hello authentication: account mismatch (packet={} claims={})",
+                hello.account_id,
+                claims.account_id
+            );
+            return self.error_for_request(
+                packet,
+                server_time_ms,
+                ServerErrorKind::AccountMismatch,
+                "Account mismatch in auth token",
+            );
+        }
+
+        if let Some(session_manager) = &self.session_manager {
+            let http_session = match session_manager.validate_session(&claims.session_id) {
+                Ok(session) => session,
+                Err(err) => {
+                    log::warn!(
+                        "Failed QUIC hello authentication: HTTP session '{}' is invalid ({})",
+                        claims.session_id,
+                        err
+                    );
+                    return self.error_for_request(
+                        packet,
+                        server_time_ms,
+                        ServerErrorKind::InvalidSession,
+                        "HTTP session is invalid or expired",
+                    );
+                }
+            };
+
+            let session_account_id = object_id_to_u64(&http_session.account_id);
+            if session_account_id != claims.account_id {
+                log::warn!(
+                    "Failed QUIC hello authentication: token/session account mismatch (token={} session={})",
+                    claims.account_id,
+                    session_account_id
+                );
+                return self.error_for_request(
+                    packet,
+                    server_time_ms,
+                    ServerErrorKind::AccountMismatch,
+                    "Account mismatch in session",
+                );
+            }
+        }
+
+        let auth_session = AuthenticatedSession::from_claims(claims, negotiated);
+        let characters = auth_session.character_list();
+        self.authenticated_sessions
+            .insert(packet.session_id, auth_session.clone());
+
+        log::info!(
+            "QUIC session authenticated: session_id={} account_id={} characters={}",
+            packet.session_id,
+            auth_session.account_id,
+            characters.len()
+        );
+
+        self.response_for_request(
+            packet,
+            server_time_ms,
+            ServerMessage::HelloAck {
+                session_id: packet.session_id,
+                heartbeat_interval_ms: 5_000,
+                motd: "Welcome to MU Online".to_string(),
+                characters,
+                protocol_version: negotiated.version,
+                capabilities: negotiated.capabilities,
+            },
+        )
+    }
+
+    async fn authenticated_session(
+        &self,
+        session_id: u64,
+        server_time_ms: u64,
+    ) -> Option<AuthenticatedSession> {
+        let session = self
+            .authenticated_sessions
+            .get(&session_id)
+            .map(|entry| entry.value().clone())?;
+
+        if session.is_expired(server_time_ms) {
+            self.detach_session_from_map(session_id).await;
+            self.clear_pending_transfers(session_id);
+            self.authenticated_sessions.remove(&session_id);
+            return None;
+        }
+
+        Some(session)
+    }
+
+    fn response_for_request(
+        &self,
+        request: &WirePacket,
+        server_time_ms: u64,
+        message: ServerMessage,
+    ) -> WirePacket {
+        WirePacket::server(
+            request.session_id,
+            request.route,
+            request.sequence.wrapping_add(1),
+            Some(request.sequence),
+            server_time_ms,
+            message,
+        )
+    }
+
+    /// Moves an item of `character_id` from where the client saw it to
+    /// `to`, putting it on or taking it off. Inventory cells are not tracked
+    /// yet, so only the grid edges are checked for them.
+    fn move_item(
+        &self,
+        character_id: u64,
+        serial: u64,
+        from: ItemLocation,
+        to: ItemLocation,
+    ) -> Result<(), ItemFailure> {
+        if self.items.owner(serial) != Some(ItemHolder::Character { character_id }) {
+            return Err(ItemFailure::NotHeld);
+        }
+        let worn_in = self.worn_items.slot_of(character_id, serial);
+        let seen_in = match from {
+            ItemLocation::Equipment(slot) => Some(slot),
+            ItemLocation::Inventory { .. } => None,
+        };
+        if worn_in != seen_in {
+            return Err(ItemFailure::NotHeld);
+        }
+
+        let code = self.items.code(serial).ok_or(ItemFailure::NotHeld)?;
+        let definition = code.definition();
+        match to {
+            ItemLocation::Equipment(slot) => {
+                if definition.and_then(|item| item.equip_slot()) != Some(slot) {
+                    return Err(ItemFailure::CannotEquip);
+                }
+                if self
+                    .worn_items
+                    .in_slot(character_id, slot)
+                    .is_some_and(|worn| worn != serial)
+                {
+                    return Err(ItemFailure::Occupied);
+                }
+                self.worn_items.wear(character_id, serial, code);
+            }
+            ItemLocation::Inventory { .. } => {
+                let (width, height) = definition.map_or((1, 1), |item| (item.width, item.height));
+                if !to.fits(width, height) {
+                    return Err(ItemFailure::InvalidLocation);
+                }
+                if let Some(slot) = worn_in {
+                    self.worn_items.take_off(character_id, slot);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Puts an inventory item of the character on the ground of its map.
+    async fn drop_item(
+        &self,
+        character_id: u64,
+        serial: u64,
+        x: u16,
+        y: u16,
+    ) -> Result<u32, ItemFailure> {
+        if self.items.owner(serial) != Some(ItemHolder::Character { character_id })
+            || self.worn_items.slot_of(character_id, serial).is_some()
+        {
+            return Err(ItemFailure::NotHeld);
+        }
+        let item = self.items.item(serial).ok_or(ItemFailure::NotHeld)?;
+        let map = self
+            .route_of_character(character_id)
+            .and_then(|route| {
+                self.map_servers
+                    .get(&route)
+                    .map(|entry| entry.value().clone())
+            })
+            .ok_or(ItemFailure::NotAllowed)?;
+        map.drop_item(character_id, item, x, y).await
+    }
+
+    async fn pick_up_item(
+        &self,
+        character_id: u64,
+        entity_id: u32,
+    ) -> Result<ItemInstance, ItemFailure> {
+        if self.free_inventory_slots_for(character_id) == 0 {
+            return Err(ItemFailure::InventoryFull);
+        }
+        let map = self
+            .route_of_character(character_id)
+            .and_then(|route| {
+                self.map_servers
+                    .get(&route)
+                    .map(|entry| entry.value().clone())
+            })
+            .ok_or(ItemFailure::NotAllowed)?;
+        map.pick_up(character_id, entity_id).await
+    }
+
+    fn error_for_request(
+        &self,
+        request: &WirePacket,
+        server_time_ms: u64,
+        kind: ServerErrorKind,
+        message: &str,
+    ) -> WirePacket {
+        self.response_for_request(
+            request,
+            server_time_ms,
+            ServerMessage::Error(ServerError::new(kind, message)),
+        )
+    }
+
+    fn error_for_unbound_session(
+        &self,
+        session_id: u64,
+        sequence: u32,
+        server_time_ms: u64,
+        kind: ServerErrorKind,
+        message: &str,
+    ) -> WirePacket {
+        WirePacket::server(
+            session_id,
+            RouteKey::LOBBY,
+            sequence,
+            None,
+            server_time_ms,
+            ServerMessage::Error(ServerError::new(kind, message)),
+        )
+    }
+
+    async fn handle_chat_command(
+        &self,
+        request: &WirePacket,
+        auth_session: &AuthenticatedSession,
+        command: Result<ChatCommand, ChatCommandError>,
+        server_time_ms: u64,
+    ) -> WirePacket {
+        let command = match command {
+            Ok(command) => command,
+            Err(err) => {
+                return self.error_for_request(
+                    request,
+                    server_time_ms,
+                    ServerErrorKind::InvalidRequest,
+                    &err.to_string(),
+                )
+            }
+        };
+        if !auth_session.role.allows(command.permission()) {
+            log::warn!(
+                "Account {} ({}) denied chat command {:?}",
+                auth_session.account_id,
+                auth_session.role.name(),
+                command
+            );
+            return self.error_for_request(
+                request,
+                server_time_ms,
+                ServerErrorKind::PermissionDenied,
+                "Your role does not allow this command",
+            );
+        }
+
+        let reply = match &command {
+            ChatCommand::Mute { name, minutes } => {
+                let until_ms = server_time_ms.saturating_add(u64::from(*minutes) * 60_000);
+                self.chat_mutes.mute(name, until_ms);
+                format!("{name} muted for {minutes} minutes")
+            }
+            ChatCommand::Unmute { name } => {
+                if !self.chat_mutes.unmute(name) {
+                    return self.error_for_request(
+                        request,
+                        server_time_ms,
+                        ServerErrorKind::NotFound,
+                        &format!("{name} is not muted"),
+                    );
+                }
+                format!("{name} unmuted")
+            }
+            ChatCommand::Kick { name } => {
+                let kicked = match self.session_playing(name) {
+                    Some(session_id) => self
+                        .kick_session(
+                            session_id,
+                            DisconnectReason::Kicked,
+                            "Kicked by a game master",
+                            server_time_ms,
+                        )
+                        .await
+                        .is_ok(),
+                    None => false,
+                };
+                if !kicked {
+                    return self.error_for_request(
+                        request,
+                        server_time_ms,
+                        ServerErrorKind::NotFound,
+                        &format!("{name} is not online"),
+                    );
+                }
+                format!("{name} kicked")
+            }
+        };
+        log::info!(
+            "Account {} ({}) ran {:?}",
+            auth_session.account_id,
+            auth_session.role.name(),
+            command
+        );
+
+        self.response_for_request(
+            request,
+            server_time_ms,
+            ServerMessage::Chat(ChatPayload {
+                channel: ChatChannel::Whisper,
+                target: None,
+                text: reply,
+            }),
+        )
+    }
+
+    async fn handle_select_character(
+        &self,
+        session_id: u64,
+        character_id: u64,
+        server_time_ms: u64,
+    ) -> WirePacket {
+        // For MVP we route character selection to map_id 0 in the least loaded entry
+        // of the first world that is not under maintenance.
+        let target_world = self
+            .config
+            .worlds
+            .iter()
+            .map(|world| world.id)
+            .find(|world_id| self.maintenance.window_for(*world_id, 0).is_none());
+        let Some(target_world) = target_world else {
+            return WirePacket::server(
+                session_id,
+                RouteKey::LOBBY,
+                0,
+                None,
+                server_time_ms,
+                ServerMessage::Error(ServerError::new(
+                    ServerErrorKind::Maintenance,
+                    "All worlds are under maintenance",
+                )),
+            );
+        };
+
+        // New characters start on their kit's map, or else in their class's
+        // town, when the entry runs it.
+        let spawn_map = self
+            .class_of(session_id, character_id)
+            .filter(|_| self.quests.is_new(character_id))
+            .and_then(|class_id| self.starting_map(target_world, class_id))
+            .unwrap_or(0);
+        let entry = self.directory.select_best_entry(target_world);
+        let map_route = match entry.as_ref() {
+            Some(entry) => match self
+                .resolve_or_scale_map_route(entry.world_id, entry.entry_id, spawn_map)
+                .await
+            {
+                None if spawn_map != 0 => {
+                    self.resolve_or_scale_map_route(entry.world_id, entry.entry_id, 0)
+                        .await
+                }
+                route => route,
+            },
+            None => None,
+        };
+
+        match (entry, map_route) {
+            (Some(entry), Some(map)) => {
+                match self.issue_transfer(session_id, character_id, entry, map, server_time_ms) {
+                    Ok(directive) => WirePacket::server(
+                        session_id,
+                        RouteKey::LOBBY,
+                        directive.transfer_id as u32,
+                        None,
+                        server_time_ms,
+                        ServerMessage::MapTransfer(directive),
+                    ),
+                    Err(err) => WirePacket::server(
+                        session_id,
+                        RouteKey::LOBBY,
+                        0,
+                        None,
+                        server_time_ms,
+                        ServerMessage::Error(ServerError::new(
+                            ServerErrorKind::Internal,
+                            format!("Failed to issue transfer token: {}", err),
+                        )),
+                    ),
+                }
+            }
+            _ => WirePacket::server(
+                session_id,
+                RouteKey::LOBBY,
+                0,
+                None,
+                server_time_ms,
+                ServerMessage::Error(ServerError::new(
+                    ServerErrorKind::RouteUnavailable,
+                    "No route available",
+                )),
+            ),
+        }
+    }
+
+    fn issue_transfer(
+        &self,
+        session_id: u64,
+        character_id: u64,
+        entry: EntryPointRoute,
+        map: MapRoute,
+        server_time_ms: u64,
+    ) -> Result<MapTransferDirective, AuthTokenError> {
+        let transfer_id = self.transfer_seq.fetch_add(1, Ordering::Relaxed);
+        let expires_at_ms = server_time_ms.saturating_add(30_000);
+        let route_token = self
+            .auth_tokens
+            .issue_transfer_token(&MapTransferTokenClaims {
+                session_id,
+                transfer_id,
+                character_id,
+                route: map.route,
+                issued_at_ms: server_time_ms,
+                expires_at_ms,
+            })?;
+
+        self.pending_transfers.insert(
+            transfer_id,
+            PendingTransfer {
+                session_id,
+                transfer_id,
+                character_id,
+                route: map.route,
+                landing: None,
+            },
+        );
+
+        Ok(MapTransferDirective {
+            transfer_id,
+            route: map.route,
+            host: entry.host,
+            port: entry.port,
+            route_token,
+            expires_at_ms,
+        })
+    }
+
+    async fn resolve_or_scale_map_route(
+        &self,
+        world_id: u16,
+        entry_id: u16,
+        map_id: u16,
+    ) -> Option<MapRoute> {
+        if let Some(route) = self
+            .directory
+            .select_best_map_instance(world_id, entry_id, map_id)
+        {
+            return Some(route);
+        }
+
+        // Serialize scale-out decisions to avoid creating duplicate instances.
+        let _guard = self.scale_lock.lock().await;
+
+        if let Some(route) = self
+            .directory
+            .select_best_map_instance(world_id, entry_id, map_id)
+        {
+            return Some(route);
+        }
+
+        self.spawn_additional_map_instance(world_id, entry_id, map_id)
+            .await?;
+
+        self.directory
+            .select_best_map_instance(world_id, entry_id, map_id)
+    }
+
+    /// Starts one more instance of a map; callers hold `scale_lock`.
+    async fn spawn_additional_map_instance(
+        &self,
+        world_id: u16,
+        entry_id: u16,
+        map_id: u16,
+    ) -> Option<RouteKey> {
+        let (map_name, soft_player_cap) =
+            self.directory.map_template(world_id, entry_id, map_id)?;
+        let instance_id = self
+            .directory
+            .next_instance_id(world_id, entry_id, map_id)?;
+
+        let route = RouteKey {
+            world_id,
+            entry_id,
+            map_id,
+            instance_id,
+        };
+
+        if !self.directory.register_instance_route(route) {
+            return Some(route);
+        }
+
+        let handle = start_map_server(
+            MapServerConfig {
+                route,
+                map_name,
+                soft_player_cap,
+                pvp: self.map_pvp_rules(world_id, entry_id, map_id),
+                gens_zone: self.map_gens_zone(world_id, entry_id, map_id),
+                player_tick: self.config.player_tick(),
+                monster_tick: self.config.monster_tick(),
+                collision: self.collision.grid_for(world_id, entry_id, map_id),
+                doors: self.map_doors(world_id, entry_id, map_id),
+                monsters: self.config.monsters.clone(),
+                combat: self.config.combat.clone(),
+                cleanup: self.map_cleanup(world_id, entry_id, map_id),
+            },
+            self.directory.clone(),
+            self.persistence.clone(),
+            self.message_hub.clone(),
+            self.guilds.clone(),
+            self.guild_wars.clone(),
+            self.gens.clone(),
+            self.session_push.clone(),
+            self.items.clone(),
+            self.monster_kills.clone(),
+        );
+
+        self.follow_world_events(world_id, &handle);
+        self.map_servers.insert(route, handle);
+
+        log::info!(
+            "Spawned dynamic map instance world={} entry={} map={} instance={}",
+            world_id,
+            entry_id,
+            map_id,
+            instance_id
+        );
+
+        Some(route)
+    }
+
+    /// Stops a map instance spawned for a single use.
+    async fn retire_map_instance(&self, route: RouteKey) {
+        if let Some((_, map)) = self.map_servers.remove(&route) {
+            let _ = map.shutdown().await;
+        }
+        self.directory.remove_instance_route(route);
+        log::info!(
+            "Retired map instance world={} entry={} map={} instance={}",
+            route.world_id,
+            route.entry_id,
+            route.map_id,
+            route.instance_id
+        );
+    }
+
+    async fn handle_transfer_ack(
+        &self,
+        session_id: u64,
+        transfer_id: u64,
+        route_token: &str,
+        server_time_ms: u64,
+    ) -> WirePacket {
+        let transfer_token_claims = match self
+            .auth_tokens
+            .verify_transfer_token(route_token, server_time_ms)
+        {
+            Ok(claims) => claims,
+            Err(err) => {
+                return self.error_for_unbound_session(
+                    session_id,
+                    transfer_id as u32,
+                    server_time_ms,
+                    ServerErrorKind::InvalidSession,
+                    &format!("Invalid transfer token: {}", err),
+                );
+            }
+        };
+
+        if transfer_token_claims.session_id != session_id
+            || transfer_token_claims.transfer_id != transfer_id
+        {
+            return self.error_for_unbound_session(
+                session_id,
+                transfer_id as u32,
+                server_time_ms,
+                ServerErrorKind::AccountMismatch,
+                "Transfer token/session mismatch",
+            );
+        }
+
+        let transfer = self
+            .pending_transfers
+            .remove(&transfer_id)
+            .map(|(_, val)| val);
+        match transfer {
+            Some(transfer) => {
+                if transfer.session_id != session_id {
+                    return self.error_for_unbound_session(
+                        session_id,
+                        transfer_id as u32,
+                        server_time_ms,
+                        ServerErrorKind::AccountMismatch,
+                        "Transfer does not belong to this session",
+                    );
+                }
+
+                if transfer_token_claims.character_id != transfer.character_id
+                    || transfer_token_claims.route != transfer.route
+                {
+                    return self.error_for_unbound_session(
+                        session_id,
+                        transfer_id as u32,
+                        server_time_ms,
+                        ServerErrorKind::TamperedRequest,
+                        "Transfer token payload does not match transfer route",
+                    );
+                }
+
+                if let Some(existing_owner) = self.active_characters.get(&transfer.character_id) {
+                    if *existing_owner.value() != session_id {
+                        return self.error_for_unbound_session(
+                            session_id,
+                            transfer_id as u32,
+                            server_time_ms,
+                            ServerErrorKind::CharacterInUse,
+                            "Character is already active in another session",
+                        );
+                    }
+                }
+
+                if self.maintenance.blocks_route(transfer.route) {
+                    return self.error_for_unbound_session(
+                        session_id,
+                        transfer_id as u32,
+                        server_time_ms,
+                        ServerErrorKind::Maintenance,
+                        "Map is under maintenance",
+                    );
+                }
+
+                if self
+                    .events
+                    .blocks_map(transfer.route.world_id, transfer.route.map_id)
+                {
+                    return self.error_for_unbound_session(
+                        session_id,
+                        transfer_id as u32,
+                        server_time_ms,
+                        ServerErrorKind::MapClosed,
+                        "Map is closed until the event opens its gate",
+                    );
+                }
+
+                let map = self
+                    .map_servers
+                    .get(&transfer.route)
+                    .map(|entry| entry.value().clone());
+
+                if let Some(map) = map {
+                    self.detach_session_from_map(session_id).await;
+                    let (x, y) = transfer.landing.unwrap_or(DEFAULT_LANDING);
+                    let _ = map.join(session_id, transfer.character_id, x, y).await;
+                    self.session_routes
+                        .insert(session_id, (transfer.character_id, transfer.route));
+                    self.active_characters
+                        .insert(transfer.character_id, session_id);
+                    let guild_id =
+                        self.authenticated_sessions
+                            .get(&session_id)
+                            .and_then(|session| {
+                                session.characters.get(&transfer.character_id)?.guild_id
+                            });
+                    self.guilds.set_membership(transfer.character_id, guild_id);
+                    self.welcome_character(
+                        session_id,
+                        transfer.character_id,
+                        transfer.route,
+                        server_time_ms,
+                    )
+                    .await;
+                    let doors = map.doors().await;
+                    if !doors.is_empty() {
+                        self.message_hub.publish(
+                            MessageScope::Session(session_id),
+                            HubMessage {
+                                from_session_id: 0,
+                                route: transfer.route,
+                                payload: HubPayload::Doors(doors),
+                            },
+                        );
+                    }
+
+                    WirePacket::server(
+                        session_id,
+                        transfer.route,
+                        transfer.transfer_id as u32,
+                        None,
+                        server_time_ms,
+                        ServerMessage::EnterMap {
+                            entity_id: transfer.character_id as u32,
+                            map_id: transfer.route.map_id,
+                            x,
+                            y,
+                        },
+                    )
+                } else {
+                    WirePacket::server(
+                        session_id,
+                        RouteKey::LOBBY,
+                        transfer_id as u32,
+                        None,
+                        server_time_ms,
+                        ServerMessage::Error(ServerError::new(
+                            ServerErrorKind::InstanceUnavailable,
+                            "Map instance unavailable",
+                        )),
+                    )
+                }
+            }
+            None => self.error_for_unbound_session(
+                session_id,
+                transfer_id as u32,
+                server_time_ms,
+                ServerErrorKind::InvalidRequest,
+                "Invalid transfer ack",
+            ),
+        }
+    }
+
+    /// Hands a character entering for the first time its starting kit and
+    /// starts its tutorial; returning characters get their running tutorial.
+    async fn welcome_character(
+        &self,
+        session_id: u64,
+        character_id: u64,
+        route: RouteKey,
         server_time_ms: u64,
-    ) -> Option<AuthenticatedSession> {
-        let session = self
-            .authenticated_sessions
+    ) {
+        let class_id = self.class_of(session_id, character_id).unwrap_or(u8::MAX);
+        let tutorial = match self.quests.welcome(character_id, class_id, server_time_ms) {
+            Some(welcome) => {
+                if let Some(kit) = welcome.kit {
+                    if let Err(err) = self
+                        .grant_event_reward(
+                            character_id,
+                            route,
+                            RewardSource::StartingKit,
+                            kit,
+                            server_time_ms,
+                        )
+                        .await
+                    {
+                        log::warn!(
+                            "Failed to grant the starting kit of character {}: {}",
+                            character_id,
+                            err
+                        );
+                    }
+                }
+                welcome.tutorial
+            }
+            None => self
+                .quests
+                .status(character_id)
+                .filter(|status| status.objective.is_some()),
+        };
+        if let Some(status) = tutorial {
+            self.push(
+                session_id,
+                ServerMessage::QuestStatus(status),
+                server_time_ms,
+            );
+        }
+    }
+
+    fn starting_map(&self, world_id: u16, class_id: u8) -> Option<u16> {
+        self.quests.spawn_map(class_id).or_else(|| {
+            let zone = self.config.starting_zones.for_class(class_id);
+            self.config
+                .worlds
+                .iter()
+                .find(|world| world.id == world_id)?
+                .map_id_of(zone)
+        })
+    }
+
+    fn class_of(&self, session_id: u64, character_id: u64) -> Option<u8> {
+        self.authenticated_sessions
+            .get(&session_id)?
+            .characters
+            .get(&character_id)
+            .map(|character| character.class_id)
+    }
+
+    fn character_for_session(&self, session_id: u64) -> Option<u64> {
+        self.session_routes
             .get(&session_id)
-            .map(|entry| entry.value().clone())?;
+            .map(|entry| entry.value().0)
+    }
 
-        if session.is_expired(server_time_ms) {
-            self.detach_session_from_map(session_id).await;
-            self.clear_pending_transfers(session_id);
-            self.authenticated_sessions.remove(&session_id);
-            return None;
+    fn account_of_character(&self, character_id: u64) -> Option<u64> {
+        let session_id = *self.active_characters.get(&character_id)?.value();
+        self.authenticated_sessions
+            .get(&session_id)
+            .map(|session| session.account_id)
+    }
+
+    fn character_summary(
+        &self,
+        session_id: u64,
+        character_id: u64,
+    ) -> Option<AuthCharacterSummary> {
+        self.authenticated_sessions
+            .get(&session_id)?
+            .characters
+            .get(&character_id)
+            .cloned()
+    }
+
+    fn active_character_name(&self, session_id: u64) -> Option<String> {
+        let character_id = self.character_for_session(session_id)?;
+        self.authenticated_sessions
+            .get(&session_id)?
+            .characters
+            .get(&character_id)
+            .map(|character| character.name.clone())
+    }
+
+    /// Session whose character on a map is called `name`, ignoring case.
+    fn session_playing(&self, name: &str) -> Option<u64> {
+        let playing: Vec<(u64, u64)> = self
+            .session_routes
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().0))
+            .collect();
+        playing
+            .into_iter()
+            .find(|(session_id, character_id)| {
+                self.authenticated_sessions
+                    .get(session_id)
+                    .and_then(|session| {
+                        session
+                            .characters
+                            .get(character_id)
+                            .map(|character| character.name.eq_ignore_ascii_case(name))
+                    })
+                    .unwrap_or(false)
+            })
+            .map(|(session_id, _)| session_id)
+    }
+
+    async fn detach_session_from_map(&self, session_id: u64) {
+        // The next map may sit in a world that denies the helper.
+        self.helpers.stop(session_id);
+        if let Some((_, (character_id, route))) = self.session_routes.remove(&session_id) {
+            let map = self
+                .map_servers
+                .get(&route)
+                .map(|entry| entry.value().clone());
+
+            if let Some(map) = map {
+                let _ = map.leave(character_id).await;
+            }
+
+            self.active_characters.remove(&character_id);
         }
+    }
 
-        Some(session)
+    fn clear_pending_transfers(&self, session_id: u64) {
+        let transfer_ids: Vec<u64> = self
+            .pending_transfers
+            .iter()
+            .filter_map(|entry| {
+                if entry.value().session_id == session_id {
+                    Some(*entry.key())
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        for transfer_id in transfer_ids {
+            self.pending_transfers.remove(&transfer_id);
+        }
     }
+}
 
-    fn response_for_request(
-        &self,
-        request: &WirePacket,
-        server_time_ms: u64,
-        message: ServerMessage,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use crate::auth_token::{object_id_to_u64, AuthCharacterSummary, AuthTokenService};
+    use crate::runtime::config::{ClassKitConfig, QuestConfig, QuestStepConfig};
+    use crate::runtime::session_links::SessionLink;
+    use crate::session::SessionManager;
+    use common::WorldMap;
+    use mongodb::bson::oid::ObjectId;
+    use protocol::{
+        AccountSettings, ClientHello, EquipSlot, QuicChannel, UseSkillInput, WhisperRequest,
+    };
+
+    fn build_runtime() -> MuCoreRuntime {
+        let auth_tokens = AuthTokenService::new(
+            b"01234567890123456789012345678901".to_vec(),
+            Duration::from_secs(3600),
+        )
+        .expect("auth tokens");
+
+        MuCoreRuntime::bootstrap(RuntimeConfig::default(), auth_tokens, None).expect("runtime boot")
+    }
+
+    fn build_hello_packet(
+        runtime: &MuCoreRuntime,
+        session_id: u64,
+        account_id: u64,
+        character_ids: &[u64],
     ) -> WirePacket {
-        WirePacket::server(
-            request.session_id,
-            request.route,
-            request.sequence.wrapping_add(1),
-            Some(request.sequence),
-            server_time_ms,
-            message,
+        build_hello_packet_as(
+            runtime,
+            session_id,
+            account_id,
+            character_ids,
+            AccountRole::Player,
         )
     }
 
-    fn error_for_request(
-        &self,
-        request: &WirePacket,
-        server_time_ms: u64,
-        kind: ServerErrorKind,
-        message: &str,
+    fn build_hello_packet_as(
+        runtime: &MuCoreRuntime,
+        session_id: u64,
+        account_id: u64,
+        character_ids: &[u64],
+        role: AccountRole,
     ) -> WirePacket {
-        self.response_for_request(
-            request,
-            server_time_ms,
-            ServerMessage::Error {
-                kind,
-                message: message.to_string(),
-            },
+        let token = runtime
+            .auth_tokens
+            .issue_session_token(
+                account_id,
+                role,
+                format!("session-{session_id}"),
+                character_ids
+                    .iter()
+                    .map(|character_id| AuthCharacterSummary {
+                        character_id: *character_id,
+                        db_id: format!("{:024x}", character_id),
+                        name: format!("Character-{character_id}"),
+                        class_id: 1,
+                        level: 150,
+                        guild_id: None,
+                    })
+                    .collect(),
+                100,
+            )
+            .expect("issue auth token");
+
+        WirePacket::client(
+            session_id,
+            RouteKey::LOBBY,
+            1,
+            None,
+            100,
+            ClientMessage::Hello(ClientHello {
+                account_id,
+                auth_token: token,
+                client_build: "0.1.0".to_string(),
+                locale: "pt-BR".to_string(),
+                capabilities: ProtocolCapabilities::NONE,
+            }),
+        )
+    }
+
+    async fn enter_map(runtime: &MuCoreRuntime, session_id: u64, character_id: u64) {
+        let transfer = runtime
+            .handle_client_packet(
+                WirePacket::client(
+                    session_id,
+                    RouteKey::LOBBY,
+                    1,
+                    None,
+                    100,
+                    ClientMessage::SelectCharacter { character_id },
+                ),
+                100,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        let PacketPayload::Server(ServerMessage::MapTransfer(directive)) = transfer.payload else {
+            panic!("expected transfer");
+        };
+        runtime
+            .handle_client_packet(
+                WirePacket::client(
+                    session_id,
+                    RouteKey::LOBBY,
+                    2,
+                    None,
+                    110,
+                    ClientMessage::MapTransferAck {
+                        transfer_id: directive.transfer_id,
+                        route_token: directive.route_token,
+                    },
+                ),
+                110,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    fn chat_packet(session_id: u64, text: &str) -> WirePacket {
+        WirePacket::client(
+            session_id,
+            RouteKey::LOBBY,
+            3,
+            None,
+            200,
+            ClientMessage::Chat(ChatPayload {
+                channel: ChatChannel::Local,
+                target: None,
+                text: text.to_string(),
+            }),
+        )
+    }
+
+    fn whisper_packet(session_id: u64, target: &str, text: &str) -> WirePacket {
+        WirePacket::client(
+            session_id,
+            RouteKey::LOBBY,
+            4,
+            None,
+            200,
+            ClientMessage::Whisper(WhisperRequest {
+                target: target.to_string(),
+                text: text.to_string(),
+            }),
         )
     }
 
-    fn error_for_unbound_session(
-        &self,
-        session_id: u64,
-        sequence: u32,
-        server_time_ms: u64,
-        kind: ServerErrorKind,
-        message: &str,
-    ) -> WirePacket {
-        WirePacket::server(
-            session_id,
-            RouteKey::LOBBY,
-            sequence,
-            None,
-            server_time_ms,
-            ServerMessage::Error {
-                kind,
-                message: message.to_string(),
-            },
-        )
+    fn whisper_result(packet: Option<WirePacket>) -> Option<WhisperResult> {
+        match packet?.payload {
+            PacketPayload::Server(ServerMessage::WhisperResult { result, .. }) => Some(result),
+            _ => None,
+        }
+    }
+
+    fn error_kind(packet: Option<WirePacket>) -> Option<ServerErrorKind> {
+        match packet?.payload {
+            PacketPayload::Server(ServerMessage::Error(error)) => Some(error.kind),
+            _ => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn hello_settles_version_and_capabilities() {
+        let runtime = build_runtime();
+        let mut hello = build_hello_packet(&runtime, 41, 20, &[410]);
+        let PacketPayload::Client(ClientMessage::Hello(offer)) = &mut hello.payload else {
+            unreachable!();
+        };
+        offer.capabilities =
+            ProtocolCapabilities::SUPPORTED.union(ProtocolCapabilities::from_bits(1 << 31));
+        let ack = runtime.handle_client_packet(hello, 100).await.unwrap();
+        match ack.map(|packet| packet.payload) {
+            Some(PacketPayload::Server(ServerMessage::HelloAck {
+                protocol_version,
+                capabilities,
+                ..
+            })) => {
+                assert_eq!(protocol_version, protocol::PROTOCOL_VERSION);
+                assert_eq!(capabilities, ProtocolCapabilities::SUPPORTED);
+            }
+            other => panic!("expected HelloAck, got {other:?}"),
+        }
+        assert!(runtime.stream_codec(41).compression().is_some());
+
+        let mut old = build_hello_packet(&runtime, 42, 21, &[420]);
+        old.version = protocol::ProtocolVersion::new(protocol::PROTOCOL_VERSION.major - 1, 0);
+        let refused = runtime.handle_client_packet(old, 100).await.unwrap();
+        assert_eq!(
+            error_kind(refused),
+            Some(ServerErrorKind::UnsupportedVersion)
+        );
+        assert!(runtime.stream_codec(42).compression().is_none());
+    }
+
+    #[tokio::test]
+    async fn chat_commands_follow_the_role_of_the_account() {
+        let runtime = build_runtime();
+        runtime
+            .handle_client_packet(build_hello_packet(&runtime, 51, 21, &[510]), 100)
+            .await
+            .unwrap();
+        runtime
+            .handle_client_packet(
+                build_hello_packet_as(&runtime, 52, 22, &[520], AccountRole::Helper),
+                100,
+            )
+            .await
+            .unwrap();
+        enter_map(&runtime, 51, 510).await;
+        enter_map(&runtime, 52, 520).await;
+
+        let denied = runtime
+            .handle_client_packet(chat_packet(51, "/mute Character-520 5"), 200)
+            .await
+            .unwrap();
+        assert_eq!(error_kind(denied), Some(ServerErrorKind::PermissionDenied));
+
+        let muted = runtime
+            .handle_client_packet(chat_packet(52, "/mute character-510 5"), 200)
+            .await
+            .unwrap();
+        assert_eq!(error_kind(muted), None);
+        let blocked = runtime
+            .handle_client_packet(chat_packet(51, "hello"), 200)
+            .await
+            .unwrap();
+        assert_eq!(error_kind(blocked), Some(ServerErrorKind::ChatMuted));
+        let expired = runtime
+            .handle_client_packet(chat_packet(51, "hello"), 200 + 5 * 60_000)
+            .await
+            .unwrap();
+        assert_eq!(error_kind(expired), None);
+
+        let kick = runtime
+            .handle_client_packet(chat_packet(52, "/kick Character-510"), 300)
+            .await
+            .unwrap();
+        assert_eq!(error_kind(kick), Some(ServerErrorKind::PermissionDenied));
+
+        runtime.shutdown().await.unwrap();
     }
 
-    async fn handle_select_character(
-        &self,
-        session_id: u64,
-        character_id: u64,
-        server_time_ms: u64,
-    ) -> WirePacket {
-        // For MVP we route character selection to map_id 0 in the least loaded entry.
-        let target_world = self.config.worlds.first().map(|w| w.id).unwrap_or(0);
+    #[tokio::test]
+    async fn whispers_reach_the_named_character_only() {
+        let runtime = build_runtime();
+        let mut target_rx = runtime.message_hub.subscribe(MessageScope::Session(54));
+        runtime
+            .handle_client_packet(build_hello_packet(&runtime, 53, 23, &[530]), 100)
+            .await
+            .unwrap();
+        runtime
+            .handle_client_packet(build_hello_packet(&runtime, 54, 24, &[540]), 100)
+            .await
+            .unwrap();
 
-        let entry = self.directory.select_best_entry(target_world);
-        let map_route = match entry.as_ref() {
-            Some(entry) => {
-                self.resolve_or_scale_map_route(entry.world_id, entry.entry_id, 0)
-                    .await
+        let early = runtime
+            .handle_client_packet(whisper_packet(53, "Character-540", "oi"), 200)
+            .await
+            .unwrap();
+        assert_eq!(error_kind(early), Some(ServerErrorKind::NotInMap));
+
+        enter_map(&runtime, 53, 530).await;
+        enter_map(&runtime, 54, 540).await;
+        let delivered = runtime
+            .handle_client_packet(whisper_packet(53, "character-540", "oi"), 200)
+            .await
+            .unwrap();
+        assert_eq!(whisper_result(delivered), Some(WhisperResult::Delivered));
+        let mut whispers = Vec::new();
+        while let Ok(message) = target_rx.try_recv() {
+            if let HubPayload::Chat(chat) = message.payload {
+                whispers.push(chat);
             }
-            None => None,
+        }
+        assert_eq!(
+            whispers,
+            vec![ChatPayload {
+                channel: ChatChannel::Whisper,
+                target: Some("Character-530".to_string()),
+                text: "oi".to_string(),
+            }]
+        );
+
+        let missing = runtime
+            .handle_client_packet(whisper_packet(53, "Nobody", "oi"), 200)
+            .await
+            .unwrap();
+        assert_eq!(whisper_result(missing), Some(WhisperResult::NotOnline));
+
+        runtime.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn items_move_between_the_inventory_and_equipment() {
+        let runtime = build_runtime();
+        runtime
+            .handle_client_packet(build_hello_packet(&runtime, 61, 31, &[610]), 100)
+            .await
+            .unwrap();
+        enter_map(&runtime, 61, 610).await;
+        let mut sword = ItemInstance {
+            serial: 0,
+            group: 0,
+            index: 1,
+            level: 0,
+            quantity: 1,
+            options: Default::default(),
+            expires_at_ms: None,
+        };
+        let serial =
+            runtime
+                .items
+                .mint(&mut sword, ItemHolder::Character { character_id: 610 }, 100);
+        let send = |message| {
+            runtime.handle_client_packet(
+                WirePacket::client(61, RouteKey::LOBBY, 5, None, 200, message),
+                200,
+            )
+        };
+        let reply = |packet: Option<WirePacket>| match packet.map(|packet| packet.payload) {
+            Some(PacketPayload::Server(message)) => Some(message),
+            _ => None,
         };
+        let rejected = |action, reason| Some(ServerMessage::ItemRejected { action, reason });
+        let in_bag = ItemLocation::Inventory { cell: 0 };
 
-        match (entry, map_route) {
-            (Some(entry), Some(map)) => {
-                let transfer_id = self.transfer_seq.fetch_add(1, Ordering::Relaxed);
-                let expires_at_ms = server_time_ms.saturating_add(30_000);
-                let route_token_claims = MapTransferTokenClaims {
-                    session_id,
-                    transfer_id,
-                    character_id,
-                    route: map.route,
-                    issued_at_ms: server_time_ms,
-                    expires_at_ms,
-                };
+        let armor = send(ClientMessage::MoveItem {
+            serial,
+            from: in_bag,
+            to: ItemLocation::Equipment(EquipSlot::Armor),
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            reply(armor),
+            rejected(ItemAction::Move, ItemFailure::CannotEquip)
+        );
+        let wielded = send(ClientMessage::MoveItem {
+            serial,
+            from: in_bag,
+            to: ItemLocation::Equipment(EquipSlot::Weapon),
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            reply(wielded),
+            Some(ServerMessage::ItemMoved {
+                serial,
+                to: ItemLocation::Equipment(EquipSlot::Weapon),
+            })
+        );
+        assert_eq!(
+            runtime.worn_items.in_slot(610, EquipSlot::Weapon),
+            Some(serial)
+        );
 
-                let route_token = match self.auth_tokens.issue_transfer_token(&route_token_claims) {
-                    Ok(token) => token,
-                    Err(err) => {
-                        return WirePacket::server(
-                            session_id,
-                            RouteKey::LOBBY,
-                            transfer_id as u32,
-                            None,
-                            server_time_ms,
-                            ServerMessage::Error {
-                                kind: ServerErrorKind::Internal,
-                                message: format!("Failed to issue transfer token: {}", err),
-                            },
-                        )
-                    }
-                };
+        let unequip = |cell| ClientMessage::UnequipItem {
+            slot: EquipSlot::Weapon,
+            cell,
+        };
+        // The sword is three cells tall and the bottom row has no room.
+        let past_the_edge = send(unequip(62)).await.unwrap();
+        assert_eq!(
+            reply(past_the_edge),
+            rejected(ItemAction::Unequip, ItemFailure::InvalidLocation)
+        );
+        let stowed = send(unequip(7)).await.unwrap();
+        assert_eq!(
+            reply(stowed),
+            Some(ServerMessage::ItemMoved {
+                serial,
+                to: ItemLocation::Inventory { cell: 7 },
+            })
+        );
+        assert_eq!(runtime.worn_items.in_slot(610, EquipSlot::Weapon), None);
+        let empty = send(unequip(7)).await.unwrap();
+        assert_eq!(
+            reply(empty),
+            rejected(ItemAction::Unequip, ItemFailure::NotHeld)
+        );
 
-                self.pending_transfers.insert(
-                    transfer_id,
-                    PendingTransfer {
-                        session_id,
-                        transfer_id,
-                        character_id,
-                        route: map.route,
-                    },
-                );
+        let used = send(ClientMessage::UseItem { serial }).await.unwrap();
+        assert_eq!(
+            reply(used),
+            rejected(ItemAction::Use, ItemFailure::NotUsable)
+        );
+        let picked = send(ClientMessage::PickupItem { entity_id: 1 })
+            .await
+            .unwrap();
+        assert_eq!(
+            reply(picked),
+            rejected(ItemAction::Pickup, ItemFailure::Gone)
+        );
 
-                WirePacket::server(
-                    session_id,
-                    RouteKey::LOBBY,
-                    transfer_id as u32,
-                    None,
-                    server_time_ms,
-                    ServerMessage::MapTransfer(MapTransferDirective {
-                        transfer_id,
-                        route: map.route,
-                        host: entry.host,
-                        port: entry.port,
-                        route_token,
-                        expires_at_ms,
-                    }),
-                )
-            }
-            _ => WirePacket::server(
-                session_id,
-                RouteKey::LOBBY,
-                0,
-                None,
-                server_time_ms,
-                ServerMessage::Error {
-                    kind: ServerErrorKind::RouteUnavailable,
-                    message: "No route available".to_string(),
-                },
+        let route = runtime.route_of_character(610).unwrap();
+        let map = runtime.map_servers.get(&route).unwrap().value().clone();
+        let (x, y) = map.position(610).await.unwrap();
+        let far = send(ClientMessage::DropItem {
+            serial,
+            x: x.wrapping_add(10),
+            y,
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            reply(far),
+            rejected(ItemAction::Drop, ItemFailure::OutOfReach)
+        );
+        let dropped = send(ClientMessage::DropItem { serial, x, y })
+            .await
+            .unwrap();
+        let Some(ServerMessage::ItemDropped {
+            serial: on_ground,
+            entity_id,
+        }) = reply(dropped)
+        else {
+            panic!("expected the sword on the ground");
+        };
+        assert_eq!(on_ground, serial);
+        assert_eq!(
+            runtime.items.owner(serial),
+            Some(ItemHolder::Ground { route })
+        );
+
+        let picked = send(ClientMessage::PickupItem { entity_id }).await.unwrap();
+        assert!(matches!(
+            reply(picked),
+            Some(ServerMessage::ItemPickedUp { item, .. }) if item.serial == serial
+        ));
+        assert_eq!(
+            runtime.items.owner(serial),
+            Some(ItemHolder::Character { character_id: 610 })
+        );
+        let again = send(ClientMessage::PickupItem { entity_id }).await.unwrap();
+        assert_eq!(
+            reply(again),
+            rejected(ItemAction::Pickup, ItemFailure::Gone)
+        );
+
+        runtime.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn kills_reported_by_maps_unlock_the_bestiary() {
+        let runtime = build_runtime();
+        runtime
+            .handle_client_packet(build_hello_packet(&runtime, 62, 32, &[620]), 100)
+            .await
+            .unwrap();
+        enter_map(&runtime, 62, 620).await;
+        let mut link = runtime.session_links().attach(62);
+
+        for _ in 0..2 {
+            runtime
+                .monster_kills
+                .send(MonsterKill {
+                    character_id: 620,
+                    monster_id: 3,
+                    rank: None,
+                    server_time_ms: 300,
+                })
+                .unwrap();
+        }
+        match tokio::time::timeout(Duration::from_secs(1), link.commands.recv()).await {
+            Ok(Some(SessionCommand::Send(packet))) => assert_eq!(
+                packet.payload,
+                PacketPayload::Server(ServerMessage::BestiaryUnlocked { monster_id: 3 })
             ),
+            other => panic!("expected the unlock, got {other:?}"),
         }
+        assert_eq!(runtime.bestiary.unlocked(620), vec![3]);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(link.commands.try_recv().is_err(), "unlocked once");
+
+        runtime.shutdown().await.unwrap();
     }
 
-    async fn resolve_or_scale_map_route(
-        &self,
-        world_id: u16,
-        entry_id: u16,
-        map_id: u16,
-    ) -> Option<MapRoute> {
-        if let Some(route) = self
-            .directory
-            .select_best_map_instance(world_id, entry_id, map_id)
-        {
-            return Some(route);
-        }
+    #[tokio::test]
+    async fn select_character_returns_map_transfer() {
+        let runtime = build_runtime();
 
-        // Serialize scale-out decisions to avoid creating duplicate instances.
-        let _guard = self.scale_lock.lock().await;
+        let hello = runtime
+            .handle_client_packet(build_hello_packet(&runtime, 7, 10, &[42]), 100)
+            .await
+            .expect("hello packet")
+            .expect("hello response");
+        assert!(matches!(
+            hello.payload,
+            PacketPayload::Server(ServerMessage::HelloAck { .. })
+        ));
 
-        if let Some(route) = self
-            .directory
-            .select_best_map_instance(world_id, entry_id, map_id)
-        {
-            return Some(route);
-        }
+        let response = runtime
+            .handle_client_packet(
+                WirePacket::client(
+                    7,
+                    RouteKey::LOBBY,
+                    1,
+                    None,
+                    100,
+                    ClientMessage::SelectCharacter { character_id: 42 },
+                ),
+                100,
+            )
+            .await
+            .expect("handle packet")
+            .expect("must respond");
 
-        self.spawn_additional_map_instance(world_id, entry_id, map_id)
-            .await?;
+        assert!(matches!(
+            response.payload,
+            PacketPayload::Server(ServerMessage::MapTransfer(_))
+        ));
 
-        self.directory
-            .select_best_map_instance(world_id, entry_id, map_id)
+        runtime.shutdown().await.unwrap();
     }
 
-    async fn spawn_additional_map_instance(
-        &self,
-        world_id: u16,
-        entry_id: u16,
-        map_id: u16,
-    ) -> Option<()> {
-        let (map_name, soft_player_cap) =
-            self.directory.map_template(world_id, entry_id, map_id)?;
-        let instance_id = self
-            .directory
-            .next_instance_id(world_id, entry_id, map_id)?;
+    #[tokio::test]
+    async fn transfer_ack_enters_map() {
+        let runtime = build_runtime();
 
-        let route = RouteKey {
-            world_id,
-            entry_id,
-            map_id,
-            instance_id,
+        let _ = runtime
+            .handle_client_packet(build_hello_packet(&runtime, 9, 11, &[99]), 100)
+            .await
+            .expect("hello packet")
+            .expect("hello response");
+
+        let transfer = runtime
+            .handle_client_packet(
+                WirePacket::client(
+                    9,
+                    RouteKey::LOBBY,
+                    1,
+                    None,
+                    100,
+                    ClientMessage::SelectCharacter { character_id: 99 },
+                ),
+                100,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+
+        let (transfer_id, route_token, _map_route) = match transfer.payload {
+            PacketPayload::Server(ServerMessage::MapTransfer(directive)) => (
+                directive.transfer_id,
+                directive.route_token,
+                directive.route,
+            ),
+            _ => panic!("expected transfer"),
         };
 
-        if !self.directory.register_instance_route(route) {
-            return Some(());
-        }
+        let enter = runtime
+            .handle_client_packet(
+                WirePacket::client(
+                    9,
+                    RouteKey::LOBBY,
+                    2,
+                    None,
+                    110,
+                    ClientMessage::MapTransferAck {
+                        transfer_id,
+                        route_token,
+                    },
+                ),
+                110,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(matches!(
+            enter.payload,
+            PacketPayload::Server(ServerMessage::EnterMap { .. })
+        ));
 
-        let handle = start_map_server(
-            MapServerConfig {
-                route,
-                map_name,
-                soft_player_cap,
-                player_tick: self.config.player_tick(),
-                monster_tick: self.config.monster_tick(),
-            },
-            self.directory.clone(),
-            self.persistence.clone(),
-            self.message_hub.clone(),
-        );
+        runtime.shutdown().await.unwrap();
+    }
 
-        self.map_servers.insert(route, handle);
+    #[tokio::test]
+    async fn kicks_and_transfers_reach_the_linked_connection() {
+        let runtime = build_runtime();
+        runtime
+            .handle_client_packet(build_hello_packet(&runtime, 12, 13, &[120]), 100)
+            .await
+            .unwrap()
+            .unwrap();
+        let mut link = runtime.session_links().attach(12);
 
-        log::info!(
-            "Spawned dynamic map instance world={} entry={} map={} instance={}",
-            world_id,
-            entry_id,
-            map_id,
-            instance_id
-        );
+        assert!(matches!(
+            runtime.transfer_session(12, 1, None, None, 150).await,
+            Err(SessionControlError::NoCharacter(12))
+        ));
+        let transfer = runtime
+            .handle_client_packet(
+                WirePacket::client(
+                    12,
+                    RouteKey::LOBBY,
+                    1,
+                    None,
+                    100,
+                    ClientMessage::SelectCharacter { character_id: 120 },
+                ),
+                100,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        let PacketPayload::Server(ServerMessage::MapTransfer(directive)) = transfer.payload else {
+            panic!("expected transfer");
+        };
+        runtime
+            .handle_client_packet(
+                WirePacket::client(
+                    12,
+                    RouteKey::LOBBY,
+                    2,
+                    None,
+                    110,
+                    ClientMessage::MapTransferAck {
+                        transfer_id: directive.transfer_id,
+                        route_token: directive.route_token,
+                    },
+                ),
+                110,
+            )
+            .await
+            .unwrap()
+            .unwrap();
 
-        Some(())
-    }
+        let handed = runtime
+            .transfer_session(12, 1, None, None, 200)
+            .await
+            .expect("transfer");
+        assert_eq!(handed.route.map_id, directive.route.map_id);
+        match link.commands.recv().await {
+            Some(SessionCommand::Send(packet)) => assert_eq!(
+                packet.payload,
+                PacketPayload::Server(ServerMessage::MapTransfer(handed))
+            ),
+            other => panic!("expected the transfer, got {other:?}"),
+        }
 
-    async fn handle_transfer_ack(
-        &self,
-        session_id: u64,
-        transfer_id: u64,
-        route_token: &str,
-        server_time_ms: u64,
-    ) -> WirePacket {
-        let transfer_token_claims = match self
-            .auth_tokens
-            .verify_transfer_token(route_token, server_time_ms)
-        {
-            Ok(claims) => claims,
-            Err(err) => {
-                return self.error_for_unbound_session(
-                    session_id,
-                    transfer_id as u32,
-                    server_time_ms,
-                    ServerErrorKind::InvalidSession,
-                    &format!("Invalid transfer token: {}", err),
+        assert!(runtime
+            .kick_session(12, DisconnectReason::Banned, "cheating", 300)
+            .await
+            .unwrap());
+        match link.commands.recv().await {
+            Some(SessionCommand::Close { packet, reason }) => {
+                assert_eq!(reason, DisconnectReason::Banned);
+                assert_eq!(
+                    packet.payload,
+                    PacketPayload::Server(ServerMessage::Disconnect {
+                        reason,
+                        message: "cheating".to_string(),
+                    })
                 );
             }
-        };
-
-        if transfer_token_claims.session_id != session_id
-            || transfer_token_claims.transfer_id != transfer_id
-        {
-            return self.error_for_unbound_session(
-                session_id,
-                transfer_id as u32,
-                server_time_ms,
-                ServerErrorKind::InvalidSession,
-                "Transfer token/session mismatch",
-            );
+            other => panic!("expected the close, got {other:?}"),
         }
+        assert!(matches!(
+            runtime
+                .kick_session(12, DisconnectReason::Kicked, "", 400)
+                .await,
+            Err(SessionControlError::UnknownSession(12))
+        ));
 
-        let transfer = self
-            .pending_transfers
-            .remove(&transfer_id)
-            .map(|(_, val)| val);
-        match transfer {
-            Some(transfer) => {
-                if transfer.session_id != session_id {
-                    return self.error_for_unbound_session(
-                        session_id,
-                        transfer_id as u32,
-                        server_time_ms,
-                        ServerErrorKind::InvalidSession,
-                        "Transfer does not belong to this session",
-                    );
-                }
+        runtime.shutdown().await.unwrap();
+    }
 
-                if transfer_token_claims.character_id != transfer.character_id
-                    || transfer_token_claims.route != transfer.route
-                {
-                    return self.error_for_unbound_session(
-                        session_id,
-                        transfer_id as u32,
-                        server_time_ms,
-                        ServerErrorKind::InvalidSession,
-                        "Transfer token payload does not match transfer route",
-                    );
-                }
+    #[tokio::test]
+    async fn new_characters_start_in_their_class_town() {
+        let mut config = RuntimeConfig::default();
+        config.starting_zones.default = WorldMap::Noria;
+        let auth_tokens = AuthTokenService::new(
+            b"01234567890123456789012345678901".to_vec(),
+            Duration::from_secs(3600),
+        )
+        .expect("auth tokens");
+        let runtime = MuCoreRuntime::bootstrap(config, auth_tokens, None).expect("runtime boot");
+        runtime
+            .handle_client_packet(build_hello_packet(&runtime, 14, 15, &[140]), 100)
+            .await
+            .unwrap()
+            .unwrap();
 
-                if let Some(existing_owner) = self.active_characters.get(&transfer.character_id) {
-                    if *existing_owner.value() != session_id {
-                        return self.error_for_unbound_session(
-                            session_id,
-                            transfer_id as u32,
-                            server_time_ms,
-                            ServerErrorKind::InvalidAction,
-                            "Character is already active in another session",
-                        );
-                    }
-                }
+        let transfer = runtime
+            .handle_client_packet(
+                WirePacket::client(
+                    14,
+                    RouteKey::LOBBY,
+                    1,
+                    None,
+                    100,
+                    ClientMessage::SelectCharacter { character_id: 140 },
+                ),
+                100,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        let PacketPayload::Server(ServerMessage::MapTransfer(directive)) = transfer.payload else {
+            panic!("expected transfer");
+        };
+        assert_eq!(directive.route.map_id, 1, "Noria in the default config");
 
-                let map = self
-                    .map_servers
-                    .get(&transfer.route)
-                    .map(|entry| entry.value().clone());
+        runtime.shutdown().await.unwrap();
+    }
 
-                if let Some(map) = map {
-                    self.detach_session_from_map(session_id).await;
-                    let _ = map.join(session_id, transfer.character_id, 125, 125).await;
-                    self.session_routes
-                        .insert(session_id, (transfer.character_id, transfer.route));
-                    self.active_characters
-                        .insert(transfer.character_id, session_id);
+    #[tokio::test]
+    async fn new_character_gets_its_kit_and_tutorial() {
+        let mut config = RuntimeConfig::default();
+        config.starting_kit.classes.push(ClassKitConfig {
+            class_id: Some(1),
+            zen: 5_000,
+            map_id: Some(1),
+            items: Vec::new(),
+        });
+        config.tutorial = Some(QuestConfig {
+            quest_id: 1,
+            name: "First Steps".to_string(),
+            steps: vec![QuestStepConfig::TalkToNpc { npc_id: 249 }],
+            reward_zen: 1_000,
+            reward_items: Vec::new(),
+        });
+        let auth_tokens = AuthTokenService::new(
+            b"01234567890123456789012345678901".to_vec(),
+            Duration::from_secs(3600),
+        )
+        .expect("auth tokens");
+        let runtime = MuCoreRuntime::bootstrap(config, auth_tokens, None).expect("runtime boot");
+        runtime
+            .handle_client_packet(build_hello_packet(&runtime, 12, 13, &[120]), 100)
+            .await
+            .unwrap()
+            .unwrap();
 
-                    WirePacket::server(
-                        session_id,
-                        transfer.route,
-                        transfer.transfer_id as u32,
-                        None,
-                        server_time_ms,
-                        ServerMessage::EnterMap {
-                            entity_id: transfer.character_id as u32,
-                            map_id: transfer.route.map_id,
-                            x: 125,
-                            y: 125,
-                        },
-                    )
-                } else {
-                    WirePacket::server(
-                        session_id,
-                        RouteKey::LOBBY,
-                        transfer_id as u32,
-                        None,
-                        server_time_ms,
-                        ServerMessage::Error {
-                            kind: ServerErrorKind::RouteUnavailable,
-                            message: "Map instance unavailable".to_string(),
-                        },
-                    )
-                }
-            }
-            None => self.error_for_unbound_session(
-                session_id,
-                transfer_id as u32,
-                server_time_ms,
-                ServerErrorKind::InvalidAction,
-                "Invalid transfer ack",
-            ),
+        let transfer = runtime
+            .handle_client_packet(
+                WirePacket::client(
+                    12,
+                    RouteKey::LOBBY,
+                    1,
+                    None,
+                    100,
+                    ClientMessage::SelectCharacter { character_id: 120 },
+                ),
+                100,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        let PacketPayload::Server(ServerMessage::MapTransfer(directive)) = transfer.payload else {
+            panic!("expected transfer");
+        };
+        assert_eq!(directive.route.map_id, 1);
+
+        runtime
+            .handle_client_packet(
+                WirePacket::client(
+                    12,
+                    RouteKey::LOBBY,
+                    2,
+                    None,
+                    110,
+                    ClientMessage::MapTransferAck {
+                        transfer_id: directive.transfer_id,
+                        route_token: directive.route_token,
+                    },
+                ),
+                110,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!runtime.quest_logs().is_new(120));
+        assert_eq!(
+            runtime.quest_logs().status(120).map(|status| status.step),
+            Some(0)
+        );
+
+        let talked = runtime
+            .handle_client_packet(
+                WirePacket::client(
+                    12,
+                    RouteKey::LOBBY,
+                    3,
+                    None,
+                    120,
+                    ClientMessage::TalkToNpc { npc_id: 249 },
+                ),
+                120,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        let PacketPayload::Server(ServerMessage::QuestStatus(status)) = talked.payload else {
+            panic!("expected quest status");
+        };
+        assert_eq!((status.step, status.objective), (1, None));
+
+        runtime.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn online_reward_without_inventory_room_is_mailed() {
+        let runtime = build_runtime();
+        let route = RouteKey {
+            world_id: 1,
+            entry_id: 1,
+            map_id: 0,
+            instance_id: 1,
+        };
+        runtime.active_characters.insert(77, 5);
+        runtime.session_routes.insert(5, (77, route));
+        assert_eq!(runtime.route_of_character(77), Some(route));
+
+        let reward = RewardBundle {
+            zen: 500,
+            items: vec![protocol::ItemInstance {
+                serial: 0,
+                group: 14,
+                index: 13,
+                level: 0,
+                quantity: 1,
+                options: protocol::ItemOptions::default(),
+                expires_at_ms: None,
+            }],
+        };
+        let carried: Vec<u64> = (0..INVENTORY_SLOTS)
+            .map(|_| {
+                let mut item = reward.items[0].clone();
+                runtime
+                    .items
+                    .mint(&mut item, ItemHolder::Character { character_id: 77 }, 40)
+            })
+            .collect();
+        assert_eq!(runtime.free_inventory_slots_for(77), 0);
+        let delivery = runtime
+            .grant_event_reward(
+                77,
+                route,
+                RewardSource::DevilSquare { level: 3 },
+                reward.clone(),
+                50,
+            )
+            .await
+            .expect("valid reward");
+        assert!(matches!(delivery, RewardDelivery::Mailed { .. }));
+        assert_eq!(runtime.wallets.balance(77), 0);
+
+        for serial in &carried[..8] {
+            runtime
+                .items
+                .transfer(
+                    *serial,
+                    ItemHolder::Character { character_id: 77 },
+                    ItemHolder::Store { character_id: 77 },
+                    TransferReason::Store,
+                    55,
+                )
+                .unwrap();
         }
+        let delivery = runtime
+            .grant_event_reward(
+                77,
+                route,
+                RewardSource::DevilSquare { level: 3 },
+                reward,
+                60,
+            )
+            .await
+            .expect("valid reward");
+        assert_eq!(delivery, RewardDelivery::Direct);
+        assert_eq!(runtime.wallets.balance(77), 500);
+        assert_eq!(runtime.free_inventory_slots_for(77), 7);
+
+        // Each grant minted its own serial; only the mailed copy is scanned.
+        let report = runtime.scan_item_dupes(70);
+        assert_eq!((report.items_scanned, report.anomalies.len()), (1, 0));
+
+        runtime.shutdown().await.unwrap();
     }
 
-    fn character_for_session(&self, session_id: u64) -> Option<u64> {
-        self.session_routes
-            .get(&session_id)
-            .map(|entry| entry.value().0)
+    #[tokio::test]
+    async fn cash_purchase_is_delivered_once_per_key() {
+        let auth_tokens = AuthTokenService::new(
+            b"01234567890123456789012345678901".to_vec(),
+            Duration::from_secs(3600),
+        )
+        .expect("auth tokens");
+        let mut config = RuntimeConfig::default();
+        config.cash_shop = Some(crate::runtime::config::CashShopConfig {
+            currency_name: "WCoin".to_string(),
+            products: vec![crate::runtime::config::CashShopProductConfig {
+                product_id: 4,
+                name: "Zen pouch".to_string(),
+                price: 100,
+                zen: 1_000_000,
+                items: vec![crate::runtime::config::ItemGrantConfig {
+                    group: 14,
+                    index: 13,
+                    level: 0,
+                    quantity: 1,
+                }],
+            }],
+        });
+        let runtime = MuCoreRuntime::bootstrap(config, auth_tokens, None).expect("runtime boot");
+        let shop = runtime.cash_shop().expect("cash shop enabled");
+        shop.top_up(13, 150, "order-1", 10).unwrap();
+
+        let receipt = runtime
+            .purchase_cash_product(13, 42, 4, "buy-1", 20)
+            .await
+            .expect("purchase");
+        assert_eq!(receipt.balance_after, 50);
+        assert_eq!(runtime.wallets.balance(42), 1_000_000);
+        assert_eq!(runtime.free_inventory_slots_for(42), INVENTORY_SLOTS - 1);
+
+        let retried = runtime
+            .purchase_cash_product(13, 42, 4, "buy-1", 30)
+            .await
+            .expect("replayed purchase");
+        assert_eq!(retried, receipt);
+        assert_eq!(runtime.wallets.balance(42), 1_000_000);
+
+        runtime
+            .wallets
+            .credit(42, protocol::MAX_ZEN - 1_000_000)
+            .unwrap();
+        shop.top_up(13, 100, "order-2", 35).unwrap();
+        assert!(matches!(
+            runtime.purchase_cash_product(13, 42, 4, "buy-2", 40).await,
+            Err(CashShopError::Wallet(_))
+        ));
+        assert_eq!(shop.balance(13), 150);
+        assert_eq!(runtime.free_inventory_slots_for(42), INVENTORY_SLOTS - 1);
+
+        runtime.shutdown().await.unwrap();
     }
 
-    async fn detach_session_from_map(&self, session_id: u64) {
-        if let Some((_, (character_id, route))) = self.session_routes.remove(&session_id) {
-            let map = self
-                .map_servers
-                .get(&route)
-                .map(|entry| entry.value().clone());
+    #[tokio::test]
+    async fn ranking_leads_are_announced_when_they_change_hands() {
+        let runtime = build_runtime();
+        let gens = runtime.gens();
+        gens.join(1, GensFaction::Vanert, 10).unwrap();
+        gens.join(2, GensFaction::Vanert, 20).unwrap();
+        gens.join(3, GensFaction::Duprian, 30).unwrap();
+        assert_eq!(runtime.announce_ranking_leaders(), 0);
 
-            if let Some(map) = map {
-                let _ = map.leave(character_id).await;
-            }
+        // The first leader only sets the baseline.
+        gens.record_kill(1, 3);
+        assert_eq!(runtime.announce_ranking_leaders(), 0);
 
-            self.active_characters.remove(&character_id);
-        }
+        gens.record_kill(2, 3);
+        gens.record_kill(2, 3);
+        assert_eq!(runtime.announce_ranking_leaders(), 1);
+        assert_eq!(runtime.announce_ranking_leaders(), 0);
+
+        runtime.shutdown().await.unwrap();
     }
 
-    fn clear_pending_transfers(&self, session_id: u64) {
-        let transfer_ids: Vec<u64> = self
-            .pending_transfers
-            .iter()
-            .filter_map(|entry| {
-                if entry.value().session_id == session_id {
-                    Some(*entry.key())
-                } else {
-                    None
-                }
-            })
-            .collect();
+    #[tokio::test]
+    async fn offline_reward_is_mailed_and_claimed_after_entering_map() {
+        let runtime = build_runtime();
 
-        for transfer_id in transfer_ids {
-            self.pending_transfers.remove(&transfer_id);
+        let delivery = runtime
+            .grant_event_reward(
+                42,
+                RouteKey::LOBBY,
+                RewardSource::BloodCastle { level: 2 },
+                RewardBundle {
+                    zen: 50_000,
+                    items: Vec::new(),
+                },
+                90,
+            )
+            .await
+            .expect("valid reward");
+        let RewardDelivery::Mailed { mail_id } = delivery else {
+            panic!("offline character should receive mail");
+        };
+        assert_eq!(runtime.runtime_stats().await.pending_reward_mail, 1);
+
+        let _ = runtime
+            .handle_client_packet(build_hello_packet(&runtime, 21, 13, &[42]), 100)
+            .await
+            .expect("hello packet")
+            .expect("hello response");
+        let transfer = runtime
+            .handle_client_packet(
+                WirePacket::client(
+                    21,
+                    RouteKey::LOBBY,
+                    2,
+                    None,
+                    100,
+                    ClientMessage::SelectCharacter { character_id: 42 },
+                ),
+                100,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        let PacketPayload::Server(ServerMessage::MapTransfer(directive)) = transfer.payload else {
+            panic!("expected transfer");
+        };
+        let _ = runtime
+            .handle_client_packet(
+                WirePacket::client(
+                    21,
+                    RouteKey::LOBBY,
+                    3,
+                    None,
+                    105,
+                    ClientMessage::MapTransferAck {
+                        transfer_id: directive.transfer_id,
+                        route_token: directive.route_token,
+                    },
+                ),
+                105,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+
+        let mailbox = runtime
+            .handle_client_packet(
+                WirePacket::client(
+                    21,
+                    directive.route,
+                    4,
+                    None,
+                    110,
+                    ClientMessage::RequestMailbox,
+                ),
+                110,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        match mailbox.payload {
+            PacketPayload::Server(ServerMessage::Mailbox { entries }) => {
+                assert_eq!(entries.len(), 1);
+                assert_eq!(entries[0].mail_id, mail_id);
+                assert_eq!(entries[0].zen, 50_000);
+            }
+            _ => panic!("expected mailbox"),
         }
+
+        let claimed = runtime
+            .handle_client_packet(
+                WirePacket::client(
+                    21,
+                    directive.route,
+                    5,
+                    None,
+                    115,
+                    ClientMessage::ClaimMail { mail_id },
+                ),
+                115,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            claimed.payload,
+            PacketPayload::Server(ServerMessage::MailClaimed { mail_id: claimed_id }) if claimed_id == mail_id
+        ));
+        assert_eq!(runtime.runtime_stats().await.pending_reward_mail, 0);
+
+        runtime.shutdown().await.unwrap();
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::time::Duration;
+    async fn send_trade(
+        runtime: &MuCoreRuntime,
+        session_id: u64,
+        message: ClientMessage,
+    ) -> Option<ServerMessage> {
+        let route = runtime.session_routes.get(&session_id).unwrap().1;
+        let reply = runtime
+            .handle_client_packet(
+                WirePacket::client(session_id, route, 10, None, 200, message),
+                200,
+            )
+            .await
+            .unwrap()?;
+        match reply.payload {
+            PacketPayload::Server(message) => Some(message),
+            PacketPayload::Client(_) => panic!("expected a server reply"),
+        }
+    }
 
-    use crate::auth_token::{object_id_to_u64, AuthCharacterSummary, AuthTokenService};
-    use crate::session::SessionManager;
-    use mongodb::bson::oid::ObjectId;
-    use protocol::{ClientHello, QuicChannel};
+    fn next_trade_push(link: &mut SessionLink) -> ServerMessage {
+        while let Ok(command) = link.commands.try_recv() {
+            let SessionCommand::Send(packet) = command else {
+                continue;
+            };
+            if let PacketPayload::Server(
+                message @ (ServerMessage::TradeRequested { .. }
+                | ServerMessage::TradeOpened { .. }
+                | ServerMessage::TradeUpdated { .. }
+                | ServerMessage::TradeClosed { .. }),
+            ) = packet.payload
+            {
+                return message;
+            }
+        }
+        panic!("expected a trade message");
+    }
 
-    fn build_runtime() -> MuCoreRuntime {
+    #[tokio::test]
+    async fn confirmed_trade_swaps_items_and_zen_in_one_step() {
         let auth_tokens = AuthTokenService::new(
             b"01234567890123456789012345678901".to_vec(),
             Duration::from_secs(3600),
         )
         .expect("auth tokens");
+        let mut config = RuntimeConfig::default();
+        config.transfer_limits.zen_per_day = Some(900);
+        let runtime = MuCoreRuntime::bootstrap(config, auth_tokens, None).expect("runtime boot");
+        for (session_id, account_id, character_id) in [(61, 31, 610), (62, 32, 620)] {
+            runtime
+                .handle_client_packet(
+                    build_hello_packet(&runtime, session_id, account_id, &[character_id]),
+                    100,
+                )
+                .await
+                .unwrap();
+            enter_map(&runtime, session_id, character_id).await;
+        }
+        let mut seller = runtime.session_links().attach(61);
+        let mut buyer = runtime.session_links().attach(62);
+        let mut sword = protocol::ItemInstance {
+            serial: 0,
+            group: 0,
+            index: 3,
+            level: 7,
+            quantity: 1,
+            options: protocol::ItemOptions::default(),
+            expires_at_ms: None,
+        };
+        let serial =
+            runtime
+                .items
+                .mint(&mut sword, ItemHolder::Character { character_id: 610 }, 100);
+        runtime.wallets.credit(620, 1_000).unwrap();
+
+        let asked = send_trade(
+            &runtime,
+            61,
+            ClientMessage::RequestTrade {
+                target_entity_id: 620,
+            },
+        )
+        .await;
+        assert_eq!(asked, None);
+        assert!(matches!(
+            next_trade_push(&mut buyer),
+            ServerMessage::TradeRequested { from_entity_id: 610, ref name, level: 150 } if name == "Character-610"
+        ));
+        let opened = send_trade(&runtime, 62, ClientMessage::RespondTrade { accept: true }).await;
+        assert!(matches!(
+            opened,
+            Some(ServerMessage::TradeOpened {
+                partner_entity_id: 610,
+                ..
+            })
+        ));
+        assert!(matches!(
+            next_trade_push(&mut seller),
+            ServerMessage::TradeOpened {
+                partner_entity_id: 620,
+                ..
+            }
+        ));
 
-        MuCoreRuntime::bootstrap(RuntimeConfig::default(), auth_tokens, None).expect("runtime boot")
-    }
+        let short = send_trade(&runtime, 62, ClientMessage::SetTradeZen { zen: 5_000 }).await;
+        assert_eq!(
+            short,
+            Some(ServerMessage::TradeRejected {
+                reason: TradeFailure::NotEnoughZen
+            })
+        );
+        send_trade(&runtime, 62, ClientMessage::SetTradeZen { zen: 800 }).await;
+        let Some(ServerMessage::TradeUpdated { own, .. }) = send_trade(
+            &runtime,
+            61,
+            ClientMessage::AddTradeItem { serial, cell: 0 },
+        )
+        .await
+        else {
+            panic!("expected the offers");
+        };
+        assert_eq!(own.items[0].item.level, 7);
+        let ServerMessage::TradeUpdated { partner, .. } = next_trade_push(&mut buyer) else {
+            panic!("expected the offers");
+        };
+        assert_eq!(partner.items[0].item.serial, serial);
 
-    fn build_hello_packet(
-        runtime: &MuCoreRuntime,
-        session_id: u64,
-        account_id: u64,
-        character_ids: &[u64],
-    ) -> WirePacket {
-        let token = runtime
-            .auth_tokens
-            .issue_session_token(
-                account_id,
-                format!("session-{session_id}"),
-                character_ids
-                    .iter()
-                    .map(|character_id| AuthCharacterSummary {
-                        character_id: *character_id,
-                        db_id: format!("{:024x}", character_id),
-                        name: format!("Character-{character_id}"),
-                        class_id: 1,
-                        level: 150,
-                    })
-                    .collect(),
-                100,
+        for session_id in [61, 62] {
+            send_trade(
+                &runtime,
+                session_id,
+                ClientMessage::LockTrade { locked: true },
             )
-            .expect("issue auth token");
-
-        WirePacket::client(
-            session_id,
-            RouteKey::LOBBY,
-            1,
-            None,
-            100,
-            ClientMessage::Hello(ClientHello {
-                account_id,
-                auth_token: token,
-                client_build: "0.1.0".to_string(),
-                locale: "pt-BR".to_string(),
-            }),
+            .await;
+        }
+        assert_eq!(
+            send_trade(&runtime, 61, ClientMessage::ConfirmTrade).await,
+            None
+        );
+        let closed = send_trade(&runtime, 62, ClientMessage::ConfirmTrade).await;
+        assert_eq!(
+            closed,
+            Some(ServerMessage::TradeClosed {
+                outcome: TradeOutcome::Completed
+            })
+        );
+        assert_eq!(
+            runtime.items.owner(serial),
+            Some(ItemHolder::Character { character_id: 620 })
+        );
+        assert_eq!(
+            (runtime.wallets.balance(610), runtime.wallets.balance(620)),
+            (800, 200)
+        );
+        assert_eq!(runtime.transfer_limits.usage(32, 200).zen, 800);
+        assert_eq!(runtime.transfer_limits.usage(31, 200).items, 1);
+
+        // Another 200 zen would take the buyer's account past its daily cap.
+        send_trade(
+            &runtime,
+            61,
+            ClientMessage::RequestTrade {
+                target_entity_id: 620,
+            },
         )
+        .await;
+        send_trade(&runtime, 62, ClientMessage::RespondTrade { accept: true }).await;
+        send_trade(&runtime, 62, ClientMessage::SetTradeZen { zen: 200 }).await;
+        for session_id in [61, 62] {
+            send_trade(
+                &runtime,
+                session_id,
+                ClientMessage::LockTrade { locked: true },
+            )
+            .await;
+        }
+        send_trade(&runtime, 61, ClientMessage::ConfirmTrade).await;
+        assert_eq!(
+            send_trade(&runtime, 62, ClientMessage::ConfirmTrade).await,
+            Some(ServerMessage::TradeClosed {
+                outcome: TradeOutcome::Failed(TradeFailure::TransferLimit)
+            })
+        );
+        assert_eq!(runtime.wallets.balance(620), 200);
+
+        runtime.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn guild_war_ends_with_a_final_score_for_both_guilds() {
+        let runtime = build_runtime();
+        runtime
+            .guild_relations()
+            .declare(1, 3, GuildRelation::Alliance)
+            .unwrap();
+        // A member of guild 1 online on session 41.
+        runtime.guild_relations().set_membership(410, Some(1));
+        runtime.active_characters.insert(410, 41);
+        let mut attacker = runtime.session_links().attach(41);
+
+        let duration = crate::runtime::guild_wars::MIN_WAR_DURATION;
+        assert_eq!(
+            runtime.start_guild_war(3, 1, duration, 0),
+            Err(GuildWarError::Allied(3, 1))
+        );
+        let war = runtime.start_guild_war(1, 2, duration, 0).unwrap();
+        assert_eq!(runtime.end_expired_guild_wars(war.ends_at_ms - 1), 0);
+        assert_eq!(runtime.end_expired_guild_wars(war.ends_at_ms), 1);
+
+        let mut finished = Vec::new();
+        while let Ok(SessionCommand::Send(packet)) = attacker.commands.try_recv() {
+            if let PacketPayload::Server(ServerMessage::GuildWarScore(score)) = packet.payload {
+                finished.push(score.finished);
+            }
+        }
+        assert_eq!(finished, vec![false, true]);
+        assert!(runtime.guild_wars().active().is_empty());
+    }
+
+    #[tokio::test]
+    async fn planned_restart_warns_sessions_still_in_the_lobby() {
+        let runtime = build_runtime();
+        let mut link = runtime.session_links().attach(32);
+        let _ = runtime
+            .handle_client_packet(build_hello_packet(&runtime, 32, 18, &[320]), 100)
+            .await
+            .unwrap();
+
+        runtime
+            .schedule_restart(600_100, "patch".to_string(), 100)
+            .expect("restart planned");
+
+        let Ok(SessionCommand::Send(packet)) = link.commands.try_recv() else {
+            panic!("expected a pushed notice");
+        };
+        assert_eq!(packet.route, RouteKey::LOBBY);
+        let PacketPayload::Server(ServerMessage::Maintenance(notice)) = packet.payload else {
+            panic!("expected a maintenance notice");
+        };
+        assert_eq!(notice.map_id, None);
+        assert_eq!(notice.closes_at_ms, 600_100);
     }
 
     #[tokio::test]
-    async fn select_character_returns_map_transfer() {
+    async fn maintenance_migrates_present_players_and_blocks_new_entries() {
         let runtime = build_runtime();
+        let mut link = runtime.session_links().attach(31);
 
-        let hello = runtime
-            .handle_client_packet(build_hello_packet(&runtime, 7, 10, &[42]), 100)
+        let _ = runtime
+            .handle_client_packet(build_hello_packet(&runtime, 31, 17, &[310]), 100)
             .await
-            .expect("hello packet")
-            .expect("hello response");
-        assert!(matches!(
-            hello.payload,
-            PacketPayload::Server(ServerMessage::HelloAck { .. })
-        ));
-
-        let response = runtime
+            .unwrap();
+        let transfer = runtime
             .handle_client_packet(
                 WirePacket::client(
-                    7,
+                    31,
                     RouteKey::LOBBY,
-                    1,
+                    2,
                     None,
                     100,
-                    ClientMessage::SelectCharacter { character_id: 42 },
+                    ClientMessage::SelectCharacter { character_id: 310 },
                 ),
                 100,
             )
             .await
-            .expect("handle packet")
-            .expect("must respond");
+            .unwrap()
+            .unwrap();
+        let PacketPayload::Server(ServerMessage::MapTransfer(directive)) = transfer.payload else {
+            panic!("expected transfer");
+        };
+        let _ = runtime
+            .handle_client_packet(
+                WirePacket::client(
+                    31,
+                    RouteKey::LOBBY,
+                    3,
+                    None,
+                    105,
+                    ClientMessage::MapTransferAck {
+                        transfer_id: directive.transfer_id,
+                        route_token: directive.route_token,
+                    },
+                ),
+                105,
+            )
+            .await
+            .unwrap();
+
+        let lorencia = MaintenanceScope {
+            world_id: 1,
+            map_id: Some(0),
+        };
+        let whole_world = MaintenanceScope {
+            world_id: 1,
+            map_id: None,
+        };
+        assert_eq!(
+            runtime
+                .begin_maintenance(whole_world, "patch".to_string(), 0, 0, 110)
+                .await
+                .unwrap_err(),
+            MaintenanceError::NoFallbackTown(0)
+        );
+        runtime
+            .begin_maintenance(lorencia, "patch".to_string(), 0, 1, 110)
+            .await
+            .expect("maintenance scheduled");
+
+        let mut pushed = Vec::new();
+        while let Ok(SessionCommand::Send(packet)) = link.commands.try_recv() {
+            pushed.push(packet.payload);
+        }
+        match pushed.as_slice() {
+            [PacketPayload::Server(ServerMessage::Maintenance(notice)), PacketPayload::Server(ServerMessage::MapTransfer(directive))] =>
+            {
+                assert_eq!(notice.map_id, Some(0));
+                assert_eq!(directive.route.map_id, 1);
+            }
+            other => panic!("expected a notice and a transfer, got {other:?}"),
+        }
+        assert_eq!(runtime.character_for_session(31), None);
 
+        let _ = runtime
+            .handle_client_packet(build_hello_packet(&runtime, 32, 18, &[320]), 120)
+            .await
+            .unwrap();
+        let blocked = runtime
+            .handle_client_packet(
+                WirePacket::client(
+                    32,
+                    RouteKey::LOBBY,
+                    2,
+                    None,
+                    120,
+                    ClientMessage::SelectCharacter { character_id: 320 },
+                ),
+                120,
+            )
+            .await
+            .unwrap()
+            .unwrap();
         assert!(matches!(
-            response.payload,
-            PacketPayload::Server(ServerMessage::MapTransfer(_))
+            blocked.payload,
+            PacketPayload::Server(ServerMessage::Error(ServerError {
+                kind: ServerErrorKind::Maintenance,
+                ..
+            }))
         ));
 
+        assert!(runtime.end_maintenance(lorencia).is_some());
         runtime.shutdown().await.unwrap();
     }
 
     #[tokio::test]
-    async fn transfer_ack_enters_map() {
+    async fn account_settings_follow_the_account_across_sessions() {
         let runtime = build_runtime();
+        let settings_request = |session_id: u64, sequence: u32, message: ClientMessage| {
+            WirePacket::client(session_id, RouteKey::LOBBY, sequence, None, 100, message)
+        };
+        let settings_of = |packet: WirePacket| match packet.payload {
+            PacketPayload::Server(ServerMessage::AccountSettings { settings }) => settings,
+            other => panic!("expected account settings, got {other:?}"),
+        };
 
-        let _ = runtime
-            .handle_client_packet(build_hello_packet(&runtime, 9, 11, &[99]), 100)
-            .await
-            .expect("hello packet")
-            .expect("hello response");
+        for session_id in [61, 62] {
+            runtime
+                .handle_client_packet(build_hello_packet(&runtime, session_id, 600, &[]), 100)
+                .await
+                .unwrap()
+                .unwrap();
+        }
 
-        let transfer = runtime
+        let missing = runtime
             .handle_client_packet(
-                WirePacket::client(
-                    9,
-                    RouteKey::LOBBY,
-                    1,
-                    None,
-                    100,
-                    ClientMessage::SelectCharacter { character_id: 99 },
-                ),
+                settings_request(61, 2, ClientMessage::RequestAccountSettings),
                 100,
             )
             .await
             .unwrap()
             .unwrap();
+        assert_eq!(settings_of(missing), None);
 
-        let (transfer_id, route_token, _map_route) = match transfer.payload {
-            PacketPayload::Server(ServerMessage::MapTransfer(directive)) => (
-                directive.transfer_id,
-                directive.route_token,
-                directive.route,
-            ),
-            _ => panic!("expected transfer"),
+        let uploaded = AccountSettings {
+            updated_at_ms: 5_000,
+            yaml: "audio:\n  master_volume: 40\n".to_string(),
         };
+        runtime
+            .handle_client_packet(
+                settings_request(61, 3, ClientMessage::StoreAccountSettings(uploaded.clone())),
+                100,
+            )
+            .await
+            .unwrap()
+            .unwrap();
 
-        let enter = runtime
+        // A stale copy from the other machine loses and gets the newer one back.
+        let stale = runtime
             .handle_client_packet(
-                WirePacket::client(
-                    9,
-                    RouteKey::LOBBY,
+                settings_request(
+                    62,
                     2,
-                    None,
-                    110,
-                    ClientMessage::MapTransferAck {
-                        transfer_id,
-                        route_token,
-                    },
+                    ClientMessage::StoreAccountSettings(AccountSettings {
+                        updated_at_ms: 4_000,
+                        yaml: String::new(),
+                    }),
                 ),
-                110,
+                100,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(settings_of(stale), Some(uploaded.clone()));
+
+        let downloaded = runtime
+            .handle_client_packet(
+                settings_request(62, 3, ClientMessage::RequestAccountSettings),
+                100,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(settings_of(downloaded), Some(uploaded));
+
+        runtime.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn helper_follows_the_world_policy_and_is_listed_while_on() {
+        let runtime = build_runtime();
+        let helper_request = |sequence: u32, active: bool| {
+            WirePacket::client(
+                71,
+                RouteKey::LOBBY,
+                sequence,
+                None,
+                100,
+                ClientMessage::SetHelperActive { active },
+            )
+        };
+        let status_of = |packet: WirePacket| match packet.payload {
+            PacketPayload::Server(ServerMessage::HelperStatus { allowed, active }) => {
+                (allowed, active)
+            }
+            other => panic!("expected helper status, got {other:?}"),
+        };
+        runtime
+            .handle_client_packet(build_hello_packet(&runtime, 71, 700, &[]), 100)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let outside_map = runtime
+            .handle_client_packet(helper_request(2, true), 100)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            outside_map.payload,
+            PacketPayload::Server(ServerMessage::Error(ServerError {
+                kind: ServerErrorKind::NotInMap,
+                ..
+            }))
+        ));
+
+        let midgard = RouteKey {
+            world_id: 1,
+            entry_id: 1,
+            map_id: 0,
+            instance_id: 1,
+        };
+        runtime.session_routes.insert(71, (7, midgard));
+        let on = runtime
+            .handle_client_packet(helper_request(3, true), 200)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(status_of(on), (true, true));
+        assert_eq!(runtime.helpers().list()[0].since_ms, 200);
+        assert_eq!(runtime.runtime_stats().await.helper_sessions, 1);
+
+        // Leaving the map ends the helper; a world without the policy refuses it.
+        runtime.detach_session_from_map(71).await;
+        assert_eq!(runtime.helpers().count(), 0);
+        runtime.session_routes.insert(
+            71,
+            (
+                7,
+                RouteKey {
+                    world_id: 9,
+                    ..midgard
+                },
+            ),
+        );
+        let denied = runtime
+            .handle_client_packet(helper_request(4, true), 300)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(status_of(denied), (false, false));
+        assert_eq!(runtime.runtime_stats().await.helper_sessions, 0);
+
+        runtime.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn gens_membership_follows_the_world_flag() {
+        let mut config = RuntimeConfig::default();
+        config.worlds[0].gens = true;
+        let auth_tokens = AuthTokenService::new(
+            b"01234567890123456789012345678901".to_vec(),
+            Duration::from_secs(3600),
+        )
+        .expect("auth tokens");
+        let runtime = MuCoreRuntime::bootstrap(config, auth_tokens, None).expect("runtime boot");
+        let join_request = |sequence: u32, faction: GensFaction| {
+            WirePacket::client(
+                81,
+                RouteKey::LOBBY,
+                sequence,
+                None,
+                100,
+                ClientMessage::JoinGens { faction },
             )
+        };
+        runtime
+            .handle_client_packet(build_hello_packet(&runtime, 81, 800, &[]), 100)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let gens_world = RouteKey {
+            world_id: 1,
+            entry_id: 1,
+            map_id: 0,
+            instance_id: 1,
+        };
+        runtime.session_routes.insert(81, (8, gens_world));
+        let joined = runtime
+            .handle_client_packet(join_request(2, GensFaction::Vanert), 200)
             .await
             .unwrap()
             .unwrap();
+        assert_eq!(
+            joined.payload,
+            PacketPayload::Server(ServerMessage::GensStatus(GensStatus {
+                enabled: true,
+                faction: Some(GensFaction::Vanert),
+                contribution: 0,
+            }))
+        );
+        assert_eq!(
+            runtime.gens_state_flags(1, 8),
+            EntityDelta::FLAG_GENS_VANERT
+        );
 
+        let again = runtime
+            .handle_client_packet(join_request(3, GensFaction::Duprian), 300)
+            .await
+            .unwrap()
+            .unwrap();
         assert!(matches!(
-            enter.payload,
-            PacketPayload::Server(ServerMessage::EnterMap { .. })
+            again.payload,
+            PacketPayload::Server(ServerMessage::Error(ServerError {
+                kind: ServerErrorKind::InvalidAction,
+                ..
+            }))
+        ));
+
+        // Classic worlds neither register nor show the faction.
+        runtime.session_routes.insert(
+            81,
+            (
+                8,
+                RouteKey {
+                    world_id: 9,
+                    ..gens_world
+                },
+            ),
+        );
+        let classic = runtime
+            .handle_client_packet(join_request(4, GensFaction::Duprian), 400)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            classic.payload,
+            PacketPayload::Server(ServerMessage::GensStatus(GensStatus {
+                enabled: false,
+                faction: None,
+                contribution: 0,
+            }))
+        );
+        assert_eq!(runtime.gens_state_flags(9, 8), 0);
+        assert_eq!(runtime.gens().faction_of(8), Some(GensFaction::Vanert));
+
+        runtime.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn early_casts_are_rejected_with_a_cooldown_error() {
+        let runtime = build_runtime();
+        let midgard = RouteKey {
+            world_id: 1,
+            entry_id: 1,
+            map_id: 0,
+            instance_id: 1,
+        };
+        let cast = |sequence: u32| {
+            WirePacket::client(
+                91,
+                midgard,
+                sequence,
+                None,
+                100,
+                ClientMessage::UseSkill(UseSkillInput {
+                    client_tick: sequence,
+                    skill_id: 1,
+                    target_entity_id: None,
+                    target_x: 10,
+                    target_y: 10,
+                }),
+            )
+        };
+        let is_cooldown = |packet: Option<WirePacket>| {
+            matches!(
+                packet.map(|packet| packet.payload),
+                Some(PacketPayload::Server(ServerMessage::Error(ServerError {
+                    kind: ServerErrorKind::SkillCooldown,
+                    retry_after_ms: Some(_),
+                    ..
+                })))
+            )
+        };
+        runtime
+            .handle_client_packet(build_hello_packet(&runtime, 91, 900, &[]), 100)
+            .await
+            .unwrap()
+            .unwrap();
+        runtime.session_routes.insert(91, (9, midgard));
+
+        let global = runtime.config().combat.global_cooldown_ms;
+        assert!(!is_cooldown(
+            runtime.handle_client_packet(cast(2), 1_000).await.unwrap()
+        ));
+        assert!(is_cooldown(
+            runtime
+                .handle_client_packet(cast(3), 1_000 + 1)
+                .await
+                .unwrap()
+        ));
+        assert!(!is_cooldown(
+            runtime
+                .handle_client_packet(cast(4), 1_000 + global)
+                .await
+                .unwrap()
         ));
 
         runtime.shutdown().await.unwrap();
@@ -1162,10 +5602,10 @@ mod tests {
 
         assert!(matches!(
             enter.payload,
-            PacketPayload::Server(ServerMessage::Error {
+            PacketPayload::Server(ServerMessage::Error(ServerError {
                 kind: ServerErrorKind::InvalidSession,
                 ..
-            })
+            }))
         ));
 
         runtime.shutdown().await.unwrap();
@@ -1188,6 +5628,7 @@ mod tests {
         let token = auth_tokens
             .issue_session_token(
                 100,
+                AccountRole::Player,
                 "missing-http-session".to_string(),
                 vec![AuthCharacterSummary {
                     character_id: 1,
@@ -1195,6 +5636,7 @@ mod tests {
                     name: "Character-1".to_string(),
                     class_id: 1,
                     level: 150,
+                    guild_id: None,
                 }],
                 100,
             )
@@ -1213,6 +5655,7 @@ mod tests {
                         auth_token: token,
                         client_build: "0.1.0".to_string(),
                         locale: "pt-BR".to_string(),
+                        capabilities: ProtocolCapabilities::NONE,
                     }),
                 ),
                 100,
@@ -1223,10 +5666,10 @@ mod tests {
 
         assert!(matches!(
             hello.payload,
-            PacketPayload::Server(ServerMessage::Error {
+            PacketPayload::Server(ServerMessage::Error(ServerError {
                 kind: ServerErrorKind::InvalidSession,
                 ..
-            })
+            }))
         ));
 
         runtime.shutdown().await.unwrap();
@@ -1255,6 +5698,7 @@ mod tests {
         let token = auth_tokens
             .issue_session_token(
                 object_id_to_u64(&account_id),
+                AccountRole::Player,
                 http_session.session_id,
                 vec![AuthCharacterSummary {
                     character_id: 1,
@@ -1262,6 +5706,7 @@ mod tests {
                     name: "Character-1".to_string(),
                     class_id: 1,
                     level: 150,
+                    guild_id: None,
                 }],
                 100,
             )
@@ -1280,6 +5725,7 @@ mod tests {
                         auth_token: token,
                         client_build: "0.1.0".to_string(),
                         locale: "pt-BR".to_string(),
+                        capabilities: ProtocolCapabilities::NONE,
                     }),
                 ),
                 100,
@@ -1398,8 +5844,9 @@ mod tests {
             .encode_datagram_frame(QuicChannel::GameplayInput, &move_packet)
             .expect("encode datagram");
 
+        let mut sequencer = SequencedReceiver::new();
         let response = runtime
-            .handle_datagram_frame(&datagram, 200)
+            .handle_datagram_frame(&datagram, &mut sequencer, 200)
             .await
             .expect("dispatch datagram");
 
@@ -1408,6 +5855,113 @@ mod tests {
             Some(PacketPayload::Server(ServerMessage::StateDelta { .. }))
         ));
 
+        let stale = WirePacket {
+            sequence: 3,
+            ..move_packet.clone()
+        };
+        for packet in [&move_packet, &stale] {
+            let datagram = codec
+                .encode_datagram_frame(QuicChannel::GameplayInput, packet)
+                .expect("encode datagram");
+            let response = runtime
+                .handle_datagram_frame(&datagram, &mut sequencer, 210)
+                .await
+                .expect("dispatch datagram");
+            assert!(response.is_none());
+        }
+        assert_eq!(sequencer.stats().duplicates, 1);
+        assert_eq!(sequencer.stats().stale, 1);
+
+        runtime.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn walking_into_a_gate_transfers_to_its_target_map() {
+        let mut config = RuntimeConfig::default();
+        let maps = &mut config.worlds[0].entry_points[0].maps;
+        maps[0].id = WorldMap::Dungeon as u16;
+        maps[1].id = WorldMap::Lorencia as u16;
+        config.starting_kit.default.map_id = Some(WorldMap::Lorencia as u16);
+        let auth_tokens = AuthTokenService::new(
+            b"01234567890123456789012345678901".to_vec(),
+            Duration::from_secs(3600),
+        )
+        .expect("auth tokens");
+        let runtime = MuCoreRuntime::bootstrap(config, auth_tokens, None).expect("runtime boot");
+        runtime
+            .handle_client_packet(build_hello_packet(&runtime, 71, 31, &[710]), 100)
+            .await
+            .unwrap();
+        enter_map(&runtime, 71, 710).await;
+        let lorencia = runtime.route_of_character(710).expect("on Lorencia");
+        assert_eq!(lorencia.map_id, WorldMap::Lorencia as u16);
+
+        let step = |x, y| {
+            WirePacket::client(
+                71,
+                lorencia,
+                4,
+                None,
+                300,
+                ClientMessage::Move(protocol::MoveInput {
+                    client_tick: 1,
+                    x,
+                    y,
+                    direction: 0,
+                    path: [0; 8],
+                }),
+            )
+        };
+        let walk = runtime
+            .handle_client_packet(step(130, 130), 300)
+            .await
+            .unwrap();
+        assert!(matches!(
+            walk.map(|packet| packet.payload),
+            Some(PacketPayload::Server(ServerMessage::StateDelta { .. }))
+        ));
+
+        let gate = runtime
+            .handle_client_packet(step(122, 232), 310)
+            .await
+            .unwrap()
+            .unwrap();
+        let PacketPayload::Server(ServerMessage::MapTransfer(directive)) = gate.payload else {
+            panic!("expected transfer");
+        };
+        assert_eq!(directive.route.map_id, WorldMap::Dungeon as u16);
+        assert_eq!(runtime.route_of_character(710), None);
+
+        let entered = runtime
+            .handle_client_packet(
+                WirePacket::client(
+                    71,
+                    RouteKey::LOBBY,
+                    5,
+                    None,
+                    320,
+                    ClientMessage::MapTransferAck {
+                        transfer_id: directive.transfer_id,
+                        route_token: directive.route_token,
+                    },
+                ),
+                320,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            entered.payload,
+            PacketPayload::Server(ServerMessage::EnterMap { x: 108, y: 247, .. })
+        ));
+        assert_eq!(runtime.route_of_character(710), Some(directive.route));
+        let dungeon = runtime
+            .map_servers
+            .get(&directive.route)
+            .map(|entry| entry.value().clone())
+            .unwrap();
+        assert_eq!(dungeon.position(710).await, Some((108, 247)));
+
         runtime.shutdown().await.unwrap();
     }
 }
@@ -4,6 +4,7 @@ use std::sync::Arc;
 use dashmap::DashMap;
 use protocol::RouteKey;
 use serde::Serialize;
+use utoipa::ToSchema;
 
 use super::config::RuntimeConfig;
 
@@ -28,14 +29,14 @@ pub struct WorldDirectorySnapshot {
     pub worlds: Vec<WorldSnapshot>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct WorldSnapshot {
     pub world_id: u16,
     pub world_name: String,
     pub entries: Vec<EntrySnapshot>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct EntrySnapshot {
     pub entry_id: u16,
     pub entry_name: String,
@@ -46,7 +47,7 @@ pub struct EntrySnapshot {
     pub maps: Vec<MapSnapshot>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct MapSnapshot {
     pub map_id: u16,
     pub map_name: String,
@@ -157,6 +158,18 @@ impl WorldDirectory {
         })
     }
 
+    /// A specific entry point (shard) of a world, whether or not it is full.
+    pub fn entry(&self, world_id: u16, entry_id: u16) -> Option<EntryPointRoute> {
+        let entry = self.entry_static.get(&(world_id, entry_id))?;
+        Some(EntryPointRoute {
+            world_id,
+            entry_id,
+            host: entry.host.clone(),
+            port: entry.port,
+            max_players: entry.max_players,
+        })
+    }
+
     pub fn select_best_map_instance(
         &self,
         world_id: u16,
@@ -250,6 +263,10 @@ impl WorldDirectory {
         self.route_players.insert(route, 0).is_none()
     }
 
+    pub fn remove_instance_route(&self, route: RouteKey) {
+        self.route_players.remove(&route);
+    }
+
     pub fn snapshot(&self) -> WorldDirectorySnapshot {
         let mut grouped: HashMap<u16, WorldSnapshot> = HashMap::new();
 
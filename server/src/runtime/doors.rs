@@ -0,0 +1,241 @@
+//! Doors and gates whose state the server owns.
+//!
+//! Devias gates, event doors and Castle Siege gates are listed per map under
+//! `doors` in `config/runtime.toml`. A closed door blocks its tiles for
+//! movement; an open or destroyed one lets players through. Destructible
+//! gates lose hit points to skills aimed at their entity id and stay broken
+//! until they are closed again.
+
+use std::sync::Arc;
+
+use common::collision::CollisionGrid;
+use protocol::{DoorState, DoorStatus, RouteKey, SequenceEvent};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::config::DoorConfig;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DoorError {
+    #[error("map {map_id} of world {world_id} is not running")]
+    UnknownMap { world_id: u16, map_id: u16 },
+    #[error("map {map_id} has no door {door_id}")]
+    UnknownDoor { map_id: u16, door_id: u16 },
+    #[error("map server stopped")]
+    MapStopped,
+}
+
+/// A door on one map instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub struct MapDoor {
+    pub route: RouteKey,
+    pub door: DoorStatus,
+}
+
+#[derive(Debug, Clone)]
+struct Door {
+    config: DoorConfig,
+    state: DoorState,
+    hp: Option<u32>,
+}
+
+impl Door {
+    fn status(&self) -> DoorStatus {
+        DoorStatus {
+            door_id: self.config.id,
+            state: self.state,
+            hp: self.hp,
+        }
+    }
+}
+
+/// Doors of one map instance and the collision grid they leave.
+///
+/// Owned by the map server task, so no locking is needed.
+#[derive(Debug, Clone)]
+pub struct MapDoors {
+    base: Arc<CollisionGrid>,
+    grid: Arc<CollisionGrid>,
+    doors: Vec<Door>,
+}
+
+impl MapDoors {
+    pub fn new(base: Arc<CollisionGrid>, configs: &[DoorConfig]) -> Self {
+        let doors = configs
+            .iter()
+            .map(|config| Door {
+                config: config.clone(),
+                state: config.state,
+                hp: config.hp,
+            })
+            .collect();
+        let mut doors = Self {
+            grid: base.clone(),
+            base,
+            doors,
+        };
+        doors.rebuild_grid();
+        doors
+    }
+
+    /// Object collision plus the tiles of every closed door.
+    pub fn grid(&self) -> &Arc<CollisionGrid> {
+        &self.grid
+    }
+
+    pub fn statuses(&self) -> Vec<DoorStatus> {
+        self.doors.iter().map(Door::status).collect()
+    }
+
+    /// Puts a door in `state`; a destroyed gate put in any other state is
+    /// rebuilt with full hit points. Returns `None` for unknown doors and the
+    /// status otherwise, whether or not it changed.
+    pub fn set_state(&mut self, door_id: u16, state: DoorState) -> Option<DoorStatus> {
+        let door = self
+            .doors
+            .iter_mut()
+            .find(|door| door.config.id == door_id)?;
+        if door.state == DoorState::Destroyed && state != DoorState::Destroyed {
+            door.hp = door.config.hp;
+        }
+        if state == DoorState::Destroyed {
+            door.hp = door.hp.map(|_| 0);
+        }
+        let blocked_before = door.state.blocks();
+        door.state = state;
+        let status = door.status();
+        if blocked_before != state.blocks() {
+            self.rebuild_grid();
+        }
+        Some(status)
+    }
+
+    /// Hits a closed destructible gate. Returns the new status, or `None`
+    /// when the door cannot take damage.
+    pub fn damage(&mut self, door_id: u16, amount: u32) -> Option<DoorStatus> {
+        let door = self
+            .doors
+            .iter_mut()
+            .find(|door| door.config.id == door_id && door.state == DoorState::Closed)?;
+        let hp = door.hp.as_mut()?;
+        *hp = hp.saturating_sub(amount);
+        if *hp > 0 {
+            return Some(door.status());
+        }
+        door.state = DoorState::Destroyed;
+        log::info!("{} destroyed", door.config.name);
+        let status = door.status();
+        self.rebuild_grid();
+        Some(status)
+    }
+
+    /// Opens or closes the doors following `event`. Returns the doors that
+    /// changed.
+    pub fn follow_event(&mut self, event: SequenceEvent, open: bool) -> Vec<DoorStatus> {
+        let state = if open {
+            DoorState::Open
+        } else {
+            DoorState::Closed
+        };
+        let door_ids: Vec<u16> = self
+            .doors
+            .iter()
+            .filter(|door| door.config.event == Some(event) && door.state != state)
+            .map(|door| door.config.id)
+            .collect();
+        door_ids
+            .into_iter()
+            .filter_map(|door_id| self.set_state(door_id, state))
+            .collect()
+    }
+
+    fn rebuild_grid(&mut self) {
+        let closed: Vec<&Door> = self
+            .doors
+            .iter()
+            .filter(|door| door.state.blocks())
+            .collect();
+        if closed.is_empty() {
+            self.grid = self.base.clone();
+            return;
+        }
+        let mut grid = (*self.base).clone();
+        for door in closed {
+            for (x, y) in door.config.tiles() {
+                grid.block(x, y);
+            }
+        }
+        self.grid = Arc::new(grid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn door(id: u16, x: u16, hp: Option<u32>, event: Option<SequenceEvent>) -> DoorConfig {
+        DoorConfig {
+            id,
+            name: format!("Door {id}"),
+            x,
+            y: 10,
+            width: 2,
+            height: 1,
+            state: DoorState::Closed,
+            hp,
+            event,
+        }
+    }
+
+    fn doors() -> MapDoors {
+        MapDoors::new(
+            Arc::new(CollisionGrid::open()),
+            &[
+                door(1, 10, None, None),
+                door(2, 20, Some(100), None),
+                door(3, 30, None, Some(SequenceEvent::Kanturu)),
+            ],
+        )
+    }
+
+    #[test]
+    fn closed_doors_block_their_tiles() {
+        let mut doors = doors();
+        assert!(doors.grid().is_blocked(10, 10) && doors.grid().is_blocked(11, 10));
+        assert!(!doors.grid().is_blocked(12, 10));
+
+        let opened = doors.set_state(1, DoorState::Open).unwrap();
+        assert_eq!(opened.state, DoorState::Open);
+        assert!(!doors.grid().is_blocked(10, 10));
+        assert!(doors.grid().is_blocked(20, 10));
+        assert_eq!(doors.set_state(9, DoorState::Open), None);
+    }
+
+    #[test]
+    fn gates_break_and_are_rebuilt_by_closing() {
+        let mut doors = doors();
+        assert_eq!(doors.damage(1, 50), None);
+        assert_eq!(doors.damage(2, 60).unwrap().hp, Some(40));
+
+        let broken = doors.damage(2, 60).unwrap();
+        assert_eq!((broken.state, broken.hp), (DoorState::Destroyed, Some(0)));
+        assert!(!doors.grid().is_blocked(20, 10));
+        assert_eq!(doors.damage(2, 10), None);
+
+        let rebuilt = doors.set_state(2, DoorState::Closed).unwrap();
+        assert_eq!(rebuilt.hp, Some(100));
+        assert!(doors.grid().is_blocked(20, 10));
+    }
+
+    #[test]
+    fn event_doors_follow_the_gate() {
+        let mut doors = doors();
+        assert!(doors.follow_event(SequenceEvent::Crywolf, true).is_empty());
+        let changed = doors.follow_event(SequenceEvent::Kanturu, true);
+        assert_eq!(changed.len(), 1);
+        assert_eq!((changed[0].door_id, changed[0].state), (3, DoorState::Open));
+        assert!(!doors.grid().is_blocked(30, 10));
+        // Already open: nothing to announce.
+        assert!(doors.follow_event(SequenceEvent::Kanturu, true).is_empty());
+    }
+}
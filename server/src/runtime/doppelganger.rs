@@ -0,0 +1,440 @@
+//! Doppelganger: a party's private run through waves of monsters.
+//!
+//! A party of up to five presents a Mirror of Dimensions and gets its own
+//! instance of one of the four Doppelganger zones, taken in turn. Every member
+//! is mirrored by an allied NPC of the same level. Each of the [`WAVES`] timed
+//! waves brings more monsters than the last; the run is lost once no member is
+//! left inside. Members still inside at the end are rewarded by the waves
+//! cleared and the size of the party.
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::WorldMap;
+use dashmap::DashMap;
+use protocol::{DoppelgangerStatus, ItemInstance, ItemOptions, MirrorAlly, RouteKey};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::mailbox::RewardBundle;
+
+pub const MAX_PARTY_SIZE: usize = common::party::MAX_PARTY_MEMBERS;
+pub const WAVES: u8 = 5;
+pub const WAVE_DURATION: Duration = Duration::from_secs(3 * 60);
+/// Monsters of a wave leave just before its timer, so the next wave can spawn.
+pub const WAVE_MONSTER_LIFETIME: Duration = Duration::from_secs(WAVE_DURATION.as_secs() - 1);
+/// Time the party has to reach the instance before an empty one is lost.
+pub const ENTRY_GRACE: Duration = Duration::from_secs(30);
+/// Longest a run lasts, every wave played to its timer.
+pub const RUN_DURATION: Duration = Duration::from_secs(WAVES as u64 * WAVE_DURATION.as_secs());
+/// Mirror of Dimensions, the entry ticket.
+pub const TICKET_ITEM: (u8, u16) = (14, 111);
+/// Where members still inside are sent when the run ends.
+pub const RETURN_MAP: WorldMap = WorldMap::Lorencia;
+pub const ZONES: [WorldMap; 4] = [
+    WorldMap::DoppelgangerIceZone,
+    WorldMap::DoppelgangerBlazeZone,
+    WorldMap::DoppelgangerUnderwater,
+    WorldMap::DoppelgangerCrystalCave,
+];
+
+const MONSTERS_PER_WAVE_AND_MEMBER: u32 = 10;
+const ZEN_PER_WAVE: u64 = 50_000;
+/// Extra zen per member beyond the first, in percent.
+const PARTY_BONUS_PERCENT: u64 = 10;
+/// Jewel of Bless, given for clearing every wave.
+const CLEAR_REWARD_ITEM: (u8, u16) = (14, 13);
+/// Entity id of the first mirrored ally, below the synthetic monsters.
+const MIRROR_ENTITY_ID_BASE: u32 = 0x3000_0000;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DoppelgangerError {
+    #[error("party must have 1 to {MAX_PARTY_SIZE} members")]
+    InvalidPartySize,
+    #[error("character {0} is listed twice")]
+    DuplicateMember(u64),
+    #[error(
+        "entry needs a Mirror of Dimensions (item {group}:{index})",
+        group = TICKET_ITEM.0,
+        index = TICKET_ITEM.1
+    )]
+    NotATicket,
+    #[error("character {0} is already in a Doppelganger run")]
+    AlreadyInRun(u64),
+    #[error("character {0} is not online")]
+    Offline(u64),
+    #[error("world {0} has no Doppelganger zone")]
+    NoZone(u16),
+    #[error("the server restarts before the run would end")]
+    RestartPending,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct PartyMember {
+    pub character_id: u64,
+    pub level: u16,
+}
+
+/// Zones tried for a run, starting one further along for every run.
+pub fn zone_rotation(run_id: u32) -> impl Iterator<Item = u16> {
+    let start = run_id as usize % ZONES.len();
+    ZONES
+        .iter()
+        .cycle()
+        .skip(start)
+        .take(ZONES.len())
+        .map(|zone| *zone as u16)
+}
+
+/// Party run on its own zone instance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct DoppelgangerRun {
+    pub run_id: u32,
+    pub route: RouteKey,
+    pub party: Vec<PartyMember>,
+    pub allies: Vec<MirrorAlly>,
+    /// Current wave, from 1.
+    pub wave: u8,
+    pub started_at_ms: u64,
+    pub wave_ends_at_ms: u64,
+    /// Set once the run ends: whether the party survived every wave.
+    pub outcome: Option<bool>,
+}
+
+impl DoppelgangerRun {
+    fn new(run_id: u32, route: RouteKey, party: Vec<PartyMember>, now_ms: u64) -> Self {
+        let allies = party
+            .iter()
+            .enumerate()
+            .map(|(slot, member)| MirrorAlly {
+                entity_id: MIRROR_ENTITY_ID_BASE
+                    + run_id.wrapping_mul(MAX_PARTY_SIZE as u32)
+                    + slot as u32,
+                character_id: member.character_id,
+                level: member.level,
+            })
+            .collect();
+
+        Self {
+            run_id,
+            route,
+            party,
+            allies,
+            wave: 1,
+            started_at_ms: now_ms,
+            wave_ends_at_ms: now_ms + WAVE_DURATION.as_millis() as u64,
+            outcome: None,
+        }
+    }
+
+    /// Monsters of the current wave, scaled by the party size.
+    pub fn wave_monsters(&self) -> u32 {
+        MONSTERS_PER_WAVE_AND_MEMBER * self.wave as u32 * self.party.len() as u32
+    }
+
+    pub fn waves_cleared(&self) -> u8 {
+        match self.outcome {
+            Some(true) => WAVES,
+            _ => self.wave - 1,
+        }
+    }
+
+    /// Reward of every member still inside at the end.
+    pub fn reward(&self) -> RewardBundle {
+        let bonus = 100 + PARTY_BONUS_PERCENT * (self.party.len() as u64 - 1);
+        let mut reward = RewardBundle {
+            zen: ZEN_PER_WAVE * self.waves_cleared() as u64 * bonus / 100,
+            items: Vec::new(),
+        };
+        if self.outcome == Some(true) {
+            reward.items.push(ItemInstance {
+                serial: 0,
+                group: CLEAR_REWARD_ITEM.0,
+                index: CLEAR_REWARD_ITEM.1,
+                level: 0,
+                quantity: 1,
+                options: ItemOptions::default(),
+                expires_at_ms: None,
+            });
+        }
+        reward
+    }
+
+    pub fn status(&self) -> DoppelgangerStatus {
+        DoppelgangerStatus {
+            run_id: self.run_id,
+            zone_map_id: self.route.map_id,
+            wave: self.wave,
+            waves: WAVES,
+            wave_ends_at_ms: self.wave_ends_at_ms,
+            allies: self.allies.clone(),
+            outcome: self.outcome,
+        }
+    }
+
+    /// Starts the next wave or ends the run; `occupied` tells whether any
+    /// member is inside. Returns whether anything changed.
+    fn tick(&mut self, now_ms: u64, occupied: bool) -> bool {
+        if !occupied && now_ms >= self.started_at_ms + ENTRY_GRACE.as_millis() as u64 {
+            self.outcome = Some(false);
+            return true;
+        }
+        if now_ms < self.wave_ends_at_ms {
+            return false;
+        }
+
+        if self.wave >= WAVES {
+            self.outcome = Some(true);
+        } else {
+            self.wave += 1;
+            self.wave_ends_at_ms = now_ms + WAVE_DURATION.as_millis() as u64;
+        }
+        true
+    }
+}
+
+/// Runs in progress, shared by the admin API and the core.
+#[derive(Clone)]
+pub struct DoppelgangerRuns {
+    // key: run_id
+    runs: Arc<DashMap<u32, DoppelgangerRun>>,
+    // key: character_id, value: run_id
+    members: Arc<DashMap<u64, u32>>,
+    next_run_id: Arc<AtomicU32>,
+}
+
+impl Default for DoppelgangerRuns {
+    fn default() -> Self {
+        Self {
+            runs: Arc::new(DashMap::new()),
+            members: Arc::new(DashMap::new()),
+            next_run_id: Arc::new(AtomicU32::new(1)),
+        }
+    }
+}
+
+impl DoppelgangerRuns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks the party and the ticket before a zone is opened for them.
+    pub fn check_entry(
+        &self,
+        party: &[PartyMember],
+        ticket: &ItemInstance,
+    ) -> Result<(), DoppelgangerError> {
+        if party.is_empty() || party.len() > MAX_PARTY_SIZE {
+            return Err(DoppelgangerError::InvalidPartySize);
+        }
+        if (ticket.group, ticket.index) != TICKET_ITEM || ticket.quantity == 0 {
+            return Err(DoppelgangerError::NotATicket);
+        }
+
+        let mut seen = HashSet::new();
+        for member in party {
+            if !seen.insert(member.character_id) {
+                return Err(DoppelgangerError::DuplicateMember(member.character_id));
+            }
+            if self.members.contains_key(&member.character_id) {
+                return Err(DoppelgangerError::AlreadyInRun(member.character_id));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn next_run_id(&self) -> u32 {
+        self.next_run_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Starts the first wave of a run on `route`.
+    pub fn open(
+        &self,
+        run_id: u32,
+        route: RouteKey,
+        party: Vec<PartyMember>,
+        now_ms: u64,
+    ) -> Result<DoppelgangerRun, DoppelgangerError> {
+        for (claimed, member) in party.iter().enumerate() {
+            if let Some(other_run) = self.members.insert(member.character_id, run_id) {
+                // Joined another run since the check; undo this party's claims.
+                self.members.insert(member.character_id, other_run);
+                for member in &party[..claimed] {
+                    self.members.remove(&member.character_id);
+                }
+                return Err(DoppelgangerError::AlreadyInRun(member.character_id));
+            }
+        }
+
+        let run = DoppelgangerRun::new(run_id, route, party, now_ms);
+        self.runs.insert(run_id, run.clone());
+        Ok(run)
+    }
+
+    pub fn runs(&self) -> Vec<DoppelgangerRun> {
+        let mut runs: Vec<DoppelgangerRun> = self
+            .runs
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect();
+        runs.sort_by_key(|run| run.run_id);
+        runs
+    }
+
+    /// Moves every run along its wave timer and returns the ones that changed.
+    /// Ended runs are dropped, freeing their members for another run.
+    pub fn tick(
+        &self,
+        now_ms: u64,
+        occupied: impl Fn(&DoppelgangerRun) -> bool,
+    ) -> Vec<DoppelgangerRun> {
+        let mut changed = Vec::new();
+        for mut entry in self.runs.iter_mut() {
+            let inside = occupied(entry.value());
+            if entry.value_mut().tick(now_ms, inside) {
+                changed.push(entry.value().clone());
+            }
+        }
+
+        for run in changed.iter().filter(|run| run.outcome.is_some()) {
+            self.runs.remove(&run.run_id);
+            for member in &run.party {
+                self.members.remove(&member.character_id);
+            }
+        }
+        changed.sort_by_key(|run| run.run_id);
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WAVE_MS: u64 = WAVE_DURATION.as_millis() as u64;
+
+    fn ticket() -> ItemInstance {
+        ItemInstance {
+            serial: 0,
+            group: TICKET_ITEM.0,
+            index: TICKET_ITEM.1,
+            level: 0,
+            quantity: 1,
+            options: ItemOptions::default(),
+            expires_at_ms: None,
+        }
+    }
+
+    fn party(ids: &[u64]) -> Vec<PartyMember> {
+        ids.iter()
+            .map(|&character_id| PartyMember {
+                character_id,
+                level: 200,
+            })
+            .collect()
+    }
+
+    fn route(map_id: u16) -> RouteKey {
+        RouteKey {
+            world_id: 1,
+            entry_id: 1,
+            map_id,
+            instance_id: 1,
+        }
+    }
+
+    #[test]
+    fn entry_needs_a_ticket_and_a_free_party() {
+        let runs = DoppelgangerRuns::new();
+        assert_eq!(
+            runs.check_entry(&[], &ticket()),
+            Err(DoppelgangerError::InvalidPartySize)
+        );
+        assert_eq!(
+            runs.check_entry(&party(&[1, 2, 3, 4, 5, 6]), &ticket()),
+            Err(DoppelgangerError::InvalidPartySize)
+        );
+        assert_eq!(
+            runs.check_entry(&party(&[1, 2, 1]), &ticket()),
+            Err(DoppelgangerError::DuplicateMember(1))
+        );
+        let mut jewel = ticket();
+        jewel.index = 13;
+        assert_eq!(
+            runs.check_entry(&party(&[1]), &jewel),
+            Err(DoppelgangerError::NotATicket)
+        );
+
+        let run = runs
+            .open(runs.next_run_id(), route(66), party(&[1, 2]), 0)
+            .unwrap();
+        assert_eq!(run.allies.len(), 2);
+        assert_ne!(run.allies[0].entity_id, run.allies[1].entity_id);
+        assert_eq!(
+            runs.check_entry(&party(&[3, 2]), &ticket()),
+            Err(DoppelgangerError::AlreadyInRun(2))
+        );
+        assert_eq!(
+            runs.open(runs.next_run_id(), route(67), party(&[3, 2]), 0),
+            Err(DoppelgangerError::AlreadyInRun(2))
+        );
+        // The failed claim left member 3 free.
+        assert_eq!(runs.check_entry(&party(&[3]), &ticket()), Ok(()));
+    }
+
+    #[test]
+    fn surviving_every_wave_wins_a_reward_scaled_by_the_party() {
+        let runs = DoppelgangerRuns::new();
+        let solo = runs
+            .open(runs.next_run_id(), route(66), party(&[1]), 0)
+            .unwrap();
+        runs.open(runs.next_run_id(), route(67), party(&[2, 3, 4]), 0)
+            .unwrap();
+        assert_eq!(solo.wave_monsters(), MONSTERS_PER_WAVE_AND_MEMBER);
+
+        assert!(runs.tick(WAVE_MS - 1, |_| true).is_empty());
+        let mut now = 0;
+        for wave in 2..=WAVES {
+            now += WAVE_MS;
+            let changed = runs.tick(now, |_| true);
+            assert_eq!(changed.len(), 2);
+            assert_eq!(changed[0].wave, wave);
+            assert_eq!(changed[1].wave_monsters(), 30 * wave as u32);
+        }
+
+        now += WAVE_MS;
+        let finished = runs.tick(now, |_| true);
+        assert!(finished.iter().all(|run| run.outcome == Some(true)));
+        assert!(runs.runs().is_empty());
+
+        let solo_reward = finished[0].reward();
+        let party_reward = finished[1].reward();
+        assert_eq!(solo_reward.zen, ZEN_PER_WAVE * WAVES as u64);
+        assert_eq!(party_reward.zen, solo_reward.zen * 120 / 100);
+        assert_eq!(party_reward.items.len(), 1);
+        assert_eq!(runs.check_entry(&party(&[1, 2]), &ticket()), Ok(()));
+    }
+
+    #[test]
+    fn an_empty_instance_is_lost_after_the_grace() {
+        let runs = DoppelgangerRuns::new();
+        runs.open(runs.next_run_id(), route(68), party(&[1, 2]), 0)
+            .unwrap();
+
+        let grace = ENTRY_GRACE.as_millis() as u64;
+        assert!(runs.tick(grace - 1, |_| false).is_empty());
+        assert!(runs.tick(WAVE_MS, |_| true)[0].wave == 2);
+
+        let lost = runs.tick(WAVE_MS + 1, |_| false);
+        assert_eq!(lost[0].outcome, Some(false));
+        assert_eq!(lost[0].waves_cleared(), 1);
+        assert!(lost[0].reward().items.is_empty());
+        assert!(runs.runs().is_empty());
+
+        let zones: HashSet<u16> = zone_rotation(7).collect();
+        assert_eq!(zones.len(), ZONES.len());
+        assert_ne!(zone_rotation(1).next(), zone_rotation(2).next());
+    }
+}
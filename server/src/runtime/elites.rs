@@ -0,0 +1,138 @@
+//! Elite and boss monster variants.
+//!
+//! Every spawn has a small chance to roll affixes: one makes an elite, two or
+//! three make a boss. Affixes change the monster's stats and add drops; the
+//! client gets them in `MonsterAffixes` to scale, tint and color the nameplate.
+
+use protocol::{MonsterAffix, MonsterRank};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Spawns out of a thousand that roll a boss.
+pub const BOSS_CHANCE_PER_MILLE: u64 = 5;
+/// Spawns out of a thousand that roll an elite (bosses included).
+pub const ELITE_CHANCE_PER_MILLE: u64 = 40;
+
+const ELITE_HP_MULTIPLIER: u32 = 3;
+const BOSS_HP_MULTIPLIER: u32 = 8;
+const VAMPIRIC_LIFE_STEAL_PERCENT: u8 = 25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub struct MonsterStats {
+    pub max_hp: u32,
+    pub damage: u32,
+    pub defense: u32,
+    /// Tiles walked per monster tick.
+    pub steps_per_tick: u8,
+    /// Share of dealt damage healed back.
+    pub life_steal_percent: u8,
+    /// Drops on top of the regular loot roll.
+    pub extra_drops: u8,
+}
+
+impl MonsterStats {
+    pub const BASE: Self = Self {
+        max_hp: 100,
+        damage: 10,
+        defense: 5,
+        steps_per_tick: 1,
+        life_steal_percent: 0,
+        extra_drops: 0,
+    };
+
+    pub fn with_affixes(self, affixes: &[MonsterAffix]) -> Self {
+        let mut stats = self;
+        stats.max_hp *= match MonsterRank::of(affixes) {
+            None => 1,
+            Some(MonsterRank::Elite) => ELITE_HP_MULTIPLIER,
+            Some(MonsterRank::Boss) => BOSS_HP_MULTIPLIER,
+        };
+        stats.extra_drops += affixes.len() as u8;
+
+        for affix in affixes {
+            match affix {
+                MonsterAffix::Enraged => stats.damage = stats.damage * 3 / 2,
+                MonsterAffix::Armored => stats.defense *= 2,
+                MonsterAffix::Swift => stats.steps_per_tick = 2,
+                MonsterAffix::Vampiric => stats.life_steal_percent = VAMPIRIC_LIFE_STEAL_PERCENT,
+            }
+        }
+        stats
+    }
+}
+
+/// Monster that rolled affixes at spawn.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct EliteSpawn {
+    pub entity_id: u32,
+    pub rank: MonsterRank,
+    pub affixes: Vec<MonsterAffix>,
+    pub stats: MonsterStats,
+}
+
+/// Rolls the affixes of a new spawn; most spawns get none.
+pub fn roll_affixes(mut next_random: impl FnMut() -> u64) -> Vec<MonsterAffix> {
+    let roll = next_random() % 1000;
+    let count = if roll < BOSS_CHANCE_PER_MILLE {
+        2 + (next_random() % 2) as usize
+    } else if roll < ELITE_CHANCE_PER_MILLE {
+        1
+    } else {
+        return Vec::new();
+    };
+
+    let mut pool = MonsterAffix::ALL.to_vec();
+    (0..count)
+        .map(|_| pool.swap_remove((next_random() % pool.len() as u64) as usize))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sequence(values: &[u64]) -> impl FnMut() -> u64 + '_ {
+        let mut values = values.iter().copied();
+        move || values.next().unwrap_or(0)
+    }
+
+    #[test]
+    fn rolls_follow_the_chances_and_never_repeat_an_affix() {
+        assert!(roll_affixes(sequence(&[ELITE_CHANCE_PER_MILLE])).is_empty());
+        assert_eq!(
+            roll_affixes(sequence(&[ELITE_CHANCE_PER_MILLE - 1, 2])),
+            vec![MonsterAffix::Swift]
+        );
+
+        // The same pick three times still yields three different affixes.
+        assert_eq!(
+            roll_affixes(sequence(&[0, 1, 0, 0, 0])),
+            vec![
+                MonsterAffix::Enraged,
+                MonsterAffix::Vampiric,
+                MonsterAffix::Swift
+            ]
+        );
+    }
+
+    #[test]
+    fn affixes_change_stats_and_drops() {
+        let base = MonsterStats::BASE;
+        assert_eq!(base.with_affixes(&[]), base);
+
+        let elite = base.with_affixes(&[MonsterAffix::Enraged]);
+        assert_eq!(elite.max_hp, base.max_hp * ELITE_HP_MULTIPLIER);
+        assert_eq!(elite.damage, 15);
+        assert_eq!(elite.extra_drops, 1);
+
+        let boss = base.with_affixes(&[
+            MonsterAffix::Armored,
+            MonsterAffix::Swift,
+            MonsterAffix::Vampiric,
+        ]);
+        assert_eq!(boss.max_hp, base.max_hp * BOSS_HP_MULTIPLIER);
+        assert_eq!((boss.defense, boss.steps_per_tick), (10, 2));
+        assert_eq!(boss.life_steal_percent, VAMPIRIC_LIFE_STEAL_PERCENT);
+        assert_eq!(boss.extra_drops, 3);
+    }
+}
@@ -0,0 +1,524 @@
+//! Crywolf and Kanturu sequence events.
+//!
+//! Every world runs both events in cycles. A cycle walks through the event's
+//! phases on a timer and each phase opens or closes the map the event guards.
+//! Phases with an objective (defending the statue, killing Maya's hands or
+//! Nightmare) are settled by [`SequenceEvents::resolve`]: a win moves on to the
+//! next phase, a loss closes the cycle. When the timer of a kill objective runs
+//! out the cycle is lost; holding the statue until the end wins it.
+//!
+//! Phase changes are kept in memory and written back by
+//! [`SequenceEvents::persist`], so a restart resumes the cycle where it was.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::WorldMap;
+use dashmap::{DashMap, DashSet};
+use protocol::{EventNotice, EventPhase, SequenceEvent};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::db::{models::SequenceEventRecord, repository::SequenceEventRepository};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EventError {
+    #[error("world {0} does not exist")]
+    UnknownWorld(u16),
+    #[error("{event:?} is in phase {phase:?}, which has no objective")]
+    NoObjective {
+        event: SequenceEvent,
+        phase: EventPhase,
+    },
+}
+
+/// What the end of a phase's timer means.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Objective {
+    /// Nothing to settle; the next phase starts.
+    None,
+    /// Players win by holding out until the timer ends.
+    Survive,
+    /// Players lose unless the target dies before the timer ends.
+    Kill,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Stage {
+    phase: EventPhase,
+    duration: Duration,
+    gate_open: bool,
+    objective: Objective,
+}
+
+const fn stage(phase: EventPhase, secs: u64, gate_open: bool, objective: Objective) -> Stage {
+    Stage {
+        phase,
+        duration: Duration::from_secs(secs),
+        gate_open,
+        objective,
+    }
+}
+
+struct EventPlan {
+    /// Map closed to new entries while the gate is shut.
+    gated_map: WorldMap,
+    /// Maps whose players are told about phase changes.
+    notice_maps: &'static [WorldMap],
+    closed_for: Duration,
+    gate_open_while_closed: bool,
+    stages: &'static [Stage],
+}
+
+const CRYWOLF: EventPlan = EventPlan {
+    gated_map: WorldMap::Crywolf,
+    notice_maps: &[WorldMap::Crywolf],
+    closed_for: Duration::from_secs(3 * 60 * 60),
+    gate_open_while_closed: true,
+    stages: &[
+        stage(EventPhase::Notice, 5 * 60, true, Objective::None),
+        stage(EventPhase::AltarContracts, 10 * 60, true, Objective::None),
+        // Nobody joins once the waves march on the statue.
+        stage(
+            EventPhase::StatueDefense,
+            20 * 60,
+            false,
+            Objective::Survive,
+        ),
+    ],
+};
+
+const KANTURU: EventPlan = EventPlan {
+    gated_map: WorldMap::KanturuRemain,
+    notice_maps: &[WorldMap::Kanturu, WorldMap::KanturuRemain],
+    closed_for: Duration::from_secs(2 * 60 * 60),
+    gate_open_while_closed: false,
+    stages: &[
+        // The gateway lets players in before the fight.
+        stage(EventPhase::Notice, 5 * 60, true, Objective::None),
+        stage(EventPhase::MayaHands, 15 * 60, false, Objective::Kill),
+        stage(EventPhase::Nightmare, 20 * 60, false, Objective::Kill),
+        stage(EventPhase::TowerOpen, 60 * 60, true, Objective::None),
+    ],
+};
+
+const EVENTS: [SequenceEvent; 2] = [SequenceEvent::Crywolf, SequenceEvent::Kanturu];
+
+fn plan(event: SequenceEvent) -> &'static EventPlan {
+    match event {
+        SequenceEvent::Crywolf => &CRYWOLF,
+        SequenceEvent::Kanturu => &KANTURU,
+    }
+}
+
+/// Time from the start of a cycle to the end of its last phase.
+fn cycle_length(plan: &EventPlan) -> Duration {
+    plan.stages.iter().map(|stage| stage.duration).sum()
+}
+
+/// Maps whose players hear about the event.
+pub fn notice_maps(event: SequenceEvent) -> impl Iterator<Item = u16> {
+    plan(event).notice_maps.iter().map(|map| *map as u16)
+}
+
+/// Where an event stands in its current cycle on one world.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct EventState {
+    pub world_id: u16,
+    pub event: SequenceEvent,
+    /// Cycles started so far; 0 until the first one.
+    pub cycle: u32,
+    pub phase: EventPhase,
+    pub phase_started_at_ms: u64,
+    pub phase_ends_at_ms: u64,
+    /// Whether the players won the last closed cycle.
+    pub last_outcome: Option<bool>,
+    pub gated_map_id: u16,
+    pub gate_open: bool,
+}
+
+impl EventState {
+    fn closed(world_id: u16, event: SequenceEvent, now_ms: u64) -> Self {
+        let mut state = Self {
+            world_id,
+            event,
+            cycle: 0,
+            phase: EventPhase::Closed,
+            phase_started_at_ms: now_ms,
+            phase_ends_at_ms: now_ms,
+            last_outcome: None,
+            gated_map_id: plan(event).gated_map as u16,
+            gate_open: false,
+        };
+        state.enter(EventPhase::Closed, now_ms);
+        state
+    }
+
+    fn stage_index(&self) -> Option<usize> {
+        plan(self.event)
+            .stages
+            .iter()
+            .position(|stage| stage.phase == self.phase)
+    }
+
+    fn objective(&self) -> Objective {
+        self.stage_index().map_or(Objective::None, |index| {
+            plan(self.event).stages[index].objective
+        })
+    }
+
+    fn enter(&mut self, phase: EventPhase, now_ms: u64) {
+        let plan = plan(self.event);
+        let (duration, gate_open) = plan
+            .stages
+            .iter()
+            .find(|stage| stage.phase == phase)
+            .map_or((plan.closed_for, plan.gate_open_while_closed), |stage| {
+                (stage.duration, stage.gate_open)
+            });
+
+        self.phase = phase;
+        self.phase_started_at_ms = now_ms;
+        self.phase_ends_at_ms = now_ms + duration.as_millis() as u64;
+        self.gate_open = gate_open;
+    }
+
+    /// Next phase of the cycle; after the last one the cycle is won.
+    fn advance(&mut self, now_ms: u64) {
+        let stages = plan(self.event).stages;
+        match self.stage_index() {
+            None => {
+                self.cycle += 1;
+                self.enter(stages[0].phase, now_ms);
+            }
+            Some(index) if index + 1 < stages.len() => {
+                self.enter(stages[index + 1].phase, now_ms);
+            }
+            Some(_) => self.close(true, now_ms),
+        }
+    }
+
+    fn close(&mut self, won: bool, now_ms: u64) {
+        self.last_outcome = Some(won);
+        self.enter(EventPhase::Closed, now_ms);
+    }
+
+    /// Moves on once the timer ran out. A new cycle only starts when
+    /// `may_start` allows one of its length. Returns whether the phase changed.
+    fn tick(&mut self, now_ms: u64, may_start: impl Fn(Duration) -> bool) -> bool {
+        if now_ms < self.phase_ends_at_ms {
+            return false;
+        }
+        if self.phase == EventPhase::Closed && !may_start(cycle_length(plan(self.event))) {
+            return false;
+        }
+        match self.objective() {
+            Objective::Kill => self.close(false, now_ms),
+            Objective::None | Objective::Survive => self.advance(now_ms),
+        }
+        true
+    }
+
+    pub fn notice(&self) -> EventNotice {
+        EventNotice {
+            world_id: self.world_id,
+            event: self.event,
+            cycle: self.cycle,
+            phase: self.phase,
+            ends_at_ms: self.phase_ends_at_ms,
+            outcome: if self.phase == EventPhase::Closed {
+                self.last_outcome
+            } else {
+                None
+            },
+        }
+    }
+}
+
+/// Crywolf and Kanturu of every world, shared by the admin API and the core.
+#[derive(Clone, Default)]
+pub struct SequenceEvents {
+    // key: (world_id, event)
+    states: Arc<DashMap<(u16, SequenceEvent), EventState>>,
+    dirty: Arc<DashSet<(u16, SequenceEvent)>>,
+}
+
+impl SequenceEvents {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts both events of a world closed, their first cycle one interval away.
+    pub fn open_world(&self, world_id: u16, now_ms: u64) {
+        for event in EVENTS {
+            self.states
+                .entry((world_id, event))
+                .or_insert_with(|| EventState::closed(world_id, event, now_ms));
+        }
+    }
+
+    /// Restores saved phases of the worlds already opened.
+    pub fn load(&self, records: impl IntoIterator<Item = SequenceEventRecord>) {
+        for record in records {
+            let Some(mut state) = self.states.get_mut(&(record.world_id, record.event)) else {
+                continue;
+            };
+            let ends_at_ms = record.phase_ends_at_ms;
+            state.cycle = record.cycle;
+            state.last_outcome = record.last_outcome;
+            state.enter(record.phase, record.phase_started_at_ms);
+            state.phase_ends_at_ms = ends_at_ms;
+        }
+    }
+
+    pub fn state(&self, world_id: u16, event: SequenceEvent) -> Option<EventState> {
+        self.states
+            .get(&(world_id, event))
+            .map(|entry| entry.value().clone())
+    }
+
+    pub fn states(&self) -> Vec<EventState> {
+        let mut states: Vec<EventState> = self
+            .states
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect();
+        states.sort_by_key(|state| (state.world_id, state.event as u8));
+        states
+    }
+
+    /// Advances every event whose phase timer ran out and returns them.
+    /// Closed events wait instead of starting a cycle `may_start` refuses,
+    /// e.g. one that would still run at a planned restart.
+    pub fn tick(&self, now_ms: u64, may_start: impl Fn(Duration) -> bool) -> Vec<EventState> {
+        let mut changed = Vec::new();
+        for mut entry in self.states.iter_mut() {
+            if entry.value_mut().tick(now_ms, &may_start) {
+                self.dirty.insert(*entry.key());
+                changed.push(entry.value().clone());
+            }
+        }
+        changed.sort_by_key(|state| (state.world_id, state.event as u8));
+        changed
+    }
+
+    /// Settles the objective of the current phase: a win moves on, a loss
+    /// closes the cycle.
+    pub fn resolve(
+        &self,
+        world_id: u16,
+        event: SequenceEvent,
+        won: bool,
+        now_ms: u64,
+    ) -> Result<EventState, EventError> {
+        let key = (world_id, event);
+        let mut state = self
+            .states
+            .get_mut(&key)
+            .ok_or(EventError::UnknownWorld(world_id))?;
+        if state.objective() == Objective::None {
+            return Err(EventError::NoObjective {
+                event,
+                phase: state.phase,
+            });
+        }
+
+        if won {
+            state.advance(now_ms);
+        } else {
+            state.close(false, now_ms);
+        }
+        self.dirty.insert(key);
+        Ok(state.clone())
+    }
+
+    /// Whether an event keeps players out of the map right now.
+    pub fn blocks_map(&self, world_id: u16, map_id: u16) -> bool {
+        self.states.iter().any(|entry| {
+            entry.world_id == world_id && entry.gated_map_id == map_id && !entry.gate_open
+        })
+    }
+
+    /// Writes changed phases to MongoDB. Failed writes stay pending for the
+    /// next call. Returns how many events were written.
+    pub async fn persist(&self, repository: &SequenceEventRepository) -> usize {
+        let pending: Vec<(u16, SequenceEvent)> = self.dirty.iter().map(|key| *key).collect();
+        let mut written = 0;
+        for key in pending {
+            self.dirty.remove(&key);
+            let Some(state) = self.state(key.0, key.1) else {
+                continue;
+            };
+
+            match repository.save(&event_record(&state)).await {
+                Ok(()) => written += 1,
+                Err(err) => {
+                    log::error!(
+                        "Failed to save {:?} phase of world {}: {}",
+                        key.1,
+                        key.0,
+                        err
+                    );
+                    self.dirty.insert(key);
+                }
+            }
+        }
+        written
+    }
+
+    #[cfg(test)]
+    pub fn pending_writes(&self) -> usize {
+        self.dirty.len()
+    }
+}
+
+fn event_record(state: &EventState) -> SequenceEventRecord {
+    SequenceEventRecord {
+        id: None,
+        world_id: state.world_id,
+        event: state.event,
+        cycle: state.cycle,
+        phase: state.phase,
+        phase_started_at_ms: state.phase_started_at_ms,
+        phase_ends_at_ms: state.phase_ends_at_ms,
+        last_outcome: state.last_outcome,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE_MS: u64 = 60_000;
+    const REMAIN: u16 = WorldMap::KanturuRemain as u16;
+
+    fn opened() -> SequenceEvents {
+        let events = SequenceEvents::new();
+        events.open_world(1, 0);
+        events
+    }
+
+    #[test]
+    fn crywolf_closes_its_gate_for_the_defense_and_wins_by_holding_out() {
+        let events = opened();
+        let crywolf = |events: &SequenceEvents| events.state(1, SequenceEvent::Crywolf).unwrap();
+        let mut now = CRYWOLF.closed_for.as_millis() as u64;
+        events.tick(now - 1, |_| true);
+        assert_eq!(crywolf(&events).phase, EventPhase::Closed);
+
+        let changed = events.tick(now, |_| true);
+        let notice = changed
+            .iter()
+            .find(|state| state.event == SequenceEvent::Crywolf)
+            .unwrap();
+        assert_eq!((notice.cycle, notice.phase), (1, EventPhase::Notice));
+        assert_eq!(
+            events.resolve(1, SequenceEvent::Crywolf, true, now),
+            Err(EventError::NoObjective {
+                event: SequenceEvent::Crywolf,
+                phase: EventPhase::Notice,
+            })
+        );
+
+        now += 5 * MINUTE_MS;
+        events.tick(now, |_| true);
+        assert_eq!(crywolf(&events).phase, EventPhase::AltarContracts);
+        now += 10 * MINUTE_MS;
+        events.tick(now, |_| true);
+        assert_eq!(crywolf(&events).phase, EventPhase::StatueDefense);
+        assert!(events.blocks_map(1, WorldMap::Crywolf as u16));
+        assert!(!events.blocks_map(2, WorldMap::Crywolf as u16));
+
+        now += 20 * MINUTE_MS;
+        events.tick(now, |_| true);
+        let state = crywolf(&events);
+        assert_eq!(state.phase, EventPhase::Closed);
+        assert_eq!(state.notice().outcome, Some(true));
+        assert!(!events.blocks_map(1, WorldMap::Crywolf as u16));
+        // Kanturu ran its own cycle meanwhile.
+        assert_eq!(events.pending_writes(), 2);
+    }
+
+    #[test]
+    fn kanturu_needs_both_kills_to_open_the_tower() {
+        let events = opened();
+        let mut now = KANTURU.closed_for.as_millis() as u64;
+        assert!(events.blocks_map(1, REMAIN));
+        events.tick(now, |_| true);
+        assert!(!events.blocks_map(1, REMAIN));
+
+        now += 5 * MINUTE_MS;
+        events.tick(now, |_| true);
+        let state = events
+            .resolve(1, SequenceEvent::Kanturu, true, now)
+            .unwrap();
+        assert_eq!(state.phase, EventPhase::Nightmare);
+        assert!(events.blocks_map(1, REMAIN));
+        let state = events
+            .resolve(1, SequenceEvent::Kanturu, true, now)
+            .unwrap();
+        assert_eq!(state.phase, EventPhase::TowerOpen);
+        assert!(!events.blocks_map(1, REMAIN));
+
+        // Next cycle: Maya's hands survive their timer, so the cycle is lost.
+        now += 60 * MINUTE_MS;
+        events.tick(now, |_| true);
+        now += KANTURU.closed_for.as_millis() as u64;
+        events.tick(now, |_| true);
+        now += 5 * MINUTE_MS;
+        events.tick(now, |_| true);
+        now += 15 * MINUTE_MS;
+        events.tick(now, |_| true);
+        let lost = events.state(1, SequenceEvent::Kanturu).unwrap();
+        assert_eq!((lost.cycle, lost.phase), (2, EventPhase::Closed));
+        assert_eq!(lost.notice().outcome, Some(false));
+        assert_eq!(
+            events.resolve(9, SequenceEvent::Kanturu, true, now),
+            Err(EventError::UnknownWorld(9))
+        );
+    }
+
+    #[test]
+    fn cycles_wait_while_they_would_run_into_a_restart() {
+        let events = opened();
+        let now = CRYWOLF.closed_for.as_millis() as u64;
+        let restart_at = now + 20 * MINUTE_MS;
+        let before_restart = |cycle: Duration| now + cycle.as_millis() as u64 <= restart_at;
+
+        let changed = events.tick(now, before_restart);
+        assert!(changed.is_empty());
+        assert_eq!(
+            events.state(1, SequenceEvent::Crywolf).unwrap().phase,
+            EventPhase::Closed
+        );
+        assert_eq!(cycle_length(&CRYWOLF), Duration::from_secs(35 * 60));
+
+        // Called off: the overdue cycles start on the next tick.
+        let changed = events.tick(now + MINUTE_MS, |_| true);
+        assert_eq!(changed.len(), 2);
+        assert!(changed
+            .iter()
+            .all(|state| state.phase == EventPhase::Notice));
+    }
+
+    #[test]
+    fn saved_phase_is_resumed() {
+        let events = opened();
+        events.load([SequenceEventRecord {
+            id: None,
+            world_id: 1,
+            event: SequenceEvent::Kanturu,
+            cycle: 7,
+            phase: EventPhase::Nightmare,
+            phase_started_at_ms: 1_000,
+            phase_ends_at_ms: 9_000,
+            last_outcome: Some(true),
+        }]);
+
+        let state = events.state(1, SequenceEvent::Kanturu).unwrap();
+        assert_eq!((state.cycle, state.phase), (7, EventPhase::Nightmare));
+        assert_eq!(state.phase_ends_at_ms, 9_000);
+        assert!(!state.gate_open);
+        assert_eq!(state.notice().outcome, None);
+    }
+}
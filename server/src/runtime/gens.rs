@@ -0,0 +1,263 @@
+//! Gens factions (Duprian and Vanert).
+//!
+//! A character joins one faction for good. Inside Gens battle zones members of
+//! rival factions may attack each other, and each kill moves contribution from
+//! the victim to the killer. Worlds opt in through `gens` in the runtime config;
+//! on other worlds the registry is never consulted.
+
+use std::sync::Arc;
+
+use dashmap::mapref::entry::Entry;
+use dashmap::{DashMap, DashSet};
+use protocol::{GensFaction, GensStatus, ServerErrorKind};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::db::{models::GensMemberRecord, repository::GensMemberRepository};
+
+/// Contribution awarded to the killer of a rival member.
+pub const KILL_CONTRIBUTION: u32 = 5;
+/// Contribution the victim loses, never going below zero.
+pub const DEATH_PENALTY: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum GensError {
+    #[error("already a member of the {0:?} Gens")]
+    AlreadyMember(GensFaction),
+}
+
+impl GensError {
+    pub fn kind(&self) -> ServerErrorKind {
+        match self {
+            Self::AlreadyMember(_) => ServerErrorKind::InvalidAction,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GensMember {
+    pub faction: GensFaction,
+    pub contribution: u32,
+    pub joined_at_ms: u64,
+}
+
+/// Position of a member in the contribution ranking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub struct GensRankEntry {
+    /// 1-based, counted within the requested faction.
+    pub rank: u32,
+    pub character_id: u64,
+    pub faction: GensFaction,
+    pub contribution: u32,
+}
+
+/// Gens members of every world, shared by the session handler and the map
+/// servers.
+///
+/// Loaded from MongoDB at boot; changes are written back in batches by
+/// [`GensRegistry::persist`].
+#[derive(Clone, Default)]
+pub struct GensRegistry {
+    // key: character_id
+    members: Arc<DashMap<u64, GensMember>>,
+    dirty: Arc<DashSet<u64>>,
+}
+
+impl GensRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(&self, records: impl IntoIterator<Item = GensMemberRecord>) {
+        for record in records {
+            self.members.insert(
+                record.character_id,
+                GensMember {
+                    faction: record.faction,
+                    contribution: record.contribution,
+                    joined_at_ms: record.joined_at_ms,
+                },
+            );
+        }
+    }
+
+    pub fn member(&self, character_id: u64) -> Option<GensMember> {
+        self.members.get(&character_id).map(|entry| *entry.value())
+    }
+
+    pub fn faction_of(&self, character_id: u64) -> Option<GensFaction> {
+        self.member(character_id).map(|member| member.faction)
+    }
+
+    pub fn status(&self, character_id: u64) -> GensStatus {
+        let member = self.member(character_id);
+        GensStatus {
+            enabled: true,
+            faction: member.map(|member| member.faction),
+            contribution: member.map_or(0, |member| member.contribution),
+        }
+    }
+
+    /// Registers the character with `faction`. Membership is permanent, so
+    /// joining twice fails even for the same faction.
+    pub fn join(
+        &self,
+        character_id: u64,
+        faction: GensFaction,
+        now_ms: u64,
+    ) -> Result<GensMember, GensError> {
+        match self.members.entry(character_id) {
+            Entry::Occupied(entry) => Err(GensError::AlreadyMember(entry.get().faction)),
+            Entry::Vacant(entry) => {
+                let member = GensMember {
+                    faction,
+                    contribution: 0,
+                    joined_at_ms: now_ms,
+                };
+                entry.insert(member);
+                self.dirty.insert(character_id);
+                Ok(member)
+            }
+        }
+    }
+
+    pub fn are_rivals(&self, character_id: u64, target_id: u64) -> bool {
+        match (self.faction_of(character_id), self.faction_of(target_id)) {
+            (Some(faction), Some(target)) => faction.rival() == target,
+            _ => false,
+        }
+    }
+
+    /// Scores a kill made inside a Gens battle zone. Returns the killer's new
+    /// contribution, or `None` when the two are not rival members.
+    pub fn record_kill(&self, killer_id: u64, victim_id: u64) -> Option<u32> {
+        if !self.are_rivals(killer_id, victim_id) {
+            return None;
+        }
+
+        if let Some(mut victim) = self.members.get_mut(&victim_id) {
+            victim.contribution = victim.contribution.saturating_sub(DEATH_PENALTY);
+        }
+        self.dirty.insert(victim_id);
+
+        let mut killer = self.members.get_mut(&killer_id)?;
+        killer.contribution = killer.contribution.saturating_add(KILL_CONTRIBUTION);
+        self.dirty.insert(killer_id);
+        Some(killer.contribution)
+    }
+
+    /// Members by contribution, highest first; ties go to whoever joined
+    /// earlier. `faction` narrows the ranking to one side.
+    pub fn ranking(&self, faction: Option<GensFaction>, limit: usize) -> Vec<GensRankEntry> {
+        let mut members: Vec<(u64, GensMember)> = self
+            .members
+            .iter()
+            .filter(|entry| faction.is_none_or(|faction| entry.faction == faction))
+            .map(|entry| (*entry.key(), *entry.value()))
+            .collect();
+        members.sort_by(|(a_id, a), (b_id, b)| {
+            b.contribution
+                .cmp(&a.contribution)
+                .then(a.joined_at_ms.cmp(&b.joined_at_ms))
+                .then(a_id.cmp(b_id))
+        });
+
+        members
+            .into_iter()
+            .take(limit)
+            .enumerate()
+            .map(|(index, (character_id, member))| GensRankEntry {
+                rank: index as u32 + 1,
+                character_id,
+                faction: member.faction,
+                contribution: member.contribution,
+            })
+            .collect()
+    }
+
+    /// Writes every changed member to MongoDB. Failed writes stay pending for
+    /// the next call. Returns how many members were written.
+    pub async fn persist(&self, repository: &GensMemberRepository) -> usize {
+        let pending: Vec<u64> = self.dirty.iter().map(|entry| *entry).collect();
+        let mut written = 0;
+        for character_id in pending {
+            self.dirty.remove(&character_id);
+            let Some(member) = self.member(character_id) else {
+                continue;
+            };
+
+            let record = GensMemberRecord {
+                id: None,
+                character_id,
+                faction: member.faction,
+                contribution: member.contribution,
+                joined_at_ms: member.joined_at_ms,
+            };
+            match repository.save(&record).await {
+                Ok(()) => written += 1,
+                Err(err) => {
+                    log::error!(
+                        "Failed to save Gens membership of character {}: {}",
+                        character_id,
+                        err
+                    );
+                    self.dirty.insert(character_id);
+                }
+            }
+        }
+        written
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn membership_is_permanent() {
+        let gens = GensRegistry::new();
+        assert!(gens.join(1, GensFaction::Duprian, 10).is_ok());
+        assert_eq!(
+            gens.join(1, GensFaction::Vanert, 20),
+            Err(GensError::AlreadyMember(GensFaction::Duprian))
+        );
+        assert_eq!(gens.faction_of(1), Some(GensFaction::Duprian));
+        assert_eq!(gens.status(2).faction, None);
+    }
+
+    #[test]
+    fn only_rival_kills_score() {
+        let gens = GensRegistry::new();
+        gens.join(1, GensFaction::Duprian, 10).unwrap();
+        gens.join(2, GensFaction::Vanert, 20).unwrap();
+        gens.join(3, GensFaction::Duprian, 30).unwrap();
+
+        assert_eq!(gens.record_kill(1, 3), None);
+        assert_eq!(gens.record_kill(1, 4), None);
+        assert_eq!(gens.record_kill(1, 2), Some(KILL_CONTRIBUTION));
+        assert_eq!(gens.member(2).unwrap().contribution, 0);
+        assert_eq!(gens.record_kill(2, 1), Some(KILL_CONTRIBUTION));
+        assert_eq!(
+            gens.member(1).unwrap().contribution,
+            KILL_CONTRIBUTION - DEATH_PENALTY
+        );
+    }
+
+    #[test]
+    fn ranking_orders_by_contribution_then_seniority() {
+        let gens = GensRegistry::new();
+        gens.join(1, GensFaction::Duprian, 10).unwrap();
+        gens.join(2, GensFaction::Vanert, 20).unwrap();
+        gens.join(3, GensFaction::Duprian, 5).unwrap();
+        gens.record_kill(2, 1);
+
+        let all = gens.ranking(None, 10);
+        let order: Vec<u64> = all.iter().map(|entry| entry.character_id).collect();
+        assert_eq!(order, vec![2, 3, 1]);
+        assert_eq!(all[0].rank, 1);
+
+        let duprian = gens.ranking(Some(GensFaction::Duprian), 1);
+        assert_eq!(duprian.len(), 1);
+        assert_eq!(duprian[0].character_id, 3);
+    }
+}
@@ -0,0 +1,327 @@
+//! Timed wars between two guilds.
+//!
+//! An operator starts a war for a pair of guilds; until its window closes the
+//! two guilds may fight anywhere and every kill between their members scores.
+//! Finished wars are kept in memory until [`GuildWars::persist`] writes them to
+//! the guild war records.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::DateTime;
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use protocol::{GuildWarScore, ServerMessage};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::guilds::GuildRelations;
+use super::session_links::SessionPush;
+use crate::db::{models::GuildWarRecord, repository::GuildWarRepository};
+
+pub const MIN_WAR_DURATION: Duration = Duration::from_secs(60);
+pub const MAX_WAR_DURATION: Duration = Duration::from_secs(2 * 60 * 60);
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum GuildWarError {
+    #[error("guild {0} cannot go to war with itself")]
+    SameGuild(u32),
+    #[error("guilds {0} and {1} are allied")]
+    Allied(u32, u32),
+    #[error("guild {0} is already at war")]
+    AlreadyAtWar(u32),
+    #[error(
+        "duration must be between {min} and {max} seconds",
+        min = MIN_WAR_DURATION.as_secs(),
+        max = MAX_WAR_DURATION.as_secs()
+    )]
+    InvalidDuration,
+}
+
+/// War between two guilds and its kill count so far.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct GuildWar {
+    pub war_id: u32,
+    pub guild_id: u32,
+    pub target_guild_id: u32,
+    pub guild_kills: u32,
+    pub target_kills: u32,
+    pub started_at_ms: u64,
+    pub ends_at_ms: u64,
+}
+
+impl GuildWar {
+    pub fn score(&self, finished: bool) -> GuildWarScore {
+        GuildWarScore {
+            war_id: self.war_id,
+            guild_id: self.guild_id.into(),
+            target_guild_id: self.target_guild_id.into(),
+            guild_kills: self.guild_kills,
+            target_kills: self.target_kills,
+            ends_at_ms: self.ends_at_ms,
+            finished,
+        }
+    }
+
+    /// Guild with more kills; `None` on a draw.
+    pub fn winner(&self) -> Option<u32> {
+        match self.guild_kills.cmp(&self.target_kills) {
+            std::cmp::Ordering::Greater => Some(self.guild_id),
+            std::cmp::Ordering::Less => Some(self.target_guild_id),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+/// Wars in progress, shared by the admin API and every map server.
+#[derive(Clone)]
+pub struct GuildWars {
+    // key: war_id
+    wars: Arc<DashMap<u32, GuildWar>>,
+    // key: guild_id, value: war_id
+    fronts: Arc<DashMap<u32, u32>>,
+    // finished wars not written yet, key: war_id
+    unsaved: Arc<DashMap<u32, GuildWar>>,
+    next_war_id: Arc<AtomicU32>,
+}
+
+impl Default for GuildWars {
+    fn default() -> Self {
+        Self {
+            wars: Arc::new(DashMap::new()),
+            fronts: Arc::new(DashMap::new()),
+            unsaved: Arc::new(DashMap::new()),
+            next_war_id: Arc::new(AtomicU32::new(1)),
+        }
+    }
+}
+
+impl GuildWars {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a war; a guild fights one war at a time.
+    pub fn start(
+        &self,
+        guild_id: u32,
+        target_guild_id: u32,
+        duration: Duration,
+        now_ms: u64,
+    ) -> Result<GuildWar, GuildWarError> {
+        if guild_id == target_guild_id {
+            return Err(GuildWarError::SameGuild(guild_id));
+        }
+        if !(MIN_WAR_DURATION..=MAX_WAR_DURATION).contains(&duration) {
+            return Err(GuildWarError::InvalidDuration);
+        }
+
+        let war_id = self.next_war_id.fetch_add(1, Ordering::Relaxed);
+        match self.fronts.entry(guild_id) {
+            Entry::Occupied(_) => return Err(GuildWarError::AlreadyAtWar(guild_id)),
+            Entry::Vacant(entry) => {
+                entry.insert(war_id);
+            }
+        }
+        // The entry guard must be gone before touching the map again: both
+        // guilds may share a shard.
+        let target_free = match self.fronts.entry(target_guild_id) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(war_id);
+                true
+            }
+        };
+        if !target_free {
+            self.fronts.remove(&guild_id);
+            return Err(GuildWarError::AlreadyAtWar(target_guild_id));
+        }
+
+        let war = GuildWar {
+            war_id,
+            guild_id,
+            target_guild_id,
+            guild_kills: 0,
+            target_kills: 0,
+            started_at_ms: now_ms,
+            ends_at_ms: now_ms + duration.as_millis() as u64,
+        };
+        self.wars.insert(war_id, war.clone());
+        Ok(war)
+    }
+
+    /// War the two guilds are fighting against each other, if any.
+    pub fn war_between(&self, guild_id: u32, target_guild_id: u32) -> Option<u32> {
+        let war_id = *self.fronts.get(&guild_id)?;
+        let target_war_id = *self.fronts.get(&target_guild_id)?;
+        (guild_id != target_guild_id && war_id == target_war_id).then_some(war_id)
+    }
+
+    /// Scores a kill and returns the new scoreboard, or `None` when the guilds
+    /// are not at war or the window already closed.
+    pub fn record_kill(
+        &self,
+        killer_guild_id: u32,
+        victim_guild_id: u32,
+        now_ms: u64,
+    ) -> Option<GuildWarScore> {
+        let war_id = self.war_between(killer_guild_id, victim_guild_id)?;
+        let mut war = self.wars.get_mut(&war_id)?;
+        if now_ms >= war.ends_at_ms {
+            return None;
+        }
+
+        if war.guild_id == killer_guild_id {
+            war.guild_kills += 1;
+        } else {
+            war.target_kills += 1;
+        }
+        Some(war.score(false))
+    }
+
+    /// Closes every war whose window ended and returns them.
+    pub fn finish_expired(&self, now_ms: u64) -> Vec<GuildWar> {
+        let expired: Vec<u32> = self
+            .wars
+            .iter()
+            .filter(|entry| now_ms >= entry.ends_at_ms)
+            .map(|entry| *entry.key())
+            .collect();
+
+        let mut finished = Vec::new();
+        for war_id in expired {
+            let Some((_, war)) = self.wars.remove(&war_id) else {
+                continue;
+            };
+            self.fronts.remove(&war.guild_id);
+            self.fronts.remove(&war.target_guild_id);
+            self.unsaved.insert(war_id, war.clone());
+            finished.push(war);
+        }
+        finished.sort_by_key(|war| war.war_id);
+        finished
+    }
+
+    pub fn active(&self) -> Vec<GuildWar> {
+        let mut wars: Vec<GuildWar> = self.wars.iter().map(|entry| entry.clone()).collect();
+        wars.sort_by_key(|war| war.war_id);
+        wars
+    }
+
+    /// Writes finished wars to MongoDB. Failed writes stay pending for the next
+    /// call. Returns how many wars were written.
+    pub async fn persist(&self, repository: &GuildWarRepository) -> usize {
+        let pending: Vec<u32> = self.unsaved.iter().map(|entry| *entry.key()).collect();
+        let mut written = 0;
+        for war_id in pending {
+            let Some((_, war)) = self.unsaved.remove(&war_id) else {
+                continue;
+            };
+
+            match repository.insert(&war_record(&war)).await {
+                Ok(()) => written += 1,
+                Err(err) => {
+                    log::error!("Failed to save result of guild war {}: {}", war_id, err);
+                    self.unsaved.insert(war_id, war);
+                }
+            }
+        }
+        written
+    }
+
+    #[cfg(test)]
+    pub fn pending_writes(&self) -> usize {
+        self.unsaved.len()
+    }
+}
+
+fn war_record(war: &GuildWar) -> GuildWarRecord {
+    let at = |ms: u64| DateTime::from_timestamp_millis(ms as i64).unwrap_or_default();
+    GuildWarRecord {
+        id: None,
+        guild_id: war.guild_id,
+        target_guild_id: war.target_guild_id,
+        guild_kills: war.guild_kills,
+        target_kills: war.target_kills,
+        winner_guild_id: war.winner(),
+        started_at: at(war.started_at_ms),
+        ended_at: at(war.ends_at_ms),
+    }
+}
+
+/// Sends the scoreboard to the online members of both guilds.
+pub fn publish_score(
+    guilds: &GuildRelations,
+    push: &SessionPush,
+    score: &GuildWarScore,
+    server_time_ms: u64,
+) {
+    for guild_id in [score.guild_id, score.target_guild_id] {
+        for character_id in guilds.members(guild_id.into()) {
+            push.push_to_character(
+                character_id,
+                ServerMessage::GuildWarScore(score.clone()),
+                server_time_ms,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE_MS: u64 = 60_000;
+
+    #[test]
+    fn kills_score_only_between_the_warring_guilds() {
+        let wars = GuildWars::new();
+        let war = wars.start(1, 2, MIN_WAR_DURATION, 0).unwrap();
+        assert_eq!(war.ends_at_ms, MINUTE_MS);
+        assert_eq!(
+            wars.start(3, 2, MIN_WAR_DURATION, 0),
+            Err(GuildWarError::AlreadyAtWar(2))
+        );
+        assert_eq!(
+            wars.start(4, 4, MIN_WAR_DURATION, 0),
+            Err(GuildWarError::SameGuild(4))
+        );
+        assert_eq!(
+            wars.start(4, 5, Duration::from_secs(1), 0),
+            Err(GuildWarError::InvalidDuration)
+        );
+        // The failed start left guild 3 free.
+        wars.start(3, 4, MIN_WAR_DURATION, 0).unwrap();
+
+        assert_eq!(wars.war_between(2, 1), Some(war.war_id));
+        assert_eq!(wars.war_between(1, 3), None);
+        assert_eq!(wars.record_kill(1, 3, 10), None);
+
+        wars.record_kill(1, 2, 10).unwrap();
+        wars.record_kill(2, 1, 20).unwrap();
+        let score = wars.record_kill(2, 1, 30).unwrap();
+        assert_eq!((score.guild_kills, score.target_kills), (1, 2));
+        assert!(!score.finished);
+        // Kills after the window do not count.
+        assert_eq!(wars.record_kill(1, 2, MINUTE_MS), None);
+    }
+
+    #[test]
+    fn expired_wars_free_both_guilds_and_wait_for_persistence() {
+        let wars = GuildWars::new();
+        wars.start(1, 2, MIN_WAR_DURATION, 0).unwrap();
+        wars.start(3, 4, MAX_WAR_DURATION, 0).unwrap();
+        wars.record_kill(2, 1, 5).unwrap();
+
+        assert!(wars.finish_expired(MINUTE_MS - 1).is_empty());
+        let finished = wars.finish_expired(MINUTE_MS);
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].winner(), Some(2));
+        assert_eq!(wars.pending_writes(), 1);
+
+        assert_eq!(wars.war_between(1, 2), None);
+        assert_eq!(wars.active().len(), 1);
+        wars.start(1, 2, MIN_WAR_DURATION, MINUTE_MS).unwrap();
+    }
+}
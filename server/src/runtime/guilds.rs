@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use common::PvpStanding;
+use dashmap::DashMap;
+use protocol::GuildRelation;
+
+use crate::db::models::GuildRelationRecord;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum GuildRelationError {
+    #[error("guild {0} cannot declare a relation with itself")]
+    SameGuild(u32),
+}
+
+/// In-memory view of guild memberships and alliance/hostility declarations.
+///
+/// MongoDB is the source of truth for relations; the table is loaded at boot and
+/// kept in sync by whoever persists a declaration.
+#[derive(Clone, Default)]
+pub struct GuildRelations {
+    // key: (lower guild_id, higher guild_id)
+    relations: Arc<DashMap<(u32, u32), GuildRelation>>,
+    // key: character_id
+    memberships: Arc<DashMap<u64, u32>>,
+}
+
+impl GuildRelations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(&self, records: impl IntoIterator<Item = GuildRelationRecord>) {
+        for record in records {
+            let _ = self.declare(record.guild_id, record.target_guild_id, record.relation);
+        }
+    }
+
+    pub fn declare(
+        &self,
+        guild_id: u32,
+        target_guild_id: u32,
+        relation: GuildRelation,
+    ) -> Result<(), GuildRelationError> {
+        if guild_id == target_guild_id {
+            return Err(GuildRelationError::SameGuild(guild_id));
+        }
+
+        self.relations
+            .insert(pair_key(guild_id, target_guild_id), relation);
+        Ok(())
+    }
+
+    pub fn revoke(&self, guild_id: u32, target_guild_id: u32) -> Option<GuildRelation> {
+        self.relations
+            .remove(&pair_key(guild_id, target_guild_id))
+            .map(|(_, relation)| relation)
+    }
+
+    pub fn relation(&self, guild_id: u32, target_guild_id: u32) -> Option<GuildRelation> {
+        self.relations
+            .get(&pair_key(guild_id, target_guild_id))
+            .map(|entry| *entry.value())
+    }
+
+    pub fn set_membership(&self, character_id: u64, guild_id: Option<u32>) {
+        match guild_id {
+            Some(guild_id) => {
+                self.memberships.insert(character_id, guild_id);
+            }
+            None => {
+                self.memberships.remove(&character_id);
+            }
+        }
+    }
+
+    pub fn guild_of(&self, character_id: u64) -> Option<u32> {
+        self.memberships
+            .get(&character_id)
+            .map(|entry| *entry.value())
+    }
+
+    /// Characters known to belong to `guild_id`.
+    pub fn members(&self, guild_id: u32) -> Vec<u64> {
+        self.memberships
+            .iter()
+            .filter(|entry| *entry.value() == guild_id)
+            .map(|entry| *entry.key())
+            .collect()
+    }
+
+    /// Relation between the guilds of two characters, as seen by `observer_id`.
+    pub fn relation_between(&self, observer_id: u64, target_id: u64) -> Option<GuildRelation> {
+        let observer_guild = self.guild_of(observer_id)?;
+        let target_guild = self.guild_of(target_id)?;
+        if observer_guild == target_guild {
+            return Some(GuildRelation::Alliance);
+        }
+        self.relation(observer_guild, target_guild)
+    }
+
+    /// Guild side of the PvP standing between two characters; wars and Gens
+    /// are added by the map.
+    pub fn pvp_standing(&self, attacker_id: u64, target_id: u64) -> PvpStanding {
+        let relation = self.relation_between(attacker_id, target_id);
+        PvpStanding {
+            allied: relation == Some(GuildRelation::Alliance),
+            hostile: relation == Some(GuildRelation::Hostility),
+            dueling: false,
+        }
+    }
+}
+
+fn pair_key(guild_id: u32, target_guild_id: u32) -> (u32, u32) {
+    (guild_id.min(target_guild_id), guild_id.max(target_guild_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{MurderStatus, PvpMode, PvpRules};
+
+    #[test]
+    fn relations_are_symmetric() {
+        let guilds = GuildRelations::new();
+        guilds.declare(7, 3, GuildRelation::Hostility).unwrap();
+
+        assert_eq!(guilds.relation(3, 7), Some(GuildRelation::Hostility));
+        assert_eq!(
+            guilds.declare(4, 4, GuildRelation::Alliance),
+            Err(GuildRelationError::SameGuild(4))
+        );
+        assert_eq!(guilds.revoke(3, 7), Some(GuildRelation::Hostility));
+        assert_eq!(guilds.relation(7, 3), None);
+    }
+
+    #[test]
+    fn pvp_rules_follow_guild_relations() {
+        let guilds = GuildRelations::new();
+        guilds.declare(1, 2, GuildRelation::Hostility).unwrap();
+        guilds.declare(1, 3, GuildRelation::Alliance).unwrap();
+        guilds.set_membership(100, Some(1));
+        guilds.set_membership(101, Some(1));
+        guilds.set_membership(200, Some(2));
+        guilds.set_membership(300, Some(3));
+
+        let field = PvpRules::default();
+        let free_pk = PvpRules::new(PvpMode::FreePk);
+        let commoner = MurderStatus::Commoner;
+        // Hostile guilds fight outside free PK maps.
+        assert!(field.allows(guilds.pvp_standing(100, 200), commoner));
+        // Guildmates and allies never fight, even on free PK maps.
+        assert!(!free_pk.allows(guilds.pvp_standing(100, 101), commoner));
+        assert!(!free_pk.allows(guilds.pvp_standing(100, 300), commoner));
+        // Unrelated players only fight on free PK maps.
+        assert!(!field.allows(guilds.pvp_standing(200, 300), commoner));
+        assert!(free_pk.allows(guilds.pvp_standing(200, 300), commoner));
+        assert!(!field.allows(guilds.pvp_standing(100, 999), commoner));
+    }
+}
@@ -0,0 +1,105 @@
+//! Sessions running the MU Helper (auto-hunt).
+//!
+//! The helper lives on the client and only sends normal inputs, so the server
+//! cannot tell it apart from a player; clients announce it instead, and the
+//! world's `mu_helper` policy decides whether they may. Marked sessions are
+//! listed for operators.
+
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use protocol::RouteKey;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Session with the helper switched on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct HelperActivity {
+    pub session_id: u64,
+    pub character_id: u64,
+    pub route: RouteKey,
+    pub since_ms: u64,
+}
+
+#[derive(Clone, Default)]
+pub struct HelperSessions {
+    // key: session_id
+    active: Arc<DashMap<u64, HelperActivity>>,
+}
+
+impl HelperSessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks the session; switching on again keeps the original start time.
+    pub fn start(&self, session_id: u64, character_id: u64, route: RouteKey, now_ms: u64) {
+        self.active
+            .entry(session_id)
+            .and_modify(|activity| {
+                activity.character_id = character_id;
+                activity.route = route;
+            })
+            .or_insert(HelperActivity {
+                session_id,
+                character_id,
+                route,
+                since_ms: now_ms,
+            });
+    }
+
+    pub fn stop(&self, session_id: u64) -> Option<HelperActivity> {
+        self.active
+            .remove(&session_id)
+            .map(|(_, activity)| activity)
+    }
+
+    pub fn is_active(&self, session_id: u64) -> bool {
+        self.active.contains_key(&session_id)
+    }
+
+    pub fn count(&self) -> usize {
+        self.active.len()
+    }
+
+    pub fn list(&self) -> Vec<HelperActivity> {
+        let mut sessions: Vec<HelperActivity> =
+            self.active.iter().map(|entry| entry.clone()).collect();
+        sessions.sort_by_key(|activity| activity.session_id);
+        sessions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(map_id: u16) -> RouteKey {
+        RouteKey {
+            world_id: 1,
+            entry_id: 1,
+            map_id,
+            instance_id: 1,
+        }
+    }
+
+    #[test]
+    fn restarting_keeps_the_first_start_time() {
+        let helpers = HelperSessions::new();
+        helpers.start(7, 70, route(0), 1_000);
+        helpers.start(3, 30, route(0), 1_500);
+        helpers.start(7, 70, route(2), 2_000);
+
+        let listed = helpers.list();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].session_id, 3);
+        assert_eq!((listed[1].since_ms, listed[1].route), (1_000, route(2)));
+
+        assert_eq!(
+            helpers.stop(7).map(|activity| activity.character_id),
+            Some(70)
+        );
+        assert!(!helpers.is_active(7));
+        assert_eq!(helpers.count(), 1);
+    }
+}
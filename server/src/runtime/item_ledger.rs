@@ -0,0 +1,726 @@
+//! Item serials and their ownership trail.
+//!
+//! Every item the server creates gets a serial from [`ItemLedger::mint`], and
+//! each move between holders (trade, drop, mail, store) is logged as an
+//! [`ItemTransfer`]. [`ItemLedger::scan`] compares where serials actually sit
+//! with the ledger and reports the anomalies a duplication exploit leaves
+//! behind. Transfers are kept in memory until [`ItemLedger::persist`] writes
+//! them to the item transfer records.
+//!
+//! Moves that belong together (both sides of a trade, a mail's attachments,
+//! the inputs a mix consumes) go through [`ItemLedger::apply`]: either every
+//! move happens or none does, and their transfers are written as one journaled
+//! operation that startup recovery completes if the server stopped halfway.
+//!
+//! Time-limited items carry their expiry as Unix time on the item itself, so
+//! a restart never extends them. [`ItemLedger::expire_due`] retires them to
+//! [`ItemHolder::Destroyed`], where a copy resurfacing shows up as misplaced.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+
+use chrono::DateTime;
+use common::ItemCode;
+use dashmap::DashMap;
+use protocol::{ItemInstance, RouteKey};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::db::{
+    models::{ItemOperationRecord, ItemTransferRecord},
+    repository::ItemTransferRepository,
+};
+
+/// Low bits of a serial counting items minted since boot. The high bits hold
+/// the boot time in ms, so serials stay unique across restarts without a
+/// stored counter.
+const SERIAL_SEQUENCE_BITS: u32 = 20;
+
+/// Where an item is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemHolder {
+    Character {
+        character_id: u64,
+    },
+    Mail {
+        mail_id: u64,
+    },
+    Ground {
+        route: RouteKey,
+    },
+    /// Listed on a character's personal store.
+    Store {
+        character_id: u64,
+    },
+    /// No longer exists.
+    Destroyed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferReason {
+    Created,
+    Trade,
+    Drop,
+    Pickup,
+    /// Into or out of a mail.
+    Mail,
+    /// Listed on, withdrawn from or sold through a personal store.
+    Store,
+    /// Removed when its time ran out.
+    Expired,
+}
+
+/// One move of an item; `from` is `None` when it was created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ItemTransfer {
+    pub serial: u64,
+    pub from: Option<ItemHolder>,
+    pub to: ItemHolder,
+    pub reason: TransferReason,
+    pub at_ms: u64,
+}
+
+impl From<&ItemTransferRecord> for ItemTransfer {
+    fn from(record: &ItemTransferRecord) -> Self {
+        Self {
+            serial: record.serial,
+            from: record.from,
+            to: record.to,
+            reason: record.reason,
+            at_ms: record.at.timestamp_millis().max(0) as u64,
+        }
+    }
+}
+
+/// One move of an operation applied with [`ItemLedger::apply`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ItemMove {
+    pub serial: u64,
+    pub from: ItemHolder,
+    pub to: ItemHolder,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LedgerError {
+    #[error("item serial {0} was never minted")]
+    UnknownSerial(u64),
+    #[error("item serial {serial} is not held by {holder:?}")]
+    NotHolder { serial: u64, holder: ItemHolder },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    /// The same serial sits in more than one place.
+    Duplicate,
+    /// An item that never got a serial.
+    Unserialized,
+    /// A serial the ledger never minted.
+    Unknown,
+    /// A single copy, but not where the ledger last saw it.
+    Misplaced,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ItemAnomaly {
+    pub serial: u64,
+    pub kind: AnomalyKind,
+    /// Every place the serial was found, in scan order.
+    pub holders: Vec<ItemHolder>,
+    /// Holder according to the ledger.
+    pub owner: Option<ItemHolder>,
+}
+
+/// Result of one duplicate scan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct DupeReport {
+    pub scanned_at_ms: u64,
+    pub items_scanned: usize,
+    pub anomalies: Vec<ItemAnomaly>,
+}
+
+/// Transfers logged together, written as one operation when there is an id.
+#[derive(Debug, Clone)]
+struct UnsavedWrite {
+    operation_id: Option<u64>,
+    transfers: Vec<ItemTransfer>,
+}
+
+/// Serials, their current holders and the transfers not written yet.
+#[derive(Clone)]
+pub struct ItemLedger {
+    next_serial: Arc<AtomicU64>,
+    next_operation: Arc<AtomicU64>,
+    /// Held while ownership changes, so an operation's checks and moves are
+    /// not interleaved with another move.
+    moving: Arc<StdMutex<()>>,
+    // key: serial
+    owners: Arc<DashMap<u64, ItemHolder>>,
+    // key: serial; the item as minted
+    items: Arc<DashMap<u64, ItemInstance>>,
+    // key: serial, for items with an expiry
+    expiring: Arc<DashMap<u64, ItemInstance>>,
+    // key: transfer sequence, so writes keep their order
+    unsaved: Arc<DashMap<u64, UnsavedWrite>>,
+    transfer_seq: Arc<AtomicU64>,
+    last_report: Arc<StdMutex<Option<DupeReport>>>,
+}
+
+impl ItemLedger {
+    pub fn new(boot_time_ms: u64) -> Self {
+        Self {
+            next_serial: Arc::new(AtomicU64::new((boot_time_ms << SERIAL_SEQUENCE_BITS) | 1)),
+            next_operation: Arc::new(AtomicU64::new((boot_time_ms << SERIAL_SEQUENCE_BITS) | 1)),
+            moving: Arc::new(StdMutex::new(())),
+            owners: Arc::new(DashMap::new()),
+            items: Arc::new(DashMap::new()),
+            expiring: Arc::new(DashMap::new()),
+            unsaved: Arc::new(DashMap::new()),
+            transfer_seq: Arc::new(AtomicU64::new(0)),
+            last_report: Arc::new(StdMutex::new(None)),
+        }
+    }
+
+    /// Assigns a fresh serial to a newly created item held by `to`.
+    pub fn mint(&self, item: &mut ItemInstance, to: ItemHolder, now_ms: u64) -> u64 {
+        let serial = self.next_serial.fetch_add(1, Ordering::Relaxed);
+        item.serial = serial;
+        self.owners.insert(serial, to);
+        self.items.insert(serial, item.clone());
+        if item.expires_at_ms.is_some() {
+            self.expiring.insert(serial, item.clone());
+        }
+        self.log(
+            None,
+            vec![ItemTransfer {
+                serial,
+                from: None,
+                to,
+                reason: TransferReason::Created,
+                at_ms: now_ms,
+            }],
+        );
+        serial
+    }
+
+    /// Moves an item from `from` to `to`; refused unless `from` holds it.
+    pub fn transfer(
+        &self,
+        serial: u64,
+        from: ItemHolder,
+        to: ItemHolder,
+        reason: TransferReason,
+        now_ms: u64,
+    ) -> Result<ItemTransfer, LedgerError> {
+        let _moving = self.lock_moves();
+        self.check_holder(serial, from)?;
+        self.owners.insert(serial, to);
+
+        let transfer = ItemTransfer {
+            serial,
+            from: Some(from),
+            to,
+            reason,
+            at_ms: now_ms,
+        };
+        self.log(None, vec![transfer]);
+        Ok(transfer)
+    }
+
+    /// Applies `moves` as one operation: every `from` is checked first, and a
+    /// single refusal leaves all the items where they were. Returns the
+    /// operation id their transfers are written under.
+    pub fn apply(
+        &self,
+        moves: &[ItemMove],
+        reason: TransferReason,
+        now_ms: u64,
+    ) -> Result<u64, LedgerError> {
+        let _moving = self.lock_moves();
+        for (index, step) in moves.iter().enumerate() {
+            // A serial listed twice would be moved from a holder it has left.
+            if moves[..index]
+                .iter()
+                .any(|other| other.serial == step.serial)
+            {
+                return Err(LedgerError::NotHolder {
+                    serial: step.serial,
+                    holder: step.from,
+                });
+            }
+            self.check_holder(step.serial, step.from)?;
+        }
+
+        let transfers = moves
+            .iter()
+            .map(|step| {
+                self.owners.insert(step.serial, step.to);
+                ItemTransfer {
+                    serial: step.serial,
+                    from: Some(step.from),
+                    to: step.to,
+                    reason,
+                    at_ms: now_ms,
+                }
+            })
+            .collect();
+        let operation_id = self.next_operation.fetch_add(1, Ordering::Relaxed);
+        self.log(Some(operation_id), transfers);
+        Ok(operation_id)
+    }
+
+    /// Destroys every time-limited item whose expiry is at or before `now_ms`
+    /// and returns each with the holder it was taken from.
+    pub fn expire_due(&self, now_ms: u64) -> Vec<(ItemHolder, ItemInstance)> {
+        let due: Vec<u64> = self
+            .expiring
+            .iter()
+            .filter(|entry| entry.value().expires_at_ms.is_some_and(|at| at <= now_ms))
+            .map(|entry| *entry.key())
+            .collect();
+
+        let mut expired = Vec::with_capacity(due.len());
+        for serial in due {
+            let Some((_, item)) = self.expiring.remove(&serial) else {
+                continue;
+            };
+            let Some(holder) = self.owner(serial) else {
+                continue;
+            };
+            if holder == ItemHolder::Destroyed {
+                continue;
+            }
+            match self.transfer(
+                serial,
+                holder,
+                ItemHolder::Destroyed,
+                TransferReason::Expired,
+                now_ms,
+            ) {
+                Ok(_) => expired.push((holder, item)),
+                // Moved while being expired; the next sweep retries.
+                Err(LedgerError::NotHolder { .. }) => {
+                    self.expiring.insert(serial, item);
+                }
+                Err(err) => log::warn!("Failed to expire item serial {}: {}", serial, err),
+            }
+        }
+        expired
+    }
+
+    pub fn owner(&self, serial: u64) -> Option<ItemHolder> {
+        self.owners.get(&serial).map(|entry| *entry.value())
+    }
+
+    /// Item with `serial` as it was minted.
+    pub fn item(&self, serial: u64) -> Option<ItemInstance> {
+        self.items.get(&serial).map(|entry| entry.value().clone())
+    }
+
+    /// Definition the item with `serial` was minted from.
+    pub fn code(&self, serial: u64) -> Option<ItemCode> {
+        self.items
+            .get(&serial)
+            .map(|entry| ItemCode::new(entry.group, entry.index))
+    }
+
+    /// Whether `holder` has any item minted from `code`.
+    pub fn holds(&self, holder: ItemHolder, code: ItemCode) -> bool {
+        self.items.iter().any(|entry| {
+            ItemCode::new(entry.group, entry.index) == code
+                && self.owner(*entry.key()) == Some(holder)
+        })
+    }
+
+    /// Serials `holder` has now.
+    pub fn held_by(&self, holder: ItemHolder) -> Vec<u64> {
+        self.owners
+            .iter()
+            .filter(|entry| *entry.value() == holder)
+            .map(|entry| *entry.key())
+            .collect()
+    }
+
+    /// Puts back the holder the written transfers end on, without logging a
+    /// transfer. Run at startup, before anything moves.
+    pub fn restore(&self, serial: u64, holder: ItemHolder) {
+        self.owners.insert(serial, holder);
+    }
+
+    /// Like [`ItemLedger::restore`], for an item kept whole elsewhere (a
+    /// saved mail), so its definition and expiry come back too.
+    pub fn restore_item(&self, item: &ItemInstance, holder: ItemHolder) {
+        self.restore(item.serial, holder);
+        self.items.insert(item.serial, item.clone());
+        if item.expires_at_ms.is_some() {
+            self.expiring.insert(item.serial, item.clone());
+        }
+    }
+
+    /// Transfers of `serial` not written yet, oldest first.
+    pub fn unsaved_transfers(&self, serial: u64) -> Vec<ItemTransfer> {
+        let mut transfers: Vec<(u64, ItemTransfer)> = self
+            .unsaved
+            .iter()
+            .flat_map(|entry| {
+                let seq = *entry.key();
+                entry
+                    .value()
+                    .transfers
+                    .iter()
+                    .filter(|transfer| transfer.serial == serial)
+                    .map(|transfer| (seq, *transfer))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        transfers.sort_by_key(|(seq, _)| *seq);
+        transfers
+            .into_iter()
+            .map(|(_, transfer)| transfer)
+            .collect()
+    }
+
+    /// Checks every item found in `holdings` against the ledger and keeps the
+    /// report for [`ItemLedger::last_report`].
+    pub fn scan<'a>(
+        &self,
+        holdings: impl IntoIterator<Item = (ItemHolder, &'a ItemInstance)>,
+        now_ms: u64,
+    ) -> DupeReport {
+        let mut found: BTreeMap<u64, Vec<ItemHolder>> = BTreeMap::new();
+        let mut items_scanned = 0;
+        for (holder, item) in holdings {
+            items_scanned += 1;
+            found.entry(item.serial).or_default().push(holder);
+        }
+
+        let mut anomalies = Vec::new();
+        for (serial, holders) in found {
+            let owner = self.owner(serial);
+            let kind = if serial == 0 {
+                AnomalyKind::Unserialized
+            } else if holders.len() > 1 {
+                AnomalyKind::Duplicate
+            } else if owner.is_none() {
+                AnomalyKind::Unknown
+            } else if owner != Some(holders[0]) {
+                AnomalyKind::Misplaced
+            } else {
+                continue;
+            };
+            anomalies.push(ItemAnomaly {
+                serial,
+                kind,
+                holders,
+                owner,
+            });
+        }
+
+        let report = DupeReport {
+            scanned_at_ms: now_ms,
+            items_scanned,
+            anomalies,
+        };
+        *self
+            .last_report
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(report.clone());
+        report
+    }
+
+    pub fn last_report(&self) -> Option<DupeReport> {
+        self.last_report
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Writes logged transfers to MongoDB in the order they happened, the
+    /// transfers of an operation behind its journal entry. Failed writes stay
+    /// pending for the next call. Returns how many transfers were written.
+    pub async fn persist(&self, repository: &ItemTransferRepository) -> usize {
+        let mut pending: Vec<u64> = self.unsaved.iter().map(|entry| *entry.key()).collect();
+        pending.sort_unstable();
+        let mut written = 0;
+        for seq in pending {
+            let Some((_, write)) = self.unsaved.remove(&seq) else {
+                continue;
+            };
+
+            let records: Vec<ItemTransferRecord> = write
+                .transfers
+                .iter()
+                .map(|transfer| transfer_record(transfer, write.operation_id))
+                .collect();
+            let result = match write.operation_id {
+                Some(operation_id) => {
+                    repository
+                        .insert_operation(&ItemOperationRecord {
+                            id: None,
+                            operation_id,
+                            transfers: records,
+                            committed: false,
+                        })
+                        .await
+                }
+                None => match records.first() {
+                    Some(record) => repository.insert(record).await,
+                    None => Ok(()),
+                },
+            };
+
+            match result {
+                Ok(()) => written += write.transfers.len(),
+                Err(err) => {
+                    log::error!(
+                        "Failed to save {} item transfers (operation {:?}): {}",
+                        write.transfers.len(),
+                        write.operation_id,
+                        err
+                    );
+                    self.unsaved.insert(seq, write);
+                }
+            }
+        }
+        written
+    }
+
+    fn lock_moves(&self) -> std::sync::MutexGuard<'_, ()> {
+        self.moving
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn check_holder(&self, serial: u64, from: ItemHolder) -> Result<(), LedgerError> {
+        match self.owner(serial) {
+            None => Err(LedgerError::UnknownSerial(serial)),
+            Some(owner) if owner != from => Err(LedgerError::NotHolder {
+                serial,
+                holder: from,
+            }),
+            Some(_) => Ok(()),
+        }
+    }
+
+    fn log(&self, operation_id: Option<u64>, transfers: Vec<ItemTransfer>) {
+        let seq = self.transfer_seq.fetch_add(1, Ordering::Relaxed);
+        self.unsaved.insert(
+            seq,
+            UnsavedWrite {
+                operation_id,
+                transfers,
+            },
+        );
+    }
+}
+
+fn transfer_record(transfer: &ItemTransfer, operation_id: Option<u64>) -> ItemTransferRecord {
+    ItemTransferRecord {
+        id: None,
+        serial: transfer.serial,
+        from: transfer.from,
+        to: transfer.to,
+        reason: transfer.reason,
+        at: DateTime::from_timestamp_millis(transfer.at_ms as i64).unwrap_or_default(),
+        operation_id,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::ItemOptions;
+
+    fn jewel() -> ItemInstance {
+        ItemInstance {
+            serial: 0,
+            group: 14,
+            index: 13,
+            level: 0,
+            quantity: 1,
+            options: ItemOptions::default(),
+            expires_at_ms: None,
+        }
+    }
+
+    const ALICE: ItemHolder = ItemHolder::Character { character_id: 7 };
+    const BOB: ItemHolder = ItemHolder::Character { character_id: 8 };
+
+    #[test]
+    fn serials_are_unique_and_move_with_their_holder() {
+        let ledger = ItemLedger::new(1_000);
+        let (mut first, mut second) = (jewel(), jewel());
+        let a = ledger.mint(&mut first, ALICE, 1_000);
+        let b = ledger.mint(&mut second, ALICE, 1_000);
+        assert_ne!(a, b);
+        assert_eq!(first.serial, a);
+        assert!(a >> SERIAL_SEQUENCE_BITS == 1_000);
+
+        ledger
+            .transfer(a, ALICE, BOB, TransferReason::Trade, 2_000)
+            .unwrap();
+        assert_eq!(ledger.owner(a), Some(BOB));
+        assert_eq!(ledger.code(a), Some(ItemCode::new(14, 13)));
+        assert!(ledger.holds(BOB, ItemCode::new(14, 13)));
+        assert!(!ledger.holds(BOB, ItemCode::new(14, 14)));
+        assert_eq!(
+            ledger.transfer(a, ALICE, BOB, TransferReason::Trade, 3_000),
+            Err(LedgerError::NotHolder {
+                serial: a,
+                holder: ALICE
+            })
+        );
+        assert_eq!(
+            ledger.transfer(99, ALICE, BOB, TransferReason::Drop, 3_000),
+            Err(LedgerError::UnknownSerial(99))
+        );
+
+        let trail = ledger.unsaved_transfers(a);
+        assert_eq!(trail.len(), 2);
+        assert_eq!(trail[0].reason, TransferReason::Created);
+        assert_eq!((trail[1].from, trail[1].to), (Some(ALICE), BOB));
+    }
+
+    #[test]
+    fn operations_move_every_item_or_none() {
+        let ledger = ItemLedger::new(1_000);
+        let (mut sword, mut shield, mut zen_box) = (jewel(), jewel(), jewel());
+        let sword = ledger.mint(&mut sword, ALICE, 1_000);
+        let shield = ledger.mint(&mut shield, BOB, 1_000);
+        let zen_box = ledger.mint(&mut zen_box, BOB, 1_000);
+        let step = |serial, from, to| ItemMove { serial, from, to };
+
+        // Bob no longer has the box Alice was promised; nothing changes hands.
+        ledger
+            .transfer(
+                zen_box,
+                BOB,
+                ItemHolder::Destroyed,
+                TransferReason::Drop,
+                1_500,
+            )
+            .unwrap();
+        assert_eq!(
+            ledger.apply(
+                &[
+                    step(sword, ALICE, BOB),
+                    step(shield, BOB, ALICE),
+                    step(zen_box, BOB, ALICE)
+                ],
+                TransferReason::Trade,
+                2_000,
+            ),
+            Err(LedgerError::NotHolder {
+                serial: zen_box,
+                holder: BOB
+            })
+        );
+        assert_eq!(ledger.owner(sword), Some(ALICE));
+        assert_eq!(ledger.owner(shield), Some(BOB));
+        assert!(ledger
+            .apply(
+                &[step(sword, ALICE, BOB), step(sword, ALICE, BOB)],
+                TransferReason::Trade,
+                2_000,
+            )
+            .is_err());
+
+        let operation = ledger
+            .apply(
+                &[step(sword, ALICE, BOB), step(shield, BOB, ALICE)],
+                TransferReason::Trade,
+                3_000,
+            )
+            .unwrap();
+        assert_eq!(operation >> SERIAL_SEQUENCE_BITS, 1_000);
+        assert_eq!(ledger.owner(sword), Some(BOB));
+        assert_eq!(ledger.owner(shield), Some(ALICE));
+
+        let writes: Vec<UnsavedWrite> = ledger
+            .unsaved
+            .iter()
+            .filter(|entry| entry.value().operation_id == Some(operation))
+            .map(|entry| entry.value().clone())
+            .collect();
+        assert_eq!(writes.len(), 1);
+        assert_eq!(writes[0].transfers.len(), 2);
+        assert_eq!(ledger.unsaved_transfers(shield).last().unwrap().to, ALICE);
+    }
+
+    #[test]
+    fn expired_items_are_destroyed_once() {
+        let ledger = ItemLedger::new(1_000);
+        let mut seal = ItemInstance {
+            expires_at_ms: Some(5_000),
+            ..jewel()
+        };
+        let mut permanent = jewel();
+        ledger.mint(&mut seal, ALICE, 1_000);
+        ledger.mint(&mut permanent, ALICE, 1_000);
+        ledger
+            .transfer(seal.serial, ALICE, BOB, TransferReason::Trade, 2_000)
+            .unwrap();
+
+        assert!(ledger.expire_due(4_999).is_empty());
+        let expired = ledger.expire_due(5_000);
+        assert_eq!(expired, vec![(BOB, seal.clone())]);
+        assert_eq!(ledger.owner(seal.serial), Some(ItemHolder::Destroyed));
+        assert_eq!(ledger.owner(permanent.serial), Some(ALICE));
+        assert!(ledger.expire_due(9_000).is_empty());
+
+        let trail = ledger.unsaved_transfers(seal.serial);
+        assert_eq!(trail.last().unwrap().reason, TransferReason::Expired);
+
+        let report = ledger.scan([(BOB, &seal)], 9_000);
+        assert_eq!(report.anomalies[0].kind, AnomalyKind::Misplaced);
+    }
+
+    #[test]
+    fn scan_reports_copies_forgeries_and_strays() {
+        let ledger = ItemLedger::new(1_000);
+        let mut clean = jewel();
+        ledger.mint(&mut clean, ALICE, 1_000);
+        let mut duped = jewel();
+        ledger.mint(&mut duped, ALICE, 1_000);
+        let mut stray = jewel();
+        ledger.mint(&mut stray, ALICE, 1_000);
+        let forged = ItemInstance {
+            serial: 42,
+            ..jewel()
+        };
+        let unserialized = jewel();
+
+        let report = ledger.scan(
+            [
+                (ALICE, &clean),
+                (ALICE, &duped),
+                (BOB, &duped),
+                (BOB, &stray),
+                (BOB, &forged),
+                (BOB, &unserialized),
+            ],
+            5_000,
+        );
+
+        assert_eq!(report.items_scanned, 6);
+        let kinds: Vec<(u64, AnomalyKind)> = report
+            .anomalies
+            .iter()
+            .map(|anomaly| (anomaly.serial, anomaly.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (0, AnomalyKind::Unserialized),
+                (42, AnomalyKind::Unknown),
+                (duped.serial, AnomalyKind::Duplicate),
+                (stray.serial, AnomalyKind::Misplaced),
+            ]
+        );
+        assert_eq!(report.anomalies[2].holders, vec![ALICE, BOB]);
+        assert_eq!(ledger.last_report(), Some(report));
+    }
+}
@@ -0,0 +1,201 @@
+use common::ItemCode;
+use protocol::ItemInstance;
+
+pub const MAX_ITEM_LEVEL: u8 = 15;
+pub const MAX_ADDITIONAL_LEVEL: u8 = 7;
+/// Six excellent options, one bit each.
+pub const EXCELLENT_OPTION_MASK: u8 = 0b0011_1111;
+pub const MAX_SOCKET_SLOTS: usize = 5;
+pub const MAX_SOCKET_BLOB_LEN: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ItemOptionError {
+    #[error("unknown item {0}")]
+    UnknownItem(ItemCode),
+
+    #[error("item level {0} exceeds +{max}", max = MAX_ITEM_LEVEL)]
+    LevelOutOfRange(u8),
+
+    #[error("item quantity must be at least 1")]
+    EmptyStack,
+
+    #[error("item {code} stacks up to {max}, got {quantity}")]
+    StackTooLarge {
+        code: ItemCode,
+        quantity: u16,
+        max: u16,
+    },
+
+    #[error("excellent option mask {0:#04x} has unknown bits")]
+    UnknownExcellentOption(u8),
+
+    #[error("additional option level {0} exceeds {max}", max = MAX_ADDITIONAL_LEVEL)]
+    AdditionalOutOfRange(u8),
+
+    #[error("item {0} cannot carry options")]
+    OptionsNotAllowed(ItemCode),
+
+    #[error("item has {0} sockets, at most {max} allowed", max = MAX_SOCKET_SLOTS)]
+    TooManySockets(usize),
+
+    #[error("socket {slot} payload has {len} bytes, expected 1..={max}", max = MAX_SOCKET_BLOB_LEN)]
+    InvalidSocketPayload { slot: usize, len: usize },
+}
+
+/// Checks option legality for an item being created or changing hands.
+pub fn validate_item(item: &ItemInstance) -> Result<(), ItemOptionError> {
+    let code = ItemCode::new(item.group, item.index);
+    let Some(definition) = code.definition() else {
+        return Err(ItemOptionError::UnknownItem(code));
+    };
+    if item.level > MAX_ITEM_LEVEL {
+        return Err(ItemOptionError::LevelOutOfRange(item.level));
+    }
+    if item.quantity == 0 {
+        return Err(ItemOptionError::EmptyStack);
+    }
+    if item.quantity > definition.max_stack {
+        return Err(ItemOptionError::StackTooLarge {
+            code,
+            quantity: item.quantity,
+            max: definition.max_stack,
+        });
+    }
+
+    let options = &item.options;
+    if options.excellent & !EXCELLENT_OPTION_MASK != 0 {
+        return Err(ItemOptionError::UnknownExcellentOption(options.excellent));
+    }
+    if options.additional > MAX_ADDITIONAL_LEVEL {
+        return Err(ItemOptionError::AdditionalOutOfRange(options.additional));
+    }
+
+    let has_options = options.excellent != 0
+        || options.luck
+        || options.additional != 0
+        || !options.sockets.is_empty();
+    if has_options && !definition.takes_options() {
+        return Err(ItemOptionError::OptionsNotAllowed(code));
+    }
+
+    if options.sockets.len() > MAX_SOCKET_SLOTS {
+        return Err(ItemOptionError::TooManySockets(options.sockets.len()));
+    }
+    for (slot, payload) in options.sockets.iter().enumerate() {
+        if payload.is_empty() || payload.len() > MAX_SOCKET_BLOB_LEN {
+            return Err(ItemOptionError::InvalidSocketPayload {
+                slot,
+                len: payload.len(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+pub fn validate_items<'a>(
+    items: impl IntoIterator<Item = &'a ItemInstance>,
+) -> Result<(), ItemOptionError> {
+    items.into_iter().try_for_each(validate_item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::ItemOptions;
+
+    fn sword(options: ItemOptions) -> ItemInstance {
+        ItemInstance {
+            serial: 0,
+            group: 0,
+            index: 5,
+            level: 9,
+            quantity: 1,
+            options,
+            expires_at_ms: None,
+        }
+    }
+
+    #[test]
+    fn accepts_fully_optioned_weapon() {
+        let item = sword(ItemOptions {
+            excellent: EXCELLENT_OPTION_MASK,
+            luck: true,
+            additional: MAX_ADDITIONAL_LEVEL,
+            sockets: vec![vec![1, 2], vec![3]],
+        });
+
+        assert_eq!(validate_item(&item), Ok(()));
+    }
+
+    #[test]
+    fn rejects_out_of_range_options() {
+        let excellent = sword(ItemOptions {
+            excellent: 0b0100_0000,
+            ..ItemOptions::default()
+        });
+        let additional = sword(ItemOptions {
+            additional: 8,
+            ..ItemOptions::default()
+        });
+        let sockets = sword(ItemOptions {
+            sockets: vec![vec![1]; MAX_SOCKET_SLOTS + 1],
+            ..ItemOptions::default()
+        });
+
+        assert_eq!(
+            validate_item(&excellent),
+            Err(ItemOptionError::UnknownExcellentOption(0b0100_0000))
+        );
+        assert_eq!(
+            validate_item(&additional),
+            Err(ItemOptionError::AdditionalOutOfRange(8))
+        );
+        assert_eq!(
+            validate_item(&sockets),
+            Err(ItemOptionError::TooManySockets(MAX_SOCKET_SLOTS + 1))
+        );
+    }
+
+    #[test]
+    fn consumables_cannot_carry_options() {
+        let jewel = ItemInstance {
+            serial: 0,
+            group: 14,
+            index: 13,
+            level: 0,
+            quantity: 1,
+            options: ItemOptions {
+                luck: true,
+                ..ItemOptions::default()
+            },
+            expires_at_ms: None,
+        };
+
+        assert_eq!(
+            validate_items([&jewel]),
+            Err(ItemOptionError::OptionsNotAllowed(ItemCode::new(14, 13)))
+        );
+    }
+
+    #[test]
+    fn rejects_items_outside_the_catalog() {
+        let mut unknown = sword(ItemOptions::default());
+        unknown.index = 999;
+        let mut jewels = sword(ItemOptions::default());
+        (jewels.group, jewels.index, jewels.level, jewels.quantity) = (14, 13, 0, 5);
+
+        assert_eq!(
+            validate_item(&unknown),
+            Err(ItemOptionError::UnknownItem(ItemCode::new(0, 999)))
+        );
+        assert_eq!(
+            validate_item(&jewels),
+            Err(ItemOptionError::StackTooLarge {
+                code: ItemCode::new(14, 13),
+                quantity: 5,
+                max: 1,
+            })
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! Latency-compensated hit validation.
+//!
+//! A client aims at where it last saw its target, which is where the server
+//! had the target about one round trip earlier. Every entity keeps a short
+//! history of its positions, and a hit is checked against the target's
+//! position rewound by the attacker's RTT as well as its current one. The
+//! rewind is capped, so a high-ping client cannot hit targets that left long
+//! ago.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dashmap::DashMap;
+
+/// Longest rewind granted, whatever the attacker's RTT.
+pub const MAX_REWIND_MS: u64 = 250;
+/// How far back positions are kept; covers the longest rewind.
+const HISTORY_MS: u64 = 500;
+
+/// Recent positions of one entity, oldest first.
+#[derive(Debug, Clone)]
+pub struct PositionHistory {
+    // (at_ms, position), one entry per position change
+    samples: VecDeque<(u64, (u16, u16))>,
+}
+
+impl PositionHistory {
+    pub fn new(at_ms: u64, position: (u16, u16)) -> Self {
+        Self {
+            samples: VecDeque::from([(at_ms, position)]),
+        }
+    }
+
+    /// Notes that the entity stands on `position` from `at_ms` on.
+    pub fn record(&mut self, at_ms: u64, position: (u16, u16)) {
+        if self.latest() == position {
+            return;
+        }
+        self.samples.push_back((at_ms, position));
+
+        // Keep the sample in effect at the cutoff, drop the older ones.
+        let cutoff = at_ms.saturating_sub(HISTORY_MS);
+        while self.samples.len() > 1 && self.samples[1].0 <= cutoff {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn latest(&self) -> (u16, u16) {
+        self.samples
+            .back()
+            .map(|(_, position)| *position)
+            .unwrap_or_default()
+    }
+
+    /// Where the entity stood at `at_ms`; the oldest known position when
+    /// `at_ms` predates the history.
+    pub fn position_at(&self, at_ms: u64) -> (u16, u16) {
+        self.samples
+            .iter()
+            .rev()
+            .find(|(sample_ms, _)| *sample_ms <= at_ms)
+            .or(self.samples.front())
+            .map(|(_, position)| *position)
+            .unwrap_or_default()
+    }
+}
+
+/// Whether a target is within `range` tiles of `attacker`, either now or at
+/// the moment the attacker saw it, `rtt_ms` ago.
+pub fn in_reach(
+    attacker: (u16, u16),
+    target: &PositionHistory,
+    now_ms: u64,
+    rtt_ms: u64,
+    range: u16,
+) -> bool {
+    let rewound = target.position_at(now_ms.saturating_sub(rtt_ms.min(MAX_REWIND_MS)));
+    tile_distance(attacker, target.latest()) <= range || tile_distance(attacker, rewound) <= range
+}
+
+/// Tiles between two positions, diagonal steps counting as one.
+fn tile_distance(a: (u16, u16), b: (u16, u16)) -> u16 {
+    a.0.abs_diff(b.0).max(a.1.abs_diff(b.1))
+}
+
+/// Round-trip time measured on each session's connection.
+#[derive(Clone, Default)]
+pub struct SessionLatency {
+    // key: session_id, value: RTT in ms
+    rtt_ms: Arc<DashMap<u64, u64>>,
+}
+
+impl SessionLatency {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, session_id: u64, rtt: Duration) {
+        self.rtt_ms.insert(session_id, rtt.as_millis() as u64);
+    }
+
+    /// Latest RTT of the session; 0 before any measurement.
+    pub fn rtt_ms(&self, session_id: u64) -> u64 {
+        self.rtt_ms
+            .get(&session_id)
+            .map(|entry| *entry.value())
+            .unwrap_or_default()
+    }
+
+    pub fn forget(&self, session_id: u64) {
+        self.rtt_ms.remove(&session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewinds_to_the_position_held_at_the_time() {
+        let mut history = PositionHistory::new(1_000, (10, 10));
+        history.record(1_100, (12, 10));
+        history.record(1_200, (14, 10));
+        history.record(1_250, (14, 10));
+
+        assert_eq!(history.position_at(900), (10, 10));
+        assert_eq!(history.position_at(1_150), (12, 10));
+        assert_eq!(history.position_at(1_300), (14, 10));
+        assert_eq!(history.latest(), (14, 10));
+
+        // Samples older than the window go, except the one in effect at its start.
+        history.record(1_700, (16, 10));
+        assert_eq!(history.position_at(1_000), (14, 10));
+    }
+
+    #[test]
+    fn laggy_attackers_hit_where_they_saw_the_target() {
+        let mut target = PositionHistory::new(1_000, (20, 20));
+        target.record(1_080, (24, 20));
+        let attacker = (19, 20);
+        let now = 1_120;
+
+        assert!(!in_reach(attacker, &target, now, 0, 2));
+        assert!(in_reach(attacker, &target, now, 120, 2));
+        // The rewind is capped.
+        assert!(!in_reach(attacker, &target, 1_500, 1_000, 2));
+    }
+}
@@ -0,0 +1,638 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use dashmap::{DashMap, DashSet};
+use protocol::{ItemInstance, MailEntry, ServerErrorKind};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::item_ledger::{ItemHolder, ItemLedger, ItemMove, LedgerError, TransferReason};
+use super::wallets::{WalletError, ZenWallets};
+use crate::db::{models::RewardMailRecord, repository::RewardMailRepository};
+
+/// Event that granted a reward.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum RewardSource {
+    BloodCastle {
+        level: u8,
+    },
+    DevilSquare {
+        level: u8,
+    },
+    Doppelganger {
+        waves: u8,
+    },
+    /// Items and zen of a new character.
+    StartingKit,
+    Quest {
+        quest_id: u16,
+    },
+    CashShop {
+        product_id: u32,
+    },
+}
+
+impl RewardSource {
+    pub fn label(&self) -> String {
+        match self {
+            RewardSource::BloodCastle { level } => format!("Blood Castle {level}"),
+            RewardSource::DevilSquare { level } => format!("Devil Square {level}"),
+            RewardSource::Doppelganger { waves } => format!("Doppelganger ({waves} waves)"),
+            RewardSource::StartingKit => "Starting kit".to_string(),
+            RewardSource::Quest { quest_id } => format!("Quest {quest_id}"),
+            RewardSource::CashShop { product_id } => format!("Cash shop ({product_id})"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RewardBundle {
+    pub zen: u64,
+    pub items: Vec<ItemInstance>,
+}
+
+impl RewardBundle {
+    /// Inventory slots needed to receive the items directly.
+    pub fn required_slots(&self) -> usize {
+        self.items.len()
+    }
+}
+
+/// Recipient state at the moment a reward is granted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RewardRecipient {
+    pub online: bool,
+    pub free_inventory_slots: u16,
+}
+
+impl RewardRecipient {
+    fn can_receive(&self, reward: &RewardBundle) -> bool {
+        self.online && self.free_inventory_slots as usize >= reward.required_slots()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardDelivery {
+    Direct,
+    Mailed { mail_id: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewardMail {
+    pub mail_id: u64,
+    pub character_id: u64,
+    pub source: RewardSource,
+    pub reward: RewardBundle,
+    pub received_at_ms: u64,
+}
+
+impl RewardMail {
+    pub fn to_protocol(&self) -> MailEntry {
+        MailEntry {
+            mail_id: self.mail_id,
+            source: self.source.label(),
+            zen: self.reward.zen,
+            items: self.reward.items.clone(),
+            received_at_ms: self.received_at_ms,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MailboxError {
+    #[error("mail {0} not found")]
+    NotFound(u64),
+
+    #[error("inventory needs {required} free slots, only {available} available")]
+    InventoryFull { required: usize, available: u16 },
+
+    #[error(transparent)]
+    Wallet(#[from] WalletError),
+
+    #[error(transparent)]
+    Ledger(#[from] LedgerError),
+}
+
+impl MailboxError {
+    pub fn kind(&self) -> ServerErrorKind {
+        match self {
+            Self::NotFound(_) => ServerErrorKind::NotFound,
+            Self::InventoryFull { .. } => ServerErrorKind::InventoryFull,
+            Self::Wallet(_) => ServerErrorKind::InvalidAction,
+            Self::Ledger(_) => ServerErrorKind::ItemNotHeld,
+        }
+    }
+}
+
+/// Per-character mailbox used for rewards that cannot be delivered in place.
+///
+/// Loaded from MongoDB at boot; new, changed and claimed mail is written back
+/// in batches by [`RewardMailbox::persist`].
+#[derive(Clone)]
+pub struct RewardMailbox {
+    mail_seq: Arc<AtomicU64>,
+    // key: character_id
+    pending: Arc<DashMap<u64, Vec<RewardMail>>>,
+    // key: mail_id
+    dirty: Arc<DashSet<u64>>,
+    ledger: ItemLedger,
+    wallets: ZenWallets,
+}
+
+impl RewardMailbox {
+    /// Mailbox whose granted items get their serials from `ledger` and whose
+    /// zen goes to `wallets`.
+    pub fn new(ledger: ItemLedger, wallets: ZenWallets) -> Self {
+        Self {
+            mail_seq: Arc::new(AtomicU64::new(1)),
+            pending: Arc::new(DashMap::new()),
+            dirty: Arc::new(DashSet::new()),
+            ledger,
+            wallets,
+        }
+    }
+
+    /// Puts back saved mail, with its items held by their mail in the ledger.
+    pub fn load(&self, records: impl IntoIterator<Item = RewardMailRecord>) {
+        for record in records {
+            let holder = ItemHolder::Mail {
+                mail_id: record.mail_id,
+            };
+            for item in &record.items {
+                self.ledger.restore_item(item, holder);
+            }
+            self.mail_seq
+                .fetch_max(record.mail_id + 1, Ordering::Relaxed);
+            self.pending
+                .entry(record.character_id)
+                .or_default()
+                .push(RewardMail {
+                    mail_id: record.mail_id,
+                    character_id: record.character_id,
+                    source: record.source,
+                    reward: RewardBundle {
+                        zen: record.zen,
+                        items: record.items,
+                    },
+                    received_at_ms: record.received_at_ms,
+                });
+        }
+        for mut entry in self.pending.iter_mut() {
+            entry.value_mut().sort_by_key(|mail| mail.mail_id);
+        }
+    }
+
+    /// Delivers directly when the recipient is online with room, otherwise mails it.
+    /// The items are minted to wherever they end up; the zen of a direct
+    /// delivery goes to the recipient's wallet.
+    pub fn grant(
+        &self,
+        character_id: u64,
+        source: RewardSource,
+        mut reward: RewardBundle,
+        recipient: RewardRecipient,
+        now_ms: u64,
+    ) -> RewardDelivery {
+        if recipient.can_receive(&reward) && self.wallets.credit(character_id, reward.zen).is_ok() {
+            for item in &mut reward.items {
+                self.ledger
+                    .mint(item, ItemHolder::Character { character_id }, now_ms);
+            }
+            return RewardDelivery::Direct;
+        }
+
+        let mail_id = self.mail_seq.fetch_add(1, Ordering::Relaxed);
+        for item in &mut reward.items {
+            self.ledger.mint(item, ItemHolder::Mail { mail_id }, now_ms);
+        }
+        self.dirty.insert(mail_id);
+        self.pending
+            .entry(character_id)
+            .or_default()
+            .push(RewardMail {
+                mail_id,
+                character_id,
+                source,
+                reward,
+                received_at_ms: now_ms,
+            });
+
+        RewardDelivery::Mailed { mail_id }
+    }
+
+    pub fn pending_for(&self, character_id: u64) -> Vec<RewardMail> {
+        self.pending
+            .get(&character_id)
+            .map(|entry| entry.value().clone())
+            .unwrap_or_default()
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.iter().map(|entry| entry.value().len()).sum()
+    }
+
+    /// Every item waiting in a mail, with the mail holding it.
+    pub fn holdings(&self) -> Vec<(ItemHolder, ItemInstance)> {
+        self.pending
+            .iter()
+            .flat_map(|entry| {
+                entry
+                    .value()
+                    .iter()
+                    .flat_map(|mail| {
+                        let holder = ItemHolder::Mail {
+                            mail_id: mail.mail_id,
+                        };
+                        mail.reward
+                            .items
+                            .iter()
+                            .map(move |item| (holder, item.clone()))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Delivers a mail once the recipient has room for its items: the items
+    /// move to the recipient in the item ledger and the zen goes to its
+    /// wallet, and only then is the mail removed. A refusal leaves the mail,
+    /// the items and the wallet as they were. Items whose time ran out are
+    /// left behind for the expiry sweep.
+    pub fn claim(
+        &self,
+        character_id: u64,
+        mail_id: u64,
+        free_inventory_slots: u16,
+        now_ms: u64,
+    ) -> Result<RewardMail, MailboxError> {
+        let mut entries = self
+            .pending
+            .get_mut(&character_id)
+            .ok_or(MailboxError::NotFound(mail_id))?;
+
+        let index = entries
+            .iter()
+            .position(|mail| mail.mail_id == mail_id)
+            .ok_or(MailboxError::NotFound(mail_id))?;
+
+        let mail = &entries[index];
+        let items: Vec<&ItemInstance> = mail
+            .reward
+            .items
+            .iter()
+            .filter(|item| !is_expired(item, now_ms))
+            .collect();
+        if (free_inventory_slots as usize) < items.len() {
+            return Err(MailboxError::InventoryFull {
+                required: items.len(),
+                available: free_inventory_slots,
+            });
+        }
+
+        let moves: Vec<ItemMove> = items
+            .iter()
+            .map(|item| ItemMove {
+                serial: item.serial,
+                from: ItemHolder::Mail { mail_id },
+                to: ItemHolder::Character { character_id },
+            })
+            .collect();
+        let zen = mail.reward.zen;
+        self.wallets.credit(character_id, zen)?;
+        if let Err(err) = self.ledger.apply(&moves, TransferReason::Mail, now_ms) {
+            self.wallets.debit(character_id, zen);
+            return Err(err.into());
+        }
+
+        let mut mail = entries.remove(index);
+        mail.reward.items.retain(|item| !is_expired(item, now_ms));
+        let now_empty = entries.is_empty();
+        drop(entries);
+
+        if now_empty {
+            self.pending
+                .remove_if(&character_id, |_, entries| entries.is_empty());
+        }
+        self.dirty.insert(mail_id);
+
+        Ok(mail)
+    }
+
+    /// Takes an expired item out of its mail, dropping the mail when nothing
+    /// is left in it. Returns the recipient.
+    pub fn remove_item(&self, mail_id: u64, serial: u64) -> Option<u64> {
+        let mut removed_from = None;
+        for mut entry in self.pending.iter_mut() {
+            let Some(mail) = entry
+                .value_mut()
+                .iter_mut()
+                .find(|mail| mail.mail_id == mail_id)
+            else {
+                continue;
+            };
+            mail.reward.items.retain(|item| item.serial != serial);
+            self.dirty.insert(mail_id);
+            let character_id = *entry.key();
+            entry
+                .value_mut()
+                .retain(|mail| mail.reward.zen > 0 || !mail.reward.items.is_empty());
+            removed_from = Some(character_id);
+            break;
+        }
+
+        if let Some(character_id) = removed_from {
+            self.pending
+                .remove_if(&character_id, |_, entries| entries.is_empty());
+        }
+        removed_from
+    }
+
+    fn find(&self, mail_id: u64) -> Option<RewardMail> {
+        self.pending.iter().find_map(|entry| {
+            entry
+                .value()
+                .iter()
+                .find(|mail| mail.mail_id == mail_id)
+                .cloned()
+        })
+    }
+
+    /// Writes every new or changed mail to MongoDB and deletes the ones that
+    /// are gone. Failed writes stay pending for the next call. Returns how
+    /// many mails were written.
+    pub async fn persist(&self, repository: &RewardMailRepository) -> usize {
+        let pending: Vec<u64> = self.dirty.iter().map(|entry| *entry).collect();
+        let mut written = 0;
+        for mail_id in pending {
+            self.dirty.remove(&mail_id);
+            let result = match self.find(mail_id) {
+                Some(mail) => repository.save(&mail_record(mail)).await,
+                None => repository.delete(mail_id).await,
+            };
+            match result {
+                Ok(()) => written += 1,
+                Err(err) => {
+                    log::error!("Failed to save mail {}: {}", mail_id, err);
+                    self.dirty.insert(mail_id);
+                }
+            }
+        }
+        written
+    }
+}
+
+fn mail_record(mail: RewardMail) -> RewardMailRecord {
+    RewardMailRecord {
+        id: None,
+        mail_id: mail.mail_id,
+        character_id: mail.character_id,
+        source: mail.source,
+        zen: mail.reward.zen,
+        items: mail.reward.items,
+        received_at_ms: mail.received_at_ms,
+    }
+}
+
+fn is_expired(item: &ItemInstance, now_ms: u64) -> bool {
+    item.expires_at_ms.is_some_and(|at| at <= now_ms)
+}
+
+impl Default for RewardMailbox {
+    fn default() -> Self {
+        Self::new(ItemLedger::new(0), ZenWallets::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::ItemOptions;
+
+    fn sample_reward() -> RewardBundle {
+        RewardBundle {
+            zen: 100_000,
+            items: vec![ItemInstance {
+                serial: 0,
+                group: 14,
+                index: 13,
+                level: 0,
+                quantity: 1,
+                options: ItemOptions::default(),
+                expires_at_ms: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn online_recipient_with_room_receives_directly() {
+        let wallets = ZenWallets::new();
+        let mailbox = RewardMailbox::new(ItemLedger::new(0), wallets.clone());
+        let delivery = mailbox.grant(
+            7,
+            RewardSource::BloodCastle { level: 1 },
+            sample_reward(),
+            RewardRecipient {
+                online: true,
+                free_inventory_slots: 10,
+            },
+            100,
+        );
+
+        assert_eq!(delivery, RewardDelivery::Direct);
+        assert_eq!(mailbox.pending_count(), 0);
+        assert_eq!(wallets.balance(7), 100_000);
+    }
+
+    #[test]
+    fn offline_or_full_recipient_gets_mail() {
+        let mailbox = RewardMailbox::default();
+        let offline = mailbox.grant(
+            7,
+            RewardSource::DevilSquare { level: 2 },
+            sample_reward(),
+            RewardRecipient {
+                online: false,
+                free_inventory_slots: 10,
+            },
+            100,
+        );
+        let full = mailbox.grant(
+            7,
+            RewardSource::BloodCastle { level: 3 },
+            sample_reward(),
+            RewardRecipient {
+                online: true,
+                free_inventory_slots: 0,
+            },
+            200,
+        );
+
+        assert!(matches!(offline, RewardDelivery::Mailed { .. }));
+        assert!(matches!(full, RewardDelivery::Mailed { .. }));
+
+        let pending = mailbox.pending_for(7);
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].to_protocol().source, "Devil Square 2");
+    }
+
+    #[test]
+    fn claim_requires_inventory_room() {
+        let ledger = ItemLedger::new(0);
+        let wallets = ZenWallets::new();
+        let mailbox = RewardMailbox::new(ledger.clone(), wallets.clone());
+        let RewardDelivery::Mailed { mail_id } = mailbox.grant(
+            9,
+            RewardSource::BloodCastle { level: 1 },
+            sample_reward(),
+            RewardRecipient {
+                online: false,
+                free_inventory_slots: 0,
+            },
+            100,
+        ) else {
+            panic!("expected mail");
+        };
+        let (holder, item) = mailbox.holdings().pop().expect("mailed item");
+        assert_eq!(holder, ItemHolder::Mail { mail_id });
+        assert_eq!(ledger.owner(item.serial), Some(holder));
+
+        assert_eq!(
+            mailbox.claim(9, mail_id, 0, 200),
+            Err(MailboxError::InventoryFull {
+                required: 1,
+                available: 0
+            })
+        );
+
+        let mail = mailbox.claim(9, mail_id, 4, 200).expect("claim");
+        assert_eq!(mail.reward.zen, 100_000);
+        assert_eq!(wallets.balance(9), 100_000);
+        assert_eq!(mailbox.pending_count(), 0);
+        assert_eq!(
+            ledger.owner(item.serial),
+            Some(ItemHolder::Character { character_id: 9 })
+        );
+        assert_eq!(
+            mailbox.claim(9, mail_id, 4, 300),
+            Err(MailboxError::NotFound(mail_id))
+        );
+    }
+
+    #[test]
+    fn expired_items_stay_out_of_claims() {
+        let ledger = ItemLedger::new(0);
+        let mailbox = RewardMailbox::new(ledger.clone(), ZenWallets::new());
+        let mut reward = sample_reward();
+        reward.items.push(ItemInstance {
+            expires_at_ms: Some(500),
+            ..reward.items[0].clone()
+        });
+        let recipient = RewardRecipient {
+            online: false,
+            free_inventory_slots: 0,
+        };
+        let RewardDelivery::Mailed { mail_id } = mailbox.grant(
+            9,
+            RewardSource::BloodCastle { level: 1 },
+            reward,
+            recipient,
+            100,
+        ) else {
+            panic!("expected mail");
+        };
+
+        let mail = mailbox.claim(9, mail_id, 1, 600).expect("claim");
+        assert_eq!(mail.reward.items.len(), 1);
+        assert_eq!(mail.reward.items[0].expires_at_ms, None);
+
+        let seal = RewardBundle {
+            zen: 0,
+            items: vec![ItemInstance {
+                expires_at_ms: Some(500),
+                ..sample_reward().items[0].clone()
+            }],
+        };
+        let RewardDelivery::Mailed { mail_id } = mailbox.grant(
+            9,
+            RewardSource::BloodCastle { level: 1 },
+            seal,
+            recipient,
+            100,
+        ) else {
+            panic!("expected mail");
+        };
+        // The item left behind by the claim expires too, from a mail that is gone.
+        let expired = ledger.expire_due(600);
+        assert_eq!(expired.len(), 2);
+        for (holder, item) in expired {
+            let ItemHolder::Mail { mail_id: held_in } = holder else {
+                panic!("expected mailed items");
+            };
+            let recipient = (held_in == mail_id).then_some(9);
+            assert_eq!(mailbox.remove_item(held_in, item.serial), recipient);
+        }
+        assert_eq!(mailbox.pending_count(), 0);
+    }
+
+    #[test]
+    fn claim_waits_for_room_in_the_wallet() {
+        let wallets = ZenWallets::new();
+        let mailbox = RewardMailbox::new(ItemLedger::new(0), wallets.clone());
+        let RewardDelivery::Mailed { mail_id } = mailbox.grant(
+            9,
+            RewardSource::BloodCastle { level: 1 },
+            sample_reward(),
+            RewardRecipient {
+                online: false,
+                free_inventory_slots: 0,
+            },
+            100,
+        ) else {
+            panic!("expected mail");
+        };
+
+        wallets.credit(9, protocol::MAX_ZEN).expect("fill wallet");
+        assert!(matches!(
+            mailbox.claim(9, mail_id, 4, 200),
+            Err(MailboxError::Wallet(_))
+        ));
+        assert_eq!(mailbox.pending_for(9).len(), 1);
+    }
+
+    #[test]
+    fn refused_item_moves_leave_the_mail_in_place() {
+        let ledger = ItemLedger::new(0);
+        let wallets = ZenWallets::new();
+        let mailbox = RewardMailbox::new(ledger.clone(), wallets.clone());
+        let RewardDelivery::Mailed { mail_id } = mailbox.grant(
+            9,
+            RewardSource::BloodCastle { level: 1 },
+            sample_reward(),
+            RewardRecipient {
+                online: false,
+                free_inventory_slots: 0,
+            },
+            100,
+        ) else {
+            panic!("expected mail");
+        };
+        let (holder, item) = mailbox.holdings().pop().expect("mailed item");
+        ledger
+            .transfer(
+                item.serial,
+                holder,
+                ItemHolder::Destroyed,
+                TransferReason::Expired,
+                150,
+            )
+            .unwrap();
+
+        assert!(matches!(
+            mailbox.claim(9, mail_id, 4, 200),
+            Err(MailboxError::Ledger(_))
+        ));
+        assert_eq!(mailbox.pending_for(9).len(), 1);
+        assert_eq!(wallets.balance(9), 0);
+    }
+}
@@ -0,0 +1,260 @@
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use protocol::{MaintenanceNotice, RouteKey};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Town players are moved to when their map closes (Lorencia).
+pub const DEFAULT_FALLBACK_MAP_ID: u16 = 0;
+pub const DEFAULT_COUNTDOWN_SECS: u32 = 300;
+pub const MAX_COUNTDOWN_SECS: u32 = 3_600;
+
+/// Remaining seconds at which the countdown is announced again.
+const NOTICE_MARKS_SECS: [u32; 8] = [600, 300, 120, 60, 30, 10, 5, 0];
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MaintenanceError {
+    #[error("world {0} does not exist")]
+    UnknownWorld(u16),
+
+    #[error("map {map_id} does not exist in world {world_id}")]
+    UnknownMap { world_id: u16, map_id: u16 },
+
+    #[error("countdown of {0}s exceeds {max}s", max = MAX_COUNTDOWN_SECS)]
+    CountdownTooLong(u32),
+
+    #[error("maintenance already scheduled for {0}")]
+    AlreadyScheduled(MaintenanceScope),
+
+    #[error("no open fallback town (map {0}) to migrate players to")]
+    NoFallbackTown(u16),
+}
+
+/// A whole world, or a single map across every entry point and instance of it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, ToSchema)]
+pub struct MaintenanceScope {
+    pub world_id: u16,
+    pub map_id: Option<u16>,
+}
+
+impl MaintenanceScope {
+    pub fn covers(&self, world_id: u16, map_id: u16) -> bool {
+        self.world_id == world_id && self.map_id.is_none_or(|scoped| scoped == map_id)
+    }
+
+    pub fn covers_route(&self, route: RouteKey) -> bool {
+        self.covers(route.world_id, route.map_id)
+    }
+}
+
+impl std::fmt::Display for MaintenanceScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.map_id {
+            Some(map_id) => write!(f, "world {} map {}", self.world_id, map_id),
+            None => write!(f, "world {}", self.world_id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct MaintenanceWindow {
+    pub scope: MaintenanceScope,
+    pub reason: String,
+    pub started_at_ms: u64,
+    pub closes_at_ms: u64,
+    pub fallback_map_id: u16,
+    /// Set once the countdown ended and present players were moved out.
+    pub closed: bool,
+    #[serde(skip)]
+    last_notice_mark: Option<u32>,
+}
+
+impl MaintenanceWindow {
+    pub fn seconds_remaining(&self, now_ms: u64) -> u32 {
+        let remaining_ms = self.closes_at_ms.saturating_sub(now_ms);
+        u32::try_from(remaining_ms.div_ceil(1_000)).unwrap_or(u32::MAX)
+    }
+
+    pub fn notice(&self, now_ms: u64) -> MaintenanceNotice {
+        MaintenanceNotice {
+            world_id: self.scope.world_id,
+            map_id: self.scope.map_id,
+            closes_at_ms: self.closes_at_ms,
+            seconds_remaining: self.seconds_remaining(now_ms),
+            reason: self.reason.clone(),
+        }
+    }
+}
+
+/// Worlds and maps closed for maintenance.
+///
+/// New entries into a scope are refused from the moment it is scheduled; players
+/// already inside are warned during the countdown and migrated when it ends.
+#[derive(Clone, Default)]
+pub struct MaintenanceRegistry {
+    windows: Arc<DashMap<MaintenanceScope, MaintenanceWindow>>,
+}
+
+impl MaintenanceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn schedule(
+        &self,
+        scope: MaintenanceScope,
+        reason: String,
+        countdown_secs: u32,
+        fallback_map_id: u16,
+        now_ms: u64,
+    ) -> Result<MaintenanceWindow, MaintenanceError> {
+        if countdown_secs > MAX_COUNTDOWN_SECS {
+            return Err(MaintenanceError::CountdownTooLong(countdown_secs));
+        }
+        if self.windows.contains_key(&scope) {
+            return Err(MaintenanceError::AlreadyScheduled(scope));
+        }
+
+        let window = MaintenanceWindow {
+            scope,
+            reason,
+            started_at_ms: now_ms,
+            closes_at_ms: now_ms.saturating_add(u64::from(countdown_secs) * 1_000),
+            fallback_map_id,
+            closed: false,
+            last_notice_mark: None,
+        };
+        self.windows.insert(scope, window.clone());
+        Ok(window)
+    }
+
+    /// Reopens a scope. Returns the window that was active, if any.
+    pub fn end(&self, scope: MaintenanceScope) -> Option<MaintenanceWindow> {
+        self.windows.remove(&scope).map(|(_, window)| window)
+    }
+
+    /// Window blocking entry into the given map, if any.
+    pub fn window_for(&self, world_id: u16, map_id: u16) -> Option<MaintenanceWindow> {
+        self.windows
+            .iter()
+            .find(|entry| entry.key().covers(world_id, map_id))
+            .map(|entry| entry.value().clone())
+    }
+
+    pub fn blocks_route(&self, route: RouteKey) -> bool {
+        self.window_for(route.world_id, route.map_id).is_some()
+    }
+
+    pub fn is_world_closed(&self, world_id: u16) -> bool {
+        self.windows.contains_key(&MaintenanceScope {
+            world_id,
+            map_id: None,
+        })
+    }
+
+    pub fn windows(&self) -> Vec<MaintenanceWindow> {
+        let mut windows: Vec<_> = self
+            .windows
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect();
+        windows.sort_by_key(|window| (window.scope.world_id, window.scope.map_id));
+        windows
+    }
+
+    /// Windows whose countdown crossed a new announcement mark since the last call.
+    pub fn due_notices(&self, now_ms: u64) -> Vec<MaintenanceWindow> {
+        let mut due = Vec::new();
+        for mut entry in self.windows.iter_mut() {
+            let window = entry.value_mut();
+            if window.closed {
+                continue;
+            }
+
+            let remaining = window.seconds_remaining(now_ms);
+            let mark = NOTICE_MARKS_SECS
+                .iter()
+                .copied()
+                .find(|mark| remaining >= *mark)
+                .unwrap_or(0);
+            let announce = match window.last_notice_mark {
+                None => true,
+                Some(last) => mark < last,
+            };
+            if announce {
+                window.last_notice_mark = Some(mark);
+                due.push(window.clone());
+            }
+        }
+        due
+    }
+
+    /// Windows whose countdown just ended; each is returned exactly once.
+    pub fn take_closing(&self, now_ms: u64) -> Vec<MaintenanceWindow> {
+        let mut closing = Vec::new();
+        for mut entry in self.windows.iter_mut() {
+            let window = entry.value_mut();
+            if !window.closed && now_ms >= window.closes_at_ms {
+                window.closed = true;
+                closing.push(window.clone());
+            }
+        }
+        closing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEVIAS: MaintenanceScope = MaintenanceScope {
+        world_id: 1,
+        map_id: Some(2),
+    };
+
+    #[test]
+    fn map_scope_blocks_only_its_map() {
+        let registry = MaintenanceRegistry::new();
+        registry
+            .schedule(DEVIAS, "patch".to_string(), 60, 0, 1_000)
+            .unwrap();
+
+        assert!(registry.window_for(1, 2).is_some());
+        assert!(registry.window_for(1, 0).is_none());
+        assert!(registry.window_for(2, 2).is_none());
+        assert!(!registry.is_world_closed(1));
+        assert_eq!(
+            registry
+                .schedule(DEVIAS, "again".to_string(), 60, 0, 1_000)
+                .unwrap_err(),
+            MaintenanceError::AlreadyScheduled(DEVIAS)
+        );
+
+        assert!(registry.end(DEVIAS).is_some());
+        assert!(registry.window_for(1, 2).is_none());
+    }
+
+    #[test]
+    fn countdown_is_announced_at_marks_and_closes_once() {
+        let registry = MaintenanceRegistry::new();
+        registry
+            .schedule(DEVIAS, "patch".to_string(), 45, 0, 0)
+            .unwrap();
+
+        let first = registry.due_notices(0);
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].notice(0).seconds_remaining, 45);
+        // Still inside the 30..60 mark, nothing new to announce.
+        assert!(registry.due_notices(10_000).is_empty());
+        assert_eq!(registry.due_notices(16_000).len(), 1);
+
+        assert!(registry.take_closing(44_000).is_empty());
+        assert_eq!(registry.take_closing(45_000).len(), 1);
+        assert!(registry.take_closing(46_000).is_empty());
+        assert!(registry.due_notices(46_000).is_empty());
+        assert!(registry
+            .window_for(1, 2)
+            .is_some_and(|window| window.closed));
+    }
+}
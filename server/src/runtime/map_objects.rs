@@ -0,0 +1,268 @@
+//! Objects a map leaves lying around: items dropped on the ground, effect
+//! entities of skills and events, and the corpses of dead monsters.
+//!
+//! Each kind lives for its `cleanup` time in `config/runtime.toml`, and a map
+//! instance keeps at most its cap of each, dropping the oldest first. The map
+//! server sweeps every `cleanup.interval_ms` and reports what it holds and
+//! what it evicted in its stats, so a server up for weeks does not grow
+//! without bound. Players drop items on the ground, skills leave an effect
+//! where they are cast and monsters leave a corpse where they fall.
+
+use std::collections::VecDeque;
+
+use protocol::{ItemFailure, ItemInstance};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::config::{CleanupConfig, ObjectCaps};
+
+/// First entity id of map objects, clear of players, doors and monsters.
+const OBJECT_ENTITY_ID_BASE: u32 = 0x5000_0000;
+const OBJECT_ENTITY_ID_SPAN: u32 = 0x1000_0000;
+/// Queue of the ground items.
+const GROUND_ITEMS: usize = 0;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapObjectKind {
+    GroundItem(ItemInstance),
+    Effect {
+        effect_id: u16,
+    },
+    /// `monster_id` is `None` for monsters spawned without a definition.
+    Corpse {
+        monster_id: Option<u16>,
+    },
+}
+
+impl MapObjectKind {
+    fn slot(&self) -> usize {
+        match self {
+            Self::GroundItem(_) => GROUND_ITEMS,
+            Self::Effect { .. } => 1,
+            Self::Corpse { .. } => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapObject {
+    pub entity_id: u32,
+    pub x: u16,
+    pub y: u16,
+    pub placed_at_ms: u64,
+    pub kind: MapObjectKind,
+}
+
+/// Objects per kind, for the map stats.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ObjectCounts {
+    pub ground_items: u64,
+    pub effects: u64,
+    pub corpses: u64,
+}
+
+impl ObjectCounts {
+    fn add(&mut self, kind: &MapObjectKind) {
+        match kind {
+            MapObjectKind::GroundItem(_) => self.ground_items += 1,
+            MapObjectKind::Effect { .. } => self.effects += 1,
+            MapObjectKind::Corpse { .. } => self.corpses += 1,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.ground_items + self.effects + self.corpses
+    }
+}
+
+impl std::ops::AddAssign for ObjectCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.ground_items += other.ground_items;
+        self.effects += other.effects;
+        self.corpses += other.corpses;
+    }
+}
+
+/// Objects removed by one sweep, oldest first.
+#[derive(Debug, Default)]
+pub struct Sweep {
+    pub expired: Vec<MapObject>,
+    pub over_cap: Vec<MapObject>,
+}
+
+impl Sweep {
+    pub fn is_empty(&self) -> bool {
+        self.expired.is_empty() && self.over_cap.is_empty()
+    }
+
+    pub fn counts(&self) -> ObjectCounts {
+        let mut counts = ObjectCounts::default();
+        for object in self.expired.iter().chain(&self.over_cap) {
+            counts.add(&object.kind);
+        }
+        counts
+    }
+}
+
+/// Objects on one map instance, one queue per kind in placement order.
+pub struct MapObjects {
+    cleanup: CleanupConfig,
+    queues: [VecDeque<MapObject>; 3],
+    next_entity: u32,
+}
+
+impl MapObjects {
+    pub fn new(cleanup: CleanupConfig) -> Self {
+        Self {
+            cleanup,
+            queues: Default::default(),
+            next_entity: 0,
+        }
+    }
+
+    /// Puts an object on the map and returns its entity id. Caps are only
+    /// enforced by [`MapObjects::sweep`].
+    pub fn place(&mut self, kind: MapObjectKind, x: u16, y: u16, now_ms: u64) -> u32 {
+        let entity_id = OBJECT_ENTITY_ID_BASE + self.next_entity;
+        self.next_entity = (self.next_entity + 1) % OBJECT_ENTITY_ID_SPAN;
+        self.queues[kind.slot()].push_back(MapObject {
+            entity_id,
+            x,
+            y,
+            placed_at_ms: now_ms,
+            kind,
+        });
+        entity_id
+    }
+
+    /// Takes the ground item `entity_id` off the map when it lies within
+    /// `reach` tiles of `from`.
+    pub fn pick_up(
+        &mut self,
+        entity_id: u32,
+        from: (u16, u16),
+        reach: u16,
+    ) -> Result<ItemInstance, ItemFailure> {
+        let queue = &mut self.queues[GROUND_ITEMS];
+        let index = queue
+            .iter()
+            .position(|object| object.entity_id == entity_id)
+            .ok_or(ItemFailure::Gone)?;
+        let object = &queue[index];
+        if object.x.abs_diff(from.0).max(object.y.abs_diff(from.1)) > reach {
+            return Err(ItemFailure::OutOfReach);
+        }
+        match queue.remove(index).map(|object| object.kind) {
+            Some(MapObjectKind::GroundItem(item)) => Ok(item),
+            _ => Err(ItemFailure::Gone),
+        }
+    }
+
+    pub fn counts(&self) -> ObjectCounts {
+        let mut counts = ObjectCounts::default();
+        for object in self.queues.iter().flatten() {
+            counts.add(&object.kind);
+        }
+        counts
+    }
+
+    /// Removes the objects past their lifetime, then the oldest of each kind
+    /// beyond its cap.
+    pub fn sweep(&mut self, now_ms: u64) -> Sweep {
+        let ObjectCaps {
+            ground_items,
+            effects,
+            corpses,
+        } = self.cleanup.caps;
+        let limits = [
+            (self.cleanup.ground_item_ttl_ms, ground_items),
+            (self.cleanup.effect_ttl_ms, effects),
+            (self.cleanup.corpse_ttl_ms, corpses),
+        ];
+
+        let mut sweep = Sweep::default();
+        for (queue, (ttl_ms, cap)) in self.queues.iter_mut().zip(limits) {
+            while queue
+                .front()
+                .is_some_and(|object| now_ms.saturating_sub(object.placed_at_ms) >= ttl_ms)
+            {
+                sweep.expired.extend(queue.pop_front());
+            }
+            while queue.len() > cap {
+                sweep.over_cap.extend(queue.pop_front());
+            }
+        }
+        sweep
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cleanup() -> CleanupConfig {
+        CleanupConfig {
+            interval_ms: 1_000,
+            ground_item_ttl_ms: 100,
+            effect_ttl_ms: 10,
+            corpse_ttl_ms: 50,
+            caps: ObjectCaps {
+                ground_items: 2,
+                effects: 10,
+                corpses: 10,
+            },
+        }
+    }
+
+    #[test]
+    fn sweeps_expire_objects_and_trim_the_oldest_over_the_cap() {
+        let mut objects = MapObjects::new(cleanup());
+        let item = || {
+            MapObjectKind::GroundItem(ItemInstance {
+                serial: 0,
+                group: 14,
+                index: 0,
+                level: 0,
+                quantity: 1,
+                options: Default::default(),
+                expires_at_ms: None,
+            })
+        };
+        let first = objects.place(item(), 10, 10, 0);
+        objects.place(item(), 11, 10, 5);
+        let third = objects.place(item(), 12, 10, 8);
+        objects.place(MapObjectKind::Effect { effect_id: 4 }, 10, 10, 0);
+        objects.place(
+            MapObjectKind::Corpse {
+                monster_id: Some(3),
+            },
+            10,
+            10,
+            0,
+        );
+        assert_eq!(objects.counts().total(), 5);
+
+        let sweep = objects.sweep(20);
+        assert_eq!(sweep.expired.len(), 1, "the effect outlived its 10 ms");
+        assert_eq!(sweep.over_cap[0].entity_id, first);
+        assert_eq!(
+            sweep.counts(),
+            ObjectCounts {
+                ground_items: 1,
+                effects: 1,
+                corpses: 0,
+            }
+        );
+
+        assert_eq!(
+            objects.pick_up(third, (20, 10), 3),
+            Err(ItemFailure::OutOfReach)
+        );
+        assert!(objects.pick_up(third, (13, 11), 3).is_ok());
+        assert_eq!(objects.pick_up(third, (13, 11), 3), Err(ItemFailure::Gone));
+        let sweep = objects.sweep(200);
+        assert_eq!(sweep.counts().total(), 2);
+        assert_eq!(objects.counts(), ObjectCounts::default());
+        assert!(objects.sweep(300).is_empty());
+    }
+}
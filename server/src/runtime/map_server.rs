@@ -2,24 +2,81 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use protocol::{ChatPayload, MoveInput, RouteKey, UseSkillInput};
+use common::collision::CollisionGrid;
+use common::{MurderStatus, PvpRules};
+use protocol::{
+    ChatPayload, DamageEvent, DoorState, DoorStatus, Emote, ItemFailure, ItemInstance, MonsterRank,
+    MoveInput, RouteKey, SequenceEvent, ServerMessage, UseSkillInput, WaypointPath,
+};
 use serde::Serialize;
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{mpsc, oneshot, Mutex};
+use utoipa::ToSchema;
 
+use super::config::{CleanupConfig, CombatConfig, DoorConfig, MonsterConfig};
 use super::directory::WorldDirectory;
-use super::message_hub::{HubMessage, MessageHub, MessageScope};
+use super::doors::{DoorError, MapDoors};
+use super::gens::GensRegistry;
+use super::guild_wars::{publish_score, GuildWars};
+use super::guilds::GuildRelations;
+use super::item_ledger::{ItemHolder, ItemLedger, TransferReason};
+use super::lag_compensation::{in_reach, PositionHistory};
+use super::map_objects::{MapObjectKind, MapObjects, ObjectCounts};
+use super::message_hub::{HubMessage, HubPayload, MessageHub, MessageScope};
 use super::persistence::{CharacterStateSnapshot, PersistenceHandle};
+use super::session_links::SessionPush;
+use super::stress::{
+    route_seed, MonsterHit, StressError, StressReport, SyntheticMonsters, TickLoad, TickPercentiles,
+};
+use crate::auth_token::now_ms;
+
+/// Longest path broadcast as waypoints; longer jumps are sent as a snap.
+const MAX_PATH_STEPS: usize = 15;
+/// Monsters a map reports before any synthetic load is added.
+const BASE_MONSTER_COUNT: u32 = 16;
+/// Recent tick times kept per loop for the percentiles.
+const TICK_SAMPLE_WINDOW: usize = 200;
+const PLAYER_MAX_HP: u16 = 100;
+/// HP a skill takes from a player target.
+const PLAYER_HIT_DAMAGE: u16 = 20;
+/// Shortest gap between two emotes of a player that are relayed; spammed
+/// ones are dropped.
+const EMOTE_INTERVAL_MS: u64 = 1_000;
+/// Farthest tile from the character an item is dropped on or picked up from.
+const ITEM_REACH: u16 = 3;
 
 #[derive(Debug, Clone)]
 pub struct MapServerConfig {
     pub route: RouteKey,
     pub map_name: String,
     pub soft_player_cap: u32,
+    /// Who may hit whom between players (see [`common::pvp`]).
+    pub pvp: PvpRules,
+    /// Gens battle zone: rival Gens members may fight and kills score
+    /// contribution.
+    pub gens_zone: bool,
     pub player_tick: Duration,
     pub monster_tick: Duration,
+    pub collision: Arc<CollisionGrid>,
+    pub doors: Vec<DoorConfig>,
+    /// Behavior of the monster definitions spawned on the map.
+    pub monsters: Vec<MonsterConfig>,
+    /// Skill reach checked on every hit.
+    pub combat: CombatConfig,
+    /// Lifetime and caps of ground items, effects and corpses.
+    pub cleanup: CleanupConfig,
+}
+
+/// A monster a character killed, sent to the runtime for the bestiary,
+/// quests and boss webhooks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonsterKill {
+    pub character_id: u64,
+    pub monster_id: u16,
+    pub rank: Option<MonsterRank>,
+    pub server_time_ms: u64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct MapServerStats {
     pub route: RouteKey,
     pub map_name: String,
@@ -30,6 +87,10 @@ pub struct MapServerStats {
     pub monster_ticks: u64,
     pub monster_degradation_level: u8,
     pub player_tick_p95_us: u64,
+    /// Ground items, effects and corpses on the map after the last sweep.
+    pub objects: ObjectCounts,
+    /// Objects removed since the map started, expired or over the cap.
+    pub evicted_objects: ObjectCounts,
 }
 
 impl MapServerStats {
@@ -39,11 +100,13 @@ impl MapServerStats {
             map_name: config.map_name.clone(),
             current_players: 0,
             soft_player_cap: config.soft_player_cap,
-            monster_count: 16,
+            monster_count: BASE_MONSTER_COUNT,
             player_ticks: 0,
             monster_ticks: 0,
             monster_degradation_level: 0,
             player_tick_p95_us: 0,
+            objects: ObjectCounts::default(),
+            evicted_objects: ObjectCounts::default(),
         }
     }
 }
@@ -51,6 +114,7 @@ impl MapServerStats {
 #[derive(Debug)]
 enum MapServerCommand {
     Join {
+        session_id: u64,
         character_id: u64,
         x: u16,
         y: u16,
@@ -65,41 +129,118 @@ enum MapServerCommand {
     UseSkill {
         character_id: u64,
         input: UseSkillInput,
+        /// Attacker's round-trip time, for rewinding its target.
+        rtt_ms: u64,
     },
     LocalChat {
         session_id: u64,
         character_id: u64,
         chat: ChatPayload,
     },
+    Emote {
+        session_id: u64,
+        character_id: u64,
+        emote: Emote,
+    },
+    DropItem {
+        character_id: u64,
+        item: ItemInstance,
+        x: u16,
+        y: u16,
+        reply: oneshot::Sender<Result<u32, ItemFailure>>,
+    },
+    PickUp {
+        character_id: u64,
+        entity_id: u32,
+        reply: oneshot::Sender<Result<ItemInstance, ItemFailure>>,
+    },
+    StartStress {
+        monsters: u32,
+        duration: Duration,
+        reply: oneshot::Sender<Result<StressReport, StressError>>,
+    },
+    SetDoor {
+        door_id: u16,
+        state: DoorState,
+        reply: oneshot::Sender<Option<DoorStatus>>,
+    },
+    Doors {
+        reply: oneshot::Sender<Vec<DoorStatus>>,
+    },
+    Position {
+        character_id: u64,
+        reply: oneshot::Sender<Option<(u16, u16)>>,
+    },
+    FollowEvent {
+        event: SequenceEvent,
+        open: bool,
+    },
     Shutdown,
 }
 
 #[derive(Debug, Clone)]
 struct PlayerState {
+    session_id: u64,
     character_id: u64,
     x: u16,
     y: u16,
     hp: u16,
     mp: u16,
     last_tick: u32,
+    /// When the player's last relayed emote started.
+    last_emote_ms: Option<u64>,
+    /// Where attackers may have seen the player lately.
+    history: PositionHistory,
+    /// Murders committed since joining the map; kept with the instance until
+    /// characters persist their murder status.
+    murder_kills: u32,
+}
+
+impl PlayerState {
+    fn murder_status(&self) -> MurderStatus {
+        MurderStatus::from_kills(self.murder_kills)
+    }
+}
+
+/// Synthetic monsters alive on the map and the tick times recorded meanwhile.
+struct ActiveStress {
+    monsters: SyntheticMonsters,
+    deadline: Instant,
+    report: StressReport,
+    player_tick_us: Vec<u64>,
+    monster_tick_us: Vec<u64>,
+    peak_degradation_level: u8,
+}
+
+fn push_tick_sample(window: &mut Vec<u64>, elapsed_us: u64) {
+    window.push(elapsed_us);
+    if window.len() > TICK_SAMPLE_WINDOW {
+        window.remove(0);
+    }
 }
 
 #[derive(Clone)]
 pub struct MapServerHandle {
     tx: mpsc::Sender<MapServerCommand>,
     stats: Arc<Mutex<MapServerStats>>,
+    stress_report: Arc<Mutex<Option<StressReport>>>,
 }
 
 impl MapServerHandle {
     pub async fn join(
         &self,
-        _session_id: u64,
+        session_id: u64,
         character_id: u64,
         x: u16,
         y: u16,
     ) -> anyhow::Result<()> {
         self.tx
-            .send(MapServerCommand::Join { character_id, x, y })
+            .send(MapServerCommand::Join {
+                session_id,
+                character_id,
+                x,
+                y,
+            })
             .await?;
         Ok(())
     }
@@ -121,11 +262,19 @@ impl MapServerHandle {
         Ok(())
     }
 
-    pub async fn use_skill(&self, character_id: u64, input: UseSkillInput) -> anyhow::Result<()> {
+    /// Casts a skill. Hits are checked against where the target stood
+    /// `rtt_ms` ago as well as where it stands now.
+    pub async fn use_skill(
+        &self,
+        character_id: u64,
+        input: UseSkillInput,
+        rtt_ms: u64,
+    ) -> anyhow::Result<()> {
         self.tx
             .send(MapServerCommand::UseSkill {
                 character_id,
                 input,
+                rtt_ms,
             })
             .await?;
         Ok(())
@@ -147,6 +296,149 @@ impl MapServerHandle {
         Ok(())
     }
 
+    /// Shows `emote` to the players on the map; ignored unless the character
+    /// is on it.
+    pub async fn emote(
+        &self,
+        session_id: u64,
+        character_id: u64,
+        emote: Emote,
+    ) -> anyhow::Result<()> {
+        self.tx
+            .send(MapServerCommand::Emote {
+                session_id,
+                character_id,
+                emote,
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Spawns `monsters` synthetic monsters for `duration`; the returned report
+    /// holds the tick times from before the spawn.
+    pub async fn start_stress(
+        &self,
+        monsters: u32,
+        duration: Duration,
+    ) -> Result<StressReport, StressError> {
+        let (reply, response) = oneshot::channel();
+        self.tx
+            .send(MapServerCommand::StartStress {
+                monsters,
+                duration,
+                reply,
+            })
+            .await
+            .map_err(|_| StressError::MapStopped)?;
+        response.await.map_err(|_| StressError::MapStopped)?
+    }
+
+    /// Puts a door of the map in `state` and tells the players on the map.
+    /// Returns `None` when the map has no such door.
+    pub async fn set_door(
+        &self,
+        door_id: u16,
+        state: DoorState,
+    ) -> Result<Option<DoorStatus>, DoorError> {
+        let (reply, response) = oneshot::channel();
+        self.tx
+            .send(MapServerCommand::SetDoor {
+                door_id,
+                state,
+                reply,
+            })
+            .await
+            .map_err(|_| DoorError::MapStopped)?;
+        response.await.map_err(|_| DoorError::MapStopped)
+    }
+
+    /// Every door of the map; empty once the map stopped.
+    pub async fn doors(&self) -> Vec<DoorStatus> {
+        let (reply, response) = oneshot::channel();
+        if self
+            .tx
+            .send(MapServerCommand::Doors { reply })
+            .await
+            .is_err()
+        {
+            return Vec::new();
+        }
+        response.await.unwrap_or_default()
+    }
+
+    /// Puts an item of the character on the ground at `x`, `y` and returns
+    /// its entity id. The ledger moves the item to the map first, so an item
+    /// the character no longer holds is refused.
+    pub async fn drop_item(
+        &self,
+        character_id: u64,
+        item: ItemInstance,
+        x: u16,
+        y: u16,
+    ) -> Result<u32, ItemFailure> {
+        let (reply, response) = oneshot::channel();
+        self.tx
+            .send(MapServerCommand::DropItem {
+                character_id,
+                item,
+                x,
+                y,
+                reply,
+            })
+            .await
+            .map_err(|_| ItemFailure::NotAllowed)?;
+        response.await.unwrap_or(Err(ItemFailure::NotAllowed))
+    }
+
+    /// Gives the character the ground item `entity_id` when it is within
+    /// reach. The caller checks the inventory has room.
+    pub async fn pick_up(
+        &self,
+        character_id: u64,
+        entity_id: u32,
+    ) -> Result<ItemInstance, ItemFailure> {
+        let (reply, response) = oneshot::channel();
+        self.tx
+            .send(MapServerCommand::PickUp {
+                character_id,
+                entity_id,
+                reply,
+            })
+            .await
+            .map_err(|_| ItemFailure::NotAllowed)?;
+        response.await.unwrap_or(Err(ItemFailure::NotAllowed))
+    }
+
+    /// Tile the character stands on once the commands sent before this one
+    /// are applied; `None` when it is not on the map.
+    pub async fn position(&self, character_id: u64) -> Option<(u16, u16)> {
+        let (reply, response) = oneshot::channel();
+        self.tx
+            .send(MapServerCommand::Position {
+                character_id,
+                reply,
+            })
+            .await
+            .ok()?;
+        response.await.ok().flatten()
+    }
+
+    /// Opens or closes the doors that follow `event`. Does not wait for the
+    /// map: event timers call it from synchronous code.
+    pub fn follow_event(&self, event: SequenceEvent, open: bool) {
+        if let Err(err) = self
+            .tx
+            .try_send(MapServerCommand::FollowEvent { event, open })
+        {
+            log::warn!("Failed to move {:?} doors: {}", event, err);
+        }
+    }
+
+    /// Latest stress run, active or finished.
+    pub async fn stress_report(&self) -> Option<StressReport> {
+        self.stress_report.lock().await.clone()
+    }
+
     pub async fn shutdown(&self) -> anyhow::Result<()> {
         self.tx.send(MapServerCommand::Shutdown).await?;
         Ok(())
@@ -157,34 +449,52 @@ impl MapServerHandle {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn start_map_server(
     config: MapServerConfig,
     directory: WorldDirectory,
     persistence: PersistenceHandle,
     message_hub: MessageHub,
+    guilds: GuildRelations,
+    wars: GuildWars,
+    gens: GensRegistry,
+    push: SessionPush,
+    items: ItemLedger,
+    kills: mpsc::UnboundedSender<MonsterKill>,
 ) -> MapServerHandle {
     let (tx, mut rx) = mpsc::channel::<MapServerCommand>(4096);
     let stats = Arc::new(Mutex::new(MapServerStats::new(&config)));
     let stats_clone = stats.clone();
+    let stress_report = Arc::new(Mutex::new(None));
+    let stress_report_clone = stress_report.clone();
 
     tokio::spawn(async move {
         let mut players: HashMap<u64, PlayerState> = HashMap::new();
         let mut player_tick = tokio::time::interval(config.player_tick);
         let mut monster_tick = tokio::time::interval(config.monster_tick);
         let mut last_player_tick_us: Vec<u64> = Vec::new();
+        let mut last_monster_tick_us: Vec<u64> = Vec::new();
+        let mut stress: Option<ActiveStress> = None;
+        let mut doors = MapDoors::new(config.collision.clone(), &config.doors);
+        let mut objects = MapObjects::new(config.cleanup.clone());
+        let mut cleanup_tick = tokio::time::interval(config.cleanup.interval());
 
         loop {
             tokio::select! {
                 cmd = rx.recv() => {
                     match cmd {
-                        Some(MapServerCommand::Join { character_id, x, y }) => {
+                        Some(MapServerCommand::Join { session_id, character_id, x, y }) => {
                             players.insert(character_id, PlayerState {
+                                session_id,
                                 character_id,
                                 x,
                                 y,
-                                hp: 100,
+                                hp: PLAYER_MAX_HP,
                                 mp: 100,
                                 last_tick: 0,
+                                last_emote_ms: None,
+                                history: PositionHistory::new(now_ms(), (x, y)),
+                                murder_kills: 0,
                             });
 
                             let count = players.len() as u32;
@@ -204,16 +514,220 @@ pub fn start_map_server(
                         }
                         Some(MapServerCommand::Move { character_id, input }) => {
                             if let Some(player) = players.get_mut(&character_id) {
-                                player.x = input.x;
-                                player.y = input.y;
+                                let entity_id = character_id as u32;
+                                let from = (player.x, player.y);
+                                let to = (input.x, input.y);
+
+                                let collision = doors.grid();
+                                let path = if collision.is_blocked(input.x, input.y) {
+                                    None
+                                } else if collision.line_is_clear(from, to) {
+                                    Some(
+                                        WaypointPath::between(entity_id, from, to, MAX_PATH_STEPS)
+                                            .unwrap_or_else(|| WaypointPath {
+                                                entity_id,
+                                                start_x: input.x,
+                                                start_y: input.y,
+                                                directions: Vec::new(),
+                                            }),
+                                    )
+                                } else {
+                                    collision
+                                        .find_path(from, to, MAX_PATH_STEPS)
+                                        .and_then(|route| WaypointPath::along(entity_id, from, &route))
+                                };
+
                                 player.last_tick = input.client_tick;
+                                let path = match path {
+                                    Some(path) => {
+                                        player.x = input.x;
+                                        player.y = input.y;
+                                        player.history.record(now_ms(), (input.x, input.y));
+                                        (from != to).then_some(path)
+                                    }
+                                    None => {
+                                        // Unreachable or blocked tile: snap the mover back into place.
+                                        log::debug!(
+                                            "Rejected move of {} from {:?} to {:?} on {}",
+                                            character_id,
+                                            from,
+                                            to,
+                                            config.map_name
+                                        );
+                                        Some(WaypointPath {
+                                            entity_id,
+                                            start_x: from.0,
+                                            start_y: from.1,
+                                            directions: Vec::new(),
+                                        })
+                                    }
+                                };
+
+                                // Observers animate along the path instead of per-tick positions.
+                                if let Some(path) = path {
+                                    let now = now_ms();
+                                    for observer in players.values() {
+                                        push.push(observer.session_id, ServerMessage::EntityPath(path.clone()), now);
+                                    }
+                                }
                             }
                         }
-                        Some(MapServerCommand::UseSkill { character_id, input }) => {
-                            if let Some(player) = players.get_mut(&character_id) {
-                                player.last_tick = input.client_tick;
-                                if input.target_entity_id.is_some() {
-                                    player.mp = player.mp.saturating_sub(1);
+                        Some(MapServerCommand::UseSkill { character_id, input, rtt_ms }) => {
+                            let player_target = input
+                                .target_entity_id
+                                .map(u64::from)
+                                .filter(|target_id| players.contains_key(target_id));
+                            // Player targets are only valid when the map's PvP rules allow the hit,
+                            // given the guilds, wars and Gens of both sides.
+                            let standing = player_target.map(|target_id| {
+                                let mut standing = guilds.pvp_standing(character_id, target_id);
+                                standing.hostile |= at_war(&guilds, &wars, character_id, target_id)
+                                    || (config.gens_zone && gens.are_rivals(character_id, target_id));
+                                standing
+                            });
+                            let target_allowed = match (player_target.zip(standing), input.target_entity_id) {
+                                (Some((target_id, standing)), _) => {
+                                    let victim = players
+                                        .get(&target_id)
+                                        .map_or(MurderStatus::Commoner, PlayerState::murder_status);
+                                    config.pvp.allows(standing, victim)
+                                }
+                                (None, Some(_)) => true,
+                                (None, None) => false,
+                            };
+
+                            let Some(player) = players.get_mut(&character_id) else {
+                                continue;
+                            };
+                            player.last_tick = input.client_tick;
+                            if !target_allowed {
+                                continue;
+                            }
+                            player.mp = player.mp.saturating_sub(1);
+                            let session_id = player.session_id;
+                            let attacker = (player.x, player.y);
+                            let reach = config.combat.range_of(input.skill_id);
+                            let now = now_ms();
+                            objects.place(
+                                MapObjectKind::Effect { effect_id: input.skill_id },
+                                input.target_x,
+                                input.target_y,
+                                now,
+                            );
+
+                            // Siege gates are hit like any other target.
+                            if let Some(door) = input
+                                .target_entity_id
+                                .and_then(DoorStatus::door_id)
+                                .and_then(|door_id| doors.damage(door_id, u32::from(PLAYER_HIT_DAMAGE)))
+                            {
+                                publish_doors(&message_hub, config.route, session_id, vec![door]);
+                                continue;
+                            }
+
+                            // Stress monsters fight back, with their spawn group.
+                            if let Some((target_entity_id, hit)) = input
+                                .target_entity_id
+                                .zip(stress.as_mut())
+                                .and_then(|(entity_id, active)| {
+                                    let history = active.monsters.history(entity_id)?;
+                                    if !in_reach(attacker, history, now, rtt_ms, reach) {
+                                        return None;
+                                    }
+                                    active
+                                        .monsters
+                                        .hit(entity_id, character_id, u32::from(PLAYER_HIT_DAMAGE))
+                                        .map(|hit| (entity_id, hit))
+                                })
+                            {
+                                let remaining_hp = match hit {
+                                    MonsterHit::Wounded { remaining_hp } => remaining_hp,
+                                    MonsterHit::Killed(death) => {
+                                        if let Some(monster_id) = death.monster_id {
+                                            let _ = kills.send(MonsterKill {
+                                                character_id,
+                                                monster_id,
+                                                rank: death.rank,
+                                                server_time_ms: now,
+                                            });
+                                        }
+                                        objects.place(
+                                            MapObjectKind::Corpse { monster_id: death.monster_id },
+                                            death.x,
+                                            death.y,
+                                            now,
+                                        );
+                                        0
+                                    }
+                                };
+                                let damage = DamageEvent {
+                                    attacker_entity_id: character_id as u32,
+                                    target_entity_id,
+                                    skill_id: input.skill_id,
+                                    damage: u32::from(PLAYER_HIT_DAMAGE),
+                                    remaining_hp,
+                                    server_time_ms: now,
+                                };
+                                let msg = HubMessage {
+                                    from_session_id: session_id,
+                                    route: config.route,
+                                    payload: HubPayload::Damage(damage),
+                                };
+                                let _ = message_hub.publish(MessageScope::LocalMap(config.route), msg);
+                                continue;
+                            }
+
+                            let Some(target) = player_target
+                                .filter(|target_id| *target_id != character_id)
+                                .and_then(|target_id| players.get_mut(&target_id))
+                            else {
+                                continue;
+                            };
+                            if !in_reach(attacker, &target.history, now, rtt_ms, reach) {
+                                log::debug!(
+                                    "Hit of {} on {} out of reach (rtt {} ms) on {}",
+                                    character_id,
+                                    target.character_id,
+                                    rtt_ms,
+                                    config.map_name
+                                );
+                                continue;
+                            }
+                            target.hp = target.hp.saturating_sub(PLAYER_HIT_DAMAGE);
+                            let damage = DamageEvent {
+                                attacker_entity_id: character_id as u32,
+                                target_entity_id: target.character_id as u32,
+                                skill_id: input.skill_id,
+                                damage: u32::from(PLAYER_HIT_DAMAGE),
+                                remaining_hp: u32::from(target.hp),
+                                server_time_ms: now,
+                            };
+                            let msg = HubMessage {
+                                from_session_id: session_id,
+                                route: config.route,
+                                payload: HubPayload::Damage(damage),
+                            };
+                            let _ = message_hub.publish(MessageScope::LocalMap(config.route), msg);
+                            if target.hp == 0 {
+                                // The victim gets back up where it fell.
+                                target.hp = PLAYER_MAX_HP;
+                                let murdered = standing
+                                    .is_some_and(|standing| config.pvp.is_murder(standing, target.murder_status()));
+                                let killer_guild = guilds.guild_of(character_id);
+                                let victim_guild = guilds.guild_of(target.character_id);
+                                if let Some(score) = killer_guild
+                                    .zip(victim_guild)
+                                    .and_then(|(killer, victim)| wars.record_kill(killer, victim, now_ms()))
+                                {
+                                    publish_score(&guilds, &push, &score, now);
+                                }
+                                if config.gens_zone {
+                                    gens.record_kill(character_id, target.character_id);
+                                }
+                                if murdered {
+                                    if let Some(killer) = players.get_mut(&character_id) {
+                                        killer.murder_kills += 1;
+                                    }
                                 }
                             }
                         }
@@ -222,11 +736,170 @@ pub fn start_map_server(
                                 let msg = HubMessage {
                                     from_session_id: session_id,
                                     route: config.route,
-                                    payload: chat,
+                                    payload: HubPayload::Chat(chat),
                                 };
                                 let _ = message_hub.publish(MessageScope::LocalMap(config.route), msg);
                             }
                         }
+                        Some(MapServerCommand::Emote { session_id, character_id, emote }) => {
+                            let now = now_ms();
+                            let relayed = players.get_mut(&character_id).is_some_and(|player| {
+                                let ready = player.last_emote_ms.is_none_or(|last| {
+                                    now.saturating_sub(last) >= EMOTE_INTERVAL_MS
+                                });
+                                if ready {
+                                    player.last_emote_ms = Some(now);
+                                }
+                                ready
+                            });
+                            if relayed {
+                                let msg = HubMessage {
+                                    from_session_id: session_id,
+                                    route: config.route,
+                                    payload: HubPayload::Emote {
+                                        entity_id: character_id as u32,
+                                        emote,
+                                    },
+                                };
+                                let _ = message_hub.publish(MessageScope::LocalMap(config.route), msg);
+                            }
+                        }
+                        Some(MapServerCommand::DropItem { character_id, item, x, y, reply }) => {
+                            let Some(player) = players.get(&character_id) else {
+                                let _ = reply.send(Err(ItemFailure::NotAllowed));
+                                continue;
+                            };
+                            let dropped = if x.abs_diff(player.x).max(y.abs_diff(player.y)) > ITEM_REACH {
+                                Err(ItemFailure::OutOfReach)
+                            } else if doors.grid().is_blocked(x, y) {
+                                Err(ItemFailure::NotAllowed)
+                            } else {
+                                let now = now_ms();
+                                items
+                                    .transfer(
+                                        item.serial,
+                                        ItemHolder::Character { character_id },
+                                        ItemHolder::Ground { route: config.route },
+                                        TransferReason::Drop,
+                                        now,
+                                    )
+                                    .map(|_| objects.place(MapObjectKind::GroundItem(item), x, y, now))
+                                    .map_err(|_| ItemFailure::NotHeld)
+                            };
+                            let _ = reply.send(dropped);
+                        }
+                        Some(MapServerCommand::PickUp { character_id, entity_id, reply }) => {
+                            let picked = players
+                                .get(&character_id)
+                                .ok_or(ItemFailure::NotAllowed)
+                                .and_then(|player| objects.pick_up(entity_id, (player.x, player.y), ITEM_REACH))
+                                .and_then(|item| {
+                                    // An item that expired on the ground is gone with it.
+                                    items
+                                        .transfer(
+                                            item.serial,
+                                            ItemHolder::Ground { route: config.route },
+                                            ItemHolder::Character { character_id },
+                                            TransferReason::Pickup,
+                                            now_ms(),
+                                        )
+                                        .map(|_| item)
+                                        .map_err(|_| ItemFailure::Gone)
+                                });
+                            let _ = reply.send(picked);
+                        }
+                        Some(MapServerCommand::StartStress { monsters, duration, reply }) => {
+                            if stress.is_some() {
+                                let _ = reply.send(Err(StressError::AlreadyRunning));
+                                continue;
+                            }
+                            let pack = match SyntheticMonsters::spawn(
+                                monsters,
+                                doors.grid(),
+                                &config.monsters,
+                                route_seed(config.route),
+                            ) {
+                                Ok(pack) => pack,
+                                Err(err) => {
+                                    let _ = reply.send(Err(err));
+                                    continue;
+                                }
+                            };
+
+                            let elites = pack.elites();
+                            for elite in &elites {
+                                let msg = HubMessage {
+                                    from_session_id: 0,
+                                    route: config.route,
+                                    payload: HubPayload::MonsterAffixes {
+                                        entity_id: elite.entity_id,
+                                        affixes: elite.affixes.clone(),
+                                    },
+                                };
+                                let _ = message_hub.publish(MessageScope::LocalMap(config.route), msg);
+                            }
+
+                            let mut st = stats_clone.lock().await;
+                            st.monster_count += pack.count();
+                            let report = StressReport {
+                                route: config.route,
+                                monsters: pack.count(),
+                                duration_secs: duration.as_secs(),
+                                started_at_ms: now_ms(),
+                                finished_at_ms: None,
+                                before: TickLoad {
+                                    player_tick: TickPercentiles::from_samples(&last_player_tick_us),
+                                    monster_tick: TickPercentiles::from_samples(&last_monster_tick_us),
+                                    peak_degradation_level: st.monster_degradation_level,
+                                },
+                                during: None,
+                                elites,
+                            };
+                            let kinds: Vec<String> = config
+                                .monsters
+                                .iter()
+                                .map(|monster| format!("{} ({})", monster.name, monster.id))
+                                .collect();
+                            log::info!(
+                                "Stress run on {}: {} synthetic monsters for {}s, groups as {:?}",
+                                config.map_name,
+                                report.monsters,
+                                report.duration_secs,
+                                kinds
+                            );
+
+                            *stress_report_clone.lock().await = Some(report.clone());
+                            let _ = reply.send(Ok(report.clone()));
+                            stress = Some(ActiveStress {
+                                monsters: pack,
+                                deadline: Instant::now() + duration,
+                                report,
+                                player_tick_us: Vec::new(),
+                                monster_tick_us: Vec::new(),
+                                peak_degradation_level: st.monster_degradation_level,
+                            });
+                        }
+                        Some(MapServerCommand::SetDoor { door_id, state, reply }) => {
+                            let door = doors.set_state(door_id, state);
+                            if let Some(door) = door {
+                                publish_doors(&message_hub, config.route, 0, vec![door]);
+                            }
+                            let _ = reply.send(door);
+                        }
+                        Some(MapServerCommand::Doors { reply }) => {
+                            let _ = reply.send(doors.statuses());
+                        }
+                        Some(MapServerCommand::Position { character_id, reply }) => {
+                            let _ = reply.send(
+                                players.get(&character_id).map(|player| (player.x, player.y)),
+                            );
+                        }
+                        Some(MapServerCommand::FollowEvent { event, open }) => {
+                            let changed = doors.follow_event(event, open);
+                            if !changed.is_empty() {
+                                publish_doors(&message_hub, config.route, 0, changed);
+                            }
+                        }
                         Some(MapServerCommand::Shutdown) | None => {
                             for player in players.values() {
                                 let _ = persistence
@@ -256,15 +929,11 @@ pub fn start_map_server(
                     }
 
                     let elapsed = started.elapsed().as_micros() as u64;
-                    last_player_tick_us.push(elapsed);
-                    if last_player_tick_us.len() > 200 {
-                        last_player_tick_us.remove(0);
+                    push_tick_sample(&mut last_player_tick_us, elapsed);
+                    if let Some(active) = stress.as_mut() {
+                        active.player_tick_us.push(elapsed);
                     }
-
-                    let mut sorted = last_player_tick_us.clone();
-                    sorted.sort_unstable();
-                    let p95_idx = ((sorted.len().saturating_sub(1)) as f64 * 0.95) as usize;
-                    let p95 = sorted.get(p95_idx).copied().unwrap_or(0);
+                    let p95 = TickPercentiles::from_samples(&last_player_tick_us).p95_us;
 
                     let mut st = stats_clone.lock().await;
                     st.player_ticks += 1;
@@ -277,10 +946,60 @@ pub fn start_map_server(
                     } else if st.monster_degradation_level > 0 {
                         st.monster_degradation_level -= 1;
                     }
+                    if let Some(active) = stress.as_mut() {
+                        active.peak_degradation_level =
+                            active.peak_degradation_level.max(st.monster_degradation_level);
+                    }
+                }
+                _ = cleanup_tick.tick() => {
+                    let now = now_ms();
+                    let sweep = objects.sweep(now);
+                    for object in sweep.expired.iter().chain(&sweep.over_cap) {
+                        if let MapObjectKind::GroundItem(item) = &object.kind {
+                            // Items that expired by time were destroyed already.
+                            let _ = items.transfer(
+                                item.serial,
+                                ItemHolder::Ground { route: config.route },
+                                ItemHolder::Destroyed,
+                                TransferReason::Expired,
+                                now,
+                            );
+                        }
+                    }
+                    let mut st = stats_clone.lock().await;
+                    st.objects = objects.counts();
+                    if sweep.is_empty() {
+                        continue;
+                    }
+                    let evicted = sweep.counts();
+                    st.evicted_objects += evicted;
+                    if !sweep.over_cap.is_empty() {
+                        log::warn!(
+                            "{} objects over the caps of {} evicted early",
+                            sweep.over_cap.len(),
+                            config.map_name
+                        );
+                    }
+                    log::debug!("Swept {} objects from {}: {:?}", evicted.total(), config.map_name, evicted);
                 }
                 _ = monster_tick.tick() => {
                     let mut st = stats_clone.lock().await;
 
+                    if stress.as_ref().is_some_and(|active| Instant::now() >= active.deadline) {
+                        if let Some(active) = stress.take() {
+                            st.monster_count -= active.monsters.count();
+                            let mut report = active.report;
+                            report.finished_at_ms = Some(now_ms());
+                            report.during = Some(TickLoad {
+                                player_tick: TickPercentiles::from_samples(&active.player_tick_us),
+                                monster_tick: TickPercentiles::from_samples(&active.monster_tick_us),
+                                peak_degradation_level: active.peak_degradation_level,
+                            });
+                            log::info!("Stress run on {} finished: {:?}", config.map_name, report.during);
+                            *stress_report_clone.lock().await = Some(report);
+                        }
+                    }
+
                     // Monster updates are intentionally lower priority.
                     let skip_ratio = st.monster_degradation_level as u64;
                     if skip_ratio > 0 && st.monster_ticks % (skip_ratio + 1) != 0 {
@@ -289,18 +1008,79 @@ pub fn start_map_server(
                     }
 
                     st.monster_ticks += 1;
+                    drop(st);
+
+                    let started = Instant::now();
+                    if let Some(active) = stress.as_mut() {
+                        let positions: Vec<(u64, (u16, u16))> = players
+                            .values()
+                            .map(|player| (player.character_id, (player.x, player.y)))
+                            .collect();
+                        active.monsters.tick(&positions, doors.grid(), now_ms());
+                    }
+                    let elapsed = started.elapsed().as_micros() as u64;
+                    push_tick_sample(&mut last_monster_tick_us, elapsed);
+                    if let Some(active) = stress.as_mut() {
+                        active.monster_tick_us.push(elapsed);
+                    }
                 }
             }
         }
     });
 
-    MapServerHandle { tx, stats }
+    MapServerHandle {
+        tx,
+        stats,
+        stress_report,
+    }
+}
+
+fn publish_doors(
+    message_hub: &MessageHub,
+    route: RouteKey,
+    from_session_id: u64,
+    doors: Vec<DoorStatus>,
+) {
+    let msg = HubMessage {
+        from_session_id,
+        route,
+        payload: HubPayload::Doors(doors),
+    };
+    let _ = message_hub.publish(MessageScope::LocalMap(route), msg);
+}
+
+fn at_war(guilds: &GuildRelations, wars: &GuildWars, attacker_id: u64, target_id: u64) -> bool {
+    guilds
+        .guild_of(attacker_id)
+        .zip(guilds.guild_of(target_id))
+        .is_some_and(|(guild_id, target_guild_id)| {
+            wars.war_between(guild_id, target_guild_id).is_some()
+        })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::runtime::session_links::{SessionCommand, SessionLink, SessionLinks};
     use crate::runtime::{config::RuntimeConfig, directory::WorldDirectory};
+    use common::collision::TERRAIN_SIZE;
+    use common::PvpMode;
+    use dashmap::DashMap;
+    use protocol::{PacketPayload, WirePacket};
+
+    async fn next_path(observer: &mut SessionLink) -> WaypointPath {
+        let command = tokio::time::timeout(Duration::from_millis(200), observer.commands.recv())
+            .await
+            .expect("path pushed")
+            .expect("link open");
+        match command {
+            SessionCommand::Send(WirePacket {
+                payload: PacketPayload::Server(ServerMessage::EntityPath(path)),
+                ..
+            }) => path,
+            other => panic!("expected path, got {other:?}"),
+        }
+    }
 
     #[tokio::test]
     async fn join_move_and_leave_player() {
@@ -326,12 +1106,25 @@ mod tests {
                 route,
                 map_name: "Lorencia".to_string(),
                 soft_player_cap: 300,
+                pvp: PvpRules::default(),
+                gens_zone: false,
                 player_tick: Duration::from_millis(10),
                 monster_tick: Duration::from_millis(20),
+                collision: Arc::new(CollisionGrid::open()),
+                doors: Vec::new(),
+                monsters: Vec::new(),
+                combat: CombatConfig::default(),
+                cleanup: CleanupConfig::default(),
             },
             directory.clone(),
             persistence.clone(),
             MessageHub::default(),
+            GuildRelations::new(),
+            GuildWars::new(),
+            GensRegistry::new(),
+            SessionPush::default(),
+            ItemLedger::new(0),
+            mpsc::unbounded_channel().0,
         );
 
         map.join(10, 99, 10, 10).await.unwrap();
@@ -358,4 +1151,685 @@ mod tests {
         map.shutdown().await.unwrap();
         persistence.shutdown().await.unwrap();
     }
+
+    #[tokio::test]
+    async fn move_broadcasts_waypoint_path() {
+        let config = RuntimeConfig::default();
+        let directory = WorldDirectory::from_runtime_config(&config);
+        let sink = Arc::new(crate::runtime::persistence::InMemoryPersistenceSink::new());
+        let persistence = crate::runtime::persistence::start_persistence_worker(
+            Duration::from_millis(10),
+            Duration::from_millis(10),
+            100,
+            sink,
+        );
+        let hub = MessageHub::default();
+
+        let route = RouteKey {
+            world_id: 1,
+            entry_id: 1,
+            map_id: 0,
+            instance_id: 1,
+        };
+        // The mover's own session watches its paths.
+        let links = SessionLinks::new();
+        let mut observer = links.attach(10);
+        let push = SessionPush::new(links, Arc::default(), Arc::default());
+
+        let map = start_map_server(
+            MapServerConfig {
+                route,
+                map_name: "Lorencia".to_string(),
+                soft_player_cap: 300,
+                pvp: PvpRules::default(),
+                gens_zone: false,
+                player_tick: Duration::from_millis(10),
+                monster_tick: Duration::from_millis(20),
+                collision: Arc::new(CollisionGrid::open()),
+                doors: Vec::new(),
+                monsters: Vec::new(),
+                combat: CombatConfig::default(),
+                cleanup: CleanupConfig::default(),
+            },
+            directory,
+            persistence.clone(),
+            hub.clone(),
+            GuildRelations::new(),
+            GuildWars::new(),
+            GensRegistry::new(),
+            push,
+            ItemLedger::new(0),
+            mpsc::unbounded_channel().0,
+        );
+
+        map.join(10, 99, 10, 10).await.unwrap();
+        map.move_player(
+            99,
+            MoveInput {
+                client_tick: 1,
+                x: 12,
+                y: 13,
+                direction: 1,
+                path: [0; 8],
+            },
+        )
+        .await
+        .unwrap();
+
+        let path = next_path(&mut observer).await;
+        assert_eq!((path.start_x, path.start_y), (10, 10));
+        assert_eq!(path.end_tile(), (12, 13));
+        assert_eq!(path.entity_id, 99);
+
+        map.shutdown().await.unwrap();
+        persistence.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn moves_route_around_and_never_into_blocked_tiles() {
+        let config = RuntimeConfig::default();
+        let directory = WorldDirectory::from_runtime_config(&config);
+        let sink = Arc::new(crate::runtime::persistence::InMemoryPersistenceSink::new());
+        let persistence = crate::runtime::persistence::start_persistence_worker(
+            Duration::from_millis(10),
+            Duration::from_millis(10),
+            100,
+            sink,
+        );
+        let hub = MessageHub::default();
+
+        let route = RouteKey {
+            world_id: 1,
+            entry_id: 1,
+            map_id: 0,
+            instance_id: 1,
+        };
+        // The mover's own session watches its paths.
+        let links = SessionLinks::new();
+        let mut observer = links.attach(10);
+        let push = SessionPush::new(links, Arc::default(), Arc::default());
+
+        // A wall across x = 12 between y = 8 and y = 12.
+        let mut collision = CollisionGrid::open();
+        for y in 8..=12 {
+            collision.block(12, y);
+        }
+
+        let map = start_map_server(
+            MapServerConfig {
+                route,
+                map_name: "Lorencia".to_string(),
+                soft_player_cap: 300,
+                pvp: PvpRules::default(),
+                gens_zone: false,
+                player_tick: Duration::from_millis(10),
+                monster_tick: Duration::from_millis(20),
+                collision: Arc::new(collision.clone()),
+                doors: Vec::new(),
+                monsters: Vec::new(),
+                combat: CombatConfig::default(),
+                cleanup: CleanupConfig::default(),
+            },
+            directory,
+            persistence.clone(),
+            hub.clone(),
+            GuildRelations::new(),
+            GuildWars::new(),
+            GensRegistry::new(),
+            push,
+            ItemLedger::new(0),
+            mpsc::unbounded_channel().0,
+        );
+
+        let step = |x, y| MoveInput {
+            client_tick: 1,
+            x,
+            y,
+            direction: 0,
+            path: [0; 8],
+        };
+
+        map.join(10, 99, 10, 10).await.unwrap();
+
+        map.move_player(99, step(12, 10)).await.unwrap();
+        let rejected = next_path(&mut observer).await;
+        assert_eq!((rejected.start_x, rejected.start_y), (10, 10));
+        assert!(rejected.directions.is_empty());
+
+        map.move_player(99, step(14, 10)).await.unwrap();
+        let detour = next_path(&mut observer).await;
+        assert_eq!((detour.start_x, detour.start_y), (10, 10));
+        assert_eq!(detour.end_tile(), (14, 10));
+        assert!(detour.tiles().all(|(x, y)| !collision.is_blocked(x, y)));
+
+        map.shutdown().await.unwrap();
+        persistence.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn closed_gates_block_until_opened_or_destroyed() {
+        let config = RuntimeConfig::default();
+        let directory = WorldDirectory::from_runtime_config(&config);
+        let sink = Arc::new(crate::runtime::persistence::InMemoryPersistenceSink::new());
+        let persistence = crate::runtime::persistence::start_persistence_worker(
+            Duration::from_millis(10),
+            Duration::from_millis(10),
+            100,
+            sink,
+        );
+        let hub = MessageHub::default();
+        let route = RouteKey {
+            world_id: 1,
+            entry_id: 1,
+            map_id: 2,
+            instance_id: 1,
+        };
+        // The mover's own session watches its paths.
+        let links = SessionLinks::new();
+        let mut observer = links.attach(10);
+        let push = SessionPush::new(links, Arc::default(), Arc::default());
+
+        let mut local = hub.subscribe(MessageScope::LocalMap(route));
+        // A wall across the map at x = 12 with the gate as its only gap.
+        let mut collision = CollisionGrid::open();
+        for y in (0..TERRAIN_SIZE).filter(|y| *y != 10) {
+            collision.block(12, y);
+        }
+        let map = start_map_server(
+            MapServerConfig {
+                route,
+                map_name: "Devias".to_string(),
+                soft_player_cap: 300,
+                pvp: PvpRules::default(),
+                gens_zone: false,
+                player_tick: Duration::from_millis(10),
+                monster_tick: Duration::from_millis(20),
+                collision: Arc::new(collision),
+                doors: vec![DoorConfig {
+                    id: 1,
+                    name: "Devias Gate".to_string(),
+                    x: 12,
+                    y: 10,
+                    width: 1,
+                    height: 1,
+                    state: DoorState::Closed,
+                    hp: Some(u32::from(PLAYER_HIT_DAMAGE)),
+                    event: None,
+                }],
+                monsters: Vec::new(),
+                combat: CombatConfig::default(),
+                cleanup: CleanupConfig::default(),
+            },
+            directory,
+            persistence.clone(),
+            hub.clone(),
+            GuildRelations::new(),
+            GuildWars::new(),
+            GensRegistry::new(),
+            push,
+            ItemLedger::new(0),
+            mpsc::unbounded_channel().0,
+        );
+        let step = |x, y| MoveInput {
+            client_tick: 1,
+            x,
+            y,
+            direction: 0,
+            path: [0; 8],
+        };
+        async fn next_doors(
+            observer: &mut tokio::sync::broadcast::Receiver<HubMessage>,
+        ) -> Vec<DoorStatus> {
+            loop {
+                let msg = tokio::time::timeout(Duration::from_millis(200), observer.recv())
+                    .await
+                    .expect("door broadcast")
+                    .expect("hub open");
+                if let HubPayload::Doors(doors) = msg.payload {
+                    return doors;
+                }
+            }
+        }
+
+        map.join(10, 99, 10, 10).await.unwrap();
+        map.move_player(99, step(14, 10)).await.unwrap();
+        let rejected = next_path(&mut observer).await;
+        assert!(rejected.directions.is_empty());
+
+        map.use_skill(
+            99,
+            UseSkillInput {
+                client_tick: 2,
+                skill_id: 1,
+                target_entity_id: Some(DoorStatus::entity_id(1)),
+                target_x: 12,
+                target_y: 10,
+            },
+            0,
+        )
+        .await
+        .unwrap();
+        let broken = next_doors(&mut local).await;
+        assert_eq!(broken[0].state, DoorState::Destroyed);
+        map.move_player(99, step(14, 10)).await.unwrap();
+        assert_eq!(next_path(&mut observer).await.end_tile(), (14, 10));
+
+        let rebuilt = map.set_door(1, DoorState::Closed).await.unwrap().unwrap();
+        assert_eq!(rebuilt.hp, Some(u32::from(PLAYER_HIT_DAMAGE)));
+        assert_eq!(next_doors(&mut local).await, vec![rebuilt]);
+        map.move_player(99, step(10, 10)).await.unwrap();
+        assert!(next_path(&mut observer).await.directions.is_empty());
+
+        assert_eq!(map.set_door(7, DoorState::Open).await.unwrap(), None);
+        assert_eq!(map.doors().await, vec![rebuilt]);
+
+        map.shutdown().await.unwrap();
+        persistence.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn stress_run_spawns_monsters_and_reports_tick_times() {
+        let config = RuntimeConfig::default();
+        let directory = WorldDirectory::from_runtime_config(&config);
+        let sink = Arc::new(crate::runtime::persistence::InMemoryPersistenceSink::new());
+        let persistence = crate::runtime::persistence::start_persistence_worker(
+            Duration::from_millis(10),
+            Duration::from_millis(10),
+            100,
+            sink,
+        );
+
+        let route = RouteKey {
+            world_id: 1,
+            entry_id: 1,
+            map_id: 0,
+            instance_id: 1,
+        };
+        let map = start_map_server(
+            MapServerConfig {
+                route,
+                map_name: "Lorencia".to_string(),
+                soft_player_cap: 300,
+                pvp: PvpRules::default(),
+                gens_zone: false,
+                player_tick: Duration::from_millis(10),
+                monster_tick: Duration::from_millis(5),
+                collision: Arc::new(CollisionGrid::open()),
+                doors: Vec::new(),
+                monsters: Vec::new(),
+                combat: CombatConfig::default(),
+                cleanup: CleanupConfig::default(),
+            },
+            directory,
+            persistence.clone(),
+            MessageHub::default(),
+            GuildRelations::new(),
+            GuildWars::new(),
+            GensRegistry::new(),
+            SessionPush::default(),
+            ItemLedger::new(0),
+            mpsc::unbounded_channel().0,
+        );
+        map.join(10, 99, 128, 128).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(map.stress_report().await, None);
+
+        let started = map
+            .start_stress(500, Duration::from_millis(60))
+            .await
+            .unwrap();
+        assert_eq!(started.monsters, 500);
+        assert!(started.before.monster_tick.samples > 0);
+        assert_eq!(started.during, None);
+        assert_eq!(map.stats().await.monster_count, BASE_MONSTER_COUNT + 500);
+        assert_eq!(
+            map.start_stress(10, Duration::from_millis(60)).await,
+            Err(StressError::AlreadyRunning)
+        );
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let finished = map.stress_report().await.expect("finished report");
+        assert!(finished.finished_at_ms.is_some());
+        let during = finished.during.expect("tick times under load");
+        assert!(during.monster_tick.samples > 0);
+        assert!(during.player_tick.samples > 0);
+        assert_eq!(map.stats().await.monster_count, BASE_MONSTER_COUNT);
+
+        map.shutdown().await.unwrap();
+        persistence.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn kills_between_warring_guilds_reach_both_scoreboards() {
+        let config = RuntimeConfig::default();
+        let directory = WorldDirectory::from_runtime_config(&config);
+        let sink = Arc::new(crate::runtime::persistence::InMemoryPersistenceSink::new());
+        let persistence = crate::runtime::persistence::start_persistence_worker(
+            Duration::from_millis(10),
+            Duration::from_millis(10),
+            100,
+            sink,
+        );
+        let hub = MessageHub::default();
+        let guilds = GuildRelations::new();
+        guilds.set_membership(99, Some(1));
+        guilds.set_membership(100, Some(2));
+        let wars = GuildWars::new();
+        wars.start(1, 2, crate::runtime::guild_wars::MIN_WAR_DURATION, now_ms())
+            .unwrap();
+        // The defender's character plays on session 11.
+        let links = SessionLinks::new();
+        let mut defender = links.attach(11);
+        let push = SessionPush::new(
+            links,
+            Arc::default(),
+            Arc::new(DashMap::from_iter([(100, 11)])),
+        );
+        let mut local = hub.subscribe(MessageScope::LocalMap(RouteKey::LOBBY));
+
+        let map = start_map_server(
+            MapServerConfig {
+                route: RouteKey::LOBBY,
+                map_name: "Lorencia".to_string(),
+                soft_player_cap: 300,
+                pvp: PvpRules::default(),
+                gens_zone: false,
+                player_tick: Duration::from_millis(10),
+                monster_tick: Duration::from_millis(20),
+                collision: Arc::new(CollisionGrid::open()),
+                doors: Vec::new(),
+                monsters: Vec::new(),
+                combat: CombatConfig::default(),
+                cleanup: CleanupConfig::default(),
+            },
+            directory,
+            persistence.clone(),
+            hub,
+            guilds,
+            wars.clone(),
+            GensRegistry::new(),
+            push,
+            ItemLedger::new(0),
+            mpsc::unbounded_channel().0,
+        );
+        map.join(10, 99, 10, 10).await.unwrap();
+        map.join(11, 100, 11, 10).await.unwrap();
+
+        // Not a PvP map, but the war lets the guilds fight.
+        for client_tick in 0..(PLAYER_MAX_HP / PLAYER_HIT_DAMAGE) as u32 {
+            map.use_skill(
+                99,
+                UseSkillInput {
+                    client_tick,
+                    skill_id: 1,
+                    target_entity_id: Some(100),
+                    target_x: 11,
+                    target_y: 10,
+                },
+                0,
+            )
+            .await
+            .unwrap();
+        }
+
+        let command = tokio::time::timeout(Duration::from_millis(200), defender.commands.recv())
+            .await
+            .expect("score pushed")
+            .expect("link open");
+        match command {
+            SessionCommand::Send(packet) => {
+                let PacketPayload::Server(ServerMessage::GuildWarScore(score)) = packet.payload
+                else {
+                    panic!("expected war score, got {:?}", packet.payload);
+                };
+                assert_eq!((score.guild_kills, score.target_kills), (1, 0));
+                assert!(!score.finished);
+            }
+            other => panic!("expected a push, got {other:?}"),
+        }
+        assert_eq!(wars.active()[0].guild_kills, 1);
+
+        // Every hit reaches the map's combat log; the last one killed.
+        let mut hits = Vec::new();
+        while let Ok(msg) = local.try_recv() {
+            if let HubPayload::Damage(hit) = msg.payload {
+                hits.push((
+                    hit.attacker_entity_id,
+                    hit.target_entity_id,
+                    hit.remaining_hp,
+                ));
+            }
+        }
+        assert_eq!(hits.len(), (PLAYER_MAX_HP / PLAYER_HIT_DAMAGE) as usize);
+        assert_eq!(hits.last(), Some(&(99, 100, 0)));
+
+        map.shutdown().await.unwrap();
+        persistence.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn hits_are_checked_against_where_the_attacker_saw_the_target() {
+        let config = RuntimeConfig::default();
+        let directory = WorldDirectory::from_runtime_config(&config);
+        let sink = Arc::new(crate::runtime::persistence::InMemoryPersistenceSink::new());
+        let persistence = crate::runtime::persistence::start_persistence_worker(
+            Duration::from_millis(10),
+            Duration::from_millis(10),
+            100,
+            sink,
+        );
+        let hub = MessageHub::default();
+        let mut local = hub.subscribe(MessageScope::LocalMap(RouteKey::LOBBY));
+
+        let map = start_map_server(
+            MapServerConfig {
+                route: RouteKey::LOBBY,
+                map_name: "Lorencia".to_string(),
+                soft_player_cap: 300,
+                pvp: PvpRules::new(PvpMode::FreePk),
+                gens_zone: false,
+                player_tick: Duration::from_millis(10),
+                monster_tick: Duration::from_millis(20),
+                collision: Arc::new(CollisionGrid::open()),
+                doors: Vec::new(),
+                monsters: Vec::new(),
+                combat: CombatConfig::default(),
+                cleanup: CleanupConfig::default(),
+            },
+            directory,
+            persistence.clone(),
+            hub,
+            GuildRelations::new(),
+            GuildWars::new(),
+            GensRegistry::new(),
+            SessionPush::default(),
+            ItemLedger::new(0),
+            mpsc::unbounded_channel().0,
+        );
+        map.join(10, 99, 10, 10).await.unwrap();
+        map.join(11, 100, 11, 10).await.unwrap();
+        // The target runs off right before the hits arrive.
+        map.move_player(
+            100,
+            MoveInput {
+                client_tick: 1,
+                x: 20,
+                y: 10,
+                direction: 0,
+                path: [0; 8],
+            },
+        )
+        .await
+        .unwrap();
+
+        let swing = UseSkillInput {
+            client_tick: 2,
+            skill_id: 1,
+            target_entity_id: Some(100),
+            target_x: 11,
+            target_y: 10,
+        };
+        // Without latency the target is long gone; 120 ms ago it was in reach.
+        map.use_skill(99, swing.clone(), 0).await.unwrap();
+        map.use_skill(99, swing, 120).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let mut hits = 0;
+        while let Ok(msg) = local.try_recv() {
+            if let HubPayload::Damage(hit) = msg.payload {
+                assert_eq!(
+                    hit.remaining_hp,
+                    u32::from(PLAYER_MAX_HP - PLAYER_HIT_DAMAGE)
+                );
+                hits += 1;
+            }
+        }
+        assert_eq!(hits, 1);
+
+        map.shutdown().await.unwrap();
+        persistence.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn rival_gens_fight_only_inside_gens_zones() {
+        let config = RuntimeConfig::default();
+        let directory = WorldDirectory::from_runtime_config(&config);
+        let sink = Arc::new(crate::runtime::persistence::InMemoryPersistenceSink::new());
+        let persistence = crate::runtime::persistence::start_persistence_worker(
+            Duration::from_millis(10),
+            Duration::from_millis(10),
+            100,
+            sink,
+        );
+        let gens = GensRegistry::new();
+        gens.join(99, protocol::GensFaction::Duprian, 1).unwrap();
+        gens.join(100, protocol::GensFaction::Vanert, 2).unwrap();
+
+        let mut maps = Vec::new();
+        for gens_zone in [false, true] {
+            let map = start_map_server(
+                MapServerConfig {
+                    route: RouteKey::LOBBY,
+                    map_name: "Acheron".to_string(),
+                    soft_player_cap: 300,
+                    pvp: PvpRules::default(),
+                    gens_zone,
+                    player_tick: Duration::from_millis(10),
+                    monster_tick: Duration::from_millis(20),
+                    collision: Arc::new(CollisionGrid::open()),
+                    doors: Vec::new(),
+                    monsters: Vec::new(),
+                    combat: CombatConfig::default(),
+                    cleanup: CleanupConfig::default(),
+                },
+                directory.clone(),
+                persistence.clone(),
+                MessageHub::default(),
+                GuildRelations::new(),
+                GuildWars::new(),
+                gens.clone(),
+                SessionPush::default(),
+                ItemLedger::new(0),
+                mpsc::unbounded_channel().0,
+            );
+            map.join(10, 99, 10, 10).await.unwrap();
+            map.join(11, 100, 11, 10).await.unwrap();
+
+            for client_tick in 0..(PLAYER_MAX_HP / PLAYER_HIT_DAMAGE) as u32 {
+                map.use_skill(
+                    99,
+                    UseSkillInput {
+                        client_tick,
+                        skill_id: 1,
+                        target_entity_id: Some(100),
+                        target_x: 11,
+                        target_y: 10,
+                    },
+                    0,
+                )
+                .await
+                .unwrap();
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            let expected = if gens_zone {
+                crate::runtime::gens::KILL_CONTRIBUTION
+            } else {
+                0
+            };
+            assert_eq!(gens.member(99).unwrap().contribution, expected);
+            maps.push(map);
+        }
+
+        for map in maps {
+            map.shutdown().await.unwrap();
+        }
+        persistence.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn emotes_reach_the_map_from_players_on_it_at_a_limited_rate() {
+        let config = RuntimeConfig::default();
+        let directory = WorldDirectory::from_runtime_config(&config);
+        let sink = Arc::new(crate::runtime::persistence::InMemoryPersistenceSink::new());
+        let persistence = crate::runtime::persistence::start_persistence_worker(
+            Duration::from_millis(10),
+            Duration::from_millis(10),
+            100,
+            sink,
+        );
+        let hub = MessageHub::default();
+        let route = RouteKey::LOBBY;
+        let mut observer = hub.subscribe(MessageScope::LocalMap(route));
+
+        let map = start_map_server(
+            MapServerConfig {
+                route,
+                map_name: "Lorencia".to_string(),
+                soft_player_cap: 300,
+                pvp: PvpRules::default(),
+                gens_zone: false,
+                player_tick: Duration::from_millis(10),
+                monster_tick: Duration::from_millis(20),
+                collision: Arc::new(CollisionGrid::open()),
+                doors: Vec::new(),
+                monsters: Vec::new(),
+                combat: CombatConfig::default(),
+                cleanup: CleanupConfig::default(),
+            },
+            directory,
+            persistence.clone(),
+            hub.clone(),
+            GuildRelations::new(),
+            GuildWars::new(),
+            GensRegistry::new(),
+            SessionPush::default(),
+            ItemLedger::new(0),
+            mpsc::unbounded_channel().0,
+        );
+
+        // Not on the map yet: nothing is relayed.
+        map.emote(10, 99, Emote::Drink).await.unwrap();
+        map.join(10, 99, 130, 120).await.unwrap();
+        map.emote(10, 99, Emote::Sit).await.unwrap();
+        // Too soon after the last one.
+        map.emote(10, 99, Emote::Clap).await.unwrap();
+
+        let msg = tokio::time::timeout(Duration::from_millis(200), observer.recv())
+            .await
+            .expect("emote broadcast")
+            .expect("hub open");
+        assert_eq!(msg.from_session_id, 10);
+        assert_eq!(
+            msg.payload,
+            HubPayload::Emote {
+                entity_id: 99,
+                emote: Emote::Sit
+            }
+        );
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(observer.try_recv().is_err());
+
+        map.shutdown().await.unwrap();
+        persistence.shutdown().await.unwrap();
+    }
 }
@@ -1,19 +1,41 @@
 use std::sync::Arc;
 
 use dashmap::DashMap;
-use protocol::{ChatPayload, RouteKey};
+use protocol::{
+    ChatPayload, DamageEvent, DoorStatus, DoppelgangerStatus, Emote, EventNotice, ItemInstance,
+    MonsterAffix, RouteKey,
+};
 use tokio::sync::broadcast;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MessageScope {
     LocalMap(RouteKey),
+    Session(u64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HubPayload {
+    Chat(ChatPayload),
+    Damage(DamageEvent),
+    Event(EventNotice),
+    Doppelganger(DoppelgangerStatus),
+    ItemsExpired(Vec<ItemInstance>),
+    MonsterAffixes {
+        entity_id: u32,
+        affixes: Vec<MonsterAffix>,
+    },
+    Emote {
+        entity_id: u32,
+        emote: Emote,
+    },
+    Doors(Vec<DoorStatus>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct HubMessage {
     pub from_session_id: u64,
     pub route: RouteKey,
-    pub payload: ChatPayload,
+    pub payload: HubPayload,
 }
 
 #[derive(Clone)]
@@ -64,6 +86,7 @@ fn scope_key(scope: &MessageScope) -> String {
             "local:{}:{}:{}:{}",
             route.world_id, route.entry_id, route.map_id, route.instance_id
         ),
+        MessageScope::Session(session_id) => format!("session:{}", session_id),
     }
 }
 
@@ -87,16 +110,16 @@ mod tests {
             HubMessage {
                 from_session_id: 7,
                 route,
-                payload: ChatPayload {
+                payload: HubPayload::Chat(ChatPayload {
                     channel: protocol::ChatChannel::Local,
                     target: None,
                     text: "hello".to_string(),
-                },
+                }),
             },
         );
 
         assert_eq!(delivered, 1);
         let msg = rx.recv().await.expect("must receive message");
-        assert_eq!(msg.payload.text, "hello");
+        assert!(matches!(msg.payload, HubPayload::Chat(chat) if chat.text == "hello"));
     }
 }
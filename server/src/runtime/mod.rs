@@ -1,10 +1,39 @@
+pub mod account_settings;
+pub mod bestiary;
+pub mod cash_shop;
+pub mod chat_commands;
+pub mod collision;
 pub mod config;
+pub mod cooldowns;
 pub mod core;
 pub mod directory;
+pub mod doors;
+pub mod doppelganger;
+pub mod elites;
+pub mod events;
+pub mod gens;
+pub mod guild_wars;
+pub mod guilds;
+pub mod helper;
+pub mod item_ledger;
+pub mod items;
+pub mod lag_compensation;
+pub mod mailbox;
+pub mod maintenance;
+pub mod map_objects;
 pub mod map_server;
 pub mod message_hub;
 pub mod persistence;
+pub mod portals;
+pub mod quests;
 pub mod quic_gateway;
+pub mod restart;
+pub mod session_links;
+pub mod stress;
+pub mod trades;
+pub mod transfer_limits;
+pub mod wallets;
+pub mod webhooks;
 
 pub use config::RuntimeConfig;
 pub use core::MuCoreRuntime;
@@ -7,6 +7,7 @@ use dashmap::DashMap;
 use protocol::RouteKey;
 use serde::Serialize;
 use tokio::sync::{mpsc, oneshot, Mutex};
+use utoipa::ToSchema;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CharacterStateSnapshot {
@@ -22,6 +23,8 @@ pub struct CharacterStateSnapshot {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CriticalEventKind {
     EconomyMutation,
+    RewardMailed,
+    RewardClaimed,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -34,7 +37,7 @@ pub struct CriticalEvent {
     pub occurred_at_ms: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Default)]
+#[derive(Debug, Clone, Serialize, Default, ToSchema)]
 pub struct PersistenceMetrics {
     pub queue_depth: usize,
     pub pending_non_critical: usize,
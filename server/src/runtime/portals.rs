@@ -0,0 +1,218 @@
+//! Walk-in portals: the gates of `common::gates` checked against the
+//! character that steps on them.
+//!
+//! A gate asks for its own level, and the map it leads to may ask for more:
+//! an item carried in the inventory (the Moonstone Pendant for Kanturu
+//! Remain) or, on maps without ground such as Icarus, wings or a flying mount
+//! worn. Worn items are what the character put on with `EquipItem`; one only
+//! counts while the ledger still has the character holding it, so traded or
+//! expired items stop counting on their own.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use common::gates::{self, Gate};
+use common::{EntryRequirement, EquipSlot, ItemCode, Traversal, WorldMap};
+use dashmap::DashMap;
+
+/// Mounts that carry their rider over maps without ground: the Horn of
+/// Dinorant and the Horn of Fenrir.
+const FLYING_MOUNTS: [ItemCode; 2] = [ItemCode::new(13, 3), ItemCode::new(13, 37)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum PortalError {
+    #[error("level {required} is needed to pass this gate")]
+    Level { required: u16 },
+    #[error("wings or a flying mount are needed to enter {map}")]
+    Flight { map: WorldMap },
+    #[error("item {item} is needed to enter {map}")]
+    MissingItem { map: WorldMap, item: ItemCode },
+}
+
+/// Gate covering tile `(x, y)` of map `map_id`.
+pub fn gate_at(map_id: u16, x: u16, y: u16) -> Option<&'static Gate> {
+    let map = WorldMap::from_id(u8::try_from(map_id).ok()?)?;
+    gates::gate_at(map, x, y)
+}
+
+/// Item carried to pass a gate into a map with `requirement`. Event entries
+/// take their tickets through the event itself, so only the requirements of
+/// maps a gate leads to have one.
+fn entry_item(requirement: EntryRequirement) -> Option<ItemCode> {
+    match requirement {
+        EntryRequirement::MoonstonePendant => Some(ItemCode::new(13, 38)),
+        _ => None,
+    }
+}
+
+/// Whether a character of `level`, wearing `worn` and carrying the items
+/// `holds` answers for, may walk through `gate`.
+pub fn check_gate(
+    gate: &Gate,
+    level: u16,
+    worn: &[ItemCode],
+    holds: impl Fn(ItemCode) -> bool,
+) -> Result<(), PortalError> {
+    if !gate.admits_level(level) {
+        return Err(PortalError::Level {
+            required: gate.min_level,
+        });
+    }
+
+    let map = gate.target_map;
+    let info = map.info();
+    if info.traversal == Traversal::Fly && !worn.iter().copied().any(can_fly) {
+        return Err(PortalError::Flight { map });
+    }
+    if let Some(item) = info.entry.and_then(entry_item) {
+        if !holds(item) {
+            return Err(PortalError::MissingItem { map, item });
+        }
+    }
+    Ok(())
+}
+
+fn can_fly(code: ItemCode) -> bool {
+    FLYING_MOUNTS.contains(&code)
+        || code
+            .definition()
+            .and_then(|item| item.equip_slot())
+            .is_some_and(|slot| slot == EquipSlot::Wings)
+}
+
+/// Serial and code of the item worn in each slot.
+type Slots = HashMap<EquipSlot, (u64, ItemCode)>;
+
+/// Items each character has on, shared by all sessions.
+#[derive(Clone, Default)]
+pub struct WornItems {
+    // key: character_id
+    worn: Arc<DashMap<u64, Slots>>,
+}
+
+impl WornItems {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Puts the item on, replacing whatever was in its slot. Items without a
+    /// slot are ignored.
+    pub fn wear(&self, character_id: u64, serial: u64, code: ItemCode) {
+        let Some(slot) = code.definition().and_then(|item| item.equip_slot()) else {
+            return;
+        };
+        self.worn
+            .entry(character_id)
+            .or_default()
+            .insert(slot, (serial, code));
+    }
+
+    /// Serial of the item worn in `slot`.
+    pub fn in_slot(&self, character_id: u64, slot: EquipSlot) -> Option<u64> {
+        self.worn
+            .get(&character_id)
+            .and_then(|slots| slots.get(&slot).map(|(serial, _)| *serial))
+    }
+
+    /// Slot `serial` is worn in, if it is worn.
+    pub fn slot_of(&self, character_id: u64, serial: u64) -> Option<EquipSlot> {
+        self.worn.get(&character_id).and_then(|slots| {
+            slots
+                .iter()
+                .find_map(|(slot, (worn, _))| (*worn == serial).then_some(*slot))
+        })
+    }
+
+    /// Takes off the item worn in `slot` and returns its serial.
+    pub fn take_off(&self, character_id: u64, slot: EquipSlot) -> Option<u64> {
+        self.worn
+            .get_mut(&character_id)
+            .and_then(|mut slots| slots.remove(&slot))
+            .map(|(serial, _)| serial)
+    }
+
+    /// Serials and codes of the items `character_id` has on.
+    pub fn worn(&self, character_id: u64) -> Vec<(u64, ItemCode)> {
+        self.worn
+            .get(&character_id)
+            .map(|slots| slots.values().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn forget(&self, character_id: u64) {
+        self.worn.remove(&character_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::gates::TileRect;
+
+    const WINGS_OF_ELF: ItemCode = ItemCode::new(12, 0);
+    const DINORANT: ItemCode = ItemCode::new(13, 3);
+
+    fn gate_to(target_map: WorldMap, min_level: u16) -> Gate {
+        Gate {
+            id: 900,
+            from_map: WorldMap::Devias,
+            target_map,
+            source_rect: TileRect::new(1, 1, 2, 2),
+            target_coords: (14, 12),
+            min_level,
+        }
+    }
+
+    #[test]
+    fn gates_check_level_before_anything_else() {
+        let gate = gate_at(WorldMap::Lorencia as u16, 122, 232).unwrap();
+        assert_eq!(gate.target_map, WorldMap::Dungeon);
+        assert_eq!(gate_at(WorldMap::Lorencia as u16, 140, 125), None);
+        assert_eq!(
+            check_gate(gate, 19, &[], |_| false),
+            Err(PortalError::Level { required: 20 })
+        );
+        assert_eq!(check_gate(gate, 20, &[], |_| false), Ok(()));
+    }
+
+    #[test]
+    fn icarus_needs_wings_or_a_flying_mount() {
+        let gate = gate_to(WorldMap::Icarus, 170);
+        assert_eq!(
+            check_gate(&gate, 170, &[], |_| true),
+            Err(PortalError::Flight {
+                map: WorldMap::Icarus
+            })
+        );
+        assert_eq!(check_gate(&gate, 170, &[WINGS_OF_ELF], |_| false), Ok(()));
+        assert_eq!(check_gate(&gate, 170, &[DINORANT], |_| false), Ok(()));
+        // The Horn of Uniria does not fly.
+        assert!(check_gate(&gate, 170, &[ItemCode::new(13, 2)], |_| false).is_err());
+    }
+
+    #[test]
+    fn kanturu_remain_needs_the_moonstone_pendant() {
+        let gate = gates::gate_by_id(135).unwrap();
+        let pendant = ItemCode::new(13, 38);
+        assert_eq!(
+            check_gate(gate, 400, &[], |_| false),
+            Err(PortalError::MissingItem {
+                map: WorldMap::KanturuRemain,
+                item: pendant
+            })
+        );
+        assert_eq!(check_gate(gate, 400, &[], |code| code == pendant), Ok(()));
+    }
+
+    #[test]
+    fn worn_items_keep_one_per_slot() {
+        let worn = WornItems::new();
+        worn.wear(7, 1, WINGS_OF_ELF);
+        worn.wear(7, 2, ItemCode::new(12, 1));
+        worn.wear(7, 3, ItemCode::new(14, 13));
+        assert_eq!(worn.worn(7), vec![(2, ItemCode::new(12, 1))]);
+
+        worn.forget(7);
+        assert!(worn.worn(7).is_empty());
+    }
+}
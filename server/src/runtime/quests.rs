@@ -0,0 +1,377 @@
+//! Starting kit and tutorial quest of new characters.
+//!
+//! The first time a character enters the game it spawns in its class's town,
+//! receives the kit configured under `[starting_kit]` and, when `[tutorial]`
+//! is set, starts the tutorial chain. Steps advance on `QuestEvent`s: talking
+//! to an NPC, killing monsters or equipping an item. The last step grants the
+//! quest reward.
+
+use std::sync::Arc;
+
+use dashmap::{DashMap, DashSet};
+use protocol::QuestStatus;
+use serde::{Deserialize, Serialize};
+
+use super::config::{ItemGrantConfig, QuestConfig, QuestStepConfig, StartingKitConfig};
+use super::mailbox::RewardBundle;
+use crate::db::{models::QuestLogRecord, repository::QuestLogRepository};
+
+/// Something a character did that quest steps may ask for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuestEvent {
+    TalkedTo { npc_id: u16 },
+    Killed { monster_id: u16 },
+    Equipped,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuestProgress {
+    pub quest_id: u16,
+    pub step: u8,
+    /// Kills counted towards the current step.
+    pub kills: u16,
+    pub completed_at_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuestLog {
+    pub kit_granted_at_ms: Option<u64>,
+    pub tutorial: Option<QuestProgress>,
+}
+
+/// What a character gets on its first entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Welcome {
+    /// `None` when the kit is empty.
+    pub kit: Option<RewardBundle>,
+    pub tutorial: Option<QuestStatus>,
+}
+
+/// Result of an event that moved a quest forward.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuestAdvance {
+    pub status: QuestStatus,
+    /// Set when the event finished the quest and it has a reward.
+    pub reward: Option<RewardBundle>,
+}
+
+fn bundle(zen: u64, items: &[ItemGrantConfig]) -> Option<RewardBundle> {
+    (zen > 0 || !items.is_empty()).then(|| RewardBundle {
+        zen,
+        items: items.iter().map(ItemGrantConfig::to_item).collect(),
+    })
+}
+
+fn quest_status(quest: &QuestConfig, progress: &QuestProgress) -> QuestStatus {
+    QuestStatus {
+        quest_id: quest.quest_id,
+        name: quest.name.clone(),
+        step: progress.step,
+        steps: quest.steps.len() as u8,
+        objective: quest
+            .steps
+            .get(progress.step as usize)
+            .filter(|_| progress.completed_at_ms.is_none())
+            .map(|step| step.objective()),
+        progress: progress.kills,
+    }
+}
+
+/// Quest logs of every character, shared by all sessions.
+///
+/// Loaded from MongoDB at boot; changes are written back in batches by
+/// [`QuestLogs::persist`].
+#[derive(Clone)]
+pub struct QuestLogs {
+    kit: Arc<StartingKitConfig>,
+    tutorial: Option<Arc<QuestConfig>>,
+    // key: character_id
+    logs: Arc<DashMap<u64, QuestLog>>,
+    dirty: Arc<DashSet<u64>>,
+}
+
+impl QuestLogs {
+    pub fn new(kit: StartingKitConfig, tutorial: Option<QuestConfig>) -> Self {
+        Self {
+            kit: Arc::new(kit),
+            tutorial: tutorial.map(Arc::new),
+            logs: Arc::new(DashMap::new()),
+            dirty: Arc::new(DashSet::new()),
+        }
+    }
+
+    pub fn load(&self, records: impl IntoIterator<Item = QuestLogRecord>) {
+        for record in records {
+            self.logs.insert(
+                record.character_id,
+                QuestLog {
+                    kit_granted_at_ms: record.kit_granted_at_ms,
+                    tutorial: record.tutorial,
+                },
+            );
+        }
+    }
+
+    pub fn log(&self, character_id: u64) -> Option<QuestLog> {
+        self.logs.get(&character_id).map(|entry| *entry.value())
+    }
+
+    /// A character is new until it received its starting kit.
+    pub fn is_new(&self, character_id: u64) -> bool {
+        self.log(character_id)
+            .is_none_or(|log| log.kit_granted_at_ms.is_none())
+    }
+
+    /// Map the kit of `class_id` spawns new characters on, when it sets one.
+    pub fn spawn_map(&self, class_id: u8) -> Option<u16> {
+        self.kit.for_class(class_id).map_id
+    }
+
+    /// Hands out the starting kit and starts the tutorial, once per character.
+    pub fn welcome(&self, character_id: u64, class_id: u8, now_ms: u64) -> Option<Welcome> {
+        let mut log = self.logs.entry(character_id).or_default();
+        if log.kit_granted_at_ms.is_some() {
+            return None;
+        }
+        log.kit_granted_at_ms = Some(now_ms);
+        let kit = self.kit.for_class(class_id);
+
+        let tutorial = self.tutorial.as_ref().map(|quest| {
+            let progress = QuestProgress {
+                quest_id: quest.quest_id,
+                step: 0,
+                kills: 0,
+                completed_at_ms: quest.steps.is_empty().then_some(now_ms),
+            };
+            log.tutorial = Some(progress);
+            quest_status(quest, &progress)
+        });
+        self.dirty.insert(character_id);
+
+        Some(Welcome {
+            kit: bundle(kit.zen, &kit.items),
+            tutorial,
+        })
+    }
+
+    /// Tutorial progress of the character, if it ever started it.
+    pub fn status(&self, character_id: u64) -> Option<QuestStatus> {
+        let quest = self.tutorial.as_ref()?;
+        let progress = self.log(character_id)?.tutorial?;
+        (progress.quest_id == quest.quest_id).then(|| quest_status(quest, &progress))
+    }
+
+    /// Applies `event` to the character's running tutorial. Returns `None`
+    /// when the current step does not care about it.
+    pub fn record(
+        &self,
+        character_id: u64,
+        event: QuestEvent,
+        now_ms: u64,
+    ) -> Option<QuestAdvance> {
+        let quest = self.tutorial.as_ref()?;
+        let mut log = self.logs.get_mut(&character_id)?;
+        let progress = log.tutorial.as_mut().filter(|progress| {
+            progress.quest_id == quest.quest_id && progress.completed_at_ms.is_none()
+        })?;
+
+        let step_done = match (*quest.steps.get(progress.step as usize)?, event) {
+            (QuestStepConfig::TalkToNpc { npc_id }, QuestEvent::TalkedTo { npc_id: talked })
+                if npc_id == talked =>
+            {
+                true
+            }
+            (
+                QuestStepConfig::KillMonsters { monster_id, count },
+                QuestEvent::Killed { monster_id: killed },
+            ) if monster_id == killed => {
+                progress.kills += 1;
+                progress.kills >= count
+            }
+            (QuestStepConfig::EquipItem, QuestEvent::Equipped) => true,
+            _ => return None,
+        };
+
+        let mut reward = None;
+        if step_done {
+            progress.step += 1;
+            progress.kills = 0;
+            if progress.step as usize == quest.steps.len() {
+                progress.completed_at_ms = Some(now_ms);
+                reward = bundle(quest.reward_zen, &quest.reward_items);
+            }
+        }
+        self.dirty.insert(character_id);
+
+        Some(QuestAdvance {
+            status: quest_status(quest, progress),
+            reward,
+        })
+    }
+
+    /// Writes every changed log to MongoDB. Failed writes stay pending for
+    /// the next call. Returns how many logs were written.
+    pub async fn persist(&self, repository: &QuestLogRepository) -> usize {
+        let pending: Vec<u64> = self.dirty.iter().map(|entry| *entry).collect();
+        let mut written = 0;
+        for character_id in pending {
+            self.dirty.remove(&character_id);
+            let Some(log) = self.log(character_id) else {
+                continue;
+            };
+
+            let record = QuestLogRecord {
+                id: None,
+                character_id,
+                kit_granted_at_ms: log.kit_granted_at_ms,
+                tutorial: log.tutorial,
+            };
+            match repository.save(&record).await {
+                Ok(()) => written += 1,
+                Err(err) => {
+                    log::error!(
+                        "Failed to save quest log of character {}: {}",
+                        character_id,
+                        err
+                    );
+                    self.dirty.insert(character_id);
+                }
+            }
+        }
+        written
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::config::ClassKitConfig;
+    use protocol::QuestObjective;
+
+    fn item(group: u8, index: u16) -> ItemGrantConfig {
+        ItemGrantConfig {
+            group,
+            index,
+            level: 0,
+            quantity: 1,
+        }
+    }
+
+    fn quest_logs() -> QuestLogs {
+        QuestLogs::new(
+            StartingKitConfig {
+                default: ClassKitConfig {
+                    class_id: None,
+                    zen: 2_000,
+                    map_id: None,
+                    items: vec![item(14, 0)],
+                },
+                classes: vec![ClassKitConfig {
+                    class_id: Some(2),
+                    zen: 0,
+                    map_id: Some(3),
+                    items: Vec::new(),
+                }],
+            },
+            Some(QuestConfig {
+                quest_id: 1,
+                name: "First Steps".to_string(),
+                steps: vec![
+                    QuestStepConfig::TalkToNpc { npc_id: 249 },
+                    QuestStepConfig::KillMonsters {
+                        monster_id: 3,
+                        count: 2,
+                    },
+                    QuestStepConfig::EquipItem,
+                ],
+                reward_zen: 10_000,
+                reward_items: vec![item(0, 1)],
+            }),
+        )
+    }
+
+    #[test]
+    fn kit_is_handed_out_once_per_character() {
+        let logs = quest_logs();
+        assert!(logs.is_new(1));
+        assert_eq!((logs.spawn_map(1), logs.spawn_map(2)), (None, Some(3)));
+
+        let welcome = logs.welcome(1, 1, 100).expect("first entry");
+        let kit = welcome.kit.expect("default kit");
+        assert_eq!(kit.zen, 2_000);
+        assert_eq!((kit.items[0].group, kit.items[0].index), (14, 0));
+        let tutorial = welcome.tutorial.expect("tutorial started");
+        assert_eq!(
+            tutorial.objective,
+            Some(QuestObjective::TalkToNpc { npc_id: 249 })
+        );
+
+        assert!(!logs.is_new(1));
+        assert_eq!(logs.welcome(1, 1, 200), None);
+        // An empty class kit still counts as received.
+        assert_eq!(logs.welcome(2, 2, 200).unwrap().kit, None);
+        assert!(!logs.is_new(2));
+    }
+
+    #[test]
+    fn tutorial_steps_advance_in_order() {
+        let logs = quest_logs();
+        logs.welcome(1, 1, 100);
+
+        // Out-of-order events are ignored.
+        assert_eq!(logs.record(1, QuestEvent::Equipped, 110), None);
+        assert_eq!(
+            logs.record(1, QuestEvent::TalkedTo { npc_id: 250 }, 110),
+            None
+        );
+        let talked = logs
+            .record(1, QuestEvent::TalkedTo { npc_id: 249 }, 120)
+            .unwrap();
+        assert_eq!(talked.status.step, 1);
+
+        assert_eq!(
+            logs.record(1, QuestEvent::Killed { monster_id: 4 }, 130),
+            None
+        );
+        let first_kill = logs
+            .record(1, QuestEvent::Killed { monster_id: 3 }, 130)
+            .unwrap();
+        assert_eq!((first_kill.status.step, first_kill.status.progress), (1, 1));
+        logs.record(1, QuestEvent::Killed { monster_id: 3 }, 140);
+
+        let done = logs.record(1, QuestEvent::Equipped, 150).unwrap();
+        assert_eq!(done.status.objective, None);
+        assert_eq!(done.status.step, done.status.steps);
+        assert_eq!(done.reward.map(|reward| reward.zen), Some(10_000));
+        assert_eq!(
+            logs.log(1).unwrap().tutorial.unwrap().completed_at_ms,
+            Some(150)
+        );
+
+        // Finished quests ignore further events.
+        assert_eq!(logs.record(1, QuestEvent::Equipped, 160), None);
+        assert_eq!(logs.record(2, QuestEvent::Equipped, 160), None);
+    }
+
+    #[test]
+    fn loaded_logs_resume_where_they_stopped() {
+        let logs = quest_logs();
+        logs.load([QuestLogRecord {
+            id: None,
+            character_id: 7,
+            kit_granted_at_ms: Some(50),
+            tutorial: Some(QuestProgress {
+                quest_id: 1,
+                step: 1,
+                kills: 1,
+                completed_at_ms: None,
+            }),
+        }]);
+        assert!(!logs.is_new(7));
+        assert_eq!(logs.status(7).unwrap().progress, 1);
+        let advance = logs
+            .record(7, QuestEvent::Killed { monster_id: 3 }, 60)
+            .unwrap();
+        assert_eq!(advance.status.objective, Some(QuestObjective::EquipItem));
+    }
+}
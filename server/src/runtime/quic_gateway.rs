@@ -1,17 +1,27 @@
 use std::io::Cursor;
 use std::net::SocketAddr;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{anyhow, bail, Context};
-use protocol::{preferred_channel, TransportKind, WireCodec, WirePacket};
+use protocol::{
+    preferred_channel, PacketPayload, SequencedReceiver, SequencedSender, ServerMessage,
+    TransportKind, WireCodec, WirePacket,
+};
 use quinn::{Connection, Endpoint, RecvStream, SendStream};
 use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
 
 use super::config::GatewayConfig;
+use super::session_links::{SessionCommand, SessionLink};
 use super::MuCoreRuntime;
 
+/// How long a kicked client gets to read the `Disconnect` message before the
+/// connection is closed, which discards anything still in flight.
+const KICK_GRACE: Duration = Duration::from_secs(1);
+/// How often a session's connection RTT is sampled for hit validation.
+const RTT_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
 #[derive(Debug, Clone)]
 pub struct QuicTlsPaths {
     pub cert: PathBuf,
@@ -102,9 +112,18 @@ async fn accept_loop(endpoint: Endpoint, runtime: Arc<MuCoreRuntime>) {
     }
 }
 
+/// Sequence numbers of the datagrams a connection sends, shared by everything
+/// that answers or pushes over it.
+type DatagramSequencer = Arc<Mutex<SequencedSender>>;
+
 async fn handle_connection(connection: Connection, runtime: Arc<MuCoreRuntime>) {
-    let stream_task = tokio::spawn(handle_bidi_streams(connection.clone(), runtime.clone()));
-    let datagram_task = tokio::spawn(handle_datagrams(connection.clone(), runtime));
+    let sequencer = DatagramSequencer::default();
+    let stream_task = tokio::spawn(handle_bidi_streams(
+        connection.clone(),
+        runtime.clone(),
+        sequencer.clone(),
+    ));
+    let datagram_task = tokio::spawn(handle_datagrams(connection.clone(), runtime, sequencer));
 
     let _ = tokio::join!(stream_task, datagram_task);
 
@@ -114,7 +133,11 @@ async fn handle_connection(connection: Connection, runtime: Arc<MuCoreRuntime>)
     );
 }
 
-async fn handle_bidi_streams(connection: Connection, runtime: Arc<MuCoreRuntime>) {
+async fn handle_bidi_streams(
+    connection: Connection,
+    runtime: Arc<MuCoreRuntime>,
+    sequencer: DatagramSequencer,
+) {
     let codec = WireCodec::default();
 
     loop {
@@ -132,10 +155,19 @@ async fn handle_bidi_streams(connection: Connection, runtime: Arc<MuCoreRuntime>
 
         let runtime_clone = runtime.clone();
         let codec_clone = codec.clone();
+        let connection_clone = connection.clone();
+        let sequencer_clone = sequencer.clone();
 
         tokio::spawn(async move {
-            if let Err(err) =
-                handle_single_bidi_stream(&runtime_clone, &codec_clone, &mut recv, &mut send).await
+            if let Err(err) = handle_single_bidi_stream(
+                &runtime_clone,
+                &connection_clone,
+                &sequencer_clone,
+                &codec_clone,
+                &mut recv,
+                &mut send,
+            )
+            .await
             {
                 log::debug!("QUIC stream handling error: {}", err);
             }
@@ -145,14 +177,18 @@ async fn handle_bidi_streams(connection: Connection, runtime: Arc<MuCoreRuntime>
 
 async fn handle_single_bidi_stream(
     runtime: &Arc<MuCoreRuntime>,
+    connection: &Connection,
+    sequencer: &DatagramSequencer,
     codec: &WireCodec,
     recv: &mut RecvStream,
     send: &mut SendStream,
 ) -> anyhow::Result<()> {
-    let max_read_size = codec
-        .limits()
+    let limits = codec.limits();
+    // Room for a fragmented message plus the frames around it.
+    let max_read_size = limits
         .max_stream_payload_size
         .saturating_mul(8)
+        .max(limits.max_message_size.saturating_mul(2))
         .max(1024);
 
     let bytes = recv
@@ -173,7 +209,17 @@ async fn handle_single_bidi_stream(
         .context("failed to process stream bytes")?;
 
     for packet in responses {
-        write_packet_to_stream(codec, send, &packet).await?;
+        if let PacketPayload::Server(ServerMessage::HelloAck { session_id, .. }) = &packet.payload {
+            let link = runtime.session_links().attach(*session_id);
+            tokio::spawn(forward_session_link(
+                connection.clone(),
+                runtime.clone(),
+                sequencer.clone(),
+                link,
+            ));
+        }
+        let codec = runtime.stream_codec(packet.session_id);
+        write_packet_to_stream(&codec, send, &packet).await?;
     }
 
     send.finish()
@@ -205,8 +251,13 @@ async fn write_packet_to_stream(
     Ok(())
 }
 
-async fn handle_datagrams(connection: Connection, runtime: Arc<MuCoreRuntime>) {
+async fn handle_datagrams(
+    connection: Connection,
+    runtime: Arc<MuCoreRuntime>,
+    sequencer: DatagramSequencer,
+) {
     let codec = WireCodec::default();
+    let mut received = SequencedReceiver::new();
 
     loop {
         let datagram = match connection.read_datagram().await {
@@ -222,7 +273,7 @@ async fn handle_datagrams(connection: Connection, runtime: Arc<MuCoreRuntime>) {
         };
 
         let response = match runtime
-            .handle_datagram_frame(datagram.as_ref(), now_ms())
+            .handle_datagram_frame(datagram.as_ref(), &mut received, now_ms())
             .await
         {
             Ok(response) => response,
@@ -233,15 +284,69 @@ async fn handle_datagrams(connection: Connection, runtime: Arc<MuCoreRuntime>) {
         };
 
         if let Some(packet) = response {
-            if let Err(err) = send_packet_over_connection(&connection, &codec, &packet).await {
+            if let Err(err) =
+                send_packet_over_connection(&connection, &sequencer, &codec, &packet).await
+            {
                 log::debug!("QUIC datagram response send failed: {}", err);
             }
         }
     }
 }
 
+/// Pushes what the runtime sends to an authenticated session over its
+/// connection until the connection ends, another connection takes the
+/// session over, or the session is kicked.
+async fn forward_session_link(
+    connection: Connection,
+    runtime: Arc<MuCoreRuntime>,
+    sequencer: DatagramSequencer,
+    mut link: SessionLink,
+) {
+    let codec = runtime.stream_codec(link.session_id);
+    let mut rtt_sample = tokio::time::interval(RTT_SAMPLE_INTERVAL);
+
+    loop {
+        let command = tokio::select! {
+            command = link.commands.recv() => command,
+            _ = rtt_sample.tick() => {
+                runtime.latency().record(link.session_id, connection.rtt());
+                continue;
+            }
+            _ = connection.closed() => None,
+        };
+
+        match command {
+            Some(SessionCommand::Send(packet)) => {
+                if let Err(err) =
+                    send_packet_over_connection(&connection, &sequencer, &codec, &packet).await
+                {
+                    log::debug!("QUIC push to session {} failed: {}", link.session_id, err);
+                }
+            }
+            Some(SessionCommand::Close { packet, reason }) => {
+                if let Err(err) =
+                    send_packet_over_connection(&connection, &sequencer, &codec, &packet).await
+                {
+                    log::debug!(
+                        "QUIC disconnect of session {} failed: {}",
+                        link.session_id,
+                        err
+                    );
+                }
+                let _ = tokio::time::timeout(KICK_GRACE, connection.closed()).await;
+                connection.close(reason.close_code().into(), b"session closed by server");
+                break;
+            }
+            None => break,
+        }
+    }
+
+    runtime.session_links().detach(&link);
+}
+
 async fn send_packet_over_connection(
     connection: &Connection,
+    sequencer: &Mutex<SequencedSender>,
     codec: &WireCodec,
     packet: &WirePacket,
 ) -> anyhow::Result<()> {
@@ -249,8 +354,15 @@ async fn send_packet_over_connection(
 
     match channel.transport() {
         TransportKind::Datagram => {
+            // Datagrams are numbered per route in the order they leave, so
+            // the client can drop those overtaken by a newer one.
+            let mut packet = packet.clone();
+            sequencer
+                .lock()
+                .map_err(|_| anyhow!("datagram sequencer poisoned"))?
+                .stamp(&mut packet);
             let frame = codec
-                .encode_datagram_frame(channel, packet)
+                .encode_datagram_frame(channel, &packet)
                 .context("failed to encode datagram frame")?;
 
             connection
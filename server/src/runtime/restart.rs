@@ -0,0 +1,193 @@
+//! Restarts of the whole server planned for a given time.
+//!
+//! Players are warned when the restart is planned and again 60, 30, 10 and 1
+//! minutes before it. Events that would still be running at that time do not
+//! start, and at T-0 [`RestartScheduler::triggered`] wakes the task that stops
+//! the HTTP server, so the usual graceful shutdown saves everything and closes
+//! the map servers.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use serde::Serialize;
+use tokio::sync::Notify;
+use utoipa::ToSchema;
+
+/// Minutes before the restart at which players are warned again.
+const NOTICE_MARKS_MINS: [u32; 4] = [60, 30, 10, 1];
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RestartError {
+    #[error("restart time {0} is not in the future")]
+    NotInFuture(u64),
+
+    #[error("a restart is already planned for {0}")]
+    AlreadyPlanned(u64),
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PlannedRestart {
+    pub restart_at_ms: u64,
+    pub reason: String,
+    pub planned_at_ms: u64,
+    /// Smallest mark announced so far; `u32::MAX` while only the notice sent
+    /// when it was planned went out.
+    #[serde(skip)]
+    last_notice_mark: Option<u32>,
+    #[serde(skip)]
+    triggered: bool,
+}
+
+impl PlannedRestart {
+    pub fn seconds_remaining(&self, now_ms: u64) -> u32 {
+        let remaining_ms = self.restart_at_ms.saturating_sub(now_ms);
+        u32::try_from(remaining_ms.div_ceil(1_000)).unwrap_or(u32::MAX)
+    }
+}
+
+/// The restart planned for this server, if any.
+#[derive(Clone, Default)]
+pub struct RestartScheduler {
+    planned: Arc<Mutex<Option<PlannedRestart>>>,
+    due: Arc<Notify>,
+}
+
+impl RestartScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn schedule(
+        &self,
+        restart_at_ms: u64,
+        reason: String,
+        now_ms: u64,
+    ) -> Result<PlannedRestart, RestartError> {
+        if restart_at_ms <= now_ms {
+            return Err(RestartError::NotInFuture(restart_at_ms));
+        }
+        let mut planned = self.lock();
+        if let Some(current) = planned.as_ref() {
+            return Err(RestartError::AlreadyPlanned(current.restart_at_ms));
+        }
+
+        let restart = PlannedRestart {
+            restart_at_ms,
+            reason,
+            planned_at_ms: now_ms,
+            last_notice_mark: None,
+            triggered: false,
+        };
+        *planned = Some(restart.clone());
+        Ok(restart)
+    }
+
+    /// Calls the restart off, unless it already started.
+    pub fn cancel(&self) -> Option<PlannedRestart> {
+        let mut planned = self.lock();
+        if planned.as_ref().is_some_and(|restart| restart.triggered) {
+            return None;
+        }
+        planned.take()
+    }
+
+    pub fn planned(&self) -> Option<PlannedRestart> {
+        self.lock().clone()
+    }
+
+    /// Whether something starting at `now_ms` and lasting `duration_ms` would
+    /// still be going when the server restarts.
+    pub fn blocks_start(&self, now_ms: u64, duration_ms: u64) -> bool {
+        self.lock()
+            .as_ref()
+            .is_some_and(|restart| now_ms.saturating_add(duration_ms) > restart.restart_at_ms)
+    }
+
+    /// The restart, when its countdown crossed a new mark since the last
+    /// call. The first call announces it whatever the time left.
+    pub fn due_notice(&self, now_ms: u64) -> Option<PlannedRestart> {
+        let mut planned = self.lock();
+        let restart = planned.as_mut().filter(|restart| !restart.triggered)?;
+
+        let remaining_ms = restart.restart_at_ms.saturating_sub(now_ms);
+        let mark = NOTICE_MARKS_MINS
+            .into_iter()
+            .filter(|mark| remaining_ms <= u64::from(*mark) * 60_000)
+            .min()
+            .unwrap_or(u32::MAX);
+        if restart.last_notice_mark.is_some_and(|last| mark >= last) {
+            return None;
+        }
+        restart.last_notice_mark = Some(mark);
+        Some(restart.clone())
+    }
+
+    /// Marks the restart as started once its time came; returns it exactly
+    /// once and wakes [`RestartScheduler::triggered`].
+    pub fn take_due(&self, now_ms: u64) -> Option<PlannedRestart> {
+        let mut planned = self.lock();
+        let restart = planned
+            .as_mut()
+            .filter(|restart| !restart.triggered && now_ms >= restart.restart_at_ms)?;
+        restart.triggered = true;
+        self.due.notify_one();
+        Some(restart.clone())
+    }
+
+    /// Resolves once the planned restart is due.
+    pub async fn triggered(&self) {
+        self.due.notified().await;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<PlannedRestart>> {
+        self.planned.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE_MS: u64 = 60_000;
+
+    #[test]
+    fn countdown_is_announced_at_each_mark_then_triggers_once() {
+        let scheduler = RestartScheduler::new();
+        assert_eq!(
+            scheduler.schedule(0, String::new(), 0).unwrap_err(),
+            RestartError::NotInFuture(0)
+        );
+        scheduler
+            .schedule(45 * MINUTE_MS, "patch".to_string(), 0)
+            .unwrap();
+        assert!(scheduler.schedule(MINUTE_MS, String::new(), 0).is_err());
+
+        assert!(scheduler.due_notice(0).is_some(), "planned notice");
+        assert!(scheduler.due_notice(10 * MINUTE_MS).is_none());
+        assert!(scheduler.due_notice(15 * MINUTE_MS).is_some(), "30 minutes");
+        assert!(scheduler.due_notice(20 * MINUTE_MS).is_none());
+        assert!(scheduler.due_notice(35 * MINUTE_MS).is_some(), "10 minutes");
+        assert!(scheduler.due_notice(44 * MINUTE_MS + 1).is_some());
+        assert!(scheduler.due_notice(44 * MINUTE_MS + 2).is_none());
+
+        assert!(scheduler.blocks_start(40 * MINUTE_MS, 10 * MINUTE_MS));
+        assert!(!scheduler.blocks_start(30 * MINUTE_MS, 10 * MINUTE_MS));
+
+        assert!(scheduler.take_due(45 * MINUTE_MS - 1).is_none());
+        assert!(scheduler.take_due(45 * MINUTE_MS).is_some());
+        assert!(scheduler.take_due(46 * MINUTE_MS).is_none());
+        assert!(scheduler.due_notice(46 * MINUTE_MS).is_none());
+        assert!(scheduler.cancel().is_none(), "too late to call it off");
+    }
+
+    #[test]
+    fn cancelled_restarts_stop_blocking_events() {
+        let scheduler = RestartScheduler::new();
+        scheduler
+            .schedule(10 * MINUTE_MS, String::new(), 0)
+            .unwrap();
+        assert!(scheduler.blocks_start(0, 20 * MINUTE_MS));
+        assert!(scheduler.cancel().is_some());
+        assert!(!scheduler.blocks_start(0, 20 * MINUTE_MS));
+        assert!(scheduler.planned().is_none());
+    }
+}
@@ -0,0 +1,201 @@
+//! Live gateway connections of authenticated sessions.
+//!
+//! The QUIC gateway links a connection to its session once the `Hello` is
+//! accepted and forwards what the runtime pushes through the link: packets
+//! such as a transfer to another shard, and the close of a kicked session,
+//! which carries a typed `DisconnectReason` both as a `Disconnect` message
+//! and as the QUIC close code.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use protocol::{DisconnectReason, RouteKey, ServerMessage, WirePacket};
+use tokio::sync::mpsc;
+
+use crate::auth_token::AuthTokenError;
+
+/// Commands a link can queue before the connection task falls behind.
+const LINK_CAPACITY: usize = 32;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SessionControlError {
+    #[error("session {0} is not authenticated")]
+    UnknownSession(u64),
+    #[error("session {0} has no character in a map")]
+    NoCharacter(u64),
+    #[error("no shard of world {world_id} can take map {map_id}")]
+    NoShard { world_id: u16, map_id: u16 },
+    #[error("failed to issue the transfer token: {0}")]
+    Token(#[from] AuthTokenError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionCommand {
+    Send(WirePacket),
+    /// Send the packet, then close the connection with the reason's code.
+    Close {
+        packet: WirePacket,
+        reason: DisconnectReason,
+    },
+}
+
+/// Receiving end of a session link, owned by the connection task.
+pub struct SessionLink {
+    pub session_id: u64,
+    link_id: u64,
+    pub commands: mpsc::Receiver<SessionCommand>,
+}
+
+#[derive(Clone, Default)]
+pub struct SessionLinks {
+    // key: session_id, value: (link_id, sender)
+    links: Arc<DashMap<u64, (u64, mpsc::Sender<SessionCommand>)>>,
+    next_link_id: Arc<AtomicU64>,
+}
+
+impl SessionLinks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Links a connection to `session_id`, replacing an older link of the
+    /// same session (a reconnect), whose receiver then ends.
+    pub fn attach(&self, session_id: u64) -> SessionLink {
+        let (sender, commands) = mpsc::channel(LINK_CAPACITY);
+        let link_id = self.next_link_id.fetch_add(1, Ordering::Relaxed);
+        self.links.insert(session_id, (link_id, sender));
+        SessionLink {
+            session_id,
+            link_id,
+            commands,
+        }
+    }
+
+    /// Drops the link when its connection ends, unless a newer connection
+    /// already took the session over.
+    pub fn detach(&self, link: &SessionLink) {
+        self.links
+            .remove_if(&link.session_id, |_, (link_id, _)| *link_id == link.link_id);
+    }
+
+    /// Queues a command for the session's connection. Returns false when the
+    /// session has no live connection or its queue is full.
+    pub fn send(&self, session_id: u64, command: SessionCommand) -> bool {
+        let Some(sender) = self.links.get(&session_id).map(|entry| entry.1.clone()) else {
+            return false;
+        };
+        match sender.try_send(command) {
+            Ok(()) => true,
+            Err(err) => {
+                log::warn!("Session {} link rejected a command: {}", session_id, err);
+                false
+            }
+        }
+    }
+}
+
+/// Pushes server messages to sessions through their links, for the core and
+/// the map servers alike. Sees the core's session routes and characters in
+/// play, so a packet carries the route the session is on.
+#[derive(Clone, Default)]
+pub struct SessionPush {
+    links: SessionLinks,
+    // key: session_id, value: (character_id, route)
+    routes: Arc<DashMap<u64, (u64, RouteKey)>>,
+    // key: character_id, value: session_id
+    characters: Arc<DashMap<u64, u64>>,
+}
+
+impl SessionPush {
+    pub fn new(
+        links: SessionLinks,
+        routes: Arc<DashMap<u64, (u64, RouteKey)>>,
+        characters: Arc<DashMap<u64, u64>>,
+    ) -> Self {
+        Self {
+            links,
+            routes,
+            characters,
+        }
+    }
+
+    pub fn links(&self) -> &SessionLinks {
+        &self.links
+    }
+
+    /// Wraps `message` for the session, on its route or the lobby's when it
+    /// has none.
+    pub fn packet(
+        &self,
+        session_id: u64,
+        message: ServerMessage,
+        server_time_ms: u64,
+    ) -> WirePacket {
+        let route = self
+            .routes
+            .get(&session_id)
+            .map_or(RouteKey::LOBBY, |entry| entry.value().1);
+        WirePacket::server(session_id, route, 0, None, server_time_ms, message)
+    }
+
+    /// Sends `message` to the session over its live connection. Returns
+    /// false when the session has none.
+    pub fn push(&self, session_id: u64, message: ServerMessage, server_time_ms: u64) -> bool {
+        let packet = self.packet(session_id, message, server_time_ms);
+        self.links.send(session_id, SessionCommand::Send(packet))
+    }
+
+    /// Sends `message` to the session playing `character_id`, if any.
+    pub fn push_to_character(
+        &self,
+        character_id: u64,
+        message: ServerMessage,
+        server_time_ms: u64,
+    ) -> bool {
+        let Some(session_id) = self.characters.get(&character_id).map(|entry| *entry) else {
+            return false;
+        };
+        self.push(session_id, message, server_time_ms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::{RouteKey, ServerMessage};
+
+    fn pong(session_id: u64) -> SessionCommand {
+        SessionCommand::Send(WirePacket::server(
+            session_id,
+            RouteKey::LOBBY,
+            0,
+            None,
+            0,
+            ServerMessage::Pong {
+                client_sent_ms: 0,
+                server_received_ms: 0,
+                server_sent_ms: 0,
+            },
+        ))
+    }
+
+    #[tokio::test]
+    async fn a_reconnect_takes_the_session_over() {
+        let links = SessionLinks::new();
+        assert!(!links.send(7, pong(7)));
+
+        let mut first = links.attach(7);
+        assert!(links.send(7, pong(7)));
+        assert_eq!(first.commands.recv().await, Some(pong(7)));
+
+        let mut second = links.attach(7);
+        // The old connection's link ends and its late detach is ignored.
+        assert_eq!(first.commands.recv().await, None);
+        links.detach(&first);
+        assert!(links.send(7, pong(7)));
+        assert_eq!(second.commands.recv().await, Some(pong(7)));
+        links.detach(&second);
+        assert!(!links.send(7, pong(7)));
+    }
+}
@@ -0,0 +1,667 @@
+//! Synthetic monster load for capacity planning.
+//!
+//! An operator can fill a map with fake monsters running a small aggro, chase
+//! and wander AI for a fixed time. The map server records its player and
+//! monster tick times before and during the run, so the cost of a crowded
+//! event map can be measured without real players.
+//!
+//! Monsters spawn in groups that fight together: when one is hit, the members
+//! of its group within the `link_radius` of its definition turn on the
+//! attacker. A monster pulled past its `leash_radius` walks back to its spawn
+//! and heals to full.
+
+use std::time::Duration;
+
+use common::collision::{CollisionGrid, TERRAIN_SIZE};
+use protocol::{MonsterAffix, MonsterRank, RouteKey};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::config::{MonsterConfig, DEFAULT_LEASH_RADIUS, DEFAULT_LINK_RADIUS};
+use super::elites::{roll_affixes, EliteSpawn, MonsterStats};
+use super::lag_compensation::PositionHistory;
+
+pub const MAX_STRESS_MONSTERS: u32 = 20_000;
+pub const MAX_STRESS_DURATION: Duration = Duration::from_secs(600);
+
+/// Players closer than this (in tiles) are chased.
+const AGGRO_RANGE: u16 = 6;
+/// How far from its spawn tile a monster wanders.
+const WANDER_RADIUS: u16 = 5;
+/// Monster ticks between two wander decisions.
+const WANDER_INTERVAL_TICKS: u8 = 25;
+/// Monsters spawned together around the group's first tile.
+const SPAWN_GROUP_SIZE: usize = 4;
+/// How far from the group's first tile the other members spawn.
+const SPAWN_GROUP_SPREAD: u16 = 2;
+/// Random tiles tried per requested monster before giving up on spawning it.
+const SPAWN_ATTEMPTS_PER_MONSTER: u32 = 8;
+/// Entity id of the first synthetic monster, well above character ids.
+const SYNTHETIC_ENTITY_ID_BASE: u32 = 0x4000_0000;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum StressError {
+    #[error("monster count must be between 1 and {MAX_STRESS_MONSTERS}")]
+    InvalidMonsterCount,
+    #[error(
+        "duration must be between 1 and {max} seconds",
+        max = MAX_STRESS_DURATION.as_secs()
+    )]
+    InvalidDuration,
+    #[error("map {map_id} of world {world_id} is not running")]
+    UnknownMap { world_id: u16, map_id: u16 },
+    #[error("a stress run is already active on this map")]
+    AlreadyRunning,
+    #[error("the map has no walkable tile to spawn monsters on")]
+    NoSpawnTiles,
+    #[error("map server stopped")]
+    MapStopped,
+}
+
+pub fn validate_stress(monsters: u32, duration: Duration) -> Result<(), StressError> {
+    if monsters == 0 || monsters > MAX_STRESS_MONSTERS {
+        return Err(StressError::InvalidMonsterCount);
+    }
+    if duration < Duration::from_secs(1) || duration > MAX_STRESS_DURATION {
+        return Err(StressError::InvalidDuration);
+    }
+    Ok(())
+}
+
+/// Tick-time distribution in microseconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct TickPercentiles {
+    pub samples: u32,
+    pub p50_us: u64,
+    pub p95_us: u64,
+    pub p99_us: u64,
+    pub max_us: u64,
+}
+
+impl TickPercentiles {
+    pub fn from_samples(samples: &[u64]) -> Self {
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let at = |quantile: f64| {
+            let index = (sorted.len().saturating_sub(1) as f64 * quantile) as usize;
+            sorted.get(index).copied().unwrap_or(0)
+        };
+
+        Self {
+            samples: sorted.len() as u32,
+            p50_us: at(0.50),
+            p95_us: at(0.95),
+            p99_us: at(0.99),
+            max_us: sorted.last().copied().unwrap_or(0),
+        }
+    }
+}
+
+/// Player and monster tick times over one period of a stress run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct TickLoad {
+    pub player_tick: TickPercentiles,
+    pub monster_tick: TickPercentiles,
+    /// Highest monster AI degradation level seen in the period.
+    pub peak_degradation_level: u8,
+}
+
+/// Latest stress run of a map.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct StressReport {
+    pub route: RouteKey,
+    /// Monsters actually spawned; blocked tiles can make it lower than asked.
+    pub monsters: u32,
+    pub duration_secs: u64,
+    pub started_at_ms: u64,
+    /// `None` while the run is active.
+    pub finished_at_ms: Option<u64>,
+    /// Recent ticks before the monsters spawned.
+    pub before: TickLoad,
+    /// Ticks while the monsters were alive, once the run finished.
+    pub during: Option<TickLoad>,
+    /// Monsters that rolled affixes.
+    pub elites: Vec<EliteSpawn>,
+}
+
+/// What a player's hit did to a synthetic monster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonsterHit {
+    Wounded { remaining_hp: u32 },
+    Killed(MonsterDeath),
+}
+
+/// A synthetic monster that fell, and the tile it fell on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonsterDeath {
+    /// `None` when the map has no monster definitions.
+    pub monster_id: Option<u16>,
+    pub rank: Option<MonsterRank>,
+    pub x: u16,
+    pub y: u16,
+}
+
+#[derive(Debug, Clone)]
+struct SyntheticMonster {
+    /// Definition the monster was spawned from.
+    monster_id: Option<u16>,
+    x: u16,
+    y: u16,
+    home: (u16, u16),
+    goal: (u16, u16),
+    wander_in: u8,
+    group: u32,
+    link_radius: u16,
+    leash_radius: u16,
+    hp: u32,
+    /// Character the monster fights.
+    target: Option<u64>,
+    /// Leashed: walking back to its spawn, ignoring players until there.
+    returning: bool,
+    affixes: Vec<MonsterAffix>,
+    stats: MonsterStats,
+    history: PositionHistory,
+}
+
+impl SyntheticMonster {
+    /// Steps towards `to`, stopping next to it when `melee`. Returns false
+    /// when a blocked tile is in the way.
+    fn walk(&mut self, to: (u16, u16), melee: bool, collision: &CollisionGrid) -> bool {
+        for _ in 0..self.stats.steps_per_tick {
+            let position = (self.x, self.y);
+            if position == to || (melee && chebyshev(position, to) <= 1) {
+                break;
+            }
+            let next = step_towards(position, to);
+            if !collision.line_is_clear(position, next) {
+                return false;
+            }
+            (self.x, self.y) = next;
+        }
+        true
+    }
+
+    fn reset(&mut self) {
+        (self.x, self.y) = self.home;
+        self.hp = self.stats.max_hp;
+        self.target = None;
+        self.returning = false;
+    }
+}
+
+/// Fake monsters driven by a cheap approximation of the real AI: chase the
+/// attacker or the nearest player in range, otherwise wander around the spawn
+/// tile.
+#[derive(Debug, Clone)]
+pub struct SyntheticMonsters {
+    monsters: Vec<SyntheticMonster>,
+    rng: u64,
+}
+
+impl SyntheticMonsters {
+    /// Spawns up to `count` monsters on walkable tiles, in groups around
+    /// random tiles. Groups take the behavior of `definitions` in turn.
+    pub fn spawn(
+        count: u32,
+        collision: &CollisionGrid,
+        definitions: &[MonsterConfig],
+        seed: u64,
+    ) -> Result<Self, StressError> {
+        let mut pack = Self {
+            monsters: Vec::with_capacity(count as usize),
+            rng: seed | 1,
+        };
+
+        let mut attempts = count.saturating_mul(SPAWN_ATTEMPTS_PER_MONSTER);
+        while pack.monsters.len() < count as usize && attempts > 0 {
+            attempts -= 1;
+            let spawned = pack.monsters.len();
+            let group = (spawned / SPAWN_GROUP_SIZE) as u32;
+            let (x, y) = match spawned % SPAWN_GROUP_SIZE {
+                0 => (
+                    (pack.next_random() % u64::from(TERRAIN_SIZE)) as u16,
+                    (pack.next_random() % u64::from(TERRAIN_SIZE)) as u16,
+                ),
+                member => {
+                    let first = pack.monsters[spawned - member].home;
+                    pack.tile_near(first, SPAWN_GROUP_SPREAD)
+                }
+            };
+            if collision.is_blocked(x, y) {
+                continue;
+            }
+            let (monster_id, link_radius, leash_radius) = match definitions {
+                [] => (None, DEFAULT_LINK_RADIUS, DEFAULT_LEASH_RADIUS),
+                _ => {
+                    let definition = &definitions[group as usize % definitions.len()];
+                    (
+                        Some(definition.id),
+                        definition.link_radius,
+                        definition.leash_radius,
+                    )
+                }
+            };
+            let wander_in = (pack.next_random() % u64::from(WANDER_INTERVAL_TICKS)) as u8;
+            let affixes = roll_affixes(|| pack.next_random());
+            let stats = MonsterStats::BASE.with_affixes(&affixes);
+            pack.monsters.push(SyntheticMonster {
+                monster_id,
+                x,
+                y,
+                home: (x, y),
+                goal: (x, y),
+                wander_in,
+                group,
+                link_radius,
+                leash_radius,
+                hp: stats.max_hp,
+                target: None,
+                returning: false,
+                stats,
+                affixes,
+                history: PositionHistory::new(0, (x, y)),
+            });
+        }
+
+        if pack.monsters.is_empty() {
+            return Err(StressError::NoSpawnTiles);
+        }
+        Ok(pack)
+    }
+
+    pub fn count(&self) -> u32 {
+        self.monsters.len() as u32
+    }
+
+    /// Monsters that rolled affixes.
+    pub fn elites(&self) -> Vec<EliteSpawn> {
+        self.monsters
+            .iter()
+            .enumerate()
+            .filter_map(|(index, monster)| {
+                Some(EliteSpawn {
+                    entity_id: SYNTHETIC_ENTITY_ID_BASE + index as u32,
+                    rank: MonsterRank::of(&monster.affixes)?,
+                    affixes: monster.affixes.clone(),
+                    stats: monster.stats,
+                })
+            })
+            .collect()
+    }
+
+    /// Applies a player's hit. The monster and the members of its spawn group
+    /// within its link radius turn on the attacker; a killed monster respawns
+    /// on its spawn tile. Returns `None` when `entity_id` is not one of these
+    /// monsters.
+    pub fn hit(&mut self, entity_id: u32, attacker_id: u64, damage: u32) -> Option<MonsterHit> {
+        let index = entity_id.checked_sub(SYNTHETIC_ENTITY_ID_BASE)? as usize;
+        let monster = self.monsters.get_mut(index)?;
+        if monster.returning {
+            // Evades while leashed, so it cannot be pulled and beaten at the edge.
+            return Some(MonsterHit::Wounded {
+                remaining_hp: monster.hp,
+            });
+        }
+        monster.hp = monster.hp.saturating_sub(damage);
+        if monster.hp == 0 {
+            let death = MonsterDeath {
+                monster_id: monster.monster_id,
+                rank: MonsterRank::of(&monster.affixes),
+                x: monster.x,
+                y: monster.y,
+            };
+            monster.reset();
+            return Some(MonsterHit::Killed(death));
+        }
+        monster.target = Some(attacker_id);
+
+        let remaining_hp = monster.hp;
+        let (group, position, link_radius) =
+            (monster.group, (monster.x, monster.y), monster.link_radius);
+        for ally in &mut self.monsters {
+            if ally.group == group
+                && ally.target.is_none()
+                && !ally.returning
+                && chebyshev((ally.x, ally.y), position) <= link_radius
+            {
+                ally.target = Some(attacker_id);
+            }
+        }
+        Some(MonsterHit::Wounded { remaining_hp })
+    }
+
+    /// Recent positions of a monster, for checking hits against it.
+    pub fn history(&self, entity_id: u32) -> Option<&PositionHistory> {
+        let index = entity_id.checked_sub(SYNTHETIC_ENTITY_ID_BASE)? as usize;
+        self.monsters.get(index).map(|monster| &monster.history)
+    }
+
+    /// One AI step for every monster.
+    pub fn tick(&mut self, players: &[(u64, (u16, u16))], collision: &CollisionGrid, now_ms: u64) {
+        self.step(players, collision);
+        for monster in &mut self.monsters {
+            monster.history.record(now_ms, (monster.x, monster.y));
+        }
+    }
+
+    fn step(&mut self, players: &[(u64, (u16, u16))], collision: &CollisionGrid) {
+        for index in 0..self.monsters.len() {
+            let monster = &mut self.monsters[index];
+            let position = (monster.x, monster.y);
+            if monster.returning {
+                if position == monster.home {
+                    monster.returning = false;
+                    monster.hp = monster.stats.max_hp;
+                } else {
+                    let home = monster.home;
+                    if !monster.walk(home, false, collision) {
+                        // No way back: reset on the spawn tile.
+                        monster.reset();
+                    }
+                }
+                continue;
+            }
+
+            // The current target is chased while it stays on the map; otherwise
+            // the nearest player in range is picked up.
+            let chased = monster
+                .target
+                .and_then(|target| players.iter().find(|(id, _)| *id == target))
+                .or_else(|| {
+                    players
+                        .iter()
+                        .filter(|(_, player)| chebyshev(position, *player) <= AGGRO_RANGE)
+                        .min_by_key(|(_, player)| chebyshev(position, *player))
+                })
+                .copied();
+            monster.target = chased.map(|(id, _)| id);
+
+            if let Some((_, player)) = chased {
+                if chebyshev(position, monster.home) > monster.leash_radius {
+                    monster.target = None;
+                    monster.returning = true;
+                } else if !monster.walk(player, true, collision) {
+                    monster.wander_in = 0;
+                }
+                continue;
+            }
+
+            if monster.wander_in == 0 || monster.goal == position {
+                let home = monster.home;
+                let goal = self.tile_near(home, WANDER_RADIUS);
+                let monster = &mut self.monsters[index];
+                monster.goal = goal;
+                monster.wander_in = WANDER_INTERVAL_TICKS;
+            }
+            let monster = &mut self.monsters[index];
+            monster.wander_in = monster.wander_in.saturating_sub(1);
+            let goal = monster.goal;
+            if !monster.walk(goal, false, collision) {
+                // Blocked: pick another wander goal on the next tick.
+                monster.wander_in = 0;
+            }
+        }
+    }
+
+    /// Random tile at most `radius` tiles from `center`.
+    fn tile_near(&mut self, center: (u16, u16), radius: u16) -> (u16, u16) {
+        let span = u64::from(radius) * 2 + 1;
+        let offset = |value: u64| value as i32 - i32::from(radius);
+        let dx = offset(self.next_random() % span);
+        let dy = offset(self.next_random() % span);
+        let clamp = |base: u16, delta: i32| {
+            (i32::from(base) + delta).clamp(0, i32::from(TERRAIN_SIZE) - 1) as u16
+        };
+        (clamp(center.0, dx), clamp(center.1, dy))
+    }
+
+    /// xorshift64*: deterministic per seed and cheap enough for thousands of
+    /// monsters per tick.
+    fn next_random(&mut self) -> u64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        self.rng.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+fn chebyshev(a: (u16, u16), b: (u16, u16)) -> u16 {
+    a.0.abs_diff(b.0).max(a.1.abs_diff(b.1))
+}
+
+fn step_towards(from: (u16, u16), to: (u16, u16)) -> (u16, u16) {
+    let step = |from: u16, to: u16| match from.cmp(&to) {
+        std::cmp::Ordering::Less => from + 1,
+        std::cmp::Ordering::Greater => from - 1,
+        std::cmp::Ordering::Equal => from,
+    };
+    (step(from.0, to.0), step(from.1, to.1))
+}
+
+/// Seed for a route's monsters, so repeated runs on a map spawn the same pack.
+pub fn route_seed(route: RouteKey) -> u64 {
+    u64::from(route.world_id) << 48
+        | u64::from(route.entry_id) << 32
+        | u64::from(route.map_id) << 16
+        | u64::from(route.instance_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::collision::{TerrainHeightData, DEFAULT_MAX_SLOPE};
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let samples: Vec<u64> = (1..=100).rev().collect();
+        let load = TickPercentiles::from_samples(&samples);
+        assert_eq!(load.samples, 100);
+        assert_eq!((load.p50_us, load.p95_us, load.p99_us), (50, 95, 99));
+        assert_eq!(load.max_us, 100);
+        assert_eq!(
+            TickPercentiles::from_samples(&[]),
+            TickPercentiles::default()
+        );
+    }
+
+    #[test]
+    fn requests_are_bounded() {
+        assert_eq!(validate_stress(500, Duration::from_secs(30)), Ok(()));
+        assert_eq!(
+            validate_stress(0, Duration::from_secs(30)),
+            Err(StressError::InvalidMonsterCount)
+        );
+        assert_eq!(
+            validate_stress(MAX_STRESS_MONSTERS + 1, Duration::from_secs(30)),
+            Err(StressError::InvalidMonsterCount)
+        );
+        assert_eq!(
+            validate_stress(500, MAX_STRESS_DURATION + Duration::from_secs(1)),
+            Err(StressError::InvalidDuration)
+        );
+    }
+
+    #[test]
+    fn monsters_chase_players_in_range_and_never_enter_blocked_tiles() {
+        let mut collision = CollisionGrid::open();
+        for y in 0..TERRAIN_SIZE {
+            for x in 0..TERRAIN_SIZE {
+                if x % 7 == 0 {
+                    collision.block(x, y);
+                }
+            }
+        }
+
+        let mut pack = SyntheticMonsters::spawn(300, &collision, &[], 42).unwrap();
+        assert_eq!(pack.count(), 300);
+
+        let player = (pack.monsters[0].x + 3, pack.monsters[0].y);
+        let open_player = if collision.is_blocked(player.0, player.1) {
+            (player.0 - 1, player.1)
+        } else {
+            player
+        };
+        for _ in 0..100 {
+            pack.tick(&[(1, open_player)], &collision, 0);
+            assert!(pack
+                .monsters
+                .iter()
+                .all(|monster| !collision.is_blocked(monster.x, monster.y)));
+        }
+
+        // Monsters wander within reach of their spawn tile.
+        assert!(pack
+            .monsters
+            .iter()
+            .filter(|monster| chebyshev((monster.x, monster.y), open_player) > AGGRO_RANGE)
+            .all(|monster| chebyshev((monster.x, monster.y), monster.home) <= WANDER_RADIUS));
+
+        let mut walled = CollisionGrid::open();
+        for y in 0..TERRAIN_SIZE {
+            for x in 0..TERRAIN_SIZE {
+                walled.block(x, y);
+            }
+        }
+        assert_eq!(
+            SyntheticMonsters::spawn(10, &walled, &[], 1).unwrap_err(),
+            StressError::NoSpawnTiles
+        );
+    }
+
+    #[test]
+    fn monsters_do_not_climb_cliffs() {
+        // A plateau over the east half of the map, far too steep to walk up.
+        let terrain = TerrainHeightData {
+            width: u32::from(TERRAIN_SIZE),
+            height: u32::from(TERRAIN_SIZE),
+            heights: (0..TERRAIN_SIZE)
+                .map(|_| {
+                    (0..TERRAIN_SIZE)
+                        .map(|x| if x < 128 { 0.0 } else { 1_000.0 })
+                        .collect()
+                })
+                .collect(),
+        };
+        let collision = CollisionGrid::open().with_heights(&terrain, DEFAULT_MAX_SLOPE);
+        let mut pack = SyntheticMonsters::spawn(300, &collision, &[], 9).unwrap();
+        let sides: Vec<bool> = pack
+            .monsters
+            .iter()
+            .map(|monster| monster.x < 128)
+            .collect();
+
+        for _ in 0..100 {
+            pack.tick(&[(1, (127, 128)), (2, (128, 128))], &collision, 0);
+        }
+        assert!(pack
+            .monsters
+            .iter()
+            .zip(&sides)
+            .all(|(monster, low)| (monster.x < 128) == *low));
+    }
+
+    #[test]
+    fn elites_report_their_rolled_affixes_and_stats() {
+        let pack = SyntheticMonsters::spawn(2_000, &CollisionGrid::open(), &[], 7).unwrap();
+        let elites = pack.elites();
+        assert!(!elites.is_empty());
+        for elite in elites {
+            let monster = &pack.monsters[(elite.entity_id - SYNTHETIC_ENTITY_ID_BASE) as usize];
+            assert_eq!(elite.affixes, monster.affixes);
+            assert_eq!(Some(elite.rank), MonsterRank::of(&elite.affixes));
+            assert_eq!(elite.stats, MonsterStats::BASE.with_affixes(&elite.affixes));
+        }
+    }
+
+    fn definition(link_radius: u16, leash_radius: u16) -> MonsterConfig {
+        MonsterConfig {
+            id: 0,
+            name: "Bull Fighter".to_string(),
+            link_radius,
+            leash_radius,
+            drops: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn spawn_groups_assist_within_their_link_radius() {
+        let open = CollisionGrid::open();
+        let linked = definition(SPAWN_GROUP_SPREAD * 2, DEFAULT_LEASH_RADIUS);
+        let mut pack = SyntheticMonsters::spawn(8, &open, &[linked, definition(0, 15)], 3).unwrap();
+        for monster in &pack.monsters[1..SPAWN_GROUP_SIZE] {
+            assert_eq!(monster.group, 0);
+            assert!(chebyshev(monster.home, pack.monsters[0].home) <= SPAWN_GROUP_SPREAD);
+        }
+
+        let max_hp = pack.monsters[0].stats.max_hp;
+        assert_eq!(
+            pack.hit(SYNTHETIC_ENTITY_ID_BASE, 7, 30),
+            Some(MonsterHit::Wounded {
+                remaining_hp: max_hp - 30
+            })
+        );
+        assert!(pack.monsters[..SPAWN_GROUP_SIZE]
+            .iter()
+            .all(|monster| monster.target == Some(7)));
+        assert!(pack.monsters[SPAWN_GROUP_SIZE..]
+            .iter()
+            .all(|monster| monster.target.is_none()));
+
+        // A group without link radius fights alone.
+        let loner = SYNTHETIC_ENTITY_ID_BASE + SPAWN_GROUP_SIZE as u32;
+        pack.hit(loner, 8, 1);
+        let second_group = &pack.monsters[SPAWN_GROUP_SIZE..];
+        assert_eq!(second_group[0].target, Some(8));
+        assert!(second_group[1..]
+            .iter()
+            .all(|monster| monster.target.is_none()));
+        assert_eq!(pack.hit(SYNTHETIC_ENTITY_ID_BASE + 8, 7, 1), None);
+    }
+
+    #[test]
+    fn leashed_monsters_walk_home_and_heal() {
+        let open = CollisionGrid::open();
+        let mut pack = SyntheticMonsters::spawn(1, &open, &[definition(0, 5)], 9).unwrap();
+        let home = pack.monsters[0].home;
+        let max_hp = pack.monsters[0].stats.max_hp;
+        pack.hit(SYNTHETIC_ENTITY_ID_BASE, 7, 10);
+
+        // The attacker runs away, out of aggro range but still the target.
+        let far = |home: u16| if home > 100 { home - 40 } else { home + 40 };
+        let kite = (far(home.0), home.1);
+        let mut leashed = false;
+        for _ in 0..60 {
+            pack.tick(&[(7, kite)], &open, 0);
+            let monster = &pack.monsters[0];
+            assert!(chebyshev((monster.x, monster.y), home) <= 6);
+            leashed |= monster.returning;
+            if leashed && !monster.returning {
+                break;
+            }
+        }
+        let monster = &pack.monsters[0];
+        assert!(leashed);
+        assert_eq!(((monster.x, monster.y), monster.hp), (home, max_hp));
+        assert_eq!(monster.target, None);
+        // Hits are evaded on the way back, and a kill respawns it at home.
+        pack.monsters[0].returning = true;
+        assert_eq!(
+            pack.hit(SYNTHETIC_ENTITY_ID_BASE, 7, 10),
+            Some(MonsterHit::Wounded {
+                remaining_hp: max_hp
+            })
+        );
+        pack.monsters[0].returning = false;
+        pack.monsters[0].x += 1;
+        assert_eq!(
+            pack.hit(SYNTHETIC_ENTITY_ID_BASE, 7, max_hp),
+            Some(MonsterHit::Killed(MonsterDeath {
+                monster_id: Some(0),
+                rank: MonsterRank::of(&pack.monsters[0].affixes),
+                x: home.0 + 1,
+                y: home.1,
+            }))
+        );
+        assert_eq!(pack.monsters[0].hp, max_hp);
+    }
+}
@@ -0,0 +1,307 @@
+//! Trades between two characters.
+//!
+//! A request opens a window once the other character accepts it. Each side
+//! offers items it holds and zen; any change to an offer clears both locks,
+//! and the offers are swapped once both sides locked and confirmed. The
+//! runtime moves the items of both offers with one
+//! [`super::item_ledger::ItemLedger::apply`], so a refused or interrupted
+//! swap leaves every item where it was.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex, MutexGuard};
+
+use protocol::{ItemInstance, TradeFailure, TradeItem, TradeOffer, MAX_ZEN};
+
+#[derive(Debug, Clone)]
+struct TradeSide {
+    partner: u64,
+    offer: TradeOffer,
+    confirmed: bool,
+}
+
+#[derive(Debug, Default)]
+struct TradeBook {
+    // key: character asked; value: character asking
+    requests: HashMap<u64, u64>,
+    // key: character_id
+    open: HashMap<u64, TradeSide>,
+}
+
+impl TradeBook {
+    fn is_busy(&self, character_id: u64) -> bool {
+        self.open.contains_key(&character_id)
+            || self.requests.contains_key(&character_id)
+            || self.requests.values().any(|asking| *asking == character_id)
+    }
+
+    fn unlock(&mut self, character_id: u64) {
+        if let Some(side) = self.open.get_mut(&character_id) {
+            side.offer.locked = false;
+            side.confirmed = false;
+        }
+    }
+}
+
+/// Offers of a trade both sides confirmed; the window is closed and the
+/// runtime swaps them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfirmedTrade {
+    pub first: u64,
+    pub first_offer: TradeOffer,
+    pub second: u64,
+    pub second_offer: TradeOffer,
+}
+
+/// Open trade windows and pending requests, shared by all sessions.
+#[derive(Clone, Default)]
+pub struct Trades {
+    book: Arc<StdMutex<TradeBook>>,
+}
+
+impl Trades {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks `target` to trade with `character_id`; refused while either one
+    /// has a window or request open.
+    pub fn request(&self, character_id: u64, target: u64) -> Result<(), TradeFailure> {
+        if character_id == target {
+            return Err(TradeFailure::NotAllowed);
+        }
+        let mut book = self.book();
+        if book.is_busy(character_id) || book.is_busy(target) {
+            return Err(TradeFailure::Busy);
+        }
+        book.requests.insert(target, character_id);
+        Ok(())
+    }
+
+    /// Answers the request `character_id` got and returns the character that
+    /// asked. Accepting opens an empty window on both sides.
+    pub fn respond(&self, character_id: u64, accept: bool) -> Result<u64, TradeFailure> {
+        let mut book = self.book();
+        let asking = book
+            .requests
+            .remove(&character_id)
+            .ok_or(TradeFailure::NoTrade)?;
+        if accept {
+            for (side, partner) in [(character_id, asking), (asking, character_id)] {
+                book.open.insert(
+                    side,
+                    TradeSide {
+                        partner,
+                        offer: TradeOffer::default(),
+                        confirmed: false,
+                    },
+                );
+            }
+        }
+        Ok(asking)
+    }
+
+    /// Partner, own offer and partner's offer of the window `character_id`
+    /// has open.
+    pub fn offers(&self, character_id: u64) -> Option<(u64, TradeOffer, TradeOffer)> {
+        let book = self.book();
+        let side = book.open.get(&character_id)?;
+        let partner = book.open.get(&side.partner)?;
+        Some((side.partner, side.offer.clone(), partner.offer.clone()))
+    }
+
+    /// Puts `item` at `cell` of the offer. The caller checks the character
+    /// holds it.
+    pub fn add_item(
+        &self,
+        character_id: u64,
+        cell: u8,
+        item: ItemInstance,
+    ) -> Result<(), TradeFailure> {
+        self.change(character_id, |offer| {
+            let mut changed = offer.clone();
+            changed.items.push(TradeItem { cell, item });
+            changed.validate()?;
+            *offer = changed;
+            Ok(())
+        })
+    }
+
+    pub fn remove_item(&self, character_id: u64, serial: u64) -> Result<(), TradeFailure> {
+        self.change(character_id, |offer| {
+            let index = offer
+                .items
+                .iter()
+                .position(|placed| placed.item.serial == serial)
+                .ok_or(TradeFailure::NotHeld)?;
+            offer.items.remove(index);
+            Ok(())
+        })
+    }
+
+    /// Replaces the zen offered. The caller checks the wallet covers it.
+    pub fn set_zen(&self, character_id: u64, zen: u64) -> Result<(), TradeFailure> {
+        if zen > MAX_ZEN {
+            return Err(TradeFailure::ZenLimit);
+        }
+        self.change(character_id, |offer| {
+            offer.zen = zen;
+            Ok(())
+        })
+    }
+
+    /// Presses or releases OK; releasing it withdraws both confirmations.
+    pub fn lock(&self, character_id: u64, locked: bool) -> Result<(), TradeFailure> {
+        let mut book = self.book();
+        let side = book
+            .open
+            .get_mut(&character_id)
+            .ok_or(TradeFailure::NoTrade)?;
+        side.offer.locked = locked;
+        if !locked {
+            side.confirmed = false;
+            let partner = side.partner;
+            if let Some(partner) = book.open.get_mut(&partner) {
+                partner.confirmed = false;
+            }
+        }
+        Ok(())
+    }
+
+    /// Confirms the window once both offers are locked. Returns both offers
+    /// when the partner confirmed too, closing the window.
+    pub fn confirm(&self, character_id: u64) -> Result<Option<ConfirmedTrade>, TradeFailure> {
+        let mut book = self.book();
+        let side = book.open.get(&character_id).ok_or(TradeFailure::NoTrade)?;
+        let partner = side.partner;
+        let partner_side = book.open.get(&partner).ok_or(TradeFailure::NoTrade)?;
+        if !side.offer.locked || !partner_side.offer.locked {
+            return Err(TradeFailure::NotLocked);
+        }
+        if !partner_side.confirmed {
+            if let Some(side) = book.open.get_mut(&character_id) {
+                side.confirmed = true;
+            }
+            return Ok(None);
+        }
+
+        let (Some(side), Some(partner_side)) =
+            (book.open.remove(&character_id), book.open.remove(&partner))
+        else {
+            return Err(TradeFailure::NoTrade);
+        };
+        Ok(Some(ConfirmedTrade {
+            first: character_id,
+            first_offer: side.offer,
+            second: partner,
+            second_offer: partner_side.offer,
+        }))
+    }
+
+    /// Closes the window or withdraws the request `character_id` is part of
+    /// and returns the other character, if there was one.
+    pub fn cancel(&self, character_id: u64) -> Option<u64> {
+        let mut book = self.book();
+        if let Some(side) = book.open.remove(&character_id) {
+            book.open.remove(&side.partner);
+            return Some(side.partner);
+        }
+        if let Some(asking) = book.requests.remove(&character_id) {
+            return Some(asking);
+        }
+        let asked = book
+            .requests
+            .iter()
+            .find_map(|(asked, asking)| (*asking == character_id).then_some(*asked))?;
+        book.requests.remove(&asked);
+        Some(asked)
+    }
+
+    fn change(
+        &self,
+        character_id: u64,
+        edit: impl FnOnce(&mut TradeOffer) -> Result<(), TradeFailure>,
+    ) -> Result<(), TradeFailure> {
+        let mut book = self.book();
+        let side = book
+            .open
+            .get_mut(&character_id)
+            .ok_or(TradeFailure::NoTrade)?;
+        if side.offer.locked {
+            return Err(TradeFailure::Locked);
+        }
+        edit(&mut side.offer)?;
+        let partner = side.partner;
+        book.unlock(character_id);
+        book.unlock(partner);
+        Ok(())
+    }
+
+    fn book(&self) -> MutexGuard<'_, TradeBook> {
+        match self.book.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::ItemOptions;
+
+    fn item(serial: u64) -> ItemInstance {
+        ItemInstance {
+            serial,
+            group: 14,
+            index: 13,
+            level: 0,
+            quantity: 1,
+            options: ItemOptions::default(),
+            expires_at_ms: None,
+        }
+    }
+
+    fn opened() -> Trades {
+        let trades = Trades::new();
+        trades.request(1, 2).unwrap();
+        assert_eq!(trades.request(3, 2), Err(TradeFailure::Busy));
+        assert_eq!(trades.respond(2, true), Ok(1));
+        trades
+    }
+
+    #[test]
+    fn both_sides_confirm_locked_offers() {
+        let trades = opened();
+        trades.add_item(1, 0, item(10)).unwrap();
+        assert_eq!(trades.add_item(1, 0, item(11)), Err(TradeFailure::Occupied));
+        trades.set_zen(2, 500).unwrap();
+
+        trades.lock(1, true).unwrap();
+        assert_eq!(trades.confirm(1), Err(TradeFailure::NotLocked));
+        trades.lock(2, true).unwrap();
+        assert_eq!(trades.add_item(1, 1, item(11)), Err(TradeFailure::Locked));
+        assert_eq!(trades.confirm(1), Ok(None));
+
+        let confirmed = trades.confirm(2).unwrap().expect("swap");
+        assert_eq!((confirmed.first, confirmed.second), (2, 1));
+        assert_eq!(confirmed.first_offer.zen, 500);
+        assert_eq!(confirmed.second_offer.items[0].item.serial, 10);
+        assert_eq!(trades.offers(1), None);
+        assert_eq!(trades.request(2, 1), Ok(()));
+    }
+
+    #[test]
+    fn changing_an_offer_clears_both_locks() {
+        let trades = opened();
+        trades.lock(1, true).unwrap();
+        trades.set_zen(2, 100).unwrap();
+
+        let (partner, own, other) = trades.offers(1).expect("window");
+        assert_eq!(partner, 2);
+        assert!(!own.locked);
+        assert_eq!(other.zen, 100);
+
+        assert_eq!(trades.cancel(2), Some(1));
+        assert_eq!(trades.lock(1, true), Err(TradeFailure::NoTrade));
+    }
+}
@@ -0,0 +1,436 @@
+//! Daily caps on the zen and items an account moves to other characters.
+//!
+//! Every warehouse, trade or mail move that leaves an account is counted in a
+//! rolling 24-hour window per account; [`TransferLimits::record`] refuses the
+//! move that would take the window past a cap. A stolen account emptied in
+//! one evening or a gold seller's mule feeding buyers hits the cap, and
+//! [`TransferLimits::near_limits`] lists both before they do.
+//!
+//! Moves are loaded from MongoDB at boot, so a restart does not reset the
+//! window, and written back by [`TransferLimits::persist`].
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::config::TransferLimitsConfig;
+use crate::db::{models::AccountTransferRecord, repository::AccountTransferRepository};
+
+/// Length of the rolling window the caps apply to.
+pub const TRANSFER_WINDOW_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Way zen or items left the account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferChannel {
+    /// Through the account warehouse to another character of the account.
+    Warehouse,
+    Trade,
+    /// Attached to a mail sent to another character.
+    Mail,
+}
+
+/// What one account moved in the current window, against its caps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub struct TransferUsage {
+    pub account_id: u64,
+    pub zen: u64,
+    pub items: u32,
+    /// `None` when zen is not capped.
+    pub zen_limit: Option<u64>,
+    pub item_limit: Option<u32>,
+}
+
+impl TransferUsage {
+    /// Largest share of a cap used, in percent.
+    pub fn percent_used(&self) -> u64 {
+        let zen = self.zen_limit.map(|limit| percent(self.zen, limit));
+        let items = self
+            .item_limit
+            .map(|limit| percent(u64::from(self.items), u64::from(limit)));
+        zen.max(items).unwrap_or(0)
+    }
+}
+
+/// Accounts at or above the report threshold of a cap, most used first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct TransferLimitReport {
+    pub window_ms: u64,
+    pub threshold_percent: u8,
+    pub accounts: Vec<TransferUsage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TransferLimitError {
+    #[error("daily zen transfer limit of {limit} reached ({moved} moved)")]
+    Zen { moved: u64, limit: u64 },
+
+    #[error("daily item transfer limit of {limit} reached ({moved} moved)")]
+    Items { moved: u32, limit: u32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TransferEntry {
+    channel: TransferChannel,
+    zen: u64,
+    items: u32,
+    at_ms: u64,
+}
+
+/// Rolling transfer windows per account.
+#[derive(Clone)]
+pub struct TransferLimits {
+    config: TransferLimitsConfig,
+    // key: account_id; moves inside the window, oldest first
+    windows: Arc<DashMap<u64, VecDeque<TransferEntry>>>,
+    // key: (account_id, at_ms); moves not written yet
+    unsaved: Arc<DashMap<(u64, u64), Vec<TransferEntry>>>,
+}
+
+impl TransferLimits {
+    pub fn new(config: &TransferLimitsConfig) -> Self {
+        Self {
+            config: config.clone(),
+            windows: Arc::new(DashMap::new()),
+            unsaved: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Restores the moves of the current window; older records are skipped.
+    pub fn load(&self, records: impl IntoIterator<Item = AccountTransferRecord>, now_ms: u64) {
+        for record in records {
+            if record.at_ms + TRANSFER_WINDOW_MS <= now_ms {
+                continue;
+            }
+            let mut window = self.windows.entry(record.account_id).or_default();
+            let entry = TransferEntry {
+                channel: record.channel,
+                zen: record.zen,
+                items: record.items,
+                at_ms: record.at_ms,
+            };
+            let at = window.partition_point(|other| other.at_ms <= entry.at_ms);
+            window.insert(at, entry);
+        }
+    }
+
+    /// Counts a move of `zen` and `items` out of the account, or refuses it
+    /// when it would pass a cap; a refused move is not counted.
+    pub fn record(
+        &self,
+        account_id: u64,
+        channel: TransferChannel,
+        zen: u64,
+        items: u32,
+        now_ms: u64,
+    ) -> Result<TransferUsage, TransferLimitError> {
+        let mut window = self.windows.entry(account_id).or_default();
+        prune(&mut window, now_ms);
+        let (moved_zen, moved_items) = totals(&window);
+        self.check_totals(moved_zen, moved_items, zen, items)?;
+
+        let entry = TransferEntry {
+            channel,
+            zen,
+            items,
+            at_ms: now_ms,
+        };
+        window.push_back(entry);
+        self.unsaved
+            .entry((account_id, now_ms))
+            .or_default()
+            .push(entry);
+        Ok(self.usage_of(
+            account_id,
+            moved_zen.saturating_add(zen),
+            moved_items.saturating_add(items),
+        ))
+    }
+
+    /// Refuses a move [`TransferLimits::record`] would refuse, without
+    /// counting it; for moves that can still fail for other reasons.
+    pub fn check(
+        &self,
+        account_id: u64,
+        zen: u64,
+        items: u32,
+        now_ms: u64,
+    ) -> Result<(), TransferLimitError> {
+        let (moved_zen, moved_items) = self
+            .windows
+            .get(&account_id)
+            .map(|window| totals_since(&window, now_ms))
+            .unwrap_or_default();
+        self.check_totals(moved_zen, moved_items, zen, items)
+    }
+
+    /// What the account moved in the window ending at `now_ms`.
+    pub fn usage(&self, account_id: u64, now_ms: u64) -> TransferUsage {
+        let (zen, items) = self
+            .windows
+            .get(&account_id)
+            .map(|window| totals_since(&window, now_ms))
+            .unwrap_or_default();
+        self.usage_of(account_id, zen, items)
+    }
+
+    /// Accounts that used at least the configured share of a cap.
+    pub fn near_limits(&self, now_ms: u64) -> TransferLimitReport {
+        let mut accounts: Vec<TransferUsage> = self
+            .windows
+            .iter()
+            .map(|window| {
+                let (zen, items) = totals_since(window.value(), now_ms);
+                self.usage_of(*window.key(), zen, items)
+            })
+            .filter(|usage| usage.percent_used() >= u64::from(self.config.report_threshold_percent))
+            .collect();
+        accounts.sort_by_key(|usage| (std::cmp::Reverse(usage.percent_used()), usage.account_id));
+        TransferLimitReport {
+            window_ms: TRANSFER_WINDOW_MS,
+            threshold_percent: self.config.report_threshold_percent,
+            accounts,
+        }
+    }
+
+    /// Drops accounts whose window emptied. Returns how many were dropped.
+    pub fn prune_idle(&self, now_ms: u64) -> usize {
+        let before = self.windows.len();
+        self.windows.retain(|_, window| {
+            prune(window, now_ms);
+            !window.is_empty()
+        });
+        before - self.windows.len()
+    }
+
+    /// Writes new moves to MongoDB and deletes the records that left the
+    /// window. Failed writes stay pending for the next call. Returns how many
+    /// moves were written.
+    pub async fn persist(&self, repository: &AccountTransferRepository, now_ms: u64) -> usize {
+        let keys: Vec<(u64, u64)> = self.unsaved.iter().map(|entry| *entry.key()).collect();
+        let mut written = 0;
+        for key in keys {
+            let Some((_, entries)) = self.unsaved.remove(&key) else {
+                continue;
+            };
+            let records: Vec<AccountTransferRecord> = entries
+                .iter()
+                .map(|entry| AccountTransferRecord {
+                    id: None,
+                    account_id: key.0,
+                    channel: entry.channel,
+                    zen: entry.zen,
+                    items: entry.items,
+                    at_ms: entry.at_ms,
+                })
+                .collect();
+            match repository.insert_many(&records).await {
+                Ok(()) => written += records.len(),
+                Err(err) => {
+                    log::error!(
+                        "Failed to save {} transfers of account {}: {}",
+                        records.len(),
+                        key.0,
+                        err
+                    );
+                    self.unsaved.entry(key).or_default().extend(entries);
+                }
+            }
+        }
+
+        if let Err(err) = repository
+            .delete_before(now_ms.saturating_sub(TRANSFER_WINDOW_MS))
+            .await
+        {
+            log::warn!("Failed to delete expired account transfers: {}", err);
+        }
+        written
+    }
+
+    fn check_totals(
+        &self,
+        moved_zen: u64,
+        moved_items: u32,
+        zen: u64,
+        items: u32,
+    ) -> Result<(), TransferLimitError> {
+        if let Some(limit) = self.config.zen_per_day {
+            if zen > 0 && moved_zen.saturating_add(zen) > limit {
+                return Err(TransferLimitError::Zen {
+                    moved: moved_zen,
+                    limit,
+                });
+            }
+        }
+        if let Some(limit) = self.config.items_per_day {
+            if items > 0 && moved_items.saturating_add(items) > limit {
+                return Err(TransferLimitError::Items {
+                    moved: moved_items,
+                    limit,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn usage_of(&self, account_id: u64, zen: u64, items: u32) -> TransferUsage {
+        TransferUsage {
+            account_id,
+            zen,
+            items,
+            zen_limit: self.config.zen_per_day,
+            item_limit: self.config.items_per_day,
+        }
+    }
+}
+
+fn prune(window: &mut VecDeque<TransferEntry>, now_ms: u64) {
+    while window
+        .front()
+        .is_some_and(|entry| entry.at_ms + TRANSFER_WINDOW_MS <= now_ms)
+    {
+        window.pop_front();
+    }
+}
+
+fn totals(window: &VecDeque<TransferEntry>) -> (u64, u32) {
+    window.iter().fold((0, 0), |(zen, items), entry| {
+        (
+            zen.saturating_add(entry.zen),
+            items.saturating_add(entry.items),
+        )
+    })
+}
+
+/// Totals of the moves still inside the window at `now_ms`, without
+/// touching the window itself.
+fn totals_since(window: &VecDeque<TransferEntry>, now_ms: u64) -> (u64, u32) {
+    window
+        .iter()
+        .filter(|entry| entry.at_ms + TRANSFER_WINDOW_MS > now_ms)
+        .fold((0, 0), |(zen, items), entry| {
+            (
+                zen.saturating_add(entry.zen),
+                items.saturating_add(entry.items),
+            )
+        })
+}
+
+fn percent(used: u64, limit: u64) -> u64 {
+    if limit == 0 {
+        return 100;
+    }
+    (u128::from(used) * 100 / u128::from(limit)).min(u128::from(u64::MAX)) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR_MS: u64 = 60 * 60 * 1000;
+
+    fn limits() -> TransferLimits {
+        TransferLimits::new(&TransferLimitsConfig {
+            zen_per_day: Some(1_000_000),
+            items_per_day: Some(10),
+            report_threshold_percent: 80,
+        })
+    }
+
+    #[test]
+    fn caps_apply_over_a_rolling_day() {
+        let limits = limits();
+        limits
+            .record(1, TransferChannel::Trade, 600_000, 2, HOUR_MS)
+            .unwrap();
+        limits
+            .record(1, TransferChannel::Mail, 300_000, 0, 2 * HOUR_MS)
+            .unwrap();
+        assert_eq!(
+            limits.record(1, TransferChannel::Warehouse, 200_000, 0, 3 * HOUR_MS),
+            Err(TransferLimitError::Zen {
+                moved: 900_000,
+                limit: 1_000_000
+            })
+        );
+        assert!(limits.check(1, 200_000, 0, 3 * HOUR_MS).is_err());
+        assert_eq!(limits.check(1, 100_000, 0, 3 * HOUR_MS), Ok(()));
+        // Items still fit; the refused zen was not counted.
+        let usage = limits
+            .record(1, TransferChannel::Trade, 0, 8, 3 * HOUR_MS)
+            .unwrap();
+        assert_eq!((usage.zen, usage.items), (900_000, 10));
+        assert!(matches!(
+            limits.record(1, TransferChannel::Trade, 0, 1, 3 * HOUR_MS),
+            Err(TransferLimitError::Items { moved: 10, .. })
+        ));
+        // Another account has its own window.
+        limits
+            .record(2, TransferChannel::Trade, 1_000_000, 10, 3 * HOUR_MS)
+            .unwrap();
+
+        // The first move leaves the window a day after it was made.
+        let usage = limits
+            .record(1, TransferChannel::Warehouse, 200_000, 0, 25 * HOUR_MS)
+            .unwrap();
+        assert_eq!((usage.zen, usage.items), (500_000, 8));
+    }
+
+    #[test]
+    fn accounts_near_a_cap_are_reported() {
+        let limits = limits();
+        limits
+            .record(1, TransferChannel::Trade, 100_000, 9, HOUR_MS)
+            .unwrap();
+        limits
+            .record(2, TransferChannel::Trade, 850_000, 0, HOUR_MS)
+            .unwrap();
+        limits
+            .record(3, TransferChannel::Mail, 500_000, 1, HOUR_MS)
+            .unwrap();
+
+        let report = limits.near_limits(2 * HOUR_MS);
+        let accounts: Vec<(u64, u64)> = report
+            .accounts
+            .iter()
+            .map(|usage| (usage.account_id, usage.percent_used()))
+            .collect();
+        assert_eq!(accounts, vec![(1, 90), (2, 85)]);
+        assert!(limits.near_limits(26 * HOUR_MS).accounts.is_empty());
+        assert_eq!(limits.prune_idle(26 * HOUR_MS), 3);
+    }
+
+    #[test]
+    fn loaded_moves_count_until_they_leave_the_window() {
+        let limits = limits();
+        let record = |at_ms, zen| AccountTransferRecord {
+            id: None,
+            account_id: 1,
+            channel: TransferChannel::Trade,
+            zen,
+            items: 0,
+            at_ms,
+        };
+        limits.load(
+            [record(10 * HOUR_MS, 400_000), record(HOUR_MS, 900_000)],
+            20 * HOUR_MS,
+        );
+        assert_eq!(limits.usage(1, 20 * HOUR_MS).zen, 1_300_000);
+        assert_eq!(limits.usage(1, 26 * HOUR_MS).zen, 400_000);
+        limits.load([record(0, 900_000)], 30 * HOUR_MS);
+        assert_eq!(limits.usage(1, 30 * HOUR_MS).zen, 400_000);
+    }
+
+    #[test]
+    fn uncapped_config_only_counts() {
+        let limits = TransferLimits::new(&TransferLimitsConfig::default());
+        let usage = limits
+            .record(1, TransferChannel::Trade, u64::MAX, u32::MAX, HOUR_MS)
+            .unwrap();
+        assert_eq!(usage.percent_used(), 0);
+        assert!(limits.near_limits(HOUR_MS).accounts.is_empty());
+    }
+}
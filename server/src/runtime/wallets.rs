@@ -0,0 +1,200 @@
+//! Zen each character holds.
+//!
+//! Rewards credit the wallet when they reach the character, straight away or
+//! when a mail is claimed, and trades swap zen between two wallets. A wallet
+//! never goes past [`MAX_ZEN`] or below zero; a move that would is refused
+//! whole, so the zen stays where it was.
+
+use std::sync::{Arc, Mutex as StdMutex};
+
+use dashmap::{DashMap, DashSet};
+use protocol::MAX_ZEN;
+
+use crate::db::{models::ZenBalanceRecord, repository::ZenBalanceRepository};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum WalletError {
+    #[error("character {character_id} cannot hold {zen} more zen")]
+    Full { character_id: u64, zen: u64 },
+
+    #[error("character {character_id} holds less than {zen} zen")]
+    Short { character_id: u64, zen: u64 },
+}
+
+/// Zen of every character, shared by all sessions.
+///
+/// Loaded from MongoDB at boot; changes are written back in batches by
+/// [`ZenWallets::persist`].
+#[derive(Clone, Default)]
+pub struct ZenWallets {
+    // key: character_id
+    balances: Arc<DashMap<u64, u64>>,
+    dirty: Arc<DashSet<u64>>,
+    // Held while balances change, so a swap between two wallets is not
+    // interleaved with another move.
+    moving: Arc<StdMutex<()>>,
+}
+
+impl ZenWallets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(&self, records: impl IntoIterator<Item = ZenBalanceRecord>) {
+        for record in records {
+            self.balances.insert(record.character_id, record.zen);
+        }
+    }
+
+    pub fn balance(&self, character_id: u64) -> u64 {
+        self.balances
+            .get(&character_id)
+            .map_or(0, |entry| *entry.value())
+    }
+
+    /// Adds `zen` to the wallet and returns the new balance.
+    pub fn credit(&self, character_id: u64, zen: u64) -> Result<u64, WalletError> {
+        if zen == 0 {
+            return Ok(self.balance(character_id));
+        }
+        let _moving = self.lock_moves();
+        let mut balance = self.balances.entry(character_id).or_insert(0);
+        let total = balance
+            .checked_add(zen)
+            .filter(|total| *total <= MAX_ZEN)
+            .ok_or(WalletError::Full { character_id, zen })?;
+        *balance = total;
+        drop(balance);
+        self.dirty.insert(character_id);
+        Ok(total)
+    }
+
+    /// Takes back zen credited by an operation that failed afterwards.
+    pub fn debit(&self, character_id: u64, zen: u64) {
+        if zen == 0 {
+            return;
+        }
+        let _moving = self.lock_moves();
+        if let Some(mut balance) = self.balances.get_mut(&character_id) {
+            *balance = balance.saturating_sub(zen);
+            self.dirty.insert(character_id);
+        }
+    }
+
+    /// Moves `first_zen` from `first` to `second` and `second_zen` the other
+    /// way, or nothing when either wallet is short or would pass the cap.
+    pub fn exchange(
+        &self,
+        first: u64,
+        first_zen: u64,
+        second: u64,
+        second_zen: u64,
+    ) -> Result<(), WalletError> {
+        let _moving = self.lock_moves();
+        let first_after = settle(first, self.balance(first), first_zen, second_zen)?;
+        let second_after = settle(second, self.balance(second), second_zen, first_zen)?;
+        for (character_id, balance) in [(first, first_after), (second, second_after)] {
+            self.balances.insert(character_id, balance);
+            self.dirty.insert(character_id);
+        }
+        Ok(())
+    }
+
+    /// Writes every changed wallet to MongoDB. Failed writes stay pending
+    /// for the next call. Returns how many wallets were written.
+    pub async fn persist(&self, repository: &ZenBalanceRepository) -> usize {
+        let pending: Vec<u64> = self.dirty.iter().map(|entry| *entry).collect();
+        let mut written = 0;
+        for character_id in pending {
+            self.dirty.remove(&character_id);
+            let record = ZenBalanceRecord {
+                id: None,
+                character_id,
+                zen: self.balance(character_id),
+            };
+            match repository.save(&record).await {
+                Ok(()) => written += 1,
+                Err(err) => {
+                    log::error!(
+                        "Failed to save the zen of character {}: {}",
+                        character_id,
+                        err
+                    );
+                    self.dirty.insert(character_id);
+                }
+            }
+        }
+        written
+    }
+
+    fn lock_moves(&self) -> std::sync::MutexGuard<'_, ()> {
+        match self.moving.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// Balance left after paying `paid` out of `balance` and receiving `received`.
+fn settle(character_id: u64, balance: u64, paid: u64, received: u64) -> Result<u64, WalletError> {
+    let left = balance.checked_sub(paid).ok_or(WalletError::Short {
+        character_id,
+        zen: paid,
+    })?;
+    left.checked_add(received)
+        .filter(|total| *total <= MAX_ZEN)
+        .ok_or(WalletError::Full {
+            character_id,
+            zen: received,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credits_stop_at_the_zen_cap() {
+        let wallets = ZenWallets::new();
+        assert_eq!(wallets.credit(7, 1_000), Ok(1_000));
+        assert_eq!(
+            wallets.credit(7, MAX_ZEN),
+            Err(WalletError::Full {
+                character_id: 7,
+                zen: MAX_ZEN
+            })
+        );
+        assert_eq!(wallets.balance(7), 1_000);
+
+        assert_eq!(wallets.credit(7, MAX_ZEN - 1_000), Ok(MAX_ZEN));
+        assert_eq!(wallets.balance(8), 0);
+    }
+
+    #[test]
+    fn exchanges_move_both_ways_or_not_at_all() {
+        let wallets = ZenWallets::new();
+        wallets.credit(1, 500).unwrap();
+        wallets.credit(2, 300).unwrap();
+
+        assert_eq!(
+            wallets.exchange(1, 600, 2, 0),
+            Err(WalletError::Short {
+                character_id: 1,
+                zen: 600
+            })
+        );
+        wallets.exchange(1, 200, 2, 100).unwrap();
+        assert_eq!((wallets.balance(1), wallets.balance(2)), (400, 400));
+
+        wallets.credit(2, MAX_ZEN - 400).unwrap();
+        assert!(wallets.exchange(2, 0, 1, 500).is_err());
+        assert_eq!(
+            wallets.exchange(1, 1, 2, 0),
+            Err(WalletError::Full {
+                character_id: 2,
+                zen: 1
+            })
+        );
+        assert_eq!((wallets.balance(1), wallets.balance(2)), (400, MAX_ZEN));
+    }
+}
@@ -0,0 +1,331 @@
+//! Server events posted to Discord or Slack channels.
+//!
+//! Every `[[webhooks]]` entry of the runtime config gets its own queue and
+//! worker, so a slow or failing channel never holds the others back. Posts to
+//! one URL are spaced to stay under `max_per_minute`; failed posts are retried
+//! with a doubling delay, and a 429 waits for the `Retry-After` it came with.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use protocol::GensFaction;
+use reqwest::{StatusCode, Url};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+
+use super::config::WebhookConfig;
+
+/// Posts waiting per webhook; newer events are dropped past it.
+pub const QUEUE_CAPACITY: usize = 256;
+
+const BASE_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Event a webhook can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventKind {
+    ServerStarted,
+    ServerStopping,
+    BossKilled,
+    /// Accepted in `events` already; posted once Castle Siege exists.
+    CastleSiegeEnded,
+    TopRanking,
+}
+
+/// Body shape the webhook URL expects.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    #[default]
+    Discord,
+    Slack,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebhookEvent {
+    ServerStarted {
+        worlds: usize,
+    },
+    ServerStopping,
+    BossKilled {
+        world_id: u16,
+        map_id: u16,
+        monster: String,
+        character_id: u64,
+    },
+    /// A new character leads a Gens faction's contribution ranking.
+    TopRanking {
+        faction: GensFaction,
+        character_id: u64,
+        contribution: u32,
+    },
+}
+
+impl WebhookEvent {
+    pub fn kind(&self) -> WebhookEventKind {
+        match self {
+            Self::ServerStarted { .. } => WebhookEventKind::ServerStarted,
+            Self::ServerStopping => WebhookEventKind::ServerStopping,
+            Self::BossKilled { .. } => WebhookEventKind::BossKilled,
+            Self::TopRanking { .. } => WebhookEventKind::TopRanking,
+        }
+    }
+
+    /// Line posted to the channel.
+    pub fn message(&self) -> String {
+        match self {
+            Self::ServerStarted { worlds } => format!("Server is online with {worlds} worlds"),
+            Self::ServerStopping => "Server is shutting down".to_string(),
+            Self::BossKilled {
+                world_id,
+                map_id,
+                monster,
+                character_id,
+            } => format!(
+                "Boss {monster} was killed by character {character_id} on world {world_id}, map {map_id}"
+            ),
+            Self::TopRanking {
+                faction,
+                character_id,
+                contribution,
+            } => format!(
+                "Character {character_id} now leads the {faction:?} Gens with {contribution} contribution"
+            ),
+        }
+    }
+}
+
+impl WebhookFormat {
+    pub fn payload(self, message: &str) -> Value {
+        match self {
+            Self::Discord => json!({ "content": message }),
+            Self::Slack => json!({ "text": message }),
+        }
+    }
+}
+
+enum WebhookCommand {
+    Post(Value),
+    Shutdown(oneshot::Sender<()>),
+}
+
+struct WebhookQueue {
+    events: Vec<WebhookEventKind>,
+    format: WebhookFormat,
+    tx: mpsc::Sender<WebhookCommand>,
+}
+
+impl WebhookQueue {
+    fn wants(&self, kind: WebhookEventKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+}
+
+/// Queues of the configured webhooks; empty when none is set.
+#[derive(Clone, Default)]
+pub struct WebhookDispatcher {
+    queues: Arc<Vec<WebhookQueue>>,
+}
+
+impl WebhookDispatcher {
+    /// Starts one worker per webhook whose URL parses; the others are logged
+    /// and skipped.
+    pub fn start(configs: &[WebhookConfig]) -> Self {
+        if configs.is_empty() {
+            return Self::default();
+        }
+        let client = match reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build() {
+            Ok(client) => client,
+            Err(err) => {
+                log::error!("Webhooks disabled, HTTP client failed to start: {}", err);
+                return Self::default();
+            }
+        };
+
+        let mut queues = Vec::new();
+        for config in configs {
+            let url = match Url::parse(&config.url) {
+                Ok(url) => url,
+                Err(err) => {
+                    log::error!("Skipping webhook with an invalid url: {}", err);
+                    continue;
+                }
+            };
+            let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+            let worker = WebhookWorker {
+                client: client.clone(),
+                url,
+                spacing: post_spacing(config.max_per_minute),
+                max_retries: config.max_retries,
+            };
+            tokio::spawn(worker.run(rx));
+            queues.push(WebhookQueue {
+                events: config.events.clone(),
+                format: config.format,
+                tx,
+            });
+        }
+        Self {
+            queues: Arc::new(queues),
+        }
+    }
+
+    /// Queues `event` on every webhook subscribed to it. Never waits; a full
+    /// queue drops the event.
+    pub fn notify(&self, event: &WebhookEvent) {
+        let kind = event.kind();
+        let message = event.message();
+        for queue in self.queues.iter().filter(|queue| queue.wants(kind)) {
+            let post = WebhookCommand::Post(queue.format.payload(&message));
+            if queue.tx.try_send(post).is_err() {
+                log::warn!("Webhook queue full, dropped {:?} event", kind);
+            }
+        }
+    }
+
+    /// Lets every worker post what it has queued, waiting up to `timeout`.
+    pub async fn shutdown(&self, timeout: Duration) {
+        let drained = async {
+            let mut acks = Vec::new();
+            for queue in self.queues.iter() {
+                let (ack_tx, ack_rx) = oneshot::channel();
+                if queue
+                    .tx
+                    .send(WebhookCommand::Shutdown(ack_tx))
+                    .await
+                    .is_ok()
+                {
+                    acks.push(ack_rx);
+                }
+            }
+            for ack in acks {
+                let _ = ack.await;
+            }
+        };
+        if tokio::time::timeout(timeout, drained).await.is_err() {
+            log::warn!("Webhooks still had posts queued at shutdown");
+        }
+    }
+}
+
+struct WebhookWorker {
+    client: reqwest::Client,
+    url: Url,
+    spacing: Duration,
+    max_retries: u32,
+}
+
+impl WebhookWorker {
+    async fn run(self, mut rx: mpsc::Receiver<WebhookCommand>) {
+        let mut next_post = Instant::now();
+        while let Some(command) = rx.recv().await {
+            match command {
+                WebhookCommand::Post(body) => {
+                    tokio::time::sleep_until(next_post).await;
+                    self.deliver(&body).await;
+                    next_post = Instant::now() + self.spacing;
+                }
+                WebhookCommand::Shutdown(ack) => {
+                    let _ = ack.send(());
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn deliver(&self, body: &Value) {
+        // The path carries the webhook's secret; only the host is logged.
+        let host = self.url.host_str().unwrap_or("webhook");
+        for attempt in 0..=self.max_retries {
+            let retry_after = match self.client.post(self.url.clone()).json(body).send().await {
+                Ok(response) if response.status().is_success() => return,
+                Ok(response) if !is_retryable(response.status()) => {
+                    log::warn!("Webhook post to {} refused: {}", host, response.status());
+                    return;
+                }
+                Ok(response) => {
+                    log::warn!("Webhook post to {} failed: {}", host, response.status());
+                    response
+                        .headers()
+                        .get(reqwest::header::RETRY_AFTER)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(|value| value.parse::<u64>().ok())
+                        .map(Duration::from_secs)
+                }
+                Err(err) => {
+                    log::warn!("Webhook post to {} failed: {}", host, err);
+                    None
+                }
+            };
+            if attempt < self.max_retries {
+                tokio::time::sleep(retry_delay(attempt, retry_after)).await;
+            }
+        }
+        log::error!(
+            "Gave up on a webhook post to {} after {} retries",
+            host,
+            self.max_retries
+        );
+    }
+}
+
+/// Rate limits and server errors may pass; other refusals never will.
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Wait before retry `attempt` (0-based): what the service asked for, or a
+/// doubling delay, capped either way.
+fn retry_delay(attempt: u32, retry_after: Option<Duration>) -> Duration {
+    retry_after
+        .unwrap_or_else(|| BASE_RETRY_DELAY.saturating_mul(1 << attempt.min(16)))
+        .min(MAX_RETRY_DELAY)
+}
+
+fn post_spacing(max_per_minute: u32) -> Duration {
+    Duration::from_secs(60) / max_per_minute.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_back_off_and_honor_retry_after() {
+        assert_eq!(retry_delay(0, None), Duration::from_secs(1));
+        assert_eq!(retry_delay(3, None), Duration::from_secs(8));
+        assert_eq!(retry_delay(30, None), MAX_RETRY_DELAY);
+        assert_eq!(
+            retry_delay(0, Some(Duration::from_secs(5))),
+            Duration::from_secs(5)
+        );
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable(StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable(StatusCode::NOT_FOUND));
+        assert_eq!(post_spacing(30), Duration::from_secs(2));
+        assert_eq!(post_spacing(0), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn payloads_follow_the_channel_format() {
+        let event = WebhookEvent::TopRanking {
+            faction: GensFaction::Vanert,
+            character_id: 7,
+            contribution: 120,
+        };
+        assert_eq!(event.kind(), WebhookEventKind::TopRanking);
+        let message = event.message();
+        assert_eq!(
+            WebhookFormat::Discord.payload(&message),
+            json!({ "content": "Character 7 now leads the Vanert Gens with 120 contribution" })
+        );
+        assert_eq!(
+            WebhookFormat::Slack.payload(&message),
+            json!({ "text": message })
+        );
+    }
+}
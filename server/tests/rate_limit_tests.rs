@@ -42,6 +42,21 @@ fn test_rate_limit_blocks_over_limit() {
     );
 }
 
+#[test]
+fn test_rate_limit_reports_when_to_retry() {
+    let limiter = RateLimiter::new();
+    let ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+
+    assert_eq!(limiter.retry_after(ip), Duration::ZERO);
+    for _ in 0..10 {
+        limiter.check_rate_limit(ip);
+    }
+
+    let retry_after = limiter.retry_after(ip);
+    assert!(retry_after > Duration::from_secs(59));
+    assert!(retry_after <= Duration::from_secs(60));
+}
+
 #[test]
 fn test_rate_limit_per_ip() {
     let limiter = RateLimiter::new();
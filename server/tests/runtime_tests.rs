@@ -88,3 +88,183 @@ async fn runtime_endpoints_fail_when_disabled() {
         actix_web::http::StatusCode::INTERNAL_SERVER_ERROR
     );
 }
+
+#[actix_web::test]
+async fn admin_maintenance_closes_map_and_marks_world_listing() {
+    use actix_web::middleware::from_fn;
+    use server::config::ServerConfig;
+    use server::middleware::{admin_middleware, AdminToken};
+    use server::monitor::HealthMonitor;
+
+    let auth_tokens = AuthTokenService::new(
+        b"01234567890123456789012345678901".to_vec(),
+        Duration::from_secs(3600),
+    )
+    .expect("auth tokens");
+    let runtime = Arc::new(
+        MuCoreRuntime::bootstrap(RuntimeConfig::default(), auth_tokens, None).expect("runtime"),
+    );
+    let config = ServerConfig::load_from_file(format!(
+        "{}/config/servers.toml",
+        env!("CARGO_MANIFEST_DIR")
+    ))
+    .expect("servers config");
+    let health_monitor = HealthMonitor::new();
+    health_monitor.record_heartbeat("world-1-lorencia".to_string(), 10);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(Some(runtime.clone())))
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(health_monitor))
+            .app_data(web::Data::new(AdminToken::new(Some("secret".to_string()))))
+            .service(handlers::list_worlds)
+            .service(
+                web::scope("/admin")
+                    .wrap(from_fn(admin_middleware))
+                    .service(handlers::begin_maintenance)
+                    .service(handlers::end_maintenance),
+            ),
+    )
+    .await;
+
+    let payload = serde_json::json!({
+        "world_id": 1,
+        "map_id": 0,
+        "countdown_secs": 60,
+        "fallback_map_id": 1,
+        "reason": "patch"
+    });
+
+    let rejected = test::try_call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/admin/maintenance")
+            .insert_header(("X-Admin-Token", "wrong"))
+            .set_json(&payload)
+            .to_request(),
+    )
+    .await
+    .expect_err("wrong admin token must be rejected");
+    assert_eq!(
+        rejected.as_response_error().status_code(),
+        actix_web::http::StatusCode::FORBIDDEN
+    );
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/admin/maintenance")
+            .insert_header(("X-Admin-Token", "secret"))
+            .set_json(&payload)
+            .to_request(),
+    )
+    .await;
+    assert!(resp.status().is_success());
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["window"]["fallback_map_id"], 1);
+
+    let resp = test::call_service(&app, test::TestRequest::get().uri("/worlds").to_request()).await;
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["worlds"][0]["id"], "world-1-lorencia");
+    assert_eq!(body["worlds"][0]["status"], "maintenance");
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::delete()
+            .uri("/admin/maintenance")
+            .insert_header(("X-Admin-Token", "secret"))
+            .set_json(serde_json::json!({ "world_id": 1, "map_id": 0 }))
+            .to_request(),
+    )
+    .await;
+    assert!(resp.status().is_success());
+
+    let resp = test::call_service(&app, test::TestRequest::get().uri("/nope").to_request()).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NOT_FOUND);
+
+    runtime.shutdown().await.unwrap();
+}
+
+#[actix_web::test]
+async fn runtime_listings_page_filter_and_select_fields() {
+    let auth_tokens = AuthTokenService::new(
+        b"01234567890123456789012345678901".to_vec(),
+        Duration::from_secs(3600),
+    )
+    .expect("auth tokens");
+    let runtime = Arc::new(
+        MuCoreRuntime::bootstrap(RuntimeConfig::default(), auth_tokens, None).expect("runtime"),
+    );
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(Some(runtime.clone())))
+            .service(handlers::runtime_worlds)
+            .service(handlers::runtime_maps),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/runtime/maps?per_page=1&page=2")
+            .to_request(),
+    )
+    .await;
+    assert!(resp.status().is_success());
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["maps"].as_array().unwrap().len(), 1);
+    assert_eq!(body["pagination"]["page"], 2);
+    let total = body["pagination"]["total"].as_u64().unwrap();
+    assert!(total > 1);
+    assert_eq!(body["pagination"]["total_pages"], total);
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/runtime/maps?map=lorencia&fields=route,map_name")
+            .to_request(),
+    )
+    .await;
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let maps = body["maps"].as_array().unwrap();
+    assert!(!maps.is_empty());
+    for map in maps {
+        assert_eq!(map["map_name"], "Lorencia");
+        assert_eq!(map.as_object().unwrap().len(), 2);
+    }
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/runtime/worlds?world_id=1&map=Noria")
+            .to_request(),
+    )
+    .await;
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let worlds = body["worlds"]["worlds"].as_array().unwrap();
+    assert_eq!(worlds.len(), 1);
+    assert_eq!(worlds[0]["world_id"], 1);
+    for entry in worlds[0]["entries"].as_array().unwrap() {
+        for map in entry["maps"].as_array().unwrap() {
+            assert_eq!(map["map_name"], "Noria");
+        }
+    }
+
+    for uri in [
+        "/runtime/maps?map=Atlantis",
+        "/runtime/maps?fields=route,password",
+        "/runtime/worlds?page=0",
+        "/runtime/worlds?per_page=1000",
+    ] {
+        let resp = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        assert_eq!(
+            resp.status(),
+            actix_web::http::StatusCode::BAD_REQUEST,
+            "{uri}"
+        );
+    }
+
+    runtime.shutdown().await.unwrap();
+}